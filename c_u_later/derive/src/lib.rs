@@ -11,14 +11,24 @@ use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Type};
 ///
 /// - `#[program]`: includes this field's bytes in `program_mask()`.
 /// - `#[authority]`: includes this field's bytes in `authority_mask()`.
+/// - `#[readonly]`: documents that neither caller may write the field. It changes nothing
+///   in the output and is a compile error alongside `#[program]` or `#[authority]`.
 /// - `#[embed]` — for fields whose type does not implement `CuLaterMask`. Marks every byte
 ///   of the field writable without sub-field granularity. The field type must be
-///   `Pod + Zeroable`. If the type implements `CuLater`, calling `program_mask()` or
-///   `authority_mask()` panics; remove `#[embed]` and let the type's own mask compose
-///   recursively instead.
+///   `Pod + Zeroable`. If the type implements `CuLater` the derive fails to compile; remove
+///   `#[embed]` and let the type's own mask compose recursively instead.
+/// - `#[sequenced]`: marks an 8-byte field as a counter that no write may decrease. Lists the
+///   field's offset in `sequenced_offsets()`, from which `c_u_later::sequenced_constraints`
+///   builds the constraints the program enforces. It does not change either mask.
 ///
 /// Fields without any attribute are read-only from both callers' perspectives.
 ///
+/// # Padding
+///
+/// Fields whose names start with `_` are padding: blocked in both masks, with no wrapper
+/// accessor or delta setter. Marking one `#[program]`, `#[authority]`, or `#[sequenced]` is a
+/// compile error; rename the field if it holds data callers should write.
+///
 /// # Mask composition (without `#[embed]`)
 ///
 /// For `#[program]` / `#[authority]` fields whose type implements `CuLaterMask`, the
@@ -31,14 +41,27 @@ use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Type};
 ///
 /// - `impl CuLaterMask for MyStruct`: `program_mask()` and `authority_mask()` each return
 ///   `Vec<bool>` of length `size_of::<MyStruct>()` where `true` = writable, `false` = blocked.
+///   `sequenced_offsets()` returns the offsets of the `#[sequenced]` fields, in declaration
+///   order.
 /// - `MyStructProgram<'a>` and `MyStructAuthority<'a>` wrappers with mut accessors only for
 ///   fields marked `#[program]` / `#[authority]`.
-/// - A const assertion that `size_of::<MyStruct>() <= AUX_SIZE` (255 bytes).
+/// - `MyStructRef<'a, R>` and `MyStructMut<'a, R>` zero-copy views for role `R`
+///   (`c_u_later::ProgramRole` or `c_u_later::AuthorityRole`), built from an envelope with
+///   `AuxView::from_envelope` / `AuxViewMut::from_envelope_mut`. Unlike the wrappers they
+///   do not deref to the struct: `field()` and, on `Mut`, `field_mut()` exist only for the
+///   role's fields, so other fields cannot even be read through them.
+/// - A const assertion that `size_of::<MyStruct>() <= AUX_SIZE` (255 bytes), one per
+///   `#[sequenced]` field that the field is 8 bytes, and one per `#[embed]` field that its
+///   type does not implement `CuLater`.
+/// - For each `#[program]` / `#[authority]` field without `#[embed]` whose type implements
+///   `TypeHash`, const assertions that its size matches its `METADATA` size and its offset
+///   matches the packed declaration-order layout the parent's `TypeHash` hashes. Reordering
+///   or resizing fields so the two drift apart fails to compile.
 ///
 /// # Requirements
 ///
 /// - `#[repr(C)]` is required for deterministic field layout.
-/// - Only named-field structs are supported.
+/// - Only named-field structs without generic parameters are supported.
 /// - `#[program]` / `#[authority]` fields without `#[embed]` must implement `CuLaterMask`.
 /// - `#[embed]` field types must be `Pod + Zeroable`.
 ///
@@ -63,7 +86,7 @@ use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Type};
 /// // program_mask():   bytes 4-7 and 12-15 are writable
 /// // authority_mask(): bytes 8-11 and 12-15 are writable
 /// ```
-#[proc_macro_derive(CuLater, attributes(program, authority, embed))]
+#[proc_macro_derive(CuLater, attributes(program, authority, embed, readonly, sequenced))]
 pub fn derive_cu_later(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     match derive_cu_later_impl(input) {
@@ -83,6 +106,14 @@ fn derive_cu_later_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
         ));
     }
 
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "CuLater does not support generic structs; masks and wrappers are generated for \
+             one concrete layout",
+        ));
+    }
+
     let fields = match &input.data {
         Data::Struct(data) => match &data.fields {
             Fields::Named(fields) => &fields.named,
@@ -108,6 +139,29 @@ fn derive_cu_later_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
         let has_program = has_attr(&field.attrs, "program");
         let has_authority = has_attr(&field.attrs, "authority");
         let has_embed = has_attr(&field.attrs, "embed");
+        let has_sequenced = has_attr(&field.attrs, "sequenced");
+
+        if has_attr(&field.attrs, "readonly") && (has_program || has_authority) {
+            return Err(syn::Error::new_spanned(
+                field_name,
+                "CuLater: #[readonly] field cannot also be #[program] or #[authority]",
+            ));
+        }
+        if is_padding_field(field_name) && (has_program || has_authority) {
+            return Err(syn::Error::new_spanned(
+                field_name,
+                "CuLater: fields starting with `_` are padding and always blocked in both \
+                 masks, so they cannot be #[program] or #[authority]. Remove the attribute, \
+                 or rename the field if callers should write it.",
+            ));
+        }
+        if is_padding_field(field_name) && has_sequenced {
+            return Err(syn::Error::new_spanned(
+                field_name,
+                "CuLater: fields starting with `_` are padding and cannot be #[sequenced]. \
+                 Rename the field if it holds a counter.",
+            ));
+        }
 
         field_infos.push(FieldInfo {
             name: field_name.clone(),
@@ -115,6 +169,7 @@ fn derive_cu_later_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
             has_program,
             has_authority,
             has_embed,
+            has_sequenced,
         });
     }
 
@@ -128,14 +183,6 @@ fn derive_cu_later_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
                 if f.has_embed {
                     quote! {
                         {
-                            if ::c_u_later::IsCuLaterWrapper::<#field_ty>::is_cu_later() {
-                                panic!(
-                                    "Field '{}' has #[embed] but type {} implements CuLater. \
-                                     Remove #[embed] to preserve fine-grained bitmask control.",
-                                    stringify!(#field_name),
-                                    ::core::any::type_name::<#field_ty>()
-                                );
-                            }
                             let offset = ::core::mem::offset_of!(#name, #field_name);
                             let size = ::core::mem::size_of::<#field_ty>();
                             for i in 0..size {
@@ -168,14 +215,6 @@ fn derive_cu_later_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
                 if f.has_embed {
                     quote! {
                         {
-                            if ::c_u_later::IsCuLaterWrapper::<#field_ty>::is_cu_later() {
-                                panic!(
-                                    "Field '{}' has #[embed] but type {} implements CuLater. \
-                                     Remove #[embed] to preserve fine-grained bitmask control.",
-                                    stringify!(#field_name),
-                                    ::core::any::type_name::<#field_ty>()
-                                );
-                            }
                             let offset = ::core::mem::offset_of!(#name, #field_name);
                             let size = ::core::mem::size_of::<#field_ty>();
                             for i in 0..size {
@@ -198,8 +237,37 @@ fn derive_cu_later_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
         })
         .collect();
 
+    let layout_checks = generate_layout_checks(name, &field_infos);
+    let embed_checks = generate_embed_checks(&field_infos);
+
+    let sequenced: Vec<&FieldInfo> = field_infos.iter().filter(|f| f.has_sequenced).collect();
+    let sequenced_checks = sequenced.iter().map(|f| {
+        let field_ty = &f.ty;
+        let message = format!("CuLater: #[sequenced] field '{}' must be 8 bytes", f.name);
+        quote! {
+            const _: () = assert!(::core::mem::size_of::<#field_ty>() == 8, #message);
+        }
+    });
+    let sequenced_offsets = sequenced.iter().map(|f| {
+        let field_name = &f.name;
+        quote! { ::core::mem::offset_of!(#name, #field_name) }
+    });
+
+    let field_spans = field_infos.iter().map(|f| {
+        let field_name = &f.name;
+        let field_ty = &f.ty;
+        quote! {
+            ::c_u_later::FieldSpan {
+                name: stringify!(#field_name),
+                offset: ::core::mem::offset_of!(#name, #field_name),
+                size: ::core::mem::size_of::<#field_ty>(),
+            }
+        }
+    });
+
     let program_wrapper = generate_wrapper(name, vis, &field_infos, "Program", true)?;
     let authority_wrapper = generate_wrapper(name, vis, &field_infos, "Authority", false)?;
+    let views = generate_views(name, vis, &field_infos)?;
     let program_delta = generate_delta_builder(name, vis, &field_infos, "Program", true);
     let authority_delta = generate_delta_builder(name, vis, &field_infos, "Authority", false);
 
@@ -213,10 +281,12 @@ fn derive_cu_later_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
             }
         };
 
+        #layout_checks
+        #embed_checks
+        #(#sequenced_checks)*
+
         #[doc(hidden)]
         fn #program_mask_fn() -> ::c_u_later::__private::Vec<bool> {
-            #[allow(unused_imports)]
-            use ::c_u_later::IsNotCuLater as _;
             let mut mask = ::c_u_later::__private::vec![false; ::core::mem::size_of::<#name>()];
             #(#program_mask_parts)*
             mask
@@ -224,8 +294,6 @@ fn derive_cu_later_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
 
         #[doc(hidden)]
         fn #authority_mask_fn() -> ::c_u_later::__private::Vec<bool> {
-            #[allow(unused_imports)]
-            use ::c_u_later::IsNotCuLater as _;
             let mut mask = ::c_u_later::__private::vec![false; ::core::mem::size_of::<#name>()];
             #(#authority_mask_parts)*
             mask
@@ -239,10 +307,19 @@ fn derive_cu_later_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
             fn authority_mask() -> ::c_u_later::__private::Vec<bool> {
                 #authority_mask_fn()
             }
+
+            fn sequenced_offsets() -> ::c_u_later::__private::Vec<usize> {
+                ::c_u_later::__private::vec![#(#sequenced_offsets),*]
+            }
+
+            fn field_spans() -> ::c_u_later::__private::Vec<::c_u_later::FieldSpan> {
+                ::c_u_later::__private::vec![#(#field_spans),*]
+            }
         }
 
         #program_wrapper
         #authority_wrapper
+        #views
         #program_delta
         #authority_delta
     };
@@ -256,6 +333,7 @@ struct FieldInfo {
     has_program: bool,
     has_authority: bool,
     has_embed: bool,
+    has_sequenced: bool,
 }
 
 fn has_repr_c(attrs: &[Attribute]) -> bool {
@@ -300,14 +378,20 @@ const PRIMITIVE_NAMES: &[&str] = &[
     "u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128", "f32", "f64", "bool",
 ];
 
+/// `c_u_soon` value types with built-in all-writable masks, matched by their last path
+/// segment so `c_u_soon::U64Le` and an imported `U64Le` both count.
+const LEAF_NAMES: &[&str] = &["U32Le", "U64Le", "I32Le", "I64Le", "PriceValue"];
+
 fn is_primitive_or_array(ty: &Type) -> bool {
     match ty {
         Type::Path(type_path) => {
             if let Some(ident) = type_path.path.get_ident() {
                 let s = ident.to_string();
-                return PRIMITIVE_NAMES.contains(&s.as_str());
+                return PRIMITIVE_NAMES.contains(&s.as_str()) || LEAF_NAMES.contains(&s.as_str());
             }
-            false
+            type_path.path.segments.last().is_some_and(|last| {
+                last.arguments.is_none() && LEAF_NAMES.contains(&last.ident.to_string().as_str())
+            })
         }
         Type::Array(_) => true,
         _ => false,
@@ -340,6 +424,99 @@ fn build_wrapper_path(ty: &Type, suffix: &str) -> syn::Result<syn::Path> {
     }
 }
 
+/// Const assertions tying each composed field (`#[program]` / `#[authority]` without
+/// `#[embed]`) whose type implements `TypeHash` to the layout the parent's `TypeHash`
+/// describes: fields packed in declaration order, each as large as its `METADATA` says.
+/// The field's `size_of` must equal its metadata size, and its offset the sum of the
+/// preceding fields' sizes (metadata size where the type has one, `size_of` otherwise).
+/// A child mask is spliced in at `offset_of`, so this catches the mask and the hash
+/// disagreeing about where a nested struct lives.
+fn generate_layout_checks(struct_name: &syn::Ident, fields: &[FieldInfo]) -> TokenStream2 {
+    let mut checks = Vec::new();
+    let mut preceding: Vec<&Type> = Vec::new();
+    for field in fields {
+        if (field.has_program || field.has_authority) && !field.has_embed {
+            let field_name = &field.name;
+            let field_ty = &field.ty;
+            let preceding_sizes = preceding.iter().map(|ty| {
+                quote! {
+                    + match <::c_u_later::TypeHashSize<#ty>>::SIZE {
+                        Some(size) => size,
+                        None => ::core::mem::size_of::<#ty>(),
+                    }
+                }
+            });
+            checks.push(quote! {
+                {
+                    let expected_offset = 0 #(#preceding_sizes)*;
+                    if let Some(size) = <::c_u_later::TypeHashSize<#field_ty>>::SIZE {
+                        assert!(
+                            size == ::core::mem::size_of::<#field_ty>(),
+                            concat!(
+                                "CuLater: field `", stringify!(#field_name),
+                                "` has a TypeHash metadata size different from its size_of"
+                            )
+                        );
+                        assert!(
+                            ::core::mem::offset_of!(#struct_name, #field_name) == expected_offset,
+                            concat!(
+                                "CuLater: field `", stringify!(#field_name),
+                                "` is not at the offset its TypeHash field order implies"
+                            )
+                        );
+                    }
+                }
+            });
+        }
+        preceding.push(&field.ty);
+    }
+
+    if checks.is_empty() {
+        return quote! {};
+    }
+    quote! {
+        const _: () = {
+            #[allow(unused_imports)]
+            use ::c_u_later::NoTypeHashSize as _;
+            #(#checks)*
+        };
+    }
+}
+
+/// Const assertions that no `#[embed]` field's type implements `CuLater`. Embedding one
+/// would mark all its bytes writable and discard the masks it declares for itself.
+fn generate_embed_checks(fields: &[FieldInfo]) -> TokenStream2 {
+    let checks: Vec<TokenStream2> = fields
+        .iter()
+        .filter(|f| f.has_embed)
+        .map(|f| {
+            let field_name = &f.name;
+            let field_ty = &f.ty;
+            quote! {
+                assert!(
+                    !<::c_u_later::IsCuLaterWrapper<#field_ty>>::IS_CU_LATER,
+                    concat!(
+                        "CuLater: field `", stringify!(#field_name),
+                        "` has #[embed] but its type implements CuLater; remove #[embed] so \
+                         the type's own masks compose at the field's offset"
+                    )
+                );
+            }
+        })
+        .collect();
+
+    if checks.is_empty() {
+        return quote! {};
+    }
+    quote! {
+        const _: () = {
+            #[allow(unused_imports)]
+            use ::c_u_later::IsNotCuLater as _;
+            #(#checks)*
+        };
+    }
+}
+
 fn generate_delta_builder(
     struct_name: &syn::Ident,
     vis: &syn::Visibility,
@@ -448,6 +625,127 @@ fn generate_delta_builder(
     }
 }
 
+/// `{Name}Ref<'a, R>` and `{Name}Mut<'a, R>`: views that expose only the fields role `R`
+/// (`ProgramRole` or `AuthorityRole`) may write. Composite fields return the child's views
+/// for the same role; leaf and `#[embed]` fields return plain references.
+fn generate_views(
+    struct_name: &syn::Ident,
+    vis: &syn::Visibility,
+    fields: &[FieldInfo],
+) -> syn::Result<TokenStream2> {
+    let ref_name = format_ident!("{}Ref", struct_name);
+    let mut_name = format_ident!("{}Mut", struct_name);
+
+    let mut role_impls = Vec::new();
+    for (role, is_program) in [
+        (quote!(::c_u_later::ProgramRole), true),
+        (quote!(::c_u_later::AuthorityRole), false),
+    ] {
+        let mut getters = Vec::new();
+        let mut setters = Vec::new();
+        for field in fields {
+            let included = if is_program {
+                field.has_program
+            } else {
+                field.has_authority
+            };
+            if !included || is_padding_field(&field.name) {
+                continue;
+            }
+
+            let field_name = &field.name;
+            let accessor_name = format_ident!("{}_mut", field_name);
+            let field_ty = &field.ty;
+
+            if !field.has_embed && !is_primitive_or_array(field_ty) {
+                let child_ref = build_wrapper_path(field_ty, "Ref")?;
+                let child_mut = build_wrapper_path(field_ty, "Mut")?;
+                getters.push(quote! {
+                    #vis fn #field_name(&self) -> #child_ref<'_, #role> {
+                        <#child_ref<'_, #role> as ::c_u_later::AuxView<'_>>::from_target(
+                            &self.0.#field_name,
+                        )
+                    }
+                });
+                setters.push(quote! {
+                    #vis fn #accessor_name(&mut self) -> #child_mut<'_, #role> {
+                        <#child_mut<'_, #role> as ::c_u_later::AuxViewMut<'_>>::from_target_mut(
+                            &mut self.0.#field_name,
+                        )
+                    }
+                });
+            } else {
+                getters.push(quote! {
+                    #vis fn #field_name(&self) -> &#field_ty {
+                        &self.0.#field_name
+                    }
+                });
+                setters.push(quote! {
+                    #vis fn #accessor_name(&mut self) -> &mut #field_ty {
+                        &mut self.0.#field_name
+                    }
+                });
+            }
+        }
+
+        role_impls.push(quote! {
+            impl #ref_name<'_, #role> {
+                #(#getters)*
+            }
+
+            impl #mut_name<'_, #role> {
+                #(#getters)*
+                #(#setters)*
+            }
+        });
+    }
+
+    Ok(quote! {
+        #vis struct #ref_name<'a, R: ::c_u_later::Role>(
+            &'a #struct_name,
+            ::core::marker::PhantomData<R>,
+        );
+
+        impl<R: ::c_u_later::Role> ::core::clone::Clone for #ref_name<'_, R> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<R: ::c_u_later::Role> ::core::marker::Copy for #ref_name<'_, R> {}
+
+        impl<'a, R: ::c_u_later::Role> ::c_u_later::AuxView<'a> for #ref_name<'a, R> {
+            type Target = #struct_name;
+
+            fn from_target(target: &'a #struct_name) -> Self {
+                Self(target, ::core::marker::PhantomData)
+            }
+        }
+
+        #vis struct #mut_name<'a, R: ::c_u_later::Role>(
+            &'a mut #struct_name,
+            ::core::marker::PhantomData<R>,
+        );
+
+        impl<'a, R: ::c_u_later::Role> ::c_u_later::AuxViewMut<'a> for #mut_name<'a, R> {
+            type Target = #struct_name;
+
+            fn from_target_mut(target: &'a mut #struct_name) -> Self {
+                Self(target, ::core::marker::PhantomData)
+            }
+        }
+
+        impl<R: ::c_u_later::Role> #mut_name<'_, R> {
+            /// Read-only view of the same fields.
+            #vis fn as_view(&self) -> #ref_name<'_, R> {
+                #ref_name(&*self.0, ::core::marker::PhantomData)
+            }
+        }
+
+        #(#role_impls)*
+    })
+}
+
 fn generate_wrapper(
     struct_name: &syn::Ident,
     vis: &syn::Visibility,
@@ -7,12 +7,21 @@
 //! type to be valid oracle auxiliary data.
 //!
 //! Masks are `Vec<bool>` (length = `size_of::<T>()`) where `true` = writable.
-//! The on-chain wire format uses inverted encoding: `0x00` = writable, `0xFF` = blocked,
-//! with trailing bytes (beyond struct size) padded to `0xFF`.
+//! The on-chain [`Mask`](c_u_soon::Mask) stores a [`Permission`] per byte (wire encoding
+//! `0x00` = writable, `0xFF` = blocked), with trailing bytes beyond the struct size blocked.
+//! [`program_permission`] and [`authority_permission`] read a single offset.
+//! [`to_program_compact_mask`] and [`to_authority_compact_mask`] pack a mask into a
+//! 32-byte [`CompactMask`] for instruction data.
 //!
 //! The `#[derive(CuLater)]` macro (from [`c_u_later_derive`]) generates `CuLaterMask`
-//! for a `#[repr(C)]` struct, annotating fields with `#[program]`, `#[authority]`, or
-//! `#[embed]` to control per-field write permissions.
+//! for a `#[repr(C)]` struct, annotating fields with `#[program]`, `#[authority]`,
+//! `#[embed]`, or `#[readonly]` to control per-field write permissions. Fields named `_...`
+//! are padding and always blocked. `#[sequenced]` marks 8-byte counters that
+//! [`sequenced_constraints`] turns into constraints the program enforces.
+//!
+//! The derive also generates `MyStructRef<'a, R>` and `MyStructMut<'a, R>`, zero-copy
+//! [`AuxView`]s over an envelope's auxiliary data that expose only the fields role `R`
+//! ([`ProgramRole`] or [`AuthorityRole`]) may write.
 
 extern crate alloc;
 
@@ -24,77 +33,33 @@ use core::marker::PhantomData;
 pub use c_u_later_derive::CuLater;
 
 pub use bytemuck::{Pod, Zeroable};
-pub use c_u_soon_instruction::WriteSpec;
+pub use c_u_soon_instruction::{ConstraintSpec, WriteSpec};
 
 #[cfg(feature = "alloc")]
 pub mod validation;
 
 pub const AUX_SIZE: usize = c_u_soon::MAX_AUX_STRUCT_SIZE;
 
-/// Compact 256-bit permission mask (32 bytes, 1 bit per aux byte).
-/// Internal to c_u_later — use `to_program_wire_mask`/`to_authority_wire_mask` for on-chain format.
-#[derive(Clone, Copy, Pod, Zeroable, Debug, PartialEq, Eq)]
-#[repr(C)]
-pub(crate) struct BitVec256([u8; 32]);
-
-impl BitVec256 {
-    pub(crate) const ZERO: Self = BitVec256([0; 32]);
-    #[cfg(test)]
-    pub(crate) const FULL: Self = BitVec256([0xFF; 32]);
-
-    /// Marks aux byte `bit` as writable. No-op if `bit >= 256`.
-    #[inline]
-    pub fn set_bit(&mut self, bit: usize) {
-        if bit < 256 {
-            let byte = bit / 8;
-            let bit_in_byte = bit % 8;
-            self.0[byte] |= 1 << bit_in_byte;
-        }
-    }
-
-    /// Returns `true` if aux byte `bit` is writable. Returns `false` if `bit >= 256`.
-    #[inline]
-    pub fn get_bit(&self, bit: usize) -> bool {
-        if bit < 256 {
-            let byte = bit / 8;
-            let bit_in_byte = bit % 8;
-            (self.0[byte] & (1 << bit_in_byte)) != 0
-        } else {
-            false
-        }
-    }
-
-    /// Returns `true` if every byte in `offset..offset+size` is writable.
-    /// Returns `false` if `offset + size > 256` or any byte in the range is blocked.
-    #[inline]
-    pub fn is_write_allowed(&self, offset: usize, size: usize) -> bool {
-        (offset + size <= 256) && (offset..offset + size).all(|i| self.get_bit(i))
-    }
-}
+/// Bool ↔ wire mask conversions, shared with clients that don't use the derive.
+pub use c_u_soon::{
+    bools_to_bitvec, bools_to_wire_mask, wire_mask_to_bools, BitVec256, CompactMask,
+    COMPACT_MASK_SIZE,
+};
 
-/// Convert a bool slice mask to BitVec256 (32 bytes).
-/// Only iterates `mask.len()` entries; bits beyond that stay 0 (not writable).
-#[inline]
-pub(crate) fn bools_to_bitvec(mask: &[bool]) -> BitVec256 {
-    let mut result = BitVec256::ZERO;
-    for i in 0..mask.len() {
-        if mask[i] {
-            result.set_bit(i);
-        }
-    }
-    result
-}
+pub use c_u_soon::Permission;
 
-/// Get compact 256-bit program write mask for a CuLater type.
+/// Compact 256-bit program write mask for a CuLater type, the 32-byte form
+/// `SetDelegatedProgramCompact` takes. Equal to [`to_program_wire_mask`] once expanded.
 #[inline]
-pub(crate) fn to_program_bitvec<T: CuLaterMask>() -> BitVec256 {
+pub fn to_program_compact_mask<T: CuLaterMask>() -> CompactMask {
     let mask = T::program_mask();
     bools_to_bitvec(&mask)
 }
 
-/// Get compact 256-bit authority write mask for a CuLater type.
+/// Compact 256-bit authority write mask for a CuLater type. See
+/// [`to_program_compact_mask`].
 #[inline]
-pub(crate) fn to_authority_bitvec<T: CuLaterMask>() -> BitVec256 {
+pub fn to_authority_compact_mask<T: CuLaterMask>() -> CompactMask {
     let mask = T::authority_mask();
     bools_to_bitvec(&mask)
 }
@@ -107,11 +72,33 @@ pub(crate) fn to_authority_bitvec<T: CuLaterMask>() -> BitVec256 {
 /// - `program_mask()`: bytes the delegated program may write.
 /// - `authority_mask()`: bytes the oracle authority may write.
 ///
+/// `sequenced_offsets()` lists the offsets of 8-byte counters that writes may not decrease.
+/// It defaults to none; the derive fills it from `#[sequenced]` fields.
+///
+/// `field_spans()` lists the top-level fields in offset order, for annotating masks when
+/// debugging. It also defaults to none; the derive lists every named field.
+///
 /// Primitives and fixed-size arrays of `CuLaterMask` types have built-in impls (all
 /// bytes writable). Composite types derive this via `#[derive(CuLater)]`.
 pub trait CuLaterMask {
     fn program_mask() -> Vec<bool>;
     fn authority_mask() -> Vec<bool>;
+
+    fn sequenced_offsets() -> Vec<usize> {
+        Vec::new()
+    }
+
+    fn field_spans() -> Vec<FieldSpan> {
+        Vec::new()
+    }
+}
+
+/// A top-level field of a `#[derive(CuLater)]` struct and the bytes it occupies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldSpan {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
 }
 
 /// Marker supertrait for a complete oracle auxiliary type.
@@ -166,6 +153,11 @@ impl_cu_later_mask_primitive!(i128, 16);
 impl_cu_later_mask_primitive!(f32, 4);
 impl_cu_later_mask_primitive!(f64, 8);
 impl_cu_later_mask_primitive!(bool, 1);
+impl_cu_later_mask_primitive!(c_u_soon::U32Le, 4);
+impl_cu_later_mask_primitive!(c_u_soon::U64Le, 8);
+impl_cu_later_mask_primitive!(c_u_soon::I32Le, 4);
+impl_cu_later_mask_primitive!(c_u_soon::I64Le, 8);
+impl_cu_later_mask_primitive!(c_u_soon::PriceValue, 20);
 
 impl<T: CuLaterMask, const N: usize> CuLaterMask for [T; N] {
     fn program_mask() -> Vec<bool> {
@@ -196,27 +188,89 @@ impl<T: CuLaterMask, const N: usize> CuLaterMask for [T; N] {
 }
 
 /// Convert a CuLaterMask program mask to c_u_soon on-chain Mask format.
-/// Polarity: true (writable) → 0x00, false (blocked) → 0xFF.
+/// Writable bytes become [`Permission::Writable`]; everything else is blocked.
 pub fn to_program_wire_mask<T: CuLaterMask>() -> c_u_soon::Mask {
     let mask = T::program_mask();
     bools_to_wire_mask(&mask)
 }
 
 /// Convert a CuLaterMask authority mask to c_u_soon on-chain Mask format.
-/// Polarity: true (writable) → 0x00, false (blocked) → 0xFF.
+/// Writable bytes become [`Permission::Writable`]; everything else is blocked.
 pub fn to_authority_wire_mask<T: CuLaterMask>() -> c_u_soon::Mask {
     let mask = T::authority_mask();
     bools_to_wire_mask(&mask)
 }
 
-fn bools_to_wire_mask(mask: &[bool]) -> c_u_soon::Mask {
-    let mut wire = [0xFFu8; c_u_soon::MASK_SIZE];
-    for i in 0..mask.len().min(c_u_soon::MASK_SIZE) {
-        if mask[i] {
-            wire[i] = 0x00;
-        }
+/// Program permission for the byte at `offset` of `T`. Offsets past `size_of::<T>()`
+/// are blocked.
+pub fn program_permission<T: CuLaterMask>(offset: usize) -> Permission {
+    Permission::from(T::program_mask().get(offset).copied().unwrap_or(false))
+}
+
+/// Authority permission for the byte at `offset` of `T`. Offsets past `size_of::<T>()`
+/// are blocked.
+pub fn authority_permission<T: CuLaterMask>(offset: usize) -> Permission {
+    Permission::from(T::authority_mask().get(offset).copied().unwrap_or(false))
+}
+
+/// A caller role for the derive's `Ref` / `Mut` views: [`ProgramRole`] or [`AuthorityRole`].
+pub trait Role {}
+
+/// The delegated program: views expose the `#[program]` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramRole;
+
+/// The oracle authority: views expose the `#[authority]` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthorityRole;
+
+impl Role for ProgramRole {}
+impl Role for AuthorityRole {}
+
+/// Read-only role view over a CuLater struct, generated by the derive as `MyStructRef`.
+pub trait AuxView<'a>: Sized {
+    type Target;
+
+    fn from_target(target: &'a Self::Target) -> Self;
+
+    /// View `envelope.auxiliary_data` in place. `None` if the envelope's aux metadata is
+    /// not `Target`'s or the bytes are misaligned; see
+    /// [`Envelope::aux`](c_u_soon::Envelope::aux).
+    fn from_envelope(envelope: &'a c_u_soon::Envelope) -> Option<Self>
+    where
+        Self::Target: c_u_soon::TypeHash + Pod,
+    {
+        envelope.aux::<Self::Target>().map(Self::from_target)
+    }
+}
+
+/// Mutable role view over a CuLater struct, generated by the derive as `MyStructMut`.
+pub trait AuxViewMut<'a>: Sized {
+    type Target;
+
+    fn from_target_mut(target: &'a mut Self::Target) -> Self;
+
+    /// [`AuxView::from_envelope`] for writing; see
+    /// [`Envelope::aux_mut`](c_u_soon::Envelope::aux_mut).
+    fn from_envelope_mut(envelope: &'a mut c_u_soon::Envelope) -> Option<Self>
+    where
+        Self::Target: c_u_soon::TypeHash + Pod,
+    {
+        envelope
+            .aux_mut::<Self::Target>()
+            .map(Self::from_target_mut)
     }
-    c_u_soon::Mask::from(wire)
+}
+
+/// One [`Constraint::sequenced`](c_u_soon::Constraint::sequenced) entry per `#[sequenced]`
+/// field of `T`, ready for `set_constraints_instruction_data`. Once stored, the program
+/// rejects any aux write that decreases one of the fields, whichever role sends it. The
+/// entries count toward the table's [`MAX_CONSTRAINTS`](c_u_soon::MAX_CONSTRAINTS).
+pub fn sequenced_constraints<T: CuLaterMask>() -> Vec<ConstraintSpec> {
+    T::sequenced_offsets()
+        .into_iter()
+        .map(|offset| c_u_soon::Constraint::sequenced(offset as u8).into())
+        .collect()
 }
 
 #[doc(hidden)]
@@ -225,11 +279,16 @@ pub mod __private {
     pub use alloc::vec::Vec;
 }
 
+/// Whether `T` implements [`CuLater`], read by the derive's `#[embed]` assertions.
+/// `IS_CU_LATER` and `is_cu_later()` are `true` for `CuLater` types and, through
+/// [`IsNotCuLater`], `false` for the rest. Only resolves this way for concrete types.
 pub struct IsCuLaterWrapper<T> {
     _inner: PhantomData<T>,
 }
 
 pub trait IsNotCuLater {
+    const IS_CU_LATER: bool = false;
+
     fn is_cu_later() -> bool;
 }
 
@@ -240,11 +299,30 @@ impl<T> IsNotCuLater for IsCuLaterWrapper<T> {
 }
 
 impl<T: CuLater> IsCuLaterWrapper<T> {
+    pub const IS_CU_LATER: bool = true;
+
     pub fn is_cu_later() -> bool {
         true
     }
 }
 
+/// Size a field type declares through [`TypeHash`](c_u_soon::TypeHash), read by the derive's
+/// layout assertions. `SIZE` is `Some(METADATA.type_size())` for `TypeHash` types and, through
+/// [`NoTypeHashSize`], `None` for the rest. Only resolves this way for concrete types.
+pub struct TypeHashSize<T> {
+    _inner: PhantomData<T>,
+}
+
+pub trait NoTypeHashSize {
+    const SIZE: Option<usize> = None;
+}
+
+impl<T> NoTypeHashSize for TypeHashSize<T> {}
+
+impl<T: c_u_soon::TypeHash> TypeHashSize<T> {
+    pub const SIZE: Option<usize> = Some(T::METADATA.type_size() as usize);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -295,6 +373,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_type_hash_size_probe() {
+        struct Opaque;
+        assert_eq!(<TypeHashSize<u32>>::SIZE, Some(4));
+        assert_eq!(<TypeHashSize<[u16; 3]>>::SIZE, Some(6));
+        assert_eq!(<TypeHashSize<Opaque>>::SIZE, None);
+        assert_eq!(<TypeHashSize<bool>>::SIZE, None);
+    }
+
     #[test]
     fn test_compose_mask_at_offset() {
         let child = vec![true, true];
@@ -351,44 +438,6 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_bitmask_set_get_bits() {
-        let mut mask = BitVec256::ZERO;
-        assert!(!mask.get_bit(0));
-        assert!(!mask.get_bit(255));
-
-        mask.set_bit(0);
-        assert!(mask.get_bit(0));
-        assert!(!mask.get_bit(1));
-
-        mask.set_bit(255);
-        assert!(mask.get_bit(255));
-        assert!(mask.get_bit(0));
-
-        mask.set_bit(256);
-        assert!(!mask.get_bit(256));
-    }
-
-    #[test]
-    fn test_bitmask_is_write_allowed() {
-        let mut mask = BitVec256::ZERO;
-
-        assert!(!mask.is_write_allowed(0, 1));
-        assert!(!mask.is_write_allowed(0, 256));
-
-        for i in 0..8 {
-            mask.set_bit(i);
-        }
-        assert!(mask.is_write_allowed(0, 8));
-        assert!(!mask.is_write_allowed(0, 9));
-        assert!(!mask.is_write_allowed(7, 2));
-
-        let full_mask = BitVec256::FULL;
-        assert!(full_mask.is_write_allowed(0, 256));
-        assert!(full_mask.is_write_allowed(100, 100));
-        assert!(!full_mask.is_write_allowed(255, 2));
-    }
-
     #[test]
     fn test_to_program_bitmask_conversion() {
         let program_mask = u8::program_mask();
@@ -417,4 +466,27 @@ mod tests {
 
         assert_eq!(original, unpacked);
     }
+
+    #[test]
+    fn test_compact_mask_matches_wire_mask() {
+        assert_eq!(
+            c_u_soon::Mask::from(to_program_compact_mask::<u16>()),
+            to_program_wire_mask::<u16>()
+        );
+        assert_eq!(
+            c_u_soon::Mask::from(to_authority_compact_mask::<u32>()),
+            to_authority_wire_mask::<u32>()
+        );
+    }
+
+    #[test]
+    fn test_permission_at_offset() {
+        assert_eq!(program_permission::<u16>(1), Permission::Writable);
+        assert_eq!(program_permission::<u16>(2), Permission::Blocked);
+        assert_eq!(authority_permission::<u8>(0), Permission::Writable);
+        assert_eq!(
+            to_program_wire_mask::<u16>().get(1),
+            program_permission::<u16>(1)
+        );
+    }
 }
@@ -2,15 +2,22 @@
 //!
 //! [`validate_program_change`] and [`validate_authority_change`] verify that a proposed
 //! auxiliary data update stays within mask-defined write permissions. [`diff_report`]
-//! produces a per-byte breakdown for debugging rejected changes.
+//! produces a per-byte breakdown for debugging rejected changes. [`verify_envelope_masks`]
+//! checks that the masks stored in an envelope match the ones derived for its aux type,
+//! and [`annotate_mask`] prints a mask with the type's field boundaries marked.
+//! [`check_write`] checks one byte-range write the way the program will, and names the
+//! first byte and field it would be rejected for.
 //!
 //! This module requires the `alloc` feature (gated in `c_u_later/src/lib.rs`).
 //! On-chain enforcement uses the bitmask directly in the program handler.
 
 extern crate alloc;
 
-use crate::{BitVec256, CuLaterMask, AUX_SIZE};
+use crate::{BitVec256, CuLater, CuLaterMask, FieldSpan, Permission, AUX_SIZE};
+use alloc::string::ToString;
 use alloc::vec::Vec;
+use c_u_soon::{Envelope, Mask, AUX_DATA_SIZE, MASK_SIZE};
+use core::fmt;
 
 /// Returns `true` if every changed byte is permitted by `mask`.
 ///
@@ -32,14 +39,14 @@ pub(crate) fn validate_change(old: &[u8], new: &[u8], mask: &BitVec256) -> bool
 /// Validate that a change respects the program write mask for type T.
 #[inline]
 pub fn validate_program_change<T: CuLaterMask>(old: &[u8], new: &[u8]) -> bool {
-    let mask = crate::to_program_bitvec::<T>();
+    let mask = crate::to_program_compact_mask::<T>();
     validate_change(old, new, &mask)
 }
 
 /// Validate that a change respects the authority write mask for type T.
 #[inline]
 pub fn validate_authority_change<T: CuLaterMask>(old: &[u8], new: &[u8]) -> bool {
-    let mask = crate::to_authority_bitvec::<T>();
+    let mask = crate::to_authority_compact_mask::<T>();
     validate_change(old, new, &mask)
 }
 
@@ -74,8 +81,8 @@ pub struct ChangeReport {
 /// Only bytes where `old[i] != new[i]` appear in [`ChangeReport::changes`]. Each entry
 /// records whether the change is permitted by the program and authority masks.
 pub fn diff_report<T: CuLaterMask>(old: &[u8], new: &[u8]) -> ChangeReport {
-    let program_mask = crate::to_program_bitvec::<T>();
-    let authority_mask = crate::to_authority_bitvec::<T>();
+    let program_mask = crate::to_program_compact_mask::<T>();
+    let authority_mask = crate::to_authority_compact_mask::<T>();
 
     let mut changes = Vec::new();
     for i in 0..old.len().min(new.len()).min(AUX_SIZE) {
@@ -105,8 +112,8 @@ pub fn diff_report<T: CuLaterMask>(old: &[u8], new: &[u8]) -> ChangeReport {
 /// A byte is marked as constant if neither the program nor the authority
 /// is allowed to write to it.
 pub(crate) fn constant_mask<T: CuLaterMask>() -> BitVec256 {
-    let program_mask = crate::to_program_bitvec::<T>();
-    let authority_mask = crate::to_authority_bitvec::<T>();
+    let program_mask = crate::to_program_compact_mask::<T>();
+    let authority_mask = crate::to_authority_compact_mask::<T>();
 
     let mut result = BitVec256::ZERO;
     for i in 0..256 {
@@ -124,6 +131,235 @@ pub fn verify_constants_unchanged<T: CuLaterMask>(old: &[u8], new: &[u8]) -> boo
     (0..old.len().min(new.len()).min(AUX_SIZE)).all(|i| !const_mask.get_bit(i) || old[i] == new[i])
 }
 
+/// A mask byte whose stored permission differs from the one derived for the aux type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaskMismatch {
+    /// Index within the mask (0..MASK_SIZE), the same as the aux byte it guards.
+    pub byte_offset: usize,
+    /// Permission the derived mask gives this byte.
+    pub expected: Permission,
+    /// Permission stored in the envelope.
+    pub actual: Permission,
+}
+
+/// Differences between an envelope's stored masks and the masks derived for type T.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaskDiff {
+    /// Bytes where `program_bitmask` differs from [`to_program_wire_mask`](crate::to_program_wire_mask).
+    pub program: Vec<MaskMismatch>,
+    /// Bytes where `user_bitmask` differs from [`to_authority_wire_mask`](crate::to_authority_wire_mask).
+    pub user: Vec<MaskMismatch>,
+}
+
+impl MaskDiff {
+    /// `true` if both stored masks match the derived ones.
+    pub fn is_empty(&self) -> bool {
+        self.program.is_empty() && self.user.is_empty()
+    }
+}
+
+fn mask_mismatches(expected: &Mask, actual: &Mask) -> Vec<MaskMismatch> {
+    (0..MASK_SIZE)
+        .filter_map(|i| {
+            let (expected, actual) = (expected.get(i), actual.get(i));
+            (expected != actual).then_some(MaskMismatch {
+                byte_offset: i,
+                expected,
+                actual,
+            })
+        })
+        .collect()
+}
+
+/// Compare the masks stored in `envelope` against the masks derived for type T.
+///
+/// Checks every byte of `program_bitmask` against [`to_program_wire_mask`](crate::to_program_wire_mask)
+/// and every byte of `user_bitmask` against [`to_authority_wire_mask`](crate::to_authority_wire_mask).
+/// Bytes are compared as [`Mask::get`] reads them, the way the program enforces them, so a
+/// stored byte that is neither wire value counts as blocked.
+pub fn verify_envelope_masks<T: CuLater>(envelope: &Envelope) -> MaskDiff {
+    MaskDiff {
+        program: mask_mismatches(
+            &crate::to_program_wire_mask::<T>(),
+            &envelope.program_bitmask,
+        ),
+        user: mask_mismatches(
+            &crate::to_authority_wire_mask::<T>(),
+            &envelope.user_bitmask,
+        ),
+    }
+}
+
+/// The writer of an aux update, as a value for [`check_write`]. The
+/// [`ProgramRole`](crate::ProgramRole) and [`AuthorityRole`](crate::AuthorityRole) marker
+/// types pick the same masks at compile time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// The delegated program, gated by the program mask.
+    Program,
+    /// The oracle authority, gated by the authority mask.
+    Authority,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Role::Program => "program",
+            Role::Authority => "authority",
+        })
+    }
+}
+
+/// Why [`check_write`] rejected a write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskViolation {
+    /// The write has no bytes. The program fails it with `InvalidInstructionData`.
+    Empty,
+    /// The write ends at `end`, past `limit`: the type size, or the length of `current` if
+    /// that is shorter. The program fails it with `InvalidInstructionData`.
+    OutOfBounds { end: usize, limit: usize },
+    /// The write changes a byte the role may not write. The program fails it with
+    /// `InvalidArgument`.
+    Blocked {
+        /// Index within the aux data of the first such byte.
+        byte_offset: usize,
+        /// The top-level field holding the byte, for types with
+        /// [`field_spans`](CuLaterMask::field_spans).
+        field: Option<&'static str>,
+        /// Value stored now.
+        current: u8,
+        /// Value the write would store.
+        new: u8,
+    },
+}
+
+impl fmt::Display for MaskViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaskViolation::Empty => f.write_str("empty aux write"),
+            MaskViolation::OutOfBounds { end, limit } => {
+                write!(f, "aux write ends at byte {end}, past {limit}")
+            }
+            MaskViolation::Blocked {
+                byte_offset,
+                field,
+                current,
+                new,
+            } => {
+                write!(f, "aux byte {byte_offset}")?;
+                if let Some(field) = field {
+                    write!(f, " (field `{field}`)")?;
+                }
+                write!(f, " is blocked: {current:#04x} -> {new:#04x}")
+            }
+        }
+    }
+}
+
+/// Check writing `data` at `offset` of the aux data as `role`, against the masks derived
+/// for type T, with the result the program would give.
+///
+/// `current` is the stored aux data from byte 0, such as `envelope.auxiliary_data`. Blocked
+/// bytes may be written with their current value, as on chain; only a change fails. A full
+/// `UpdateAuxiliary` is the write at offset 0 of all `size_of::<T>()` bytes. The program
+/// enforces the envelope's stored masks, which match these when
+/// [`verify_envelope_masks`] is empty.
+pub fn check_write<T: CuLater>(
+    role: Role,
+    offset: usize,
+    data: &[u8],
+    current: &[u8],
+) -> Result<(), MaskViolation> {
+    if data.is_empty() {
+        return Err(MaskViolation::Empty);
+    }
+    let limit = core::mem::size_of::<T>().min(current.len());
+    let end = offset.saturating_add(data.len());
+    if end > limit {
+        return Err(MaskViolation::OutOfBounds { end, limit });
+    }
+
+    let mask = match role {
+        Role::Program => crate::to_program_wire_mask::<T>(),
+        Role::Authority => crate::to_authority_wire_mask::<T>(),
+    };
+    let mut stored = [0; AUX_DATA_SIZE];
+    stored[..limit].copy_from_slice(&current[..limit]);
+    if mask.check_masked_update(&stored, offset, data) {
+        return Ok(());
+    }
+
+    let byte_offset = (offset..end)
+        .find(|&i| data[i - offset] != stored[i] && !mask.is_writable(i))
+        .expect("check_masked_update fails only on a changed blocked byte");
+    let field = T::field_spans()
+        .into_iter()
+        .find(|span| (span.offset..span.offset + span.size).contains(&byte_offset))
+        .map(|span| span.name);
+    Err(MaskViolation::Blocked {
+        byte_offset,
+        field,
+        current: stored[byte_offset],
+        new: data[byte_offset - offset],
+    })
+}
+
+/// A [`Mask`] grid annotated with the fields of an aux type; see [`annotate_mask`].
+#[derive(Debug, Clone)]
+pub struct AnnotatedMask<'a> {
+    mask: &'a Mask,
+    fields: Vec<FieldSpan>,
+}
+
+/// Render `mask` as its `Display` grid with the [`field_spans`](CuLaterMask::field_spans)
+/// of type T marked: a `|` before each field and after the last, and the fields starting
+/// in each row listed after it as `name@offset`.
+///
+/// ```text
+/// 00 BBBB|WW|W|B|BBBBBBBB  readonly@0x00 both@0x04 program_only@0x06 authority_only@0x07
+/// 10 BBBBBBBBBBBBBBBB
+/// ```
+///
+/// Types without a derive have no spans and print the plain grid.
+pub fn annotate_mask<T: CuLaterMask>(mask: &Mask) -> AnnotatedMask<'_> {
+    AnnotatedMask {
+        mask,
+        fields: T::field_spans(),
+    }
+}
+
+impl fmt::Display for AnnotatedMask<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let end = self.fields.iter().map(|span| span.offset + span.size).max();
+        let is_boundary =
+            |i: usize| end == Some(i) || self.fields.iter().any(|span| span.offset == i);
+        let grid = self.mask.to_string();
+        for (row, line) in grid.lines().enumerate() {
+            if row > 0 {
+                f.write_str("\n")?;
+            }
+            let (prefix, cells) = line.split_at(3);
+            f.write_str(prefix)?;
+            let start = row * 16;
+            for (i, cell) in (start..).zip(cells.chars()) {
+                if i > start && is_boundary(i) {
+                    f.write_str("|")?;
+                }
+                write!(f, "{cell}")?;
+            }
+            let starting = self
+                .fields
+                .iter()
+                .filter(|span| (start..start + 16).contains(&span.offset));
+            for (k, span) in starting.enumerate() {
+                let sep = if k == 0 { "  " } else { " " };
+                write!(f, "{sep}{}@{:#04x}", span.name, span.offset)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,4 +462,53 @@ mod tests {
         new[2] = 6;
         assert!(!verify_constants_unchanged::<u8>(&old, &new));
     }
+
+    #[test]
+    fn test_verify_envelope_masks() {
+        let mut envelope: Envelope = bytemuck::Zeroable::zeroed();
+        envelope.program_bitmask = crate::to_program_wire_mask::<u16>();
+        envelope.user_bitmask = crate::to_authority_wire_mask::<u16>();
+        assert!(verify_envelope_masks::<u16>(&envelope).is_empty());
+
+        // u32 derives four writable bytes in each mask; only two are stored.
+        let diff = verify_envelope_masks::<u32>(&envelope);
+        let offsets: Vec<usize> = diff.program.iter().map(|m| m.byte_offset).collect();
+        assert_eq!(offsets, [2, 3]);
+        assert_eq!(diff.program, diff.user);
+        assert_eq!(diff.program[0].expected, Permission::Writable);
+        assert_eq!(diff.program[0].actual, Permission::Blocked);
+
+        envelope.user_bitmask.allow(200);
+        let diff = verify_envelope_masks::<u16>(&envelope);
+        assert!(diff.program.is_empty());
+        assert_eq!(
+            diff.user,
+            [MaskMismatch {
+                byte_offset: 200,
+                expected: Permission::Blocked,
+                actual: Permission::Writable,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_write_bounds() {
+        let current = [0u8; 256];
+        assert_eq!(
+            check_write::<u32>(Role::Program, 0, &[], &current),
+            Err(MaskViolation::Empty)
+        );
+        assert_eq!(
+            check_write::<u32>(Role::Program, 2, &[1; 3], &current),
+            Err(MaskViolation::OutOfBounds { end: 5, limit: 4 })
+        );
+        assert_eq!(
+            check_write::<u32>(Role::Authority, 0, &[1; 4], &current[..2]),
+            Err(MaskViolation::OutOfBounds { end: 4, limit: 2 })
+        );
+        assert_eq!(
+            check_write::<u32>(Role::Authority, 1, &[1; 3], &current),
+            Ok(())
+        );
+    }
 }
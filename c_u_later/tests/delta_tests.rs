@@ -309,7 +309,6 @@ fn empty_delta_for_role_without_fields() {
 struct WithPadding {
     #[program]
     value: u8,
-    #[program]
     _pad: [u8; 3],
     #[program]
     big: u32,
@@ -317,7 +316,7 @@ struct WithPadding {
 
 #[test]
 fn padding_fields_excluded_from_delta() {
-    // _pad starts with '_' so should NOT appear in delta
+    // _pad starts with '_' so it is padding and has no setter
     // Only value and big should have setters
     let mut d = WithPaddingProgramDelta::new();
     d.set_value(0xFF);
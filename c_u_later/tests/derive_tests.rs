@@ -1,4 +1,5 @@
 use bytemuck::{Pod, Zeroable};
+use c_u_later::validation::{annotate_mask, check_write, MaskViolation, Role};
 use c_u_later::{CuLater, CuLaterMask, IsCuLaterWrapper, IsNotCuLater};
 use c_u_soon::TypeHash;
 use core::ops::Deref;
@@ -41,6 +42,100 @@ fn test_simple_struct_masks() {
     assert!(authority_mask[7], "authority should write byte 7");
 }
 
+#[test]
+fn test_check_write_names_the_blocked_field() {
+    let current = bytemuck::bytes_of(&Simple {
+        readonly: 9,
+        both: 0,
+        program_only: 0,
+        authority_only: 5,
+    })
+    .to_vec();
+
+    assert_eq!(
+        check_write::<Simple>(Role::Program, 4, &[1, 2, 3], &current),
+        Ok(())
+    );
+    let violation = check_write::<Simple>(Role::Program, 4, &[1, 2, 3, 4], &current).unwrap_err();
+    assert_eq!(
+        violation,
+        MaskViolation::Blocked {
+            byte_offset: 7,
+            field: Some("authority_only"),
+            current: 5,
+            new: 4,
+        }
+    );
+    assert_eq!(
+        violation.to_string(),
+        "aux byte 7 (field `authority_only`) is blocked: 0x05 -> 0x04"
+    );
+
+    // Blocked bytes may be rewritten with their current value, as on chain.
+    assert_eq!(
+        check_write::<Simple>(Role::Authority, 0, &[9, 0, 0, 0, 1, 1, 0, 7], &current),
+        Ok(())
+    );
+    assert!(matches!(
+        check_write::<Simple>(Role::Authority, 0, &[8], &current),
+        Err(MaskViolation::Blocked {
+            byte_offset: 0,
+            field: Some("readonly"),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_field_spans_annotate_mask() {
+    let spans = Simple::field_spans();
+    let layout: Vec<_> = spans.iter().map(|s| (s.name, s.offset, s.size)).collect();
+    assert_eq!(
+        layout,
+        [
+            ("readonly", 0, 4),
+            ("both", 4, 2),
+            ("program_only", 6, 1),
+            ("authority_only", 7, 1),
+        ]
+    );
+    assert!(u64::field_spans().is_empty());
+
+    let mask = c_u_later::to_program_wire_mask::<Simple>();
+    let text = annotate_mask::<Simple>(&mask).to_string();
+    let rows: Vec<&str> = text.lines().collect();
+    assert_eq!(rows.len(), 16);
+    assert_eq!(
+        rows[0],
+        "00 BBBB|WW|W|B|BBBBBBBB  readonly@0x00 both@0x04 program_only@0x06 authority_only@0x07"
+    );
+    assert_eq!(rows[1], "10 BBBBBBBBBBBBBBBB");
+    assert_eq!(annotate_mask::<u64>(&mask).to_string(), mask.to_string());
+}
+
+#[derive(Clone, Copy, Pod, Zeroable, TypeHash, CuLater)]
+#[repr(C)]
+struct LittleEndianFields {
+    #[program]
+    price: c_u_soon::I64Le,
+    #[authority]
+    updated: c_u_soon::U64Le,
+    count: c_u_soon::U32Le,
+}
+
+#[test]
+fn test_little_endian_wrapper_fields() {
+    let program_mask = LittleEndianFields::program_mask();
+    let authority_mask = LittleEndianFields::authority_mask();
+
+    assert_eq!(core::mem::size_of::<LittleEndianFields>(), 20);
+    assert_eq!(program_mask, [vec![true; 8], vec![false; 12]].concat());
+    assert_eq!(
+        authority_mask,
+        [vec![false; 8], vec![true; 8], vec![false; 4]].concat()
+    );
+}
+
 #[test]
 fn test_array_field() {
     #[derive(Clone, Copy, Pod, Zeroable, TypeHash, CuLater, Debug)]
@@ -204,6 +299,9 @@ fn detects_is_cu_later() {
 
     assert!(!IsCuLaterWrapper::<NotCuLater>::is_cu_later());
     assert!(IsCuLaterWrapper::<IsCuLater>::is_cu_later());
+    const { assert!(!<IsCuLaterWrapper<NotCuLater>>::IS_CU_LATER) };
+    const { assert!(<IsCuLaterWrapper<IsCuLater>>::IS_CU_LATER) };
+    const { assert!(<IsCuLaterWrapper<[IsCuLater; 2]>>::IS_CU_LATER) };
 }
 
 #[test]
@@ -296,48 +394,6 @@ fn test_repr_c_with_align() {
     }
 }
 
-#[test]
-#[should_panic(expected = "implements CuLater")]
-fn embed_rejects_cu_later_type() {
-    #[derive(Pod, Zeroable, TypeHash, CuLater, Copy, Clone)]
-    #[repr(C)]
-    struct InnerCuLater {
-        #[program]
-        x: u8,
-    }
-
-    #[derive(Pod, Zeroable, TypeHash, CuLater, Copy, Clone)]
-    #[repr(C)]
-    struct OuterWithBadEmbed {
-        #[program]
-        #[embed]
-        inner: InnerCuLater,
-    }
-
-    let _ = OuterWithBadEmbed::program_mask();
-}
-
-#[test]
-#[should_panic(expected = "implements CuLater")]
-fn embed_rejects_array_of_cu_later() {
-    #[derive(Pod, Zeroable, TypeHash, CuLater, Copy, Clone)]
-    #[repr(C)]
-    struct InnerCuLater {
-        #[program]
-        x: u8,
-    }
-
-    #[derive(Pod, Zeroable, TypeHash, CuLater, Copy, Clone)]
-    #[repr(C)]
-    struct OuterWithBadArrayEmbed {
-        #[program]
-        #[embed]
-        inners: [InnerCuLater; 2],
-    }
-
-    let _ = OuterWithBadArrayEmbed::program_mask();
-}
-
 #[test]
 fn test_inter_field_padding() {
     #[derive(Clone, Copy, Pod, Zeroable, TypeHash, CuLater, Debug)]
@@ -688,27 +744,6 @@ fn embed_with_program_and_authority() {
     }
 }
 
-#[test]
-#[should_panic(expected = "implements CuLater")]
-fn embed_rejects_cu_later_type_via_authority() {
-    #[derive(Pod, Zeroable, TypeHash, CuLater, Copy, Clone)]
-    #[repr(C)]
-    struct InnerCuLater {
-        #[program]
-        x: u8,
-    }
-
-    #[derive(Pod, Zeroable, TypeHash, CuLater, Copy, Clone)]
-    #[repr(C)]
-    struct OuterWithBadEmbed {
-        #[authority]
-        #[embed]
-        inner: InnerCuLater,
-    }
-
-    let _ = OuterWithBadEmbed::authority_mask();
-}
-
 // --- TypeHash integration tests ---
 
 #[test]
@@ -934,6 +969,72 @@ fn wrapper_nested_cu_later_authority() {
     assert_eq!(o.inner_both.auth_field, 99);
 }
 
+// --- Role view tests ---
+
+#[test]
+fn views_expose_only_role_fields() {
+    use c_u_later::{AuthorityRole, AuxView, AuxViewMut, ProgramRole};
+
+    let mut s = Simple {
+        readonly: 1,
+        both: 2,
+        program_only: 3,
+        authority_only: 4,
+    };
+    let program = SimpleRef::<ProgramRole>::from_target(&s);
+    assert_eq!((*program.both(), *program.program_only()), (2, 3));
+    let authority = SimpleRef::<AuthorityRole>::from_target(&s);
+    assert_eq!((*authority.both(), *authority.authority_only()), (2, 4));
+    {
+        let mut view = SimpleMut::<AuthorityRole>::from_target_mut(&mut s);
+        *view.both_mut() = 20;
+        *view.authority_only_mut() = 40;
+        assert_eq!(*view.as_view().both(), 20);
+    }
+    assert_eq!((s.readonly, s.both, s.authority_only), (1, 20, 40));
+}
+
+#[test]
+fn views_nest_with_the_same_role() {
+    use c_u_later::{AuthorityRole, AuxView, AuxViewMut, ProgramRole};
+
+    let inner = Inner {
+        prog_field: 1,
+        auth_field: 2,
+    };
+    let mut o = Outer {
+        header: 0,
+        inner_prog: inner,
+        inner_auth: inner,
+        inner_both: inner,
+    };
+    {
+        let mut view = OuterMut::<ProgramRole>::from_target_mut(&mut o);
+        *view.inner_both_mut().prog_field_mut() = 7;
+        assert_eq!(*view.inner_prog().prog_field(), 1);
+    }
+    assert_eq!(o.inner_both.prog_field, 7);
+    let view = OuterRef::<AuthorityRole>::from_target(&o);
+    assert_eq!(*view.inner_both().auth_field(), 2);
+}
+
+#[test]
+fn views_borrow_envelope_aux_in_place() {
+    use c_u_later::{AuxView, AuxViewMut, ProgramRole};
+    use c_u_soon::Envelope;
+
+    let mut envelope = Envelope::zeroed();
+    assert!(SimpleRef::<ProgramRole>::from_envelope(&envelope).is_none());
+
+    envelope.auxiliary_metadata = Simple::METADATA;
+    *SimpleMut::<ProgramRole>::from_envelope_mut(&mut envelope)
+        .unwrap()
+        .program_only_mut() = 9;
+    assert_eq!(envelope.auxiliary_data[6], 9);
+    let view = SimpleRef::<ProgramRole>::from_envelope(&envelope).unwrap();
+    assert_eq!(*view.program_only(), 9);
+}
+
 #[test]
 fn wrapper_embed_returns_mut_ref() {
     #[derive(Pod, Zeroable, TypeHash, Copy, Clone)]
@@ -973,7 +1074,6 @@ fn wrapper_padding_has_no_accessor() {
     struct WithPad {
         #[authority]
         val: u8,
-        #[authority]
         _pad: [u8; 7],
     }
 
@@ -989,6 +1089,43 @@ fn wrapper_padding_has_no_accessor() {
     assert_eq!(w.val, 42);
 }
 
+#[test]
+fn readonly_and_padding_fields_are_blocked() {
+    #[derive(Clone, Copy, Pod, Zeroable, TypeHash, CuLater, Debug)]
+    #[repr(C)]
+    struct Marked {
+        #[readonly]
+        version: u8,
+        #[program]
+        #[authority]
+        val: u8,
+        _pad: [u8; 2],
+    }
+
+    assert_eq!(Marked::program_mask(), [false, true, false, false]);
+    assert_eq!(Marked::authority_mask(), [false, true, false, false]);
+}
+
+#[test]
+fn nested_manual_type_hash_field_passes_layout_checks() {
+    // PriceValue implements TypeHash by hand; the derive's const assertions check its
+    // metadata size and offset against the declaration order.
+    #[derive(Clone, Copy, Pod, Zeroable, TypeHash, CuLater)]
+    #[repr(C)]
+    struct Quote {
+        venue: u32,
+        #[program]
+        price: c_u_soon::PriceValue,
+        #[authority]
+        spread_bps: u32,
+    }
+
+    let program_mask = Quote::program_mask();
+    assert_eq!(program_mask.iter().filter(|&&w| w).count(), 20);
+    assert!(program_mask[4..24].iter().all(|&w| w));
+    assert!(Quote::authority_mask()[24..28].iter().all(|&w| w));
+}
+
 #[test]
 fn wrapper_mutation_persists() {
     let mut s = Simple {
@@ -1038,3 +1175,35 @@ fn wrapper_qualified_path_nested() {
     *inner_w.prog_mut() = 42;
     assert_eq!(s.inner.prog, 42);
 }
+
+#[derive(Clone, Copy, Pod, Zeroable, TypeHash, CuLater)]
+#[repr(C)]
+struct Counters {
+    #[program]
+    #[sequenced]
+    fills: u64,
+    #[program]
+    price: u64,
+    #[authority]
+    #[sequenced]
+    epoch: c_u_soon::U64Le,
+}
+
+#[test]
+fn sequenced_fields_become_constraints() {
+    use c_u_soon::{Constraint, RHS_PREVIOUS};
+
+    assert_eq!(Counters::sequenced_offsets(), [0, 16]);
+    assert!(Simple::sequenced_offsets().is_empty());
+    assert!(u64::sequenced_offsets().is_empty());
+    assert_eq!(
+        Counters::program_mask(),
+        [vec![true; 16], vec![false; 8]].concat(),
+        "#[sequenced] leaves the masks alone"
+    );
+
+    let specs = c_u_later::sequenced_constraints::<Counters>();
+    assert_eq!(specs.len(), 2);
+    assert!(specs.iter().all(|s| s.rhs_kind == RHS_PREVIOUS));
+    assert_eq!(Constraint::from(specs[1]), Constraint::sequenced(16));
+}
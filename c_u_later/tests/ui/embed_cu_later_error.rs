@@ -0,0 +1,23 @@
+// #[embed] on a type that implements CuLater would mark all its bytes writable and
+// discard the masks it declares, so it must not compile.
+
+use bytemuck::{Pod, Zeroable};
+use c_u_later::CuLater;
+use c_u_soon::TypeHash;
+
+#[derive(Pod, Zeroable, Copy, Clone, TypeHash, CuLater)]
+#[repr(C)]
+struct Inner {
+    #[program]
+    x: u8,
+}
+
+#[derive(Pod, Zeroable, Copy, Clone, TypeHash, CuLater)]
+#[repr(C)]
+struct Outer {
+    #[program]
+    #[embed]
+    inner: Inner,
+}
+
+fn main() {}
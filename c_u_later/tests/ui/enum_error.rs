@@ -0,0 +1,12 @@
+// An enum has no fixed set of field offsets to build masks from.
+
+use c_u_later::CuLater;
+
+#[derive(CuLater)]
+#[repr(C)]
+enum Mode {
+    Idle,
+    Active,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+// The generated masks, wrappers, and views describe one concrete layout, so a generic
+// struct must be rejected rather than producing impls that do not compile.
+
+use c_u_later::CuLater;
+
+#[derive(CuLater)]
+#[repr(C)]
+struct Slot<T> {
+    #[program]
+    val: T,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// Masks are byte offsets into the struct, so its layout must be pinned with #[repr(C)].
+
+use c_u_later::CuLater;
+
+#[derive(CuLater)]
+struct NoRepr {
+    #[program]
+    val: u32,
+}
+
+fn main() {}
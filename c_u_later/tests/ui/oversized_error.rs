@@ -0,0 +1,14 @@
+// Auxiliary data holds at most 255 bytes; a larger struct must fail to compile.
+
+use bytemuck::{Pod, Zeroable};
+use c_u_later::CuLater;
+
+#[derive(Pod, Zeroable, Copy, Clone, CuLater)]
+#[repr(C)]
+struct Oversized {
+    #[program]
+    val: u8,
+    data: [u8; 255],
+}
+
+fn main() {}
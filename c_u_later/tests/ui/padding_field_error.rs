@@ -0,0 +1,17 @@
+// A `_`-prefixed field is padding. Marking it writable must fail instead of silently
+// leaving it blocked.
+
+use bytemuck::{Pod, Zeroable};
+use c_u_later::CuLater;
+use c_u_soon::TypeHash;
+
+#[derive(Pod, Zeroable, Copy, Clone, TypeHash, CuLater)]
+#[repr(C)]
+struct AnnotatedPadding {
+    #[program]
+    val: u8,
+    #[authority]
+    _pad: [u8; 3],
+}
+
+fn main() {}
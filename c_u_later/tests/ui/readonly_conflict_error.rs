@@ -0,0 +1,15 @@
+// #[readonly] next to #[program] or #[authority] contradicts itself and must not compile.
+
+use bytemuck::{Pod, Zeroable};
+use c_u_later::CuLater;
+use c_u_soon::TypeHash;
+
+#[derive(Pod, Zeroable, Copy, Clone, TypeHash, CuLater)]
+#[repr(C)]
+struct Conflicting {
+    #[readonly]
+    #[program]
+    val: u32,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+// A `_`-prefixed field is padding. Marking it sequenced must fail instead of silently
+// guarding bytes nobody writes.
+
+use bytemuck::{Pod, Zeroable};
+use c_u_later::CuLater;
+use c_u_soon::TypeHash;
+
+#[derive(Pod, Zeroable, Copy, Clone, TypeHash, CuLater)]
+#[repr(C)]
+struct SequencedPadding {
+    #[program]
+    val: u64,
+    #[sequenced]
+    _counter: u64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+// Wrappers and views name their accessors after fields, so tuple structs are rejected.
+
+use c_u_later::CuLater;
+
+#[derive(CuLater)]
+#[repr(C)]
+struct Pair(u32, u32);
+
+fn main() {}
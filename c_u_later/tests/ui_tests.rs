@@ -1,5 +1,14 @@
 #[test]
 fn ui() {
     let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/embed_cu_later_error.rs");
+    t.compile_fail("tests/ui/enum_error.rs");
     t.compile_fail("tests/ui/generic_field_error.rs");
+    t.compile_fail("tests/ui/generic_struct_error.rs");
+    t.compile_fail("tests/ui/missing_repr_c_error.rs");
+    t.compile_fail("tests/ui/oversized_error.rs");
+    t.compile_fail("tests/ui/padding_field_error.rs");
+    t.compile_fail("tests/ui/readonly_conflict_error.rs");
+    t.compile_fail("tests/ui/sequenced_padding_error.rs");
+    t.compile_fail("tests/ui/tuple_struct_error.rs");
 }
@@ -3,9 +3,10 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields};
+use syn::{parse_macro_input, Attribute, Data, DataEnum, DeriveInput, Expr, Fields, Lit, UnOp};
 
-/// Derives [`c_u_soon::TypeHash`] for a `#[repr(C)]` struct with named fields.
+/// Derives [`c_u_soon::TypeHash`] for a `#[repr(C)]` struct with named fields, or for an enum
+/// with a `#[repr(C)]` or integer `repr`.
 ///
 /// # Generated code
 ///
@@ -27,17 +28,65 @@ use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields};
 /// Both the struct name and field order affect the hash. Renaming or reordering fields
 /// changes the identity and will cause any stored oracle metadata to be rejected.
 ///
+/// Enums fold in their `repr` and, per variant, the variant name, discriminant, and payload
+/// field types:
+///
+/// ```text
+/// hash = fnv1a("MyEnum")
+/// hash = combine_hash(hash, fnv1a(repr))      // "C", "u8", "C,u8", ...
+/// for each variant in declaration order:
+///     hash = combine_hash(hash, fnv1a("Variant"))
+///     hash = combine_hash(hash, discriminant as u64)
+///     for each payload field: hash = combine_hash(hash, FieldType::TYPE_HASH)
+/// ```
+///
+/// Renaming, reordering, or renumbering variants, changing a payload type, or changing the
+/// `repr` changes the identity. Payload field names do not, as for struct fields.
+///
+/// # Generic types
+///
+/// Type and const parameters are folded in after the name (after the `repr` for enums), in
+/// declaration order: a type parameter's `TYPE_HASH`, a const parameter's value as `u64`.
+/// Each instantiation is its own identity, so `Wrapper<u32, 4>`, `Wrapper<u64, 4>`, and
+/// `Wrapper<u32, 8>` have different metadata. Every type parameter gets a `TypeHash` bound
+/// on the impl. Lifetime parameters are rejected. A non-generic type hashes exactly as
+/// before.
+///
+/// ```rust,ignore
+/// #[derive(Clone, Copy, TypeHash)]
+/// #[repr(C)]
+/// struct Window<T, const N: usize> {
+///     samples: [T; N],
+///     head: u32,
+/// }
+/// // hash = combine_hash(combine_hash(combine_hash(fnv1a("Window"), T::TYPE_HASH), N as u64),
+/// //                     <[T; N]>::TYPE_HASH), then u32::TYPE_HASH
+/// ```
+///
+/// # Alternative scheme
+///
+/// `#[type_hash(xxh64)]` replaces `fnv1a` with `const_xxh64` and `combine_hash` with
+/// `combine_hash_xxh64`, and packs with `StructMetadata::new_xxh64` (sets the xxh64 metadata
+/// bit). `#[type_hash(fnv1a)]` spells out the default.
+///
 /// # Requirements
 ///
-/// - `#[repr(C)]` is required for deterministic field layout.
-/// - Only named-field structs are supported (no tuple structs, no enums).
-/// - `size_of::<Self>()` must be ≤ 255; the derive emits a compile-time assertion.
+/// - `#[repr(C)]` is required for deterministic field layout. Enums may use `#[repr(C)]`,
+///   an integer `repr` such as `#[repr(u8)]`, or both.
+/// - Structs must have named fields (no tuple or unit structs). Enums need at least one
+///   variant, and explicit discriminants must be integer literals.
+/// - Generic parameters must be types or consts, not lifetimes.
+/// - Enums are not `Pod`: read them with `Envelope::oracle_validated` /
+///   `Envelope::aux_validated`, which require `bytemuck::CheckedBitPattern`.
+/// - `size_of::<Self>()` must be ≤ 255; the derive emits a compile-time assertion. For a
+///   non-generic type it fails at the definition; for a generic one, at the first use of an
+///   oversized instantiation's `METADATA`.
 /// - Each field type must implement `TypeHash`.
 ///
 /// # Example
 ///
 /// ```rust,ignore
-/// use bytemuck::{Pod, Zeroable};
+/// use bytemuck::{CheckedBitPattern, NoUninit, Pod, Zeroable};
 /// use c_u_soon::TypeHash;
 ///
 /// #[derive(Clone, Copy, Pod, Zeroable, TypeHash)]
@@ -46,8 +95,16 @@ use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields};
 ///     x: f32,
 ///     y: f32,
 /// }
+///
+/// #[derive(Clone, Copy, CheckedBitPattern, NoUninit, TypeHash)]
+/// #[repr(u8)]
+/// enum Status {
+///     Trading,
+///     Halted,
+///     Auction = 4,
+/// }
 /// ```
-#[proc_macro_derive(TypeHash)]
+#[proc_macro_derive(TypeHash, attributes(type_hash))]
 pub fn derive_type_hash(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     match derive_type_hash_impl(input) {
@@ -58,6 +115,19 @@ pub fn derive_type_hash(input: TokenStream) -> TokenStream {
 
 fn derive_type_hash_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
     let name = &input.ident;
+    let scheme = hash_scheme(&input.attrs)?;
+
+    if let Some(lifetime) = input.generics.lifetimes().next() {
+        return Err(syn::Error::new_spanned(
+            lifetime,
+            "TypeHash does not support lifetime parameters",
+        ));
+    }
+
+    if let Data::Enum(data) = &input.data {
+        let hash_expr = enum_hash_expr(&input, data, &scheme)?;
+        return Ok(type_hash_impl(&input, hash_expr, &scheme));
+    }
 
     if !has_repr_c(&input.attrs) {
         return Err(syn::Error::new(
@@ -79,41 +149,282 @@ fn derive_type_hash_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
         _ => {
             return Err(syn::Error::new(
                 input.ident.span(),
-                "TypeHash only supports structs",
+                "TypeHash only supports structs and enums",
             ))
         }
     };
 
-    let mut hash_expr: TokenStream2 =
-        quote! { ::c_u_soon::const_fnv1a(stringify!(#name).as_bytes()) };
+    let seed_fn = scheme.seed_fn();
+    let combine_fn = scheme.combine_fn();
+    let mut hash_expr = generic_params_hash(
+        quote! { ::c_u_soon::#seed_fn(stringify!(#name).as_bytes()) },
+        &input.generics,
+        &scheme,
+    );
 
     for field in fields.iter() {
         let field_ty = &field.ty;
         hash_expr = quote! {
-            ::c_u_soon::combine_hash(
+            ::c_u_soon::#combine_fn(
                 #hash_expr,
                 <#field_ty as ::c_u_soon::TypeHash>::TYPE_HASH,
             )
         };
     }
 
-    let expanded = quote! {
-        impl ::c_u_soon::TypeHash for #name {
+    Ok(type_hash_impl(&input, hash_expr, &scheme))
+}
+
+/// The impl for `input`. Every type parameter gets a `TypeHash` bound. A non-generic type
+/// has its size checked where it is defined; a generic one can only be checked per
+/// instantiation, so the assertion moves into `METADATA`.
+fn type_hash_impl(
+    input: &DeriveInput,
+    hash_expr: TokenStream2,
+    scheme: &HashScheme,
+) -> TokenStream2 {
+    let name = &input.ident;
+    let metadata_fn = scheme.metadata_fn();
+
+    let mut generics = input.generics.clone();
+    let type_params: Vec<syn::Ident> = generics.type_params().map(|p| p.ident.clone()).collect();
+    for param in &type_params {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote! { #param: ::c_u_soon::TypeHash });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let (definition_check, metadata_check) = if input.generics.params.is_empty() {
+        let check = quote! {
+            const _: () = assert!(
+                ::core::mem::size_of::<#name>() <= 255,
+                "TypeHash: struct size exceeds u8 max"
+            );
+        };
+        (check, quote! {})
+    } else {
+        let check = quote! {
+            assert!(
+                ::core::mem::size_of::<Self>() <= 255,
+                "TypeHash: struct size exceeds u8 max"
+            );
+        };
+        (quote! {}, check)
+    };
+
+    quote! {
+        #definition_check
+
+        impl #impl_generics ::c_u_soon::TypeHash for #name #ty_generics #where_clause {
             const TYPE_HASH: u64 = #hash_expr;
             const METADATA: ::c_u_soon::StructMetadata = {
-                assert!(
-                    ::core::mem::size_of::<Self>() <= 255,
-                    "TypeHash: struct size exceeds u8 max"
-                );
-                ::c_u_soon::StructMetadata::new(
+                #metadata_check
+                ::c_u_soon::StructMetadata::#metadata_fn(
                     ::core::mem::size_of::<Self>() as u8,
                     Self::TYPE_HASH,
                 )
             };
         }
-    };
+    }
+}
+
+/// Folds each generic parameter into `hash_expr` in declaration order: a type parameter's
+/// `TYPE_HASH`, or a const parameter's value as `u64`. Evaluated per instantiation, so
+/// `Wrapper<u32, 4>` and `Wrapper<u64, 4>` hash differently even if a parameter appears in
+/// no field. Returns `hash_expr` unchanged for a non-generic type.
+fn generic_params_hash(
+    mut hash_expr: TokenStream2,
+    generics: &syn::Generics,
+    scheme: &HashScheme,
+) -> TokenStream2 {
+    let combine_fn = scheme.combine_fn();
+    for param in &generics.params {
+        let value = match param {
+            syn::GenericParam::Type(ty) => {
+                let ident = &ty.ident;
+                quote! { <#ident as ::c_u_soon::TypeHash>::TYPE_HASH }
+            }
+            syn::GenericParam::Const(c) => {
+                let ident = &c.ident;
+                quote! { #ident as u64 }
+            }
+            syn::GenericParam::Lifetime(_) => continue,
+        };
+        hash_expr = quote! { ::c_u_soon::#combine_fn(#hash_expr, #value) };
+    }
+    hash_expr
+}
+
+/// Hash expression for an enum: name, `repr`, then every variant's name, discriminant, and
+/// payload field hashes in declaration order.
+fn enum_hash_expr(
+    input: &DeriveInput,
+    data: &DataEnum,
+    scheme: &HashScheme,
+) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let repr = enum_repr(&input.attrs).ok_or_else(|| {
+        syn::Error::new(
+            name.span(),
+            "TypeHash requires #[repr(C)] or an integer repr such as #[repr(u8)] on enums",
+        )
+    })?;
+    if data.variants.is_empty() {
+        return Err(syn::Error::new(
+            name.span(),
+            "TypeHash requires at least one enum variant",
+        ));
+    }
+
+    let seed_fn = scheme.seed_fn();
+    let combine_fn = scheme.combine_fn();
+    let mut hash_expr = generic_params_hash(
+        quote! {
+            ::c_u_soon::#combine_fn(
+                ::c_u_soon::#seed_fn(stringify!(#name).as_bytes()),
+                ::c_u_soon::#seed_fn(#repr.as_bytes()),
+            )
+        },
+        &input.generics,
+        scheme,
+    );
+
+    let mut discriminant: i128 = 0;
+    for variant in &data.variants {
+        if let Some((_, expr)) = &variant.discriminant {
+            discriminant = int_literal(expr).ok_or_else(|| {
+                syn::Error::new_spanned(
+                    expr,
+                    "TypeHash requires enum discriminants to be integer literals",
+                )
+            })?;
+        }
+        let variant_name = variant.ident.to_string();
+        let value = discriminant as u64;
+        hash_expr = quote! {
+            ::c_u_soon::#combine_fn(
+                ::c_u_soon::#combine_fn(#hash_expr, ::c_u_soon::#seed_fn(#variant_name.as_bytes())),
+                #value,
+            )
+        };
+        for field in variant.fields.iter() {
+            let field_ty = &field.ty;
+            hash_expr = quote! {
+                ::c_u_soon::#combine_fn(
+                    #hash_expr,
+                    <#field_ty as ::c_u_soon::TypeHash>::TYPE_HASH,
+                )
+            };
+        }
+        discriminant += 1;
+    }
+
+    Ok(hash_expr)
+}
+
+/// Value of an integer literal, optionally negated.
+fn int_literal(expr: &Expr) -> Option<i128> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(int) => int.base10_parse().ok(),
+            _ => None,
+        },
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => {
+            int_literal(&unary.expr).map(|value| -value)
+        }
+        Expr::Group(group) => int_literal(&group.expr),
+        Expr::Paren(paren) => int_literal(&paren.expr),
+        _ => None,
+    }
+}
+
+enum HashScheme {
+    Fnv1a,
+    Xxh64,
+}
+
+impl HashScheme {
+    fn seed_fn(&self) -> TokenStream2 {
+        match self {
+            HashScheme::Fnv1a => quote! { const_fnv1a },
+            HashScheme::Xxh64 => quote! { const_xxh64 },
+        }
+    }
 
-    Ok(expanded)
+    fn combine_fn(&self) -> TokenStream2 {
+        match self {
+            HashScheme::Fnv1a => quote! { combine_hash },
+            HashScheme::Xxh64 => quote! { combine_hash_xxh64 },
+        }
+    }
+
+    fn metadata_fn(&self) -> TokenStream2 {
+        match self {
+            HashScheme::Fnv1a => quote! { new },
+            HashScheme::Xxh64 => quote! { new_xxh64 },
+        }
+    }
+}
+
+/// Reads `#[type_hash(fnv1a | xxh64)]`. Absent means [`HashScheme::Fnv1a`].
+fn hash_scheme(attrs: &[Attribute]) -> syn::Result<HashScheme> {
+    let mut scheme = None;
+    for attr in attrs {
+        if !attr.path().is_ident("type_hash") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if scheme.is_some() {
+                return Err(meta.error("hash scheme specified more than once"));
+            }
+            if meta.path.is_ident("fnv1a") {
+                scheme = Some(HashScheme::Fnv1a);
+            } else if meta.path.is_ident("xxh64") {
+                scheme = Some(HashScheme::Xxh64);
+            } else {
+                return Err(meta.error("expected `fnv1a` or `xxh64`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(scheme.unwrap_or(HashScheme::Fnv1a))
+}
+
+const INT_REPRS: &[&str] = &["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"];
+
+/// Canonical `repr` of an enum: `"C"`, an integer type, or `"C,<int>"`. `None` if the enum
+/// has neither.
+fn enum_repr(attrs: &[Attribute]) -> Option<String> {
+    let mut c = false;
+    let mut int = None;
+    for attr in attrs {
+        if !attr.path().is_ident("repr") {
+            continue;
+        }
+        let Ok(nested) = attr.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+        ) else {
+            continue;
+        };
+        for meta in &nested {
+            let syn::Meta::Path(path) = meta else {
+                continue;
+            };
+            if path.is_ident("C") {
+                c = true;
+            } else if let Some(ty) = INT_REPRS.iter().find(|ty| path.is_ident(ty)) {
+                int = Some(*ty);
+            }
+        }
+    }
+    match (c, int) {
+        (true, Some(ty)) => Some(format!("C,{ty}")),
+        (true, None) => Some("C".to_string()),
+        (false, Some(ty)) => Some(ty.to_string()),
+        (false, None) => None,
+    }
 }
 
 fn has_repr_c(attrs: &[Attribute]) -> bool {
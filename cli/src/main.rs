@@ -0,0 +1,474 @@
+//! Envelope administration from the command line: `c_u_soon-cli <COMMAND> [OPTIONS]`.
+//!
+//! - `create [--seed S].. [--oracle-metadata M]`: create the payer's envelope.
+//! - `inspect <ENVELOPE> [--schema FILE]`: print an envelope, its masks, and its aux data.
+//! - `update-aux <ENVELOPE> (--data HEX | --schema FILE --set FIELD=VALUE..)`: write aux
+//!   data as the authority.
+//! - `set-delegation <ENVELOPE> --delegate-keypair PATH (--schema FILE | [--program-writable
+//!   RANGES] [--user-writable RANGES])`: delegate aux writes.
+//! - `clear-delegation <ENVELOPE> --delegate-keypair PATH`: end the delegation.
+//! - `close <ENVELOPE> [--recipient ADDRESS]`: close the envelope and reclaim its rent.
+//! - `watch <ENVELOPE> [--schema FILE] [--interval-ms N]`: print every change.
+//!
+//! Every command takes `--url` (default `http://127.0.0.1:8899`), `--program-id` (or the
+//! `C_U_SOON_PROGRAM_ID` environment variable), and, if it signs, `--keypair` (default
+//! `~/.config/solana/id.json`), which is both the envelope authority and the fee payer.
+//! Delegates must be keypairs: an envelope delegated to a program PDA is administered from
+//! that program.
+//!
+//! Schema files describe the aux layout for typed decoding; see [`schema`].
+
+mod mask;
+mod schema;
+mod watch;
+
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Duration;
+
+use c_u_soon::{Envelope, StructMetadata};
+use c_u_soon_client::{
+    build_clear_delegation_ix, build_close_ix, build_create_ix, build_set_delegated_program_ix,
+    build_update_auxiliary_ix, find_envelope_address, DecodedEnvelope, EnvelopeClient,
+};
+use solana_address::Address;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+use schema::{hex, parse_hex, parse_u64, AuxSchema};
+
+const USAGE: &str = "usage: c_u_soon-cli <create|inspect|update-aux|set-delegation|\
+clear-delegation|close|watch> [ENVELOPE] [--url URL] [--program-id ID] [--keypair PATH] ..";
+
+const DEFAULT_URL: &str = "http://127.0.0.1:8899";
+
+/// Third `UpdateAuxiliary` account when `--constraint-table` is not given.
+const SYSTEM_PROGRAM_ID: Address = Address::new_from_array([0; 32]);
+
+/// Positional arguments and `--name value` options, in order.
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                positional.push(arg);
+                continue;
+            };
+            let (name, value) = match name.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("--{name} needs a value"))?;
+                    (name.to_string(), value)
+                }
+            };
+            options.push((name, value));
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    /// Last value of `--name`.
+    fn get(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every value of `--name`, in order.
+    fn all(&self, name: &str) -> Vec<String> {
+        self.options
+            .iter()
+            .filter(|(n, _)| n == name)
+            .map(|(_, v)| v.clone())
+            .collect()
+    }
+
+    fn require(&self, name: &str) -> Result<&str, String> {
+        self.get(name).ok_or_else(|| format!("missing --{name}"))
+    }
+
+    fn envelope(&self) -> Result<Address, String> {
+        let arg = self.positional.get(1).ok_or("missing ENVELOPE address")?;
+        address(arg)
+    }
+
+    fn rpc(&self) -> RpcClient {
+        RpcClient::new(self.get("url").unwrap_or(DEFAULT_URL).to_string())
+    }
+
+    fn program_id(&self) -> Result<Address, String> {
+        match self.get("program-id") {
+            Some(id) => address(id),
+            None => address(
+                &std::env::var("C_U_SOON_PROGRAM_ID")
+                    .map_err(|_| "missing --program-id or C_U_SOON_PROGRAM_ID")?,
+            ),
+        }
+    }
+
+    fn keypair(&self) -> Result<Keypair, String> {
+        match self.get("keypair") {
+            Some(path) => keypair(path),
+            None => {
+                let home = std::env::var("HOME").map_err(|_| "missing --keypair and $HOME")?;
+                keypair(&format!("{home}/.config/solana/id.json"))
+            }
+        }
+    }
+
+    fn schema(&self) -> Result<Option<AuxSchema>, String> {
+        self.get("schema").map(AuxSchema::load).transpose()
+    }
+}
+
+fn address(s: &str) -> Result<Address, String> {
+    Address::from_str(s).map_err(|_| format!("invalid address: {s}"))
+}
+
+fn keypair(path: &str) -> Result<Keypair, String> {
+    read_keypair_file(path).map_err(|e| format!("{path}: {e}"))
+}
+
+fn fetch(args: &Args, rpc: &RpcClient, envelope: &Address) -> Result<DecodedEnvelope, String> {
+    EnvelopeClient::new(args.program_id()?)
+        .fetch(rpc, envelope)
+        .map_err(|e| format!("{envelope}: {e}"))
+}
+
+/// Sign `ix` with `signers`, the first paying the fee, and wait for confirmation.
+fn send(rpc: &RpcClient, ix: Instruction, signers: &[&Keypair]) -> Result<Signature, String> {
+    let blockhash = rpc.get_latest_blockhash().map_err(|e| e.to_string())?;
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&signers[0].pubkey()), signers, blockhash);
+    rpc.send_and_confirm_transaction(&tx)
+        .map_err(|e| e.to_string())
+}
+
+fn create(args: &Args) -> Result<(), String> {
+    let (rpc, program_id, payer) = (args.rpc(), args.program_id()?, args.keypair()?);
+    let seeds = args.all("seed");
+    let seeds: Vec<&[u8]> = seeds.iter().map(|s| s.as_bytes()).collect();
+    let metadata = match args.get("oracle-metadata") {
+        Some(m) => StructMetadata::from_raw(
+            parse_u64(m).ok_or_else(|| format!("oracle metadata `{m}` is not a u64"))?,
+        ),
+        None => StructMetadata::ZERO,
+    };
+    let (envelope, bump) = find_envelope_address(&program_id, &payer.pubkey(), &seeds);
+    let ix = build_create_ix(&program_id, &payer.pubkey(), &seeds, bump, metadata)
+        .map_err(|e| e.to_string())?;
+    let signature = send(&rpc, ix, &[&payer])?;
+    println!("envelope   {envelope}");
+    println!("signature  {signature}");
+    Ok(())
+}
+
+fn print_envelope(decoded: &DecodedEnvelope, schema: Option<&AuxSchema>) {
+    let envelope = decoded.envelope();
+    let oracle = &envelope.oracle_state;
+    println!("envelope          {}", decoded.address);
+    println!("slot              {}", decoded.context_slot());
+    println!("authority         {}", envelope.authority);
+    match decoded.publisher() {
+        Some(publisher) => println!("publisher         {publisher}"),
+        None => println!("publisher         (none)"),
+    }
+    if envelope.is_paused() {
+        println!("paused            yes");
+    }
+    if envelope.emits_events() {
+        println!("events            yes");
+    }
+    println!(
+        "oracle metadata   0x{:016x}",
+        oracle.oracle_metadata.as_u64()
+    );
+    println!("oracle sequence   {}", oracle.sequence);
+    if let Some(slot) = envelope.last_heartbeat_slot() {
+        println!("heartbeat slot    {slot}");
+    }
+    println!("oracle data       0x{}", hex(watch::oracle_bytes(envelope)));
+    println!(
+        "aux metadata      0x{:016x}",
+        envelope.auxiliary_metadata.as_u64()
+    );
+    println!("authority aux seq {}", envelope.authority_aux_sequence);
+    println!("program aux seq   {}", envelope.program_aux_sequence);
+    match decoded.delegation_authority() {
+        Some(delegate) => println!("delegated to      {delegate}"),
+        None => println!("delegated to      (none)"),
+    }
+    if let Some(hook) = envelope.write_hook() {
+        println!("write hook        {hook}");
+    }
+
+    match schema {
+        Some(schema) => {
+            if schema
+                .metadata
+                .is_some_and(|m| m != envelope.auxiliary_metadata)
+            {
+                println!(
+                    "warning: aux metadata does not match schema {}",
+                    schema.name
+                );
+            }
+            println!("\naux ({})", schema.name);
+            let width = schema
+                .fields
+                .iter()
+                .map(|f| f.name.len())
+                .max()
+                .unwrap_or(0);
+            for (name, value) in schema.decode(&envelope.auxiliary_data) {
+                println!("  {name:<width$}  {value}");
+            }
+        }
+        None => println!("aux data          0x{}", hex(watch::aux_bytes(envelope))),
+    }
+
+    if envelope.has_delegation() {
+        println!(
+            "\nprogram mask\n{}",
+            mask::render(&envelope.program_bitmask)
+        );
+        println!("user mask\n{}", mask::render(&envelope.user_bitmask));
+        if let Some(schema) = schema {
+            let lines =
+                mask::field_access(schema, &envelope.program_bitmask, &envelope.user_bitmask);
+            println!("field access (! differs from schema)");
+            for line in lines {
+                println!("  {line}");
+            }
+        }
+    }
+}
+
+fn inspect(args: &Args) -> Result<(), String> {
+    let decoded = fetch(args, &args.rpc(), &args.envelope()?)?;
+    print_envelope(&decoded, args.schema()?.as_ref());
+    Ok(())
+}
+
+/// Aux metadata and data for `update-aux`, starting from the current aux bytes of `current`.
+fn aux_update(args: &Args, current: &Envelope) -> Result<(StructMetadata, Vec<u8>), String> {
+    let metadata = match args.get("metadata") {
+        Some(m) => Some(StructMetadata::from_raw(
+            parse_u64(m).ok_or_else(|| format!("metadata `{m}` is not a u64"))?,
+        )),
+        None => None,
+    };
+    if let Some(data) = args.get("data") {
+        let data = parse_hex(data.strip_prefix("0x").unwrap_or(data))
+            .ok_or_else(|| format!("`{data}` is not hex"))?;
+        return Ok((metadata.unwrap_or(current.auxiliary_metadata), data));
+    }
+
+    let schema = args
+        .schema()?
+        .ok_or("update-aux needs --data or --schema")?;
+    let metadata = metadata
+        .or(schema.metadata)
+        .unwrap_or(current.auxiliary_metadata);
+    let mut aux = current.auxiliary_data;
+    schema.apply(&mut aux, &args.all("set"))?;
+    Ok((metadata, aux[..schema.size()].to_vec()))
+}
+
+fn update_aux(args: &Args) -> Result<(), String> {
+    let (rpc, program_id, payer) = (args.rpc(), args.program_id()?, args.keypair()?);
+    let address = args.envelope()?;
+    let current = *fetch(args, &rpc, &address)?.envelope();
+    if !current.has_delegation() {
+        return Err("UpdateAuxiliary requires an active delegation".to_string());
+    }
+
+    let (metadata, data) = aux_update(args, &current)?;
+    if metadata != current.auxiliary_metadata {
+        return Err(format!(
+            "aux metadata 0x{:016x} does not match the envelope's 0x{:016x}",
+            metadata.as_u64(),
+            current.auxiliary_metadata.as_u64()
+        ));
+    }
+    let blocked: Vec<String> = data
+        .iter()
+        .zip(&current.auxiliary_data)
+        .enumerate()
+        .filter(|&(i, (new, old))| new != old && !current.user_bitmask.is_writable(i))
+        .map(|(i, _)| i.to_string())
+        .collect();
+    if !blocked.is_empty() {
+        return Err(format!(
+            "user mask blocks changed bytes {}",
+            blocked.join(", ")
+        ));
+    }
+
+    let sequence = current
+        .authority_aux_sequence
+        .checked_add(1)
+        .ok_or("authority aux sequence overflow")?;
+    let third = match args.get("constraint-table") {
+        Some(table) => address(table)?,
+        None => SYSTEM_PROGRAM_ID,
+    };
+    let ix = build_update_auxiliary_ix(
+        &program_id,
+        &payer.pubkey(),
+        &address,
+        &third,
+        metadata.as_u64(),
+        sequence,
+        &data,
+    );
+    println!("signature  {}", send(&rpc, ix, &[&payer])?);
+    Ok(())
+}
+
+fn set_delegation(args: &Args) -> Result<(), String> {
+    let (rpc, program_id, payer) = (args.rpc(), args.program_id()?, args.keypair()?);
+    let address = args.envelope()?;
+    let delegate = keypair(args.require("delegate-keypair")?)?;
+    let (program_mask, user_mask) = match args.schema()? {
+        Some(schema) => schema.masks(),
+        None => (
+            mask::parse_ranges(args.get("program-writable").unwrap_or(""))?,
+            mask::parse_ranges(args.get("user-writable").unwrap_or(""))?,
+        ),
+    };
+    println!("program mask\n{}", mask::render(&program_mask));
+    println!("user mask\n{}", mask::render(&user_mask));
+
+    let ix = build_set_delegated_program_ix(
+        &program_id,
+        &payer.pubkey(),
+        &address,
+        &delegate.pubkey(),
+        program_mask,
+        user_mask,
+    )
+    .map_err(|e| e.to_string())?;
+    println!("signature  {}", send(&rpc, ix, &[&payer, &delegate])?);
+    Ok(())
+}
+
+fn clear_delegation(args: &Args) -> Result<(), String> {
+    let (rpc, program_id, payer) = (args.rpc(), args.program_id()?, args.keypair()?);
+    let delegate = keypair(args.require("delegate-keypair")?)?;
+    let ix = build_clear_delegation_ix(
+        &program_id,
+        &payer.pubkey(),
+        &args.envelope()?,
+        &delegate.pubkey(),
+    )
+    .map_err(|e| e.to_string())?;
+    println!("signature  {}", send(&rpc, ix, &[&payer, &delegate])?);
+    Ok(())
+}
+
+fn close(args: &Args) -> Result<(), String> {
+    let (rpc, program_id, payer) = (args.rpc(), args.program_id()?, args.keypair()?);
+    let recipient = match args.get("recipient") {
+        Some(recipient) => address(recipient)?,
+        None => payer.pubkey(),
+    };
+    let ix = build_close_ix(&program_id, &payer.pubkey(), &args.envelope()?, &recipient)
+        .map_err(|e| e.to_string())?;
+    println!("signature  {}", send(&rpc, ix, &[&payer])?);
+    Ok(())
+}
+
+/// Poll the envelope and print each change until interrupted. Read errors are printed and
+/// polling continues; reads older than the newest one seen are skipped.
+fn watch(args: &Args) -> Result<(), String> {
+    let rpc = args.rpc();
+    let address = args.envelope()?;
+    let schema = args.schema()?;
+    let interval = match args.get("interval-ms") {
+        Some(ms) => ms
+            .parse()
+            .map_err(|_| format!("interval `{ms}` is not a number of milliseconds"))?,
+        None => 1000,
+    };
+    let client = EnvelopeClient::new(args.program_id()?);
+
+    let mut last: Option<(u64, Envelope)> = None;
+    loop {
+        match client.fetch(&rpc, &address) {
+            Ok(decoded)
+                if last
+                    .as_ref()
+                    .is_some_and(|(slot, _)| decoded.context_slot() < *slot) => {}
+            Ok(decoded) => {
+                let prev = last.as_ref().map(|(_, envelope)| envelope);
+                for line in watch::changes(prev, decoded.envelope(), schema.as_ref()) {
+                    println!("slot {} {line}", decoded.context_slot());
+                }
+                last = Some((decoded.context_slot(), *decoded.envelope()));
+            }
+            Err(e) => eprintln!("{address}: {e}"),
+        }
+        std::thread::sleep(Duration::from_millis(interval));
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    match args.positional.first().map(String::as_str) {
+        Some("create") => create(args),
+        Some("inspect") => inspect(args),
+        Some("update-aux") => update_aux(args),
+        Some("set-delegation") => set_delegation(args),
+        Some("clear-delegation") => clear_delegation(args),
+        Some("close") => close(args),
+        Some("watch") => watch(args),
+        Some(other) => Err(format!("unknown command `{other}`\n{USAGE}")),
+        None => Err(USAGE.to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let result = Args::parse(std::env::args().skip(1)).and_then(|args| run(&args));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Args {
+        Args::parse(line.split_whitespace().map(String::from)).unwrap()
+    }
+
+    #[test]
+    fn options_and_positionals() {
+        let args = args("inspect ENV --url http://x --seed a --seed=b --url http://y");
+        assert_eq!(args.positional, ["inspect", "ENV"]);
+        assert_eq!(args.get("url"), Some("http://y"));
+        assert_eq!(args.all("seed"), ["a", "b"]);
+        assert_eq!(args.get("schema"), None);
+        assert!(args.require("keypair").is_err());
+        assert!(Args::parse(["--url".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! Text rendering of envelope masks.
+
+use c_u_soon::{Mask, Permission, MASK_SIZE, SEALED_MASK_BYTE};
+
+use crate::schema::AuxSchema;
+
+/// Bytes per rendered row.
+const ROW: usize = 32;
+
+/// One character per mask byte: `W` writable, `.` blocked, `S` sealed, `?` any other
+/// non-canonical byte (read as blocked, and rejected by `SetDelegatedProgram`). Rows of 32
+/// bytes, prefixed with their offset and split into groups of 8.
+pub fn render(mask: &Mask) -> String {
+    let mut out = String::new();
+    for (row, bytes) in mask.as_bytes().chunks(ROW).enumerate() {
+        out.push_str(&format!("{:>5} ", row * ROW));
+        for (i, &byte) in bytes.iter().enumerate() {
+            if i % 8 == 0 {
+                out.push(' ');
+            }
+            out.push(match Permission::from_byte(byte) {
+                Some(Permission::Writable) => 'W',
+                Some(Permission::Blocked) => '.',
+                None if byte == SEALED_MASK_BYTE => 'S',
+                None => '?',
+            });
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// `writable`, `blocked`, `sealed`, or `partial` for the bytes in `range`.
+fn access(mask: &Mask, range: core::ops::Range<usize>) -> &'static str {
+    if !range.is_empty() && range.clone().all(|i| mask.is_sealed(i)) {
+        return "sealed";
+    }
+    let writable = range.clone().filter(|&i| mask.is_writable(i)).count();
+    match writable {
+        0 => "blocked",
+        n if n == range.len() => "writable",
+        _ => "partial",
+    }
+}
+
+/// One line per schema field with the access each mask grants it. Fields whose access
+/// differs from their `program` or `authority` flag are marked with `!`.
+pub fn field_access(schema: &AuxSchema, program: &Mask, user: &Mask) -> Vec<String> {
+    let width = schema
+        .fields
+        .iter()
+        .map(|f| f.name.len())
+        .max()
+        .unwrap_or(0);
+    schema
+        .fields
+        .iter()
+        .map(|field| {
+            let program_access = access(program, field.range());
+            let user_access = access(user, field.range());
+            let flag = |access: &str, expected: bool| {
+                if (access == "writable") == expected {
+                    ' '
+                } else {
+                    '!'
+                }
+            };
+            format!(
+                "{:<width$}  {:>3}..{:<3}  program: {:<8}{}  user: {:<8}{}",
+                field.name,
+                field.range().start,
+                field.range().end,
+                program_access,
+                flag(program_access, field.program),
+                user_access,
+                flag(user_access, field.authority),
+            )
+            .trim_end()
+            .to_string()
+        })
+        .collect()
+}
+
+/// Mask granting the bytes of `ranges`, written `start..end` and separated by commas.
+pub fn parse_ranges(ranges: &str) -> Result<Mask, String> {
+    let mut mask = Mask::ALL_BLOCKED;
+    for range in ranges.split(',').filter(|r| !r.is_empty()) {
+        let (start, end) = range
+            .split_once("..")
+            .and_then(|(s, e)| Some((s.trim().parse::<usize>().ok()?, e.trim().parse().ok()?)))
+            .ok_or_else(|| format!("`{range}` is not START..END"))?;
+        if start >= end || end > MASK_SIZE {
+            return Err(format!("`{range}` is empty or past byte {MASK_SIZE}"));
+        }
+        mask.allow_range(start, end - start);
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_marks_each_byte() {
+        let mut mask = Mask::ALL_BLOCKED;
+        mask.allow_range(0, 4);
+        mask.allow(40);
+        mask.seal_range(44, 2);
+        mask.as_bytes_mut()[255] = 0x7F;
+
+        let rendered = render(&mask);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), MASK_SIZE / ROW);
+        assert_eq!(lines[0], "    0  WWWW.... ........ ........ ........");
+        assert_eq!(lines[1], "   32  ........ W...SS.. ........ ........");
+        assert!(lines[7].ends_with(".......?"));
+    }
+
+    #[test]
+    fn field_access_flags_mismatches() {
+        let schema = AuxSchema::parse(
+            r#"{ "fields": [
+                { "name": "price", "type": "u64", "program": true },
+                { "name": "fee", "type": "u16", "authority": true }
+            ] }"#,
+        )
+        .unwrap();
+        let (mut program, mut user) = schema.masks();
+        user.block(9);
+
+        assert_eq!(
+            field_access(&schema, &program, &user),
+            [
+                "price    0..8    program: writable   user: blocked",
+                "fee      8..10   program: blocked    user: partial !",
+            ]
+        );
+
+        program.seal_range(8, 2);
+        assert_eq!(
+            field_access(&schema, &program, &user)[1],
+            "fee      8..10   program: sealed     user: partial !"
+        );
+    }
+
+    #[test]
+    fn parse_ranges_builds_mask() {
+        let mask = parse_ranges("0..8,16..18").unwrap();
+        assert!((0..8).all(|i| mask.is_writable(i)));
+        assert!(!mask.is_writable(8));
+        assert!(mask.is_writable(17) && !mask.is_writable(18));
+        assert_eq!(parse_ranges("").unwrap(), Mask::ALL_BLOCKED);
+        assert!(parse_ranges("4..4").is_err());
+        assert!(parse_ranges("250..257").is_err());
+        assert!(parse_ranges("8").is_err());
+    }
+}
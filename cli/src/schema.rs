@@ -0,0 +1,421 @@
+//! Aux layouts read from a JSON schema file, for decoding and editing auxiliary data without
+//! the Rust type.
+//!
+//! A schema lists the fields of a `#[repr(C)]` aux struct in declaration order:
+//!
+//! ```json
+//! {
+//!   "name": "AmmState",
+//!   "metadata": "0x303c9a0e5b7f21aa",
+//!   "fields": [
+//!     { "name": "pool_price", "type": "u64", "program": true },
+//!     { "name": "fee_rate", "type": "u16", "authority": true },
+//!     { "name": "_reserved", "type": { "array": ["u8", 6] } },
+//!     { "name": "admin", "type": "pubkey" }
+//!   ]
+//! }
+//! ```
+//!
+//! Types are the IDL primitives `u8` to `u128`, `i8` to `i128`, `bool`, and `pubkey`, plus
+//! `{ "array": [<type>, <len>] }`. Fields are laid out back to back, as a `Pod` struct has no
+//! padding. `metadata` is the type's `StructMetadata` as printed by `inspect`; when present it
+//! is checked against the envelope and used for `update-aux`. `program` and `authority` mark
+//! the fields each side may write, the same as the `CuLater` attributes, and let
+//! `set-delegation` build its masks from the schema.
+
+use std::str::FromStr;
+
+use c_u_soon::{Mask, StructMetadata, AUX_DATA_SIZE};
+use serde_json::Value;
+use solana_address::Address;
+
+/// Type of one schema field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldType {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Bool,
+    Pubkey,
+    Array(Box<FieldType>, usize),
+}
+
+impl FieldType {
+    fn parse(value: &Value) -> Result<Self, String> {
+        if let Some(name) = value.as_str() {
+            return Ok(match name {
+                "u8" => Self::U8,
+                "u16" => Self::U16,
+                "u32" => Self::U32,
+                "u64" => Self::U64,
+                "u128" => Self::U128,
+                "i8" => Self::I8,
+                "i16" => Self::I16,
+                "i32" => Self::I32,
+                "i64" => Self::I64,
+                "i128" => Self::I128,
+                "bool" => Self::Bool,
+                "pubkey" => Self::Pubkey,
+                other => return Err(format!("unknown type `{other}`")),
+            });
+        }
+        match value
+            .get("array")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+        {
+            Some([elem, len]) => {
+                let len = len
+                    .as_u64()
+                    .ok_or_else(|| format!("array length must be an integer, got {len}"))?;
+                Ok(Self::Array(Box::new(Self::parse(elem)?), len as usize))
+            }
+            _ => Err(format!("unknown type {value}")),
+        }
+    }
+
+    /// Size in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::U8 | Self::I8 | Self::Bool => 1,
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 => 4,
+            Self::U64 | Self::I64 => 8,
+            Self::U128 | Self::I128 => 16,
+            Self::Pubkey => 32,
+            Self::Array(elem, len) => elem.size().saturating_mul(*len),
+        }
+    }
+
+    /// Human-readable value of `bytes`, which must be [`size`](Self::size) long. Byte arrays
+    /// print as hex.
+    pub fn format(&self, bytes: &[u8]) -> String {
+        macro_rules! int {
+            ($ty:ty) => {
+                <$ty>::from_le_bytes(bytes.try_into().unwrap()).to_string()
+            };
+        }
+        match self {
+            Self::U8 => int!(u8),
+            Self::U16 => int!(u16),
+            Self::U32 => int!(u32),
+            Self::U64 => int!(u64),
+            Self::U128 => int!(u128),
+            Self::I8 => int!(i8),
+            Self::I16 => int!(i16),
+            Self::I32 => int!(i32),
+            Self::I64 => int!(i64),
+            Self::I128 => int!(i128),
+            Self::Bool => match bytes[0] {
+                0 => "false".to_string(),
+                1 => "true".to_string(),
+                b => format!("invalid bool 0x{b:02x}"),
+            },
+            Self::Pubkey => Address::new_from_array(bytes.try_into().unwrap()).to_string(),
+            Self::Array(elem, _) if **elem == Self::U8 => format!("0x{}", hex(bytes)),
+            Self::Array(elem, _) => {
+                let items: Vec<String> = bytes
+                    .chunks_exact(elem.size())
+                    .map(|chunk| elem.format(chunk))
+                    .collect();
+                format!("[{}]", items.join(", "))
+            }
+        }
+    }
+
+    /// Little-endian bytes of `value` parsed as this type. Byte arrays take `0x`-prefixed hex;
+    /// other arrays cannot be set from the command line.
+    pub fn encode(&self, value: &str) -> Result<Vec<u8>, String> {
+        macro_rules! int {
+            ($ty:ty) => {
+                value
+                    .parse::<$ty>()
+                    .map(|v| v.to_le_bytes().to_vec())
+                    .map_err(|e| format!("`{value}` is not a {}: {e}", stringify!($ty)))
+            };
+        }
+        match self {
+            Self::U8 => int!(u8),
+            Self::U16 => int!(u16),
+            Self::U32 => int!(u32),
+            Self::U64 => int!(u64),
+            Self::U128 => int!(u128),
+            Self::I8 => int!(i8),
+            Self::I16 => int!(i16),
+            Self::I32 => int!(i32),
+            Self::I64 => int!(i64),
+            Self::I128 => int!(i128),
+            Self::Bool => match value {
+                "true" => Ok(vec![1]),
+                "false" => Ok(vec![0]),
+                _ => Err(format!("`{value}` is not a bool")),
+            },
+            Self::Pubkey => Address::from_str(value)
+                .map(|a| a.as_ref().to_vec())
+                .map_err(|_| format!("`{value}` is not a pubkey")),
+            Self::Array(elem, len) if **elem == Self::U8 => {
+                let bytes = value
+                    .strip_prefix("0x")
+                    .and_then(parse_hex)
+                    .ok_or_else(|| format!("`{value}` is not 0x-prefixed hex"))?;
+                if bytes.len() != *len {
+                    return Err(format!("expected {len} bytes, got {}", bytes.len()));
+                }
+                Ok(bytes)
+            }
+            Self::Array(..) => Err("only byte arrays can be set".to_string()),
+        }
+    }
+}
+
+/// One field of an [`AuxSchema`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub ty: FieldType,
+    /// Byte offset within the aux data.
+    pub offset: usize,
+    /// Writable by the delegated program.
+    pub program: bool,
+    /// Writable by the authority while delegated.
+    pub authority: bool,
+}
+
+impl Field {
+    /// Byte range of the field within the aux data.
+    pub fn range(&self) -> core::ops::Range<usize> {
+        self.offset..self.offset + self.ty.size()
+    }
+}
+
+/// Aux struct layout loaded from a schema file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuxSchema {
+    pub name: String,
+    pub metadata: Option<StructMetadata>,
+    pub fields: Vec<Field>,
+}
+
+impl AuxSchema {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        Self::parse(&text).map_err(|e| format!("{path}: {e}"))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let root: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let name = root["name"].as_str().unwrap_or("aux").to_string();
+        let metadata = match &root["metadata"] {
+            Value::Null => None,
+            Value::String(s) => Some(StructMetadata::from_raw(
+                parse_u64(s).ok_or_else(|| format!("metadata `{s}` is not a u64"))?,
+            )),
+            other => return Err(format!("metadata must be a string, got {other}")),
+        };
+        let entries = root["fields"]
+            .as_array()
+            .ok_or("schema has no `fields` array")?;
+
+        let mut fields = Vec::with_capacity(entries.len());
+        let mut offset = 0;
+        for entry in entries {
+            let name = entry["name"]
+                .as_str()
+                .ok_or("every field needs a `name`")?
+                .to_string();
+            let ty = FieldType::parse(&entry["type"]).map_err(|e| format!("{name}: {e}"))?;
+            let program = entry["program"].as_bool().unwrap_or(false);
+            let authority = entry["authority"].as_bool().unwrap_or(false);
+            if name.starts_with('_') && (program || authority) {
+                return Err(format!("{name}: padding fields cannot be writable"));
+            }
+            let size = ty.size();
+            if size == 0 {
+                return Err(format!("{name}: zero-sized field"));
+            }
+            fields.push(Field {
+                name,
+                ty,
+                offset,
+                program,
+                authority,
+            });
+            offset += size;
+        }
+        if offset > AUX_DATA_SIZE {
+            return Err(format!(
+                "fields take {offset} bytes, more than the {AUX_DATA_SIZE}-byte aux region"
+            ));
+        }
+        if let Some(metadata) = metadata {
+            if metadata.type_size() as usize != offset {
+                return Err(format!(
+                    "metadata type size is {}, but fields take {offset} bytes",
+                    metadata.type_size()
+                ));
+            }
+        }
+        Ok(Self {
+            name,
+            metadata,
+            fields,
+        })
+    }
+
+    /// Total size of the fields.
+    pub fn size(&self) -> usize {
+        self.fields.last().map_or(0, |f| f.range().end)
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// `(name, value)` for every field of `aux`.
+    pub fn decode(&self, aux: &[u8]) -> Vec<(&str, String)> {
+        self.fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.ty.format(&aux[f.range()])))
+            .collect()
+    }
+
+    /// Apply `name=value` assignments to `aux`.
+    pub fn apply(&self, aux: &mut [u8], assignments: &[String]) -> Result<(), String> {
+        for assignment in assignments {
+            let (name, value) = assignment
+                .split_once('=')
+                .ok_or_else(|| format!("`{assignment}` is not FIELD=VALUE"))?;
+            let field = self
+                .field(name)
+                .ok_or_else(|| format!("{} has no field `{name}`", self.name))?;
+            let bytes = field.ty.encode(value).map_err(|e| format!("{name}: {e}"))?;
+            aux[field.range()].copy_from_slice(&bytes);
+        }
+        Ok(())
+    }
+
+    /// Program and user masks granting the fields marked `program` and `authority`.
+    pub fn masks(&self) -> (Mask, Mask) {
+        let mut program = Mask::ALL_BLOCKED;
+        let mut user = Mask::ALL_BLOCKED;
+        for field in &self.fields {
+            if field.program {
+                program.allow_range(field.offset, field.ty.size());
+            }
+            if field.authority {
+                user.allow_range(field.offset, field.ty.size());
+            }
+        }
+        (program, user)
+    }
+}
+
+/// `u64` from decimal or `0x`-prefixed hex.
+pub fn parse_u64(s: &str) -> Option<u64> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+pub fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMM: &str = r#"{
+        "name": "AmmState",
+        "fields": [
+            { "name": "pool_price", "type": "u64", "program": true },
+            { "name": "fee_rate", "type": "i16", "authority": true },
+            { "name": "paused", "type": "bool", "program": true, "authority": true },
+            { "name": "_reserved", "type": { "array": ["u8", 5] } },
+            { "name": "admin", "type": "pubkey" }
+        ]
+    }"#;
+
+    #[test]
+    fn fields_are_laid_out_back_to_back() {
+        let schema = AuxSchema::parse(AMM).unwrap();
+        let ranges: Vec<_> = schema.fields.iter().map(Field::range).collect();
+        assert_eq!(ranges, [0..8, 8..10, 10..11, 11..16, 16..48]);
+        assert_eq!(schema.size(), 48);
+        assert_eq!(schema.metadata, None);
+    }
+
+    #[test]
+    fn decode_and_apply_roundtrip() {
+        let schema = AuxSchema::parse(AMM).unwrap();
+        let admin = Address::new_from_array([7; 32]);
+        let mut aux = [0u8; AUX_DATA_SIZE];
+        schema
+            .apply(
+                &mut aux,
+                &[
+                    "pool_price=1500".to_string(),
+                    "fee_rate=-3".to_string(),
+                    "paused=true".to_string(),
+                    "_reserved=0x0102030405".to_string(),
+                    format!("admin={admin}"),
+                ],
+            )
+            .unwrap();
+        assert_eq!(
+            schema.decode(&aux),
+            [
+                ("pool_price", "1500".to_string()),
+                ("fee_rate", "-3".to_string()),
+                ("paused", "true".to_string()),
+                ("_reserved", "0x0102030405".to_string()),
+                ("admin", admin.to_string()),
+            ]
+        );
+
+        assert!(schema.apply(&mut aux, &["fee_rate=40000".into()]).is_err());
+        assert!(schema.apply(&mut aux, &["missing=1".into()]).is_err());
+        assert!(schema.apply(&mut aux, &["_reserved=0x01".into()]).is_err());
+    }
+
+    #[test]
+    fn masks_follow_field_flags() {
+        let (program, user) = AuxSchema::parse(AMM).unwrap().masks();
+        assert!((0..8).all(|i| program.is_writable(i) && !user.is_writable(i)));
+        assert!((8..10).all(|i| !program.is_writable(i) && user.is_writable(i)));
+        assert!(program.is_writable(10) && user.is_writable(10));
+        assert!((11..AUX_DATA_SIZE).all(|i| !program.is_writable(i) && !user.is_writable(i)));
+    }
+
+    #[test]
+    fn rejects_bad_schemas() {
+        for bad in [
+            r#"{ "fields": [{ "name": "x", "type": "f32" }] }"#,
+            r#"{ "fields": [{ "name": "_pad", "type": "u8", "program": true }] }"#,
+            r#"{ "fields": [{ "name": "x", "type": { "array": ["u8", 257] } }] }"#,
+            r#"{ "metadata": "0x0100000000000000", "fields": [{ "name": "x", "type": "u16" }] }"#,
+            r#"{ "fields": [{ "name": "x", "type": { "array": ["u8", 0] } }] }"#,
+            r#"{ "name": "no fields" }"#,
+        ] {
+            assert!(AuxSchema::parse(bad).is_err(), "{bad}");
+        }
+    }
+}
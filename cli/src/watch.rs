@@ -0,0 +1,133 @@
+//! Change descriptions for `watch`.
+
+use c_u_soon::Envelope;
+
+use crate::schema::{hex, AuxSchema};
+
+/// Oracle bytes of `envelope`: `type_size` bytes of the payload, or all of it if the type
+/// size is out of range.
+pub fn oracle_bytes(envelope: &Envelope) -> &[u8] {
+    let data = &envelope.oracle_state.data;
+    data.get(..envelope.oracle_state.oracle_metadata.type_size() as usize)
+        .unwrap_or(data)
+}
+
+/// Aux bytes of `envelope` covered by its aux metadata.
+pub fn aux_bytes(envelope: &Envelope) -> &[u8] {
+    &envelope.auxiliary_data[..envelope.auxiliary_metadata.type_size() as usize]
+}
+
+/// One line per change from `prev` to `next`: oracle writes, aux writes, and delegation
+/// changes. Aux writes list changed fields when a schema is given, and the aux bytes
+/// otherwise. With no `prev`, describes `next` as if every counter had just moved.
+pub fn changes(
+    prev: Option<&Envelope>,
+    next: &Envelope,
+    schema: Option<&AuxSchema>,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if prev.is_none_or(|p| {
+        p.oracle_state.sequence != next.oracle_state.sequence
+            || p.oracle_state.data != next.oracle_state.data
+    }) {
+        lines.push(format!(
+            "oracle seq {} 0x{}",
+            next.oracle_state.sequence,
+            hex(oracle_bytes(next))
+        ));
+    }
+
+    if prev.is_none_or(|p| {
+        p.authority_aux_sequence != next.authority_aux_sequence
+            || p.program_aux_sequence != next.program_aux_sequence
+            || p.auxiliary_data != next.auxiliary_data
+    }) {
+        let seqs = format!(
+            "aux seq authority {} program {}",
+            next.authority_aux_sequence, next.program_aux_sequence
+        );
+        match schema {
+            Some(schema) => {
+                let after = schema.decode(&next.auxiliary_data);
+                let before = prev.map(|p| schema.decode(&p.auxiliary_data));
+                lines.push(seqs);
+                for (i, (name, value)) in after.iter().enumerate() {
+                    match before.as_ref().map(|b| &b[i].1) {
+                        Some(old) if old == value => {}
+                        Some(old) => lines.push(format!("  {name}: {old} -> {value}")),
+                        None => lines.push(format!("  {name}: {value}")),
+                    }
+                }
+            }
+            None => lines.push(format!("{seqs} 0x{}", hex(aux_bytes(next)))),
+        }
+    }
+
+    if prev.is_some_and(|p| {
+        p.delegation_authority != next.delegation_authority
+            || p.program_bitmask != next.program_bitmask
+            || p.user_bitmask != next.user_bitmask
+    }) {
+        lines.push(if next.has_delegation() {
+            format!("delegated to {}", next.delegation_authority)
+        } else {
+            "delegation cleared".to_string()
+        });
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+    use c_u_soon::{StructMetadata, TypeHash};
+    use solana_address::Address;
+
+    fn envelope() -> Envelope {
+        let mut envelope = Envelope::zeroed();
+        envelope.oracle_state.oracle_metadata = u32::METADATA;
+        envelope.auxiliary_metadata = StructMetadata::new(3, 1);
+        envelope
+    }
+
+    #[test]
+    fn first_read_describes_everything() {
+        let mut next = envelope();
+        next.oracle_state.sequence = 4;
+        next.oracle_state.data[..4].copy_from_slice(&7u32.to_le_bytes());
+        next.auxiliary_data[..3].copy_from_slice(&[1, 2, 3]);
+        assert_eq!(
+            changes(None, &next, None),
+            [
+                "oracle seq 4 0x07000000",
+                "aux seq authority 0 program 0 0x010203"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_only_what_moved() {
+        let prev = envelope();
+        assert!(changes(Some(&prev), &prev, None).is_empty());
+
+        let schema = AuxSchema::parse(
+            r#"{ "fields": [{ "name": "a", "type": "u8" }, { "name": "b", "type": "u16" }] }"#,
+        )
+        .unwrap();
+        let mut next = prev;
+        next.authority_aux_sequence = 1;
+        next.auxiliary_data[1] = 9;
+        next.delegation_authority = Address::new_from_array([5; 32]);
+        assert_eq!(
+            changes(Some(&prev), &next, Some(&schema)),
+            [
+                "aux seq authority 1 program 0".to_string(),
+                "  b: 0 -> 9".to_string(),
+                format!("delegated to {}", next.delegation_authority),
+            ]
+        );
+    }
+}
@@ -0,0 +1,22 @@
+//! Prints the end-to-end fast-path latency report.
+//!
+//! ```text
+//! make build-sbf
+//! cargo run --release -p c_u_soon_client --features bench-e2e --example bench_e2e -- [iterations]
+//! ```
+
+use c_u_soon_client::bench_e2e::{run, BenchConfig};
+use solana_address::Address;
+
+const PROGRAM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../target/deploy/c_u_soon_program"
+);
+
+fn main() {
+    let mut config = BenchConfig::new(Address::new_from_array([0xC5; 32]), PROGRAM_PATH);
+    if let Some(iterations) = std::env::args().nth(1) {
+        config.iterations = iterations.parse().expect("iterations must be a number");
+    }
+    print!("{}", run(&config));
+}
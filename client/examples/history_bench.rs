@@ -0,0 +1,127 @@
+//! Prints compression ratio and throughput of `compress_envelope_history` over synthetic
+//! per-slot snapshot corpora.
+//!
+//! ```text
+//! cargo run --release -p c_u_soon_client --example history_bench -- [snapshots]
+//! ```
+
+use std::time::Instant;
+
+use bytemuck::Zeroable;
+use c_u_soon::Envelope;
+use c_u_soon_client::{compress_envelope_history, decompress_envelope_history, EnvelopeSnapshot};
+
+const DEFAULT_SNAPSHOTS: u64 = 100_000;
+
+/// Deterministic xorshift, so every run measures the same corpus.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// One snapshot per slot. `step` mutates the envelope for slot `i` before it is recorded.
+fn corpus(len: u64, mut step: impl FnMut(u64, &mut Envelope, &mut Rng)) -> Vec<EnvelopeSnapshot> {
+    let mut envelope = Envelope::zeroed();
+    envelope.authority = solana_address::Address::new_from_array([7; 32]);
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    (0..len)
+        .map(|i| {
+            step(i, &mut envelope, &mut rng);
+            EnvelopeSnapshot {
+                context_slot: 300_000_000 + i,
+                envelope,
+            }
+        })
+        .collect()
+}
+
+/// Random walk on the `u64` at `data[offset..offset + 8]`.
+fn walk(envelope: &mut Envelope, offset: usize, rng: &mut Rng) {
+    let field = &mut envelope.oracle_state.data[offset..offset + 8];
+    let value = u64::from_le_bytes(field.try_into().unwrap());
+    let value = value.wrapping_add(rng.next() % 201).wrapping_sub(100);
+    field.copy_from_slice(&value.to_le_bytes());
+}
+
+fn main() {
+    let len = std::env::args()
+        .nth(1)
+        .map(|n| n.parse().expect("snapshots must be a number"))
+        .unwrap_or(DEFAULT_SNAPSHOTS);
+
+    let feeds: [(&str, Vec<EnvelopeSnapshot>); 4] = [
+        (
+            "price every slot",
+            corpus(len, |_, env, rng| {
+                env.oracle_state.sequence += 1;
+                walk(env, 0, rng);
+            }),
+        ),
+        (
+            "bid/ask/conf every slot",
+            corpus(len, |i, env, rng| {
+                env.oracle_state.sequence += 1;
+                walk(env, 0, rng);
+                walk(env, 8, rng);
+                walk(env, 16, rng);
+                env.oracle_state.data[24..32].copy_from_slice(&i.to_le_bytes());
+            }),
+        ),
+        (
+            "price every 10 slots",
+            corpus(len, |i, env, rng| {
+                if i % 10 == 0 {
+                    env.oracle_state.sequence += 1;
+                    walk(env, 0, rng);
+                }
+            }),
+        ),
+        (
+            "price + aux every slot",
+            corpus(len, |_, env, rng| {
+                env.oracle_state.sequence += 1;
+                walk(env, 0, rng);
+                env.authority_aux_sequence += 1;
+                let block = (rng.next() % 8) as usize * 32;
+                env.auxiliary_data[block..block + 8].copy_from_slice(&rng.next().to_le_bytes());
+                env.aux_write_count = env.aux_write_count.wrapping_add(1);
+                env.aux_dirty_blocks = 1 << (block / 32);
+            }),
+        ),
+    ];
+
+    println!(
+        "{:<24} {:>10} {:>12} {:>12} {:>8} {:>12} {:>12}",
+        "feed", "snapshots", "raw KiB", "packed KiB", "ratio", "pack MiB/s", "unpack MiB/s"
+    );
+    for (name, snapshots) in feeds {
+        let raw = snapshots.len() * (8 + Envelope::SIZE);
+
+        let start = Instant::now();
+        let packed = compress_envelope_history(snapshots);
+        let pack = start.elapsed();
+
+        let start = Instant::now();
+        let unpacked = decompress_envelope_history(&packed).expect("round trip");
+        let unpack = start.elapsed();
+        assert_eq!(unpacked.len() as u64, len);
+
+        let mib_per_s = |secs: f64| raw as f64 / (1 << 20) as f64 / secs;
+        println!(
+            "{:<24} {:>10} {:>12.1} {:>12.1} {:>7.1}x {:>12.1} {:>12.1}",
+            name,
+            len,
+            raw as f64 / 1024.0,
+            packed.len() as f64 / 1024.0,
+            raw as f64 / packed.len() as f64,
+            mib_per_s(pack.as_secs_f64()),
+            mib_per_s(unpack.as_secs_f64()),
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Incremental auxiliary-data sync using the envelope's dirty-block bitmap.
+//!
+//! Every aux write records which 32-byte blocks it touched in
+//! [`Envelope::aux_dirty_blocks`] and bumps [`Envelope::aux_write_count`]. A light client
+//! keeps an [`AuxCache`], polls the 3-byte [`AUX_DIFF_HEADER`] with an RPC `dataSlice`, and
+//! then requests only the account ranges from [`AuxCache::slices_to_fetch`].
+
+use core::ops::Range;
+
+use c_u_soon::{Envelope, AUX_BLOCKS, AUX_BLOCK_SIZE, AUX_DATA_SIZE};
+
+const WRITE_COUNT_OFFSET: usize = core::mem::offset_of!(Envelope, aux_write_count);
+const AUX_DATA_OFFSET: usize = core::mem::offset_of!(Envelope, auxiliary_data);
+
+/// Account byte range holding `aux_write_count` (u16 LE) followed by `aux_dirty_blocks`.
+pub const AUX_DIFF_HEADER: Range<usize> = WRITE_COUNT_OFFSET..WRITE_COUNT_OFFSET + 3;
+
+/// Parse `(aux_write_count, aux_dirty_blocks)` from bytes fetched at [`AUX_DIFF_HEADER`].
+pub fn parse_aux_diff_header(bytes: &[u8]) -> Option<(u16, u8)> {
+    let &[lo, hi, dirty, ..] = bytes else {
+        return None;
+    };
+    Some((u16::from_le_bytes([lo, hi]), dirty))
+}
+
+/// Account byte ranges covering the blocks set in `dirty_blocks`, with adjacent blocks merged.
+pub fn dirty_block_slices(dirty_blocks: u8) -> Vec<Range<usize>> {
+    let mut slices: Vec<Range<usize>> = Vec::new();
+    for block in (0..AUX_BLOCKS).filter(|&b| dirty_blocks & (1 << b) != 0) {
+        let start = AUX_DATA_OFFSET + block * AUX_BLOCK_SIZE;
+        let end = start + AUX_BLOCK_SIZE;
+        match slices.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => slices.push(start..end),
+        }
+    }
+    slices
+}
+
+/// Local copy of an envelope's auxiliary data, tagged with the write count it reflects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuxCache {
+    pub write_count: u16,
+    pub data: [u8; AUX_DATA_SIZE],
+}
+
+impl AuxCache {
+    /// Seed the cache from a full envelope read.
+    pub fn from_envelope(envelope: &Envelope) -> Self {
+        Self {
+            write_count: envelope.aux_write_count,
+            data: envelope.auxiliary_data,
+        }
+    }
+
+    /// Account ranges to fetch to catch up to an envelope at `write_count`.
+    ///
+    /// Empty if nothing was written. If exactly one write happened, only its dirty blocks.
+    /// Otherwise the bitmap only describes the last of several writes, so the whole aux
+    /// region is returned.
+    pub fn slices_to_fetch(&self, write_count: u16, dirty_blocks: u8) -> Vec<Range<usize>> {
+        match write_count.wrapping_sub(self.write_count) {
+            0 => Vec::new(),
+            1 => dirty_block_slices(dirty_blocks),
+            _ => core::iter::once(AUX_DATA_OFFSET..AUX_DATA_OFFSET + AUX_DATA_SIZE).collect(),
+        }
+    }
+
+    /// Store bytes fetched at account offset `start`. Returns `false`, leaving the cache
+    /// unchanged, if the bytes fall outside the aux region.
+    ///
+    /// Set [`write_count`](Self::write_count) once every slice has been applied.
+    pub fn apply_slice(&mut self, start: usize, bytes: &[u8]) -> bool {
+        let Some(offset) = start.checked_sub(AUX_DATA_OFFSET) else {
+            return false;
+        };
+        let Some(dst) = self.data.get_mut(offset..offset + bytes.len()) else {
+            return false;
+        };
+        dst.copy_from_slice(bytes);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    #[test]
+    fn header_matches_envelope_layout() {
+        let mut envelope = Envelope::zeroed();
+        envelope.aux_write_count = 0x0102;
+        envelope.aux_dirty_blocks = 0b1010_0000;
+        let bytes = bytemuck::bytes_of(&envelope);
+        assert_eq!(
+            parse_aux_diff_header(&bytes[AUX_DIFF_HEADER]),
+            Some((0x0102, 0b1010_0000))
+        );
+        assert_eq!(parse_aux_diff_header(&[0, 0]), None);
+    }
+
+    #[test]
+    fn dirty_slices_merge_adjacent_blocks() {
+        assert!(dirty_block_slices(0).is_empty());
+        assert_eq!(
+            dirty_block_slices(0b1000_0110),
+            vec![
+                AUX_DATA_OFFSET + 32..AUX_DATA_OFFSET + 96,
+                AUX_DATA_OFFSET + 224..AUX_DATA_OFFSET + 256,
+            ]
+        );
+        assert_eq!(
+            dirty_block_slices(0xFF),
+            vec![AUX_DATA_OFFSET..AUX_DATA_OFFSET + AUX_DATA_SIZE]
+        );
+    }
+
+    #[test]
+    fn slices_to_fetch_depends_on_write_gap() {
+        let mut envelope = Envelope::zeroed();
+        envelope.aux_write_count = u16::MAX;
+        let cache = AuxCache::from_envelope(&envelope);
+
+        assert!(cache.slices_to_fetch(u16::MAX, 0xFF).is_empty());
+        // One write, across the u16 wrap.
+        assert_eq!(
+            cache.slices_to_fetch(0, 0b0000_0001),
+            vec![AUX_DATA_OFFSET..AUX_DATA_OFFSET + 32]
+        );
+        assert_eq!(
+            cache.slices_to_fetch(1, 0b0000_0001),
+            vec![AUX_DATA_OFFSET..AUX_DATA_OFFSET + AUX_DATA_SIZE]
+        );
+    }
+
+    #[test]
+    fn apply_slice_updates_cache() {
+        let mut cache = AuxCache::from_envelope(&Envelope::zeroed());
+        assert!(cache.apply_slice(AUX_DATA_OFFSET + 32, &[0xAA; 32]));
+        assert!(cache.data[32..64].iter().all(|&b| b == 0xAA));
+        assert!(cache.data[..32].iter().all(|&b| b == 0));
+
+        assert!(!cache.apply_slice(AUX_DATA_OFFSET - 1, &[0; 2]));
+        assert!(!cache.apply_slice(AUX_DATA_OFFSET + 250, &[0; 8]));
+    }
+}
@@ -0,0 +1,272 @@
+//! End-to-end fast-path latency harness (feature `bench-e2e`).
+//!
+//! Each iteration runs the four stages a publisher pays for on every update and times them
+//! separately:
+//!
+//! 1. **build**: [`fast_path_instruction_data`] plus the `Instruction` wrapper.
+//! 2. **sign**: a single-instruction `Transaction` signed by the authority.
+//! 3. **simulate**: execute against the current envelope without committing, as
+//!    `simulateTransaction` does.
+//! 4. **apply**: execute and commit the resulting envelope, so the next iteration's
+//!    sequence check runs against real state.
+//!
+//! Execution goes through Mollusk, so the simulate and apply numbers measure the SVM and the
+//! program, not network or leader scheduling.
+
+use std::time::{Duration, Instant};
+
+use bytemuck::Zeroable;
+use c_u_soon::{Envelope, ORACLE_BYTES};
+use mollusk_svm::{result::Check, Mollusk};
+use solana_address::Address;
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    signature::Keypair,
+    signer::Signer,
+    transaction::Transaction,
+};
+
+use crate::fast_path_instruction_data;
+
+/// Payload lengths measured when none are configured: empty, one word, common price
+/// structs, the largest memo-compatible payload, and a full oracle frame.
+pub const DEFAULT_PAYLOAD_SIZES: &[usize] = &[0, 8, 32, 64, 128, 199, ORACLE_BYTES];
+
+/// Iterations per payload size when none are configured.
+pub const DEFAULT_ITERATIONS: usize = 1_000;
+
+/// Harness configuration.
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    pub program_id: Address,
+    /// Path to the program's `.so`, without the extension (as `Mollusk::new` expects).
+    pub program_path: String,
+    pub payload_sizes: Vec<usize>,
+    pub iterations: usize,
+}
+
+impl BenchConfig {
+    pub fn new(program_id: Address, program_path: impl Into<String>) -> Self {
+        Self {
+            program_id,
+            program_path: program_path.into(),
+            payload_sizes: DEFAULT_PAYLOAD_SIZES.to_vec(),
+            iterations: DEFAULT_ITERATIONS,
+        }
+    }
+}
+
+/// Latency distribution of one stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StageStats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p99: Duration,
+    pub mean: Duration,
+}
+
+impl StageStats {
+    /// Summarize `samples`. Returns all-zero stats for an empty slice.
+    pub fn from_samples(samples: &mut [Duration]) -> Self {
+        if samples.is_empty() {
+            return Self {
+                min: Duration::ZERO,
+                median: Duration::ZERO,
+                p99: Duration::ZERO,
+                mean: Duration::ZERO,
+            };
+        }
+        samples.sort_unstable();
+        let len = samples.len();
+        let total: Duration = samples.iter().sum();
+        Self {
+            min: samples[0],
+            median: samples[len / 2],
+            p99: samples[(len * 99 / 100).min(len - 1)],
+            mean: total / len as u32,
+        }
+    }
+}
+
+/// Results for one payload size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeReport {
+    pub payload_len: usize,
+    /// Compute units consumed by the applied update.
+    pub compute_units: u64,
+    pub build: StageStats,
+    pub sign: StageStats,
+    pub simulate: StageStats,
+    pub apply: StageStats,
+    /// Sum of all four stages per iteration.
+    pub total: StageStats,
+}
+
+/// Results for every configured payload size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchReport {
+    pub iterations: usize,
+    pub sizes: Vec<SizeReport>,
+}
+
+impl core::fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(
+            f,
+            "fast path end-to-end, {} iterations per size (median / p99, µs)",
+            self.iterations
+        )?;
+        writeln!(
+            f,
+            "{:>7} {:>4} {:>15} {:>15} {:>15} {:>15} {:>15}",
+            "payload", "CUs", "build", "sign", "simulate", "apply", "total"
+        )?;
+        for size in &self.sizes {
+            writeln!(
+                f,
+                "{:>7} {:>4} {:>15} {:>15} {:>15} {:>15} {:>15}",
+                size.payload_len,
+                size.compute_units,
+                micros(&size.build),
+                micros(&size.sign),
+                micros(&size.simulate),
+                micros(&size.apply),
+                micros(&size.total),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn micros(stats: &StageStats) -> String {
+    format!(
+        "{:.1} / {:.1}",
+        stats.median.as_secs_f64() * 1e6,
+        stats.p99.as_secs_f64() * 1e6
+    )
+}
+
+/// Run the harness.
+///
+/// Panics if a payload size exceeds [`ORACLE_BYTES`] or the program rejects an update, since
+/// either means the measurement is meaningless.
+pub fn run(config: &BenchConfig) -> BenchReport {
+    let mollusk = Mollusk::new(&config.program_id, &config.program_path);
+    let sizes = config
+        .payload_sizes
+        .iter()
+        .map(|&payload_len| run_size(&mollusk, config, payload_len))
+        .collect();
+    BenchReport {
+        iterations: config.iterations,
+        sizes,
+    }
+}
+
+fn run_size(mollusk: &Mollusk, config: &BenchConfig, payload_len: usize) -> SizeReport {
+    assert!(
+        payload_len <= ORACLE_BYTES,
+        "payload size {payload_len} exceeds ORACLE_BYTES"
+    );
+
+    let authority = Keypair::new();
+    let authority_address = authority.pubkey();
+    let envelope_address = Address::new_from_array([0xE0; 32]);
+    let mut envelope = Envelope::zeroed();
+    envelope.authority = authority_address;
+    let mut accounts = vec![
+        (
+            authority_address,
+            Account::new(1_000_000_000, 0, &Address::default()),
+        ),
+        (
+            envelope_address,
+            Account {
+                lamports: 1_000_000_000,
+                data: bytemuck::bytes_of(&envelope).to_vec(),
+                owner: config.program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+    ];
+
+    let payload = vec![0x5A; payload_len];
+    let blockhash = Hash::default();
+    let mut build = Vec::with_capacity(config.iterations);
+    let mut sign = Vec::with_capacity(config.iterations);
+    let mut simulate = Vec::with_capacity(config.iterations);
+    let mut apply = Vec::with_capacity(config.iterations);
+    let mut total = Vec::with_capacity(config.iterations);
+    let mut compute_units = 0;
+
+    for sequence in 1..=config.iterations as u64 {
+        let start = Instant::now();
+        let instruction = Instruction::new_with_bytes(
+            config.program_id,
+            &fast_path_instruction_data(0, sequence, &payload).expect("payload size checked above"),
+            vec![
+                AccountMeta::new_readonly(authority_address, true),
+                AccountMeta::new(envelope_address, false),
+            ],
+        );
+        let built = Instant::now();
+
+        let transaction = Transaction::new_signed_with_payer(
+            core::slice::from_ref(&instruction),
+            Some(&authority_address),
+            &[&authority],
+            blockhash,
+        );
+        core::hint::black_box(&transaction);
+        let signed = Instant::now();
+
+        core::hint::black_box(mollusk.process_instruction(&instruction, &accounts));
+        let simulated = Instant::now();
+
+        let result =
+            mollusk.process_and_validate_instruction(&instruction, &accounts, &[Check::success()]);
+        let applied = Instant::now();
+
+        compute_units = result.compute_units_consumed;
+        accounts = result.resulting_accounts;
+
+        build.push(built - start);
+        sign.push(signed - built);
+        simulate.push(simulated - signed);
+        apply.push(applied - simulated);
+        total.push(applied - start);
+    }
+
+    SizeReport {
+        payload_len,
+        compute_units,
+        build: StageStats::from_samples(&mut build),
+        sign: StageStats::from_samples(&mut sign),
+        simulate: StageStats::from_samples(&mut simulate),
+        apply: StageStats::from_samples(&mut apply),
+        total: StageStats::from_samples(&mut total),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stage_stats_percentiles() {
+        let mut samples: Vec<Duration> = (1..=100).rev().map(Duration::from_micros).collect();
+        let stats = StageStats::from_samples(&mut samples);
+        assert_eq!(stats.min, Duration::from_micros(1));
+        assert_eq!(stats.median, Duration::from_micros(51));
+        assert_eq!(stats.p99, Duration::from_micros(100));
+        assert_eq!(stats.mean, Duration::from_nanos(50_500));
+    }
+
+    #[test]
+    fn stage_stats_empty() {
+        assert_eq!(StageStats::from_samples(&mut []).median, Duration::ZERO);
+    }
+}
@@ -0,0 +1,775 @@
+//! Complete instructions: data plus account metas in the order the program reads them.
+//!
+//! The `*_instruction_data` functions return only the data, leaving callers to list the
+//! accounts by hand. The `build_*_ix` functions here return a ready
+//! [`Instruction`] with each account's signer and writable flags set, and derive the
+//! envelope PDA where the instruction creates it. Instructions that change state end with
+//! the program config PDA, which the account lists below leave out (see
+//! [`append_config_account`]). Errors are the same as the matching data builder's.
+
+use c_u_soon::{
+    create_envelope_address, derive_config_address, derive_envelope_address, CompactMask, Mask,
+    StructMetadata,
+};
+use solana_address::Address;
+use solana_instruction::{AccountMeta, Instruction};
+
+use crate::{
+    clear_delegation_instruction_data, close_instruction_data, create_instruction_data,
+    create_with_events_instruction_data, export_state_instruction_data, fast_path_instruction_data,
+    heartbeat_instruction_data, init_config_instruction_data, migrate_envelope_instruction_data,
+    read_oracle_instruction_data, seal_auxiliary_range_instruction_data,
+    set_config_instruction_data, set_delegated_program_compact_instruction_data,
+    set_delegated_program_instruction_data, set_oracle_metadata_instruction_data,
+    set_paused_instruction_data, set_publisher_instruction_data, set_write_hook_instruction_data,
+    split_update_instruction_data, transfer_delegation_instruction_data,
+    update_auxiliary_instruction_data, InstructionError,
+};
+
+/// The system program (`11111111111111111111111111111111`).
+const SYSTEM_PROGRAM_ID: Address = Address::new_from_array([0; 32]);
+
+/// The instructions sysvar (`Sysvar1nstructions1111111111111111111111111`).
+const INSTRUCTIONS_SYSVAR_ID: Address = Address::new_from_array([
+    0x06, 0xa7, 0xd5, 0x17, 0x18, 0x7b, 0xd1, 0x66, 0x35, 0xda, 0xd4, 0x04, 0x55, 0xfd, 0xc2, 0xc0,
+    0xc1, 0x24, 0xc6, 0x8f, 0x21, 0x56, 0x75, 0xa5, 0xdb, 0xba, 0xcb, 0x5f, 0x08, 0x00, 0x00, 0x00,
+]);
+
+/// Envelope address and canonical bump for `authority` and `custom_seeds`.
+///
+/// `custom_seeds` must be valid `Create` seeds (see [`create_instruction_data`]);
+/// [`c_u_soon::derive_envelope_address`] panics otherwise. Use [`PdaCache`](crate::PdaCache) to avoid
+/// repeating the bump search.
+pub fn find_envelope_address(
+    program_id: &Address,
+    authority: &Address,
+    custom_seeds: &[&[u8]],
+) -> (Address, u8) {
+    derive_envelope_address(program_id, authority, custom_seeds)
+}
+
+/// `Create`: `[authority (writable signer), envelope (writable), system_program,
+/// config (writable)]`.
+///
+/// The envelope address is derived from `authority`, `custom_seeds`, and `bump`, and the
+/// config address from `program_id`; the config account receives the creation fee. Returns
+/// [`InstructionError::InvalidPdaBump`] if they do not derive a valid PDA, as well as the
+/// errors of [`create_instruction_data`].
+pub fn build_create_ix(
+    program_id: &Address,
+    authority: &Address,
+    custom_seeds: &[&[u8]],
+    bump: u8,
+    oracle_metadata: StructMetadata,
+) -> Result<Instruction, InstructionError> {
+    let data = create_instruction_data(custom_seeds, bump, oracle_metadata)?;
+    create_ix(program_id, authority, custom_seeds, bump, &data)
+}
+
+/// `CreateWithEvents`: as [`build_create_ix`], for an envelope whose fast-path writes log
+/// a `c_u_soon::OracleEvent`.
+pub fn build_create_with_events_ix(
+    program_id: &Address,
+    authority: &Address,
+    custom_seeds: &[&[u8]],
+    bump: u8,
+    oracle_metadata: StructMetadata,
+) -> Result<Instruction, InstructionError> {
+    let data = create_with_events_instruction_data(custom_seeds, bump, oracle_metadata)?;
+    create_ix(program_id, authority, custom_seeds, bump, &data)
+}
+
+fn create_ix(
+    program_id: &Address,
+    authority: &Address,
+    custom_seeds: &[&[u8]],
+    bump: u8,
+    data: &[u8],
+) -> Result<Instruction, InstructionError> {
+    let envelope = create_envelope_address(program_id, authority, custom_seeds, bump)
+        .ok_or(InstructionError::InvalidPdaBump)?;
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        data,
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(envelope, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new(derive_config_address(program_id).0, false),
+        ],
+    ))
+}
+
+/// The upgradeable BPF loader (`BPFLoaderUpgradeab1e11111111111111111111111`).
+const BPF_LOADER_UPGRADEABLE_ID: Address = Address::new_from_array([
+    0x02, 0xa8, 0xf6, 0x91, 0x4e, 0x88, 0xa1, 0xb0, 0xe2, 0x10, 0x15, 0x3e, 0xf7, 0x63, 0xae, 0x2b,
+    0x00, 0xc2, 0xb9, 0x3d, 0x16, 0xc1, 0x24, 0xd2, 0xc0, 0x53, 0x7a, 0x10, 0x04, 0x80, 0x00, 0x00,
+]);
+
+/// `InitConfig`: `[upgrade_authority (writable signer), config (writable), system_program,
+/// program, program_data]`.
+///
+/// The config address and its bump are derived from `program_id`, and the program data
+/// address from the upgradeable loader's seeds.
+pub fn build_init_config_ix(
+    program_id: &Address,
+    upgrade_authority: &Address,
+    admin: &Address,
+    create_fee: u64,
+) -> Result<Instruction, InstructionError> {
+    let (config, bump) = derive_config_address(program_id);
+    let (program_data, _) =
+        Address::find_program_address(&[program_id.as_ref()], &BPF_LOADER_UPGRADEABLE_ID);
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &init_config_instruction_data(bump, admin.to_bytes(), create_fee)?,
+        vec![
+            AccountMeta::new(*upgrade_authority, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*program_id, false),
+            AccountMeta::new_readonly(program_data, false),
+        ],
+    ))
+}
+
+/// `SetConfig`: `[admin (signer), config (writable), fee_destination]`.
+///
+/// `new_admin` replaces `admin`; pass `admin` to keep it. With `fee_destination`, it is
+/// writable and the collected creation fees are swept to it; without, the system program
+/// is passed as read-only padding and the fees stay in the config account.
+pub fn build_set_config_ix(
+    program_id: &Address,
+    admin: &Address,
+    new_admin: &Address,
+    create_fee: u64,
+    paused: bool,
+    fee_destination: Option<&Address>,
+) -> Result<Instruction, InstructionError> {
+    let fee_destination = match fee_destination {
+        Some(fee_destination) => AccountMeta::new(*fee_destination, false),
+        None => AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    };
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &set_config_instruction_data(new_admin.to_bytes(), create_fee, paused)?,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(derive_config_address(program_id).0, false),
+            fee_destination,
+        ],
+    ))
+}
+
+/// Append the program config, read-only, to a hand-built slow-path instruction that
+/// [`requires_trailing_config`](c_u_soon_instruction::requires_trailing_config). The
+/// program fails the instruction with `NotEnoughAccountKeys` or `InvalidSeeds` without it,
+/// and with `PROGRAM_PAUSED_ERROR` while the config is paused. The `build_*_ix` functions
+/// already end with it.
+pub fn append_config_account(ix: &mut Instruction) {
+    ix.accounts.push(config_meta(&ix.program_id));
+}
+
+/// The program config as the read-only last account of a state-changing instruction.
+fn config_meta(program_id: &Address) -> AccountMeta {
+    AccountMeta::new_readonly(derive_config_address(program_id).0, false)
+}
+
+/// `Close`: `[authority (signer), envelope (writable), recipient (writable)]`.
+pub fn build_close_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    recipient: &Address,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &close_instruction_data()?,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new(*recipient, false),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// Fast-path oracle update: `[authority (signer), envelope (writable)]`.
+pub fn build_fast_path_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    oracle_meta: u64,
+    sequence: u64,
+    payload: &[u8],
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &fast_path_instruction_data(oracle_meta, sequence, payload)?,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+        ],
+    ))
+}
+
+/// `UpdateAuxiliary`: `[authority (signer), envelope (writable), third]`.
+///
+/// `third` is the envelope's constraint table if it constrains its auxiliary region, and any
+/// other account (the system program works) otherwise. An envelope with a write hook also
+/// needs the hook program, read-only, as the fourth account, before the config.
+pub fn build_update_auxiliary_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    third: &Address,
+    metadata: u64,
+    sequence: u64,
+    data: &[u8],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &update_auxiliary_instruction_data(metadata, sequence, data),
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(*third, false),
+            config_meta(program_id),
+        ],
+    )
+}
+
+/// `SetDelegatedProgram`:
+/// `[authority (signer), envelope (writable), delegation_authority (signer)]`.
+pub fn build_set_delegated_program_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    delegation_authority: &Address,
+    program_bitmask: Mask,
+    user_bitmask: Mask,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &set_delegated_program_instruction_data(program_bitmask, user_bitmask)?,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(*delegation_authority, true),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// `SetDelegatedProgramCompact`: the accounts of [`build_set_delegated_program_ix`], with
+/// 32-byte masks.
+pub fn build_set_delegated_program_compact_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    delegation_authority: &Address,
+    program_bitmask: CompactMask,
+    user_bitmask: CompactMask,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &set_delegated_program_compact_instruction_data(program_bitmask, user_bitmask)?,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(*delegation_authority, true),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// `ClearDelegation`:
+/// `[authority (signer), envelope (writable), delegation_authority (signer)]`.
+pub fn build_clear_delegation_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    delegation_authority: &Address,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &clear_delegation_instruction_data()?,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(*delegation_authority, true),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// `TransferDelegation`: `[authority (signer), envelope (writable), old_delegation_authority
+/// (signer), new_delegation_authority (signer)]`.
+pub fn build_transfer_delegation_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    old_delegation_authority: &Address,
+    new_delegation_authority: &Address,
+    program_bitmask: Mask,
+    user_bitmask: Mask,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &transfer_delegation_instruction_data(program_bitmask, user_bitmask)?,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(*old_delegation_authority, true),
+            AccountMeta::new_readonly(*new_delegation_authority, true),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// `SetWriteHook`: `[authority (signer), envelope (writable), delegation_authority (signer),
+/// hook_program]`. `hook_program: None` removes the hook, passing the system program in its
+/// place.
+pub fn build_set_write_hook_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    delegation_authority: &Address,
+    hook_program: Option<&Address>,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &set_write_hook_instruction_data(hook_program.is_some())?,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(*delegation_authority, true),
+            AccountMeta::new_readonly(*hook_program.unwrap_or(&SYSTEM_PROGRAM_ID), false),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// `SetPublisher`: `[authority (signer), envelope (writable), padding]`, with the system
+/// program as padding. `publisher: None` removes the envelope's publisher.
+pub fn build_set_publisher_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    publisher: Option<&Address>,
+) -> Result<Instruction, InstructionError> {
+    let publisher = publisher.map_or([0; 32], Address::to_bytes);
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &set_publisher_instruction_data(publisher)?,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// `SetPaused`: `[authority (signer), envelope (writable), padding]`, with the system
+/// program as padding.
+pub fn build_set_paused_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    paused: bool,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &set_paused_instruction_data(paused)?,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// `SetOracleMetadata`: `[authority (signer), envelope (writable), padding]`, with the
+/// system program as padding.
+pub fn build_set_oracle_metadata_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    oracle_metadata: StructMetadata,
+    auxiliary_metadata: StructMetadata,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &set_oracle_metadata_instruction_data(oracle_metadata, auxiliary_metadata)?,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// `SealAuxiliaryRange`: `[authority (signer), envelope (writable), padding]`, with the
+/// system program as padding.
+pub fn build_seal_auxiliary_range_ix(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    offset: u16,
+    len: u16,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &seal_auxiliary_range_instruction_data(offset, len)?,
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// `Heartbeat`: `[cranker (signer), envelope (writable), padding]`, with the system program
+/// as padding. `sequence` is the envelope's current oracle sequence.
+pub fn build_heartbeat_ix(
+    program_id: &Address,
+    cranker: &Address,
+    envelope: &Address,
+    sequence: u64,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &heartbeat_instruction_data(sequence)?,
+        vec![
+            AccountMeta::new_readonly(*cranker, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// Both halves of a `SplitUpdate`, in the order they must appear in the transaction. Each
+/// is `[authority (signer), envelope (writable), instructions_sysvar]`.
+pub fn build_split_update_ixs(
+    program_id: &Address,
+    authority: &Address,
+    envelope: &Address,
+    type_hash: u64,
+    sequence: u64,
+    payload: &[u8],
+) -> Result<[Instruction; 2], InstructionError> {
+    Ok(
+        split_update_instruction_data(type_hash, sequence, payload)?.map(|data| {
+            Instruction::new_with_bytes(
+                *program_id,
+                &data,
+                vec![
+                    AccountMeta::new_readonly(*authority, true),
+                    AccountMeta::new(*envelope, false),
+                    AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false),
+                    config_meta(program_id),
+                ],
+            )
+        }),
+    )
+}
+
+/// `MigrateEnvelope`: `[payer (signer, writable), envelope (writable), system_program]`.
+pub fn build_migrate_envelope_ix(
+    program_id: &Address,
+    payer: &Address,
+    envelope: &Address,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &migrate_envelope_instruction_data()?,
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            config_meta(program_id),
+        ],
+    ))
+}
+
+/// `ReadOracle`: `[envelope]`.
+pub fn build_read_oracle_ix(
+    program_id: &Address,
+    envelope: &Address,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &read_oracle_instruction_data()?,
+        vec![AccountMeta::new_readonly(*envelope, false)],
+    ))
+}
+
+/// `ExportState`: `[envelope]`.
+pub fn build_export_state_ix(
+    program_id: &Address,
+    envelope: &Address,
+) -> Result<Instruction, InstructionError> {
+    Ok(Instruction::new_with_bytes(
+        *program_id,
+        &export_state_instruction_data()?,
+        vec![AccountMeta::new_readonly(*envelope, false)],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_instruction;
+    use c_u_soon::ENVELOPE_SEED;
+
+    fn roles(ix: &Instruction) -> Vec<&'static str> {
+        let accounts: Vec<Address> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        decode_instruction(&ix.program_id, &accounts, &ix.data)
+            .unwrap()
+            .accounts
+            .iter()
+            .map(|account| account.role)
+            .collect()
+    }
+
+    #[test]
+    fn create_derives_envelope() {
+        let program = Address::new_from_array([9; 32]);
+        let authority = Address::new_from_array([1; 32]);
+        let seeds: &[&[u8]] = &[b"feed"];
+        let (envelope, bump) = find_envelope_address(&program, &authority, seeds);
+
+        let ix = build_create_ix(&program, &authority, seeds, bump, StructMetadata::ZERO).unwrap();
+        assert_eq!(ix.program_id, program);
+        assert_eq!(ix.accounts[0], AccountMeta::new(authority, true));
+        assert_eq!(ix.accounts[1], AccountMeta::new(envelope, false));
+        assert_eq!(ix.accounts[2].pubkey, SYSTEM_PROGRAM_ID);
+        assert_eq!(
+            ix.accounts[3],
+            AccountMeta::new(derive_config_address(&program).0, false)
+        );
+        assert_eq!(
+            roles(&ix),
+            ["authority", "envelope", "system_program", "config"]
+        );
+
+        let events =
+            build_create_with_events_ix(&program, &authority, seeds, bump, StructMetadata::ZERO)
+                .unwrap();
+        assert_eq!(events.accounts, ix.accounts);
+        assert_eq!(events.data[..4], 42u32.to_le_bytes());
+    }
+
+    #[test]
+    fn create_rejects_bad_seeds() {
+        let program = Address::new_from_array([9; 32]);
+        let authority = Address::new_from_array([1; 32]);
+        assert_eq!(
+            build_create_ix(&program, &authority, &[&[0; 33]], 255, StructMetadata::ZERO),
+            Err(InstructionError::SeedTooLong)
+        );
+        // About half of all bumps put the address on the curve.
+        let on_curve = (0..=255u8)
+            .find(|&bump| {
+                Address::create_program_address(
+                    &[ENVELOPE_SEED, authority.as_ref(), &[bump]],
+                    &program,
+                )
+                .is_err()
+            })
+            .unwrap();
+        assert_eq!(
+            build_create_ix(&program, &authority, &[], on_curve, StructMetadata::ZERO),
+            Err(InstructionError::InvalidPdaBump)
+        );
+    }
+
+    #[test]
+    fn account_orders_match_decoder() {
+        let program = Address::new_from_array([9; 32]);
+        let [a, b, c] = [1u8, 2, 3].map(|i| Address::new_from_array([i; 32]));
+
+        let ix = build_close_ix(&program, &a, &b, &c).unwrap();
+        assert_eq!(roles(&ix), ["authority", "envelope", "recipient", "config"]);
+        assert!(ix.accounts[0].is_signer && !ix.accounts[0].is_writable);
+
+        let ix = build_fast_path_ix(&program, &a, &b, 0, 1, &[7; 8]).unwrap();
+        assert_eq!(roles(&ix), ["authority", "envelope"]);
+        assert!(ix.accounts[1].is_writable);
+
+        let ix = build_update_auxiliary_ix(&program, &a, &b, &c, 0, 1, &[7; 8]);
+        assert_eq!(ix.accounts.len(), 4);
+        assert_eq!(ix.accounts[3], config_meta(&program));
+        assert!(ix.accounts[0].is_signer && ix.accounts[1].is_writable);
+
+        let ix = build_set_delegated_program_ix(
+            &program,
+            &a,
+            &b,
+            &c,
+            Mask::ALL_BLOCKED,
+            Mask::ALL_BLOCKED,
+        )
+        .unwrap();
+        assert_eq!(
+            roles(&ix),
+            ["authority", "envelope", "delegation_authority", "config"]
+        );
+        assert!(ix.accounts[2].is_signer);
+
+        let ix = build_set_delegated_program_compact_ix(
+            &program,
+            &a,
+            &b,
+            &c,
+            CompactMask::ZERO,
+            CompactMask::FULL,
+        )
+        .unwrap();
+        assert_eq!(
+            roles(&ix),
+            ["authority", "envelope", "delegation_authority", "config"]
+        );
+        assert!(ix.accounts[2].is_signer);
+
+        let ix = build_clear_delegation_ix(&program, &a, &b, &c).unwrap();
+        assert_eq!(
+            roles(&ix),
+            ["authority", "envelope", "delegation_authority", "config"]
+        );
+
+        let d = Address::new_from_array([4; 32]);
+        let ix = build_transfer_delegation_ix(
+            &program,
+            &a,
+            &b,
+            &c,
+            &d,
+            Mask::ALL_WRITABLE,
+            Mask::ALL_BLOCKED,
+        )
+        .unwrap();
+        assert_eq!(
+            roles(&ix),
+            [
+                "authority",
+                "envelope",
+                "old_delegation_authority",
+                "new_delegation_authority",
+                "config"
+            ]
+        );
+        assert!(ix.accounts[2].is_signer && ix.accounts[3].is_signer);
+
+        let hook = build_set_write_hook_ix(&program, &a, &b, &c, Some(&d)).unwrap();
+        assert_eq!(
+            roles(&hook),
+            [
+                "authority",
+                "envelope",
+                "delegation_authority",
+                "hook_program",
+                "config"
+            ]
+        );
+        assert!(hook.accounts[0].is_signer && hook.accounts[2].is_signer);
+        assert!(!hook.accounts[3].is_signer && !hook.accounts[3].is_writable);
+        let removed = build_set_write_hook_ix(&program, &a, &b, &c, None).unwrap();
+        assert_eq!(removed.accounts[3].pubkey, SYSTEM_PROGRAM_ID);
+        assert_ne!(removed.data, hook.data);
+
+        let ix = build_set_publisher_ix(&program, &a, &b, Some(&c)).unwrap();
+        assert_eq!(roles(&ix), ["authority", "envelope", "padding", "config"]);
+        assert!(ix.accounts[0].is_signer && ix.accounts[1].is_writable);
+        let cleared = build_set_publisher_ix(&program, &a, &b, None).unwrap();
+        assert_eq!(cleared.data[4..], [0; 32]);
+
+        let ix = build_set_paused_ix(&program, &a, &b, true).unwrap();
+        assert_eq!(roles(&ix), ["authority", "envelope", "padding", "config"]);
+        assert!(ix.accounts[0].is_signer && ix.accounts[1].is_writable);
+
+        let ix = build_set_oracle_metadata_ix(
+            &program,
+            &a,
+            &b,
+            StructMetadata::ZERO,
+            StructMetadata::ZERO,
+        )
+        .unwrap();
+        assert_eq!(roles(&ix), ["authority", "envelope", "padding", "config"]);
+        assert!(ix.accounts[0].is_signer && ix.accounts[1].is_writable);
+
+        let ix = build_seal_auxiliary_range_ix(&program, &a, &b, 0, 8).unwrap();
+        assert_eq!(roles(&ix), ["authority", "envelope", "padding", "config"]);
+        assert!(ix.accounts[0].is_signer && ix.accounts[1].is_writable);
+
+        let ix = build_heartbeat_ix(&program, &a, &b, 3).unwrap();
+        assert_eq!(roles(&ix), ["cranker", "envelope", "padding", "config"]);
+        assert!(!ix.accounts[3].is_writable);
+        assert!(ix.accounts[0].is_signer && ix.accounts[1].is_writable);
+        assert!(build_seal_auxiliary_range_ix(&program, &a, &b, 0, 0).is_err());
+
+        let ixs = build_split_update_ixs(&program, &a, &b, 9, 1, &[1, 2, 3]).unwrap();
+        for ix in &ixs {
+            assert_eq!(
+                roles(ix),
+                ["authority", "envelope", "instructions_sysvar", "config"]
+            );
+            assert!(ix.accounts[0].is_signer && ix.accounts[1].is_writable);
+            assert_eq!(
+                ix.accounts[2].pubkey.to_string(),
+                "Sysvar1nstructions1111111111111111111111111"
+            );
+        }
+        assert!(build_split_update_ixs(&program, &a, &b, 9, 1, &[]).is_err());
+
+        let ix = build_migrate_envelope_ix(&program, &a, &b).unwrap();
+        assert_eq!(
+            roles(&ix),
+            ["payer", "envelope", "system_program", "config"]
+        );
+        assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+        assert!(ix.accounts[1].is_writable);
+
+        let mut ix =
+            Instruction::new_with_bytes(program, &close_instruction_data().unwrap(), vec![]);
+        append_config_account(&mut ix);
+        assert_eq!(ix.accounts, [config_meta(&program)]);
+
+        let ix = build_init_config_ix(&program, &a, &b, 100).unwrap();
+        assert_eq!(
+            roles(&ix),
+            [
+                "upgrade_authority",
+                "config",
+                "system_program",
+                "program",
+                "program_data"
+            ]
+        );
+        assert_eq!(ix.accounts[1].pubkey, derive_config_address(&program).0);
+        assert_eq!(ix.accounts[3].pubkey, program);
+        assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+
+        let ix = build_set_config_ix(&program, &a, &b, 0, true, Some(&c)).unwrap();
+        assert_eq!(roles(&ix), ["admin", "config", "fee_destination"]);
+        assert!(ix.accounts[0].is_signer && ix.accounts[2].is_writable);
+        let kept = build_set_config_ix(&program, &a, &a, 0, false, None).unwrap();
+        assert_eq!(
+            kept.accounts[2],
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false)
+        );
+
+        let ix = build_read_oracle_ix(&program, &b).unwrap();
+        assert_eq!(ix.accounts, [AccountMeta::new_readonly(b, false)]);
+
+        let ix = build_export_state_ix(&program, &b).unwrap();
+        assert_eq!(roles(&ix), ["envelope"]);
+        assert!(!ix.accounts[0].is_writable);
+    }
+}
@@ -0,0 +1,1951 @@
+//! Classify and label c_u_soon instructions, for incident response and indexers.
+//!
+//! [`decode_instruction`] follows the program's own dispatch: an instruction with exactly two
+//! accounts is a fast-path oracle update whatever its data, and anything else is read by its
+//! 4-byte tag. Every account gets the role its handler documents, and PDAs whose seeds and
+//! bump appear in the instruction are re-derived, so a wrong address shows up in
+//! [`DecodedCuSoonInstruction::warnings`] before anyone has to read an `InvalidSeeds` log.
+//! A versioned wrapper is unwrapped and its inner instruction decoded, if the version is one
+//! this crate knows.
+//!
+//! Decoding never checks account state. An instruction that decodes cleanly can still fail
+//! on-chain on a stale sequence, a mask, or a missing signature.
+
+use core::fmt;
+
+use c_u_soon::{
+    derive_config_address, Permission, StructMetadata, CONFIG_SEED, CONSTRAINTS_SEED,
+    DELEGATE_SCHEMA_SEED, ENVELOPE_SEED, GROUP_ANCHOR_SEED, LABEL_SEED, ORACLE_BYTES,
+    PUBLISHERS_SEED, RANGE_GUARD_SEED, READERS_SEED, RHS_FIELD, RHS_PREVIOUS,
+};
+use c_u_soon_instruction::{
+    is_supported_version, requires_trailing_config, split_versioned, SlowPathInstruction,
+    WriteRange, BATCH_ENTRY_HEADER_SIZE, BATCH_UPDATE_HEADER_SIZE, BATCH_UPDATE_TAG,
+    PUBLISHER_UPDATE_HEADER_SIZE, PUBLISHER_UPDATE_TAG, SPLIT_UPDATE_HEADER_SIZE, SPLIT_UPDATE_TAG,
+    UPDATE_AUX_DELEGATED_RANGE_TAG, UPDATE_AUX_DELEGATED_SCHEMA_TAG, UPDATE_AUX_DELEGATED_TAG,
+    UPDATE_AUX_FORCE_HEADER_SIZE, UPDATE_AUX_FORCE_TAG, UPDATE_AUX_HEADER_SIZE,
+    UPDATE_AUX_RANGE_HEADER_SIZE, UPDATE_AUX_RANGE_TAG, UPDATE_AUX_TAG, VERSIONED_TAG,
+};
+use solana_address::Address;
+use wincode::SchemaRead;
+
+/// Tags of the wincode-encoded [`SlowPathInstruction`] variants.
+const WINCODE_TAGS: &[u32] = &[
+    0, 1, 2, 3, 9, 10, 11, 12, 13, 14, 16, 17, 18, 19, 20, 21, 22, 23, 24, 26, 27, 28, 29, 30, 31,
+    33, 34, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 49, 50, 51, 52, 53, 54, 55,
+];
+
+/// Byte strings longer than this are shortened when printed.
+const MAX_PRINTED_BYTES: usize = 32;
+
+/// Why instruction data could not be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data ends before the fields its tag (or the fast-path header) requires.
+    Truncated,
+    /// The 4-byte tag belongs to no c_u_soon instruction.
+    UnknownTag(u32),
+    /// A wincode variant whose fields do not deserialize, or that has trailing bytes.
+    Malformed(u32),
+    /// A versioned instruction built for an instruction set newer than this crate's.
+    UnsupportedVersion(u8),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "instruction data truncated"),
+            Self::UnknownTag(tag) => write!(f, "unknown instruction tag {tag}"),
+            Self::Malformed(tag) => write!(f, "malformed data for instruction tag {tag}"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported instruction version {version}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// One entry of a decoded `BatchUpdate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedBatchEntry {
+    pub oracle_meta: u64,
+    pub sequence: u64,
+    pub payload: Vec<u8>,
+}
+
+/// The instruction kind and its parameters.
+///
+/// Manual-wire instructions get their own variants; every wincode instruction is carried
+/// as the [`SlowPathInstruction`] the program itself deserializes.
+#[derive(Debug, Clone)]
+pub enum DecodedParams {
+    FastPath {
+        oracle_meta: u64,
+        sequence: u64,
+        payload: Vec<u8>,
+    },
+    UpdateAuxiliary {
+        metadata: u64,
+        sequence: u64,
+        data: Vec<u8>,
+    },
+    UpdateAuxiliaryDelegated {
+        metadata: u64,
+        sequence: u64,
+        data: Vec<u8>,
+    },
+    UpdateAuxiliaryDelegatedSchema {
+        schema_metadata: u64,
+        sequence: u64,
+        data: Vec<u8>,
+    },
+    UpdateAuxiliaryForce {
+        metadata: u64,
+        authority_sequence: u64,
+        program_sequence: u64,
+        data: Vec<u8>,
+    },
+    UpdateAuxiliaryRange {
+        metadata: u64,
+        sequence: u64,
+        offset: u8,
+        data: Vec<u8>,
+    },
+    UpdateAuxiliaryDelegatedRange {
+        metadata: u64,
+        sequence: u64,
+        offset: u8,
+        data: Vec<u8>,
+    },
+    BatchUpdate {
+        entries: Vec<DecodedBatchEntry>,
+    },
+    PublisherUpdate {
+        oracle_meta: u64,
+        sequence: u64,
+        payload: Vec<u8>,
+    },
+    SplitUpdate {
+        type_hash: u64,
+        sequence: u64,
+        part: u8,
+        payload: Vec<u8>,
+    },
+    /// Boxed, since the largest variants carry both 256-byte masks.
+    Slow(Box<SlowPathInstruction>),
+}
+
+impl DecodedParams {
+    /// The instruction's name, as used by the program and the instruction crate.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FastPath { .. } => "FastPath",
+            Self::UpdateAuxiliary { .. } => "UpdateAuxiliary",
+            Self::UpdateAuxiliaryDelegated { .. } => "UpdateAuxiliaryDelegated",
+            Self::UpdateAuxiliaryDelegatedSchema { .. } => "UpdateAuxiliaryDelegatedSchema",
+            Self::UpdateAuxiliaryForce { .. } => "UpdateAuxiliaryForce",
+            Self::UpdateAuxiliaryRange { .. } => "UpdateAuxiliaryRange",
+            Self::UpdateAuxiliaryDelegatedRange { .. } => "UpdateAuxiliaryDelegatedRange",
+            Self::BatchUpdate { .. } => "BatchUpdate",
+            Self::PublisherUpdate { .. } => "PublisherUpdate",
+            Self::SplitUpdate { .. } => "SplitUpdate",
+            Self::Slow(ix) => match ix.as_ref() {
+                SlowPathInstruction::Create { .. } => "Create",
+                SlowPathInstruction::Close => "Close",
+                SlowPathInstruction::SetDelegatedProgram { .. } => "SetDelegatedProgram",
+                SlowPathInstruction::ClearDelegation => "ClearDelegation",
+                SlowPathInstruction::UpdateAuxiliaryMultiRange { .. } => {
+                    "UpdateAuxiliaryMultiRange"
+                }
+                SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange { .. } => {
+                    "UpdateAuxiliaryDelegatedMultiRange"
+                }
+                SlowPathInstruction::UpdateAuxiliaryMultiRangeForce { .. } => {
+                    "UpdateAuxiliaryMultiRangeForce"
+                }
+                SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 { .. } => {
+                    "UpdateAuxiliaryMultiRangeV2"
+                }
+                SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRangeV2 { .. } => {
+                    "UpdateAuxiliaryDelegatedMultiRangeV2"
+                }
+                SlowPathInstruction::UpdateAuxiliaryMultiRangeForceV2 { .. } => {
+                    "UpdateAuxiliaryMultiRangeForceV2"
+                }
+                SlowPathInstruction::SetRangeGuards { .. } => "SetRangeGuards",
+                SlowPathInstruction::SetDelegateSchema { .. } => "SetDelegateSchema",
+                SlowPathInstruction::SetPermanent => "SetPermanent",
+                SlowPathInstruction::SetMutationGuard { .. } => "SetMutationGuard",
+                SlowPathInstruction::ReadAuxRange { .. } => "ReadAuxRange",
+                SlowPathInstruction::SetGroupMembers { .. } => "SetGroupMembers",
+                SlowPathInstruction::GroupCommit { .. } => "GroupCommit",
+                SlowPathInstruction::SetLabel { .. } => "SetLabel",
+                SlowPathInstruction::AssertAuxHash { .. } => "AssertAuxHash",
+                SlowPathInstruction::ReadOracle => "ReadOracle",
+                SlowPathInstruction::ReadAux => "ReadAux",
+                SlowPathInstruction::ExportState => "ExportState",
+                SlowPathInstruction::SetWriteHook { .. } => "SetWriteHook",
+                SlowPathInstruction::SetOracleMetadata { .. } => "SetOracleMetadata",
+                SlowPathInstruction::Resize { .. } => "Resize",
+                SlowPathInstruction::MigrateEnvelope => "MigrateEnvelope",
+                SlowPathInstruction::UpdateExtAux { .. } => "UpdateExtAux",
+                SlowPathInstruction::SetTwap { .. } => "SetTwap",
+                SlowPathInstruction::SetUpdateStamp { .. } => "SetUpdateStamp",
+                SlowPathInstruction::SetConstraints { .. } => "SetConstraints",
+                SlowPathInstruction::SetPublishers { .. } => "SetPublishers",
+                SlowPathInstruction::SetDelegatedPda { .. } => "SetDelegatedPda",
+                SlowPathInstruction::CloseWithSplit { .. } => "CloseWithSplit",
+                SlowPathInstruction::SetConflation { .. } => "SetConflation",
+                SlowPathInstruction::SetReaders { .. } => "SetReaders",
+                SlowPathInstruction::ReadOracleGated => "ReadOracleGated",
+                SlowPathInstruction::Aggregate { .. } => "Aggregate",
+                SlowPathInstruction::SetPublisher { .. } => "SetPublisher",
+                SlowPathInstruction::SetPaused { .. } => "SetPaused",
+                SlowPathInstruction::CreateWithEvents { .. } => "CreateWithEvents",
+                SlowPathInstruction::SealAuxiliaryRange { .. } => "SealAuxiliaryRange",
+                SlowPathInstruction::InitConfig { .. } => "InitConfig",
+                SlowPathInstruction::SetConfig { .. } => "SetConfig",
+                SlowPathInstruction::Heartbeat { .. } => "Heartbeat",
+                SlowPathInstruction::TransferDelegation { .. } => "TransferDelegation",
+                SlowPathInstruction::SetDelegatedProgramCompact { .. } => {
+                    "SetDelegatedProgramCompact"
+                }
+            },
+        }
+    }
+
+    /// The parameters as `(name, value)` pairs, formatted for people: metadata as size and
+    /// hash, long byte strings shortened, masks as a count of writable bytes.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::FastPath {
+                oracle_meta,
+                sequence,
+                payload,
+            }
+            | Self::PublisherUpdate {
+                oracle_meta,
+                sequence,
+                payload,
+            } => vec![
+                ("oracle_meta", metadata(*oracle_meta)),
+                ("sequence", sequence.to_string()),
+                ("payload", bytes(payload)),
+            ],
+            Self::UpdateAuxiliary {
+                metadata: meta,
+                sequence,
+                data,
+            }
+            | Self::UpdateAuxiliaryDelegated {
+                metadata: meta,
+                sequence,
+                data,
+            } => vec![
+                ("metadata", metadata(*meta)),
+                ("sequence", sequence.to_string()),
+                ("data", bytes(data)),
+            ],
+            Self::UpdateAuxiliaryDelegatedSchema {
+                schema_metadata,
+                sequence,
+                data,
+            } => vec![
+                ("schema_metadata", metadata(*schema_metadata)),
+                ("sequence", sequence.to_string()),
+                ("data", bytes(data)),
+            ],
+            Self::UpdateAuxiliaryForce {
+                metadata: meta,
+                authority_sequence,
+                program_sequence,
+                data,
+            } => vec![
+                ("metadata", metadata(*meta)),
+                ("authority_sequence", authority_sequence.to_string()),
+                ("program_sequence", program_sequence.to_string()),
+                ("data", bytes(data)),
+            ],
+            Self::UpdateAuxiliaryRange {
+                metadata: meta,
+                sequence,
+                offset,
+                data,
+            }
+            | Self::UpdateAuxiliaryDelegatedRange {
+                metadata: meta,
+                sequence,
+                offset,
+                data,
+            } => vec![
+                ("metadata", metadata(*meta)),
+                ("sequence", sequence.to_string()),
+                ("offset", offset.to_string()),
+                ("data", bytes(data)),
+            ],
+            Self::BatchUpdate { entries } => entries
+                .iter()
+                .map(|e| {
+                    let value = format!(
+                        "oracle_meta {}, sequence {}, payload {}",
+                        metadata(e.oracle_meta),
+                        e.sequence,
+                        bytes(&e.payload)
+                    );
+                    ("entry", value)
+                })
+                .collect(),
+            Self::SplitUpdate {
+                type_hash,
+                sequence,
+                part,
+                payload,
+            } => vec![
+                ("type_hash", format!("0x{type_hash:016x}")),
+                ("sequence", sequence.to_string()),
+                ("part", format!("{part} of 2")),
+                ("payload", bytes(payload)),
+            ],
+            Self::Slow(ix) => slow_fields(ix),
+        }
+    }
+}
+
+fn slow_fields(ix: &SlowPathInstruction) -> Vec<(&'static str, String)> {
+    match ix {
+        SlowPathInstruction::Create {
+            custom_seeds,
+            bump,
+            oracle_metadata,
+        }
+        | SlowPathInstruction::CreateWithEvents {
+            custom_seeds,
+            bump,
+            oracle_metadata,
+        } => {
+            let mut fields: Vec<_> = custom_seeds.iter().map(|s| ("seed", bytes(s))).collect();
+            fields.push(("bump", bump.to_string()));
+            fields.push(("oracle_metadata", metadata(*oracle_metadata)));
+            fields
+        }
+        SlowPathInstruction::Close
+        | SlowPathInstruction::ClearDelegation
+        | SlowPathInstruction::SetPermanent
+        | SlowPathInstruction::ReadOracle
+        | SlowPathInstruction::ReadOracleGated
+        | SlowPathInstruction::ReadAux
+        | SlowPathInstruction::ExportState
+        | SlowPathInstruction::MigrateEnvelope => Vec::new(),
+        SlowPathInstruction::SetDelegatedProgram {
+            program_bitmask,
+            user_bitmask,
+        }
+        | SlowPathInstruction::TransferDelegation {
+            program_bitmask,
+            user_bitmask,
+        } => vec![
+            ("program_bitmask", mask(program_bitmask)),
+            ("user_bitmask", mask(user_bitmask)),
+        ],
+        SlowPathInstruction::SetDelegatedProgramCompact {
+            program_bitmask,
+            user_bitmask,
+        } => vec![
+            ("program_bitmask", compact_mask(program_bitmask)),
+            ("user_bitmask", compact_mask(user_bitmask)),
+        ],
+        SlowPathInstruction::SetDelegatedPda {
+            program_id,
+            seeds,
+            bump,
+            program_bitmask,
+            user_bitmask,
+        } => {
+            let mut fields = vec![(
+                "program_id",
+                Address::new_from_array(*program_id).to_string(),
+            )];
+            fields.extend(seeds.iter().map(|s| ("seed", bytes(s))));
+            fields.push(("bump", bump.to_string()));
+            fields.push(("program_bitmask", mask(program_bitmask)));
+            fields.push(("user_bitmask", mask(user_bitmask)));
+            fields
+        }
+        SlowPathInstruction::UpdateAuxiliaryMultiRange {
+            metadata: meta,
+            sequence,
+            ranges,
+        }
+        | SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange {
+            metadata: meta,
+            sequence,
+            ranges,
+        } => {
+            let mut fields = vec![
+                ("metadata", metadata(*meta)),
+                ("sequence", sequence.to_string()),
+            ];
+            fields.extend(ranges.iter().map(range));
+            fields
+        }
+        SlowPathInstruction::UpdateAuxiliaryMultiRangeForce {
+            metadata: meta,
+            authority_sequence,
+            program_sequence,
+            ranges,
+        } => {
+            let mut fields = vec![
+                ("metadata", metadata(*meta)),
+                ("authority_sequence", authority_sequence.to_string()),
+                ("program_sequence", program_sequence.to_string()),
+            ];
+            fields.extend(ranges.iter().map(range));
+            fields
+        }
+        SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 {
+            metadata: meta,
+            sequence,
+            ranges,
+        }
+        | SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRangeV2 {
+            metadata: meta,
+            sequence,
+            ranges,
+        } => {
+            let mut fields = vec![
+                ("metadata", metadata(*meta)),
+                ("sequence", sequence.to_string()),
+            ];
+            fields.extend(ranges.iter().map(range));
+            fields
+        }
+        SlowPathInstruction::UpdateAuxiliaryMultiRangeForceV2 {
+            metadata: meta,
+            authority_sequence,
+            program_sequence,
+            ranges,
+        } => {
+            let mut fields = vec![
+                ("metadata", metadata(*meta)),
+                ("authority_sequence", authority_sequence.to_string()),
+                ("program_sequence", program_sequence.to_string()),
+            ];
+            fields.extend(ranges.iter().map(range));
+            fields
+        }
+        SlowPathInstruction::SetRangeGuards { bump, entries } => {
+            let mut fields = vec![("bump", bump.to_string())];
+            fields.extend(entries.iter().map(|e| {
+                let value = format!(
+                    "[{}, {}) requires {}",
+                    e.offset,
+                    u32::from(e.offset) + u32::from(e.len),
+                    Address::new_from_array(e.program_id)
+                );
+                ("guard", value)
+            }));
+            fields
+        }
+        SlowPathInstruction::SetDelegateSchema {
+            bump,
+            offset,
+            metadata: meta,
+        } => vec![
+            ("bump", bump.to_string()),
+            ("offset", offset.to_string()),
+            ("metadata", metadata(*meta)),
+        ],
+        SlowPathInstruction::SetMutationGuard { allow_multi } => {
+            vec![("allow_multi", allow_multi.to_string())]
+        }
+        SlowPathInstruction::SetTwap { enabled }
+        | SlowPathInstruction::SetUpdateStamp { enabled }
+        | SlowPathInstruction::SetConflation { enabled } => {
+            vec![("enabled", enabled.to_string())]
+        }
+        SlowPathInstruction::SetConstraints { bump, constraints } => {
+            let mut fields = vec![("bump", bump.to_string())];
+            fields.extend(constraints.iter().map(|c| {
+                let rhs = match c.rhs_kind {
+                    RHS_FIELD => format!("field {}", c.rhs_offset),
+                    RHS_PREVIOUS => "previous".to_string(),
+                    _ => c.value.to_string(),
+                };
+                let value = format!(
+                    "region {} [{}; {}] op {} {}{}",
+                    c.region,
+                    c.offset,
+                    c.width,
+                    c.op,
+                    rhs,
+                    if c.signed { " signed" } else { "" }
+                );
+                ("constraint", value)
+            }));
+            fields
+        }
+        SlowPathInstruction::SetPublishers {
+            bump,
+            threshold,
+            publishers,
+        } => {
+            let mut fields = vec![
+                ("bump", bump.to_string()),
+                ("threshold", threshold.to_string()),
+            ];
+            fields.extend(
+                publishers
+                    .iter()
+                    .map(|&p| ("publisher", Address::new_from_array(p).to_string())),
+            );
+            fields
+        }
+        SlowPathInstruction::SetReaders { bump, readers } => {
+            let mut fields = vec![("bump", bump.to_string())];
+            fields.extend(
+                readers
+                    .iter()
+                    .map(|&r| ("reader", Address::new_from_array(r).to_string())),
+            );
+            fields
+        }
+        SlowPathInstruction::CloseWithSplit { treasury_bps } => {
+            vec![("treasury_bps", treasury_bps.to_string())]
+        }
+        SlowPathInstruction::SetPublisher { publisher } => {
+            vec![("publisher", Address::new_from_array(*publisher).to_string())]
+        }
+        SlowPathInstruction::SetPaused { paused } => vec![("paused", paused.to_string())],
+        SlowPathInstruction::InitConfig {
+            bump,
+            admin,
+            create_fee,
+        } => vec![
+            ("bump", bump.to_string()),
+            ("admin", Address::new_from_array(*admin).to_string()),
+            ("create_fee", create_fee.to_string()),
+        ],
+        SlowPathInstruction::SetConfig {
+            admin,
+            create_fee,
+            paused,
+        } => vec![
+            ("admin", Address::new_from_array(*admin).to_string()),
+            ("create_fee", create_fee.to_string()),
+            ("paused", paused.to_string()),
+        ],
+        SlowPathInstruction::Heartbeat { sequence } => vec![("sequence", sequence.to_string())],
+        SlowPathInstruction::SetWriteHook {
+            notify_on_authority_write,
+        } => vec![(
+            "notify_on_authority_write",
+            notify_on_authority_write.to_string(),
+        )],
+        SlowPathInstruction::SetOracleMetadata {
+            oracle_metadata,
+            auxiliary_metadata,
+        } => vec![
+            ("oracle_metadata", metadata(*oracle_metadata)),
+            ("auxiliary_metadata", metadata(*auxiliary_metadata)),
+        ],
+        SlowPathInstruction::Aggregate {
+            sequence,
+            min_sources,
+            max_deviation_bps,
+        } => vec![
+            ("sequence", sequence.to_string()),
+            ("min_sources", min_sources.to_string()),
+            ("max_deviation_bps", max_deviation_bps.to_string()),
+        ],
+        SlowPathInstruction::ReadAuxRange { offset, len }
+        | SlowPathInstruction::SealAuxiliaryRange { offset, len } => {
+            vec![("offset", offset.to_string()), ("len", len.to_string())]
+        }
+        SlowPathInstruction::SetGroupMembers { group_id, bump } => vec![
+            ("group_id", group_id.to_string()),
+            ("bump", bump.to_string()),
+        ],
+        SlowPathInstruction::GroupCommit { sequence, updates } => {
+            let mut fields = vec![("sequence", sequence.to_string())];
+            fields.extend(updates.iter().map(|u| {
+                let value = format!(
+                    "oracle_meta {}, payload {}",
+                    metadata(u.oracle_meta),
+                    bytes(&u.payload)
+                );
+                ("update", value)
+            }));
+            fields
+        }
+        SlowPathInstruction::SetLabel { bump, label } => {
+            let end = label.iter().position(|&b| b == 0).unwrap_or(label.len());
+            let text = match core::str::from_utf8(&label[..end]) {
+                Ok(text) => format!("{text:?}"),
+                Err(_) => bytes(label),
+            };
+            vec![("bump", bump.to_string()), ("label", text)]
+        }
+        SlowPathInstruction::AssertAuxHash { expected } => vec![("expected", hex(expected))],
+        SlowPathInstruction::Resize { type_hash, ext_len } => vec![
+            ("type_hash", format!("0x{type_hash:016x}")),
+            ("ext_len", ext_len.to_string()),
+        ],
+        SlowPathInstruction::UpdateExtAux {
+            sequence,
+            offset,
+            data,
+        } => vec![
+            ("sequence", sequence.to_string()),
+            ("offset", offset.to_string()),
+            ("data", bytes(data)),
+        ],
+    }
+}
+
+/// An instruction account labelled with the role its handler gives it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedAccount {
+    /// Handler role, e.g. `"authority"` or `"envelope"`. Accounts past the ones the handler
+    /// reads are `"unused"`.
+    pub role: &'static str,
+    pub address: Address,
+}
+
+/// Result of [`decode_instruction`].
+#[derive(Debug, Clone)]
+pub struct DecodedCuSoonInstruction {
+    pub params: DecodedParams,
+    /// One entry per instruction account, in order.
+    pub accounts: Vec<DecodedAccount>,
+    /// Problems the program would reject, or that are likely mistakes, found without account
+    /// state: failed `validate()`, missing accounts, PDAs that do not match their seeds.
+    pub warnings: Vec<String>,
+}
+
+impl fmt::Display for DecodedCuSoonInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.params.name())?;
+        for (name, value) in self.params.fields() {
+            writeln!(f, "  {name}: {value}")?;
+        }
+        for (i, account) in self.accounts.iter().enumerate() {
+            writeln!(f, "  #{i} {}: {}", account.role, account.address)?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "  warning: {warning}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Warning for a program config account that is not at the config PDA.
+const NOT_CONFIG: &str = "config is not the program config PDA";
+
+/// How a handler reads the accounts after its fixed ones.
+enum Trailing {
+    /// None; any extras are unused.
+    None,
+    /// Every remaining account has this role.
+    Repeat(&'static str),
+    /// Delegated writes: `[instructions_sysvar]` or `[range_guard, instructions_sysvar]`.
+    Guards,
+    /// One optional account with this role, such as the `write_hook` of an authority aux
+    /// write to a hooked envelope.
+    Optional(&'static str),
+}
+
+/// Decode one c_u_soon instruction.
+///
+/// - `program_id`: the c_u_soon deployment the instruction was sent to, used to re-derive
+///   PDAs.
+/// - `accounts`: the instruction's account addresses, in order.
+/// - `data`: the instruction data.
+///
+/// Fails only when `data` cannot be parsed at all. Anything the program would reject for
+/// reasons visible in the instruction alone is reported in
+/// [`warnings`](DecodedCuSoonInstruction::warnings) instead, so a failed transaction still
+/// decodes.
+pub fn decode_instruction(
+    program_id: &Address,
+    accounts: &[Address],
+    data: &[u8],
+) -> Result<DecodedCuSoonInstruction, DecodeError> {
+    let mut warnings = Vec::new();
+    // The program checks and removes the program config, the last account, before the
+    // handler runs.
+    let trailing_config = accounts.len() != 2
+        && !accounts.is_empty()
+        && dispatched_tag(data).is_some_and(requires_trailing_config);
+    if trailing_config && accounts.last() != Some(&derive_config_address(program_id).0) {
+        warnings.push(NOT_CONFIG.to_string());
+    }
+    let handler_accounts = accounts.len() - usize::from(trailing_config);
+    let (params, fixed, trailing): (_, &[&'static str], _) = if accounts.len() == 2 {
+        (
+            decode_fast_path(data, &mut warnings)?,
+            &["authority", "envelope"],
+            Trailing::None,
+        )
+    } else {
+        decode_slow_path(data, handler_accounts, &mut warnings)?
+    };
+
+    if handler_accounts < fixed.len() {
+        warnings.push(format!(
+            "{} reads at least {} accounts, got {}",
+            params.name(),
+            fixed.len(),
+            handler_accounts
+        ));
+    }
+    let mut roles = assign_roles(fixed, trailing, handler_accounts);
+    if trailing_config {
+        roles.push("config");
+    }
+    if let DecodedParams::Slow(ix) = &params {
+        check_pdas(program_id, ix, accounts, &mut warnings);
+    }
+
+    Ok(DecodedCuSoonInstruction {
+        params,
+        accounts: accounts
+            .iter()
+            .zip(roles)
+            .map(|(&address, role)| DecodedAccount { role, address })
+            .collect(),
+        warnings,
+    })
+}
+
+/// The tag the slow path dispatches on, behind any versioned wrapper.
+fn dispatched_tag(data: &[u8]) -> Option<u32> {
+    let data = split_versioned(data).map_or(data, |(_, inner)| inner);
+    Some(u32::from_le_bytes(data.get(..4)?.try_into().unwrap()))
+}
+
+fn decode_fast_path(data: &[u8], warnings: &mut Vec<String>) -> Result<DecodedParams, DecodeError> {
+    if data.len() < 16 {
+        return Err(DecodeError::Truncated);
+    }
+    let payload = &data[16..];
+    if payload.len() > ORACLE_BYTES {
+        warnings.push(format!("payload exceeds {ORACLE_BYTES} bytes"));
+    }
+    Ok(DecodedParams::FastPath {
+        oracle_meta: u64_at(data, 0),
+        sequence: u64_at(data, 8),
+        payload: payload.to_vec(),
+    })
+}
+
+fn decode_slow_path(
+    data: &[u8],
+    account_count: usize,
+    warnings: &mut Vec<String>,
+) -> Result<(DecodedParams, &'static [&'static str], Trailing), DecodeError> {
+    const DELEGATED: &[&str] = &["delegation_authority", "envelope", "padding"];
+    const AUTHORITY_PDA: &[&str] = &["authority", "envelope", "pda"];
+
+    if data.len() < 4 {
+        return Err(DecodeError::Truncated);
+    }
+    let tag = u32::from_le_bytes(data[..4].try_into().unwrap());
+    if tag == VERSIONED_TAG {
+        let (version, inner) = split_versioned(data).ok_or(DecodeError::Truncated)?;
+        if !is_supported_version(version) {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        // The program does not unwrap twice.
+        if split_versioned(inner).is_some() {
+            return Err(DecodeError::Malformed(VERSIONED_TAG));
+        }
+        return decode_slow_path(inner, account_count, warnings);
+    }
+    let header = |size: usize| {
+        if data.len() < size {
+            Err(DecodeError::Truncated)
+        } else {
+            Ok((u64_at(data, 4), u64_at(data, 12)))
+        }
+    };
+
+    let decoded = match tag {
+        UPDATE_AUX_TAG => {
+            let (metadata, sequence) = header(UPDATE_AUX_HEADER_SIZE)?;
+            let params = DecodedParams::UpdateAuxiliary {
+                metadata,
+                sequence,
+                data: data[UPDATE_AUX_HEADER_SIZE..].to_vec(),
+            };
+            (params, AUTHORITY_PDA, Trailing::Optional("write_hook"))
+        }
+        UPDATE_AUX_DELEGATED_TAG => {
+            let (metadata, sequence) = header(UPDATE_AUX_HEADER_SIZE)?;
+            let params = DecodedParams::UpdateAuxiliaryDelegated {
+                metadata,
+                sequence,
+                data: data[UPDATE_AUX_HEADER_SIZE..].to_vec(),
+            };
+            (params, DELEGATED, Trailing::Guards)
+        }
+        UPDATE_AUX_DELEGATED_SCHEMA_TAG => {
+            let (schema_metadata, sequence) = header(UPDATE_AUX_HEADER_SIZE)?;
+            let params = DecodedParams::UpdateAuxiliaryDelegatedSchema {
+                schema_metadata,
+                sequence,
+                data: data[UPDATE_AUX_HEADER_SIZE..].to_vec(),
+            };
+            let roles: &[&str] = &["delegation_authority", "envelope", "delegate_schema"];
+            (params, roles, Trailing::Guards)
+        }
+        UPDATE_AUX_FORCE_TAG => {
+            let (metadata, authority_sequence) = header(UPDATE_AUX_FORCE_HEADER_SIZE)?;
+            let params = DecodedParams::UpdateAuxiliaryForce {
+                metadata,
+                authority_sequence,
+                program_sequence: u64_at(data, 20),
+                data: data[UPDATE_AUX_FORCE_HEADER_SIZE..].to_vec(),
+            };
+            let roles: &[&str] = &["authority", "envelope", "delegation_authority"];
+            (params, roles, Trailing::None)
+        }
+        UPDATE_AUX_RANGE_TAG => {
+            let (metadata, sequence) = header(UPDATE_AUX_RANGE_HEADER_SIZE)?;
+            let params = DecodedParams::UpdateAuxiliaryRange {
+                metadata,
+                sequence,
+                offset: data[20],
+                data: data[UPDATE_AUX_RANGE_HEADER_SIZE..].to_vec(),
+            };
+            (params, AUTHORITY_PDA, Trailing::Optional("write_hook"))
+        }
+        UPDATE_AUX_DELEGATED_RANGE_TAG => {
+            let (metadata, sequence) = header(UPDATE_AUX_RANGE_HEADER_SIZE)?;
+            let params = DecodedParams::UpdateAuxiliaryDelegatedRange {
+                metadata,
+                sequence,
+                offset: data[20],
+                data: data[UPDATE_AUX_RANGE_HEADER_SIZE..].to_vec(),
+            };
+            (params, DELEGATED, Trailing::Guards)
+        }
+        BATCH_UPDATE_TAG => {
+            let params = decode_batch(data, account_count, warnings)?;
+            (params, &["authority"][..], Trailing::Repeat("envelope"))
+        }
+        PUBLISHER_UPDATE_TAG => {
+            let (oracle_meta, sequence) = header(PUBLISHER_UPDATE_HEADER_SIZE)?;
+            let payload = &data[PUBLISHER_UPDATE_HEADER_SIZE..];
+            if payload.len() > ORACLE_BYTES {
+                warnings.push(format!("payload exceeds {ORACLE_BYTES} bytes"));
+            }
+            let params = DecodedParams::PublisherUpdate {
+                oracle_meta,
+                sequence,
+                payload: payload.to_vec(),
+            };
+            // A constrained envelope's table sits at index 2; the decoder cannot tell.
+            let roles: &[&str] = &["envelope", "publisher_set"];
+            (params, roles, Trailing::Repeat("publisher"))
+        }
+        SPLIT_UPDATE_TAG => {
+            let (type_hash, sequence) = header(SPLIT_UPDATE_HEADER_SIZE)?;
+            let part = data[20];
+            if part != 1 && part != 2 {
+                warnings.push(format!("part {part} is not 1 or 2; the program rejects it"));
+            }
+            let params = DecodedParams::SplitUpdate {
+                type_hash,
+                sequence,
+                part,
+                payload: data[SPLIT_UPDATE_HEADER_SIZE..].to_vec(),
+            };
+            let roles: &[&str] = &["authority", "envelope", "instructions_sysvar"];
+            (params, roles, Trailing::None)
+        }
+        _ if WINCODE_TAGS.contains(&tag) => {
+            let mut cursor = data;
+            let ix = <SlowPathInstruction as SchemaRead>::get(&mut cursor)
+                .map_err(|_| DecodeError::Malformed(tag))?;
+            if !cursor.is_empty() {
+                return Err(DecodeError::Malformed(tag));
+            }
+            if !ix.validate() {
+                warnings.push("fails validate(); the program rejects it".to_string());
+            }
+            let (roles, trailing) = slow_roles(&ix);
+            (DecodedParams::Slow(Box::new(ix)), roles, trailing)
+        }
+        _ => return Err(DecodeError::UnknownTag(tag)),
+    };
+    Ok(decoded)
+}
+
+fn decode_batch(
+    data: &[u8],
+    account_count: usize,
+    warnings: &mut Vec<String>,
+) -> Result<DecodedParams, DecodeError> {
+    if data.len() < BATCH_UPDATE_HEADER_SIZE {
+        return Err(DecodeError::Truncated);
+    }
+    let count = data[4] as usize;
+    let mut rest = &data[BATCH_UPDATE_HEADER_SIZE..];
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if rest.len() < BATCH_ENTRY_HEADER_SIZE {
+            return Err(DecodeError::Truncated);
+        }
+        let len = rest[16] as usize;
+        let end = BATCH_ENTRY_HEADER_SIZE + len;
+        if rest.len() < end {
+            return Err(DecodeError::Truncated);
+        }
+        if len > ORACLE_BYTES {
+            warnings.push(format!("batch payload exceeds {ORACLE_BYTES} bytes"));
+        }
+        entries.push(DecodedBatchEntry {
+            oracle_meta: u64_at(rest, 0),
+            sequence: u64_at(rest, 8),
+            payload: rest[BATCH_ENTRY_HEADER_SIZE..end].to_vec(),
+        });
+        rest = &rest[end..];
+    }
+    // Constraint tables may follow the envelopes.
+    if account_count < count + 1 {
+        warnings.push(format!(
+            "batch has {count} entries but {} envelope accounts",
+            account_count.saturating_sub(1)
+        ));
+    }
+    if !rest.is_empty() {
+        warnings.push(format!("{} bytes after the last batch entry", rest.len()));
+    }
+    Ok(DecodedParams::BatchUpdate { entries })
+}
+
+fn slow_roles(ix: &SlowPathInstruction) -> (&'static [&'static str], Trailing) {
+    match ix {
+        SlowPathInstruction::Create { .. } | SlowPathInstruction::CreateWithEvents { .. } => (
+            &["authority", "envelope", "system_program", "config"],
+            Trailing::None,
+        ),
+        SlowPathInstruction::Resize { .. } => {
+            (&["authority", "envelope", "system_program"], Trailing::None)
+        }
+        SlowPathInstruction::MigrateEnvelope => {
+            (&["payer", "envelope", "system_program"], Trailing::None)
+        }
+        SlowPathInstruction::InitConfig { .. } => (
+            &[
+                "upgrade_authority",
+                "config",
+                "system_program",
+                "program",
+                "program_data",
+            ],
+            Trailing::None,
+        ),
+        SlowPathInstruction::SetConfig { .. } => {
+            (&["admin", "config", "fee_destination"], Trailing::None)
+        }
+        SlowPathInstruction::Heartbeat { .. } => {
+            (&["cranker", "envelope", "padding"], Trailing::None)
+        }
+        SlowPathInstruction::Close => (&["authority", "envelope", "recipient"], Trailing::None),
+        SlowPathInstruction::CloseWithSplit { .. } => (
+            &["authority", "envelope", "recipient", "treasury"],
+            Trailing::None,
+        ),
+        SlowPathInstruction::SetDelegatedProgram { .. }
+        | SlowPathInstruction::SetDelegatedProgramCompact { .. }
+        | SlowPathInstruction::SetDelegatedPda { .. }
+        | SlowPathInstruction::ClearDelegation => (
+            &["authority", "envelope", "delegation_authority"],
+            Trailing::None,
+        ),
+        SlowPathInstruction::SetWriteHook { .. } => (
+            &[
+                "authority",
+                "envelope",
+                "delegation_authority",
+                "hook_program",
+            ],
+            Trailing::None,
+        ),
+        SlowPathInstruction::TransferDelegation { .. } => (
+            &[
+                "authority",
+                "envelope",
+                "old_delegation_authority",
+                "new_delegation_authority",
+            ],
+            Trailing::None,
+        ),
+        SlowPathInstruction::UpdateAuxiliaryMultiRange { .. }
+        | SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 { .. } => (
+            &["authority", "envelope", "pda"],
+            Trailing::Optional("write_hook"),
+        ),
+        SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange { .. }
+        | SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRangeV2 { .. } => (
+            &["delegation_authority", "envelope", "padding"],
+            Trailing::Guards,
+        ),
+        SlowPathInstruction::UpdateAuxiliaryMultiRangeForce { .. }
+        | SlowPathInstruction::UpdateAuxiliaryMultiRangeForceV2 { .. } => (
+            &["authority", "envelope", "delegation_authority"],
+            Trailing::None,
+        ),
+        SlowPathInstruction::SetRangeGuards { .. } => (
+            &["authority", "envelope", "range_guard", "system_program"],
+            Trailing::None,
+        ),
+        SlowPathInstruction::SetConstraints { .. } => (
+            &["authority", "envelope", "constraints", "system_program"],
+            Trailing::None,
+        ),
+        SlowPathInstruction::SetPublishers { .. } => (
+            &["authority", "envelope", "publisher_set", "system_program"],
+            Trailing::None,
+        ),
+        SlowPathInstruction::SetReaders { .. } => (
+            &["authority", "envelope", "reader_list", "system_program"],
+            Trailing::None,
+        ),
+        SlowPathInstruction::ReadOracleGated => (
+            &["envelope", "reader_list", "instructions_sysvar"],
+            Trailing::None,
+        ),
+        SlowPathInstruction::SetDelegateSchema { .. } => (
+            &[
+                "delegation_authority",
+                "envelope",
+                "delegate_schema",
+                "payer",
+                "system_program",
+            ],
+            Trailing::None,
+        ),
+        SlowPathInstruction::SetPermanent
+        | SlowPathInstruction::SetMutationGuard { .. }
+        | SlowPathInstruction::UpdateExtAux { .. }
+        | SlowPathInstruction::SetTwap { .. }
+        | SlowPathInstruction::SetUpdateStamp { .. }
+        | SlowPathInstruction::SetConflation { .. }
+        | SlowPathInstruction::SetPublisher { .. }
+        | SlowPathInstruction::SetPaused { .. }
+        | SlowPathInstruction::SealAuxiliaryRange { .. }
+        | SlowPathInstruction::SetOracleMetadata { .. } => {
+            (&["authority", "envelope", "padding"], Trailing::None)
+        }
+        SlowPathInstruction::ReadAuxRange { .. }
+        | SlowPathInstruction::AssertAuxHash { .. }
+        | SlowPathInstruction::ReadOracle
+        | SlowPathInstruction::ReadAux
+        | SlowPathInstruction::ExportState => (&["envelope"], Trailing::None),
+        SlowPathInstruction::SetGroupMembers { .. } => (
+            &["authority", "group_anchor", "system_program"],
+            Trailing::Repeat("member"),
+        ),
+        SlowPathInstruction::GroupCommit { .. } => {
+            (&["authority", "group_anchor"], Trailing::Repeat("member"))
+        }
+        // A constrained envelope's table sits at index 2; the decoder cannot tell.
+        SlowPathInstruction::Aggregate { .. } => {
+            (&["authority", "envelope"], Trailing::Repeat("source"))
+        }
+        SlowPathInstruction::SetLabel { .. } => (
+            &["authority", "envelope", "label", "system_program"],
+            Trailing::None,
+        ),
+    }
+}
+
+fn assign_roles(fixed: &[&'static str], trailing: Trailing, count: usize) -> Vec<&'static str> {
+    let mut roles: Vec<&'static str> = fixed.iter().copied().take(count).collect();
+    let extra = count - roles.len();
+    match trailing {
+        Trailing::None => roles.extend(core::iter::repeat_n("unused", extra)),
+        Trailing::Repeat(role) => roles.extend(core::iter::repeat_n(role, extra)),
+        Trailing::Guards => match extra {
+            0 => {}
+            1 => roles.push("instructions_sysvar"),
+            _ => {
+                roles.push("range_guard");
+                roles.push("instructions_sysvar");
+                roles.extend(core::iter::repeat_n("unused", extra - 2));
+            }
+        },
+        Trailing::Optional(role) => roles.extend(
+            [role]
+                .into_iter()
+                .chain(core::iter::repeat("unused"))
+                .take(extra),
+        ),
+    }
+    roles
+}
+
+/// Re-derive the PDA an instruction names by seeds and bump, and warn if the account differs.
+fn check_pdas(
+    program_id: &Address,
+    ix: &SlowPathInstruction,
+    accounts: &[Address],
+    warnings: &mut Vec<String>,
+) {
+    let mut check = |index: usize, role: &str, seeds: &[&[u8]]| {
+        let Some(account) = accounts.get(index) else {
+            return;
+        };
+        match Address::create_program_address(seeds, program_id) {
+            Ok(derived) if &derived == account => {}
+            _ => warnings.push(format!("{role} is not the PDA of the instruction's seeds")),
+        }
+    };
+    match ix {
+        SlowPathInstruction::Create {
+            custom_seeds, bump, ..
+        }
+        | SlowPathInstruction::CreateWithEvents {
+            custom_seeds, bump, ..
+        } => {
+            let Some(authority) = accounts.first() else {
+                return;
+            };
+            let bump = [*bump];
+            let mut seeds: Vec<&[u8]> = vec![ENVELOPE_SEED, authority.as_ref()];
+            seeds.extend(custom_seeds.iter().map(Vec::as_slice));
+            seeds.push(&bump);
+            check(1, "envelope", &seeds);
+            if accounts
+                .get(3)
+                .is_some_and(|config| config != &derive_config_address(program_id).0)
+            {
+                warnings.push(NOT_CONFIG.to_string());
+            }
+        }
+        SlowPathInstruction::InitConfig { bump, .. } => {
+            check(1, "config", &[CONFIG_SEED, &[*bump]]);
+        }
+        SlowPathInstruction::SetRangeGuards { bump, .. } => {
+            if let Some(envelope) = accounts.get(1) {
+                check(
+                    2,
+                    "range_guard",
+                    &[RANGE_GUARD_SEED, envelope.as_ref(), &[*bump]],
+                );
+            }
+        }
+        SlowPathInstruction::SetDelegateSchema { bump, .. } => {
+            if let Some(envelope) = accounts.get(1) {
+                check(
+                    2,
+                    "delegate_schema",
+                    &[DELEGATE_SCHEMA_SEED, envelope.as_ref(), &[*bump]],
+                );
+            }
+        }
+        SlowPathInstruction::SetConstraints { bump, .. } => {
+            if let Some(envelope) = accounts.get(1) {
+                check(
+                    2,
+                    "constraints",
+                    &[CONSTRAINTS_SEED, envelope.as_ref(), &[*bump]],
+                );
+            }
+        }
+        SlowPathInstruction::SetPublishers { bump, .. } => {
+            if let Some(envelope) = accounts.get(1) {
+                check(
+                    2,
+                    "publisher_set",
+                    &[PUBLISHERS_SEED, envelope.as_ref(), &[*bump]],
+                );
+            }
+        }
+        SlowPathInstruction::SetReaders { bump, .. } => {
+            if let Some(envelope) = accounts.get(1) {
+                check(
+                    2,
+                    "reader_list",
+                    &[READERS_SEED, envelope.as_ref(), &[*bump]],
+                );
+            }
+        }
+        SlowPathInstruction::SetDelegatedPda {
+            program_id: delegate_program,
+            seeds,
+            bump,
+            ..
+        } => {
+            let bump = [*bump];
+            let mut seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+            seeds.push(&bump);
+            let delegate_program = Address::new_from_array(*delegate_program);
+            if let Some(delegate) = accounts.get(2) {
+                match Address::create_program_address(&seeds, &delegate_program) {
+                    Ok(derived) if &derived == delegate => {}
+                    _ => warnings.push(
+                        "delegation_authority is not the PDA of the instruction's seeds"
+                            .to_string(),
+                    ),
+                }
+            }
+        }
+        SlowPathInstruction::SetLabel { bump, .. } => {
+            if let Some(envelope) = accounts.get(1) {
+                check(2, "label", &[LABEL_SEED, envelope.as_ref(), &[*bump]]);
+            }
+        }
+        SlowPathInstruction::SetGroupMembers { group_id, bump } => {
+            if let Some(authority) = accounts.first() {
+                let group_id = group_id.to_le_bytes();
+                check(
+                    1,
+                    "group_anchor",
+                    &[GROUP_ANCHOR_SEED, authority.as_ref(), &group_id, &[*bump]],
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+fn u64_at(data: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(data[at..at + 8].try_into().unwrap())
+}
+
+fn metadata(raw: u64) -> String {
+    let meta = StructMetadata::from_raw(raw);
+    format!(
+        "0x{raw:016x} (size {}, hash 0x{:014x})",
+        meta.type_size(),
+        meta.hash_56()
+    )
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn bytes(data: &[u8]) -> String {
+    if data.len() <= MAX_PRINTED_BYTES {
+        format!("0x{} ({} bytes)", hex(data), data.len())
+    } else {
+        format!(
+            "0x{}.. ({} bytes)",
+            hex(&data[..MAX_PRINTED_BYTES]),
+            data.len()
+        )
+    }
+}
+
+fn range<R: WriteRange>(spec: &R) -> (&'static str, String) {
+    (
+        "range",
+        format!("offset {}, data {}", spec.start(), bytes(spec.bytes())),
+    )
+}
+
+fn mask(mask: &[u8]) -> String {
+    let writable = mask
+        .iter()
+        .filter(|&&b| Permission::from_byte(b) == Some(Permission::Writable))
+        .count();
+    format!("{writable} of {} bytes writable", mask.len())
+}
+
+/// As [`mask`], for a `CompactMask`'s one bit per byte.
+fn compact_mask(bits: &[u8]) -> String {
+    let writable: u32 = bits.iter().map(|b| b.count_ones()).sum();
+    format!("{writable} of {} bytes writable", bits.len() * 8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        batch_update_instruction_data, create_instruction_data, fast_path_instruction_data,
+        publisher_update_instruction_data, set_delegated_pda_instruction_data,
+        set_delegated_program_instruction_data, set_publishers_instruction_data,
+        update_auxiliary_delegated_instruction_data,
+    };
+    use c_u_soon::{CompactMask, Mask, TypeHash, MASK_SIZE};
+    use c_u_soon_instruction::{BatchEntry, WriteSpecV2, INSTRUCTION_VERSION};
+
+    fn address(byte: u8) -> Address {
+        Address::new_from_array([byte; 32])
+    }
+
+    fn roles(decoded: &DecodedCuSoonInstruction) -> Vec<&'static str> {
+        decoded.accounts.iter().map(|a| a.role).collect()
+    }
+
+    /// `accounts` followed by the program config, as the program requires.
+    fn with_config(program: &Address, accounts: &[Address]) -> Vec<Address> {
+        let mut accounts = accounts.to_vec();
+        accounts.push(derive_config_address(program).0);
+        accounts
+    }
+
+    #[test]
+    fn two_accounts_always_decode_as_fast_path() {
+        let program = address(9);
+        let data = fast_path_instruction_data(u64::METADATA.as_u64(), 7, &[1, 2, 3]).unwrap();
+        let decoded = decode_instruction(&program, &[address(1), address(2)], &data).unwrap();
+        let DecodedParams::FastPath {
+            oracle_meta,
+            sequence,
+            payload,
+        } = &decoded.params
+        else {
+            panic!("expected fast path, got {}", decoded.params.name());
+        };
+        assert_eq!(*oracle_meta, u64::METADATA.as_u64());
+        assert_eq!(*sequence, 7);
+        assert_eq!(payload, &[1, 2, 3]);
+        assert_eq!(roles(&decoded), ["authority", "envelope"]);
+        assert!(decoded.warnings.is_empty());
+
+        // A slow-path tag sent with two accounts is still routed to the fast path.
+        let close = crate::close_instruction_data().unwrap();
+        let padded = [close, vec![0; 12]].concat();
+        let decoded = decode_instruction(&program, &[address(1), address(2)], &padded).unwrap();
+        assert_eq!(decoded.params.name(), "FastPath");
+    }
+
+    #[test]
+    fn delegated_write_labels_trailing_guard_accounts() {
+        let program = address(9);
+        let data = update_auxiliary_delegated_instruction_data(u64::METADATA.as_u64(), 3, &[0; 8]);
+        let accounts = [address(1), address(2), address(3), address(4), address(5)];
+
+        let decoded =
+            decode_instruction(&program, &with_config(&program, &accounts[..4]), &data).unwrap();
+        assert_eq!(decoded.params.name(), "UpdateAuxiliaryDelegated");
+        assert_eq!(
+            roles(&decoded),
+            [
+                "delegation_authority",
+                "envelope",
+                "padding",
+                "instructions_sysvar",
+                "config"
+            ]
+        );
+
+        let decoded =
+            decode_instruction(&program, &with_config(&program, &accounts), &data).unwrap();
+        assert_eq!(
+            roles(&decoded),
+            [
+                "delegation_authority",
+                "envelope",
+                "padding",
+                "range_guard",
+                "instructions_sysvar",
+                "config"
+            ]
+        );
+    }
+
+    #[test]
+    fn create_checks_envelope_pda() {
+        let program = address(9);
+        let authority = address(1);
+        let (envelope, bump) =
+            Address::find_program_address(&[ENVELOPE_SEED, authority.as_ref(), b"btc"], &program);
+        let seeds: &[&[u8]] = &[b"btc"];
+        let data = create_instruction_data(seeds, bump, u64::METADATA).unwrap();
+
+        let accounts = with_config(&program, &[authority, envelope, address(0)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "Create");
+        assert_eq!(
+            roles(&decoded),
+            ["authority", "envelope", "system_program", "config"]
+        );
+        assert!(decoded.warnings.is_empty(), "{:?}", decoded.warnings);
+        let printed = decoded.to_string();
+        assert!(printed.starts_with("Create\n"));
+        assert!(printed.contains("seed: 0x627463 (3 bytes)"));
+        assert!(printed.contains(&format!("#1 envelope: {envelope}")));
+
+        let decoded = decode_instruction(
+            &program,
+            &[authority, address(7), address(0), address(8)],
+            &data,
+        )
+        .unwrap();
+        assert_eq!(
+            decoded.warnings,
+            [
+                "envelope is not the PDA of the instruction's seeds",
+                NOT_CONFIG
+            ]
+        );
+
+        // The config is required, even before InitConfig.
+        let decoded =
+            decode_instruction(&program, &[authority, envelope, address(0)], &data).unwrap();
+        assert_eq!(
+            decoded.warnings,
+            ["Create reads at least 4 accounts, got 3"]
+        );
+    }
+
+    #[test]
+    fn set_delegated_pda_checks_delegate_against_its_program() {
+        let program = address(9);
+        let delegate_program = address(5);
+        let (delegate, bump) = Address::find_program_address(&[b"writer"], &delegate_program);
+        let data = set_delegated_pda_instruction_data(
+            delegate_program.to_bytes(),
+            &[b"writer"],
+            bump,
+            Mask::ALL_WRITABLE,
+            Mask::ALL_BLOCKED,
+        )
+        .unwrap();
+
+        let accounts = with_config(&program, &[address(1), address(2), delegate]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "SetDelegatedPda");
+        assert_eq!(
+            roles(&decoded),
+            ["authority", "envelope", "delegation_authority", "config"]
+        );
+        assert!(decoded.warnings.is_empty(), "{:?}", decoded.warnings);
+        assert_eq!(
+            decoded.params.fields()[0],
+            ("program_id", delegate_program.to_string())
+        );
+
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(
+            decoded.warnings,
+            ["delegation_authority is not the PDA of the instruction's seeds"]
+        );
+    }
+
+    #[test]
+    fn warnings_for_rejected_but_parseable_instructions() {
+        let program = address(9);
+        let mut data =
+            set_delegated_program_instruction_data(Mask::ALL_BLOCKED, Mask::ALL_BLOCKED).unwrap();
+        // Corrupt one program_bitmask byte so validate() fails.
+        data[4] = 0x01;
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "SetDelegatedProgram");
+        assert_eq!(decoded.warnings.len(), 1);
+        assert!(decoded.params.fields()[0]
+            .1
+            .ends_with(&format!("of {MASK_SIZE} bytes writable")));
+
+        let entries = [
+            BatchEntry {
+                oracle_meta: 1,
+                sequence: 2,
+                payload: &[3],
+            },
+            BatchEntry {
+                oracle_meta: 4,
+                sequence: 5,
+                payload: &[],
+            },
+        ];
+        let data = batch_update_instruction_data(&entries).unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert!(decoded.warnings.is_empty());
+        assert_eq!(
+            roles(&decoded),
+            ["authority", "envelope", "envelope", "config"]
+        );
+
+        // Extra accounts may be constraint tables; missing envelopes are reported.
+        let accounts = with_config(&program, &[address(1), address(2), address(3), address(4)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert!(decoded.warnings.is_empty());
+        let three = [entries[0], entries[1], entries[1]];
+        let data = batch_update_instruction_data(&three).unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(
+            decoded.warnings,
+            ["batch has 3 entries but 2 envelope accounts"]
+        );
+    }
+
+    #[test]
+    fn split_update_halves() {
+        let program = address(9);
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let [first, second] = crate::split_update_instruction_data(7, 4, &[5; 9]).unwrap();
+        for (data, part) in [(first, "1 of 2"), (second, "2 of 2")] {
+            let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+            assert_eq!(decoded.params.name(), "SplitUpdate");
+            assert_eq!(
+                roles(&decoded),
+                ["authority", "envelope", "instructions_sysvar", "config"]
+            );
+            assert_eq!(decoded.params.fields()[2], ("part", part.to_string()));
+            assert!(decoded.warnings.is_empty());
+        }
+    }
+
+    #[test]
+    fn publisher_instructions() {
+        let program = address(9);
+        let envelope = address(2);
+        let data = publisher_update_instruction_data(u64::METADATA.as_u64(), 4, &[5; 8]).unwrap();
+        let accounts = with_config(&program, &[envelope, address(3), address(4), address(5)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "PublisherUpdate");
+        assert_eq!(
+            roles(&decoded),
+            [
+                "envelope",
+                "publisher_set",
+                "publisher",
+                "publisher",
+                "config"
+            ]
+        );
+        assert_eq!(decoded.params.fields()[1], ("sequence", "4".to_string()));
+
+        let (set, bump) =
+            Address::find_program_address(&[PUBLISHERS_SEED, envelope.as_ref()], &program);
+        let data = set_publishers_instruction_data(bump, 1, &[[4; 32]]).unwrap();
+        let accounts = with_config(&program, &[address(1), envelope, set, address(0)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "SetPublishers");
+        assert!(decoded.warnings.is_empty(), "{:?}", decoded.warnings);
+        assert_eq!(
+            decoded.params.fields()[2],
+            ("publisher", address(4).to_string())
+        );
+
+        let accounts = with_config(&program, &[address(1), envelope, address(6), address(0)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(
+            decoded.warnings,
+            ["publisher_set is not the PDA of the instruction's seeds"]
+        );
+    }
+
+    #[test]
+    fn aggregate_names_sources() {
+        let program = address(9);
+        let data = crate::aggregate_instruction_data(7, 2, 150).unwrap();
+        let accounts = with_config(
+            &program,
+            &[address(1), address(2), address(3), address(4), address(5)],
+        );
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "Aggregate");
+        assert!(decoded.warnings.is_empty());
+        assert_eq!(
+            roles(&decoded),
+            [
+                "authority",
+                "envelope",
+                "source",
+                "source",
+                "source",
+                "config"
+            ]
+        );
+        assert_eq!(
+            decoded.params.fields()[2],
+            ("max_deviation_bps", "150".to_string())
+        );
+    }
+
+    #[test]
+    fn set_publisher_names_key() {
+        let program = address(9);
+        let data = crate::set_publisher_instruction_data(address(4).to_bytes()).unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "SetPublisher");
+        assert_eq!(
+            roles(&decoded),
+            ["authority", "envelope", "padding", "config"]
+        );
+        assert_eq!(
+            decoded.params.fields(),
+            [("publisher", address(4).to_string())]
+        );
+    }
+
+    #[test]
+    fn create_with_events_checks_envelope_pda() {
+        let program = address(9);
+        let authority = address(1);
+        let (envelope, bump) = crate::find_envelope_address(&program, &authority, &[b"feed"]);
+        let data =
+            crate::create_with_events_instruction_data(&[b"feed"], bump, u64::METADATA).unwrap();
+        let accounts = with_config(&program, &[authority, envelope, address(0)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "CreateWithEvents");
+        assert_eq!(
+            roles(&decoded),
+            ["authority", "envelope", "system_program", "config"]
+        );
+        assert!(decoded.warnings.is_empty(), "{:?}", decoded.warnings);
+
+        let wrong = decode_instruction(
+            &program,
+            &with_config(&program, &[authority, address(2), address(0)]),
+            &data,
+        )
+        .unwrap();
+        assert_eq!(
+            wrong.warnings,
+            ["envelope is not the PDA of the instruction's seeds"]
+        );
+    }
+
+    #[test]
+    fn set_paused_shows_flag() {
+        let program = address(9);
+        let data = crate::set_paused_instruction_data(true).unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "SetPaused");
+        assert_eq!(
+            roles(&decoded),
+            ["authority", "envelope", "padding", "config"]
+        );
+        assert_eq!(decoded.params.fields(), [("paused", "true".to_string())]);
+    }
+
+    #[test]
+    fn heartbeat_shows_sequence() {
+        let program = address(9);
+        let data = crate::heartbeat_instruction_data(12).unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "Heartbeat");
+        assert_eq!(
+            roles(&decoded),
+            ["cranker", "envelope", "padding", "config"]
+        );
+        assert_eq!(decoded.params.fields(), [("sequence", "12".to_string())]);
+    }
+
+    #[test]
+    fn export_state_has_only_envelope() {
+        let program = address(9);
+        let data = crate::export_state_instruction_data().unwrap();
+        let decoded = decode_instruction(&program, &[address(1)], &data).unwrap();
+        assert_eq!(decoded.params.name(), "ExportState");
+        assert_eq!(roles(&decoded), ["envelope"]);
+        assert!(decoded.params.fields().is_empty());
+    }
+
+    #[test]
+    fn migrate_envelope_names_payer() {
+        let program = address(9);
+        let data = crate::migrate_envelope_instruction_data().unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "MigrateEnvelope");
+        assert_eq!(
+            roles(&decoded),
+            ["payer", "envelope", "system_program", "config"]
+        );
+        assert!(decoded.params.fields().is_empty());
+        assert!(decoded.warnings.is_empty());
+    }
+
+    #[test]
+    fn set_delegated_program_compact_counts_bits() {
+        let program = address(9);
+        let mut bits = [0u8; 32];
+        bits[0] = 0b1011;
+        let data = crate::set_delegated_program_compact_instruction_data(
+            CompactMask::from_bytes(bits),
+            CompactMask::FULL,
+        )
+        .unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "SetDelegatedProgramCompact");
+        assert_eq!(
+            roles(&decoded),
+            ["authority", "envelope", "delegation_authority", "config"]
+        );
+        assert_eq!(
+            decoded.params.fields(),
+            [
+                ("program_bitmask", "3 of 256 bytes writable".to_string()),
+                ("user_bitmask", "256 of 256 bytes writable".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn set_write_hook_roles_and_hooked_writes() {
+        let program = address(9);
+        let data = crate::set_write_hook_instruction_data(true).unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3), address(4)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "SetWriteHook");
+        assert_eq!(
+            roles(&decoded),
+            [
+                "authority",
+                "envelope",
+                "delegation_authority",
+                "hook_program",
+                "config"
+            ]
+        );
+        assert_eq!(
+            decoded.params.fields(),
+            [("notify_on_authority_write", "true".to_string())]
+        );
+
+        let data = crate::update_auxiliary_instruction_data(0, 1, &[]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(
+            roles(&decoded),
+            ["authority", "envelope", "pda", "write_hook", "config"]
+        );
+    }
+
+    #[test]
+    fn set_oracle_metadata_shows_both_metadata() {
+        let program = address(9);
+        let data = crate::set_oracle_metadata_instruction_data(u64::METADATA, StructMetadata::ZERO)
+            .unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "SetOracleMetadata");
+        assert_eq!(
+            roles(&decoded),
+            ["authority", "envelope", "padding", "config"]
+        );
+        assert_eq!(
+            decoded.params.fields(),
+            [
+                ("oracle_metadata", metadata(u64::METADATA.as_u64())),
+                ("auxiliary_metadata", metadata(0)),
+            ]
+        );
+        assert!(decoded.warnings.is_empty());
+    }
+
+    #[test]
+    fn multi_range_v2_shows_u16_offsets() {
+        let program = address(9);
+        let ranges = [WriteSpecV2 {
+            offset: 250,
+            data: vec![0xAB; 2],
+        }];
+        let data = crate::update_auxiliary_delegated_multi_range_v2_instruction_data(
+            u64::METADATA.as_u64(),
+            4,
+            &ranges,
+        )
+        .unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(
+            decoded.params.name(),
+            "UpdateAuxiliaryDelegatedMultiRangeV2"
+        );
+        assert_eq!(
+            roles(&decoded),
+            ["delegation_authority", "envelope", "padding", "config"]
+        );
+        assert_eq!(
+            decoded.params.fields()[2],
+            ("range", "offset 250, data 0xabab (2 bytes)".to_string())
+        );
+    }
+
+    #[test]
+    fn transfer_delegation_shows_masks() {
+        let program = address(9);
+        let data =
+            crate::transfer_delegation_instruction_data(Mask::ALL_WRITABLE, Mask::ALL_BLOCKED)
+                .unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3), address(4)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "TransferDelegation");
+        assert_eq!(
+            roles(&decoded),
+            [
+                "authority",
+                "envelope",
+                "old_delegation_authority",
+                "new_delegation_authority",
+                "config"
+            ]
+        );
+        let fields = decoded.params.fields();
+        assert_eq!(fields[0].0, "program_bitmask");
+        assert_eq!(fields[1].0, "user_bitmask");
+        assert!(decoded.warnings.is_empty());
+    }
+
+    #[test]
+    fn seal_auxiliary_range_shows_range() {
+        let program = address(9);
+        let data = crate::seal_auxiliary_range_instruction_data(16, 2).unwrap();
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "SealAuxiliaryRange");
+        assert_eq!(
+            roles(&decoded),
+            ["authority", "envelope", "padding", "config"]
+        );
+        assert_eq!(
+            decoded.params.fields(),
+            [("offset", "16".to_string()), ("len", "2".to_string())]
+        );
+        assert!(decoded.warnings.is_empty());
+    }
+
+    #[test]
+    fn config_instructions_check_config_pda() {
+        let program = address(9);
+        let (config, bump) = Address::find_program_address(&[CONFIG_SEED], &program);
+        let data = crate::init_config_instruction_data(bump, [1; 32], 10).unwrap();
+        let accounts = [address(1), config, address(0), program, address(3)];
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "InitConfig");
+        assert_eq!(
+            roles(&decoded),
+            [
+                "upgrade_authority",
+                "config",
+                "system_program",
+                "program",
+                "program_data"
+            ]
+        );
+        assert!(decoded.warnings.is_empty(), "{:?}", decoded.warnings);
+
+        let accounts = [address(1), address(2), address(0), program, address(3)];
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(
+            decoded.warnings,
+            ["config is not the PDA of the instruction's seeds"]
+        );
+
+        let data = crate::set_config_instruction_data([1; 32], 0, true).unwrap();
+        let decoded =
+            decode_instruction(&program, &[address(1), config, address(4)], &data).unwrap();
+        assert_eq!(decoded.params.name(), "SetConfig");
+        assert_eq!(roles(&decoded), ["admin", "config", "fee_destination"]);
+        assert_eq!(decoded.params.fields()[2], ("paused", "true".to_string()));
+    }
+
+    #[test]
+    fn trailing_config_is_labelled() {
+        let program = address(9);
+        let config = derive_config_address(&program).0;
+        let data = crate::heartbeat_instruction_data(3).unwrap();
+        let decoded = decode_instruction(
+            &program,
+            &[address(1), address(2), address(0), config],
+            &data,
+        )
+        .unwrap();
+        assert_eq!(
+            roles(&decoded),
+            ["cranker", "envelope", "padding", "config"]
+        );
+        assert!(decoded.warnings.is_empty(), "{:?}", decoded.warnings);
+
+        // The last account is taken as the config whatever it is.
+        let decoded = decode_instruction(
+            &program,
+            &[address(1), address(2), address(0), address(3)],
+            &data,
+        )
+        .unwrap();
+        assert_eq!(
+            roles(&decoded),
+            ["cranker", "envelope", "padding", "config"]
+        );
+        assert_eq!(decoded.warnings, [NOT_CONFIG]);
+
+        // The program strips the config first, so it does not stand in for the padding.
+        let decoded =
+            decode_instruction(&program, &[address(1), address(2), config], &data).unwrap();
+        assert_eq!(roles(&decoded), ["cranker", "envelope", "config"]);
+        assert_eq!(
+            decoded.warnings,
+            ["Heartbeat reads at least 3 accounts, got 2"]
+        );
+
+        // Read-only instructions never take it.
+        let data = crate::read_oracle_instruction_data().unwrap();
+        let decoded =
+            decode_instruction(&program, &[address(2), address(0), config], &data).unwrap();
+        assert_eq!(roles(&decoded), ["envelope", "unused", "unused"]);
+    }
+
+    #[test]
+    fn undecodable_data() {
+        let program = address(9);
+        let three = [address(1), address(2), address(3)];
+        assert_eq!(
+            decode_instruction(&program, &[address(1), address(2)], &[0; 15]).unwrap_err(),
+            DecodeError::Truncated
+        );
+        assert_eq!(
+            decode_instruction(&program, &three, &[4, 0, 0]).unwrap_err(),
+            DecodeError::Truncated
+        );
+        assert_eq!(
+            decode_instruction(&program, &three, &48u32.to_le_bytes()).unwrap_err(),
+            DecodeError::UnknownTag(48)
+        );
+        let mut close = crate::close_instruction_data().unwrap();
+        close.push(0);
+        assert_eq!(
+            decode_instruction(&program, &three, &close).unwrap_err(),
+            DecodeError::Malformed(1)
+        );
+    }
+
+    #[test]
+    fn versioned_instruction_decodes_inner() {
+        let program = address(9);
+        let accounts = with_config(&program, &[address(1), address(2), address(3)]);
+        let close = crate::close_instruction_data().unwrap();
+        let data = crate::versioned_instruction_data(&close);
+
+        let decoded = decode_instruction(&program, &accounts, &data).unwrap();
+        assert_eq!(decoded.params.name(), "Close");
+        assert_eq!(
+            roles(&decoded),
+            ["authority", "envelope", "recipient", "config"]
+        );
+
+        let newer = c_u_soon_instruction::versioned(INSTRUCTION_VERSION + 1, &close);
+        assert_eq!(
+            decode_instruction(&program, &accounts, &newer).unwrap_err(),
+            DecodeError::UnsupportedVersion(INSTRUCTION_VERSION + 1)
+        );
+        assert_eq!(
+            decode_instruction(
+                &program,
+                &accounts,
+                &crate::versioned_instruction_data(&data)
+            )
+            .unwrap_err(),
+            DecodeError::Malformed(VERSIONED_TAG)
+        );
+    }
+}
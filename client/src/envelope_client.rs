@@ -0,0 +1,265 @@
+//! Fetch-and-decode for envelope accounts.
+//!
+//! [`EnvelopeClient::fetch`] reads an envelope through any [`AccountSource`], checks that the
+//! account is owned by the c_u_soon program and large enough to be an envelope, and returns
+//! a [`DecodedEnvelope`] with typed oracle and aux accessors, the sequence counters, and the
+//! delegation masks, so callers never cast raw account bytes themselves.
+//!
+//! With the `rpc` feature, `solana_rpc_client::rpc_client::RpcClient` implements
+//! [`AccountSource`], so an `RpcClient` can be passed to [`EnvelopeClient::fetch`] directly.
+
+use bytemuck::Pod;
+use c_u_soon::{Envelope, Mask, TypeHash};
+use solana_address::Address;
+
+use crate::snapshot::{
+    fetch_account, AccountSource, EnvelopeSnapshot, FetchConfig, FetchError, StaleRead,
+};
+
+/// An envelope account that passed the owner and size checks, as of `context_slot`.
+#[derive(Debug, Clone, Copy)]
+pub struct DecodedEnvelope {
+    pub address: Address,
+    pub snapshot: EnvelopeSnapshot,
+}
+
+impl DecodedEnvelope {
+    /// Validate and parse raw account data read at `context_slot`.
+    ///
+    /// Fails with [`EnvelopeError::WrongOwner`] unless `owner` is `program_id`, and
+    /// [`EnvelopeError::InvalidAccountData`] if `data` is shorter than [`Envelope::SIZE`].
+    /// Extended auxiliary bytes past [`Envelope::SIZE`] are ignored.
+    pub fn from_account<E>(
+        program_id: &Address,
+        address: Address,
+        owner: &Address,
+        context_slot: u64,
+        data: &[u8],
+    ) -> Result<Self, EnvelopeError<E>> {
+        if owner != program_id {
+            return Err(EnvelopeError::WrongOwner { owner: *owner });
+        }
+        let snapshot = EnvelopeSnapshot::from_account_data(context_slot, data)
+            .ok_or(EnvelopeError::InvalidAccountData)?;
+        Ok(Self { address, snapshot })
+    }
+
+    /// The parsed envelope.
+    pub fn envelope(&self) -> &Envelope {
+        &self.snapshot.envelope
+    }
+
+    /// Slot the account was read at.
+    pub fn context_slot(&self) -> u64 {
+        self.snapshot.context_slot
+    }
+
+    /// The oracle value as `T`, or `None` if the envelope's oracle metadata is not
+    /// `T::METADATA`.
+    pub fn oracle<T: TypeHash + Pod>(&self) -> Option<&T> {
+        self.envelope().oracle()
+    }
+
+    /// The auxiliary value as `T`, or `None` if the envelope's aux metadata is not
+    /// `T::METADATA`.
+    pub fn aux<T: TypeHash + Pod>(&self) -> Option<&T> {
+        self.envelope().aux()
+    }
+
+    /// Sequence of the last oracle write.
+    pub fn oracle_sequence(&self) -> u64 {
+        self.envelope().oracle_state.sequence
+    }
+
+    /// Sequence of the last authority aux write.
+    pub fn authority_aux_sequence(&self) -> u64 {
+        self.envelope().authority_aux_sequence
+    }
+
+    /// Sequence of the last delegated-program aux write.
+    pub fn program_aux_sequence(&self) -> u64 {
+        self.envelope().program_aux_sequence
+    }
+
+    /// The delegated program's signer, or `None` if the envelope is not delegated.
+    pub fn delegation_authority(&self) -> Option<&Address> {
+        let envelope = self.envelope();
+        envelope
+            .has_delegation()
+            .then_some(&envelope.delegation_authority)
+    }
+
+    /// The key allowed to write the oracle in place of the authority, or `None` if unset.
+    pub fn publisher(&self) -> Option<&Address> {
+        let envelope = self.envelope();
+        (envelope.publisher != Address::default()).then_some(&envelope.publisher)
+    }
+
+    /// Aux bytes the delegated program may write.
+    pub fn program_bitmask(&self) -> &Mask {
+        &self.envelope().program_bitmask
+    }
+
+    /// Aux bytes the authority may write while delegation is active.
+    pub fn user_bitmask(&self) -> &Mask {
+        &self.envelope().user_bitmask
+    }
+}
+
+/// Errors from [`EnvelopeClient::fetch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvelopeError<E> {
+    /// The account source failed.
+    Source(E),
+    /// The envelope account does not exist.
+    NotFound,
+    /// The account is not owned by the program the client was built for.
+    WrongOwner { owner: Address },
+    /// The account is shorter than [`Envelope::SIZE`].
+    InvalidAccountData,
+    /// The source ignored `min_context_slot` and served an older read.
+    Stale(StaleRead),
+}
+
+impl<E> From<FetchError<E>> for EnvelopeError<E> {
+    fn from(error: FetchError<E>) -> Self {
+        match error {
+            FetchError::Source(e) => Self::Source(e),
+            FetchError::NotFound => Self::NotFound,
+            FetchError::InvalidAccountData => Self::InvalidAccountData,
+            FetchError::Stale(stale) => Self::Stale(stale),
+        }
+    }
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for EnvelopeError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Source(e) => write!(f, "account source: {e}"),
+            Self::NotFound => write!(f, "envelope account not found"),
+            Self::WrongOwner { owner } => write!(f, "account owned by {owner}, not c_u_soon"),
+            Self::InvalidAccountData => write!(f, "account too small for an envelope"),
+            Self::Stale(stale) => write!(f, "{stale}"),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> std::error::Error for EnvelopeError<E> {}
+
+/// Reads envelopes of one c_u_soon deployment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnvelopeClient {
+    pub program_id: Address,
+    pub config: FetchConfig,
+}
+
+impl EnvelopeClient {
+    /// Client for the program deployed at `program_id`, reading at the default commitment.
+    pub fn new(program_id: Address) -> Self {
+        Self {
+            program_id,
+            config: FetchConfig::default(),
+        }
+    }
+
+    /// Fetch `address` and decode it as an envelope.
+    ///
+    /// `config.min_context_slot` is passed to the source and also checked on the result, as
+    /// in [`fetch_envelope`](crate::fetch_envelope).
+    pub fn fetch<S: AccountSource>(
+        &self,
+        source: &S,
+        address: &Address,
+    ) -> Result<DecodedEnvelope, EnvelopeError<S::Error>> {
+        let account = fetch_account(source, address, &self.config)?;
+        DecodedEnvelope::from_account(
+            &self.program_id,
+            *address,
+            &account.owner,
+            account.context_slot,
+            &account.data,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::SlotAccount;
+    use bytemuck::Zeroable;
+
+    const PROGRAM: Address = Address::new_from_array([1; 32]);
+
+    struct Source(Option<SlotAccount>);
+
+    impl AccountSource for Source {
+        type Error = &'static str;
+
+        fn get_account(
+            &self,
+            _address: &Address,
+            _config: &FetchConfig,
+        ) -> Result<Option<SlotAccount>, Self::Error> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn account(owner: Address, data: Vec<u8>) -> Source {
+        Source(Some(SlotAccount {
+            context_slot: 50,
+            owner,
+            data,
+        }))
+    }
+
+    fn envelope_bytes() -> Vec<u8> {
+        let mut envelope = Envelope::zeroed();
+        envelope.oracle_state.oracle_metadata = u64::METADATA;
+        envelope.oracle_state.sequence = 9;
+        envelope.oracle_state.data[..8].copy_from_slice(&42u64.to_le_bytes());
+        envelope.authority_aux_sequence = 3;
+        envelope.user_bitmask = Mask::ALL_WRITABLE;
+        bytemuck::bytes_of(&envelope).to_vec()
+    }
+
+    #[test]
+    fn fetch_decodes_typed_values() {
+        let client = EnvelopeClient::new(PROGRAM);
+        let address = Address::new_from_array([2; 32]);
+        let decoded = client
+            .fetch(&account(PROGRAM, envelope_bytes()), &address)
+            .unwrap();
+        assert_eq!(decoded.address, address);
+        assert_eq!(decoded.context_slot(), 50);
+        assert_eq!(decoded.oracle::<u64>(), Some(&42));
+        assert!(decoded.aux::<u64>().is_none());
+        assert_eq!(decoded.oracle_sequence(), 9);
+        assert_eq!(decoded.authority_aux_sequence(), 3);
+        assert_eq!(decoded.delegation_authority(), None);
+        assert_eq!(decoded.publisher(), None);
+        assert_eq!(decoded.user_bitmask(), &Mask::ALL_WRITABLE);
+    }
+
+    #[test]
+    fn fetch_rejects_foreign_and_short_accounts() {
+        let client = EnvelopeClient::new(PROGRAM);
+        let address = Address::default();
+        let other = Address::new_from_array([3; 32]);
+        assert_eq!(
+            client
+                .fetch(&account(other, envelope_bytes()), &address)
+                .unwrap_err(),
+            EnvelopeError::WrongOwner { owner: other }
+        );
+        assert_eq!(
+            client
+                .fetch(&account(PROGRAM, vec![0; Envelope::SIZE - 1]), &address)
+                .unwrap_err(),
+            EnvelopeError::InvalidAccountData
+        );
+        assert_eq!(
+            client.fetch(&Source(None), &address).unwrap_err(),
+            EnvelopeError::NotFound
+        );
+    }
+}
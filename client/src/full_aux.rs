@@ -0,0 +1,112 @@
+//! Full-buffer auxiliary writes that respect the envelope's bitmasks.
+//!
+//! `UpdateAuxiliary` and `UpdateAuxiliaryDelegated` rewrite the whole aux struct and fail if
+//! any blocked byte differs from its current on-chain value. Serializing a desired struct
+//! directly usually trips that check on bytes the writer does not own.
+//! [`prepare_full_aux_write`] merges the desired struct with the current state so only
+//! writable bytes change.
+
+use bytemuck::Pod;
+use c_u_soon::{Envelope, Mask, AUX_DATA_SIZE};
+
+/// Which side of a delegated envelope is writing, selecting the bitmask that applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteRole {
+    /// The oracle authority (`UpdateAuxiliary`), gated by `user_bitmask`.
+    Authority,
+    /// The delegated program (`UpdateAuxiliaryDelegated`), gated by `program_bitmask`.
+    Program,
+}
+
+impl WriteRole {
+    /// The bitmask gating this role's writes on `envelope`.
+    pub fn mask(self, envelope: &Envelope) -> &Mask {
+        match self {
+            WriteRole::Authority => &envelope.user_bitmask,
+            WriteRole::Program => &envelope.program_bitmask,
+        }
+    }
+}
+
+/// Aux buffer holding `desired` in every byte `role` may write and the current value in every
+/// blocked byte.
+///
+/// Bytes past `size_of::<T>()` are copied from `current`. Send the first `size_of::<T>()`
+/// bytes as the update data. Desired values for blocked bytes are silently replaced; compare
+/// the result against `desired` if that should be an error. Only valid against the same
+/// `current` state the transaction will see: a concurrent write to a blocked byte still
+/// makes the update fail.
+pub fn prepare_full_aux_write<T: Pod>(
+    current: &Envelope,
+    desired: &T,
+    role: WriteRole,
+) -> [u8; AUX_DATA_SIZE] {
+    const {
+        assert!(
+            core::mem::size_of::<T>() <= AUX_DATA_SIZE,
+            "aux type exceeds AUX_DATA_SIZE"
+        )
+    };
+    let mask = role.mask(current);
+    let mut buf = current.auxiliary_data;
+    for (i, &byte) in bytemuck::bytes_of(desired).iter().enumerate() {
+        if mask.is_writable(i) {
+            buf[i] = byte;
+        }
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn envelope_with_masks() -> Envelope {
+        let mut envelope = Envelope::zeroed();
+        envelope.auxiliary_data = [0x11; AUX_DATA_SIZE];
+        envelope.program_bitmask = Mask::ALL_BLOCKED;
+        envelope.user_bitmask = Mask::ALL_BLOCKED;
+        // Program owns bytes 0..8, the authority owns 8..16.
+        for i in 0..8 {
+            envelope.program_bitmask.allow(i);
+            envelope.user_bitmask.allow(8 + i);
+        }
+        envelope
+    }
+
+    #[test]
+    fn keeps_blocked_bytes_from_current_state() {
+        let envelope = envelope_with_masks();
+        let desired = [0xAAu8; 16];
+
+        let program = prepare_full_aux_write(&envelope, &desired, WriteRole::Program);
+        assert_eq!(program[..8], [0xAA; 8]);
+        assert_eq!(program[8..], [0x11; AUX_DATA_SIZE - 8]);
+
+        let authority = prepare_full_aux_write(&envelope, &desired, WriteRole::Authority);
+        assert_eq!(authority[..8], [0x11; 8]);
+        assert_eq!(authority[8..16], [0xAA; 8]);
+        assert_eq!(authority[16..], [0x11; AUX_DATA_SIZE - 16]);
+    }
+
+    #[test]
+    fn result_passes_mask_check() {
+        let envelope = envelope_with_masks();
+        let desired = [0x5Au8; 64];
+        for role in [WriteRole::Authority, WriteRole::Program] {
+            let buf = prepare_full_aux_write(&envelope, &desired, role);
+            assert!(role.mask(&envelope).check_masked_update(
+                &envelope.auxiliary_data,
+                0,
+                &buf[..64]
+            ));
+            // The raw desired bytes would have been rejected.
+            assert!(!role.mask(&envelope).check_masked_update(
+                &envelope.auxiliary_data,
+                0,
+                &desired
+            ));
+        }
+    }
+}
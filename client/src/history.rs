@@ -0,0 +1,401 @@
+//! Compact archival encoding for per-slot envelope snapshots.
+//!
+//! An indexer that keeps every snapshot it reads stores 1128 bytes per slot, most of them
+//! identical to the previous snapshot: a fast-path write changes the sequence and a few
+//! payload bytes, and slots without writes change nothing. [`compress_envelope_history`]
+//! stores each snapshot as a delta against the one before it:
+//!
+//! ```text
+//! version        u8 (HISTORY_FORMAT_VERSION)
+//! count          varint
+//! per snapshot:
+//!   slot delta                    zigzag varint
+//!   oracle sequence delta         zigzag varint
+//!   authority aux sequence delta  zigzag varint
+//!   program aux sequence delta    zigzag varint
+//!   changed runs until the envelope is covered:
+//!     unchanged byte count        varint
+//!     changed byte count          varint
+//!     changed bytes
+//! ```
+//!
+//! The three sequence counters are left out of the byte diff, so an ordinary write costs one
+//! byte for its sequence step plus the payload bytes it actually changed. The first snapshot
+//! is diffed against a zeroed envelope at slot 0. Deltas wrap, so any input order
+//! round-trips; out-of-order input just compresses worse.
+
+use c_u_soon::Envelope;
+use core::ops::Range;
+
+use crate::EnvelopeSnapshot;
+
+/// Format byte written at the start of every history.
+pub const HISTORY_FORMAT_VERSION: u8 = 1;
+
+const ORACLE_SEQUENCE: Range<usize> = field_range(
+    core::mem::offset_of!(Envelope, oracle_state)
+        + core::mem::offset_of!(c_u_soon::OracleState, sequence),
+);
+const AUTHORITY_AUX_SEQUENCE: Range<usize> =
+    field_range(core::mem::offset_of!(Envelope, authority_aux_sequence));
+const PROGRAM_AUX_SEQUENCE: Range<usize> =
+    field_range(core::mem::offset_of!(Envelope, program_aux_sequence));
+const SEQUENCES: [Range<usize>; 3] = [
+    ORACLE_SEQUENCE,
+    AUTHORITY_AUX_SEQUENCE,
+    PROGRAM_AUX_SEQUENCE,
+];
+
+/// Unchanged bytes shorter than this are folded into the surrounding changed run, since a
+/// new run header would cost as much as the bytes it skips.
+const MIN_GAP: usize = 3;
+
+const fn field_range(offset: usize) -> Range<usize> {
+    offset..offset + 8
+}
+
+/// Errors from [`decompress_envelope_history`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryError {
+    /// The version byte is not [`HISTORY_FORMAT_VERSION`].
+    UnsupportedVersion(u8),
+    /// The input ends in the middle of a snapshot.
+    Truncated,
+    /// A varint is longer than 10 bytes or overflows a `u64`.
+    InvalidVarint,
+    /// A run extends past [`Envelope::SIZE`].
+    RunOutOfBounds,
+    /// Bytes remain after the last snapshot.
+    TrailingBytes,
+}
+
+impl core::fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedVersion(v) => write!(f, "unsupported history format version {v}"),
+            Self::Truncated => write!(f, "history ends mid-snapshot"),
+            Self::InvalidVarint => write!(f, "malformed varint"),
+            Self::RunOutOfBounds => write!(f, "run extends past the envelope"),
+            Self::TrailingBytes => write!(f, "trailing bytes after the last snapshot"),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+/// Encode `snapshots` in order. See the [module docs](self) for the format.
+pub fn compress_envelope_history(snapshots: Vec<EnvelopeSnapshot>) -> Vec<u8> {
+    let mut out = vec![HISTORY_FORMAT_VERSION];
+    write_varint(&mut out, snapshots.len() as u64);
+
+    let mut prev_slot = 0u64;
+    let mut prev = [0u8; Envelope::SIZE];
+    for snapshot in &snapshots {
+        let cur = bytemuck::bytes_of(&snapshot.envelope);
+        let mut diffed = [0u8; Envelope::SIZE];
+        diffed.copy_from_slice(cur);
+
+        write_varint(
+            &mut out,
+            zigzag(snapshot.context_slot.wrapping_sub(prev_slot)),
+        );
+        for range in SEQUENCES {
+            let delta = read_u64(cur, &range).wrapping_sub(read_u64(&prev, &range));
+            write_varint(&mut out, zigzag(delta));
+            // Already stored as a delta; keep the counter out of the byte diff.
+            diffed[range.clone()].copy_from_slice(&prev[range]);
+        }
+        write_runs(&mut out, &prev, &diffed);
+
+        prev_slot = snapshot.context_slot;
+        prev.copy_from_slice(cur);
+    }
+    out
+}
+
+/// Decode a history produced by [`compress_envelope_history`].
+pub fn decompress_envelope_history(bytes: &[u8]) -> Result<Vec<EnvelopeSnapshot>, HistoryError> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let version = reader.byte()?;
+    if version != HISTORY_FORMAT_VERSION {
+        return Err(HistoryError::UnsupportedVersion(version));
+    }
+    let count = reader.varint()?;
+    // Every snapshot takes at least 6 bytes, so a larger count cannot be honest and must not
+    // size the allocation.
+    let mut snapshots = Vec::with_capacity((count as usize).min(bytes.len() / 6));
+
+    let mut slot = 0u64;
+    let mut envelope = [0u8; Envelope::SIZE];
+    for _ in 0..count {
+        slot = slot.wrapping_add(unzigzag(reader.varint()?));
+        let mut sequences = [0u64; 3];
+        for (sequence, range) in sequences.iter_mut().zip(SEQUENCES) {
+            *sequence = read_u64(&envelope, &range).wrapping_add(unzigzag(reader.varint()?));
+        }
+        reader.runs(&mut envelope)?;
+        for (sequence, range) in sequences.iter().zip(SEQUENCES) {
+            envelope[range].copy_from_slice(&sequence.to_le_bytes());
+        }
+        snapshots.push(EnvelopeSnapshot {
+            context_slot: slot,
+            envelope: bytemuck::pod_read_unaligned(&envelope),
+        });
+    }
+    if reader.pos != bytes.len() {
+        return Err(HistoryError::TrailingBytes);
+    }
+    Ok(snapshots)
+}
+
+fn write_runs(out: &mut Vec<u8>, prev: &[u8], cur: &[u8]) {
+    let mut changed = (0..cur.len()).filter(|&i| cur[i] != prev[i]).peekable();
+    let mut pos = 0;
+    while let Some(start) = changed.next() {
+        let mut end = start + 1;
+        while let Some(next) = changed.next_if(|&next| next - end < MIN_GAP) {
+            end = next + 1;
+        }
+        write_varint(out, (start - pos) as u64);
+        write_varint(out, (end - start) as u64);
+        out.extend_from_slice(&cur[start..end]);
+        pos = end;
+    }
+    if pos < cur.len() {
+        write_varint(out, (cur.len() - pos) as u64);
+        write_varint(out, 0);
+    }
+}
+
+fn read_u64(bytes: &[u8], range: &Range<usize>) -> u64 {
+    u64::from_le_bytes(bytes[range.clone()].try_into().unwrap())
+}
+
+fn zigzag(delta: u64) -> u64 {
+    let delta = delta as i64;
+    ((delta << 1) ^ (delta >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> u64 {
+    (value >> 1) ^ (value & 1).wrapping_neg()
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, HistoryError> {
+        let byte = *self.bytes.get(self.pos).ok_or(HistoryError::Truncated)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<u64, HistoryError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7F) as u64;
+            if bits << shift >> shift != bits {
+                return Err(HistoryError::InvalidVarint);
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(HistoryError::InvalidVarint)
+    }
+
+    fn len(&mut self) -> Result<usize, HistoryError> {
+        usize::try_from(self.varint()?)
+            .ok()
+            .filter(|&len| len <= Envelope::SIZE)
+            .ok_or(HistoryError::RunOutOfBounds)
+    }
+
+    /// Apply changed runs to `envelope` until every byte is covered.
+    fn runs(&mut self, envelope: &mut [u8; Envelope::SIZE]) -> Result<(), HistoryError> {
+        let mut pos = 0;
+        while pos < Envelope::SIZE {
+            let start = pos + self.len()?;
+            let end = start + self.len()?;
+            if end > Envelope::SIZE || (end == start && start != Envelope::SIZE) {
+                return Err(HistoryError::RunOutOfBounds);
+            }
+            let literal = self
+                .bytes
+                .get(self.pos..self.pos + (end - start))
+                .ok_or(HistoryError::Truncated)?;
+            envelope[start..end].copy_from_slice(literal);
+            self.pos += end - start;
+            pos = end;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn zeroed_snapshot() -> EnvelopeSnapshot {
+        EnvelopeSnapshot {
+            context_slot: 0,
+            envelope: Envelope::zeroed(),
+        }
+    }
+
+    /// `len` snapshots of one feed: one fast-path write per slot (sequence +1, an 8-byte
+    /// price), a skipped slot every 7, and an aux write every 50.
+    fn corpus(len: u64) -> Vec<EnvelopeSnapshot> {
+        let mut envelope = Envelope::zeroed();
+        envelope.authority = solana_address::Address::new_from_array([7; 32]);
+        envelope.oracle_state.oracle_metadata = c_u_soon::StructMetadata::of::<u64>();
+        envelope.user_bitmask = c_u_soon::Mask::ALL_WRITABLE;
+        let mut price = 1_000_000u64;
+        (0..len)
+            .map(|i| {
+                let slot = 300_000_000 + i + i / 7;
+                if i % 7 != 6 {
+                    envelope.oracle_state.sequence += 1;
+                    price = price.wrapping_add(i * 37 % 101).wrapping_sub(50);
+                    envelope.oracle_state.data[..8].copy_from_slice(&price.to_le_bytes());
+                }
+                if i % 50 == 49 {
+                    envelope.authority_aux_sequence += 1;
+                    envelope.auxiliary_data[..4].copy_from_slice(&(i as u32).to_le_bytes());
+                    envelope.aux_write_count = envelope.aux_write_count.wrapping_add(1);
+                    envelope.aux_dirty_blocks = 1;
+                }
+                EnvelopeSnapshot {
+                    context_slot: slot,
+                    envelope,
+                }
+            })
+            .collect()
+    }
+
+    fn assert_same(a: &[EnvelopeSnapshot], b: &[EnvelopeSnapshot]) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b) {
+            assert_eq!(a.context_slot, b.context_slot);
+            assert_eq!(
+                bytemuck::bytes_of(&a.envelope),
+                bytemuck::bytes_of(&b.envelope)
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let snapshots = corpus(200);
+        let bytes = compress_envelope_history(snapshots.clone());
+        assert_same(&decompress_envelope_history(&bytes).unwrap(), &snapshots);
+
+        let empty = compress_envelope_history(Vec::new());
+        assert_eq!(empty, [HISTORY_FORMAT_VERSION, 0]);
+        assert!(decompress_envelope_history(&empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn round_trips_out_of_order_and_extreme_values() {
+        let mut a = zeroed_snapshot();
+        a.context_slot = u64::MAX;
+        a.envelope.oracle_state.sequence = u64::MAX;
+        a.envelope.program_aux_sequence = 5;
+        a.envelope.auxiliary_data = [0xFF; 256];
+        let mut b = zeroed_snapshot();
+        b.context_slot = 1;
+        b.envelope.authority_aux_sequence = u64::MAX;
+        // Changes one byte apart, merged into a single run.
+        b.envelope.oracle_state.data[0] = 1;
+        b.envelope.oracle_state.data[2] = 1;
+        let snapshots = vec![a, b, a, a];
+        let bytes = compress_envelope_history(snapshots.clone());
+        assert_same(&decompress_envelope_history(&bytes).unwrap(), &snapshots);
+    }
+
+    #[test]
+    fn unchanged_snapshot_costs_seven_bytes() {
+        let mut snapshots = corpus(1);
+        snapshots.push(snapshots[0]);
+        let one = compress_envelope_history(snapshots[..1].to_vec()).len();
+        let two = compress_envelope_history(snapshots).len();
+        // Four zero deltas, then one run: 1200 unchanged (2-byte varint) and 0 changed.
+        assert_eq!(two - one, 7);
+    }
+
+    #[test]
+    fn corpus_compresses_well() {
+        let snapshots = corpus(10_000);
+        let raw = snapshots.len() * (8 + Envelope::SIZE);
+        let bytes = compress_envelope_history(snapshots.clone());
+        assert!(
+            bytes.len() * 50 < raw,
+            "{} bytes for {raw} raw bytes",
+            bytes.len()
+        );
+        assert_same(&decompress_envelope_history(&bytes).unwrap(), &snapshots);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let bytes = compress_envelope_history(corpus(3));
+        assert_eq!(
+            decompress_envelope_history(&[]).unwrap_err(),
+            HistoryError::Truncated
+        );
+        assert_eq!(
+            decompress_envelope_history(&[2, 0]).unwrap_err(),
+            HistoryError::UnsupportedVersion(2)
+        );
+        assert_eq!(
+            decompress_envelope_history(&bytes[..bytes.len() - 1]).unwrap_err(),
+            HistoryError::Truncated
+        );
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            decompress_envelope_history(&trailing).unwrap_err(),
+            HistoryError::TrailingBytes
+        );
+
+        let mut overlong = vec![HISTORY_FORMAT_VERSION];
+        overlong.extend([0xFF; 10]);
+        overlong.push(0x01);
+        assert_eq!(
+            decompress_envelope_history(&overlong).unwrap_err(),
+            HistoryError::InvalidVarint
+        );
+
+        // One snapshot, zero deltas, a run starting past the envelope.
+        let mut out_of_bounds = vec![HISTORY_FORMAT_VERSION, 1, 0, 0, 0, 0];
+        write_varint(&mut out_of_bounds, Envelope::SIZE as u64);
+        write_varint(&mut out_of_bounds, 1);
+        out_of_bounds.push(0);
+        assert_eq!(
+            decompress_envelope_history(&out_of_bounds).unwrap_err(),
+            HistoryError::RunOutOfBounds
+        );
+    }
+
+    #[test]
+    fn zigzag_round_trips() {
+        for delta in [0, 1, u64::MAX, 2, u64::MAX - 1, 1 << 63, i64::MAX as u64] {
+            assert_eq!(unzigzag(zigzag(delta)), delta);
+        }
+        assert_eq!(zigzag(1), 2);
+        assert_eq!(zigzag(u64::MAX), 1);
+    }
+}
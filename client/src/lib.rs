@@ -7,24 +7,183 @@
 //!
 //! All functions return `Vec<u8>` to pass as transaction instruction data. The `_typed`
 //! variants take a `T: TypeHash` and read `T::METADATA` so you don't pass it manually.
+//!
+//! [`parse_failure_logs`] goes the other way: it decodes a failed transaction's logs into a
+//! [`CuSoonError`] plus the index of the failing instruction.
+//!
+//! The `build_*_ix` functions ([`build_create_ix`], [`build_fast_path_ix`], ...) return a
+//! complete `solana_instruction::Instruction` with its accounts in the order the program
+//! reads them, deriving the envelope PDA where needed. Every state-changing slow-path
+//! instruction except `InitConfig` and `SetConfig` also takes the program config PDA:
+//! `Create` and `CreateWithEvents` as their fourth account, the others as their last
+//! (see [`requires_trailing_config`](c_u_soon_instruction::requires_trailing_config)). The
+//! builders add it; the `Accounts:` lists of the `*_instruction_data` functions leave the
+//! trailing config out.
+//!
+//! [`decode_instruction`] classifies any c_u_soon instruction, fast path or slow path, labels
+//! its accounts by role, and pretty-prints its parameters for incident response and indexers.
+//!
+//! [`fetch_envelope`] and [`ReadTracker`] read envelopes with their RPC context slot and
+//! reject reads that lag the newest slot seen, so sequences are not computed from stale state.
+//!
+//! [`EnvelopeClient`] fetches an envelope, checks its owner and size, and returns a
+//! [`DecodedEnvelope`] with typed oracle and aux values, sequences, and masks. The `rpc`
+//! feature lets it read through `solana_rpc_client::rpc_client::RpcClient`.
+//!
+//! [`AuxCache`] keeps a local copy of auxiliary data in sync by fetching only the 32-byte
+//! blocks the last write touched.
+//!
+//! [`prepare_full_aux_write`] builds a full aux buffer that keeps current values in every
+//! byte the writer's bitmask blocks, so whole-struct updates do not fail the mask check.
+//!
+//! [`compress_envelope_history`] and [`decompress_envelope_history`] store runs of envelope
+//! snapshots as deltas keyed on the sequence counters, for indexers that keep every slot.
+//!
+//! [`SequenceTracker`] caches the fast-path sequence of each envelope and hands out the next
+//! one, re-reading it from the chain per its [`RefreshPolicy`];
+//! [`fast_path_update_auto`](SequenceTracker::fast_path_update_auto) also sends the write and
+//! retries it after a stale-sequence rejection.
+//!
+//! [`PdaCache`] remembers envelope addresses and bumps, in memory or on disk, so they are
+//! not re-derived with `find_program_address` on every run.
+//!
+//! [`RetryPolicy`] is the shared exponential backoff with jitter for anything that resends
+//! transactions or re-reads accounts; [`Retryable`] classifies which errors are worth a retry.
+//!
+//! [`UnsignedTransaction`] compiles administrative instructions into an unsigned wire
+//! transaction with a placeholder blockhash, so authority keys can sign offline.
+//!
+//! With the `schema` feature, [`schema_metadata`] computes a type's `StructMetadata` from a
+//! JSON description of its fields, for clients without the Rust type.
+//!
+//! With the `wasm` feature, `wasm` exports the instruction builders, masks, and schema type
+//! hashes to JavaScript through `wasm-bindgen`.
+//!
+//! With the `bench-e2e` feature, [`bench_e2e`] measures build, sign, simulate, and apply
+//! latency of fast-path updates under Mollusk.
 
-use c_u_soon::{Mask, StructMetadata, TypeHash, MAX_CUSTOM_SEEDS, ORACLE_BYTES};
+use bytemuck::NoUninit;
+use c_u_soon::{
+    is_valid_publisher_set, is_valid_reader_list, CompactMask, Constraint, Envelope, Mask,
+    PriceValue, StructMetadata, TypeHash, AUX_DATA_SIZE, LABEL_SIZE, MAX_AGGREGATE_SOURCES,
+    MAX_AUX_STRUCT_SIZE, MAX_CONSTRAINTS, MAX_CUSTOM_SEEDS, MAX_DELEGATE_SEEDS, MAX_EXT_AUX_SIZE,
+    MAX_GROUP_MEMBERS, MAX_PUBLISHERS, MAX_RANGE_GUARDS, MAX_READERS, ORACLE_BYTES,
+    ORACLE_MEMO_OFFSET, ORACLE_MEMO_SIZE, TINY_PAYLOAD_SIZE,
+};
 use c_u_soon_instruction::{
-    SlowPathInstruction, WriteSpec, UPDATE_AUX_DELEGATED_RANGE_TAG, UPDATE_AUX_DELEGATED_TAG,
+    ranges_overlap, versioned, BatchEntry, BuildError, ConstraintSpec, GroupMemberUpdate,
+    RangeGuardSpec, SetDelegationBuilder, SlowPathInstruction, WriteRange, WriteSpec, WriteSpecV2,
+    BATCH_ENTRY_HEADER_SIZE, BATCH_UPDATE_HEADER_SIZE, BATCH_UPDATE_TAG, INSTRUCTION_VERSION,
+    MAX_BATCH_SIZE, MAX_TREASURY_BPS, MAX_WRITE_RANGES, PUBLISHER_UPDATE_HEADER_SIZE,
+    PUBLISHER_UPDATE_TAG, SPLIT_UPDATE_HEADER_SIZE, SPLIT_UPDATE_TAG,
+    UPDATE_AUX_DELEGATED_RANGE_TAG, UPDATE_AUX_DELEGATED_SCHEMA_TAG, UPDATE_AUX_DELEGATED_TAG,
     UPDATE_AUX_FORCE_TAG, UPDATE_AUX_RANGE_TAG, UPDATE_AUX_TAG,
 };
 
+mod aux_diff;
+#[cfg(feature = "bench-e2e")]
+pub mod bench_e2e;
+mod builders;
+mod decode;
+mod envelope_client;
+mod full_aux;
+mod history;
+mod offline;
+mod pda_cache;
+mod program_error;
+mod retry;
+#[cfg(feature = "rpc")]
+mod rpc;
+mod sequence;
+mod snapshot;
+#[cfg(feature = "schema")]
+mod type_schema;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use aux_diff::{dirty_block_slices, parse_aux_diff_header, AuxCache, AUX_DIFF_HEADER};
+pub use builders::{
+    append_config_account, build_clear_delegation_ix, build_close_ix, build_create_ix,
+    build_create_with_events_ix, build_export_state_ix, build_fast_path_ix, build_heartbeat_ix,
+    build_init_config_ix, build_migrate_envelope_ix, build_read_oracle_ix,
+    build_seal_auxiliary_range_ix, build_set_config_ix, build_set_delegated_program_compact_ix,
+    build_set_delegated_program_ix, build_set_oracle_metadata_ix, build_set_paused_ix,
+    build_set_publisher_ix, build_set_write_hook_ix, build_split_update_ixs,
+    build_transfer_delegation_ix, build_update_auxiliary_ix, find_envelope_address,
+};
+pub use decode::{
+    decode_instruction, DecodeError, DecodedAccount, DecodedBatchEntry, DecodedCuSoonInstruction,
+    DecodedParams,
+};
+pub use envelope_client::{DecodedEnvelope, EnvelopeClient, EnvelopeError};
+pub use full_aux::{prepare_full_aux_write, WriteRole};
+pub use history::{
+    compress_envelope_history, decompress_envelope_history, HistoryError, HISTORY_FORMAT_VERSION,
+};
+pub use offline::{OfflineError, UnsignedTransaction, BLOCKHASH_PLACEHOLDER, MAX_TRANSACTION_SIZE};
+pub use pda_cache::PdaCache;
+pub use program_error::{parse_failure_logs, CuSoonError, ProgramFailure};
+pub use retry::{RetryPolicy, Retryable};
+pub use sequence::{InstructionSender, RefreshPolicy, SequenceError, SequenceTracker, UpdateError};
+pub use snapshot::{
+    fetch_envelope, AccountSource, Commitment, EnvelopeSnapshot, FetchConfig, FetchError,
+    ReadTracker, SlotAccount, StaleRead,
+};
+#[cfg(feature = "schema")]
+pub use type_schema::schema_metadata;
+
 /// Errors returned by instruction builders.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InstructionError {
     /// Payload exceeds [`ORACLE_BYTES`] (239) bytes.
     PayloadTooLarge,
-    /// `custom_seeds` has more than [`MAX_CUSTOM_SEEDS`] (13) entries.
+    /// `custom_seeds` has more than [`MAX_CUSTOM_SEEDS`] (13) entries, or delegate seeds more
+    /// than [`MAX_DELEGATE_SEEDS`] (15).
     TooManySeeds,
     /// A seed is longer than 32 bytes.
     SeedTooLong,
     /// A mask byte is not `0x00` (writable) or `0xFF` (blocked).
     NonCanonicalMask,
+    /// More than [`MAX_RANGE_GUARDS`] (4) range guard entries.
+    TooManyRangeGuards,
+    /// A range guard is empty or extends past [`AUX_DATA_SIZE`] (256).
+    InvalidRangeGuard,
+    /// Delegate schema type is zero-sized or extends past [`AUX_DATA_SIZE`] (256).
+    InvalidDelegateSchema,
+    /// Multi-range update has more than [`MAX_WRITE_RANGES`] (16) ranges.
+    TooManyRanges,
+    /// Two ranges of a multi-range update write the same byte (see [`ranges_overlap`]).
+    OverlappingRanges,
+    /// Aux read range or V2 write range is empty or extends past [`AUX_DATA_SIZE`] (256),
+    /// or a typed write range is empty or extends past the value it is taken from.
+    InvalidAuxRange,
+    /// Group has zero or more than [`MAX_GROUP_MEMBERS`] (8) members.
+    InvalidGroupSize,
+    /// Batch is empty or has more than [`MAX_BATCH_SIZE`] (63) entries.
+    InvalidBatchSize,
+    /// Label is empty, longer than [`LABEL_SIZE`] (64) bytes, or contains a NUL byte.
+    InvalidLabel,
+    /// Extended aux length exceeds [`MAX_EXT_AUX_SIZE`], or a write to it is empty or
+    /// extends past it.
+    InvalidExtAux,
+    /// More than [`MAX_CONSTRAINTS`] (4) constraints.
+    TooManyConstraints,
+    /// Constraint has an unknown region, predicate, or operand kind, a width other than
+    /// 1, 2, 4, or 8, or a field past the end of its region.
+    InvalidConstraint,
+    /// Publisher list has duplicate or zero addresses or more than [`MAX_PUBLISHERS`] (8),
+    /// or the threshold is outside `1..=len` (zero is required for an empty list).
+    InvalidPublisherSet,
+    /// Reader list has duplicate or zero program ids or more than [`MAX_READERS`] (8).
+    InvalidReaderList,
+    /// Treasury share exceeds [`MAX_TREASURY_BPS`] (10000) basis points.
+    InvalidTreasuryBps,
+    /// Aggregation `min_sources` is zero or above [`MAX_AGGREGATE_SOURCES`] (16).
+    InvalidMinSources,
+    /// Program config admin is the zero address.
+    InvalidConfigAdmin,
+    /// The seeds and bump do not derive a valid envelope PDA (the address is on the curve).
+    InvalidPdaBump,
     /// `wincode` serialization failed. Should not happen for valid inputs.
     SerializationFailed,
 }
@@ -33,9 +192,57 @@ impl core::fmt::Display for InstructionError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::PayloadTooLarge => write!(f, "payload exceeds {} bytes", ORACLE_BYTES),
-            Self::TooManySeeds => write!(f, "more than {} custom seeds", MAX_CUSTOM_SEEDS),
+            Self::TooManySeeds => write!(f, "too many seeds"),
             Self::SeedTooLong => write!(f, "seed exceeds 32 bytes"),
             Self::NonCanonicalMask => write!(f, "mask byte not 0x00 or 0xFF"),
+            Self::TooManyRangeGuards => {
+                write!(f, "more than {} range guards", MAX_RANGE_GUARDS)
+            }
+            Self::InvalidRangeGuard => {
+                write!(f, "range guard empty or past {} bytes", AUX_DATA_SIZE)
+            }
+            Self::InvalidDelegateSchema => {
+                write!(f, "delegate schema empty or past {} bytes", AUX_DATA_SIZE)
+            }
+            Self::TooManyRanges => write!(f, "more than {} write ranges", MAX_WRITE_RANGES),
+            Self::OverlappingRanges => write!(f, "write ranges overlap"),
+            Self::InvalidAuxRange => {
+                write!(f, "aux read range empty or past {} bytes", AUX_DATA_SIZE)
+            }
+            Self::InvalidGroupSize => {
+                write!(f, "group needs 1 to {} members", MAX_GROUP_MEMBERS)
+            }
+            Self::InvalidBatchSize => {
+                write!(f, "batch needs 1 to {} entries", MAX_BATCH_SIZE)
+            }
+            Self::InvalidLabel => {
+                write!(f, "label empty, past {} bytes, or contains NUL", LABEL_SIZE)
+            }
+            Self::InvalidExtAux => {
+                write!(f, "extended aux empty or past {} bytes", MAX_EXT_AUX_SIZE)
+            }
+            Self::TooManyConstraints => {
+                write!(f, "more than {} constraints", MAX_CONSTRAINTS)
+            }
+            Self::InvalidConstraint => write!(f, "constraint malformed or out of bounds"),
+            Self::InvalidPublisherSet => write!(
+                f,
+                "publisher set invalid: duplicates, over {} keys, or bad threshold",
+                MAX_PUBLISHERS
+            ),
+            Self::InvalidReaderList => write!(
+                f,
+                "reader list invalid: duplicates, zero keys, or over {} keys",
+                MAX_READERS
+            ),
+            Self::InvalidTreasuryBps => {
+                write!(f, "treasury share exceeds {} bps", MAX_TREASURY_BPS)
+            }
+            Self::InvalidMinSources => {
+                write!(f, "min sources must be 1 to {}", MAX_AGGREGATE_SOURCES)
+            }
+            Self::InvalidConfigAdmin => write!(f, "config admin is the zero address"),
+            Self::InvalidPdaBump => write!(f, "seeds and bump do not derive a PDA"),
             Self::SerializationFailed => write!(f, "wincode serialization failed"),
         }
     }
@@ -43,6 +250,21 @@ impl core::fmt::Display for InstructionError {
 
 impl std::error::Error for InstructionError {}
 
+/// Lets a builder returning [`InstructionError`] use `?` on the `c_u_soon_instruction`
+/// typed builders.
+impl From<BuildError> for InstructionError {
+    fn from(err: BuildError) -> Self {
+        match err {
+            BuildError::TooManySeeds => Self::TooManySeeds,
+            BuildError::SeedTooLong => Self::SeedTooLong,
+            BuildError::NonCanonicalMask => Self::NonCanonicalMask,
+            BuildError::InvalidRange | BuildError::NoRanges => Self::InvalidAuxRange,
+            BuildError::TooManyRanges => Self::TooManyRanges,
+            BuildError::OverlappingRanges => Self::OverlappingRanges,
+        }
+    }
+}
+
 /// Build fast-path instruction data: `[oracle_meta: u64 LE | sequence: u64 LE | payload]`.
 ///
 /// - `oracle_meta`: packed [`StructMetadata`] identifying the oracle's auxiliary type schema.
@@ -67,6 +289,121 @@ pub fn fast_path_instruction_data(
     Ok(data)
 }
 
+/// Build fast-path instruction data that binds a 32-byte `memo` to this update.
+///
+/// The payload is zero-padded to [`ORACLE_MEMO_OFFSET`] (199) bytes and followed by the
+/// trailer `[memo:32][sequence:8]`, for a full 255-byte frame. The program copies it like any
+/// other fast-path update; [`Envelope::oracle_memo`](c_u_soon::Envelope::oracle_memo) reports
+/// the memo only while `sequence` is the envelope's latest.
+///
+/// Returns [`InstructionError::PayloadTooLarge`] if `payload.len() > ORACLE_MEMO_OFFSET`.
+pub fn fast_path_instruction_data_with_memo(
+    oracle_meta: u64,
+    sequence: u64,
+    payload: &[u8],
+    memo: &[u8; ORACLE_MEMO_SIZE],
+) -> Result<Vec<u8>, InstructionError> {
+    if payload.len() > ORACLE_MEMO_OFFSET {
+        return Err(InstructionError::PayloadTooLarge);
+    }
+    let mut data = Vec::with_capacity(8 + 8 + ORACLE_BYTES);
+    data.extend_from_slice(&oracle_meta.to_le_bytes());
+    data.extend_from_slice(&sequence.to_le_bytes());
+    data.extend_from_slice(payload);
+    data.resize(8 + 8 + ORACLE_MEMO_OFFSET, 0);
+    data.extend_from_slice(memo);
+    data.extend_from_slice(&sequence.to_le_bytes());
+    Ok(data)
+}
+
+/// Build fast-path instruction data for the program's tiny lane.
+///
+/// The payload is zero-padded to [`TINY_PAYLOAD_SIZE`] (32) bytes. Instruction data of
+/// exactly that size is copied with word stores instead of a `sol_memcpy_` call, so the
+/// update costs `TINY_COMPUTE_BUDGET` rather than `COMPUTE_BUDGET`; the checks are the same.
+/// The padding overwrites any previous bytes up to offset 32.
+///
+/// Returns [`InstructionError::PayloadTooLarge`] if `payload.len() > TINY_PAYLOAD_SIZE`.
+pub fn fast_path_tiny_instruction_data(
+    oracle_meta: u64,
+    sequence: u64,
+    payload: &[u8],
+) -> Result<Vec<u8>, InstructionError> {
+    if payload.len() > TINY_PAYLOAD_SIZE {
+        return Err(InstructionError::PayloadTooLarge);
+    }
+    let mut data = Vec::with_capacity(8 + 8 + TINY_PAYLOAD_SIZE);
+    data.extend_from_slice(&oracle_meta.to_le_bytes());
+    data.extend_from_slice(&sequence.to_le_bytes());
+    data.extend_from_slice(payload);
+    data.resize(8 + 8 + TINY_PAYLOAD_SIZE, 0);
+    Ok(data)
+}
+
+/// Build batched oracle update instruction data (manual wire format).
+///
+/// Wire: `[disc:4][count:1]` then per entry `[oracle_meta:8][sequence:8][len:1][payload:len]`
+///
+/// Accounts: `[authority (signer), envelope_0 (writable), ..]`, one envelope per entry in the
+/// same order. Each entry is checked like a [`fast_path_instruction_data`] update, and the
+/// whole batch fails if any entry does.
+///
+/// Envelopes with oracle constraints also need their constraint table, see
+/// [`set_constraints_instruction_data`]. A batch of one is only accepted on-chain for such an
+/// envelope; send any other single update through the fast path.
+///
+/// Returns [`InstructionError::InvalidBatchSize`] unless there are 1 to [`MAX_BATCH_SIZE`]
+/// entries, or [`InstructionError::PayloadTooLarge`] if a payload exceeds [`ORACLE_BYTES`].
+pub fn batch_update_instruction_data(entries: &[BatchEntry]) -> Result<Vec<u8>, InstructionError> {
+    if !(1..=MAX_BATCH_SIZE).contains(&entries.len()) {
+        return Err(InstructionError::InvalidBatchSize);
+    }
+    if entries.iter().any(|e| e.payload.len() > ORACLE_BYTES) {
+        return Err(InstructionError::PayloadTooLarge);
+    }
+    let payload_bytes: usize = entries.iter().map(|e| e.payload.len()).sum();
+    let mut data = Vec::with_capacity(
+        BATCH_UPDATE_HEADER_SIZE + entries.len() * BATCH_ENTRY_HEADER_SIZE + payload_bytes,
+    );
+    data.extend_from_slice(&BATCH_UPDATE_TAG.to_le_bytes());
+    data.push(entries.len() as u8);
+    for entry in entries {
+        data.extend_from_slice(&entry.oracle_meta.to_le_bytes());
+        data.extend_from_slice(&entry.sequence.to_le_bytes());
+        data.push(entry.payload.len() as u8);
+        data.extend_from_slice(entry.payload);
+    }
+    Ok(data)
+}
+
+/// Build a publisher-signed oracle update (manual wire format).
+///
+/// Wire: `[disc:4][oracle_meta:8][sequence:8][payload]`, the [`fast_path_instruction_data`]
+/// layout behind [`PUBLISHER_UPDATE_TAG`]. It is checked and applied like a fast-path update
+/// and shares the envelope's oracle sequence.
+///
+/// Accounts: `[envelope (writable), publisher_set, publisher_0 (signer), ..]`, with at least
+/// the set's threshold of registered publishers signing (see
+/// [`set_publishers_instruction_data`]). An envelope with oracle constraints also takes its
+/// constraint table, between the publisher set and the publishers.
+///
+/// Returns [`InstructionError::PayloadTooLarge`] if `payload.len() > ORACLE_BYTES`.
+pub fn publisher_update_instruction_data(
+    oracle_meta: u64,
+    sequence: u64,
+    payload: &[u8],
+) -> Result<Vec<u8>, InstructionError> {
+    if payload.len() > ORACLE_BYTES {
+        return Err(InstructionError::PayloadTooLarge);
+    }
+    let mut data = Vec::with_capacity(PUBLISHER_UPDATE_HEADER_SIZE + payload.len());
+    data.extend_from_slice(&PUBLISHER_UPDATE_TAG.to_le_bytes());
+    data.extend_from_slice(&oracle_meta.to_le_bytes());
+    data.extend_from_slice(&sequence.to_le_bytes());
+    data.extend_from_slice(payload);
+    Ok(data)
+}
+
 /// Serialize a `Create` instruction (slow path): initialize an oracle PDA.
 ///
 /// - `custom_seeds`: up to [`MAX_CUSTOM_SEEDS`] (13) seeds, each ≤ 32 bytes.
@@ -81,33 +418,218 @@ pub fn create_instruction_data(
     bump: u8,
     oracle_metadata: StructMetadata,
 ) -> Result<Vec<u8>, InstructionError> {
-    if custom_seeds.len() > MAX_CUSTOM_SEEDS {
-        return Err(InstructionError::TooManySeeds);
-    }
-    for seed in custom_seeds {
-        if seed.len() > 32 {
-            return Err(InstructionError::SeedTooLong);
-        }
-    }
-    let seeds_vecs: Vec<Vec<u8>> = custom_seeds.iter().map(|s| s.to_vec()).collect();
     let ix = SlowPathInstruction::Create {
-        custom_seeds: seeds_vecs,
+        custom_seeds: owned_custom_seeds(custom_seeds)?,
+        bump,
+        oracle_metadata: oracle_metadata.as_u64(),
+    };
+    wincode::serialize(&ix).map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `CreateWithEvents` instruction (slow path): as [`create_instruction_data`],
+/// but every fast-path write to the new envelope logs a [`c_u_soon::OracleEvent`] for
+/// indexers. The setting cannot be changed later, and those writes cost more than
+/// `COMPUTE_BUDGET`.
+pub fn create_with_events_instruction_data(
+    custom_seeds: &[&[u8]],
+    bump: u8,
+    oracle_metadata: StructMetadata,
+) -> Result<Vec<u8>, InstructionError> {
+    let ix = SlowPathInstruction::CreateWithEvents {
+        custom_seeds: owned_custom_seeds(custom_seeds)?,
         bump,
         oracle_metadata: oracle_metadata.as_u64(),
     };
     wincode::serialize(&ix).map_err(|_| InstructionError::SerializationFailed)
 }
 
+fn owned_custom_seeds(custom_seeds: &[&[u8]]) -> Result<Vec<Vec<u8>>, InstructionError> {
+    if custom_seeds.len() > MAX_CUSTOM_SEEDS {
+        return Err(InstructionError::TooManySeeds);
+    }
+    if custom_seeds.iter().any(|seed| seed.len() > 32) {
+        return Err(InstructionError::SeedTooLong);
+    }
+    Ok(custom_seeds.iter().map(|s| s.to_vec()).collect())
+}
+
+/// Wrap slow-path instruction data with this crate's [`INSTRUCTION_VERSION`].
+///
+/// A program built for an older instruction set rejects the result with
+/// [`CuSoonError::UnsupportedVersion`] instead of misreading it. The wrapper costs 5 bytes.
+/// Fast-path data (two accounts) cannot be wrapped.
+pub fn versioned_instruction_data(instruction_data: &[u8]) -> Vec<u8> {
+    versioned(INSTRUCTION_VERSION, instruction_data)
+}
+
 /// Serialize a `Close` instruction (slow path): deallocate the oracle account.
 ///
-/// Blocked on-chain if delegation is active. Lamports are returned to the authority.
+/// Blocked on-chain if delegation is active or the envelope is permanent. Lamports are
+/// returned to the authority.
 pub fn close_instruction_data() -> Result<Vec<u8>, InstructionError> {
     wincode::serialize(&SlowPathInstruction::Close)
         .map_err(|_| InstructionError::SerializationFailed)
 }
 
+/// Serialize a `CloseWithSplit` instruction (slow path): close, splitting the lamports.
+///
+/// Accounts: `[authority (signer), envelope, recipient, treasury]`. `treasury` gets
+/// `treasury_bps` basis points of the envelope's lamports, rounded down, and `recipient` the
+/// rest. Returns [`InstructionError::InvalidTreasuryBps`] above [`MAX_TREASURY_BPS`].
+pub fn close_with_split_instruction_data(treasury_bps: u16) -> Result<Vec<u8>, InstructionError> {
+    if treasury_bps > MAX_TREASURY_BPS {
+        return Err(InstructionError::InvalidTreasuryBps);
+    }
+    wincode::serialize(&SlowPathInstruction::CloseWithSplit { treasury_bps })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetPermanent` instruction (slow path): irreversibly block `Close`.
+///
+/// Accounts: `[authority (signer), envelope, padding]`. There is no way to undo this.
+pub fn set_permanent_instruction_data() -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::SetPermanent)
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetPublisher` instruction (slow path): let `publisher` write the oracle.
+///
+/// Accounts: `[authority (signer), envelope, padding]`. The publisher key signs fast-path
+/// writes and `BatchUpdate` entries in place of the authority, but cannot close the envelope
+/// or change its delegation, so the authority key can be kept cold. An all-zero
+/// `publisher` removes it.
+pub fn set_publisher_instruction_data(publisher: [u8; 32]) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::SetPublisher { publisher })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetPaused` instruction (slow path): halt or resume every write.
+///
+/// Accounts: `[authority (signer), envelope, padding]`. While paused, oracle and aux writes
+/// fail with `ENVELOPE_PAUSED_ERROR`; reads and delegation state are unaffected.
+pub fn set_paused_instruction_data(paused: bool) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::SetPaused { paused })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SealAuxiliaryRange` instruction (slow path): make aux bytes immutable.
+///
+/// Accounts: `[authority (signer), envelope, padding]`. Once sealed,
+/// `auxiliary_data[offset..offset + len]` can never change again, whatever the bitmasks or
+/// delegation; write the value before sealing it. Returns
+/// [`InstructionError::InvalidAuxRange`] if `len` is zero or the range extends past
+/// [`AUX_DATA_SIZE`].
+pub fn seal_auxiliary_range_instruction_data(
+    offset: u16,
+    len: u16,
+) -> Result<Vec<u8>, InstructionError> {
+    if len == 0 || offset as usize + len as usize > AUX_DATA_SIZE {
+        return Err(InstructionError::InvalidAuxRange);
+    }
+    wincode::serialize(&SlowPathInstruction::SealAuxiliaryRange { offset, len })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize an `InitConfig` instruction (slow path): create the program config, once.
+///
+/// Accounts: `[upgrade_authority (writable signer), config (writable), system_program,
+/// program, program_data]`. Only the program's upgrade authority can call it; `bump` is the
+/// config PDA's (see [`c_u_soon::derive_config_address`]). Returns
+/// [`InstructionError::InvalidConfigAdmin`] if `admin` is zero.
+pub fn init_config_instruction_data(
+    bump: u8,
+    admin: [u8; 32],
+    create_fee: u64,
+) -> Result<Vec<u8>, InstructionError> {
+    if admin == [0u8; 32] {
+        return Err(InstructionError::InvalidConfigAdmin);
+    }
+    wincode::serialize(&SlowPathInstruction::InitConfig {
+        bump,
+        admin,
+        create_fee,
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetConfig` instruction (slow path): replace the config's admin, creation
+/// fee, and pause flag.
+///
+/// Accounts: `[admin (signer), config (writable), fee_destination]`. While `paused`, every
+/// state-changing instruction but `InitConfig` and `SetConfig` fails with
+/// `PROGRAM_PAUSED_ERROR` (see [`append_config_account`]). If `fee_destination` is writable,
+/// the fees collected so far move there. Returns [`InstructionError::InvalidConfigAdmin`] if
+/// `admin` is zero.
+pub fn set_config_instruction_data(
+    admin: [u8; 32],
+    create_fee: u64,
+    paused: bool,
+) -> Result<Vec<u8>, InstructionError> {
+    if admin == [0u8; 32] {
+        return Err(InstructionError::InvalidConfigAdmin);
+    }
+    wincode::serialize(&SlowPathInstruction::SetConfig {
+        admin,
+        create_fee,
+        paused,
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `Heartbeat` instruction (slow path): confirm that the oracle value at
+/// `sequence` is still current.
+///
+/// Accounts: `[cranker (signer), envelope (writable), padding]`. Any signer may crank it.
+/// Fails with `InvalidInstructionData` once the oracle has moved past `sequence`.
+pub fn heartbeat_instruction_data(sequence: u64) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::Heartbeat { sequence })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetMutationGuard` instruction (slow path).
+///
+/// Accounts: `[authority (signer), envelope, padding]`. With `allow_multi: false`, oracle,
+/// aux, and heartbeat writes must append the instructions sysvar and fail if the envelope
+/// was already written in their transaction; fast-path writes fail outright.
+pub fn set_mutation_guard_instruction_data(allow_multi: bool) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::SetMutationGuard { allow_multi })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetTwap` instruction (slow path).
+///
+/// Accounts: `[authority (signer), envelope, padding]`. With `enabled: true`, every oracle
+/// write must carry an 8 to [`TWAP_OFFSET`](c_u_soon::TWAP_OFFSET) byte payload whose first
+/// 8 bytes are the `u64` value to average; read the result with [`Envelope::twap`].
+pub fn set_twap_instruction_data(enabled: bool) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::SetTwap { enabled })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetUpdateStamp` instruction (slow path).
+///
+/// Accounts: `[authority (signer), envelope, padding]`. With `enabled: true`, every oracle
+/// write must fit in [`Envelope::oracle_capacity`] bytes and records its slot and unix
+/// timestamp; read them with [`Envelope::last_update_slot`] or bound the age of a read with
+/// [`Envelope::oracle_max_staleness`].
+pub fn set_update_stamp_instruction_data(enabled: bool) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::SetUpdateStamp { enabled })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetConflation` instruction (slow path).
+///
+/// Accounts: `[authority (signer), envelope, padding]`. With `enabled: true`, every oracle
+/// write must carry an 8 to [`CONFLATION_OFFSET`](c_u_soon::CONFLATION_OFFSET) byte payload
+/// whose first 8 bytes are the `u64` value to track; read the slot's extremes with
+/// [`Envelope::conflation`].
+pub fn set_conflation_instruction_data(enabled: bool) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::SetConflation { enabled })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
 fn validate_mask_canonical(mask: &Mask) -> Result<(), InstructionError> {
-    if !mask.as_bytes().iter().all(|&b| b == 0x00 || b == 0xFF) {
+    if !mask.is_canonical() {
         return Err(InstructionError::NonCanonicalMask);
     }
     Ok(())
@@ -124,9 +646,54 @@ pub fn set_delegated_program_instruction_data(
     program_bitmask: Mask,
     user_bitmask: Mask,
 ) -> Result<Vec<u8>, InstructionError> {
+    let ix = SetDelegationBuilder::new(&program_bitmask, &user_bitmask)?.build();
+    wincode::serialize(&ix).map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetDelegatedProgramCompact` instruction (slow path): as
+/// [`set_delegated_program_instruction_data`], with each mask packed into 32 bytes.
+///
+/// Any [`CompactMask`] is canonical, so this cannot fail on the masks; a set bit is a
+/// writable byte. The instruction data is 68 bytes instead of 516.
+pub fn set_delegated_program_compact_instruction_data(
+    program_bitmask: CompactMask,
+    user_bitmask: CompactMask,
+) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::SetDelegatedProgramCompact {
+        program_bitmask: program_bitmask.to_bytes(),
+        user_bitmask: user_bitmask.to_bytes(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetDelegatedPda` instruction (slow path): delegate to a PDA of another program.
+///
+/// - `program_id`: the program that derives, and signs for, the delegate.
+/// - `seeds`: up to [`MAX_DELEGATE_SEEDS`] (15) seeds, each ≤ 32 bytes, excluding `bump`.
+/// - `bump`: the delegate's bump, appended as the last seed.
+///
+/// The delegate account need not sign; the program checks it against the derived address.
+/// Returns [`InstructionError::TooManySeeds`], [`InstructionError::SeedTooLong`], or
+/// [`InstructionError::NonCanonicalMask`] on bad inputs.
+pub fn set_delegated_pda_instruction_data(
+    program_id: [u8; 32],
+    seeds: &[&[u8]],
+    bump: u8,
+    program_bitmask: Mask,
+    user_bitmask: Mask,
+) -> Result<Vec<u8>, InstructionError> {
+    if seeds.len() > MAX_DELEGATE_SEEDS {
+        return Err(InstructionError::TooManySeeds);
+    }
+    if seeds.iter().any(|seed| seed.len() > 32) {
+        return Err(InstructionError::SeedTooLong);
+    }
     validate_mask_canonical(&program_bitmask)?;
     validate_mask_canonical(&user_bitmask)?;
-    wincode::serialize(&SlowPathInstruction::SetDelegatedProgram {
+    wincode::serialize(&SlowPathInstruction::SetDelegatedPda {
+        program_id,
+        seeds: seeds.iter().map(|seed| seed.to_vec()).collect(),
+        bump,
         program_bitmask: program_bitmask.into(),
         user_bitmask: user_bitmask.into(),
     })
@@ -141,74 +708,533 @@ pub fn clear_delegation_instruction_data() -> Result<Vec<u8>, InstructionError>
         .map_err(|_| InstructionError::SerializationFailed)
 }
 
-/// Build `UpdateAuxiliary` instruction data (manual wire format).
-///
-/// Wire: `[disc:4][metadata:8][sequence:8][data:N]`
+/// Serialize a `TransferDelegation` instruction (slow path): hand the delegation to a new
+/// delegate with new bitmasks, keeping the oracle state and auxiliary data.
 ///
-/// `metadata` is `T::METADATA.as_u64()`. `sequence` must match the oracle's current
-/// authority sequence counter. `data` is the raw aux bytes (length = `type_size`).
-pub fn update_auxiliary_instruction_data(metadata: u64, sequence: u64, data: &[u8]) -> Vec<u8> {
-    let mut buf = Vec::with_capacity(20 + data.len());
-    buf.extend_from_slice(&UPDATE_AUX_TAG.to_le_bytes());
-    buf.extend_from_slice(&metadata.to_le_bytes());
-    buf.extend_from_slice(&sequence.to_le_bytes());
-    buf.extend_from_slice(data);
-    buf
+/// Accounts: `[authority (signer), envelope, old_delegate (signer), new_delegate (signer)]`.
+/// Both masks must be canonical, as for [`set_delegated_program_instruction_data`].
+/// Returns [`InstructionError::NonCanonicalMask`] otherwise.
+pub fn transfer_delegation_instruction_data(
+    program_bitmask: Mask,
+    user_bitmask: Mask,
+) -> Result<Vec<u8>, InstructionError> {
+    let ix = SetDelegationBuilder::new(&program_bitmask, &user_bitmask)?.build_transfer();
+    wincode::serialize(&ix).map_err(|_| InstructionError::SerializationFailed)
 }
 
-/// Build `UpdateAuxiliaryForce` instruction data (manual wire format).
+/// Serialize a `SetWriteHook` instruction (slow path): have authority aux writes call the
+/// delegated program, or stop them.
 ///
-/// Wire: `[disc:4][metadata:8][auth_seq:8][prog_seq:8][data:N]`
-pub fn update_auxiliary_force_instruction_data(
-    metadata: u64,
-    authority_sequence: u64,
-    program_sequence: u64,
-    data: &[u8],
-) -> Vec<u8> {
-    let mut buf = Vec::with_capacity(28 + data.len());
-    buf.extend_from_slice(&UPDATE_AUX_FORCE_TAG.to_le_bytes());
-    buf.extend_from_slice(&metadata.to_le_bytes());
-    buf.extend_from_slice(&authority_sequence.to_le_bytes());
-    buf.extend_from_slice(&program_sequence.to_le_bytes());
-    buf.extend_from_slice(data);
-    buf
+/// Accounts: `[authority (signer), envelope, delegation_authority (signer), hook_program]`.
+/// With `notify_on_authority_write`, `hook_program` must be executable; every later
+/// `UpdateAuxiliary` and `UpdateAuxiliaryMultiRange` then passes it as a fourth account and
+/// fails if it does. Without it, `hook_program` is not read.
+pub fn set_write_hook_instruction_data(
+    notify_on_authority_write: bool,
+) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::SetWriteHook {
+        notify_on_authority_write,
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
 }
 
-/// Build `UpdateAuxiliaryDelegated` instruction data (manual wire format).
+/// Serialize a `SetOracleMetadata` instruction (slow path): re-type the envelope in place.
 ///
-/// Wire: `[disc:4][metadata:8][sequence:8][data:N]`
-pub fn update_auxiliary_delegated_instruction_data(
-    metadata: u64,
-    sequence: u64,
-    data: &[u8],
-) -> Vec<u8> {
-    let mut buf = Vec::with_capacity(20 + data.len());
-    buf.extend_from_slice(&UPDATE_AUX_DELEGATED_TAG.to_le_bytes());
-    buf.extend_from_slice(&metadata.to_le_bytes());
-    buf.extend_from_slice(&sequence.to_le_bytes());
-    buf.extend_from_slice(data);
-    buf
+/// Accounts: `[authority (signer), envelope, padding]`. Each metadata that differs from the
+/// envelope's replaces it and zeroes its region, oracle data or auxiliary data; pass the
+/// current value to leave a region as it is. Blocked on-chain while delegation is active.
+pub fn set_oracle_metadata_instruction_data(
+    oracle_metadata: StructMetadata,
+    auxiliary_metadata: StructMetadata,
+) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::SetOracleMetadata {
+        oracle_metadata: oracle_metadata.as_u64(),
+        auxiliary_metadata: auxiliary_metadata.as_u64(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
 }
 
-/// Build `UpdateAuxiliaryRange` instruction data (manual wire format).
+/// Serialize a `SetRangeGuards` instruction (slow path): replace the range allow-list.
 ///
-/// Wire: `[disc:4][metadata:8][sequence:8][offset:1][data:N]`
-pub fn update_auxiliary_range_instruction_data(
-    metadata: u64,
-    sequence: u64,
-    offset: u8,
-    data: &[u8],
-) -> Vec<u8> {
-    let mut buf = Vec::with_capacity(21 + data.len());
-    buf.extend_from_slice(&UPDATE_AUX_RANGE_TAG.to_le_bytes());
-    buf.extend_from_slice(&metadata.to_le_bytes());
-    buf.extend_from_slice(&sequence.to_le_bytes());
-    buf.push(offset);
-    buf.extend_from_slice(data);
-    buf
-}
-
-/// Build `UpdateAuxiliaryDelegatedRange` instruction data (manual wire format).
+/// - `bump`: the canonical bump of the `[RANGE_GUARD_SEED, envelope_address]` PDA.
+/// - `entries`: up to [`MAX_RANGE_GUARDS`] (4) ranges. Delegated writes that change a guarded
+///   range require a top-level instruction from its `program_id` in the same transaction.
+///   An empty slice disables guarding.
+///
+/// Blocked on-chain while delegation is active. Returns [`InstructionError::TooManyRangeGuards`]
+/// or [`InstructionError::InvalidRangeGuard`] on bad inputs.
+pub fn set_range_guards_instruction_data(
+    bump: u8,
+    entries: &[RangeGuardSpec],
+) -> Result<Vec<u8>, InstructionError> {
+    if entries.len() > MAX_RANGE_GUARDS {
+        return Err(InstructionError::TooManyRangeGuards);
+    }
+    for entry in entries {
+        if entry.len == 0 || entry.offset as usize + entry.len as usize > AUX_DATA_SIZE {
+            return Err(InstructionError::InvalidRangeGuard);
+        }
+    }
+    wincode::serialize(&SlowPathInstruction::SetRangeGuards {
+        bump,
+        entries: entries.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetConstraints` instruction (slow path): replace the constraint table.
+///
+/// - `bump`: the canonical bump of the `[CONSTRAINTS_SEED, envelope_address]` PDA.
+/// - `constraints`: up to [`MAX_CONSTRAINTS`] (4) predicates over oracle or aux fields. Writes
+///   to a constrained region must pass the table as an extra account and fail with
+///   [`CuSoonError::ConstraintViolated`] if any predicate stops holding. An empty slice
+///   disables the table; fast-path writes are refused while the oracle region is constrained.
+///   [`Constraint::sequenced`] entries make an aux field a counter no write may decrease.
+///
+/// Aux constraints cannot be changed while delegation is active. Returns
+/// [`InstructionError::TooManyConstraints`] or [`InstructionError::InvalidConstraint`] on bad
+/// inputs.
+pub fn set_constraints_instruction_data(
+    bump: u8,
+    constraints: &[ConstraintSpec],
+) -> Result<Vec<u8>, InstructionError> {
+    if constraints.len() > MAX_CONSTRAINTS {
+        return Err(InstructionError::TooManyConstraints);
+    }
+    if !constraints
+        .iter()
+        .all(|&spec| Constraint::from(spec).is_valid())
+    {
+        return Err(InstructionError::InvalidConstraint);
+    }
+    wincode::serialize(&SlowPathInstruction::SetConstraints {
+        bump,
+        constraints: constraints.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetPublishers` instruction (slow path): replace the envelope's publishers.
+///
+/// - `bump`: the canonical bump of the `[PUBLISHERS_SEED, envelope_address]` PDA.
+/// - `threshold`: how many distinct publishers must sign each
+///   [`publisher_update_instruction_data`] update.
+/// - `publishers`: up to [`MAX_PUBLISHERS`] (8) distinct addresses. An empty slice with
+///   `threshold: 0` turns publisher writes off.
+///
+/// Accounts: `[authority (signer), envelope, publisher_set (writable), system_program]`; the
+/// authority pays rent the first time. Returns [`InstructionError::InvalidPublisherSet`] on
+/// bad inputs.
+pub fn set_publishers_instruction_data(
+    bump: u8,
+    threshold: u8,
+    publishers: &[[u8; 32]],
+) -> Result<Vec<u8>, InstructionError> {
+    if !is_valid_publisher_set(publishers, threshold) {
+        return Err(InstructionError::InvalidPublisherSet);
+    }
+    wincode::serialize(&SlowPathInstruction::SetPublishers {
+        bump,
+        threshold,
+        publishers: publishers.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetReaders` instruction (slow path): replace the envelope's reader list.
+///
+/// - `bump`: the canonical bump of the `[READERS_SEED, envelope_address]` PDA.
+/// - `readers`: up to [`MAX_READERS`] (8) distinct program ids allowed to read the oracle
+///   with [`read_oracle_gated_instruction_data`]. An empty slice turns gating off.
+///
+/// Accounts: `[authority (signer), envelope (writable), reader_list (writable),
+/// system_program]`; the authority pays rent the first time. Returns
+/// [`InstructionError::InvalidReaderList`] on bad inputs.
+pub fn set_readers_instruction_data(
+    bump: u8,
+    readers: &[[u8; 32]],
+) -> Result<Vec<u8>, InstructionError> {
+    if !is_valid_reader_list(readers) {
+        return Err(InstructionError::InvalidReaderList);
+    }
+    wincode::serialize(&SlowPathInstruction::SetReaders {
+        bump,
+        readers: readers.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetDelegateSchema` instruction (slow path): register the delegate's sub-struct.
+///
+/// - `bump`: the canonical bump of the `[DELEGATE_SCHEMA_SEED, envelope_address]` PDA.
+/// - `offset`: where the sub-struct starts in the auxiliary region.
+/// - `metadata`: the sub-struct's packed [`StructMetadata`].
+///
+/// Signed by the delegation authority. On-chain, the whole span must be program-writable.
+/// Returns [`InstructionError::InvalidDelegateSchema`] if the type is zero-sized or the span
+/// extends past [`AUX_DATA_SIZE`].
+pub fn set_delegate_schema_instruction_data(
+    bump: u8,
+    offset: u8,
+    metadata: u64,
+) -> Result<Vec<u8>, InstructionError> {
+    let size = StructMetadata::from_raw(metadata).type_size() as usize;
+    if size == 0 || offset as usize + size > AUX_DATA_SIZE {
+        return Err(InstructionError::InvalidDelegateSchema);
+    }
+    wincode::serialize(&SlowPathInstruction::SetDelegateSchema {
+        bump,
+        offset,
+        metadata,
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `ReadAuxRange` instruction (slow path, read-only).
+///
+/// Accounts: `[envelope]`. The program returns `auxiliary_data[offset..offset + len]` as
+/// transaction return data. Returns [`InstructionError::InvalidAuxRange`] if `len` is zero
+/// or the range extends past [`AUX_DATA_SIZE`].
+pub fn read_aux_range_instruction_data(offset: u16, len: u16) -> Result<Vec<u8>, InstructionError> {
+    if len == 0 || offset as usize + len as usize > AUX_DATA_SIZE {
+        return Err(InstructionError::InvalidAuxRange);
+    }
+    wincode::serialize(&SlowPathInstruction::ReadAuxRange { offset, len })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `ReadOracle` instruction (slow path, read-only).
+///
+/// Accounts: `[envelope]`. The program returns `[oracle_metadata:8][sequence:8][data:239]` as
+/// transaction return data; decode it with [`c_u_soon_instruction::split_read_oracle`].
+pub fn read_oracle_instruction_data() -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::ReadOracle)
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize an `Aggregate` instruction (slow path): write the median of several
+/// publisher envelopes into an aggregate envelope.
+///
+/// Accounts: `[authority (signer), envelope (writable), source_0, ..]` with 1 to
+/// [`MAX_AGGREGATE_SOURCES`] distinct source envelopes of the same oracle type; a constrained
+/// envelope's table goes before the sources. Sources more than `max_deviation_bps` from the
+/// median of all of them are dropped, and the payload of the source holding the median of
+/// the rest is written under `sequence`. The program fails with
+/// [`CuSoonError::TooFewSources`] if fewer than `min_sources` remain. Returns
+/// [`InstructionError::InvalidMinSources`] if `min_sources` is out of range.
+pub fn aggregate_instruction_data(
+    sequence: u64,
+    min_sources: u8,
+    max_deviation_bps: u16,
+) -> Result<Vec<u8>, InstructionError> {
+    if !(1..=MAX_AGGREGATE_SOURCES).contains(&(min_sources as usize)) {
+        return Err(InstructionError::InvalidMinSources);
+    }
+    wincode::serialize(&SlowPathInstruction::Aggregate {
+        sequence,
+        min_sources,
+        max_deviation_bps,
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `ReadOracleGated` instruction (slow path, read-only).
+///
+/// Accounts: `[envelope, reader_list, instructions_sysvar]`. Returns the same data as
+/// [`read_oracle_instruction_data`], but only when the transaction's current top-level
+/// instruction targets a program in the envelope's reader list. Readers send it by CPI.
+pub fn read_oracle_gated_instruction_data() -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::ReadOracleGated)
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `ReadAux` instruction (slow path, read-only).
+///
+/// Accounts: `[envelope]`. The program returns `[auxiliary_metadata:8][auxiliary_data:256]` as
+/// transaction return data; decode it with [`c_u_soon_instruction::split_read_aux`].
+pub fn read_aux_instruction_data() -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::ReadAux)
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `Resize` instruction (slow path): set the size of the envelope's extended
+/// auxiliary region.
+///
+/// Accounts: `[authority (signer, writable), envelope, system_program]`. `type_hash` is the
+/// extension type's `TypeHash::TYPE_HASH`; `ext_len: 0` removes the extension. Returns
+/// [`InstructionError::InvalidExtAux`] if `ext_len > MAX_EXT_AUX_SIZE`.
+///
+/// Fast-path updates to a resized envelope still succeed, but cost more than the fixed
+/// fast-path budget.
+pub fn resize_instruction_data(
+    type_hash: u64,
+    ext_len: usize,
+) -> Result<Vec<u8>, InstructionError> {
+    if ext_len > MAX_EXT_AUX_SIZE {
+        return Err(InstructionError::InvalidExtAux);
+    }
+    wincode::serialize(&SlowPathInstruction::Resize {
+        type_hash,
+        ext_len: ext_len as u16,
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `MigrateEnvelope` instruction (slow path): grow an envelope created before
+/// the `publisher`, `heartbeat_slot`, `write_hook`, and `mutation_marker` fields to the
+/// current layout.
+///
+/// Accounts: `[payer (signer, writable), envelope (writable), system_program]`.
+/// Permissionless; the payer tops up the rent. Fails on-chain for an envelope that is
+/// already current.
+pub fn migrate_envelope_instruction_data() -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::MigrateEnvelope)
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize an `UpdateExtAux` instruction (slow path): write `data` at `offset` in the
+/// extended auxiliary region as the authority.
+///
+/// Accounts: `[authority (signer), envelope, padding]`. `sequence` must exceed the
+/// extension's stored sequence. Returns [`InstructionError::InvalidExtAux`] if `data` is
+/// empty or the write extends past [`MAX_EXT_AUX_SIZE`]; the program also rejects writes
+/// past the envelope's actual extension length.
+pub fn update_ext_aux_instruction_data(
+    sequence: u64,
+    offset: usize,
+    data: &[u8],
+) -> Result<Vec<u8>, InstructionError> {
+    if data.is_empty() || offset > MAX_EXT_AUX_SIZE || data.len() > MAX_EXT_AUX_SIZE - offset {
+        return Err(InstructionError::InvalidExtAux);
+    }
+    wincode::serialize(&SlowPathInstruction::UpdateExtAux {
+        sequence,
+        offset: offset as u16,
+        data: data.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Build the two halves of a `SplitUpdate` (manual wire format): write `payload` to the
+/// start of the extended auxiliary region across two instructions.
+///
+/// Wire of each half: `[disc:4][type_hash:8][sequence:8][part:1][payload]`, behind
+/// [`SPLIT_UPDATE_TAG`]. `payload` is split at its midpoint; part 1 carries the first half.
+///
+/// Accounts of each half: `[authority (signer), envelope (writable), instructions_sysvar]`,
+/// signed by the authority or the publisher. Send both in one transaction, part 1
+/// immediately before part 2: each checks the other through the instructions sysvar, so the
+/// payload lands whole or not at all. `type_hash` must equal the extension's and `sequence`
+/// must exceed its stored sequence, which part 2 updates. Returns
+/// [`InstructionError::InvalidExtAux`] if `payload` is empty or longer than
+/// [`MAX_EXT_AUX_SIZE`]; the program also rejects a payload longer than the envelope's
+/// actual extension.
+pub fn split_update_instruction_data(
+    type_hash: u64,
+    sequence: u64,
+    payload: &[u8],
+) -> Result<[Vec<u8>; 2], InstructionError> {
+    if payload.is_empty() || payload.len() > MAX_EXT_AUX_SIZE {
+        return Err(InstructionError::InvalidExtAux);
+    }
+    let (first, second) = payload.split_at(payload.len() / 2);
+    let part = |part: u8, bytes: &[u8]| {
+        let mut data = Vec::with_capacity(SPLIT_UPDATE_HEADER_SIZE + bytes.len());
+        data.extend_from_slice(&SPLIT_UPDATE_TAG.to_le_bytes());
+        data.extend_from_slice(&type_hash.to_le_bytes());
+        data.extend_from_slice(&sequence.to_le_bytes());
+        data.push(part);
+        data.extend_from_slice(bytes);
+        data
+    };
+    Ok([part(1, first), part(2, second)])
+}
+
+/// Serialize a `SetGroupMembers` instruction (slow path): replace a group anchor's members.
+///
+/// Accounts: `[authority, group_anchor, system_program, members...]`, with 1 to
+/// [`MAX_GROUP_MEMBERS`] member envelopes in group order. `bump` is the canonical bump of the
+/// `[GROUP_ANCHOR_SEED, authority, group_id (u64 LE)]` PDA. The member count is checked
+/// on-chain from the accounts passed.
+pub fn set_group_members_instruction_data(
+    group_id: u64,
+    bump: u8,
+) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::SetGroupMembers { group_id, bump })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `GroupCommit` instruction (slow path): update every group member's oracle.
+///
+/// Accounts: `[authority, group_anchor, members...]`, members in the anchor's order.
+/// `updates` has one entry per member in the same order. `sequence` must exceed the anchor's
+/// and every member's current oracle sequence.
+///
+/// Returns [`InstructionError::InvalidGroupSize`] if `updates` is empty or longer than
+/// [`MAX_GROUP_MEMBERS`], or [`InstructionError::PayloadTooLarge`] if a payload exceeds
+/// [`ORACLE_BYTES`].
+pub fn group_commit_instruction_data(
+    sequence: u64,
+    updates: &[GroupMemberUpdate],
+) -> Result<Vec<u8>, InstructionError> {
+    if updates.is_empty() || updates.len() > MAX_GROUP_MEMBERS {
+        return Err(InstructionError::InvalidGroupSize);
+    }
+    if updates.iter().any(|u| u.payload.len() > ORACLE_BYTES) {
+        return Err(InstructionError::PayloadTooLarge);
+    }
+    wincode::serialize(&SlowPathInstruction::GroupCommit {
+        sequence,
+        updates: updates.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize a `SetLabel` instruction (slow path): set the envelope's label, once.
+///
+/// Accounts: `[authority, envelope, label, system_program]`. `bump` is the canonical bump of
+/// the `[LABEL_SEED, envelope_address]` PDA. `label` is zero-padded to [`LABEL_SIZE`].
+/// Returns [`InstructionError::InvalidLabel`] if it is empty, longer than `LABEL_SIZE`
+/// bytes, or contains a NUL byte.
+pub fn set_label_instruction_data(bump: u8, label: &str) -> Result<Vec<u8>, InstructionError> {
+    if label.is_empty() || label.len() > LABEL_SIZE || label.contains('\0') {
+        return Err(InstructionError::InvalidLabel);
+    }
+    let mut padded = [0u8; LABEL_SIZE];
+    padded[..label.len()].copy_from_slice(label.as_bytes());
+    wincode::serialize(&SlowPathInstruction::SetLabel {
+        bump,
+        label: padded,
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// SHA-256 of the envelope's whole auxiliary region, as checked by `AssertAuxHash`.
+pub fn aux_hash(envelope: &Envelope) -> [u8; 32] {
+    use sha2::Digest;
+    sha2::Sha256::digest(envelope.auxiliary_data).into()
+}
+
+/// SHA-256 of the envelope's `Envelope` bytes, as returned by `ExportState`. Extended aux
+/// bytes past the `Envelope` are not included.
+pub fn envelope_state_hash(envelope: &Envelope) -> [u8; 32] {
+    use sha2::Digest;
+    sha2::Sha256::digest(bytemuck::bytes_of(envelope)).into()
+}
+
+/// SHA-256 of a fast-path payload, as logged in [`c_u_soon::OracleEvent::payload_hash`].
+pub fn oracle_payload_hash(payload: &[u8]) -> [u8; 32] {
+    use sha2::Digest;
+    sha2::Sha256::digest(payload).into()
+}
+
+/// Serialize an `AssertAuxHash` instruction (slow path, read-only).
+///
+/// Accounts: `[envelope]`. Fails on-chain unless [`aux_hash`] of the current envelope equals
+/// `expected`. Place it before an instruction that depends on the aux state you read, so the
+/// transaction aborts if that state changed in between.
+pub fn assert_aux_hash_instruction_data(expected: [u8; 32]) -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::AssertAuxHash { expected })
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Serialize an `ExportState` instruction (slow path, read-only).
+///
+/// Accounts: `[envelope]`. The program returns `[slot:8][state_hash:32]` as transaction
+/// return data, where `state_hash` is [`envelope_state_hash`] of the envelope at `slot`;
+/// decode it with [`c_u_soon_instruction::split_export_state`].
+pub fn export_state_instruction_data() -> Result<Vec<u8>, InstructionError> {
+    wincode::serialize(&SlowPathInstruction::ExportState)
+        .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Build `UpdateAuxiliary` instruction data (manual wire format).
+///
+/// Wire: `[disc:4][metadata:8][sequence:8][data:N]`
+///
+/// `metadata` is `T::METADATA.as_u64()`. `sequence` must match the oracle's current
+/// authority sequence counter. `data` is the raw aux bytes (length = `type_size`).
+pub fn update_auxiliary_instruction_data(metadata: u64, sequence: u64, data: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(20 + data.len());
+    buf.extend_from_slice(&UPDATE_AUX_TAG.to_le_bytes());
+    buf.extend_from_slice(&metadata.to_le_bytes());
+    buf.extend_from_slice(&sequence.to_le_bytes());
+    buf.extend_from_slice(data);
+    buf
+}
+
+/// Build `UpdateAuxiliaryForce` instruction data (manual wire format).
+///
+/// Wire: `[disc:4][metadata:8][auth_seq:8][prog_seq:8][data:N]`
+pub fn update_auxiliary_force_instruction_data(
+    metadata: u64,
+    authority_sequence: u64,
+    program_sequence: u64,
+    data: &[u8],
+) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(28 + data.len());
+    buf.extend_from_slice(&UPDATE_AUX_FORCE_TAG.to_le_bytes());
+    buf.extend_from_slice(&metadata.to_le_bytes());
+    buf.extend_from_slice(&authority_sequence.to_le_bytes());
+    buf.extend_from_slice(&program_sequence.to_le_bytes());
+    buf.extend_from_slice(data);
+    buf
+}
+
+/// Build `UpdateAuxiliaryDelegated` instruction data (manual wire format).
+///
+/// Wire: `[disc:4][metadata:8][sequence:8][data:N]`
+pub fn update_auxiliary_delegated_instruction_data(
+    metadata: u64,
+    sequence: u64,
+    data: &[u8],
+) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(20 + data.len());
+    buf.extend_from_slice(&UPDATE_AUX_DELEGATED_TAG.to_le_bytes());
+    buf.extend_from_slice(&metadata.to_le_bytes());
+    buf.extend_from_slice(&sequence.to_le_bytes());
+    buf.extend_from_slice(data);
+    buf
+}
+
+/// Build `UpdateAuxiliaryDelegatedSchema` instruction data (manual wire format).
+///
+/// Wire: `[disc:4][schema_metadata:8][sequence:8][data:N]`
+///
+/// Writes `data` at the registered schema's offset. `schema_metadata` must match the
+/// delegate schema, not the envelope's `auxiliary_metadata`.
+pub fn update_auxiliary_delegated_schema_instruction_data(
+    schema_metadata: u64,
+    sequence: u64,
+    data: &[u8],
+) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(20 + data.len());
+    buf.extend_from_slice(&UPDATE_AUX_DELEGATED_SCHEMA_TAG.to_le_bytes());
+    buf.extend_from_slice(&schema_metadata.to_le_bytes());
+    buf.extend_from_slice(&sequence.to_le_bytes());
+    buf.extend_from_slice(data);
+    buf
+}
+
+/// Build `UpdateAuxiliaryRange` instruction data (manual wire format).
+///
+/// Wire: `[disc:4][metadata:8][sequence:8][offset:1][data:N]`
+pub fn update_auxiliary_range_instruction_data(
+    metadata: u64,
+    sequence: u64,
+    offset: u8,
+    data: &[u8],
+) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(21 + data.len());
+    buf.extend_from_slice(&UPDATE_AUX_RANGE_TAG.to_le_bytes());
+    buf.extend_from_slice(&metadata.to_le_bytes());
+    buf.extend_from_slice(&sequence.to_le_bytes());
+    buf.push(offset);
+    buf.extend_from_slice(data);
+    buf
+}
+
+/// Build `UpdateAuxiliaryDelegatedRange` instruction data (manual wire format).
 ///
 /// Wire: `[disc:4][metadata:8][sequence:8][offset:1][data:N]`
 pub fn update_auxiliary_delegated_range_instruction_data(
@@ -226,171 +1252,1440 @@ pub fn update_auxiliary_delegated_range_instruction_data(
     buf
 }
 
-/// Build `UpdateAuxiliaryMultiRange` instruction data (wincode serialized).
-pub fn update_auxiliary_multi_range_instruction_data(
-    metadata: u64,
-    sequence: u64,
-    ranges: &[WriteSpec],
-) -> Vec<u8> {
-    wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryMultiRange {
-        metadata,
-        sequence,
-        ranges: ranges.to_vec(),
-    })
-    .expect("multi-range serialization failed")
-}
+/// Range count and overlap checks shared by the multi-range builders.
+fn check_write_ranges<R: WriteRange>(ranges: &[R]) -> Result<(), InstructionError> {
+    if ranges.len() > MAX_WRITE_RANGES {
+        return Err(InstructionError::TooManyRanges);
+    }
+    if ranges_overlap(ranges) {
+        return Err(InstructionError::OverlappingRanges);
+    }
+    Ok(())
+}
+
+/// Build `UpdateAuxiliaryMultiRange` instruction data (wincode serialized).
+///
+/// Returns [`InstructionError::TooManyRanges`] for more than [`MAX_WRITE_RANGES`] ranges
+/// and [`InstructionError::OverlappingRanges`] if two ranges overlap, both of which the
+/// program would reject. Ranges are serialized in the order given; pass them through
+/// `c_u_soon_instruction::normalize_ranges` first to sort and merge them.
+pub fn update_auxiliary_multi_range_instruction_data(
+    metadata: u64,
+    sequence: u64,
+    ranges: &[WriteSpec],
+) -> Result<Vec<u8>, InstructionError> {
+    check_write_ranges(ranges)?;
+    wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryMultiRange {
+        metadata,
+        sequence,
+        ranges: ranges.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Build `UpdateAuxiliaryDelegatedMultiRange` instruction data (wincode serialized).
+///
+/// Same checks as [`update_auxiliary_multi_range_instruction_data`].
+pub fn update_auxiliary_delegated_multi_range_instruction_data(
+    metadata: u64,
+    sequence: u64,
+    ranges: &[WriteSpec],
+) -> Result<Vec<u8>, InstructionError> {
+    check_write_ranges(ranges)?;
+    wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange {
+        metadata,
+        sequence,
+        ranges: ranges.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Build `UpdateAuxiliaryMultiRangeForce` instruction data (wincode serialized).
+///
+/// Same checks as [`update_auxiliary_multi_range_instruction_data`].
+pub fn update_auxiliary_multi_range_force_instruction_data(
+    metadata: u64,
+    authority_sequence: u64,
+    program_sequence: u64,
+    ranges: &[WriteSpec],
+) -> Result<Vec<u8>, InstructionError> {
+    check_write_ranges(ranges)?;
+    wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryMultiRangeForce {
+        metadata,
+        authority_sequence,
+        program_sequence,
+        ranges: ranges.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// [`check_write_ranges`], plus the bounds the V2 variants carry explicitly: every range is
+/// non-empty and ends within [`AUX_DATA_SIZE`].
+fn check_write_ranges_v2(ranges: &[WriteSpecV2]) -> Result<(), InstructionError> {
+    check_write_ranges(ranges)?;
+    if ranges
+        .iter()
+        .any(|spec| spec.data.is_empty() || spec.end() > AUX_DATA_SIZE)
+    {
+        return Err(InstructionError::InvalidAuxRange);
+    }
+    Ok(())
+}
+
+/// Build `UpdateAuxiliaryMultiRangeV2` instruction data (wincode serialized): the
+/// [`update_auxiliary_multi_range_instruction_data`] update with `u16` range offsets.
+///
+/// Same checks, and also returns [`InstructionError::InvalidAuxRange`] for an empty range
+/// or one ending past [`AUX_DATA_SIZE`]. Convert existing ranges with `WriteSpecV2::from`.
+pub fn update_auxiliary_multi_range_v2_instruction_data(
+    metadata: u64,
+    sequence: u64,
+    ranges: &[WriteSpecV2],
+) -> Result<Vec<u8>, InstructionError> {
+    check_write_ranges_v2(ranges)?;
+    wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 {
+        metadata,
+        sequence,
+        ranges: ranges.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Build `UpdateAuxiliaryDelegatedMultiRangeV2` instruction data (wincode serialized).
+///
+/// Same checks as [`update_auxiliary_multi_range_v2_instruction_data`].
+pub fn update_auxiliary_delegated_multi_range_v2_instruction_data(
+    metadata: u64,
+    sequence: u64,
+    ranges: &[WriteSpecV2],
+) -> Result<Vec<u8>, InstructionError> {
+    check_write_ranges_v2(ranges)?;
+    wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRangeV2 {
+        metadata,
+        sequence,
+        ranges: ranges.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Build `UpdateAuxiliaryMultiRangeForceV2` instruction data (wincode serialized).
+///
+/// Same checks as [`update_auxiliary_multi_range_v2_instruction_data`].
+pub fn update_auxiliary_multi_range_force_v2_instruction_data(
+    metadata: u64,
+    authority_sequence: u64,
+    program_sequence: u64,
+    ranges: &[WriteSpecV2],
+) -> Result<Vec<u8>, InstructionError> {
+    check_write_ranges_v2(ranges)?;
+    wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryMultiRangeForceV2 {
+        metadata,
+        authority_sequence,
+        program_sequence,
+        ranges: ranges.to_vec(),
+    })
+    .map_err(|_| InstructionError::SerializationFailed)
+}
+
+/// Typed `UpdateAuxiliary`: derives metadata from `T::METADATA`.
+///
+/// Emits a compile-time assertion that `size_of::<T>() <= MAX_AUX_STRUCT_SIZE`.
+pub fn update_auxiliary_typed<T: TypeHash + NoUninit>(sequence: u64, value: &T) -> Vec<u8> {
+    const { assert!(core::mem::size_of::<T>() <= MAX_AUX_STRUCT_SIZE) };
+    update_auxiliary_instruction_data(T::METADATA.as_u64(), sequence, bytemuck::bytes_of(value))
+}
+
+/// The bytes of `value` in `range`, validated as a write range within `T`.
+fn typed_range<T: NoUninit>(
+    value: &T,
+    range: core::ops::Range<usize>,
+) -> Result<WriteSpec, InstructionError> {
+    let bytes = bytemuck::bytes_of(value);
+    if range.is_empty() || range.end > bytes.len() {
+        return Err(InstructionError::InvalidAuxRange);
+    }
+    Ok(WriteSpec {
+        offset: range.start as u8,
+        data: bytes[range].to_vec(),
+    })
+}
+
+/// [`typed_range`] for each of `ranges`.
+fn typed_ranges<T: NoUninit>(
+    value: &T,
+    ranges: &[core::ops::Range<usize>],
+) -> Result<Vec<WriteSpec>, InstructionError> {
+    ranges
+        .iter()
+        .map(|range| typed_range(value, range.clone()))
+        .collect()
+}
+
+/// Typed `UpdateAuxiliaryRange`: writes the bytes of `value` in `range`, at the same offset,
+/// under `T::METADATA`. Take `range` from `core::mem::offset_of!` to update one field.
+///
+/// Returns [`InstructionError::InvalidAuxRange`] if `range` is empty or extends past
+/// `size_of::<T>()`. Emits a compile-time assertion that
+/// `size_of::<T>() <= MAX_AUX_STRUCT_SIZE`.
+pub fn update_auxiliary_range_typed<T: TypeHash + NoUninit>(
+    sequence: u64,
+    value: &T,
+    range: core::ops::Range<usize>,
+) -> Result<Vec<u8>, InstructionError> {
+    const { assert!(core::mem::size_of::<T>() <= MAX_AUX_STRUCT_SIZE) };
+    let spec = typed_range(value, range)?;
+    Ok(update_auxiliary_range_instruction_data(
+        T::METADATA.as_u64(),
+        sequence,
+        spec.offset,
+        &spec.data,
+    ))
+}
+
+/// Typed `UpdateAuxiliaryDelegatedRange`. See [`update_auxiliary_range_typed`].
+pub fn update_auxiliary_delegated_range_typed<T: TypeHash + NoUninit>(
+    sequence: u64,
+    value: &T,
+    range: core::ops::Range<usize>,
+) -> Result<Vec<u8>, InstructionError> {
+    const { assert!(core::mem::size_of::<T>() <= MAX_AUX_STRUCT_SIZE) };
+    let spec = typed_range(value, range)?;
+    Ok(update_auxiliary_delegated_range_instruction_data(
+        T::METADATA.as_u64(),
+        sequence,
+        spec.offset,
+        &spec.data,
+    ))
+}
+
+/// Typed `UpdateAuxiliaryMultiRange`: one write range per entry of `ranges`, each holding
+/// the bytes of `value` at that offset, under `T::METADATA`.
+///
+/// Returns [`InstructionError::InvalidAuxRange`] if any range is empty or extends past
+/// `size_of::<T>()`, [`InstructionError::TooManyRanges`] for more than
+/// [`MAX_WRITE_RANGES`], and [`InstructionError::OverlappingRanges`] if two ranges
+/// overlap. Emits a compile-time assertion that
+/// `size_of::<T>() <= MAX_AUX_STRUCT_SIZE`.
+pub fn update_auxiliary_multi_range_typed<T: TypeHash + NoUninit>(
+    sequence: u64,
+    value: &T,
+    ranges: &[core::ops::Range<usize>],
+) -> Result<Vec<u8>, InstructionError> {
+    const { assert!(core::mem::size_of::<T>() <= MAX_AUX_STRUCT_SIZE) };
+    update_auxiliary_multi_range_instruction_data(
+        T::METADATA.as_u64(),
+        sequence,
+        &typed_ranges(value, ranges)?,
+    )
+}
+
+/// Typed `UpdateAuxiliaryDelegatedMultiRange`. See [`update_auxiliary_multi_range_typed`].
+pub fn update_auxiliary_delegated_multi_range_typed<T: TypeHash + NoUninit>(
+    sequence: u64,
+    value: &T,
+    ranges: &[core::ops::Range<usize>],
+) -> Result<Vec<u8>, InstructionError> {
+    const { assert!(core::mem::size_of::<T>() <= MAX_AUX_STRUCT_SIZE) };
+    update_auxiliary_delegated_multi_range_instruction_data(
+        T::METADATA.as_u64(),
+        sequence,
+        &typed_ranges(value, ranges)?,
+    )
+}
+
+/// Typed `UpdateAuxiliaryMultiRangeForce`. See [`update_auxiliary_multi_range_typed`].
+pub fn update_auxiliary_multi_range_force_typed<T: TypeHash + NoUninit>(
+    authority_sequence: u64,
+    program_sequence: u64,
+    value: &T,
+    ranges: &[core::ops::Range<usize>],
+) -> Result<Vec<u8>, InstructionError> {
+    const { assert!(core::mem::size_of::<T>() <= MAX_AUX_STRUCT_SIZE) };
+    update_auxiliary_multi_range_force_instruction_data(
+        T::METADATA.as_u64(),
+        authority_sequence,
+        program_sequence,
+        &typed_ranges(value, ranges)?,
+    )
+}
+
+/// Typed `SetDelegateSchema`: registers `T` at `offset`.
+pub fn set_delegate_schema_typed<T: TypeHash>(
+    bump: u8,
+    offset: u8,
+) -> Result<Vec<u8>, InstructionError> {
+    set_delegate_schema_instruction_data(bump, offset, T::METADATA.as_u64())
+}
+
+/// Typed `UpdateAuxiliaryDelegatedSchema`: derives the schema metadata from `T::METADATA`.
+///
+/// Emits a compile-time assertion that `size_of::<T>() <= MAX_AUX_STRUCT_SIZE`.
+pub fn update_auxiliary_delegated_schema_typed<T: TypeHash + NoUninit>(
+    sequence: u64,
+    value: &T,
+) -> Vec<u8> {
+    const { assert!(core::mem::size_of::<T>() <= MAX_AUX_STRUCT_SIZE) };
+    update_auxiliary_delegated_schema_instruction_data(
+        T::METADATA.as_u64(),
+        sequence,
+        bytemuck::bytes_of(value),
+    )
+}
+
+/// Typed `UpdateAuxiliaryDelegated`: derives metadata from `T::METADATA`.
+///
+/// Emits a compile-time assertion that `size_of::<T>() <= MAX_AUX_STRUCT_SIZE`.
+pub fn update_auxiliary_delegated_typed<T: TypeHash + NoUninit>(
+    sequence: u64,
+    value: &T,
+) -> Vec<u8> {
+    const { assert!(core::mem::size_of::<T>() <= MAX_AUX_STRUCT_SIZE) };
+    update_auxiliary_delegated_instruction_data(
+        T::METADATA.as_u64(),
+        sequence,
+        bytemuck::bytes_of(value),
+    )
+}
+
+/// Typed `UpdateAuxiliaryForce`: derives metadata from `T::METADATA`.
+///
+/// Emits a compile-time assertion that `size_of::<T>() <= MAX_AUX_STRUCT_SIZE`.
+pub fn update_auxiliary_force_typed<T: TypeHash + NoUninit>(
+    authority_sequence: u64,
+    program_sequence: u64,
+    value: &T,
+) -> Vec<u8> {
+    const { assert!(core::mem::size_of::<T>() <= MAX_AUX_STRUCT_SIZE) };
+    update_auxiliary_force_instruction_data(
+        T::METADATA.as_u64(),
+        authority_sequence,
+        program_sequence,
+        bytemuck::bytes_of(value),
+    )
+}
+
+/// Typed `Create`: derives oracle metadata from `T::METADATA` at compile time.
+///
+/// Emits a compile-time assertion that `size_of::<T>() <= ORACLE_BYTES`.
+/// Otherwise identical to [`create_instruction_data`].
+pub fn create_envelope_typed<T: TypeHash>(
+    custom_seeds: &[&[u8]],
+    bump: u8,
+) -> Result<Vec<u8>, InstructionError> {
+    const { assert!(core::mem::size_of::<T>() <= ORACLE_BYTES) };
+    create_instruction_data(custom_seeds, bump, T::METADATA)
+}
+
+/// Typed fast-path update: serializes `value` as oracle payload using `T::METADATA`.
+///
+/// Casts `value` to bytes via `bytemuck::bytes_of`. Emits a compile-time assertion that
+/// `size_of::<T>() <= ORACLE_BYTES`. Otherwise identical to [`fast_path_instruction_data`].
+pub fn fast_path_update_typed<T: TypeHash + NoUninit>(
+    sequence: u64,
+    value: &T,
+) -> Result<Vec<u8>, InstructionError> {
+    const { assert!(core::mem::size_of::<T>() <= ORACLE_BYTES) };
+    fast_path_instruction_data(T::METADATA.as_u64(), sequence, bytemuck::bytes_of(value))
+}
+
+/// Fast-path update publishing `mantissa * 10^exponent +- conf * 10^exponent` as a
+/// [`PriceValue`]. Shorthand for [`fast_path_update_typed`] with `PriceValue::new`.
+pub fn fast_path_update_price(
+    sequence: u64,
+    mantissa: i64,
+    exponent: i32,
+    conf: u64,
+) -> Result<Vec<u8>, InstructionError> {
+    fast_path_update_typed(sequence, &PriceValue::new(mantissa, exponent, conf))
+}
+
+/// Typed publisher-signed update. See [`publisher_update_instruction_data`].
+///
+/// Emits a compile-time assertion that `size_of::<T>() <= ORACLE_BYTES`.
+pub fn publisher_update_typed<T: TypeHash + NoUninit>(
+    sequence: u64,
+    value: &T,
+) -> Result<Vec<u8>, InstructionError> {
+    const { assert!(core::mem::size_of::<T>() <= ORACLE_BYTES) };
+    publisher_update_instruction_data(T::METADATA.as_u64(), sequence, bytemuck::bytes_of(value))
+}
+
+/// Typed split update of the extended auxiliary region. See
+/// [`split_update_instruction_data`].
+pub fn split_update_typed<T: TypeHash + NoUninit>(
+    sequence: u64,
+    value: &T,
+) -> Result<[Vec<u8>; 2], InstructionError> {
+    split_update_instruction_data(T::TYPE_HASH, sequence, bytemuck::bytes_of(value))
+}
+
+/// Typed fast-path update on the tiny lane. See [`fast_path_tiny_instruction_data`].
+///
+/// Emits a compile-time assertion that `size_of::<T>() <= TINY_PAYLOAD_SIZE`.
+pub fn fast_path_update_tiny<T: TypeHash + NoUninit>(
+    sequence: u64,
+    value: &T,
+) -> Result<Vec<u8>, InstructionError> {
+    const { assert!(core::mem::size_of::<T>() <= TINY_PAYLOAD_SIZE) };
+    fast_path_tiny_instruction_data(T::METADATA.as_u64(), sequence, bytemuck::bytes_of(value))
+}
+
+/// Typed fast-path update with a memo. See [`fast_path_instruction_data_with_memo`].
+///
+/// Emits a compile-time assertion that `size_of::<T>() <= ORACLE_MEMO_OFFSET`.
+pub fn fast_path_update_typed_with_memo<T: TypeHash + NoUninit>(
+    sequence: u64,
+    value: &T,
+    memo: &[u8; ORACLE_MEMO_SIZE],
+) -> Result<Vec<u8>, InstructionError> {
+    const { assert!(core::mem::size_of::<T>() <= ORACLE_MEMO_OFFSET) };
+    fast_path_instruction_data_with_memo(
+        T::METADATA.as_u64(),
+        sequence,
+        bytemuck::bytes_of(value),
+        memo,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c_u_soon::{ConstraintOp, ConstraintRegion, MASK_SIZE, RHS_CONST};
+
+    #[test]
+    fn typed_create_matches_untyped() {
+        let seeds: &[&[u8]] = &[b"test"];
+        let typed = create_envelope_typed::<u32>(seeds, 42).unwrap();
+        let untyped = create_instruction_data(seeds, 42, u32::METADATA).unwrap();
+        assert_eq!(typed, untyped);
+    }
+
+    #[test]
+    fn typed_fast_path_matches_untyped() {
+        let value: u32 = 0xDEAD_BEEF;
+        let typed = fast_path_update_typed::<u32>(7, &value).unwrap();
+        let untyped =
+            fast_path_instruction_data(u32::METADATA.as_u64(), 7, bytemuck::bytes_of(&value))
+                .unwrap();
+        assert_eq!(typed, untyped);
+    }
+
+    #[test]
+    fn tiny_fast_path_pads_payload() {
+        let data = fast_path_update_tiny(7, &0xABu64).unwrap();
+        assert_eq!(data.len(), 8 + 8 + TINY_PAYLOAD_SIZE);
+        assert_eq!(
+            data,
+            fast_path_tiny_instruction_data(u64::METADATA.as_u64(), 7, &0xABu64.to_le_bytes())
+                .unwrap()
+        );
+        assert_eq!(data[16], 0xAB);
+        assert!(data[17..].iter().all(|&b| b == 0));
+        assert_eq!(
+            fast_path_tiny_instruction_data(0, 1, &[0; TINY_PAYLOAD_SIZE + 1]),
+            Err(InstructionError::PayloadTooLarge)
+        );
+    }
+
+    #[test]
+    fn typed_fast_path_roundtrip() {
+        let value: u64 = 0x1234_5678_9ABC_DEF0;
+        let data = fast_path_update_typed::<u64>(99, &value).unwrap();
+        assert_eq!(data.len(), 8 + 8 + 8);
+        let meta = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let seq = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let payload: u64 = *bytemuck::from_bytes(&data[16..24]);
+        assert_eq!(meta, u64::METADATA.as_u64());
+        assert_eq!(seq, 99);
+        assert_eq!(payload, value);
+    }
+
+    #[test]
+    fn fast_path_price_roundtrip() {
+        let data = fast_path_update_price(3, 123_456, -3, 5).unwrap();
+        assert_eq!(data.len(), 8 + 8 + 20);
+        assert_eq!(data[..8], PriceValue::METADATA.as_u64().to_le_bytes());
+        let price: &PriceValue = bytemuck::from_bytes(&data[16..]);
+        assert_eq!(*price, PriceValue::new(123_456, -3, 5));
+        assert_eq!(price.to_mantissa(0), Some(123));
+    }
+
+    #[test]
+    fn fast_path_rejects_oversized_payload() {
+        let big = [0u8; ORACLE_BYTES + 1];
+        assert_eq!(
+            fast_path_instruction_data(0, 1, &big),
+            Err(InstructionError::PayloadTooLarge)
+        );
+    }
+
+    #[test]
+    fn fast_path_accepts_max_payload() {
+        let max = [0u8; ORACLE_BYTES];
+        assert!(fast_path_instruction_data(0, 1, &max).is_ok());
+    }
+
+    #[test]
+    fn fast_path_with_memo_layout() {
+        let memo = [0x5A; ORACLE_MEMO_SIZE];
+        let data = fast_path_instruction_data_with_memo(7, 42, &[1, 2, 3], &memo).unwrap();
+        assert_eq!(data.len(), 8 + 8 + ORACLE_BYTES);
+        assert_eq!(&data[16..19], &[1, 2, 3]);
+        assert!(data[19..16 + ORACLE_MEMO_OFFSET].iter().all(|&b| b == 0));
+        let trailer = &data[16 + ORACLE_MEMO_OFFSET..];
+        assert_eq!(&trailer[..ORACLE_MEMO_SIZE], &memo);
+        assert_eq!(&trailer[ORACLE_MEMO_SIZE..], &42u64.to_le_bytes());
+    }
+
+    #[test]
+    fn fast_path_with_memo_rejects_payload_overlapping_trailer() {
+        let payload = [0u8; ORACLE_MEMO_OFFSET + 1];
+        assert_eq!(
+            fast_path_instruction_data_with_memo(0, 1, &payload, &[0; ORACLE_MEMO_SIZE]),
+            Err(InstructionError::PayloadTooLarge)
+        );
+    }
+
+    #[test]
+    fn typed_fast_path_with_memo_matches_untyped() {
+        let value: u64 = 0xFEED;
+        let memo = [3u8; ORACLE_MEMO_SIZE];
+        let typed = fast_path_update_typed_with_memo::<u64>(9, &value, &memo).unwrap();
+        let untyped = fast_path_instruction_data_with_memo(
+            u64::METADATA.as_u64(),
+            9,
+            bytemuck::bytes_of(&value),
+            &memo,
+        )
+        .unwrap();
+        assert_eq!(typed, untyped);
+    }
+
+    #[test]
+    fn typed_aux_ranges_take_bytes_at_their_offset() {
+        let value: [u32; 4] = [1, 2, 3, 4];
+        let bytes = bytemuck::bytes_of(&value);
+        let meta = <[u32; 4]>::METADATA.as_u64();
+
+        assert_eq!(
+            update_auxiliary_range_typed(7, &value, 4..8).unwrap(),
+            update_auxiliary_range_instruction_data(meta, 7, 4, &bytes[4..8])
+        );
+        assert_eq!(
+            update_auxiliary_delegated_range_typed(7, &value, 12..16).unwrap(),
+            update_auxiliary_delegated_range_instruction_data(meta, 7, 12, &bytes[12..])
+        );
+
+        let specs = [
+            WriteSpec {
+                offset: 0,
+                data: bytes[..4].to_vec(),
+            },
+            WriteSpec {
+                offset: 8,
+                data: bytes[8..12].to_vec(),
+            },
+        ];
+        assert_eq!(
+            update_auxiliary_multi_range_typed(7, &value, &[0..4, 8..12]).unwrap(),
+            update_auxiliary_multi_range_instruction_data(meta, 7, &specs).unwrap()
+        );
+        assert_eq!(
+            update_auxiliary_delegated_multi_range_typed(7, &value, &[0..4, 8..12]).unwrap(),
+            update_auxiliary_delegated_multi_range_instruction_data(meta, 7, &specs).unwrap()
+        );
+        assert_eq!(
+            update_auxiliary_multi_range_force_typed(7, 8, &value, &[0..4, 8..12]).unwrap(),
+            update_auxiliary_multi_range_force_instruction_data(meta, 7, 8, &specs).unwrap()
+        );
+    }
+
+    #[test]
+    fn typed_aux_ranges_stay_inside_the_value() {
+        let value = [0u8; 16];
+        assert_eq!(
+            update_auxiliary_range_typed(1, &value, 12..17),
+            Err(InstructionError::InvalidAuxRange)
+        );
+        assert_eq!(
+            update_auxiliary_range_typed(1, &value, 4..4),
+            Err(InstructionError::InvalidAuxRange)
+        );
+        assert_eq!(
+            update_auxiliary_multi_range_typed(1, &value, &[0..4, 15..20]),
+            Err(InstructionError::InvalidAuxRange)
+        );
+        assert_eq!(
+            update_auxiliary_multi_range_typed(1, &value, &[0..4, 2..6]),
+            Err(InstructionError::OverlappingRanges)
+        );
+        let many = vec![0..1; MAX_WRITE_RANGES + 1];
+        assert_eq!(
+            update_auxiliary_multi_range_typed(1, &value, &many),
+            Err(InstructionError::TooManyRanges)
+        );
+    }
+
+    #[test]
+    fn create_rejects_too_many_seeds() {
+        let seeds: Vec<&[u8]> = (0..14).map(|_| b"x" as &[u8]).collect();
+        assert_eq!(
+            create_instruction_data(&seeds, 0, u32::METADATA),
+            Err(InstructionError::TooManySeeds)
+        );
+    }
+
+    #[test]
+    fn oracle_payload_hash_is_sha256() {
+        assert_eq!(oracle_payload_hash(b"")[..4], [0xe3, 0xb0, 0xc4, 0x42],);
+    }
+
+    #[test]
+    fn create_with_events_roundtrip() {
+        let data = create_with_events_instruction_data(&[b"feed"], 7, u32::METADATA).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        assert!(matches!(
+            ix,
+            SlowPathInstruction::CreateWithEvents { ref custom_seeds, bump: 7, oracle_metadata }
+                if custom_seeds == &[b"feed".to_vec()] && oracle_metadata == u32::METADATA.as_u64()
+        ));
+        assert_eq!(data[..4], 42u32.to_le_bytes());
+        assert_eq!(
+            create_with_events_instruction_data(&[&[0; 33]], 0, u32::METADATA),
+            Err(InstructionError::SeedTooLong)
+        );
+    }
+
+    #[test]
+    fn create_rejects_long_seed() {
+        let long = [0u8; 33];
+        let seeds: &[&[u8]] = &[&long];
+        assert_eq!(
+            create_instruction_data(seeds, 0, u32::METADATA),
+            Err(InstructionError::SeedTooLong)
+        );
+    }
+
+    #[test]
+    fn set_delegation_rejects_non_canonical_mask() {
+        let mut bad = [0x00u8; MASK_SIZE];
+        bad[5] = 0x42;
+        assert_eq!(
+            set_delegated_program_instruction_data(Mask::from(bad), Mask::ALL_BLOCKED),
+            Err(InstructionError::NonCanonicalMask)
+        );
+    }
+
+    #[test]
+    fn set_delegation_accepts_canonical_masks() {
+        assert!(
+            set_delegated_program_instruction_data(Mask::ALL_WRITABLE, Mask::ALL_BLOCKED).is_ok()
+        );
+    }
+
+    #[test]
+    fn set_delegated_program_compact_roundtrip() {
+        let mut program = Mask::ALL_BLOCKED;
+        program.allow_range(4, 12);
+        let data = set_delegated_program_compact_instruction_data(
+            CompactMask::from(&program),
+            CompactMask::ZERO,
+        )
+        .unwrap();
+        assert_eq!(data.len(), 4 + 2 * 32);
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        let SlowPathInstruction::SetDelegatedProgramCompact {
+            program_bitmask,
+            user_bitmask,
+        } = ix
+        else {
+            panic!("expected SetDelegatedProgramCompact");
+        };
+        assert_eq!(
+            Mask::from(CompactMask::from_bytes(program_bitmask)),
+            program
+        );
+        assert_eq!(
+            Mask::from(CompactMask::from_bytes(user_bitmask)),
+            Mask::ALL_BLOCKED
+        );
+    }
+
+    #[test]
+    fn transfer_delegation_roundtrip() {
+        let data =
+            transfer_delegation_instruction_data(Mask::ALL_WRITABLE, Mask::ALL_BLOCKED).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        let SlowPathInstruction::TransferDelegation {
+            program_bitmask,
+            user_bitmask,
+        } = ix
+        else {
+            panic!("expected TransferDelegation");
+        };
+        assert_eq!(Mask::from(program_bitmask), Mask::ALL_WRITABLE);
+        assert_eq!(Mask::from(user_bitmask), Mask::ALL_BLOCKED);
+        assert_eq!(data[..4], 47u32.to_le_bytes());
+
+        let mut bad = [0x00u8; MASK_SIZE];
+        bad[5] = 0x42;
+        assert_eq!(
+            transfer_delegation_instruction_data(Mask::ALL_WRITABLE, Mask::from(bad)),
+            Err(InstructionError::NonCanonicalMask)
+        );
+    }
+
+    #[test]
+    fn set_write_hook_roundtrip() {
+        for notify in [true, false] {
+            let data = set_write_hook_instruction_data(notify).unwrap();
+            assert_eq!(data[..4], 50u32.to_le_bytes());
+            let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+            assert!(ix.validate());
+            let SlowPathInstruction::SetWriteHook {
+                notify_on_authority_write,
+            } = ix
+            else {
+                panic!("expected SetWriteHook");
+            };
+            assert_eq!(notify_on_authority_write, notify);
+        }
+    }
+
+    #[test]
+    fn set_oracle_metadata_roundtrip() {
+        let data =
+            set_oracle_metadata_instruction_data(u64::METADATA, StructMetadata::ZERO).unwrap();
+        assert_eq!(data[..4], 51u32.to_le_bytes());
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        let SlowPathInstruction::SetOracleMetadata {
+            oracle_metadata,
+            auxiliary_metadata,
+        } = ix
+        else {
+            panic!("expected SetOracleMetadata");
+        };
+        assert_eq!(oracle_metadata, u64::METADATA.as_u64());
+        assert_eq!(auxiliary_metadata, 0);
+    }
+
+    #[test]
+    fn set_delegated_pda_checks_seeds_and_masks() {
+        let seeds: Vec<&[u8]> = vec![b"x"; MAX_DELEGATE_SEEDS + 1];
+        assert_eq!(
+            set_delegated_pda_instruction_data(
+                [1; 32],
+                &seeds,
+                0,
+                Mask::ALL_WRITABLE,
+                Mask::ALL_BLOCKED
+            ),
+            Err(InstructionError::TooManySeeds)
+        );
+        assert_eq!(
+            set_delegated_pda_instruction_data(
+                [1; 32],
+                &[&[0; 33]],
+                0,
+                Mask::ALL_WRITABLE,
+                Mask::ALL_BLOCKED
+            ),
+            Err(InstructionError::SeedTooLong)
+        );
+        let mut bad = [0x00u8; MASK_SIZE];
+        bad[5] = 0x42;
+        assert_eq!(
+            set_delegated_pda_instruction_data([1; 32], &[], 0, Mask::from(bad), Mask::ALL_BLOCKED),
+            Err(InstructionError::NonCanonicalMask)
+        );
+
+        let data = set_delegated_pda_instruction_data(
+            [1; 32],
+            &[b"delegate"],
+            254,
+            Mask::ALL_WRITABLE,
+            Mask::ALL_BLOCKED,
+        )
+        .unwrap();
+        match wincode::deserialize::<SlowPathInstruction>(&data).unwrap() {
+            SlowPathInstruction::SetDelegatedPda {
+                program_id,
+                seeds,
+                bump,
+                ..
+            } => assert_eq!(
+                (program_id, seeds, bump),
+                ([1; 32], vec![b"delegate".to_vec()], 254)
+            ),
+            other => panic!("unexpected variant: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn set_range_guards_rejects_bad_entries() {
+        let guard = |offset, len| RangeGuardSpec {
+            program_id: [3u8; 32],
+            offset,
+            len,
+        };
+        assert_eq!(
+            set_range_guards_instruction_data(0, &[guard(0, 1); MAX_RANGE_GUARDS + 1]),
+            Err(InstructionError::TooManyRangeGuards)
+        );
+        assert_eq!(
+            set_range_guards_instruction_data(0, &[guard(8, 0)]),
+            Err(InstructionError::InvalidRangeGuard)
+        );
+        assert_eq!(
+            set_range_guards_instruction_data(0, &[guard(255, 2)]),
+            Err(InstructionError::InvalidRangeGuard)
+        );
+    }
+
+    #[test]
+    fn set_constraints_rejects_bad_entries() {
+        let spec = |width, offset| ConstraintSpec {
+            region: ConstraintRegion::Aux as u8,
+            op: ConstraintOp::Ge as u8,
+            width,
+            signed: false,
+            offset,
+            rhs_kind: RHS_CONST,
+            rhs_offset: 0,
+            value: 1,
+        };
+        assert_eq!(
+            set_constraints_instruction_data(0, &[spec(8, 0); MAX_CONSTRAINTS + 1]),
+            Err(InstructionError::TooManyConstraints)
+        );
+        assert_eq!(
+            set_constraints_instruction_data(0, &[spec(3, 0)]),
+            Err(InstructionError::InvalidConstraint)
+        );
+        assert_eq!(
+            set_constraints_instruction_data(0, &[spec(8, 250)]),
+            Err(InstructionError::InvalidConstraint)
+        );
+        let data = set_constraints_instruction_data(7, &[spec(8, 248)]).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        assert!(matches!(
+            ix,
+            SlowPathInstruction::SetConstraints { bump: 7, ref constraints }
+                if constraints == &[spec(8, 248)]
+        ));
+    }
+
+    #[test]
+    fn split_update_halves_payload() {
+        let payload: Vec<u8> = (0..5).collect();
+        let [first, second] = split_update_instruction_data(0xAB, 9, &payload).unwrap();
+        for (data, part, tail) in [(&first, 1, &[0, 1][..]), (&second, 2, &[2, 3, 4][..])] {
+            assert_eq!(data[..4], SPLIT_UPDATE_TAG.to_le_bytes());
+            assert_eq!(
+                c_u_soon_instruction::read_split_update(data),
+                Some((0xAB, 9, part, tail))
+            );
+        }
+        assert_eq!(
+            split_update_instruction_data(0xAB, 9, &[]),
+            Err(InstructionError::InvalidExtAux)
+        );
+        assert_eq!(
+            split_update_instruction_data(0xAB, 9, &vec![0; MAX_EXT_AUX_SIZE + 1]),
+            Err(InstructionError::InvalidExtAux)
+        );
+        let [first, second] = split_update_typed(9, &[7u64; 40]).unwrap();
+        assert_eq!(
+            first.len() + second.len(),
+            2 * SPLIT_UPDATE_HEADER_SIZE + 320
+        );
+        assert_eq!(first[4..12], <[u64; 40]>::TYPE_HASH.to_le_bytes());
+    }
 
-/// Build `UpdateAuxiliaryDelegatedMultiRange` instruction data (wincode serialized).
-pub fn update_auxiliary_delegated_multi_range_instruction_data(
-    metadata: u64,
-    sequence: u64,
-    ranges: &[WriteSpec],
-) -> Vec<u8> {
-    wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange {
-        metadata,
-        sequence,
-        ranges: ranges.to_vec(),
-    })
-    .expect("delegated multi-range serialization failed")
-}
+    #[test]
+    fn publisher_update_is_tagged_fast_path_data() {
+        let fast = fast_path_instruction_data(0xAB, 9, &[1, 2, 3]).unwrap();
+        let data = publisher_update_instruction_data(0xAB, 9, &[1, 2, 3]).unwrap();
+        assert_eq!(data[..4], PUBLISHER_UPDATE_TAG.to_le_bytes());
+        assert_eq!(data[4..], fast[..]);
+        assert_eq!(publisher_update_typed(9, &7u64).unwrap().len(), 28);
+        assert_eq!(
+            publisher_update_instruction_data(0, 1, &[0; ORACLE_BYTES + 1]),
+            Err(InstructionError::PayloadTooLarge)
+        );
+    }
 
-/// Typed `UpdateAuxiliary`: derives metadata from `T::METADATA`.
-pub fn update_auxiliary_typed<T: TypeHash>(sequence: u64, value: &T) -> Vec<u8> {
-    update_auxiliary_instruction_data(T::METADATA.as_u64(), sequence, bytemuck::bytes_of(value))
-}
+    #[test]
+    fn versioned_wraps_with_current_version() {
+        let close = close_instruction_data().unwrap();
+        let data = versioned_instruction_data(&close);
+        assert_eq!(
+            c_u_soon_instruction::split_versioned(&data),
+            Some((INSTRUCTION_VERSION, close.as_slice()))
+        );
+    }
 
-/// Typed `UpdateAuxiliaryDelegated`: derives metadata from `T::METADATA`.
-pub fn update_auxiliary_delegated_typed<T: TypeHash>(sequence: u64, value: &T) -> Vec<u8> {
-    update_auxiliary_delegated_instruction_data(
-        T::METADATA.as_u64(),
-        sequence,
-        bytemuck::bytes_of(value),
-    )
-}
+    #[test]
+    fn close_with_split_rejects_bps_over_max() {
+        assert_eq!(
+            close_with_split_instruction_data(MAX_TREASURY_BPS + 1),
+            Err(InstructionError::InvalidTreasuryBps)
+        );
+        let data = close_with_split_instruction_data(MAX_TREASURY_BPS).unwrap();
+        assert!(matches!(
+            wincode::deserialize::<SlowPathInstruction>(&data).unwrap(),
+            SlowPathInstruction::CloseWithSplit {
+                treasury_bps: MAX_TREASURY_BPS
+            }
+        ));
+    }
 
-/// Typed `UpdateAuxiliaryForce`: derives metadata from `T::METADATA`.
-pub fn update_auxiliary_force_typed<T: TypeHash>(
-    authority_sequence: u64,
-    program_sequence: u64,
-    value: &T,
-) -> Vec<u8> {
-    update_auxiliary_force_instruction_data(
-        T::METADATA.as_u64(),
-        authority_sequence,
-        program_sequence,
-        bytemuck::bytes_of(value),
-    )
-}
+    #[test]
+    fn set_publishers_rejects_bad_sets() {
+        assert_eq!(
+            set_publishers_instruction_data(0, 2, &[[1; 32]]),
+            Err(InstructionError::InvalidPublisherSet)
+        );
+        assert_eq!(
+            set_publishers_instruction_data(0, 1, &[[1; 32], [1; 32]]),
+            Err(InstructionError::InvalidPublisherSet)
+        );
+        assert_eq!(
+            set_publishers_instruction_data(0, 1, &[[1; 32]; MAX_PUBLISHERS + 1]),
+            Err(InstructionError::InvalidPublisherSet)
+        );
+        assert!(set_publishers_instruction_data(0, 0, &[]).is_ok());
 
-/// Typed `Create`: derives oracle metadata from `T::METADATA` at compile time.
-///
-/// Emits a compile-time assertion that `size_of::<T>() <= ORACLE_BYTES`.
-/// Otherwise identical to [`create_instruction_data`].
-pub fn create_envelope_typed<T: TypeHash>(
-    custom_seeds: &[&[u8]],
-    bump: u8,
-) -> Result<Vec<u8>, InstructionError> {
-    const { assert!(core::mem::size_of::<T>() <= ORACLE_BYTES) };
-    create_instruction_data(custom_seeds, bump, T::METADATA)
-}
+        let data = set_publishers_instruction_data(5, 2, &[[1; 32], [2; 32]]).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        assert!(matches!(
+            ix,
+            SlowPathInstruction::SetPublishers { bump: 5, threshold: 2, ref publishers }
+                if publishers == &[[1; 32], [2; 32]]
+        ));
+    }
 
-/// Typed fast-path update: serializes `value` as oracle payload using `T::METADATA`.
-///
-/// Casts `value` to bytes via `bytemuck::bytes_of`. Emits a compile-time assertion that
-/// `size_of::<T>() <= ORACLE_BYTES`. Otherwise identical to [`fast_path_instruction_data`].
-pub fn fast_path_update_typed<T: TypeHash>(
-    sequence: u64,
-    value: &T,
-) -> Result<Vec<u8>, InstructionError> {
-    const { assert!(core::mem::size_of::<T>() <= ORACLE_BYTES) };
-    fast_path_instruction_data(T::METADATA.as_u64(), sequence, bytemuck::bytes_of(value))
-}
+    #[test]
+    fn set_readers_rejects_bad_lists() {
+        assert_eq!(
+            set_readers_instruction_data(0, &[[1; 32], [1; 32]]),
+            Err(InstructionError::InvalidReaderList)
+        );
+        assert_eq!(
+            set_readers_instruction_data(0, &[[0; 32]]),
+            Err(InstructionError::InvalidReaderList)
+        );
+        let too_many: Vec<[u8; 32]> = (1..=MAX_READERS as u8 + 1).map(|i| [i; 32]).collect();
+        assert_eq!(
+            set_readers_instruction_data(0, &too_many),
+            Err(InstructionError::InvalidReaderList)
+        );
+        assert!(set_readers_instruction_data(0, &[]).is_ok());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use c_u_soon::MASK_SIZE;
+        let data = set_readers_instruction_data(4, &[[1; 32], [2; 32]]).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        assert!(matches!(
+            ix,
+            SlowPathInstruction::SetReaders { bump: 4, ref readers }
+                if readers == &[[1; 32], [2; 32]]
+        ));
+    }
 
     #[test]
-    fn typed_create_matches_untyped() {
-        let seeds: &[&[u8]] = &[b"test"];
-        let typed = create_envelope_typed::<u32>(seeds, 42).unwrap();
-        let untyped = create_instruction_data(seeds, 42, u32::METADATA).unwrap();
-        assert_eq!(typed, untyped);
+    fn aggregate_roundtrip_and_limits() {
+        assert_eq!(
+            aggregate_instruction_data(1, 0, 100),
+            Err(InstructionError::InvalidMinSources)
+        );
+        assert_eq!(
+            aggregate_instruction_data(1, MAX_AGGREGATE_SOURCES as u8 + 1, 100),
+            Err(InstructionError::InvalidMinSources)
+        );
+
+        let data = aggregate_instruction_data(9, 3, 250).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        assert!(matches!(
+            ix,
+            SlowPathInstruction::Aggregate {
+                sequence: 9,
+                min_sources: 3,
+                max_deviation_bps: 250
+            }
+        ));
     }
 
     #[test]
-    fn typed_fast_path_matches_untyped() {
-        let value: u32 = 0xDEAD_BEEF;
-        let typed = fast_path_update_typed::<u32>(7, &value).unwrap();
-        let untyped =
-            fast_path_instruction_data(u32::METADATA.as_u64(), 7, bytemuck::bytes_of(&value))
-                .unwrap();
-        assert_eq!(typed, untyped);
+    fn set_publisher_roundtrip() {
+        let data = set_publisher_instruction_data([6; 32]).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        let SlowPathInstruction::SetPublisher { publisher } = ix else {
+            panic!("expected SetPublisher");
+        };
+        assert_eq!(publisher, [6; 32]);
+        assert_eq!(data[..4], 40u32.to_le_bytes());
     }
 
     #[test]
-    fn typed_fast_path_roundtrip() {
-        let value: u64 = 0x1234_5678_9ABC_DEF0;
-        let data = fast_path_update_typed::<u64>(99, &value).unwrap();
-        assert_eq!(data.len(), 8 + 8 + 8);
-        let meta = u64::from_le_bytes(data[0..8].try_into().unwrap());
-        let seq = u64::from_le_bytes(data[8..16].try_into().unwrap());
-        let payload: u64 = *bytemuck::from_bytes(&data[16..24]);
-        assert_eq!(meta, u64::METADATA.as_u64());
-        assert_eq!(seq, 99);
-        assert_eq!(payload, value);
+    fn set_paused_roundtrip() {
+        let data = set_paused_instruction_data(true).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        assert!(matches!(
+            ix,
+            SlowPathInstruction::SetPaused { paused: true }
+        ));
+        assert_eq!(data, [41, 0, 0, 0, 1]);
     }
 
     #[test]
-    fn fast_path_rejects_oversized_payload() {
+    fn seal_auxiliary_range_roundtrip() {
+        let data = seal_auxiliary_range_instruction_data(8, 4).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        assert!(matches!(
+            ix,
+            SlowPathInstruction::SealAuxiliaryRange { offset: 8, len: 4 }
+        ));
+        assert_eq!(data, [43, 0, 0, 0, 8, 0, 4, 0]);
+
+        assert!(matches!(
+            seal_auxiliary_range_instruction_data(0, 0),
+            Err(InstructionError::InvalidAuxRange)
+        ));
+        assert!(matches!(
+            seal_auxiliary_range_instruction_data(250, 7),
+            Err(InstructionError::InvalidAuxRange)
+        ));
+    }
+
+    #[test]
+    fn config_roundtrip() {
+        let data = init_config_instruction_data(254, [7u8; 32], 5_000).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        let SlowPathInstruction::InitConfig {
+            bump,
+            admin,
+            create_fee,
+        } = ix
+        else {
+            panic!("expected InitConfig");
+        };
+        assert_eq!((bump, admin, create_fee), (254, [7u8; 32], 5_000));
+        assert_eq!(data[..5], [44, 0, 0, 0, 254]);
+
+        let data = set_config_instruction_data([8u8; 32], 0, true).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        let SlowPathInstruction::SetConfig {
+            admin,
+            create_fee,
+            paused,
+        } = ix
+        else {
+            panic!("expected SetConfig");
+        };
+        assert_eq!((admin, create_fee, paused), ([8u8; 32], 0, true));
+        assert_eq!(data.len(), 4 + 32 + 8 + 1);
+
+        assert!(matches!(
+            init_config_instruction_data(254, [0u8; 32], 0),
+            Err(InstructionError::InvalidConfigAdmin)
+        ));
+        assert!(matches!(
+            set_config_instruction_data([0u8; 32], 0, false),
+            Err(InstructionError::InvalidConfigAdmin)
+        ));
+    }
+
+    #[test]
+    fn heartbeat_roundtrip() {
+        let data = heartbeat_instruction_data(7).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        assert!(matches!(ix, SlowPathInstruction::Heartbeat { sequence: 7 }));
+        assert_eq!(data, [46, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn migrate_envelope_roundtrip() {
+        let data = migrate_envelope_instruction_data().unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        assert!(matches!(ix, SlowPathInstruction::MigrateEnvelope));
+        assert_eq!(data, [55, 0, 0, 0]);
+    }
+
+    #[test]
+    fn set_range_guards_roundtrip() {
+        let entries = [RangeGuardSpec {
+            program_id: [3u8; 32],
+            offset: 16,
+            len: 8,
+        }];
+        let data = set_range_guards_instruction_data(253, &entries).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        match ix {
+            SlowPathInstruction::SetRangeGuards { bump, entries } => {
+                assert_eq!(bump, 253);
+                assert_eq!(entries.len(), 1);
+                assert_eq!((entries[0].offset, entries[0].len), (16, 8));
+            }
+            _ => panic!("Wrong variant"),
+        }
+    }
+
+    #[test]
+    fn set_delegate_schema_rejects_bad_span() {
+        assert_eq!(
+            set_delegate_schema_typed::<u64>(0, 249),
+            Err(InstructionError::InvalidDelegateSchema)
+        );
+        assert_eq!(
+            set_delegate_schema_instruction_data(0, 0, 0),
+            Err(InstructionError::InvalidDelegateSchema)
+        );
+    }
+
+    #[test]
+    fn set_delegate_schema_roundtrip() {
+        let data = set_delegate_schema_typed::<u64>(252, 16).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+        match ix {
+            SlowPathInstruction::SetDelegateSchema {
+                bump,
+                offset,
+                metadata,
+            } => {
+                assert_eq!((bump, offset), (252, 16));
+                assert_eq!(metadata, u64::METADATA.as_u64());
+            }
+            _ => panic!("Wrong variant"),
+        }
+    }
+
+    #[test]
+    fn read_aux_range_rejects_bad_range() {
+        assert_eq!(
+            read_aux_range_instruction_data(0, 0),
+            Err(InstructionError::InvalidAuxRange)
+        );
+        assert_eq!(
+            read_aux_range_instruction_data(250, 7),
+            Err(InstructionError::InvalidAuxRange)
+        );
+        let ix: SlowPathInstruction =
+            wincode::deserialize(&read_aux_range_instruction_data(0, 256).unwrap()).unwrap();
+        assert!(matches!(
+            ix,
+            SlowPathInstruction::ReadAuxRange {
+                offset: 0,
+                len: 256
+            }
+        ));
+    }
+
+    #[test]
+    fn batch_update_wire_format_and_limits() {
+        let entry = |sequence, payload| BatchEntry {
+            oracle_meta: 0xAA,
+            sequence,
+            payload,
+        };
+        let data = batch_update_instruction_data(&[entry(1, &[1, 2]), entry(9, &[])]).unwrap();
+        assert_eq!(data[..4], BATCH_UPDATE_TAG.to_le_bytes());
+        assert_eq!(data[4], 2);
+        assert_eq!(data[5..13], 0xAAu64.to_le_bytes());
+        assert_eq!(data[13..21], 1u64.to_le_bytes());
+        assert_eq!(data[21..24], [2, 1, 2]);
+        assert_eq!(data[32..41], [9, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            data.len(),
+            BATCH_UPDATE_HEADER_SIZE + 2 * BATCH_ENTRY_HEADER_SIZE + 2
+        );
+
+        assert_eq!(
+            batch_update_instruction_data(&[]),
+            Err(InstructionError::InvalidBatchSize)
+        );
+        assert!(batch_update_instruction_data(&[entry(1, &[])]).is_ok());
+        let many = vec![entry(1, &[]); MAX_BATCH_SIZE + 1];
+        assert_eq!(
+            batch_update_instruction_data(&many),
+            Err(InstructionError::InvalidBatchSize)
+        );
         let big = [0u8; ORACLE_BYTES + 1];
         assert_eq!(
-            fast_path_instruction_data(0, 1, &big),
+            batch_update_instruction_data(&[entry(1, &[]), entry(2, &big)]),
             Err(InstructionError::PayloadTooLarge)
         );
     }
 
     #[test]
-    fn fast_path_accepts_max_payload() {
-        let max = [0u8; ORACLE_BYTES];
-        assert!(fast_path_instruction_data(0, 1, &max).is_ok());
+    fn multi_range_builders_enforce_range_cap() {
+        let specs = |count: usize| -> Vec<WriteSpec> {
+            (0..count)
+                .map(|i| WriteSpec {
+                    offset: 2 * i as u8,
+                    data: vec![1],
+                })
+                .collect()
+        };
+        let max = specs(MAX_WRITE_RANGES);
+        let over = specs(MAX_WRITE_RANGES + 1);
+        assert!(update_auxiliary_multi_range_instruction_data(0, 1, &max).is_ok());
+        assert!(update_auxiliary_delegated_multi_range_instruction_data(0, 1, &max).is_ok());
+        assert!(update_auxiliary_multi_range_force_instruction_data(0, 1, 1, &max).is_ok());
+        assert_eq!(
+            update_auxiliary_multi_range_instruction_data(0, 1, &over),
+            Err(InstructionError::TooManyRanges)
+        );
+        assert_eq!(
+            update_auxiliary_delegated_multi_range_instruction_data(0, 1, &over),
+            Err(InstructionError::TooManyRanges)
+        );
+        assert_eq!(
+            update_auxiliary_multi_range_force_instruction_data(0, 1, 1, &over),
+            Err(InstructionError::TooManyRanges)
+        );
+
+        let overlapping = [
+            WriteSpec {
+                offset: 4,
+                data: vec![1; 4],
+            },
+            WriteSpec {
+                offset: 0,
+                data: vec![2; 5],
+            },
+        ];
+        assert_eq!(
+            update_auxiliary_multi_range_instruction_data(0, 1, &overlapping),
+            Err(InstructionError::OverlappingRanges)
+        );
+        assert_eq!(
+            update_auxiliary_delegated_multi_range_instruction_data(0, 1, &overlapping),
+            Err(InstructionError::OverlappingRanges)
+        );
+        assert_eq!(
+            update_auxiliary_multi_range_force_instruction_data(0, 1, 1, &overlapping),
+            Err(InstructionError::OverlappingRanges)
+        );
     }
 
     #[test]
-    fn create_rejects_too_many_seeds() {
-        let seeds: Vec<&[u8]> = (0..14).map(|_| b"x" as &[u8]).collect();
+    fn multi_range_v2_builders_check_bounds() {
+        let spec = |offset: u16, len: usize| WriteSpecV2 {
+            offset,
+            data: vec![1; len],
+        };
+        let ok = [spec(0, 4), spec(252, 4)];
+        let data = update_auxiliary_multi_range_v2_instruction_data(0, 1, &ok).unwrap();
+        assert_eq!(data[..4], 52u32.to_le_bytes());
+        let data = update_auxiliary_delegated_multi_range_v2_instruction_data(0, 1, &ok).unwrap();
+        assert_eq!(data[..4], 53u32.to_le_bytes());
+        let data = update_auxiliary_multi_range_force_v2_instruction_data(0, 1, 1, &ok).unwrap();
+        assert_eq!(data[..4], 54u32.to_le_bytes());
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        assert!(ix.validate());
+
+        for bad in [spec(253, 4), spec(300, 1), spec(0, 0)] {
+            assert_eq!(
+                update_auxiliary_multi_range_v2_instruction_data(0, 1, core::slice::from_ref(&bad)),
+                Err(InstructionError::InvalidAuxRange)
+            );
+            assert_eq!(
+                update_auxiliary_delegated_multi_range_v2_instruction_data(
+                    0,
+                    1,
+                    core::slice::from_ref(&bad)
+                ),
+                Err(InstructionError::InvalidAuxRange)
+            );
+            assert_eq!(
+                update_auxiliary_multi_range_force_v2_instruction_data(0, 1, 1, &[bad]),
+                Err(InstructionError::InvalidAuxRange)
+            );
+        }
         assert_eq!(
-            create_instruction_data(&seeds, 0, u32::METADATA),
-            Err(InstructionError::TooManySeeds)
+            update_auxiliary_multi_range_v2_instruction_data(0, 1, &[spec(0, 4), spec(2, 4)]),
+            Err(InstructionError::OverlappingRanges)
+        );
+        let over: Vec<_> = (0..MAX_WRITE_RANGES as u16 + 1)
+            .map(|i| spec(2 * i, 1))
+            .collect();
+        assert_eq!(
+            update_auxiliary_multi_range_v2_instruction_data(0, 1, &over),
+            Err(InstructionError::TooManyRanges)
+        );
+
+        // A V1 range converts to the same bytes at the same offset.
+        let v1 = WriteSpec {
+            offset: 9,
+            data: vec![3; 2],
+        };
+        let v2 = WriteSpecV2::from(v1);
+        assert_eq!(
+            (v2.offset, v2.data.as_slice(), v2.end()),
+            (9, &[3, 3][..], 11)
         );
     }
 
     #[test]
-    fn create_rejects_long_seed() {
-        let long = [0u8; 33];
-        let seeds: &[&[u8]] = &[&long];
+    fn read_oracle_and_read_aux_roundtrip() {
+        let ix: SlowPathInstruction =
+            wincode::deserialize(&read_oracle_instruction_data().unwrap()).unwrap();
+        assert!(matches!(ix, SlowPathInstruction::ReadOracle));
+        let ix: SlowPathInstruction =
+            wincode::deserialize(&read_oracle_gated_instruction_data().unwrap()).unwrap();
+        assert!(matches!(ix, SlowPathInstruction::ReadOracleGated));
+        let ix: SlowPathInstruction =
+            wincode::deserialize(&read_aux_instruction_data().unwrap()).unwrap();
+        assert!(matches!(ix, SlowPathInstruction::ReadAux));
+        let ix: SlowPathInstruction =
+            wincode::deserialize(&export_state_instruction_data().unwrap()).unwrap();
+        assert!(matches!(ix, SlowPathInstruction::ExportState));
+    }
+
+    #[test]
+    fn group_commit_roundtrip_and_limits() {
+        let update = |meta, len| GroupMemberUpdate {
+            oracle_meta: meta,
+            payload: vec![0xAB; len],
+        };
+        let data = group_commit_instruction_data(7, &[update(1, 8), update(2, 16)]).unwrap();
+        let ix: SlowPathInstruction = wincode::deserialize(&data).unwrap();
+        match ix {
+            SlowPathInstruction::GroupCommit { sequence, updates } => {
+                assert_eq!(sequence, 7);
+                assert_eq!(updates.len(), 2);
+                assert_eq!(updates[1].oracle_meta, 2);
+                assert_eq!(updates[1].payload, vec![0xAB; 16]);
+            }
+            _ => panic!("Wrong variant"),
+        }
+
         assert_eq!(
-            create_instruction_data(seeds, 0, u32::METADATA),
-            Err(InstructionError::SeedTooLong)
+            group_commit_instruction_data(7, &[]),
+            Err(InstructionError::InvalidGroupSize)
+        );
+        let too_many: Vec<_> = (0..=MAX_GROUP_MEMBERS).map(|_| update(1, 8)).collect();
+        assert_eq!(
+            group_commit_instruction_data(7, &too_many),
+            Err(InstructionError::InvalidGroupSize)
+        );
+        assert_eq!(
+            group_commit_instruction_data(7, &[update(1, ORACLE_BYTES + 1)]),
+            Err(InstructionError::PayloadTooLarge)
         );
     }
 
     #[test]
-    fn set_delegation_rejects_non_canonical_mask() {
-        let mut bad = [0x00u8; MASK_SIZE];
-        bad[5] = 0x42;
+    fn set_label_pads_and_rejects_bad_labels() {
+        let ix: SlowPathInstruction =
+            wincode::deserialize(&set_label_instruction_data(254, "ETH/USD").unwrap()).unwrap();
+        match ix {
+            SlowPathInstruction::SetLabel { bump, label } => {
+                assert_eq!(bump, 254);
+                assert_eq!(&label[..7], b"ETH/USD");
+                assert!(label[7..].iter().all(|&b| b == 0));
+            }
+            _ => panic!("Wrong variant"),
+        }
+
+        for bad in ["", "a\0b", &"x".repeat(LABEL_SIZE + 1)] {
+            assert_eq!(
+                set_label_instruction_data(0, bad),
+                Err(InstructionError::InvalidLabel)
+            );
+        }
+        assert!(set_label_instruction_data(0, &"x".repeat(LABEL_SIZE)).is_ok());
+    }
+
+    #[test]
+    fn ext_aux_builders_roundtrip_and_reject_bad_sizes() {
+        let ix: SlowPathInstruction =
+            wincode::deserialize(&resize_instruction_data(9, MAX_EXT_AUX_SIZE).unwrap()).unwrap();
+        assert!(matches!(
+            ix,
+            SlowPathInstruction::Resize { type_hash: 9, ext_len } if ext_len as usize == MAX_EXT_AUX_SIZE
+        ));
         assert_eq!(
-            set_delegated_program_instruction_data(Mask::from(bad), Mask::ALL_BLOCKED),
-            Err(InstructionError::NonCanonicalMask)
+            resize_instruction_data(9, MAX_EXT_AUX_SIZE + 1),
+            Err(InstructionError::InvalidExtAux)
+        );
+
+        let ix: SlowPathInstruction =
+            wincode::deserialize(&update_ext_aux_instruction_data(3, 100, &[1, 2]).unwrap())
+                .unwrap();
+        match ix {
+            SlowPathInstruction::UpdateExtAux {
+                sequence,
+                offset,
+                data,
+            } => {
+                assert_eq!((sequence, offset), (3, 100));
+                assert_eq!(data, vec![1, 2]);
+            }
+            _ => panic!("Wrong variant"),
+        }
+        assert_eq!(
+            update_ext_aux_instruction_data(3, 0, &[]),
+            Err(InstructionError::InvalidExtAux)
+        );
+        assert_eq!(
+            update_ext_aux_instruction_data(3, MAX_EXT_AUX_SIZE, &[1]),
+            Err(InstructionError::InvalidExtAux)
         );
     }
 
     #[test]
-    fn set_delegation_accepts_canonical_masks() {
-        assert!(
-            set_delegated_program_instruction_data(Mask::ALL_WRITABLE, Mask::ALL_BLOCKED).is_ok()
-        );
+    fn aux_hash_covers_only_aux_data() {
+        use bytemuck::Zeroable;
+        let mut envelope = Envelope::zeroed();
+        // SHA-256 of 256 zero bytes.
+        assert_eq!(aux_hash(&envelope)[..4], [0x53, 0x41, 0xe6, 0xb2]);
+
+        envelope.auxiliary_data[255] = 1;
+        let changed = aux_hash(&envelope);
+        assert_ne!(changed[..4], [0x53, 0x41, 0xe6, 0xb2]);
+
+        envelope.oracle_state.sequence = 9;
+        envelope.authority_aux_sequence = 3;
+        assert_eq!(aux_hash(&envelope), changed);
+    }
+
+    #[test]
+    fn envelope_state_hash_covers_every_field() {
+        use bytemuck::Zeroable;
+        let mut envelope = Envelope::zeroed();
+        let zero = envelope_state_hash(&envelope);
+        envelope.heartbeat_slot = 1;
+        let stamped = envelope_state_hash(&envelope);
+        assert_ne!(stamped, zero);
+        envelope.auxiliary_data[0] = 1;
+        assert_ne!(envelope_state_hash(&envelope), stamped);
+    }
+
+    #[test]
+    fn typed_update_aux_delegated_schema_layout() {
+        let value: u64 = 0x0102_0304;
+        let data = update_auxiliary_delegated_schema_typed::<u64>(11, &value);
+        assert_eq!(&data[..4], &UPDATE_AUX_DELEGATED_SCHEMA_TAG.to_le_bytes());
+        assert_eq!(&data[4..12], &u64::METADATA.as_u64().to_le_bytes());
+        assert_eq!(&data[12..20], &11u64.to_le_bytes());
+        assert_eq!(&data[20..], &value.to_le_bytes());
     }
 
     #[test]
@@ -0,0 +1,349 @@
+//! Unsigned transactions for offline and hardware-wallet signing.
+//!
+//! Administrative instructions (`Close`, delegation, `SetPublisher`, `SetPaused`,
+//! `SetConfig`, ...) are signed by keys that should never sit on an online machine.
+//! [`UnsignedTransaction`] compiles the instructions from the `build_*_ix` helpers into a
+//! legacy Solana message and serializes it as a wire transaction whose signatures are all
+//! zero, the format `solana` tooling and signing pipelines exchange for offline signing.
+//!
+//! The recent blockhash is a zeroed placeholder at [`UnsignedTransaction::blockhash_offset`]:
+//! a blockhash expires after about 150 slots, so it is slotted in with
+//! [`set_blockhash`](UnsignedTransaction::set_blockhash) just before the message goes to
+//! the signer. Each signer signs [`message`](UnsignedTransaction::message); its signature is
+//! placed with [`set_signature`](UnsignedTransaction::set_signature), after which
+//! [`as_bytes`](UnsignedTransaction::as_bytes) is ready to send.
+//!
+//! No signing or RPC code is involved, so the crate needs no keypair or network dependency.
+
+use solana_address::Address;
+use solana_instruction::Instruction;
+
+/// Largest serialized transaction the cluster accepts (`PACKET_DATA_SIZE`).
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Recent blockhash of a freshly built [`UnsignedTransaction`].
+pub const BLOCKHASH_PLACEHOLDER: [u8; 32] = [0; 32];
+
+const SIGNATURE_SIZE: usize = 64;
+
+/// Legacy messages index accounts with one byte.
+const MAX_ACCOUNT_KEYS: usize = 256;
+
+/// Why instructions could not be compiled into an [`UnsignedTransaction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OfflineError {
+    /// No instructions were given.
+    NoInstructions,
+    /// The instructions reference more distinct accounts than a legacy message can index.
+    TooManyAccounts(usize),
+    /// The serialized transaction exceeds [`MAX_TRANSACTION_SIZE`] bytes.
+    TooLarge(usize),
+}
+
+impl core::fmt::Display for OfflineError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NoInstructions => write!(f, "transaction has no instructions"),
+            Self::TooManyAccounts(n) => {
+                write!(
+                    f,
+                    "{n} accounts, at most {MAX_ACCOUNT_KEYS} fit a legacy message"
+                )
+            }
+            Self::TooLarge(n) => {
+                write!(
+                    f,
+                    "transaction is {n} bytes, at most {MAX_TRANSACTION_SIZE} are allowed"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OfflineError {}
+
+/// A serialized legacy transaction waiting for its blockhash and signatures.
+///
+/// Layout: `[signature count][signatures: 64 * n][message]`, where the message is
+/// `[header: 3][account keys][recent blockhash: 32][instructions]` with compact-u16 lengths.
+/// Signature `i` belongs to [`signers`](Self::signers)`()[i]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsignedTransaction {
+    bytes: Vec<u8>,
+    signers: Vec<Address>,
+    message_offset: usize,
+    blockhash_offset: usize,
+}
+
+impl UnsignedTransaction {
+    /// Compile `instructions` with `fee_payer` as the first signer and a
+    /// [`BLOCKHASH_PLACEHOLDER`].
+    ///
+    /// Accounts are ordered writable signers, read-only signers, writable non-signers, then
+    /// read-only non-signers, each group in order of first use, with `fee_payer` first. An
+    /// account used more than once gets the union of its signer and writable flags.
+    pub fn new(fee_payer: &Address, instructions: &[Instruction]) -> Result<Self, OfflineError> {
+        if instructions.is_empty() {
+            return Err(OfflineError::NoInstructions);
+        }
+
+        let mut keys = vec![(*fee_payer, true, true)];
+        for ix in instructions {
+            for meta in &ix.accounts {
+                add_key(&mut keys, &meta.pubkey, meta.is_signer, meta.is_writable);
+            }
+            add_key(&mut keys, &ix.program_id, false, false);
+        }
+        if keys.len() > MAX_ACCOUNT_KEYS {
+            return Err(OfflineError::TooManyAccounts(keys.len()));
+        }
+        keys.sort_by_key(|&(_, signer, writable)| (!signer, !writable));
+
+        let count = |signer: bool, writable: bool| {
+            keys.iter()
+                .filter(|&&(_, s, w)| s == signer && w == writable)
+                .count()
+        };
+        let signers: Vec<Address> = keys.iter().filter(|k| k.1).map(|k| k.0).collect();
+        let index = |address: &Address| keys.iter().position(|k| &k.0 == address).unwrap() as u8;
+
+        let mut bytes = Vec::new();
+        write_compact_u16(&mut bytes, signers.len());
+        bytes.resize(bytes.len() + SIGNATURE_SIZE * signers.len(), 0);
+        let message_offset = bytes.len();
+
+        bytes.extend_from_slice(&[
+            signers.len() as u8,
+            count(true, false) as u8,
+            count(false, false) as u8,
+        ]);
+        write_compact_u16(&mut bytes, keys.len());
+        for (key, _, _) in &keys {
+            bytes.extend_from_slice(key.as_ref());
+        }
+        let blockhash_offset = bytes.len();
+        bytes.extend_from_slice(&BLOCKHASH_PLACEHOLDER);
+
+        write_compact_u16(&mut bytes, instructions.len());
+        for ix in instructions {
+            bytes.push(index(&ix.program_id));
+            write_compact_u16(&mut bytes, ix.accounts.len());
+            bytes.extend(ix.accounts.iter().map(|meta| index(&meta.pubkey)));
+            write_compact_u16(&mut bytes, ix.data.len());
+            bytes.extend_from_slice(&ix.data);
+        }
+
+        if bytes.len() > MAX_TRANSACTION_SIZE {
+            return Err(OfflineError::TooLarge(bytes.len()));
+        }
+        Ok(Self {
+            bytes,
+            signers,
+            message_offset,
+            blockhash_offset,
+        })
+    }
+
+    /// The required signers, fee payer first, in signature order.
+    pub fn signers(&self) -> &[Address] {
+        &self.signers
+    }
+
+    /// The message bytes every signer signs. Set the blockhash first.
+    pub fn message(&self) -> &[u8] {
+        &self.bytes[self.message_offset..]
+    }
+
+    /// Offset of the 32-byte recent blockhash within [`as_bytes`](Self::as_bytes), for
+    /// pipelines that patch it in themselves.
+    pub fn blockhash_offset(&self) -> usize {
+        self.blockhash_offset
+    }
+
+    /// The recent blockhash currently in the message.
+    pub fn blockhash(&self) -> [u8; 32] {
+        self.bytes[self.blockhash_offset..self.blockhash_offset + 32]
+            .try_into()
+            .unwrap()
+    }
+
+    /// Slot in the recent blockhash. Signatures made over the previous message no longer
+    /// verify, so they are cleared.
+    pub fn set_blockhash(&mut self, blockhash: [u8; 32]) {
+        self.bytes[self.blockhash_offset..self.blockhash_offset + 32].copy_from_slice(&blockhash);
+        self.bytes[self.message_offset - SIGNATURE_SIZE * self.signers.len()..self.message_offset]
+            .fill(0);
+    }
+
+    /// Place `signer`'s signature over [`message`](Self::message). Returns `false` if
+    /// `signer` is not a required signer. The signature is not verified.
+    pub fn set_signature(&mut self, signer: &Address, signature: [u8; 64]) -> bool {
+        let Some(i) = self.signers.iter().position(|s| s == signer) else {
+            return false;
+        };
+        let start = self.signature_offset(i);
+        self.bytes[start..start + SIGNATURE_SIZE].copy_from_slice(&signature);
+        true
+    }
+
+    /// Returns `true` once every signature is set (non-zero).
+    pub fn is_fully_signed(&self) -> bool {
+        (0..self.signers.len()).all(|i| {
+            let start = self.signature_offset(i);
+            self.bytes[start..start + SIGNATURE_SIZE] != [0; SIGNATURE_SIZE]
+        })
+    }
+
+    /// The wire transaction, with zeroed signatures where none are set yet.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn signature_offset(&self, index: usize) -> usize {
+        self.message_offset - SIGNATURE_SIZE * (self.signers.len() - index)
+    }
+}
+
+/// Add `address` to `keys`, or widen the flags of its existing entry.
+fn add_key(
+    keys: &mut Vec<(Address, bool, bool)>,
+    address: &Address,
+    is_signer: bool,
+    is_writable: bool,
+) {
+    match keys.iter_mut().find(|(key, _, _)| key == address) {
+        Some((_, signer, writable)) => {
+            *signer |= is_signer;
+            *writable |= is_writable;
+        }
+        None => keys.push((*address, is_signer, is_writable)),
+    }
+}
+
+/// Solana's compact-u16 ("shortvec") length prefix: 7 bits per byte, low bits first.
+fn write_compact_u16(out: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_close_ix, build_set_paused_ix};
+
+    fn address(byte: u8) -> Address {
+        Address::new_from_array([byte; 32])
+    }
+
+    #[test]
+    fn compiles_authority_paid_instruction() {
+        let (program, authority, envelope) = (address(9), address(1), address(2));
+        let ix = build_set_paused_ix(&program, &authority, &envelope, true).unwrap();
+        let tx = UnsignedTransaction::new(&authority, core::slice::from_ref(&ix)).unwrap();
+
+        assert_eq!(tx.signers(), [authority]);
+        let bytes = tx.as_bytes();
+        assert_eq!(bytes[0], 1);
+        assert!(bytes[1..65].iter().all(|&b| b == 0));
+
+        let message = tx.message();
+        // One signer, no read-only signers; the system program padding, the config and the
+        // program are read-only.
+        assert_eq!(message[..4], [1, 0, 3, 5]);
+        let keys: Vec<&[u8]> = message[4..4 + 5 * 32].chunks(32).collect();
+        let padding = ix.accounts[2].pubkey;
+        let config = ix.accounts[3].pubkey;
+        assert_eq!(
+            keys,
+            [
+                authority.as_ref(),
+                envelope.as_ref(),
+                padding.as_ref(),
+                config.as_ref(),
+                program.as_ref()
+            ]
+        );
+        assert_eq!(tx.blockhash_offset(), 65 + 4 + 5 * 32);
+        assert_eq!(tx.blockhash(), BLOCKHASH_PLACEHOLDER);
+
+        let instructions = &bytes[tx.blockhash_offset() + 32..];
+        assert_eq!(instructions[..7], [1, 4, 4, 0, 1, 2, 3]);
+        assert_eq!(instructions[7] as usize, ix.data.len());
+        assert_eq!(instructions[8..], ix.data[..]);
+    }
+
+    #[test]
+    fn separate_fee_payer_signs_first() {
+        let (program, payer, authority) = (address(9), address(1), address(3));
+        let close = build_close_ix(&program, &authority, &address(4), &address(5)).unwrap();
+        let pause = build_set_paused_ix(&program, &authority, &address(4), true).unwrap();
+        let tx = UnsignedTransaction::new(&payer, &[close, pause]).unwrap();
+
+        assert_eq!(tx.signers(), [payer, authority]);
+        assert_eq!(tx.as_bytes()[0], 2);
+        // The authority signs read-only; the padding, the config and the program are
+        // read-only too.
+        assert_eq!(tx.message()[..3], [2, 1, 3]);
+    }
+
+    #[test]
+    fn blockhash_and_signatures_slot_in() {
+        let (program, payer, authority) = (address(9), address(1), address(3));
+        let ix = build_set_paused_ix(&program, &authority, &address(4), false).unwrap();
+        let mut tx = UnsignedTransaction::new(&payer, &[ix]).unwrap();
+
+        assert!(!tx.set_signature(&address(7), [1; 64]));
+        assert!(tx.set_signature(&authority, [2; 64]));
+        assert!(!tx.is_fully_signed());
+
+        tx.set_blockhash([5; 32]);
+        assert_eq!(tx.blockhash(), [5; 32]);
+        assert_eq!(tx.as_bytes()[tx.blockhash_offset()], 5);
+        assert_eq!(tx.message().len(), tx.as_bytes().len() - 1 - 2 * 64);
+        assert!(
+            tx.as_bytes()[1..129].iter().all(|&b| b == 0),
+            "stale signature"
+        );
+
+        assert!(tx.set_signature(&payer, [1; 64]));
+        assert!(tx.set_signature(&authority, [2; 64]));
+        assert!(tx.is_fully_signed());
+        assert_eq!(tx.as_bytes()[1..65], [1; 64]);
+        assert_eq!(tx.as_bytes()[65..129], [2; 64]);
+    }
+
+    #[test]
+    fn rejects_empty_and_oversized() {
+        assert_eq!(
+            UnsignedTransaction::new(&address(1), &[]),
+            Err(OfflineError::NoInstructions)
+        );
+        let ix = Instruction::new_with_bytes(address(9), &[0; MAX_TRANSACTION_SIZE], vec![]);
+        assert!(matches!(
+            UnsignedTransaction::new(&address(1), &[ix]),
+            Err(OfflineError::TooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn compact_u16_encoding() {
+        for (value, expected) in [
+            (0, &[0x00][..]),
+            (0x7f, &[0x7f]),
+            (0x80, &[0x80, 0x01]),
+            (0x3fff, &[0xff, 0x7f]),
+            (0x4000, &[0x80, 0x80, 0x01]),
+        ] {
+            let mut out = Vec::new();
+            write_compact_u16(&mut out, value);
+            assert_eq!(out, expected, "{value:#x}");
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! Cache of envelope PDA derivations.
+//!
+//! `find_program_address` tries bumps from 255 down, hashing each candidate, so a publisher
+//! managing thousands of envelopes pays for that search on every restart. [`PdaCache`] maps
+//! `(program_id, authority, custom_seeds)` to `(address, bump)` in memory, evicts the least
+//! recently used entry past its capacity, and can persist to a file. Entries loaded from disk
+//! are re-checked with a single `create_program_address` call, so a corrupted or edited file
+//! cannot hand out a wrong address.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use c_u_soon::{derive_envelope_address, verify_envelope_address};
+use solana_address::Address;
+
+const FILE_MAGIC: &[u8; 8] = b"CUSPDA1\0";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PdaKey {
+    program_id: Address,
+    authority: Address,
+    custom_seeds: Vec<Vec<u8>>,
+}
+
+impl PdaKey {
+    fn new(program_id: &Address, authority: &Address, custom_seeds: &[&[u8]]) -> Self {
+        Self {
+            program_id: *program_id,
+            authority: *authority,
+            custom_seeds: custom_seeds.iter().map(|s| s.to_vec()).collect(),
+        }
+    }
+
+    fn custom_seeds(&self) -> Vec<&[u8]> {
+        self.custom_seeds.iter().map(Vec::as_slice).collect()
+    }
+
+    fn derive(&self) -> (Address, u8) {
+        derive_envelope_address(&self.program_id, &self.authority, &self.custom_seeds())
+    }
+
+    fn verify(&self, address: &Address, bump: u8) -> bool {
+        verify_envelope_address(
+            &self.program_id,
+            &self.authority,
+            &self.custom_seeds(),
+            bump,
+            address,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PdaEntry {
+    address: Address,
+    bump: u8,
+    last_used: u64,
+}
+
+/// Envelope PDA cache with LRU eviction and optional file persistence.
+#[derive(Debug, Clone)]
+pub struct PdaCache {
+    entries: HashMap<PdaKey, PdaEntry>,
+    capacity: usize,
+    path: Option<PathBuf>,
+    clock: u64,
+}
+
+impl PdaCache {
+    /// In-memory cache holding at most `capacity` entries (at least one).
+    pub fn in_memory(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity: capacity.max(1),
+            path: None,
+            clock: 0,
+        }
+    }
+
+    /// Cache backed by `path`. Loads existing entries if the file exists; entries that fail
+    /// re-verification are dropped. [`save`](Self::save) writes back to the same path.
+    ///
+    /// Returns [`io::ErrorKind::InvalidData`] if the file exists but is not a cache file.
+    pub fn open(path: impl Into<PathBuf>, capacity: usize) -> io::Result<Self> {
+        let mut cache = Self::in_memory(capacity);
+        let path = path.into();
+        match fs::read(&path) {
+            Ok(bytes) => cache.load(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        cache.path = Some(path);
+        Ok(cache)
+    }
+
+    /// Persistence path, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Cached `(address, bump)` without deriving on a miss.
+    pub fn get(
+        &mut self,
+        program_id: &Address,
+        authority: &Address,
+        custom_seeds: &[&[u8]],
+    ) -> Option<(Address, u8)> {
+        let key = PdaKey::new(program_id, authority, custom_seeds);
+        let now = self.tick();
+        let entry = self.entries.get_mut(&key)?;
+        entry.last_used = now;
+        Some((entry.address, entry.bump))
+    }
+
+    /// Envelope address and canonical bump, derived with `find_program_address` on a miss.
+    ///
+    /// `custom_seeds` must satisfy the same limits as `Create` (at most [`MAX_CUSTOM_SEEDS`]
+    /// seeds of at most 32 bytes); `find_program_address` panics otherwise.
+    ///
+    /// [`MAX_CUSTOM_SEEDS`]: c_u_soon::MAX_CUSTOM_SEEDS
+    pub fn envelope(
+        &mut self,
+        program_id: &Address,
+        authority: &Address,
+        custom_seeds: &[&[u8]],
+    ) -> (Address, u8) {
+        if let Some(hit) = self.get(program_id, authority, custom_seeds) {
+            return hit;
+        }
+        let key = PdaKey::new(program_id, authority, custom_seeds);
+        let (address, bump) = key.derive();
+        self.insert(key, address, bump);
+        (address, bump)
+    }
+
+    /// Derive every missing entry in `envelopes` ahead of time. Returns how many were
+    /// derived; entries already cached are only marked as used.
+    pub fn warm_up<'a, I>(&mut self, program_id: &Address, envelopes: I) -> usize
+    where
+        I: IntoIterator<Item = (Address, &'a [&'a [u8]])>,
+    {
+        let mut derived = 0;
+        for (authority, custom_seeds) in envelopes {
+            if self.get(program_id, &authority, custom_seeds).is_none() {
+                self.envelope(program_id, &authority, custom_seeds);
+                derived += 1;
+            }
+        }
+        derived
+    }
+
+    /// Drop one entry. Returns `true` if it was cached.
+    pub fn invalidate(
+        &mut self,
+        program_id: &Address,
+        authority: &Address,
+        custom_seeds: &[&[u8]],
+    ) -> bool {
+        self.entries
+            .remove(&PdaKey::new(program_id, authority, custom_seeds))
+            .is_some()
+    }
+
+    /// Drop every entry derived for `program_id`, e.g. after redeploying to a new address.
+    pub fn invalidate_program(&mut self, program_id: &Address) {
+        self.entries.retain(|key, _| &key.program_id != program_id);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Write all entries to [`path`](Self::path), replacing the file atomically. No-op for an
+    /// in-memory cache.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.to_bytes())?;
+        fs::rename(&tmp, path)
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn insert(&mut self, key: PdaKey, address: Address, bump: u8) {
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        let last_used = self.tick();
+        self.entries.insert(
+            key,
+            PdaEntry {
+                address,
+                bump,
+                last_used,
+            },
+        );
+    }
+
+    /// `[magic:8]` then per entry, least recently used first:
+    /// `[program_id:32][authority:32][address:32][bump:1][num_seeds:1]([len:1][seed])*`.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by_key(|(_, entry)| entry.last_used);
+
+        let mut out = FILE_MAGIC.to_vec();
+        for (key, entry) in entries {
+            out.extend_from_slice(key.program_id.as_ref());
+            out.extend_from_slice(key.authority.as_ref());
+            out.extend_from_slice(entry.address.as_ref());
+            out.push(entry.bump);
+            out.push(key.custom_seeds.len() as u8);
+            for seed in &key.custom_seeds {
+                out.push(seed.len() as u8);
+                out.extend_from_slice(seed);
+            }
+        }
+        out
+    }
+
+    fn load(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "not a PDA cache file");
+        let mut rest = bytes.strip_prefix(FILE_MAGIC).ok_or_else(invalid)?;
+        while !rest.is_empty() {
+            let program_id = take_address(&mut rest).ok_or_else(invalid)?;
+            let authority = take_address(&mut rest).ok_or_else(invalid)?;
+            let address = take_address(&mut rest).ok_or_else(invalid)?;
+            let bump = take(&mut rest, 1).ok_or_else(invalid)?[0];
+            let num_seeds = take(&mut rest, 1).ok_or_else(invalid)?[0];
+            let mut custom_seeds = Vec::with_capacity(num_seeds as usize);
+            for _ in 0..num_seeds {
+                let len = take(&mut rest, 1).ok_or_else(invalid)?[0];
+                custom_seeds.push(take(&mut rest, len as usize).ok_or_else(invalid)?.to_vec());
+            }
+            let key = PdaKey {
+                program_id,
+                authority,
+                custom_seeds,
+            };
+            if key.verify(&address, bump) {
+                self.insert(key, address, bump);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn take<'a>(rest: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if rest.len() < len {
+        return None;
+    }
+    let (head, tail) = rest.split_at(len);
+    *rest = tail;
+    Some(head)
+}
+
+fn take_address(rest: &mut &[u8]) -> Option<Address> {
+    let bytes: [u8; 32] = take(rest, 32)?.try_into().ok()?;
+    Some(Address::new_from_array(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c_u_soon::ENVELOPE_SEED;
+
+    const PROGRAM_ID: Address = Address::new_from_array([7; 32]);
+
+    fn authority(n: u8) -> Address {
+        Address::new_from_array([n; 32])
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("c_u_soon_pda_cache_{}_{name}", std::process::id()))
+    }
+
+    #[test]
+    fn envelope_matches_find_program_address() {
+        let mut cache = PdaCache::in_memory(8);
+        let seeds: &[&[u8]] = &[b"btc-usd"];
+        let expected = Address::find_program_address(
+            &[ENVELOPE_SEED, authority(1).as_ref(), b"btc-usd"],
+            &PROGRAM_ID,
+        );
+        assert_eq!(cache.envelope(&PROGRAM_ID, &authority(1), seeds), expected);
+        assert_eq!(cache.get(&PROGRAM_ID, &authority(1), seeds), Some(expected));
+        assert_eq!(cache.get(&PROGRAM_ID, &authority(1), &[b"eth-usd"]), None);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = PdaCache::in_memory(2);
+        cache.envelope(&PROGRAM_ID, &authority(1), &[]);
+        cache.envelope(&PROGRAM_ID, &authority(2), &[]);
+        cache.get(&PROGRAM_ID, &authority(1), &[]);
+        cache.envelope(&PROGRAM_ID, &authority(3), &[]);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&PROGRAM_ID, &authority(1), &[]).is_some());
+        assert!(cache.get(&PROGRAM_ID, &authority(2), &[]).is_none());
+    }
+
+    #[test]
+    fn warm_up_and_invalidate() {
+        let mut cache = PdaCache::in_memory(16);
+        let seeds: &[&[u8]] = &[b"feed"];
+        let batch = [(authority(1), seeds), (authority(2), seeds)];
+        assert_eq!(cache.warm_up(&PROGRAM_ID, batch), 2);
+        assert_eq!(cache.warm_up(&PROGRAM_ID, batch), 0);
+
+        assert!(cache.invalidate(&PROGRAM_ID, &authority(1), seeds));
+        assert!(!cache.invalidate(&PROGRAM_ID, &authority(1), seeds));
+        assert_eq!(cache.len(), 1);
+
+        cache.invalidate_program(&PROGRAM_ID);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn persists_and_reloads() {
+        let path = temp_path("roundtrip");
+        let _ = fs::remove_file(&path);
+
+        let seeds: &[&[u8]] = &[b"a", b"bc"];
+        let mut cache = PdaCache::open(&path, 16).unwrap();
+        assert!(cache.is_empty());
+        let derived = cache.envelope(&PROGRAM_ID, &authority(4), seeds);
+        cache.envelope(&PROGRAM_ID, &authority(5), &[]);
+        cache.save().unwrap();
+
+        let mut reloaded = PdaCache::open(&path, 16).unwrap();
+        assert_eq!(reloaded.len(), 2);
+        assert_eq!(
+            reloaded.get(&PROGRAM_ID, &authority(4), seeds),
+            Some(derived)
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn load_drops_entries_that_fail_verification() {
+        let path = temp_path("tampered");
+        let mut cache = PdaCache::open(&path, 16).unwrap();
+        cache.envelope(&PROGRAM_ID, &authority(6), &[]);
+        cache.save().unwrap();
+
+        // Corrupt the stored address (after magic, program_id, authority).
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[8 + 64] ^= 0xFF;
+        fs::write(&path, &bytes).unwrap();
+        assert!(PdaCache::open(&path, 16).unwrap().is_empty());
+
+        fs::write(&path, b"garbage").unwrap();
+        assert_eq!(
+            PdaCache::open(&path, 16).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
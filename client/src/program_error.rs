@@ -0,0 +1,329 @@
+//! Decoding of failed transactions back into typed c_u_soon errors.
+//!
+//! The program returns builtin `ProgramError` variants, plus a few `Custom` codes
+//! ([`CuSoonError::TooManyRanges`], [`CuSoonError::MetadataSizeMismatch`],
+//! [`CuSoonError::MetadataHashMismatch`], [`CuSoonError::ConstraintViolated`],
+//! [`CuSoonError::UnsupportedVersion`], [`CuSoonError::TooFewSources`],
+//! [`CuSoonError::EnvelopePaused`], [`CuSoonError::ProgramPaused`],
+//! [`CuSoonError::SplitUpdateUnpaired`]). The runtime
+//! logs them as `Program <id> failed: <message>`. [`parse_failure_logs`] finds that line for
+//! the c_u_soon program, maps the message to a [`CuSoonError`], and records which top-level
+//! instruction failed. Publisher daemons can then branch on the cause (for example,
+//! re-reading the sequence after [`CuSoonError::InvalidInstructionData`]) instead of
+//! string-matching logs themselves.
+
+use c_u_soon_instruction::{
+    CONSTRAINT_VIOLATED_ERROR, ENVELOPE_PAUSED_ERROR, MAX_WRITE_RANGES,
+    METADATA_HASH_MISMATCH_ERROR, METADATA_SIZE_MISMATCH_ERROR, PROGRAM_PAUSED_ERROR,
+    SPLIT_UPDATE_UNPAIRED_ERROR, TOO_FEW_SOURCES_ERROR, TOO_MANY_RANGES_ERROR,
+    UNSUPPORTED_VERSION_ERROR,
+};
+
+/// A c_u_soon program error, decoded from the runtime's failure message.
+///
+/// Each variant names the `ProgramError` the program returned, and its docs list the
+/// handler checks that produce it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CuSoonError {
+    /// Authority, delegate, or PDA did not sign.
+    MissingRequiredSignature,
+    /// Signer is not the envelope's authority or delegation authority.
+    IncorrectAuthority,
+    /// Envelope or range guard not owned by the program, or an uninitialized account is not
+    /// system-owned.
+    IncorrectProgramId,
+    /// Seeds and bump do not derive the envelope or range guard address.
+    InvalidSeeds,
+    /// Stale sequence, wrong data length, or undecodable instruction.
+    InvalidInstructionData,
+    /// Masked write touched a blocked byte, a range guard was violated, the delegation
+    /// state does not allow the operation, the envelope is permanent, or `AssertAuxHash`
+    /// found different aux data.
+    InvalidArgument,
+    /// Envelope account has the wrong size or layout.
+    InvalidAccountData,
+    /// Multi-range update carried more than `MAX_WRITE_RANGES` ranges
+    /// (`Custom(TOO_MANY_RANGES_ERROR)`).
+    TooManyRanges,
+    /// The update's metadata has a different type size than the stored metadata
+    /// (`Custom(METADATA_SIZE_MISMATCH_ERROR)`). The stored layout changed; the update has to
+    /// be rebuilt for the new type.
+    MetadataSizeMismatch,
+    /// The update's metadata has the stored type size but a different hash
+    /// (`Custom(METADATA_HASH_MISMATCH_ERROR)`), typically a renamed type or field.
+    MetadataHashMismatch,
+    /// The write left a field outside one of the envelope's constraints
+    /// (`Custom(CONSTRAINT_VIOLATED_ERROR)`). Resending the same values fails again.
+    ConstraintViolated,
+    /// The instruction was built for a newer program: a versioned instruction past the
+    /// deployment's `INSTRUCTION_VERSION`, or an unknown tag past `MAX_SLOW_PATH_TAG`
+    /// (`Custom(UNSUPPORTED_VERSION_ERROR)`). Upgrade the program or downgrade the client.
+    UnsupportedVersion,
+    /// `Aggregate` kept fewer sources than `min_sources` after dropping outliers
+    /// (`Custom(TOO_FEW_SOURCES_ERROR)`). The sources disagree, or too few were passed.
+    TooFewSources,
+    /// The envelope is paused (`Custom(ENVELOPE_PAUSED_ERROR)`): its authority halted oracle
+    /// and aux writes with `SetPaused`. Not retryable until it resumes.
+    EnvelopePaused,
+    /// The program is paused (`Custom(PROGRAM_PAUSED_ERROR)`): the config admin set the pause
+    /// flag with `SetConfig`, and the instruction was passed the config. Not retryable until
+    /// it is cleared.
+    ProgramPaused,
+    /// A `SplitUpdate` half was sent without the other half for the same envelope, type
+    /// hash, and sequence right next to it in the transaction, or through CPI
+    /// (`Custom(SPLIT_UPDATE_UNPAIRED_ERROR)`). Send both halves together.
+    SplitUpdateUnpaired,
+    /// Fewer accounts than the instruction requires.
+    NotEnoughAccountKeys,
+    /// The instructions sysvar account has the wrong address.
+    UnsupportedSysvar,
+    /// Account already borrowed (duplicate account in the instruction).
+    AccountBorrowFailed,
+    /// Authority cannot pay rent for a new account.
+    InsufficientFunds,
+    /// Transaction ran out of compute units inside the program.
+    ComputationalBudgetExceeded,
+    /// `ProgramError::Custom` code.
+    Custom(u32),
+    /// Any failure message not listed above, verbatim.
+    Other(String),
+}
+
+impl CuSoonError {
+    /// Map the message part of a `Program <id> failed: <message>` log line.
+    pub fn from_log_message(message: &str) -> Self {
+        if let Some(hex) = message.strip_prefix("custom program error: 0x") {
+            if let Ok(code) = u32::from_str_radix(hex, 16) {
+                return match code {
+                    TOO_MANY_RANGES_ERROR => Self::TooManyRanges,
+                    METADATA_SIZE_MISMATCH_ERROR => Self::MetadataSizeMismatch,
+                    METADATA_HASH_MISMATCH_ERROR => Self::MetadataHashMismatch,
+                    CONSTRAINT_VIOLATED_ERROR => Self::ConstraintViolated,
+                    UNSUPPORTED_VERSION_ERROR => Self::UnsupportedVersion,
+                    TOO_FEW_SOURCES_ERROR => Self::TooFewSources,
+                    ENVELOPE_PAUSED_ERROR => Self::EnvelopePaused,
+                    PROGRAM_PAUSED_ERROR => Self::ProgramPaused,
+                    SPLIT_UPDATE_UNPAIRED_ERROR => Self::SplitUpdateUnpaired,
+                    code => Self::Custom(code),
+                };
+            }
+        }
+        match message {
+            "missing required signature for instruction" => Self::MissingRequiredSignature,
+            "Incorrect authority provided" => Self::IncorrectAuthority,
+            "incorrect program id for instruction" => Self::IncorrectProgramId,
+            "Provided seeds do not result in a valid address" => Self::InvalidSeeds,
+            "invalid instruction data" => Self::InvalidInstructionData,
+            "invalid program argument" => Self::InvalidArgument,
+            "invalid account data for instruction" => Self::InvalidAccountData,
+            "insufficient account keys for instruction" => Self::NotEnoughAccountKeys,
+            "Unsupported sysvar" => Self::UnsupportedSysvar,
+            "instruction tries to borrow reference for an account which is already borrowed" => {
+                Self::AccountBorrowFailed
+            }
+            "insufficient funds for instruction" => Self::InsufficientFunds,
+            "Computational budget exceeded" => Self::ComputationalBudgetExceeded,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl core::fmt::Display for CuSoonError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MissingRequiredSignature => write!(f, "missing required signature"),
+            Self::IncorrectAuthority => write!(f, "incorrect authority"),
+            Self::IncorrectProgramId => write!(f, "account not owned by the expected program"),
+            Self::InvalidSeeds => write!(f, "seeds do not derive the account address"),
+            Self::InvalidInstructionData => {
+                write!(f, "invalid instruction data (stale sequence or length)")
+            }
+            Self::InvalidArgument => write!(f, "write rejected by mask, guard, or delegation"),
+            Self::InvalidAccountData => write!(f, "invalid envelope account data"),
+            Self::TooManyRanges => {
+                write!(f, "more than {MAX_WRITE_RANGES} ranges in one update")
+            }
+            Self::MetadataSizeMismatch => write!(f, "metadata type size mismatch"),
+            Self::MetadataHashMismatch => write!(f, "metadata type hash mismatch"),
+            Self::ConstraintViolated => write!(f, "write violates an envelope constraint"),
+            Self::UnsupportedVersion => write!(f, "instruction version not supported by program"),
+            Self::TooFewSources => write!(f, "too few aggregation sources within deviation"),
+            Self::EnvelopePaused => write!(f, "envelope paused"),
+            Self::ProgramPaused => write!(f, "program paused"),
+            Self::SplitUpdateUnpaired => write!(f, "split update half sent without its partner"),
+            Self::NotEnoughAccountKeys => write!(f, "not enough accounts"),
+            Self::UnsupportedSysvar => write!(f, "wrong instructions sysvar"),
+            Self::AccountBorrowFailed => write!(f, "account already borrowed"),
+            Self::InsufficientFunds => write!(f, "insufficient funds"),
+            Self::ComputationalBudgetExceeded => write!(f, "compute budget exceeded"),
+            Self::Custom(code) => write!(f, "custom program error {code:#x}"),
+            Self::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for CuSoonError {}
+
+/// A c_u_soon failure located within its transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramFailure {
+    /// Index of the top-level instruction that failed. For a CPI failure, this is the
+    /// index of the outer instruction that invoked c_u_soon.
+    pub instruction_index: usize,
+    /// CPI depth at which c_u_soon failed (1 = called directly by the transaction).
+    pub depth: usize,
+    pub error: CuSoonError,
+}
+
+impl core::fmt::Display for ProgramFailure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "instruction {}: {}", self.instruction_index, self.error)
+    }
+}
+
+impl std::error::Error for ProgramFailure {}
+
+/// Find the c_u_soon failure in a transaction's log messages.
+///
+/// `program_id` is the base58 program address as printed in logs. Top-level instructions
+/// are counted from their `invoke [1]` lines, so the index is correct only when `logs` is
+/// complete (not truncated by the runtime's log limit). Returns `None` if c_u_soon did
+/// not fail, for example because the transaction failed in another program.
+pub fn parse_failure_logs<S: AsRef<str>>(logs: &[S], program_id: &str) -> Option<ProgramFailure> {
+    let invoke_prefix = format!("Program {program_id} invoke [");
+    let failed_prefix = format!("Program {program_id} failed: ");
+    let mut instruction_index = None::<usize>;
+    let mut depth = 0;
+
+    for line in logs.iter().map(AsRef::as_ref) {
+        if line.starts_with("Program ") && line.ends_with(" invoke [1]") {
+            instruction_index = Some(instruction_index.map_or(0, |i| i + 1));
+        }
+        if let Some(level) = line.strip_prefix(&invoke_prefix) {
+            depth = level.trim_end_matches(']').parse().unwrap_or(0);
+        }
+        if let Some(message) = line.strip_prefix(&failed_prefix) {
+            return Some(ProgramFailure {
+                instruction_index: instruction_index.unwrap_or(0),
+                depth,
+                error: CuSoonError::from_log_message(message),
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "CuSoon1111111111111111111111111111111111111";
+    const OTHER: &str = "Other11111111111111111111111111111111111111";
+
+    #[test]
+    fn maps_builtin_messages() {
+        assert_eq!(
+            CuSoonError::from_log_message("invalid instruction data"),
+            CuSoonError::InvalidInstructionData
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("invalid program argument"),
+            CuSoonError::InvalidArgument
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("Incorrect authority provided"),
+            CuSoonError::IncorrectAuthority
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("custom program error: 0x2a"),
+            CuSoonError::Custom(42)
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("custom program error: 0x1"),
+            CuSoonError::TooManyRanges
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("custom program error: 0x2"),
+            CuSoonError::MetadataSizeMismatch
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("custom program error: 0x3"),
+            CuSoonError::MetadataHashMismatch
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("custom program error: 0x4"),
+            CuSoonError::ConstraintViolated
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("custom program error: 0x5"),
+            CuSoonError::UnsupportedVersion
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("custom program error: 0x6"),
+            CuSoonError::TooFewSources
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("custom program error: 0x7"),
+            CuSoonError::EnvelopePaused
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("custom program error: 0x8"),
+            CuSoonError::ProgramPaused
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("custom program error: 0x9"),
+            CuSoonError::SplitUpdateUnpaired
+        );
+        assert_eq!(
+            CuSoonError::from_log_message("something new"),
+            CuSoonError::Other("something new".to_string())
+        );
+    }
+
+    #[test]
+    fn locates_failing_top_level_instruction() {
+        let logs = [
+            format!("Program {OTHER} invoke [1]"),
+            format!("Program {OTHER} success"),
+            format!("Program {ID} invoke [1]"),
+            format!("Program {ID} consumed 39 of 200000 compute units"),
+            format!("Program {ID} success"),
+            format!("Program {ID} invoke [1]"),
+            format!("Program {ID} consumed 39 of 199961 compute units"),
+            format!("Program {ID} failed: invalid instruction data"),
+        ];
+        assert_eq!(
+            parse_failure_logs(&logs, ID),
+            Some(ProgramFailure {
+                instruction_index: 2,
+                depth: 1,
+                error: CuSoonError::InvalidInstructionData,
+            })
+        );
+    }
+
+    #[test]
+    fn cpi_failure_reports_outer_instruction() {
+        let logs = [
+            format!("Program {OTHER} invoke [1]"),
+            format!("Program {ID} invoke [2]"),
+            format!("Program {ID} failed: invalid program argument"),
+            format!("Program {OTHER} failed: invalid program argument"),
+        ];
+        let failure = parse_failure_logs(&logs, ID).unwrap();
+        assert_eq!(failure.instruction_index, 0);
+        assert_eq!(failure.depth, 2);
+        assert_eq!(failure.error, CuSoonError::InvalidArgument);
+    }
+
+    #[test]
+    fn ignores_failures_in_other_programs() {
+        let logs = [
+            format!("Program {ID} invoke [1]"),
+            format!("Program {ID} success"),
+            format!("Program {OTHER} invoke [1]"),
+            format!("Program {OTHER} failed: custom program error: 0x1"),
+        ];
+        assert_eq!(parse_failure_logs(&logs, ID), None);
+    }
+}
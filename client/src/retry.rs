@@ -0,0 +1,270 @@
+//! Retry with exponential backoff and jitter.
+//!
+//! A [`RetryPolicy`] holds the tunables (attempt limit, base and maximum delay, jitter
+//! fraction) so every sender, keeper, and RPC wrapper in a deployment backs off the same
+//! way. Which errors are worth retrying is decided by [`Retryable`], implemented here for
+//! the errors this crate returns, or by an explicit classifier passed to
+//! [`RetryPolicy::retry_with`].
+
+use crate::envelope_client::EnvelopeError;
+use crate::program_error::{CuSoonError, ProgramFailure};
+use crate::snapshot::FetchError;
+use std::time::Duration;
+
+/// Whether an error may succeed if the operation is attempted again.
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+/// Failure messages from the RPC node or cluster that clear up on their own.
+const TRANSIENT_MESSAGES: &[&str] = &[
+    "Blockhash not found",
+    "Node is behind",
+    "Too Many Requests",
+    "timed out",
+    "connection",
+];
+
+impl Retryable for CuSoonError {
+    /// `InvalidInstructionData` is retryable because it is usually a stale sequence lost to
+    /// a concurrent write; the caller must re-read the sequence before the next attempt.
+    /// Metadata mismatches and constraint violations are not: resending the same write cannot
+    /// change the outcome.
+    /// `Other` messages are retryable when they match a known transient RPC failure.
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::InvalidInstructionData => true,
+            Self::Other(message) => TRANSIENT_MESSAGES.iter().any(|m| message.contains(m)),
+            _ => false,
+        }
+    }
+}
+
+impl Retryable for ProgramFailure {
+    fn is_retryable(&self) -> bool {
+        self.error.is_retryable()
+    }
+}
+
+impl<E: Retryable> Retryable for FetchError<E> {
+    /// A stale read succeeds once the node catches up; source errors defer to `E`.
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::Source(e) => e.is_retryable(),
+            Self::Stale(_) => true,
+            Self::NotFound | Self::InvalidAccountData => false,
+        }
+    }
+}
+
+impl<E: Retryable> Retryable for EnvelopeError<E> {
+    /// As for [`FetchError`]; an account with the wrong owner never becomes an envelope.
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::Source(e) => e.is_retryable(),
+            Self::Stale(_) => true,
+            Self::NotFound | Self::WrongOwner { .. } | Self::InvalidAccountData => false,
+        }
+    }
+}
+
+/// Exponential backoff with jitter.
+///
+/// The delay after failed attempt `n` (0-based) is `base_delay * 2^n`, capped at
+/// `max_delay`, then reduced by a random amount up to `jitter` of itself so that
+/// concurrent clients do not retry in lockstep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one. `0` and `1` both mean no retries.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Fraction of each delay that is randomized, clamped to `0.0..=1.0`.
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    /// 5 attempts, 200 ms base, 5 s cap, 50% jitter.
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: 0.5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Run once and never retry.
+    pub const NONE: Self = Self {
+        max_attempts: 1,
+        base_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+        jitter: 0.0,
+    };
+
+    /// Delay before retrying after failed attempt `attempt`, before jitter.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        self.base_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |d| d.min(self.max_delay))
+    }
+
+    /// [`backoff`](Self::backoff) with jitter applied. `sample` is a uniform value in
+    /// `0.0..1.0`; the result lies in `backoff * (1 - jitter)..=backoff`.
+    pub fn delay(&self, attempt: u32, sample: f64) -> Duration {
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        self.backoff(attempt)
+            .mul_f64(1.0 - jitter * sample.clamp(0.0, 1.0))
+    }
+
+    /// Run `op` until it succeeds, returns an error that is not [`Retryable`], or the
+    /// attempt limit is reached. Sleeps the current thread between attempts.
+    ///
+    /// `op` receives the 0-based attempt number. The last error is returned on failure.
+    pub fn retry<T, E: Retryable>(&self, op: impl FnMut(u32) -> Result<T, E>) -> Result<T, E> {
+        self.retry_with(E::is_retryable, std::thread::sleep, op)
+    }
+
+    /// [`retry`](Self::retry) with an explicit classifier and sleep function, for error types
+    /// without a [`Retryable`] impl and for async or test callers that must not block.
+    pub fn retry_with<T, E>(
+        &self,
+        is_retryable: impl Fn(&E) -> bool,
+        mut sleep: impl FnMut(Duration),
+        mut op: impl FnMut(u32) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut rng = Jitter::seeded();
+        let mut attempt = 0;
+        loop {
+            match op(attempt) {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt + 1 < self.max_attempts && is_retryable(&e) => {
+                    sleep(self.delay(attempt, rng.sample()));
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// SplitMix64, seeded from the clock. Jitter only has to decorrelate clients, so this
+/// avoids a `rand` dependency.
+struct Jitter(u64);
+
+impl Jitter {
+    fn seeded() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Self(nanos)
+    }
+
+    /// Uniform value in `0.0..1.0`.
+    fn sample(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+            jitter: 0.5,
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        let p = policy();
+        assert_eq!(p.backoff(0), Duration::from_millis(100));
+        assert_eq!(p.backoff(1), Duration::from_millis(200));
+        assert_eq!(p.backoff(2), Duration::from_millis(350));
+        assert_eq!(p.backoff(40), Duration::from_millis(350));
+
+        assert_eq!(p.delay(1, 0.0), Duration::from_millis(200));
+        assert_eq!(p.delay(1, 1.0), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn retries_only_retryable_errors_up_to_limit() {
+        let mut sleeps = Vec::new();
+        let mut calls = 0;
+        let result: Result<(), CuSoonError> = policy().retry_with(
+            CuSoonError::is_retryable,
+            |d| sleeps.push(d),
+            |_| {
+                calls += 1;
+                Err(CuSoonError::InvalidInstructionData)
+            },
+        );
+        assert_eq!(result, Err(CuSoonError::InvalidInstructionData));
+        assert_eq!(calls, 4);
+        assert_eq!(sleeps.len(), 3);
+        for (attempt, d) in sleeps.iter().enumerate() {
+            let full = policy().backoff(attempt as u32);
+            assert!(*d <= full && *d >= full / 2, "jitter out of range: {d:?}");
+        }
+
+        let mut calls = 0;
+        let result: Result<(), CuSoonError> = policy().retry_with(
+            CuSoonError::is_retryable,
+            |_| {},
+            |_| {
+                calls += 1;
+                Err(CuSoonError::IncorrectAuthority)
+            },
+        );
+        assert_eq!(result, Err(CuSoonError::IncorrectAuthority));
+        assert_eq!(calls, 1);
+        assert!(!CuSoonError::MetadataSizeMismatch.is_retryable());
+        assert!(!CuSoonError::MetadataHashMismatch.is_retryable());
+        assert!(!CuSoonError::EnvelopePaused.is_retryable());
+        assert!(!CuSoonError::ProgramPaused.is_retryable());
+        assert!(!CuSoonError::ConstraintViolated.is_retryable());
+        assert!(!CuSoonError::UnsupportedVersion.is_retryable());
+    }
+
+    #[test]
+    fn returns_first_success() {
+        let result = policy().retry_with(
+            CuSoonError::is_retryable,
+            |_| {},
+            |attempt| {
+                if attempt < 2 {
+                    Err(CuSoonError::Other("Blockhash not found".into()))
+                } else {
+                    Ok(attempt)
+                }
+            },
+        );
+        assert_eq!(result, Ok(2));
+    }
+
+    #[test]
+    fn fetch_error_classification() {
+        let stale: FetchError<CuSoonError> = FetchError::Stale(crate::StaleRead {
+            context_slot: 1,
+            required_slot: 2,
+        });
+        assert!(stale.is_retryable());
+        assert!(!FetchError::<CuSoonError>::NotFound.is_retryable());
+        assert!(
+            FetchError::Source(CuSoonError::Other("Node is behind by 40 slots".into()))
+                .is_retryable()
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! [`AccountSource`] for the Solana RPC client, enabled by the `rpc` feature.
+
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_address::Address;
+use solana_commitment_config::CommitmentConfig;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::{client_error::Error as ClientError, config::RpcAccountInfoConfig};
+
+use crate::snapshot::{AccountSource, Commitment, FetchConfig, SlotAccount};
+
+fn commitment_config(commitment: Commitment) -> CommitmentConfig {
+    match commitment {
+        Commitment::Processed => CommitmentConfig::processed(),
+        Commitment::Confirmed => CommitmentConfig::confirmed(),
+        Commitment::Finalized => CommitmentConfig::finalized(),
+    }
+}
+
+impl AccountSource for RpcClient {
+    type Error = ClientError;
+
+    /// `getAccountInfo` with base64 encoding, `config.commitment`, and
+    /// `config.min_context_slot`.
+    fn get_account(
+        &self,
+        address: &Address,
+        config: &FetchConfig,
+    ) -> Result<Option<SlotAccount>, Self::Error> {
+        let response = self.get_account_with_config(
+            address,
+            RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: None,
+                commitment: Some(commitment_config(config.commitment)),
+                min_context_slot: config.min_context_slot,
+            },
+        )?;
+        let context_slot = response.context.slot;
+        Ok(response.value.map(|account| SlotAccount {
+            context_slot,
+            owner: account.owner,
+            data: account.data,
+        }))
+    }
+}
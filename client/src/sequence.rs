@@ -0,0 +1,417 @@
+//! Cached fast-path sequences.
+//!
+//! Every fast-path write needs the stored oracle sequence plus one. Reading the envelope
+//! before each write costs a round trip, and two writers that read the same value race:
+//! one lands and the other fails as stale. [`SequenceTracker`] keeps the last sequence used
+//! per envelope, re-reads it from the chain as its [`RefreshPolicy`] asks, and
+//! [`fast_path_update_auto`](SequenceTracker::fast_path_update_auto) runs the whole
+//! read, build, send loop, re-reading after each rejected write.
+//!
+//! Sending is abstracted behind [`InstructionSender`], as reads are behind
+//! [`AccountSource`], so this module carries no signer or network dependency.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use bytemuck::NoUninit;
+use c_u_soon::{TypeHash, ORACLE_BYTES};
+use solana_address::Address;
+use solana_instruction::Instruction;
+
+use crate::envelope_client::{EnvelopeClient, EnvelopeError};
+use crate::retry::{RetryPolicy, Retryable};
+use crate::snapshot::{AccountSource, FetchConfig};
+
+/// Signs, sends, and confirms a transaction holding one instruction, for
+/// [`SequenceTracker::fast_path_update_auto`].
+///
+/// A write the program rejects should come back as an error that reports a stale sequence
+/// as [`Retryable`], such as the [`ProgramFailure`](crate::ProgramFailure) that
+/// [`parse_failure_logs`](crate::parse_failure_logs) returns for the transaction's logs.
+pub trait InstructionSender {
+    type Error: Retryable;
+
+    fn send_instruction(&self, instruction: &Instruction) -> Result<(), Self::Error>;
+}
+
+/// When [`SequenceTracker::next`] re-reads the stored sequence instead of counting up from
+/// the cached one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RefreshPolicy {
+    /// Before every write. Costs a read per write but follows other writers. A lagging read
+    /// never moves the sequence below one this tracker already used.
+    Always,
+    /// Only for the first write and after a write fails. Suits an envelope with one writer.
+    #[default]
+    OnError,
+    /// As [`OnError`](Self::OnError), and also once the last read is older than the
+    /// duration.
+    After(Duration),
+}
+
+/// Errors from [`SequenceTracker::next`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceError<E> {
+    /// Reading the envelope failed.
+    Read(EnvelopeError<E>),
+    /// The stored sequence is `u64::MAX`, so no write can follow it.
+    Exhausted,
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for SequenceError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Read(e) => write!(f, "{e}"),
+            Self::Exhausted => write!(f, "oracle sequence exhausted"),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> std::error::Error for SequenceError<E> {}
+
+/// Errors from [`SequenceTracker::fast_path_update_auto`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateError<R, S> {
+    /// No sequence could be found for the write.
+    Sequence(SequenceError<R>),
+    /// The last attempt failed to send, or the program rejected it.
+    Send(S),
+}
+
+impl<R: core::fmt::Display, S: core::fmt::Display> core::fmt::Display for UpdateError<R, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Sequence(e) => write!(f, "{e}"),
+            Self::Send(e) => write!(f, "send: {e}"),
+        }
+    }
+}
+
+impl<R, S> std::error::Error for UpdateError<R, S>
+where
+    R: core::fmt::Debug + core::fmt::Display,
+    S: core::fmt::Debug + core::fmt::Display,
+{
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CachedSequence {
+    /// Last sequence used, or read if none was used since.
+    sequence: u64,
+    /// Context slot of the last read, so a re-read cannot go back in time.
+    context_slot: u64,
+    /// When the last read happened; `None` once a write failed.
+    read_at: Option<Instant>,
+}
+
+/// Per-envelope fast-path sequences for one authority.
+#[derive(Debug, Clone)]
+pub struct SequenceTracker {
+    /// Reads the envelopes; its `config` sets the commitment.
+    pub client: EnvelopeClient,
+    /// Signer of the fast-path writes.
+    pub authority: Address,
+    pub refresh: RefreshPolicy,
+    /// Attempts for [`fast_path_update_auto`](Self::fast_path_update_auto).
+    pub retry: RetryPolicy,
+    cache: HashMap<Address, CachedSequence>,
+}
+
+impl SequenceTracker {
+    /// Tracker for writes signed by `authority` to envelopes of the program at
+    /// `program_id`, with the default refresh and retry policies.
+    pub fn new(program_id: Address, authority: Address) -> Self {
+        Self {
+            client: EnvelopeClient::new(program_id),
+            authority,
+            refresh: RefreshPolicy::default(),
+            retry: RetryPolicy::default(),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Last sequence used or read for `envelope`, if any.
+    pub fn current(&self, envelope: &Address) -> Option<u64> {
+        self.cache.get(envelope).map(|entry| entry.sequence)
+    }
+
+    /// Record that `envelope` has reached `sequence`, for example from a subscription.
+    /// Lower sequences and envelopes never read are ignored.
+    pub fn observe(&mut self, envelope: &Address, sequence: u64) {
+        if let Some(entry) = self.cache.get_mut(envelope) {
+            entry.sequence = entry.sequence.max(sequence);
+        }
+    }
+
+    /// Re-read `envelope` before its next write, as after a failed write.
+    pub fn invalidate(&mut self, envelope: &Address) {
+        if let Some(entry) = self.cache.get_mut(envelope) {
+            entry.read_at = None;
+        }
+    }
+
+    /// Sequence for the next write to `envelope`, reading it from `source` when the
+    /// [`refresh`](Self::refresh) policy asks. The returned sequence counts as used: the
+    /// following call returns one more unless the envelope is re-read.
+    pub fn next<S: AccountSource>(
+        &mut self,
+        source: &S,
+        envelope: &Address,
+    ) -> Result<u64, SequenceError<S::Error>> {
+        let now = Instant::now();
+        let cached = self.cache.get(envelope).copied();
+        let fresh = cached.filter(|entry| match (self.refresh, entry.read_at) {
+            (_, None) | (RefreshPolicy::Always, _) => false,
+            (RefreshPolicy::OnError, Some(_)) => true,
+            (RefreshPolicy::After(max_age), Some(read_at)) => now - read_at < max_age,
+        });
+        let mut entry = match fresh {
+            Some(entry) => entry,
+            None => {
+                let client = EnvelopeClient {
+                    config: FetchConfig {
+                        min_context_slot: cached
+                            .map(|entry| entry.context_slot)
+                            .or(self.client.config.min_context_slot),
+                        ..self.client.config
+                    },
+                    ..self.client
+                };
+                let decoded = client
+                    .fetch(source, envelope)
+                    .map_err(SequenceError::Read)?;
+                // A write that did not fail may not be visible yet; after a failure the
+                // chain is the only truth.
+                let floor = cached
+                    .filter(|entry| entry.read_at.is_some())
+                    .map_or(0, |entry| entry.sequence);
+                CachedSequence {
+                    sequence: decoded.oracle_sequence().max(floor),
+                    context_slot: decoded.context_slot(),
+                    read_at: Some(now),
+                }
+            }
+        };
+        entry.sequence = entry
+            .sequence
+            .checked_add(1)
+            .ok_or(SequenceError::Exhausted)?;
+        self.cache.insert(*envelope, entry);
+        Ok(entry.sequence)
+    }
+
+    /// Write `value` to `envelope` through the fast path with the [`next`](Self::next)
+    /// sequence, and return that sequence.
+    ///
+    /// A failed send re-reads the envelope before the next attempt. Sends that fail with a
+    /// [`Retryable`] error, such as a stale sequence lost to another writer, are retried
+    /// under [`retry`](Self::retry); read errors are returned at once.
+    pub fn fast_path_update_auto<S, T>(
+        &mut self,
+        rpc: &S,
+        envelope: &Address,
+        value: &T,
+    ) -> Result<u64, UpdateError<<S as AccountSource>::Error, <S as InstructionSender>::Error>>
+    where
+        S: AccountSource + InstructionSender,
+        T: TypeHash + NoUninit,
+    {
+        const { assert!(core::mem::size_of::<T>() <= ORACLE_BYTES) };
+        let policy = self.retry;
+        policy.retry_with(
+            |e: &UpdateError<_, <S as InstructionSender>::Error>| {
+                matches!(e, UpdateError::Send(e) if e.is_retryable())
+            },
+            std::thread::sleep,
+            |_| {
+                let sequence = self.next(rpc, envelope).map_err(UpdateError::Sequence)?;
+                let ix = crate::build_fast_path_ix(
+                    &self.client.program_id,
+                    &self.authority,
+                    envelope,
+                    T::METADATA.as_u64(),
+                    sequence,
+                    bytemuck::bytes_of(value),
+                )
+                .expect("payload size is checked at compile time");
+                rpc.send_instruction(&ix).map_err(|e| {
+                    self.invalidate(envelope);
+                    UpdateError::Send(e)
+                })?;
+                Ok(sequence)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program_error::{CuSoonError, ProgramFailure};
+    use crate::snapshot::SlotAccount;
+    use bytemuck::Zeroable;
+    use c_u_soon::Envelope;
+    use core::cell::Cell;
+
+    const PROGRAM: Address = Address::new_from_array([1; 32]);
+    const AUTHORITY: Address = Address::new_from_array([2; 32]);
+    const ENVELOPE: Address = Address::new_from_array([3; 32]);
+
+    /// One envelope whose fast path accepts strictly increasing sequences. `rival` bumps
+    /// the stored sequence before each send, as a competing writer would.
+    struct Chain {
+        sequence: Cell<u64>,
+        slot: Cell<u64>,
+        reads: Cell<u32>,
+        rival: Cell<u32>,
+        error: CuSoonError,
+    }
+
+    impl Chain {
+        fn new(sequence: u64) -> Self {
+            Self {
+                sequence: Cell::new(sequence),
+                slot: Cell::new(10),
+                reads: Cell::new(0),
+                rival: Cell::new(0),
+                error: CuSoonError::InvalidInstructionData,
+            }
+        }
+    }
+
+    impl AccountSource for Chain {
+        type Error = String;
+
+        fn get_account(
+            &self,
+            _address: &Address,
+            _config: &FetchConfig,
+        ) -> Result<Option<SlotAccount>, String> {
+            self.reads.set(self.reads.get() + 1);
+            let mut envelope = Envelope::zeroed();
+            envelope.oracle_state.sequence = self.sequence.get();
+            Ok(Some(SlotAccount {
+                context_slot: self.slot.get(),
+                owner: PROGRAM,
+                data: bytemuck::bytes_of(&envelope).to_vec(),
+            }))
+        }
+    }
+
+    impl InstructionSender for Chain {
+        type Error = ProgramFailure;
+
+        fn send_instruction(&self, instruction: &Instruction) -> Result<(), ProgramFailure> {
+            if self.rival.get() > 0 {
+                self.rival.set(self.rival.get() - 1);
+                self.sequence.set(self.sequence.get() + 1);
+            }
+            self.slot.set(self.slot.get() + 1);
+            let sequence = u64::from_le_bytes(instruction.data[8..16].try_into().unwrap());
+            if sequence <= self.sequence.get() {
+                return Err(ProgramFailure {
+                    instruction_index: 0,
+                    depth: 1,
+                    error: self.error.clone(),
+                });
+            }
+            self.sequence.set(sequence);
+            Ok(())
+        }
+    }
+
+    fn tracker() -> SequenceTracker {
+        let mut tracker = SequenceTracker::new(PROGRAM, AUTHORITY);
+        tracker.retry = RetryPolicy {
+            max_attempts: 3,
+            ..RetryPolicy::NONE
+        };
+        tracker
+    }
+
+    #[test]
+    fn next_reads_once_then_counts_up() {
+        let chain = Chain::new(7);
+        let mut tracker = tracker();
+        assert_eq!(tracker.current(&ENVELOPE), None);
+        assert_eq!(tracker.next(&chain, &ENVELOPE), Ok(8));
+        assert_eq!(tracker.next(&chain, &ENVELOPE), Ok(9));
+        assert_eq!(chain.reads.get(), 1);
+
+        tracker.observe(&ENVELOPE, 20);
+        assert_eq!(tracker.next(&chain, &ENVELOPE), Ok(21));
+
+        tracker.invalidate(&ENVELOPE);
+        assert_eq!(tracker.next(&chain, &ENVELOPE), Ok(8));
+        assert_eq!(chain.reads.get(), 2);
+    }
+
+    #[test]
+    fn always_refresh_never_goes_below_a_used_sequence() {
+        let chain = Chain::new(7);
+        let mut tracker = tracker();
+        tracker.refresh = RefreshPolicy::Always;
+        assert_eq!(tracker.next(&chain, &ENVELOPE), Ok(8));
+        // The write of 8 is not visible yet.
+        assert_eq!(tracker.next(&chain, &ENVELOPE), Ok(9));
+        chain.sequence.set(30);
+        assert_eq!(tracker.next(&chain, &ENVELOPE), Ok(31));
+        assert_eq!(chain.reads.get(), 3);
+
+        tracker.refresh = RefreshPolicy::After(Duration::from_secs(3600));
+        assert_eq!(tracker.next(&chain, &ENVELOPE), Ok(32));
+        assert_eq!(chain.reads.get(), 3);
+    }
+
+    #[test]
+    fn next_fails_at_the_last_sequence() {
+        let chain = Chain::new(u64::MAX);
+        assert_eq!(
+            tracker().next(&chain, &ENVELOPE),
+            Err(SequenceError::Exhausted)
+        );
+    }
+
+    #[test]
+    fn update_auto_rereads_after_losing_a_race() {
+        let chain = Chain::new(7);
+        let mut tracker = tracker();
+        assert_eq!(
+            tracker.fast_path_update_auto(&chain, &ENVELOPE, &5u64),
+            Ok(8)
+        );
+
+        chain.rival.set(2);
+        assert_eq!(
+            tracker.fast_path_update_auto(&chain, &ENVELOPE, &6u64),
+            Ok(11)
+        );
+        assert_eq!(chain.sequence.get(), 11);
+        assert_eq!(tracker.current(&ENVELOPE), Some(11));
+        assert_eq!(chain.reads.get(), 3);
+    }
+
+    #[test]
+    fn update_auto_returns_errors_that_are_not_retryable() {
+        let mut chain = Chain::new(7);
+        chain.error = CuSoonError::EnvelopePaused;
+        chain.rival.set(1);
+        let mut tracker = tracker();
+        let err = tracker
+            .fast_path_update_auto(&chain, &ENVELOPE, &5u64)
+            .unwrap_err();
+        assert!(
+            matches!(err, UpdateError::Send(failure) if failure.error == CuSoonError::EnvelopePaused)
+        );
+        assert_eq!(chain.reads.get(), 1);
+
+        // Attempts stop at the policy limit.
+        chain.error = CuSoonError::InvalidInstructionData;
+        chain.rival.set(3);
+        assert!(matches!(
+            tracker.fast_path_update_auto(&chain, &ENVELOPE, &5u64),
+            Err(UpdateError::Send(_))
+        ));
+        assert_eq!(chain.reads.get(), 4);
+    }
+}
@@ -0,0 +1,375 @@
+//! Slot-aware envelope reads.
+//!
+//! A publisher that computes its next sequence from a lagging RPC node signs a transaction
+//! the program rejects as stale. [`fetch_envelope`] returns the envelope together with the
+//! slot the node served it at, and [`ReadTracker`] remembers the newest slot seen so reads
+//! that go backwards, or fall more than `max_lag` slots behind, are rejected before a
+//! transaction is built from them.
+//!
+//! The RPC transport is abstracted behind [`AccountSource`] so this module carries no
+//! network dependency.
+
+use c_u_soon::Envelope;
+use solana_address::Address;
+
+/// RPC commitment level for account reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Commitment {
+    Processed,
+    #[default]
+    Confirmed,
+    Finalized,
+}
+
+/// Read options forwarded to the RPC `getAccountInfo` call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FetchConfig {
+    pub commitment: Commitment,
+    /// Ask the node to refuse the read if it has not reached this slot.
+    pub min_context_slot: Option<u64>,
+}
+
+/// Raw account read together with the node's context slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotAccount {
+    pub context_slot: u64,
+    /// Program that owns the account.
+    pub owner: Address,
+    pub data: Vec<u8>,
+}
+
+/// Source of account data, typically an RPC client.
+pub trait AccountSource {
+    type Error;
+
+    /// Fetch `address` with `config`. Returns `Ok(None)` if the account does not exist.
+    fn get_account(
+        &self,
+        address: &Address,
+        config: &FetchConfig,
+    ) -> Result<Option<SlotAccount>, Self::Error>;
+}
+
+/// An envelope as of `context_slot`.
+#[derive(Clone, Copy)]
+pub struct EnvelopeSnapshot {
+    pub context_slot: u64,
+    pub envelope: Envelope,
+}
+
+impl core::fmt::Debug for EnvelopeSnapshot {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EnvelopeSnapshot")
+            .field("context_slot", &self.context_slot)
+            .field("oracle_sequence", &self.envelope.oracle_state.sequence)
+            .field(
+                "authority_aux_sequence",
+                &self.envelope.authority_aux_sequence,
+            )
+            .field("program_aux_sequence", &self.envelope.program_aux_sequence)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EnvelopeSnapshot {
+    /// Parse the first [`Envelope::SIZE`] bytes of `data`. Returns `None` if `data` is shorter.
+    pub fn from_account_data(context_slot: u64, data: &[u8]) -> Option<Self> {
+        let bytes = data.get(..Envelope::SIZE)?;
+        Some(Self {
+            context_slot,
+            envelope: bytemuck::pod_read_unaligned(bytes),
+        })
+    }
+
+    /// Slots between this read and `current_slot` (0 if the read is newer).
+    pub fn age(&self, current_slot: u64) -> u64 {
+        current_slot.saturating_sub(self.context_slot)
+    }
+
+    /// Returns [`StaleRead`] if this read is more than `max_age` slots behind `current_slot`.
+    pub fn ensure_fresh(&self, current_slot: u64, max_age: u64) -> Result<&Self, StaleRead> {
+        if self.age(current_slot) > max_age {
+            return Err(StaleRead {
+                context_slot: self.context_slot,
+                required_slot: current_slot - max_age,
+            });
+        }
+        Ok(self)
+    }
+
+    /// Next fast-path sequence, or `None` if the stored sequence is `u64::MAX`.
+    pub fn next_oracle_sequence(&self) -> Option<u64> {
+        self.envelope.oracle_state.sequence.checked_add(1)
+    }
+}
+
+/// A read served at an older slot than required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleRead {
+    pub context_slot: u64,
+    pub required_slot: u64,
+}
+
+impl core::fmt::Display for StaleRead {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "read at slot {} is older than required slot {}",
+            self.context_slot, self.required_slot
+        )
+    }
+}
+
+impl std::error::Error for StaleRead {}
+
+/// Errors from [`fetch_envelope`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchError<E> {
+    /// The account source failed.
+    Source(E),
+    /// The envelope account does not exist.
+    NotFound,
+    /// The account is shorter than [`Envelope::SIZE`].
+    InvalidAccountData,
+    /// The source ignored `min_context_slot` and served an older read.
+    Stale(StaleRead),
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for FetchError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Source(e) => write!(f, "account source: {e}"),
+            Self::NotFound => write!(f, "envelope account not found"),
+            Self::InvalidAccountData => write!(f, "account too small for an envelope"),
+            Self::Stale(stale) => write!(f, "{stale}"),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> std::error::Error for FetchError<E> {}
+
+/// Fetch and parse an envelope, returning it with its context slot.
+///
+/// `config.min_context_slot` is passed to the source and also checked on the result, so a
+/// source that ignores it still cannot return an older read.
+pub fn fetch_envelope<S: AccountSource>(
+    source: &S,
+    address: &Address,
+    config: &FetchConfig,
+) -> Result<EnvelopeSnapshot, FetchError<S::Error>> {
+    let account = fetch_account(source, address, config)?;
+    EnvelopeSnapshot::from_account_data(account.context_slot, &account.data)
+        .ok_or(FetchError::InvalidAccountData)
+}
+
+/// Fetch `address`, failing if it does not exist or was read below `min_context_slot`.
+pub(crate) fn fetch_account<S: AccountSource>(
+    source: &S,
+    address: &Address,
+    config: &FetchConfig,
+) -> Result<SlotAccount, FetchError<S::Error>> {
+    let account = source
+        .get_account(address, config)
+        .map_err(FetchError::Source)?
+        .ok_or(FetchError::NotFound)?;
+    if let Some(min) = config.min_context_slot {
+        if account.context_slot < min {
+            return Err(FetchError::Stale(StaleRead {
+                context_slot: account.context_slot,
+                required_slot: min,
+            }));
+        }
+    }
+    Ok(account)
+}
+
+/// Tracks the newest slot observed and rejects reads lagging more than `max_lag` behind it.
+///
+/// Feed it every slot the publisher learns about (landed transactions, `getSlot`, previous
+/// reads) via [`observe`](Self::observe). [`config`](Self::config) then sets
+/// `min_context_slot` so the node itself refuses lagging reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadTracker {
+    pub max_lag: u64,
+    highest_slot: u64,
+}
+
+impl ReadTracker {
+    pub const fn new(max_lag: u64) -> Self {
+        Self {
+            max_lag,
+            highest_slot: 0,
+        }
+    }
+
+    /// Newest slot observed so far.
+    pub fn highest_slot(&self) -> u64 {
+        self.highest_slot
+    }
+
+    /// Record a slot known to have been reached. Older slots are ignored.
+    pub fn observe(&mut self, slot: u64) {
+        self.highest_slot = self.highest_slot.max(slot);
+    }
+
+    /// Oldest context slot a read may have.
+    pub fn min_context_slot(&self) -> u64 {
+        self.highest_slot.saturating_sub(self.max_lag)
+    }
+
+    /// Fetch options requesting at least [`min_context_slot`](Self::min_context_slot).
+    pub fn config(&self, commitment: Commitment) -> FetchConfig {
+        FetchConfig {
+            commitment,
+            min_context_slot: Some(self.min_context_slot()),
+        }
+    }
+
+    /// Check `snapshot` against the tracked slot, then record its slot.
+    pub fn check(&mut self, snapshot: &EnvelopeSnapshot) -> Result<(), StaleRead> {
+        snapshot.ensure_fresh(self.highest_slot, self.max_lag)?;
+        self.observe(snapshot.context_slot);
+        Ok(())
+    }
+
+    /// [`fetch_envelope`] with [`config`](Self::config), then [`check`](Self::check).
+    pub fn fetch<S: AccountSource>(
+        &mut self,
+        source: &S,
+        address: &Address,
+        commitment: Commitment,
+    ) -> Result<EnvelopeSnapshot, FetchError<S::Error>> {
+        let snapshot = fetch_envelope(source, address, &self.config(commitment))?;
+        self.check(&snapshot).map_err(FetchError::Stale)?;
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+    use core::cell::Cell;
+
+    /// Serves one envelope at a fixed slot, ignoring `min_context_slot`.
+    struct FixedSource {
+        slot: Cell<u64>,
+        data: Option<Vec<u8>>,
+    }
+
+    impl AccountSource for FixedSource {
+        type Error = &'static str;
+
+        fn get_account(
+            &self,
+            _address: &Address,
+            _config: &FetchConfig,
+        ) -> Result<Option<SlotAccount>, Self::Error> {
+            Ok(self.data.clone().map(|data| SlotAccount {
+                context_slot: self.slot.get(),
+                owner: Address::default(),
+                data,
+            }))
+        }
+    }
+
+    fn envelope_source(slot: u64, sequence: u64) -> FixedSource {
+        let mut envelope = Envelope::zeroed();
+        envelope.oracle_state.sequence = sequence;
+        FixedSource {
+            slot: Cell::new(slot),
+            data: Some(bytemuck::bytes_of(&envelope).to_vec()),
+        }
+    }
+
+    #[test]
+    fn fetch_returns_context_slot() {
+        let source = envelope_source(100, 7);
+        let snapshot =
+            fetch_envelope(&source, &Address::default(), &FetchConfig::default()).unwrap();
+        assert_eq!(snapshot.context_slot, 100);
+        assert_eq!(snapshot.next_oracle_sequence(), Some(8));
+    }
+
+    #[test]
+    fn fetch_rejects_read_below_min_context_slot() {
+        let source = envelope_source(100, 7);
+        let config = FetchConfig {
+            min_context_slot: Some(101),
+            ..FetchConfig::default()
+        };
+        assert_eq!(
+            fetch_envelope(&source, &Address::default(), &config).unwrap_err(),
+            FetchError::Stale(StaleRead {
+                context_slot: 100,
+                required_slot: 101,
+            })
+        );
+    }
+
+    #[test]
+    fn fetch_reports_missing_and_short_accounts() {
+        let missing = FixedSource {
+            slot: Cell::new(1),
+            data: None,
+        };
+        assert_eq!(
+            fetch_envelope(&missing, &Address::default(), &FetchConfig::default()).unwrap_err(),
+            FetchError::NotFound
+        );
+
+        let short = FixedSource {
+            slot: Cell::new(1),
+            data: Some(vec![0; Envelope::SIZE - 1]),
+        };
+        assert_eq!(
+            fetch_envelope(&short, &Address::default(), &FetchConfig::default()).unwrap_err(),
+            FetchError::InvalidAccountData
+        );
+    }
+
+    #[test]
+    fn snapshot_freshness() {
+        let source = envelope_source(100, 0);
+        let snapshot =
+            fetch_envelope(&source, &Address::default(), &FetchConfig::default()).unwrap();
+        assert_eq!(snapshot.age(110), 10);
+        assert_eq!(snapshot.age(90), 0);
+        assert!(snapshot.ensure_fresh(110, 10).is_ok());
+        assert_eq!(
+            snapshot.ensure_fresh(111, 10).unwrap_err(),
+            StaleRead {
+                context_slot: 100,
+                required_slot: 101,
+            }
+        );
+    }
+
+    #[test]
+    fn tracker_rejects_reads_lagging_behind_observed_slot() {
+        let source = envelope_source(100, 0);
+        let mut tracker = ReadTracker::new(5);
+
+        tracker
+            .fetch(&source, &Address::default(), Commitment::Confirmed)
+            .unwrap();
+        assert_eq!(tracker.highest_slot(), 100);
+
+        // A transaction landed at 120; the node still serves slot 100.
+        tracker.observe(120);
+        assert_eq!(
+            tracker.config(Commitment::Confirmed).min_context_slot,
+            Some(115)
+        );
+        assert!(matches!(
+            tracker.fetch(&source, &Address::default(), Commitment::Confirmed),
+            Err(FetchError::Stale(_))
+        ));
+
+        source.slot.set(116);
+        tracker
+            .fetch(&source, &Address::default(), Commitment::Confirmed)
+            .unwrap();
+        assert_eq!(tracker.highest_slot(), 120);
+    }
+}
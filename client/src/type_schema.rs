@@ -0,0 +1,172 @@
+//! `StructMetadata` of a type described by a JSON schema, for clients without its Rust
+//! definition. Enabled by the `schema` feature.
+//!
+//! A schema lists the fields of a `#[repr(C)]` struct in declaration order:
+//!
+//! ```json
+//! {
+//!   "name": "AmmState",
+//!   "hash": "xxh64",
+//!   "fields": [
+//!     { "name": "pool_price", "type": "u64" },
+//!     { "name": "weights", "type": { "array": ["U32Le", 4] } },
+//!     { "name": "inner", "type": { "struct": { "name": "Inner", "fields": [] } } }
+//!   ]
+//! }
+//! ```
+//!
+//! Types are the primitives `u8` to `u128`, `i8` to `i128`, `f32`, and `f64`, the wrappers
+//! `U32Le`, `U64Le`, `I32Le`, and `I64Le`, `{ "array": [<type>, <len>] }`, and nested
+//! `{ "struct": <schema> }`. The hash matches `#[derive(TypeHash)]` on a non-generic struct
+//! with those fields in that order. `hash` is `"fnv1a"` (the default) or `"xxh64"`, as
+//! `#[type_hash(..)]`. Field names are not hashed and fields are laid out back to back, as a
+//! `Pod` struct has no padding.
+
+use c_u_soon::{combine_hash, combine_hash_xxh64, const_fnv1a, const_xxh64, StructMetadata};
+use serde_json::Value;
+
+/// Hash scheme of a schema struct, as `#[type_hash(..)]`.
+#[derive(Clone, Copy)]
+enum Scheme {
+    Fnv1a,
+    Xxh64,
+}
+
+impl Scheme {
+    fn parse(schema: &Value) -> Result<Self, String> {
+        match schema.get("hash") {
+            None => Ok(Self::Fnv1a),
+            Some(value) => match value.as_str() {
+                Some("fnv1a") => Ok(Self::Fnv1a),
+                Some("xxh64") => Ok(Self::Xxh64),
+                _ => Err(format!("hash must be \"fnv1a\" or \"xxh64\", got {value}")),
+            },
+        }
+    }
+}
+
+/// `(TYPE_HASH, size, scheme)` of a schema struct.
+fn struct_hash(schema: &Value) -> Result<(u64, usize, Scheme), String> {
+    let scheme = Scheme::parse(schema)?;
+    let name = schema
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or("schema needs a `name` string")?;
+    let fields = schema
+        .get("fields")
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("schema `{name}` needs a `fields` array"))?;
+    let (mut hash, combine): (u64, fn(u64, u64) -> u64) = match scheme {
+        Scheme::Fnv1a => (const_fnv1a(name.as_bytes()), combine_hash),
+        Scheme::Xxh64 => (const_xxh64(name.as_bytes()), combine_hash_xxh64),
+    };
+    let mut size = 0usize;
+    for field in fields {
+        let ty = field
+            .get("type")
+            .ok_or_else(|| format!("field {field} of `{name}` needs a `type`"))?;
+        let (field_hash, field_size) = type_hash(ty)?;
+        hash = combine(hash, field_hash);
+        size = size.saturating_add(field_size);
+    }
+    Ok((hash, size, scheme))
+}
+
+/// `(TYPE_HASH, size)` of a schema field type.
+fn type_hash(ty: &Value) -> Result<(u64, usize), String> {
+    if let Some(name) = ty.as_str() {
+        let size = match name {
+            "u8" | "i8" => 1,
+            "u16" | "i16" => 2,
+            "u32" | "i32" | "f32" | "U32Le" | "I32Le" => 4,
+            "u64" | "i64" | "f64" | "U64Le" | "I64Le" => 8,
+            "u128" | "i128" => 16,
+            other => return Err(format!("unknown type `{other}`")),
+        };
+        return Ok((const_fnv1a(name.as_bytes()), size));
+    }
+    if let Some(schema) = ty.get("struct") {
+        let (hash, size, _) = struct_hash(schema)?;
+        return Ok((hash, size));
+    }
+    match ty.get("array").and_then(Value::as_array).map(Vec::as_slice) {
+        Some([elem, len]) => {
+            let len = len
+                .as_u64()
+                .ok_or_else(|| format!("array length must be an integer, got {len}"))?;
+            let (elem_hash, elem_size) = type_hash(elem)?;
+            let hash = combine_hash(combine_hash(const_fnv1a(b"array"), elem_hash), len);
+            Ok((hash, elem_size.saturating_mul(len as usize)))
+        }
+        _ => Err(format!("unknown type {ty}")),
+    }
+}
+
+/// `StructMetadata` of the struct `schema` describes. Fails on a malformed schema, or if the
+/// struct is over 255 bytes, which no `TypeHash` type can be.
+pub fn schema_metadata(schema: &Value) -> Result<StructMetadata, String> {
+    let (hash, size, scheme) = struct_hash(schema)?;
+    let size = u8::try_from(size).map_err(|_| format!("type is {size} bytes, over 255"))?;
+    Ok(match scheme {
+        Scheme::Fnv1a => StructMetadata::new(size, hash),
+        Scheme::Xxh64 => StructMetadata::new_xxh64(size, hash),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c_u_soon::{TypeHash, U32Le};
+    use serde_json::json;
+
+    #[test]
+    fn schema_metadata_matches_the_derive() {
+        let schema = json!({
+            "name": "AmmState",
+            "fields": [
+                { "name": "price", "type": "u64" },
+                { "name": "weights", "type": { "array": ["U32Le", 4] } },
+                { "name": "fee", "type": "i16" },
+            ],
+        });
+        let hash = combine_hash(
+            combine_hash(
+                combine_hash(const_fnv1a(b"AmmState"), u64::TYPE_HASH),
+                <[U32Le; 4]>::TYPE_HASH,
+            ),
+            i16::TYPE_HASH,
+        );
+        assert_eq!(
+            schema_metadata(&schema).unwrap(),
+            StructMetadata::new(26, hash)
+        );
+    }
+
+    #[test]
+    fn schema_metadata_xxh64_and_nested_structs() {
+        let inner = json!({ "name": "Inner", "fields": [{ "name": "x", "type": "f64" }] });
+        let schema = json!({
+            "name": "Outer",
+            "hash": "xxh64",
+            "fields": [{ "name": "inner", "type": { "struct": inner } }],
+        });
+        let inner_hash = combine_hash(const_fnv1a(b"Inner"), f64::TYPE_HASH);
+        let hash = combine_hash_xxh64(const_xxh64(b"Outer"), inner_hash);
+        let metadata = schema_metadata(&schema).unwrap();
+        assert_eq!(metadata, StructMetadata::new_xxh64(8, hash));
+        assert!(metadata.has_xxh64_bit());
+    }
+
+    #[test]
+    fn schema_metadata_rejects_bad_schemas() {
+        for schema in [
+            json!({ "fields": [] }),
+            json!({ "name": "A" }),
+            json!({ "name": "A", "fields": [{ "name": "b", "type": "bool" }] }),
+            json!({ "name": "A", "fields": [{ "name": "b", "type": { "array": ["u8", 256] } }] }),
+            json!({ "name": "A", "hash": "sha256", "fields": [] }),
+        ] {
+            assert!(schema_metadata(&schema).is_err(), "{schema}");
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! JavaScript bindings through `wasm-bindgen`, enabled by the `wasm` feature.
+//!
+//! The exports call the same functions as the Rust API, so instruction bytes built in the
+//! browser match the ones built here. Addresses are base58 strings, metadata words are
+//! `bigint`, and built instructions are plain objects:
+//!
+//! ```text
+//! { programId: string, keys: [{ pubkey: string, isSigner: boolean, isWritable: boolean }], data: Uint8Array }
+//! ```
+//!
+//! which map onto `new TransactionInstruction(...)` after converting the address strings.
+//!
+//! [`type_metadata`] computes a type's `StructMetadata` from a JSON schema in the
+//! [`schema_metadata`](crate::schema_metadata) format, so a dashboard can create and write
+//! envelopes for a type without its Rust definition.
+
+use std::str::FromStr;
+
+use c_u_soon::{Mask, StructMetadata, MASK_SIZE};
+use js_sys::{Array, Object, Reflect, Uint8Array};
+use serde_json::Value;
+use solana_address::Address;
+use solana_instruction::Instruction;
+use wasm_bindgen::prelude::*;
+
+fn address(value: &str) -> Result<Address, JsError> {
+    Address::from_str(value).map_err(|_| JsError::new(&format!("`{value}` is not an address")))
+}
+
+/// Seeds passed from JavaScript as an array of `Uint8Array`.
+fn seeds(value: &Array) -> Vec<Vec<u8>> {
+    value
+        .iter()
+        .map(|seed| Uint8Array::new(&seed).to_vec())
+        .collect()
+}
+
+fn set(target: &Object, key: &str, value: &JsValue) {
+    Reflect::set(target, &key.into(), value).expect("plain objects accept new properties");
+}
+
+fn instruction_object(ix: Instruction) -> JsValue {
+    let keys = Array::new();
+    for meta in &ix.accounts {
+        let key = Object::new();
+        set(&key, "pubkey", &meta.pubkey.to_string().into());
+        set(&key, "isSigner", &meta.is_signer.into());
+        set(&key, "isWritable", &meta.is_writable.into());
+        keys.push(&key);
+    }
+    let object = Object::new();
+    set(&object, "programId", &ix.program_id.to_string().into());
+    set(&object, "keys", &keys);
+    set(&object, "data", &Uint8Array::from(ix.data.as_slice()));
+    object.into()
+}
+
+/// [`crate::fast_path_instruction_data`].
+#[wasm_bindgen(js_name = fastPathInstructionData)]
+pub fn fast_path_instruction_data(
+    oracle_meta: u64,
+    sequence: u64,
+    payload: &[u8],
+) -> Result<Vec<u8>, JsError> {
+    Ok(crate::fast_path_instruction_data(
+        oracle_meta,
+        sequence,
+        payload,
+    )?)
+}
+
+/// [`crate::versioned_instruction_data`].
+#[wasm_bindgen(js_name = versionedInstructionData)]
+pub fn versioned_instruction_data(instruction_data: &[u8]) -> Vec<u8> {
+    crate::versioned_instruction_data(instruction_data)
+}
+
+/// [`crate::create_instruction_data`].
+#[wasm_bindgen(js_name = createInstructionData)]
+pub fn create_instruction_data(
+    custom_seeds: &Array,
+    bump: u8,
+    oracle_metadata: u64,
+) -> Result<Vec<u8>, JsError> {
+    let custom_seeds = seeds(custom_seeds);
+    let custom_seeds: Vec<&[u8]> = custom_seeds.iter().map(Vec::as_slice).collect();
+    Ok(crate::create_instruction_data(
+        &custom_seeds,
+        bump,
+        StructMetadata::from_raw(oracle_metadata),
+    )?)
+}
+
+/// [`crate::create_with_events_instruction_data`].
+#[wasm_bindgen(js_name = createWithEventsInstructionData)]
+pub fn create_with_events_instruction_data(
+    custom_seeds: &Array,
+    bump: u8,
+    oracle_metadata: u64,
+) -> Result<Vec<u8>, JsError> {
+    let custom_seeds = seeds(custom_seeds);
+    let custom_seeds: Vec<&[u8]> = custom_seeds.iter().map(Vec::as_slice).collect();
+    Ok(crate::create_with_events_instruction_data(
+        &custom_seeds,
+        bump,
+        StructMetadata::from_raw(oracle_metadata),
+    )?)
+}
+
+/// [`crate::close_instruction_data`].
+#[wasm_bindgen(js_name = closeInstructionData)]
+pub fn close_instruction_data() -> Result<Vec<u8>, JsError> {
+    Ok(crate::close_instruction_data()?)
+}
+
+/// [`crate::set_delegated_program_instruction_data`].
+#[wasm_bindgen(js_name = setDelegatedProgramInstructionData)]
+pub fn set_delegated_program_instruction_data(
+    program_bitmask: &JsMask,
+    user_bitmask: &JsMask,
+) -> Result<Vec<u8>, JsError> {
+    Ok(crate::set_delegated_program_instruction_data(
+        program_bitmask.0,
+        user_bitmask.0,
+    )?)
+}
+
+/// [`crate::clear_delegation_instruction_data`].
+#[wasm_bindgen(js_name = clearDelegationInstructionData)]
+pub fn clear_delegation_instruction_data() -> Result<Vec<u8>, JsError> {
+    Ok(crate::clear_delegation_instruction_data()?)
+}
+
+/// [`crate::transfer_delegation_instruction_data`].
+#[wasm_bindgen(js_name = transferDelegationInstructionData)]
+pub fn transfer_delegation_instruction_data(
+    program_bitmask: &JsMask,
+    user_bitmask: &JsMask,
+) -> Result<Vec<u8>, JsError> {
+    Ok(crate::transfer_delegation_instruction_data(
+        program_bitmask.0,
+        user_bitmask.0,
+    )?)
+}
+
+/// [`crate::find_envelope_address`], returned as `[address, bump]`.
+#[wasm_bindgen(js_name = findEnvelopeAddress)]
+pub fn find_envelope_address(
+    program_id: &str,
+    authority: &str,
+    custom_seeds: &Array,
+) -> Result<Array, JsError> {
+    let custom_seeds = seeds(custom_seeds);
+    let custom_seeds: Vec<&[u8]> = custom_seeds.iter().map(Vec::as_slice).collect();
+    let (envelope, bump) =
+        crate::find_envelope_address(&address(program_id)?, &address(authority)?, &custom_seeds);
+    Ok(Array::of2(&envelope.to_string().into(), &bump.into()))
+}
+
+/// [`crate::build_create_ix`].
+#[wasm_bindgen(js_name = buildCreateIx)]
+pub fn build_create_ix(
+    program_id: &str,
+    authority: &str,
+    custom_seeds: &Array,
+    bump: u8,
+    oracle_metadata: u64,
+) -> Result<JsValue, JsError> {
+    let custom_seeds = seeds(custom_seeds);
+    let custom_seeds: Vec<&[u8]> = custom_seeds.iter().map(Vec::as_slice).collect();
+    let ix = crate::build_create_ix(
+        &address(program_id)?,
+        &address(authority)?,
+        &custom_seeds,
+        bump,
+        StructMetadata::from_raw(oracle_metadata),
+    )?;
+    Ok(instruction_object(ix))
+}
+
+/// [`crate::build_close_ix`].
+#[wasm_bindgen(js_name = buildCloseIx)]
+pub fn build_close_ix(
+    program_id: &str,
+    authority: &str,
+    envelope: &str,
+    recipient: &str,
+) -> Result<JsValue, JsError> {
+    let ix = crate::build_close_ix(
+        &address(program_id)?,
+        &address(authority)?,
+        &address(envelope)?,
+        &address(recipient)?,
+    )?;
+    Ok(instruction_object(ix))
+}
+
+/// [`crate::build_fast_path_ix`].
+#[wasm_bindgen(js_name = buildFastPathIx)]
+pub fn build_fast_path_ix(
+    program_id: &str,
+    authority: &str,
+    envelope: &str,
+    oracle_meta: u64,
+    sequence: u64,
+    payload: &[u8],
+) -> Result<JsValue, JsError> {
+    let ix = crate::build_fast_path_ix(
+        &address(program_id)?,
+        &address(authority)?,
+        &address(envelope)?,
+        oracle_meta,
+        sequence,
+        payload,
+    )?;
+    Ok(instruction_object(ix))
+}
+
+/// [`crate::build_set_delegated_program_ix`].
+#[wasm_bindgen(js_name = buildSetDelegatedProgramIx)]
+pub fn build_set_delegated_program_ix(
+    program_id: &str,
+    authority: &str,
+    envelope: &str,
+    delegation_authority: &str,
+    program_bitmask: &JsMask,
+    user_bitmask: &JsMask,
+) -> Result<JsValue, JsError> {
+    let ix = crate::build_set_delegated_program_ix(
+        &address(program_id)?,
+        &address(authority)?,
+        &address(envelope)?,
+        &address(delegation_authority)?,
+        program_bitmask.0,
+        user_bitmask.0,
+    )?;
+    Ok(instruction_object(ix))
+}
+
+/// [`crate::build_clear_delegation_ix`].
+#[wasm_bindgen(js_name = buildClearDelegationIx)]
+pub fn build_clear_delegation_ix(
+    program_id: &str,
+    authority: &str,
+    envelope: &str,
+    delegation_authority: &str,
+) -> Result<JsValue, JsError> {
+    let ix = crate::build_clear_delegation_ix(
+        &address(program_id)?,
+        &address(authority)?,
+        &address(envelope)?,
+        &address(delegation_authority)?,
+    )?;
+    Ok(instruction_object(ix))
+}
+
+/// [`crate::build_transfer_delegation_ix`].
+#[wasm_bindgen(js_name = buildTransferDelegationIx)]
+pub fn build_transfer_delegation_ix(
+    program_id: &str,
+    authority: &str,
+    envelope: &str,
+    old_delegation_authority: &str,
+    new_delegation_authority: &str,
+    program_bitmask: &JsMask,
+    user_bitmask: &JsMask,
+) -> Result<JsValue, JsError> {
+    let ix = crate::build_transfer_delegation_ix(
+        &address(program_id)?,
+        &address(authority)?,
+        &address(envelope)?,
+        &address(old_delegation_authority)?,
+        &address(new_delegation_authority)?,
+        program_bitmask.0,
+        user_bitmask.0,
+    )?;
+    Ok(instruction_object(ix))
+}
+
+/// [`Mask`] for JavaScript, exported as `Mask`.
+#[wasm_bindgen(js_name = Mask)]
+#[derive(Clone, Copy)]
+pub struct JsMask(Mask);
+
+#[wasm_bindgen(js_class = Mask)]
+impl JsMask {
+    /// [`Mask::ALL_BLOCKED`].
+    #[wasm_bindgen(js_name = allBlocked)]
+    pub fn all_blocked() -> JsMask {
+        Self(Mask::ALL_BLOCKED)
+    }
+
+    /// [`Mask::ALL_WRITABLE`].
+    #[wasm_bindgen(js_name = allWritable)]
+    pub fn all_writable() -> JsMask {
+        Self(Mask::ALL_WRITABLE)
+    }
+
+    /// A mask from its 256 wire bytes, as read from an envelope.
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<JsMask, JsError> {
+        let bytes: [u8; MASK_SIZE] = bytes.try_into().map_err(|_| {
+            JsError::new(&format!(
+                "expected {MASK_SIZE} mask bytes, got {}",
+                bytes.len()
+            ))
+        })?;
+        Ok(Self(Mask::from(bytes)))
+    }
+
+    /// [`Mask::allow`].
+    pub fn allow(&mut self, byte_idx: usize) {
+        self.0.allow(byte_idx);
+    }
+
+    /// [`Mask::block`].
+    pub fn block(&mut self, byte_idx: usize) {
+        self.0.block(byte_idx);
+    }
+
+    /// [`Mask::allow_range`].
+    #[wasm_bindgen(js_name = allowRange)]
+    pub fn allow_range(&mut self, offset: usize, len: usize) {
+        self.0.allow_range(offset, len);
+    }
+
+    /// [`Mask::block_range`].
+    #[wasm_bindgen(js_name = blockRange)]
+    pub fn block_range(&mut self, offset: usize, len: usize) {
+        self.0.block_range(offset, len);
+    }
+
+    /// [`Mask::is_writable`].
+    #[wasm_bindgen(js_name = isWritable)]
+    pub fn is_writable(&self, byte_idx: usize) -> bool {
+        self.0.is_writable(byte_idx)
+    }
+
+    /// [`Mask::is_canonical`].
+    #[wasm_bindgen(js_name = isCanonical)]
+    pub fn is_canonical(&self) -> bool {
+        self.0.is_canonical()
+    }
+
+    /// The 256 wire bytes.
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.as_bytes().to_vec()
+    }
+}
+
+/// [`crate::schema_metadata`] of a JSON schema string, packed.
+#[wasm_bindgen(js_name = typeMetadata)]
+pub fn type_metadata(schema: &str) -> Result<u64, JsError> {
+    let schema: Value = serde_json::from_str(schema)?;
+    crate::schema_metadata(&schema)
+        .map(|metadata| metadata.as_u64())
+        .map_err(|e| JsError::new(&e))
+}
@@ -0,0 +1,9 @@
+//! Wire compatibility checks between the workspace `c_u_soon_instruction` and the last
+//! version published to crates.io.
+//!
+//! The tests in `tests/` build the same instruction with both crates and require the
+//! serialized bytes to be identical, and each side to decode the other's output. Only
+//! variants and constants present in the published release are compared; new variants
+//! are covered by `discriminant_stability` in the instruction crate.
+//!
+//! To move the baseline after a release, bump the `published` version in `Cargo.toml`.
@@ -0,0 +1,161 @@
+use current::SlowPathInstruction as Current;
+use published::SlowPathInstruction as Published;
+
+/// Both encodings must match byte-for-byte, and each crate must decode the other's output
+/// back to the same bytes.
+fn assert_wire_compatible(current: &Current, published: &Published) {
+    let current_bytes = wincode::serialize(current).unwrap();
+    let published_bytes = wincode::serialize(published).unwrap();
+    assert_eq!(
+        current_bytes, published_bytes,
+        "encoding diverged for {current:?}"
+    );
+
+    let decoded: Published = wincode::deserialize(&current_bytes).unwrap();
+    assert_eq!(wincode::serialize(&decoded).unwrap(), current_bytes);
+
+    let decoded: Current = wincode::deserialize(&published_bytes).unwrap();
+    assert_eq!(wincode::serialize(&decoded).unwrap(), published_bytes);
+}
+
+fn bitmask<const N: usize>(seed: u8) -> [u8; N] {
+    core::array::from_fn(|i| {
+        if (i as u8 ^ seed) % 3 == 0 {
+            0x00
+        } else {
+            0xFF
+        }
+    })
+}
+
+#[test]
+fn create() {
+    let seeds = vec![vec![1, 2, 3], vec![], vec![0xAB; 32]];
+    assert_wire_compatible(
+        &Current::Create {
+            custom_seeds: seeds.clone(),
+            bump: 254,
+            oracle_metadata: 0xDEAD_BEEF_1234_5678,
+        },
+        &Published::Create {
+            custom_seeds: seeds,
+            bump: 254,
+            oracle_metadata: 0xDEAD_BEEF_1234_5678,
+        },
+    );
+}
+
+#[test]
+fn close() {
+    assert_wire_compatible(&Current::Close, &Published::Close);
+}
+
+#[test]
+fn set_delegated_program() {
+    assert_wire_compatible(
+        &Current::SetDelegatedProgram {
+            program_bitmask: bitmask(1),
+            user_bitmask: bitmask(2),
+        },
+        &Published::SetDelegatedProgram {
+            program_bitmask: bitmask(1),
+            user_bitmask: bitmask(2),
+        },
+    );
+}
+
+#[test]
+fn clear_delegation() {
+    assert_wire_compatible(&Current::ClearDelegation, &Published::ClearDelegation);
+}
+
+#[test]
+fn update_auxiliary_multi_range() {
+    let ranges = [(0u8, vec![1u8]), (17, vec![2, 3, 4]), (250, vec![0xFF; 5])];
+    assert_wire_compatible(
+        &Current::UpdateAuxiliaryMultiRange {
+            metadata: 0x0102_0304_0506_0708,
+            sequence: 42,
+            ranges: ranges
+                .iter()
+                .map(|(offset, data)| current::WriteSpec {
+                    offset: *offset,
+                    data: data.clone(),
+                })
+                .collect(),
+        },
+        &Published::UpdateAuxiliaryMultiRange {
+            metadata: 0x0102_0304_0506_0708,
+            sequence: 42,
+            ranges: ranges
+                .iter()
+                .map(|(offset, data)| published::WriteSpec {
+                    offset: *offset,
+                    data: data.clone(),
+                })
+                .collect(),
+        },
+    );
+}
+
+#[test]
+fn update_auxiliary_delegated_multi_range() {
+    let ranges = [(3u8, vec![9u8, 8]), (128, vec![7; 64])];
+    assert_wire_compatible(
+        &Current::UpdateAuxiliaryDelegatedMultiRange {
+            metadata: u64::MAX,
+            sequence: 1,
+            ranges: ranges
+                .iter()
+                .map(|(offset, data)| current::WriteSpec {
+                    offset: *offset,
+                    data: data.clone(),
+                })
+                .collect(),
+        },
+        &Published::UpdateAuxiliaryDelegatedMultiRange {
+            metadata: u64::MAX,
+            sequence: 1,
+            ranges: ranges
+                .iter()
+                .map(|(offset, data)| published::WriteSpec {
+                    offset: *offset,
+                    data: data.clone(),
+                })
+                .collect(),
+        },
+    );
+}
+
+#[test]
+fn manual_wire_format_constants() {
+    assert_eq!(current::UPDATE_AUX_TAG, published::UPDATE_AUX_TAG);
+    assert_eq!(
+        current::UPDATE_AUX_DELEGATED_TAG,
+        published::UPDATE_AUX_DELEGATED_TAG
+    );
+    assert_eq!(
+        current::UPDATE_AUX_FORCE_TAG,
+        published::UPDATE_AUX_FORCE_TAG
+    );
+    assert_eq!(
+        current::UPDATE_AUX_RANGE_TAG,
+        published::UPDATE_AUX_RANGE_TAG
+    );
+    assert_eq!(
+        current::UPDATE_AUX_DELEGATED_RANGE_TAG,
+        published::UPDATE_AUX_DELEGATED_RANGE_TAG
+    );
+    assert_eq!(
+        current::UPDATE_AUX_HEADER_SIZE,
+        published::UPDATE_AUX_HEADER_SIZE
+    );
+    assert_eq!(
+        current::UPDATE_AUX_FORCE_HEADER_SIZE,
+        published::UPDATE_AUX_FORCE_HEADER_SIZE
+    );
+    assert_eq!(
+        current::UPDATE_AUX_RANGE_HEADER_SIZE,
+        published::UPDATE_AUX_RANGE_HEADER_SIZE
+    );
+}
@@ -3,17 +3,26 @@
 //!
 //! Each struct assembles instruction data and accounts, then provides
 //! `invoke()` and `invoke_signed()` methods following the pinocchio convention.
+//!
+//! Every instruction that changes an envelope, except the fast path, takes the program
+//! config as its `config` account: the program's `[CONFIG_SEED, bump]` PDA, from
+//! `c_u_soon::derive_config_address`, whether or not it has been initialized. The program
+//! fails while it is paused.
 
 extern crate alloc;
 
-use c_u_soon::ORACLE_BYTES;
+use alloc::vec::Vec;
+use bytemuck::Pod;
+use c_u_soon::{CompactMask, Envelope, TypeHash, ORACLE_BYTES};
 use c_u_soon_instruction::{
-    SlowPathInstruction, WriteSpec, UPDATE_AUX_DELEGATED_RANGE_TAG, UPDATE_AUX_DELEGATED_TAG,
+    BatchEntry, SlowPathInstruction, WriteSpec, BATCH_ENTRY_HEADER_SIZE, BATCH_UPDATE_HEADER_SIZE,
+    BATCH_UPDATE_TAG, MAX_BATCH_SIZE, MAX_WRITE_RANGES, TOO_MANY_RANGES_ERROR,
+    UPDATE_AUX_DELEGATED_RANGE_TAG, UPDATE_AUX_DELEGATED_SCHEMA_TAG, UPDATE_AUX_DELEGATED_TAG,
     UPDATE_AUX_FORCE_MAX_SIZE, UPDATE_AUX_FORCE_TAG, UPDATE_AUX_MAX_SIZE,
     UPDATE_AUX_RANGE_MAX_SIZE, UPDATE_AUX_RANGE_TAG, UPDATE_AUX_TAG,
 };
 use pinocchio::{
-    cpi::{invoke_signed, Signer},
+    cpi::{invoke_signed, slice_invoke_signed, Signer},
     error::ProgramError,
     instruction::{InstructionAccount, InstructionView},
     AccountView, ProgramResult,
@@ -26,8 +35,376 @@ pub fn next_sequence(current: u64) -> Result<u64, ProgramError> {
         .ok_or(ProgramError::ArithmeticOverflow)
 }
 
+/// Read an envelope's oracle value as `T` and pass it to `f`, after checking that the
+/// envelope belongs to `program`, holds a `T`, and is fresh.
+///
+/// Fails with `IncorrectProgramId` if `envelope` is not owned by `program`,
+/// `InvalidAccountData` if it is not an envelope or its oracle metadata is not
+/// `T::METADATA`, and `InvalidArgument` if its oracle sequence is below `min_sequence`.
+///
+/// Envelopes do not record when they were written, so freshness is a sequence floor: pass
+/// the lowest sequence the caller will act on, e.g. one it saw earlier in the same
+/// transaction or stored in its own state. The envelope stays borrowed while `f` runs.
+///
+/// Usually called through [`with_fresh_oracle!`].
+pub fn read_fresh_oracle<T: TypeHash + Pod, R>(
+    envelope: &AccountView,
+    program: &AccountView,
+    min_sequence: u64,
+    f: impl FnOnce(&T) -> Result<R, ProgramError>,
+) -> Result<R, ProgramError> {
+    if !envelope.owned_by(program.address()) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let data = envelope.try_borrow()?;
+    let env = Envelope::from_prefix_bytes(&data).ok_or(ProgramError::InvalidAccountData)?;
+    let value = env.oracle::<T>().ok_or(ProgramError::InvalidAccountData)?;
+    if env.oracle_state.sequence < min_sequence {
+        return Err(ProgramError::InvalidArgument);
+    }
+    f(value)
+}
+
+/// Run `body` with the envelope's oracle value bound as `&T`, only if the checks of
+/// [`read_fresh_oracle`] pass. `body` returns `Result<R, ProgramError>`.
+///
+/// ```ignore
+/// let price = with_fresh_oracle!(envelope, c_u_soon_program, Price, min_sequence, |p| {
+///     Ok(p.value)
+/// })?;
+/// ```
+#[macro_export]
+macro_rules! with_fresh_oracle {
+    ($envelope:expr, $program:expr, $ty:ty, $min_sequence:expr, |$value:ident| $body:expr) => {
+        $crate::read_fresh_oracle::<$ty, _>($envelope, $program, $min_sequence, |$value: &$ty| {
+            $body
+        })
+    };
+}
+
+/// Trailing accounts for delegated writes to an envelope with range guards.
+///
+/// `range_guard` is the envelope's `[RANGE_GUARD_SEED, envelope]` PDA; `instructions_sysvar`
+/// is `Sysvar1nstructions1111111111111111111111111`.
+///
+/// An envelope with only the mutation guard needs just the sysvar as its last account; the
+/// program does not read `range_guard` unless the range guard flag is set, so passing the
+/// sysvar in both fields works.
+pub struct RangeGuardAccounts<'a> {
+    pub range_guard: &'a AccountView,
+    pub instructions_sysvar: &'a AccountView,
+}
+
+/// Invoke a delegated write with `[delegation_auth, envelope, padding]` (the padding slot
+/// carries the delegate schema for schema writes), appending the
+/// range guard accounts when present and then `config`.
+fn invoke_delegated(
+    program: &AccountView,
+    [delegation_auth, envelope, padding]: [&AccountView; 3],
+    range_guard: &Option<RangeGuardAccounts>,
+    config: &AccountView,
+    data: &[u8],
+    signers: &[Signer],
+) -> ProgramResult {
+    let Some(guard) = range_guard else {
+        let cpi_accounts = [
+            InstructionAccount::readonly_signer(delegation_auth.address()),
+            InstructionAccount::writable(envelope.address()),
+            InstructionAccount::readonly(padding.address()),
+            InstructionAccount::readonly(config.address()),
+        ];
+        let ix = InstructionView {
+            program_id: program.address(),
+            accounts: &cpi_accounts,
+            data,
+        };
+        return invoke_signed(&ix, &[delegation_auth, envelope, padding, config], signers);
+    };
+
+    let cpi_accounts = [
+        InstructionAccount::readonly_signer(delegation_auth.address()),
+        InstructionAccount::writable(envelope.address()),
+        InstructionAccount::readonly(padding.address()),
+        InstructionAccount::readonly(guard.range_guard.address()),
+        InstructionAccount::readonly(guard.instructions_sysvar.address()),
+        InstructionAccount::readonly(config.address()),
+    ];
+    let ix = InstructionView {
+        program_id: program.address(),
+        accounts: &cpi_accounts,
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            delegation_auth,
+            envelope,
+            padding,
+            guard.range_guard,
+            guard.instructions_sysvar,
+            config,
+        ],
+        signers,
+    )
+}
+
+/// CPI: Create (initialize an envelope PDA owned by `authority`).
+///
+/// Serialized via wincode as `SlowPathInstruction::Create`.
+///
+/// Account order: `[authority (writable signer), envelope (writable), system_program (readonly),
+/// config (writable)]`
+///
+/// `envelope` is the `[ENVELOPE_SEED, authority, ...custom_seeds, bump]` PDA of `program`;
+/// with the sdk's `pda` feature, `c_u_soon::derive_envelope_address` computes it and its
+/// bump. `config` is the program's `[CONFIG_SEED, bump]` PDA, from
+/// `c_u_soon::derive_config_address`, whether or not it has been initialized. `authority`
+/// pays the envelope's rent and the config's creation fee, so a program provisioning
+/// envelopes for its users passes one of its own PDAs as `authority` and signs for it with
+/// `invoke_signed`. Create is idempotent: calling it on an existing envelope with the same
+/// authority, bump, and metadata succeeds without changes.
+pub struct CreateEnvelope<'a> {
+    pub authority: &'a AccountView,
+    pub envelope: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub config: &'a AccountView,
+    pub program: &'a AccountView,
+    pub custom_seeds: &'a [&'a [u8]],
+    pub bump: u8,
+    pub oracle_metadata: u64,
+}
+
+impl CreateEnvelope<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let ix_data = SlowPathInstruction::Create {
+            custom_seeds: self.custom_seeds.iter().map(|seed| seed.to_vec()).collect(),
+            bump: self.bump,
+            oracle_metadata: self.oracle_metadata,
+        };
+        if !ix_data.validate() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let buf = wincode::serialize(&ix_data).map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let cpi_accounts = [
+            InstructionAccount::writable_signer(self.authority.address()),
+            InstructionAccount::writable(self.envelope.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+            InstructionAccount::writable(self.config.address()),
+        ];
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &buf,
+        };
+        invoke_signed(
+            &ix,
+            &[
+                self.authority,
+                self.envelope,
+                self.system_program,
+                self.config,
+            ],
+            signers,
+        )
+    }
+}
+
+/// CPI: Close (zero an envelope and send its lamports to `recipient`).
+///
+/// Serialized via wincode as `SlowPathInstruction::Close`.
+///
+/// Account order: `[authority (readonly signer), envelope (writable), recipient (writable),
+/// config (readonly)]`
+///
+/// The program reassigns the closed account to the system program itself, so no
+/// system program account is passed. Fails while the envelope is delegated or permanent.
+pub struct CloseEnvelope<'a> {
+    pub authority: &'a AccountView,
+    pub envelope: &'a AccountView,
+    pub recipient: &'a AccountView,
+    pub config: &'a AccountView,
+    pub program: &'a AccountView,
+}
+
+impl CloseEnvelope<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let buf = wincode::serialize(&SlowPathInstruction::Close)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let cpi_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::writable(self.envelope.address()),
+            InstructionAccount::writable(self.recipient.address()),
+        ];
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &buf,
+        };
+        invoke_signed(
+            &ix,
+            &[self.authority, self.envelope, self.recipient],
+            signers,
+        )
+    }
+}
+
 const FAST_PATH_MAX: usize = 8 + 8 + ORACLE_BYTES; // 255
 
+/// Invoke an authority aux write: `[authority (readonly signer), envelope (writable), pda
+/// (readonly signer)]`, followed by `[write_hook (readonly)]` if `write_hook` is set,
+/// `[instructions_sysvar (readonly)]` if `instructions_sysvar` is set, and then
+/// `[config (readonly)]`.
+fn invoke_authority(
+    program: &AccountView,
+    [authority, envelope, pda]: [&AccountView; 3],
+    write_hook: Option<&AccountView>,
+    instructions_sysvar: Option<&AccountView>,
+    config: &AccountView,
+    data: &[u8],
+    signers: &[Signer],
+) -> ProgramResult {
+    let mut cpi_accounts = Vec::with_capacity(6);
+    cpi_accounts.push(InstructionAccount::readonly_signer(authority.address()));
+    cpi_accounts.push(InstructionAccount::writable(envelope.address()));
+    cpi_accounts.push(InstructionAccount::readonly_signer(pda.address()));
+    let mut views = Vec::with_capacity(6);
+    views.extend([authority, envelope, pda]);
+    for account in [write_hook, instructions_sysvar].into_iter().flatten() {
+        cpi_accounts.push(InstructionAccount::readonly(account.address()));
+        views.push(account);
+    }
+    cpi_accounts.push(InstructionAccount::readonly(config.address()));
+    views.push(config);
+    let ix = InstructionView {
+        program_id: program.address(),
+        accounts: &cpi_accounts,
+        data,
+    };
+    slice_invoke_signed(&ix, &views, signers)
+}
+
+/// CPI: SetDelegatedProgramCompact (delegate an envelope with 32-byte packed bitmasks).
+///
+/// Serialized via wincode as `SlowPathInstruction::SetDelegatedProgramCompact`.
+///
+/// Account order: `[authority (readonly signer), envelope (writable),
+/// delegation_auth (readonly signer), config (readonly)]`
+///
+/// The program expands both masks and installs them as `SetDelegatedProgram` would, so a
+/// program delegating envelopes it controls sends 68 bytes of instruction data instead of
+/// 516. Fails if the envelope is already delegated.
+pub struct SetDelegatedProgramCompact<'a> {
+    pub authority: &'a AccountView,
+    pub envelope: &'a AccountView,
+    pub delegation_auth: &'a AccountView,
+    pub config: &'a AccountView,
+    pub program: &'a AccountView,
+    pub program_bitmask: CompactMask,
+    pub user_bitmask: CompactMask,
+}
+
+impl SetDelegatedProgramCompact<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let ix_data = SlowPathInstruction::SetDelegatedProgramCompact {
+            program_bitmask: self.program_bitmask.to_bytes(),
+            user_bitmask: self.user_bitmask.to_bytes(),
+        };
+        let buf = wincode::serialize(&ix_data).map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let cpi_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::writable(self.envelope.address()),
+            InstructionAccount::readonly_signer(self.delegation_auth.address()),
+            InstructionAccount::readonly(self.config.address()),
+        ];
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &buf,
+        };
+        invoke_signed(
+            &ix,
+            &[
+                self.authority,
+                self.envelope,
+                self.delegation_auth,
+                self.config,
+            ],
+            signers,
+        )
+    }
+}
+
+/// CPI: SetWriteHook (turn the delegation's write hook on or off).
+///
+/// Serialized via wincode as `SlowPathInstruction::SetWriteHook`.
+///
+/// Account order: `[authority (readonly signer), envelope (writable),
+/// delegation_auth (readonly signer), hook_program (readonly), config (readonly)]`
+///
+/// With `notify_on_authority_write`, every later authority aux write calls `hook_program`
+/// after applying the write, and fails if it does. `hook_program` is not read otherwise.
+pub struct SetWriteHook<'a> {
+    pub authority: &'a AccountView,
+    pub envelope: &'a AccountView,
+    pub delegation_auth: &'a AccountView,
+    pub hook_program: &'a AccountView,
+    pub config: &'a AccountView,
+    pub program: &'a AccountView,
+    pub notify_on_authority_write: bool,
+}
+
+impl SetWriteHook<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let ix_data = SlowPathInstruction::SetWriteHook {
+            notify_on_authority_write: self.notify_on_authority_write,
+        };
+        let buf = wincode::serialize(&ix_data).map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let cpi_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::writable(self.envelope.address()),
+            InstructionAccount::readonly_signer(self.delegation_auth.address()),
+            InstructionAccount::readonly(self.hook_program.address()),
+            InstructionAccount::readonly(self.config.address()),
+        ];
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &buf,
+        };
+        invoke_signed(
+            &ix,
+            &[
+                self.authority,
+                self.envelope,
+                self.delegation_auth,
+                self.hook_program,
+                self.config,
+            ],
+            signers,
+        )
+    }
+}
+
 /// CPI: fast path oracle update.
 ///
 /// Instruction data: `[oracle_meta: u64 LE | sequence: u64 LE | payload: ...]`
@@ -70,11 +447,83 @@ impl FastPathUpdate<'_> {
     }
 }
 
+/// CPI: batched oracle update (one fast-path style update per envelope).
+///
+/// Wire format: `[disc:4][count:1]` then per entry `[oracle_meta:8][sequence:8][len:1][payload:len]`
+///
+/// Account order: `[authority (readonly signer), envelopes[0] (writable), ..,
+/// instructions_sysvar (readonly, if set), config (readonly)]`
+///
+/// `entries[i]` updates `envelopes[i]`. Needs 2 to `MAX_BATCH_SIZE` envelopes; use
+/// [`FastPathUpdate`] for one.
+pub struct BatchUpdate<'a> {
+    pub authority: &'a AccountView,
+    pub envelopes: &'a [&'a AccountView],
+    /// The instructions sysvar, required when any envelope has the mutation guard; `None`
+    /// otherwise.
+    pub instructions_sysvar: Option<&'a AccountView>,
+    pub config: &'a AccountView,
+    pub program: &'a AccountView,
+    pub entries: &'a [BatchEntry<'a>],
+}
+
+impl BatchUpdate<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let count = self.entries.len();
+        if !(2..=MAX_BATCH_SIZE).contains(&count) || self.envelopes.len() != count {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut buf =
+            Vec::with_capacity(BATCH_UPDATE_HEADER_SIZE + count * BATCH_ENTRY_HEADER_SIZE);
+        buf.extend_from_slice(&BATCH_UPDATE_TAG.to_le_bytes());
+        buf.push(count as u8);
+        for entry in self.entries {
+            if entry.payload.len() > ORACLE_BYTES {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            buf.extend_from_slice(&entry.oracle_meta.to_le_bytes());
+            buf.extend_from_slice(&entry.sequence.to_le_bytes());
+            buf.push(entry.payload.len() as u8);
+            buf.extend_from_slice(entry.payload);
+        }
+
+        let mut cpi_accounts = Vec::with_capacity(3 + count);
+        cpi_accounts.push(InstructionAccount::readonly_signer(
+            self.authority.address(),
+        ));
+        let mut views = Vec::with_capacity(3 + count);
+        views.push(self.authority);
+        for envelope in self.envelopes {
+            cpi_accounts.push(InstructionAccount::writable(envelope.address()));
+            views.push(*envelope);
+        }
+        if let Some(instructions_sysvar) = self.instructions_sysvar {
+            cpi_accounts.push(InstructionAccount::readonly(instructions_sysvar.address()));
+            views.push(instructions_sysvar);
+        }
+        cpi_accounts.push(InstructionAccount::readonly(self.config.address()));
+        views.push(self.config);
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &buf,
+        };
+        slice_invoke_signed(&ix, &views, signers)
+    }
+}
+
 /// CPI: UpdateAuxiliary (authority writes aux data).
 ///
 /// Wire format: `[disc:4][metadata:8][sequence:8][data:N]`
 ///
-/// Account order: `[authority (readonly signer), envelope (writable), pda (readonly signer)]`
+/// Account order: `[authority (readonly signer), envelope (writable), pda (readonly signer)]`,
+/// followed by `[write_hook (readonly)]` if `write_hook` is set,
+/// `[instructions_sysvar (readonly)]` if `instructions_sysvar` is set, and then
+/// `[config (readonly)]`.
 ///
 /// `pda` is the caller's PDA; the Solana runtime verifies it as a signer to confirm
 /// the call's origin.
@@ -82,6 +531,12 @@ pub struct UpdateAuxiliary<'a> {
     pub authority: &'a AccountView,
     pub envelope: &'a AccountView,
     pub pda: &'a AccountView,
+    /// The envelope's write hook program, required when it has one; `None` otherwise.
+    pub write_hook: Option<&'a AccountView>,
+    /// The instructions sysvar, required when the envelope has the mutation guard; `None`
+    /// otherwise.
+    pub instructions_sysvar: Option<&'a AccountView>,
+    pub config: &'a AccountView,
     pub program: &'a AccountView,
     pub metadata: u64,
     pub sequence: u64,
@@ -105,17 +560,15 @@ impl UpdateAuxiliary<'_> {
         buf[12..20].copy_from_slice(&self.sequence.to_le_bytes());
         buf[20..20 + data_len].copy_from_slice(self.data);
 
-        let cpi_accounts = [
-            InstructionAccount::readonly_signer(self.authority.address()),
-            InstructionAccount::writable(self.envelope.address()),
-            InstructionAccount::readonly_signer(self.pda.address()),
-        ];
-        let ix = InstructionView {
-            program_id: self.program.address(),
-            accounts: &cpi_accounts,
-            data: &buf[..total],
-        };
-        invoke_signed(&ix, &[self.authority, self.envelope, self.pda], signers)
+        invoke_authority(
+            self.program,
+            [self.authority, self.envelope, self.pda],
+            self.write_hook,
+            self.instructions_sysvar,
+            self.config,
+            &buf[..total],
+            signers,
+        )
     }
 }
 
@@ -123,7 +576,9 @@ impl UpdateAuxiliary<'_> {
 ///
 /// Wire format: `[disc:4][metadata:8][sequence:8][data:N]`
 ///
-/// Account order: `[delegation_auth (readonly signer), envelope (writable), padding (readonly)]`
+/// Account order: `[delegation_auth (readonly signer), envelope (writable), padding (readonly)]`,
+/// followed by `[range_guard (readonly), instructions_sysvar (readonly)]` if `range_guard` is set,
+/// and then `[config (readonly)]`.
 ///
 /// `delegation_auth` must match `envelope.delegation_authority`.
 /// `padding` is required so the instruction has 3 accounts and routes to the slow path
@@ -132,6 +587,9 @@ pub struct UpdateAuxiliaryDelegated<'a> {
     pub envelope: &'a AccountView,
     pub delegation_auth: &'a AccountView,
     pub padding: &'a AccountView,
+    /// Required when the envelope has range guards; `None` otherwise.
+    pub range_guard: Option<RangeGuardAccounts<'a>>,
+    pub config: &'a AccountView,
     pub program: &'a AccountView,
     pub metadata: u64,
     pub sequence: u64,
@@ -155,19 +613,124 @@ impl UpdateAuxiliaryDelegated<'_> {
         buf[12..20].copy_from_slice(&self.sequence.to_le_bytes());
         buf[20..20 + data_len].copy_from_slice(self.data);
 
+        invoke_delegated(
+            self.program,
+            [self.delegation_auth, self.envelope, self.padding],
+            &self.range_guard,
+            self.config,
+            &buf[..total],
+            signers,
+        )
+    }
+}
+
+/// CPI: SetDelegateSchema (delegated program registers its aux sub-struct).
+///
+/// Serialized via wincode as `SlowPathInstruction::SetDelegateSchema`.
+///
+/// Account order: `[delegation_auth (readonly signer), envelope (readonly),
+/// delegate_schema (writable), payer (writable signer), system_program (readonly),
+/// config (readonly)]`
+///
+/// `delegate_schema` is the `[DELEGATE_SCHEMA_SEED, envelope]` PDA; `payer` funds its rent
+/// on first use.
+pub struct SetDelegateSchema<'a> {
+    pub delegation_auth: &'a AccountView,
+    pub envelope: &'a AccountView,
+    pub delegate_schema: &'a AccountView,
+    pub payer: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub config: &'a AccountView,
+    pub program: &'a AccountView,
+    pub bump: u8,
+    pub offset: u8,
+    pub metadata: u64,
+}
+
+impl SetDelegateSchema<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let ix_data = SlowPathInstruction::SetDelegateSchema {
+            bump: self.bump,
+            offset: self.offset,
+            metadata: self.metadata,
+        };
+        let buf = wincode::serialize(&ix_data).map_err(|_| ProgramError::InvalidInstructionData)?;
+
         let cpi_accounts = [
             InstructionAccount::readonly_signer(self.delegation_auth.address()),
-            InstructionAccount::writable(self.envelope.address()),
-            InstructionAccount::readonly(self.padding.address()),
+            InstructionAccount::readonly(self.envelope.address()),
+            InstructionAccount::writable(self.delegate_schema.address()),
+            InstructionAccount::writable_signer(self.payer.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+            InstructionAccount::readonly(self.config.address()),
         ];
         let ix = InstructionView {
             program_id: self.program.address(),
             accounts: &cpi_accounts,
-            data: &buf[..total],
+            data: &buf,
         };
         invoke_signed(
             &ix,
-            &[self.delegation_auth, self.envelope, self.padding],
+            &[
+                self.delegation_auth,
+                self.envelope,
+                self.delegate_schema,
+                self.payer,
+                self.system_program,
+                self.config,
+            ],
+            signers,
+        )
+    }
+}
+
+/// CPI: UpdateAuxiliaryDelegatedSchema (delegated program writes its registered sub-struct).
+///
+/// Wire format: `[disc:4][schema_metadata:8][sequence:8][data:N]`
+///
+/// Account order: `[delegation_auth (readonly signer), envelope (writable),
+/// delegate_schema (readonly)]`, followed by `[range_guard (readonly), instructions_sysvar
+/// (readonly)]` if `range_guard` is set, and then `[config (readonly)]`.
+pub struct UpdateAuxiliaryDelegatedSchema<'a> {
+    pub envelope: &'a AccountView,
+    pub delegation_auth: &'a AccountView,
+    pub delegate_schema: &'a AccountView,
+    /// Required when the envelope has range guards; `None` otherwise.
+    pub range_guard: Option<RangeGuardAccounts<'a>>,
+    pub config: &'a AccountView,
+    pub program: &'a AccountView,
+    pub schema_metadata: u64,
+    pub sequence: u64,
+    pub data: &'a [u8],
+}
+
+impl UpdateAuxiliaryDelegatedSchema<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let data_len = self.data.len();
+        let total = 20 + data_len;
+        if total > UPDATE_AUX_MAX_SIZE {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut buf = [0u8; UPDATE_AUX_MAX_SIZE];
+        buf[..4].copy_from_slice(&UPDATE_AUX_DELEGATED_SCHEMA_TAG.to_le_bytes());
+        buf[4..12].copy_from_slice(&self.schema_metadata.to_le_bytes());
+        buf[12..20].copy_from_slice(&self.sequence.to_le_bytes());
+        buf[20..20 + data_len].copy_from_slice(self.data);
+
+        invoke_delegated(
+            self.program,
+            [self.delegation_auth, self.envelope, self.delegate_schema],
+            &self.range_guard,
+            self.config,
+            &buf[..total],
             signers,
         )
     }
@@ -177,11 +740,13 @@ impl UpdateAuxiliaryDelegated<'_> {
 ///
 /// Wire format: `[disc:4][metadata:8][auth_seq:8][prog_seq:8][data:N]`
 ///
-/// Account order: `[authority (readonly signer), envelope (writable), delegation_auth (readonly signer)]`
+/// Account order: `[authority (readonly signer), envelope (writable), delegation_auth (readonly signer),
+/// config (readonly)]`
 pub struct UpdateAuxiliaryForce<'a> {
     pub authority: &'a AccountView,
     pub envelope: &'a AccountView,
     pub delegation_auth: &'a AccountView,
+    pub config: &'a AccountView,
     pub program: &'a AccountView,
     pub metadata: u64,
     pub authority_sequence: u64,
@@ -211,6 +776,7 @@ impl UpdateAuxiliaryForce<'_> {
             InstructionAccount::readonly_signer(self.authority.address()),
             InstructionAccount::writable(self.envelope.address()),
             InstructionAccount::readonly_signer(self.delegation_auth.address()),
+            InstructionAccount::readonly(self.config.address()),
         ];
         let ix = InstructionView {
             program_id: self.program.address(),
@@ -219,7 +785,12 @@ impl UpdateAuxiliaryForce<'_> {
         };
         invoke_signed(
             &ix,
-            &[self.authority, self.envelope, self.delegation_auth],
+            &[
+                self.authority,
+                self.envelope,
+                self.delegation_auth,
+                self.config,
+            ],
             signers,
         )
     }
@@ -229,11 +800,20 @@ impl UpdateAuxiliaryForce<'_> {
 ///
 /// Wire format: `[disc:4][metadata:8][sequence:8][offset:1][data:N]`
 ///
-/// Account order: `[authority (readonly signer), envelope (writable), pda (readonly signer)]`
+/// Account order: `[authority (readonly signer), envelope (writable), pda (readonly signer)]`,
+/// followed by `[write_hook (readonly)]` if `write_hook` is set,
+/// `[instructions_sysvar (readonly)]` if `instructions_sysvar` is set, and then
+/// `[config (readonly)]`.
 pub struct UpdateAuxiliaryRange<'a> {
     pub authority: &'a AccountView,
     pub envelope: &'a AccountView,
     pub pda: &'a AccountView,
+    /// The envelope's write hook program, required when it has one; `None` otherwise.
+    pub write_hook: Option<&'a AccountView>,
+    /// The instructions sysvar, required when the envelope has the mutation guard; `None`
+    /// otherwise.
+    pub instructions_sysvar: Option<&'a AccountView>,
+    pub config: &'a AccountView,
     pub program: &'a AccountView,
     pub metadata: u64,
     pub sequence: u64,
@@ -259,17 +839,15 @@ impl UpdateAuxiliaryRange<'_> {
         buf[20] = self.offset;
         buf[21..21 + data_len].copy_from_slice(self.data);
 
-        let cpi_accounts = [
-            InstructionAccount::readonly_signer(self.authority.address()),
-            InstructionAccount::writable(self.envelope.address()),
-            InstructionAccount::readonly_signer(self.pda.address()),
-        ];
-        let ix = InstructionView {
-            program_id: self.program.address(),
-            accounts: &cpi_accounts,
-            data: &buf[..total],
-        };
-        invoke_signed(&ix, &[self.authority, self.envelope, self.pda], signers)
+        invoke_authority(
+            self.program,
+            [self.authority, self.envelope, self.pda],
+            self.write_hook,
+            self.instructions_sysvar,
+            self.config,
+            &buf[..total],
+            signers,
+        )
     }
 }
 
@@ -277,11 +855,16 @@ impl UpdateAuxiliaryRange<'_> {
 ///
 /// Wire format: `[disc:4][metadata:8][sequence:8][offset:1][data:N]`
 ///
-/// Account order: `[delegation_auth (readonly signer), envelope (writable), padding (readonly)]`
+/// Account order: `[delegation_auth (readonly signer), envelope (writable), padding (readonly)]`,
+/// followed by `[range_guard (readonly), instructions_sysvar (readonly)]` if `range_guard` is set,
+/// and then `[config (readonly)]`.
 pub struct UpdateAuxiliaryDelegatedRange<'a> {
     pub envelope: &'a AccountView,
     pub delegation_auth: &'a AccountView,
     pub padding: &'a AccountView,
+    /// Required when the envelope has range guards; `None` otherwise.
+    pub range_guard: Option<RangeGuardAccounts<'a>>,
+    pub config: &'a AccountView,
     pub program: &'a AccountView,
     pub metadata: u64,
     pub sequence: u64,
@@ -307,19 +890,12 @@ impl UpdateAuxiliaryDelegatedRange<'_> {
         buf[20] = self.offset;
         buf[21..21 + data_len].copy_from_slice(self.data);
 
-        let cpi_accounts = [
-            InstructionAccount::readonly_signer(self.delegation_auth.address()),
-            InstructionAccount::writable(self.envelope.address()),
-            InstructionAccount::readonly(self.padding.address()),
-        ];
-        let ix = InstructionView {
-            program_id: self.program.address(),
-            accounts: &cpi_accounts,
-            data: &buf[..total],
-        };
-        invoke_signed(
-            &ix,
-            &[self.delegation_auth, self.envelope, self.padding],
+        invoke_delegated(
+            self.program,
+            [self.delegation_auth, self.envelope, self.padding],
+            &self.range_guard,
+            self.config,
+            &buf[..total],
             signers,
         )
     }
@@ -327,13 +903,24 @@ impl UpdateAuxiliaryDelegatedRange<'_> {
 
 /// CPI: UpdateAuxiliaryMultiRange (authority writes multiple byte ranges of aux data).
 ///
-/// Serialized via wincode as `SlowPathInstruction::UpdateAuxiliaryMultiRange`.
+/// Serialized via wincode as `SlowPathInstruction::UpdateAuxiliaryMultiRange`. At most
+/// `MAX_WRITE_RANGES` ranges; more fail with `Custom(TOO_MANY_RANGES_ERROR)` without
+/// invoking.
 ///
-/// Account order: `[authority (readonly signer), envelope (writable), pda (readonly signer)]`
+/// Account order: `[authority (readonly signer), envelope (writable), pda (readonly signer)]`,
+/// followed by `[write_hook (readonly)]` if `write_hook` is set,
+/// `[instructions_sysvar (readonly)]` if `instructions_sysvar` is set, and then
+/// `[config (readonly)]`.
 pub struct UpdateAuxiliaryMultiRange<'a> {
     pub authority: &'a AccountView,
     pub envelope: &'a AccountView,
     pub pda: &'a AccountView,
+    /// The envelope's write hook program, required when it has one; `None` otherwise.
+    pub write_hook: Option<&'a AccountView>,
+    /// The instructions sysvar, required when the envelope has the mutation guard; `None`
+    /// otherwise.
+    pub instructions_sysvar: Option<&'a AccountView>,
+    pub config: &'a AccountView,
     pub program: &'a AccountView,
     pub metadata: u64,
     pub sequence: u64,
@@ -346,6 +933,9 @@ impl UpdateAuxiliaryMultiRange<'_> {
     }
 
     pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        if self.ranges.len() > MAX_WRITE_RANGES {
+            return Err(ProgramError::Custom(TOO_MANY_RANGES_ERROR));
+        }
         let ix_data = SlowPathInstruction::UpdateAuxiliaryMultiRange {
             metadata: self.metadata,
             sequence: self.sequence,
@@ -353,17 +943,15 @@ impl UpdateAuxiliaryMultiRange<'_> {
         };
         let buf = wincode::serialize(&ix_data).map_err(|_| ProgramError::InvalidInstructionData)?;
 
-        let cpi_accounts = [
-            InstructionAccount::readonly_signer(self.authority.address()),
-            InstructionAccount::writable(self.envelope.address()),
-            InstructionAccount::readonly_signer(self.pda.address()),
-        ];
-        let ix = InstructionView {
-            program_id: self.program.address(),
-            accounts: &cpi_accounts,
-            data: &buf,
-        };
-        invoke_signed(&ix, &[self.authority, self.envelope, self.pda], signers)
+        invoke_authority(
+            self.program,
+            [self.authority, self.envelope, self.pda],
+            self.write_hook,
+            self.instructions_sysvar,
+            self.config,
+            &buf,
+            signers,
+        )
     }
 }
 
@@ -371,11 +959,16 @@ impl UpdateAuxiliaryMultiRange<'_> {
 ///
 /// Serialized via wincode as `SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange`.
 ///
-/// Account order: `[delegation_auth (readonly signer), envelope (writable), padding (readonly)]`
+/// Account order: `[delegation_auth (readonly signer), envelope (writable), padding (readonly)]`,
+/// followed by `[range_guard (readonly), instructions_sysvar (readonly)]` if `range_guard` is set,
+/// and then `[config (readonly)]`.
 pub struct UpdateAuxiliaryDelegatedMultiRange<'a> {
     pub envelope: &'a AccountView,
     pub delegation_auth: &'a AccountView,
     pub padding: &'a AccountView,
+    /// Required when the envelope has range guards; `None` otherwise.
+    pub range_guard: Option<RangeGuardAccounts<'a>>,
+    pub config: &'a AccountView,
     pub program: &'a AccountView,
     pub metadata: u64,
     pub sequence: u64,
@@ -388,6 +981,9 @@ impl UpdateAuxiliaryDelegatedMultiRange<'_> {
     }
 
     pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        if self.ranges.len() > MAX_WRITE_RANGES {
+            return Err(ProgramError::Custom(TOO_MANY_RANGES_ERROR));
+        }
         let ix_data = SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange {
             metadata: self.metadata,
             sequence: self.sequence,
@@ -395,10 +991,60 @@ impl UpdateAuxiliaryDelegatedMultiRange<'_> {
         };
         let buf = wincode::serialize(&ix_data).map_err(|_| ProgramError::InvalidInstructionData)?;
 
+        invoke_delegated(
+            self.program,
+            [self.delegation_auth, self.envelope, self.padding],
+            &self.range_guard,
+            self.config,
+            &buf,
+            signers,
+        )
+    }
+}
+
+/// CPI: UpdateAuxiliaryMultiRangeForce (authority overrides both sequence counters while
+/// writing multiple byte ranges).
+///
+/// Serialized via wincode as `SlowPathInstruction::UpdateAuxiliaryMultiRangeForce`. At most
+/// `MAX_WRITE_RANGES` ranges; more fail with `Custom(TOO_MANY_RANGES_ERROR)` without
+/// invoking.
+///
+/// Account order: `[authority (readonly signer), envelope (writable), delegation_auth (readonly signer),
+/// config (readonly)]`
+pub struct UpdateAuxiliaryMultiRangeForce<'a> {
+    pub authority: &'a AccountView,
+    pub envelope: &'a AccountView,
+    pub delegation_auth: &'a AccountView,
+    pub config: &'a AccountView,
+    pub program: &'a AccountView,
+    pub metadata: u64,
+    pub authority_sequence: u64,
+    pub program_sequence: u64,
+    pub ranges: &'a [WriteSpec],
+}
+
+impl UpdateAuxiliaryMultiRangeForce<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        if self.ranges.len() > MAX_WRITE_RANGES {
+            return Err(ProgramError::Custom(TOO_MANY_RANGES_ERROR));
+        }
+        let ix_data = SlowPathInstruction::UpdateAuxiliaryMultiRangeForce {
+            metadata: self.metadata,
+            authority_sequence: self.authority_sequence,
+            program_sequence: self.program_sequence,
+            ranges: self.ranges.to_vec(),
+        };
+        let buf = wincode::serialize(&ix_data).map_err(|_| ProgramError::InvalidInstructionData)?;
+
         let cpi_accounts = [
-            InstructionAccount::readonly_signer(self.delegation_auth.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
             InstructionAccount::writable(self.envelope.address()),
-            InstructionAccount::readonly(self.padding.address()),
+            InstructionAccount::readonly_signer(self.delegation_auth.address()),
+            InstructionAccount::readonly(self.config.address()),
         ];
         let ix = InstructionView {
             program_id: self.program.address(),
@@ -407,8 +1053,201 @@ impl UpdateAuxiliaryDelegatedMultiRange<'_> {
         };
         invoke_signed(
             &ix,
-            &[self.delegation_auth, self.envelope, self.padding],
+            &[
+                self.authority,
+                self.envelope,
+                self.delegation_auth,
+                self.config,
+            ],
             signers,
         )
     }
 }
+
+/// CPI: ReadAuxRange (fetch `auxiliary_data[offset..offset + len]` via return data).
+///
+/// Serialized via wincode as `SlowPathInstruction::ReadAuxRange`.
+///
+/// Account order: `[envelope (readonly)]`
+///
+/// After `invoke`, read the bytes with pinocchio's `get_return_data` and check that its
+/// program id is `program`.
+pub struct ReadAuxRange<'a> {
+    pub envelope: &'a AccountView,
+    pub program: &'a AccountView,
+    pub offset: u16,
+    pub len: u16,
+}
+
+impl ReadAuxRange<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let ix_data = SlowPathInstruction::ReadAuxRange {
+            offset: self.offset,
+            len: self.len,
+        };
+        let buf = wincode::serialize(&ix_data).map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let cpi_accounts = [InstructionAccount::readonly(self.envelope.address())];
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &buf,
+        };
+        invoke_signed(&ix, &[self.envelope], &[])
+    }
+}
+
+/// CPI: AssertAuxHash (fail unless the auxiliary data hashes to `expected`).
+///
+/// Serialized via wincode as `SlowPathInstruction::AssertAuxHash`.
+///
+/// Account order: `[envelope (readonly)]`
+///
+/// `expected` is the SHA-256 of all 256 aux bytes (`c_u_soon_client::aux_hash` off-chain).
+/// A mismatch fails with `InvalidArgument`, which aborts the calling instruction.
+pub struct AssertAuxHash<'a> {
+    pub envelope: &'a AccountView,
+    pub program: &'a AccountView,
+    pub expected: [u8; 32],
+}
+
+impl AssertAuxHash<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let ix_data = SlowPathInstruction::AssertAuxHash {
+            expected: self.expected,
+        };
+        let buf = wincode::serialize(&ix_data).map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let cpi_accounts = [InstructionAccount::readonly(self.envelope.address())];
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &buf,
+        };
+        invoke_signed(&ix, &[self.envelope], &[])
+    }
+}
+
+/// CPI: ReadOracle (fetch the oracle metadata, sequence, and payload via return data).
+///
+/// Serialized via wincode as `SlowPathInstruction::ReadOracle`.
+///
+/// Account order: `[envelope (readonly)]`
+///
+/// After `invoke`, read the return data with pinocchio's `get_return_data`, check that its
+/// program id is `program`, and decode it with `c_u_soon_instruction::split_read_oracle`.
+pub struct ReadOracle<'a> {
+    pub envelope: &'a AccountView,
+    pub program: &'a AccountView,
+}
+
+impl ReadOracle<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let buf = wincode::serialize(&SlowPathInstruction::ReadOracle)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let cpi_accounts = [InstructionAccount::readonly(self.envelope.address())];
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &buf,
+        };
+        invoke_signed(&ix, &[self.envelope], &[])
+    }
+}
+
+/// CPI: ReadOracleGated (ReadOracle for envelopes that only serve listed readers).
+///
+/// Serialized via wincode as `SlowPathInstruction::ReadOracleGated`.
+///
+/// Account order: `[envelope (readonly), reader_list (readonly), instructions_sysvar (readonly)]`
+///
+/// Succeeds only when the transaction's current top-level instruction targets a program in
+/// the envelope's `ReaderList`, so invoke it from that program's own instruction. Decode the
+/// return data as for [`ReadOracle`].
+pub struct ReadOracleGated<'a> {
+    pub envelope: &'a AccountView,
+    pub reader_list: &'a AccountView,
+    pub instructions_sysvar: &'a AccountView,
+    pub program: &'a AccountView,
+}
+
+impl ReadOracleGated<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let buf = wincode::serialize(&SlowPathInstruction::ReadOracleGated)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let cpi_accounts = [
+            InstructionAccount::readonly(self.envelope.address()),
+            InstructionAccount::readonly(self.reader_list.address()),
+            InstructionAccount::readonly(self.instructions_sysvar.address()),
+        ];
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &buf,
+        };
+        invoke_signed(
+            &ix,
+            &[self.envelope, self.reader_list, self.instructions_sysvar],
+            &[],
+        )
+    }
+}
+
+/// CPI: ReadAux (fetch the auxiliary metadata and all aux bytes via return data).
+///
+/// Serialized via wincode as `SlowPathInstruction::ReadAux`.
+///
+/// Account order: `[envelope (readonly)]`
+///
+/// Decode the return data with `c_u_soon_instruction::split_read_aux` after checking its
+/// program id is `program`.
+pub struct ReadAux<'a> {
+    pub envelope: &'a AccountView,
+    pub program: &'a AccountView,
+}
+
+impl ReadAux<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let buf = wincode::serialize(&SlowPathInstruction::ReadAux)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let cpi_accounts = [InstructionAccount::readonly(self.envelope.address())];
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &buf,
+        };
+        invoke_signed(&ix, &[self.envelope], &[])
+    }
+}
+
+/// CPI: ExportState (fetch the slot and a SHA-256 of the whole envelope via return data).
+///
+/// Serialized via wincode as `SlowPathInstruction::ExportState`.
+///
+/// Account order: `[envelope (readonly)]`
+///
+/// Decode the return data with `c_u_soon_instruction::split_export_state` after checking its
+/// program id is `program`. A program can store the hash to commit to the envelope state it
+/// acted on.
+pub struct ExportState<'a> {
+    pub envelope: &'a AccountView,
+    pub program: &'a AccountView,
+}
+
+impl ExportState<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let buf = wincode::serialize(&SlowPathInstruction::ExportState)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let cpi_accounts = [InstructionAccount::readonly(self.envelope.address())];
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &buf,
+        };
+        invoke_signed(&ix, &[self.envelope], &[])
+    }
+}
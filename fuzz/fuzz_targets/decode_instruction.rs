@@ -0,0 +1,131 @@
+//! `decode_instruction` over arbitrary instruction data, which covers the manual wire
+//! parsers of the UpdateAuxiliary variants, BatchUpdate, PublisherUpdate, and SplitUpdate.
+//!
+//! Input: `[account_count:1][data:N]`. Decoding must not panic, and every manual-wire
+//! instruction it accepts must re-encode with the client builders to the bytes it read.
+
+#![no_main]
+
+use c_u_soon_client::{
+    decode_instruction, update_auxiliary_delegated_instruction_data,
+    update_auxiliary_delegated_range_instruction_data,
+    update_auxiliary_delegated_schema_instruction_data, update_auxiliary_force_instruction_data,
+    update_auxiliary_instruction_data, update_auxiliary_range_instruction_data, DecodedParams,
+};
+use c_u_soon_instruction::{
+    split_versioned, BATCH_UPDATE_TAG, PUBLISHER_UPDATE_TAG, SPLIT_UPDATE_TAG,
+};
+use libfuzzer_sys::fuzz_target;
+use solana_address::Address;
+
+fuzz_target!(|input: &[u8]| {
+    let Some((&account_count, data)) = input.split_first() else {
+        return;
+    };
+    let accounts: Vec<Address> = (0..account_count)
+        .map(|i| Address::new_from_array([i; 32]))
+        .collect();
+    let program = Address::new_from_array([0xFF; 32]);
+
+    let Ok(decoded) = decode_instruction(&program, &accounts, data) else {
+        return;
+    };
+    assert_eq!(decoded.accounts.len(), accounts.len());
+    let _ = decoded.to_string();
+
+    let data = match split_versioned(data) {
+        Some((_, inner)) if accounts.len() != 2 => inner,
+        _ => data,
+    };
+    let encoded = match decoded.params {
+        DecodedParams::FastPath {
+            oracle_meta,
+            sequence,
+            payload,
+        } => [
+            &oracle_meta.to_le_bytes()[..],
+            &sequence.to_le_bytes(),
+            &payload,
+        ]
+        .concat(),
+        DecodedParams::UpdateAuxiliary {
+            metadata,
+            sequence,
+            data,
+        } => update_auxiliary_instruction_data(metadata, sequence, &data),
+        DecodedParams::UpdateAuxiliaryDelegated {
+            metadata,
+            sequence,
+            data,
+        } => update_auxiliary_delegated_instruction_data(metadata, sequence, &data),
+        DecodedParams::UpdateAuxiliaryDelegatedSchema {
+            schema_metadata,
+            sequence,
+            data,
+        } => update_auxiliary_delegated_schema_instruction_data(schema_metadata, sequence, &data),
+        DecodedParams::UpdateAuxiliaryForce {
+            metadata,
+            authority_sequence,
+            program_sequence,
+            data,
+        } => update_auxiliary_force_instruction_data(
+            metadata,
+            authority_sequence,
+            program_sequence,
+            &data,
+        ),
+        DecodedParams::UpdateAuxiliaryRange {
+            metadata,
+            sequence,
+            offset,
+            data,
+        } => update_auxiliary_range_instruction_data(metadata, sequence, offset, &data),
+        DecodedParams::UpdateAuxiliaryDelegatedRange {
+            metadata,
+            sequence,
+            offset,
+            data,
+        } => update_auxiliary_delegated_range_instruction_data(metadata, sequence, offset, &data),
+        DecodedParams::BatchUpdate { entries } => {
+            let mut encoded = BATCH_UPDATE_TAG.to_le_bytes().to_vec();
+            encoded.push(entries.len() as u8);
+            for entry in &entries {
+                encoded.extend_from_slice(&entry.oracle_meta.to_le_bytes());
+                encoded.extend_from_slice(&entry.sequence.to_le_bytes());
+                encoded.push(entry.payload.len() as u8);
+                encoded.extend_from_slice(&entry.payload);
+            }
+            // Trailing bytes after the last entry are reported as a warning, not decoded.
+            assert!(data.starts_with(&encoded));
+            return;
+        }
+        DecodedParams::PublisherUpdate {
+            oracle_meta,
+            sequence,
+            payload,
+        } => [
+            &PUBLISHER_UPDATE_TAG.to_le_bytes()[..],
+            &oracle_meta.to_le_bytes(),
+            &sequence.to_le_bytes(),
+            &payload,
+        ]
+        .concat(),
+        DecodedParams::SplitUpdate {
+            type_hash,
+            sequence,
+            part,
+            payload,
+        } => [
+            &SPLIT_UPDATE_TAG.to_le_bytes()[..],
+            &type_hash.to_le_bytes(),
+            &sequence.to_le_bytes(),
+            &[part],
+            &payload,
+        ]
+        .concat(),
+        DecodedParams::Slow(ix) => {
+            wincode::serialize(ix.as_ref()).expect("decoded instruction serializes")
+        }
+    };
+    assert_eq!(encoded, data);
+});
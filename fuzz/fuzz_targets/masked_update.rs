@@ -0,0 +1,47 @@
+//! `Mask::apply_masked_update` against a byte-by-byte reference.
+//!
+//! Input: `[offset:1][mask bits:32][dest:256][src:N]`. Each mask bit is one aux byte
+//! (1 = blocked), so the mask is always canonical, as the program requires. The
+//! u64-chunked check must accept exactly the updates the reference accepts, write `src`
+//! when it does, and leave `dest` untouched when it does not.
+
+#![no_main]
+
+use c_u_soon::{Mask, Permission, AUX_DATA_SIZE};
+use libfuzzer_sys::fuzz_target;
+
+const HEADER: usize = 1 + AUX_DATA_SIZE / 8 + AUX_DATA_SIZE;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < HEADER {
+        return;
+    }
+    let offset = data[0] as usize;
+    let bits = &data[1..1 + AUX_DATA_SIZE / 8];
+    let mut dest: [u8; AUX_DATA_SIZE] = data[1 + AUX_DATA_SIZE / 8..HEADER].try_into().unwrap();
+    let src = &data[HEADER..];
+
+    let mut mask = Mask::ALL_WRITABLE;
+    for i in 0..AUX_DATA_SIZE {
+        if bits[i / 8] & (1 << (i % 8)) != 0 {
+            mask.set(i, Permission::Blocked);
+        }
+    }
+
+    let expected = offset + src.len() <= AUX_DATA_SIZE
+        && src
+            .iter()
+            .enumerate()
+            .all(|(i, &b)| b == dest[offset + i] || mask.is_writable(offset + i));
+
+    let before = dest;
+    assert_eq!(mask.check_masked_update(&dest, offset, src), expected);
+    assert_eq!(mask.apply_masked_update(&mut dest, offset, src), expected);
+    if expected {
+        assert_eq!(&dest[offset..offset + src.len()], src);
+        assert_eq!(dest[..offset], before[..offset]);
+        assert_eq!(dest[offset + src.len()..], before[offset + src.len()..]);
+    } else {
+        assert_eq!(dest, before);
+    }
+});
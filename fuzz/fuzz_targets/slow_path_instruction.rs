@@ -0,0 +1,22 @@
+//! Wincode deserialization of `SlowPathInstruction`, as the program's slow path runs it.
+//!
+//! Anything that deserializes must serialize back to exactly the bytes it consumed, and
+//! `validate` must not panic on it.
+
+#![no_main]
+
+use c_u_soon_instruction::SlowPathInstruction;
+use libfuzzer_sys::fuzz_target;
+use wincode::SchemaRead;
+
+fuzz_target!(|data: &[u8]| {
+    let mut cursor = data;
+    let Ok(ix) = <SlowPathInstruction as SchemaRead>::get(&mut cursor) else {
+        return;
+    };
+    let consumed = &data[..data.len() - cursor.len()];
+    let _ = ix.validate();
+
+    let bytes = wincode::serialize(&ix).expect("deserialized instruction serializes");
+    assert_eq!(bytes, consumed, "round trip changed {ix:?}");
+});
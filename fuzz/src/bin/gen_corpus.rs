@@ -0,0 +1,184 @@
+//! Write seed inputs for every fuzz target to `corpus/<target>/`.
+//!
+//! Seeds mirror the instructions the unit tests build, so the fuzzers start from inputs
+//! that decode and mutate outward from there. Run from `fuzz/`:
+//! `cargo run --bin gen_corpus`.
+
+use std::fs;
+use std::path::Path;
+
+use c_u_soon::{Mask, TypeHash, AUX_DATA_SIZE};
+use c_u_soon_client::{
+    batch_update_instruction_data, clear_delegation_instruction_data, close_instruction_data,
+    close_with_split_instruction_data, create_instruction_data, fast_path_instruction_data,
+    publisher_update_instruction_data, read_aux_range_instruction_data,
+    set_delegated_program_instruction_data, set_label_instruction_data, set_twap_instruction_data,
+    split_update_instruction_data, update_auxiliary_delegated_instruction_data,
+    update_auxiliary_delegated_range_instruction_data,
+    update_auxiliary_delegated_schema_instruction_data, update_auxiliary_force_instruction_data,
+    update_auxiliary_instruction_data, update_auxiliary_multi_range_instruction_data,
+    update_auxiliary_range_instruction_data, versioned_instruction_data,
+};
+use c_u_soon_instruction::{BatchEntry, WriteSpec};
+
+const META: u64 = <u64 as TypeHash>::METADATA.as_u64();
+
+fn slow_path_seeds() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        (
+            "create",
+            create_instruction_data(&[b"test"], 254, u64::METADATA).unwrap(),
+        ),
+        ("close", close_instruction_data().unwrap()),
+        (
+            "close_with_split",
+            close_with_split_instruction_data(250).unwrap(),
+        ),
+        (
+            "set_delegated_program",
+            set_delegated_program_instruction_data(Mask::ALL_WRITABLE, Mask::ALL_BLOCKED).unwrap(),
+        ),
+        (
+            "clear_delegation",
+            clear_delegation_instruction_data().unwrap(),
+        ),
+        (
+            "multi_range",
+            update_auxiliary_multi_range_instruction_data(
+                META,
+                1,
+                &[
+                    WriteSpec {
+                        offset: 0,
+                        data: vec![1, 2, 3],
+                    },
+                    WriteSpec {
+                        offset: 200,
+                        data: vec![0xAA; 8],
+                    },
+                ],
+            )
+            .unwrap(),
+        ),
+        (
+            "read_aux_range",
+            read_aux_range_instruction_data(16, 8).unwrap(),
+        ),
+        (
+            "set_label",
+            set_label_instruction_data(1, "SOL/USD").unwrap(),
+        ),
+        ("set_twap", set_twap_instruction_data(true).unwrap()),
+    ]
+}
+
+fn manual_wire_seeds() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        (
+            "update_aux",
+            update_auxiliary_instruction_data(META, 1, &[7; 8]),
+        ),
+        (
+            "update_aux_delegated",
+            update_auxiliary_delegated_instruction_data(META, 2, &[7; 8]),
+        ),
+        (
+            "update_aux_delegated_schema",
+            update_auxiliary_delegated_schema_instruction_data(META, 3, &[7; 8]),
+        ),
+        (
+            "update_aux_force",
+            update_auxiliary_force_instruction_data(META, 4, 5, &[7; 8]),
+        ),
+        (
+            "update_aux_range",
+            update_auxiliary_range_instruction_data(META, 6, 32, &[7; 4]),
+        ),
+        (
+            "update_aux_delegated_range",
+            update_auxiliary_delegated_range_instruction_data(META, 7, 64, &[7; 4]),
+        ),
+        (
+            "batch_update",
+            batch_update_instruction_data(&[
+                BatchEntry {
+                    oracle_meta: META,
+                    sequence: 1,
+                    payload: &[1, 2, 3],
+                },
+                BatchEntry {
+                    oracle_meta: META,
+                    sequence: 9,
+                    payload: &[4; 8],
+                },
+            ])
+            .unwrap(),
+        ),
+        (
+            "publisher_update",
+            publisher_update_instruction_data(META, 8, &[5; 8]).unwrap(),
+        ),
+        (
+            "split_update",
+            split_update_instruction_data(9, 8, &[5; 16]).unwrap()[1].clone(),
+        ),
+    ]
+}
+
+fn masked_update_seeds() -> Vec<(&'static str, Vec<u8>)> {
+    // `[offset:1][mask bits:32][dest:256][src:N]`; bit set = blocked.
+    let seed = |offset: u8, blocked: &[usize], src: &[u8]| {
+        let mut bits = [0u8; AUX_DATA_SIZE / 8];
+        for &i in blocked {
+            bits[i / 8] |= 1 << (i % 8);
+        }
+        [&[offset][..], &bits, &[0; AUX_DATA_SIZE], src].concat()
+    };
+    vec![
+        ("all_writable", seed(0, &[], &[1; 32])),
+        ("blocked_head", seed(3, &[4], &[0, 0, 0, 0, 0])),
+        ("blocked_violated", seed(3, &[4], &[0, 1, 0])),
+        ("unaligned_body", seed(5, &[40, 41], &[9; 30])),
+        ("past_end", seed(250, &[], &[1; 8])),
+    ]
+}
+
+fn write(target: &str, seeds: &[(&str, Vec<u8>)]) {
+    let dir = Path::new("corpus").join(target);
+    fs::create_dir_all(&dir).unwrap();
+    for (name, data) in seeds {
+        fs::write(dir.join(name), data).unwrap();
+    }
+}
+
+fn main() {
+    let slow = slow_path_seeds();
+    let manual = manual_wire_seeds();
+    write("slow_path_instruction", &slow);
+
+    // Three accounts routes everything but the fast-path seed to the slow path.
+    let mut decode: Vec<(&str, Vec<u8>)> = slow
+        .iter()
+        .chain(&manual)
+        .map(|(name, data)| (*name, [&[3u8][..], data].concat()))
+        .collect();
+    decode.push((
+        "fast_path",
+        [
+            &[2u8][..],
+            &fast_path_instruction_data(META, 1, &[1, 2, 3]).unwrap(),
+        ]
+        .concat(),
+    ));
+    decode.push((
+        "versioned_close",
+        [
+            &[3u8][..],
+            &versioned_instruction_data(&close_instruction_data().unwrap()),
+        ]
+        .concat(),
+    ));
+    write("decode_instruction", &decode);
+
+    write("masked_update", &masked_update_seeds());
+}
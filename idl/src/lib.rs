@@ -0,0 +1,1500 @@
+//! Anchor-style IDL for the c_u_soon program.
+//!
+//! [`idl`] describes the fast path, every slow-path instruction, and the program's account
+//! layouts in the Anchor IDL JSON format (spec `0.1.0`, as produced by Anchor 0.30), so
+//! Anchor-based frontends, wallets, and explorers can label c_u_soon transactions. The
+//! `c_u_soon_idl` binary prints it: `cargo run -p c_u_soon_idl -- <PROGRAM_ID>`.
+//!
+//! c_u_soon is not an Anchor program, and a few parts of its wire format have no exact IDL
+//! equivalent. Each is called out in the instruction's `docs`:
+//!
+//! - Discriminators are the 4-byte little-endian slow-path tag, not an 8-byte sighash. The
+//!   fast path has no discriminator at all; it is recognised by having exactly two
+//!   accounts.
+//! - Wincode prefixes `vec` and `bytes` arguments with a `u64` length, where Borsh uses a
+//!   `u32`.
+//! - Trailing payloads of the manual-wire instructions (fast path, `UpdateAuxiliary*`,
+//!   `BatchUpdate`, `PublisherUpdate`, `SplitUpdate`) are typed `bytes` but carry no length prefix: they run
+//!   to the end of the instruction data.
+//! - Accounts have no discriminator. They are told apart by owner and data length, listed
+//!   in the `docs` of the matching type.
+//! - Every state-changing instruction other than `Create*`, `InitConfig` and `SetConfig`
+//!   ends with the read-only program `config`, after any optional accounts, so a list with
+//!   optional accounts left out still ends with it.
+
+use c_u_soon::{
+    ConstraintTable, DelegateSchema, Envelope, EnvelopeLabel, GroupAnchor, ProgramConfig,
+    PublisherSet, RangeGuard, ReaderList, AUX_DATA_SIZE, COMPACT_MASK_SIZE, LABEL_SIZE, MASK_SIZE,
+    MAX_CONSTRAINTS, MAX_GROUP_MEMBERS, MAX_PUBLISHERS, MAX_RANGE_GUARDS, MAX_READERS,
+    ORACLE_BYTES,
+};
+use c_u_soon_instruction::{
+    requires_trailing_config, BATCH_UPDATE_TAG, PUBLISHER_UPDATE_TAG, SPLIT_UPDATE_TAG,
+    UPDATE_AUX_DELEGATED_RANGE_TAG, UPDATE_AUX_DELEGATED_SCHEMA_TAG, UPDATE_AUX_DELEGATED_TAG,
+    UPDATE_AUX_FORCE_TAG, UPDATE_AUX_RANGE_TAG, UPDATE_AUX_TAG,
+};
+use serde_json::{json, Value};
+use solana_address::Address;
+
+/// Version of the Anchor IDL specification the output follows.
+pub const IDL_SPEC: &str = "0.1.0";
+
+/// IDL field or argument type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ty {
+    Bool,
+    U8,
+    U16,
+    U64,
+    Pubkey,
+    /// Length-prefixed byte string, or the rest of the data for manual-wire payloads.
+    Bytes,
+    Array(&'static Ty, usize),
+    Vec(&'static Ty),
+    Defined(&'static str),
+}
+
+impl Ty {
+    fn to_json(self) -> Value {
+        match self {
+            Ty::Bool => json!("bool"),
+            Ty::U8 => json!("u8"),
+            Ty::U16 => json!("u16"),
+            Ty::U64 => json!("u64"),
+            Ty::Pubkey => json!("pubkey"),
+            Ty::Bytes => json!("bytes"),
+            Ty::Array(inner, len) => json!({ "array": [inner.to_json(), len] }),
+            Ty::Vec(inner) => json!({ "vec": inner.to_json() }),
+            Ty::Defined(name) => json!({ "defined": { "name": name } }),
+        }
+    }
+}
+
+const BYTES_32: Ty = Ty::Array(&Ty::U8, 32);
+
+#[derive(Debug, Clone, Copy)]
+struct Field {
+    name: &'static str,
+    ty: Ty,
+}
+
+const fn field(name: &'static str, ty: Ty) -> Field {
+    Field { name, ty }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AccountMeta {
+    name: &'static str,
+    writable: bool,
+    signer: bool,
+    optional: bool,
+}
+
+const fn readonly(name: &'static str) -> AccountMeta {
+    AccountMeta {
+        name,
+        writable: false,
+        signer: false,
+        optional: false,
+    }
+}
+
+const fn writable(name: &'static str) -> AccountMeta {
+    AccountMeta {
+        writable: true,
+        ..readonly(name)
+    }
+}
+
+const fn signer(name: &'static str) -> AccountMeta {
+    AccountMeta {
+        signer: true,
+        ..readonly(name)
+    }
+}
+
+const fn payer(name: &'static str) -> AccountMeta {
+    AccountMeta {
+        signer: true,
+        writable: true,
+        ..readonly(name)
+    }
+}
+
+const fn optional(account: AccountMeta) -> AccountMeta {
+    AccountMeta {
+        optional: true,
+        ..account
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Instruction {
+    name: &'static str,
+    /// Slow-path tag; `None` for the fast path.
+    tag: Option<u32>,
+    docs: &'static [&'static str],
+    accounts: &'static [AccountMeta],
+    args: &'static [Field],
+}
+
+const ENVELOPE: AccountMeta = writable("envelope");
+const PADDING: AccountMeta = readonly("padding");
+const SYSTEM_PROGRAM: AccountMeta = readonly("system_program");
+
+const AUTHORITY_ONLY: &[AccountMeta] = &[signer("authority"), ENVELOPE, PADDING];
+const AUTHORITY_PDA: &[AccountMeta] = &[signer("authority"), ENVELOPE, signer("pda")];
+const BOTH_SIGN: &[AccountMeta] = &[
+    signer("authority"),
+    ENVELOPE,
+    signer("delegation_authority"),
+];
+const DELEGATED: &[AccountMeta] = &[
+    signer("delegation_authority"),
+    ENVELOPE,
+    PADDING,
+    optional(readonly("range_guard")),
+    optional(readonly("instructions_sysvar")),
+];
+const READ_ONLY: &[AccountMeta] = &[readonly("envelope")];
+const CREATE_ACCOUNTS: &[AccountMeta] = &[
+    payer("authority"),
+    ENVELOPE,
+    SYSTEM_PROGRAM,
+    writable("config"),
+];
+/// The program config, appended to every instruction that [`requires_trailing_config`].
+const TRAILING_CONFIG: AccountMeta = readonly("config");
+
+const MANUAL_WIRE: &str = "Manual wire format: fields are packed with no padding and `data` \
+                           runs to the end of the instruction data, with no length prefix.";
+const CREATE_CONFIG: &str = "`config` is the `[\"config\", bump]` PDA, initialized or not. Once \
+                             initialized, `authority` pays its `create_fee` into it for each new \
+                             envelope, and creation fails with custom error 8 while it is paused. \
+                             Before InitConfig it holds no data, and creation is free.";
+const GUARD_ACCOUNTS: &str = "`range_guard` is passed when the envelope has FLAG_RANGE_GUARD; \
+                              `instructions_sysvar` when it has FLAG_RANGE_GUARD or \
+                              FLAG_MUTATION_GUARD.";
+const CONSTRAINT_ACCOUNTS: &str = "When the envelope constrains its auxiliary region, the \
+                                   third account is its constraint table.";
+const ORACLE_CONSTRAINT_ACCOUNTS: &str = "The constraint table of each envelope with oracle \
+                                          constraints follows, in envelope order.";
+const WRITE_HOOK_ACCOUNTS: &str = "When the envelope has a write hook, the hook program \
+                                   follows as the fourth account and is called after the write.";
+
+const INSTRUCTIONS: &[Instruction] = &[
+    Instruction {
+        name: "fast_path",
+        tag: None,
+        docs: &[
+            "Oracle update. Recognised by having exactly two accounts; there is no discriminator.",
+            "Instruction data is `[oracle_meta:8][sequence:8][payload]`, where `payload` runs to \
+             the end of the data (at most 239 bytes).",
+            "The signer is the envelope authority or its publisher.",
+        ],
+        accounts: &[signer("authority"), ENVELOPE],
+        args: &[
+            field("oracle_meta", Ty::U64),
+            field("sequence", Ty::U64),
+            field("payload", Ty::Bytes),
+        ],
+    },
+    Instruction {
+        name: "create",
+        tag: Some(0),
+        docs: &[
+            "Create an envelope PDA at `[\"envelope\", authority, ..custom_seeds, bump]`.",
+            CREATE_CONFIG,
+        ],
+        accounts: CREATE_ACCOUNTS,
+        args: &[
+            field("custom_seeds", Ty::Vec(&Ty::Bytes)),
+            field("bump", Ty::U8),
+            field("oracle_metadata", Ty::U64),
+        ],
+    },
+    Instruction {
+        name: "close",
+        tag: Some(1),
+        docs: &["Close an envelope and send its lamports to `recipient`."],
+        accounts: &[signer("authority"), ENVELOPE, writable("recipient")],
+        args: &[],
+    },
+    Instruction {
+        name: "set_delegated_program",
+        tag: Some(2),
+        docs: &["Delegate auxiliary writes. Mask bytes are 0x00 (writable) or 0xFF (blocked)."],
+        accounts: BOTH_SIGN,
+        args: &[
+            field("program_bitmask", Ty::Array(&Ty::U8, MASK_SIZE)),
+            field("user_bitmask", Ty::Array(&Ty::U8, MASK_SIZE)),
+        ],
+    },
+    Instruction {
+        name: "clear_delegation",
+        tag: Some(3),
+        docs: &["Remove the delegation and reset both masks to all blocked."],
+        accounts: BOTH_SIGN,
+        args: &[],
+    },
+    Instruction {
+        name: "update_auxiliary",
+        tag: Some(UPDATE_AUX_TAG),
+        docs: &[
+            "Authority writes the whole auxiliary struct.",
+            MANUAL_WIRE,
+            CONSTRAINT_ACCOUNTS,
+            WRITE_HOOK_ACCOUNTS,
+        ],
+        accounts: AUTHORITY_PDA,
+        args: &[
+            field("metadata", Ty::U64),
+            field("sequence", Ty::U64),
+            field("data", Ty::Bytes),
+        ],
+    },
+    Instruction {
+        name: "update_auxiliary_delegated",
+        tag: Some(UPDATE_AUX_DELEGATED_TAG),
+        docs: &[
+            "Delegated program writes the whole auxiliary struct.",
+            MANUAL_WIRE,
+            GUARD_ACCOUNTS,
+            CONSTRAINT_ACCOUNTS,
+        ],
+        accounts: DELEGATED,
+        args: &[
+            field("metadata", Ty::U64),
+            field("sequence", Ty::U64),
+            field("data", Ty::Bytes),
+        ],
+    },
+    Instruction {
+        name: "update_auxiliary_force",
+        tag: Some(UPDATE_AUX_FORCE_TAG),
+        docs: &[
+            "Both parties write the whole auxiliary struct, ignoring the masks but not sealed \
+             bytes.",
+            MANUAL_WIRE,
+            "The constraint table follows when the envelope constrains its auxiliary region.",
+        ],
+        accounts: BOTH_SIGN,
+        args: &[
+            field("metadata", Ty::U64),
+            field("authority_sequence", Ty::U64),
+            field("program_sequence", Ty::U64),
+            field("data", Ty::Bytes),
+        ],
+    },
+    Instruction {
+        name: "update_auxiliary_range",
+        tag: Some(UPDATE_AUX_RANGE_TAG),
+        docs: &[
+            "Authority writes `data` at `offset`.",
+            MANUAL_WIRE,
+            CONSTRAINT_ACCOUNTS,
+            WRITE_HOOK_ACCOUNTS,
+        ],
+        accounts: AUTHORITY_PDA,
+        args: &[
+            field("metadata", Ty::U64),
+            field("sequence", Ty::U64),
+            field("offset", Ty::U8),
+            field("data", Ty::Bytes),
+        ],
+    },
+    Instruction {
+        name: "update_auxiliary_delegated_range",
+        tag: Some(UPDATE_AUX_DELEGATED_RANGE_TAG),
+        docs: &[
+            "Delegated program writes `data` at `offset`.",
+            MANUAL_WIRE,
+            GUARD_ACCOUNTS,
+            CONSTRAINT_ACCOUNTS,
+        ],
+        accounts: DELEGATED,
+        args: &[
+            field("metadata", Ty::U64),
+            field("sequence", Ty::U64),
+            field("offset", Ty::U8),
+            field("data", Ty::Bytes),
+        ],
+    },
+    Instruction {
+        name: "update_auxiliary_multi_range",
+        tag: Some(9),
+        docs: &[
+            "Authority writes several ranges in one instruction.",
+            CONSTRAINT_ACCOUNTS,
+            WRITE_HOOK_ACCOUNTS,
+        ],
+        accounts: AUTHORITY_PDA,
+        args: &[
+            field("metadata", Ty::U64),
+            field("sequence", Ty::U64),
+            field("ranges", Ty::Vec(&Ty::Defined("WriteSpec"))),
+        ],
+    },
+    Instruction {
+        name: "update_auxiliary_delegated_multi_range",
+        tag: Some(10),
+        docs: &[
+            "Delegated program writes several ranges in one instruction.",
+            GUARD_ACCOUNTS,
+            CONSTRAINT_ACCOUNTS,
+        ],
+        accounts: DELEGATED,
+        args: &[
+            field("metadata", Ty::U64),
+            field("sequence", Ty::U64),
+            field("ranges", Ty::Vec(&Ty::Defined("WriteSpec"))),
+        ],
+    },
+    Instruction {
+        name: "update_auxiliary_multi_range_force",
+        tag: Some(11),
+        docs: &[
+            "Both parties write several ranges, ignoring the masks but not sealed bytes, and set \
+             both sequences.",
+            "The constraint table follows when the envelope constrains its auxiliary region.",
+        ],
+        accounts: BOTH_SIGN,
+        args: &[
+            field("metadata", Ty::U64),
+            field("authority_sequence", Ty::U64),
+            field("program_sequence", Ty::U64),
+            field("ranges", Ty::Vec(&Ty::Defined("WriteSpec"))),
+        ],
+    },
+    Instruction {
+        name: "set_delegated_program_compact",
+        tag: Some(12),
+        docs: &[
+            "As `set_delegated_program`, with each mask packed one bit per aux byte: bit \
+             `i % 8` of byte `i / 8` set means byte `i` is writable.",
+        ],
+        accounts: BOTH_SIGN,
+        args: &[
+            field("program_bitmask", Ty::Array(&Ty::U8, COMPACT_MASK_SIZE)),
+            field("user_bitmask", Ty::Array(&Ty::U8, COMPACT_MASK_SIZE)),
+        ],
+    },
+    Instruction {
+        name: "set_range_guards",
+        tag: Some(13),
+        docs: &["Create or replace the envelope's range guard PDA at `[\"range_guard\", envelope, bump]`."],
+        accounts: &[
+            payer("authority"),
+            ENVELOPE,
+            writable("range_guard"),
+            SYSTEM_PROGRAM,
+        ],
+        args: &[
+            field("bump", Ty::U8),
+            field("entries", Ty::Vec(&Ty::Defined("RangeGuardSpec"))),
+        ],
+    },
+    Instruction {
+        name: "set_delegate_schema",
+        tag: Some(14),
+        docs: &["Delegated program registers the sub-struct it writes at `offset`."],
+        accounts: &[
+            signer("delegation_authority"),
+            readonly("envelope"),
+            writable("delegate_schema"),
+            payer("payer"),
+            SYSTEM_PROGRAM,
+        ],
+        args: &[
+            field("bump", Ty::U8),
+            field("offset", Ty::U8),
+            field("metadata", Ty::U64),
+        ],
+    },
+    Instruction {
+        name: "update_auxiliary_delegated_schema",
+        tag: Some(UPDATE_AUX_DELEGATED_SCHEMA_TAG),
+        docs: &[
+            "Delegated program writes its registered sub-struct.",
+            MANUAL_WIRE,
+            GUARD_ACCOUNTS,
+            "The constraint table follows `delegate_schema` when the envelope constrains its \
+             auxiliary region.",
+        ],
+        accounts: &[
+            signer("delegation_authority"),
+            ENVELOPE,
+            readonly("delegate_schema"),
+            optional(readonly("range_guard")),
+            optional(readonly("instructions_sysvar")),
+        ],
+        args: &[
+            field("schema_metadata", Ty::U64),
+            field("sequence", Ty::U64),
+            field("data", Ty::Bytes),
+        ],
+    },
+    Instruction {
+        name: "set_permanent",
+        tag: Some(16),
+        docs: &["Make the envelope permanent. Irreversible."],
+        accounts: AUTHORITY_ONLY,
+        args: &[],
+    },
+    Instruction {
+        name: "set_mutation_guard",
+        tag: Some(17),
+        docs: &["Set or clear FLAG_MUTATION_GUARD (`allow_multi: false` sets it)."],
+        accounts: AUTHORITY_ONLY,
+        args: &[field("allow_multi", Ty::Bool)],
+    },
+    Instruction {
+        name: "read_aux_range",
+        tag: Some(18),
+        docs: &["Set the return data to `auxiliary_data[offset..offset + len]`."],
+        accounts: READ_ONLY,
+        args: &[field("offset", Ty::U16), field("len", Ty::U16)],
+    },
+    Instruction {
+        name: "set_group_members",
+        tag: Some(19),
+        docs: &[
+            "Create or replace a group anchor PDA at `[\"group_anchor\", authority, group_id, bump]`.",
+            "Followed by 1 to 8 member envelopes as remaining accounts.",
+        ],
+        accounts: &[payer("authority"), writable("group_anchor"), SYSTEM_PROGRAM],
+        args: &[field("group_id", Ty::U64), field("bump", Ty::U8)],
+    },
+    Instruction {
+        name: "group_commit",
+        tag: Some(20),
+        docs: &[
+            "Update every member of a group under one sequence.",
+            "Followed by the anchor's member envelopes, writable, as remaining accounts.",
+            ORACLE_CONSTRAINT_ACCOUNTS,
+        ],
+        accounts: &[signer("authority"), writable("group_anchor")],
+        args: &[
+            field("sequence", Ty::U64),
+            field("updates", Ty::Vec(&Ty::Defined("GroupMemberUpdate"))),
+        ],
+    },
+    Instruction {
+        name: "set_label",
+        tag: Some(21),
+        docs: &["Create or replace the label PDA at `[\"label\", envelope, bump]`."],
+        accounts: &[
+            payer("authority"),
+            readonly("envelope"),
+            writable("label"),
+            SYSTEM_PROGRAM,
+        ],
+        args: &[
+            field("bump", Ty::U8),
+            field("label", Ty::Array(&Ty::U8, LABEL_SIZE)),
+        ],
+    },
+    Instruction {
+        name: "assert_aux_hash",
+        tag: Some(22),
+        docs: &["Fail unless SHA-256 of `auxiliary_data` equals `expected`."],
+        accounts: READ_ONLY,
+        args: &[field("expected", BYTES_32)],
+    },
+    Instruction {
+        name: "read_oracle",
+        tag: Some(23),
+        docs: &["Set the return data to `[oracle_metadata:8][sequence:8][data:239]`."],
+        accounts: READ_ONLY,
+        args: &[],
+    },
+    Instruction {
+        name: "read_aux",
+        tag: Some(24),
+        docs: &["Set the return data to `[auxiliary_metadata:8][auxiliary_data:256]`."],
+        accounts: READ_ONLY,
+        args: &[],
+    },
+    Instruction {
+        name: "batch_update",
+        tag: Some(BATCH_UPDATE_TAG),
+        docs: &[
+            "Fast-path style update of several envelopes, passed as remaining accounts.",
+            "Manual wire format: `entries` holds `count` back-to-back \
+             `[oracle_meta:8][sequence:8][len:1][payload:len]` records and runs to the end \
+             of the instruction data.",
+            ORACLE_CONSTRAINT_ACCOUNTS,
+        ],
+        accounts: &[signer("authority")],
+        args: &[field("count", Ty::U8), field("entries", Ty::Bytes)],
+    },
+    Instruction {
+        name: "resize",
+        tag: Some(26),
+        docs: &["Grow or shrink the extended auxiliary region after the fixed layout."],
+        accounts: &[payer("authority"), ENVELOPE, SYSTEM_PROGRAM],
+        args: &[field("type_hash", Ty::U64), field("ext_len", Ty::U16)],
+    },
+    Instruction {
+        name: "update_ext_aux",
+        tag: Some(27),
+        docs: &["Authority writes `data` at `offset` in the extended auxiliary region."],
+        accounts: AUTHORITY_ONLY,
+        args: &[
+            field("sequence", Ty::U64),
+            field("offset", Ty::U16),
+            field("data", Ty::Bytes),
+        ],
+    },
+    Instruction {
+        name: "set_twap",
+        tag: Some(28),
+        docs: &["Enable or disable the TWAP accumulator (FLAG_TWAP)."],
+        accounts: AUTHORITY_ONLY,
+        args: &[field("enabled", Ty::Bool)],
+    },
+    Instruction {
+        name: "set_update_stamp",
+        tag: Some(29),
+        docs: &["Enable or disable the update stamp (FLAG_UPDATE_STAMP)."],
+        accounts: AUTHORITY_ONLY,
+        args: &[field("enabled", Ty::Bool)],
+    },
+    Instruction {
+        name: "set_constraints",
+        tag: Some(30),
+        docs: &[
+            "Create or replace the constraint table PDA at `[\"constraints\", envelope, bump]`.",
+            "An empty list disables it. Oracle constraints take the envelope off the fast path.",
+        ],
+        accounts: &[
+            payer("authority"),
+            ENVELOPE,
+            writable("constraints"),
+            SYSTEM_PROGRAM,
+        ],
+        args: &[
+            field("bump", Ty::U8),
+            field("constraints", Ty::Vec(&Ty::Defined("ConstraintSpec"))),
+        ],
+    },
+    Instruction {
+        name: "set_publishers",
+        tag: Some(31),
+        docs: &[
+            "Create or replace the publisher set PDA at `[\"publishers\", envelope, bump]`.",
+            "An empty list with a zero threshold disables publisher writes.",
+        ],
+        accounts: &[
+            payer("authority"),
+            readonly("envelope"),
+            writable("publisher_set"),
+            SYSTEM_PROGRAM,
+        ],
+        args: &[
+            field("bump", Ty::U8),
+            field("threshold", Ty::U8),
+            field("publishers", Ty::Vec(&BYTES_32)),
+        ],
+    },
+    Instruction {
+        name: "publisher_update",
+        tag: Some(PUBLISHER_UPDATE_TAG),
+        docs: &[
+            "Fast-path style oracle update signed by at least `threshold` registered publishers.",
+            "When the envelope has oracle constraints, its constraint table comes third; the \
+             signing publishers follow as remaining accounts.",
+            MANUAL_WIRE,
+        ],
+        accounts: &[ENVELOPE, readonly("publisher_set")],
+        args: &[
+            field("oracle_meta", Ty::U64),
+            field("sequence", Ty::U64),
+            field("data", Ty::Bytes),
+        ],
+    },
+    Instruction {
+        name: "set_delegated_pda",
+        tag: Some(33),
+        docs: &[
+            "Delegate auxiliary writes to the PDA of `program_id` at `[...seeds, bump]`.",
+            "The delegate is checked by derivation and need not sign.",
+        ],
+        accounts: &[
+            signer("authority"),
+            ENVELOPE,
+            readonly("delegation_authority"),
+        ],
+        args: &[
+            field("program_id", BYTES_32),
+            field("seeds", Ty::Vec(&Ty::Bytes)),
+            field("bump", Ty::U8),
+            field("program_bitmask", Ty::Array(&Ty::U8, MASK_SIZE)),
+            field("user_bitmask", Ty::Array(&Ty::U8, MASK_SIZE)),
+        ],
+    },
+    Instruction {
+        name: "close_with_split",
+        tag: Some(34),
+        docs: &[
+            "Close an envelope, sending `treasury_bps` / 10000 of its lamports to `treasury`",
+            "and the rest to `recipient`.",
+        ],
+        accounts: &[
+            signer("authority"),
+            ENVELOPE,
+            writable("recipient"),
+            writable("treasury"),
+        ],
+        args: &[field("treasury_bps", Ty::U16)],
+    },
+    Instruction {
+        name: "set_conflation",
+        tag: Some(36),
+        docs: &["Enable or disable the per-slot conflation window (FLAG_CONFLATION)."],
+        accounts: AUTHORITY_ONLY,
+        args: &[field("enabled", Ty::Bool)],
+    },
+    Instruction {
+        name: "set_readers",
+        tag: Some(37),
+        docs: &[
+            "Create or replace the reader list PDA at `[\"readers\", envelope, bump]` and set \
+             FLAG_GATED_READS.",
+            "An empty list clears the flag.",
+        ],
+        accounts: &[
+            payer("authority"),
+            ENVELOPE,
+            writable("reader_list"),
+            SYSTEM_PROGRAM,
+        ],
+        args: &[
+            field("bump", Ty::U8),
+            field("readers", Ty::Vec(&BYTES_32)),
+        ],
+    },
+    Instruction {
+        name: "read_oracle_gated",
+        tag: Some(38),
+        docs: &[
+            "Same return data as `read_oracle`, for envelopes with FLAG_GATED_READS.",
+            "The current top-level instruction must target a program in the reader list.",
+        ],
+        accounts: &[
+            readonly("envelope"),
+            readonly("reader_list"),
+            readonly("instructions_sysvar"),
+        ],
+        args: &[],
+    },
+    Instruction {
+        name: "aggregate",
+        tag: Some(39),
+        docs: &[
+            "Write the median of 1 to 16 source envelopes' oracle values, after dropping those \
+             more than `max_deviation_bps` from the median of all of them.",
+            "Followed by the source envelopes, readonly and of the same oracle type, as \
+             remaining accounts.",
+            "A constrained envelope's constraint table goes between the envelope and the sources.",
+        ],
+        accounts: &[signer("authority"), ENVELOPE],
+        args: &[
+            field("sequence", Ty::U64),
+            field("min_sources", Ty::U8),
+            field("max_deviation_bps", Ty::U16),
+        ],
+    },
+    Instruction {
+        name: "set_publisher",
+        tag: Some(40),
+        docs: &[
+            "Set the key that may sign fast-path and batch oracle writes in place of the \
+             authority. All zeros removes it.",
+        ],
+        accounts: AUTHORITY_ONLY,
+        args: &[field("publisher", Ty::Pubkey)],
+    },
+    Instruction {
+        name: "set_paused",
+        tag: Some(41),
+        docs: &[
+            "Pause or resume the envelope. While paused, every oracle and aux write fails with \
+             custom error 7; reads and delegation are unaffected.",
+        ],
+        accounts: AUTHORITY_ONLY,
+        args: &[field("paused", Ty::Bool)],
+    },
+    Instruction {
+        name: "seal_auxiliary_range",
+        tag: Some(43),
+        docs: &[
+            "Make `auxiliary_data[offset..offset + len]` permanently immutable by marking it \
+             0x5E in both masks. No later write, forced or not, can change a sealed byte, and \
+             `clear_delegation` and `set_delegated_program` keep it sealed.",
+        ],
+        accounts: AUTHORITY_ONLY,
+        args: &[field("offset", Ty::U16), field("len", Ty::U16)],
+    },
+    Instruction {
+        name: "init_config",
+        tag: Some(44),
+        docs: &[
+            "Create the program config at `[\"config\", bump]`, once. The signer must be the \
+             upgrade authority recorded in `program_data`.",
+        ],
+        accounts: &[
+            payer("upgrade_authority"),
+            writable("config"),
+            SYSTEM_PROGRAM,
+            readonly("program"),
+            readonly("program_data"),
+        ],
+        args: &[
+            field("bump", Ty::U8),
+            field("admin", Ty::Pubkey),
+            field("create_fee", Ty::U64),
+        ],
+    },
+    Instruction {
+        name: "set_config",
+        tag: Some(45),
+        docs: &[
+            "Replace the config's admin, creation fee, and pause flag. The signer must be the \
+             current admin.",
+            "If `fee_destination` is writable, the config's lamports above its rent-exempt \
+             minimum move to it; pass a read-only account to keep them.",
+        ],
+        accounts: &[signer("admin"), writable("config"), readonly("fee_destination")],
+        args: &[
+            field("admin", Ty::Pubkey),
+            field("create_fee", Ty::U64),
+            field("paused", Ty::Bool),
+        ],
+    },
+    Instruction {
+        name: "create_with_events",
+        tag: Some(42),
+        docs: &[
+            "`create`, but every fast-path write to the envelope also logs an 80-byte \
+             `sol_log_data` event `[envelope:32][sequence:8][oracle_metadata:8][payload_hash:32]`, \
+             where `payload_hash` is the SHA-256 of the payload.",
+            "The flag is fixed at creation.",
+            CREATE_CONFIG,
+        ],
+        accounts: CREATE_ACCOUNTS,
+        args: &[
+            field("custom_seeds", Ty::Vec(&Ty::Bytes)),
+            field("bump", Ty::U8),
+            field("oracle_metadata", Ty::U64),
+        ],
+    },
+    Instruction {
+        name: "heartbeat",
+        tag: Some(46),
+        docs: &[
+            "Permissionless. Record the current slot in the envelope's `heartbeat_slot` if its \
+             oracle is still at `sequence`, marking the value as unchanged rather than \
+             abandoned. Fails with `InvalidInstructionData` once the oracle has moved on.",
+        ],
+        accounts: &[signer("cranker"), ENVELOPE, PADDING],
+        args: &[field("sequence", Ty::U64)],
+    },
+    Instruction {
+        name: "transfer_delegation",
+        tag: Some(47),
+        docs: &[
+            "Hand the delegation to a new delegate with new masks, keeping the oracle and aux \
+             state. The authority and both delegates sign.",
+        ],
+        accounts: &[
+            signer("authority"),
+            ENVELOPE,
+            signer("old_delegation_authority"),
+            signer("new_delegation_authority"),
+        ],
+        args: &[
+            field("program_bitmask", Ty::Array(&Ty::U8, MASK_SIZE)),
+            field("user_bitmask", Ty::Array(&Ty::U8, MASK_SIZE)),
+        ],
+    },
+    Instruction {
+        name: "export_state",
+        tag: Some(49),
+        docs: &[
+            "Set the return data to `[slot:8][state_hash:32]`, the `Clock` slot and the \
+             SHA-256 of the whole `Envelope`.",
+        ],
+        accounts: READ_ONLY,
+        args: &[],
+    },
+    Instruction {
+        name: "set_write_hook",
+        tag: Some(50),
+        docs: &[
+            "With `notify_on_authority_write`, store `hook_program` as the envelope's write \
+             hook: authority aux writes then call it with the changed ranges and fail if it \
+             fails. Without it, remove the hook. The authority and the delegate sign.",
+        ],
+        accounts: &[
+            signer("authority"),
+            ENVELOPE,
+            signer("delegation_authority"),
+            readonly("hook_program"),
+        ],
+        args: &[field("notify_on_authority_write", Ty::Bool)],
+    },
+    Instruction {
+        name: "set_oracle_metadata",
+        tag: Some(51),
+        docs: &[
+            "Replace each metadata that differs from the envelope's and zero its region, \
+             keeping the sequences. Blocked while delegation is active.",
+        ],
+        accounts: AUTHORITY_ONLY,
+        args: &[
+            field("oracle_metadata", Ty::U64),
+            field("auxiliary_metadata", Ty::U64),
+        ],
+    },
+    Instruction {
+        name: "update_auxiliary_multi_range_v2",
+        tag: Some(52),
+        docs: &[
+            "`update_auxiliary_multi_range` with u16 range offsets.",
+            CONSTRAINT_ACCOUNTS,
+            WRITE_HOOK_ACCOUNTS,
+        ],
+        accounts: AUTHORITY_PDA,
+        args: &[
+            field("metadata", Ty::U64),
+            field("sequence", Ty::U64),
+            field("ranges", Ty::Vec(&Ty::Defined("WriteSpecV2"))),
+        ],
+    },
+    Instruction {
+        name: "update_auxiliary_delegated_multi_range_v2",
+        tag: Some(53),
+        docs: &[
+            "`update_auxiliary_delegated_multi_range` with u16 range offsets.",
+            GUARD_ACCOUNTS,
+            CONSTRAINT_ACCOUNTS,
+        ],
+        accounts: DELEGATED,
+        args: &[
+            field("metadata", Ty::U64),
+            field("sequence", Ty::U64),
+            field("ranges", Ty::Vec(&Ty::Defined("WriteSpecV2"))),
+        ],
+    },
+    Instruction {
+        name: "update_auxiliary_multi_range_force_v2",
+        tag: Some(54),
+        docs: &[
+            "`update_auxiliary_multi_range_force` with u16 range offsets.",
+            "The constraint table follows when the envelope constrains its auxiliary region.",
+        ],
+        accounts: BOTH_SIGN,
+        args: &[
+            field("metadata", Ty::U64),
+            field("authority_sequence", Ty::U64),
+            field("program_sequence", Ty::U64),
+            field("ranges", Ty::Vec(&Ty::Defined("WriteSpecV2"))),
+        ],
+    },
+    Instruction {
+        name: "migrate_envelope",
+        tag: Some(55),
+        docs: &[
+            "Permissionless. Grow an envelope created before `publisher`, `heartbeat_slot`, \
+             `write_hook`, and `mutation_marker` to the current layout, zeroing the new fields.",
+        ],
+        accounts: &[payer("payer"), ENVELOPE, SYSTEM_PROGRAM],
+        args: &[],
+    },
+    Instruction {
+        name: "split_update",
+        tag: Some(SPLIT_UPDATE_TAG),
+        docs: &[
+            "One half (`part` 1 or 2) of a payload written to the start of the extended \
+             auxiliary region. Signed by the authority or the publisher.",
+            "Both halves must be adjacent top-level instructions in one transaction, part 1 \
+             first, with the same envelope, `type_hash`, and `sequence`; part 2 writes after \
+             part 1's payload and stores the sequence.",
+            MANUAL_WIRE,
+        ],
+        accounts: &[
+            signer("authority"),
+            ENVELOPE,
+            readonly("instructions_sysvar"),
+        ],
+        args: &[
+            field("type_hash", Ty::U64),
+            field("sequence", Ty::U64),
+            field("part", Ty::U8),
+            field("data", Ty::Bytes),
+        ],
+    },
+];
+
+#[derive(Debug, Clone, Copy)]
+struct TypeDef {
+    name: &'static str,
+    docs: &'static [&'static str],
+    /// `true` for `#[repr(C)]` Pod account layouts, `false` for wincode argument structs.
+    bytemuck: bool,
+    fields: &'static [Field],
+}
+
+const TYPES: &[TypeDef] = &[
+    TypeDef {
+        name: "Envelope",
+        docs: &["Oracle envelope account, 1200 bytes."],
+        bytemuck: true,
+        fields: &[
+            field("authority", Ty::Pubkey),
+            field("oracle_state", Ty::Defined("OracleState")),
+            field("bump", Ty::U8),
+            field("flags", Ty::U8),
+            field("aux_write_count", Ty::U16),
+            field("aux_dirty_blocks", Ty::U8),
+            field("constrained_regions", Ty::U8),
+            field("paused", Ty::U8),
+            field("emit_events", Ty::U8),
+            field("delegation_authority", Ty::Pubkey),
+            field("program_bitmask", Ty::Array(&Ty::U8, MASK_SIZE)),
+            field("user_bitmask", Ty::Array(&Ty::U8, MASK_SIZE)),
+            field("authority_aux_sequence", Ty::U64),
+            field("program_aux_sequence", Ty::U64),
+            field("auxiliary_metadata", Ty::U64),
+            field("auxiliary_data", Ty::Array(&Ty::U8, AUX_DATA_SIZE)),
+            field("publisher", Ty::Pubkey),
+            field("heartbeat_slot", Ty::U64),
+            field("write_hook", Ty::Pubkey),
+            field("mutation_marker", Ty::U64),
+        ],
+    },
+    TypeDef {
+        name: "OracleState",
+        docs: &["Oracle region of an envelope, written by the fast path."],
+        bytemuck: true,
+        fields: &[
+            field("oracle_metadata", Ty::U64),
+            field("sequence", Ty::U64),
+            field("data", Ty::Array(&Ty::U8, ORACLE_BYTES)),
+            field("pad", Ty::Array(&Ty::U8, 1)),
+        ],
+    },
+    TypeDef {
+        name: "RangeGuard",
+        docs: &["Range guard account at `[\"range_guard\", envelope, bump]`, 200 bytes."],
+        bytemuck: true,
+        fields: &[
+            field("envelope", Ty::Pubkey),
+            field("count", Ty::U8),
+            field("bump", Ty::U8),
+            field("padding", Ty::Array(&Ty::U8, 6)),
+            field(
+                "entries",
+                Ty::Array(&Ty::Defined("RangeGuardEntry"), MAX_RANGE_GUARDS),
+            ),
+        ],
+    },
+    TypeDef {
+        name: "RangeGuardEntry",
+        docs: &[],
+        bytemuck: true,
+        fields: &[
+            field("program_id", Ty::Pubkey),
+            field("offset", Ty::U16),
+            field("len", Ty::U16),
+            field("padding", Ty::Array(&Ty::U8, 4)),
+        ],
+    },
+    TypeDef {
+        name: "DelegateSchema",
+        docs: &["Delegate schema account at `[\"delegate_schema\", envelope, bump]`, 80 bytes."],
+        bytemuck: true,
+        fields: &[
+            field("envelope", Ty::Pubkey),
+            field("delegation_authority", Ty::Pubkey),
+            field("metadata", Ty::U64),
+            field("offset", Ty::U8),
+            field("bump", Ty::U8),
+            field("padding", Ty::Array(&Ty::U8, 6)),
+        ],
+    },
+    TypeDef {
+        name: "GroupAnchor",
+        docs: &[
+            "Group anchor account at `[\"group_anchor\", authority, group_id, bump]`, 312 bytes.",
+        ],
+        bytemuck: true,
+        fields: &[
+            field("authority", Ty::Pubkey),
+            field("group_id", Ty::U64),
+            field("sequence", Ty::U64),
+            field("count", Ty::U8),
+            field("bump", Ty::U8),
+            field("padding", Ty::Array(&Ty::U8, 6)),
+            field("members", Ty::Array(&Ty::Pubkey, MAX_GROUP_MEMBERS)),
+        ],
+    },
+    TypeDef {
+        name: "EnvelopeLabel",
+        docs: &["Label account at `[\"label\", envelope, bump]`, 104 bytes."],
+        bytemuck: true,
+        fields: &[
+            field("envelope", Ty::Pubkey),
+            field("label", Ty::Array(&Ty::U8, LABEL_SIZE)),
+            field("bump", Ty::U8),
+            field("padding", Ty::Array(&Ty::U8, 7)),
+        ],
+    },
+    TypeDef {
+        name: "ConstraintTable",
+        docs: &["Constraint table account at `[\"constraints\", envelope, bump]`, 104 bytes."],
+        bytemuck: true,
+        fields: &[
+            field("envelope", Ty::Pubkey),
+            field("count", Ty::U8),
+            field("bump", Ty::U8),
+            field("padding", Ty::Array(&Ty::U8, 6)),
+            field(
+                "entries",
+                Ty::Array(&Ty::Defined("Constraint"), MAX_CONSTRAINTS),
+            ),
+        ],
+    },
+    TypeDef {
+        name: "PublisherSet",
+        docs: &["Publisher set account at `[\"publishers\", envelope, bump]`, 296 bytes."],
+        bytemuck: true,
+        fields: &[
+            field("envelope", Ty::Pubkey),
+            field("count", Ty::U8),
+            field("bump", Ty::U8),
+            field("threshold", Ty::U8),
+            field("padding", Ty::Array(&Ty::U8, 5)),
+            field("publishers", Ty::Array(&Ty::Pubkey, MAX_PUBLISHERS)),
+        ],
+    },
+    TypeDef {
+        name: "ReaderList",
+        docs: &["Reader list account at `[\"readers\", envelope, bump]`, 296 bytes."],
+        bytemuck: true,
+        fields: &[
+            field("envelope", Ty::Pubkey),
+            field("count", Ty::U8),
+            field("bump", Ty::U8),
+            field("padding", Ty::Array(&Ty::U8, 6)),
+            field("readers", Ty::Array(&Ty::Pubkey, MAX_READERS)),
+        ],
+    },
+    TypeDef {
+        name: "ProgramConfig",
+        docs: &["Program config account at `[\"config\", bump]`, 48 bytes."],
+        bytemuck: true,
+        fields: &[
+            field("admin", Ty::Pubkey),
+            field("create_fee", Ty::U64),
+            field("bump", Ty::U8),
+            field("paused", Ty::U8),
+            field("padding", Ty::Array(&Ty::U8, 6)),
+        ],
+    },
+    TypeDef {
+        name: "Constraint",
+        docs: &[
+            "`region[offset..offset + width] <op> rhs`, little-endian. `region`: 0 oracle, 1 aux.",
+            "`op`: 0 eq, 1 ne, 2 lt, 3 le, 4 gt, 5 ge. `rhs_kind`: 0 `value`, 1 the field at \
+             `rhs_offset`, 2 the same field before the write (aux only).",
+        ],
+        bytemuck: true,
+        fields: &[
+            field("region", Ty::U8),
+            field("op", Ty::U8),
+            field("width", Ty::U8),
+            field("signed", Ty::U8),
+            field("offset", Ty::U8),
+            field("rhs_kind", Ty::U8),
+            field("rhs_offset", Ty::U8),
+            field("padding", Ty::U8),
+            field("value", Ty::U64),
+        ],
+    },
+    TypeDef {
+        name: "WriteSpec",
+        docs: &["One range of a multi-range auxiliary write."],
+        bytemuck: false,
+        fields: &[field("offset", Ty::U8), field("data", Ty::Bytes)],
+    },
+    TypeDef {
+        name: "WriteSpecV2",
+        docs: &["`WriteSpec` with a u16 offset; the range must end within the auxiliary region."],
+        bytemuck: false,
+        fields: &[field("offset", Ty::U16), field("data", Ty::Bytes)],
+    },
+    TypeDef {
+        name: "RangeGuardSpec",
+        docs: &["One guarded range in `set_range_guards`."],
+        bytemuck: false,
+        fields: &[
+            field("program_id", BYTES_32),
+            field("offset", Ty::U16),
+            field("len", Ty::U16),
+        ],
+    },
+    TypeDef {
+        name: "ConstraintSpec",
+        docs: &["One entry of `set_constraints`, stored as a `Constraint`."],
+        bytemuck: false,
+        fields: &[
+            field("region", Ty::U8),
+            field("op", Ty::U8),
+            field("width", Ty::U8),
+            field("signed", Ty::Bool),
+            field("offset", Ty::U8),
+            field("rhs_kind", Ty::U8),
+            field("rhs_offset", Ty::U8),
+            field("value", Ty::U64),
+        ],
+    },
+    TypeDef {
+        name: "GroupMemberUpdate",
+        docs: &["One member's oracle update in `group_commit`."],
+        bytemuck: false,
+        fields: &[field("oracle_meta", Ty::U64), field("payload", Ty::Bytes)],
+    },
+];
+
+/// Program-owned account types, by [`TYPES`] name, with their data length.
+const ACCOUNTS: &[(&str, usize)] = &[
+    ("Envelope", Envelope::SIZE),
+    ("RangeGuard", core::mem::size_of::<RangeGuard>()),
+    ("DelegateSchema", core::mem::size_of::<DelegateSchema>()),
+    ("GroupAnchor", core::mem::size_of::<GroupAnchor>()),
+    ("EnvelopeLabel", core::mem::size_of::<EnvelopeLabel>()),
+    ("ConstraintTable", ConstraintTable::SIZE),
+    ("PublisherSet", PublisherSet::SIZE),
+    ("ReaderList", ReaderList::SIZE),
+    ("ProgramConfig", ProgramConfig::SIZE),
+];
+
+fn discriminator(tag: Option<u32>) -> Vec<u8> {
+    tag.map_or_else(Vec::new, |tag| tag.to_le_bytes().to_vec())
+}
+
+fn fields_json(fields: &[Field]) -> Value {
+    fields
+        .iter()
+        .map(|field| json!({ "name": field.name, "type": field.ty.to_json() }))
+        .collect()
+}
+
+fn account_json(account: &AccountMeta) -> Value {
+    let mut value = json!({ "name": account.name });
+    for (key, set) in [
+        ("writable", account.writable),
+        ("signer", account.signer),
+        ("optional", account.optional),
+    ] {
+        if set {
+            value[key] = json!(true);
+        }
+    }
+    value
+}
+
+fn instruction_json(ix: &Instruction) -> Value {
+    let mut accounts: Vec<Value> = ix.accounts.iter().map(account_json).collect();
+    if ix.tag.is_some_and(requires_trailing_config) {
+        accounts.push(account_json(&TRAILING_CONFIG));
+    }
+    json!({
+        "name": ix.name,
+        "docs": ix.docs,
+        "discriminator": discriminator(ix.tag),
+        "accounts": accounts,
+        "args": fields_json(ix.args),
+    })
+}
+
+fn type_json(def: &TypeDef) -> Value {
+    let mut value = json!({
+        "name": def.name,
+        "docs": def.docs,
+        "type": { "kind": "struct", "fields": fields_json(def.fields) },
+    });
+    if def.bytemuck {
+        value["serialization"] = json!("bytemuck");
+        value["repr"] = json!({ "kind": "c" });
+    }
+    value
+}
+
+/// The program's IDL as Anchor IDL JSON, with `program_id` as its `address`.
+pub fn idl(program_id: &Address) -> Value {
+    json!({
+        "address": program_id.to_string(),
+        "metadata": {
+            "name": "c_u_soon",
+            "version": env!("CARGO_PKG_VERSION"),
+            "spec": IDL_SPEC,
+            "description": "Oracle envelopes with a fast-path update and permission-masked auxiliary data",
+        },
+        "docs": [
+            "Slow-path discriminators are the 4-byte little-endian instruction tag. The fast \
+             path has none and is recognised by its two accounts.",
+            "Wincode encodes `vec` and `bytes` lengths as u64, not Borsh's u32. Trailing \
+             `bytes` of manual-wire instructions have no length prefix.",
+            "Accounts have no discriminator; they are identified by owner and data length.",
+            "Every state-changing slow-path instruction except create, create_with_events, \
+             init_config and set_config ends with the read-only program config, after any \
+             optional accounts.",
+        ],
+        "instructions": INSTRUCTIONS.iter().map(instruction_json).collect::<Vec<_>>(),
+        "accounts": ACCOUNTS
+            .iter()
+            .map(|(name, _)| json!({ "name": name, "discriminator": [] }))
+            .collect::<Vec<_>>(),
+        "types": TYPES.iter().map(type_json).collect::<Vec<_>>(),
+    })
+}
+
+/// [`idl`] rendered as pretty-printed JSON.
+pub fn idl_json(program_id: &Address) -> String {
+    serde_json::to_string_pretty(&idl(program_id)).expect("IDL values always serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c_u_soon_instruction::{SlowPathInstruction, VERSIONED_TAG};
+
+    fn instruction(name: &str) -> &'static Instruction {
+        INSTRUCTIONS.iter().find(|ix| ix.name == name).unwrap()
+    }
+
+    /// Encoded size, or `None` for variable-length types.
+    fn fixed_size(ty: Ty) -> Option<usize> {
+        match ty {
+            Ty::Bool | Ty::U8 => Some(1),
+            Ty::U16 => Some(2),
+            Ty::U64 => Some(8),
+            Ty::Pubkey => Some(32),
+            Ty::Array(inner, len) => Some(fixed_size(*inner)? * len),
+            Ty::Defined(name) => {
+                let def = TYPES.iter().find(|def| def.name == name)?;
+                def.fields.iter().map(|field| fixed_size(field.ty)).sum()
+            }
+            Ty::Bytes | Ty::Vec(_) => None,
+        }
+    }
+
+    fn args_size(ix: &Instruction) -> usize {
+        ix.args.iter().map(|arg| fixed_size(arg.ty).unwrap()).sum()
+    }
+
+    #[test]
+    fn tags_are_unique_and_cover_the_program() {
+        let mut tags: Vec<u32> = INSTRUCTIONS.iter().filter_map(|ix| ix.tag).collect();
+        tags.sort_unstable();
+        let expected: Vec<u32> = (0..=56)
+            .filter(|&tag| tag != 48 && tag != VERSIONED_TAG)
+            .collect();
+        assert_eq!(tags, expected);
+
+        let mut names: Vec<&str> = INSTRUCTIONS.iter().map(|ix| ix.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), INSTRUCTIONS.len());
+    }
+
+    #[test]
+    fn fixed_size_args_match_wincode() {
+        let cases = [
+            (
+                "set_delegated_program",
+                SlowPathInstruction::SetDelegatedProgram {
+                    program_bitmask: [0; MASK_SIZE],
+                    user_bitmask: [0xFF; MASK_SIZE],
+                },
+            ),
+            ("close", SlowPathInstruction::Close),
+            ("export_state", SlowPathInstruction::ExportState),
+            ("migrate_envelope", SlowPathInstruction::MigrateEnvelope),
+            (
+                "set_write_hook",
+                SlowPathInstruction::SetWriteHook {
+                    notify_on_authority_write: true,
+                },
+            ),
+            (
+                "set_oracle_metadata",
+                SlowPathInstruction::SetOracleMetadata {
+                    oracle_metadata: 1,
+                    auxiliary_metadata: 2,
+                },
+            ),
+            (
+                "close_with_split",
+                SlowPathInstruction::CloseWithSplit { treasury_bps: 1 },
+            ),
+            (
+                "set_delegate_schema",
+                SlowPathInstruction::SetDelegateSchema {
+                    bump: 1,
+                    offset: 2,
+                    metadata: 3,
+                },
+            ),
+            (
+                "read_aux_range",
+                SlowPathInstruction::ReadAuxRange { offset: 1, len: 2 },
+            ),
+            (
+                "set_group_members",
+                SlowPathInstruction::SetGroupMembers {
+                    group_id: 1,
+                    bump: 2,
+                },
+            ),
+            (
+                "set_label",
+                SlowPathInstruction::SetLabel {
+                    bump: 1,
+                    label: [0; LABEL_SIZE],
+                },
+            ),
+            (
+                "assert_aux_hash",
+                SlowPathInstruction::AssertAuxHash { expected: [0; 32] },
+            ),
+            (
+                "resize",
+                SlowPathInstruction::Resize {
+                    type_hash: 1,
+                    ext_len: 2,
+                },
+            ),
+            ("set_twap", SlowPathInstruction::SetTwap { enabled: true }),
+            (
+                "set_update_stamp",
+                SlowPathInstruction::SetUpdateStamp { enabled: true },
+            ),
+            (
+                "set_conflation",
+                SlowPathInstruction::SetConflation { enabled: true },
+            ),
+            ("read_oracle_gated", SlowPathInstruction::ReadOracleGated),
+            (
+                "aggregate",
+                SlowPathInstruction::Aggregate {
+                    sequence: 1,
+                    min_sources: 2,
+                    max_deviation_bps: 3,
+                },
+            ),
+            (
+                "set_publisher",
+                SlowPathInstruction::SetPublisher { publisher: [0; 32] },
+            ),
+            (
+                "set_paused",
+                SlowPathInstruction::SetPaused { paused: true },
+            ),
+            (
+                "seal_auxiliary_range",
+                SlowPathInstruction::SealAuxiliaryRange { offset: 1, len: 2 },
+            ),
+            (
+                "init_config",
+                SlowPathInstruction::InitConfig {
+                    bump: 1,
+                    admin: [2; 32],
+                    create_fee: 3,
+                },
+            ),
+            (
+                "set_config",
+                SlowPathInstruction::SetConfig {
+                    admin: [2; 32],
+                    create_fee: 3,
+                    paused: true,
+                },
+            ),
+            ("heartbeat", SlowPathInstruction::Heartbeat { sequence: 4 }),
+            (
+                "transfer_delegation",
+                SlowPathInstruction::TransferDelegation {
+                    program_bitmask: [0; MASK_SIZE],
+                    user_bitmask: [0xFF; MASK_SIZE],
+                },
+            ),
+            (
+                "set_delegated_program_compact",
+                SlowPathInstruction::SetDelegatedProgramCompact {
+                    program_bitmask: [0; COMPACT_MASK_SIZE],
+                    user_bitmask: [0xFF; COMPACT_MASK_SIZE],
+                },
+            ),
+        ];
+        for (name, ix) in cases {
+            let bytes = wincode::serialize(&ix).unwrap();
+            let idl_ix = instruction(name);
+            assert_eq!(bytes[..4], discriminator(idl_ix.tag)[..], "{name}");
+            assert_eq!(bytes.len(), 4 + args_size(idl_ix), "{name}");
+        }
+    }
+
+    #[test]
+    fn account_layouts_match_sdk_sizes() {
+        for &(name, size) in ACCOUNTS {
+            assert_eq!(fixed_size(Ty::Defined(name)), Some(size), "{name}");
+        }
+    }
+
+    #[test]
+    fn emits_anchor_shape() {
+        let program_id = Address::new_from_array([7; 32]);
+        let idl = idl(&program_id);
+        assert_eq!(idl["address"], program_id.to_string());
+        assert_eq!(idl["metadata"]["spec"], IDL_SPEC);
+
+        let set_twap = idl["instructions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|ix| ix["name"] == "set_twap")
+            .unwrap();
+        assert_eq!(set_twap["discriminator"], json!([28, 0, 0, 0]));
+        assert_eq!(
+            set_twap["accounts"][0],
+            json!({ "name": "authority", "signer": true })
+        );
+        assert_eq!(
+            set_twap["args"][0],
+            json!({ "name": "enabled", "type": "bool" })
+        );
+        assert_eq!(
+            set_twap["accounts"].as_array().unwrap().last().unwrap(),
+            &json!({ "name": "config" })
+        );
+        let create = &idl["instructions"][1];
+        assert_eq!(create["name"], "create");
+        assert_eq!(
+            create["accounts"][3],
+            json!({ "name": "config", "writable": true })
+        );
+        assert_eq!(create["accounts"].as_array().unwrap().len(), 4);
+
+        let envelope = &idl["types"][0];
+        assert_eq!(envelope["serialization"], "bytemuck");
+        assert_eq!(
+            envelope["type"]["fields"][1]["type"],
+            json!({ "defined": { "name": "OracleState" } })
+        );
+        assert!(serde_json::from_str::<Value>(&idl_json(&program_id)).is_ok());
+    }
+}
@@ -0,0 +1,19 @@
+//! Print the c_u_soon IDL: `c_u_soon_idl <PROGRAM_ID>`.
+
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use solana_address::Address;
+
+fn main() -> ExitCode {
+    let Some(arg) = std::env::args().nth(1) else {
+        eprintln!("usage: c_u_soon_idl <PROGRAM_ID>");
+        return ExitCode::FAILURE;
+    };
+    let Ok(program_id) = Address::from_str(&arg) else {
+        eprintln!("invalid program id: {arg}");
+        return ExitCode::FAILURE;
+    };
+    println!("{}", c_u_soon_idl::idl_json(&program_id));
+    ExitCode::SUCCESS
+}
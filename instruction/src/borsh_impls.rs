@@ -0,0 +1,166 @@
+//! `BorshSerialize` / `BorshDeserialize` for [`SlowPathInstruction`], behind the `borsh`
+//! feature.
+//!
+//! The impls read and write the program's wincode bytes, so `borsh::to_vec(&ix)` is exactly
+//! `wincode::serialize(&ix)` and either decoder accepts the other's output. They are not what
+//! `#[derive(BorshSerialize)]` would produce. Against a derived Borsh schema the layout
+//! differs in two places:
+//!
+//! - The variant tag is a little-endian `u32` (the values in [`SlowPathInstruction`]'s
+//!   docs), where Borsh writes a `u8` variant index.
+//! - `Vec` lengths are a little-endian `u64`, where Borsh writes a `u32`. This covers nested
+//!   vectors (`custom_seeds`, `seeds`) and every vector of structs or byte arrays.
+//!
+//! Everything else coincides: integers are little-endian, `bool` is one byte `0` or `1`,
+//! fixed-size arrays are their bytes with no prefix, and struct fields follow in declaration
+//! order. A Borsh-only client that mirrors the enum by hand has to use the two encodings
+//! above.
+//!
+//! Like the program, decoding takes the whole input as one instruction and rejects trailing
+//! bytes, so an instruction cannot be nested inside a larger Borsh value. The manual-wire
+//! updates (tags 4-8, 15, 25, and 32) are not `SlowPathInstruction`s and are not covered.
+
+use alloc::vec::Vec;
+use borsh::io::{ErrorKind, Read, Result, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+use wincode::SchemaRead;
+
+use crate::SlowPathInstruction;
+
+impl BorshSerialize for SlowPathInstruction {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let bytes = wincode::serialize(self).map_err(|_| ErrorKind::InvalidData)?;
+        writer.write_all(&bytes)
+    }
+}
+
+impl BorshDeserialize for SlowPathInstruction {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut data = Vec::new();
+        let mut chunk = [0u8; 256];
+        loop {
+            let read = reader.read(&mut chunk)?;
+            if read == 0 {
+                break;
+            }
+            data.extend_from_slice(&chunk[..read]);
+        }
+        let mut cursor = data.as_slice();
+        let ix = <SlowPathInstruction as SchemaRead>::get(&mut cursor)
+            .map_err(|_| ErrorKind::InvalidData)?;
+        if !cursor.is_empty() {
+            return Err(ErrorKind::InvalidData.into());
+        }
+        Ok(ix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConstraintSpec, WriteSpec};
+    use alloc::vec;
+
+    fn samples() -> Vec<SlowPathInstruction> {
+        vec![
+            SlowPathInstruction::Create {
+                custom_seeds: vec![b"feed".to_vec(), vec![7; 32]],
+                bump: 254,
+                oracle_metadata: 0x0812_3456_789A_BCDE,
+            },
+            SlowPathInstruction::Close,
+            SlowPathInstruction::UpdateAuxiliaryMultiRange {
+                metadata: 9,
+                sequence: u64::MAX,
+                ranges: vec![
+                    WriteSpec {
+                        offset: 0,
+                        data: vec![1; 8],
+                    },
+                    WriteSpec {
+                        offset: 200,
+                        data: vec![2; 56],
+                    },
+                ],
+            },
+            SlowPathInstruction::SetConstraints {
+                bump: 1,
+                constraints: vec![ConstraintSpec {
+                    region: 1,
+                    op: 3,
+                    width: 8,
+                    signed: false,
+                    offset: 8,
+                    rhs_kind: 2,
+                    rhs_offset: 0,
+                    value: 0,
+                }],
+            },
+            SlowPathInstruction::SetPublishers {
+                bump: 3,
+                threshold: 2,
+                publishers: vec![[1; 32], [2; 32]],
+            },
+            SlowPathInstruction::SetDelegatedPda {
+                program_id: [5; 32],
+                seeds: vec![vec![], b"vault".to_vec()],
+                bump: 255,
+                program_bitmask: [0xFF; 256],
+                user_bitmask: [0; 256],
+            },
+            SlowPathInstruction::UpdateExtAux {
+                sequence: 4,
+                offset: 300,
+                data: vec![0xAB; 40],
+            },
+            SlowPathInstruction::SetMutationGuard { allow_multi: true },
+            SlowPathInstruction::Aggregate {
+                sequence: 2,
+                min_sources: 3,
+                max_deviation_bps: 500,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_borsh_matches_wincode() {
+        for ix in samples() {
+            let wincode_bytes = wincode::serialize(&ix).unwrap();
+            let borsh_bytes = borsh::to_vec(&ix).unwrap();
+            assert_eq!(borsh_bytes, wincode_bytes, "{ix:?}");
+
+            let from_slice: SlowPathInstruction = borsh::from_slice(&wincode_bytes).unwrap();
+            assert_eq!(wincode::serialize(&from_slice).unwrap(), wincode_bytes);
+            let from_reader: SlowPathInstruction =
+                borsh::from_reader(&mut wincode_bytes.as_slice()).unwrap();
+            assert_eq!(wincode::serialize(&from_reader).unwrap(), wincode_bytes);
+            let back: SlowPathInstruction = wincode::deserialize(&borsh_bytes).unwrap();
+            assert_eq!(wincode::serialize(&back).unwrap(), wincode_bytes);
+        }
+    }
+
+    #[test]
+    fn test_borsh_layout() {
+        let bytes = borsh::to_vec(&SlowPathInstruction::SetPublishers {
+            bump: 3,
+            threshold: 2,
+            publishers: vec![[9; 32]],
+        })
+        .unwrap();
+        assert_eq!(bytes[..4], 31u32.to_le_bytes());
+        assert_eq!(bytes[4..6], [3, 2]);
+        assert_eq!(bytes[6..14], 1u64.to_le_bytes());
+        assert_eq!(bytes[14..], [9; 32]);
+    }
+
+    #[test]
+    fn test_borsh_rejects_bad_input() {
+        // Trailing bytes, a derived-Borsh `u8` tag, and a reserved tag.
+        let mut trailing = borsh::to_vec(&SlowPathInstruction::Close).unwrap();
+        trailing.push(0);
+        assert!(borsh::from_slice::<SlowPathInstruction>(&trailing).is_err());
+        assert!(borsh::from_reader::<_, SlowPathInstruction>(&mut trailing.as_slice()).is_err());
+        assert!(borsh::from_slice::<SlowPathInstruction>(&[1]).is_err());
+        assert!(borsh::from_slice::<SlowPathInstruction>(&48u32.to_le_bytes()).is_err());
+    }
+}
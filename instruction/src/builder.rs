@@ -0,0 +1,452 @@
+//! Builders for the [`SlowPathInstruction`] variants whose fields are easiest to mis-fill.
+//!
+//! Each builder checks its inputs as they are added, so a mistake surfaces at the call that
+//! made it rather than at [`SlowPathInstruction::validate`] or on-chain, and whatever it
+//! builds passes `validate`. The enum stays available for callers that need a field the
+//! builders do not expose.
+
+use crate::{ranges_overlap, SlowPathInstruction, WriteSpec, MAX_WRITE_RANGES};
+use alloc::vec::Vec;
+use c_u_soon::{Mask, StructMetadata, AUX_DATA_SIZE, MAX_CUSTOM_SEEDS, MAX_DELEGATE_SEEDS};
+
+/// Why a builder refused an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    /// More than [`MAX_CUSTOM_SEEDS`] envelope seeds, or more than [`MAX_DELEGATE_SEEDS`]
+    /// delegate seeds.
+    TooManySeeds,
+    /// A seed is longer than 32 bytes.
+    SeedTooLong,
+    /// A mask byte is not `0x00` (writable) or `0xFF` (blocked).
+    NonCanonicalMask,
+    /// A range is empty or extends past [`AUX_DATA_SIZE`].
+    InvalidRange,
+    /// More than [`MAX_WRITE_RANGES`] ranges.
+    TooManyRanges,
+    /// A range writes a byte an earlier range already writes.
+    OverlappingRanges,
+    /// A multi-range update was built with no ranges.
+    NoRanges,
+}
+
+impl core::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooManySeeds => write!(f, "too many seeds"),
+            Self::SeedTooLong => write!(f, "seed exceeds 32 bytes"),
+            Self::NonCanonicalMask => write!(f, "mask byte not 0x00 or 0xFF"),
+            Self::InvalidRange => write!(f, "write range empty or past {} bytes", AUX_DATA_SIZE),
+            Self::TooManyRanges => write!(f, "more than {} write ranges", MAX_WRITE_RANGES),
+            Self::OverlappingRanges => write!(f, "write ranges overlap"),
+            Self::NoRanges => write!(f, "no write ranges"),
+        }
+    }
+}
+
+impl core::error::Error for BuildError {}
+
+fn check_seed(seeds: &[Vec<u8>], max: usize, seed: &[u8]) -> Result<(), BuildError> {
+    if seeds.len() == max {
+        return Err(BuildError::TooManySeeds);
+    }
+    if seed.len() > 32 {
+        return Err(BuildError::SeedTooLong);
+    }
+    Ok(())
+}
+
+/// `Create`, or `CreateWithEvents` after [`with_events`](Self::with_events).
+#[derive(Debug, Clone)]
+pub struct CreateBuilder {
+    custom_seeds: Vec<Vec<u8>>,
+    bump: u8,
+    oracle_metadata: StructMetadata,
+    emit_events: bool,
+}
+
+impl CreateBuilder {
+    /// An envelope for oracle values described by `oracle_metadata`, with no custom seeds
+    /// and bump 0.
+    pub fn new(oracle_metadata: StructMetadata) -> Self {
+        Self {
+            custom_seeds: Vec::new(),
+            bump: 0,
+            oracle_metadata,
+            emit_events: false,
+        }
+    }
+
+    /// Append a custom PDA seed. Fails with `TooManySeeds` past [`MAX_CUSTOM_SEEDS`] and
+    /// `SeedTooLong` past 32 bytes.
+    pub fn seed(mut self, seed: &[u8]) -> Result<Self, BuildError> {
+        check_seed(&self.custom_seeds, MAX_CUSTOM_SEEDS, seed)?;
+        self.custom_seeds.push(seed.to_vec());
+        Ok(self)
+    }
+
+    /// Bump of the envelope PDA at these seeds.
+    pub fn bump(mut self, bump: u8) -> Self {
+        self.bump = bump;
+        self
+    }
+
+    /// Build `CreateWithEvents`, so the envelope logs an event on every fast-path write.
+    pub fn with_events(mut self) -> Self {
+        self.emit_events = true;
+        self
+    }
+
+    pub fn build(self) -> SlowPathInstruction {
+        let oracle_metadata = self.oracle_metadata.as_u64();
+        if self.emit_events {
+            SlowPathInstruction::CreateWithEvents {
+                custom_seeds: self.custom_seeds,
+                bump: self.bump,
+                oracle_metadata,
+            }
+        } else {
+            SlowPathInstruction::Create {
+                custom_seeds: self.custom_seeds,
+                bump: self.bump,
+                oracle_metadata,
+            }
+        }
+    }
+}
+
+/// `SetDelegatedProgram`, `SetDelegatedPda` after [`pda`](Self::pda), or
+/// `TransferDelegation` through [`build_transfer`](Self::build_transfer).
+#[derive(Debug, Clone)]
+pub struct SetDelegationBuilder {
+    program_bitmask: Mask,
+    user_bitmask: Mask,
+    pda: Option<DelegatePda>,
+}
+
+#[derive(Debug, Clone)]
+struct DelegatePda {
+    program_id: [u8; 32],
+    seeds: Vec<Vec<u8>>,
+    bump: u8,
+}
+
+impl SetDelegationBuilder {
+    /// Delegate with `program_bitmask` limiting the delegate's writes and `user_bitmask`
+    /// the authority's. Fails with `NonCanonicalMask` unless both are canonical (see
+    /// [`Mask::is_canonical`]).
+    pub fn new(program_bitmask: &Mask, user_bitmask: &Mask) -> Result<Self, BuildError> {
+        if !program_bitmask.is_canonical() || !user_bitmask.is_canonical() {
+            return Err(BuildError::NonCanonicalMask);
+        }
+        Ok(Self {
+            program_bitmask: *program_bitmask,
+            user_bitmask: *user_bitmask,
+            pda: None,
+        })
+    }
+
+    /// Delegate to the PDA of `program_id` at `[..seeds, bump]`, building
+    /// `SetDelegatedPda`. Fails with `TooManySeeds` past [`MAX_DELEGATE_SEEDS`] and
+    /// `SeedTooLong` if a seed is longer than 32 bytes.
+    pub fn pda(
+        mut self,
+        program_id: [u8; 32],
+        seeds: &[&[u8]],
+        bump: u8,
+    ) -> Result<Self, BuildError> {
+        let mut pda = DelegatePda {
+            program_id,
+            seeds: Vec::with_capacity(seeds.len()),
+            bump,
+        };
+        for seed in seeds {
+            check_seed(&pda.seeds, MAX_DELEGATE_SEEDS, seed)?;
+            pda.seeds.push(seed.to_vec());
+        }
+        self.pda = Some(pda);
+        Ok(self)
+    }
+
+    pub fn build(self) -> SlowPathInstruction {
+        let program_bitmask = self.program_bitmask.into();
+        let user_bitmask = self.user_bitmask.into();
+        match self.pda {
+            None => SlowPathInstruction::SetDelegatedProgram {
+                program_bitmask,
+                user_bitmask,
+            },
+            Some(pda) => SlowPathInstruction::SetDelegatedPda {
+                program_id: pda.program_id,
+                seeds: pda.seeds,
+                bump: pda.bump,
+                program_bitmask,
+                user_bitmask,
+            },
+        }
+    }
+
+    /// `TransferDelegation` installing these masks for the new delegate. A PDA set with
+    /// [`pda`](Self::pda) is ignored: the new delegate signs for itself.
+    pub fn build_transfer(self) -> SlowPathInstruction {
+        SlowPathInstruction::TransferDelegation {
+            program_bitmask: self.program_bitmask.into(),
+            user_bitmask: self.user_bitmask.into(),
+        }
+    }
+}
+
+/// `UpdateAuxiliaryMultiRange` and its delegated and force forms.
+///
+/// Ranges are checked as they are added: each must be non-empty, end within
+/// [`AUX_DATA_SIZE`], and not overlap an earlier one, and there may be at most
+/// [`MAX_WRITE_RANGES`].
+#[derive(Debug, Clone)]
+pub struct MultiRangeBuilder {
+    metadata: StructMetadata,
+    sequence: u64,
+    ranges: Vec<WriteSpec>,
+}
+
+impl MultiRangeBuilder {
+    /// A write to aux data described by `metadata` under `sequence`: the authority
+    /// sequence, or the program sequence for [`build_delegated`](Self::build_delegated).
+    pub fn new(metadata: StructMetadata, sequence: u64) -> Self {
+        Self {
+            metadata,
+            sequence,
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Write `data` at aux byte `offset`. Fails with `InvalidRange` if `data` is empty or
+    /// ends past [`AUX_DATA_SIZE`], `TooManyRanges` past [`MAX_WRITE_RANGES`], and
+    /// `OverlappingRanges` if it shares a byte with an earlier range.
+    pub fn range(mut self, offset: u8, data: &[u8]) -> Result<Self, BuildError> {
+        if data.is_empty() || offset as usize + data.len() > AUX_DATA_SIZE {
+            return Err(BuildError::InvalidRange);
+        }
+        if self.ranges.len() == MAX_WRITE_RANGES {
+            return Err(BuildError::TooManyRanges);
+        }
+        self.ranges.push(WriteSpec {
+            offset,
+            data: data.to_vec(),
+        });
+        if ranges_overlap(&self.ranges) {
+            return Err(BuildError::OverlappingRanges);
+        }
+        Ok(self)
+    }
+
+    /// Ranges added so far.
+    pub fn ranges(&self) -> &[WriteSpec] {
+        &self.ranges
+    }
+
+    fn into_parts(self) -> Result<(u64, u64, Vec<WriteSpec>), BuildError> {
+        if self.ranges.is_empty() {
+            return Err(BuildError::NoRanges);
+        }
+        Ok((self.metadata.as_u64(), self.sequence, self.ranges))
+    }
+
+    /// `UpdateAuxiliaryMultiRange`, written by the authority. Fails with `NoRanges` if no
+    /// range was added.
+    pub fn build(self) -> Result<SlowPathInstruction, BuildError> {
+        let (metadata, sequence, ranges) = self.into_parts()?;
+        Ok(SlowPathInstruction::UpdateAuxiliaryMultiRange {
+            metadata,
+            sequence,
+            ranges,
+        })
+    }
+
+    /// `UpdateAuxiliaryDelegatedMultiRange`, written by the delegate.
+    pub fn build_delegated(self) -> Result<SlowPathInstruction, BuildError> {
+        let (metadata, sequence, ranges) = self.into_parts()?;
+        Ok(SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange {
+            metadata,
+            sequence,
+            ranges,
+        })
+    }
+
+    /// `UpdateAuxiliaryMultiRangeForce`, with this builder's sequence as the authority
+    /// sequence and `program_sequence` as the program sequence.
+    pub fn build_force(self, program_sequence: u64) -> Result<SlowPathInstruction, BuildError> {
+        let (metadata, authority_sequence, ranges) = self.into_parts()?;
+        Ok(SlowPathInstruction::UpdateAuxiliaryMultiRangeForce {
+            metadata,
+            authority_sequence,
+            program_sequence,
+            ranges,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c_u_soon::TypeHash;
+
+    #[test]
+    fn create_builder_checks_seeds() {
+        let ix = CreateBuilder::new(u64::METADATA)
+            .seed(b"btc")
+            .unwrap()
+            .bump(254)
+            .build();
+        assert!(ix.validate());
+        let SlowPathInstruction::Create {
+            custom_seeds,
+            bump,
+            oracle_metadata,
+        } = ix
+        else {
+            panic!("expected Create");
+        };
+        assert_eq!(custom_seeds, [b"btc".to_vec()]);
+        assert_eq!(bump, 254);
+        assert_eq!(oracle_metadata, u64::METADATA.as_u64());
+
+        let events = CreateBuilder::new(u64::METADATA).with_events().build();
+        assert!(matches!(
+            events,
+            SlowPathInstruction::CreateWithEvents { .. }
+        ));
+
+        assert_eq!(
+            CreateBuilder::new(u64::METADATA)
+                .seed(&[0; 33])
+                .unwrap_err(),
+            BuildError::SeedTooLong
+        );
+        let mut full = CreateBuilder::new(u64::METADATA);
+        for _ in 0..MAX_CUSTOM_SEEDS {
+            full = full.seed(b"s").unwrap();
+        }
+        assert!(full.clone().build().validate());
+        assert_eq!(full.seed(b"s").unwrap_err(), BuildError::TooManySeeds);
+    }
+
+    #[test]
+    fn set_delegation_builder_checks_masks_and_seeds() {
+        let mut program = Mask::ALL_BLOCKED;
+        program.allow_range(0, 8);
+        let builder = SetDelegationBuilder::new(&program, &Mask::ALL_WRITABLE).unwrap();
+
+        let ix = builder.clone().build();
+        assert!(ix.validate());
+        assert!(matches!(
+            ix,
+            SlowPathInstruction::SetDelegatedProgram { .. }
+        ));
+        let transfer = builder.clone().build_transfer();
+        assert!(transfer.validate());
+        assert!(matches!(
+            transfer,
+            SlowPathInstruction::TransferDelegation { .. }
+        ));
+
+        let pda = builder
+            .clone()
+            .pda([7; 32], &[b"vault".as_slice()], 255)
+            .unwrap()
+            .build();
+        assert!(pda.validate());
+        let SlowPathInstruction::SetDelegatedPda { seeds, bump, .. } = pda else {
+            panic!("expected SetDelegatedPda");
+        };
+        assert_eq!(seeds, [b"vault".to_vec()]);
+        assert_eq!(bump, 255);
+
+        let seeds = [b"s".as_slice(); MAX_DELEGATE_SEEDS + 1];
+        assert_eq!(
+            builder.clone().pda([7; 32], &seeds, 255).unwrap_err(),
+            BuildError::TooManySeeds
+        );
+        assert!(builder
+            .clone()
+            .pda([7; 32], &seeds[1..], 255)
+            .unwrap()
+            .build()
+            .validate());
+        assert_eq!(
+            builder.pda([7; 32], &[&[0; 33][..]], 255).unwrap_err(),
+            BuildError::SeedTooLong
+        );
+
+        let mut non_canonical = Mask::ALL_BLOCKED;
+        non_canonical.as_bytes_mut()[3] = 0x01;
+        assert_eq!(
+            SetDelegationBuilder::new(&Mask::ALL_BLOCKED, &non_canonical).unwrap_err(),
+            BuildError::NonCanonicalMask
+        );
+    }
+
+    #[test]
+    fn multi_range_builder_checks_ranges() {
+        type Aux = [u8; 16];
+        let builder = MultiRangeBuilder::new(Aux::METADATA, 4)
+            .range(8, &[3])
+            .unwrap()
+            .range(0, &[1, 2])
+            .unwrap();
+        assert_eq!(builder.ranges().len(), 2);
+
+        let ix = builder.clone().build().unwrap();
+        assert!(ix.validate());
+        let SlowPathInstruction::UpdateAuxiliaryMultiRange {
+            metadata,
+            sequence,
+            ranges,
+        } = ix
+        else {
+            panic!("expected UpdateAuxiliaryMultiRange");
+        };
+        assert_eq!(metadata, Aux::METADATA.as_u64());
+        assert_eq!(sequence, 4);
+        assert_eq!(ranges[0].offset, 8);
+        assert!(matches!(
+            builder.clone().build_delegated().unwrap(),
+            SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange { sequence: 4, .. }
+        ));
+        assert!(matches!(
+            builder.clone().build_force(9).unwrap(),
+            SlowPathInstruction::UpdateAuxiliaryMultiRangeForce {
+                authority_sequence: 4,
+                program_sequence: 9,
+                ..
+            }
+        ));
+
+        assert_eq!(
+            builder.clone().range(1, &[0]).unwrap_err(),
+            BuildError::OverlappingRanges
+        );
+        assert_eq!(
+            builder.clone().range(20, &[]).unwrap_err(),
+            BuildError::InvalidRange
+        );
+        assert_eq!(
+            builder.range(255, &[0, 0]).unwrap_err(),
+            BuildError::InvalidRange
+        );
+        assert_eq!(
+            MultiRangeBuilder::new(Aux::METADATA, 1)
+                .build()
+                .unwrap_err(),
+            BuildError::NoRanges
+        );
+
+        let mut full = MultiRangeBuilder::new(Aux::METADATA, 1);
+        for i in 0..MAX_WRITE_RANGES as u8 {
+            full = full.range(i * 2, &[i]).unwrap();
+        }
+        assert!(full.clone().build().unwrap().validate());
+        assert_eq!(
+            full.range(200, &[0]).unwrap_err(),
+            BuildError::TooManyRanges
+        );
+    }
+}
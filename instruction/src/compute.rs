@@ -0,0 +1,170 @@
+//! Compute-unit estimates for setting a transaction's `SetComputeUnitLimit`.
+//!
+//! Each figure is a ceiling on what the program consumes for the instruction, with variable
+//! length arguments charged per element. Accounts are not part of the instruction data, so
+//! an instruction whose cost depends on its accounts is charged for the largest account list
+//! the program accepts. The program's `compute_units_tests` integration test runs the
+//! instructions under mollusk and fails if any measured cost exceeds its estimate, so the
+//! table has to be raised along with the program.
+
+use c_u_soon::{MAX_AGGREGATE_SOURCES, TINY_PAYLOAD_SIZE};
+
+use crate::SlowPathInstruction;
+
+/// Fast-path update of an envelope without TWAP, update stamps, conflation, or events,
+/// signed by its authority. Equal to the program's `fast_path::COMPUTE_BUDGET`.
+pub const FAST_PATH_COMPUTE_UNITS: u32 = 48;
+
+/// As [`FAST_PATH_COMPUTE_UNITS`], for a payload of exactly [`TINY_PAYLOAD_SIZE`] bytes,
+/// which takes the fast path's tiny lane. Equal to the program's
+/// `fast_path::TINY_COMPUTE_BUDGET`.
+pub const FAST_PATH_TINY_COMPUTE_UNITS: u32 = 44;
+
+/// Ceiling for a fast-path update that leaves the straight-line path: a publisher signer,
+/// a paused envelope, or any of TWAP, update stamps, conflation, or events. Reading the
+/// `Clock` sysvar, hashing the payload, and logging the event are all covered.
+pub const FAST_PATH_FLAGGED_COMPUTE_UNITS: u32 = 1_500;
+
+/// `sol_memcpy` costs one CU per this many bytes, with a floor of [`MEMCPY_BASE`].
+const MEMCPY_BYTES_PER_CU: usize = 250;
+const MEMCPY_BASE: usize = 10;
+
+/// Slow-path instructions that allocate a PDA pay for a `create_program_address` and up to
+/// three system program CPIs (`Transfer`, `Allocate`, `Assign`).
+const ACCOUNT_CREATION: u32 = 9_000;
+
+/// `Create` looks up the program config: a `find_program_address` while it does not exist,
+/// otherwise a `create_program_address` and the creation fee `Transfer`.
+const CONFIG_LOOKUP: u32 = 6_000;
+
+/// Every other state-changing instruction checks the program config, its last account: a
+/// `find_program_address` while the config does not exist, otherwise a
+/// `create_program_address`.
+const CONFIG_CHECK: u32 = 4_500;
+
+/// Writes to an envelope with the mutation guard read the `Clock` sysvar and hash it with
+/// the instructions sysvar.
+const MUTATION_GUARD: u32 = 1_500;
+
+/// Instructions that only read or flip envelope state.
+const SIMPLE: u32 = 1_500;
+
+/// Estimated compute units of a fast-path update of `payload_len` bytes.
+///
+/// The payload is copied with one `sol_memcpy`, which costs the same for every length the
+/// fast path accepts, so this is [`FAST_PATH_COMPUTE_UNITS`] for any valid payload except
+/// one of [`TINY_PAYLOAD_SIZE`] bytes, which is [`FAST_PATH_TINY_COMPUTE_UNITS`]. Use
+/// [`FAST_PATH_FLAGGED_COMPUTE_UNITS`] for envelopes that leave the straight-line path.
+pub const fn estimated_fast_path_compute_units(payload_len: usize) -> u32 {
+    if payload_len == TINY_PAYLOAD_SIZE {
+        return FAST_PATH_TINY_COMPUTE_UNITS;
+    }
+    let extra = (payload_len / MEMCPY_BYTES_PER_CU).saturating_sub(MEMCPY_BASE);
+    FAST_PATH_COMPUTE_UNITS + extra as u32
+}
+
+/// Estimated compute units of `ix`, including any CPIs it makes.
+///
+/// - `Create`, `CreateWithEvents`, `InitConfig`, and instructions that create a sidecar PDA
+///   (`SetRangeGuards`, `SetDelegateSchema`, `SetGroupMembers`, `SetLabel`,
+///   `SetConstraints`, `SetPublishers`, `SetReaders`) are charged for the account creation
+///   even when the account already exists. `Create` and `CreateWithEvents` are also charged
+///   for the config lookup.
+/// - Instructions that
+///   [`requires_trailing_config`](crate::requires_trailing_config) are charged for the
+///   config check.
+/// - Oracle, aux, and heartbeat writes are charged for the mutation guard, whether or not the
+///   envelope has it.
+/// - `Aggregate` reads its sources from the accounts, so it is charged for
+///   `MAX_AGGREGATE_SOURCES` of them.
+/// - Aux writes are charged per range, `GroupCommit` per member update. An authority aux
+///   write to an envelope with a write hook also pays for the hook program's CPI, which
+///   depends on that program and is not included.
+///
+/// Manual-wire updates (tags 4-8, 15, 25, and 32) are not `SlowPathInstruction`s and are
+/// not covered.
+pub fn estimated_compute_units(ix: &SlowPathInstruction) -> u32 {
+    use SlowPathInstruction::*;
+    let config_check = match ix {
+        Create { .. }
+        | CreateWithEvents { .. }
+        | InitConfig { .. }
+        | SetConfig { .. }
+        | ReadAuxRange { .. }
+        | AssertAuxHash { .. }
+        | ReadOracle
+        | ReadAux
+        | ReadOracleGated
+        | ExportState => 0,
+        _ => CONFIG_CHECK,
+    };
+    let mutation_guard = match ix {
+        Heartbeat { .. }
+        | UpdateAuxiliaryMultiRange { .. }
+        | UpdateAuxiliaryMultiRangeForce { .. }
+        | UpdateAuxiliaryMultiRangeV2 { .. }
+        | UpdateAuxiliaryMultiRangeForceV2 { .. }
+        | UpdateAuxiliaryDelegatedMultiRange { .. }
+        | UpdateAuxiliaryDelegatedMultiRangeV2 { .. }
+        | GroupCommit { .. }
+        | UpdateExtAux { .. }
+        | Aggregate { .. } => MUTATION_GUARD,
+        _ => 0,
+    };
+    let cost = match ix {
+        Create { custom_seeds, .. } | CreateWithEvents { custom_seeds, .. } => {
+            ACCOUNT_CREATION + CONFIG_LOOKUP + 300 * custom_seeds.len() as u32
+        }
+        InitConfig { .. } => ACCOUNT_CREATION,
+        // Sweeping fees reads the `Rent` sysvar.
+        SetConfig { .. } => 2_000,
+        // Reads the `Clock` sysvar.
+        Heartbeat { .. } => 2_000,
+        Close => SIMPLE,
+        CloseWithSplit { .. } => 2_000,
+        SetDelegatedProgram { .. } => 5_000,
+        // Expands both masks to 256 bytes each.
+        SetDelegatedProgramCompact { .. } => 8_000,
+        SetDelegatedPda { seeds, .. } => 7_000 + 300 * seeds.len() as u32,
+        ClearDelegation => 2_000,
+        TransferDelegation { .. } => 5_000,
+        // Checks that the hook program is executable.
+        SetWriteHook { .. } => 2_000,
+        UpdateAuxiliaryMultiRange { ranges, .. }
+        | UpdateAuxiliaryMultiRangeForce { ranges, .. } => 3_000 + 1_200 * ranges.len() as u32,
+        UpdateAuxiliaryMultiRangeV2 { ranges, .. }
+        | UpdateAuxiliaryMultiRangeForceV2 { ranges, .. } => 3_000 + 1_200 * ranges.len() as u32,
+        // Checks range guards and the instructions sysvar when they are passed.
+        UpdateAuxiliaryDelegatedMultiRange { ranges, .. } => 5_000 + 1_200 * ranges.len() as u32,
+        UpdateAuxiliaryDelegatedMultiRangeV2 { ranges, .. } => 5_000 + 1_200 * ranges.len() as u32,
+        SetRangeGuards { entries, .. } => ACCOUNT_CREATION + 200 * entries.len() as u32,
+        SetConstraints { constraints, .. } => ACCOUNT_CREATION + 200 * constraints.len() as u32,
+        SetPublishers { publishers, .. } => ACCOUNT_CREATION + 200 * publishers.len() as u32,
+        SetReaders { readers, .. } => ACCOUNT_CREATION + 200 * readers.len() as u32,
+        SetDelegateSchema { .. } | SetGroupMembers { .. } | SetLabel { .. } => ACCOUNT_CREATION,
+        GroupCommit { updates, .. } => 2_000 + 1_500 * updates.len() as u32,
+        // Both realloc the envelope and may transfer rent.
+        Resize { .. } | MigrateEnvelope => 5_000,
+        UpdateExtAux { data, .. } => 2_000 + (data.len() / MEMCPY_BYTES_PER_CU) as u32,
+        AssertAuxHash { .. } => 3_000,
+        // Checks the reader list's PDA with a `create_program_address`.
+        ReadOracleGated => 4_500,
+        // Hashes the whole envelope and reads the `Clock` sysvar.
+        ExportState => 3_000,
+        Aggregate { .. } => 3_000 + 1_000 * MAX_AGGREGATE_SOURCES as u32,
+        SetPermanent
+        | SetMutationGuard { .. }
+        | ReadAuxRange { .. }
+        | ReadOracle
+        | ReadAux
+        | SetTwap { .. }
+        | SetUpdateStamp { .. }
+        | SetConflation { .. }
+        | SetPublisher { .. }
+        | SetPaused { .. }
+        | SealAuxiliaryRange { .. } => SIMPLE,
+        // Zeroes up to the whole oracle and aux regions.
+        SetOracleMetadata { .. } => 2_000,
+    };
+    config_check + mutation_guard + cost
+}
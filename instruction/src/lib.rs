@@ -8,13 +8,45 @@
 //!
 //! Serialized with `wincode`: a little-endian `u32` discriminant followed by variant
 //! fields. Discriminant tags are stable on-chain (see test `discriminant_stability`).
+//!
+//! Any slow-path instruction may be wrapped behind [`VERSIONED_TAG`] with the instruction-set
+//! version it was built for (see [`versioned`]). A program rejects versions newer than its
+//! [`INSTRUCTION_VERSION`], and tags past [`MAX_SLOW_PATH_TAG`], with
+//! [`UNSUPPORTED_VERSION_ERROR`] rather than a generic decode failure.
+//!
+//! [`CreateBuilder`], [`SetDelegationBuilder`], and [`MultiRangeBuilder`] build the variants
+//! with the most invariants, checking each input as it is added, so what they build always
+//! passes [`SlowPathInstruction::validate`].
+//!
+//! [`SlowPathInstruction::parse`] classifies raw instruction data of either encoding into a
+//! [`ParsedInstruction`], borrowing the payloads of manual-wire updates.
+//!
+//! With the `borsh` feature, [`SlowPathInstruction`] also implements Borsh's traits, encoding
+//! the same wincode bytes (see the `borsh_impls` module docs for how they map onto Borsh).
 
 extern crate alloc;
 
 use alloc::vec::Vec;
-use c_u_soon::{MASK_SIZE, MAX_AUX_STRUCT_SIZE, MAX_CUSTOM_SEEDS};
+use c_u_soon::{
+    is_valid_publisher_set, is_valid_reader_list, Constraint, Permission, StructMetadata, U64Le,
+    AUX_DATA_SIZE, COMPACT_MASK_SIZE, LABEL_SIZE, MASK_SIZE, MAX_AGGREGATE_SOURCES,
+    MAX_AUX_STRUCT_SIZE, MAX_CONSTRAINTS, MAX_CUSTOM_SEEDS, MAX_DELEGATE_SEEDS, MAX_EXT_AUX_SIZE,
+    MAX_GROUP_MEMBERS, MAX_RANGE_GUARDS, ORACLE_BYTES,
+};
 use wincode::{SchemaRead, SchemaWrite};
 
+#[cfg(feature = "borsh")]
+mod borsh_impls;
+mod builder;
+pub use builder::{BuildError, CreateBuilder, MultiRangeBuilder, SetDelegationBuilder};
+mod compute;
+pub use compute::{
+    estimated_compute_units, estimated_fast_path_compute_units, FAST_PATH_COMPUTE_UNITS,
+    FAST_PATH_FLAGGED_COMPUTE_UNITS, FAST_PATH_TINY_COMPUTE_UNITS,
+};
+mod parse;
+pub use parse::ParsedInstruction;
+
 /// Wire format tag for UpdateAuxiliary: `[disc:4][metadata:8][sequence:8][data:N]`
 pub const UPDATE_AUX_TAG: u32 = 4;
 /// Wire format tag for UpdateAuxiliaryDelegated: `[disc:4][metadata:8][sequence:8][data:N]`
@@ -25,12 +57,70 @@ pub const UPDATE_AUX_FORCE_TAG: u32 = 6;
 pub const UPDATE_AUX_RANGE_TAG: u32 = 7;
 /// Wire format tag for UpdateAuxiliaryDelegatedRange: `[disc:4][metadata:8][sequence:8][offset:1][data:N]`
 pub const UPDATE_AUX_DELEGATED_RANGE_TAG: u32 = 8;
-/// Header size for UpdateAuxiliary/UpdateAuxiliaryDelegated: disc(4) + metadata(8) + sequence(8)
+/// Wire format tag for UpdateAuxiliaryDelegatedSchema: `[disc:4][schema_metadata:8][sequence:8][data:N]`
+pub const UPDATE_AUX_DELEGATED_SCHEMA_TAG: u32 = 15;
+/// Wire format tag for BatchUpdate: `[disc:4][count:1]` then `count` entries of
+/// `[oracle_meta:8][sequence:8][len:1][payload:len]`
+pub const BATCH_UPDATE_TAG: u32 = 25;
+/// Wire format tag for PublisherUpdate: `[disc:4][oracle_meta:8][sequence:8][payload:N]`,
+/// the fast-path layout behind a tag
+pub const PUBLISHER_UPDATE_TAG: u32 = 32;
+/// Wire format tag for a versioned instruction: `[disc:4][version:1][instruction:N]`, where
+/// `instruction` is any other slow-path instruction, tag included
+pub const VERSIONED_TAG: u32 = 35;
+/// Wire format tag for SplitUpdate: `[disc:4][type_hash:8][sequence:8][part:1][payload:N]`,
+/// one of two adjacent instructions that together write a payload to the extended
+/// auxiliary region
+pub const SPLIT_UPDATE_TAG: u32 = 56;
+/// Highest slow-path tag this instruction set defines. Larger tags belong to a newer version.
+pub const MAX_SLOW_PATH_TAG: u32 = 56;
+/// Instruction-set version of this crate. Bumped whenever a variant is added or a variant's
+/// fields change, so a deployment built against an older crate can tell it is out of date.
+///
+/// - 1: tags up to 35 (`CloseWithSplit` and the versioned wrapper).
+/// - 2: `SetConflation` (tag 36).
+/// - 3: `SetReaders` and `ReadOracleGated` (tags 37 and 38).
+/// - 4: `Aggregate` (tag 39).
+/// - 5: `SetPublisher` (tag 40).
+/// - 6: `SetPaused` (tag 41).
+/// - 7: `CreateWithEvents` (tag 42).
+/// - 8: `SealAuxiliaryRange` (tag 43).
+/// - 9: `InitConfig` and `SetConfig` (tags 44 and 45); `Create` and `CreateWithEvents` take
+///   an optional config account, and the other state-changing instructions accept it last.
+/// - 10: `Heartbeat` (tag 46).
+/// - 11: `TransferDelegation` (tag 47).
+/// - 12: `SetDelegatedProgramCompact` (tag 12).
+/// - 13: `ExportState` (tag 49).
+/// - 14: `SetWriteHook` (tag 50); authority aux writes to a hooked envelope take the hook
+///   program as a fourth account.
+/// - 15: `SetOracleMetadata` (tag 51).
+/// - 16: `UpdateAuxiliaryMultiRangeV2`, `UpdateAuxiliaryDelegatedMultiRangeV2`, and
+///   `UpdateAuxiliaryMultiRangeForceV2` (tags 52 to 54).
+/// - 17: `MigrateEnvelope` (tag 55).
+/// - 18: `SplitUpdate` (tag 56).
+/// - 19: the program config is required: `Create` and `CreateWithEvents` take it as their
+///   fourth account, and every other state-changing instruction as its last.
+pub const INSTRUCTION_VERSION: u8 = 19;
+/// Header size for UpdateAuxiliary/UpdateAuxiliaryDelegated/UpdateAuxiliaryDelegatedSchema:
+/// disc(4) + metadata(8) + sequence(8)
 pub const UPDATE_AUX_HEADER_SIZE: usize = 4 + 8 + 8;
 /// Header size for UpdateAuxiliaryForce: disc(4) + metadata(8) + auth_seq(8) + prog_seq(8)
 pub const UPDATE_AUX_FORCE_HEADER_SIZE: usize = 4 + 8 + 8 + 8;
 /// Header size for UpdateAuxiliaryRange/DelegatedRange: disc(4) + metadata(8) + sequence(8) + offset(1)
 pub const UPDATE_AUX_RANGE_HEADER_SIZE: usize = 4 + 8 + 8 + 1;
+/// Header size for BatchUpdate: disc(4) + count(1)
+pub const BATCH_UPDATE_HEADER_SIZE: usize = 4 + 1;
+/// Per-entry header size for BatchUpdate: oracle_meta(8) + sequence(8) + len(1)
+pub const BATCH_ENTRY_HEADER_SIZE: usize = 8 + 8 + 1;
+/// Header size for PublisherUpdate: disc(4) + oracle_meta(8) + sequence(8)
+pub const PUBLISHER_UPDATE_HEADER_SIZE: usize = 4 + 8 + 8;
+/// Header size for SplitUpdate: disc(4) + type_hash(8) + sequence(8) + part(1)
+pub const SPLIT_UPDATE_HEADER_SIZE: usize = 4 + 8 + 8 + 1;
+/// Header size for a versioned instruction: disc(4) + version(1)
+pub const VERSIONED_HEADER_SIZE: usize = 4 + 1;
+/// Envelopes per BatchUpdate: the slow-path entrypoint parses at most 64 accounts, one of
+/// which is the authority. Transaction size is usually the tighter limit.
+pub const MAX_BATCH_SIZE: usize = 63;
 
 /// Max serialized size for UpdateAuxiliary/Delegated: header(20) + max_data(255) = 275
 pub const UPDATE_AUX_MAX_SIZE: usize = UPDATE_AUX_HEADER_SIZE + MAX_AUX_STRUCT_SIZE;
@@ -39,6 +129,228 @@ pub const UPDATE_AUX_FORCE_MAX_SIZE: usize = UPDATE_AUX_FORCE_HEADER_SIZE + MAX_
 /// Max serialized size for UpdateAuxiliaryRange/DelegatedRange: header(21) + max_data(255) = 276
 pub const UPDATE_AUX_RANGE_MAX_SIZE: usize = UPDATE_AUX_RANGE_HEADER_SIZE + MAX_AUX_STRUCT_SIZE;
 
+/// Most ranges one `UpdateAuxiliaryMultiRange`, `UpdateAuxiliaryDelegatedMultiRange`, or
+/// `UpdateAuxiliaryMultiRangeForce` may carry. Every range costs a bounds check, a mask
+/// check, and a copy, so the cap bounds the compute a single update can spend. The program
+/// rejects more with [`TOO_MANY_RANGES_ERROR`].
+pub const MAX_WRITE_RANGES: usize = 16;
+
+/// Basis points of a `CloseWithSplit` refund that send all of it to the treasury.
+pub const MAX_TREASURY_BPS: u16 = 10_000;
+
+/// `ProgramError::Custom` code returned when a multi-range update exceeds
+/// [`MAX_WRITE_RANGES`].
+pub const TOO_MANY_RANGES_ERROR: u32 = 1;
+
+/// `ProgramError::Custom` code returned when an update's metadata has a different type size
+/// than the stored metadata: the layout changed, so the bytes cannot be reinterpreted.
+pub const METADATA_SIZE_MISMATCH_ERROR: u32 = 2;
+
+/// `ProgramError::Custom` code returned when an update's metadata has the stored type size
+/// but a different hash, as after a rename. See `StructMetadata::diff`.
+pub const METADATA_HASH_MISMATCH_ERROR: u32 = 3;
+
+/// `ProgramError::Custom` code returned when a write leaves a constrained region breaking
+/// one of the envelope's constraints (see `c_u_soon::ConstraintTable`).
+pub const CONSTRAINT_VIOLATED_ERROR: u32 = 4;
+
+/// `ProgramError::Custom` code returned for a versioned instruction newer than
+/// [`INSTRUCTION_VERSION`], or a tag past [`MAX_SLOW_PATH_TAG`]: the instruction was built
+/// for a later program.
+pub const UNSUPPORTED_VERSION_ERROR: u32 = 5;
+
+/// `ProgramError::Custom` code returned when fewer source envelopes than `min_sources` are
+/// left after an `Aggregate` drops outliers.
+pub const TOO_FEW_SOURCES_ERROR: u32 = 6;
+
+/// `ProgramError::Custom` code returned when an oracle or aux write targets an envelope
+/// paused with `SetPaused`.
+pub const ENVELOPE_PAUSED_ERROR: u32 = 7;
+
+/// `ProgramError::Custom` code returned when a state-changing instruction runs while the
+/// program config is paused with `SetConfig` (see [`requires_trailing_config`]).
+pub const PROGRAM_PAUSED_ERROR: u32 = 8;
+
+/// `ProgramError::Custom` code returned when a `SplitUpdate` part is not paired with the
+/// other part for the same envelope, type hash, and sequence in the neighbouring top-level
+/// instruction, or runs under CPI.
+pub const SPLIT_UPDATE_UNPAIRED_ERROR: u32 = 9;
+
+/// Returns `true` if the slow-path instruction with `tag` takes the program config PDA as
+/// its last account, after all of its own, and fails with [`PROGRAM_PAUSED_ERROR`] while
+/// the config is paused.
+///
+/// Every state-changing instruction does, except `InitConfig` and `SetConfig`, which must
+/// keep working while paused, and `Create` and `CreateWithEvents`, which take the config
+/// as their fourth account. The read-only instructions (`ReadAuxRange`, `AssertAuxHash`,
+/// `ReadOracle`, `ReadAux`, `ReadOracleGated`, `ExportState`) do not.
+///
+/// The account is required: without it the instruction fails with `NotEnoughAccountKeys`,
+/// or with `InvalidSeeds` when its last account is not the config PDA. Before `InitConfig`
+/// runs, the PDA holds no data and the instruction is not paused.
+pub const fn requires_trailing_config(tag: u32) -> bool {
+    tag <= MAX_SLOW_PATH_TAG && !matches!(tag, 0 | 18 | 22 | 23 | 24 | 38 | 42 | 44 | 45 | 49)
+}
+
+/// Return data of `ReadOracle`: `[oracle_metadata:8][sequence:8][data:239]`.
+pub const READ_ORACLE_RETURN_SIZE: usize = 8 + 8 + ORACLE_BYTES;
+/// Return data of `ReadAux`: `[auxiliary_metadata:8][auxiliary_data:256]`.
+pub const READ_AUX_RETURN_SIZE: usize = 8 + AUX_DATA_SIZE;
+/// Return data of `ExportState`: `[slot:8][state_hash:32]`.
+pub const EXPORT_STATE_RETURN_SIZE: usize = 8 + 32;
+
+/// Split `ReadOracle` return data into `(oracle_metadata, sequence, data)`.
+///
+/// Returns `None` unless `data` is exactly [`READ_ORACLE_RETURN_SIZE`] bytes. Compare the
+/// metadata with `T::METADATA` before interpreting the payload as `T`.
+pub fn split_read_oracle(data: &[u8]) -> Option<(StructMetadata, u64, &[u8])> {
+    if data.len() != READ_ORACLE_RETURN_SIZE {
+        return None;
+    }
+    let metadata = u64::from_le_bytes(data[..8].try_into().unwrap());
+    let sequence = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    Some((StructMetadata::from_raw(metadata), sequence, &data[16..]))
+}
+
+/// Split `ReadAux` return data into `(auxiliary_metadata, auxiliary_data)`.
+///
+/// Returns `None` unless `data` is exactly [`READ_AUX_RETURN_SIZE`] bytes.
+pub fn split_read_aux(data: &[u8]) -> Option<(StructMetadata, &[u8])> {
+    if data.len() != READ_AUX_RETURN_SIZE {
+        return None;
+    }
+    let metadata = u64::from_le_bytes(data[..8].try_into().unwrap());
+    Some((StructMetadata::from_raw(metadata), &data[8..]))
+}
+
+/// Split `ExportState` return data into `(slot, state_hash)`.
+///
+/// Returns `None` unless `data` is exactly [`EXPORT_STATE_RETURN_SIZE`] bytes.
+/// `state_hash` is the SHA-256 of the `Envelope` bytes as of `slot`.
+pub fn split_export_state(data: &[u8]) -> Option<(u64, [u8; 32])> {
+    if data.len() != EXPORT_STATE_RETURN_SIZE {
+        return None;
+    }
+    let slot = u64::from_le_bytes(data[..8].try_into().unwrap());
+    Some((slot, data[8..].try_into().unwrap()))
+}
+
+/// First bytes of the instruction data the program sends to an envelope's write hook
+/// (see `SetWriteHook`), so the hook program can tell the call from its own instructions.
+pub const WRITE_HOOK_DISCRIMINATOR: [u8; 8] = *b"cus_hook";
+/// Header size of a write hook call: discriminator(8) + authority_aux_sequence(8) + count(1)
+pub const WRITE_HOOK_HEADER_SIZE: usize = 8 + 8 + 1;
+/// Max size of a write hook call: header(17) + 128 runs of `[first:1][last:1]` = 273, as
+/// when every other aux byte changed.
+pub const WRITE_HOOK_MAX_SIZE: usize = WRITE_HOOK_HEADER_SIZE + AUX_DATA_SIZE;
+
+/// Encode the write hook call for an authority aux write into `out`, returning its length.
+///
+/// Layout: `[WRITE_HOOK_DISCRIMINATOR:8][authority_aux_sequence:8][count:1]` followed by
+/// `count` runs of `[first:1][last:1]`, the inclusive bounds of each maximal run of bytes
+/// that differ between `before` and `after`, in ascending order. Bytes rewritten with
+/// their old value are not reported.
+pub fn write_hook_data(
+    sequence: u64,
+    before: &[u8; AUX_DATA_SIZE],
+    after: &[u8; AUX_DATA_SIZE],
+    out: &mut [u8; WRITE_HOOK_MAX_SIZE],
+) -> usize {
+    out[..8].copy_from_slice(&WRITE_HOOK_DISCRIMINATOR);
+    out[8..16].copy_from_slice(&sequence.to_le_bytes());
+    let mut count = 0u8;
+    let mut len = WRITE_HOOK_HEADER_SIZE;
+    let mut i = 0;
+    while i < AUX_DATA_SIZE {
+        if before[i] == after[i] {
+            i += 1;
+            continue;
+        }
+        let first = i;
+        while i < AUX_DATA_SIZE && before[i] != after[i] {
+            i += 1;
+        }
+        out[len] = first as u8;
+        out[len + 1] = (i - 1) as u8;
+        len += 2;
+        count += 1;
+    }
+    out[16] = count;
+    len
+}
+
+/// Split write hook call data into `(authority_aux_sequence, changed)`, where `changed`
+/// yields the aux byte ranges the authority's write changed, as half-open ranges.
+///
+/// Returns `None` unless `data` starts with [`WRITE_HOOK_DISCRIMINATOR`], holds exactly
+/// the runs its count announces, and every run ends at or after its start.
+pub fn split_write_hook(
+    data: &[u8],
+) -> Option<(u64, impl Iterator<Item = core::ops::Range<usize>> + '_)> {
+    if data.len() < WRITE_HOOK_HEADER_SIZE || data[..8] != WRITE_HOOK_DISCRIMINATOR {
+        return None;
+    }
+    let sequence = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    let runs = &data[WRITE_HOOK_HEADER_SIZE..];
+    if runs.len() != 2 * data[16] as usize || runs.chunks_exact(2).any(|run| run[0] > run[1]) {
+        return None;
+    }
+    let changed = runs
+        .chunks_exact(2)
+        .map(|run| run[0] as usize..run[1] as usize + 1);
+    Some((sequence, changed))
+}
+
+/// Wrap serialized slow-path `instruction` data behind [`VERSIONED_TAG`] and `version`.
+pub fn versioned(version: u8, instruction: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(VERSIONED_HEADER_SIZE + instruction.len());
+    data.extend_from_slice(&VERSIONED_TAG.to_le_bytes());
+    data.push(version);
+    data.extend_from_slice(instruction);
+    data
+}
+
+/// Split versioned instruction data into `(version, instruction)`.
+///
+/// Returns `None` unless `data` starts with [`VERSIONED_TAG`] and holds the version byte.
+/// The version is not checked; see [`is_supported_version`].
+pub fn split_versioned(data: &[u8]) -> Option<(u8, &[u8])> {
+    if data.len() < VERSIONED_HEADER_SIZE || data[..4] != VERSIONED_TAG.to_le_bytes() {
+        return None;
+    }
+    Some((data[4], &data[VERSIONED_HEADER_SIZE..]))
+}
+
+/// Read `SplitUpdate` instruction data, bare or wrapped in a supported version, as
+/// `(type_hash, sequence, part, payload)`.
+///
+/// Returns `None` for any other instruction, or data shorter than
+/// [`SPLIT_UPDATE_HEADER_SIZE`]. `part` is not checked.
+pub fn read_split_update(data: &[u8]) -> Option<(u64, u64, u8, &[u8])> {
+    let data = match split_versioned(data) {
+        Some((version, inner)) if is_supported_version(version) => inner,
+        Some(_) => return None,
+        None => data,
+    };
+    if data.len() < SPLIT_UPDATE_HEADER_SIZE || data[..4] != SPLIT_UPDATE_TAG.to_le_bytes() {
+        return None;
+    }
+    let type_hash = u64::from_le_bytes(data[4..12].try_into().unwrap());
+    let sequence = u64::from_le_bytes(data[12..20].try_into().unwrap());
+    Some((
+        type_hash,
+        sequence,
+        data[20],
+        &data[SPLIT_UPDATE_HEADER_SIZE..],
+    ))
+}
+
+/// Whether a program built from this crate decodes instructions of `version`: `1` through
+/// [`INSTRUCTION_VERSION`]. Version 0 is never valid.
+pub const fn is_supported_version(version: u8) -> bool {
+    version >= 1 && version <= INSTRUCTION_VERSION
+}
+
 /// A single write operation: write `data` at byte `offset` within the auxiliary buffer.
 #[derive(Debug, Clone, SchemaWrite, SchemaRead)]
 pub struct WriteSpec {
@@ -46,6 +358,198 @@ pub struct WriteSpec {
     pub data: Vec<u8>,
 }
 
+impl WriteSpec {
+    /// One past the last byte written.
+    pub fn end(&self) -> usize {
+        self.offset as usize + self.data.len()
+    }
+}
+
+/// A single write operation with a 16-bit offset, carried by the `*V2` multi-range
+/// updates: write `data` at byte `offset` within the auxiliary buffer.
+///
+/// A [`WriteSpec`] offset cannot name a byte past 255, so it cannot outgrow the current
+/// 256-byte aux region. This one can, and the V2 variants bound it explicitly instead:
+/// [`SlowPathInstruction::validate`] rejects a range that is empty or ends past
+/// [`AUX_DATA_SIZE`].
+#[derive(Debug, Clone, SchemaWrite, SchemaRead)]
+pub struct WriteSpecV2 {
+    pub offset: u16,
+    pub data: Vec<u8>,
+}
+
+impl WriteSpecV2 {
+    /// One past the last byte written.
+    pub fn end(&self) -> usize {
+        self.offset as usize + self.data.len()
+    }
+}
+
+impl From<WriteSpec> for WriteSpecV2 {
+    fn from(spec: WriteSpec) -> Self {
+        Self {
+            offset: spec.offset as u16,
+            data: spec.data,
+        }
+    }
+}
+
+/// The bytes a write range covers, so range checks and the program's apply loop serve
+/// both [`WriteSpec`] and [`WriteSpecV2`].
+pub trait WriteRange {
+    /// First byte written.
+    fn start(&self) -> usize;
+    /// The bytes written from [`start`](Self::start).
+    fn bytes(&self) -> &[u8];
+}
+
+impl WriteRange for WriteSpec {
+    fn start(&self) -> usize {
+        self.offset as usize
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl WriteRange for WriteSpecV2 {
+    fn start(&self) -> usize {
+        self.offset as usize
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// `true` if two of `ranges` write the same byte, in any order. Empty ranges overlap
+/// nothing.
+///
+/// The multi-range updates reject overlapping ranges: which write lands last would depend
+/// on range order.
+pub fn ranges_overlap<R: WriteRange>(ranges: &[R]) -> bool {
+    ranges.iter().enumerate().any(|(i, a)| {
+        ranges[i + 1..].iter().any(|b| {
+            !a.bytes().is_empty()
+                && !b.bytes().is_empty()
+                && a.start() < b.start() + b.bytes().len()
+                && b.start() < a.start() + a.bytes().len()
+        })
+    })
+}
+
+/// The checks every multi-range variant shares: at least one range and no more than
+/// `MAX_AUX_STRUCT_SIZE`, none empty, and no two overlapping.
+fn valid_write_ranges<R: WriteRange>(ranges: &[R]) -> bool {
+    if ranges.is_empty() || ranges.len() > MAX_AUX_STRUCT_SIZE {
+        return false;
+    }
+    // Past the cap the program fails with TOO_MANY_RANGES_ERROR instead, without paying for
+    // the pairwise overlap check.
+    ranges.iter().all(|spec| !spec.bytes().is_empty())
+        && (ranges.len() > MAX_WRITE_RANGES || !ranges_overlap(ranges))
+}
+
+/// Canonical form of `ranges`: sorted by offset, with each range that starts where the
+/// previous one ends merged into it, and empty ranges dropped. The result writes the same
+/// bytes as `ranges` with the fewest mask checks and copies.
+///
+/// Returns `None` if two ranges overlap (see [`ranges_overlap`]).
+pub fn normalize_ranges(ranges: &[WriteSpec]) -> Option<Vec<WriteSpec>> {
+    if ranges_overlap(ranges) {
+        return None;
+    }
+    let mut sorted: Vec<&WriteSpec> = ranges.iter().filter(|spec| !spec.data.is_empty()).collect();
+    sorted.sort_unstable_by_key(|spec| spec.offset);
+    let mut normalized: Vec<WriteSpec> = Vec::with_capacity(sorted.len());
+    for spec in sorted {
+        match normalized.last_mut() {
+            Some(last) if last.end() == spec.offset as usize => {
+                last.data.extend_from_slice(&spec.data)
+            }
+            _ => normalized.push(spec.clone()),
+        }
+    }
+    Some(normalized)
+}
+
+/// A guarded auxiliary range: writes by the delegated program that change any byte in
+/// `[offset, offset + len)` require a top-level instruction from `program_id`.
+#[derive(Debug, Clone, Copy, SchemaWrite, SchemaRead)]
+pub struct RangeGuardSpec {
+    pub program_id: [u8; 32],
+    pub offset: u16,
+    pub len: u16,
+}
+
+/// One entry of `SetConstraints`, stored on-chain as a [`Constraint`]. The fields have the
+/// same meaning as the [`Constraint`] fields of the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SchemaWrite, SchemaRead)]
+pub struct ConstraintSpec {
+    pub region: u8,
+    pub op: u8,
+    pub width: u8,
+    pub signed: bool,
+    pub offset: u8,
+    pub rhs_kind: u8,
+    pub rhs_offset: u8,
+    pub value: u64,
+}
+
+impl From<ConstraintSpec> for Constraint {
+    fn from(spec: ConstraintSpec) -> Self {
+        Constraint {
+            region: spec.region,
+            op: spec.op,
+            width: spec.width,
+            signed: spec.signed as u8,
+            offset: spec.offset,
+            rhs_kind: spec.rhs_kind,
+            rhs_offset: spec.rhs_offset,
+            _padding: 0,
+            value: U64Le::new(spec.value),
+        }
+    }
+}
+
+impl From<Constraint> for ConstraintSpec {
+    fn from(constraint: Constraint) -> Self {
+        ConstraintSpec {
+            region: constraint.region,
+            op: constraint.op,
+            width: constraint.width,
+            signed: constraint.signed != 0,
+            offset: constraint.offset,
+            rhs_kind: constraint.rhs_kind,
+            rhs_offset: constraint.rhs_offset,
+            value: constraint.value.get(),
+        }
+    }
+}
+
+/// One envelope's oracle update within a batch (`BATCH_UPDATE_TAG`), in account order.
+///
+/// Encoded as `[oracle_meta:8][sequence:8][len:1][payload:len]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchEntry<'a> {
+    /// Must match the envelope's `oracle_state.oracle_metadata`, as on the fast path.
+    pub oracle_meta: u64,
+    /// Must be strictly greater than the envelope's stored sequence.
+    pub sequence: u64,
+    /// Written to the start of the envelope's oracle data (≤ `ORACLE_BYTES`).
+    pub payload: &'a [u8],
+}
+
+/// One member's oracle update within a `GroupCommit`, in group member order.
+#[derive(Debug, Clone, SchemaWrite, SchemaRead)]
+pub struct GroupMemberUpdate {
+    /// Must match the member's `oracle_state.oracle_metadata`, as on the fast path.
+    pub oracle_meta: u64,
+    /// Written to the start of the member's oracle data (≤ `ORACLE_BYTES`).
+    pub payload: Vec<u8>,
+}
+
 /// Instruction enum for slow-path operations on a c_u_soon oracle account.
 ///
 /// Write mask encoding: `0x00` = writable, `0xFF` = blocked. Only canonical values
@@ -58,15 +562,129 @@ pub struct WriteSpec {
 ///   and `bump` identify the PDA address. `oracle_metadata` is the packed `StructMetadata`
 ///   for the oracle's auxiliary type.
 /// - `Close`: deallocates the oracle account and returns lamports to the authority.
-///   Blocked while delegation is active.
+///   Blocked while delegation is active or the envelope is permanent.
 /// - `SetDelegatedProgram`: assigns write permissions to a delegated program.
 ///   `program_bitmask` limits what the delegate can write; `user_bitmask` limits what
 ///   the authority can write while delegation is in effect.
 /// - `ClearDelegation`: removes the delegated program and zeros the oracle state.
+/// - `UpdateAuxiliaryMultiRangeForce`: the ranged counterpart of `UpdateAuxiliaryForce`.
+///   Both the authority and the delegation authority sign; the listed ranges are written
+///   without bitmask checks and both aux sequence counters are set, so drifted counters can
+///   be recovered without rewriting the whole aux buffer.
+/// - `SetRangeGuards`: replaces the envelope's range allow-list (≤ `MAX_RANGE_GUARDS`
+///   entries). An empty list disables guarding. Blocked while delegation is active.
+/// - `SetDelegateSchema`: the delegated program registers the sub-struct type
+///   (`metadata`) it writes at `offset`. The span must lie within the program-writable
+///   region of `program_bitmask`.
+/// - `SetPermanent`: irreversibly marks the envelope permanent, so it can never be closed.
+/// - `SetMutationGuard`: with `allow_multi: false`, oracle, aux, and heartbeat writes fail
+///   if the envelope was already written in the transaction, by another top-level
+///   instruction or an earlier CPI. `allow_multi: true` lifts the restriction (the default
+///   for new envelopes).
+/// - `SetGroupMembers`: creates or updates the authority's `GroupAnchor` for `group_id`;
+///   the member envelopes are the trailing accounts (1 to `MAX_GROUP_MEMBERS`).
+/// - `GroupCommit`: writes one oracle payload to every group member under a shared
+///   `sequence`, recorded on the anchor so consumers can match a basket to one batch.
+/// - `SetLabel`: writes the envelope's human-readable label (UTF-8, zero-padded to
+///   `LABEL_SIZE`) into its label PDA. Allowed once per envelope.
+/// - `AssertAuxHash`: read-only. Fails unless the SHA-256 of the whole auxiliary region equals
+///   `expected`, so a composed transaction aborts if aux state changed since it was read.
+/// - `ReadOracle`: read-only. Returns the oracle metadata, sequence, and payload as return
+///   data (see [`split_read_oracle`]).
+/// - `ReadAux`: read-only. Returns the auxiliary metadata and all aux bytes as return data
+///   (see [`split_read_aux`]).
+/// - `ReadAuxRange`: read-only. Returns `auxiliary_data[offset..offset + len]` as return
+///   data, so a caller holding the envelope readonly can fetch one field by offset.
+/// - `Resize`: reallocs the envelope to carry an extended auxiliary region of `ext_len`
+///   bytes typed by `type_hash` (`ext_len: 0` removes it). The authority pays or is refunded
+///   the rent difference.
+/// - `UpdateExtAux`: the authority writes `data` at `offset` in the extended region under a
+///   strictly increasing `sequence`.
+/// - `SetTwap`: with `enabled: true`, every oracle write also folds its first 8 payload
+///   bytes into a time-weighted average accumulator kept in the tail of the oracle region
+///   (see `TwapAccumulator`). `enabled: false` stops it.
+/// - `SetUpdateStamp`: with `enabled: true`, every oracle write also records the `Clock`
+///   slot and unix timestamp at the end of the oracle region (see `UpdateStamp`).
+///   `enabled: false` stops it.
+/// - `SetConstraints`: replaces the envelope's constraint table (≤ `MAX_CONSTRAINTS`
+///   entries), checked on every later write to a region it names. An empty list disables
+///   checking. Changing auxiliary constraints is blocked while delegation is active.
+/// - `SetPublishers`: replaces the envelope's publisher set (≤ `MAX_PUBLISHERS` distinct
+///   addresses) and the number of them that must sign a `PublisherUpdate`. An empty list
+///   with `threshold: 0` disables publisher writes.
+/// - `SetDelegatedPda`: as `SetDelegatedProgram`, but the delegate is the PDA of
+///   `program_id` at `[...seeds, bump]`, checked by derivation instead of a signature, so a
+///   program can be made delegate without signing for its PDA. Delegated writes still need
+///   the PDA's signature, which the program supplies through `invoke_signed`.
+/// - `CloseWithSplit`: as `Close`, but `treasury_bps` (≤ `MAX_TREASURY_BPS`) of the
+///   lamports go to a treasury account and the rest to the recipient.
+/// - `SetConflation`: with `enabled: true`, every oracle write also folds its first 8
+///   payload bytes into a window of the lowest and highest value and the write count within
+///   the current slot (see `ConflationWindow`). `enabled: false` stops it.
+/// - `SetReaders`: replaces the envelope's reader allowlist (≤ `MAX_READERS` distinct program
+///   ids). A non-empty list gates oracle reads: `ReadOracle` is refused and only listed
+///   programs get the oracle through `ReadOracleGated`. An empty list lifts the gate.
+/// - `ReadOracleGated`: read-only. As `ReadOracle`, but only when the transaction's current
+///   top-level instruction targets a program in the envelope's reader list.
+/// - `Aggregate`: writes the lower median of several source envelopes' oracle values into
+///   the envelope under `sequence`, after dropping sources more than `max_deviation_bps`
+///   from the median of all of them (see `c_u_soon::median_within`). Fails unless at least
+///   `min_sources` remain.
+/// - `SetPublisher`: sets the envelope's publisher key, which may write the oracle through
+///   the fast path and `BatchUpdate` in place of the authority but cannot administer the
+///   envelope. A zero key removes it. Unrelated to the `SetPublishers` publisher set.
+/// - `SetPaused`: pauses or resumes the envelope. While paused, the fast path and every
+///   oracle and aux write instruction fail with [`ENVELOPE_PAUSED_ERROR`]; reads, admin
+///   instructions, and delegation state are unaffected.
+/// - `CreateWithEvents`: as `Create`, but the new envelope logs a `c_u_soon::OracleEvent`
+///   on every fast-path write. The setting is fixed for the envelope's lifetime.
+/// - `SealAuxiliaryRange`: irreversibly seals `auxiliary_data[offset..offset + len]` at its
+///   current value, marking it `c_u_soon::SEALED_MASK_BYTE` in both masks. No later write,
+///   forced or delegated, and no `ClearDelegation` may change a sealed byte, and replacing
+///   the masks keeps it sealed. Meant for configuration written once before sealing.
+/// - `InitConfig`: creates the singleton `c_u_soon::ProgramConfig` account, naming the
+///   protocol `admin` and the lamport fee charged per created envelope. Only the program's
+///   upgrade authority may call it, once.
+/// - `SetConfig`: the config admin replaces the admin, the creation fee, and the pause flag,
+///   and may sweep collected fees. While paused, every state-changing instruction but
+///   these two fails with [`PROGRAM_PAUSED_ERROR`] (see [`requires_trailing_config`]).
+/// - `Heartbeat`: permissionless. Records the current slot in the envelope's
+///   `heartbeat_slot` if its oracle is still at `sequence`, so consumers can tell an
+///   unchanged value from a publisher that stopped. Writes nothing else and leaves the
+///   sequence alone.
+/// - `TransferDelegation`: the authority, the current delegate, and the new delegate all
+///   sign to replace the delegate and both bitmasks in one step. Unlike `ClearDelegation`
+///   followed by `SetDelegatedProgram`, the oracle and aux state are kept.
+/// - `SetDelegatedProgramCompact`: as `SetDelegatedProgram`, with each bitmask packed one
+///   bit per aux byte (`c_u_soon::CompactMask`, set = writable) in 32 bytes instead of 256.
+/// - `ExportState`: read-only. Returns the current slot and the SHA-256 of the whole
+///   `Envelope` as return data (see [`split_export_state`]), so the state can be anchored
+///   on-chain without fetching the account.
+/// - `SetWriteHook`: the authority and the delegate both sign. With
+///   `notify_on_authority_write: true`, stores the hook program passed as the fourth account
+///   in the envelope's `write_hook`; every later authority aux write then calls it with the
+///   changed byte ranges (see [`write_hook_data`]) and fails if it fails. `false` removes the
+///   hook. `ClearDelegation` and `TransferDelegation` also remove it.
+/// - `SetOracleMetadata`: the authority re-types the envelope without closing it. Each
+///   metadata that differs from the stored one replaces it and zeroes its region; the
+///   sequences are kept, so writes signed before the change cannot be replayed. Blocked
+///   while a delegation is active.
+/// - `UpdateAuxiliaryMultiRangeV2`, `UpdateAuxiliaryDelegatedMultiRangeV2`,
+///   `UpdateAuxiliaryMultiRangeForceV2`: the multi-range updates with [`WriteSpecV2`]
+///   ranges, whose `u16` offsets leave room for a larger aux region. Accounts, checks, and
+///   effects are those of the V1 variant; the V1 variants keep their wire format.
+/// - `MigrateEnvelope`: permissionless. Grows an envelope created before `publisher`,
+///   `heartbeat_slot`, `write_hook`, and `mutation_marker` were added (shorter than
+///   `Envelope::SIZE`, at least `c_u_soon::LEGACY_ENVELOPE_SIZE`) to the current size,
+///   zeroing the new fields. The payer tops up the rent.
+///
+/// Tag 48 is reserved. Tag 35 ([`VERSIONED_TAG`]) wraps another instruction with the
+/// version it was built for and is not a variant.
 ///
-/// Update variants (tags 4-6) use a manual wire format (not wincode) for
+/// Update variants (tags 4-8, 15, 25, 32, and 56) use a manual wire format (not wincode) for
 /// variable-length data; see `UPDATE_AUX_TAG`, `UPDATE_AUX_DELEGATED_TAG`,
-/// `UPDATE_AUX_FORCE_TAG`.
+/// `UPDATE_AUX_FORCE_TAG`, `UPDATE_AUX_DELEGATED_SCHEMA_TAG`, `BATCH_UPDATE_TAG`,
+/// `PUBLISHER_UPDATE_TAG`, and `SPLIT_UPDATE_TAG`.
 #[derive(Debug, Clone, SchemaWrite, SchemaRead)]
 pub enum SlowPathInstruction {
     #[wincode(tag = 0)]
@@ -96,20 +714,202 @@ pub enum SlowPathInstruction {
         sequence: u64,
         ranges: Vec<WriteSpec>,
     },
+    #[wincode(tag = 11)]
+    UpdateAuxiliaryMultiRangeForce {
+        metadata: u64,
+        authority_sequence: u64,
+        program_sequence: u64,
+        ranges: Vec<WriteSpec>,
+    },
+    #[wincode(tag = 12)]
+    SetDelegatedProgramCompact {
+        program_bitmask: [u8; COMPACT_MASK_SIZE],
+        user_bitmask: [u8; COMPACT_MASK_SIZE],
+    },
+    #[wincode(tag = 13)]
+    SetRangeGuards {
+        bump: u8,
+        entries: Vec<RangeGuardSpec>,
+    },
+    #[wincode(tag = 14)]
+    SetDelegateSchema { bump: u8, offset: u8, metadata: u64 },
+    #[wincode(tag = 16)]
+    SetPermanent,
+    #[wincode(tag = 17)]
+    SetMutationGuard { allow_multi: bool },
+    #[wincode(tag = 18)]
+    ReadAuxRange { offset: u16, len: u16 },
+    #[wincode(tag = 19)]
+    SetGroupMembers { group_id: u64, bump: u8 },
+    #[wincode(tag = 20)]
+    GroupCommit {
+        sequence: u64,
+        updates: Vec<GroupMemberUpdate>,
+    },
+    #[wincode(tag = 21)]
+    SetLabel { bump: u8, label: [u8; LABEL_SIZE] },
+    #[wincode(tag = 22)]
+    AssertAuxHash { expected: [u8; 32] },
+    #[wincode(tag = 23)]
+    ReadOracle,
+    #[wincode(tag = 24)]
+    ReadAux,
+    #[wincode(tag = 26)]
+    Resize { type_hash: u64, ext_len: u16 },
+    #[wincode(tag = 27)]
+    UpdateExtAux {
+        sequence: u64,
+        offset: u16,
+        data: Vec<u8>,
+    },
+    #[wincode(tag = 28)]
+    SetTwap { enabled: bool },
+    #[wincode(tag = 29)]
+    SetUpdateStamp { enabled: bool },
+    #[wincode(tag = 30)]
+    SetConstraints {
+        bump: u8,
+        constraints: Vec<ConstraintSpec>,
+    },
+    #[wincode(tag = 31)]
+    SetPublishers {
+        bump: u8,
+        threshold: u8,
+        publishers: Vec<[u8; 32]>,
+    },
+    #[wincode(tag = 33)]
+    SetDelegatedPda {
+        program_id: [u8; 32],
+        seeds: Vec<Vec<u8>>,
+        bump: u8,
+        program_bitmask: [u8; MASK_SIZE],
+        user_bitmask: [u8; MASK_SIZE],
+    },
+    #[wincode(tag = 34)]
+    CloseWithSplit { treasury_bps: u16 },
+    #[wincode(tag = 36)]
+    SetConflation { enabled: bool },
+    #[wincode(tag = 37)]
+    SetReaders { bump: u8, readers: Vec<[u8; 32]> },
+    #[wincode(tag = 38)]
+    ReadOracleGated,
+    #[wincode(tag = 39)]
+    Aggregate {
+        sequence: u64,
+        min_sources: u8,
+        max_deviation_bps: u16,
+    },
+    #[wincode(tag = 40)]
+    SetPublisher { publisher: [u8; 32] },
+    #[wincode(tag = 41)]
+    SetPaused { paused: bool },
+    #[wincode(tag = 42)]
+    CreateWithEvents {
+        custom_seeds: Vec<Vec<u8>>,
+        bump: u8,
+        oracle_metadata: u64,
+    },
+    #[wincode(tag = 43)]
+    SealAuxiliaryRange { offset: u16, len: u16 },
+    #[wincode(tag = 44)]
+    InitConfig {
+        bump: u8,
+        admin: [u8; 32],
+        create_fee: u64,
+    },
+    #[wincode(tag = 45)]
+    SetConfig {
+        admin: [u8; 32],
+        create_fee: u64,
+        paused: bool,
+    },
+    #[wincode(tag = 46)]
+    Heartbeat { sequence: u64 },
+    #[wincode(tag = 47)]
+    TransferDelegation {
+        program_bitmask: [u8; MASK_SIZE],
+        user_bitmask: [u8; MASK_SIZE],
+    },
+    #[wincode(tag = 49)]
+    ExportState,
+    #[wincode(tag = 50)]
+    SetWriteHook { notify_on_authority_write: bool },
+    #[wincode(tag = 51)]
+    SetOracleMetadata {
+        oracle_metadata: u64,
+        auxiliary_metadata: u64,
+    },
+    #[wincode(tag = 52)]
+    UpdateAuxiliaryMultiRangeV2 {
+        metadata: u64,
+        sequence: u64,
+        ranges: Vec<WriteSpecV2>,
+    },
+    #[wincode(tag = 53)]
+    UpdateAuxiliaryDelegatedMultiRangeV2 {
+        metadata: u64,
+        sequence: u64,
+        ranges: Vec<WriteSpecV2>,
+    },
+    #[wincode(tag = 54)]
+    UpdateAuxiliaryMultiRangeForceV2 {
+        metadata: u64,
+        authority_sequence: u64,
+        program_sequence: u64,
+        ranges: Vec<WriteSpecV2>,
+    },
+    #[wincode(tag = 55)]
+    MigrateEnvelope,
 }
 
 impl SlowPathInstruction {
     /// Returns `false` if the instruction contains invalid fields.
     ///
-    /// - `Create`: rejects if `custom_seeds.len() > MAX_CUSTOM_SEEDS` or any seed is > 32 bytes.
-    /// - `SetDelegatedProgram`: rejects if any byte in either bitmask is not `0x00` or `0xFF`.
-    /// - `Close` and `ClearDelegation` always return `true`.
+    /// - `Create`, `CreateWithEvents`: rejects if `custom_seeds.len() > MAX_CUSTOM_SEEDS` or
+    ///   any seed is > 32 bytes.
+    /// - `SetDelegatedProgram`, `TransferDelegation`: reject if any byte in either bitmask is
+    ///   not `0x00` or `0xFF`.
+    /// - `SetDelegatedPda`: as `SetDelegatedProgram`, and also rejects more than
+    ///   `MAX_DELEGATE_SEEDS` seeds or any seed longer than 32 bytes.
+    /// - `UpdateAuxiliaryMultiRange`, `UpdateAuxiliaryDelegatedMultiRange`,
+    ///   `UpdateAuxiliaryMultiRangeForce`: rejects no ranges, an empty range, or two ranges
+    ///   that overlap (see [`ranges_overlap`]).
+    /// - `UpdateAuxiliaryMultiRangeV2`, `UpdateAuxiliaryDelegatedMultiRangeV2`,
+    ///   `UpdateAuxiliaryMultiRangeForceV2`: as their V1 counterparts, and also reject a
+    ///   range that ends past `AUX_DATA_SIZE`.
+    /// - `SetRangeGuards`: rejects if there are more than `MAX_RANGE_GUARDS` entries, or any
+    ///   entry is empty or extends past `AUX_DATA_SIZE`.
+    /// - `SetDelegateSchema`: rejects a zero-size type or a span extending past `AUX_DATA_SIZE`.
+    /// - `ReadAuxRange`, `SealAuxiliaryRange`: reject an empty range or one extending past
+    ///   `AUX_DATA_SIZE`.
+    /// - `GroupCommit`: rejects zero or more than `MAX_GROUP_MEMBERS` updates, or a payload
+    ///   longer than `ORACLE_BYTES`.
+    /// - `SetLabel`: rejects an empty label, a label that is not UTF-8, or zero bytes
+    ///   before the end of the text.
+    /// - `Resize`: rejects `ext_len > MAX_EXT_AUX_SIZE`.
+    /// - `UpdateExtAux`: rejects empty `data` or a write extending past `MAX_EXT_AUX_SIZE`.
+    /// - `SetConstraints`: rejects more than `MAX_CONSTRAINTS` entries, or any entry that
+    ///   is not a valid `Constraint` (see `Constraint::is_valid`).
+    /// - `SetPublishers`: rejects duplicate or zero addresses, more than `MAX_PUBLISHERS`,
+    ///   or a threshold outside `1..=publishers.len()` (see `is_valid_publisher_set`).
+    /// - `CloseWithSplit`: rejects `treasury_bps > MAX_TREASURY_BPS`.
+    /// - `InitConfig`, `SetConfig`: reject a zero `admin`.
+    /// - `SetReaders`: rejects duplicate or zero program ids, or more than `MAX_READERS`
+    ///   (see `is_valid_reader_list`).
+    /// - `Aggregate`: rejects `min_sources` outside `1..=MAX_AGGREGATE_SOURCES`.
+    /// - `Close`, `ClearDelegation`, `SetPermanent`, `SetMutationGuard`, `SetGroupMembers`,
+    ///   `AssertAuxHash`, `ReadOracle`, `ReadAux`, `SetTwap`, `SetUpdateStamp`,
+    ///   `SetConflation`, `ReadOracleGated`, `SetPublisher`, `SetPaused`, `Heartbeat`,
+    ///   `SetDelegatedProgramCompact` (every bit pattern is a canonical mask),
+    ///   `ExportState`, `SetWriteHook`, `SetOracleMetadata`, and `MigrateEnvelope` always
+    ///   return `true`.
     ///
     /// Account-level checks (signer authority, PDA derivation, sequence counters) are
     /// not performed here; those happen in the program handler.
     pub fn validate(&self) -> bool {
         match self {
-            SlowPathInstruction::Create { custom_seeds, .. } => {
+            SlowPathInstruction::Create { custom_seeds, .. }
+            | SlowPathInstruction::CreateWithEvents { custom_seeds, .. } => {
                 if custom_seeds.len() > MAX_CUSTOM_SEEDS {
                     return false;
                 }
@@ -123,18 +923,110 @@ impl SlowPathInstruction {
             SlowPathInstruction::SetDelegatedProgram {
                 program_bitmask,
                 user_bitmask,
+            }
+            | SlowPathInstruction::TransferDelegation {
+                program_bitmask,
+                user_bitmask,
             } => program_bitmask
                 .iter()
                 .chain(user_bitmask.iter())
-                .all(|&b| b == 0x00 || b == 0xFF),
-            SlowPathInstruction::Close | SlowPathInstruction::ClearDelegation => true,
+                .all(|&b| Permission::from_byte(b).is_some()),
+            SlowPathInstruction::SetDelegatedPda {
+                seeds,
+                program_bitmask,
+                user_bitmask,
+                ..
+            } => {
+                seeds.len() <= MAX_DELEGATE_SEEDS
+                    && seeds.iter().all(|seed| seed.len() <= 32)
+                    && program_bitmask
+                        .iter()
+                        .chain(user_bitmask.iter())
+                        .all(|&b| Permission::from_byte(b).is_some())
+            }
+            SlowPathInstruction::Close
+            | SlowPathInstruction::ClearDelegation
+            | SlowPathInstruction::SetPermanent
+            | SlowPathInstruction::SetMutationGuard { .. }
+            | SlowPathInstruction::SetGroupMembers { .. }
+            | SlowPathInstruction::AssertAuxHash { .. }
+            | SlowPathInstruction::ReadOracle
+            | SlowPathInstruction::ReadAux
+            | SlowPathInstruction::SetTwap { .. }
+            | SlowPathInstruction::SetUpdateStamp { .. }
+            | SlowPathInstruction::SetConflation { .. }
+            | SlowPathInstruction::ReadOracleGated
+            | SlowPathInstruction::SetPublisher { .. }
+            | SlowPathInstruction::SetPaused { .. }
+            | SlowPathInstruction::Heartbeat { .. }
+            | SlowPathInstruction::SetDelegatedProgramCompact { .. }
+            | SlowPathInstruction::ExportState
+            | SlowPathInstruction::SetWriteHook { .. }
+            | SlowPathInstruction::SetOracleMetadata { .. }
+            | SlowPathInstruction::MigrateEnvelope => true,
             SlowPathInstruction::UpdateAuxiliaryMultiRange { ranges, .. }
-            | SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange { ranges, .. } => {
-                if ranges.is_empty() || ranges.len() > MAX_AUX_STRUCT_SIZE {
+            | SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange { ranges, .. }
+            | SlowPathInstruction::UpdateAuxiliaryMultiRangeForce { ranges, .. } => {
+                valid_write_ranges(ranges)
+            }
+            SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 { ranges, .. }
+            | SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRangeV2 { ranges, .. }
+            | SlowPathInstruction::UpdateAuxiliaryMultiRangeForceV2 { ranges, .. } => {
+                valid_write_ranges(ranges) && ranges.iter().all(|spec| spec.end() <= AUX_DATA_SIZE)
+            }
+            SlowPathInstruction::SetRangeGuards { entries, .. } => {
+                if entries.len() > MAX_RANGE_GUARDS {
                     return false;
                 }
-                ranges.iter().all(|spec| !spec.data.is_empty())
+                entries.iter().all(|entry| {
+                    entry.len != 0 && entry.offset as usize + entry.len as usize <= AUX_DATA_SIZE
+                })
+            }
+            SlowPathInstruction::SetDelegateSchema {
+                offset, metadata, ..
+            } => {
+                let size = StructMetadata::from_raw(*metadata).type_size() as usize;
+                size != 0 && *offset as usize + size <= AUX_DATA_SIZE
+            }
+            SlowPathInstruction::ReadAuxRange { offset, len }
+            | SlowPathInstruction::SealAuxiliaryRange { offset, len } => {
+                *len != 0 && *offset as usize + *len as usize <= AUX_DATA_SIZE
+            }
+            SlowPathInstruction::GroupCommit { updates, .. } => {
+                !updates.is_empty()
+                    && updates.len() <= MAX_GROUP_MEMBERS
+                    && updates.iter().all(|u| u.payload.len() <= ORACLE_BYTES)
+            }
+            SlowPathInstruction::SetLabel { label, .. } => {
+                let len = label.iter().position(|&b| b == 0).unwrap_or(LABEL_SIZE);
+                len != 0
+                    && label[len..].iter().all(|&b| b == 0)
+                    && core::str::from_utf8(&label[..len]).is_ok()
+            }
+            SlowPathInstruction::Resize { ext_len, .. } => *ext_len as usize <= MAX_EXT_AUX_SIZE,
+            SlowPathInstruction::UpdateExtAux { offset, data, .. } => {
+                !data.is_empty() && *offset as usize + data.len() <= MAX_EXT_AUX_SIZE
             }
+            SlowPathInstruction::SetConstraints { constraints, .. } => {
+                constraints.len() <= MAX_CONSTRAINTS
+                    && constraints
+                        .iter()
+                        .all(|&spec| Constraint::from(spec).is_valid())
+            }
+            SlowPathInstruction::SetPublishers {
+                threshold,
+                publishers,
+                ..
+            } => is_valid_publisher_set(publishers, *threshold),
+            SlowPathInstruction::CloseWithSplit { treasury_bps } => {
+                *treasury_bps <= MAX_TREASURY_BPS
+            }
+            SlowPathInstruction::SetReaders { readers, .. } => is_valid_reader_list(readers),
+            SlowPathInstruction::Aggregate { min_sources, .. } => {
+                (1..=MAX_AGGREGATE_SOURCES).contains(&(*min_sources as usize))
+            }
+            SlowPathInstruction::InitConfig { admin, .. }
+            | SlowPathInstruction::SetConfig { admin, .. } => admin != &[0u8; 32],
         }
     }
 }
@@ -143,6 +1035,33 @@ impl SlowPathInstruction {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_estimated_compute_units() {
+        for len in 0..=ORACLE_BYTES {
+            let expected = if len == c_u_soon::TINY_PAYLOAD_SIZE {
+                FAST_PATH_TINY_COMPUTE_UNITS
+            } else {
+                FAST_PATH_COMPUTE_UNITS
+            };
+            assert_eq!(estimated_fast_path_compute_units(len), expected);
+        }
+        let create = |seeds: usize| SlowPathInstruction::Create {
+            custom_seeds: alloc::vec![alloc::vec![0; 32]; seeds],
+            bump: 255,
+            oracle_metadata: 0,
+        };
+        assert!(estimated_compute_units(&create(2)) > estimated_compute_units(&create(1)));
+        assert!(
+            estimated_compute_units(&create(0))
+                > estimated_compute_units(&SlowPathInstruction::ReadOracle)
+        );
+        // Both only flip a flag; `SetPaused` also checks the program config.
+        assert!(
+            estimated_compute_units(&SlowPathInstruction::SetPaused { paused: true })
+                > estimated_compute_units(&SlowPathInstruction::ReadOracle)
+        );
+    }
+
     #[test]
     fn discriminant_stability() {
         let cases: &[(SlowPathInstruction, u32)] = &[
@@ -185,6 +1104,208 @@ mod tests {
                 },
                 10,
             ),
+            (
+                SlowPathInstruction::UpdateAuxiliaryMultiRangeForce {
+                    metadata: 0,
+                    authority_sequence: 0,
+                    program_sequence: 0,
+                    ranges: alloc::vec![WriteSpec {
+                        offset: 0,
+                        data: alloc::vec![0]
+                    }],
+                },
+                11,
+            ),
+            (
+                SlowPathInstruction::SetRangeGuards {
+                    bump: 0,
+                    entries: alloc::vec![],
+                },
+                13,
+            ),
+            (
+                SlowPathInstruction::SetDelegateSchema {
+                    bump: 0,
+                    offset: 0,
+                    metadata: 0,
+                },
+                14,
+            ),
+            (SlowPathInstruction::SetPermanent, 16),
+            (
+                SlowPathInstruction::SetMutationGuard { allow_multi: true },
+                17,
+            ),
+            (SlowPathInstruction::ReadAuxRange { offset: 0, len: 1 }, 18),
+            (
+                SlowPathInstruction::SetGroupMembers {
+                    group_id: 0,
+                    bump: 0,
+                },
+                19,
+            ),
+            (
+                SlowPathInstruction::GroupCommit {
+                    sequence: 0,
+                    updates: alloc::vec![],
+                },
+                20,
+            ),
+            (
+                SlowPathInstruction::SetLabel {
+                    bump: 0,
+                    label: [0; LABEL_SIZE],
+                },
+                21,
+            ),
+            (SlowPathInstruction::AssertAuxHash { expected: [0; 32] }, 22),
+            (SlowPathInstruction::ReadOracle, 23),
+            (SlowPathInstruction::ReadAux, 24),
+            (
+                SlowPathInstruction::Resize {
+                    type_hash: 0,
+                    ext_len: 0,
+                },
+                26,
+            ),
+            (
+                SlowPathInstruction::UpdateExtAux {
+                    sequence: 0,
+                    offset: 0,
+                    data: alloc::vec![0],
+                },
+                27,
+            ),
+            (SlowPathInstruction::SetTwap { enabled: true }, 28),
+            (SlowPathInstruction::SetUpdateStamp { enabled: true }, 29),
+            (
+                SlowPathInstruction::SetConstraints {
+                    bump: 0,
+                    constraints: alloc::vec![],
+                },
+                30,
+            ),
+            (
+                SlowPathInstruction::SetPublishers {
+                    bump: 0,
+                    threshold: 0,
+                    publishers: alloc::vec![],
+                },
+                31,
+            ),
+            (
+                SlowPathInstruction::SetDelegatedPda {
+                    program_id: [0; 32],
+                    seeds: alloc::vec![],
+                    bump: 0,
+                    program_bitmask: [0; MASK_SIZE],
+                    user_bitmask: [0; MASK_SIZE],
+                },
+                33,
+            ),
+            (SlowPathInstruction::CloseWithSplit { treasury_bps: 0 }, 34),
+            (SlowPathInstruction::SetConflation { enabled: true }, 36),
+            (
+                SlowPathInstruction::SetReaders {
+                    bump: 0,
+                    readers: alloc::vec![],
+                },
+                37,
+            ),
+            (SlowPathInstruction::ReadOracleGated, 38),
+            (
+                SlowPathInstruction::Aggregate {
+                    sequence: 0,
+                    min_sources: 0,
+                    max_deviation_bps: 0,
+                },
+                39,
+            ),
+            (SlowPathInstruction::SetPublisher { publisher: [0; 32] }, 40),
+            (SlowPathInstruction::SetPaused { paused: false }, 41),
+            (
+                SlowPathInstruction::CreateWithEvents {
+                    custom_seeds: alloc::vec![],
+                    bump: 0,
+                    oracle_metadata: 0,
+                },
+                42,
+            ),
+            (
+                SlowPathInstruction::SealAuxiliaryRange { offset: 0, len: 1 },
+                43,
+            ),
+            (
+                SlowPathInstruction::InitConfig {
+                    bump: 0,
+                    admin: [1; 32],
+                    create_fee: 0,
+                },
+                44,
+            ),
+            (
+                SlowPathInstruction::SetConfig {
+                    admin: [1; 32],
+                    create_fee: 0,
+                    paused: false,
+                },
+                45,
+            ),
+            (SlowPathInstruction::Heartbeat { sequence: 0 }, 46),
+            (
+                SlowPathInstruction::TransferDelegation {
+                    program_bitmask: [0; MASK_SIZE],
+                    user_bitmask: [0; MASK_SIZE],
+                },
+                47,
+            ),
+            (
+                SlowPathInstruction::SetDelegatedProgramCompact {
+                    program_bitmask: [0; COMPACT_MASK_SIZE],
+                    user_bitmask: [0; COMPACT_MASK_SIZE],
+                },
+                12,
+            ),
+            (SlowPathInstruction::ExportState, 49),
+            (
+                SlowPathInstruction::SetWriteHook {
+                    notify_on_authority_write: true,
+                },
+                50,
+            ),
+            (
+                SlowPathInstruction::SetOracleMetadata {
+                    oracle_metadata: 0,
+                    auxiliary_metadata: 0,
+                },
+                51,
+            ),
+            (
+                SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 {
+                    metadata: 0,
+                    sequence: 0,
+                    ranges: alloc::vec![],
+                },
+                52,
+            ),
+            (
+                SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRangeV2 {
+                    metadata: 0,
+                    sequence: 0,
+                    ranges: alloc::vec![],
+                },
+                53,
+            ),
+            (
+                SlowPathInstruction::UpdateAuxiliaryMultiRangeForceV2 {
+                    metadata: 0,
+                    authority_sequence: 0,
+                    program_sequence: 0,
+                    ranges: alloc::vec![],
+                },
+                54,
+            ),
+            (SlowPathInstruction::MigrateEnvelope, 55),
         ];
         for (ix, expected_disc) in cases {
             let bytes = wincode::serialize(ix).unwrap();
@@ -198,6 +1319,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_requires_trailing_config() {
+        let tag = |ix: &SlowPathInstruction| {
+            u32::from_le_bytes(wincode::serialize(ix).unwrap()[..4].try_into().unwrap())
+        };
+        let create = SlowPathInstruction::Create {
+            custom_seeds: alloc::vec![],
+            bump: 0,
+            oracle_metadata: 0,
+        };
+        let set_config = SlowPathInstruction::SetConfig {
+            admin: [1; 32],
+            create_fee: 0,
+            paused: false,
+        };
+        for ix in [
+            create,
+            set_config,
+            SlowPathInstruction::ReadOracle,
+            SlowPathInstruction::ReadAux,
+            SlowPathInstruction::ReadOracleGated,
+            SlowPathInstruction::ExportState,
+            SlowPathInstruction::AssertAuxHash { expected: [0; 32] },
+            SlowPathInstruction::ReadAuxRange { offset: 0, len: 1 },
+        ] {
+            assert!(!requires_trailing_config(tag(&ix)), "{ix:?}");
+        }
+        for ix in [
+            SlowPathInstruction::Close,
+            SlowPathInstruction::SetPaused { paused: true },
+            SlowPathInstruction::Heartbeat { sequence: 0 },
+        ] {
+            assert!(requires_trailing_config(tag(&ix)), "{ix:?}");
+        }
+        assert!(requires_trailing_config(UPDATE_AUX_TAG));
+        assert!(requires_trailing_config(BATCH_UPDATE_TAG));
+        assert!(requires_trailing_config(PUBLISHER_UPDATE_TAG));
+        assert!(requires_trailing_config(SPLIT_UPDATE_TAG));
+        assert!(!requires_trailing_config(MAX_SLOW_PATH_TAG + 1));
+    }
+
     #[test]
     fn test_update_aux_tags_match_old_discriminants() {
         assert_eq!(UPDATE_AUX_TAG, 4);
@@ -205,6 +1367,8 @@ mod tests {
         assert_eq!(UPDATE_AUX_FORCE_TAG, 6);
         assert_eq!(UPDATE_AUX_RANGE_TAG, 7);
         assert_eq!(UPDATE_AUX_DELEGATED_RANGE_TAG, 8);
+        assert_eq!(BATCH_UPDATE_TAG, 25);
+        assert_eq!(PUBLISHER_UPDATE_TAG, 32);
     }
 
     #[test]
@@ -212,6 +1376,9 @@ mod tests {
         assert_eq!(UPDATE_AUX_HEADER_SIZE, 20);
         assert_eq!(UPDATE_AUX_FORCE_HEADER_SIZE, 28);
         assert_eq!(UPDATE_AUX_RANGE_HEADER_SIZE, 21);
+        assert_eq!(BATCH_UPDATE_HEADER_SIZE, 5);
+        assert_eq!(BATCH_ENTRY_HEADER_SIZE, 17);
+        assert_eq!(PUBLISHER_UPDATE_HEADER_SIZE, 20);
         assert_eq!(UPDATE_AUX_MAX_SIZE, 275);
         assert_eq!(UPDATE_AUX_FORCE_MAX_SIZE, 283);
         assert_eq!(UPDATE_AUX_RANGE_MAX_SIZE, 276);
@@ -285,6 +1452,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_wincode_roundtrip_update_aux_multi_range_force() {
+        let ix = SlowPathInstruction::UpdateAuxiliaryMultiRangeForce {
+            metadata: 0x1234,
+            authority_sequence: 7,
+            program_sequence: 8,
+            ranges: alloc::vec![WriteSpec {
+                offset: 3,
+                data: alloc::vec![0xCC; 2]
+            }],
+        };
+        let serialized = wincode::serialize(&ix).unwrap();
+        let disc = u32::from_le_bytes(serialized[..4].try_into().unwrap());
+        assert_eq!(disc, 11);
+        let deserialized: SlowPathInstruction = wincode::deserialize(&serialized).unwrap();
+        match deserialized {
+            SlowPathInstruction::UpdateAuxiliaryMultiRangeForce {
+                metadata,
+                authority_sequence,
+                program_sequence,
+                ranges,
+            } => {
+                assert_eq!(metadata, 0x1234);
+                assert_eq!(authority_sequence, 7);
+                assert_eq!(program_sequence, 8);
+                assert_eq!(ranges.len(), 1);
+                assert_eq!(ranges[0].offset, 3);
+                assert_eq!(ranges[0].data, alloc::vec![0xCC; 2]);
+            }
+            _ => panic!("Wrong variant"),
+        }
+    }
+
     #[test]
     fn test_validate_multi_range_empty_ranges() {
         let ix = SlowPathInstruction::UpdateAuxiliaryMultiRange {
@@ -321,6 +1521,94 @@ mod tests {
         assert!(ix.validate());
     }
 
+    fn spec(offset: u8, data: &[u8]) -> WriteSpec {
+        WriteSpec {
+            offset,
+            data: data.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_validate_multi_range_rejects_overlap() {
+        let ix = |ranges| SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange {
+            metadata: 0,
+            sequence: 1,
+            ranges,
+        };
+        assert!(ix(alloc::vec![spec(4, &[1; 4]), spec(0, &[2; 4])]).validate());
+        assert!(!ix(alloc::vec![spec(0, &[1; 4]), spec(3, &[2; 4])]).validate());
+        assert!(!ix(alloc::vec![spec(6, &[1]), spec(2, &[2; 8])]).validate());
+        assert!(!ix(alloc::vec![spec(0, &[1; 4]), spec(0, &[2; 4])]).validate());
+    }
+
+    #[test]
+    fn test_validate_multi_range_v2_bounds() {
+        let spec = |offset: u16, len: usize| WriteSpecV2 {
+            offset,
+            data: alloc::vec![1; len],
+        };
+        let ix = |ranges| SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 {
+            metadata: 0,
+            sequence: 1,
+            ranges,
+        };
+        assert!(ix(alloc::vec![spec(0, 4), spec(252, 4)]).validate());
+        assert!(!ix(alloc::vec![spec(253, 4)]).validate());
+        assert!(!ix(alloc::vec![spec(256, 1)]).validate());
+        assert!(!ix(alloc::vec![spec(u16::MAX, 1)]).validate());
+        assert!(!ix(alloc::vec![spec(0, 0)]).validate());
+        assert!(!ix(alloc::vec![]).validate());
+        assert!(!ix(alloc::vec![spec(0, 4), spec(3, 4)]).validate());
+
+        let force = SlowPathInstruction::UpdateAuxiliaryMultiRangeForceV2 {
+            metadata: 0,
+            authority_sequence: 1,
+            program_sequence: 1,
+            ranges: alloc::vec![spec(255, 2)],
+        };
+        assert!(!force.validate());
+    }
+
+    #[test]
+    fn test_write_spec_v2_roundtrip_and_conversion() {
+        let ix = SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRangeV2 {
+            metadata: 0x1234,
+            sequence: 9,
+            ranges: alloc::vec![spec(200, &[7; 3]).into()],
+        };
+        let bytes = wincode::serialize(&ix).unwrap();
+        // disc, metadata, sequence, range count, then the u16 offset.
+        assert_eq!(bytes[28..30], 200u16.to_le_bytes());
+        let SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRangeV2 { ranges, .. } =
+            wincode::deserialize(&bytes).unwrap()
+        else {
+            panic!("Wrong variant");
+        };
+        assert_eq!(ranges[0].offset, 200);
+        assert_eq!(ranges[0].data, alloc::vec![7; 3]);
+        assert_eq!(ranges[0].end(), 203);
+    }
+
+    #[test]
+    fn test_normalize_ranges() {
+        let normalized = normalize_ranges(&[
+            spec(20, &[3; 2]),
+            spec(4, &[2; 4]),
+            spec(0, &[1; 4]),
+            spec(9, &[]),
+            spec(22, &[4]),
+        ])
+        .unwrap();
+        assert_eq!(normalized.len(), 2);
+        assert_eq!(normalized[0].offset, 0);
+        assert_eq!(normalized[0].data, [[1; 4], [2; 4]].concat());
+        assert_eq!(normalized[1].offset, 20);
+        assert_eq!(normalized[1].data, alloc::vec![3, 3, 4]);
+
+        assert!(normalize_ranges(&[spec(0, &[1; 4]), spec(3, &[2])]).is_none());
+        assert!(normalize_ranges(&[]).unwrap().is_empty());
+    }
+
     #[test]
     fn test_manual_wire_format_update_aux() {
         let metadata: u64 = 0xDEAD_BEEF_1234_5678;
@@ -489,4 +1777,623 @@ mod tests {
         let deserialized: SlowPathInstruction = wincode::deserialize(&serialized).unwrap();
         assert!(matches!(deserialized, SlowPathInstruction::ClearDelegation));
     }
+
+    #[test]
+    fn test_wincode_roundtrip_set_range_guards() {
+        let ix = SlowPathInstruction::SetRangeGuards {
+            bump: 254,
+            entries: alloc::vec![
+                RangeGuardSpec {
+                    program_id: [7u8; 32],
+                    offset: 16,
+                    len: 8,
+                },
+                RangeGuardSpec {
+                    program_id: [9u8; 32],
+                    offset: 200,
+                    len: 56,
+                },
+            ],
+        };
+        let serialized = wincode::serialize(&ix).unwrap();
+        let deserialized: SlowPathInstruction = wincode::deserialize(&serialized).unwrap();
+        match deserialized {
+            SlowPathInstruction::SetRangeGuards { bump, entries } => {
+                assert_eq!(bump, 254);
+                assert_eq!(entries.len(), 2);
+                assert_eq!(entries[0].program_id, [7u8; 32]);
+                assert_eq!((entries[0].offset, entries[0].len), (16, 8));
+                assert_eq!(entries[1].program_id, [9u8; 32]);
+                assert_eq!((entries[1].offset, entries[1].len), (200, 56));
+            }
+            _ => panic!("Wrong variant"),
+        }
+    }
+
+    #[test]
+    fn test_validate_set_range_guards() {
+        let guard = |offset, len| RangeGuardSpec {
+            program_id: [1u8; 32],
+            offset,
+            len,
+        };
+
+        let ix = SlowPathInstruction::SetRangeGuards {
+            bump: 0,
+            entries: alloc::vec![],
+        };
+        assert!(ix.validate(), "empty list clears guards");
+
+        let ix = SlowPathInstruction::SetRangeGuards {
+            bump: 0,
+            entries: alloc::vec![guard(0, 256)],
+        };
+        assert!(ix.validate());
+
+        let ix = SlowPathInstruction::SetRangeGuards {
+            bump: 0,
+            entries: alloc::vec![guard(10, 0)],
+        };
+        assert!(!ix.validate(), "zero-length range");
+
+        let ix = SlowPathInstruction::SetRangeGuards {
+            bump: 0,
+            entries: alloc::vec![guard(250, 7)],
+        };
+        assert!(!ix.validate(), "range past AUX_DATA_SIZE");
+
+        let ix = SlowPathInstruction::SetRangeGuards {
+            bump: 0,
+            entries: alloc::vec![guard(0, 1); MAX_RANGE_GUARDS + 1],
+        };
+        assert!(!ix.validate(), "too many entries");
+    }
+
+    #[test]
+    fn test_validate_set_delegate_schema() {
+        let schema = |offset, type_size| SlowPathInstruction::SetDelegateSchema {
+            bump: 0,
+            offset,
+            metadata: StructMetadata::new(type_size, 0x1234).as_u64(),
+        };
+
+        assert!(schema(0, 255).validate());
+        assert!(schema(248, 8).validate());
+        assert!(!schema(249, 8).validate(), "span past AUX_DATA_SIZE");
+        assert!(!schema(16, 0).validate(), "zero-size type");
+    }
+
+    #[test]
+    fn test_validate_read_aux_range() {
+        let read = |offset, len| SlowPathInstruction::ReadAuxRange { offset, len };
+
+        assert!(read(0, 256).validate());
+        assert!(read(255, 1).validate());
+        assert!(!read(0, 0).validate(), "empty range");
+        assert!(!read(200, 57).validate(), "range past AUX_DATA_SIZE");
+        assert!(!read(256, 1).validate(), "offset past AUX_DATA_SIZE");
+        assert!(!read(u16::MAX, u16::MAX).validate(), "no u16 wraparound");
+
+        let seal = |offset, len| SlowPathInstruction::SealAuxiliaryRange { offset, len };
+        assert!(seal(0, 256).validate());
+        assert!(!seal(0, 0).validate(), "empty range");
+        assert!(!seal(200, 57).validate(), "range past AUX_DATA_SIZE");
+    }
+
+    #[test]
+    fn test_validate_group_commit() {
+        let commit = |updates: Vec<GroupMemberUpdate>| SlowPathInstruction::GroupCommit {
+            sequence: 1,
+            updates,
+        };
+        let update = |len| GroupMemberUpdate {
+            oracle_meta: 0,
+            payload: alloc::vec![0; len],
+        };
+
+        assert!(commit(alloc::vec![update(0), update(ORACLE_BYTES)]).validate());
+        assert!(!commit(alloc::vec![]).validate(), "no members");
+        assert!(
+            !commit(alloc::vec![update(ORACLE_BYTES + 1)]).validate(),
+            "payload past ORACLE_BYTES"
+        );
+        assert!(
+            !commit((0..=MAX_GROUP_MEMBERS).map(|_| update(8)).collect()).validate(),
+            "too many members"
+        );
+    }
+
+    #[test]
+    fn test_validate_set_label() {
+        let set_label = |text: &[u8]| {
+            let mut label = [0; LABEL_SIZE];
+            label[..text.len()].copy_from_slice(text);
+            SlowPathInstruction::SetLabel { bump: 0, label }
+        };
+
+        assert!(set_label(b"SOL/USD").validate());
+        assert!(set_label(&[b'x'; LABEL_SIZE]).validate());
+        assert!(!set_label(b"").validate(), "empty");
+        assert!(!set_label(b"SOL\0USD").validate(), "interior zero");
+        assert!(!set_label(&[0xFF, 0xFE]).validate(), "not UTF-8");
+    }
+
+    #[test]
+    fn test_validate_ext_aux() {
+        let resize = |ext_len| SlowPathInstruction::Resize {
+            type_hash: 1,
+            ext_len,
+        };
+        let write = |offset, len| SlowPathInstruction::UpdateExtAux {
+            sequence: 1,
+            offset,
+            data: alloc::vec![0; len],
+        };
+        let max = MAX_EXT_AUX_SIZE as u16;
+
+        assert!(resize(0).validate());
+        assert!(resize(max).validate());
+        assert!(!resize(max + 1).validate(), "past MAX_EXT_AUX_SIZE");
+
+        assert!(write(0, 1).validate());
+        assert!(write(max - 4, 4).validate());
+        assert!(!write(0, 0).validate(), "empty write");
+        assert!(!write(max - 3, 4).validate(), "write past MAX_EXT_AUX_SIZE");
+        assert!(!write(u16::MAX, 1).validate(), "no u16 wraparound");
+    }
+
+    #[test]
+    fn test_validate_set_constraints() {
+        use c_u_soon::{ConstraintOp, ConstraintRegion};
+
+        let ge_zero =
+            Constraint::against_const(ConstraintRegion::Aux, 0, 8, true, ConstraintOp::Ge, 0);
+        let spec = ConstraintSpec::from(ge_zero);
+        assert_eq!(Constraint::from(spec), ge_zero);
+
+        let set = |constraints: &[ConstraintSpec]| SlowPathInstruction::SetConstraints {
+            bump: 0,
+            constraints: constraints.to_vec(),
+        };
+        assert!(set(&[]).validate());
+        assert!(set(&[spec; MAX_CONSTRAINTS]).validate());
+        assert!(!set(&[spec; MAX_CONSTRAINTS + 1]).validate(), "too many");
+        assert!(!set(&[ConstraintSpec { width: 3, ..spec }]).validate());
+        assert!(!set(&[ConstraintSpec {
+            offset: 250,
+            ..spec
+        }])
+        .validate());
+
+        let ix = set(&[spec]);
+        let bytes = wincode::serialize(&ix).unwrap();
+        match wincode::deserialize::<SlowPathInstruction>(&bytes).unwrap() {
+            SlowPathInstruction::SetConstraints { constraints, .. } => {
+                assert_eq!(constraints, [spec]);
+            }
+            other => panic!("unexpected variant: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_validate_set_publishers() {
+        let set = |threshold: u8, publishers: &[[u8; 32]]| SlowPathInstruction::SetPublishers {
+            bump: 0,
+            threshold,
+            publishers: publishers.to_vec(),
+        };
+        assert!(set(0, &[]).validate(), "empty list disables");
+        assert!(set(2, &[[1; 32], [2; 32]]).validate());
+        assert!(
+            !set(3, &[[1; 32], [2; 32]]).validate(),
+            "threshold above count"
+        );
+        assert!(!set(0, &[[1; 32]]).validate(), "zero threshold");
+        assert!(!set(1, &[[1; 32], [1; 32]]).validate(), "duplicate");
+
+        let bytes = wincode::serialize(&set(1, &[[7; 32]])).unwrap();
+        assert_eq!(bytes.len(), 4 + 1 + 1 + 8 + 32);
+        match wincode::deserialize::<SlowPathInstruction>(&bytes).unwrap() {
+            SlowPathInstruction::SetPublishers {
+                threshold,
+                publishers,
+                ..
+            } => assert_eq!((threshold, publishers), (1, alloc::vec![[7; 32]])),
+            other => panic!("unexpected variant: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_validate_set_readers() {
+        let set = |readers: &[[u8; 32]]| SlowPathInstruction::SetReaders {
+            bump: 0,
+            readers: readers.to_vec(),
+        };
+        assert!(set(&[]).validate(), "empty list lifts the gate");
+        assert!(set(&[[1; 32], [2; 32]]).validate());
+        assert!(!set(&[[1; 32], [1; 32]]).validate(), "duplicate");
+        assert!(!set(&[[0; 32]]).validate(), "zero program id");
+
+        let bytes = wincode::serialize(&set(&[[7; 32]])).unwrap();
+        assert_eq!(bytes.len(), 4 + 1 + 8 + 32);
+    }
+
+    #[test]
+    fn test_validate_set_delegated_pda() {
+        let set = |seeds: alloc::vec::Vec<alloc::vec::Vec<u8>>, user_bitmask: [u8; MASK_SIZE]| {
+            SlowPathInstruction::SetDelegatedPda {
+                program_id: [9; 32],
+                seeds,
+                bump: 255,
+                program_bitmask: [0x00; MASK_SIZE],
+                user_bitmask,
+            }
+        };
+        assert!(set(alloc::vec![], [0xFF; MASK_SIZE]).validate());
+        assert!(set(
+            alloc::vec![alloc::vec![1; 32]; MAX_DELEGATE_SEEDS],
+            [0xFF; MASK_SIZE]
+        )
+        .validate());
+        assert!(
+            !set(
+                alloc::vec![alloc::vec![]; MAX_DELEGATE_SEEDS + 1],
+                [0xFF; MASK_SIZE]
+            )
+            .validate(),
+            "too many seeds"
+        );
+        assert!(
+            !set(alloc::vec![alloc::vec![1; 33]], [0xFF; MASK_SIZE]).validate(),
+            "seed too long"
+        );
+        let mut bad = [0xFF; MASK_SIZE];
+        bad[3] = 0x01;
+        assert!(!set(alloc::vec![], bad).validate(), "non-canonical mask");
+
+        let ix = set(alloc::vec![b"delegate".to_vec()], [0xFF; MASK_SIZE]);
+        let bytes = wincode::serialize(&ix).unwrap();
+        match wincode::deserialize::<SlowPathInstruction>(&bytes).unwrap() {
+            SlowPathInstruction::SetDelegatedPda {
+                program_id,
+                seeds,
+                bump,
+                ..
+            } => assert_eq!(
+                (program_id, seeds, bump),
+                ([9; 32], alloc::vec![b"delegate".to_vec()], 255)
+            ),
+            other => panic!("unexpected variant: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_versioned_round_trip() {
+        let inner = wincode::serialize(&SlowPathInstruction::Close).unwrap();
+        let data = versioned(INSTRUCTION_VERSION, &inner);
+        assert_eq!(data[..4], VERSIONED_TAG.to_le_bytes());
+        assert_eq!(data.len(), VERSIONED_HEADER_SIZE + inner.len());
+        assert_eq!(
+            split_versioned(&data),
+            Some((INSTRUCTION_VERSION, inner.as_slice()))
+        );
+
+        assert_eq!(split_versioned(&inner), None);
+        assert_eq!(split_versioned(&data[..4]), None);
+        assert_eq!(
+            split_versioned(&data[..5]),
+            Some((INSTRUCTION_VERSION, &[][..]))
+        );
+    }
+
+    #[test]
+    fn test_supported_versions() {
+        assert!(!is_supported_version(0));
+        assert!(is_supported_version(1));
+        assert!(is_supported_version(INSTRUCTION_VERSION));
+        assert!(!is_supported_version(INSTRUCTION_VERSION + 1));
+    }
+
+    #[test]
+    fn test_versioned_tag_is_not_a_variant() {
+        assert!(wincode::deserialize::<SlowPathInstruction>(&versioned(1, &[])).is_err());
+    }
+
+    #[test]
+    fn test_validate_close_with_split() {
+        let close = |treasury_bps| SlowPathInstruction::CloseWithSplit { treasury_bps };
+        assert!(close(0).validate());
+        assert!(close(MAX_TREASURY_BPS).validate());
+        assert!(!close(MAX_TREASURY_BPS + 1).validate());
+
+        let bytes = wincode::serialize(&close(250)).unwrap();
+        assert_eq!(bytes, [34, 0, 0, 0, 250, 0]);
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let init = |admin| SlowPathInstruction::InitConfig {
+            bump: 255,
+            admin,
+            create_fee: 1_000,
+        };
+        let set = |admin| SlowPathInstruction::SetConfig {
+            admin,
+            create_fee: 0,
+            paused: true,
+        };
+        assert!(init([1; 32]).validate());
+        assert!(!init([0; 32]).validate(), "zero admin");
+        assert!(set([1; 32]).validate());
+        assert!(!set([0; 32]).validate(), "zero admin");
+
+        let bytes = wincode::serialize(&init([1; 32])).unwrap();
+        assert_eq!(bytes.len(), 4 + 1 + 32 + 8);
+        let bytes = wincode::serialize(&set([1; 32])).unwrap();
+        assert_eq!(bytes.len(), 4 + 32 + 8 + 1);
+    }
+
+    #[test]
+    fn test_validate_aggregate() {
+        let aggregate = |min_sources| SlowPathInstruction::Aggregate {
+            sequence: 1,
+            min_sources,
+            max_deviation_bps: 100,
+        };
+        assert!(aggregate(1).validate());
+        assert!(aggregate(MAX_AGGREGATE_SOURCES as u8).validate());
+        assert!(!aggregate(0).validate());
+        assert!(!aggregate(MAX_AGGREGATE_SOURCES as u8 + 1).validate());
+
+        let bytes = wincode::serialize(&aggregate(3)).unwrap();
+        assert_eq!(bytes.len(), 4 + 8 + 1 + 2);
+    }
+
+    #[test]
+    fn test_validate_create_with_events() {
+        let create = |custom_seeds| SlowPathInstruction::CreateWithEvents {
+            custom_seeds,
+            bump: 255,
+            oracle_metadata: 1,
+        };
+        assert!(create(alloc::vec![alloc::vec![0; 32]; MAX_CUSTOM_SEEDS]).validate());
+        assert!(!create(alloc::vec![alloc::vec![]; MAX_CUSTOM_SEEDS + 1]).validate());
+        assert!(!create(alloc::vec![alloc::vec![0; 33]]).validate());
+    }
+
+    #[test]
+    fn test_split_read_returns() {
+        let mut oracle = [0u8; READ_ORACLE_RETURN_SIZE];
+        oracle[..8].copy_from_slice(&StructMetadata::new(8, 3).as_u64().to_le_bytes());
+        oracle[8..16].copy_from_slice(&42u64.to_le_bytes());
+        oracle[16] = 0xAA;
+        let (metadata, sequence, data) = split_read_oracle(&oracle).unwrap();
+        assert_eq!(metadata, StructMetadata::new(8, 3));
+        assert_eq!(sequence, 42);
+        assert_eq!((data.len(), data[0]), (ORACLE_BYTES, 0xAA));
+        assert!(split_read_oracle(&oracle[1..]).is_none());
+
+        let mut aux = [0u8; READ_AUX_RETURN_SIZE];
+        aux[8] = 0xBB;
+        let (metadata, data) = split_read_aux(&aux).unwrap();
+        assert_eq!(metadata, StructMetadata::ZERO);
+        assert_eq!((data.len(), data[0]), (AUX_DATA_SIZE, 0xBB));
+        assert!(split_read_aux(&aux[..READ_AUX_RETURN_SIZE - 1]).is_none());
+
+        let mut export = [0u8; EXPORT_STATE_RETURN_SIZE];
+        export[..8].copy_from_slice(&77u64.to_le_bytes());
+        export[8..].fill(0xCC);
+        assert_eq!(split_export_state(&export), Some((77, [0xCC; 32])));
+        assert!(split_export_state(&export[..EXPORT_STATE_RETURN_SIZE - 1]).is_none());
+    }
+
+    #[test]
+    fn test_write_hook_data_round_trip() {
+        let before = [0u8; AUX_DATA_SIZE];
+        let mut after = before;
+        after[0] = 1;
+        after[10..13].fill(2);
+        after[255] = 3;
+        let mut out = [0u8; WRITE_HOOK_MAX_SIZE];
+        let len = write_hook_data(9, &before, &after, &mut out);
+        assert_eq!(len, WRITE_HOOK_HEADER_SIZE + 6);
+        let (sequence, changed) = split_write_hook(&out[..len]).unwrap();
+        assert_eq!(sequence, 9);
+        assert_eq!(changed.collect::<Vec<_>>(), [0..1, 10..13, 255..256]);
+
+        let len = write_hook_data(1, &before, &before, &mut out);
+        assert_eq!(len, WRITE_HOOK_HEADER_SIZE);
+        assert_eq!(split_write_hook(&out[..len]).unwrap().1.count(), 0);
+
+        let mut alternating = before;
+        for byte in alternating.iter_mut().step_by(2) {
+            *byte = 1;
+        }
+        let len = write_hook_data(1, &before, &alternating, &mut out);
+        assert_eq!(len, WRITE_HOOK_MAX_SIZE);
+        assert_eq!(split_write_hook(&out).unwrap().1.count(), AUX_DATA_SIZE / 2);
+    }
+
+    #[test]
+    fn test_split_write_hook_rejects_malformed() {
+        let mut after = [0u8; AUX_DATA_SIZE];
+        after[4] = 1;
+        let mut out = [0u8; WRITE_HOOK_MAX_SIZE];
+        let len = write_hook_data(1, &[0; AUX_DATA_SIZE], &after, &mut out);
+        assert!(split_write_hook(&out[..len - 1]).is_none());
+        assert!(split_write_hook(&out[..len + 1]).is_none());
+
+        let mut reversed = out;
+        reversed[WRITE_HOOK_HEADER_SIZE] = 5;
+        assert!(split_write_hook(&reversed[..len]).is_none());
+
+        let mut foreign = out;
+        foreign[0] ^= 1;
+        assert!(split_write_hook(&foreign[..len]).is_none());
+    }
+
+    #[test]
+    fn test_parse_manual_wire() {
+        let header = |tag: u32, words: &[u64], tail: &[u8]| {
+            let mut data = tag.to_le_bytes().to_vec();
+            for word in words {
+                data.extend_from_slice(&word.to_le_bytes());
+            }
+            data.extend_from_slice(tail);
+            data
+        };
+
+        let data = header(UPDATE_AUX_TAG, &[9, 2], &[1, 2, 3]);
+        assert!(matches!(
+            SlowPathInstruction::parse(&data),
+            ParsedInstruction::UpdateAuxiliary {
+                metadata: 9,
+                sequence: 2,
+                data: [1, 2, 3]
+            }
+        ));
+        let data = header(UPDATE_AUX_DELEGATED_TAG, &[9, 2], &[]);
+        assert!(matches!(
+            SlowPathInstruction::parse(&data),
+            ParsedInstruction::UpdateAuxiliaryDelegated {
+                metadata: 9,
+                sequence: 2,
+                data: []
+            }
+        ));
+        let data = header(UPDATE_AUX_FORCE_TAG, &[9, 2, 3], &[4]);
+        assert!(matches!(
+            SlowPathInstruction::parse(&data),
+            ParsedInstruction::UpdateAuxiliaryForce {
+                metadata: 9,
+                authority_sequence: 2,
+                program_sequence: 3,
+                data: [4],
+            }
+        ));
+        let data = header(UPDATE_AUX_RANGE_TAG, &[9, 2], &[10, 5, 6]);
+        assert!(matches!(
+            SlowPathInstruction::parse(&data),
+            ParsedInstruction::UpdateAuxiliaryRange {
+                metadata: 9,
+                sequence: 2,
+                offset: 10,
+                data: [5, 6],
+            }
+        ));
+        let data = header(UPDATE_AUX_DELEGATED_RANGE_TAG, &[9, 2], &[10]);
+        assert!(matches!(
+            SlowPathInstruction::parse(&data),
+            ParsedInstruction::UpdateAuxiliaryDelegatedRange {
+                offset: 10,
+                data: [],
+                ..
+            }
+        ));
+        let data = header(UPDATE_AUX_DELEGATED_SCHEMA_TAG, &[9, 2], &[7]);
+        assert!(matches!(
+            SlowPathInstruction::parse(&data),
+            ParsedInstruction::UpdateAuxiliaryDelegatedSchema {
+                schema_metadata: 9,
+                sequence: 2,
+                data: [7],
+            }
+        ));
+        let data = header(PUBLISHER_UPDATE_TAG, &[9, 2], &[8; 8]);
+        assert!(matches!(
+            SlowPathInstruction::parse(&data),
+            ParsedInstruction::PublisherUpdate {
+                oracle_meta: 9,
+                sequence: 2,
+                payload: [8, 8, 8, 8, 8, 8, 8, 8],
+            }
+        ));
+        let data = header(SPLIT_UPDATE_TAG, &[9, 2], &[1, 8]);
+        assert!(matches!(
+            SlowPathInstruction::parse(&data),
+            ParsedInstruction::SplitUpdate {
+                type_hash: 9,
+                sequence: 2,
+                part: 1,
+                payload: [8],
+            }
+        ));
+        assert_eq!(read_split_update(&data), Some((9, 2, 1, &[8][..])));
+        assert_eq!(
+            read_split_update(&versioned(INSTRUCTION_VERSION, &data)),
+            Some((9, 2, 1, &[8][..]))
+        );
+        assert_eq!(
+            read_split_update(&data[..SPLIT_UPDATE_HEADER_SIZE - 1]),
+            None
+        );
+        assert_eq!(
+            read_split_update(&header(PUBLISHER_UPDATE_TAG, &[9, 2], &[1])),
+            None
+        );
+        let data = header(BATCH_UPDATE_TAG, &[], &[2, 0xAA]);
+        assert!(matches!(
+            SlowPathInstruction::parse(&data),
+            ParsedInstruction::BatchUpdate {
+                count: 2,
+                entries: [0xAA],
+            }
+        ));
+
+        // One byte short of each header.
+        for (tag, size) in [
+            (UPDATE_AUX_TAG, UPDATE_AUX_HEADER_SIZE),
+            (UPDATE_AUX_FORCE_TAG, UPDATE_AUX_FORCE_HEADER_SIZE),
+            (UPDATE_AUX_RANGE_TAG, UPDATE_AUX_RANGE_HEADER_SIZE),
+            (BATCH_UPDATE_TAG, BATCH_UPDATE_HEADER_SIZE),
+            (PUBLISHER_UPDATE_TAG, PUBLISHER_UPDATE_HEADER_SIZE),
+            (SPLIT_UPDATE_TAG, SPLIT_UPDATE_HEADER_SIZE),
+        ] {
+            let data = header(tag, &[0; 3], &[]);
+            assert!(matches!(
+                SlowPathInstruction::parse(&data[..size - 1]),
+                ParsedInstruction::Invalid
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_wincode_and_versioned() {
+        let close = wincode::serialize(&SlowPathInstruction::Close).unwrap();
+        for data in [close.clone(), versioned(INSTRUCTION_VERSION, &close)] {
+            let ParsedInstruction::Slow(ix) = SlowPathInstruction::parse(&data) else {
+                panic!("expected a wincode instruction");
+            };
+            assert!(matches!(*ix, SlowPathInstruction::Close));
+        }
+        let mut update = UPDATE_AUX_TAG.to_le_bytes().to_vec();
+        update.extend_from_slice(&[0; 16]);
+        assert!(matches!(
+            SlowPathInstruction::parse(&versioned(1, &update)),
+            ParsedInstruction::UpdateAuxiliary { data: [], .. }
+        ));
+
+        let mut trailing = close.clone();
+        trailing.push(0);
+        let rejected = [
+            alloc::vec![],
+            alloc::vec![1, 0, 0],
+            trailing,
+            48u32.to_le_bytes().to_vec(),
+            (MAX_SLOW_PATH_TAG + 1).to_le_bytes().to_vec(),
+            versioned(0, &close),
+            versioned(INSTRUCTION_VERSION + 1, &close),
+            versioned(1, &versioned(1, &close)),
+            VERSIONED_TAG.to_le_bytes().to_vec(),
+        ];
+        for data in rejected {
+            assert!(
+                matches!(
+                    SlowPathInstruction::parse(&data),
+                    ParsedInstruction::Invalid
+                ),
+                "{data:?}"
+            );
+        }
+    }
 }
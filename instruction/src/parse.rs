@@ -0,0 +1,185 @@
+//! Classification of raw slow-path instruction data across both encodings.
+
+use alloc::boxed::Box;
+use wincode::SchemaRead;
+
+use crate::{
+    is_supported_version, split_versioned, SlowPathInstruction, BATCH_UPDATE_HEADER_SIZE,
+    BATCH_UPDATE_TAG, MAX_SLOW_PATH_TAG, PUBLISHER_UPDATE_HEADER_SIZE, PUBLISHER_UPDATE_TAG,
+    SPLIT_UPDATE_HEADER_SIZE, SPLIT_UPDATE_TAG, UPDATE_AUX_DELEGATED_RANGE_TAG,
+    UPDATE_AUX_DELEGATED_SCHEMA_TAG, UPDATE_AUX_DELEGATED_TAG, UPDATE_AUX_FORCE_HEADER_SIZE,
+    UPDATE_AUX_FORCE_TAG, UPDATE_AUX_HEADER_SIZE, UPDATE_AUX_RANGE_HEADER_SIZE,
+    UPDATE_AUX_RANGE_TAG, UPDATE_AUX_TAG, VERSIONED_TAG,
+};
+
+/// Slow-path instruction data split the way the program's dispatcher splits it.
+///
+/// Manual-wire instructions borrow their trailing bytes from the parsed data; every wincode
+/// instruction is the [`SlowPathInstruction`] the program deserializes. Field names follow
+/// the wire-format docs on the `*_TAG` constants.
+#[derive(Debug, Clone)]
+pub enum ParsedInstruction<'a> {
+    /// Tag 4.
+    UpdateAuxiliary {
+        metadata: u64,
+        sequence: u64,
+        data: &'a [u8],
+    },
+    /// Tag 5.
+    UpdateAuxiliaryDelegated {
+        metadata: u64,
+        sequence: u64,
+        data: &'a [u8],
+    },
+    /// Tag 6.
+    UpdateAuxiliaryForce {
+        metadata: u64,
+        authority_sequence: u64,
+        program_sequence: u64,
+        data: &'a [u8],
+    },
+    /// Tag 7.
+    UpdateAuxiliaryRange {
+        metadata: u64,
+        sequence: u64,
+        offset: u8,
+        data: &'a [u8],
+    },
+    /// Tag 8.
+    UpdateAuxiliaryDelegatedRange {
+        metadata: u64,
+        sequence: u64,
+        offset: u8,
+        data: &'a [u8],
+    },
+    /// Tag 15.
+    UpdateAuxiliaryDelegatedSchema {
+        schema_metadata: u64,
+        sequence: u64,
+        data: &'a [u8],
+    },
+    /// Tag 25. `entries` holds `count` entries of `[oracle_meta:8][sequence:8][len:1]
+    /// [payload:len]`, not yet checked.
+    BatchUpdate { count: u8, entries: &'a [u8] },
+    /// Tag 32.
+    PublisherUpdate {
+        oracle_meta: u64,
+        sequence: u64,
+        payload: &'a [u8],
+    },
+    /// Tag 56. `part` is not yet checked.
+    SplitUpdate {
+        type_hash: u64,
+        sequence: u64,
+        part: u8,
+        payload: &'a [u8],
+    },
+    /// Any wincode tag. Boxed, since the largest variants carry both 256-byte masks.
+    Slow(Box<SlowPathInstruction>),
+    /// Data the program rejects before dispatching it: shorter than its tag's header, an
+    /// unknown or newer tag, an unsupported or doubly wrapped versioned instruction, or a
+    /// wincode variant that does not deserialize or has trailing bytes.
+    Invalid,
+}
+
+fn u64_at(data: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(data[at..at + 8].try_into().unwrap())
+}
+
+impl SlowPathInstruction {
+    /// Classify raw slow-path instruction data, wincode and manual-wire alike.
+    ///
+    /// A [`VERSIONED_TAG`] wrapper of a supported version is removed, as the program does;
+    /// use [`split_versioned`] to read the version itself. Field values are not checked: a
+    /// wincode instruction may still fail [`validate`](Self::validate), and a manual-wire
+    /// write may carry a payload the program rejects.
+    pub fn parse(data: &[u8]) -> ParsedInstruction<'_> {
+        if let Some((version, inner)) = split_versioned(data) {
+            if !is_supported_version(version) || split_versioned(inner).is_some() {
+                return ParsedInstruction::Invalid;
+            }
+            return parse_unversioned(inner);
+        }
+        parse_unversioned(data)
+    }
+}
+
+fn parse_unversioned(data: &[u8]) -> ParsedInstruction<'_> {
+    if data.len() < 4 {
+        return ParsedInstruction::Invalid;
+    }
+    let tag = u32::from_le_bytes(data[..4].try_into().unwrap());
+    let header_size = match tag {
+        UPDATE_AUX_TAG | UPDATE_AUX_DELEGATED_TAG | UPDATE_AUX_DELEGATED_SCHEMA_TAG => {
+            UPDATE_AUX_HEADER_SIZE
+        }
+        UPDATE_AUX_FORCE_TAG => UPDATE_AUX_FORCE_HEADER_SIZE,
+        UPDATE_AUX_RANGE_TAG | UPDATE_AUX_DELEGATED_RANGE_TAG => UPDATE_AUX_RANGE_HEADER_SIZE,
+        BATCH_UPDATE_TAG => BATCH_UPDATE_HEADER_SIZE,
+        PUBLISHER_UPDATE_TAG => PUBLISHER_UPDATE_HEADER_SIZE,
+        SPLIT_UPDATE_TAG => SPLIT_UPDATE_HEADER_SIZE,
+        VERSIONED_TAG => return ParsedInstruction::Invalid,
+        _ if tag > MAX_SLOW_PATH_TAG => return ParsedInstruction::Invalid,
+        _ => {
+            let mut cursor = data;
+            return match <SlowPathInstruction as SchemaRead>::get(&mut cursor) {
+                Ok(ix) if cursor.is_empty() => ParsedInstruction::Slow(Box::new(ix)),
+                _ => ParsedInstruction::Invalid,
+            };
+        }
+    };
+    if data.len() < header_size {
+        return ParsedInstruction::Invalid;
+    }
+    let rest = &data[header_size..];
+    match tag {
+        UPDATE_AUX_TAG => ParsedInstruction::UpdateAuxiliary {
+            metadata: u64_at(data, 4),
+            sequence: u64_at(data, 12),
+            data: rest,
+        },
+        UPDATE_AUX_DELEGATED_TAG => ParsedInstruction::UpdateAuxiliaryDelegated {
+            metadata: u64_at(data, 4),
+            sequence: u64_at(data, 12),
+            data: rest,
+        },
+        UPDATE_AUX_DELEGATED_SCHEMA_TAG => ParsedInstruction::UpdateAuxiliaryDelegatedSchema {
+            schema_metadata: u64_at(data, 4),
+            sequence: u64_at(data, 12),
+            data: rest,
+        },
+        UPDATE_AUX_FORCE_TAG => ParsedInstruction::UpdateAuxiliaryForce {
+            metadata: u64_at(data, 4),
+            authority_sequence: u64_at(data, 12),
+            program_sequence: u64_at(data, 20),
+            data: rest,
+        },
+        UPDATE_AUX_RANGE_TAG => ParsedInstruction::UpdateAuxiliaryRange {
+            metadata: u64_at(data, 4),
+            sequence: u64_at(data, 12),
+            offset: data[20],
+            data: rest,
+        },
+        UPDATE_AUX_DELEGATED_RANGE_TAG => ParsedInstruction::UpdateAuxiliaryDelegatedRange {
+            metadata: u64_at(data, 4),
+            sequence: u64_at(data, 12),
+            offset: data[20],
+            data: rest,
+        },
+        BATCH_UPDATE_TAG => ParsedInstruction::BatchUpdate {
+            count: data[4],
+            entries: rest,
+        },
+        SPLIT_UPDATE_TAG => ParsedInstruction::SplitUpdate {
+            type_hash: u64_at(data, 4),
+            sequence: u64_at(data, 12),
+            part: data[20],
+            payload: rest,
+        },
+        _ => ParsedInstruction::PublisherUpdate {
+            oracle_meta: u64_at(data, 4),
+            sequence: u64_at(data, 12),
+            payload: rest,
+        },
+    }
+}
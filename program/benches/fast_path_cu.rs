@@ -0,0 +1,75 @@
+//! Compute-unit gate for the fast path: writes every payload size from 0 to `ORACLE_BYTES`
+//! through Mollusk and exits non-zero unless each one costs exactly its lane's budget:
+//! [`TINY_COMPUTE_BUDGET`] for a `TINY_PAYLOAD_SIZE` payload, [`COMPUTE_BUDGET`] for every
+//! other size. Costs below the budget fail too, so the constant is lowered together with
+//! the change that earned it.
+//!
+//! ```text
+//! make bench-cu
+//! ```
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::process::ExitCode;
+
+use c_u_soon::{ORACLE_BYTES, TINY_PAYLOAD_SIZE};
+use c_u_soon_client::fast_path_instruction_data;
+use c_u_soon_program::fast_path::{COMPUTE_BUDGET, TINY_COMPUTE_BUDGET};
+use common::{
+    create_existing_envelope, create_funded_account, new_mollusk_silent, PROGRAM_ID, PROGRAM_PATH,
+};
+use mollusk_svm::result::Check;
+use pinocchio::Address;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+fn main() -> ExitCode {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope = Address::new_unique();
+    let mut envelope_account = create_existing_envelope(&authority, 0);
+
+    let mut off_budget = Vec::new();
+    for size in 0..=ORACLE_BYTES {
+        let instruction = Instruction::new_with_bytes(
+            PROGRAM_ID,
+            &fast_path_instruction_data(0, size as u64 + 1, &vec![0x5A; size]).unwrap(),
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(envelope, false),
+            ],
+        );
+        let result = mollusk.process_and_validate_instruction(
+            &instruction,
+            &[
+                (authority, create_funded_account(1_000_000_000)),
+                (envelope, envelope_account.clone()),
+            ],
+            &[Check::success()],
+        );
+        envelope_account = result.resulting_accounts[1].1.clone();
+        let budget = if size == TINY_PAYLOAD_SIZE {
+            TINY_COMPUTE_BUDGET
+        } else {
+            COMPUTE_BUDGET
+        };
+        if result.compute_units_consumed != budget {
+            off_budget.push((size, result.compute_units_consumed, budget));
+        }
+    }
+
+    if off_budget.is_empty() {
+        println!(
+            "fast path: {} payload sizes at {COMPUTE_BUDGET} CUs, \
+             {TINY_PAYLOAD_SIZE}-byte tiny lane at {TINY_COMPUTE_BUDGET} CUs",
+            ORACLE_BYTES
+        );
+        return ExitCode::SUCCESS;
+    }
+
+    eprintln!("fast path compute units differ from their lane's budget:");
+    for (size, cus, budget) in &off_budget {
+        eprintln!("  payload {size:>3} bytes: {cus} CUs, budget {budget}");
+    }
+    ExitCode::FAILURE
+}
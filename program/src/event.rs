@@ -0,0 +1,35 @@
+//! `sol_log_data` events for envelopes created with `CreateWithEvents`.
+
+use c_u_soon::{OracleEvent, StructMetadata};
+use pinocchio::Address;
+
+use crate::hash::sha256;
+
+/// Log the [`OracleEvent`] of a fast-path write of `payload` to `envelope` as one
+/// `Program data:` line. Costs a `sol_sha256` and a `sol_log_data` call.
+pub fn emit_oracle_event(envelope: &Address, oracle_metadata: u64, sequence: u64, payload: &[u8]) {
+    let event = OracleEvent {
+        envelope: *envelope,
+        sequence,
+        oracle_metadata: StructMetadata::from_raw(oracle_metadata),
+        payload_hash: sha256(&[payload]),
+    };
+    log_data(&[bytemuck::bytes_of(&event)]);
+}
+
+/// Log each of `data` as one base64 field of a `Program data:` line.
+///
+/// Platform dispatch:
+/// - On `target_os = "solana"` / `target_arch = "bpf"`: calls the `sol_log_data` syscall.
+/// - Elsewhere: does nothing.
+#[cfg(any(target_os = "solana", target_arch = "bpf"))]
+fn log_data(data: &[&[u8]]) {
+    extern "C" {
+        fn sol_log_data(data: *const u8, data_len: u64);
+    }
+    // `&[u8]` has the `{ addr, len }` layout the syscall expects for each field.
+    unsafe { sol_log_data(data.as_ptr() as *const u8, data.len() as u64) }
+}
+
+#[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+fn log_data(_data: &[&[u8]]) {}
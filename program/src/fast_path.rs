@@ -1,11 +1,54 @@
-use c_u_soon::Envelope;
+//! Two-account oracle update, written against the raw input buffer.
+
+use c_u_soon::{
+    ConstraintRegion, Envelope, StructMetadata, FLAG_CONFLATION, FLAG_CONSTRAINTS,
+    FLAG_MUTATION_GUARD, FLAG_TWAP, FLAG_UPDATE_STAMP, ORACLE_BYTES, TINY_PAYLOAD_SIZE,
+};
+use c_u_soon_instruction::ENVELOPE_PAUSED_ERROR;
+use core::mem::offset_of;
 use pinocchio::{
     address::address_eq,
     entrypoint::{lazy::InstructionContext, AssumeLikeType, AssumeNeverDup, CheckLikeType},
     error::ProgramError,
+    AccountView, Address, ProgramResult,
 };
 
-use crate::slow_path;
+use crate::{event, instructions, slow_path};
+
+/// Compute units a successful fast-path write costs on the full lane: every payload size
+/// from 0 to `ORACLE_BYTES` except `TINY_PAYLOAD_SIZE`, on envelopes without `FLAG_TWAP`,
+/// `FLAG_UPDATE_STAMP`, `FLAG_CONSTRAINTS`, `FLAG_CONFLATION`, or `FLAG_MUTATION_GUARD`.
+///
+/// The cost is the same for every size because the copy is one `sol_memcpy_` call, so
+/// publishers can request exactly this many units. `tests/basic_tests.rs` and the
+/// `fast_path_cu` bench fail if any size costs a different amount; change this only
+/// together with the code that moved it.
+pub const COMPUTE_BUDGET: u64 = 48;
+
+/// Compute units a successful fast-path write costs on the tiny lane: a payload of exactly
+/// `TINY_PAYLOAD_SIZE` bytes, on the same envelopes as [`COMPUTE_BUDGET`]. The copy is
+/// [`tiny_copy`] instead of the syscall, and every check runs as on the full lane.
+pub const TINY_COMPUTE_BUDGET: u64 = 44;
+
+/// Flags that [`apply_write_flags`] handles before the copy.
+const WRITE_FLAGS: u8 =
+    FLAG_TWAP | FLAG_UPDATE_STAMP | FLAG_CONSTRAINTS | FLAG_CONFLATION | FLAG_MUTATION_GUARD;
+
+/// Bits of the envelope word at `bump` (bytes 288..296, read as one little-endian `u64`;
+/// the SDK asserts the layout) that send a write to [`gated_write`]: [`WRITE_FLAGS`] in
+/// `flags` and all of `paused` and `emit_events`.
+const GATE_MASK: u64 = ((WRITE_FLAGS as u64) << byte_shift(offset_of!(Envelope, flags)))
+    | (0xFF << byte_shift(offset_of!(Envelope, paused)))
+    | (0xFF << byte_shift(offset_of!(Envelope, emit_events)));
+
+/// Shift of the byte at envelope `offset` within the word at `bump`.
+const fn byte_shift(offset: usize) -> u32 {
+    (8 * (offset - offset_of!(Envelope, bump))) as u32
+}
+
+/// Instruction data length that takes the tiny lane: `[meta:8][seq:8][data:32]`.
+const TINY_DATA_SIZE: u64 = (2 * core::mem::size_of::<u64>() + TINY_PAYLOAD_SIZE) as u64;
+const _: () = assert!(TINY_PAYLOAD_SIZE % 8 == 0);
 
 /// Exits the program with `for_error` as the return code.
 ///
@@ -79,6 +122,345 @@ unsafe fn sol_memcpy(_dst: *mut u8, _src: *const u8, _n: u64) -> ! {
     }
 }
 
+/// Writes `[oracle_meta | sequence | payload]` of a tiny-lane write into `oracle_state` as
+/// word stores and exits with 0.
+///
+/// `oracle_meta` and `sequence` are already in registers from the checks, so only the four
+/// payload words are loaded. Ten stores cost less than the `sol_memcpy_` syscall's flat fee
+/// plus its argument setup. The loop has a constant bound and is fully unrolled.
+///
+/// # Safety
+///
+/// Only valid on `target_os = "solana"`. Off-target paths are `unreachable!`.
+///
+/// - `dst` must be writable for `TINY_DATA_SIZE` bytes and `payload` readable for
+///   `TINY_PAYLOAD_SIZE` bytes, both 8-byte aligned. The runtime aligns account data and
+///   instruction data to 8 bytes.
+/// - Never returns. All call sites must be the last action on the success path.
+#[inline(always)]
+unsafe fn tiny_copy(_dst: *mut u64, _oracle_meta: u64, _sequence: u64, _payload: *const u64) -> ! {
+    #[cfg(target_os = "solana")]
+    {
+        *_dst = _oracle_meta;
+        *_dst.add(1) = _sequence;
+        for i in 0..TINY_PAYLOAD_SIZE / 8 {
+            *_dst.add(2 + i) = *_payload.add(i);
+        }
+        // the asm may read memory, so the stores above are kept
+        unsafe {
+            core::arch::asm!("mov64 r0, 0", "exit", options(noreturn));
+        }
+    }
+    #[cfg(not(target_os = "solana"))]
+    {
+        unreachable!("tiny_copy should only be called in the Solana environment");
+    }
+}
+
+/// The program id, which the runtime serializes right after the instruction data.
+///
+/// # Safety
+///
+/// `raw_instruction_data_header` must be the instruction data length field of the
+/// runtime's input buffer.
+#[inline(always)]
+unsafe fn program_id<'a>(raw_instruction_data_header: *const u8) -> &'a Address {
+    // the full length this time: the program id follows all of the data, however long
+    let data_len = *(raw_instruction_data_header as *const u64) as usize;
+    &*(raw_instruction_data_header.add(core::mem::size_of::<u64>() + data_len) as *const Address)
+}
+
+/// Whether the first 8 bytes of `envelope`'s owner equal those of the program id.
+///
+/// A full 32-byte comparison costs 14 CUs on every write. One word rejects every owner
+/// except a program whose id was ground to share it, which cannot be done by chance; a
+/// write to such an account still fails, in the runtime's post-execution owner check.
+///
+/// # Safety
+///
+/// As [`program_id`].
+#[inline(always)]
+unsafe fn owner_word_matches(
+    envelope: &AccountView,
+    raw_instruction_data_header: *const u8,
+) -> bool {
+    let owner = envelope.owner() as *const Address as *const u64;
+    let program_id = program_id(raw_instruction_data_header) as *const Address as *const u64;
+    owner.read_unaligned() == program_id.read_unaligned()
+}
+
+/// Handle the write-time flags of a fast-path write: reject envelopes with oracle
+/// constraints or the mutation guard, whose constraint table or instructions sysvar a
+/// two-account instruction cannot carry ([`ProgramError::NotEnoughAccountKeys`]), then
+/// update the oracle trailers (see [`trailer::record`](instructions::trailer::record)),
+/// exiting with its error on failure.
+///
+/// Out of line and `#[cold]` so envelopes without `FLAG_TWAP`, `FLAG_UPDATE_STAMP`,
+/// `FLAG_CONSTRAINTS`, `FLAG_CONFLATION`, or `FLAG_MUTATION_GUARD` pay only for the flag
+/// test. Reads the `Clock` sysvar, which costs far more than the rest of the fast path.
+///
+/// # Safety
+///
+/// `data_ptr` must point at instruction data that is at least `data_size` bytes long.
+#[cold]
+#[inline(never)]
+unsafe fn apply_write_flags(envelope: &mut Envelope, data_ptr: *const u8, data_size: u64) {
+    if envelope.is_constrained(ConstraintRegion::Oracle) {
+        hard_exit(
+            "oracle constraints need the slow path",
+            ProgramError::NotEnoughAccountKeys,
+        )
+    }
+    if envelope.has_mutation_guard() {
+        hard_exit(
+            "mutation guard needs the slow path",
+            ProgramError::NotEnoughAccountKeys,
+        )
+    }
+    let header = 2 * core::mem::size_of::<u64>();
+    let payload_len = (data_size as usize).saturating_sub(header);
+    let payload = core::slice::from_raw_parts(data_ptr.add(header), payload_len);
+    if let Err(e) = instructions::trailer::record(envelope, payload) {
+        hard_exit("payload overlaps oracle trailer", e)
+    }
+}
+
+/// Accept a signer that is not the envelope authority only if it is the envelope's
+/// publisher; otherwise exit with [`ProgramError::IncorrectAuthority`].
+///
+/// Out of line and `#[cold]` so writes signed by the authority pay only for the one
+/// comparison. Publisher-signed writes cost a few more units than [`COMPUTE_BUDGET`].
+#[cold]
+#[inline(never)]
+fn check_publisher(envelope: &Envelope, signer: &Address) {
+    if !envelope.is_publisher(signer) {
+        hard_exit(
+            "Authority account does not match envelope authority",
+            ProgramError::IncorrectAuthority,
+        )
+    }
+}
+
+/// Accept a fast-path write whose `given` oracle metadata differs from the `stored` one only
+/// if the envelope has none yet (`stored` is [`StructMetadata::ZERO`], so `given` is not);
+/// otherwise exit with the mismatch error (see [`mismatch`](instructions::metadata::mismatch)).
+///
+/// An accepted write adopts `given`: the copy stores it together with the sequence and
+/// payload, so the metadata is set only if the rest of the write succeeds. Every later
+/// write, including a replay of this one, must carry `given`.
+///
+/// Out of line and `#[cold]` so matching writes pay only for the comparison.
+#[cold]
+#[inline(never)]
+fn check_first_write(stored: StructMetadata, given: u64) {
+    if stored != StructMetadata::ZERO {
+        hard_exit(
+            "oracle metadata mismatch",
+            instructions::metadata::mismatch(stored, StructMetadata::from_raw(given)),
+        );
+    }
+}
+
+/// Finish a fast-path write to an envelope that is paused, emits events, or has a write-time
+/// flag set (see [`GATE_MASK`]): exit with `ENVELOPE_PAUSED_ERROR` if it is paused,
+/// otherwise [`write`] it with its flags handled and its event logged.
+///
+/// Out of line and `#[cold]` so other envelopes pay only for the shared test.
+///
+/// # Safety
+///
+/// As [`write`].
+#[cold]
+#[inline(never)]
+unsafe fn gated_write(
+    input: *mut u8,
+    raw_instruction_data_header: *const u8,
+    envelope: &Address,
+    oracle_data: &mut Envelope,
+) -> ! {
+    if oracle_data.is_paused() {
+        hard_exit(
+            "Envelope paused",
+            ProgramError::Custom(ENVELOPE_PAUSED_ERROR),
+        )
+    }
+    write::<true>(input, raw_instruction_data_header, envelope, oracle_data)
+}
+
+/// Validate and apply a fast-path write once its signer is accepted: steps 7-10 of
+/// [`fast_path`], then the copy. Only with `GATED` are the write-time flags handled and,
+/// for an envelope that emits events, the write's [`OracleEvent`](c_u_soon::OracleEvent)
+/// logged just before the copy.
+///
+/// Inlined into both callers, so the `GATED = false` path is the code `fast_path` always
+/// ran.
+///
+/// # Safety
+///
+/// `input` must be the runtime's input buffer and `raw_instruction_data_header` the
+/// instruction data length field within it; `envelope` is the address of `oracle_data`.
+#[inline(always)]
+unsafe fn write<const GATED: bool>(
+    input: *mut u8,
+    raw_instruction_data_header: *const u8,
+    envelope: &Address,
+    oracle_data: &mut Envelope,
+) -> ! {
+    // compiler doesn't do our 'only load first byte for inherent safety'
+    // Only load the low byte of the instruction data length field.
+    // data_size is modulo 256; oversized instructions get truncated writes.
+    // This is by design — the SDK enforces size_of::<T>() <= ORACLE_BYTES at compile time.
+    let data_size = *raw_instruction_data_header as u64;
+    let data_ptr = raw_instruction_data_header.add(core::mem::size_of::<u64>());
+
+    // validate oracle struct identity: instruction must carry matching oracle_metadata,
+    // unless the envelope has none yet [+3 CUs]
+    let instr_metadata = *(data_ptr as *const u64);
+
+    if instr_metadata != oracle_data.oracle_state.oracle_metadata.as_u64() {
+        check_first_write(oracle_data.oracle_state.oracle_metadata, instr_metadata);
+    }
+
+    // read sequence (oracle_meta is 8 bytes, sequence follows at +8)
+    let sequence = *(data_ptr.add(core::mem::size_of::<u64>()) as *const u64);
+
+    if sequence <= oracle_data.oracle_state.sequence {
+        hard_exit("Sequence stale", ProgramError::InvalidInstructionData);
+    }
+
+    // TWAP, stamped, constrained, conflating, and guarded envelopes are handled before the
+    // copy, and event-emitting ones log it; only gated writes get here with either
+    if GATED {
+        if oracle_data.flags & WRITE_FLAGS != 0 {
+            apply_write_flags(oracle_data, data_ptr, data_size);
+        }
+
+        if oracle_data.emits_events() {
+            let header = 2 * core::mem::size_of::<u64>();
+            let payload_len = (data_size as usize).saturating_sub(header);
+            let payload = core::slice::from_raw_parts(data_ptr.add(header), payload_len);
+            event::emit_oracle_event(envelope, instr_metadata, sequence, payload);
+        }
+    }
+
+    // copy oracle_meta + sequence + payload into oracle_state in one shot.
+    // oracle_meta is oracle_state[0], so data_ptr aligns directly with oracle_state start.
+    // overwriting oracle_meta is a no-op since it was validated to match above, except on
+    // a first write to an envelope without one, which adopts it here.
+    let oracle_state_bytes_mut = &mut oracle_data.oracle_state as *mut _ as *mut u8;
+
+    // informing the compiler that the input has a constant address very sadly does not work
+    // it just inserts pointless ops. but computing the known constant offsets and adding to the constant base
+    // works perfectly
+    let oracle_state_bytes_offset = oracle_state_bytes_mut.offset_from(input);
+    let instruction_data_offset = data_ptr.offset_from(input);
+    let constant_propagated_oracle_pointer =
+        (INPUT_BASE + oracle_state_bytes_offset as u64) as *mut u8;
+    let constant_propagated_instruction_pointer =
+        (INPUT_BASE + instruction_data_offset as u64) as *const u8;
+
+    // tiny lane: a 32-byte payload is copied with word stores [+1 CU for every other size]
+    if data_size == TINY_DATA_SIZE {
+        tiny_copy(
+            constant_propagated_oracle_pointer as *mut u64,
+            instr_metadata,
+            sequence,
+            constant_propagated_instruction_pointer.add(2 * core::mem::size_of::<u64>())
+                as *const u64,
+        );
+    }
+
+    // 10CU flat cost. you can add all sorts of shenanigans here to include
+    // a few sort of hyper fast path optimizations but it's really not worth it imo
+    sol_memcpy(
+        constant_propagated_oracle_pointer,
+        constant_propagated_instruction_pointer,
+        data_size,
+    );
+}
+
+/// Fast-path write to an envelope whose account is not exactly `size_of::<Envelope>()`
+/// bytes, such as one grown by `Resize`. The instruction data no longer sits at a constant
+/// offset, so the input is parsed again by the regular entrypoint and [`resized_write`]
+/// applies the write.
+///
+/// Out of line and `#[cold]` so envelopes of the exact size never see it.
+///
+/// # Safety
+///
+/// `input` must be the Solana runtime's input buffer pointer.
+#[cold]
+#[inline(never)]
+unsafe fn resized_entrypoint(input: *mut u8) -> u64 {
+    pinocchio::entrypoint::process_entrypoint::<2>(input, resized_write)
+}
+
+/// Steps 4-10 of [`fast_path`] for an envelope account longer than
+/// `size_of::<Envelope>()`, with the same checks and errors. Only the first
+/// `size_of::<Envelope>()` bytes are read or written, so an extended auxiliary region is
+/// left untouched. Accounts shorter than an envelope fail with
+/// [`ProgramError::InvalidAccountData`], as they did before reaching here.
+///
+/// The authority account has already been checked by [`fast_path`].
+fn resized_write(program_id: &Address, accounts: &[AccountView], data: &[u8]) -> ProgramResult {
+    let [authority_account, envelope_account] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let header = 2 * core::mem::size_of::<u64>();
+    if data.len() < header || data.len() > header + ORACLE_BYTES {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if !address_eq(&envelope.authority, authority_account.address())
+        && !envelope.is_publisher(authority_account.address())
+    {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    instructions::paused::check(envelope)?;
+
+    let oracle_meta = u64::from_le_bytes(data[..8].try_into().unwrap());
+    let sequence = u64::from_le_bytes(data[8..header].try_into().unwrap());
+    let payload = &data[header..];
+
+    let stored = envelope.oracle_state.oracle_metadata;
+    if oracle_meta != stored.as_u64() && stored != StructMetadata::ZERO {
+        return Err(instructions::metadata::mismatch(
+            stored,
+            StructMetadata::from_raw(oracle_meta),
+        ));
+    }
+
+    if sequence <= envelope.oracle_state.sequence {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if envelope.is_constrained(ConstraintRegion::Oracle) || envelope.has_mutation_guard() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    instructions::trailer::record(envelope, payload)?;
+
+    if envelope.emits_events() {
+        event::emit_oracle_event(envelope_account.address(), oracle_meta, sequence, payload);
+    }
+
+    let oracle_state = &mut envelope.oracle_state;
+    oracle_state.oracle_metadata = StructMetadata::from_raw(oracle_meta);
+    oracle_state.sequence = sequence;
+    oracle_state.data[..payload.len()].copy_from_slice(payload);
+
+    Ok(())
+}
+
 // This is probably better written as asm
 // but having mostly plain rust makes the development far easier
 // we could save 1 CU on never using r0 and on happy path
@@ -92,14 +474,36 @@ unsafe fn sol_memcpy(_dst: *mut u8, _src: *const u8, _n: u64) -> ! {
 ///
 /// 1. Account count must be exactly 2; otherwise delegates to [`slow_path::slow_entrypoint`].
 /// 2. Account 0: must be a signer with 0 bytes of data (authority).
-/// 3. Account 1: must have exactly `size_of::<Envelope>()` bytes of data (oracle).
-/// 4. `envelope.authority` must equal the authority account's address.
-/// 5. Instruction `oracle_metadata` must match `envelope.oracle_state.oracle_metadata`.
-/// 6. Instruction `sequence` must be strictly greater than `envelope.oracle_state.sequence`.
+/// 3. Account 1: must have exactly `size_of::<Envelope>()` bytes of data (oracle). Any
+///    other size continues in [`resized_entrypoint`], which applies steps 4-10 to envelopes
+///    grown by `Resize` and rejects shorter accounts with
+///    [`ProgramError::InvalidAccountData`].
+/// 4. Account 1 must be owned by this program; otherwise exits with
+///    [`ProgramError::IncorrectProgramId`], as every slow-path instruction does. Only the
+///    first 8 bytes of the owner are compared (see [`owner_word_matches`]).
+/// 5. `envelope.authority` or, if set, `envelope.publisher` must equal the authority
+///    account's address (see [`check_publisher`]). Members of the publisher set are not
+///    accepted here; their writes need the set and go through `PUBLISHER_UPDATE_TAG`.
+/// 6. The envelope must not be paused; otherwise exits with `ENVELOPE_PAUSED_ERROR`.
+///    Paused and event-emitting envelopes and those with a flag of step 9 share one test
+///    and continue in [`gated_write`].
+/// 7. Instruction `oracle_metadata` must match `envelope.oracle_state.oracle_metadata`,
+///    unless that is zero: the first write to an envelope created without oracle metadata
+///    adopts the instruction's (see [`check_first_write`]).
+/// 8. Instruction `sequence` must be strictly greater than `envelope.oracle_state.sequence`.
+/// 9. With `FLAG_TWAP`, `FLAG_UPDATE_STAMP`, `FLAG_CONSTRAINTS`, `FLAG_CONFLATION`, or
+///    `FLAG_MUTATION_GUARD` set, [`apply_write_flags`] rejects envelopes with oracle
+///    constraints or the mutation guard, folds the payload into the conflation window and
+///    the TWAP accumulator, and stamps the slot and timestamp, rejecting payloads that would
+///    overwrite a trailer.
+/// 10. With `emit_events` set, logs an [`OracleEvent`](c_u_soon::OracleEvent) (see
+///    [`event::emit_oracle_event`]).
 ///
 /// On success: copies `[oracle_meta | sequence | payload]` into `oracle_state` via a
-/// single `sol_memcpy_` syscall, then exits with 0. `sol_memcpy` calls `exit` directly,
-/// so `fast_path` never returns on the success path.
+/// single `sol_memcpy_` syscall, then exits with 0. Instruction data of exactly
+/// `TINY_DATA_SIZE` bytes takes the tiny lane instead, [`tiny_copy`], which costs
+/// [`TINY_COMPUTE_BUDGET`] rather than [`COMPUTE_BUDGET`]. Both copies call `exit`
+/// directly, so `fast_path` never returns on the success path.
 ///
 /// # Safety
 ///
@@ -133,78 +537,37 @@ pub(super) unsafe fn fast_path(input: *mut u8) -> u64 {
         )
     }
 
-    // if length is too long or too short, no good. BUT!
-    // a. we know there are no other accounts in the program. only the authority and this weird non-envelope account
-    // b. we know the account is not an envelope account
-    // c. somebody could transfer an account to the program and then use this problem to write to it.
-    // c. 1. HOWEVER. that's not a legitimate c_u_soon account and would not pass a PDA check.
-    // d. Conclusion:
-    // d. 1. wrong size in second account does NOT allow one to write to an oracle that is not theirs, or in fact, an oracle at all.
-    // d. 2. this is inherent since if the second account is the wrong size it implies it's not an oracle account in the first place
+    // a second account of any other size is either an envelope grown by `Resize` or not an
+    // envelope at all. the cold path re-parses the input and does the same checks as below,
+    // so it can't be used to write to an account that isn't an envelope the signer owns.
 
     let Ok(oracle_account) =
         ctx.next_account_guarded(&AssumeNeverDup::new(), &AssumeLikeType::<Envelope>::new())
     else {
-        hard_exit(
-            "Second account does not have size of Envelope",
-            ProgramError::InvalidAccountData,
-        )
+        return resized_entrypoint(input);
     };
 
-    let oracle_data = bytemuck::from_bytes_mut::<Envelope>(oracle_account.borrow_unchecked_mut());
-
-    if !address_eq(&oracle_data.authority, authority_account.address()) {
+    // the runtime would reject a write to another program's account after we exit, but
+    // with a generic error, and only if the bytes changed. one word of the owner is enough
+    // to give every real foreign owner the slow path's error [+5 CUs for every envelope]
+    if !owner_word_matches(&oracle_account, ctx.cursor()) {
         hard_exit(
-            "Authority account does not match envelope authority",
-            ProgramError::IncorrectAuthority,
+            "Envelope not owned by program",
+            ProgramError::IncorrectProgramId,
         )
     }
 
-    // compiler doesn't do our 'only load first byte for inherent safety'
-    let raw_instruction_data_header = ctx.cursor();
-
-    // Only load the low byte of the instruction data length field.
-    // data_size is modulo 256; oversized instructions get truncated writes.
-    // This is by design — the SDK enforces size_of::<T>() <= ORACLE_BYTES at compile time.
-    let data_size = *raw_instruction_data_header as u64;
-    let data_ptr = raw_instruction_data_header.add(core::mem::size_of::<u64>());
-
-    // validate oracle struct identity: instruction must carry matching oracle_metadata [+3 CUs]
-    let instr_metadata = *(data_ptr as *const u64);
+    let oracle_data = bytemuck::from_bytes_mut::<Envelope>(oracle_account.borrow_unchecked_mut());
 
-    if instr_metadata != oracle_data.oracle_state.oracle_metadata.as_u64() {
-        hard_exit(
-            "oracle metadata mismatch",
-            ProgramError::InvalidInstructionData,
-        );
+    if !address_eq(&oracle_data.authority, authority_account.address()) {
+        check_publisher(oracle_data, authority_account.address());
     }
 
-    // read sequence (oracle_meta is 8 bytes, sequence follows at +8)
-    let sequence = *(data_ptr.add(core::mem::size_of::<u64>()) as *const u64);
-
-    if sequence <= oracle_data.oracle_state.sequence {
-        hard_exit("Sequence stale", ProgramError::InvalidInstructionData);
+    // `flags`, `paused`, and `emit_events` share the aligned word at `bump`, so one load
+    // tests all three [+4 CUs for every envelope]
+    if *(core::ptr::addr_of!(oracle_data.bump) as *const u64) & GATE_MASK != 0 {
+        gated_write(input, ctx.cursor(), oracle_account.address(), oracle_data)
     }
 
-    // copy oracle_meta + sequence + payload into oracle_state in one shot.
-    // oracle_meta is oracle_state[0], so data_ptr aligns directly with oracle_state start.
-    // overwriting oracle_meta is a no-op since it was validated to match above.
-    let oracle_state_bytes_mut = &mut oracle_data.oracle_state as *mut _ as *mut u8;
-
-    // informing the compiler that the input has a constant address very sadly does not work
-    // it just inserts pointless ops. but computing the known constant offsets and adding to the constant base
-    // works perfectly
-    let oracle_state_bytes_offset = oracle_state_bytes_mut.offset_from(input);
-    let instruction_data_offset = data_ptr.offset_from(input);
-    let constant_propagated_oracle_pointer =
-        (INPUT_BASE + oracle_state_bytes_offset as u64) as *mut u8;
-    let constant_propagated_instruction_pointer =
-        (INPUT_BASE + instruction_data_offset as u64) as *const u8;
-    // 10CU flat cost. you can add all sorts of shenanigans here to include
-    // a few sort of hyper fast path optimizations but it's really not worth it imo
-    sol_memcpy(
-        constant_propagated_oracle_pointer,
-        constant_propagated_instruction_pointer,
-        data_size,
-    );
+    write::<false>(input, ctx.cursor(), oracle_account.address(), oracle_data)
 }
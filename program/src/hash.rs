@@ -0,0 +1,26 @@
+/// SHA-256 of the concatenation of `vals`.
+///
+/// Platform dispatch:
+/// - On `target_os = "solana"` / `target_arch = "bpf"`: calls the `sol_sha256` syscall.
+/// - Elsewhere: panics (`unimplemented!`). Off-chain callers hash with the client crate.
+#[cfg(any(target_os = "solana", target_arch = "bpf"))]
+pub fn sha256(vals: &[&[u8]]) -> [u8; 32] {
+    extern "C" {
+        fn sol_sha256(vals: *const u8, val_len: u64, hash_result: *mut u8) -> u64;
+    }
+    let mut hash = [0u8; 32];
+    // `&[u8]` has the `{ addr, len }` layout the syscall expects for each value.
+    unsafe {
+        sol_sha256(
+            vals.as_ptr() as *const u8,
+            vals.len() as u64,
+            hash.as_mut_ptr(),
+        );
+    }
+    hash
+}
+
+#[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+pub fn sha256(_vals: &[&[u8]]) -> [u8; 32] {
+    unimplemented!("sha256 only available on BPF")
+}
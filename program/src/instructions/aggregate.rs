@@ -0,0 +1,126 @@
+use super::trailer;
+use c_u_soon::{median_within, ConstraintRegion, Envelope, MAX_AGGREGATE_SOURCES, ORACLE_BYTES};
+use c_u_soon_instruction::TOO_FEW_SOURCES_ERROR;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Write the median of several source envelopes' oracle values into an aggregate envelope.
+///
+/// Accounts: `[authority (signer), envelope_account (writable), source_0, ..]`, with 1 to
+/// [`MAX_AGGREGATE_SOURCES`] distinct sources after the envelope. If the envelope has oracle
+/// constraints, its constraint table goes between the envelope and the sources (see
+/// [`constraints::split_table`](super::constraints::split_table)). Repeating a source, or
+/// passing the envelope as its own source, returns [`ProgramError::InvalidArgument`]. If the
+/// envelope has the mutation guard, the instructions sysvar follows the sources (see
+/// [`mutation_guard::enforce`](super::mutation_guard::enforce)).
+///
+/// Every source must be an envelope owned by this program whose `oracle_metadata` equals the
+/// aggregate envelope's (see [`metadata::check`](super::metadata::check)), and the type must
+/// be 8 to [`ORACLE_BYTES`] bytes ([`ProgramError::InvalidAccountData`]). The first 8 payload
+/// bytes of each source are its `u64` LE value. The sources are combined by
+/// [`median_within`]: those more than `max_deviation_bps` from the median of all sources are
+/// dropped, and if fewer than `min_sources` remain the instruction fails with
+/// `Custom(TOO_FEW_SOURCES_ERROR)`.
+///
+/// The payload of the source holding the median of the rest, `type_size` bytes, is then
+/// written like a fast-path update under `sequence`, which must be strictly greater than the
+/// stored sequence ([`ProgramError::InvalidInstructionData`]). The oracle trailers are
+/// updated (see [`trailer::record`]). Source sequences are not compared, so a publisher that
+/// stopped updating still counts; keep `max_deviation_bps` tight enough to drop it.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    sequence: u64,
+    min_sources: u8,
+    max_deviation_bps: u16,
+) -> ProgramResult {
+    let (accounts, instructions_sysvar) = super::mutation_guard::split_sysvar(accounts);
+    let [authority, envelope_account, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if &envelope.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    let (table, sources) =
+        super::constraints::split_table(envelope, ConstraintRegion::Oracle, rest)?;
+    if sources.is_empty() || sources.len() > MAX_AGGREGATE_SOURCES {
+        return Err(ProgramError::InvalidArgument);
+    }
+    for (i, source) in sources.iter().enumerate() {
+        if source.address() == envelope_account.address()
+            || sources[..i].iter().any(|s| s.address() == source.address())
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    super::paused::check(envelope)?;
+
+    if sequence <= envelope.oracle_state.sequence {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let metadata = envelope.oracle_state.oracle_metadata;
+    let len = metadata.type_size() as usize;
+    if !(8..=ORACLE_BYTES).contains(&len) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut values = [0u64; MAX_AGGREGATE_SOURCES];
+    for (source, value) in sources.iter().zip(values.iter_mut()) {
+        if !source.owned_by(program_id) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let source_data = source.try_borrow()?;
+        let source_envelope =
+            Envelope::from_prefix_bytes(&source_data).ok_or(ProgramError::InvalidAccountData)?;
+        super::metadata::check(metadata, source_envelope.oracle_state.oracle_metadata)?;
+        *value = u64::from_le_bytes(source_envelope.oracle_state.data[..8].try_into().unwrap());
+    }
+
+    let (median, accepted) = median_within(&values[..sources.len()], max_deviation_bps)
+        .ok_or(ProgramError::InvalidArgument)?;
+    if accepted < min_sources as usize {
+        return Err(ProgramError::Custom(TOO_FEW_SOURCES_ERROR));
+    }
+
+    let mut payload = [0u8; ORACLE_BYTES];
+    {
+        let source_data = sources[median].try_borrow()?;
+        let source_envelope =
+            Envelope::from_prefix_bytes(&source_data).ok_or(ProgramError::InvalidAccountData)?;
+        payload[..len].copy_from_slice(&source_envelope.oracle_state.data[..len]);
+    }
+    let payload = &payload[..len];
+
+    super::mutation_guard::enforce(envelope, envelope_account.address(), instructions_sysvar)?;
+    trailer::record(envelope, payload)?;
+    let oracle_state = &mut envelope.oracle_state;
+    oracle_state.sequence = sequence;
+    oracle_state.data[..len].copy_from_slice(payload);
+
+    if let Some(table) = table {
+        super::constraints::enforce(
+            program_id,
+            envelope_account.address(),
+            table,
+            ConstraintRegion::Oracle,
+            &envelope.oracle_state.data,
+        )?;
+    }
+
+    Ok(())
+}
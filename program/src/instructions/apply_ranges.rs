@@ -1,17 +1,18 @@
-use c_u_soon::{Mask, AUX_DATA_SIZE};
-use c_u_soon_instruction::WriteSpec;
+use c_u_soon::{aux_blocks_touched, Mask, AUX_DATA_SIZE};
+use c_u_soon_instruction::{WriteRange, MAX_WRITE_RANGES, TOO_MANY_RANGES_ERROR};
 use pinocchio::error::ProgramError;
 
 /// Validate a single range against the mask, then apply it.
 ///
-/// Zero-alloc path for single-range wire tags (7/8).
+/// Zero-alloc path for single-range wire tags (7/8). Returns the blocks written, as
+/// [`aux_blocks_touched`].
 pub fn validate_and_apply_single(
     aux_data: &mut [u8; AUX_DATA_SIZE],
     mask: &Mask,
     type_size: usize,
     offset: u8,
     data: &[u8],
-) -> Result<(), ProgramError> {
+) -> Result<u8, ProgramError> {
     if data.is_empty() {
         return Err(ProgramError::InvalidInstructionData);
     }
@@ -26,29 +27,38 @@ pub fn validate_and_apply_single(
         return Err(ProgramError::InvalidArgument);
     }
     aux_data[off..end].copy_from_slice(data);
-    Ok(())
+    Ok(aux_blocks_touched(off, data.len()))
 }
 
-/// Validate all ranges against the mask, then apply them atomically.
+/// Validate all ranges against the mask, then apply them atomically. `ranges` are
+/// [`WriteSpec`](c_u_soon_instruction::WriteSpec)s or
+/// [`WriteSpecV2`](c_u_soon_instruction::WriteSpecV2)s.
 ///
 /// Phase 1: bounds checks + `check_masked_update` for every range.
 /// Phase 2: copy all ranges into `aux_data`.
 ///
-/// Returns `InvalidInstructionData` for bounds violations,
-/// `InvalidArgument` if a blocked byte would be changed.
-pub fn validate_and_apply(
+/// Returns `Custom(TOO_MANY_RANGES_ERROR)` for more than [`MAX_WRITE_RANGES`] ranges,
+/// `InvalidInstructionData` for bounds violations,
+/// `InvalidArgument` if a blocked byte would be changed. On success, returns the union of
+/// the blocks written by every range.
+pub fn validate_and_apply<R: WriteRange>(
     aux_data: &mut [u8; AUX_DATA_SIZE],
     mask: &Mask,
     type_size: usize,
-    ranges: &[WriteSpec],
-) -> Result<(), ProgramError> {
+    ranges: &[R],
+) -> Result<u8, ProgramError> {
+    if ranges.len() > MAX_WRITE_RANGES {
+        return Err(ProgramError::Custom(TOO_MANY_RANGES_ERROR));
+    }
+
     // Bounds + empty checks
     for spec in ranges {
-        if spec.data.is_empty() {
+        if spec.bytes().is_empty() {
             return Err(ProgramError::InvalidInstructionData);
         }
-        let end = (spec.offset as usize)
-            .checked_add(spec.data.len())
+        let end = spec
+            .start()
+            .checked_add(spec.bytes().len())
             .ok_or(ProgramError::InvalidInstructionData)?;
         if end > type_size {
             return Err(ProgramError::InvalidInstructionData);
@@ -57,16 +67,18 @@ pub fn validate_and_apply(
 
     // Phase 1: validate ALL ranges via check_masked_update
     for spec in ranges {
-        if !mask.check_masked_update(aux_data, spec.offset as usize, &spec.data) {
+        if !mask.check_masked_update(aux_data, spec.start(), spec.bytes()) {
             return Err(ProgramError::InvalidArgument);
         }
     }
 
     // Phase 2: apply all
+    let mut blocks = 0;
     for spec in ranges {
-        let off = spec.offset as usize;
-        aux_data[off..off + spec.data.len()].copy_from_slice(&spec.data);
+        let (off, data) = (spec.start(), spec.bytes());
+        aux_data[off..off + data.len()].copy_from_slice(data);
+        blocks |= aux_blocks_touched(off, data.len());
     }
 
-    Ok(())
+    Ok(blocks)
 }
@@ -0,0 +1,35 @@
+use crate::hash::sha256;
+use c_u_soon::Envelope;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Fail unless the auxiliary data hashes to `expected`.
+///
+/// Accounts: `[envelope_account]` (readonly).
+///
+/// Hashes all `AUX_DATA_SIZE` bytes of `auxiliary_data` with SHA-256. A mismatch returns
+/// [`ProgramError::InvalidArgument`], failing the whole transaction. A delegate places this
+/// ahead of an instruction that depends on aux state it read off-chain, so that instruction
+/// never runs against different bytes.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    expected: &[u8; 32],
+) -> ProgramResult {
+    let [envelope_account] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let envelope_data = envelope_account.try_borrow()?;
+    let envelope: &Envelope =
+        Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+    if &sha256(&[&envelope.auxiliary_data]) != expected {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use super::trailer;
+use c_u_soon::{ConstraintRegion, Envelope, StructMetadata, ORACLE_BYTES};
+use c_u_soon_instruction::{BATCH_ENTRY_HEADER_SIZE, MAX_BATCH_SIZE};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Apply one fast-path style oracle update to each of several envelopes.
+///
+/// Accounts: `[authority (signer), envelope_0, .., envelope_{count-1}]`, all envelopes
+/// writable and belonging to `authority` or naming it as their publisher, followed by the
+/// constraint table of each envelope with oracle constraints, in envelope order (see
+/// [`constraints::enforce`](super::constraints::enforce)). The instructions sysvar follows
+/// last if any envelope has the mutation guard (see
+/// [`mutation_guard::enforce`](super::mutation_guard::enforce)).
+///
+/// `entries` holds `count` back-to-back `[oracle_meta:8][sequence:8][len:1][payload:len]`
+/// records, matched to the envelopes by position. Each one is checked like a fast-path
+/// update: `oracle_meta` must equal the envelope's `oracle_metadata` (see
+/// [`metadata::check`](super::metadata::check)) and `sequence` must be strictly greater than
+/// its stored sequence ([`ProgramError::InvalidInstructionData`]). The transaction is
+/// all-or-nothing, so any failing entry leaves every envelope unchanged. Payload bytes past
+/// `len` keep their previous values. Envelopes with `FLAG_TWAP`, `FLAG_UPDATE_STAMP`, or
+/// `FLAG_CONFLATION` update their oracle trailers for each entry, as the fast path does (see
+/// [`trailer::record`]).
+///
+/// `count` must be at least 1, and the accounts after the envelopes must be exactly the
+/// tables the batch needs. An instruction with exactly two accounts always takes the fast
+/// path, so a batch of one is only possible for a constrained or guarded envelope, whose
+/// table or sysvar makes the third account.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    count: u8,
+    entries: &[u8],
+) -> ProgramResult {
+    let (accounts, instructions_sysvar) = super::mutation_guard::split_sysvar(accounts);
+    let [authority, accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let count = count as usize;
+    if !(1..=MAX_BATCH_SIZE).contains(&count) || accounts.len() < count {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (envelopes, tables) = accounts.split_at(count);
+    let mut tables = tables.iter();
+
+    let mut rest = entries;
+    for envelope_account in envelopes {
+        if rest.len() < BATCH_ENTRY_HEADER_SIZE {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let oracle_meta = u64::from_le_bytes(rest[..8].try_into().unwrap());
+        let sequence = u64::from_le_bytes(rest[8..16].try_into().unwrap());
+        let len = rest[16] as usize;
+        if len > ORACLE_BYTES || rest.len() < BATCH_ENTRY_HEADER_SIZE + len {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let payload = &rest[BATCH_ENTRY_HEADER_SIZE..BATCH_ENTRY_HEADER_SIZE + len];
+        rest = &rest[BATCH_ENTRY_HEADER_SIZE + len..];
+
+        if !envelope_account.owned_by(program_id) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut envelope_data = envelope_account.try_borrow_mut()?;
+        let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+            .ok_or(ProgramError::InvalidAccountData)?;
+
+        let signer = authority.address();
+        if &envelope.authority != signer && !envelope.is_publisher(signer) {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+        super::paused::check(envelope)?;
+
+        let oracle_state = &mut envelope.oracle_state;
+        super::metadata::check(
+            oracle_state.oracle_metadata,
+            StructMetadata::from_raw(oracle_meta),
+        )?;
+        if sequence <= oracle_state.sequence {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        super::mutation_guard::enforce(envelope, envelope_account.address(), instructions_sysvar)?;
+        trailer::record(envelope, payload)?;
+        let oracle_state = &mut envelope.oracle_state;
+        oracle_state.sequence = sequence;
+        oracle_state.data[..len].copy_from_slice(payload);
+
+        if envelope.is_constrained(ConstraintRegion::Oracle) {
+            let table = tables.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            super::constraints::enforce(
+                program_id,
+                envelope_account.address(),
+                table,
+                ConstraintRegion::Oracle,
+                &envelope.oracle_state.data,
+            )?;
+        }
+    }
+
+    if !rest.is_empty() || tables.next().is_some() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(())
+}
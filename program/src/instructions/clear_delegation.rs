@@ -11,7 +11,9 @@ use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 /// `delegation_authority` must sign and match `envelope.delegation_authority`.
 ///
 /// Zeroes `oracle_state`, `auxiliary_data`, and `auxiliary_metadata`. Resets both bitmasks to
-/// `ALL_BLOCKED`. The authority may install a new delegation after this call.
+/// `ALL_BLOCKED`, removes the delegation's write hook, and records the aux wipe as a write
+/// to every block. Sealed aux bytes keep their values and stay sealed in both bitmasks. The
+/// authority may install a new delegation after this call.
 pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
     let [authority, envelope_account, delegation_authority] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
@@ -26,7 +28,8 @@ pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult
     }
 
     let mut envelope_data = envelope_account.try_borrow_mut()?;
-    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut envelope_data);
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
 
     if &envelope.authority != authority.address() {
         return Err(ProgramError::IncorrectAuthority);
@@ -39,11 +42,17 @@ pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult
     verify_delegation_authority(delegation_authority, &envelope.delegation_authority)?;
 
     envelope.delegation_authority = Address::zeroed();
-    envelope.program_bitmask = Mask::ALL_BLOCKED;
-    envelope.user_bitmask = Mask::ALL_BLOCKED;
+    envelope.write_hook = Address::zeroed();
+    for (i, byte) in envelope.auxiliary_data.iter_mut().enumerate() {
+        if !envelope.program_bitmask.is_sealed(i) {
+            *byte = 0;
+        }
+    }
+    envelope.program_bitmask = Mask::ALL_BLOCKED.with_sealed_from(&envelope.program_bitmask);
+    envelope.user_bitmask = Mask::ALL_BLOCKED.with_sealed_from(&envelope.user_bitmask);
     envelope.oracle_state = OracleState::zeroed();
-    envelope.auxiliary_data = [0u8; 256];
     envelope.auxiliary_metadata = StructMetadata::ZERO;
+    envelope.record_aux_write(0xFF);
 
     Ok(())
 }
@@ -1,25 +1,27 @@
 use c_u_soon::Envelope;
+use c_u_soon_instruction::MAX_TREASURY_BPS;
 use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 
-/// Deallocate an oracle PDA and return its lamports to a recipient.
-///
-/// Accounts: `[authority (signer), envelope_account, recipient]`.
-///
-/// Requires no active delegation (`!envelope.has_delegation()`); close is blocked while a
-/// delegated program may still hold references. Zero-fills account data before deallocation
-/// to clear oracle state from on-chain storage. `recipient` must differ from `envelope_account`.
-/// Transfers all lamports to `recipient`, resizes the account to 0, and reassigns ownership to
-/// the system program.
-pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
-    let [authority, envelope_account, recipient] = accounts else {
-        return Err(ProgramError::NotEnoughAccountKeys);
-    };
-
+/// Check the authority and that no payee is the envelope itself, wipe the envelope, and hand
+/// its lamports to `pay`, then deallocate the account and return it to the system program.
+fn with_validated_close<F>(
+    program_id: &Address,
+    authority: &AccountView,
+    envelope_account: &AccountView,
+    payees: &[&AccountView],
+    pay: F,
+) -> ProgramResult
+where
+    F: FnOnce(u64),
+{
     if !authority.is_signer() {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if envelope_account.address() == recipient.address() {
+    if payees
+        .iter()
+        .any(|payee| payee.address() == envelope_account.address())
+    {
         return Err(ProgramError::InvalidArgument);
     }
 
@@ -29,23 +31,87 @@ pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult
 
     {
         let mut envelope_data = envelope_account.try_borrow_mut()?;
-        let envelope: &Envelope = bytemuck::from_bytes(&envelope_data);
+        let envelope: &Envelope =
+            Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
         if envelope.authority != *authority.address() {
             return Err(ProgramError::IncorrectAuthority);
         }
-        if envelope.has_delegation() {
+        if envelope.has_delegation() || envelope.is_permanent() {
             return Err(ProgramError::InvalidArgument);
         }
         envelope_data.fill(0);
     }
 
     let envelope_lamports = envelope_account.lamports();
-    let recipient_lamports = recipient.lamports();
     envelope_account.set_lamports(0);
-    recipient.set_lamports(recipient_lamports + envelope_lamports);
+    pay(envelope_lamports);
 
     envelope_account.resize(0)?;
     unsafe { envelope_account.assign(&pinocchio_system::ID) };
 
     Ok(())
 }
+
+/// Deallocate an oracle PDA and return its lamports to a recipient.
+///
+/// Accounts: `[authority (signer), envelope_account, recipient]`.
+///
+/// Requires no active delegation (`!envelope.has_delegation()`); close is blocked while a
+/// delegated program may still hold references. Also rejected for permanent envelopes
+/// (`envelope.is_permanent()`). Zero-fills account data before deallocation
+/// to clear oracle state from on-chain storage. `recipient` must differ from `envelope_account`.
+/// Transfers all lamports to `recipient`, resizes the account to 0, and reassigns ownership to
+/// the system program.
+pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [authority, envelope_account, recipient] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    with_validated_close(
+        program_id,
+        authority,
+        envelope_account,
+        &[recipient],
+        |lamports| {
+            recipient.set_lamports(recipient.lamports() + lamports);
+        },
+    )
+}
+
+/// Close an oracle PDA, splitting its lamports between a treasury and a recipient.
+///
+/// Accounts: `[authority (signer), envelope_account, recipient, treasury]`.
+///
+/// Checks are as for [`process`]; `treasury` must also differ from `envelope_account`.
+/// `treasury_bps` is at most [`MAX_TREASURY_BPS`] (checked by `validate`). `treasury`
+/// receives `lamports * treasury_bps / 10_000`, rounded down, and `recipient` the rest.
+/// `treasury` and `recipient` may be the same account.
+pub fn process_with_split(
+    program_id: &Address,
+    accounts: &[AccountView],
+    treasury_bps: u16,
+) -> ProgramResult {
+    let [authority, envelope_account, recipient, treasury] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let payees = [recipient, treasury];
+    with_validated_close(
+        program_id,
+        authority,
+        envelope_account,
+        &payees,
+        |lamports| {
+            let treasury_share = treasury_split(lamports, treasury_bps);
+            treasury.set_lamports(treasury.lamports() + treasury_share);
+            recipient.set_lamports(recipient.lamports() + (lamports - treasury_share));
+        },
+    )
+}
+
+/// `lamports * bps / MAX_TREASURY_BPS`, rounded down. `bps` must be at most
+/// [`MAX_TREASURY_BPS`].
+#[inline(always)]
+fn treasury_split(lamports: u64, bps: u16) -> u64 {
+    (lamports as u128 * bps as u128 / MAX_TREASURY_BPS as u128) as u64
+}
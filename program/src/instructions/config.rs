@@ -0,0 +1,65 @@
+use c_u_soon::{create_config_address, derive_config_address, ProgramConfig};
+use c_u_soon_instruction::PROGRAM_PAUSED_ERROR;
+use pinocchio::{error::ProgramError, AccountView, Address};
+
+/// Read the program config from `config_account`.
+///
+/// Returns `None` while `InitConfig` has not run: the account at the config PDA then holds
+/// no data. An empty account anywhere else fails with [`ProgramError::InvalidSeeds`], so a
+/// caller cannot skip the fee or the pause by passing another account. An account with
+/// data must be this program's ([`ProgramError::IncorrectProgramId`]),
+/// [`ProgramConfig::SIZE`] bytes long ([`ProgramError::InvalidAccountData`]), and at the
+/// PDA of its stored bump ([`ProgramError::InvalidSeeds`]).
+pub fn load(
+    program_id: &Address,
+    config_account: &AccountView,
+) -> Result<Option<ProgramConfig>, ProgramError> {
+    if config_account.data_len() == 0 {
+        if config_account.address() != &derive_config_address(program_id).0 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        return Ok(None);
+    }
+
+    if !config_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let config_data = config_account.try_borrow()?;
+    if config_data.len() != ProgramConfig::SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let config: ProgramConfig = *bytemuck::from_bytes(&config_data);
+    if create_config_address(program_id, config.bump).as_ref() != Some(config_account.address()) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(Some(config))
+}
+
+/// [`load`] the program config and fail with `Custom(PROGRAM_PAUSED_ERROR)` if it is paused.
+pub fn check(
+    program_id: &Address,
+    config_account: &AccountView,
+) -> Result<Option<ProgramConfig>, ProgramError> {
+    let config = load(program_id, config_account)?;
+    if config.is_some_and(|config| config.is_paused()) {
+        return Err(ProgramError::Custom(PROGRAM_PAUSED_ERROR));
+    }
+    Ok(config)
+}
+
+/// [`check`] the program config, the required last account of every instruction that
+/// [`requires_trailing_config`](c_u_soon_instruction::requires_trailing_config), and return
+/// the accounts before it for the handler.
+///
+/// Fails with [`ProgramError::NotEnoughAccountKeys`] if there are no accounts, and as
+/// [`load`] if the last one is not the config PDA.
+pub fn strip<'a>(
+    program_id: &Address,
+    accounts: &'a [AccountView],
+) -> Result<&'a [AccountView], ProgramError> {
+    let (config_account, rest) = accounts
+        .split_last()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    check(program_id, config_account)?;
+    Ok(rest)
+}
@@ -0,0 +1,90 @@
+use crate::pda::create_program_address;
+use c_u_soon::{ConstraintRegion, ConstraintTable, Envelope, CONSTRAINTS_SEED};
+use c_u_soon_instruction::CONSTRAINT_VIOLATED_ERROR;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Check a write to `region` against the envelope's constraint table.
+///
+/// `table_account` must be owned by this program, be exactly [`ConstraintTable::SIZE`]
+/// bytes, and name `envelope_address`; otherwise returns [`ProgramError::IncorrectProgramId`]
+/// or [`ProgramError::InvalidAccountData`]. It must also sit at the
+/// `[CONSTRAINTS_SEED, envelope_address, bump]` PDA of its stored bump, or returns
+/// [`ProgramError::InvalidSeeds`]: other sidecar PDAs of the same size, such as the
+/// envelope's label, cannot pose as its table. Returns
+/// `Custom(CONSTRAINT_VIOLATED_ERROR)` if `region_bytes`, the region after the write,
+/// breaks any of the table's constraints on `region`.
+pub fn enforce(
+    program_id: &Address,
+    envelope_address: &Address,
+    table_account: &AccountView,
+    region: ConstraintRegion,
+    region_bytes: &[u8],
+) -> ProgramResult {
+    enforce_after(
+        program_id,
+        envelope_address,
+        table_account,
+        region,
+        region_bytes,
+        region_bytes,
+    )
+}
+
+/// [`enforce`] for a write that turned `before` into `after`, so sequenced fields
+/// (`RHS_PREVIOUS` constraints) are checked against their value before the write. Aux
+/// writes call this; the oracle region has no sequenced fields.
+pub fn enforce_after(
+    program_id: &Address,
+    envelope_address: &Address,
+    table_account: &AccountView,
+    region: ConstraintRegion,
+    before: &[u8],
+    after: &[u8],
+) -> ProgramResult {
+    if !table_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let table_data = table_account.try_borrow()?;
+    if table_data.len() != ConstraintTable::SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let table: &ConstraintTable = bytemuck::from_bytes(&table_data);
+
+    if &table.envelope != envelope_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let bump_bytes = [table.bump];
+    let seeds: [&[u8]; 3] = [
+        CONSTRAINTS_SEED,
+        envelope_address.as_array().as_ref(),
+        &bump_bytes,
+    ];
+    if table_account.address() != &create_program_address(&seeds, program_id)? {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if table.first_violation_after(region, before, after).is_some() {
+        return Err(ProgramError::Custom(CONSTRAINT_VIOLATED_ERROR));
+    }
+
+    Ok(())
+}
+
+/// Split the constraint table off the front of `accounts` if `envelope` constrains `region`,
+/// for handlers without a padding slot to carry it. Returns the table (if required) and the
+/// remaining accounts, or [`ProgramError::NotEnoughAccountKeys`] if the table is missing.
+pub fn split_table<'a>(
+    envelope: &Envelope,
+    region: ConstraintRegion,
+    accounts: &'a [AccountView],
+) -> Result<(Option<&'a AccountView>, &'a [AccountView]), ProgramError> {
+    if !envelope.is_constrained(region) {
+        return Ok((None, accounts));
+    }
+    let [table, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    Ok((Some(table), rest))
+}
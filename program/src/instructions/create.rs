@@ -1,6 +1,5 @@
-use crate::pda::create_program_address;
 use alloc::vec::Vec;
-use c_u_soon::{Envelope, Mask, StructMetadata, ENVELOPE_SEED};
+use c_u_soon::{verify_envelope_address, Envelope, Mask, StructMetadata, ENVELOPE_SEED};
 use pinocchio::{
     cpi::{Seed, Signer},
     error::ProgramError,
@@ -11,32 +10,43 @@ use pinocchio_system::instructions::{Allocate, Assign, Transfer};
 
 /// Initialize an oracle PDA account.
 ///
-/// Accounts (minimum 3): `[authority (signer), envelope_account, system_program_account, ...]`.
+/// Accounts (minimum 4): `[authority (signer), envelope_account, system_program_account,
+/// config_account, ...]`.
 ///
 /// PDA seeds: `[ENVELOPE_SEED, authority_address, ...custom_seeds, bump]`. The computed address
 /// must match `envelope_account`; otherwise returns [`ProgramError::InvalidSeeds`].
 ///
+/// `config_account` is the program config PDA, initialized or not (see [`config::load`]).
+/// While the config is paused every call fails with `Custom(PROGRAM_PAUSED_ERROR)`. A new
+/// envelope costs `authority` the config's `create_fee` on top of rent, transferred to
+/// `config_account`. Until `InitConfig` runs, creation is free and never paused.
+///
 /// Idempotent: if the envelope is already owned by this program with matching `authority`, `bump`,
-/// and `oracle_metadata`, returns `Ok(())` without touching the account.
+/// and `oracle_metadata`, returns `Ok(())` without touching the account. An existing envelope
+/// whose `emit_events` setting differs fails with [`ProgramError::InvalidArgument`].
 ///
 /// For a new account the CPI sequence is:
-/// 1. `Transfer`: top up lamports to the rent-exempt minimum if needed.
+/// 1. `Transfer`: the creation fee to `config_account`, if any, then top up lamports to
+///    the rent-exempt minimum if needed.
 /// 2. `Allocate`: set account data length to `size_of::<Envelope>()`.
 /// 3. `Assign`: transfer ownership to this program.
 ///
 /// Initializes `authority`, `bump`, and `oracle_metadata`. Both bitmasks start as `ALL_BLOCKED`.
+/// With `emit_events` (`CreateWithEvents`), every fast-path write logs a
+/// [`c_u_soon::OracleEvent`]; nothing changes the setting afterwards.
+///
+/// [`config::load`]: super::config::load
 pub fn process(
     program_id: &Address,
     accounts: &[AccountView],
     custom_seeds: Vec<Vec<u8>>,
     bump: u8,
     oracle_metadata: u64,
+    emit_events: bool,
 ) -> ProgramResult {
-    if accounts.len() < 3 {
+    let [authority, envelope_account, _system_program, config_account, ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
-    }
-    let authority = &accounts[0];
-    let envelope_account = &accounts[1];
+    };
 
     if !authority.is_signer() {
         return Err(ProgramError::MissingRequiredSignature);
@@ -51,23 +61,35 @@ pub fn process(
     seeds_vec.extend(custom_seeds_refs.iter().copied());
     seeds_vec.push(&bump_bytes);
 
-    let expected = create_program_address(&seeds_vec, program_id)?;
-    if envelope_account.address() != &expected {
+    if !verify_envelope_address(
+        program_id,
+        authority.address(),
+        &custom_seeds_refs,
+        bump,
+        envelope_account.address(),
+    ) {
         return Err(ProgramError::InvalidSeeds);
     }
 
+    let config = super::config::check(program_id, config_account)?;
+
     // Idempotent: if envelope already exists with correct authority/bump, succeed
     if envelope_account.owned_by(program_id) {
         let envelope_data = envelope_account.try_borrow()?;
-        let envelope: &Envelope = bytemuck::from_bytes(&envelope_data);
+        let envelope: &Envelope =
+            Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
         if envelope.authority != *authority.address() {
             return Err(ProgramError::IncorrectAuthority);
         }
         if envelope.bump != bump {
             return Err(ProgramError::InvalidSeeds);
         }
-        if envelope.oracle_state.oracle_metadata != StructMetadata::from_raw(oracle_metadata) {
-            return Err(ProgramError::InvalidInstructionData);
+        super::metadata::check(
+            envelope.oracle_state.oracle_metadata,
+            StructMetadata::from_raw(oracle_metadata),
+        )?;
+        if envelope.emits_events() != emit_events {
+            return Err(ProgramError::InvalidArgument);
         }
         return Ok(());
     }
@@ -79,6 +101,15 @@ pub fn process(
         return Err(ProgramError::InvalidAccountData);
     }
 
+    if let Some(config) = config.filter(|config| config.create_fee > 0) {
+        Transfer {
+            from: authority,
+            to: config_account,
+            lamports: config.create_fee,
+        }
+        .invoke()?;
+    }
+
     let rent_exempt_lamports =
         pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(Envelope::SIZE)?;
     let current_lamports = envelope_account.lamports();
@@ -108,13 +139,15 @@ pub fn process(
     .invoke_signed(core::slice::from_ref(&signer))?;
 
     let mut envelope_data = envelope_account.try_borrow_mut()?;
-    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut envelope_data);
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
     envelope.authority = *authority.address();
     envelope.bump = bump;
     envelope.program_bitmask = Mask::ALL_BLOCKED;
     envelope.user_bitmask = Mask::ALL_BLOCKED;
     envelope.auxiliary_metadata = StructMetadata::ZERO;
     envelope.oracle_state.oracle_metadata = StructMetadata::from_raw(oracle_metadata);
+    envelope.emit_events = emit_events as u8;
 
     Ok(())
 }
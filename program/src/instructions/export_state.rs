@@ -0,0 +1,41 @@
+use crate::hash::sha256;
+use c_u_soon::Envelope;
+use c_u_soon_instruction::EXPORT_STATE_RETURN_SIZE;
+use pinocchio::{
+    cpi::set_return_data, error::ProgramError, sysvars::clock::Clock, sysvars::Sysvar, AccountView,
+    Address, ProgramResult,
+};
+
+/// Return a hash of the whole envelope without modifying anything.
+///
+/// Accounts: `[envelope_account]` (readonly).
+///
+/// Sets the transaction return data to `[slot:8][state_hash:32]`, which
+/// [`split_export_state`] decodes: the `Clock` slot and the SHA-256 of the
+/// `size_of::<Envelope>()` bytes at the front of the account. Extended aux bytes past the
+/// `Envelope` are not covered. An auditor records the hash from the transaction instead of
+/// fetching and hashing the account, and anyone holding the account data at that slot can
+/// check it (`envelope_state_hash` in the client). Gated and paused envelopes are hashed
+/// like any other, since the hash reveals nothing a consumer could act on.
+///
+/// [`split_export_state`]: c_u_soon_instruction::split_export_state
+pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [envelope_account] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let envelope_data = envelope_account.try_borrow()?;
+    let envelope: &Envelope =
+        Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+    let mut buf = [0u8; EXPORT_STATE_RETURN_SIZE];
+    buf[..8].copy_from_slice(&Clock::get()?.slot.to_le_bytes());
+    buf[8..].copy_from_slice(&sha256(&[bytemuck::bytes_of(envelope)]));
+    set_return_data(&buf);
+
+    Ok(())
+}
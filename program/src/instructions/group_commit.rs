@@ -0,0 +1,121 @@
+use super::trailer;
+use c_u_soon::{ConstraintRegion, Envelope, GroupAnchor, StructMetadata};
+use c_u_soon_instruction::GroupMemberUpdate;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Write one oracle payload to every member of a group under a shared sequence.
+///
+/// Accounts: `[authority (signer), group_anchor_account]`, followed by the anchor's member
+/// envelopes in the order stored on the anchor. Any other set or order returns
+/// [`ProgramError::InvalidArgument`]. The constraint table of each member with oracle
+/// constraints follows the members, in member order (see
+/// [`constraints::enforce`](super::constraints::enforce)), and the instructions sysvar
+/// follows last if any member has the mutation guard (see
+/// [`mutation_guard::enforce`](super::mutation_guard::enforce)).
+///
+/// `updates` holds one entry per member, in the same order. `sequence` must be strictly
+/// greater than the anchor's sequence and every member's oracle sequence
+/// ([`ProgramError::InvalidInstructionData`]), and each `oracle_meta` must match the member's
+/// `oracle_metadata` (see [`metadata::check`](super::metadata::check)), as on the fast path.
+/// On success every member's oracle sequence and the anchor's sequence equal `sequence`.
+/// Payload bytes past `payload.len()` keep their previous values. Members with `FLAG_TWAP`,
+/// `FLAG_UPDATE_STAMP`, or `FLAG_CONFLATION` update their oracle trailers (see
+/// [`trailer::record`]).
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    sequence: u64,
+    updates: &[GroupMemberUpdate],
+) -> ProgramResult {
+    let (accounts, instructions_sysvar) = super::mutation_guard::split_sysvar(accounts);
+    let [authority, group_anchor_account, accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !group_anchor_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut anchor_data = group_anchor_account.try_borrow_mut()?;
+    if anchor_data.len() != GroupAnchor::SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let anchor: &mut GroupAnchor = bytemuck::from_bytes_mut(&mut anchor_data);
+
+    if &anchor.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    let expected = anchor.active();
+    if accounts.len() < expected.len() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (members, tables) = accounts.split_at(expected.len());
+    let mut tables = tables.iter();
+    if members
+        .iter()
+        .zip(expected)
+        .any(|(member, address)| member.address() != address)
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if updates.len() != members.len() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if sequence <= anchor.sequence {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    for (member, update) in members.iter().zip(updates) {
+        if !member.owned_by(program_id) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut envelope_data = member.try_borrow_mut()?;
+        let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+            .ok_or(ProgramError::InvalidAccountData)?;
+
+        if &envelope.authority != authority.address() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+        super::paused::check(envelope)?;
+
+        let oracle_state = &mut envelope.oracle_state;
+        super::metadata::check(
+            oracle_state.oracle_metadata,
+            StructMetadata::from_raw(update.oracle_meta),
+        )?;
+        if sequence <= oracle_state.sequence {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        super::mutation_guard::enforce(envelope, member.address(), instructions_sysvar)?;
+        trailer::record(envelope, &update.payload)?;
+        let oracle_state = &mut envelope.oracle_state;
+        oracle_state.sequence = sequence;
+        oracle_state.data[..update.payload.len()].copy_from_slice(&update.payload);
+
+        if envelope.is_constrained(ConstraintRegion::Oracle) {
+            let table = tables.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            super::constraints::enforce(
+                program_id,
+                member.address(),
+                table,
+                ConstraintRegion::Oracle,
+                &envelope.oracle_state.data,
+            )?;
+        }
+    }
+
+    if tables.next().is_some() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    anchor.sequence = sequence;
+
+    Ok(())
+}
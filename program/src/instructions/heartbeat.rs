@@ -0,0 +1,52 @@
+use c_u_soon::Envelope;
+use pinocchio::{
+    error::ProgramError, sysvars::clock::Clock, sysvars::Sysvar, AccountView, Address,
+    ProgramResult,
+};
+
+use super::paused;
+
+/// Record that an envelope's oracle value is still current.
+///
+/// Accounts: `[cranker (signer), envelope_account, padding]`. The padding account keeps
+/// the instruction off the two-account fast path and is not read. When the envelope has the
+/// mutation guard, the instructions sysvar follows last (see
+/// [`mutation_guard::enforce`](super::mutation_guard::enforce)).
+///
+/// Permissionless: `cranker` may be any signer. If the oracle is still at `sequence`,
+/// stores the `Clock` slot in `envelope.heartbeat_slot`; otherwise fails with
+/// `InvalidInstructionData`, as a stale fast-path write does, since the value the cranker
+/// vouched for has been replaced. The oracle, its trailers, and its sequence are left
+/// untouched, so consumers can tell "unchanged since the last write" from "publisher down"
+/// without a write that burns a sequence number. Fails with `Custom(ENVELOPE_PAUSED_ERROR)`
+/// on a paused envelope, whose value nobody is vouching for.
+pub fn process(program_id: &Address, accounts: &[AccountView], sequence: u64) -> ProgramResult {
+    let (accounts, instructions_sysvar) = super::mutation_guard::split_sysvar(accounts);
+    let [cranker, envelope_account, _padding] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !cranker.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    paused::check(envelope)?;
+
+    if envelope.oracle_state.sequence != sequence {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    super::mutation_guard::enforce(envelope, envelope_account.address(), instructions_sysvar)?;
+
+    envelope.heartbeat_slot = Clock::get()?.slot;
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use crate::pda::create_program_address;
+use c_u_soon::{ProgramConfig, CONFIG_SEED};
+use pinocchio::{
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::Sysvar,
+    AccountView, Address, ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, Transfer};
+
+/// Address of the upgradeable BPF loader (`BPFLoaderUpgradeab1e11111111111111111111111`).
+pub const BPF_LOADER_UPGRADEABLE_ID: Address = Address::new_from_array([
+    0x02, 0xa8, 0xf6, 0x91, 0x4e, 0x88, 0xa1, 0xb0, 0xe2, 0x10, 0x15, 0x3e, 0xf7, 0x63, 0xae, 0x2b,
+    0x00, 0xc2, 0xb9, 0x3d, 0x16, 0xc1, 0x24, 0xd2, 0xc0, 0x53, 0x7a, 0x10, 0x04, 0x80, 0x00, 0x00,
+]);
+
+/// Loader state tag of a program account: `[2u32][programdata_address:32]`.
+const LOADER_PROGRAM_TAG: [u8; 4] = 2u32.to_le_bytes();
+
+/// Loader state tag of a program data account:
+/// `[3u32][slot:8][has_upgrade_authority:1][upgrade_authority:32]...`.
+const LOADER_PROGRAM_DATA_TAG: [u8; 4] = 3u32.to_le_bytes();
+
+/// Fail with [`ProgramError::IncorrectAuthority`] unless `upgrade_authority` is the upgrade
+/// authority of `program_id`, as recorded by the upgradeable loader.
+fn verify_upgrade_authority(
+    program_id: &Address,
+    upgrade_authority: &AccountView,
+    program: &AccountView,
+    program_data: &AccountView,
+) -> ProgramResult {
+    if program.address() != program_id
+        || !program.owned_by(&BPF_LOADER_UPGRADEABLE_ID)
+        || !program_data.owned_by(&BPF_LOADER_UPGRADEABLE_ID)
+    {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    {
+        let program_bytes = program.try_borrow()?;
+        if program_bytes.len() < 36
+            || program_bytes[..4] != LOADER_PROGRAM_TAG
+            || program_bytes[4..36] != *program_data.address().as_array()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    let data = program_data.try_borrow()?;
+    if data.len() < 45 || data[..4] != LOADER_PROGRAM_DATA_TAG {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if data[12] != 1 || data[13..45] != *upgrade_authority.address().as_array() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+    Ok(())
+}
+
+/// Create the program config, once.
+///
+/// Accounts: `[upgrade_authority (signer), config_account, system_program_account,
+/// program_account, program_data_account]`.
+///
+/// `upgrade_authority` must be the upgrade authority recorded in the program's loader
+/// accounts: `program_account` is this program and `program_data_account` its program data.
+/// A program without an upgrade authority cannot create a config, so its envelopes stay
+/// free to create.
+///
+/// PDA seeds: `[CONFIG_SEED, bump]`. The computed address must match `config_account`;
+/// otherwise returns [`ProgramError::InvalidSeeds`]. The account is created here (same
+/// `Transfer`/`Allocate`/`Assign` sequence as `create`), with `upgrade_authority` paying
+/// rent. If it already belongs to this program the call fails with
+/// [`ProgramError::AccountAlreadyInitialized`]; later changes go through `SetConfig`.
+///
+/// Stores `admin` and `create_fee`, unpaused.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    bump: u8,
+    admin: &[u8; 32],
+    create_fee: u64,
+) -> ProgramResult {
+    let [upgrade_authority, config_account, _system_program, program, program_data] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !upgrade_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    verify_upgrade_authority(program_id, upgrade_authority, program, program_data)?;
+
+    let bump_bytes = [bump];
+    let seeds: [&[u8]; 2] = [CONFIG_SEED, &bump_bytes];
+    let expected = create_program_address(&seeds, program_id)?;
+    if config_account.address() != &expected {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if config_account.owned_by(program_id) {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    if !config_account.owned_by(&pinocchio_system::ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if config_account.data_len() != 0 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rent_exempt_lamports =
+        pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(ProgramConfig::SIZE)?;
+    let current_lamports = config_account.lamports();
+    if current_lamports < rent_exempt_lamports {
+        Transfer {
+            from: upgrade_authority,
+            to: config_account,
+            lamports: rent_exempt_lamports - current_lamports,
+        }
+        .invoke()?;
+    }
+
+    let signer_seeds = [Seed::from(seeds[0]), Seed::from(seeds[1])];
+    let signer = Signer::from(&signer_seeds[..]);
+
+    Allocate {
+        account: config_account,
+        space: ProgramConfig::SIZE as u64,
+    }
+    .invoke_signed(core::slice::from_ref(&signer))?;
+
+    Assign {
+        account: config_account,
+        owner: program_id,
+    }
+    .invoke_signed(core::slice::from_ref(&signer))?;
+
+    let mut config_data = config_account.try_borrow_mut()?;
+    let config: &mut ProgramConfig = bytemuck::from_bytes_mut(&mut config_data);
+    config.admin = Address::new_from_array(*admin);
+    config.create_fee = create_fee;
+    config.bump = bump;
+    config.paused = 0;
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use c_u_soon::{MetadataDiff, StructMetadata};
+use c_u_soon_instruction::{METADATA_HASH_MISMATCH_ERROR, METADATA_SIZE_MISMATCH_ERROR};
+use pinocchio::{error::ProgramError, ProgramResult};
+
+/// Fail unless `given` equals the `stored` metadata, with
+/// [`METADATA_SIZE_MISMATCH_ERROR`] or [`METADATA_HASH_MISMATCH_ERROR`] depending on how
+/// they differ.
+#[inline(always)]
+pub fn check(stored: StructMetadata, given: StructMetadata) -> ProgramResult {
+    if stored != given {
+        return Err(mismatch(stored, given));
+    }
+    Ok(())
+}
+
+/// The error for `given` not matching `stored`. Kept out of line so the comparison stays
+/// cheap on the fast path.
+#[cold]
+pub fn mismatch(stored: StructMetadata, given: StructMetadata) -> ProgramError {
+    match stored.diff(&given) {
+        MetadataDiff::Size { .. } => ProgramError::Custom(METADATA_SIZE_MISMATCH_ERROR),
+        MetadataDiff::Hash | MetadataDiff::Same => {
+            ProgramError::Custom(METADATA_HASH_MISMATCH_ERROR)
+        }
+    }
+}
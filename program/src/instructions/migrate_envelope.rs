@@ -0,0 +1,55 @@
+use c_u_soon::{Envelope, LEGACY_ENVELOPE_SIZE};
+use pinocchio::{error::ProgramError, sysvars::Sysvar, AccountView, Address, ProgramResult};
+use pinocchio_system::instructions::Transfer;
+
+/// Grow an envelope created before `publisher`, `heartbeat_slot`, `write_hook`, and
+/// `mutation_marker` were added to [`Envelope::SIZE`], so the handlers that read the current
+/// layout accept it again.
+///
+/// Accounts: `[payer (signer, writable), envelope_account (writable), system_program_account]`.
+///
+/// Permissionless: the new fields are zeroed, which is their unset value (no publisher, no
+/// heartbeat, no write hook, no guarded write yet), and nothing else changes, so the envelope behaves exactly as
+/// it did before it was migrated. If the new size needs more lamports to stay rent-exempt,
+/// `payer` pays the difference.
+///
+/// The envelope must be owned by this program ([`ProgramError::IncorrectProgramId`]) and at
+/// least [`LEGACY_ENVELOPE_SIZE`] but less than [`Envelope::SIZE`] bytes long; any other
+/// length, including an envelope that is already current, returns
+/// [`ProgramError::InvalidAccountData`]. Bytes past `LEGACY_ENVELOPE_SIZE` that the
+/// account already has are kept.
+pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [payer, envelope_account, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let old_len = envelope_account.data_len();
+    if !(LEGACY_ENVELOPE_SIZE..Envelope::SIZE).contains(&old_len) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rent_exempt_lamports =
+        pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(Envelope::SIZE)?;
+    let current_lamports = envelope_account.lamports();
+    if current_lamports < rent_exempt_lamports {
+        Transfer {
+            from: payer,
+            to: envelope_account,
+            lamports: rent_exempt_lamports - current_lamports,
+        }
+        .invoke()?;
+    }
+
+    envelope_account.resize(Envelope::SIZE)?;
+    envelope_account.try_borrow_mut()?[old_len..].fill(0);
+
+    Ok(())
+}
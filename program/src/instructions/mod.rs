@@ -1,11 +1,55 @@
+pub mod aggregate;
 pub mod apply_ranges;
+pub mod assert_aux_hash;
+pub mod batch_update;
 pub mod clear_delegation;
 pub mod close;
+pub mod config;
+pub mod constraints;
 pub mod cpi_verification;
 pub mod create;
+pub mod export_state;
+pub mod group_commit;
+pub mod heartbeat;
+pub mod init_config;
+pub mod metadata;
+pub mod migrate_envelope;
+pub mod mutation_guard;
+pub mod paused;
+pub mod publisher_update;
+pub mod range_guard;
+pub mod read_aux;
+pub mod read_aux_range;
+pub mod read_oracle;
+pub mod read_oracle_gated;
+pub mod resize;
+pub mod seal_auxiliary_range;
+pub mod set_config;
+pub mod set_conflation;
+pub mod set_constraints;
+pub mod set_delegate_schema;
 pub mod set_delegated_program;
+pub mod set_group_members;
+pub mod set_label;
+pub mod set_mutation_guard;
+pub mod set_oracle_metadata;
+pub mod set_paused;
+pub mod set_permanent;
+pub mod set_publisher;
+pub mod set_publishers;
+pub mod set_range_guards;
+pub mod set_readers;
+pub mod set_twap;
+pub mod set_update_stamp;
+pub mod set_write_hook;
+pub mod split_update;
+pub mod trailer;
+pub mod transfer_delegation;
 pub mod update_auxiliary;
 pub mod update_auxiliary_delegated;
 pub mod update_auxiliary_delegated_multi_range;
+pub mod update_auxiliary_delegated_schema;
 pub mod update_auxiliary_force;
 pub mod update_auxiliary_multi_range;
+pub mod update_ext_aux;
+pub mod write_hook;
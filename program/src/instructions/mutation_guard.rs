@@ -0,0 +1,84 @@
+use crate::hash::sha256;
+use crate::sysvar::{writable_instruction_count, INSTRUCTIONS_SYSVAR_ID};
+use c_u_soon::Envelope;
+use pinocchio::{
+    error::ProgramError, sysvars::clock::Clock, sysvars::Sysvar, AccountView, Address,
+    ProgramResult,
+};
+
+/// Reject a write to an envelope with the mutation guard if the envelope was already
+/// written in this transaction, and record this write. No-op for unguarded envelopes.
+///
+/// `instructions_sysvar` is the handler's last account (see [`split_sysvar`] for handlers
+/// whose trailing accounts are otherwise all of one kind). Missing, it fails with
+/// [`ProgramError::NotEnoughAccountKeys`]; another account fails with
+/// [`ProgramError::UnsupportedSysvar`].
+///
+/// Two checks make up the guard, both failing with [`ProgramError::InvalidArgument`]:
+///
+/// - Any instruction that can write the envelope, directly or through CPI, must list it
+///   writable at the top level, so only one top-level instruction may do so.
+/// - Repeated CPIs from within that instruction all see the same sysvar, so each write
+///   stores a marker derived from the `Clock` slot and the sysvar data, which ends with
+///   the index of the current top-level instruction, in
+///   [`Envelope::mutation_marker`]. A write carrying the stored marker is a second write
+///   from the same top-level instruction.
+pub fn enforce(
+    envelope: &mut Envelope,
+    envelope_address: &Address,
+    instructions_sysvar: Option<&AccountView>,
+) -> ProgramResult {
+    guard(envelope, envelope_address, instructions_sysvar, 1)
+}
+
+/// [`enforce`] for the two halves of a `SplitUpdate`, which are adjacent top-level
+/// instructions that both take the envelope writable and count as one write.
+pub fn enforce_split(
+    envelope: &mut Envelope,
+    envelope_address: &Address,
+    instructions_sysvar: &AccountView,
+) -> ProgramResult {
+    guard(envelope, envelope_address, Some(instructions_sysvar), 2)
+}
+
+/// The accounts before the instructions sysvar and the sysvar, if it is the last account.
+///
+/// For handlers that take a variable number of envelopes, sources, or signers after their
+/// fixed accounts, so the sysvar is not mistaken for one of them.
+pub fn split_sysvar(accounts: &[AccountView]) -> (&[AccountView], Option<&AccountView>) {
+    match accounts.split_last() {
+        Some((last, rest)) if last.address() == &INSTRUCTIONS_SYSVAR_ID => (rest, Some(last)),
+        _ => (accounts, None),
+    }
+}
+
+fn guard(
+    envelope: &mut Envelope,
+    envelope_address: &Address,
+    instructions_sysvar: Option<&AccountView>,
+    writers: usize,
+) -> ProgramResult {
+    if !envelope.has_mutation_guard() {
+        return Ok(());
+    }
+
+    let instructions_sysvar = instructions_sysvar.ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if instructions_sysvar.address() != &INSTRUCTIONS_SYSVAR_ID {
+        return Err(ProgramError::UnsupportedSysvar);
+    }
+
+    let sysvar_data = instructions_sysvar.try_borrow()?;
+    if writable_instruction_count(&sysvar_data, envelope_address)? > writers {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let slot = Clock::get()?.slot;
+    let hash = sha256(&[&slot.to_le_bytes(), &sysvar_data]);
+    let marker = u64::from_le_bytes(hash[..8].try_into().unwrap());
+    if envelope.mutation_marker == marker {
+        return Err(ProgramError::InvalidArgument);
+    }
+    envelope.mutation_marker = marker;
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use c_u_soon::Envelope;
+use c_u_soon_instruction::ENVELOPE_PAUSED_ERROR;
+use pinocchio::{error::ProgramError, ProgramResult};
+
+/// Fail with [`ENVELOPE_PAUSED_ERROR`] if `envelope` is paused (see
+/// [`set_paused`](super::set_paused)). Every oracle and aux write calls it once its signer
+/// is checked, so an unauthorized signer still gets its own error.
+#[inline(always)]
+pub fn check(envelope: &Envelope) -> ProgramResult {
+    if envelope.is_paused() {
+        return Err(ProgramError::Custom(ENVELOPE_PAUSED_ERROR));
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use super::trailer;
+use crate::pda::create_program_address;
+use c_u_soon::{
+    ConstraintRegion, Envelope, PublisherSet, StructMetadata, ORACLE_BYTES, PUBLISHERS_SEED,
+};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Fast-path style oracle update signed by registered publishers instead of the authority.
+///
+/// Accounts: `[envelope_account (writable), publisher_set_account, publisher_0 (signer), ..]`.
+/// If the envelope has oracle constraints, its constraint table goes between the publisher
+/// set and the publishers (see [`constraints::split_table`](super::constraints::split_table)).
+/// If it has the mutation guard, the instructions sysvar follows the publishers (see
+/// [`mutation_guard::enforce`](super::mutation_guard::enforce)).
+/// There are always at least three accounts, so the instruction never takes the fast path.
+///
+/// `publisher_set_account` must be owned by this program, be exactly [`PublisherSet::SIZE`]
+/// bytes, and name `envelope_account`; otherwise returns [`ProgramError::IncorrectProgramId`]
+/// or [`ProgramError::InvalidAccountData`]. It must also be at the
+/// `[PUBLISHERS_SEED, envelope_address, bump]` PDA of its stored bump
+/// ([`ProgramError::InvalidSeeds`]), so a reader list, which has the same layout, cannot
+/// pass as the set. Every publisher account must sign
+/// ([`ProgramError::MissingRequiredSignature`]), and together they must include at least
+/// `threshold` distinct registered publishers ([`ProgramError::IncorrectAuthority`]).
+///
+/// The write itself is checked and applied like a fast-path update: `oracle_meta` must equal
+/// the envelope's `oracle_metadata` (see [`metadata::check`](super::metadata::check)),
+/// `sequence` must be strictly greater than the stored sequence
+/// ([`ProgramError::InvalidInstructionData`]), the oracle trailers are updated (see
+/// [`trailer::record`]), and payload bytes past its length keep their previous values.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    oracle_meta: u64,
+    sequence: u64,
+    payload: &[u8],
+) -> ProgramResult {
+    let (accounts, instructions_sysvar) = super::mutation_guard::split_sysvar(accounts);
+    let [envelope_account, publisher_set_account, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if payload.len() > ORACLE_BYTES {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if !envelope_account.owned_by(program_id) || !publisher_set_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    let (table, publishers) =
+        super::constraints::split_table(envelope, ConstraintRegion::Oracle, rest)?;
+
+    {
+        let set_data = publisher_set_account.try_borrow()?;
+        if set_data.len() != PublisherSet::SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let set: &PublisherSet = bytemuck::from_bytes(&set_data);
+        if &set.envelope != envelope_account.address() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let bump_bytes = [set.bump];
+        let seeds: [&[u8]; 3] = [
+            PUBLISHERS_SEED,
+            envelope_account.address().as_array().as_ref(),
+            &bump_bytes,
+        ];
+        if publisher_set_account.address() != &create_program_address(&seeds, program_id)? {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if publishers.iter().any(|p| !p.is_signer()) {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if !set.is_satisfied_by(publishers.iter().map(|p| p.address())) {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+    }
+
+    super::metadata::check(
+        envelope.oracle_state.oracle_metadata,
+        StructMetadata::from_raw(oracle_meta),
+    )?;
+    super::paused::check(envelope)?;
+
+    if sequence <= envelope.oracle_state.sequence {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    super::mutation_guard::enforce(envelope, envelope_account.address(), instructions_sysvar)?;
+    trailer::record(envelope, payload)?;
+    let oracle_state = &mut envelope.oracle_state;
+    oracle_state.sequence = sequence;
+    oracle_state.data[..payload.len()].copy_from_slice(payload);
+
+    if let Some(table) = table {
+        super::constraints::enforce(
+            program_id,
+            envelope_account.address(),
+            table,
+            ConstraintRegion::Oracle,
+            &envelope.oracle_state.data,
+        )?;
+    }
+
+    Ok(())
+}
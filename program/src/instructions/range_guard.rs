@@ -0,0 +1,53 @@
+use crate::sysvar::{transaction_has_program, INSTRUCTIONS_SYSVAR_ID};
+use c_u_soon::{RangeGuard, AUX_DATA_SIZE};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Check a delegated auxiliary write against the envelope's range allow-list.
+///
+/// Accounts (trailing, after the handler's own): `[range_guard, instructions_sysvar, ...]`.
+///
+/// `range_guard` must be owned by this program, be exactly [`RangeGuard::SIZE`] bytes, and
+/// name `envelope_address`. For every active entry whose bytes differ between `before` and
+/// `after`, the transaction must contain a top-level instruction targeting the entry's
+/// `program_id`; otherwise returns [`ProgramError::InvalidArgument`], the same error as a
+/// bitmask violation.
+pub fn enforce(
+    program_id: &Address,
+    envelope_address: &Address,
+    accounts: &[AccountView],
+    before: &[u8; AUX_DATA_SIZE],
+    after: &[u8; AUX_DATA_SIZE],
+) -> ProgramResult {
+    let [range_guard, instructions_sysvar, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !range_guard.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if instructions_sysvar.address() != &INSTRUCTIONS_SYSVAR_ID {
+        return Err(ProgramError::UnsupportedSysvar);
+    }
+
+    let guard_data = range_guard.try_borrow()?;
+    if guard_data.len() != RangeGuard::SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let guard: &RangeGuard = bytemuck::from_bytes(&guard_data);
+
+    if &guard.envelope != envelope_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let sysvar_data = instructions_sysvar.try_borrow()?;
+    for entry in guard.active() {
+        if entry.is_touched(before, after)
+            && !transaction_has_program(&sysvar_data, &entry.program_id)?
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    Ok(())
+}
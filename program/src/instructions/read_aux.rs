@@ -0,0 +1,32 @@
+use c_u_soon::Envelope;
+use c_u_soon_instruction::READ_AUX_RETURN_SIZE;
+use pinocchio::{cpi::set_return_data, error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Return the auxiliary metadata and data without modifying anything.
+///
+/// Accounts: `[envelope_account]` (readonly).
+///
+/// Sets the transaction return data to `[auxiliary_metadata:8][auxiliary_data:256]`, which
+/// [`split_read_aux`] decodes. Use `ReadAuxRange` when only a slice is needed.
+///
+/// [`split_read_aux`]: c_u_soon_instruction::split_read_aux
+pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [envelope_account] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let envelope_data = envelope_account.try_borrow()?;
+    let envelope: &Envelope =
+        Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+    let mut buf = [0u8; READ_AUX_RETURN_SIZE];
+    buf[..8].copy_from_slice(bytemuck::bytes_of(&envelope.auxiliary_metadata));
+    buf[8..].copy_from_slice(&envelope.auxiliary_data);
+    set_return_data(&buf);
+
+    Ok(())
+}
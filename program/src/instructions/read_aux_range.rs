@@ -0,0 +1,41 @@
+use c_u_soon::Envelope;
+use pinocchio::{cpi::set_return_data, error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Return a slice of auxiliary data without modifying anything.
+///
+/// Accounts: `[envelope_account]` (readonly).
+///
+/// Sets the transaction return data to `auxiliary_data[offset..offset + len]`. Callers
+/// reading it after a CPI should check that the return data's program id is this program;
+/// the ownership check here makes that sufficient to trust the bytes. `offset` and `len`
+/// are bounds-checked by [`SlowPathInstruction::validate`], so at most 256 bytes are
+/// returned.
+///
+/// [`SlowPathInstruction::validate`]: c_u_soon_instruction::SlowPathInstruction::validate
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    offset: u16,
+    len: u16,
+) -> ProgramResult {
+    let [envelope_account] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let envelope_data = envelope_account.try_borrow()?;
+    let envelope: &Envelope =
+        Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+    let start = offset as usize;
+    let bytes = envelope
+        .auxiliary_data
+        .get(start..start + len as usize)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    set_return_data(bytes);
+
+    Ok(())
+}
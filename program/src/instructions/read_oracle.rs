@@ -0,0 +1,39 @@
+use c_u_soon::Envelope;
+use c_u_soon_instruction::READ_ORACLE_RETURN_SIZE;
+use pinocchio::{cpi::set_return_data, error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Return the oracle state without modifying anything.
+///
+/// Accounts: `[envelope_account]` (readonly).
+///
+/// Sets the transaction return data to `[oracle_metadata:8][sequence:8][data:239]`, the
+/// same bytes as the front of `OracleState`. Callers decode it with
+/// [`split_read_oracle`] rather than offsets into `Envelope`, and should check that the
+/// return data's program id is this program.
+///
+/// Returns [`ProgramError::IncorrectAuthority`] if the envelope has [`FLAG_GATED_READS`] set;
+/// such envelopes are read through `ReadOracleGated`.
+///
+/// [`split_read_oracle`]: c_u_soon_instruction::split_read_oracle
+/// [`FLAG_GATED_READS`]: c_u_soon::FLAG_GATED_READS
+pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [envelope_account] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let envelope_data = envelope_account.try_borrow()?;
+    let envelope: &Envelope =
+        Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+    if envelope.has_gated_reads() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    set_return_data(&bytemuck::bytes_of(&envelope.oracle_state)[..READ_ORACLE_RETURN_SIZE]);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use crate::pda::create_program_address;
+use crate::sysvar::{current_top_level_program, INSTRUCTIONS_SYSVAR_ID};
+use c_u_soon::{Envelope, ReaderList, READERS_SEED};
+use c_u_soon_instruction::READ_ORACLE_RETURN_SIZE;
+use pinocchio::{cpi::set_return_data, error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Return the oracle state to a program on the envelope's reader list.
+///
+/// Accounts: `[envelope_account, readers_account, instructions_sysvar]` (all readonly).
+///
+/// The caller is the program id of the top-level instruction currently executing, read
+/// from the instructions sysvar. It must be in the `ReaderList` stored for this envelope;
+/// otherwise returns [`ProgramError::IncorrectAuthority`]. A `readers_account` that is not
+/// this program's list for `envelope_account` returns [`ProgramError::InvalidAccountData`],
+/// and one that is not at the `[READERS_SEED, envelope_address, bump]` PDA of its stored
+/// bump returns [`ProgramError::InvalidSeeds`], so a publisher set, which has the same
+/// layout, cannot pass as the list.
+///
+/// On success sets the same return data as `ReadOracle`. This gates the instruction, not
+/// the account: envelope data stays readable by anyone who fetches the account directly.
+pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [envelope_account, readers_account, instructions_sysvar] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !envelope_account.owned_by(program_id) || !readers_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if instructions_sysvar.address() != &INSTRUCTIONS_SYSVAR_ID {
+        return Err(ProgramError::UnsupportedSysvar);
+    }
+
+    let list_data = readers_account.try_borrow()?;
+    if list_data.len() != ReaderList::SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let list: &ReaderList = bytemuck::from_bytes(&list_data);
+    if &list.envelope != envelope_account.address() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let bump_bytes = [list.bump];
+    let seeds: [&[u8]; 3] = [
+        READERS_SEED,
+        envelope_account.address().as_array().as_ref(),
+        &bump_bytes,
+    ];
+    if readers_account.address() != &create_program_address(&seeds, program_id)? {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    {
+        let sysvar_data = instructions_sysvar.try_borrow()?;
+        let caller = current_top_level_program(&sysvar_data)?;
+        if !list.active().iter().any(|reader| reader.as_ref() == caller) {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+    }
+
+    let envelope_data = envelope_account.try_borrow()?;
+    let envelope: &Envelope =
+        Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+    set_return_data(&bytemuck::bytes_of(&envelope.oracle_state)[..READ_ORACLE_RETURN_SIZE]);
+
+    Ok(())
+}
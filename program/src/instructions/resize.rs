@@ -0,0 +1,85 @@
+use c_u_soon::{ext_account_size, Envelope};
+use pinocchio::{error::ProgramError, sysvars::Sysvar, AccountView, Address, ProgramResult};
+use pinocchio_system::instructions::Transfer;
+
+/// Grow, shrink, or remove the envelope's extended auxiliary region.
+///
+/// Accounts: `[authority (signer, writable), envelope_account, system_program_account]`.
+///
+/// Reallocs the envelope to `ext_account_size(ext_len)` bytes; `ext_len == 0` returns it to
+/// `Envelope::SIZE`. If the new size needs more lamports to stay rent-exempt, `authority`
+/// pays the difference. When the account shrinks, lamports above the new minimum are
+/// refunded to `authority`.
+///
+/// If the extension already exists with the same `type_hash`, its contents and sequence are
+/// kept (truncated when shrinking) and any added bytes are zeroed. Otherwise the whole
+/// extension is zeroed, its sequence reset to 0, and `type_hash` recorded.
+///
+/// A resized envelope is no longer the exact size the fast path reads at constant offsets,
+/// so fast-path updates to it take a slower fallback that applies the same checks and
+/// costs more than [`COMPUTE_BUDGET`](crate::fast_path::COMPUTE_BUDGET).
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    type_hash: u64,
+    ext_len: u16,
+) -> ProgramResult {
+    let [authority, envelope_account, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let old_len = envelope_account.data_len();
+    let previous_type = {
+        let envelope_data = envelope_account.try_borrow()?;
+        let envelope: &Envelope =
+            Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+        if envelope.authority != *authority.address() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+        Envelope::ext_aux_header(&envelope_data).map(|header| header.type_hash)
+    };
+
+    let new_len = ext_account_size(ext_len as usize);
+    let rent_exempt_lamports =
+        pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(new_len)?;
+    let current_lamports = envelope_account.lamports();
+    if current_lamports < rent_exempt_lamports {
+        Transfer {
+            from: authority,
+            to: envelope_account,
+            lamports: rent_exempt_lamports - current_lamports,
+        }
+        .invoke()?;
+    } else if new_len < old_len {
+        let refund = current_lamports - rent_exempt_lamports;
+        envelope_account.set_lamports(rent_exempt_lamports);
+        authority.set_lamports(authority.lamports() + refund);
+    }
+
+    envelope_account.resize(new_len)?;
+    if ext_len == 0 {
+        return Ok(());
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    if previous_type == Some(type_hash) {
+        if new_len > old_len {
+            envelope_data[old_len..].fill(0);
+        }
+    } else {
+        envelope_data[Envelope::SIZE..].fill(0);
+        Envelope::ext_aux_header_mut(&mut envelope_data)
+            .ok_or(ProgramError::InvalidAccountData)?
+            .type_hash = type_hash;
+    }
+
+    Ok(())
+}
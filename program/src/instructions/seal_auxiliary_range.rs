@@ -0,0 +1,53 @@
+use c_u_soon::Envelope;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Make `auxiliary_data[offset..offset + len]` permanently immutable.
+///
+/// Accounts: `[authority (signer), envelope_account, padding]`. The padding account keeps
+/// the instruction off the two-account fast path and is not read.
+///
+/// Marks the range [`SEALED_MASK_BYTE`](c_u_soon::SEALED_MASK_BYTE) in both bitmasks, with or
+/// without an active delegation. From then on no write changes those bytes: masked writes
+/// fail with `InvalidArgument` as for any blocked byte, forced writes fail if they would
+/// change one, `ClearDelegation` leaves them in place, and a later `SetDelegatedProgram`
+/// cannot unseal them. Write the value first, then seal it. Sealing an already sealed byte
+/// succeeds without change. The range is checked by
+/// [`SlowPathInstruction::validate`](c_u_soon_instruction::SlowPathInstruction::validate).
+///
+/// Sealing does not stop `Close`; pair it with `SetPermanent` for values that must outlive
+/// every later authority action.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    offset: u16,
+    len: u16,
+) -> ProgramResult {
+    let [authority, envelope_account, _padding] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if &envelope.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    envelope
+        .program_bitmask
+        .seal_range(offset as usize, len as usize);
+    envelope
+        .user_bitmask
+        .seal_range(offset as usize, len as usize);
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use c_u_soon::ProgramConfig;
+use pinocchio::{error::ProgramError, sysvars::Sysvar, AccountView, Address, ProgramResult};
+
+/// Replace the program config's admin, creation fee, and pause flag.
+///
+/// Accounts: `[admin (signer), config_account, fee_destination]`.
+///
+/// `admin` must be the config's current admin; passing a new `admin` hands the config
+/// over. While `paused`, every state-changing instruction but this one and `InitConfig`
+/// fails with `Custom(PROGRAM_PAUSED_ERROR)`: `Create` and `CreateWithEvents` through their
+/// fourth account, the others through their last (see [`config::strip`]). A new
+/// `create_fee` applies to the next envelope created.
+///
+/// [`config::strip`]: super::config::strip
+///
+/// If `fee_destination` is writable, every lamport `config_account` holds above its
+/// rent-exempt minimum (the creation fees collected so far) moves to it. Pass a read-only
+/// account, such as the system program, to leave the fees in place; the third account is
+/// required either way, since two accounts would reach the fast path.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    new_admin: &[u8; 32],
+    create_fee: u64,
+    paused: bool,
+) -> ProgramResult {
+    let [admin, config_account, fee_destination] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !admin.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !config_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    {
+        let mut config_data = config_account.try_borrow_mut()?;
+        if config_data.len() != ProgramConfig::SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let config: &mut ProgramConfig = bytemuck::from_bytes_mut(&mut config_data);
+
+        if &config.admin != admin.address() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+
+        config.admin = Address::new_from_array(*new_admin);
+        config.create_fee = create_fee;
+        config.paused = paused as u8;
+    }
+
+    if fee_destination.is_writable() {
+        let rent_exempt_lamports =
+            pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(ProgramConfig::SIZE)?;
+        let fees = config_account
+            .lamports()
+            .saturating_sub(rent_exempt_lamports);
+        config_account.set_lamports(config_account.lamports() - fees);
+        fee_destination.set_lamports(fee_destination.lamports() + fees);
+    }
+
+    Ok(())
+}
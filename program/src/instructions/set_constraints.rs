@@ -0,0 +1,147 @@
+use crate::pda::create_program_address;
+use bytemuck::Zeroable;
+use c_u_soon::{
+    Constraint, ConstraintRegion, ConstraintTable, Envelope, CONSTRAINTS_SEED, FLAG_CONSTRAINTS,
+};
+use c_u_soon_instruction::ConstraintSpec;
+use pinocchio::{
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::Sysvar,
+    AccountView, Address, ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, Transfer};
+
+/// Replace the constraint table checked on oracle and auxiliary writes.
+///
+/// Accounts: `[authority (signer), envelope_account, constraints_account, system_program_account]`.
+///
+/// PDA seeds: `[CONSTRAINTS_SEED, envelope_address, bump]`. The computed address must match
+/// `constraints_account`; otherwise returns [`ProgramError::InvalidSeeds`]. The table account
+/// is created on first use (same `Transfer`/`Allocate`/`Assign` sequence as `create`), with
+/// `authority` paying rent.
+///
+/// Writes `constraints` into the table, records the regions they read in
+/// `envelope.constrained_regions`, and sets [`FLAG_CONSTRAINTS`]; an empty list clears the
+/// flag and leaves the table account in place for reuse. Existing data is not checked
+/// against the new constraints; the next write to each region is.
+///
+/// While delegation is active, returns [`ProgramError::InvalidArgument`] if the old or new
+/// table constrains the auxiliary region, so a delegate's writes never start failing under
+/// it. Oracle-only tables can be changed at any time.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    bump: u8,
+    constraints: &[ConstraintSpec],
+) -> ProgramResult {
+    let [authority, envelope_account, constraints_account, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    {
+        let envelope_data = envelope_account.try_borrow()?;
+        let envelope: &Envelope =
+            Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+        if &envelope.authority != authority.address() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+
+        let adds_aux = constraints
+            .iter()
+            .any(|spec| spec.region == ConstraintRegion::Aux as u8);
+        if envelope.has_delegation() && (adds_aux || envelope.is_constrained(ConstraintRegion::Aux))
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    let bump_bytes = [bump];
+    let seeds: [&[u8]; 3] = [
+        CONSTRAINTS_SEED,
+        envelope_account.address().as_array().as_ref(),
+        &bump_bytes,
+    ];
+    let expected = create_program_address(&seeds, program_id)?;
+    if constraints_account.address() != &expected {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !constraints_account.owned_by(program_id) {
+        if !constraints_account.owned_by(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if constraints_account.data_len() != 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let rent_exempt_lamports =
+            pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(ConstraintTable::SIZE)?;
+        let current_lamports = constraints_account.lamports();
+        if current_lamports < rent_exempt_lamports {
+            Transfer {
+                from: authority,
+                to: constraints_account,
+                lamports: rent_exempt_lamports - current_lamports,
+            }
+            .invoke()?;
+        }
+
+        let signer_seeds = [
+            Seed::from(seeds[0]),
+            Seed::from(seeds[1]),
+            Seed::from(seeds[2]),
+        ];
+        let signer = Signer::from(&signer_seeds[..]);
+
+        Allocate {
+            account: constraints_account,
+            space: ConstraintTable::SIZE as u64,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+
+        Assign {
+            account: constraints_account,
+            owner: program_id,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+    }
+
+    let regions = {
+        let mut table_data = constraints_account.try_borrow_mut()?;
+        if table_data.len() != ConstraintTable::SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let table: &mut ConstraintTable = bytemuck::from_bytes_mut(&mut table_data);
+
+        *table = ConstraintTable::zeroed();
+        table.envelope = *envelope_account.address();
+        table.bump = bump;
+        table.count = constraints.len() as u8;
+        for (slot, &spec) in table.entries.iter_mut().zip(constraints) {
+            *slot = Constraint::from(spec);
+        }
+        table.regions()
+    };
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    envelope.constrained_regions = regions;
+    if regions == 0 {
+        envelope.flags &= !FLAG_CONSTRAINTS;
+    } else {
+        envelope.flags |= FLAG_CONSTRAINTS;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use super::cpi_verification::verify_delegation_authority;
+use crate::pda::create_program_address;
+use bytemuck::Zeroable;
+use c_u_soon::{DelegateSchema, Envelope, StructMetadata, DELEGATE_SCHEMA_SEED};
+use pinocchio::{
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::Sysvar,
+    AccountView, Address, ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, Transfer};
+
+/// Register the sub-struct type the delegated program writes within its masked region.
+///
+/// Accounts: `[delegation_authority (signer), envelope_account, delegate_schema_account,
+/// payer (signer), system_program_account]`.
+///
+/// PDA seeds: `[DELEGATE_SCHEMA_SEED, envelope_address, bump]`; a mismatch returns
+/// [`ProgramError::InvalidSeeds`]. The schema account is created on first use with `payer`
+/// funding rent, since `delegation_authority` is usually a data-carrying PDA that cannot
+/// be a transfer source.
+///
+/// Requires an active delegation. `[offset, offset + metadata.type_size())` must be
+/// entirely writable under `program_bitmask`; otherwise returns
+/// [`ProgramError::InvalidArgument`]. Overwrites any previous schema, including a stale one
+/// left by an earlier delegation.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    bump: u8,
+    offset: u8,
+    metadata: u64,
+) -> ProgramResult {
+    let [delegation_authority, envelope_account, schema_account, payer, _system_program] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let meta = StructMetadata::from_raw(metadata);
+
+    {
+        let envelope_data = envelope_account.try_borrow()?;
+        let envelope: &Envelope =
+            Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+        if envelope.delegation_authority == Address::zeroed() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        verify_delegation_authority(delegation_authority, &envelope.delegation_authority)?;
+
+        if !envelope
+            .program_bitmask
+            .is_write_allowed(offset as usize, meta.type_size() as usize)
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    let bump_bytes = [bump];
+    let seeds: [&[u8]; 3] = [
+        DELEGATE_SCHEMA_SEED,
+        envelope_account.address().as_array().as_ref(),
+        &bump_bytes,
+    ];
+    let expected = create_program_address(&seeds, program_id)?;
+    if schema_account.address() != &expected {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !schema_account.owned_by(program_id) {
+        if !payer.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if !schema_account.owned_by(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if schema_account.data_len() != 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let rent_exempt_lamports =
+            pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(DelegateSchema::SIZE)?;
+        let current_lamports = schema_account.lamports();
+        if current_lamports < rent_exempt_lamports {
+            Transfer {
+                from: payer,
+                to: schema_account,
+                lamports: rent_exempt_lamports - current_lamports,
+            }
+            .invoke()?;
+        }
+
+        let signer_seeds = [
+            Seed::from(seeds[0]),
+            Seed::from(seeds[1]),
+            Seed::from(seeds[2]),
+        ];
+        let signer = Signer::from(&signer_seeds[..]);
+
+        Allocate {
+            account: schema_account,
+            space: DelegateSchema::SIZE as u64,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+
+        Assign {
+            account: schema_account,
+            owner: program_id,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+    }
+
+    let mut schema_data = schema_account.try_borrow_mut()?;
+    if schema_data.len() != DelegateSchema::SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let schema: &mut DelegateSchema = bytemuck::from_bytes_mut(&mut schema_data);
+
+    *schema = DelegateSchema::zeroed();
+    schema.envelope = *envelope_account.address();
+    schema.delegation_authority = *delegation_authority.address();
+    schema.metadata = meta;
+    schema.offset = offset;
+    schema.bump = bump;
+
+    Ok(())
+}
@@ -1,25 +1,21 @@
+use crate::pda::create_program_address;
+use alloc::vec::Vec;
 use bytemuck::Zeroable;
 use c_u_soon::{Envelope, Mask};
 use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 
-/// Assign a delegated program and write-access bitmasks to an oracle envelope.
-///
-/// Accounts: `[authority (signer), envelope_account, delegation_authority (signer)]`.
-///
-/// Requires no active delegation (`envelope.delegation_authority == zeroed`); both bitmasks
-/// must already be `ALL_BLOCKED`. This prevents overwriting an existing delegation without
-/// going through [`clear_delegation`] first.
-/// `delegation_authority` must be non-zero and must sign the transaction.
-///
-/// Sets `envelope.delegation_authority`, `program_bitmask`, and `user_bitmask`.
-///
-/// [`clear_delegation`]: super::clear_delegation::process
-pub fn process(
+/// Check the authority and that the envelope has no delegation, then call `verify` on the
+/// delegate account and install it with both bitmasks.
+fn with_validated_delegate<F>(
     program_id: &Address,
     accounts: &[AccountView],
     program_bitmask: &Mask,
     user_bitmask: &Mask,
-) -> ProgramResult {
+    verify: F,
+) -> ProgramResult
+where
+    F: FnOnce(&AccountView) -> ProgramResult,
+{
     let [authority, envelope_account, delegation_authority] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
@@ -33,7 +29,8 @@ pub fn process(
     }
 
     let mut envelope_data = envelope_account.try_borrow_mut()?;
-    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut envelope_data);
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
 
     if &envelope.authority != authority.address() {
         return Err(ProgramError::IncorrectAuthority);
@@ -47,17 +44,85 @@ pub fn process(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    if !delegation_authority.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    verify(delegation_authority)?;
 
     if delegation_authority.address() == &Address::zeroed() {
         return Err(ProgramError::InvalidAccountData);
     }
 
     envelope.delegation_authority = *delegation_authority.address();
-    envelope.program_bitmask = *program_bitmask;
-    envelope.user_bitmask = *user_bitmask;
+    envelope.program_bitmask = program_bitmask.with_sealed_from(&envelope.program_bitmask);
+    envelope.user_bitmask = user_bitmask.with_sealed_from(&envelope.user_bitmask);
 
     Ok(())
 }
+
+/// Assign a delegated program and write-access bitmasks to an oracle envelope.
+///
+/// Accounts: `[authority (signer), envelope_account, delegation_authority (signer)]`.
+///
+/// Requires no active delegation (`envelope.delegation_authority == zeroed`); both bitmasks
+/// must already be `ALL_BLOCKED`, apart from sealed bytes. This prevents overwriting an
+/// existing delegation without going through [`clear_delegation`] first.
+/// `delegation_authority` must be non-zero and must sign the transaction.
+///
+/// Sets `envelope.delegation_authority`, `program_bitmask`, and `user_bitmask`. Bytes sealed
+/// by `SealAuxiliaryRange` stay sealed whatever the new bitmasks say.
+///
+/// `SetDelegatedProgramCompact` lands here too, with its 32-byte masks expanded.
+///
+/// [`clear_delegation`]: super::clear_delegation::process
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    program_bitmask: &Mask,
+    user_bitmask: &Mask,
+) -> ProgramResult {
+    with_validated_delegate(
+        program_id,
+        accounts,
+        program_bitmask,
+        user_bitmask,
+        |delegation_authority| {
+            if !delegation_authority.is_signer() {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Assign a program-derived delegate without its signature.
+///
+/// Accounts: `[authority (signer), envelope_account, delegation_authority]`.
+///
+/// As [`process`], except `delegation_authority` need not sign: it must be the PDA of
+/// `delegate_program` at `[...seeds, bump]`, or the call returns
+/// [`ProgramError::InvalidSeeds`]. Only `delegate_program` can sign for that address, so
+/// every later delegated write still comes from it, through `invoke_signed`.
+pub fn process_pda(
+    program_id: &Address,
+    accounts: &[AccountView],
+    delegate_program: &Address,
+    seeds: &[Vec<u8>],
+    bump: u8,
+    program_bitmask: &Mask,
+    user_bitmask: &Mask,
+) -> ProgramResult {
+    with_validated_delegate(
+        program_id,
+        accounts,
+        program_bitmask,
+        user_bitmask,
+        |delegation_authority| {
+            let bump_bytes = [bump];
+            let mut seed_refs: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+            seed_refs.push(&bump_bytes);
+            let expected = create_program_address(&seed_refs, delegate_program)?;
+            if delegation_authority.address() != &expected {
+                return Err(ProgramError::InvalidSeeds);
+            }
+            Ok(())
+        },
+    )
+}
@@ -0,0 +1,131 @@
+use crate::pda::create_program_address;
+use bytemuck::Zeroable;
+use c_u_soon::{Envelope, GroupAnchor, GROUP_ANCHOR_SEED, MAX_GROUP_MEMBERS};
+use pinocchio::{
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::Sysvar,
+    AccountView, Address, ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, Transfer};
+
+/// Replace the member list of a group anchor.
+///
+/// Accounts: `[authority (signer), group_anchor_account, system_program_account]`, followed
+/// by 1 to [`MAX_GROUP_MEMBERS`] member envelopes in group order.
+///
+/// PDA seeds: `[GROUP_ANCHOR_SEED, authority, group_id (u64 LE), bump]`. The computed
+/// address must match `group_anchor_account`; otherwise returns
+/// [`ProgramError::InvalidSeeds`]. The anchor is created on first use (same
+/// `Transfer`/`Allocate`/`Assign` sequence as `create`), with `authority` paying rent.
+///
+/// Every member must be an envelope of this program owned by `authority`; a repeated member
+/// returns [`ProgramError::InvalidArgument`]. The anchor's `sequence` is kept, so commits
+/// after a membership change still need a higher sequence.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    group_id: u64,
+    bump: u8,
+) -> ProgramResult {
+    let [authority, group_anchor_account, _system_program, members @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if members.is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    if members.len() > MAX_GROUP_MEMBERS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    for (i, member) in members.iter().enumerate() {
+        if members[..i].iter().any(|m| m.address() == member.address()) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if !member.owned_by(program_id) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let envelope_data = member.try_borrow()?;
+        let envelope: &Envelope =
+            Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+        if &envelope.authority != authority.address() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+    }
+
+    let group_id_bytes = group_id.to_le_bytes();
+    let bump_bytes = [bump];
+    let seeds: [&[u8]; 4] = [
+        GROUP_ANCHOR_SEED,
+        authority.address().as_array().as_ref(),
+        &group_id_bytes,
+        &bump_bytes,
+    ];
+    let expected = create_program_address(&seeds, program_id)?;
+    if group_anchor_account.address() != &expected {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !group_anchor_account.owned_by(program_id) {
+        if !group_anchor_account.owned_by(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if group_anchor_account.data_len() != 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let rent_exempt_lamports =
+            pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(GroupAnchor::SIZE)?;
+        let current_lamports = group_anchor_account.lamports();
+        if current_lamports < rent_exempt_lamports {
+            Transfer {
+                from: authority,
+                to: group_anchor_account,
+                lamports: rent_exempt_lamports - current_lamports,
+            }
+            .invoke()?;
+        }
+
+        let signer_seeds = [
+            Seed::from(seeds[0]),
+            Seed::from(seeds[1]),
+            Seed::from(seeds[2]),
+            Seed::from(seeds[3]),
+        ];
+        let signer = Signer::from(&signer_seeds[..]);
+
+        Allocate {
+            account: group_anchor_account,
+            space: GroupAnchor::SIZE as u64,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+
+        Assign {
+            account: group_anchor_account,
+            owner: program_id,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+    }
+
+    let mut anchor_data = group_anchor_account.try_borrow_mut()?;
+    if anchor_data.len() != GroupAnchor::SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let anchor: &mut GroupAnchor = bytemuck::from_bytes_mut(&mut anchor_data);
+
+    anchor.authority = *authority.address();
+    anchor.group_id = group_id;
+    anchor.bump = bump;
+    anchor.count = members.len() as u8;
+    anchor.members = [Address::zeroed(); MAX_GROUP_MEMBERS];
+    for (slot, member) in anchor.members.iter_mut().zip(members) {
+        *slot = *member.address();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use crate::pda::create_program_address;
+use c_u_soon::{Envelope, EnvelopeLabel, LABEL_SEED, LABEL_SIZE};
+use pinocchio::{
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::Sysvar,
+    AccountView, Address, ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, Transfer};
+
+/// Write the envelope's human-readable label, once.
+///
+/// Accounts: `[authority (signer), envelope_account, label_account, system_program_account]`.
+///
+/// PDA seeds: `[LABEL_SEED, envelope_address, bump]`. The computed address must match
+/// `label_account`; otherwise returns [`ProgramError::InvalidSeeds`]. The label account is
+/// created here (same `Transfer`/`Allocate`/`Assign` sequence as `create`), with `authority`
+/// paying rent. If it already belongs to this program the label has been set, and the call
+/// fails with [`ProgramError::AccountAlreadyInitialized`].
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    bump: u8,
+    label: &[u8; LABEL_SIZE],
+) -> ProgramResult {
+    let [authority, envelope_account, label_account, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    {
+        let envelope_data = envelope_account.try_borrow()?;
+        let envelope: &Envelope =
+            Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+        if &envelope.authority != authority.address() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+    }
+
+    let bump_bytes = [bump];
+    let seeds: [&[u8]; 3] = [
+        LABEL_SEED,
+        envelope_account.address().as_array().as_ref(),
+        &bump_bytes,
+    ];
+    let expected = create_program_address(&seeds, program_id)?;
+    if label_account.address() != &expected {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if label_account.owned_by(program_id) {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    if !label_account.owned_by(&pinocchio_system::ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if label_account.data_len() != 0 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rent_exempt_lamports =
+        pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(EnvelopeLabel::SIZE)?;
+    let current_lamports = label_account.lamports();
+    if current_lamports < rent_exempt_lamports {
+        Transfer {
+            from: authority,
+            to: label_account,
+            lamports: rent_exempt_lamports - current_lamports,
+        }
+        .invoke()?;
+    }
+
+    let signer_seeds = [
+        Seed::from(seeds[0]),
+        Seed::from(seeds[1]),
+        Seed::from(seeds[2]),
+    ];
+    let signer = Signer::from(&signer_seeds[..]);
+
+    Allocate {
+        account: label_account,
+        space: EnvelopeLabel::SIZE as u64,
+    }
+    .invoke_signed(core::slice::from_ref(&signer))?;
+
+    Assign {
+        account: label_account,
+        owner: program_id,
+    }
+    .invoke_signed(core::slice::from_ref(&signer))?;
+
+    let mut label_data = label_account.try_borrow_mut()?;
+    let record: &mut EnvelopeLabel = bytemuck::from_bytes_mut(&mut label_data);
+    record.envelope = *envelope_account.address();
+    record.label = *label;
+    record.bump = bump;
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use c_u_soon::{Envelope, FLAG_MUTATION_GUARD};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Enable or disable the per-transaction mutation guard.
+///
+/// Accounts: `[authority (signer), envelope_account, padding]`. The padding account keeps
+/// the instruction off the two-account fast path and is not read.
+///
+/// `allow_multi: false` sets [`FLAG_MUTATION_GUARD`], after which every oracle, auxiliary,
+/// and heartbeat write must pass the instructions sysvar and is checked by
+/// [`mutation_guard::enforce`](super::mutation_guard::enforce); fast-path writes are
+/// rejected. `allow_multi: true` clears it. Setting the current value again succeeds
+/// without change.
+pub fn process(program_id: &Address, accounts: &[AccountView], allow_multi: bool) -> ProgramResult {
+    let [authority, envelope_account, _padding] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if &envelope.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    if allow_multi {
+        envelope.flags &= !FLAG_MUTATION_GUARD;
+    } else {
+        envelope.flags |= FLAG_MUTATION_GUARD;
+    }
+
+    Ok(())
+}
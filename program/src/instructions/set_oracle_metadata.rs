@@ -0,0 +1,81 @@
+use c_u_soon::{
+    oracle_capacity, Envelope, StructMetadata, FLAG_CONFLATION, FLAG_TWAP, ORACLE_BYTES,
+};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Re-type the envelope in place, for a stored type whose metadata no longer matches its
+/// readers.
+///
+/// Accounts: `[authority (signer), envelope_account, padding]`. The padding account keeps
+/// the instruction off the two-account fast path and is not read.
+///
+/// Requires no active delegation, so a delegate never sees the aux type change under it;
+/// otherwise returns [`ProgramError::InvalidArgument`].
+///
+/// A new `oracle_metadata` replaces the stored one and zeroes the whole oracle data region,
+/// trailers included, so a TWAP, conflation window, or update stamp restarts from the next
+/// write. The type must fit before the enabled trailers, and hold the 8-byte value if TWAP
+/// or conflation is on; otherwise returns [`ProgramError::InvalidArgument`]. A new
+/// `auxiliary_metadata` replaces the stored one and zeroes the auxiliary data except sealed
+/// bytes, which keep their values, recording the wipe as a write to every block. A value
+/// equal to the stored one leaves its region untouched. The oracle and aux sequences are
+/// kept, so a write signed for the old type cannot be replayed against the new one.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    oracle_metadata: u64,
+    auxiliary_metadata: u64,
+) -> ProgramResult {
+    let [authority, envelope_account, _padding] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if &envelope.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    if envelope.has_delegation() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let oracle_metadata = StructMetadata::from_raw(oracle_metadata);
+    let auxiliary_metadata = StructMetadata::from_raw(auxiliary_metadata);
+
+    if oracle_metadata != envelope.oracle_state.oracle_metadata {
+        let min_size = if envelope.flags & (FLAG_TWAP | FLAG_CONFLATION) != 0 {
+            8
+        } else {
+            0
+        };
+        let type_size = oracle_metadata.type_size() as usize;
+        if !(min_size..=oracle_capacity(envelope.flags)).contains(&type_size) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        envelope.oracle_state.oracle_metadata = oracle_metadata;
+        envelope.oracle_state.data = [0; ORACLE_BYTES];
+    }
+
+    if auxiliary_metadata != envelope.auxiliary_metadata {
+        envelope.auxiliary_metadata = auxiliary_metadata;
+        for (i, byte) in envelope.auxiliary_data.iter_mut().enumerate() {
+            if !envelope.program_bitmask.is_sealed(i) {
+                *byte = 0;
+            }
+        }
+        envelope.record_aux_write(0xFF);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use c_u_soon::Envelope;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Pause or resume writes to an envelope.
+///
+/// Accounts: `[authority (signer), envelope_account, padding]`. The padding account keeps
+/// the instruction off the two-account fast path and is not read.
+///
+/// While paused, every oracle write (fast path, `BatchUpdate`, `GroupCommit`,
+/// `PublisherUpdate`, `Aggregate`) and every aux write, delegated or not, fails with
+/// `Custom(ENVELOPE_PAUSED_ERROR)`. Reads, delegation changes, and the other administrative
+/// instructions keep working, so an emergency halt leaves the delegation in place.
+/// Setting the current value again succeeds without change.
+pub fn process(program_id: &Address, accounts: &[AccountView], paused: bool) -> ProgramResult {
+    let [authority, envelope_account, _padding] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if &envelope.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    envelope.paused = paused as u8;
+
+    Ok(())
+}
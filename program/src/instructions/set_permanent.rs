@@ -0,0 +1,41 @@
+use c_u_soon::{Envelope, FLAG_PERMANENT};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Irreversibly mark an envelope permanent.
+///
+/// Accounts: `[authority (signer), envelope_account, padding]`. The padding account keeps
+/// the instruction off the two-account fast path and is not read.
+///
+/// Sets [`FLAG_PERMANENT`]. No instruction clears it, and `Close` rejects permanent
+/// envelopes, so integrators can rely on the envelope address for the life of the program.
+/// Any future instruction that changes `envelope.authority` must reject permanent envelopes
+/// too. Fails with `InvalidArgument` if the envelope is already permanent.
+pub fn process(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [authority, envelope_account, _padding] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if &envelope.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    if envelope.is_permanent() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    envelope.flags |= FLAG_PERMANENT;
+
+    Ok(())
+}
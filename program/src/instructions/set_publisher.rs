@@ -0,0 +1,42 @@
+use c_u_soon::Envelope;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Set or remove the envelope's publisher key.
+///
+/// Accounts: `[authority (signer), envelope_account, padding]`. The padding account keeps
+/// the instruction off the two-account fast path and is not read.
+///
+/// The publisher may write the oracle through the fast path and `BatchUpdate` in place of
+/// the authority. It cannot run any other instruction, so a compromised publisher key can
+/// publish bad values but cannot close the envelope, change its delegation, or force aux
+/// writes. A zero `publisher` removes the key. Only the authority can change it, and
+/// setting the current value again succeeds without change.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    publisher: [u8; 32],
+) -> ProgramResult {
+    let [authority, envelope_account, _padding] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if &envelope.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    envelope.publisher = Address::from(publisher);
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use crate::pda::create_program_address;
+use bytemuck::Zeroable;
+use c_u_soon::{Envelope, PublisherSet, PUBLISHERS_SEED};
+use pinocchio::{
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::Sysvar,
+    AccountView, Address, ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, Transfer};
+
+/// Replace the set of publishers allowed to sign `PublisherUpdate`s for the envelope.
+///
+/// Accounts: `[authority (signer), envelope_account, publishers_account, system_program_account]`.
+///
+/// PDA seeds: `[PUBLISHERS_SEED, envelope_address, bump]`. The computed address must match
+/// `publishers_account`; otherwise returns [`ProgramError::InvalidSeeds`]. The set account
+/// is created on first use (same `Transfer`/`Allocate`/`Assign` sequence as `create`), with
+/// `authority` paying rent.
+///
+/// Writes `publishers` and `threshold` into the set, replacing the previous ones. The list
+/// was checked by `SlowPathInstruction::validate`; an empty list with a zero threshold turns
+/// publisher writes off and leaves the account in place for reuse.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    bump: u8,
+    threshold: u8,
+    publishers: &[[u8; 32]],
+) -> ProgramResult {
+    let [authority, envelope_account, publishers_account, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    {
+        let envelope_data = envelope_account.try_borrow()?;
+        let envelope: &Envelope =
+            Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+        if &envelope.authority != authority.address() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+    }
+
+    let bump_bytes = [bump];
+    let seeds: [&[u8]; 3] = [
+        PUBLISHERS_SEED,
+        envelope_account.address().as_array().as_ref(),
+        &bump_bytes,
+    ];
+    let expected = create_program_address(&seeds, program_id)?;
+    if publishers_account.address() != &expected {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !publishers_account.owned_by(program_id) {
+        if !publishers_account.owned_by(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if publishers_account.data_len() != 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let rent_exempt_lamports =
+            pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(PublisherSet::SIZE)?;
+        let current_lamports = publishers_account.lamports();
+        if current_lamports < rent_exempt_lamports {
+            Transfer {
+                from: authority,
+                to: publishers_account,
+                lamports: rent_exempt_lamports - current_lamports,
+            }
+            .invoke()?;
+        }
+
+        let signer_seeds = [
+            Seed::from(seeds[0]),
+            Seed::from(seeds[1]),
+            Seed::from(seeds[2]),
+        ];
+        let signer = Signer::from(&signer_seeds[..]);
+
+        Allocate {
+            account: publishers_account,
+            space: PublisherSet::SIZE as u64,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+
+        Assign {
+            account: publishers_account,
+            owner: program_id,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+    }
+
+    let mut set_data = publishers_account.try_borrow_mut()?;
+    if set_data.len() != PublisherSet::SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let set: &mut PublisherSet = bytemuck::from_bytes_mut(&mut set_data);
+
+    *set = PublisherSet::zeroed();
+    set.envelope = *envelope_account.address();
+    set.bump = bump;
+    set.threshold = threshold;
+    set.count = publishers.len() as u8;
+    for (slot, publisher) in set.publishers.iter_mut().zip(publishers) {
+        *slot = Address::from(*publisher);
+    }
+
+    Ok(())
+}
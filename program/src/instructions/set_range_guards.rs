@@ -0,0 +1,139 @@
+use crate::pda::create_program_address;
+use bytemuck::Zeroable;
+use c_u_soon::{Envelope, RangeGuard, RangeGuardEntry, FLAG_RANGE_GUARD, RANGE_GUARD_SEED};
+use c_u_soon_instruction::RangeGuardSpec;
+use pinocchio::{
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::Sysvar,
+    AccountView, Address, ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, Transfer};
+
+/// Replace the range allow-list that gates delegated auxiliary writes.
+///
+/// Accounts: `[authority (signer), envelope_account, range_guard_account, system_program_account]`.
+///
+/// PDA seeds: `[RANGE_GUARD_SEED, envelope_address, bump]`. The computed address must match
+/// `range_guard_account`; otherwise returns [`ProgramError::InvalidSeeds`]. The guard account
+/// is created on first use (same `Transfer`/`Allocate`/`Assign` sequence as `create`), with
+/// `authority` paying rent.
+///
+/// Requires no active delegation, so a delegate's guarantees never change under it. Writes
+/// `entries` into the guard and sets [`FLAG_RANGE_GUARD`] on the envelope; an empty list
+/// clears the flag and leaves the guard account in place for reuse.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    bump: u8,
+    entries: &[RangeGuardSpec],
+) -> ProgramResult {
+    let [authority, envelope_account, range_guard_account, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    {
+        let envelope_data = envelope_account.try_borrow()?;
+        let envelope: &Envelope =
+            Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+        if &envelope.authority != authority.address() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+
+        if envelope.has_delegation() {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    let bump_bytes = [bump];
+    let seeds: [&[u8]; 3] = [
+        RANGE_GUARD_SEED,
+        envelope_account.address().as_array().as_ref(),
+        &bump_bytes,
+    ];
+    let expected = create_program_address(&seeds, program_id)?;
+    if range_guard_account.address() != &expected {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !range_guard_account.owned_by(program_id) {
+        if !range_guard_account.owned_by(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if range_guard_account.data_len() != 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let rent_exempt_lamports =
+            pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(RangeGuard::SIZE)?;
+        let current_lamports = range_guard_account.lamports();
+        if current_lamports < rent_exempt_lamports {
+            Transfer {
+                from: authority,
+                to: range_guard_account,
+                lamports: rent_exempt_lamports - current_lamports,
+            }
+            .invoke()?;
+        }
+
+        let signer_seeds = [
+            Seed::from(seeds[0]),
+            Seed::from(seeds[1]),
+            Seed::from(seeds[2]),
+        ];
+        let signer = Signer::from(&signer_seeds[..]);
+
+        Allocate {
+            account: range_guard_account,
+            space: RangeGuard::SIZE as u64,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+
+        Assign {
+            account: range_guard_account,
+            owner: program_id,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+    }
+
+    {
+        let mut guard_data = range_guard_account.try_borrow_mut()?;
+        if guard_data.len() != RangeGuard::SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let guard: &mut RangeGuard = bytemuck::from_bytes_mut(&mut guard_data);
+
+        *guard = RangeGuard::zeroed();
+        guard.envelope = *envelope_account.address();
+        guard.bump = bump;
+        guard.count = entries.len() as u8;
+        for (slot, spec) in guard.entries.iter_mut().zip(entries) {
+            *slot = RangeGuardEntry {
+                program_id: Address::from(spec.program_id),
+                offset: spec.offset,
+                len: spec.len,
+                _padding: [0; 4],
+            };
+        }
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if entries.is_empty() {
+        envelope.flags &= !FLAG_RANGE_GUARD;
+    } else {
+        envelope.flags |= FLAG_RANGE_GUARD;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use crate::pda::create_program_address;
+use bytemuck::Zeroable;
+use c_u_soon::{Envelope, ReaderList, FLAG_GATED_READS, READERS_SEED};
+use pinocchio::{
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::Sysvar,
+    AccountView, Address, ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, Transfer};
+
+/// Replace the programs allowed to read the envelope's oracle through `ReadOracleGated`.
+///
+/// Accounts: `[authority (signer), envelope_account, readers_account, system_program_account]`.
+///
+/// PDA seeds: `[READERS_SEED, envelope_address, bump]`. The computed address must match
+/// `readers_account`; otherwise returns [`ProgramError::InvalidSeeds`]. The list account is
+/// created on first use (same `Transfer`/`Allocate`/`Assign` sequence as `create`), with
+/// `authority` paying rent.
+///
+/// Writes `readers` into the list, replacing the previous ones, and sets [`FLAG_GATED_READS`]
+/// on the envelope. The list was checked by `SlowPathInstruction::validate`; an empty list
+/// clears the flag and leaves the account in place for reuse.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    bump: u8,
+    readers: &[[u8; 32]],
+) -> ProgramResult {
+    let [authority, envelope_account, readers_account, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    {
+        let envelope_data = envelope_account.try_borrow()?;
+        let envelope: &Envelope =
+            Envelope::from_prefix_bytes(&envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+
+        if &envelope.authority != authority.address() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+    }
+
+    let bump_bytes = [bump];
+    let seeds: [&[u8]; 3] = [
+        READERS_SEED,
+        envelope_account.address().as_array().as_ref(),
+        &bump_bytes,
+    ];
+    let expected = create_program_address(&seeds, program_id)?;
+    if readers_account.address() != &expected {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !readers_account.owned_by(program_id) {
+        if !readers_account.owned_by(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if readers_account.data_len() != 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let rent_exempt_lamports =
+            pinocchio::sysvars::rent::Rent::get()?.try_minimum_balance(ReaderList::SIZE)?;
+        let current_lamports = readers_account.lamports();
+        if current_lamports < rent_exempt_lamports {
+            Transfer {
+                from: authority,
+                to: readers_account,
+                lamports: rent_exempt_lamports - current_lamports,
+            }
+            .invoke()?;
+        }
+
+        let signer_seeds = [
+            Seed::from(seeds[0]),
+            Seed::from(seeds[1]),
+            Seed::from(seeds[2]),
+        ];
+        let signer = Signer::from(&signer_seeds[..]);
+
+        Allocate {
+            account: readers_account,
+            space: ReaderList::SIZE as u64,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+
+        Assign {
+            account: readers_account,
+            owner: program_id,
+        }
+        .invoke_signed(core::slice::from_ref(&signer))?;
+    }
+
+    {
+        let mut list_data = readers_account.try_borrow_mut()?;
+        if list_data.len() != ReaderList::SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let list: &mut ReaderList = bytemuck::from_bytes_mut(&mut list_data);
+
+        *list = ReaderList::zeroed();
+        list.envelope = *envelope_account.address();
+        list.bump = bump;
+        list.count = readers.len() as u8;
+        for (slot, reader) in list.readers.iter_mut().zip(readers) {
+            *slot = Address::from(*reader);
+        }
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if readers.is_empty() {
+        envelope.flags &= !FLAG_GATED_READS;
+    } else {
+        envelope.flags |= FLAG_GATED_READS;
+    }
+
+    Ok(())
+}
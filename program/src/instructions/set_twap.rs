@@ -0,0 +1,56 @@
+use c_u_soon::{oracle_capacity, Envelope, TwapAccumulator, FLAG_TWAP};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Enable or disable the on-chain TWAP accumulator.
+///
+/// Accounts: `[authority (signer), envelope_account, padding]`. The padding account keeps
+/// the instruction off the two-account fast path and is not read.
+///
+/// `enabled: true` sets [`FLAG_TWAP`] and zeroes the accumulator at
+/// [`TWAP_OFFSET`](c_u_soon::TWAP_OFFSET) in the oracle region, so averaging starts from the
+/// next write. The oracle type must fit before the accumulator and hold at least the 8-byte
+/// value (`8 <= type_size <= TWAP_OFFSET`); otherwise returns
+/// [`ProgramError::InvalidArgument`]. `enabled: false` clears the flag and
+/// leaves the accumulator bytes to be overwritten as payload. Setting the current value
+/// again succeeds without change, so re-enabling does not reset a running average.
+pub fn process(program_id: &Address, accounts: &[AccountView], enabled: bool) -> ProgramResult {
+    let [authority, envelope_account, _padding] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if &envelope.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    if !enabled {
+        envelope.flags &= !FLAG_TWAP;
+        return Ok(());
+    }
+    if envelope.has_twap() {
+        return Ok(());
+    }
+
+    let type_size = envelope.oracle_state.oracle_metadata.type_size() as usize;
+    if !(8..=oracle_capacity(envelope.flags | FLAG_TWAP)).contains(&type_size) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    envelope.flags |= FLAG_TWAP;
+    if let Some(accumulator) = envelope.twap_mut() {
+        *accumulator = TwapAccumulator::default();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use c_u_soon::{oracle_capacity, Envelope, UpdateStamp, FLAG_UPDATE_STAMP};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Enable or disable recording the slot and unix timestamp of every oracle write.
+///
+/// Accounts: `[authority (signer), envelope_account, padding]`. The padding account keeps
+/// the instruction off the two-account fast path and is not read.
+///
+/// `enabled: true` sets [`FLAG_UPDATE_STAMP`] and zeroes the stamp at
+/// [`UPDATE_STAMP_OFFSET`](c_u_soon::UPDATE_STAMP_OFFSET), so
+/// `Envelope::last_update_slot` reports nothing until the next write. The oracle type must
+/// fit before the stamp and any TWAP accumulator (`type_size <= oracle_capacity`); otherwise
+/// returns [`ProgramError::InvalidArgument`]. `enabled: false` clears the flag and leaves
+/// the stamp bytes to be overwritten as payload. Setting the current value again succeeds
+/// without change.
+pub fn process(program_id: &Address, accounts: &[AccountView], enabled: bool) -> ProgramResult {
+    let [authority, envelope_account, _padding] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if &envelope.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    if !enabled {
+        envelope.flags &= !FLAG_UPDATE_STAMP;
+        return Ok(());
+    }
+    if envelope.has_update_stamp() {
+        return Ok(());
+    }
+
+    let type_size = envelope.oracle_state.oracle_metadata.type_size() as usize;
+    if type_size > oracle_capacity(envelope.flags | FLAG_UPDATE_STAMP) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    envelope.flags |= FLAG_UPDATE_STAMP;
+    if let Some(stamp) = envelope.update_stamp_mut() {
+        *stamp = UpdateStamp::default();
+    }
+
+    Ok(())
+}
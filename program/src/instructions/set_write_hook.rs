@@ -0,0 +1,69 @@
+use super::cpi_verification::verify_delegation_authority;
+use bytemuck::Zeroable;
+use c_u_soon::Envelope;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Turn the delegation's write hook on or off.
+///
+/// Accounts: `[authority (signer), envelope_account, delegation_authority (signer),
+/// hook_program]`.
+///
+/// Requires an active delegation; `delegation_authority` must sign and match
+/// `envelope.delegation_authority`, so the authority cannot attach a hook the delegate did
+/// not ask for, and the delegate cannot make the authority's writes depend on a program
+/// the authority did not agree to.
+///
+/// With `notify_on_authority_write`, stores `hook_program` in `envelope.write_hook`. It must
+/// be executable and not this program, else `InvalidArgument`. Every later authority aux
+/// write ([`update_auxiliary`], [`update_auxiliary_multi_range`]) must pass it as its fourth
+/// account and calls it after applying the write (see [`write_hook`]). Without the flag,
+/// zeroes `envelope.write_hook`; `hook_program` is not read. Delegated and forced writes
+/// never call the hook: the delegate signs those itself.
+///
+/// [`update_auxiliary`]: super::update_auxiliary::process
+/// [`update_auxiliary_multi_range`]: super::update_auxiliary_multi_range::process
+/// [`write_hook`]: super::write_hook
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    notify_on_authority_write: bool,
+) -> ProgramResult {
+    let [authority, envelope_account, delegation_authority, hook_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if &envelope.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    if !envelope.has_delegation() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    verify_delegation_authority(delegation_authority, &envelope.delegation_authority)?;
+
+    if !notify_on_authority_write {
+        envelope.write_hook = Address::zeroed();
+        return Ok(());
+    }
+
+    if !hook_program.executable() || hook_program.address() == program_id {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    envelope.write_hook = *hook_program.address();
+
+    Ok(())
+}
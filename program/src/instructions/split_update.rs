@@ -0,0 +1,144 @@
+use crate::sysvar::{current_index, instruction_at, INSTRUCTIONS_SYSVAR_ID};
+use c_u_soon::Envelope;
+use c_u_soon_instruction::{read_split_update, SPLIT_UPDATE_UNPAIRED_ERROR};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Write one half of a payload too large for a single instruction into the extended
+/// auxiliary region.
+///
+/// Accounts: `[authority (signer), envelope_account (writable), instructions_sysvar]`.
+/// The signer must be the envelope authority or its publisher
+/// ([`ProgramError::IncorrectAuthority`]).
+///
+/// `part` 1 and `part` 2 are sent as adjacent top-level instructions of one transaction,
+/// part 1 first, with the same `type_hash` and `sequence` and the same envelope as their
+/// second account. Each checks the other through the instructions sysvar; a part without
+/// its partner, or one sent through CPI, fails with
+/// `Custom(`[`SPLIT_UPDATE_UNPAIRED_ERROR`]`)`. Since part 2 rejects the transaction if
+/// part 1 is missing and part 1 rejects it if part 2 is, the payload lands whole or not
+/// at all.
+///
+/// Part 1 writes its payload at the start of the extension; part 2 writes right after it
+/// and stores `sequence` in the extension header, so the header moves only once both
+/// halves are in place. `type_hash` must equal the extension's
+/// ([`ProgramError::InvalidArgument`]), `sequence` must be strictly greater than the
+/// header's, and `part` must be 1 or 2 ([`ProgramError::InvalidInstructionData`]). A
+/// payload past the end of the extension fails with [`ProgramError::InvalidArgument`], and
+/// an envelope without an extension with [`ProgramError::InvalidAccountData`].
+///
+/// On an envelope with the mutation guard, the two parts count as one write: each is
+/// checked by [`mutation_guard::enforce_split`](super::mutation_guard::enforce_split), so no
+/// other top-level instruction may take the envelope writable.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    type_hash: u64,
+    sequence: u64,
+    part: u8,
+    payload: &[u8],
+) -> ProgramResult {
+    let [authority, envelope_account, instructions_sysvar] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if instructions_sysvar.address() != &INSTRUCTIONS_SYSVAR_ID {
+        return Err(ProgramError::UnsupportedSysvar);
+    }
+
+    if part != 1 && part != 2 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let offset = {
+        let sysvar_data = instructions_sysvar.try_borrow()?;
+        paired_offset(
+            &sysvar_data,
+            program_id,
+            envelope_account.address(),
+            type_hash,
+            sequence,
+            part,
+        )
+        .ok_or(ProgramError::Custom(SPLIT_UPDATE_UNPAIRED_ERROR))?
+    };
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if envelope.authority != *authority.address() && !envelope.is_publisher(authority.address()) {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+    super::paused::check(envelope)?;
+    super::mutation_guard::enforce_split(
+        envelope,
+        envelope_account.address(),
+        instructions_sysvar,
+    )?;
+
+    let header =
+        Envelope::ext_aux_header_mut(&mut envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+    if header.type_hash != type_hash {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if sequence <= header.sequence {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    if part == 2 {
+        header.sequence = sequence;
+    }
+
+    let ext =
+        Envelope::ext_aux_bytes_mut(&mut envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+    let dst = ext
+        .get_mut(offset..offset + payload.len())
+        .ok_or(ProgramError::InvalidArgument)?;
+    dst.copy_from_slice(payload);
+
+    Ok(())
+}
+
+/// Find the partner of the current instruction, `part` of a split update: the next
+/// top-level instruction for part 1, the previous one for part 2. Returns the offset
+/// `part` writes at (0 for part 1, the length of part 1's payload for part 2), or `None`
+/// if the current instruction is not a top-level call of `program_id` or the partner is
+/// not the other part of the same update.
+fn paired_offset(
+    sysvar_data: &[u8],
+    program_id: &Address,
+    envelope: &Address,
+    type_hash: u64,
+    sequence: u64,
+    part: u8,
+) -> Option<usize> {
+    let (current, count) = current_index(sysvar_data).ok()?;
+    let this = instruction_at(sysvar_data, current).ok()?;
+    if this.program_id != program_id.as_ref() {
+        return None;
+    }
+    let (partner_index, partner_part) = if part == 1 {
+        (current + 1, 2)
+    } else {
+        (current.checked_sub(1)?, 1)
+    };
+    if partner_index >= count {
+        return None;
+    }
+    let partner = instruction_at(sysvar_data, partner_index).ok()?;
+    if partner.program_id != program_id.as_ref() || partner.account(1)? != envelope.as_ref() {
+        return None;
+    }
+    let (partner_hash, partner_sequence, read_part, partner_payload) =
+        read_split_update(partner.data)?;
+    if (partner_hash, partner_sequence, read_part) != (type_hash, sequence, partner_part) {
+        return None;
+    }
+    Some(if part == 1 { 0 } else { partner_payload.len() })
+}
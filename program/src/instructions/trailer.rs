@@ -0,0 +1,44 @@
+use c_u_soon::Envelope;
+use pinocchio::{error::ProgramError, sysvars::clock::Clock, sysvars::Sysvar, ProgramResult};
+
+/// Update the oracle trailers for an oracle write: fold the value into the conflation window
+/// (`FLAG_CONFLATION`) and the TWAP accumulator (`FLAG_TWAP`), and record the slot and unix
+/// timestamp in the update stamp (`FLAG_UPDATE_STAMP`). No-op, and no `Clock` read, when
+/// none of these flags is set.
+///
+/// Call after the write's metadata and sequence checks and before `payload` is copied into
+/// the oracle region. The first 8 bytes of `payload` are the `u64` LE value the window and
+/// the TWAP track. A payload that would reach into a trailer (longer than
+/// [`Envelope::oracle_capacity`]), or a conflating or TWAP payload shorter than 8 bytes,
+/// returns [`ProgramError::InvalidInstructionData`].
+pub fn record(envelope: &mut Envelope, payload: &[u8]) -> ProgramResult {
+    if !envelope.has_twap() && !envelope.has_update_stamp() && !envelope.has_conflation() {
+        return Ok(());
+    }
+
+    if payload.len() > envelope.oracle_capacity() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let clock = Clock::get()?;
+
+    if envelope.has_twap() || envelope.has_conflation() {
+        let Some(value) = payload.get(..8) else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+        let value = u64::from_le_bytes(value.try_into().unwrap());
+        if let Some(window) = envelope.conflation_mut() {
+            window.fold(value, clock.slot);
+        }
+        if let Some(accumulator) = envelope.twap_mut() {
+            accumulator.fold(value, clock.slot);
+        }
+    }
+
+    if let Some(stamp) = envelope.update_stamp_mut() {
+        stamp.slot = clock.slot.into();
+        stamp.unix_timestamp = clock.unix_timestamp.into();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use super::cpi_verification::verify_delegation_authority;
+use bytemuck::Zeroable;
+use c_u_soon::{Envelope, Mask};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Hand an active delegation to a new delegate in one step.
+///
+/// Accounts: `[authority (signer), envelope_account, old_delegation_authority (signer),
+/// new_delegation_authority (signer)]`.
+///
+/// Requires an active delegation; `old_delegation_authority` must sign and match
+/// `envelope.delegation_authority`, and `new_delegation_authority` must sign and be
+/// non-zero. Replaces `envelope.delegation_authority` and both bitmasks, keeping sealed
+/// bytes sealed, as [`clear_delegation`] followed by [`set_delegated_program`] would, but
+/// leaves `oracle_state`, the auxiliary data and metadata, and both aux sequences as they
+/// are. The new delegate's writes continue from the current `program_aux_sequence`. A
+/// `DelegateSchema` registered by the old delegate no longer matches and must be
+/// registered again, and the old delegate's write hook is removed.
+///
+/// [`clear_delegation`]: super::clear_delegation::process
+/// [`set_delegated_program`]: super::set_delegated_program::process
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    program_bitmask: &Mask,
+    user_bitmask: &Mask,
+) -> ProgramResult {
+    let [authority, envelope_account, old_delegation_authority, new_delegation_authority] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if &envelope.authority != authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    if envelope.delegation_authority == Address::zeroed() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    verify_delegation_authority(old_delegation_authority, &envelope.delegation_authority)?;
+
+    if !new_delegation_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if new_delegation_authority.address() == &Address::zeroed() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    envelope.delegation_authority = *new_delegation_authority.address();
+    envelope.write_hook = Address::zeroed();
+    envelope.program_bitmask = program_bitmask.with_sealed_from(&envelope.program_bitmask);
+    envelope.user_bitmask = user_bitmask.with_sealed_from(&envelope.user_bitmask);
+
+    Ok(())
+}
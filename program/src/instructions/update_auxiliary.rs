@@ -1,9 +1,15 @@
-use c_u_soon::{Envelope, StructMetadata};
+use c_u_soon::{aux_blocks_touched, ConstraintRegion, Envelope, StructMetadata};
 use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 
 /// Write auxiliary data as the oracle authority.
 ///
-/// Accounts: `[authority (signer), envelope_account, pda_account (signer)]`.
+/// Accounts: `[authority (signer), envelope_account, padding]`. When the envelope constrains
+/// its auxiliary region, the third account is its constraint table instead of padding (see
+/// [`constraints::enforce`](super::constraints::enforce)). When the envelope has a write
+/// hook, the hook program follows as a fourth account and is called once the write is
+/// applied (see [`write_hook`](super::write_hook)); its error aborts the write. When the
+/// envelope has the mutation guard, the instructions sysvar follows last (see
+/// [`mutation_guard::enforce`](super::mutation_guard::enforce)).
 ///
 /// `metadata` must match `envelope.auxiliary_metadata`. `data.len()` must equal
 /// `metadata.type_size()`. `sequence` must be strictly greater than
@@ -19,7 +25,8 @@ pub fn process(
     sequence: u64,
     data: &[u8],
 ) -> ProgramResult {
-    let [authority, envelope_account, _pda] = accounts else {
+    let (accounts, instructions_sysvar) = super::mutation_guard::split_sysvar(accounts);
+    let [authority, envelope_account, padding, trailing @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
@@ -34,11 +41,10 @@ pub fn process(
     let meta = StructMetadata::from_raw(metadata);
 
     let mut envelope_data = envelope_account.try_borrow_mut()?;
-    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut envelope_data);
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
 
-    if envelope.auxiliary_metadata != meta {
-        return Err(ProgramError::InvalidInstructionData);
-    }
+    super::metadata::check(envelope.auxiliary_metadata, meta)?;
 
     if data.len() != meta.type_size() as usize {
         return Err(ProgramError::InvalidInstructionData);
@@ -48,6 +54,8 @@ pub fn process(
         return Err(ProgramError::IncorrectAuthority);
     }
 
+    super::paused::check(envelope)?;
+
     if sequence <= envelope.authority_aux_sequence {
         return Err(ProgramError::InvalidInstructionData);
     }
@@ -56,6 +64,13 @@ pub fn process(
         return Err(ProgramError::InvalidArgument);
     }
 
+    super::mutation_guard::enforce(envelope, envelope_account.address(), instructions_sysvar)?;
+
+    let hook = super::write_hook::WriteHook::load(envelope, trailing)?;
+    let before = envelope
+        .is_constrained(ConstraintRegion::Aux)
+        .then_some(envelope.auxiliary_data);
+
     if !envelope
         .user_bitmask
         .apply_masked_update(&mut envelope.auxiliary_data, 0, data)
@@ -63,7 +78,25 @@ pub fn process(
         return Err(ProgramError::InvalidArgument);
     }
 
+    if let Some(before) = before {
+        super::constraints::enforce_after(
+            program_id,
+            envelope_account.address(),
+            padding,
+            ConstraintRegion::Aux,
+            &before,
+            &envelope.auxiliary_data,
+        )?;
+    }
+
+    envelope.record_aux_write(aux_blocks_touched(0, data.len()));
     envelope.authority_aux_sequence = sequence;
 
+    let call = hook.map(|hook| hook.call(sequence, &envelope.auxiliary_data));
+    drop(envelope_data);
+    if let Some(call) = call {
+        call.invoke(envelope_account)?;
+    }
+
     Ok(())
 }
@@ -1,14 +1,18 @@
 use super::cpi_verification::verify_delegation_authority;
 use bytemuck::Zeroable;
-use c_u_soon::{Envelope, StructMetadata};
+use c_u_soon::{aux_blocks_touched, ConstraintRegion, Envelope, StructMetadata};
 use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 
 /// Write auxiliary data as the delegated program.
 ///
-/// Accounts: `[delegation_authority (signer), envelope_account, _padding]`.
+/// Accounts: `[delegation_authority (signer), envelope_account, padding]`, followed by
+/// `[range_guard, instructions_sysvar]` when the envelope has a range guard. With only the
+/// mutation guard set, the single trailing account is `instructions_sysvar`.
 ///
 /// The third account is padding to keep this a 3-account instruction so the
-/// fast path (which intercepts all 2-account instructions) doesn't misroute it.
+/// fast path (which intercepts all 2-account instructions) doesn't misroute it. When the
+/// envelope constrains its auxiliary region, it is the constraint table instead (see
+/// [`constraints::enforce`](super::constraints::enforce)).
 ///
 /// `metadata` must match `envelope.auxiliary_metadata`. `data.len()` must equal
 /// `metadata.type_size()`. Requires an active delegation. `delegation_authority`
@@ -16,7 +20,11 @@ use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 /// greater than `envelope.program_aux_sequence`.
 ///
 /// `program_bitmask` gates which bytes of `auxiliary_data` may be written (`0x00` = writable,
-/// `0xFF` = blocked). Returns [`ProgramError::InvalidArgument`] if any blocked byte differs.
+/// `0xFF` = blocked). Returns [`ProgramError::InvalidArgument`] if any blocked byte differs,
+/// if a guarded range changes without its program in the transaction (see
+/// [`range_guard::enforce`](super::range_guard::enforce)), or if the mutation guard finds
+/// the envelope already written in this transaction (see
+/// [`mutation_guard::enforce`](super::mutation_guard::enforce)).
 pub fn process(
     program_id: &Address,
     accounts: &[AccountView],
@@ -24,7 +32,7 @@ pub fn process(
     sequence: u64,
     data: &[u8],
 ) -> ProgramResult {
-    let [delegation_authority, envelope_account, _padding] = accounts else {
+    let [delegation_authority, envelope_account, padding, remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
@@ -35,11 +43,10 @@ pub fn process(
     let meta = StructMetadata::from_raw(metadata);
 
     let mut envelope_data = envelope_account.try_borrow_mut()?;
-    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut envelope_data);
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
 
-    if envelope.auxiliary_metadata != meta {
-        return Err(ProgramError::InvalidInstructionData);
-    }
+    super::metadata::check(envelope.auxiliary_metadata, meta)?;
 
     if data.len() != meta.type_size() as usize {
         return Err(ProgramError::InvalidInstructionData);
@@ -51,10 +58,17 @@ pub fn process(
 
     verify_delegation_authority(delegation_authority, &envelope.delegation_authority)?;
 
+    super::paused::check(envelope)?;
+
     if sequence <= envelope.program_aux_sequence {
         return Err(ProgramError::InvalidInstructionData);
     }
 
+    super::mutation_guard::enforce(envelope, envelope_account.address(), remaining.last())?;
+
+    let before = (envelope.has_range_guard() || envelope.is_constrained(ConstraintRegion::Aux))
+        .then_some(envelope.auxiliary_data);
+
     if !envelope
         .program_bitmask
         .apply_masked_update(&mut envelope.auxiliary_data, 0, data)
@@ -62,6 +76,29 @@ pub fn process(
         return Err(ProgramError::InvalidArgument);
     }
 
+    if let Some(before) = &before {
+        if envelope.has_range_guard() {
+            super::range_guard::enforce(
+                program_id,
+                envelope_account.address(),
+                remaining,
+                before,
+                &envelope.auxiliary_data,
+            )?;
+        }
+        if envelope.is_constrained(ConstraintRegion::Aux) {
+            super::constraints::enforce_after(
+                program_id,
+                envelope_account.address(),
+                padding,
+                ConstraintRegion::Aux,
+                before,
+                &envelope.auxiliary_data,
+            )?;
+        }
+    }
+
+    envelope.record_aux_write(aux_blocks_touched(0, data.len()));
     envelope.program_aux_sequence = sequence;
 
     Ok(())
@@ -1,11 +1,18 @@
 use super::cpi_verification::verify_delegation_authority;
 use bytemuck::Zeroable;
-use c_u_soon::{Envelope, StructMetadata};
-use c_u_soon_instruction::WriteSpec;
+use c_u_soon::{ConstraintRegion, Envelope, StructMetadata};
+use c_u_soon_instruction::WriteRange;
 use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 
 /// Validate delegation accounts, envelope ownership, metadata, sequence, and delegation authority,
-/// then call `apply` with the validated envelope and metadata.
+/// then call `apply` with the validated envelope and metadata. `apply` returns the aux
+/// blocks it wrote, which are recorded on the envelope.
+///
+/// If the envelope has a range guard, the trailing `[range_guard, instructions_sysvar]`
+/// accounts are checked against the bytes `apply` changed. If it has the mutation guard, the
+/// last trailing account must be the instructions sysvar (see
+/// [`mutation_guard::enforce`](super::mutation_guard::enforce)). If it constrains its
+/// auxiliary region, the third account is the constraint table, checked after `apply`.
 fn with_validated_delegation<F>(
     program_id: &Address,
     accounts: &[AccountView],
@@ -14,9 +21,9 @@ fn with_validated_delegation<F>(
     apply: F,
 ) -> ProgramResult
 where
-    F: FnOnce(&mut Envelope, StructMetadata) -> Result<(), ProgramError>,
+    F: FnOnce(&mut Envelope, StructMetadata) -> Result<u8, ProgramError>,
 {
-    let [delegation_authority, envelope_account, _padding] = accounts else {
+    let [delegation_authority, envelope_account, padding, remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
@@ -27,11 +34,10 @@ where
     let meta = StructMetadata::from_raw(metadata);
 
     let mut envelope_data = envelope_account.try_borrow_mut()?;
-    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut envelope_data);
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
 
-    if envelope.auxiliary_metadata != meta {
-        return Err(ProgramError::InvalidInstructionData);
-    }
+    super::metadata::check(envelope.auxiliary_metadata, meta)?;
 
     if envelope.delegation_authority == Address::zeroed() {
         return Err(ProgramError::InvalidArgument);
@@ -39,11 +45,42 @@ where
 
     verify_delegation_authority(delegation_authority, &envelope.delegation_authority)?;
 
+    super::paused::check(envelope)?;
+
     if sequence <= envelope.program_aux_sequence {
         return Err(ProgramError::InvalidInstructionData);
     }
 
-    apply(envelope, meta)?;
+    super::mutation_guard::enforce(envelope, envelope_account.address(), remaining.last())?;
+
+    let before = (envelope.has_range_guard() || envelope.is_constrained(ConstraintRegion::Aux))
+        .then_some(envelope.auxiliary_data);
+
+    let blocks = apply(envelope, meta)?;
+
+    if let Some(before) = &before {
+        if envelope.has_range_guard() {
+            super::range_guard::enforce(
+                program_id,
+                envelope_account.address(),
+                remaining,
+                before,
+                &envelope.auxiliary_data,
+            )?;
+        }
+        if envelope.is_constrained(ConstraintRegion::Aux) {
+            super::constraints::enforce_after(
+                program_id,
+                envelope_account.address(),
+                padding,
+                ConstraintRegion::Aux,
+                before,
+                &envelope.auxiliary_data,
+            )?;
+        }
+    }
+
+    envelope.record_aux_write(blocks);
     envelope.program_aux_sequence = sequence;
 
     Ok(())
@@ -51,7 +88,9 @@ where
 
 /// Zero-alloc single-range write of auxiliary data as the delegated program.
 ///
-/// Accounts: `[delegation_authority (signer), envelope_account, _padding]`.
+/// Accounts: `[delegation_authority (signer), envelope_account, padding]`, plus
+/// `[range_guard, instructions_sysvar]` when the envelope has a range guard, or
+/// `[instructions_sysvar]` with only the mutation guard.
 pub fn process_single(
     program_id: &Address,
     accounts: &[AccountView],
@@ -79,16 +118,19 @@ pub fn process_single(
 
 /// Write multiple non-contiguous byte ranges of auxiliary data as the delegated program.
 ///
-/// Accounts: `[delegation_authority (signer), envelope_account, _padding]`.
+/// Accounts: `[delegation_authority (signer), envelope_account, padding]`, plus
+/// `[range_guard, instructions_sysvar]` when the envelope has a range guard, or
+/// `[instructions_sysvar]` with only the mutation guard.
 ///
 /// Each range is validated against `program_bitmask` via `check_masked_update` (blocked
 /// bytes are allowed as long as they're unchanged). Validate-then-apply ensures atomicity.
-pub fn process(
+/// Serves `UpdateAuxiliaryDelegatedMultiRange` and its `WriteSpecV2` form alike.
+pub fn process<R: WriteRange>(
     program_id: &Address,
     accounts: &[AccountView],
     metadata: u64,
     sequence: u64,
-    ranges: Vec<WriteSpec>,
+    ranges: Vec<R>,
 ) -> ProgramResult {
     with_validated_delegation(
         program_id,
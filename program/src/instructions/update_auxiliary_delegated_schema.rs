@@ -0,0 +1,119 @@
+use super::cpi_verification::verify_delegation_authority;
+use bytemuck::Zeroable;
+use c_u_soon::{aux_blocks_touched, ConstraintRegion, DelegateSchema, Envelope, StructMetadata};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Write the delegate's registered sub-struct as the delegated program.
+///
+/// Accounts: `[delegation_authority (signer), envelope_account, delegate_schema_account]`,
+/// followed by `[range_guard, instructions_sysvar]` when the envelope has a range guard, or
+/// `[instructions_sysvar]` with only the mutation guard.
+/// The schema account takes the slot that plain delegated writes fill with padding, so a
+/// constraint table, when the auxiliary region is constrained, is the first trailing
+/// account, before the guard accounts.
+///
+/// `delegate_schema_account` must be owned by this program, be exactly
+/// [`DelegateSchema::SIZE`] bytes, name `envelope_account`, and have been registered by the
+/// current `delegation_authority`; otherwise returns [`ProgramError::InvalidAccountData`].
+/// `metadata` must match the schema's metadata (see
+/// [`metadata::check`](super::metadata::check)) and `data.len()` its type size
+/// ([`ProgramError::InvalidInstructionData`]). `data` is written at the schema's offset
+/// through `program_bitmask`, and `sequence` must be strictly greater than
+/// `envelope.program_aux_sequence`, same as other delegated writes.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    metadata: u64,
+    sequence: u64,
+    data: &[u8],
+) -> ProgramResult {
+    let [delegation_authority, envelope_account, schema_account, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !schema_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let schema_data = schema_account.try_borrow()?;
+    if schema_data.len() != DelegateSchema::SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let schema: &DelegateSchema = bytemuck::from_bytes(&schema_data);
+
+    if &schema.envelope != envelope_account.address() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if envelope.delegation_authority == Address::zeroed() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    verify_delegation_authority(delegation_authority, &envelope.delegation_authority)?;
+
+    if schema.delegation_authority != envelope.delegation_authority {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    super::metadata::check(schema.metadata, StructMetadata::from_raw(metadata))?;
+
+    let span = schema.span().ok_or(ProgramError::InvalidAccountData)?;
+    if data.len() != span.len() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    super::paused::check(envelope)?;
+
+    if sequence <= envelope.program_aux_sequence {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let (table, remaining) =
+        super::constraints::split_table(envelope, ConstraintRegion::Aux, remaining)?;
+
+    super::mutation_guard::enforce(envelope, envelope_account.address(), remaining.last())?;
+
+    let before = (envelope.has_range_guard() || table.is_some()).then_some(envelope.auxiliary_data);
+
+    if !envelope
+        .program_bitmask
+        .apply_masked_update(&mut envelope.auxiliary_data, span.start, data)
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if let Some(before) = &before {
+        if envelope.has_range_guard() {
+            super::range_guard::enforce(
+                program_id,
+                envelope_account.address(),
+                remaining,
+                before,
+                &envelope.auxiliary_data,
+            )?;
+        }
+        if let Some(table) = table {
+            super::constraints::enforce_after(
+                program_id,
+                envelope_account.address(),
+                table,
+                ConstraintRegion::Aux,
+                before,
+                &envelope.auxiliary_data,
+            )?;
+        }
+    }
+
+    envelope.record_aux_write(aux_blocks_touched(span.start, data.len()));
+    envelope.program_aux_sequence = sequence;
+
+    Ok(())
+}
@@ -1,27 +1,25 @@
 use super::cpi_verification::verify_delegation_authority;
 use bytemuck::Zeroable;
-use c_u_soon::{Envelope, StructMetadata};
+use c_u_soon::{ConstraintRegion, Envelope, StructMetadata, AUX_DATA_SIZE};
+use c_u_soon_instruction::WriteRange;
 use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 
-/// Reset both sequence counters and overwrite auxiliary data, requiring both signers.
-///
-/// Accounts: `[authority (signer), envelope_account, delegation_authority (signer)]`.
-///
-/// `metadata` must match `envelope.auxiliary_metadata`. `data.len()` must equal
-/// `metadata.type_size()`. Requires an active delegation. Both `authority` and
-/// `delegation_authority` must sign.
-///
-/// Overwrites `auxiliary_data[..data.len()]` without bitmask enforcement and zeroes
-/// trailing bytes. Sets both sequence counters simultaneously.
-pub fn process(
+/// Validate both signers, envelope ownership, metadata, delegation, and both sequences, then
+/// call `apply` with the validated envelope and metadata. `apply` returns the aux blocks it
+/// wrote, which are recorded on the envelope before both sequence counters are set.
+fn with_validated_force<F>(
     program_id: &Address,
     accounts: &[AccountView],
     metadata: u64,
     authority_sequence: u64,
     program_sequence: u64,
-    data: &[u8],
-) -> ProgramResult {
-    let [authority, envelope_account, delegation_authority] = accounts else {
+    apply: F,
+) -> ProgramResult
+where
+    F: FnOnce(&mut Envelope, StructMetadata) -> Result<u8, ProgramError>,
+{
+    let (accounts, instructions_sysvar) = super::mutation_guard::split_sysvar(accounts);
+    let [authority, envelope_account, delegation_authority, remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
@@ -36,15 +34,10 @@ pub fn process(
     let meta = StructMetadata::from_raw(metadata);
 
     let mut envelope_data = envelope_account.try_borrow_mut()?;
-    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut envelope_data);
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
 
-    if envelope.auxiliary_metadata != meta {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-
-    if data.len() != meta.type_size() as usize {
-        return Err(ProgramError::InvalidInstructionData);
-    }
+    super::metadata::check(envelope.auxiliary_metadata, meta)?;
 
     if envelope.authority != *authority.address() {
         return Err(ProgramError::IncorrectAuthority);
@@ -56,6 +49,8 @@ pub fn process(
 
     verify_delegation_authority(delegation_authority, &envelope.delegation_authority)?;
 
+    super::paused::check(envelope)?;
+
     if authority_sequence <= envelope.authority_aux_sequence {
         return Err(ProgramError::InvalidInstructionData);
     }
@@ -64,10 +59,110 @@ pub fn process(
         return Err(ProgramError::InvalidInstructionData);
     }
 
-    envelope.auxiliary_data[..data.len()].copy_from_slice(data);
-    envelope.auxiliary_data[data.len()..].fill(0);
+    super::mutation_guard::enforce(envelope, envelope_account.address(), instructions_sysvar)?;
+
+    let (table, _) = super::constraints::split_table(envelope, ConstraintRegion::Aux, remaining)?;
+
+    let before = table.is_some().then_some(envelope.auxiliary_data);
+
+    let blocks = apply(envelope, meta)?;
+
+    if let (Some(table), Some(before)) = (table, &before) {
+        super::constraints::enforce_after(
+            program_id,
+            envelope_account.address(),
+            table,
+            ConstraintRegion::Aux,
+            before,
+            &envelope.auxiliary_data,
+        )?;
+    }
+
+    envelope.record_aux_write(blocks);
     envelope.authority_aux_sequence = authority_sequence;
     envelope.program_aux_sequence = program_sequence;
 
     Ok(())
 }
+
+/// Reset both sequence counters and overwrite auxiliary data, requiring both signers.
+///
+/// Accounts: `[authority (signer), envelope_account, delegation_authority (signer)]`,
+/// followed by the constraint table when the envelope constrains its auxiliary region (see
+/// [`constraints::enforce`](super::constraints::enforce)). Constraints hold even though the
+/// masks are bypassed. When the envelope has the mutation guard, the instructions sysvar
+/// follows last (see [`mutation_guard::enforce`](super::mutation_guard::enforce)).
+///
+/// `metadata` must match `envelope.auxiliary_metadata`. `data.len()` must equal
+/// `metadata.type_size()`. Requires an active delegation. Both `authority` and
+/// `delegation_authority` must sign.
+///
+/// Overwrites `auxiliary_data[..data.len()]` without bitmask enforcement and zeroes
+/// trailing bytes, so every aux block is recorded as dirty. Sealed bytes are the exception:
+/// the write fails with `InvalidArgument` if it would change one, zeroing included. Sets
+/// both sequence counters simultaneously.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    metadata: u64,
+    authority_sequence: u64,
+    program_sequence: u64,
+    data: &[u8],
+) -> ProgramResult {
+    with_validated_force(
+        program_id,
+        accounts,
+        metadata,
+        authority_sequence,
+        program_sequence,
+        |envelope, meta| {
+            if data.len() != meta.type_size() as usize {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let sealed = envelope.program_bitmask.sealed_only();
+            let tail = [0u8; AUX_DATA_SIZE];
+            let tail = &tail[..AUX_DATA_SIZE - data.len()];
+            if !sealed.check_masked_update(&envelope.auxiliary_data, data.len(), tail)
+                || !sealed.apply_masked_update(&mut envelope.auxiliary_data, 0, data)
+            {
+                return Err(ProgramError::InvalidArgument);
+            }
+            envelope.auxiliary_data[data.len()..].fill(0);
+            Ok(0xFF)
+        },
+    )
+}
+
+/// Reset both sequence counters and write multiple byte ranges of auxiliary data,
+/// requiring both signers.
+///
+/// Accounts and signer, delegation, and sequence checks are as for [`process`]. Each range
+/// must lie within `metadata.type_size()`, and neither bitmask's permissions are consulted,
+/// but a range that would change a sealed byte fails with `InvalidArgument`. Bytes outside
+/// the ranges keep their values, and only the blocks the ranges touch are recorded as
+/// dirty. Validate-then-apply ensures atomicity. Serves `UpdateAuxiliaryMultiRangeForce` and
+/// its `WriteSpecV2` form alike.
+pub fn process_multi_range<R: WriteRange>(
+    program_id: &Address,
+    accounts: &[AccountView],
+    metadata: u64,
+    authority_sequence: u64,
+    program_sequence: u64,
+    ranges: Vec<R>,
+) -> ProgramResult {
+    with_validated_force(
+        program_id,
+        accounts,
+        metadata,
+        authority_sequence,
+        program_sequence,
+        |envelope, meta| {
+            super::apply_ranges::validate_and_apply(
+                &mut envelope.auxiliary_data,
+                &envelope.program_bitmask.sealed_only(),
+                meta.type_size() as usize,
+                &ranges,
+            )
+        },
+    )
+}
@@ -1,9 +1,14 @@
-use c_u_soon::{Envelope, StructMetadata};
-use c_u_soon_instruction::WriteSpec;
+use c_u_soon::{ConstraintRegion, Envelope, StructMetadata};
+use c_u_soon_instruction::WriteRange;
 use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 
 /// Validate authority accounts, envelope ownership, metadata, sequence, and delegation,
-/// then call `apply` with the validated envelope and metadata.
+/// then call `apply` with the validated envelope and metadata. `apply` returns the aux
+/// blocks it wrote, which are recorded on the envelope. When the envelope constrains its
+/// auxiliary region, the third account is the constraint table, checked after `apply`.
+/// When it has a write hook, the fourth account is the hook program, called last. When it
+/// has the mutation guard, the instructions sysvar follows last (see
+/// [`mutation_guard::enforce`](super::mutation_guard::enforce)).
 fn with_validated_authority<F>(
     program_id: &Address,
     accounts: &[AccountView],
@@ -12,9 +17,10 @@ fn with_validated_authority<F>(
     apply: F,
 ) -> ProgramResult
 where
-    F: FnOnce(&mut Envelope, StructMetadata) -> Result<(), ProgramError>,
+    F: FnOnce(&mut Envelope, StructMetadata) -> Result<u8, ProgramError>,
 {
-    let [authority, envelope_account, _pda] = accounts else {
+    let (accounts, instructions_sysvar) = super::mutation_guard::split_sysvar(accounts);
+    let [authority, envelope_account, padding, trailing @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
@@ -29,16 +35,17 @@ where
     let meta = StructMetadata::from_raw(metadata);
 
     let mut envelope_data = envelope_account.try_borrow_mut()?;
-    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut envelope_data);
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
 
-    if envelope.auxiliary_metadata != meta {
-        return Err(ProgramError::InvalidInstructionData);
-    }
+    super::metadata::check(envelope.auxiliary_metadata, meta)?;
 
     if envelope.authority != *authority.address() {
         return Err(ProgramError::IncorrectAuthority);
     }
 
+    super::paused::check(envelope)?;
+
     if sequence <= envelope.authority_aux_sequence {
         return Err(ProgramError::InvalidInstructionData);
     }
@@ -47,15 +54,43 @@ where
         return Err(ProgramError::InvalidArgument);
     }
 
-    apply(envelope, meta)?;
+    super::mutation_guard::enforce(envelope, envelope_account.address(), instructions_sysvar)?;
+
+    let hook = super::write_hook::WriteHook::load(envelope, trailing)?;
+    let before = envelope
+        .is_constrained(ConstraintRegion::Aux)
+        .then_some(envelope.auxiliary_data);
+
+    let blocks = apply(envelope, meta)?;
+    if let Some(before) = before {
+        super::constraints::enforce_after(
+            program_id,
+            envelope_account.address(),
+            padding,
+            ConstraintRegion::Aux,
+            &before,
+            &envelope.auxiliary_data,
+        )?;
+    }
+
+    envelope.record_aux_write(blocks);
     envelope.authority_aux_sequence = sequence;
 
+    let call = hook.map(|hook| hook.call(sequence, &envelope.auxiliary_data));
+    drop(envelope_data);
+    if let Some(call) = call {
+        call.invoke(envelope_account)?;
+    }
+
     Ok(())
 }
 
 /// Zero-alloc single-range write of auxiliary data as the oracle authority.
 ///
-/// Accounts: `[authority (signer), envelope_account, pda_account (signer)]`.
+/// Accounts: `[authority (signer), envelope_account, padding]`, with the constraint table
+/// as the third account when the auxiliary region is constrained, the hook program as the
+/// fourth when the envelope has a write hook, and the instructions sysvar last when it has
+/// the mutation guard.
 pub fn process_single(
     program_id: &Address,
     accounts: &[AccountView],
@@ -83,16 +118,20 @@ pub fn process_single(
 
 /// Write multiple non-contiguous byte ranges of auxiliary data as the oracle authority.
 ///
-/// Accounts: `[authority (signer), envelope_account, pda_account (signer)]`.
+/// Accounts: `[authority (signer), envelope_account, padding]`, with the constraint table
+/// as the third account when the auxiliary region is constrained, the hook program as the
+/// fourth when the envelope has a write hook, and the instructions sysvar last when it has
+/// the mutation guard.
 ///
 /// Each range is validated against `user_bitmask` via `check_masked_update` (blocked
 /// bytes are allowed as long as they're unchanged). Validate-then-apply ensures atomicity.
-pub fn process(
+/// Serves `UpdateAuxiliaryMultiRange` and its `WriteSpecV2` form alike.
+pub fn process<R: WriteRange>(
     program_id: &Address,
     accounts: &[AccountView],
     metadata: u64,
     sequence: u64,
-    ranges: Vec<WriteSpec>,
+    ranges: Vec<R>,
 ) -> ProgramResult {
     with_validated_authority(
         program_id,
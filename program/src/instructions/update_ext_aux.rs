@@ -0,0 +1,62 @@
+use c_u_soon::Envelope;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Write part of the extended auxiliary region as the envelope authority.
+///
+/// Accounts: `[authority (signer), envelope_account, padding]`. The padding account keeps
+/// the instruction off the two-account fast path and is not read. When the envelope has the
+/// mutation guard, the instructions sysvar follows last (see
+/// [`mutation_guard::enforce`](super::mutation_guard::enforce)).
+///
+/// `sequence` must be strictly greater than the extension header's `sequence`
+/// ([`ProgramError::InvalidInstructionData`] otherwise). `data` is written at `offset`,
+/// which with `data.len()` must lie within the extension ([`ProgramError::InvalidArgument`]).
+/// An envelope without an extension fails with [`ProgramError::InvalidAccountData`].
+///
+/// The masks and delegation do not apply: only the authority writes the extension.
+pub fn process(
+    program_id: &Address,
+    accounts: &[AccountView],
+    sequence: u64,
+    offset: u16,
+    data: &[u8],
+) -> ProgramResult {
+    let (accounts, instructions_sysvar) = super::mutation_guard::split_sysvar(accounts);
+    let [authority, envelope_account, _padding] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !envelope_account.owned_by(program_id) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut envelope_data = envelope_account.try_borrow_mut()?;
+    let envelope: &mut Envelope = Envelope::from_prefix_bytes_mut(&mut envelope_data)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if envelope.authority != *authority.address() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+    super::paused::check(envelope)?;
+    super::mutation_guard::enforce(envelope, envelope_account.address(), instructions_sysvar)?;
+
+    let header =
+        Envelope::ext_aux_header_mut(&mut envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+    if sequence <= header.sequence {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    header.sequence = sequence;
+
+    let ext =
+        Envelope::ext_aux_bytes_mut(&mut envelope_data).ok_or(ProgramError::InvalidAccountData)?;
+    let start = offset as usize;
+    let dst = ext
+        .get_mut(start..start + data.len())
+        .ok_or(ProgramError::InvalidArgument)?;
+    dst.copy_from_slice(data);
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use c_u_soon::{Envelope, AUX_DATA_SIZE};
+use c_u_soon_instruction::{write_hook_data, WRITE_HOOK_HEADER_SIZE, WRITE_HOOK_MAX_SIZE};
+use pinocchio::{
+    cpi::invoke,
+    error::ProgramError,
+    instruction::{InstructionAccount, InstructionView},
+    AccountView, ProgramResult,
+};
+
+/// The write hook of an authority aux write, with the aux bytes as they were before it.
+///
+/// [`load`](Self::load) runs while the envelope is borrowed, before the write is applied;
+/// [`call`](Self::call) encodes what changed once it has been. The envelope must be released
+/// before [`PendingCall::invoke`], since the hook program reads it.
+pub struct WriteHook<'a> {
+    program: &'a AccountView,
+    before: [u8; AUX_DATA_SIZE],
+}
+
+impl<'a> WriteHook<'a> {
+    /// `None` if `envelope` has no write hook. Otherwise the first of `trailing` (the
+    /// accounts after the padding or constraint table) must be the hook program: fails with
+    /// `NotEnoughAccountKeys` if it is missing and `IncorrectProgramId` if it is another
+    /// account.
+    pub fn load(
+        envelope: &Envelope,
+        trailing: &'a [AccountView],
+    ) -> Result<Option<Self>, ProgramError> {
+        let Some(hook) = envelope.write_hook() else {
+            return Ok(None);
+        };
+        let program = trailing.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if program.address() != hook {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Ok(Some(Self {
+            program,
+            before: envelope.auxiliary_data,
+        }))
+    }
+
+    /// Encode the call reporting the write that left the aux bytes at `after` under the
+    /// authority aux `sequence`.
+    pub fn call(&self, sequence: u64, after: &[u8; AUX_DATA_SIZE]) -> PendingCall<'a> {
+        let mut data = [0u8; WRITE_HOOK_MAX_SIZE];
+        let len = write_hook_data(sequence, &self.before, after, &mut data);
+        PendingCall {
+            program: self.program,
+            data,
+            len,
+        }
+    }
+}
+
+/// An encoded write hook call, waiting for the envelope to be released.
+pub struct PendingCall<'a> {
+    program: &'a AccountView,
+    data: [u8; WRITE_HOOK_MAX_SIZE],
+    len: usize,
+}
+
+impl PendingCall<'_> {
+    /// Invoke the hook program with `[envelope (readonly)]`, so it can read the new aux
+    /// bytes. Its error is returned as is, aborting the write. Skipped when the write
+    /// changed no byte.
+    pub fn invoke(&self, envelope_account: &AccountView) -> ProgramResult {
+        if self.len == WRITE_HOOK_HEADER_SIZE {
+            return Ok(());
+        }
+        let cpi_accounts = [InstructionAccount::readonly(envelope_account.address())];
+        let ix = InstructionView {
+            program_id: self.program.address(),
+            accounts: &cpi_accounts,
+            data: &self.data[..self.len],
+        };
+        invoke(&ix, &[envelope_account])
+    }
+}
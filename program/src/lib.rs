@@ -2,7 +2,18 @@
 //!
 //! The entry point dispatches on account count: two accounts take the fast path
 //! (direct oracle data update), anything else goes to the slow path (account
-//! administration via [`SlowPathInstruction`]).
+//! administration via [`SlowPathInstruction`]). Updates to several envelopes in one
+//! instruction (`BATCH_UPDATE_TAG`) need at least three accounts, so they are routed to
+//! the slow path and dispatched there by tag. So are oracle updates signed by registered
+//! publishers (`PUBLISHER_UPDATE_TAG`), which also carry the envelope's publisher set.
+//!
+//! Envelope accounts larger than `Envelope::SIZE` are accepted by every slow-path handler:
+//! only the first `SIZE` bytes are read or written (`Envelope::from_prefix_bytes`), and
+//! `Close` zeroes the whole account. Shorter accounts fail with `InvalidAccountData`. The
+//! fast path is fastest at the exact size; larger envelopes take a cold fallback that
+//! applies the same checks and costs more units.
+//! `Resize` grows envelopes this way on purpose to append an extended auxiliary region,
+//! which `UpdateExtAux` writes.
 //!
 //! Requires `asm_experimental_arch` for sBPF inline assembly in the fast path.
 //!
@@ -13,7 +24,10 @@
 extern crate alloc;
 
 mod entrypoint;
-mod fast_path;
+mod event;
+pub mod fast_path;
+mod hash;
 mod instructions;
 mod pda;
 mod slow_path;
+mod sysvar;
@@ -1,8 +1,11 @@
-use c_u_soon::Mask;
+use c_u_soon::{CompactMask, Mask};
 use c_u_soon_instruction::{
-    SlowPathInstruction, UPDATE_AUX_DELEGATED_RANGE_TAG, UPDATE_AUX_DELEGATED_TAG,
+    is_supported_version, requires_trailing_config, split_versioned, SlowPathInstruction,
+    BATCH_UPDATE_HEADER_SIZE, BATCH_UPDATE_TAG, MAX_SLOW_PATH_TAG, PUBLISHER_UPDATE_HEADER_SIZE,
+    PUBLISHER_UPDATE_TAG, SPLIT_UPDATE_HEADER_SIZE, SPLIT_UPDATE_TAG, UNSUPPORTED_VERSION_ERROR,
+    UPDATE_AUX_DELEGATED_RANGE_TAG, UPDATE_AUX_DELEGATED_SCHEMA_TAG, UPDATE_AUX_DELEGATED_TAG,
     UPDATE_AUX_FORCE_HEADER_SIZE, UPDATE_AUX_FORCE_TAG, UPDATE_AUX_HEADER_SIZE,
-    UPDATE_AUX_RANGE_HEADER_SIZE, UPDATE_AUX_RANGE_TAG, UPDATE_AUX_TAG,
+    UPDATE_AUX_RANGE_HEADER_SIZE, UPDATE_AUX_RANGE_TAG, UPDATE_AUX_TAG, VERSIONED_TAG,
 };
 use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 use wincode::SchemaRead;
@@ -23,19 +26,46 @@ pub(crate) unsafe fn slow_entrypoint(input: *mut u8) -> u64 {
     pinocchio::entrypoint::process_entrypoint::<64>(input, process_instruction)
 }
 
-/// Dispatch a slow-path instruction.
+/// Unwrap a versioned instruction, then dispatch it.
 ///
-/// Tags 4-8 (UpdateAuxiliary variants) use a manual wire format.
-/// All other tags (0-3, 9-10) use wincode deserialization with trailing-data rejection.
+/// Tag 35 (`VERSIONED_TAG`) carries a version byte and another instruction. Versions this
+/// build does not support fail with `Custom(UNSUPPORTED_VERSION_ERROR)` before the inner
+/// instruction is read. Wrappers do not nest: a versioned instruction inside another fails
+/// to deserialize.
 fn process_instruction(
     program_id: &Address,
     accounts: &[AccountView],
     data: &[u8],
 ) -> ProgramResult {
+    if data.len() >= 4 && data[..4] == VERSIONED_TAG.to_le_bytes() {
+        let (version, inner) = split_versioned(data).ok_or(ProgramError::InvalidInstructionData)?;
+        if !is_supported_version(version) {
+            return Err(ProgramError::Custom(UNSUPPORTED_VERSION_ERROR));
+        }
+        return dispatch(program_id, accounts, inner);
+    }
+    dispatch(program_id, accounts, data)
+}
+
+/// Dispatch a slow-path instruction.
+///
+/// Tags 4-8 and 15 (UpdateAuxiliary variants), 25 (BatchUpdate), 32 (PublisherUpdate), and 56
+/// (SplitUpdate) use a manual wire format. All other tags (0-3, 9-14, 16-24, 26-31, 33-34,
+/// 36-47, 49-55) use wincode deserialization with trailing-data rejection. Tags past
+/// `MAX_SLOW_PATH_TAG` fail with `Custom(UNSUPPORTED_VERSION_ERROR)`.
+///
+/// For tags that [`requires_trailing_config`], the program config, the last account, is
+/// checked for the pause and removed before the handler sees the accounts.
+fn dispatch(program_id: &Address, accounts: &[AccountView], data: &[u8]) -> ProgramResult {
     if data.len() < 4 {
         return Err(ProgramError::InvalidInstructionData);
     }
     let disc = u32::from_le_bytes(data[..4].try_into().unwrap());
+    let accounts = if requires_trailing_config(disc) {
+        instructions::config::strip(program_id, accounts)?
+    } else {
+        accounts
+    };
 
     match disc {
         UPDATE_AUX_TAG => {
@@ -60,6 +90,17 @@ fn process_instruction(
                 program_id, accounts, metadata, sequence, aux_data,
             )
         }
+        UPDATE_AUX_DELEGATED_SCHEMA_TAG => {
+            if data.len() < UPDATE_AUX_HEADER_SIZE {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let metadata = u64::from_le_bytes(data[4..12].try_into().unwrap());
+            let sequence = u64::from_le_bytes(data[12..20].try_into().unwrap());
+            let aux_data = &data[20..];
+            instructions::update_auxiliary_delegated_schema::process(
+                program_id, accounts, metadata, sequence, aux_data,
+            )
+        }
         UPDATE_AUX_FORCE_TAG => {
             if data.len() < UPDATE_AUX_FORCE_HEADER_SIZE {
                 return Err(ProgramError::InvalidInstructionData);
@@ -96,6 +137,42 @@ fn process_instruction(
                 program_id, accounts, metadata, sequence, offset, range_data,
             )
         }
+        BATCH_UPDATE_TAG => {
+            if data.len() < BATCH_UPDATE_HEADER_SIZE {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let count = data[4];
+            let entries = &data[5..];
+            instructions::batch_update::process(program_id, accounts, count, entries)
+        }
+        PUBLISHER_UPDATE_TAG => {
+            if data.len() < PUBLISHER_UPDATE_HEADER_SIZE {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let oracle_meta = u64::from_le_bytes(data[4..12].try_into().unwrap());
+            let sequence = u64::from_le_bytes(data[12..20].try_into().unwrap());
+            let payload = &data[20..];
+            instructions::publisher_update::process(
+                program_id,
+                accounts,
+                oracle_meta,
+                sequence,
+                payload,
+            )
+        }
+        SPLIT_UPDATE_TAG => {
+            if data.len() < SPLIT_UPDATE_HEADER_SIZE {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let type_hash = u64::from_le_bytes(data[4..12].try_into().unwrap());
+            let sequence = u64::from_le_bytes(data[12..20].try_into().unwrap());
+            let part = data[20];
+            let payload = &data[SPLIT_UPDATE_HEADER_SIZE..];
+            instructions::split_update::process(
+                program_id, accounts, type_hash, sequence, part, payload,
+            )
+        }
+        _ if disc > MAX_SLOW_PATH_TAG => Err(ProgramError::Custom(UNSUPPORTED_VERSION_ERROR)),
         _ => {
             // Wincode deserialization with trailing-data rejection
             let mut cursor: &[u8] = data;
@@ -118,6 +195,7 @@ fn process_instruction(
                     custom_seeds,
                     bump,
                     oracle_metadata,
+                    false,
                 ),
                 SlowPathInstruction::Close => instructions::close::process(program_id, accounts),
                 SlowPathInstruction::SetDelegatedProgram {
@@ -129,6 +207,15 @@ fn process_instruction(
                     &Mask::from(program_bitmask),
                     &Mask::from(user_bitmask),
                 ),
+                SlowPathInstruction::SetDelegatedProgramCompact {
+                    program_bitmask,
+                    user_bitmask,
+                } => instructions::set_delegated_program::process(
+                    program_id,
+                    accounts,
+                    &Mask::from(CompactMask::from_bytes(program_bitmask)),
+                    &Mask::from(CompactMask::from_bytes(user_bitmask)),
+                ),
                 SlowPathInstruction::ClearDelegation => {
                     instructions::clear_delegation::process(program_id, accounts)
                 }
@@ -146,6 +233,220 @@ fn process_instruction(
                 } => instructions::update_auxiliary_delegated_multi_range::process(
                     program_id, accounts, metadata, sequence, ranges,
                 ),
+                SlowPathInstruction::UpdateAuxiliaryMultiRangeForce {
+                    metadata,
+                    authority_sequence,
+                    program_sequence,
+                    ranges,
+                } => instructions::update_auxiliary_force::process_multi_range(
+                    program_id,
+                    accounts,
+                    metadata,
+                    authority_sequence,
+                    program_sequence,
+                    ranges,
+                ),
+                SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 {
+                    metadata,
+                    sequence,
+                    ranges,
+                } => instructions::update_auxiliary_multi_range::process(
+                    program_id, accounts, metadata, sequence, ranges,
+                ),
+                SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRangeV2 {
+                    metadata,
+                    sequence,
+                    ranges,
+                } => instructions::update_auxiliary_delegated_multi_range::process(
+                    program_id, accounts, metadata, sequence, ranges,
+                ),
+                SlowPathInstruction::UpdateAuxiliaryMultiRangeForceV2 {
+                    metadata,
+                    authority_sequence,
+                    program_sequence,
+                    ranges,
+                } => instructions::update_auxiliary_force::process_multi_range(
+                    program_id,
+                    accounts,
+                    metadata,
+                    authority_sequence,
+                    program_sequence,
+                    ranges,
+                ),
+                SlowPathInstruction::SetRangeGuards { bump, entries } => {
+                    instructions::set_range_guards::process(program_id, accounts, bump, &entries)
+                }
+                SlowPathInstruction::SetDelegateSchema {
+                    bump,
+                    offset,
+                    metadata,
+                } => instructions::set_delegate_schema::process(
+                    program_id, accounts, bump, offset, metadata,
+                ),
+                SlowPathInstruction::SetPermanent => {
+                    instructions::set_permanent::process(program_id, accounts)
+                }
+                SlowPathInstruction::SetMutationGuard { allow_multi } => {
+                    instructions::set_mutation_guard::process(program_id, accounts, allow_multi)
+                }
+                SlowPathInstruction::SetTwap { enabled } => {
+                    instructions::set_twap::process(program_id, accounts, enabled)
+                }
+                SlowPathInstruction::SetUpdateStamp { enabled } => {
+                    instructions::set_update_stamp::process(program_id, accounts, enabled)
+                }
+                SlowPathInstruction::ReadAuxRange { offset, len } => {
+                    instructions::read_aux_range::process(program_id, accounts, offset, len)
+                }
+                SlowPathInstruction::SetGroupMembers { group_id, bump } => {
+                    instructions::set_group_members::process(program_id, accounts, group_id, bump)
+                }
+                SlowPathInstruction::GroupCommit { sequence, updates } => {
+                    instructions::group_commit::process(program_id, accounts, sequence, &updates)
+                }
+                SlowPathInstruction::SetLabel { bump, label } => {
+                    instructions::set_label::process(program_id, accounts, bump, &label)
+                }
+                SlowPathInstruction::AssertAuxHash { expected } => {
+                    instructions::assert_aux_hash::process(program_id, accounts, &expected)
+                }
+                SlowPathInstruction::ReadOracle => {
+                    instructions::read_oracle::process(program_id, accounts)
+                }
+                SlowPathInstruction::ReadAux => {
+                    instructions::read_aux::process(program_id, accounts)
+                }
+                SlowPathInstruction::ExportState => {
+                    instructions::export_state::process(program_id, accounts)
+                }
+                SlowPathInstruction::SetWriteHook {
+                    notify_on_authority_write,
+                } => instructions::set_write_hook::process(
+                    program_id,
+                    accounts,
+                    notify_on_authority_write,
+                ),
+                SlowPathInstruction::SetOracleMetadata {
+                    oracle_metadata,
+                    auxiliary_metadata,
+                } => instructions::set_oracle_metadata::process(
+                    program_id,
+                    accounts,
+                    oracle_metadata,
+                    auxiliary_metadata,
+                ),
+                SlowPathInstruction::Resize { type_hash, ext_len } => {
+                    instructions::resize::process(program_id, accounts, type_hash, ext_len)
+                }
+                SlowPathInstruction::MigrateEnvelope => {
+                    instructions::migrate_envelope::process(program_id, accounts)
+                }
+                SlowPathInstruction::UpdateExtAux {
+                    sequence,
+                    offset,
+                    data,
+                } => instructions::update_ext_aux::process(
+                    program_id, accounts, sequence, offset, &data,
+                ),
+                SlowPathInstruction::SetConstraints { bump, constraints } => {
+                    instructions::set_constraints::process(program_id, accounts, bump, &constraints)
+                }
+                SlowPathInstruction::SetPublishers {
+                    bump,
+                    threshold,
+                    publishers,
+                } => instructions::set_publishers::process(
+                    program_id,
+                    accounts,
+                    bump,
+                    threshold,
+                    &publishers,
+                ),
+                SlowPathInstruction::SetDelegatedPda {
+                    program_id: delegate_program,
+                    seeds,
+                    bump,
+                    program_bitmask,
+                    user_bitmask,
+                } => instructions::set_delegated_program::process_pda(
+                    program_id,
+                    accounts,
+                    &Address::new_from_array(delegate_program),
+                    &seeds,
+                    bump,
+                    &Mask::from(program_bitmask),
+                    &Mask::from(user_bitmask),
+                ),
+                SlowPathInstruction::CloseWithSplit { treasury_bps } => {
+                    instructions::close::process_with_split(program_id, accounts, treasury_bps)
+                }
+                SlowPathInstruction::SetConflation { enabled } => {
+                    instructions::set_conflation::process(program_id, accounts, enabled)
+                }
+                SlowPathInstruction::SetReaders { bump, readers } => {
+                    instructions::set_readers::process(program_id, accounts, bump, &readers)
+                }
+                SlowPathInstruction::ReadOracleGated => {
+                    instructions::read_oracle_gated::process(program_id, accounts)
+                }
+                SlowPathInstruction::Aggregate {
+                    sequence,
+                    min_sources,
+                    max_deviation_bps,
+                } => instructions::aggregate::process(
+                    program_id,
+                    accounts,
+                    sequence,
+                    min_sources,
+                    max_deviation_bps,
+                ),
+                SlowPathInstruction::SetPublisher { publisher } => {
+                    instructions::set_publisher::process(program_id, accounts, publisher)
+                }
+                SlowPathInstruction::SetPaused { paused } => {
+                    instructions::set_paused::process(program_id, accounts, paused)
+                }
+                SlowPathInstruction::SealAuxiliaryRange { offset, len } => {
+                    instructions::seal_auxiliary_range::process(program_id, accounts, offset, len)
+                }
+                SlowPathInstruction::InitConfig {
+                    bump,
+                    admin,
+                    create_fee,
+                } => instructions::init_config::process(
+                    program_id, accounts, bump, &admin, create_fee,
+                ),
+                SlowPathInstruction::SetConfig {
+                    admin,
+                    create_fee,
+                    paused,
+                } => instructions::set_config::process(
+                    program_id, accounts, &admin, create_fee, paused,
+                ),
+                SlowPathInstruction::Heartbeat { sequence } => {
+                    instructions::heartbeat::process(program_id, accounts, sequence)
+                }
+                SlowPathInstruction::TransferDelegation {
+                    program_bitmask,
+                    user_bitmask,
+                } => instructions::transfer_delegation::process(
+                    program_id,
+                    accounts,
+                    &Mask::from(program_bitmask),
+                    &Mask::from(user_bitmask),
+                ),
+                SlowPathInstruction::CreateWithEvents {
+                    custom_seeds,
+                    bump,
+                    oracle_metadata,
+                } => instructions::create::process(
+                    program_id,
+                    accounts,
+                    custom_seeds,
+                    bump,
+                    oracle_metadata,
+                    true,
+                ),
             }
         }
     }
@@ -0,0 +1,140 @@
+use pinocchio::{error::ProgramError, Address};
+
+/// Address of the instructions sysvar (`Sysvar1nstructions1111111111111111111111111`).
+pub const INSTRUCTIONS_SYSVAR_ID: Address = Address::new_from_array([
+    0x06, 0xa7, 0xd5, 0x17, 0x18, 0x7b, 0xd1, 0x66, 0x35, 0xda, 0xd4, 0x04, 0x55, 0xfd, 0xc2, 0xc0,
+    0xc1, 0x24, 0xc6, 0x8f, 0x21, 0x56, 0x75, 0xa5, 0xdb, 0xba, 0xcb, 0x5f, 0x08, 0x00, 0x00, 0x00,
+]);
+
+/// Serialized size of one account entry in the instructions sysvar: `[flags:1][pubkey:32]`.
+const ACCOUNT_META_SIZE: usize = 1 + 32;
+
+#[inline(always)]
+fn read_u16(data: &[u8], at: usize) -> Result<usize, ProgramError> {
+    data.get(at..at + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+        .ok_or(ProgramError::InvalidAccountData)
+}
+
+/// Offset of top-level instruction `index` in the sysvar data.
+#[inline(always)]
+fn instruction_start(data: &[u8], index: usize) -> Result<usize, ProgramError> {
+    read_u16(data, 2 + index * 2)
+}
+
+/// Returns `true` if any top-level instruction in the transaction targets `program_id`.
+///
+/// `data` is the raw instructions sysvar account data:
+/// `[num_ixs:2][offset:2 * num_ixs]`, then per instruction
+/// `[num_accounts:2][(flags:1, pubkey:32) * num_accounts][program_id:32][data_len:2][data]`.
+///
+/// Returns [`ProgramError::InvalidAccountData`] if `data` is truncated.
+pub fn transaction_has_program(data: &[u8], program_id: &Address) -> Result<bool, ProgramError> {
+    let num_instructions = read_u16(data, 0)?;
+    for index in 0..num_instructions {
+        let start = instruction_start(data, index)?;
+        let num_accounts = read_u16(data, start)?;
+        let id_start = start + 2 + num_accounts * ACCOUNT_META_SIZE;
+        let id = data
+            .get(id_start..id_start + 32)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if id == program_id.as_ref() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Program id of the top-level instruction currently executing.
+///
+/// Same sysvar layout as [`transaction_has_program`], followed by the index of the current
+/// instruction in the last 2 bytes. Under CPI this is the program at the top of the call
+/// stack, not necessarily the immediate caller.
+///
+/// Returns [`ProgramError::InvalidAccountData`] if `data` is truncated or the index is out
+/// of range.
+pub fn current_top_level_program(data: &[u8]) -> Result<&[u8], ProgramError> {
+    let (current, _) = current_index(data)?;
+    Ok(instruction_at(data, current)?.program_id)
+}
+
+/// Index of the top-level instruction currently executing, and the number of top-level
+/// instructions in the transaction.
+///
+/// Returns [`ProgramError::InvalidAccountData`] if `data` is truncated or the index is out
+/// of range.
+pub fn current_index(data: &[u8]) -> Result<(usize, usize), ProgramError> {
+    let num_instructions = read_u16(data, 0)?;
+    let current = read_u16(data, data.len().saturating_sub(2))?;
+    if current >= num_instructions {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok((current, num_instructions))
+}
+
+/// One top-level instruction as serialized in the instructions sysvar.
+pub struct TopLevelInstruction<'a> {
+    /// `[flags:1][pubkey:32]` per account.
+    metas: &'a [u8],
+    pub program_id: &'a [u8],
+    pub data: &'a [u8],
+}
+
+impl<'a> TopLevelInstruction<'a> {
+    /// Address of account `index`, or `None` if the instruction has fewer accounts.
+    pub fn account(&self, index: usize) -> Option<&'a [u8]> {
+        let start = index * ACCOUNT_META_SIZE + 1;
+        self.metas.get(start..start + 32)
+    }
+}
+
+/// Top-level instruction `index`, in the layout of [`transaction_has_program`].
+///
+/// Returns [`ProgramError::InvalidAccountData`] if `data` is truncated. The caller bounds
+/// `index` by the instruction count (see [`current_index`]).
+pub fn instruction_at(data: &[u8], index: usize) -> Result<TopLevelInstruction<'_>, ProgramError> {
+    let start = instruction_start(data, index)?;
+    let num_accounts = read_u16(data, start)?;
+    let id_start = start + 2 + num_accounts * ACCOUNT_META_SIZE;
+    let metas = data
+        .get(start + 2..id_start)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    let program_id = data
+        .get(id_start..id_start + 32)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    let data_len = read_u16(data, id_start + 32)?;
+    let data = data
+        .get(id_start + 34..id_start + 34 + data_len)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    Ok(TopLevelInstruction {
+        metas,
+        program_id,
+        data,
+    })
+}
+
+/// Account meta flag bit marking a writable account.
+const IS_WRITABLE: u8 = 1 << 1;
+
+/// Number of top-level instructions in the transaction that take `address` writable.
+///
+/// Same sysvar layout as [`transaction_has_program`]. An instruction listing `address`
+/// several times counts once.
+pub fn writable_instruction_count(data: &[u8], address: &Address) -> Result<usize, ProgramError> {
+    let num_instructions = read_u16(data, 0)?;
+    let mut count = 0;
+    for index in 0..num_instructions {
+        let start = instruction_start(data, index)?;
+        let num_accounts = read_u16(data, start)?;
+        let metas = data
+            .get(start + 2..start + 2 + num_accounts * ACCOUNT_META_SIZE)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if metas
+            .chunks_exact(ACCOUNT_META_SIZE)
+            .any(|meta| meta[0] & IS_WRITABLE != 0 && &meta[1..] == address.as_ref())
+        {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
@@ -0,0 +1,181 @@
+use bytemuck::bytes_of;
+use c_u_soon::{PriceValue, TypeHash, FLAG_TWAP};
+use c_u_soon_client::aggregate_instruction_data;
+use c_u_soon_instruction::{METADATA_HASH_MISMATCH_ERROR, TOO_FEW_SOURCES_ERROR};
+use c_u_soon_test_utils::{fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+struct Feed {
+    runner: Runner,
+    authority: Address,
+    aggregate: Address,
+    sources: Vec<Address>,
+}
+
+fn price(mantissa: i64) -> PriceValue {
+    PriceValue::new(mantissa, -2, mantissa as u64 / 100)
+}
+
+/// A `PriceValue` aggregate envelope at sequence 1 and one source envelope per mantissa,
+/// each owned by a different publisher.
+fn feed(mantissas: &[i64]) -> Feed {
+    let mut runner = Runner::new();
+    let authority = fixture_address("aggregate/authority");
+    let aggregate = fixture_address("aggregate/envelope");
+    runner.fund(authority, 1_000_000_000).set_account(
+        aggregate,
+        EnvelopeFixture::new(authority)
+            .oracle_metadata(PriceValue::METADATA)
+            .sequence(1)
+            .account(),
+    );
+    let sources = mantissas
+        .iter()
+        .enumerate()
+        .map(|(i, &mantissa)| {
+            let source = fixture_address(&format!("aggregate/source/{i}"));
+            let publisher = fixture_address(&format!("aggregate/publisher/{i}"));
+            runner.set_account(
+                source,
+                EnvelopeFixture::new(publisher)
+                    .oracle(PriceValue::METADATA, bytes_of(&price(mantissa)))
+                    .sequence(10 + i as u64)
+                    .account(),
+            );
+            source
+        })
+        .collect();
+    Feed {
+        runner,
+        authority,
+        aggregate,
+        sources,
+    }
+}
+
+impl Feed {
+    fn aggregate(&self, sequence: u64, min_sources: u8, max_deviation_bps: u16) -> Instruction {
+        self.aggregate_from(&self.sources, sequence, min_sources, max_deviation_bps)
+    }
+
+    fn aggregate_from(
+        &self,
+        sources: &[Address],
+        sequence: u64,
+        min_sources: u8,
+        max_deviation_bps: u16,
+    ) -> Instruction {
+        let mut metas = vec![
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new(self.aggregate, false),
+        ];
+        metas.extend(sources.iter().map(|s| AccountMeta::new_readonly(*s, false)));
+        Instruction::new_with_bytes(
+            DEFAULT_PROGRAM_ID,
+            &aggregate_instruction_data(sequence, min_sources, max_deviation_bps).unwrap(),
+            metas,
+        )
+    }
+
+    fn result(&self) -> (u64, PriceValue) {
+        let envelope = self.runner.envelope(&self.aggregate);
+        (
+            envelope.oracle_state.sequence,
+            *envelope.oracle::<PriceValue>().unwrap(),
+        )
+    }
+}
+
+#[test]
+fn test_aggregate_writes_median_source() {
+    let mut feed = feed(&[10_100, 9_900, 10_000]);
+    feed.runner.expect_ok(&feed.aggregate(2, 3, 500));
+    assert_eq!(feed.result(), (2, price(10_000)));
+}
+
+#[test]
+fn test_aggregate_drops_outliers() {
+    // 100 bps of 10_000 keeps 9_900..=10_100; the faulty 1 and 50_000 are dropped, and the
+    // lower median of the remaining four is 10_000.
+    let mut feed = feed(&[10_050, 1, 10_000, 50_000, 9_950, 10_100]);
+    feed.runner.expect_ok(&feed.aggregate(2, 4, 100));
+    assert_eq!(feed.result(), (2, price(10_000)));
+
+    feed.runner.expect_err(
+        &feed.aggregate(3, 5, 100),
+        ProgramError::Custom(TOO_FEW_SOURCES_ERROR),
+    );
+    assert_eq!(feed.result().0, 2);
+}
+
+#[test]
+fn test_aggregate_updates_trailers() {
+    let mut feed = feed(&[300, 100, 200]);
+    let account = EnvelopeFixture::new(feed.authority)
+        .oracle_metadata(PriceValue::METADATA)
+        .flags(FLAG_TWAP)
+        .account();
+    feed.runner.set_account(feed.aggregate, account);
+    feed.runner.mollusk_mut().warp_to_slot(7);
+
+    feed.runner.expect_ok(&feed.aggregate(1, 1, 10_000));
+    let twap = feed.runner.envelope(&feed.aggregate).twap().unwrap();
+    assert_eq!(twap.last_value.get(), 200);
+}
+
+#[test]
+fn test_aggregate_rejects_stale_sequence() {
+    let mut feed = feed(&[100, 100]);
+    feed.runner.expect_err(
+        &feed.aggregate(1, 1, 0),
+        ProgramError::InvalidInstructionData,
+    );
+}
+
+#[test]
+fn test_aggregate_rejects_wrong_authority() {
+    let mut feed = feed(&[100, 100]);
+    feed.authority = fixture_address("aggregate/attacker");
+    feed.runner.fund(feed.authority, 1_000_000_000);
+    feed.runner
+        .expect_err(&feed.aggregate(2, 1, 0), ProgramError::IncorrectAuthority);
+}
+
+#[test]
+fn test_aggregate_rejects_repeated_source() {
+    let mut feed = feed(&[100, 500]);
+    let repeated = [feed.sources[0], feed.sources[1], feed.sources[0]];
+    feed.runner.expect_err(
+        &feed.aggregate_from(&repeated, 2, 1, 0),
+        ProgramError::InvalidArgument,
+    );
+    let own = [feed.sources[0], feed.aggregate];
+    feed.runner.expect_err(
+        &feed.aggregate_from(&own, 2, 1, 0),
+        ProgramError::InvalidArgument,
+    );
+}
+
+#[test]
+fn test_aggregate_rejects_foreign_sources() {
+    let mut feed = feed(&[100, 100]);
+    let source = feed.sources[1];
+
+    let spoofed = EnvelopeFixture::new(fixture_address("aggregate/publisher/1"))
+        .oracle(PriceValue::METADATA, bytes_of(&price(100)))
+        .owner(Address::new_from_array([0xAB; 32]))
+        .account();
+    feed.runner.set_account(source, spoofed);
+    feed.runner
+        .expect_err(&feed.aggregate(2, 1, 0), ProgramError::IncorrectProgramId);
+
+    let other_type = EnvelopeFixture::new(fixture_address("aggregate/publisher/1"))
+        .oracle(<[u8; 20]>::METADATA, &[0; 20])
+        .account();
+    feed.runner.set_account(source, other_type);
+    feed.runner.expect_err(
+        &feed.aggregate(2, 1, 0),
+        ProgramError::Custom(METADATA_HASH_MISMATCH_ERROR),
+    );
+}
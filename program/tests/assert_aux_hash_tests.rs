@@ -0,0 +1,114 @@
+mod common;
+
+use c_u_soon::{Envelope, Mask};
+use c_u_soon_client::{
+    assert_aux_hash_instruction_data, aux_hash, update_auxiliary_delegated_instruction_data,
+};
+use common::{
+    create_delegated_envelope, create_existing_envelope, create_funded_account, new_mollusk,
+    new_mollusk_silent, PROGRAM_ID, PROGRAM_PATH, TEST_META_U64, TEST_TYPE_SIZE,
+};
+use mollusk_svm::result::Check;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+};
+
+fn assert_instruction(envelope: Address, expected: [u8; 32]) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &assert_aux_hash_instruction_data(expected).unwrap(),
+        vec![AccountMeta::new_readonly(envelope, false)],
+    )
+}
+
+fn hash_of(account: &Account) -> [u8; 32] {
+    aux_hash(bytemuck::from_bytes::<Envelope>(&account.data))
+}
+
+#[test]
+fn test_assert_aux_hash_matching() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let envelope_pubkey = Address::new_unique();
+    let mut account = create_existing_envelope(&Address::new_unique(), 0);
+    bytemuck::from_bytes_mut::<Envelope>(&mut account.data).auxiliary_data[40] = 0x5A;
+
+    let result = mollusk.process_and_validate_instruction(
+        &assert_instruction(envelope_pubkey, hash_of(&account)),
+        &[(envelope_pubkey, account.clone())],
+        &[Check::success()],
+    );
+    assert_eq!(result.resulting_accounts[0].1.data, account.data);
+}
+
+#[test]
+fn test_assert_aux_hash_mismatch() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let envelope_pubkey = Address::new_unique();
+    let account = create_existing_envelope(&Address::new_unique(), 0);
+    let mut expected = hash_of(&account);
+    expected[0] ^= 1;
+
+    mollusk.process_and_validate_instruction(
+        &assert_instruction(envelope_pubkey, expected),
+        &[(envelope_pubkey, account)],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_assert_aux_hash_detects_earlier_write_in_chain() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let delegation_auth = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let padding = Address::new_unique();
+    let account = create_delegated_envelope(
+        &Address::new_unique(),
+        &delegation_auth,
+        Mask::ALL_WRITABLE,
+        Mask::ALL_BLOCKED,
+    );
+    let expected = hash_of(&account);
+
+    // A write lands between reading the state and the delegate's assertion.
+    let write = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &update_auxiliary_delegated_instruction_data(TEST_META_U64, 1, &[0xEE; TEST_TYPE_SIZE]),
+        vec![
+            AccountMeta::new_readonly(delegation_auth, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(padding, false),
+        ],
+    );
+    let written = mollusk.process_and_validate_instruction(
+        &write,
+        &[
+            (delegation_auth, create_funded_account(0)),
+            (envelope_pubkey, account),
+            (padding, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &assert_instruction(envelope_pubkey, expected),
+        &[written.resulting_accounts[1].clone()],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_assert_aux_hash_rejects_foreign_account() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let envelope_pubkey = Address::new_unique();
+    let mut account = create_existing_envelope(&Address::new_unique(), 0);
+    let expected = hash_of(&account);
+    account.owner = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &assert_instruction(envelope_pubkey, expected),
+        &[(envelope_pubkey, account)],
+        &[Check::err(ProgramError::IncorrectProgramId)],
+    );
+}
@@ -1,17 +1,22 @@
 mod common;
 
-use c_u_soon::{Envelope, Mask, StructMetadata, AUX_DATA_SIZE, ORACLE_BYTES};
+use c_u_soon::{
+    Envelope, Mask, StructMetadata, AUX_DATA_SIZE, ORACLE_BYTES, ORACLE_MEMO_SIZE,
+    TINY_PAYLOAD_SIZE,
+};
 use c_u_soon_client::{
     clear_delegation_instruction_data, close_instruction_data, create_instruction_data,
-    fast_path_instruction_data, set_delegated_program_instruction_data,
+    fast_path_instruction_data, fast_path_instruction_data_with_memo,
+    fast_path_tiny_instruction_data, set_delegated_program_instruction_data,
     update_auxiliary_delegated_instruction_data, update_auxiliary_force_instruction_data,
     update_auxiliary_instruction_data, InstructionError,
 };
 use c_u_soon_instruction;
+use c_u_soon_program::fast_path::{COMPUTE_BUDGET, TINY_COMPUTE_BUDGET};
 use common::{
     create_delegated_envelope, create_existing_envelope, create_existing_envelope_with_bump,
-    create_funded_account, find_envelope_pda, new_mollusk, new_mollusk_silent, PROGRAM_ID,
-    PROGRAM_PATH, TEST_META_U64, TEST_TYPE_SIZE,
+    create_funded_account, find_config_pda, find_envelope_pda, new_mollusk, new_mollusk_silent,
+    PROGRAM_ID, PROGRAM_PATH, TEST_META_U64, TEST_TYPE_SIZE,
 };
 use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
 use pinocchio::{error::ProgramError, Address};
@@ -27,11 +32,13 @@ fn test_create_happy_path() {
     let authority = Address::new_unique();
     let custom_seeds: &[&[u8]] = &[b"test"];
     let (envelope_pda, bump) = find_envelope_pda(&authority, custom_seeds);
+    let (config, _) = find_config_pda();
 
     let account_metas = vec![
         AccountMeta::new(authority, true),
         AccountMeta::new(envelope_pda, true),
         AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(config, false),
     ];
 
     let instruction = Instruction::new_with_bytes(
@@ -46,6 +53,7 @@ fn test_create_happy_path() {
             (authority, create_funded_account(1_000_000_000)),
             (envelope_pda, create_funded_account(0)),
             keyed_account_for_system_program(),
+            (config, create_funded_account(0)),
         ],
         &[Check::success()],
     );
@@ -64,11 +72,13 @@ fn test_create_idempotent() {
     let authority = Address::new_unique();
     let custom_seeds: &[&[u8]] = &[b"test"];
     let (envelope_pda, bump) = find_envelope_pda(&authority, custom_seeds);
+    let (config, _) = find_config_pda();
 
     let account_metas = vec![
         AccountMeta::new(authority, true),
         AccountMeta::new(envelope_pda, false),
         AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(config, false),
     ];
 
     let instruction = Instruction::new_with_bytes(
@@ -85,6 +95,7 @@ fn test_create_idempotent() {
             (authority, create_funded_account(1_000_000_000)),
             (envelope_pda, existing),
             keyed_account_for_system_program(),
+            (config, create_funded_account(0)),
         ],
         &[Check::success()],
     );
@@ -102,6 +113,7 @@ fn test_create_idempotent_wrong_metadata() {
     let authority = Address::new_unique();
     let custom_seeds: &[&[u8]] = &[b"test"];
     let (envelope_pda, bump) = find_envelope_pda(&authority, custom_seeds);
+    let (config, _) = find_config_pda();
 
     // Existing envelope has metadata A (ZERO)
     let existing = create_existing_envelope_with_bump(&authority, 5, bump);
@@ -112,6 +124,7 @@ fn test_create_idempotent_wrong_metadata() {
         AccountMeta::new(authority, true),
         AccountMeta::new(envelope_pda, false),
         AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(config, false),
     ];
 
     let instruction = Instruction::new_with_bytes(
@@ -126,8 +139,11 @@ fn test_create_idempotent_wrong_metadata() {
             (authority, create_funded_account(1_000_000_000)),
             (envelope_pda, existing),
             keyed_account_for_system_program(),
+            (config, create_funded_account(0)),
         ],
-        &[Check::err(ProgramError::InvalidInstructionData)],
+        &[Check::err(ProgramError::Custom(
+            c_u_soon_instruction::METADATA_SIZE_MISMATCH_ERROR,
+        ))],
     );
 }
 
@@ -138,6 +154,7 @@ fn test_create_wrong_pda() {
     let authority = Address::new_unique();
     let custom_seeds: &[&[u8]] = &[b"test"];
     let (_, bump) = find_envelope_pda(&authority, custom_seeds);
+    let (config, _) = find_config_pda();
 
     let wrong_pda = Address::new_unique();
 
@@ -145,6 +162,7 @@ fn test_create_wrong_pda() {
         AccountMeta::new(authority, true),
         AccountMeta::new(wrong_pda, true),
         AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(config, false),
     ];
 
     let instruction = Instruction::new_with_bytes(
@@ -159,6 +177,7 @@ fn test_create_wrong_pda() {
             (authority, create_funded_account(1_000_000_000)),
             (wrong_pda, create_funded_account(0)),
             keyed_account_for_system_program(),
+            (config, create_funded_account(0)),
         ],
         &[Check::err(ProgramError::InvalidSeeds)],
     );
@@ -171,11 +190,13 @@ fn test_create_not_signer() {
     let authority = Address::new_unique();
     let custom_seeds: &[&[u8]] = &[b"test"];
     let (envelope_pda, bump) = find_envelope_pda(&authority, custom_seeds);
+    let (config, _) = find_config_pda();
 
     let account_metas = vec![
         AccountMeta::new_readonly(authority, false), // not signer
         AccountMeta::new(envelope_pda, true),
         AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(config, false),
     ];
 
     let instruction = Instruction::new_with_bytes(
@@ -190,6 +211,7 @@ fn test_create_not_signer() {
             (authority, create_funded_account(1_000_000_000)),
             (envelope_pda, create_funded_account(0)),
             keyed_account_for_system_program(),
+            (config, create_funded_account(0)),
         ],
         &[Check::err(ProgramError::MissingRequiredSignature)],
     );
@@ -368,7 +390,7 @@ fn test_fast_path_all_write_sizes() {
                 (authority, create_funded_account(1_000_000_000)),
                 (envelope_pubkey, envelope_account),
             ],
-            &[Check::success(), Check::compute_units(39)],
+            &[Check::success(), Check::compute_units(lane_budget(i))],
         );
 
         let env: &Envelope = bytemuck::from_bytes(
@@ -388,6 +410,134 @@ fn test_fast_path_all_write_sizes() {
     }
 }
 
+/// CU budget of a fast-path write with a `payload_len`-byte payload.
+fn lane_budget(payload_len: usize) -> u64 {
+    if payload_len == TINY_PAYLOAD_SIZE {
+        TINY_COMPUTE_BUDGET
+    } else {
+        COMPUTE_BUDGET
+    }
+}
+
+/// CUs of a fast-path write built by `data(sequence, size)` for each size, in sequence.
+fn fast_path_cu_for_sizes(
+    sizes: impl Iterator<Item = usize>,
+    data: impl Fn(u64, usize) -> Vec<u8>,
+) -> Vec<u64> {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let mut envelope_account = create_existing_envelope(&authority, 0);
+    let mut seq = 0u64;
+
+    sizes
+        .map(|size| {
+            seq += 1;
+            let instruction = Instruction::new_with_bytes(
+                PROGRAM_ID,
+                &data(seq, size),
+                vec![
+                    AccountMeta::new_readonly(authority, true),
+                    AccountMeta::new(envelope_pubkey, false),
+                ],
+            );
+            let result = mollusk.process_and_validate_instruction(
+                &instruction,
+                &[
+                    (authority, create_funded_account(1_000_000_000)),
+                    (envelope_pubkey, envelope_account.clone()),
+                ],
+                &[Check::success()],
+            );
+            envelope_account = result.resulting_accounts[1].1.clone();
+            result.compute_units_consumed
+        })
+        .collect()
+}
+
+#[test]
+fn test_fast_path_tiny_lane_cu_budget() {
+    // Every payload up to the tiny size, padded onto the tiny lane.
+    let cus = fast_path_cu_for_sizes(0..=TINY_PAYLOAD_SIZE, |seq, size| {
+        fast_path_tiny_instruction_data(0, seq, &vec![0x5A; size]).unwrap()
+    });
+    assert!(cus.iter().all(|&cu| cu == TINY_COMPUTE_BUDGET), "{cus:?}");
+    assert!(TINY_COMPUTE_BUDGET < COMPUTE_BUDGET);
+}
+
+#[test]
+fn test_fast_path_full_lane_cu_budget() {
+    let sizes = (0..=ORACLE_BYTES).filter(|&size| size != TINY_PAYLOAD_SIZE);
+    let cus = fast_path_cu_for_sizes(sizes, |seq, size| {
+        fast_path_instruction_data(0, seq, &vec![0x5A; size]).unwrap()
+    });
+    assert!(cus.iter().all(|&cu| cu == COMPUTE_BUDGET), "{cus:?}");
+}
+
+#[test]
+fn test_fast_path_tiny_lane_writes_and_checks() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let run = |data: Vec<u8>, envelope: Account, check: Check| {
+        let instruction = Instruction::new_with_bytes(
+            PROGRAM_ID,
+            &data,
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(envelope_pubkey, false),
+            ],
+        );
+        let result = mollusk.process_and_validate_instruction(
+            &instruction,
+            &[
+                (authority, create_funded_account(1_000_000_000)),
+                (envelope_pubkey, envelope),
+            ],
+            &[check],
+        );
+        result.resulting_accounts[1].1.clone()
+    };
+
+    // Fill the whole oracle region, then write 8 bytes on the tiny lane.
+    let envelope = run(
+        fast_path_instruction_data(0, 1, &[0xEE; ORACLE_BYTES]).unwrap(),
+        create_existing_envelope(&authority, 0),
+        Check::success(),
+    );
+    let meta = StructMetadata::new(8, 0x1234);
+    let envelope = run(
+        fast_path_tiny_instruction_data(meta.as_u64(), 2, &[7; 8]).unwrap(),
+        envelope,
+        Check::success(),
+    );
+    let env: &Envelope = bytemuck::from_bytes(&envelope.data[..core::mem::size_of::<Envelope>()]);
+    assert_eq!(env.oracle_state.oracle_metadata, meta);
+    assert_eq!(env.oracle_state.sequence, 2);
+    assert_eq!(env.oracle_state.data[..8], [7; 8]);
+    assert!(env.oracle_state.data[8..TINY_PAYLOAD_SIZE]
+        .iter()
+        .all(|&b| b == 0));
+    assert!(env.oracle_state.data[TINY_PAYLOAD_SIZE..]
+        .iter()
+        .all(|&b| b == 0xEE));
+
+    // The checks run before the lane is chosen.
+    run(
+        fast_path_tiny_instruction_data(meta.as_u64(), 2, &[8; 8]).unwrap(),
+        envelope.clone(),
+        Check::err(ProgramError::InvalidInstructionData),
+    );
+    run(
+        fast_path_tiny_instruction_data(StructMetadata::new(8, 0x5678).as_u64(), 3, &[8; 8])
+            .unwrap(),
+        envelope,
+        Check::err(ProgramError::Custom(
+            c_u_soon_instruction::METADATA_HASH_MISMATCH_ERROR,
+        )),
+    );
+}
+
 #[test]
 fn test_fast_path_length_modulo_replay() {
     let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
@@ -477,7 +627,10 @@ fn test_fast_path_field_isolation_full_payload() {
             &mut envelope_account.data[..core::mem::size_of::<Envelope>()],
         );
         envelope.bump = 42;
-        envelope._padding = [0x11; 7];
+        envelope.flags = 0x22;
+        envelope.aux_write_count = 0x1234;
+        envelope.aux_dirty_blocks = 0x5A;
+        envelope._padding = [0x11; 3];
         envelope.authority_aux_sequence = 7;
         envelope.program_aux_sequence = 9;
         envelope.auxiliary_data = [0x77; AUX_DATA_SIZE];
@@ -509,7 +662,10 @@ fn test_fast_path_field_isolation_full_payload() {
     assert_eq!(envelope.oracle_state.sequence, 1);
     assert!(envelope.oracle_state.data.iter().all(|&b| b == 0xAB));
     assert_eq!(envelope.bump, 42);
-    assert_eq!(envelope._padding, [0x11; 7]);
+    assert_eq!(envelope.flags, 0x22);
+    assert_eq!(envelope.aux_write_count, 0x1234);
+    assert_eq!(envelope.aux_dirty_blocks, 0x5A);
+    assert_eq!(envelope._padding, [0x11; 3]);
     assert_eq!(envelope.delegation_authority, delegation_auth);
     assert_eq!(envelope.program_bitmask, program_bitmask);
     assert_eq!(envelope.user_bitmask, user_bitmask);
@@ -559,6 +715,101 @@ fn test_fast_path_rejects_wrong_oracle_metadata() {
     );
 }
 
+#[test]
+fn test_fast_path_distinguishes_metadata_size_and_hash() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let stored = StructMetadata::new(8, 0x1234);
+    let mut envelope = create_existing_envelope(&authority, 0);
+    bytemuck::from_bytes_mut::<Envelope>(&mut envelope.data[..core::mem::size_of::<Envelope>()])
+        .oracle_state
+        .oracle_metadata = stored;
+
+    for (given, code) in [
+        (
+            StructMetadata::new(4, 0x1234),
+            c_u_soon_instruction::METADATA_SIZE_MISMATCH_ERROR,
+        ),
+        (
+            StructMetadata::new(8, 0x5678),
+            c_u_soon_instruction::METADATA_HASH_MISMATCH_ERROR,
+        ),
+    ] {
+        let instruction = Instruction::new_with_bytes(
+            PROGRAM_ID,
+            &fast_path_instruction_data(given.as_u64(), 1, &[1]).unwrap(),
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(envelope_pubkey, false),
+            ],
+        );
+        mollusk.process_and_validate_instruction(
+            &instruction,
+            &[
+                (authority, create_funded_account(1_000_000_000)),
+                (envelope_pubkey, envelope.clone()),
+            ],
+            &[Check::err(ProgramError::Custom(code))],
+        );
+    }
+}
+
+#[test]
+fn test_fast_path_memo_bound_to_update() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let memo = [0xC3u8; ORACLE_MEMO_SIZE];
+
+    let with_memo = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &fast_path_instruction_data_with_memo(0, 1, &[42], &memo).unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+        ],
+    );
+
+    let result = mollusk.process_and_validate_instruction(
+        &with_memo,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+        ],
+        &[Check::success()],
+    );
+
+    let envelope: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert_eq!(envelope.oracle_state.data[0], 42);
+    assert_eq!(envelope.oracle_memo(), Some(&memo));
+
+    // A plain update afterwards must not inherit the previous memo.
+    let plain = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &fast_path_instruction_data(0, 2, &[43]).unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+        ],
+    );
+
+    let result = mollusk.process_and_validate_instruction(
+        &plain,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, result.resulting_accounts[1].1.clone()),
+        ],
+        &[Check::success()],
+    );
+
+    let envelope: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert_eq!(envelope.oracle_state.data[0], 43);
+    assert_eq!(envelope.oracle_memo(), None);
+}
+
 // -- Slow path: Close --
 
 #[test]
@@ -1845,6 +2096,7 @@ fn test_close_reopen_resets_state() {
     let authority = Address::new_unique();
     let custom_seeds: &[&[u8]] = &[b"reopen"];
     let (envelope_pda, bump) = find_envelope_pda(&authority, custom_seeds);
+    let (config, _) = find_config_pda();
     let recipient = Address::new_unique();
 
     // Step 1: Create with oracle sequence advanced
@@ -1855,6 +2107,7 @@ fn test_close_reopen_resets_state() {
             AccountMeta::new(authority, true),
             AccountMeta::new(envelope_pda, true),
             AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(config, false),
         ],
     );
 
@@ -1864,6 +2117,7 @@ fn test_close_reopen_resets_state() {
             (authority, create_funded_account(1_000_000_000)),
             (envelope_pda, create_funded_account(0)),
             keyed_account_for_system_program(),
+            (config, create_funded_account(0)),
         ],
         &[Check::success()],
     );
@@ -1950,6 +2204,7 @@ fn test_create_rejects_nonzero_data_len() {
     let authority = Address::new_unique();
     let custom_seeds: &[&[u8]] = &[b"grief"];
     let (envelope_pda, bump) = find_envelope_pda(&authority, custom_seeds);
+    let (config, _) = find_config_pda();
 
     // System-owned account with non-zero data (griefing scenario)
     let griefed_account = solana_sdk::account::Account {
@@ -1967,6 +2222,7 @@ fn test_create_rejects_nonzero_data_len() {
             AccountMeta::new(authority, true),
             AccountMeta::new(envelope_pda, true),
             AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(config, false),
         ],
     );
 
@@ -1976,6 +2232,7 @@ fn test_create_rejects_nonzero_data_len() {
             (authority, create_funded_account(1_000_000_000)),
             (envelope_pda, griefed_account),
             keyed_account_for_system_program(),
+            (config, create_funded_account(0)),
         ],
         &[Check::err(ProgramError::InvalidAccountData)],
     );
@@ -0,0 +1,205 @@
+mod common;
+
+use c_u_soon::Envelope;
+use c_u_soon_client::batch_update_instruction_data;
+use c_u_soon_instruction::{BatchEntry, BATCH_UPDATE_TAG, METADATA_HASH_MISMATCH_ERROR};
+use common::{
+    create_existing_envelope, create_funded_account, new_mollusk, new_mollusk_silent, PROGRAM_ID,
+    PROGRAM_PATH,
+};
+use mollusk_svm::result::Check;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn batch_instruction(authority: &Address, envelopes: &[Address], data: Vec<u8>) -> Instruction {
+    let mut metas = vec![AccountMeta::new_readonly(*authority, true)];
+    metas.extend(envelopes.iter().map(|e| AccountMeta::new(*e, false)));
+    Instruction::new_with_bytes(PROGRAM_ID, &data, metas)
+}
+
+fn entry(sequence: u64, payload: &[u8]) -> BatchEntry<'_> {
+    BatchEntry {
+        oracle_meta: 0,
+        sequence,
+        payload,
+    }
+}
+
+/// Authority account followed by `count` envelopes at sequence `seq`.
+fn setup(count: usize, seq: u64) -> (Address, Vec<Address>, Vec<(Address, Account)>) {
+    let authority = Address::new_unique();
+    let envelopes: Vec<Address> = (0..count).map(|_| Address::new_unique()).collect();
+    let mut accounts = vec![(authority, create_funded_account(1_000_000_000))];
+    accounts.extend(
+        envelopes
+            .iter()
+            .map(|e| (*e, create_existing_envelope(&authority, seq))),
+    );
+    (authority, envelopes, accounts)
+}
+
+fn oracle(account: &Account) -> &Envelope {
+    bytemuck::from_bytes(&account.data)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn test_batch_update_writes_every_envelope() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let (authority, envelopes, accounts) = setup(3, 5);
+    let data = batch_update_instruction_data(&[
+        entry(6, &[0x11; 8]),
+        entry(100, &[0x22; 16]),
+        entry(7, &[]),
+    ])
+    .unwrap();
+
+    let result = mollusk.process_and_validate_instruction(
+        &batch_instruction(&authority, &envelopes, data),
+        &accounts,
+        &[Check::success()],
+    );
+
+    let first = oracle(&result.resulting_accounts[1].1);
+    assert_eq!(first.oracle_state.sequence, 6);
+    assert_eq!(first.oracle_state.data[..8], [0x11; 8]);
+    assert_eq!(first.oracle_state.data[8], 0);
+
+    let second = oracle(&result.resulting_accounts[2].1);
+    assert_eq!(second.oracle_state.sequence, 100);
+    assert_eq!(second.oracle_state.data[..16], [0x22; 16]);
+
+    let third = oracle(&result.resulting_accounts[3].1);
+    assert_eq!(third.oracle_state.sequence, 7);
+    assert_eq!(third.oracle_state.data, [0; c_u_soon::ORACLE_BYTES]);
+}
+
+#[test]
+fn test_batch_update_stale_entry_fails_whole_batch() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let (authority, envelopes, accounts) = setup(2, 5);
+    let data = batch_update_instruction_data(&[entry(6, &[1]), entry(5, &[2])]).unwrap();
+
+    mollusk.process_and_validate_instruction(
+        &batch_instruction(&authority, &envelopes, data),
+        &accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_batch_update_rejects_metadata_mismatch() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let (authority, envelopes, accounts) = setup(2, 0);
+    let mut bad = entry(1, &[]);
+    bad.oracle_meta = 0xDEAD;
+    let data = batch_update_instruction_data(&[entry(1, &[]), bad]).unwrap();
+
+    // Same (zero) type size as the stored metadata, different hash.
+    mollusk.process_and_validate_instruction(
+        &batch_instruction(&authority, &envelopes, data),
+        &accounts,
+        &[Check::err(ProgramError::Custom(
+            METADATA_HASH_MISMATCH_ERROR,
+        ))],
+    );
+}
+
+#[test]
+fn test_batch_update_rejects_foreign_authority() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let (authority, envelopes, mut accounts) = setup(2, 0);
+    accounts[2].1 = create_existing_envelope(&Address::new_unique(), 0);
+    let data = batch_update_instruction_data(&[entry(1, &[]), entry(1, &[])]).unwrap();
+
+    mollusk.process_and_validate_instruction(
+        &batch_instruction(&authority, &envelopes, data),
+        &accounts,
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_batch_update_requires_signer() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let (authority, envelopes, accounts) = setup(2, 0);
+    let data = batch_update_instruction_data(&[entry(1, &[]), entry(1, &[])]).unwrap();
+    let mut ix = batch_instruction(&authority, &envelopes, data);
+    ix.accounts[0].is_signer = false;
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_batch_update_rejects_count_mismatch() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let (authority, envelopes, accounts) = setup(3, 0);
+    let data = batch_update_instruction_data(&[entry(1, &[]), entry(1, &[])]).unwrap();
+
+    mollusk.process_and_validate_instruction(
+        &batch_instruction(&authority, &envelopes, data),
+        &accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_batch_update_rejects_truncated_and_trailing_data() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let (authority, envelopes, accounts) = setup(2, 0);
+    let data = batch_update_instruction_data(&[entry(1, &[7; 4]), entry(1, &[7; 4])]).unwrap();
+
+    let mut truncated = data.clone();
+    truncated.pop();
+    let mut trailing = data;
+    trailing.push(0);
+    for data in [truncated, trailing] {
+        mollusk.process_and_validate_instruction(
+            &batch_instruction(&authority, &envelopes, data),
+            &accounts,
+            &[Check::err(ProgramError::InvalidInstructionData)],
+        );
+    }
+
+    // Header only.
+    let mut header = BATCH_UPDATE_TAG.to_le_bytes().to_vec();
+    mollusk.process_and_validate_instruction(
+        &batch_instruction(&authority, &envelopes, header.clone()),
+        &accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+    header.push(2);
+    mollusk.process_and_validate_instruction(
+        &batch_instruction(&authority, &envelopes, header),
+        &accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_batch_update_rejects_oversized_payload() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let (authority, envelopes, accounts) = setup(2, 0);
+    let mut data = batch_update_instruction_data(&[entry(1, &[]), entry(1, &[])]).unwrap();
+    // Claim a 240-byte payload for the last entry and supply it.
+    *data.last_mut().unwrap() = 240;
+    data.extend_from_slice(&[0; 240]);
+
+    mollusk.process_and_validate_instruction(
+        &batch_instruction(&authority, &envelopes, data),
+        &accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
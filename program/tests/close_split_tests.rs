@@ -0,0 +1,251 @@
+mod common;
+
+use c_u_soon::Mask;
+use c_u_soon_client::close_with_split_instruction_data;
+use c_u_soon_instruction::{SlowPathInstruction, MAX_TREASURY_BPS};
+use common::{
+    create_delegated_envelope, create_existing_envelope, create_funded_account, new_mollusk,
+    PROGRAM_ID, PROGRAM_PATH,
+};
+use mollusk_svm::result::Check;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+fn close_with_split_instruction(
+    data: &[u8],
+    authority: Address,
+    envelope: Address,
+    recipient: Address,
+    treasury: Address,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        data,
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope, false),
+            AccountMeta::new(recipient, false),
+            AccountMeta::new(treasury, false),
+        ],
+    )
+}
+
+#[test]
+fn test_close_with_split_divides_lamports() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let recipient = Address::new_unique();
+    let treasury = Address::new_unique();
+
+    let envelope = create_existing_envelope(&authority, 0);
+    let envelope_lamports = envelope.lamports;
+    let treasury_share = envelope_lamports * 250 / 10_000;
+
+    let result = mollusk.process_and_validate_instruction(
+        &close_with_split_instruction(
+            &close_with_split_instruction_data(250).unwrap(),
+            authority,
+            envelope_pubkey,
+            recipient,
+            treasury,
+        ),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            (recipient, create_funded_account(7)),
+            (treasury, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+
+    let closed = &result.resulting_accounts[1].1;
+    assert_eq!(closed.lamports, 0);
+    assert_eq!(closed.data.len(), 0);
+    assert_eq!(closed.owner, pinocchio_system::ID);
+    assert_eq!(
+        result.resulting_accounts[2].1.lamports,
+        7 + envelope_lamports - treasury_share
+    );
+    assert_eq!(result.resulting_accounts[3].1.lamports, treasury_share);
+}
+
+#[test]
+fn test_close_with_split_extremes() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let recipient = Address::new_unique();
+    let treasury = Address::new_unique();
+
+    for (bps, treasury_gets_all) in [(0, false), (MAX_TREASURY_BPS, true)] {
+        let envelope = create_existing_envelope(&authority, 0);
+        let envelope_lamports = envelope.lamports;
+
+        let result = mollusk.process_and_validate_instruction(
+            &close_with_split_instruction(
+                &close_with_split_instruction_data(bps).unwrap(),
+                authority,
+                envelope_pubkey,
+                recipient,
+                treasury,
+            ),
+            &[
+                (authority, create_funded_account(1_000_000_000)),
+                (envelope_pubkey, envelope),
+                (recipient, create_funded_account(0)),
+                (treasury, create_funded_account(0)),
+            ],
+            &[Check::success()],
+        );
+
+        let (recipient_lamports, treasury_lamports) = if treasury_gets_all {
+            (0, envelope_lamports)
+        } else {
+            (envelope_lamports, 0)
+        };
+        assert_eq!(result.resulting_accounts[2].1.lamports, recipient_lamports);
+        assert_eq!(result.resulting_accounts[3].1.lamports, treasury_lamports);
+    }
+}
+
+#[test]
+fn test_close_with_split_same_recipient_and_treasury() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let payee = Address::new_unique();
+
+    let envelope = create_existing_envelope(&authority, 0);
+    let envelope_lamports = envelope.lamports;
+
+    let result = mollusk.process_and_validate_instruction(
+        &close_with_split_instruction(
+            &close_with_split_instruction_data(3_333).unwrap(),
+            authority,
+            envelope_pubkey,
+            payee,
+            payee,
+        ),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            (payee, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+
+    assert_eq!(result.resulting_accounts[2].1.lamports, envelope_lamports);
+}
+
+#[test]
+fn test_close_with_split_rejects_bps_over_max() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let recipient = Address::new_unique();
+    let treasury = Address::new_unique();
+
+    // The client builder refuses this, so serialize the variant directly.
+    let data = wincode::serialize(&SlowPathInstruction::CloseWithSplit {
+        treasury_bps: MAX_TREASURY_BPS + 1,
+    })
+    .unwrap();
+
+    mollusk.process_and_validate_instruction(
+        &close_with_split_instruction(&data, authority, envelope_pubkey, recipient, treasury),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (recipient, create_funded_account(0)),
+            (treasury, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_close_with_split_rejects_envelope_as_treasury() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let recipient = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &close_with_split_instruction(
+            &close_with_split_instruction_data(500).unwrap(),
+            authority,
+            envelope_pubkey,
+            recipient,
+            envelope_pubkey,
+        ),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (recipient, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_close_with_split_wrong_authority() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let wrong_authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let recipient = Address::new_unique();
+    let treasury = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &close_with_split_instruction(
+            &close_with_split_instruction_data(500).unwrap(),
+            wrong_authority,
+            envelope_pubkey,
+            recipient,
+            treasury,
+        ),
+        &[
+            (wrong_authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (recipient, create_funded_account(0)),
+            (treasury, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_close_with_split_delegated_rejected() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let recipient = Address::new_unique();
+    let treasury = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &close_with_split_instruction(
+            &close_with_split_instruction_data(500).unwrap(),
+            authority,
+            envelope_pubkey,
+            recipient,
+            treasury,
+        ),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (
+                envelope_pubkey,
+                create_delegated_envelope(
+                    &authority,
+                    &delegation_auth,
+                    Mask::ALL_WRITABLE,
+                    Mask::ALL_BLOCKED,
+                ),
+            ),
+            (recipient, create_funded_account(0)),
+            (treasury, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
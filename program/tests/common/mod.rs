@@ -2,167 +2,296 @@
 
 use bytemuck::{bytes_of, Zeroable};
 use c_u_soon::{
-    Envelope, Mask, OracleState, StructMetadata, AUX_DATA_SIZE, ENVELOPE_SEED, ORACLE_BYTES,
+    Constraint, ConstraintTable, DelegateSchema, GroupAnchor, Mask, PublisherSet, RangeGuard,
+    RangeGuardEntry, ReaderList, StructMetadata, CONSTRAINTS_SEED, DELEGATE_SCHEMA_SEED,
+    GROUP_ANCHOR_SEED, LABEL_SEED, PUBLISHERS_SEED, RANGE_GUARD_SEED, READERS_SEED,
 };
-use mollusk_svm::Mollusk;
 use pinocchio::Address;
-use solana_sdk::account::Account;
-use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use solana_sdk::{account::Account, instruction::Instruction};
 
-static LOG_LOCK: RwLock<()> = RwLock::new(());
+use c_u_soon_test_utils::{funded_account, EnvelopeFixture};
+pub use c_u_soon_test_utils::{new_mollusk, new_mollusk_silent, LogWriteGuard, MolluskGuard};
 
-// Guard that holds a Mollusk and the log lock for its lifetime.
-// Generic over the lock kind so read-lock and write-lock tests share one type.
-pub struct MolluskGuard<G> {
-    pub mollusk: Mollusk,
-    _log: G,
+pub const PROGRAM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../target/deploy/c_u_soon_program"
+);
+
+pub const TEST_TYPE_SIZE: usize = 200;
+pub const TEST_META: StructMetadata = StructMetadata::new(TEST_TYPE_SIZE as u8, 0);
+pub const TEST_META_U64: u64 = TEST_META.as_u64();
+
+pub const PROGRAM_ID: Address = Address::new_from_array([
+    0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+    0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f, 0x20,
+]);
+
+pub fn find_envelope_pda(authority: &Address, custom_seeds: &[&[u8]]) -> (Address, u8) {
+    c_u_soon_test_utils::find_envelope_pda(&PROGRAM_ID, authority, custom_seeds)
 }
 
-impl<G> std::ops::Deref for MolluskGuard<G> {
-    type Target = Mollusk;
-    fn deref(&self) -> &Mollusk {
-        &self.mollusk
-    }
+pub fn find_config_pda() -> (Address, u8) {
+    c_u_soon::derive_config_address(&PROGRAM_ID)
 }
 
-impl<G> std::ops::DerefMut for MolluskGuard<G> {
-    fn deref_mut(&mut self) -> &mut Mollusk {
-        &mut self.mollusk
-    }
+pub fn find_range_guard_pda(envelope: &Address) -> (Address, u8) {
+    Address::find_program_address(&[RANGE_GUARD_SEED, envelope.as_ref()], &PROGRAM_ID)
 }
 
-// Write guard wrapper that restores the log level on drop.
-pub struct LogWriteGuard {
-    _inner: RwLockWriteGuard<'static, ()>,
-    prev_level: log::LevelFilter,
+pub fn find_delegate_schema_pda(envelope: &Address) -> (Address, u8) {
+    Address::find_program_address(&[DELEGATE_SCHEMA_SEED, envelope.as_ref()], &PROGRAM_ID)
 }
 
-impl Drop for LogWriteGuard {
-    fn drop(&mut self) {
-        log::set_max_level(self.prev_level);
-    }
+pub fn find_constraints_pda(envelope: &Address) -> (Address, u8) {
+    Address::find_program_address(&[CONSTRAINTS_SEED, envelope.as_ref()], &PROGRAM_ID)
 }
 
-/// Normal test: acquires read lock, constructs Mollusk, holds lock for test lifetime.
-pub fn new_mollusk(
-    program_id: &Address,
-    program_name: &str,
-) -> MolluskGuard<RwLockReadGuard<'static, ()>> {
-    let _log = LOG_LOCK.read().unwrap_or_else(|e| e.into_inner());
-    let mollusk = Mollusk::new(program_id, program_name);
-    MolluskGuard { mollusk, _log }
-}
-
-/// Log-suppressing test: acquires write lock, sets log level to `level`, constructs Mollusk.
-/// Previous log level is restored automatically when the guard drops.
-pub fn new_mollusk_silent(
-    program_id: &Address,
-    program_name: &str,
-    level: log::LevelFilter,
-) -> MolluskGuard<LogWriteGuard> {
-    let _inner = LOG_LOCK.write().unwrap_or_else(|e| e.into_inner());
-    // Mollusk::new calls setup_with_default() which resets the log level, so
-    // capture prev_level and set our desired level only after construction.
-    let mollusk = Mollusk::new(program_id, program_name);
-    let prev_level = log::max_level();
-    log::set_max_level(level);
-    MolluskGuard {
-        mollusk,
-        _log: LogWriteGuard { _inner, prev_level },
-    }
+pub fn find_publishers_pda(envelope: &Address) -> (Address, u8) {
+    Address::find_program_address(&[PUBLISHERS_SEED, envelope.as_ref()], &PROGRAM_ID)
 }
 
-pub const PROGRAM_PATH: &str = concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/../target/deploy/c_u_soon_program"
-);
+pub fn find_readers_pda(envelope: &Address) -> (Address, u8) {
+    Address::find_program_address(&[READERS_SEED, envelope.as_ref()], &PROGRAM_ID)
+}
 
-pub const TEST_TYPE_SIZE: usize = 200;
-pub const TEST_META: StructMetadata = StructMetadata::new(TEST_TYPE_SIZE as u8, 0);
-pub const TEST_META_U64: u64 = TEST_META.as_u64();
+pub fn find_label_pda(envelope: &Address) -> (Address, u8) {
+    Address::find_program_address(&[LABEL_SEED, envelope.as_ref()], &PROGRAM_ID)
+}
 
-pub const PROGRAM_ID: Address = Address::new_from_array([
-    0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
-    0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f, 0x20,
+pub fn find_group_anchor_pda(authority: &Address, group_id: u64) -> (Address, u8) {
+    Address::find_program_address(
+        &[
+            GROUP_ANCHOR_SEED,
+            authority.as_ref(),
+            &group_id.to_le_bytes(),
+        ],
+        &PROGRAM_ID,
+    )
+}
+
+pub const INSTRUCTIONS_SYSVAR_ID: Address = Address::new_from_array([
+    0x06, 0xa7, 0xd5, 0x17, 0x18, 0x7b, 0xd1, 0x66, 0x35, 0xda, 0xd4, 0x04, 0x55, 0xfd, 0xc2, 0xc0,
+    0xc1, 0x24, 0xc6, 0x8f, 0x21, 0x56, 0x75, 0xa5, 0xdb, 0xba, 0xcb, 0x5f, 0x08, 0x00, 0x00, 0x00,
 ]);
 
-pub fn find_envelope_pda(authority: &Address, custom_seeds: &[&[u8]]) -> (Address, u8) {
-    let mut seeds: Vec<&[u8]> = vec![ENVELOPE_SEED, authority.as_ref()];
-    seeds.extend(custom_seeds);
-    Address::find_program_address(&seeds, &PROGRAM_ID)
+/// Instructions sysvar account listing one top-level instruction per entry in `program_ids`.
+/// Each listed instruction has no accounts and no data; the program only reads program ids.
+pub fn create_instructions_sysvar(program_ids: &[Address]) -> Account {
+    let instructions: Vec<(Address, &[(Address, bool)])> =
+        program_ids.iter().map(|id| (*id, &[][..])).collect();
+    create_instructions_sysvar_with_accounts(&instructions)
 }
 
-pub fn create_funded_account(lamports: u64) -> Account {
+/// Instructions sysvar account with one top-level instruction per `(program_id, accounts)`
+/// entry, where each account is `(address, is_writable)`. Instructions carry no data.
+pub fn create_instructions_sysvar_with_accounts(
+    instructions: &[(Address, &[(Address, bool)])],
+) -> Account {
+    let mut body = Vec::new();
+    let mut offsets = Vec::new();
+    let header_len = 2 + 2 * instructions.len();
+    for (program_id, accounts) in instructions {
+        offsets.push((header_len + body.len()) as u16);
+        body.extend_from_slice(&(accounts.len() as u16).to_le_bytes());
+        for (address, is_writable) in accounts.iter() {
+            body.push(if *is_writable { 0b10 } else { 0 });
+            body.extend_from_slice(address.as_ref());
+        }
+        body.extend_from_slice(program_id.as_ref());
+        body.extend_from_slice(&0u16.to_le_bytes()); // data_len
+    }
+    let mut data = Vec::new();
+    data.extend_from_slice(&(instructions.len() as u16).to_le_bytes());
+    for offset in offsets {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(&body);
+    data.extend_from_slice(&0u16.to_le_bytes()); // current instruction index
     Account {
-        lamports,
-        data: vec![],
+        lamports: 1_000_000,
+        data,
         owner: Address::default(),
         executable: false,
         rent_epoch: 0,
     }
 }
 
-pub fn create_existing_envelope(authority: &Address, seq: u64) -> Account {
-    create_existing_envelope_with_bump(authority, seq, 0)
+/// Instructions sysvar account for a transaction of `instructions`, with their accounts and
+/// data, currently executing the one at index `current`.
+pub fn create_instructions_sysvar_for(instructions: &[Instruction], current: u16) -> Account {
+    let mut body = Vec::new();
+    let mut offsets = Vec::new();
+    let header_len = 2 + 2 * instructions.len();
+    for ix in instructions {
+        offsets.push((header_len + body.len()) as u16);
+        body.extend_from_slice(&(ix.accounts.len() as u16).to_le_bytes());
+        for meta in &ix.accounts {
+            body.push(meta.is_signer as u8 | (meta.is_writable as u8) << 1);
+            body.extend_from_slice(meta.pubkey.as_ref());
+        }
+        body.extend_from_slice(ix.program_id.as_ref());
+        body.extend_from_slice(&(ix.data.len() as u16).to_le_bytes());
+        body.extend_from_slice(&ix.data);
+    }
+    let mut data = Vec::new();
+    data.extend_from_slice(&(instructions.len() as u16).to_le_bytes());
+    for offset in offsets {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(&body);
+    data.extend_from_slice(&current.to_le_bytes());
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner: Address::default(),
+        executable: false,
+        rent_epoch: 0,
+    }
 }
 
-pub fn create_existing_envelope_with_bump(authority: &Address, seq: u64, bump: u8) -> Account {
-    let envelope = Envelope {
-        authority: *authority,
-        oracle_state: OracleState {
-            oracle_metadata: StructMetadata::ZERO,
-            sequence: seq,
-            data: [0u8; ORACLE_BYTES],
-            _pad: [0u8; 1],
-        },
-        bump,
-        _padding: [0u8; 7],
-        delegation_authority: Address::zeroed(),
-        program_bitmask: Mask::ALL_BLOCKED,
-        user_bitmask: Mask::ALL_BLOCKED,
-        authority_aux_sequence: 0,
-        program_aux_sequence: 0,
-        auxiliary_metadata: TEST_META,
-        auxiliary_data: [0u8; AUX_DATA_SIZE],
-    };
+/// Range guard account for `envelope` with `(program_id, offset, len)` entries.
+pub fn create_range_guard(envelope: &Address, entries: &[(Address, u16, u16)]) -> Account {
+    let mut guard = RangeGuard::zeroed();
+    guard.envelope = *envelope;
+    guard.count = entries.len() as u8;
+    for (slot, (program_id, offset, len)) in guard.entries.iter_mut().zip(entries) {
+        *slot = RangeGuardEntry {
+            program_id: *program_id,
+            offset: *offset,
+            len: *len,
+            _padding: [0; 4],
+        };
+    }
     Account {
         lamports: 1_000_000_000,
-        data: bytes_of(&envelope).to_vec(),
+        data: bytes_of(&guard).to_vec(),
         owner: PROGRAM_ID,
         executable: false,
         rent_epoch: 0,
     }
 }
 
-pub fn create_delegated_envelope(
-    authority: &Address,
+/// Constraint table account for `envelope` holding `constraints`, with the bump of its PDA
+/// ([`find_constraints_pda`]).
+pub fn create_constraint_table(envelope: &Address, constraints: &[Constraint]) -> Account {
+    let mut table = ConstraintTable::zeroed();
+    table.envelope = *envelope;
+    table.count = constraints.len() as u8;
+    table.bump = find_constraints_pda(envelope).1;
+    table.entries[..constraints.len()].copy_from_slice(constraints);
+    Account {
+        lamports: 1_000_000_000,
+        data: bytes_of(&table).to_vec(),
+        owner: PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Publisher set account for `envelope` registering `publishers` with `threshold`, with the
+/// bump of its PDA ([`find_publishers_pda`]).
+pub fn create_publisher_set(envelope: &Address, publishers: &[Address], threshold: u8) -> Account {
+    let mut set = PublisherSet::zeroed();
+    set.envelope = *envelope;
+    set.count = publishers.len() as u8;
+    set.bump = find_publishers_pda(envelope).1;
+    set.threshold = threshold;
+    set.publishers[..publishers.len()].copy_from_slice(publishers);
+    Account {
+        lamports: 1_000_000_000,
+        data: bytes_of(&set).to_vec(),
+        owner: PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Reader list account for `envelope` allowing `readers`, with the bump of its PDA
+/// ([`find_readers_pda`]).
+pub fn create_reader_list(envelope: &Address, readers: &[Address]) -> Account {
+    let mut list = ReaderList::zeroed();
+    list.envelope = *envelope;
+    list.count = readers.len() as u8;
+    list.bump = find_readers_pda(envelope).1;
+    list.readers[..readers.len()].copy_from_slice(readers);
+    Account {
+        lamports: 1_000_000_000,
+        data: bytes_of(&list).to_vec(),
+        owner: PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Delegate schema account for `envelope`, registered by `delegation_authority`.
+pub fn create_delegate_schema(
+    envelope: &Address,
     delegation_authority: &Address,
-    program_bitmask: Mask,
-    user_bitmask: Mask,
+    metadata: StructMetadata,
+    offset: u8,
+) -> Account {
+    let mut schema = DelegateSchema::zeroed();
+    schema.envelope = *envelope;
+    schema.delegation_authority = *delegation_authority;
+    schema.metadata = metadata;
+    schema.offset = offset;
+    Account {
+        lamports: 1_000_000_000,
+        data: bytes_of(&schema).to_vec(),
+        owner: PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Group anchor account owned by `authority` at `sequence`, listing `members` in order.
+pub fn create_group_anchor(
+    authority: &Address,
+    group_id: u64,
+    sequence: u64,
+    members: &[Address],
 ) -> Account {
-    let envelope = Envelope {
-        authority: *authority,
-        oracle_state: OracleState {
-            oracle_metadata: StructMetadata::ZERO,
-            sequence: 0,
-            data: [0u8; ORACLE_BYTES],
-            _pad: [0u8; 1],
-        },
-        bump: 0,
-        _padding: [0u8; 7],
-        delegation_authority: *delegation_authority,
-        program_bitmask,
-        user_bitmask,
-        authority_aux_sequence: 0,
-        program_aux_sequence: 0,
-        auxiliary_metadata: TEST_META,
-        auxiliary_data: [0u8; AUX_DATA_SIZE],
-    };
+    let mut anchor = GroupAnchor::zeroed();
+    anchor.authority = *authority;
+    anchor.group_id = group_id;
+    anchor.sequence = sequence;
+    anchor.count = members.len() as u8;
+    anchor.members[..members.len()].copy_from_slice(members);
     Account {
         lamports: 1_000_000_000,
-        data: bytes_of(&envelope).to_vec(),
+        data: bytes_of(&anchor).to_vec(),
         owner: PROGRAM_ID,
         executable: false,
         rent_epoch: 0,
     }
 }
+
+pub fn create_funded_account(lamports: u64) -> Account {
+    funded_account(lamports)
+}
+
+pub fn create_existing_envelope(authority: &Address, seq: u64) -> Account {
+    create_existing_envelope_with_bump(authority, seq, 0)
+}
+
+pub fn create_existing_envelope_with_bump(authority: &Address, seq: u64, bump: u8) -> Account {
+    EnvelopeFixture::new(*authority)
+        .sequence(seq)
+        .bump(bump)
+        .aux_metadata(TEST_META)
+        .owner(PROGRAM_ID)
+        .account()
+}
+
+pub fn create_delegated_envelope(
+    authority: &Address,
+    delegation_authority: &Address,
+    program_bitmask: Mask,
+    user_bitmask: Mask,
+) -> Account {
+    EnvelopeFixture::new(*authority)
+        .aux_metadata(TEST_META)
+        .delegated(*delegation_authority, program_bitmask, user_bitmask)
+        .owner(PROGRAM_ID)
+        .account()
+}
@@ -0,0 +1,462 @@
+use c_u_soon::{
+    derive_config_address, Mask, TypeHash, COMPACT_MASK_SIZE, LABEL_SEED, LABEL_SIZE,
+    LEGACY_ENVELOPE_SIZE, ORACLE_BYTES, READERS_SEED, TINY_PAYLOAD_SIZE,
+};
+use c_u_soon_client::{
+    aux_hash, build_create_with_events_ix, build_fast_path_ix, build_init_config_ix,
+    build_set_config_ix, find_envelope_address,
+};
+use c_u_soon_instruction::{
+    estimated_compute_units, estimated_fast_path_compute_units, SlowPathInstruction, WriteSpec,
+    WriteSpecV2, FAST_PATH_COMPUTE_UNITS, FAST_PATH_FLAGGED_COMPUTE_UNITS,
+    FAST_PATH_TINY_COMPUTE_UNITS,
+};
+use c_u_soon_program::fast_path::{COMPUTE_BUDGET, TINY_COMPUTE_BUDGET};
+use c_u_soon_test_utils::{
+    fixture_address, loader_accounts, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID,
+};
+use mollusk_svm::program::create_program_account_loader_v3;
+use pinocchio::Address;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_system_interface::program as system_program;
+
+type Aux = [u8; 16];
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    delegate: Address,
+    padding: Address,
+    envelope: Address,
+}
+
+/// A `u64` envelope at sequence 1 with a 16-byte aux type and no delegation.
+fn setup() -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("compute_units/authority");
+    let delegate = fixture_address("compute_units/delegate");
+    let padding = fixture_address("compute_units/padding");
+    let envelope = fixture_address("compute_units/envelope");
+    runner
+        .fund(authority, 1_000_000_000)
+        .fund(delegate, 1_000_000_000)
+        .fund(padding, 0)
+        .set_account(
+            envelope,
+            EnvelopeFixture::new(authority)
+                .oracle(u64::METADATA, &7u64.to_le_bytes())
+                .sequence(1)
+                .aux_metadata(Aux::METADATA)
+                .account(),
+        );
+    Setup {
+        runner,
+        authority,
+        delegate,
+        padding,
+        envelope,
+    }
+}
+
+impl Setup {
+    /// Run `ix` and check it stays within its estimate.
+    fn measure(&mut self, ix: SlowPathInstruction, accounts: Vec<AccountMeta>) {
+        let data = wincode::serialize(&ix).unwrap();
+        let result = self.runner.expect_ok(&Instruction::new_with_bytes(
+            DEFAULT_PROGRAM_ID,
+            &data,
+            accounts,
+        ));
+        let estimate = estimated_compute_units(&ix) as u64;
+        assert!(
+            result.compute_units_consumed <= estimate,
+            "{ix:?}: {} CUs, estimated {estimate}",
+            result.compute_units_consumed
+        );
+    }
+
+    fn authority_only(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new(self.envelope, false),
+            AccountMeta::new_readonly(self.padding, false),
+        ]
+    }
+
+    fn both_sign(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new(self.envelope, false),
+            AccountMeta::new_readonly(self.delegate, true),
+        ]
+    }
+
+    fn read_only(&self) -> Vec<AccountMeta> {
+        vec![AccountMeta::new_readonly(self.envelope, false)]
+    }
+
+    fn sidecar(&self, seed: &[u8]) -> (Address, u8) {
+        Address::find_program_address(&[seed, self.envelope.as_ref()], &DEFAULT_PROGRAM_ID)
+    }
+}
+
+#[test]
+fn test_fast_path_estimate_matches_budget() {
+    assert_eq!(FAST_PATH_COMPUTE_UNITS as u64, COMPUTE_BUDGET);
+    assert_eq!(FAST_PATH_TINY_COMPUTE_UNITS as u64, TINY_COMPUTE_BUDGET);
+    for len in 0..=ORACLE_BYTES {
+        let expected = if len == TINY_PAYLOAD_SIZE {
+            FAST_PATH_TINY_COMPUTE_UNITS
+        } else {
+            FAST_PATH_COMPUTE_UNITS
+        };
+        assert_eq!(estimated_fast_path_compute_units(len), expected);
+    }
+}
+
+#[test]
+fn test_fast_path_with_events_within_flagged_estimate() {
+    let mut s = setup();
+    let seeds: &[&[u8]] = &[b"compute_units"];
+    let (envelope, bump) = find_envelope_address(&DEFAULT_PROGRAM_ID, &s.authority, seeds);
+    s.runner.expect_ok(
+        &build_create_with_events_ix(
+            &DEFAULT_PROGRAM_ID,
+            &s.authority,
+            seeds,
+            bump,
+            u64::METADATA,
+        )
+        .unwrap(),
+    );
+    let result = s.runner.expect_ok(
+        &build_fast_path_ix(
+            &DEFAULT_PROGRAM_ID,
+            &s.authority,
+            &envelope,
+            u64::METADATA.as_u64(),
+            1,
+            &8u64.to_le_bytes(),
+        )
+        .unwrap(),
+    );
+    assert!(result.compute_units_consumed <= FAST_PATH_FLAGGED_COMPUTE_UNITS as u64);
+}
+
+#[test]
+fn test_create_within_estimate() {
+    let mut s = setup();
+    for (name, events) in [("plain", false), ("events", true)] {
+        let custom_seeds = vec![b"compute_units".to_vec(), name.as_bytes().to_vec()];
+        let (envelope, bump) = find_envelope_address(
+            &DEFAULT_PROGRAM_ID,
+            &s.authority,
+            &[&custom_seeds[0], &custom_seeds[1]],
+        );
+        let ix = if events {
+            SlowPathInstruction::CreateWithEvents {
+                custom_seeds,
+                bump,
+                oracle_metadata: u64::METADATA.as_u64(),
+            }
+        } else {
+            SlowPathInstruction::Create {
+                custom_seeds,
+                bump,
+                oracle_metadata: u64::METADATA.as_u64(),
+            }
+        };
+        let accounts = vec![
+            AccountMeta::new(s.authority, true),
+            AccountMeta::new(envelope, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(derive_config_address(&DEFAULT_PROGRAM_ID).0, false),
+        ];
+        s.measure(ix, accounts);
+    }
+}
+
+#[test]
+fn test_config_within_estimate() {
+    let mut s = setup();
+    for (address, account) in loader_accounts(&DEFAULT_PROGRAM_ID, Some(s.authority)) {
+        s.runner.set_account(address, account);
+    }
+    let admin = s.delegate.to_bytes();
+    let ix = build_init_config_ix(&DEFAULT_PROGRAM_ID, &s.authority, &s.delegate, 5_000).unwrap();
+    s.measure(
+        SlowPathInstruction::InitConfig {
+            bump: derive_config_address(&DEFAULT_PROGRAM_ID).1,
+            admin,
+            create_fee: 5_000,
+        },
+        ix.accounts,
+    );
+    let ix = build_set_config_ix(
+        &DEFAULT_PROGRAM_ID,
+        &s.delegate,
+        &s.delegate,
+        0,
+        true,
+        Some(&s.padding),
+    )
+    .unwrap();
+    s.measure(
+        SlowPathInstruction::SetConfig {
+            admin,
+            create_fee: 0,
+            paused: true,
+        },
+        ix.accounts,
+    );
+}
+
+#[test]
+fn test_reads_within_estimate() {
+    let mut s = setup();
+    let expected = aux_hash(s.runner.envelope(&s.envelope));
+    let accounts = s.read_only();
+    s.measure(SlowPathInstruction::ReadOracle, accounts.clone());
+    s.measure(SlowPathInstruction::ReadAux, accounts.clone());
+    s.measure(SlowPathInstruction::ExportState, accounts.clone());
+    s.measure(
+        SlowPathInstruction::ReadAuxRange { offset: 0, len: 8 },
+        accounts.clone(),
+    );
+    s.measure(SlowPathInstruction::AssertAuxHash { expected }, accounts);
+}
+
+#[test]
+fn test_flags_within_estimate() {
+    let mut s = setup();
+    let accounts = s.authority_only();
+    s.measure(
+        SlowPathInstruction::SetMutationGuard { allow_multi: false },
+        accounts.clone(),
+    );
+    s.measure(
+        SlowPathInstruction::SetTwap { enabled: true },
+        accounts.clone(),
+    );
+    s.measure(
+        SlowPathInstruction::SetUpdateStamp { enabled: true },
+        accounts.clone(),
+    );
+    s.measure(
+        SlowPathInstruction::SetConflation { enabled: true },
+        accounts.clone(),
+    );
+    s.measure(
+        SlowPathInstruction::SetOracleMetadata {
+            oracle_metadata: i64::METADATA.as_u64(),
+            auxiliary_metadata: u32::METADATA.as_u64(),
+        },
+        accounts.clone(),
+    );
+    s.measure(
+        SlowPathInstruction::SetPublisher {
+            publisher: s.delegate.to_bytes(),
+        },
+        accounts.clone(),
+    );
+    s.measure(
+        SlowPathInstruction::Heartbeat { sequence: 1 },
+        accounts.clone(),
+    );
+    s.measure(
+        SlowPathInstruction::SetPaused { paused: true },
+        accounts.clone(),
+    );
+    s.measure(
+        SlowPathInstruction::SealAuxiliaryRange {
+            offset: 0,
+            len: 256,
+        },
+        accounts.clone(),
+    );
+    s.measure(SlowPathInstruction::SetPermanent, accounts);
+}
+
+#[test]
+fn test_delegation_and_aux_writes_within_estimate() {
+    let mut s = setup();
+    s.measure(
+        SlowPathInstruction::SetDelegatedProgram {
+            program_bitmask: Mask::ALL_BLOCKED.into(),
+            user_bitmask: Mask::ALL_WRITABLE.into(),
+        },
+        s.both_sign(),
+    );
+
+    let ranges = (0..4)
+        .map(|i| WriteSpec {
+            offset: i * 4,
+            data: vec![i + 1; 4],
+        })
+        .collect();
+    let accounts = vec![
+        AccountMeta::new_readonly(s.authority, true),
+        AccountMeta::new(s.envelope, false),
+        AccountMeta::new_readonly(s.delegate, true),
+    ];
+    s.measure(
+        SlowPathInstruction::UpdateAuxiliaryMultiRange {
+            metadata: Aux::METADATA.as_u64(),
+            sequence: 1,
+            ranges,
+        },
+        accounts.clone(),
+    );
+    let ranges = (0..4)
+        .map(|i| WriteSpecV2 {
+            offset: i * 4,
+            data: vec![i as u8 + 5; 4],
+        })
+        .collect();
+    s.measure(
+        SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 {
+            metadata: Aux::METADATA.as_u64(),
+            sequence: 2,
+            ranges,
+        },
+        accounts,
+    );
+
+    let hook = fixture_address("compute_units/hook");
+    s.runner
+        .set_account(hook, create_program_account_loader_v3(&hook));
+    for notify_on_authority_write in [true, false] {
+        let mut accounts = s.both_sign();
+        accounts.push(AccountMeta::new_readonly(hook, false));
+        s.measure(
+            SlowPathInstruction::SetWriteHook {
+                notify_on_authority_write,
+            },
+            accounts,
+        );
+    }
+
+    // Handing the delegation to the same delegate still runs every check.
+    let mut accounts = s.both_sign();
+    accounts.push(AccountMeta::new_readonly(s.delegate, true));
+    s.measure(
+        SlowPathInstruction::TransferDelegation {
+            program_bitmask: Mask::ALL_WRITABLE.into(),
+            user_bitmask: Mask::ALL_BLOCKED.into(),
+        },
+        accounts,
+    );
+
+    s.measure(SlowPathInstruction::ClearDelegation, s.both_sign());
+
+    s.measure(
+        SlowPathInstruction::SetDelegatedProgramCompact {
+            program_bitmask: [0xFF; COMPACT_MASK_SIZE],
+            user_bitmask: [0; COMPACT_MASK_SIZE],
+        },
+        s.both_sign(),
+    );
+}
+
+#[test]
+fn test_sidecars_within_estimate() {
+    let mut s = setup();
+    let (label, bump) = s.sidecar(LABEL_SEED);
+    let mut text = [0u8; LABEL_SIZE];
+    text[..7].copy_from_slice(b"SOL/USD");
+    s.measure(
+        SlowPathInstruction::SetLabel { bump, label: text },
+        vec![
+            AccountMeta::new(s.authority, true),
+            AccountMeta::new_readonly(s.envelope, false),
+            AccountMeta::new(label, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+
+    let (reader_list, bump) = s.sidecar(READERS_SEED);
+    s.measure(
+        SlowPathInstruction::SetReaders {
+            bump,
+            readers: vec![[7; 32], [8; 32]],
+        },
+        vec![
+            AccountMeta::new(s.authority, true),
+            AccountMeta::new(s.envelope, false),
+            AccountMeta::new(reader_list, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+}
+
+#[test]
+fn test_resize_and_ext_aux_within_estimate() {
+    let mut s = setup();
+    s.measure(
+        SlowPathInstruction::Resize {
+            type_hash: <[u64; 64]>::TYPE_HASH,
+            ext_len: 512,
+        },
+        vec![
+            AccountMeta::new(s.authority, true),
+            AccountMeta::new(s.envelope, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    s.measure(
+        SlowPathInstruction::UpdateExtAux {
+            sequence: 1,
+            offset: 0,
+            data: vec![0xAB; 512],
+        },
+        s.authority_only(),
+    );
+}
+
+#[test]
+fn test_migrate_envelope_within_estimate() {
+    let mut s = setup();
+    let mut legacy = s.runner.account(&s.envelope).unwrap().clone();
+    legacy.data.truncate(LEGACY_ENVELOPE_SIZE);
+    legacy.lamports = s
+        .runner
+        .mollusk_mut()
+        .sysvars
+        .rent
+        .minimum_balance(LEGACY_ENVELOPE_SIZE);
+    s.runner.set_account(s.envelope, legacy);
+    s.measure(
+        SlowPathInstruction::MigrateEnvelope,
+        vec![
+            AccountMeta::new(s.authority, true),
+            AccountMeta::new(s.envelope, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+}
+
+#[test]
+fn test_close_within_estimate() {
+    let mut s = setup();
+    let recipient = fixture_address("compute_units/recipient");
+    s.measure(
+        SlowPathInstruction::Close,
+        vec![
+            AccountMeta::new_readonly(s.authority, true),
+            AccountMeta::new(s.envelope, false),
+            AccountMeta::new(recipient, false),
+        ],
+    );
+
+    let mut s = setup();
+    let treasury = fixture_address("compute_units/treasury");
+    s.measure(
+        SlowPathInstruction::CloseWithSplit { treasury_bps: 250 },
+        vec![
+            AccountMeta::new_readonly(s.authority, true),
+            AccountMeta::new(s.envelope, false),
+            AccountMeta::new(recipient, false),
+            AccountMeta::new(treasury, false),
+        ],
+    );
+}
@@ -0,0 +1,252 @@
+use c_u_soon::{ProgramConfig, TypeHash};
+use c_u_soon_client::{
+    build_create_ix, build_init_config_ix, build_set_config_ix, build_set_paused_ix,
+};
+use c_u_soon_instruction::PROGRAM_PAUSED_ERROR;
+use c_u_soon_test_utils::{
+    attacks, config_account, find_config_pda, find_envelope_pda, fixture_address, loader_accounts,
+    Runner, DEFAULT_PROGRAM_ID, FIXTURE_LAMPORTS,
+};
+use mollusk_svm::result::Check;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::Instruction;
+
+const SEEDS: &[&[u8]] = &[b"config"];
+
+struct Setup {
+    runner: Runner,
+    upgrade_authority: Address,
+    admin: Address,
+    authority: Address,
+    config: Address,
+}
+
+/// A program deployed with `upgrade_authority`, a funded envelope authority, and no config.
+fn setup() -> Setup {
+    let mut runner = Runner::new();
+    let upgrade_authority = fixture_address("config/upgrade_authority");
+    let admin = fixture_address("config/admin");
+    let authority = fixture_address("config/authority");
+    runner
+        .fund(upgrade_authority, 1_000_000_000)
+        .fund(admin, 1_000_000_000)
+        .fund(authority, 1_000_000_000);
+    for (address, account) in loader_accounts(&DEFAULT_PROGRAM_ID, Some(upgrade_authority)) {
+        runner.set_account(address, account);
+    }
+    Setup {
+        runner,
+        upgrade_authority,
+        admin,
+        authority,
+        config: find_config_pda(&DEFAULT_PROGRAM_ID).0,
+    }
+}
+
+impl Setup {
+    /// Replace the config with an initialized one.
+    fn configure(&mut self, create_fee: u64, paused: bool) {
+        let (address, account) = config_account(self.admin, create_fee, paused);
+        self.runner.set_account(address, account);
+    }
+
+    fn config(&self) -> ProgramConfig {
+        *bytemuck::from_bytes(&self.runner.account(&self.config).unwrap().data)
+    }
+
+    fn lamports(&self, address: &Address) -> u64 {
+        self.runner.account(address).map_or(0, |a| a.lamports)
+    }
+
+    fn create(&self) -> Instruction {
+        let (_, bump) = find_envelope_pda(&DEFAULT_PROGRAM_ID, &self.authority, SEEDS);
+        build_create_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            SEEDS,
+            bump,
+            u64::METADATA,
+        )
+        .unwrap()
+    }
+
+    fn set_config(&self, create_fee: u64, paused: bool, sweep_to: Option<&Address>) -> Instruction {
+        build_set_config_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.admin,
+            &self.admin,
+            create_fee,
+            paused,
+            sweep_to,
+        )
+        .unwrap()
+    }
+}
+
+#[test]
+fn test_upgrade_authority_inits_config_once() {
+    let mut s = setup();
+    let ix =
+        build_init_config_ix(&DEFAULT_PROGRAM_ID, &s.upgrade_authority, &s.admin, 5_000).unwrap();
+    s.runner.expect_ok(&ix);
+    let config = s.config();
+    assert_eq!(config.admin, s.admin);
+    assert_eq!(config.create_fee, 5_000);
+    assert_eq!(config.bump, find_config_pda(&DEFAULT_PROGRAM_ID).1);
+    assert!(!config.is_paused());
+
+    s.runner
+        .expect_err(&ix, ProgramError::AccountAlreadyInitialized);
+}
+
+#[test]
+fn test_only_upgrade_authority_inits_config() {
+    let mut s = setup();
+    let ix = build_init_config_ix(&DEFAULT_PROGRAM_ID, &s.admin, &s.admin, 0).unwrap();
+    s.runner.expect_err(&ix, ProgramError::IncorrectAuthority);
+    attacks::assert_requires_signer(
+        &mut s.runner,
+        &build_init_config_ix(&DEFAULT_PROGRAM_ID, &s.upgrade_authority, &s.admin, 0).unwrap(),
+        0,
+        ProgramError::MissingRequiredSignature,
+    );
+
+    // An immutable program has no upgrade authority to init a config.
+    for (address, account) in loader_accounts(&DEFAULT_PROGRAM_ID, None) {
+        s.runner.set_account(address, account);
+    }
+    let ix = build_init_config_ix(&DEFAULT_PROGRAM_ID, &s.upgrade_authority, &s.admin, 0).unwrap();
+    s.runner.expect_err(&ix, ProgramError::IncorrectAuthority);
+    assert!(s
+        .runner
+        .account(&s.config)
+        .is_none_or(|a| a.data.is_empty()));
+}
+
+#[test]
+fn test_create_is_free_without_config() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.create());
+    assert_eq!(s.lamports(&s.config), 0);
+}
+
+#[test]
+fn test_create_pays_fee_into_config() {
+    let mut s = setup();
+    s.configure(5_000, false);
+    let before = s.lamports(&s.authority);
+    s.runner.expect_ok(&s.create());
+    assert_eq!(s.lamports(&s.config), FIXTURE_LAMPORTS + 5_000);
+    assert!(s.lamports(&s.authority) < before - 5_000);
+
+    // Re-running `Create` on the existing envelope charges nothing.
+    s.runner.expect_ok(&s.create());
+    assert_eq!(s.lamports(&s.config), FIXTURE_LAMPORTS + 5_000);
+}
+
+#[test]
+fn test_create_rejects_other_config_account() {
+    let mut s = setup();
+    s.configure(5_000, false);
+    let ix = attacks::with_account(&s.create(), 3, fixture_address("config/impostor"));
+    s.runner.expect_err(&ix, ProgramError::InvalidSeeds);
+}
+
+#[test]
+fn test_pause_blocks_create() {
+    let mut s = setup();
+    s.configure(0, true);
+    s.runner
+        .expect_err(&s.create(), ProgramError::Custom(PROGRAM_PAUSED_ERROR));
+
+    s.runner.expect_ok(&s.set_config(0, false, None));
+    s.runner.expect_ok(&s.create());
+}
+
+#[test]
+fn test_create_requires_config_account() {
+    let mut s = setup();
+    s.configure(5_000, true);
+    let mut ix = s.create();
+    ix.accounts.truncate(3);
+    s.runner
+        .process_as_is(&ix, &[Check::err(ProgramError::NotEnoughAccountKeys)]);
+    assert_eq!(s.lamports(&s.config), FIXTURE_LAMPORTS);
+}
+
+#[test]
+fn test_pause_blocks_state_changing_instructions() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.create());
+    let (envelope, _) = find_envelope_pda(&DEFAULT_PROGRAM_ID, &s.authority, SEEDS);
+    let set_paused =
+        build_set_paused_ix(&DEFAULT_PROGRAM_ID, &s.authority, &envelope, false).unwrap();
+
+    // Before InitConfig the config PDA holds no data, and nothing is paused.
+    s.runner.expect_ok(&set_paused);
+
+    s.configure(0, false);
+    s.runner.expect_ok(&set_paused);
+
+    s.configure(0, true);
+    s.runner
+        .expect_err(&set_paused, ProgramError::Custom(PROGRAM_PAUSED_ERROR));
+    // SetConfig itself keeps working, so the admin can resume.
+    s.runner.expect_ok(&s.set_config(0, false, None));
+    s.runner.expect_ok(&set_paused);
+}
+
+#[test]
+fn test_state_changing_instructions_require_config_account() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.create());
+    let (envelope, _) = find_envelope_pda(&DEFAULT_PROGRAM_ID, &s.authority, SEEDS);
+    let set_paused =
+        build_set_paused_ix(&DEFAULT_PROGRAM_ID, &s.authority, &envelope, false).unwrap();
+
+    // No config has been initialized, yet leaving it out is not taken as "free and
+    // unpaused": the account before it is checked as the config instead.
+    let mut without_config = set_paused.clone();
+    without_config.accounts.pop();
+    assert!(s
+        .runner
+        .process_as_is(&without_config, &[])
+        .program_result
+        .is_err());
+
+    // Nor can another empty account stand in for the config PDA.
+    let impostor = attacks::with_account(
+        &set_paused,
+        set_paused.accounts.len() - 1,
+        fixture_address("config/impostor"),
+    );
+    s.runner
+        .process_as_is(&impostor, &[Check::err(ProgramError::InvalidSeeds)]);
+}
+
+#[test]
+fn test_set_config_is_admin_only_and_sweeps_fees() {
+    let mut s = setup();
+    s.configure(5_000, false);
+    let treasury = fixture_address("config/treasury");
+
+    let mut ix = s.set_config(0, true, Some(&treasury));
+    ix.accounts[0].pubkey = s.upgrade_authority;
+    s.runner.expect_err(&ix, ProgramError::IncorrectAuthority);
+
+    s.runner.expect_ok(&s.set_config(7_000, true, None));
+    assert_eq!(s.config().create_fee, 7_000);
+    assert!(s.config().is_paused());
+    assert_eq!(s.lamports(&s.config), FIXTURE_LAMPORTS);
+
+    let rent = s
+        .runner
+        .mollusk_mut()
+        .sysvars
+        .rent
+        .minimum_balance(ProgramConfig::SIZE);
+    s.runner
+        .expect_ok(&s.set_config(7_000, false, Some(&treasury)));
+    assert_eq!(s.lamports(&s.config), rent);
+    assert_eq!(s.lamports(&treasury), FIXTURE_LAMPORTS - rent);
+}
@@ -0,0 +1,122 @@
+use c_u_soon::{TypeHash, CONFLATION_OFFSET, FLAG_CONFLATION, FLAG_TWAP};
+use c_u_soon_client::{fast_path_instruction_data, set_conflation_instruction_data};
+use c_u_soon_test_utils::{fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+/// Third account that keeps SetConflation off the two-account fast path.
+const PADDING: Address = Address::new_from_array([0xEE; 32]);
+
+struct Feed {
+    runner: Runner,
+    authority: Address,
+    envelope: Address,
+}
+
+/// A `u64` oracle envelope with `flags`, its authority funded.
+fn feed(flags: u8) -> Feed {
+    let mut runner = Runner::new();
+    let authority = fixture_address("conflation/authority");
+    let envelope = fixture_address("conflation/envelope");
+    runner.fund(authority, 1_000_000_000).set_account(
+        envelope,
+        EnvelopeFixture::new(authority)
+            .oracle_metadata(u64::METADATA)
+            .flags(flags)
+            .account(),
+    );
+    Feed {
+        runner,
+        authority,
+        envelope,
+    }
+}
+
+impl Feed {
+    fn set_conflation(&self, enabled: bool) -> Instruction {
+        Instruction::new_with_bytes(
+            DEFAULT_PROGRAM_ID,
+            &set_conflation_instruction_data(enabled).unwrap(),
+            vec![
+                AccountMeta::new_readonly(self.authority, true),
+                AccountMeta::new(self.envelope, false),
+                AccountMeta::new_readonly(PADDING, false),
+            ],
+        )
+    }
+
+    fn update(&self, sequence: u64, payload: &[u8]) -> Instruction {
+        Instruction::new_with_bytes(
+            DEFAULT_PROGRAM_ID,
+            &fast_path_instruction_data(u64::METADATA.as_u64(), sequence, payload).unwrap(),
+            vec![
+                AccountMeta::new_readonly(self.authority, true),
+                AccountMeta::new(self.envelope, false),
+            ],
+        )
+    }
+}
+
+#[test]
+fn test_set_conflation_enables_and_zeroes_window() {
+    let mut feed = feed(0);
+    feed.runner.expect_ok(&feed.set_conflation(true));
+    let envelope = feed.runner.envelope(&feed.envelope);
+    assert_eq!(envelope.flags & FLAG_CONFLATION, FLAG_CONFLATION);
+    assert_eq!(envelope.conflation(), Some(&Default::default()));
+
+    feed.runner.expect_ok(&feed.set_conflation(false));
+    assert!(feed.runner.envelope(&feed.envelope).conflation().is_none());
+}
+
+#[test]
+fn test_set_conflation_rejects_oracle_types_that_do_not_fit() {
+    for metadata in [u32::METADATA, <[u8; CONFLATION_OFFSET + 1]>::METADATA] {
+        let mut feed = feed(0);
+        let account = EnvelopeFixture::new(feed.authority)
+            .oracle_metadata(metadata)
+            .account();
+        feed.runner.set_account(feed.envelope, account);
+        feed.runner
+            .expect_err(&feed.set_conflation(true), ProgramError::InvalidArgument);
+    }
+}
+
+#[test]
+fn test_fast_path_tracks_extremes_within_a_slot() {
+    let mut feed = feed(FLAG_CONFLATION | FLAG_TWAP);
+
+    feed.runner.mollusk_mut().warp_to_slot(10);
+    for (sequence, value) in [(1, 40u64), (2, 90), (3, 10), (4, 50)] {
+        feed.runner
+            .expect_ok(&feed.update(sequence, &value.to_le_bytes()));
+    }
+    let envelope = feed.runner.envelope(&feed.envelope);
+    assert_eq!(envelope.oracle::<u64>(), Some(&50), "last value");
+    let window = envelope.conflation().unwrap().in_slot(10).unwrap();
+    assert_eq!((window.min.get(), window.max.get()), (10, 90));
+    assert_eq!(window.count.get(), 4);
+    assert_eq!(envelope.twap().unwrap().last_value.get(), 50);
+
+    // The first write of a new slot starts a new window.
+    feed.runner.mollusk_mut().warp_to_slot(11);
+    feed.runner.expect_ok(&feed.update(5, &70u64.to_le_bytes()));
+    let window = feed.runner.envelope(&feed.envelope).conflation().unwrap();
+    assert!(window.in_slot(10).is_none());
+    assert_eq!(
+        (window.min.get(), window.max.get(), window.count.get()),
+        (70, 70, 1)
+    );
+}
+
+#[test]
+fn test_fast_path_rejects_payload_overlapping_window() {
+    let mut feed = feed(FLAG_CONFLATION);
+    feed.runner.mollusk_mut().warp_to_slot(5);
+    for payload in [vec![0u8; 4], vec![0u8; CONFLATION_OFFSET + 1]] {
+        feed.runner.expect_err(
+            &feed.update(1, &payload),
+            ProgramError::InvalidInstructionData,
+        );
+    }
+}
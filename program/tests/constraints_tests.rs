@@ -0,0 +1,574 @@
+mod common;
+
+use bytemuck::Zeroable;
+use c_u_soon::{
+    Constraint, ConstraintOp, ConstraintRegion, ConstraintTable, Envelope, EnvelopeLabel, Mask,
+    FLAG_CONSTRAINTS,
+};
+use c_u_soon_client::{
+    batch_update_instruction_data, fast_path_instruction_data, set_constraints_instruction_data,
+    update_auxiliary_delegated_instruction_data, update_auxiliary_instruction_data,
+};
+use c_u_soon_instruction::{BatchEntry, ConstraintSpec, CONSTRAINT_VIOLATED_ERROR};
+use common::{
+    create_constraint_table, create_delegated_envelope, create_existing_envelope,
+    create_funded_account, find_constraints_pda, find_label_pda, new_mollusk, new_mollusk_silent,
+    PROGRAM_ID, PROGRAM_PATH, TEST_META_U64, TEST_TYPE_SIZE,
+};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_system_interface::program as system_program;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Stand-in for an account the handler does not read.
+const PADDING: Address = Address::new_from_array([0xEE; 32]);
+
+/// `aux[0..8] >= 10`.
+const AUX_MIN: Constraint =
+    Constraint::against_const(ConstraintRegion::Aux, 0, 8, false, ConstraintOp::Ge, 10);
+
+/// `aux[8..16] <= aux[0..8]`.
+const AUX_ORDERED: Constraint =
+    Constraint::against_field(ConstraintRegion::Aux, 8, 8, false, ConstraintOp::Le, 0);
+
+/// `oracle[0..4] as i32 > -100`.
+const ORACLE_FLOOR: Constraint = Constraint::against_const(
+    ConstraintRegion::Oracle,
+    0,
+    4,
+    true,
+    ConstraintOp::Gt,
+    -100i64 as u64,
+);
+
+fn violated() -> Check<'static> {
+    Check::err(ProgramError::Custom(CONSTRAINT_VIOLATED_ERROR))
+}
+
+fn envelope(account: &Account) -> &Envelope {
+    bytemuck::from_bytes(&account.data)
+}
+
+/// `account` with its writes checked against `constraints`, and the matching table.
+fn constrained(
+    mut account: Account,
+    envelope_pubkey: &Address,
+    constraints: &[Constraint],
+) -> (Account, Account) {
+    let table = create_constraint_table(envelope_pubkey, constraints);
+    let env: &mut Envelope = bytemuck::from_bytes_mut(&mut account.data);
+    env.flags |= FLAG_CONSTRAINTS;
+    env.constrained_regions = bytemuck::from_bytes::<ConstraintTable>(&table.data).regions();
+    (account, table)
+}
+
+/// Envelope the authority can write aux data to: delegated, with every byte user-writable.
+fn delegated(authority: &Address) -> Account {
+    create_delegated_envelope(
+        authority,
+        &Address::new_unique(),
+        Mask::ALL_BLOCKED,
+        Mask::ALL_WRITABLE,
+    )
+}
+
+fn set_constraints_instruction(
+    authority: &Address,
+    envelope_pubkey: &Address,
+    table_pubkey: &Address,
+    bump: u8,
+    constraints: &[ConstraintSpec],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_constraints_instruction_data(bump, constraints).unwrap(),
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(*envelope_pubkey, false),
+            AccountMeta::new(*table_pubkey, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+/// Aux data with `lo` in bytes 0..8 and `hi` in bytes 8..16.
+fn aux_data(lo: u64, hi: u64) -> Vec<u8> {
+    let mut data = vec![0u8; TEST_TYPE_SIZE];
+    data[..8].copy_from_slice(&lo.to_le_bytes());
+    data[8..16].copy_from_slice(&hi.to_le_bytes());
+    data
+}
+
+fn update_aux_instruction(
+    authority: &Address,
+    envelope_pubkey: &Address,
+    third: &Address,
+    data: &[u8],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &update_auxiliary_instruction_data(TEST_META_U64, 1, data),
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope_pubkey, false),
+            AccountMeta::new_readonly(*third, false),
+        ],
+    )
+}
+
+// ============================================================================
+// SetConstraints
+// ============================================================================
+
+#[test]
+fn test_set_constraints_creates_table() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (table_pubkey, bump) = find_constraints_pda(&envelope_pubkey);
+    let specs: [ConstraintSpec; 2] = [AUX_MIN.into(), ORACLE_FLOOR.into()];
+
+    let ix = set_constraints_instruction(&authority, &envelope_pubkey, &table_pubkey, bump, &specs);
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (table_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    let env = envelope(&result.resulting_accounts[1].1);
+    assert!(env.has_constraints());
+    assert!(env.is_constrained(ConstraintRegion::Aux));
+    assert!(env.is_constrained(ConstraintRegion::Oracle));
+
+    let table_account = &result.resulting_accounts[2].1;
+    assert_eq!(table_account.owner, PROGRAM_ID);
+    let table: &ConstraintTable = bytemuck::from_bytes(&table_account.data);
+    assert_eq!(table.envelope, envelope_pubkey);
+    assert_eq!(table.bump, bump);
+    assert_eq!(table.active(), &[AUX_MIN, ORACLE_FLOOR]);
+}
+
+#[test]
+fn test_set_constraints_empty_clears_flag() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (table_pubkey, bump) = find_constraints_pda(&envelope_pubkey);
+    let (env_account, table) = constrained(
+        create_existing_envelope(&authority, 0),
+        &envelope_pubkey,
+        &[AUX_MIN],
+    );
+
+    let ix = set_constraints_instruction(&authority, &envelope_pubkey, &table_pubkey, bump, &[]);
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, env_account),
+            (table_pubkey, table),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    let env = envelope(&result.resulting_accounts[1].1);
+    assert!(!env.has_constraints());
+    assert_eq!(env.constrained_regions, 0);
+    let table: &ConstraintTable = bytemuck::from_bytes(&result.resulting_accounts[2].1.data);
+    assert!(table.active().is_empty());
+}
+
+#[test]
+fn test_set_constraints_rejects_wrong_authority() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let attacker = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (table_pubkey, bump) = find_constraints_pda(&envelope_pubkey);
+
+    let ix = set_constraints_instruction(
+        &attacker,
+        &envelope_pubkey,
+        &table_pubkey,
+        bump,
+        &[AUX_MIN.into()],
+    );
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (attacker, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (table_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_set_constraints_aux_blocked_while_delegated() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let delegate = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (table_pubkey, bump) = find_constraints_pda(&envelope_pubkey);
+    let delegated =
+        create_delegated_envelope(&authority, &delegate, Mask::ALL_WRITABLE, Mask::ALL_BLOCKED);
+
+    let accounts = |env: Account| {
+        vec![
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, env),
+            (table_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ]
+    };
+
+    let aux = set_constraints_instruction(
+        &authority,
+        &envelope_pubkey,
+        &table_pubkey,
+        bump,
+        &[AUX_MIN.into()],
+    );
+    mollusk.process_and_validate_instruction(
+        &aux,
+        &accounts(delegated.clone()),
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+
+    let oracle = set_constraints_instruction(
+        &authority,
+        &envelope_pubkey,
+        &table_pubkey,
+        bump,
+        &[ORACLE_FLOOR.into()],
+    );
+    mollusk.process_and_validate_instruction(&oracle, &accounts(delegated), &[Check::success()]);
+}
+
+// ============================================================================
+// Enforcement
+// ============================================================================
+
+#[test]
+fn test_aux_write_checks_constraints() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (table_pubkey, _) = find_constraints_pda(&envelope_pubkey);
+    let (env_account, table) = constrained(
+        delegated(&authority),
+        &envelope_pubkey,
+        &[AUX_MIN, AUX_ORDERED],
+    );
+
+    let accounts = [
+        (authority, create_funded_account(1_000_000_000)),
+        (envelope_pubkey, env_account),
+        (table_pubkey, table),
+    ];
+    let write = |lo, hi| {
+        update_aux_instruction(
+            &authority,
+            &envelope_pubkey,
+            &table_pubkey,
+            &aux_data(lo, hi),
+        )
+    };
+
+    let result =
+        mollusk.process_and_validate_instruction(&write(10, 10), &accounts, &[Check::success()]);
+    assert_eq!(
+        envelope(&result.resulting_accounts[1].1).authority_aux_sequence,
+        1
+    );
+
+    mollusk.process_and_validate_instruction(&write(9, 0), &accounts, &[violated()]);
+    mollusk.process_and_validate_instruction(&write(10, 11), &accounts, &[violated()]);
+}
+
+#[test]
+fn test_sequenced_aux_field_never_decreases() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let delegate = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (table_pubkey, _) = find_constraints_pda(&envelope_pubkey);
+    let mut account = create_delegated_envelope(
+        &authority,
+        &delegate,
+        Mask::ALL_WRITABLE,
+        Mask::ALL_WRITABLE,
+    );
+    bytemuck::from_bytes_mut::<Envelope>(&mut account.data).auxiliary_data[..8]
+        .copy_from_slice(&5u64.to_le_bytes());
+    let (env_account, table) = constrained(account, &envelope_pubkey, &[Constraint::sequenced(0)]);
+
+    let accounts = [
+        (authority, create_funded_account(1_000_000_000)),
+        (delegate, create_funded_account(1_000_000_000)),
+        (envelope_pubkey, env_account),
+        (table_pubkey, table),
+    ];
+    let authority_write = |counter| {
+        update_aux_instruction(
+            &authority,
+            &envelope_pubkey,
+            &table_pubkey,
+            &aux_data(counter, 0),
+        )
+    };
+    mollusk.process_and_validate_instruction(&authority_write(5), &accounts, &[Check::success()]);
+    mollusk.process_and_validate_instruction(&authority_write(6), &accounts, &[Check::success()]);
+    // A fresh aux sequence does not excuse a smaller counter.
+    mollusk.process_and_validate_instruction(&authority_write(4), &accounts, &[violated()]);
+
+    let delegated_write = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &update_auxiliary_delegated_instruction_data(TEST_META_U64, 1, &aux_data(0, 0)),
+        vec![
+            AccountMeta::new_readonly(delegate, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(table_pubkey, false),
+        ],
+    );
+    mollusk.process_and_validate_instruction(&delegated_write, &accounts, &[violated()]);
+}
+
+#[test]
+fn test_aux_write_requires_matching_table() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (table_pubkey, _) = find_constraints_pda(&envelope_pubkey);
+    let (env_account, _) = constrained(delegated(&authority), &envelope_pubkey, &[AUX_MIN]);
+    let data = aux_data(10, 0);
+
+    let other_table = create_constraint_table(&Address::new_unique(), &[AUX_MIN]);
+    mollusk.process_and_validate_instruction(
+        &update_aux_instruction(&authority, &envelope_pubkey, &table_pubkey, &data),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, env_account.clone()),
+            (table_pubkey, other_table),
+        ],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &update_aux_instruction(&authority, &envelope_pubkey, &PADDING, &data),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, env_account),
+            (PADDING, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::IncorrectProgramId)],
+    );
+}
+
+#[test]
+fn test_aux_write_rejects_label_as_table() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (env_account, _) = constrained(delegated(&authority), &envelope_pubkey, &[AUX_MIN]);
+
+    // The label is also 104 bytes, program-owned, and names the envelope first; read as a
+    // table, its empty label is an empty constraint list.
+    let (label_pubkey, bump) = find_label_pda(&envelope_pubkey);
+    let mut label = EnvelopeLabel::zeroed();
+    label.envelope = envelope_pubkey;
+    label.bump = bump;
+    let label_account = Account {
+        lamports: 1_000_000_000,
+        data: bytemuck::bytes_of(&label).to_vec(),
+        owner: PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    mollusk.process_and_validate_instruction(
+        &update_aux_instruction(&authority, &envelope_pubkey, &label_pubkey, &aux_data(0, 0)),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, env_account),
+            (label_pubkey, label_account),
+        ],
+        &[Check::err(ProgramError::InvalidSeeds)],
+    );
+}
+
+#[test]
+fn test_fast_path_refuses_oracle_constrained_envelope() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let fast_path = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &fast_path_instruction_data(0, 1, &[1, 2, 3, 4]).unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+        ],
+    );
+
+    let (oracle_constrained, _) = constrained(
+        create_existing_envelope(&authority, 0),
+        &envelope_pubkey,
+        &[ORACLE_FLOOR],
+    );
+    mollusk.process_and_validate_instruction(
+        &fast_path,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, oracle_constrained),
+        ],
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+
+    let (aux_constrained, _) = constrained(
+        create_existing_envelope(&authority, 0),
+        &envelope_pubkey,
+        &[AUX_MIN],
+    );
+    mollusk.process_and_validate_instruction(
+        &fast_path,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, aux_constrained),
+        ],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_batch_update_checks_oracle_constraints() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (table_pubkey, _) = find_constraints_pda(&envelope_pubkey);
+    let (env_account, table) = constrained(
+        create_existing_envelope(&authority, 0),
+        &envelope_pubkey,
+        &[ORACLE_FLOOR],
+    );
+    let accounts = [
+        (authority, create_funded_account(1_000_000_000)),
+        (envelope_pubkey, env_account),
+        (table_pubkey, table),
+    ];
+    let batch = |value: i32| {
+        let payload = value.to_le_bytes();
+        let data = batch_update_instruction_data(&[BatchEntry {
+            oracle_meta: 0,
+            sequence: 1,
+            payload: &payload,
+        }])
+        .unwrap();
+        Instruction::new_with_bytes(
+            PROGRAM_ID,
+            &data,
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(envelope_pubkey, false),
+                AccountMeta::new_readonly(table_pubkey, false),
+            ],
+        )
+    };
+
+    let result =
+        mollusk.process_and_validate_instruction(&batch(-99), &accounts, &[Check::success()]);
+    assert_eq!(
+        envelope(&result.resulting_accounts[1].1).oracle_state.data[..4],
+        (-99i32).to_le_bytes()
+    );
+
+    mollusk.process_and_validate_instruction(&batch(-100), &accounts, &[violated()]);
+}
+
+#[test]
+fn test_batch_update_rejects_missing_or_extra_tables() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let constrained_pubkey = Address::new_unique();
+    let plain_pubkey = Address::new_unique();
+    let (table_pubkey, _) = find_constraints_pda(&constrained_pubkey);
+    let (env_account, table) = constrained(
+        create_existing_envelope(&authority, 0),
+        &constrained_pubkey,
+        &[ORACLE_FLOOR],
+    );
+    let payload = [1u8; 4];
+    let entry = BatchEntry {
+        oracle_meta: 0,
+        sequence: 1,
+        payload: &payload,
+    };
+    let data = batch_update_instruction_data(&[entry, entry]).unwrap();
+    let envelopes = [
+        (constrained_pubkey, env_account),
+        (plain_pubkey, create_existing_envelope(&authority, 0)),
+    ];
+    let instruction = |with_table: bool, plain_first: bool| {
+        let mut metas = vec![AccountMeta::new_readonly(authority, true)];
+        let order = if plain_first {
+            [plain_pubkey, constrained_pubkey]
+        } else {
+            [constrained_pubkey, plain_pubkey]
+        };
+        metas.extend(order.iter().map(|e| AccountMeta::new(*e, false)));
+        if with_table {
+            metas.push(AccountMeta::new_readonly(table_pubkey, false));
+        }
+        Instruction::new_with_bytes(PROGRAM_ID, &data, metas)
+    };
+    let mut accounts = vec![(authority, create_funded_account(1_000_000_000))];
+    accounts.extend(envelopes.iter().cloned());
+    accounts.push((table_pubkey, table));
+
+    mollusk.process_and_validate_instruction(
+        &instruction(false, false),
+        &accounts,
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+    mollusk.process_and_validate_instruction(
+        &instruction(true, true),
+        &accounts,
+        &[Check::success()],
+    );
+
+    // A table nobody needs is rejected rather than ignored.
+    let second_plain = Address::new_unique();
+    let plain_only = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(plain_pubkey, false),
+            AccountMeta::new(second_plain, false),
+            AccountMeta::new_readonly(PADDING, false),
+        ],
+    );
+    mollusk.process_and_validate_instruction(
+        &plain_only,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (plain_pubkey, create_existing_envelope(&authority, 0)),
+            (second_plain, create_existing_envelope(&authority, 0)),
+            (PADDING, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
@@ -1,17 +1,19 @@
 mod common;
 
-use c_u_soon::{Envelope, Mask};
+use c_u_soon::{Envelope, Mask, TypeHash, AUX_DATA_SIZE};
 use c_u_soon_client::{
     set_delegated_program_instruction_data, update_auxiliary_force_instruction_data,
     update_auxiliary_instruction_data,
 };
+use c_u_soon_instruction::{write_hook_data, WRITE_HOOK_MAX_SIZE};
 use common::{
-    create_delegated_envelope, create_existing_envelope, create_funded_account, new_mollusk,
-    PROGRAM_ID, PROGRAM_PATH, TEST_META_U64, TEST_TYPE_SIZE,
+    create_delegated_envelope, create_existing_envelope, create_funded_account, find_config_pda,
+    new_mollusk, PROGRAM_ID, PROGRAM_PATH, TEST_META_U64, TEST_TYPE_SIZE,
 };
 use mollusk_svm::program::create_program_account_loader_v3;
 use mollusk_svm::result::Check;
 use pinocchio::{error::ProgramError, Address};
+use solana_sdk::account::Account;
 use solana_sdk::instruction::{AccountMeta, Instruction};
 
 // Program IDs for CPI test programs (arbitrary but stable)
@@ -35,6 +37,20 @@ const ATTACKER_PROBE_PATH: &str = concat!(
     "/../test-programs/attacker_probe/target/deploy/attacker_probe"
 );
 
+/// byte_writer's `HOOK_VETO`.
+const BYTE_WRITER_HOOK_VETO: u32 = 0xB0;
+
+/// The c_u_soon program config, the last account of every byte_writer and attacker_probe
+/// variant that reaches the slow path.
+fn config_meta() -> AccountMeta {
+    AccountMeta::new_readonly(find_config_pda().0, false)
+}
+
+/// The config PDA before `InitConfig`: no data, so nothing is paused or charged.
+fn config_account() -> (Address, Account) {
+    (find_config_pda().0, Account::default())
+}
+
 // -- Mollusk Security Integration Tests --
 // These tests verify core security properties of c_u_soon using Mollusk (single-program harness)
 
@@ -289,10 +305,36 @@ fn attacker_stale_sequence(oracle_meta: u64, sequence: u64, payload: &[u8]) -> V
     v
 }
 
+fn attacker_reentrant_aux_write(metadata: u64, sequence: u64, aux_data: &[u8]) -> Vec<u8> {
+    let mut v = Vec::with_capacity(1 + 8 + 8 + 1 + aux_data.len());
+    v.push(0x06); // ReentrantAuxWrite
+    v.extend_from_slice(&metadata.to_le_bytes());
+    v.extend_from_slice(&sequence.to_le_bytes());
+    v.push(0); // not yet reentered
+    v.extend_from_slice(aux_data);
+    v
+}
+
+fn attacker_multi_range_second_spec_blocked(
+    metadata: u64,
+    sequence: u64,
+    ranges: [(u8, u8); 2],
+) -> Vec<u8> {
+    let mut v = Vec::with_capacity(1 + 8 + 8 + 4);
+    v.push(0x09); // MultiRangeSecondSpecBlocked
+    v.extend_from_slice(&metadata.to_le_bytes());
+    v.extend_from_slice(&sequence.to_le_bytes());
+    for (offset, len) in ranges {
+        v.push(offset);
+        v.push(len);
+    }
+    v
+}
+
 #[test]
 fn test_cpi_fast_path_via_byte_writer() {
-    let mut mollusk = new_mollusk(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
 
     let authority = Address::new_unique();
     let envelope_pubkey = Address::new_unique();
@@ -327,8 +369,8 @@ fn test_cpi_fast_path_via_byte_writer() {
 
 #[test]
 fn test_cpi_slow_path_via_byte_writer() {
-    let mut mollusk = new_mollusk(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
 
     let authority = Address::new_unique();
     let delegation_auth = Address::new_unique();
@@ -348,6 +390,7 @@ fn test_cpi_slow_path_via_byte_writer() {
             AccountMeta::new(envelope_pubkey, false),
             AccountMeta::new_readonly(pda, true),
             AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
         ],
     );
 
@@ -367,6 +410,7 @@ fn test_cpi_slow_path_via_byte_writer() {
             ),
             (pda, create_funded_account(0)),
             (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
         ],
         &[Check::success()],
     );
@@ -381,8 +425,8 @@ fn test_cpi_slow_path_via_byte_writer() {
 
 #[test]
 fn test_cpi_delegated_via_byte_writer() {
-    let mut mollusk = new_mollusk(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
 
     let authority = Address::new_unique();
     let delegation_authority = Address::new_unique();
@@ -401,6 +445,7 @@ fn test_cpi_delegated_via_byte_writer() {
             AccountMeta::new(envelope_pubkey, false),
             AccountMeta::new_readonly(padding, false),
             AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
         ],
     );
 
@@ -419,6 +464,7 @@ fn test_cpi_delegated_via_byte_writer() {
             ),
             (padding, create_funded_account(0)),
             (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
         ],
         &[Check::success()],
     );
@@ -432,8 +478,8 @@ fn test_cpi_delegated_via_byte_writer() {
 
 #[test]
 fn test_cpi_force_via_byte_writer() {
-    let mut mollusk = new_mollusk(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
 
     let authority = Address::new_unique();
     let delegation_authority = Address::new_unique();
@@ -452,6 +498,7 @@ fn test_cpi_force_via_byte_writer() {
             AccountMeta::new(envelope_pubkey, false),
             AccountMeta::new_readonly(delegation_authority, true),
             AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
         ],
     );
 
@@ -470,6 +517,7 @@ fn test_cpi_force_via_byte_writer() {
             ),
             (delegation_authority, create_funded_account(1_000_000_000)),
             (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
         ],
         &[Check::success()],
     );
@@ -494,8 +542,8 @@ fn test_cpi_force_via_byte_writer() {
 // rejected; the specific error code should be investigated separately.
 #[test]
 fn test_cpi_attack_without_authority_signer() {
-    let mut mollusk = new_mollusk(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
 
     let authority = Address::new_unique();
     let envelope_pubkey = Address::new_unique();
@@ -526,8 +574,8 @@ fn test_cpi_attack_without_authority_signer() {
 // See MOLLUSK BUG comment on test_cpi_attack_without_authority_signer
 #[test]
 fn test_cpi_attack_wrong_authority() {
-    let mut mollusk = new_mollusk(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
 
     let actual_authority = Address::new_unique();
     let wrong_authority = Address::new_unique();
@@ -561,8 +609,8 @@ fn test_cpi_attack_wrong_authority() {
 
 #[test]
 fn test_cpi_attack_slow_path_without_pda_signer() {
-    let mut mollusk = new_mollusk(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
 
     let authority = Address::new_unique();
     let fake_pda = Address::new_unique();
@@ -578,6 +626,7 @@ fn test_cpi_attack_slow_path_without_pda_signer() {
             AccountMeta::new(envelope_pubkey, false),
             AccountMeta::new_readonly(fake_pda, false),
             AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
         ],
     );
 
@@ -589,6 +638,7 @@ fn test_cpi_attack_slow_path_without_pda_signer() {
             (envelope_pubkey, create_existing_envelope(&authority, 0)),
             (fake_pda, create_funded_account(0)),
             (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
         ],
         &[Check::err(ProgramError::InvalidArgument)],
     );
@@ -596,8 +646,8 @@ fn test_cpi_attack_slow_path_without_pda_signer() {
 
 #[test]
 fn test_cpi_attack_wrong_delegation_authority() {
-    let mut mollusk = new_mollusk(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
 
     let authority = Address::new_unique();
     let real_delegation = Address::new_unique();
@@ -607,7 +657,8 @@ fn test_cpi_attack_wrong_delegation_authority() {
 
     let aux_data = [0u8; TEST_TYPE_SIZE];
     let ix_data = attacker_wrong_delegation_authority(TEST_META_U64, 1, &aux_data);
-    // Accounts: [0]=wrong_delegation(signer), [1]=envelope(writable), [2]=padding, [3]=c_u_soon_program
+    // Accounts: [0]=wrong_delegation(signer), [1]=envelope(writable), [2]=padding,
+    // [3]=c_u_soon_program, [4]=config
     let instruction = Instruction::new_with_bytes(
         ATTACKER_PROBE_ID,
         &ix_data,
@@ -616,6 +667,7 @@ fn test_cpi_attack_wrong_delegation_authority() {
             AccountMeta::new(envelope_pubkey, false),
             AccountMeta::new_readonly(padding, false),
             AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
         ],
     );
 
@@ -634,6 +686,7 @@ fn test_cpi_attack_wrong_delegation_authority() {
             ),
             (padding, create_funded_account(0)),
             (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
         ],
         &[Check::err(ProgramError::IncorrectAuthority)],
     );
@@ -642,8 +695,8 @@ fn test_cpi_attack_wrong_delegation_authority() {
 // See MOLLUSK BUG comment on test_cpi_attack_without_authority_signer
 #[test]
 fn test_cpi_attack_stale_sequence() {
-    let mut mollusk = new_mollusk(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
 
     let authority = Address::new_unique();
     let envelope_pubkey = Address::new_unique();
@@ -671,12 +724,208 @@ fn test_cpi_attack_stale_sequence() {
     assert!(result.program_result.is_err());
 }
 
+#[test]
+fn test_cpi_attack_reentrant_aux_write() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
+
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let padding = Address::new_unique();
+
+    let aux_data = [0xAB; TEST_TYPE_SIZE];
+    let ix_data = attacker_reentrant_aux_write(TEST_META_U64, 1, &aux_data);
+    // Accounts: [0]=delegation_auth(signer), [1]=envelope(writable), [2]=padding,
+    // [3]=c_u_soon_program, [4]=attacker_probe_program, [5]=config
+    let instruction = Instruction::new_with_bytes(
+        ATTACKER_PROBE_ID,
+        &ix_data,
+        vec![
+            AccountMeta::new_readonly(delegation_auth, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(padding, false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+            AccountMeta::new_readonly(ATTACKER_PROBE_ID, false),
+            config_meta(),
+        ],
+    );
+
+    // The outer write lands; the replay from the re-entered frame carries the same
+    // sequence and fails, which aborts the whole instruction.
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (delegation_auth, create_funded_account(1_000_000_000)),
+            (
+                envelope_pubkey,
+                create_delegated_envelope(
+                    &authority,
+                    &delegation_auth,
+                    Mask::ALL_WRITABLE,
+                    Mask::ALL_BLOCKED,
+                ),
+            ),
+            (padding, create_funded_account(0)),
+            (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            (
+                ATTACKER_PROBE_ID,
+                create_program_account_loader_v3(&ATTACKER_PROBE_ID),
+            ),
+            config_account(),
+        ],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_cpi_attack_close_as_delegate() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
+
+    let authority = Address::new_unique();
+    let delegate = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let recipient = Address::new_unique();
+
+    // Accounts: [0]=delegate(signer), [1]=envelope(writable), [2]=recipient(writable),
+    // [3]=c_u_soon_program, [4]=config
+    let instruction = Instruction::new_with_bytes(
+        ATTACKER_PROBE_ID,
+        &[0x07], // CloseAsDelegate
+        vec![
+            AccountMeta::new_readonly(delegate, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new(recipient, false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (delegate, create_funded_account(1_000_000_000)),
+            (
+                envelope_pubkey,
+                create_delegated_envelope(
+                    &authority,
+                    &delegate,
+                    Mask::ALL_WRITABLE,
+                    Mask::ALL_WRITABLE,
+                ),
+            ),
+            (recipient, create_funded_account(0)),
+            (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_cpi_attack_envelope_as_delegation_authority() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
+
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    // Accounts: [0]=authority(signer), [1]=envelope(writable), [2]=c_u_soon_program,
+    // [3]=config
+    let instruction = Instruction::new_with_bytes(
+        ATTACKER_PROBE_ID,
+        &[0x08], // SetDelegatedProgramToEnvelope
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
+        ],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_cpi_attack_multi_range_second_spec_blocked() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&ATTACKER_PROBE_ID, ATTACKER_PROBE_PATH);
+
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let padding = Address::new_unique();
+
+    // The delegated program may write aux bytes 0..8 only.
+    let mut program_mask = Mask::ALL_BLOCKED;
+    program_mask.allow_range(0, 8);
+    let envelope = create_delegated_envelope(
+        &authority,
+        &delegation_auth,
+        program_mask,
+        Mask::ALL_BLOCKED,
+    );
+
+    // Accounts: [0]=delegation_auth(signer), [1]=envelope(writable), [2]=padding,
+    // [3]=c_u_soon_program, [4]=config
+    let instruction = |ranges| {
+        Instruction::new_with_bytes(
+            ATTACKER_PROBE_ID,
+            &attacker_multi_range_second_spec_blocked(TEST_META_U64, 1, ranges),
+            vec![
+                AccountMeta::new_readonly(delegation_auth, true),
+                AccountMeta::new(envelope_pubkey, false),
+                AccountMeta::new_readonly(padding, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                config_meta(),
+            ],
+        )
+    };
+    let accounts = [
+        (delegation_auth, create_funded_account(1_000_000_000)),
+        (envelope_pubkey, envelope),
+        (padding, create_funded_account(0)),
+        (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+        config_account(),
+    ];
+
+    // Bytes 0..4 are writable; 6..10 reaches the blocked bytes 8 and 9.
+    mollusk.process_and_validate_instruction(
+        &instruction([(0, 4), (6, 4)]),
+        &accounts,
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+
+    // The same first range with a second one inside the mask goes through.
+    let result = mollusk.process_and_validate_instruction(
+        &instruction([(0, 4), (4, 4)]),
+        &accounts,
+        &[Check::success()],
+    );
+    let env: &Envelope = bytemuck::from_bytes(
+        &result.resulting_accounts[1].1.data[..core::mem::size_of::<Envelope>()],
+    );
+    assert_eq!(env.auxiliary_data[..4], [0x11; 4]);
+    assert_eq!(env.auxiliary_data[4..8], [0x22; 4]);
+    assert_eq!(env.program_aux_sequence, 1);
+}
+
 // -- Range Update CPI Tests --
 
 #[test]
 fn test_cpi_range_via_byte_writer() {
-    let mut mollusk = new_mollusk(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
 
     let authority = Address::new_unique();
     let delegation_auth = Address::new_unique();
@@ -694,6 +943,7 @@ fn test_cpi_range_via_byte_writer() {
             AccountMeta::new(envelope_pubkey, false),
             AccountMeta::new_readonly(pda, true),
             AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
         ],
     );
 
@@ -712,6 +962,7 @@ fn test_cpi_range_via_byte_writer() {
             ),
             (pda, create_funded_account(0)),
             (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
         ],
         &[Check::success()],
     );
@@ -729,8 +980,8 @@ fn test_cpi_range_via_byte_writer() {
 
 #[test]
 fn test_cpi_delegated_range_via_byte_writer() {
-    let mut mollusk = new_mollusk(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
 
     let authority = Address::new_unique();
     let delegation_authority = Address::new_unique();
@@ -748,6 +999,7 @@ fn test_cpi_delegated_range_via_byte_writer() {
             AccountMeta::new(envelope_pubkey, false),
             AccountMeta::new_readonly(padding, false),
             AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
         ],
     );
 
@@ -766,6 +1018,7 @@ fn test_cpi_delegated_range_via_byte_writer() {
             ),
             (padding, create_funded_account(0)),
             (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
         ],
         &[Check::success()],
     );
@@ -780,8 +1033,8 @@ fn test_cpi_delegated_range_via_byte_writer() {
 
 #[test]
 fn test_cpi_range_mask_enforcement() {
-    let mut mollusk = new_mollusk(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
 
     let authority = Address::new_unique();
     let delegation_auth = Address::new_unique();
@@ -804,6 +1057,7 @@ fn test_cpi_range_mask_enforcement() {
             AccountMeta::new(envelope_pubkey, false),
             AccountMeta::new_readonly(pda, true),
             AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
         ],
     );
 
@@ -822,6 +1076,7 @@ fn test_cpi_range_mask_enforcement() {
             ),
             (pda, create_funded_account(0)),
             (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
         ],
     );
     assert!(result.program_result.is_err());
@@ -870,8 +1125,8 @@ fn pack_ranges(ranges: &[(u8, &[u8])]) -> Vec<u8> {
 
 #[test]
 fn test_cpi_multi_range_via_byte_writer() {
-    let mut mollusk = new_mollusk(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
 
     let authority = Address::new_unique();
     let delegation_auth = Address::new_unique();
@@ -889,6 +1144,7 @@ fn test_cpi_multi_range_via_byte_writer() {
             AccountMeta::new(envelope_pubkey, false),
             AccountMeta::new_readonly(pda, true),
             AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
         ],
     );
 
@@ -907,6 +1163,7 @@ fn test_cpi_multi_range_via_byte_writer() {
             ),
             (pda, create_funded_account(0)),
             (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
         ],
         &[Check::success()],
     );
@@ -921,8 +1178,8 @@ fn test_cpi_multi_range_via_byte_writer() {
 
 #[test]
 fn test_cpi_delegated_multi_range_via_byte_writer() {
-    let mut mollusk = new_mollusk(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
-    mollusk.add_program(&PROGRAM_ID, PROGRAM_PATH);
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
 
     let authority = Address::new_unique();
     let delegation_authority = Address::new_unique();
@@ -943,6 +1200,7 @@ fn test_cpi_delegated_multi_range_via_byte_writer() {
             AccountMeta::new(envelope_pubkey, false),
             AccountMeta::new_readonly(padding, false),
             AccountMeta::new_readonly(PROGRAM_ID, false),
+            config_meta(),
         ],
     );
 
@@ -961,6 +1219,7 @@ fn test_cpi_delegated_multi_range_via_byte_writer() {
             ),
             (padding, create_funded_account(0)),
             (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+            config_account(),
         ],
         &[Check::success()],
     );
@@ -972,3 +1231,191 @@ fn test_cpi_delegated_multi_range_via_byte_writer() {
     assert_eq!(&env.auxiliary_data[10..14], &[0xEE; 4]);
     assert_eq!(&env.auxiliary_data[50..52], &[0xFF; 2]);
 }
+
+fn u64_oracle_envelope(sequence: u64, value: u64) -> solana_sdk::account::Account {
+    let mut account = create_existing_envelope(&Address::new_unique(), sequence);
+    let env: &mut Envelope = bytemuck::from_bytes_mut(&mut account.data);
+    env.oracle_state.oracle_metadata = u64::METADATA;
+    env.oracle_state.data[..8].copy_from_slice(&value.to_le_bytes());
+    account
+}
+
+fn byte_writer_read_fresh_ix(envelope: Address, min_sequence: u64) -> Instruction {
+    let mut ix_data = vec![0x09];
+    ix_data.extend_from_slice(&min_sequence.to_le_bytes());
+    Instruction::new_with_bytes(
+        BYTE_WRITER_ID,
+        &ix_data,
+        vec![
+            AccountMeta::new_readonly(envelope, false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+        ],
+    )
+}
+
+#[test]
+fn test_with_fresh_oracle_reads_value() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
+    let envelope_pubkey = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &byte_writer_read_fresh_ix(envelope_pubkey, 7),
+        &[
+            (envelope_pubkey, u64_oracle_envelope(7, 42_000)),
+            (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+        ],
+        &[
+            Check::success(),
+            Check::return_data(&42_000u64.to_le_bytes()),
+        ],
+    );
+}
+
+#[test]
+fn test_with_fresh_oracle_rejects_stale_sequence() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
+    let envelope_pubkey = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &byte_writer_read_fresh_ix(envelope_pubkey, 8),
+        &[
+            (envelope_pubkey, u64_oracle_envelope(7, 42_000)),
+            (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+        ],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_with_fresh_oracle_rejects_type_mismatch() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
+    let envelope_pubkey = Address::new_unique();
+
+    // Oracle metadata is still zeroed, so it does not describe a u64.
+    mollusk.process_and_validate_instruction(
+        &byte_writer_read_fresh_ix(envelope_pubkey, 0),
+        &[
+            (
+                envelope_pubkey,
+                create_existing_envelope(&Address::new_unique(), 7),
+            ),
+            (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+        ],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_with_fresh_oracle_rejects_foreign_owner() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
+    let envelope_pubkey = Address::new_unique();
+
+    let mut envelope = u64_oracle_envelope(7, 42_000);
+    envelope.owner = Address::new_unique();
+    mollusk.process_and_validate_instruction(
+        &byte_writer_read_fresh_ix(envelope_pubkey, 0),
+        &[
+            (envelope_pubkey, envelope),
+            (PROGRAM_ID, create_program_account_loader_v3(&PROGRAM_ID)),
+        ],
+        &[Check::err(ProgramError::IncorrectProgramId)],
+    );
+}
+
+/// An authority write to a hooked envelope, with byte_writer as the hook: byte_writer vetoes
+/// any write that changes aux byte 0.
+fn hooked_write(data: &[u8; TEST_TYPE_SIZE]) -> (Instruction, Vec<(Address, Account)>) {
+    let authority = Address::new_unique();
+    let delegation_authority = Address::new_unique();
+    let padding = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let mut envelope = create_delegated_envelope(
+        &authority,
+        &delegation_authority,
+        Mask::ALL_BLOCKED,
+        Mask::ALL_WRITABLE,
+    );
+    let env: &mut Envelope = bytemuck::from_bytes_mut(&mut envelope.data);
+    env.write_hook = BYTE_WRITER_ID;
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &update_auxiliary_instruction_data(TEST_META_U64, 1, data),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(padding, false),
+            AccountMeta::new_readonly(BYTE_WRITER_ID, false),
+        ],
+    );
+    let accounts = vec![
+        (authority, create_funded_account(1_000_000_000)),
+        (envelope_pubkey, envelope),
+        (padding, create_funded_account(0)),
+        (
+            BYTE_WRITER_ID,
+            create_program_account_loader_v3(&BYTE_WRITER_ID),
+        ),
+    ];
+    (instruction, accounts)
+}
+
+#[test]
+fn test_write_hook_sees_changed_ranges() {
+    let mut data = [0u8; TEST_TYPE_SIZE];
+    data[2..4].fill(0xAB);
+    data[6] = 0xCD;
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
+    let (instruction, accounts) = hooked_write(&data);
+
+    let before = [0u8; AUX_DATA_SIZE];
+    let mut after = before;
+    after[..TEST_TYPE_SIZE].copy_from_slice(&data);
+    let mut expected = [0u8; WRITE_HOOK_MAX_SIZE];
+    let len = write_hook_data(1, &before, &after, &mut expected);
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &accounts,
+        &[Check::success(), Check::return_data(&expected[..len])],
+    );
+    let env: &Envelope = bytemuck::from_bytes(
+        &result.resulting_accounts[1].1.data[..core::mem::size_of::<Envelope>()],
+    );
+    assert_eq!(env.authority_aux_sequence, 1);
+    assert_eq!(&env.auxiliary_data[..TEST_TYPE_SIZE], &data);
+}
+
+#[test]
+fn test_write_hook_veto_aborts_the_write() {
+    let mut data = [0u8; TEST_TYPE_SIZE];
+    data[0] = 0xAB;
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
+    let (instruction, accounts) = hooked_write(&data);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::Custom(BYTE_WRITER_HOOK_VETO))],
+    );
+}
+
+#[test]
+fn test_write_hook_skipped_when_nothing_changes() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.add_program(&BYTE_WRITER_ID, BYTE_WRITER_PATH);
+    let (instruction, accounts) = hooked_write(&[0u8; TEST_TYPE_SIZE]);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &accounts,
+        &[Check::success(), Check::return_data(&[])],
+    );
+}
@@ -0,0 +1,353 @@
+mod common;
+
+use c_u_soon::{DelegateSchema, Envelope, Mask, StructMetadata, TypeHash};
+use c_u_soon_client::{
+    set_delegate_schema_typed, update_auxiliary_delegated_schema_instruction_data,
+    update_auxiliary_delegated_schema_typed,
+};
+use common::{
+    create_delegate_schema, create_delegated_envelope, create_funded_account,
+    find_delegate_schema_pda, new_mollusk, new_mollusk_silent, PROGRAM_ID, PROGRAM_PATH,
+};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_system_interface::program as system_program;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// The delegate's sub-struct: a `u64` at bytes `[16, 24)`.
+const SCHEMA_OFFSET: u8 = 16;
+
+/// Program mask with only the schema span writable.
+fn schema_span_mask() -> Mask {
+    let mut mask = Mask::ALL_BLOCKED;
+    for i in SCHEMA_OFFSET as usize..SCHEMA_OFFSET as usize + 8 {
+        mask.allow(i);
+    }
+    mask
+}
+
+fn set_delegate_schema_instruction(
+    delegation_auth: &Address,
+    envelope_pubkey: &Address,
+    schema_pubkey: &Address,
+    payer: &Address,
+    bump: u8,
+    offset: u8,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_delegate_schema_typed::<u64>(bump, offset).unwrap(),
+        vec![
+            AccountMeta::new_readonly(*delegation_auth, true),
+            AccountMeta::new_readonly(*envelope_pubkey, false),
+            AccountMeta::new(*schema_pubkey, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+fn schema_write_instruction(
+    delegation_auth: &Address,
+    envelope_pubkey: &Address,
+    schema_pubkey: &Address,
+    data: Vec<u8>,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new_readonly(*delegation_auth, true),
+            AccountMeta::new(*envelope_pubkey, false),
+            AccountMeta::new_readonly(*schema_pubkey, false),
+        ],
+    )
+}
+
+struct SchemaSetup {
+    delegation_auth: Address,
+    envelope_pubkey: Address,
+    schema_pubkey: Address,
+    accounts: Vec<(Address, Account)>,
+}
+
+/// Delegated envelope with a registered `u64` schema at `SCHEMA_OFFSET`.
+fn schema_setup() -> SchemaSetup {
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (schema_pubkey, _) = find_delegate_schema_pda(&envelope_pubkey);
+
+    let accounts = vec![
+        (delegation_auth, create_funded_account(0)),
+        (
+            envelope_pubkey,
+            create_delegated_envelope(
+                &authority,
+                &delegation_auth,
+                schema_span_mask(),
+                Mask::ALL_BLOCKED,
+            ),
+        ),
+        (
+            schema_pubkey,
+            create_delegate_schema(
+                &envelope_pubkey,
+                &delegation_auth,
+                u64::METADATA,
+                SCHEMA_OFFSET,
+            ),
+        ),
+    ];
+
+    SchemaSetup {
+        delegation_auth,
+        envelope_pubkey,
+        schema_pubkey,
+        accounts,
+    }
+}
+
+// ============================================================================
+// SetDelegateSchema
+// ============================================================================
+
+#[test]
+fn test_set_delegate_schema_creates_account() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let payer = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (schema_pubkey, bump) = find_delegate_schema_pda(&envelope_pubkey);
+
+    let ix = set_delegate_schema_instruction(
+        &delegation_auth,
+        &envelope_pubkey,
+        &schema_pubkey,
+        &payer,
+        bump,
+        SCHEMA_OFFSET,
+    );
+
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (delegation_auth, create_funded_account(0)),
+            (
+                envelope_pubkey,
+                create_delegated_envelope(
+                    &authority,
+                    &delegation_auth,
+                    schema_span_mask(),
+                    Mask::ALL_BLOCKED,
+                ),
+            ),
+            (schema_pubkey, create_funded_account(0)),
+            (payer, create_funded_account(1_000_000_000)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    let schema_account = &result.resulting_accounts[2].1;
+    assert_eq!(schema_account.owner, PROGRAM_ID);
+    let schema: &DelegateSchema = bytemuck::from_bytes(&schema_account.data);
+    assert_eq!(schema.envelope, envelope_pubkey);
+    assert_eq!(schema.delegation_authority, delegation_auth);
+    assert_eq!(schema.metadata, u64::METADATA);
+    assert_eq!(schema.offset, SCHEMA_OFFSET);
+    assert_eq!(schema.bump, bump);
+}
+
+#[test]
+fn test_set_delegate_schema_rejects_span_outside_program_mask() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let payer = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (schema_pubkey, bump) = find_delegate_schema_pda(&envelope_pubkey);
+
+    // One byte past the writable span.
+    let ix = set_delegate_schema_instruction(
+        &delegation_auth,
+        &envelope_pubkey,
+        &schema_pubkey,
+        &payer,
+        bump,
+        SCHEMA_OFFSET + 1,
+    );
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (delegation_auth, create_funded_account(0)),
+            (
+                envelope_pubkey,
+                create_delegated_envelope(
+                    &authority,
+                    &delegation_auth,
+                    schema_span_mask(),
+                    Mask::ALL_BLOCKED,
+                ),
+            ),
+            (schema_pubkey, create_funded_account(0)),
+            (payer, create_funded_account(1_000_000_000)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_set_delegate_schema_rejects_non_delegate() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let impostor = Address::new_unique();
+    let payer = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (schema_pubkey, bump) = find_delegate_schema_pda(&envelope_pubkey);
+
+    let ix = set_delegate_schema_instruction(
+        &impostor,
+        &envelope_pubkey,
+        &schema_pubkey,
+        &payer,
+        bump,
+        SCHEMA_OFFSET,
+    );
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (impostor, create_funded_account(0)),
+            (
+                envelope_pubkey,
+                create_delegated_envelope(
+                    &authority,
+                    &delegation_auth,
+                    schema_span_mask(),
+                    Mask::ALL_BLOCKED,
+                ),
+            ),
+            (schema_pubkey, create_funded_account(0)),
+            (payer, create_funded_account(1_000_000_000)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+// ============================================================================
+// UpdateAuxiliaryDelegatedSchema
+// ============================================================================
+
+#[test]
+fn test_schema_write_lands_at_schema_offset() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let setup = schema_setup();
+
+    let value: u64 = 0x0102_0304_0506_0708;
+    let ix = schema_write_instruction(
+        &setup.delegation_auth,
+        &setup.envelope_pubkey,
+        &setup.schema_pubkey,
+        update_auxiliary_delegated_schema_typed::<u64>(1, &value),
+    );
+
+    let result =
+        mollusk.process_and_validate_instruction(&ix, &setup.accounts, &[Check::success()]);
+
+    let envelope: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    let schema: &DelegateSchema = bytemuck::from_bytes(&result.resulting_accounts[2].1.data);
+    assert_eq!(envelope.delegate_aux::<u64>(schema), Some(&value));
+    assert_eq!(envelope.program_aux_sequence, 1);
+    assert!(envelope.auxiliary_data[..SCHEMA_OFFSET as usize]
+        .iter()
+        .all(|&b| b == 0));
+}
+
+#[test]
+fn test_schema_write_rejects_wrong_metadata() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let setup = schema_setup();
+
+    let ix = schema_write_instruction(
+        &setup.delegation_auth,
+        &setup.envelope_pubkey,
+        &setup.schema_pubkey,
+        update_auxiliary_delegated_schema_instruction_data(
+            StructMetadata::new(8, 0xBAD).as_u64(),
+            1,
+            &[0xAA; 8],
+        ),
+    );
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &setup.accounts,
+        &[Check::err(ProgramError::Custom(
+            c_u_soon_instruction::METADATA_HASH_MISMATCH_ERROR,
+        ))],
+    );
+}
+
+#[test]
+fn test_schema_write_rejects_stale_delegation() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let mut setup = schema_setup();
+
+    // Schema left behind by a previous delegate.
+    setup.accounts[2].1 = create_delegate_schema(
+        &setup.envelope_pubkey,
+        &Address::new_unique(),
+        u64::METADATA,
+        SCHEMA_OFFSET,
+    );
+
+    let ix = schema_write_instruction(
+        &setup.delegation_auth,
+        &setup.envelope_pubkey,
+        &setup.schema_pubkey,
+        update_auxiliary_delegated_schema_typed::<u64>(1, &7),
+    );
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &setup.accounts,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_schema_write_rejects_schema_for_other_envelope() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let mut setup = schema_setup();
+
+    setup.accounts[2].1 = create_delegate_schema(
+        &Address::new_unique(),
+        &setup.delegation_auth,
+        u64::METADATA,
+        SCHEMA_OFFSET,
+    );
+
+    let ix = schema_write_instruction(
+        &setup.delegation_auth,
+        &setup.envelope_pubkey,
+        &setup.schema_pubkey,
+        update_auxiliary_delegated_schema_typed::<u64>(1, &7),
+    );
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &setup.accounts,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
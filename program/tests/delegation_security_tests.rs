@@ -1,9 +1,10 @@
 mod common;
 
 use bytemuck::Zeroable;
-use c_u_soon::{Envelope, Mask};
+use c_u_soon::{CompactMask, Envelope, Mask};
 use c_u_soon_client::{
-    clear_delegation_instruction_data, set_delegated_program_instruction_data,
+    clear_delegation_instruction_data, set_delegated_pda_instruction_data,
+    set_delegated_program_compact_instruction_data, set_delegated_program_instruction_data,
     update_auxiliary_delegated_instruction_data, update_auxiliary_force_instruction_data,
     update_auxiliary_instruction_data,
 };
@@ -103,6 +104,99 @@ fn test_set_delegated_program_rejects_if_delegation_exists() {
     );
 }
 
+/// Run a delegating instruction on a fresh, undelegated envelope and return its bytes.
+fn delegate_with(data: Vec<u8>) -> Vec<u8> {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_from_array([1; 32]);
+    let delegation_authority = Address::new_from_array([2; 32]);
+    let envelope_pubkey = Address::new_from_array([3; 32]);
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(delegation_authority, true),
+        ],
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (delegation_authority, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+    result.resulting_accounts[1].1.data.clone()
+}
+
+#[test]
+fn test_set_delegated_program_compact_matches_full_masks() {
+    let mut alternating = Mask::ALL_BLOCKED;
+    for offset in (0..256).step_by(2) {
+        alternating.allow(offset);
+    }
+    let mut prefix = Mask::ALL_BLOCKED;
+    prefix.allow_range(0, TEST_TYPE_SIZE);
+    let cases = [
+        (Mask::ALL_BLOCKED, Mask::ALL_WRITABLE),
+        (Mask::ALL_WRITABLE, Mask::ALL_BLOCKED),
+        (alternating, prefix),
+        (prefix, alternating),
+    ];
+    for (program, user) in cases {
+        let full = delegate_with(set_delegated_program_instruction_data(program, user).unwrap());
+        let compact = delegate_with(
+            set_delegated_program_compact_instruction_data(
+                CompactMask::from(&program),
+                CompactMask::from(&user),
+            )
+            .unwrap(),
+        );
+        assert_eq!(full, compact);
+    }
+}
+
+#[test]
+fn test_set_delegated_program_compact_rejects_if_delegation_exists() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+
+    let authority = Address::new_unique();
+    let existing_delegation = Address::new_unique();
+    let new_delegation = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_delegated_program_compact_instruction_data(CompactMask::FULL, CompactMask::ZERO)
+            .unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(new_delegation, true),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (
+                envelope_pubkey,
+                create_delegated_envelope(
+                    &authority,
+                    &existing_delegation,
+                    Mask::ALL_WRITABLE,
+                    Mask::ALL_WRITABLE,
+                ),
+            ),
+            (new_delegation, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
 #[test]
 fn test_clear_delegation_happy_path() {
     let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
@@ -368,3 +462,88 @@ fn test_sequence_monotonically_increases() {
         &[Check::err(ProgramError::InvalidInstructionData)],
     );
 }
+
+fn set_delegated_pda_instruction(
+    authority: Address,
+    envelope: Address,
+    delegate: Address,
+    delegate_program: &Address,
+    bump: u8,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_delegated_pda_instruction_data(
+            delegate_program.to_bytes(),
+            &[b"writer"],
+            bump,
+            Mask::ALL_WRITABLE,
+            Mask::ALL_BLOCKED,
+        )
+        .unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope, false),
+            AccountMeta::new_readonly(delegate, false),
+        ],
+    )
+}
+
+#[test]
+fn test_set_delegated_pda_accepts_unsigned_derived_delegate() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let delegate_program = Address::new_unique();
+    let (delegate, bump) = Address::find_program_address(&[b"writer"], &delegate_program);
+
+    let result = mollusk.process_and_validate_instruction(
+        &set_delegated_pda_instruction(
+            authority,
+            envelope_pubkey,
+            delegate,
+            &delegate_program,
+            bump,
+        ),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (delegate, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+
+    let env: &Envelope = bytemuck::from_bytes(
+        &result.resulting_accounts[1].1.data[..core::mem::size_of::<Envelope>()],
+    );
+    assert_eq!(env.delegation_authority, delegate);
+    assert_eq!(env.program_bitmask, Mask::ALL_WRITABLE);
+    assert_eq!(env.user_bitmask, Mask::ALL_BLOCKED);
+}
+
+#[test]
+fn test_set_delegated_pda_rejects_underived_delegate() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let delegate_program = Address::new_unique();
+    let (delegate, bump) = Address::find_program_address(&[b"writer"], &delegate_program);
+    let impostor = Address::new_unique();
+
+    // Same seeds under another program, and an arbitrary key, both fail.
+    for (account, program) in [
+        (delegate, Address::new_unique()),
+        (impostor, delegate_program),
+    ] {
+        mollusk.process_and_validate_instruction(
+            &set_delegated_pda_instruction(authority, envelope_pubkey, account, &program, bump),
+            &[
+                (authority, create_funded_account(1_000_000_000)),
+                (envelope_pubkey, create_existing_envelope(&authority, 0)),
+                (account, create_funded_account(0)),
+            ],
+            &[Check::err(ProgramError::InvalidSeeds)],
+        );
+    }
+}
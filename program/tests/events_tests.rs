@@ -0,0 +1,124 @@
+use c_u_soon::TypeHash;
+use c_u_soon_client::{
+    build_create_ix, build_create_with_events_ix, build_fast_path_ix, build_set_paused_ix,
+    find_envelope_address,
+};
+use c_u_soon_instruction::ENVELOPE_PAUSED_ERROR;
+use c_u_soon_program::fast_path::COMPUTE_BUDGET;
+use c_u_soon_test_utils::{fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::Instruction;
+
+const SEEDS: &[&[u8]] = &[b"events"];
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    envelope: Address,
+    bump: u8,
+}
+
+/// A funded authority whose `SEEDS` envelope does not exist yet.
+fn setup() -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("events/authority");
+    let (envelope, bump) = find_envelope_address(&DEFAULT_PROGRAM_ID, &authority, SEEDS);
+    runner.fund(authority, 1_000_000_000).fund(envelope, 0);
+    Setup {
+        runner,
+        authority,
+        envelope,
+        bump,
+    }
+}
+
+impl Setup {
+    fn create(&self, events: bool) -> Instruction {
+        let build = if events {
+            build_create_with_events_ix
+        } else {
+            build_create_ix
+        };
+        build(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            SEEDS,
+            self.bump,
+            u64::METADATA,
+        )
+        .unwrap()
+    }
+
+    fn write(&self, sequence: u64, value: u64) -> Instruction {
+        build_fast_path_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            &self.envelope,
+            u64::METADATA.as_u64(),
+            sequence,
+            &value.to_le_bytes(),
+        )
+        .unwrap()
+    }
+}
+
+#[test]
+fn test_create_with_events_sets_flag() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.create(true));
+    let envelope = s.runner.envelope(&s.envelope);
+    assert!(envelope.emits_events());
+    assert_eq!(envelope.oracle_state.oracle_metadata, u64::METADATA);
+
+    // Repeating the create is idempotent only with the same flag.
+    s.runner.expect_ok(&s.create(true));
+    s.runner
+        .expect_err(&s.create(false), ProgramError::InvalidArgument);
+}
+
+#[test]
+fn test_plain_create_does_not_emit() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.create(false));
+    assert!(!s.runner.envelope(&s.envelope).emits_events());
+    s.runner
+        .expect_err(&s.create(true), ProgramError::InvalidArgument);
+
+    let result = s.runner.expect_ok(&s.write(1, 7));
+    assert_eq!(result.compute_units_consumed, COMPUTE_BUDGET);
+}
+
+#[test]
+fn test_event_writes_update_oracle() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.create(true));
+
+    let result = s.runner.expect_ok(&s.write(1, 7));
+    // Hashing and logging the event is paid only by envelopes that opted in.
+    assert!(result.compute_units_consumed > COMPUTE_BUDGET);
+    s.runner.expect_ok(&s.write(2, 8));
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.oracle_state.sequence, 2);
+    assert_eq!(envelope.oracle::<u64>().copied(), Some(8));
+
+    s.runner
+        .expect_err(&s.write(2, 9), ProgramError::InvalidInstructionData);
+}
+
+#[test]
+fn test_paused_event_envelope_rejects_writes() {
+    let mut s = setup();
+    s.runner.set_account(
+        s.envelope,
+        EnvelopeFixture::new(s.authority)
+            .oracle_metadata(u64::METADATA)
+            .emit_events()
+            .account(),
+    );
+    s.runner.expect_ok(
+        &build_set_paused_ix(&DEFAULT_PROGRAM_ID, &s.authority, &s.envelope, true).unwrap(),
+    );
+    s.runner
+        .expect_err(&s.write(1, 7), ProgramError::Custom(ENVELOPE_PAUSED_ERROR));
+    assert_eq!(s.runner.envelope(&s.envelope).oracle_state.sequence, 0);
+}
@@ -0,0 +1,345 @@
+mod common;
+
+use c_u_soon::{ext_account_size, Envelope, TypeHash, EXT_AUX_DATA_OFFSET};
+use c_u_soon_client::{
+    fast_path_instruction_data, resize_instruction_data, update_ext_aux_instruction_data,
+};
+use common::{
+    create_existing_envelope, create_funded_account, new_mollusk, new_mollusk_silent, MolluskGuard,
+    PROGRAM_ID, PROGRAM_PATH,
+};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check, Mollusk};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_system_interface::program as system_program;
+
+/// A 512-byte extension type, too large for the fixed auxiliary region.
+type Book = [u64; 64];
+
+fn resize_instruction(authority: &Address, envelope: &Address, ext_len: usize) -> Instruction {
+    resize_typed_instruction(authority, envelope, Book::TYPE_HASH, ext_len)
+}
+
+fn resize_typed_instruction(
+    authority: &Address,
+    envelope: &Address,
+    type_hash: u64,
+    ext_len: usize,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &resize_instruction_data(type_hash, ext_len).unwrap(),
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+fn update_instruction(
+    authority: &Address,
+    envelope: &Address,
+    padding: &Address,
+    sequence: u64,
+    offset: usize,
+    data: &[u8],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &update_ext_aux_instruction_data(sequence, offset, data).unwrap(),
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope, false),
+            AccountMeta::new_readonly(*padding, false),
+        ],
+    )
+}
+
+/// An envelope holding exactly the rent-exempt minimum for its size.
+fn rent_exact_envelope(mollusk: &Mollusk, authority: &Address) -> Account {
+    let mut account = create_existing_envelope(authority, 0);
+    account.lamports = mollusk.sysvars.rent.minimum_balance(account.data.len());
+    account
+}
+
+/// Resize a fresh envelope to `ext_len` and return the resulting envelope account.
+fn resized_envelope<G>(
+    mollusk: &MolluskGuard<G>,
+    authority: &Address,
+    envelope: &Address,
+    ext_len: usize,
+) -> Account {
+    let result = mollusk.process_and_validate_instruction(
+        &resize_instruction(authority, envelope, ext_len),
+        &[
+            (*authority, create_funded_account(1_000_000_000)),
+            (*envelope, rent_exact_envelope(mollusk, authority)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+    result.resulting_accounts[1].1.clone()
+}
+
+#[test]
+fn test_resize_grows_envelope_and_charges_rent() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let ext_len = core::mem::size_of::<Book>();
+
+    let result = mollusk.process_and_validate_instruction(
+        &resize_instruction(&authority, &envelope_pubkey, ext_len),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, rent_exact_envelope(&mollusk, &authority)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    let new_len = ext_account_size(ext_len);
+    let envelope = &result.resulting_accounts[1].1;
+    assert_eq!(envelope.data.len(), new_len);
+    assert_eq!(
+        envelope.lamports,
+        mollusk.sysvars.rent.minimum_balance(new_len)
+    );
+    let charged = envelope.lamports - mollusk.sysvars.rent.minimum_balance(Envelope::SIZE);
+    assert_eq!(
+        result.resulting_accounts[0].1.lamports,
+        1_000_000_000 - charged
+    );
+
+    let header = Envelope::ext_aux_header(&envelope.data).unwrap();
+    assert_eq!(header.type_hash, Book::TYPE_HASH);
+    assert_eq!(header.sequence, 0);
+    assert_eq!(Envelope::ext_aux::<Book>(&envelope.data), Some(&[0u64; 64]));
+    assert_eq!(
+        Envelope::from_prefix_bytes(&envelope.data)
+            .unwrap()
+            .authority,
+        authority
+    );
+}
+
+#[test]
+fn test_update_ext_aux_writes_and_enforces_sequence() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let padding = Address::new_unique();
+    let envelope = resized_envelope(&mollusk, &authority, &envelope_pubkey, 512);
+
+    let accounts = |envelope: Account| {
+        vec![
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            (padding, create_funded_account(0)),
+        ]
+    };
+
+    // Write the last level, past anything the fixed aux region could hold.
+    let result = mollusk.process_and_validate_instruction(
+        &update_instruction(
+            &authority,
+            &envelope_pubkey,
+            &padding,
+            1,
+            504,
+            &7u64.to_le_bytes(),
+        ),
+        &accounts(envelope),
+        &[Check::success()],
+    );
+    let envelope = result.resulting_accounts[1].1.clone();
+    assert_eq!(Envelope::ext_aux::<Book>(&envelope.data).unwrap()[63], 7);
+    assert_eq!(
+        Envelope::ext_aux_header(&envelope.data).unwrap().sequence,
+        1
+    );
+
+    mollusk.process_and_validate_instruction(
+        &update_instruction(&authority, &envelope_pubkey, &padding, 1, 0, &[1]),
+        &accounts(envelope.clone()),
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &update_instruction(&authority, &envelope_pubkey, &padding, 2, 510, &[1, 2, 3]),
+        &accounts(envelope.clone()),
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+
+    let mut foreign = accounts(envelope);
+    foreign[0].0 = Address::new_unique();
+    mollusk.process_and_validate_instruction(
+        &update_instruction(&foreign[0].0, &envelope_pubkey, &padding, 2, 0, &[1]),
+        &foreign,
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_update_ext_aux_requires_extension() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let padding = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &update_instruction(&authority, &envelope_pubkey, &padding, 1, 0, &[1]),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (padding, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_resize_same_type_keeps_data_and_new_type_clears_it() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let mut envelope = resized_envelope(&mollusk, &authority, &envelope_pubkey, 256);
+    envelope.data[EXT_AUX_DATA_OFFSET..].fill(0xAB);
+
+    let accounts = |envelope: Account| {
+        [
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            keyed_account_for_system_program(),
+        ]
+    };
+
+    let result = mollusk.process_and_validate_instruction(
+        &resize_instruction(&authority, &envelope_pubkey, 512),
+        &accounts(envelope.clone()),
+        &[Check::success()],
+    );
+    let grown = Envelope::ext_aux_bytes(&result.resulting_accounts[1].1.data).unwrap();
+    assert_eq!(grown.len(), 512);
+    assert!(grown[..256].iter().all(|&b| b == 0xAB));
+    assert!(grown[256..].iter().all(|&b| b == 0));
+
+    let result = mollusk.process_and_validate_instruction(
+        &resize_typed_instruction(&authority, &envelope_pubkey, u64::TYPE_HASH, 256),
+        &accounts(envelope),
+        &[Check::success()],
+    );
+    let data = &result.resulting_accounts[1].1.data;
+    assert_eq!(Envelope::ext_aux::<u64>(data), Some(&0));
+    assert!(Envelope::ext_aux::<Book>(data).is_none());
+    assert!(Envelope::ext_aux_bytes(data)
+        .unwrap()
+        .iter()
+        .all(|&b| b == 0));
+}
+
+#[test]
+fn test_resize_to_zero_removes_extension_and_refunds() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let envelope = resized_envelope(&mollusk, &authority, &envelope_pubkey, 1024);
+    let excess = envelope.lamports - mollusk.sysvars.rent.minimum_balance(Envelope::SIZE);
+
+    let result = mollusk.process_and_validate_instruction(
+        &resize_instruction(&authority, &envelope_pubkey, 0),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    let envelope = &result.resulting_accounts[1].1;
+    assert_eq!(envelope.data.len(), Envelope::SIZE);
+    assert_eq!(
+        envelope.lamports,
+        mollusk.sysvars.rent.minimum_balance(Envelope::SIZE)
+    );
+    assert_eq!(
+        result.resulting_accounts[0].1.lamports,
+        1_000_000_000 + excess
+    );
+    assert!(Envelope::ext_aux_header(&envelope.data).is_none());
+}
+
+#[test]
+fn test_resize_rejects_foreign_authority() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let attacker = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &resize_instruction(&attacker, &envelope_pubkey, 64),
+        &[
+            (attacker, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_fast_path_writes_resized_envelope() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let mut envelope = resized_envelope(&mollusk, &authority, &envelope_pubkey, 256);
+    envelope.data[EXT_AUX_DATA_OFFSET..].fill(0xAB);
+
+    let fast_path = |signer: Address, sequence: u64| {
+        Instruction::new_with_bytes(
+            PROGRAM_ID,
+            &fast_path_instruction_data(0, sequence, &[7, 8, 9]).unwrap(),
+            vec![
+                AccountMeta::new_readonly(signer, true),
+                AccountMeta::new(envelope_pubkey, false),
+            ],
+        )
+    };
+
+    let result = mollusk.process_and_validate_instruction(
+        &fast_path(authority, 1),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope.clone()),
+        ],
+        &[Check::success()],
+    );
+    let data = &result.resulting_accounts[1].1.data;
+    assert_eq!(data.len(), ext_account_size(256));
+    let written = Envelope::from_prefix_bytes(data).unwrap();
+    assert_eq!(written.oracle_state.sequence, 1);
+    assert_eq!(&written.oracle_state.data[..3], &[7, 8, 9]);
+    assert!(data[EXT_AUX_DATA_OFFSET..].iter().all(|&b| b == 0xAB));
+
+    let written = result.resulting_accounts[1].1.clone();
+    mollusk.process_and_validate_instruction(
+        &fast_path(authority, 1),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, written),
+        ],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    let foreign = Address::new_unique();
+    mollusk.process_and_validate_instruction(
+        &fast_path(foreign, 1),
+        &[
+            (foreign, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
@@ -0,0 +1,118 @@
+use c_u_soon::{StructMetadata, TypeHash};
+use c_u_soon_client::build_fast_path_ix;
+use c_u_soon_instruction::{METADATA_HASH_MISMATCH_ERROR, METADATA_SIZE_MISMATCH_ERROR};
+use c_u_soon_test_utils::{fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::Instruction;
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    envelope: Address,
+}
+
+/// An envelope created with zero oracle metadata and never written.
+fn setup() -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("first_write/authority");
+    let envelope = fixture_address("first_write/envelope");
+    runner
+        .fund(authority, 1_000_000_000)
+        .set_account(envelope, EnvelopeFixture::new(authority).account());
+    Setup {
+        runner,
+        authority,
+        envelope,
+    }
+}
+
+impl Setup {
+    fn write(&self, metadata: StructMetadata, sequence: u64, payload: &[u8]) -> Instruction {
+        build_fast_path_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            &self.envelope,
+            metadata.as_u64(),
+            sequence,
+            payload,
+        )
+        .unwrap()
+    }
+
+    fn metadata(&self) -> StructMetadata {
+        self.runner
+            .envelope(&self.envelope)
+            .oracle_state
+            .oracle_metadata
+    }
+}
+
+#[test]
+fn test_first_write_adopts_metadata() {
+    let mut s = setup();
+    assert_eq!(s.metadata(), StructMetadata::ZERO);
+
+    s.runner
+        .expect_ok(&s.write(u64::METADATA, 1, &7u64.to_le_bytes()));
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.oracle_state.oracle_metadata, u64::METADATA);
+    assert_eq!(envelope.oracle::<u64>(), Some(&7));
+
+    s.runner
+        .expect_ok(&s.write(u64::METADATA, 2, &8u64.to_le_bytes()));
+    assert_eq!(s.runner.envelope(&s.envelope).oracle::<u64>(), Some(&8));
+}
+
+#[test]
+fn test_adopted_metadata_is_fixed() {
+    let mut s = setup();
+    s.runner
+        .expect_ok(&s.write(u64::METADATA, 1, &7u64.to_le_bytes()));
+
+    // The losing side of two racing first writes fails as any mismatched write does,
+    // whatever its sequence.
+    s.runner.expect_err(
+        &s.write(u32::METADATA, 2, &9u32.to_le_bytes()),
+        ProgramError::Custom(METADATA_SIZE_MISMATCH_ERROR),
+    );
+    s.runner.expect_err(
+        &s.write(i64::METADATA, 2, &9i64.to_le_bytes()),
+        ProgramError::Custom(METADATA_HASH_MISMATCH_ERROR),
+    );
+    // Zero metadata does not reset it either.
+    s.runner.expect_err(
+        &s.write(StructMetadata::ZERO, 2, &[]),
+        ProgramError::Custom(METADATA_SIZE_MISMATCH_ERROR),
+    );
+    assert_eq!(s.metadata(), u64::METADATA);
+    assert_eq!(s.runner.envelope(&s.envelope).oracle_state.sequence, 1);
+}
+
+#[test]
+fn test_replayed_first_write_is_stale() {
+    let mut s = setup();
+    let first = s.write(u64::METADATA, 1, &7u64.to_le_bytes());
+    s.runner.expect_ok(&first);
+    s.runner
+        .expect_err(&first, ProgramError::InvalidInstructionData);
+}
+
+#[test]
+fn test_failed_first_write_adopts_nothing() {
+    let mut s = setup();
+    s.runner.expect_err(
+        &s.write(u64::METADATA, 0, &7u64.to_le_bytes()),
+        ProgramError::InvalidInstructionData,
+    );
+    assert_eq!(s.metadata(), StructMetadata::ZERO);
+
+    // A write carrying zero metadata matches and leaves the envelope untyped, so a later
+    // write can still adopt a type.
+    s.runner
+        .expect_ok(&s.write(StructMetadata::ZERO, 1, &[1, 2, 3]));
+    assert_eq!(s.metadata(), StructMetadata::ZERO);
+    s.runner
+        .expect_ok(&s.write(u32::METADATA, 2, &5u32.to_le_bytes()));
+    assert_eq!(s.metadata(), u32::METADATA);
+    assert_eq!(s.runner.envelope(&s.envelope).oracle::<u32>(), Some(&5));
+}
@@ -0,0 +1,327 @@
+mod common;
+
+use c_u_soon::{Envelope, GroupAnchor};
+use c_u_soon_client::{group_commit_instruction_data, set_group_members_instruction_data};
+use c_u_soon_instruction::GroupMemberUpdate;
+use common::{
+    create_existing_envelope, create_funded_account, create_group_anchor, find_group_anchor_pda,
+    new_mollusk, new_mollusk_silent, PROGRAM_ID, PROGRAM_PATH,
+};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_system_interface::program as system_program;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn set_group_members_instruction(
+    authority: &Address,
+    anchor: &Address,
+    group_id: u64,
+    bump: u8,
+    members: &[Address],
+) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(*anchor, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    metas.extend(members.iter().map(|m| AccountMeta::new_readonly(*m, false)));
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_group_members_instruction_data(group_id, bump).unwrap(),
+        metas,
+    )
+}
+
+fn group_commit_instruction(
+    authority: &Address,
+    anchor: &Address,
+    members: &[Address],
+    sequence: u64,
+    payloads: &[&[u8]],
+) -> Instruction {
+    let updates: Vec<GroupMemberUpdate> = payloads
+        .iter()
+        .map(|p| GroupMemberUpdate {
+            oracle_meta: 0,
+            payload: p.to_vec(),
+        })
+        .collect();
+    let mut metas = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*anchor, false),
+    ];
+    metas.extend(members.iter().map(|m| AccountMeta::new(*m, false)));
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &group_commit_instruction_data(sequence, &updates).unwrap(),
+        metas,
+    )
+}
+
+struct GroupSetup {
+    authority: Address,
+    anchor: Address,
+    members: Vec<Address>,
+    accounts: Vec<(Address, Account)>,
+}
+
+/// Group of `n` envelopes at oracle sequence `envelope_seq`, anchor at `anchor_seq`.
+fn group_setup(n: usize, envelope_seq: u64, anchor_seq: u64) -> GroupSetup {
+    let authority = Address::new_unique();
+    let (anchor, _) = find_group_anchor_pda(&authority, 1);
+    let members: Vec<Address> = (0..n).map(|_| Address::new_unique()).collect();
+
+    let mut accounts = vec![
+        (authority, create_funded_account(0)),
+        (
+            anchor,
+            create_group_anchor(&authority, 1, anchor_seq, &members),
+        ),
+    ];
+    accounts.extend(
+        members
+            .iter()
+            .map(|m| (*m, create_existing_envelope(&authority, envelope_seq))),
+    );
+
+    GroupSetup {
+        authority,
+        anchor,
+        members,
+        accounts,
+    }
+}
+
+// ============================================================================
+// SetGroupMembers
+// ============================================================================
+
+#[test]
+fn test_set_group_members_creates_anchor() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let (anchor, bump) = find_group_anchor_pda(&authority, 7);
+    let members = [Address::new_unique(), Address::new_unique()];
+
+    let ix = set_group_members_instruction(&authority, &anchor, 7, bump, &members);
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (anchor, create_funded_account(0)),
+            keyed_account_for_system_program(),
+            (members[0], create_existing_envelope(&authority, 0)),
+            (members[1], create_existing_envelope(&authority, 0)),
+        ],
+        &[Check::success()],
+    );
+
+    let anchor_account = &result.resulting_accounts[1].1;
+    assert_eq!(anchor_account.owner, PROGRAM_ID);
+    let group: &GroupAnchor = bytemuck::from_bytes(&anchor_account.data);
+    assert_eq!(group.authority, authority);
+    assert_eq!((group.group_id, group.bump, group.sequence), (7, bump, 0));
+    assert_eq!(group.active(), &members);
+}
+
+#[test]
+fn test_set_group_members_keeps_sequence() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let setup = group_setup(2, 0, 40);
+    let (_, bump) = find_group_anchor_pda(&setup.authority, 1);
+    let new_member = Address::new_unique();
+
+    let ix = set_group_members_instruction(&setup.authority, &setup.anchor, 1, bump, &[new_member]);
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            setup.accounts[0].clone(),
+            setup.accounts[1].clone(),
+            keyed_account_for_system_program(),
+            (new_member, create_existing_envelope(&setup.authority, 0)),
+        ],
+        &[Check::success()],
+    );
+
+    let group: &GroupAnchor = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert_eq!(group.sequence, 40);
+    assert_eq!(group.active(), &[new_member]);
+    assert_eq!(group.members[1], Address::default(), "old members cleared");
+}
+
+#[test]
+fn test_set_group_members_rejects_duplicate_member() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let (anchor, bump) = find_group_anchor_pda(&authority, 1);
+    let member = Address::new_unique();
+
+    let ix = set_group_members_instruction(&authority, &anchor, 1, bump, &[member, member]);
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (anchor, create_funded_account(0)),
+            keyed_account_for_system_program(),
+            (member, create_existing_envelope(&authority, 0)),
+        ],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_set_group_members_rejects_foreign_envelope() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let (anchor, bump) = find_group_anchor_pda(&authority, 1);
+    let member = Address::new_unique();
+
+    let ix = set_group_members_instruction(&authority, &anchor, 1, bump, &[member]);
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (anchor, create_funded_account(0)),
+            keyed_account_for_system_program(),
+            (member, create_existing_envelope(&Address::new_unique(), 0)),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_set_group_members_rejects_wrong_pda() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let (_, bump) = find_group_anchor_pda(&authority, 1);
+    let wrong_anchor = Address::new_unique();
+    let member = Address::new_unique();
+
+    let ix = set_group_members_instruction(&authority, &wrong_anchor, 1, bump, &[member]);
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (wrong_anchor, create_funded_account(0)),
+            keyed_account_for_system_program(),
+            (member, create_existing_envelope(&authority, 0)),
+        ],
+        &[Check::err(ProgramError::InvalidSeeds)],
+    );
+}
+
+// ============================================================================
+// GroupCommit
+// ============================================================================
+
+#[test]
+fn test_group_commit_updates_all_members() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let setup = group_setup(3, 5, 5);
+
+    let ix = group_commit_instruction(
+        &setup.authority,
+        &setup.anchor,
+        &setup.members,
+        9,
+        &[&[1; 8], &[2; 16], &[3; 4]],
+    );
+    let result =
+        mollusk.process_and_validate_instruction(&ix, &setup.accounts, &[Check::success()]);
+
+    let group: &GroupAnchor = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert_eq!(group.sequence, 9);
+    for (i, len) in [8usize, 16, 4].into_iter().enumerate() {
+        let (address, account) = &result.resulting_accounts[2 + i];
+        let envelope: &Envelope = bytemuck::from_bytes(&account.data);
+        assert_eq!(envelope.oracle_state.sequence, 9);
+        assert_eq!(
+            envelope.oracle_state.data[..len],
+            vec![i as u8 + 1; len][..]
+        );
+        assert_eq!(envelope.oracle_state.data[len], 0);
+        assert!(group.in_batch(address, envelope));
+    }
+}
+
+#[test]
+fn test_group_commit_rejects_stale_anchor_sequence() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let setup = group_setup(2, 0, 9);
+
+    let ix = group_commit_instruction(
+        &setup.authority,
+        &setup.anchor,
+        &setup.members,
+        9,
+        &[&[1; 8], &[2; 8]],
+    );
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &setup.accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_group_commit_rejects_member_ahead_of_sequence() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let mut setup = group_setup(2, 0, 0);
+    // A fast-path update moved the second member past the commit sequence.
+    bytemuck::from_bytes_mut::<Envelope>(&mut setup.accounts[3].1.data)
+        .oracle_state
+        .sequence = 20;
+
+    let ix = group_commit_instruction(
+        &setup.authority,
+        &setup.anchor,
+        &setup.members,
+        10,
+        &[&[1; 8], &[2; 8]],
+    );
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &setup.accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_group_commit_rejects_reordered_members() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let setup = group_setup(2, 0, 0);
+    let reordered = [setup.members[1], setup.members[0]];
+
+    let ix = group_commit_instruction(
+        &setup.authority,
+        &setup.anchor,
+        &reordered,
+        1,
+        &[&[1; 8], &[2; 8]],
+    );
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &setup.accounts,
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_group_commit_rejects_wrong_authority() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let mut setup = group_setup(1, 0, 0);
+    let attacker = Address::new_unique();
+    setup.accounts[0] = (attacker, create_funded_account(0));
+
+    let ix = group_commit_instruction(&attacker, &setup.anchor, &setup.members, 1, &[&[1; 8]]);
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &setup.accounts,
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
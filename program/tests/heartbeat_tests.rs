@@ -0,0 +1,124 @@
+use c_u_soon::TypeHash;
+use c_u_soon_client::{build_fast_path_ix, build_heartbeat_ix};
+use c_u_soon_instruction::ENVELOPE_PAUSED_ERROR;
+use c_u_soon_test_utils::{attacks, fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::Instruction;
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    cranker: Address,
+    envelope: Address,
+}
+
+/// A `u64` envelope holding 7 at sequence 1, and a funded cranker unrelated to it.
+fn setup(fixture: impl FnOnce(EnvelopeFixture) -> EnvelopeFixture) -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("heartbeat/authority");
+    let cranker = fixture_address("heartbeat/cranker");
+    let envelope = fixture_address("heartbeat/envelope");
+    runner
+        .fund(authority, 1_000_000_000)
+        .fund(cranker, 1_000_000_000)
+        .set_account(
+            envelope,
+            fixture(
+                EnvelopeFixture::new(authority)
+                    .oracle(u64::METADATA, &7u64.to_le_bytes())
+                    .sequence(1),
+            )
+            .account(),
+        );
+    Setup {
+        runner,
+        authority,
+        cranker,
+        envelope,
+    }
+}
+
+impl Setup {
+    fn heartbeat(&self, sequence: u64) -> Instruction {
+        build_heartbeat_ix(&DEFAULT_PROGRAM_ID, &self.cranker, &self.envelope, sequence).unwrap()
+    }
+
+    fn write(&self, sequence: u64, value: u64) -> Instruction {
+        build_fast_path_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            &self.envelope,
+            u64::METADATA.as_u64(),
+            sequence,
+            &value.to_le_bytes(),
+        )
+        .unwrap()
+    }
+}
+
+#[test]
+fn test_any_signer_heartbeats_unchanged_value() {
+    let mut s = setup(|f| f);
+    assert_eq!(s.runner.envelope(&s.envelope).last_live_slot(), None);
+
+    s.runner.mollusk_mut().warp_to_slot(50);
+    s.runner.expect_ok(&s.heartbeat(1));
+    let env = s.runner.envelope(&s.envelope);
+    assert_eq!(env.last_heartbeat_slot(), Some(50));
+    assert_eq!(env.last_live_slot(), Some(50));
+    assert_eq!(env.oracle_state.sequence, 1, "no sequence burned");
+    assert_eq!(env.oracle::<u64>(), Some(&7));
+
+    s.runner.mollusk_mut().warp_to_slot(80);
+    s.runner.expect_ok(&s.heartbeat(1));
+    assert_eq!(
+        s.runner.envelope(&s.envelope).last_heartbeat_slot(),
+        Some(80)
+    );
+}
+
+#[test]
+fn test_heartbeat_rejects_replaced_value() {
+    let mut s = setup(|f| f);
+    s.runner.expect_ok(&s.write(2, 9));
+    s.runner
+        .expect_err(&s.heartbeat(1), ProgramError::InvalidInstructionData);
+    s.runner
+        .expect_err(&s.heartbeat(3), ProgramError::InvalidInstructionData);
+    assert_eq!(s.runner.envelope(&s.envelope).last_heartbeat_slot(), None);
+
+    s.runner.expect_ok(&s.heartbeat(2));
+}
+
+#[test]
+fn test_heartbeat_requires_signer_and_owned_envelope() {
+    let mut s = setup(|f| f);
+    attacks::assert_requires_signer(
+        &mut s.runner,
+        &s.heartbeat(1),
+        0,
+        ProgramError::MissingRequiredSignature,
+    );
+
+    let foreign = fixture_address("heartbeat/foreign");
+    s.runner.set_account(
+        foreign,
+        EnvelopeFixture::new(s.authority)
+            .owner(fixture_address("heartbeat/other_program"))
+            .account(),
+    );
+    attacks::assert_rejects_impostor(
+        &mut s.runner,
+        &s.heartbeat(0),
+        1,
+        foreign,
+        ProgramError::IncorrectProgramId,
+    );
+}
+
+#[test]
+fn test_heartbeat_rejects_paused_envelope() {
+    let mut s = setup(EnvelopeFixture::paused);
+    s.runner
+        .expect_err(&s.heartbeat(1), ProgramError::Custom(ENVELOPE_PAUSED_ERROR));
+}
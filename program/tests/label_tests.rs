@@ -0,0 +1,134 @@
+mod common;
+
+use bytemuck::Zeroable;
+use c_u_soon::EnvelopeLabel;
+use c_u_soon_client::set_label_instruction_data;
+use common::{
+    create_existing_envelope, create_funded_account, find_label_pda, new_mollusk,
+    new_mollusk_silent, PROGRAM_ID, PROGRAM_PATH,
+};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_system_interface::program as system_program;
+
+fn set_label_instruction(
+    authority: &Address,
+    envelope_pubkey: &Address,
+    label_pubkey: &Address,
+    bump: u8,
+    label: &str,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_label_instruction_data(bump, label).unwrap(),
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(*envelope_pubkey, false),
+            AccountMeta::new(*label_pubkey, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+#[test]
+fn test_set_label_creates_label_account() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (label_pubkey, bump) = find_label_pda(&envelope_pubkey);
+
+    let ix = set_label_instruction(&authority, &envelope_pubkey, &label_pubkey, bump, "SOL/USD");
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (label_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    let label_account = &result.resulting_accounts[2].1;
+    assert_eq!(label_account.owner, PROGRAM_ID);
+    let label: &EnvelopeLabel = bytemuck::from_bytes(&label_account.data);
+    assert_eq!(label.envelope, envelope_pubkey);
+    assert_eq!(label.bump, bump);
+    assert_eq!(label.as_str(), Some("SOL/USD"));
+}
+
+#[test]
+fn test_set_label_rejects_second_call() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (label_pubkey, bump) = find_label_pda(&envelope_pubkey);
+
+    let mut existing = EnvelopeLabel::zeroed();
+    existing.envelope = envelope_pubkey;
+    existing.label[..7].copy_from_slice(b"SOL/USD");
+    existing.bump = bump;
+    let label_account = Account {
+        lamports: 1_000_000_000,
+        data: bytemuck::bytes_of(&existing).to_vec(),
+        owner: PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    let ix = set_label_instruction(&authority, &envelope_pubkey, &label_pubkey, bump, "BTC/USD");
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (label_pubkey, label_account),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::AccountAlreadyInitialized)],
+    );
+}
+
+#[test]
+fn test_set_label_rejects_wrong_authority() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let attacker = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (label_pubkey, bump) = find_label_pda(&envelope_pubkey);
+
+    let ix = set_label_instruction(&attacker, &envelope_pubkey, &label_pubkey, bump, "FAKE");
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (attacker, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (label_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_set_label_rejects_wrong_pda() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (_, bump) = find_label_pda(&envelope_pubkey);
+    let wrong_label = Address::new_unique();
+
+    let ix = set_label_instruction(&authority, &envelope_pubkey, &wrong_label, bump, "SOL/USD");
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (wrong_label, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::InvalidSeeds)],
+    );
+}
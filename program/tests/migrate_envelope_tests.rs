@@ -0,0 +1,167 @@
+use c_u_soon::{Envelope, TypeHash, LEGACY_ENVELOPE_SIZE};
+use c_u_soon_client::{build_fast_path_ix, build_migrate_envelope_ix};
+use c_u_soon_test_utils::{fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::{account::Account, instruction::Instruction};
+
+type Aux = [u8; 16];
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    payer: Address,
+    envelope: Address,
+}
+
+/// A `u64` oracle holding 7 at sequence 3 with `Aux` bytes `[1; 16]`, cut to the first
+/// `len` bytes and holding the rent-exempt minimum for that length, as an envelope created
+/// by an older build would be.
+fn setup(len: usize) -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("migrate_envelope/authority");
+    let payer = fixture_address("migrate_envelope/payer");
+    let envelope = fixture_address("migrate_envelope/envelope");
+    let publisher = fixture_address("migrate_envelope/publisher");
+
+    let mut account = EnvelopeFixture::new(authority)
+        .oracle(u64::METADATA, &7u64.to_le_bytes())
+        .sequence(3)
+        .aux(Aux::METADATA, &[1; 16])
+        .aux_sequences(2, 0)
+        .publisher(publisher)
+        .account();
+    account.data.truncate(len);
+    account.lamports = runner.mollusk_mut().sysvars.rent.minimum_balance(len);
+
+    runner
+        .fund(authority, 1_000_000_000)
+        .fund(payer, 1_000_000_000)
+        .set_account(envelope, account);
+    Setup {
+        runner,
+        authority,
+        payer,
+        envelope,
+    }
+}
+
+impl Setup {
+    fn migrate(&self) -> Instruction {
+        build_migrate_envelope_ix(&DEFAULT_PROGRAM_ID, &self.payer, &self.envelope).unwrap()
+    }
+
+    fn write(&self, sequence: u64) -> Instruction {
+        build_fast_path_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            &self.envelope,
+            u64::METADATA.as_u64(),
+            sequence,
+            &9u64.to_le_bytes(),
+        )
+        .unwrap()
+    }
+
+    fn account(&self) -> &Account {
+        self.runner.account(&self.envelope).unwrap()
+    }
+}
+
+#[test]
+fn test_migrate_grows_legacy_envelope_and_zeroes_new_fields() {
+    let mut s = setup(LEGACY_ENVELOPE_SIZE);
+    let before = s.account().data.clone();
+    let payer_lamports = s.runner.account(&s.payer).unwrap().lamports;
+
+    s.runner.expect_ok(&s.migrate());
+
+    let rent = s.runner.mollusk_mut().sysvars.rent.clone();
+    let account = s.account();
+    assert_eq!(account.data.len(), Envelope::SIZE);
+    assert_eq!(account.data[..LEGACY_ENVELOPE_SIZE], before[..]);
+    assert_eq!(account.lamports, rent.minimum_balance(Envelope::SIZE));
+    assert_eq!(
+        s.runner.account(&s.payer).unwrap().lamports,
+        payer_lamports
+            - (rent.minimum_balance(Envelope::SIZE) - rent.minimum_balance(LEGACY_ENVELOPE_SIZE))
+    );
+
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.authority, s.authority);
+    assert_eq!(envelope.oracle::<u64>(), Some(&7));
+    assert_eq!(envelope.oracle_state.sequence, 3);
+    assert_eq!(envelope.aux::<Aux>(), Some(&[1; 16]));
+    assert_eq!(envelope.publisher, Address::zeroed());
+    assert_eq!(envelope.heartbeat_slot, 0);
+    assert_eq!(envelope.write_hook(), None);
+}
+
+#[test]
+fn test_legacy_envelope_is_writable_only_after_migrating() {
+    let mut s = setup(LEGACY_ENVELOPE_SIZE);
+    s.runner
+        .expect_err(&s.write(4), ProgramError::InvalidAccountData);
+
+    s.runner.expect_ok(&s.migrate());
+    s.runner.expect_ok(&s.write(4));
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.oracle::<u64>(), Some(&9));
+    assert_eq!(envelope.oracle_state.sequence, 4);
+}
+
+#[test]
+fn test_migrate_keeps_fields_a_partial_layout_already_has() {
+    let partial = core::mem::offset_of!(Envelope, heartbeat_slot);
+    let mut s = setup(partial);
+    let publisher = fixture_address("migrate_envelope/publisher");
+
+    s.runner.expect_ok(&s.migrate());
+    assert_eq!(s.account().data.len(), Envelope::SIZE);
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.publisher, publisher);
+    assert_eq!(envelope.heartbeat_slot, 0);
+}
+
+#[test]
+fn test_migrate_grows_envelope_without_mutation_marker() {
+    let mut s = setup(core::mem::offset_of!(Envelope, mutation_marker));
+    let publisher = fixture_address("migrate_envelope/publisher");
+
+    s.runner.expect_ok(&s.migrate());
+    assert_eq!(s.account().data.len(), Envelope::SIZE);
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.publisher, publisher);
+    assert_eq!(envelope.mutation_marker, 0);
+}
+
+#[test]
+fn test_migrate_rejects_current_and_short_envelopes() {
+    let mut s = setup(Envelope::SIZE);
+    s.runner
+        .expect_err(&s.migrate(), ProgramError::InvalidAccountData);
+
+    let mut s = setup(LEGACY_ENVELOPE_SIZE);
+    s.runner.expect_ok(&s.migrate());
+    s.runner
+        .expect_err(&s.migrate(), ProgramError::InvalidAccountData);
+
+    let mut s = setup(LEGACY_ENVELOPE_SIZE - 1);
+    s.runner
+        .expect_err(&s.migrate(), ProgramError::InvalidAccountData);
+}
+
+#[test]
+fn test_migrate_checks_owner_and_payer_signature() {
+    let mut s = setup(LEGACY_ENVELOPE_SIZE);
+    let mut foreign = s.account().clone();
+    foreign.owner = Address::new_unique();
+    s.runner.set_account(s.envelope, foreign);
+    s.runner
+        .expect_err(&s.migrate(), ProgramError::IncorrectProgramId);
+
+    let mut s = setup(LEGACY_ENVELOPE_SIZE);
+    let mut unsigned = s.migrate();
+    unsigned.accounts[0].is_signer = false;
+    s.runner
+        .expect_err(&unsigned, ProgramError::MissingRequiredSignature);
+}
@@ -3,14 +3,18 @@ mod common;
 use c_u_soon::{Envelope, Mask};
 use c_u_soon_client::{
     update_auxiliary_delegated_multi_range_instruction_data,
+    update_auxiliary_multi_range_force_instruction_data,
     update_auxiliary_multi_range_instruction_data,
 };
-use c_u_soon_instruction::WriteSpec;
+use c_u_soon_instruction::{
+    SlowPathInstruction, WriteSpec, MAX_WRITE_RANGES, METADATA_SIZE_MISMATCH_ERROR,
+    TOO_MANY_RANGES_ERROR,
+};
 use common::{
     create_delegated_envelope, create_existing_envelope, create_funded_account, new_mollusk,
     new_mollusk_silent, PROGRAM_ID, PROGRAM_PATH, TEST_META_U64, TEST_TYPE_SIZE,
 };
-use mollusk_svm::result::Check;
+use mollusk_svm::result::{Check, InstructionResult};
 use pinocchio::{error::ProgramError, Address};
 use solana_sdk::instruction::{AccountMeta, Instruction};
 
@@ -38,7 +42,7 @@ fn multi_range_instruction(
 ) -> Instruction {
     Instruction::new_with_bytes(
         PROGRAM_ID,
-        &update_auxiliary_multi_range_instruction_data(metadata, sequence, ranges),
+        &update_auxiliary_multi_range_instruction_data(metadata, sequence, ranges).unwrap(),
         vec![
             AccountMeta::new_readonly(*authority, true),
             AccountMeta::new(*envelope_pubkey, false),
@@ -57,7 +61,8 @@ fn delegated_multi_range_instruction(
 ) -> Instruction {
     Instruction::new_with_bytes(
         PROGRAM_ID,
-        &update_auxiliary_delegated_multi_range_instruction_data(metadata, sequence, ranges),
+        &update_auxiliary_delegated_multi_range_instruction_data(metadata, sequence, ranges)
+            .unwrap(),
         vec![
             AccountMeta::new_readonly(*delegation_auth, true),
             AccountMeta::new(*envelope_pubkey, false),
@@ -346,7 +351,9 @@ fn test_multi_range_reject_bad_metadata() {
             (envelope_pubkey, envelope),
             (pda, create_funded_account(0)),
         ],
-        &[Check::err(ProgramError::InvalidInstructionData)],
+        &[Check::err(ProgramError::Custom(
+            METADATA_SIZE_MISMATCH_ERROR,
+        ))],
     );
 }
 
@@ -405,7 +412,7 @@ fn test_multi_range_reject_missing_signer() {
     let ranges = make_specs(&[(0, &[0xAA])]);
     let ix = Instruction::new_with_bytes(
         PROGRAM_ID,
-        &update_auxiliary_multi_range_instruction_data(TEST_META_U64, 1, &ranges),
+        &update_auxiliary_multi_range_instruction_data(TEST_META_U64, 1, &ranges).unwrap(),
         vec![
             AccountMeta::new_readonly(authority, false), // not a signer
             AccountMeta::new(envelope_pubkey, false),
@@ -494,9 +501,10 @@ fn test_multi_range_reject_no_delegation() {
 // Authority Multi-Range — Overlap
 // ============================================================================
 
-#[test]
-fn test_multi_range_overlap_last_write_wins() {
-    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+/// Run an authority multi-range update of `ranges`, serialized as given: the client
+/// builder refuses overlapping ranges.
+fn process_raw_multi_range(ranges: Vec<WriteSpec>, checks: &[Check]) -> InstructionResult {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
     let authority = Address::new_unique();
     let delegation_auth = Address::new_unique();
     let pda = Address::new_unique();
@@ -509,75 +517,66 @@ fn test_multi_range_overlap_last_write_wins() {
         Mask::ALL_WRITABLE,
     );
 
-    // Two ranges at same offset: second overwrites first
-    let ranges = make_specs(&[(0, &[0xAA; 4]), (0, &[0xBB; 4])]);
-    let ix = multi_range_instruction(
-        &authority,
-        &envelope_pubkey,
-        &pda,
-        TEST_META_U64,
-        1,
-        &ranges,
+    let data = wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryMultiRange {
+        metadata: TEST_META_U64,
+        sequence: 1,
+        ranges,
+    })
+    .unwrap();
+    let ix = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(pda, true),
+        ],
     );
 
-    let result = mollusk.process_and_validate_instruction(
+    mollusk.process_and_validate_instruction(
         &ix,
         &[
             (authority, create_funded_account(1_000_000_000)),
             (envelope_pubkey, envelope),
             (pda, create_funded_account(0)),
         ],
-        &[Check::success()],
-    );
+        checks,
+    )
+}
 
-    let env: &Envelope = bytemuck::from_bytes(
-        &result.resulting_accounts[1].1.data[..core::mem::size_of::<Envelope>()],
+#[test]
+fn test_multi_range_rejects_same_offset() {
+    // The final bytes would depend on which range is applied last.
+    process_raw_multi_range(
+        make_specs(&[(0, &[0xAA; 4]), (0, &[0xBB; 4])]),
+        &[Check::err(ProgramError::InvalidInstructionData)],
     );
-    assert_eq!(&env.auxiliary_data[..4], &[0xBB; 4]);
 }
 
 #[test]
-fn test_multi_range_partial_overlap() {
-    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
-    let authority = Address::new_unique();
-    let delegation_auth = Address::new_unique();
-    let pda = Address::new_unique();
-    let envelope_pubkey = Address::new_unique();
-
-    let envelope = create_delegated_envelope(
-        &authority,
-        &delegation_auth,
-        Mask::ALL_BLOCKED,
-        Mask::ALL_WRITABLE,
+fn test_multi_range_rejects_partial_overlap() {
+    // [0..4) and [2..6) share bytes 2 and 3, in either order.
+    process_raw_multi_range(
+        make_specs(&[(0, &[0xAA; 4]), (2, &[0xBB; 4])]),
+        &[Check::err(ProgramError::InvalidInstructionData)],
     );
-
-    // Range 1: [0..4) = 0xAA, Range 2: [2..6) = 0xBB
-    let ranges = make_specs(&[(0, &[0xAA; 4]), (2, &[0xBB; 4])]);
-    let ix = multi_range_instruction(
-        &authority,
-        &envelope_pubkey,
-        &pda,
-        TEST_META_U64,
-        1,
-        &ranges,
+    process_raw_multi_range(
+        make_specs(&[(2, &[0xBB; 4]), (0, &[0xAA; 4])]),
+        &[Check::err(ProgramError::InvalidInstructionData)],
     );
+}
 
-    let result = mollusk.process_and_validate_instruction(
-        &ix,
-        &[
-            (authority, create_funded_account(1_000_000_000)),
-            (envelope_pubkey, envelope),
-            (pda, create_funded_account(0)),
-        ],
+#[test]
+fn test_multi_range_accepts_adjacent_and_unsorted() {
+    let result = process_raw_multi_range(
+        make_specs(&[(4, &[0xBB; 4]), (0, &[0xAA; 4])]),
         &[Check::success()],
     );
-
     let env: &Envelope = bytemuck::from_bytes(
         &result.resulting_accounts[1].1.data[..core::mem::size_of::<Envelope>()],
     );
-    // [0..2) = AA from first range, [2..6) = BB from second range
-    assert_eq!(&env.auxiliary_data[..2], &[0xAA; 2]);
-    assert_eq!(&env.auxiliary_data[2..6], &[0xBB; 4]);
+    assert_eq!(&env.auxiliary_data[..4], &[0xAA; 4]);
+    assert_eq!(&env.auxiliary_data[4..8], &[0xBB; 4]);
 }
 
 // ============================================================================
@@ -934,7 +933,8 @@ fn test_multi_range_reject_trailing_data() {
 
     // Build valid wincode data, then append garbage
     let ranges = make_specs(&[(0, &[0xAA])]);
-    let mut ix_data = update_auxiliary_multi_range_instruction_data(TEST_META_U64, 1, &ranges);
+    let mut ix_data =
+        update_auxiliary_multi_range_instruction_data(TEST_META_U64, 1, &ranges).unwrap();
     ix_data.push(0xFF); // trailing garbage
 
     let ix = Instruction::new_with_bytes(
@@ -1160,3 +1160,293 @@ fn test_delegated_multi_range_blocked_byte_unchanged_succeeds() {
     assert_eq!(env.auxiliary_data[15], 0x77);
     assert_eq!(env.auxiliary_data[17], 0xDD);
 }
+
+// ============================================================================
+// Range Count Cap
+// ============================================================================
+
+/// `count` ranges of 12 bytes each, back to back from offset 0. At `MAX_WRITE_RANGES` this
+/// covers 192 of the 200 typed bytes: the most mask checks and copying one update can ask for.
+fn tiled_specs(count: usize) -> Vec<WriteSpec> {
+    (0..count)
+        .map(|i| WriteSpec {
+            offset: (i * 12) as u8,
+            data: vec![i as u8 + 1; 12],
+        })
+        .collect()
+}
+
+#[test]
+fn test_multi_range_max_ranges_boundary() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let pda = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let accounts = [
+        (authority, create_funded_account(1_000_000_000)),
+        (
+            envelope_pubkey,
+            create_delegated_envelope(
+                &authority,
+                &delegation_auth,
+                Mask::ALL_BLOCKED,
+                Mask::ALL_WRITABLE,
+            ),
+        ),
+        (pda, create_funded_account(0)),
+    ];
+
+    let ranges = tiled_specs(MAX_WRITE_RANGES);
+    let result = mollusk.process_and_validate_instruction(
+        &multi_range_instruction(
+            &authority,
+            &envelope_pubkey,
+            &pda,
+            TEST_META_U64,
+            1,
+            &ranges,
+        ),
+        &accounts,
+        &[Check::success()],
+    );
+    let env: &Envelope = bytemuck::from_bytes(
+        &result.resulting_accounts[1].1.data[..core::mem::size_of::<Envelope>()],
+    );
+    assert_eq!(env.auxiliary_data[0], 1);
+    assert_eq!(
+        env.auxiliary_data[12 * MAX_WRITE_RANGES - 1],
+        MAX_WRITE_RANGES as u8
+    );
+
+    // One more range is rejected before anything is written. The client refuses to build
+    // it, so serialize the instruction directly.
+    assert!(update_auxiliary_multi_range_instruction_data(
+        TEST_META_U64,
+        1,
+        &tiled_specs(MAX_WRITE_RANGES + 1)
+    )
+    .is_err());
+    let data = wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryMultiRange {
+        metadata: TEST_META_U64,
+        sequence: 1,
+        ranges: tiled_specs(MAX_WRITE_RANGES + 1),
+    })
+    .unwrap();
+    let ix = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(pda, true),
+        ],
+    );
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(ProgramError::Custom(TOO_MANY_RANGES_ERROR))],
+    );
+}
+
+#[test]
+fn test_delegated_multi_range_rejects_too_many_ranges() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let padding = Address::new_unique();
+
+    let data = wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange {
+        metadata: TEST_META_U64,
+        sequence: 1,
+        ranges: tiled_specs(MAX_WRITE_RANGES + 1),
+    })
+    .unwrap();
+    let ix = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new_readonly(delegation_auth, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(padding, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (delegation_auth, create_funded_account(1_000_000_000)),
+            (
+                envelope_pubkey,
+                create_delegated_envelope(
+                    &authority,
+                    &delegation_auth,
+                    Mask::ALL_WRITABLE,
+                    Mask::ALL_BLOCKED,
+                ),
+            ),
+            (padding, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::Custom(TOO_MANY_RANGES_ERROR))],
+    );
+}
+
+// ============================================================================
+// Multi-Range Force
+// ============================================================================
+
+fn force_multi_range_instruction(
+    authority: &Address,
+    envelope_pubkey: &Address,
+    delegation_auth: &Address,
+    authority_sequence: u64,
+    program_sequence: u64,
+    ranges: &[WriteSpec],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &update_auxiliary_multi_range_force_instruction_data(
+            TEST_META_U64,
+            authority_sequence,
+            program_sequence,
+            ranges,
+        )
+        .unwrap(),
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*envelope_pubkey, false),
+            AccountMeta::new_readonly(*delegation_auth, true),
+        ],
+    )
+}
+
+#[test]
+fn test_force_multi_range_ignores_masks_and_sets_both_sequences() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let mut envelope = create_delegated_envelope(
+        &authority,
+        &delegation_auth,
+        Mask::ALL_BLOCKED,
+        Mask::ALL_BLOCKED,
+    );
+    let env: &mut Envelope = bytemuck::from_bytes_mut(&mut envelope.data);
+    env.auxiliary_data[..TEST_TYPE_SIZE].fill(0x11);
+
+    let ranges = make_specs(&[(0, &[0xAA; 4]), (100, &[0xBB; 2])]);
+    let ix = force_multi_range_instruction(
+        &authority,
+        &envelope_pubkey,
+        &delegation_auth,
+        7,
+        9,
+        &ranges,
+    );
+
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            (delegation_auth, create_funded_account(1_000_000_000)),
+        ],
+        &[Check::success()],
+    );
+
+    let env: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert_eq!(&env.auxiliary_data[..4], &[0xAA; 4]);
+    assert_eq!(&env.auxiliary_data[100..102], &[0xBB; 2]);
+    assert!(env.auxiliary_data[4..100].iter().all(|&b| b == 0x11));
+    assert!(env.auxiliary_data[102..TEST_TYPE_SIZE]
+        .iter()
+        .all(|&b| b == 0x11));
+    assert_eq!(env.authority_aux_sequence, 7);
+    assert_eq!(env.program_aux_sequence, 9);
+    assert_eq!(env.aux_dirty_blocks, 0b1001);
+}
+
+#[test]
+fn test_force_multi_range_requires_delegation_signer() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let mut ix = force_multi_range_instruction(
+        &authority,
+        &envelope_pubkey,
+        &delegation_auth,
+        1,
+        1,
+        &make_specs(&[(0, &[1])]),
+    );
+    ix.accounts[2].is_signer = false;
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (
+                envelope_pubkey,
+                create_delegated_envelope(
+                    &authority,
+                    &delegation_auth,
+                    Mask::ALL_BLOCKED,
+                    Mask::ALL_BLOCKED,
+                ),
+            ),
+            (delegation_auth, create_funded_account(1_000_000_000)),
+        ],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_force_multi_range_rejects_stale_sequences_and_overflow() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let accounts = [
+        (authority, create_funded_account(1_000_000_000)),
+        (
+            envelope_pubkey,
+            create_delegated_envelope(
+                &authority,
+                &delegation_auth,
+                Mask::ALL_BLOCKED,
+                Mask::ALL_BLOCKED,
+            ),
+        ),
+        (delegation_auth, create_funded_account(1_000_000_000)),
+    ];
+    let ix = |authority_sequence, program_sequence, ranges: &[WriteSpec]| {
+        force_multi_range_instruction(
+            &authority,
+            &envelope_pubkey,
+            &delegation_auth,
+            authority_sequence,
+            program_sequence,
+            ranges,
+        )
+    };
+    let one = make_specs(&[(0, &[1])]);
+
+    for (authority_sequence, program_sequence) in [(0, 1), (1, 0)] {
+        mollusk.process_and_validate_instruction(
+            &ix(authority_sequence, program_sequence, &one),
+            &accounts,
+            &[Check::err(ProgramError::InvalidInstructionData)],
+        );
+    }
+    mollusk.process_and_validate_instruction(
+        &ix(1, 1, &make_specs(&[(TEST_TYPE_SIZE as u8 - 1, &[1, 2])])),
+        &accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
@@ -0,0 +1,216 @@
+use c_u_soon::{Mask, StructMetadata, TypeHash, AUX_DATA_SIZE, MAX_AUX_STRUCT_SIZE};
+use c_u_soon_client::{
+    update_auxiliary_delegated_multi_range_v2_instruction_data,
+    update_auxiliary_multi_range_force_v2_instruction_data,
+    update_auxiliary_multi_range_instruction_data,
+    update_auxiliary_multi_range_v2_instruction_data,
+};
+use c_u_soon_instruction::{SlowPathInstruction, WriteSpec, WriteSpecV2};
+use c_u_soon_test_utils::{fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+type Aux = [u8; 16];
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    delegate: Address,
+    padding: Address,
+    envelope: Address,
+}
+
+/// An envelope delegated to `delegate`, which may write aux bytes `0..8` while the
+/// authority writes `8..16`.
+fn setup_with(aux_metadata: StructMetadata) -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("multi_range_v2/authority");
+    let delegate = fixture_address("multi_range_v2/delegate");
+    let padding = fixture_address("multi_range_v2/padding");
+    let envelope = fixture_address("multi_range_v2/envelope");
+    let mut program = Mask::ALL_BLOCKED;
+    let mut user = Mask::ALL_BLOCKED;
+    for i in 0..8 {
+        program.allow(i);
+        user.allow(i + 8);
+    }
+    runner
+        .fund(authority, 1_000_000_000)
+        .fund(delegate, 1_000_000_000)
+        .fund(padding, 0)
+        .set_account(
+            envelope,
+            EnvelopeFixture::new(authority)
+                .aux_metadata(aux_metadata)
+                .delegated(delegate, program, user)
+                .account(),
+        );
+    Setup {
+        runner,
+        authority,
+        delegate,
+        padding,
+        envelope,
+    }
+}
+
+fn setup() -> Setup {
+    setup_with(Aux::METADATA)
+}
+
+fn spec(offset: u16, data: &[u8]) -> WriteSpecV2 {
+    WriteSpecV2 {
+        offset,
+        data: data.to_vec(),
+    }
+}
+
+impl Setup {
+    fn accounts(&self, signer: Address) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(signer, true),
+            AccountMeta::new(self.envelope, false),
+            AccountMeta::new_readonly(self.padding, false),
+        ]
+    }
+
+    fn authority_write(&self, data: Vec<u8>) -> Instruction {
+        Instruction::new_with_bytes(DEFAULT_PROGRAM_ID, &data, self.accounts(self.authority))
+    }
+
+    fn delegated_write(&self, data: Vec<u8>) -> Instruction {
+        Instruction::new_with_bytes(DEFAULT_PROGRAM_ID, &data, self.accounts(self.delegate))
+    }
+}
+
+#[test]
+fn test_multi_range_v2_writes_as_v1() {
+    let ranges = [
+        WriteSpec {
+            offset: 8,
+            data: vec![1; 2],
+        },
+        WriteSpec {
+            offset: 12,
+            data: vec![2; 4],
+        },
+    ];
+
+    let mut v1 = setup();
+    v1.runner.expect_ok(&v1.authority_write(
+        update_auxiliary_multi_range_instruction_data(Aux::METADATA.as_u64(), 1, &ranges).unwrap(),
+    ));
+
+    let mut v2 = setup();
+    let ranges: Vec<WriteSpecV2> = ranges.into_iter().map(WriteSpecV2::from).collect();
+    v2.runner.expect_ok(
+        &v2.authority_write(
+            update_auxiliary_multi_range_v2_instruction_data(Aux::METADATA.as_u64(), 1, &ranges)
+                .unwrap(),
+        ),
+    );
+
+    assert_eq!(
+        v2.runner.account(&v2.envelope).unwrap().data,
+        v1.runner.account(&v1.envelope).unwrap().data
+    );
+    let envelope = v2.runner.envelope(&v2.envelope);
+    assert_eq!(
+        envelope.auxiliary_data[..16],
+        [[0; 8], [1, 1, 0, 0], [2; 4]].concat()[..]
+    );
+    assert_eq!(envelope.authority_aux_sequence, 1);
+}
+
+#[test]
+fn test_multi_range_v2_keeps_the_masks() {
+    let mut s = setup();
+    s.runner.expect_err(
+        &s.delegated_write(
+            update_auxiliary_delegated_multi_range_v2_instruction_data(
+                Aux::METADATA.as_u64(),
+                1,
+                &[spec(0, &[3; 4]), spec(6, &[3; 4])],
+            )
+            .unwrap(),
+        ),
+        ProgramError::InvalidArgument,
+    );
+    s.runner.expect_ok(
+        &s.delegated_write(
+            update_auxiliary_delegated_multi_range_v2_instruction_data(
+                Aux::METADATA.as_u64(),
+                1,
+                &[spec(0, &[3; 4]), spec(4, &[4; 4])],
+            )
+            .unwrap(),
+        ),
+    );
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.auxiliary_data[..8], [[3; 4], [4; 4]].concat()[..]);
+    assert_eq!(envelope.program_aux_sequence, 1);
+}
+
+#[test]
+fn test_multi_range_v2_force_ignores_the_masks() {
+    let mut s = setup();
+    let ix = Instruction::new_with_bytes(
+        DEFAULT_PROGRAM_ID,
+        &update_auxiliary_multi_range_force_v2_instruction_data(
+            Aux::METADATA.as_u64(),
+            1,
+            1,
+            &[spec(0, &[9; 16])],
+        )
+        .unwrap(),
+        vec![
+            AccountMeta::new_readonly(s.authority, true),
+            AccountMeta::new(s.envelope, false),
+            AccountMeta::new_readonly(s.delegate, true),
+        ],
+    );
+    s.runner.expect_ok(&ix);
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.auxiliary_data[..16], [9; 16]);
+    assert_eq!(
+        (
+            envelope.authority_aux_sequence,
+            envelope.program_aux_sequence
+        ),
+        (1, 1)
+    );
+}
+
+#[test]
+fn test_multi_range_v2_rejects_ranges_past_the_aux_region() {
+    let metadata = StructMetadata::new(MAX_AUX_STRUCT_SIZE as u8, 1);
+    let mut s = setup_with(metadata);
+    // The client refuses the range, so the instruction is serialized by hand.
+    let ranges = vec![spec(AUX_DATA_SIZE as u16 - 4, &[5; 8])];
+    assert!(
+        update_auxiliary_multi_range_v2_instruction_data(metadata.as_u64(), 1, &ranges).is_err()
+    );
+    let data = wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 {
+        metadata: metadata.as_u64(),
+        sequence: 1,
+        ranges,
+    })
+    .unwrap();
+    s.runner.expect_err(
+        &s.authority_write(data),
+        ProgramError::InvalidInstructionData,
+    );
+
+    // An offset past `u8::MAX` fails the same way rather than wrapping.
+    let data = wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 {
+        metadata: metadata.as_u64(),
+        sequence: 1,
+        ranges: vec![spec(0x108, &[5; 2])],
+    })
+    .unwrap();
+    s.runner.expect_err(
+        &s.authority_write(data),
+        ProgramError::InvalidInstructionData,
+    );
+    assert_eq!(s.runner.envelope(&s.envelope).authority_aux_sequence, 0);
+}
@@ -0,0 +1,442 @@
+mod common;
+
+use c_u_soon::{Envelope, Mask, FLAG_MUTATION_GUARD, FLAG_PERMANENT};
+use c_u_soon_client::{
+    batch_update_instruction_data, fast_path_instruction_data, set_mutation_guard_instruction_data,
+    update_auxiliary_delegated_range_instruction_data, update_auxiliary_instruction_data,
+};
+use c_u_soon_instruction::BatchEntry;
+use common::{
+    create_delegated_envelope, create_existing_envelope, create_funded_account,
+    create_instructions_sysvar_with_accounts, new_mollusk, new_mollusk_silent,
+    INSTRUCTIONS_SYSVAR_ID, PROGRAM_ID, PROGRAM_PATH, TEST_META_U64, TEST_TYPE_SIZE,
+};
+use mollusk_svm::result::Check;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Third account that keeps SetMutationGuard off the two-account fast path.
+const GUARD_PADDING: Address = Address::new_from_array([0xEE; 32]);
+
+fn set_mutation_guard_instruction(
+    authority: Address,
+    envelope: Address,
+    allow_multi: bool,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_mutation_guard_instruction_data(allow_multi).unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope, false),
+            AccountMeta::new_readonly(GUARD_PADDING, false),
+        ],
+    )
+}
+
+struct GuardedSetup {
+    delegation_auth: Address,
+    envelope_pubkey: Address,
+    padding: Address,
+    accounts: Vec<(Address, Account)>,
+}
+
+/// Delegated envelope with the mutation guard set. `instructions` lists the other
+/// accounts of each top-level instruction, as `(address, is_writable)`; the sysvar is built
+/// with the envelope substituted for `None`.
+fn guarded_setup(instructions: &[(Address, &[(Option<Address>, bool)])]) -> GuardedSetup {
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let padding = Address::new_unique();
+
+    let mut envelope = create_delegated_envelope(
+        &authority,
+        &delegation_auth,
+        Mask::ALL_WRITABLE,
+        Mask::ALL_BLOCKED,
+    );
+    bytemuck::from_bytes_mut::<Envelope>(&mut envelope.data).flags |= FLAG_MUTATION_GUARD;
+
+    let resolved: Vec<(Address, Vec<(Address, bool)>)> = instructions
+        .iter()
+        .map(|(program_id, accounts)| {
+            let accounts = accounts
+                .iter()
+                .map(|(address, writable)| (address.unwrap_or(envelope_pubkey), *writable))
+                .collect();
+            (*program_id, accounts)
+        })
+        .collect();
+    let borrowed: Vec<(Address, &[(Address, bool)])> = resolved
+        .iter()
+        .map(|(id, accounts)| (*id, accounts.as_slice()))
+        .collect();
+
+    GuardedSetup {
+        delegation_auth,
+        envelope_pubkey,
+        padding,
+        accounts: vec![
+            (delegation_auth, create_funded_account(0)),
+            (envelope_pubkey, envelope),
+            (padding, create_funded_account(0)),
+            (
+                INSTRUCTIONS_SYSVAR_ID,
+                create_instructions_sysvar_with_accounts(&borrowed),
+            ),
+        ],
+    }
+}
+
+fn guarded_write_instruction(
+    setup: &GuardedSetup,
+    sequence: u64,
+    with_sysvar: bool,
+) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new_readonly(setup.delegation_auth, true),
+        AccountMeta::new(setup.envelope_pubkey, false),
+        AccountMeta::new_readonly(setup.padding, false),
+    ];
+    if with_sysvar {
+        metas.push(AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false));
+    }
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &update_auxiliary_delegated_range_instruction_data(TEST_META_U64, sequence, 8, &[0xAB; 4]),
+        metas,
+    )
+}
+
+// ============================================================================
+// SetMutationGuard
+// ============================================================================
+
+#[test]
+fn test_set_mutation_guard_toggles_flag() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let mut envelope = create_existing_envelope(&authority, 0);
+    bytemuck::from_bytes_mut::<Envelope>(&mut envelope.data).flags = FLAG_PERMANENT;
+
+    let result = mollusk.process_and_validate_instruction(
+        &set_mutation_guard_instruction(authority, envelope_pubkey, false),
+        &[
+            (authority, create_funded_account(0)),
+            (envelope_pubkey, envelope),
+            (GUARD_PADDING, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+    let env: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert!(env.has_mutation_guard());
+    assert!(env.is_permanent());
+
+    let result = mollusk.process_and_validate_instruction(
+        &set_mutation_guard_instruction(authority, envelope_pubkey, true),
+        &result.resulting_accounts,
+        &[Check::success()],
+    );
+    let env: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert!(!env.has_mutation_guard());
+    assert!(env.is_permanent());
+}
+
+#[test]
+fn test_set_mutation_guard_rejects_wrong_authority() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let attacker = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &set_mutation_guard_instruction(attacker, envelope_pubkey, true),
+        &[
+            (attacker, create_funded_account(0)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (GUARD_PADDING, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+// ============================================================================
+// Guarded delegated writes
+// ============================================================================
+
+#[test]
+fn test_guarded_write_succeeds_as_only_writer() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let consumer = Address::new_unique();
+    // The envelope is read by a consumer in a later instruction, but written only here.
+    let setup = guarded_setup(&[(PROGRAM_ID, &[(None, true)]), (consumer, &[(None, false)])]);
+
+    let result = mollusk.process_and_validate_instruction(
+        &guarded_write_instruction(&setup, 1, true),
+        &setup.accounts,
+        &[Check::success()],
+    );
+
+    let envelope: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert_eq!(envelope.auxiliary_data[8..12], [0xAB; 4]);
+    assert_eq!(envelope.program_aux_sequence, 1);
+    assert_ne!(envelope.mutation_marker, 0);
+}
+
+#[test]
+fn test_guarded_write_rejects_second_writable_instruction() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let composer = Address::new_unique();
+    // A second instruction, e.g. a program that CPIs into c_u_soon, also takes it writable.
+    let setup = guarded_setup(&[
+        (PROGRAM_ID, &[(None, true)]),
+        (
+            composer,
+            &[(Some(Address::new_unique()), true), (None, true)],
+        ),
+    ]);
+
+    mollusk.process_and_validate_instruction(
+        &guarded_write_instruction(&setup, 1, true),
+        &setup.accounts,
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_guarded_write_requires_instructions_sysvar() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let setup = guarded_setup(&[(PROGRAM_ID, &[(None, true)])]);
+
+    let mut accounts = setup.accounts.clone();
+    accounts.pop();
+    mollusk.process_and_validate_instruction(
+        &guarded_write_instruction(&setup, 1, false),
+        &accounts,
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+}
+
+#[test]
+fn test_unguarded_write_allows_multiple_writers() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let composer = Address::new_unique();
+    let mut setup = guarded_setup(&[(PROGRAM_ID, &[(None, true)]), (composer, &[(None, true)])]);
+    bytemuck::from_bytes_mut::<Envelope>(&mut setup.accounts[1].1.data).flags = 0;
+
+    mollusk.process_and_validate_instruction(
+        &guarded_write_instruction(&setup, 1, true),
+        &setup.accounts,
+        &[Check::success()],
+    );
+}
+
+// ============================================================================
+// Repeated writes within one instruction
+// ============================================================================
+
+#[test]
+fn test_guarded_write_rejects_repeat_in_same_instruction() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let setup = guarded_setup(&[(PROGRAM_ID, &[(None, true)])]);
+
+    let result = mollusk.process_and_validate_instruction(
+        &guarded_write_instruction(&setup, 1, true),
+        &setup.accounts,
+        &[Check::success()],
+    );
+
+    // A second CPI from the same top-level instruction sees the same slot and sysvar.
+    mollusk.process_and_validate_instruction(
+        &guarded_write_instruction(&setup, 2, true),
+        &result.resulting_accounts,
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_guarded_write_allows_later_slot() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let setup = guarded_setup(&[(PROGRAM_ID, &[(None, true)])]);
+
+    let result = mollusk.process_and_validate_instruction(
+        &guarded_write_instruction(&setup, 1, true),
+        &setup.accounts,
+        &[Check::success()],
+    );
+    let first: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    let first_marker = first.mutation_marker;
+
+    mollusk.mollusk.warp_to_slot(1);
+    let result = mollusk.process_and_validate_instruction(
+        &guarded_write_instruction(&setup, 2, true),
+        &result.resulting_accounts,
+        &[Check::success()],
+    );
+    let second: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert_eq!(second.program_aux_sequence, 2);
+    assert_ne!(second.mutation_marker, first_marker);
+}
+
+#[test]
+fn test_guarded_write_allows_other_transaction_in_same_slot() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let setup = guarded_setup(&[(PROGRAM_ID, &[(None, true)])]);
+
+    let result = mollusk.process_and_validate_instruction(
+        &guarded_write_instruction(&setup, 1, true),
+        &setup.accounts,
+        &[Check::success()],
+    );
+
+    // Another transaction lists different instructions, so its sysvar differs.
+    let mut accounts = result.resulting_accounts;
+    accounts[3].1 = create_instructions_sysvar_with_accounts(&[
+        (Address::new_unique(), &[]),
+        (PROGRAM_ID, &[(setup.envelope_pubkey, true)]),
+    ]);
+    mollusk.process_and_validate_instruction(
+        &guarded_write_instruction(&setup, 2, true),
+        &accounts,
+        &[Check::success()],
+    );
+}
+
+// ============================================================================
+// Other write paths
+// ============================================================================
+
+#[test]
+fn test_guarded_authority_write_requires_instructions_sysvar() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let padding = Address::new_unique();
+
+    let mut envelope = create_delegated_envelope(
+        &authority,
+        &Address::new_unique(),
+        Mask::ALL_WRITABLE,
+        Mask::ALL_WRITABLE,
+    );
+    bytemuck::from_bytes_mut::<Envelope>(&mut envelope.data).flags |= FLAG_MUTATION_GUARD;
+    let accounts = vec![
+        (authority, create_funded_account(0)),
+        (envelope_pubkey, envelope),
+        (padding, create_funded_account(0)),
+        (
+            INSTRUCTIONS_SYSVAR_ID,
+            create_instructions_sysvar_with_accounts(&[(PROGRAM_ID, &[(envelope_pubkey, true)])]),
+        ),
+    ];
+    let instruction = |with_sysvar: bool| {
+        let mut metas = vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(padding, false),
+        ];
+        if with_sysvar {
+            metas.push(AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false));
+        }
+        Instruction::new_with_bytes(
+            PROGRAM_ID,
+            &update_auxiliary_instruction_data(TEST_META_U64, 1, &[0x5A; TEST_TYPE_SIZE]),
+            metas,
+        )
+    };
+
+    mollusk.process_and_validate_instruction(
+        &instruction(false),
+        &accounts[..3],
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &instruction(true),
+        &accounts,
+        &[Check::success()],
+    );
+    let envelope: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert_eq!(envelope.authority_aux_sequence, 1);
+    assert_ne!(envelope.mutation_marker, 0);
+}
+
+#[test]
+fn test_guarded_envelope_rejects_fast_path() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let mut envelope = create_existing_envelope(&authority, 0);
+    bytemuck::from_bytes_mut::<Envelope>(&mut envelope.data).flags |= FLAG_MUTATION_GUARD;
+
+    mollusk.process_and_validate_instruction(
+        &Instruction::new_with_bytes(
+            PROGRAM_ID,
+            &fast_path_instruction_data(0, 1, &[42]).unwrap(),
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(envelope_pubkey, false),
+            ],
+        ),
+        &[
+            (authority, create_funded_account(0)),
+            (envelope_pubkey, envelope),
+        ],
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+}
+
+#[test]
+fn test_guarded_envelope_accepts_batch_update_with_sysvar() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let mut envelope = create_existing_envelope(&authority, 0);
+    bytemuck::from_bytes_mut::<Envelope>(&mut envelope.data).flags |= FLAG_MUTATION_GUARD;
+
+    let data = batch_update_instruction_data(&[BatchEntry {
+        oracle_meta: 0,
+        sequence: 1,
+        payload: &[42],
+    }])
+    .unwrap();
+    let result = mollusk.process_and_validate_instruction(
+        &Instruction::new_with_bytes(
+            PROGRAM_ID,
+            &data,
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(envelope_pubkey, false),
+                AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false),
+            ],
+        ),
+        &[
+            (authority, create_funded_account(0)),
+            (envelope_pubkey, envelope),
+            (
+                INSTRUCTIONS_SYSVAR_ID,
+                create_instructions_sysvar_with_accounts(&[(
+                    PROGRAM_ID,
+                    &[(envelope_pubkey, true)],
+                )]),
+            ),
+        ],
+        &[Check::success()],
+    );
+
+    let envelope: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert_eq!(envelope.oracle_state.sequence, 1);
+    assert_eq!(envelope.oracle_state.data[0], 42);
+    assert_ne!(envelope.mutation_marker, 0);
+}
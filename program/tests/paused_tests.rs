@@ -0,0 +1,163 @@
+use c_u_soon::{Mask, TypeHash};
+use c_u_soon_client::{
+    build_fast_path_ix, build_read_oracle_ix, build_set_paused_ix, build_update_auxiliary_ix,
+    update_auxiliary_delegated_instruction_data,
+};
+use c_u_soon_instruction::ENVELOPE_PAUSED_ERROR;
+use c_u_soon_test_utils::{fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use mollusk_svm::result::Check;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+const PAUSED: ProgramError = ProgramError::Custom(ENVELOPE_PAUSED_ERROR);
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    delegate: Address,
+    padding: Address,
+    envelope: Address,
+}
+
+/// A delegated `u64` envelope at sequence 1, whose delegate may write aux bytes 0..8 and
+/// whose authority may write aux bytes 8..16.
+fn setup() -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("paused/authority");
+    let delegate = fixture_address("paused/delegate");
+    let padding = fixture_address("paused/padding");
+    let envelope = fixture_address("paused/envelope");
+    let mut program = Mask::ALL_BLOCKED;
+    let mut user = Mask::ALL_BLOCKED;
+    program.allow_range(0, 8);
+    user.allow_range(8, 8);
+    runner
+        .fund(authority, 1_000_000_000)
+        .fund(delegate, 1_000_000_000)
+        .fund(padding, 0)
+        .set_account(
+            envelope,
+            EnvelopeFixture::new(authority)
+                .oracle(u64::METADATA, &7u64.to_le_bytes())
+                .sequence(1)
+                .aux_metadata(<[u8; 16]>::METADATA)
+                .delegated(delegate, program, user)
+                .account(),
+        );
+    Setup {
+        runner,
+        authority,
+        delegate,
+        padding,
+        envelope,
+    }
+}
+
+impl Setup {
+    fn set_paused(&self, signer: Address, paused: bool) -> Instruction {
+        build_set_paused_ix(&DEFAULT_PROGRAM_ID, &signer, &self.envelope, paused).unwrap()
+    }
+
+    fn write(&self, signer: Address, sequence: u64, value: u64) -> Instruction {
+        build_fast_path_ix(
+            &DEFAULT_PROGRAM_ID,
+            &signer,
+            &self.envelope,
+            u64::METADATA.as_u64(),
+            sequence,
+            &value.to_le_bytes(),
+        )
+        .unwrap()
+    }
+
+    /// Authority aux write of `data`, which must match the delegate's bytes 0..8.
+    fn aux_write(&self, sequence: u64, data: [u8; 16]) -> Instruction {
+        build_update_auxiliary_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            &self.envelope,
+            &self.padding,
+            <[u8; 16]>::METADATA.as_u64(),
+            sequence,
+            &data,
+        )
+    }
+
+    /// Delegated aux write of `data`, which must match the authority's bytes 8..16.
+    fn delegated_write(&self, sequence: u64, data: [u8; 16]) -> Instruction {
+        Instruction::new_with_bytes(
+            DEFAULT_PROGRAM_ID,
+            &update_auxiliary_delegated_instruction_data(
+                <[u8; 16]>::METADATA.as_u64(),
+                sequence,
+                &data,
+            ),
+            vec![
+                AccountMeta::new_readonly(self.delegate, true),
+                AccountMeta::new(self.envelope, false),
+                AccountMeta::new_readonly(self.padding, false),
+            ],
+        )
+    }
+}
+
+#[test]
+fn test_paused_envelope_rejects_writes() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.set_paused(s.authority, true));
+    assert!(s.runner.envelope(&s.envelope).is_paused());
+
+    s.runner.expect_err(&s.write(s.authority, 2, 8), PAUSED);
+    s.runner.expect_err(&s.aux_write(1, [1; 16]), PAUSED);
+    s.runner.expect_err(&s.delegated_write(1, [1; 16]), PAUSED);
+
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.oracle_state.sequence, 1);
+    assert_eq!(envelope.oracle::<u64>().copied(), Some(7));
+    assert_eq!(envelope.auxiliary_data[..16], [0; 16]);
+}
+
+#[test]
+fn test_paused_envelope_still_reads_and_keeps_delegation() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.set_paused(s.authority, true));
+
+    let mut expected = u64::METADATA.as_u64().to_le_bytes().to_vec();
+    expected.extend_from_slice(&1u64.to_le_bytes());
+    expected.extend_from_slice(&s.runner.envelope(&s.envelope).oracle_state.data);
+    s.runner.process(
+        &build_read_oracle_ix(&DEFAULT_PROGRAM_ID, &s.envelope).unwrap(),
+        &[Check::success(), Check::return_data(&expected)],
+    );
+
+    s.runner.expect_ok(&s.set_paused(s.authority, false));
+    let envelope = s.runner.envelope(&s.envelope);
+    assert!(!envelope.is_paused());
+    assert_eq!(envelope.delegation_authority, s.delegate);
+
+    s.runner.expect_ok(&s.write(s.authority, 2, 8));
+    let mut data = [0u8; 16];
+    data[..8].fill(0xBB);
+    s.runner.expect_ok(&s.delegated_write(1, data));
+    data[8..].fill(0xAA);
+    s.runner.expect_ok(&s.aux_write(1, data));
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.oracle::<u64>().copied(), Some(8));
+    assert_eq!(envelope.auxiliary_data[..8], [0xBB; 8]);
+    assert_eq!(envelope.auxiliary_data[8..16], [0xAA; 8]);
+}
+
+#[test]
+fn test_only_authority_pauses() {
+    let mut s = setup();
+    s.runner.expect_err(
+        &s.set_paused(s.delegate, true),
+        ProgramError::IncorrectAuthority,
+    );
+    assert!(!s.runner.envelope(&s.envelope).is_paused());
+
+    s.runner.expect_ok(&s.set_paused(s.authority, true));
+    // An unauthorized write fails on its signer, not on the pause.
+    s.runner
+        .expect_err(&s.write(s.delegate, 2, 8), ProgramError::IncorrectAuthority);
+}
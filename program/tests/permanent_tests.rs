@@ -0,0 +1,150 @@
+mod common;
+
+use c_u_soon::{Envelope, Mask, FLAG_PERMANENT, FLAG_RANGE_GUARD};
+use c_u_soon_client::{close_instruction_data, set_permanent_instruction_data};
+use common::{
+    create_delegated_envelope, create_existing_envelope, create_funded_account, new_mollusk,
+    new_mollusk_silent, PROGRAM_ID, PROGRAM_PATH,
+};
+use mollusk_svm::result::Check;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+};
+
+/// Third account that keeps SetPermanent off the two-account fast path.
+const PADDING: Address = Address::new_from_array([0xEE; 32]);
+
+fn set_permanent_instruction(authority: Address, envelope: Address) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_permanent_instruction_data().unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope, false),
+            AccountMeta::new_readonly(PADDING, false),
+        ],
+    )
+}
+
+fn close_instruction(authority: Address, envelope: Address, recipient: Address) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &close_instruction_data().unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope, false),
+            AccountMeta::new(recipient, false),
+        ],
+    )
+}
+
+fn permanent_envelope(authority: &Address) -> Account {
+    let mut account = create_existing_envelope(authority, 0);
+    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut account.data);
+    envelope.flags |= FLAG_PERMANENT;
+    account
+}
+
+#[test]
+fn test_set_permanent_sets_flag() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let mut envelope = create_existing_envelope(&authority, 0);
+    bytemuck::from_bytes_mut::<Envelope>(&mut envelope.data).flags = FLAG_RANGE_GUARD;
+
+    let result = mollusk.process_and_validate_instruction(
+        &set_permanent_instruction(authority, envelope_pubkey),
+        &[
+            (authority, create_funded_account(0)),
+            (envelope_pubkey, envelope),
+            (PADDING, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+
+    let env: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert!(env.is_permanent());
+    assert!(env.has_range_guard());
+}
+
+#[test]
+fn test_set_permanent_works_while_delegated() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &set_permanent_instruction(authority, envelope_pubkey),
+        &[
+            (authority, create_funded_account(0)),
+            (
+                envelope_pubkey,
+                create_delegated_envelope(
+                    &authority,
+                    &Address::new_unique(),
+                    Mask::ALL_BLOCKED,
+                    Mask::ALL_BLOCKED,
+                ),
+            ),
+            (PADDING, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_set_permanent_rejects_wrong_authority() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let impostor = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &set_permanent_instruction(impostor, envelope_pubkey),
+        &[
+            (impostor, create_funded_account(0)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (PADDING, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_set_permanent_twice_rejected() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &set_permanent_instruction(authority, envelope_pubkey),
+        &[
+            (authority, create_funded_account(0)),
+            (envelope_pubkey, permanent_envelope(&authority)),
+            (PADDING, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_close_rejected_for_permanent_envelope() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let recipient = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &close_instruction(authority, envelope_pubkey, recipient),
+        &[
+            (authority, create_funded_account(0)),
+            (envelope_pubkey, permanent_envelope(&authority)),
+            (recipient, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
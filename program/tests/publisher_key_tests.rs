@@ -0,0 +1,183 @@
+use c_u_soon::TypeHash;
+use c_u_soon_client::{
+    batch_update_instruction_data, build_close_ix, build_fast_path_ix, build_set_publisher_ix,
+};
+use c_u_soon_instruction::BatchEntry;
+use c_u_soon_test_utils::{fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    publisher: Address,
+    envelope: Address,
+}
+
+/// A `u64` envelope at sequence 1 whose authority has not set a publisher yet.
+fn setup() -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("publisher_key/authority");
+    let publisher = fixture_address("publisher_key/publisher");
+    let envelope = fixture_address("publisher_key/envelope");
+    runner
+        .fund(authority, 1_000_000_000)
+        .fund(publisher, 1_000_000_000)
+        .set_account(
+            envelope,
+            EnvelopeFixture::new(authority)
+                .oracle_metadata(u64::METADATA)
+                .sequence(1)
+                .account(),
+        );
+    Setup {
+        runner,
+        authority,
+        publisher,
+        envelope,
+    }
+}
+
+impl Setup {
+    fn set_publisher(&self, signer: Address, publisher: Option<&Address>) -> Instruction {
+        build_set_publisher_ix(&DEFAULT_PROGRAM_ID, &signer, &self.envelope, publisher).unwrap()
+    }
+
+    fn write(&self, signer: Address, sequence: u64, value: u64) -> Instruction {
+        build_fast_path_ix(
+            &DEFAULT_PROGRAM_ID,
+            &signer,
+            &self.envelope,
+            u64::METADATA.as_u64(),
+            sequence,
+            &value.to_le_bytes(),
+        )
+        .unwrap()
+    }
+
+    fn oracle(&self) -> (u64, u64) {
+        let envelope = self.runner.envelope(&self.envelope);
+        (
+            envelope.oracle_state.sequence,
+            *envelope.oracle::<u64>().unwrap(),
+        )
+    }
+}
+
+#[test]
+fn test_publisher_writes_through_fast_path() {
+    let mut s = setup();
+    s.runner.expect_err(
+        &s.write(s.publisher, 2, 7),
+        ProgramError::IncorrectAuthority,
+    );
+
+    s.runner
+        .expect_ok(&s.set_publisher(s.authority, Some(&s.publisher)));
+    assert_eq!(s.runner.envelope(&s.envelope).publisher, s.publisher);
+
+    s.runner.expect_ok(&s.write(s.publisher, 2, 7));
+    assert_eq!(s.oracle(), (2, 7));
+    // The authority keeps publishing rights, under the same sequence.
+    s.runner.expect_ok(&s.write(s.authority, 3, 8));
+    assert_eq!(s.oracle(), (3, 8));
+    s.runner.expect_err(
+        &s.write(s.publisher, 3, 9),
+        ProgramError::InvalidInstructionData,
+    );
+
+    let stranger = fixture_address("publisher_key/stranger");
+    s.runner.fund(stranger, 1_000_000_000);
+    s.runner
+        .expect_err(&s.write(stranger, 4, 9), ProgramError::IncorrectAuthority);
+}
+
+#[test]
+fn test_publisher_cannot_administer() {
+    let mut s = setup();
+    s.runner
+        .expect_ok(&s.set_publisher(s.authority, Some(&s.publisher)));
+
+    let recipient = fixture_address("publisher_key/recipient");
+    let close = build_close_ix(&DEFAULT_PROGRAM_ID, &s.publisher, &s.envelope, &recipient).unwrap();
+    s.runner
+        .expect_err(&close, ProgramError::IncorrectAuthority);
+
+    let other = fixture_address("publisher_key/other");
+    s.runner.expect_err(
+        &s.set_publisher(s.publisher, Some(&other)),
+        ProgramError::IncorrectAuthority,
+    );
+    s.runner.expect_err(
+        &s.set_publisher(s.publisher, None),
+        ProgramError::IncorrectAuthority,
+    );
+    assert_eq!(s.runner.envelope(&s.envelope).publisher, s.publisher);
+}
+
+#[test]
+fn test_cleared_publisher_is_rejected() {
+    let mut s = setup();
+    s.runner
+        .expect_ok(&s.set_publisher(s.authority, Some(&s.publisher)));
+    s.runner.expect_ok(&s.write(s.publisher, 2, 7));
+
+    s.runner.expect_ok(&s.set_publisher(s.authority, None));
+    assert_eq!(s.runner.envelope(&s.envelope).publisher, Address::default());
+    s.runner.expect_err(
+        &s.write(s.publisher, 3, 8),
+        ProgramError::IncorrectAuthority,
+    );
+    assert_eq!(s.oracle(), (2, 7));
+}
+
+#[test]
+fn test_publisher_signs_batch_update() {
+    let mut s = setup();
+    s.runner
+        .expect_ok(&s.set_publisher(s.authority, Some(&s.publisher)));
+    let second = fixture_address("publisher_key/second");
+    s.runner.set_account(
+        second,
+        EnvelopeFixture::new(s.authority)
+            .oracle_metadata(u64::METADATA)
+            .account(),
+    );
+
+    let entries = [
+        BatchEntry {
+            oracle_meta: u64::METADATA.as_u64(),
+            sequence: 2,
+            payload: &5u64.to_le_bytes(),
+        },
+        BatchEntry {
+            oracle_meta: u64::METADATA.as_u64(),
+            sequence: 1,
+            payload: &6u64.to_le_bytes(),
+        },
+    ];
+    let batch = Instruction::new_with_bytes(
+        DEFAULT_PROGRAM_ID,
+        &batch_update_instruction_data(&entries).unwrap(),
+        vec![
+            AccountMeta::new_readonly(s.publisher, true),
+            AccountMeta::new(s.envelope, false),
+            AccountMeta::new(second, false),
+        ],
+    );
+    // `second` has no publisher, so the whole batch fails.
+    s.runner
+        .expect_err(&batch, ProgramError::IncorrectAuthority);
+    assert_eq!(s.oracle(), (1, 0));
+
+    s.runner.set_account(
+        second,
+        EnvelopeFixture::new(s.authority)
+            .oracle_metadata(u64::METADATA)
+            .publisher(s.publisher)
+            .account(),
+    );
+    s.runner.expect_ok(&batch);
+    assert_eq!(s.oracle(), (2, 5));
+    assert_eq!(s.runner.envelope(&second).oracle::<u64>().copied(), Some(6));
+}
@@ -0,0 +1,394 @@
+mod common;
+
+use c_u_soon::{
+    Constraint, ConstraintOp, ConstraintRegion, ConstraintTable, Envelope, PublisherSet,
+    FLAG_CONSTRAINTS,
+};
+use c_u_soon_client::{publisher_update_instruction_data, set_publishers_instruction_data};
+use c_u_soon_instruction::CONSTRAINT_VIOLATED_ERROR;
+use common::{
+    create_constraint_table, create_existing_envelope, create_funded_account, create_publisher_set,
+    create_reader_list, find_constraints_pda, find_publishers_pda, find_readers_pda, new_mollusk,
+    new_mollusk_silent, PROGRAM_ID, PROGRAM_PATH,
+};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_system_interface::program as system_program;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn envelope(account: &Account) -> &Envelope {
+    bytemuck::from_bytes(&account.data)
+}
+
+fn set_publishers_instruction(
+    authority: &Address,
+    envelope_pubkey: &Address,
+    set_pubkey: &Address,
+    bump: u8,
+    threshold: u8,
+    publishers: &[Address],
+) -> Instruction {
+    let publishers: Vec<[u8; 32]> = publishers.iter().map(|p| p.to_bytes()).collect();
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_publishers_instruction_data(bump, threshold, &publishers).unwrap(),
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(*envelope_pubkey, false),
+            AccountMeta::new(*set_pubkey, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+/// `PublisherUpdate` writing `payload` at `sequence`; `rest` follows the publisher set.
+fn publisher_update_instruction(
+    envelope_pubkey: &Address,
+    set_pubkey: &Address,
+    rest: &[(Address, bool)],
+    sequence: u64,
+    payload: &[u8],
+) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new(*envelope_pubkey, false),
+        AccountMeta::new_readonly(*set_pubkey, false),
+    ];
+    metas.extend(
+        rest.iter()
+            .map(|&(pubkey, signer)| AccountMeta::new_readonly(pubkey, signer)),
+    );
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &publisher_update_instruction_data(0, sequence, payload).unwrap(),
+        metas,
+    )
+}
+
+/// Three publishers, two of which must sign.
+struct Fixture {
+    envelope_pubkey: Address,
+    set_pubkey: Address,
+    publishers: [Address; 3],
+    accounts: Vec<(Address, Account)>,
+}
+
+impl Fixture {
+    fn new(sequence: u64) -> Self {
+        let authority = Address::new_unique();
+        let envelope_pubkey = Address::new_unique();
+        let (set_pubkey, _) = find_publishers_pda(&envelope_pubkey);
+        let publishers = [
+            Address::new_unique(),
+            Address::new_unique(),
+            Address::new_unique(),
+        ];
+        let mut accounts = vec![
+            (
+                envelope_pubkey,
+                create_existing_envelope(&authority, sequence),
+            ),
+            (
+                set_pubkey,
+                create_publisher_set(&envelope_pubkey, &publishers, 2),
+            ),
+        ];
+        accounts.extend(
+            publishers
+                .iter()
+                .map(|p| (*p, create_funded_account(1_000_000_000))),
+        );
+        Self {
+            envelope_pubkey,
+            set_pubkey,
+            publishers,
+            accounts,
+        }
+    }
+
+    fn update(&self, signers: &[Address], sequence: u64, payload: &[u8]) -> Instruction {
+        let rest: Vec<(Address, bool)> = signers.iter().map(|p| (*p, true)).collect();
+        publisher_update_instruction(
+            &self.envelope_pubkey,
+            &self.set_pubkey,
+            &rest,
+            sequence,
+            payload,
+        )
+    }
+}
+
+// ============================================================================
+// SetPublishers
+// ============================================================================
+
+#[test]
+fn test_set_publishers_creates_set() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (set_pubkey, bump) = find_publishers_pda(&envelope_pubkey);
+    let publishers = [Address::new_unique(), Address::new_unique()];
+
+    let ix = set_publishers_instruction(
+        &authority,
+        &envelope_pubkey,
+        &set_pubkey,
+        bump,
+        2,
+        &publishers,
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (set_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    let set_account = &result.resulting_accounts[2].1;
+    assert_eq!(set_account.owner, PROGRAM_ID);
+    let set: &PublisherSet = bytemuck::from_bytes(&set_account.data);
+    assert_eq!(set.envelope, envelope_pubkey);
+    assert_eq!(set.bump, bump);
+    assert_eq!(set.threshold, 2);
+    assert_eq!(set.active(), &publishers);
+}
+
+#[test]
+fn test_set_publishers_replaces_existing_set() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (set_pubkey, bump) = find_publishers_pda(&envelope_pubkey);
+    let old = [Address::new_unique(), Address::new_unique()];
+    let new = [Address::new_unique()];
+
+    let ix = set_publishers_instruction(&authority, &envelope_pubkey, &set_pubkey, bump, 1, &new);
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (set_pubkey, create_publisher_set(&envelope_pubkey, &old, 2)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    let set: &PublisherSet = bytemuck::from_bytes(&result.resulting_accounts[2].1.data);
+    assert_eq!(set.threshold, 1);
+    assert_eq!(set.active(), &new);
+    assert!(set.publishers[1..].iter().all(|p| p == &Address::default()));
+}
+
+#[test]
+fn test_set_publishers_rejects_wrong_authority() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let attacker = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (set_pubkey, bump) = find_publishers_pda(&envelope_pubkey);
+
+    let ix = set_publishers_instruction(
+        &attacker,
+        &envelope_pubkey,
+        &set_pubkey,
+        bump,
+        1,
+        &[attacker],
+    );
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (attacker, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (set_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_set_publishers_rejects_wrong_pda() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (_, bump) = find_publishers_pda(&envelope_pubkey);
+    let wrong = Address::new_unique();
+
+    let ix =
+        set_publishers_instruction(&authority, &envelope_pubkey, &wrong, bump, 1, &[authority]);
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (wrong, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::InvalidSeeds)],
+    );
+}
+
+// ============================================================================
+// PublisherUpdate
+// ============================================================================
+
+#[test]
+fn test_publisher_update_meets_threshold() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let fixture = Fixture::new(5);
+    let [a, _, c] = fixture.publishers;
+
+    let result = mollusk.process_and_validate_instruction(
+        &fixture.update(&[c, a], 6, &[1, 2, 3, 4]),
+        &fixture.accounts,
+        &[Check::success()],
+    );
+
+    let env = envelope(&result.resulting_accounts[0].1);
+    assert_eq!(env.oracle_state.sequence, 6);
+    assert_eq!(&env.oracle_state.data[..4], &[1, 2, 3, 4]);
+}
+
+#[test]
+fn test_publisher_update_rejects_below_threshold() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let fixture = Fixture::new(0);
+    let [a, _, _] = fixture.publishers;
+    let outsider = Address::new_unique();
+    let mut accounts = fixture.accounts.clone();
+    accounts.push((outsider, create_funded_account(1_000_000_000)));
+
+    for signers in [&[a][..], &[a, a], &[a, outsider]] {
+        mollusk.process_and_validate_instruction(
+            &fixture.update(signers, 1, &[1]),
+            &accounts,
+            &[Check::err(ProgramError::IncorrectAuthority)],
+        );
+    }
+}
+
+#[test]
+fn test_publisher_update_requires_signatures() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let fixture = Fixture::new(0);
+    let [a, b, _] = fixture.publishers;
+
+    let ix = publisher_update_instruction(
+        &fixture.envelope_pubkey,
+        &fixture.set_pubkey,
+        &[(a, true), (b, false)],
+        1,
+        &[1],
+    );
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &fixture.accounts,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_publisher_update_rejects_set_for_other_envelope() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let mut fixture = Fixture::new(0);
+    let [a, b, _] = fixture.publishers;
+    fixture.accounts[1].1 = create_publisher_set(&Address::new_unique(), &fixture.publishers, 2);
+
+    mollusk.process_and_validate_instruction(
+        &fixture.update(&[a, b], 1, &[1]),
+        &fixture.accounts,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_publisher_update_rejects_reader_list() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let mut fixture = Fixture::new(0);
+    let [a, b, _] = fixture.publishers;
+    // A reader list naming the publishers has the set's size and owner, and reads as a set
+    // with a zero threshold; only its address tells them apart.
+    let (list_pubkey, _) = find_readers_pda(&fixture.envelope_pubkey);
+    fixture.accounts[1] = (
+        list_pubkey,
+        create_reader_list(&fixture.envelope_pubkey, &fixture.publishers),
+    );
+    fixture.set_pubkey = list_pubkey;
+
+    mollusk.process_and_validate_instruction(
+        &fixture.update(&[a, b], 1, &[1]),
+        &fixture.accounts,
+        &[Check::err(ProgramError::InvalidSeeds)],
+    );
+}
+
+#[test]
+fn test_publisher_update_rejects_forged_set() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let mut fixture = Fixture::new(0);
+    let [a, b, _] = fixture.publishers;
+    fixture.accounts[1].1.owner = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &fixture.update(&[a, b], 1, &[1]),
+        &fixture.accounts,
+        &[Check::err(ProgramError::IncorrectProgramId)],
+    );
+}
+
+#[test]
+fn test_publisher_update_rejects_stale_sequence() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let fixture = Fixture::new(5);
+    let [a, b, _] = fixture.publishers;
+
+    for sequence in [4, 5] {
+        mollusk.process_and_validate_instruction(
+            &fixture.update(&[a, b], sequence, &[1]),
+            &fixture.accounts,
+            &[Check::err(ProgramError::InvalidInstructionData)],
+        );
+    }
+}
+
+#[test]
+fn test_publisher_update_checks_oracle_constraints() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let mut fixture = Fixture::new(0);
+    let [a, b, _] = fixture.publishers;
+    let (table_pubkey, _) = find_constraints_pda(&fixture.envelope_pubkey);
+    let floor =
+        Constraint::against_const(ConstraintRegion::Oracle, 0, 1, false, ConstraintOp::Ge, 10);
+    let table = create_constraint_table(&fixture.envelope_pubkey, &[floor]);
+    let env: &mut Envelope = bytemuck::from_bytes_mut(&mut fixture.accounts[0].1.data);
+    env.flags |= FLAG_CONSTRAINTS;
+    env.constrained_regions = bytemuck::from_bytes::<ConstraintTable>(&table.data).regions();
+    fixture.accounts.push((table_pubkey, table));
+
+    let ix = |payload: &[u8]| {
+        publisher_update_instruction(
+            &fixture.envelope_pubkey,
+            &fixture.set_pubkey,
+            &[(table_pubkey, false), (a, true), (b, true)],
+            1,
+            payload,
+        )
+    };
+    mollusk.process_and_validate_instruction(
+        &ix(&[9]),
+        &fixture.accounts,
+        &[Check::err(ProgramError::Custom(CONSTRAINT_VIOLATED_ERROR))],
+    );
+    mollusk.process_and_validate_instruction(&ix(&[10]), &fixture.accounts, &[Check::success()]);
+}
@@ -0,0 +1,456 @@
+mod common;
+
+use c_u_soon::{Envelope, Mask, RangeGuard, FLAG_RANGE_GUARD};
+use c_u_soon_client::{
+    set_range_guards_instruction_data, update_auxiliary_delegated_instruction_data,
+    update_auxiliary_delegated_range_instruction_data,
+};
+use c_u_soon_instruction::RangeGuardSpec;
+use common::{
+    create_delegated_envelope, create_existing_envelope, create_funded_account,
+    create_instructions_sysvar, create_range_guard, find_range_guard_pda, new_mollusk,
+    new_mollusk_silent, INSTRUCTIONS_SYSVAR_ID, PROGRAM_ID, PROGRAM_PATH, TEST_META_U64,
+    TEST_TYPE_SIZE,
+};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_system_interface::program as system_program;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn set_range_guards_instruction(
+    authority: &Address,
+    envelope_pubkey: &Address,
+    guard_pubkey: &Address,
+    bump: u8,
+    entries: &[RangeGuardSpec],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_range_guards_instruction_data(bump, entries).unwrap(),
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(*envelope_pubkey, false),
+            AccountMeta::new(*guard_pubkey, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+fn guarded_delegated_envelope(authority: &Address, delegation_auth: &Address) -> Account {
+    let mut account = create_delegated_envelope(
+        authority,
+        delegation_auth,
+        Mask::ALL_WRITABLE,
+        Mask::ALL_BLOCKED,
+    );
+    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut account.data);
+    envelope.flags |= FLAG_RANGE_GUARD;
+    account
+}
+
+fn delegated_write_instruction(
+    delegation_auth: &Address,
+    envelope_pubkey: &Address,
+    guard_pubkey: &Address,
+    sysvar_pubkey: &Address,
+    data: &[u8],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &update_auxiliary_delegated_instruction_data(TEST_META_U64, 1, data),
+        vec![
+            AccountMeta::new_readonly(*delegation_auth, true),
+            AccountMeta::new(*envelope_pubkey, false),
+            AccountMeta::new_readonly(Address::new_unique(), false),
+            AccountMeta::new_readonly(*guard_pubkey, false),
+            AccountMeta::new_readonly(*sysvar_pubkey, false),
+        ],
+    )
+}
+
+struct GuardedSetup {
+    delegation_auth: Address,
+    envelope_pubkey: Address,
+    guard_pubkey: Address,
+    settlement_program: Address,
+    accounts: Vec<(Address, Account)>,
+}
+
+/// Delegated envelope guarding bytes `[16, 24)` behind `settlement_program`.
+fn guarded_setup(listed_programs: &[Address], settlement_program: Address) -> GuardedSetup {
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (guard_pubkey, _) = find_range_guard_pda(&envelope_pubkey);
+
+    let accounts = vec![
+        (delegation_auth, create_funded_account(0)),
+        (
+            envelope_pubkey,
+            guarded_delegated_envelope(&authority, &delegation_auth),
+        ),
+        (
+            guard_pubkey,
+            create_range_guard(&envelope_pubkey, &[(settlement_program, 16, 8)]),
+        ),
+        (
+            INSTRUCTIONS_SYSVAR_ID,
+            create_instructions_sysvar(listed_programs),
+        ),
+    ];
+
+    GuardedSetup {
+        delegation_auth,
+        envelope_pubkey,
+        guard_pubkey,
+        settlement_program,
+        accounts,
+    }
+}
+
+/// Accounts for `delegated_write_instruction`, inserting the padding account at index 2.
+fn with_padding(ix: &Instruction, accounts: &[(Address, Account)]) -> Vec<(Address, Account)> {
+    let mut out = accounts.to_vec();
+    out.insert(2, (ix.accounts[2].pubkey, create_funded_account(0)));
+    out
+}
+
+// ============================================================================
+// SetRangeGuards
+// ============================================================================
+
+#[test]
+fn test_set_range_guards_creates_guard_and_sets_flag() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (guard_pubkey, bump) = find_range_guard_pda(&envelope_pubkey);
+    let settlement_program = Address::new_unique();
+
+    let entries = [RangeGuardSpec {
+        program_id: settlement_program.to_bytes(),
+        offset: 16,
+        len: 8,
+    }];
+    let ix =
+        set_range_guards_instruction(&authority, &envelope_pubkey, &guard_pubkey, bump, &entries);
+
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (guard_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    let envelope: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert!(envelope.has_range_guard());
+
+    let guard_account = &result.resulting_accounts[2].1;
+    assert_eq!(guard_account.owner, PROGRAM_ID);
+    let guard: &RangeGuard = bytemuck::from_bytes(&guard_account.data);
+    assert_eq!(guard.envelope, envelope_pubkey);
+    assert_eq!(guard.bump, bump);
+    assert_eq!(guard.active().len(), 1);
+    assert_eq!(guard.active()[0].program_id, settlement_program);
+    assert_eq!((guard.active()[0].offset, guard.active()[0].len), (16, 8));
+}
+
+#[test]
+fn test_set_range_guards_empty_clears_flag() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (guard_pubkey, bump) = find_range_guard_pda(&envelope_pubkey);
+
+    let mut envelope_account = create_existing_envelope(&authority, 0);
+    bytemuck::from_bytes_mut::<Envelope>(&mut envelope_account.data).flags |= FLAG_RANGE_GUARD;
+    let guard_account = create_range_guard(&envelope_pubkey, &[(Address::new_unique(), 0, 4)]);
+
+    let ix = set_range_guards_instruction(&authority, &envelope_pubkey, &guard_pubkey, bump, &[]);
+
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope_account),
+            (guard_pubkey, guard_account),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    let envelope: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert!(!envelope.has_range_guard());
+    let guard: &RangeGuard = bytemuck::from_bytes(&result.resulting_accounts[2].1.data);
+    assert!(guard.active().is_empty());
+}
+
+#[test]
+fn test_set_range_guards_rejects_active_delegation() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (guard_pubkey, bump) = find_range_guard_pda(&envelope_pubkey);
+
+    let envelope_account = create_delegated_envelope(
+        &authority,
+        &Address::new_unique(),
+        Mask::ALL_WRITABLE,
+        Mask::ALL_BLOCKED,
+    );
+    let ix = set_range_guards_instruction(&authority, &envelope_pubkey, &guard_pubkey, bump, &[]);
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope_account),
+            (guard_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_set_range_guards_rejects_wrong_pda() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (_, bump) = find_range_guard_pda(&envelope_pubkey);
+    let wrong_guard = Address::new_unique();
+
+    let ix = set_range_guards_instruction(&authority, &envelope_pubkey, &wrong_guard, bump, &[]);
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (wrong_guard, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::InvalidSeeds)],
+    );
+}
+
+#[test]
+fn test_set_range_guards_rejects_wrong_authority() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let attacker = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (guard_pubkey, bump) = find_range_guard_pda(&envelope_pubkey);
+
+    let ix = set_range_guards_instruction(&attacker, &envelope_pubkey, &guard_pubkey, bump, &[]);
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (attacker, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (guard_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+// ============================================================================
+// Enforcement on delegated writes
+// ============================================================================
+
+fn guarded_payload() -> Vec<u8> {
+    let mut data = vec![0u8; TEST_TYPE_SIZE];
+    data[16] = 0xAA;
+    data
+}
+
+#[test]
+fn test_guarded_write_succeeds_with_program_in_transaction() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let settlement_program = Address::new_unique();
+    let setup = guarded_setup(&[PROGRAM_ID, settlement_program], settlement_program);
+
+    let ix = delegated_write_instruction(
+        &setup.delegation_auth,
+        &setup.envelope_pubkey,
+        &setup.guard_pubkey,
+        &INSTRUCTIONS_SYSVAR_ID,
+        &guarded_payload(),
+    );
+
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &with_padding(&ix, &setup.accounts),
+        &[Check::success()],
+    );
+
+    let envelope: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert_eq!(envelope.auxiliary_data[16], 0xAA);
+    assert_eq!(envelope.program_aux_sequence, 1);
+}
+
+#[test]
+fn test_guarded_write_fails_without_program_in_transaction() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let settlement_program = Address::new_unique();
+    let setup = guarded_setup(&[PROGRAM_ID], settlement_program);
+
+    let ix = delegated_write_instruction(
+        &setup.delegation_auth,
+        &setup.envelope_pubkey,
+        &setup.guard_pubkey,
+        &INSTRUCTIONS_SYSVAR_ID,
+        &guarded_payload(),
+    );
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &with_padding(&ix, &setup.accounts),
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
+
+#[test]
+fn test_unguarded_bytes_writable_without_program() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let setup = guarded_setup(&[PROGRAM_ID], Address::new_unique());
+
+    let mut data = vec![0u8; TEST_TYPE_SIZE];
+    data[15] = 0x01;
+    data[24] = 0x02;
+    let ix = delegated_write_instruction(
+        &setup.delegation_auth,
+        &setup.envelope_pubkey,
+        &setup.guard_pubkey,
+        &INSTRUCTIONS_SYSVAR_ID,
+        &data,
+    );
+
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &with_padding(&ix, &setup.accounts),
+        &[Check::success()],
+    );
+
+    let envelope: &Envelope = bytemuck::from_bytes(&result.resulting_accounts[1].1.data);
+    assert_eq!(envelope.auxiliary_data[15], 0x01);
+    assert_eq!(envelope.auxiliary_data[24], 0x02);
+}
+
+#[test]
+fn test_guarded_write_requires_guard_accounts() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let padding = Address::new_unique();
+
+    let ix = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &update_auxiliary_delegated_instruction_data(TEST_META_U64, 1, &guarded_payload()),
+        vec![
+            AccountMeta::new_readonly(delegation_auth, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(padding, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (delegation_auth, create_funded_account(0)),
+            (
+                envelope_pubkey,
+                guarded_delegated_envelope(&authority, &delegation_auth),
+            ),
+            (padding, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+}
+
+#[test]
+fn test_guarded_write_rejects_fake_sysvar() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let settlement_program = Address::new_unique();
+    let mut setup = guarded_setup(&[], settlement_program);
+    let fake_sysvar = Address::new_unique();
+    setup.accounts[3] = (
+        fake_sysvar,
+        create_instructions_sysvar(&[setup.settlement_program]),
+    );
+
+    let ix = delegated_write_instruction(
+        &setup.delegation_auth,
+        &setup.envelope_pubkey,
+        &setup.guard_pubkey,
+        &fake_sysvar,
+        &guarded_payload(),
+    );
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &with_padding(&ix, &setup.accounts),
+        &[Check::err(ProgramError::UnsupportedSysvar)],
+    );
+}
+
+#[test]
+fn test_guarded_write_rejects_guard_for_other_envelope() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let settlement_program = Address::new_unique();
+    let mut setup = guarded_setup(&[PROGRAM_ID], settlement_program);
+    // A permissive guard that belongs to a different envelope.
+    let other_guard = Address::new_unique();
+    setup.accounts[2] = (other_guard, create_range_guard(&Address::new_unique(), &[]));
+
+    let ix = delegated_write_instruction(
+        &setup.delegation_auth,
+        &setup.envelope_pubkey,
+        &other_guard,
+        &INSTRUCTIONS_SYSVAR_ID,
+        &guarded_payload(),
+    );
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &with_padding(&ix, &setup.accounts),
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_guarded_range_write_checks_guard() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let settlement_program = Address::new_unique();
+    let setup = guarded_setup(&[PROGRAM_ID], settlement_program);
+
+    let ix = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &update_auxiliary_delegated_range_instruction_data(TEST_META_U64, 1, 20, &[0x55]),
+        vec![
+            AccountMeta::new_readonly(setup.delegation_auth, true),
+            AccountMeta::new(setup.envelope_pubkey, false),
+            AccountMeta::new_readonly(Address::new_unique(), false),
+            AccountMeta::new_readonly(setup.guard_pubkey, false),
+            AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &with_padding(&ix, &setup.accounts),
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
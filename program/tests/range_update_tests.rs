@@ -4,6 +4,7 @@ use c_u_soon::{Envelope, Mask, AUX_DATA_SIZE};
 use c_u_soon_client::{
     update_auxiliary_delegated_range_instruction_data, update_auxiliary_range_instruction_data,
 };
+use c_u_soon_instruction::{METADATA_HASH_MISMATCH_ERROR, METADATA_SIZE_MISMATCH_ERROR};
 use common::{
     create_delegated_envelope, create_funded_account, new_mollusk, new_mollusk_silent, PROGRAM_ID,
     PROGRAM_PATH, TEST_META_U64, TEST_TYPE_SIZE,
@@ -413,18 +414,25 @@ fn test_range_reject_bad_metadata() {
         Mask::ALL_WRITABLE,
     );
 
-    let bad_meta = 0xDEAD_BEEF_u64;
-    let ix = range_instruction(&authority, &envelope_pubkey, &pda, bad_meta, 1, 0, &[0x01]);
-
-    mollusk.process_and_validate_instruction(
-        &ix,
-        &[
-            (authority, create_funded_account(1_000_000_000)),
-            (envelope_pubkey, envelope),
-            (pda, create_funded_account(0)),
-        ],
-        &[Check::err(ProgramError::InvalidInstructionData)],
-    );
+    for (bad_meta, code) in [
+        (0xDEAD_BEEF_u64, METADATA_SIZE_MISMATCH_ERROR),
+        (
+            StructMetadata::new(TEST_TYPE_SIZE as u8, 0xBAD).as_u64(),
+            METADATA_HASH_MISMATCH_ERROR,
+        ),
+    ] {
+        let ix = range_instruction(&authority, &envelope_pubkey, &pda, bad_meta, 1, 0, &[0x01]);
+
+        mollusk.process_and_validate_instruction(
+            &ix,
+            &[
+                (authority, create_funded_account(1_000_000_000)),
+                (envelope_pubkey, envelope.clone()),
+                (pda, create_funded_account(0)),
+            ],
+            &[Check::err(ProgramError::Custom(code))],
+        );
+    }
 }
 
 #[test]
@@ -1242,7 +1250,12 @@ fn create_delegated_envelope_with_meta(
             _pad: [0u8; 1],
         },
         bump: 0,
-        _padding: [0u8; 7],
+        flags: 0,
+        aux_write_count: 0,
+        aux_dirty_blocks: 0,
+        constrained_regions: 0,
+        paused: 0,
+        emit_events: 0,
         delegation_authority: *delegation_authority,
         program_bitmask,
         user_bitmask,
@@ -1250,6 +1263,10 @@ fn create_delegated_envelope_with_meta(
         program_aux_sequence: 0,
         auxiliary_metadata: meta,
         auxiliary_data: [0u8; AUX_DATA_SIZE],
+        publisher: Address::default(),
+        heartbeat_slot: 0,
+        write_hook: Address::default(),
+        mutation_marker: 0,
     };
     solana_sdk::account::Account {
         lamports: 1_000_000_000,
@@ -1580,3 +1597,97 @@ fn test_delegated_range_blocked_byte_unchanged_succeeds() {
     assert_eq!(env.auxiliary_data[5], 0x42);
     assert_eq!(env.auxiliary_data[7], 0xCC);
 }
+
+// ============================================================================
+// Dirty block tracking
+// ============================================================================
+
+#[test]
+fn test_range_write_records_dirty_blocks() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let pda = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let envelope = create_delegated_envelope(
+        &authority,
+        &delegation_auth,
+        Mask::ALL_BLOCKED,
+        Mask::ALL_WRITABLE,
+    );
+
+    // Bytes [30, 34) straddle blocks 0 and 1.
+    let ix = range_instruction(
+        &authority,
+        &envelope_pubkey,
+        &pda,
+        TEST_META_U64,
+        1,
+        30,
+        &[0xAA; 4],
+    );
+
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            (pda, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+
+    let env: &Envelope = bytemuck::from_bytes(
+        &result.resulting_accounts[1].1.data[..core::mem::size_of::<Envelope>()],
+    );
+    assert_eq!(env.aux_dirty_blocks, 0b0000_0011);
+    assert_eq!(env.aux_write_count, 1);
+}
+
+#[test]
+fn test_delegated_range_write_replaces_dirty_blocks() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+    let padding = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let mut envelope = create_delegated_envelope(
+        &authority,
+        &delegation_auth,
+        Mask::ALL_WRITABLE,
+        Mask::ALL_BLOCKED,
+    );
+    {
+        let env: &mut Envelope = bytemuck::from_bytes_mut(&mut envelope.data);
+        env.aux_dirty_blocks = 0b0000_0001;
+        env.aux_write_count = 7;
+    }
+
+    let ix = delegated_range_instruction(
+        &delegation_auth,
+        &envelope_pubkey,
+        &padding,
+        TEST_META_U64,
+        1,
+        160,
+        &[0xBB; 8],
+    );
+
+    let result = mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (delegation_auth, create_funded_account(0)),
+            (envelope_pubkey, envelope),
+            (padding, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+
+    let env: &Envelope = bytemuck::from_bytes(
+        &result.resulting_accounts[1].1.data[..core::mem::size_of::<Envelope>()],
+    );
+    assert_eq!(env.aux_dirty_blocks, 0b0010_0000);
+    assert_eq!(env.aux_write_count, 8);
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use c_u_soon::Envelope;
+use c_u_soon_client::read_aux_range_instruction_data;
+use c_u_soon_instruction::SlowPathInstruction;
+use common::{
+    create_existing_envelope, create_funded_account, new_mollusk, new_mollusk_silent, PROGRAM_ID,
+    PROGRAM_PATH,
+};
+use mollusk_svm::result::Check;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+};
+
+fn read_instruction(envelope: Address, data: Vec<u8>) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![AccountMeta::new_readonly(envelope, false)],
+    )
+}
+
+/// Envelope whose aux byte `i` is `i as u8`.
+fn patterned_envelope() -> Account {
+    let mut account = create_existing_envelope(&Address::new_unique(), 0);
+    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut account.data);
+    for (i, byte) in envelope.auxiliary_data.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    account
+}
+
+#[test]
+fn test_read_aux_range_returns_requested_bytes() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let envelope_pubkey = Address::new_unique();
+    let account = patterned_envelope();
+
+    let result = mollusk.process_and_validate_instruction(
+        &read_instruction(
+            envelope_pubkey,
+            read_aux_range_instruction_data(16, 8).unwrap(),
+        ),
+        &[(envelope_pubkey, account.clone())],
+        &[
+            Check::success(),
+            Check::return_data(&[16, 17, 18, 19, 20, 21, 22, 23]),
+        ],
+    );
+
+    // Read-only: the envelope is unchanged.
+    assert_eq!(result.resulting_accounts[0].1.data, account.data);
+}
+
+#[test]
+fn test_read_aux_range_full_region() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let envelope_pubkey = Address::new_unique();
+    let expected: Vec<u8> = (0..=255).collect();
+
+    mollusk.process_and_validate_instruction(
+        &read_instruction(
+            envelope_pubkey,
+            read_aux_range_instruction_data(0, 256).unwrap(),
+        ),
+        &[(envelope_pubkey, patterned_envelope())],
+        &[Check::success(), Check::return_data(&expected)],
+    );
+}
+
+#[test]
+fn test_read_aux_range_rejects_out_of_bounds() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let envelope_pubkey = Address::new_unique();
+    let data = wincode::serialize(&SlowPathInstruction::ReadAuxRange {
+        offset: 250,
+        len: 8,
+    })
+    .unwrap();
+
+    mollusk.process_and_validate_instruction(
+        &read_instruction(envelope_pubkey, data),
+        &[(envelope_pubkey, patterned_envelope())],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_read_aux_range_rejects_foreign_account() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let envelope_pubkey = Address::new_unique();
+    let mut account = patterned_envelope();
+    account.owner = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &read_instruction(
+            envelope_pubkey,
+            read_aux_range_instruction_data(0, 8).unwrap(),
+        ),
+        &[(envelope_pubkey, account)],
+        &[Check::err(ProgramError::IncorrectProgramId)],
+    );
+}
+
+#[test]
+fn test_read_aux_range_rejects_extra_accounts() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let envelope_pubkey = Address::new_unique();
+    let other = Address::new_unique();
+
+    let mut ix = read_instruction(
+        envelope_pubkey,
+        read_aux_range_instruction_data(0, 8).unwrap(),
+    );
+    ix.accounts.push(AccountMeta::new_readonly(other, false));
+    ix.accounts.push(AccountMeta::new_readonly(other, false));
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &[
+            (envelope_pubkey, patterned_envelope()),
+            (other, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+}
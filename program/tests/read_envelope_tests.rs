@@ -0,0 +1,142 @@
+mod common;
+
+use c_u_soon::{Envelope, StructMetadata, AUX_DATA_SIZE, ORACLE_BYTES};
+use c_u_soon_client::{
+    envelope_state_hash, export_state_instruction_data, read_aux_instruction_data,
+    read_oracle_instruction_data,
+};
+use c_u_soon_instruction::{
+    split_export_state, split_read_aux, split_read_oracle, EXPORT_STATE_RETURN_SIZE,
+    READ_AUX_RETURN_SIZE, READ_ORACLE_RETURN_SIZE,
+};
+use common::{create_existing_envelope, new_mollusk, new_mollusk_silent, PROGRAM_ID, PROGRAM_PATH};
+use mollusk_svm::result::Check;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+};
+
+fn read_instruction(envelope: Address, data: Vec<u8>) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![AccountMeta::new_readonly(envelope, false)],
+    )
+}
+
+/// Envelope with distinct oracle and aux contents.
+fn populated_envelope() -> Account {
+    let mut account = create_existing_envelope(&Address::new_unique(), 41);
+    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut account.data);
+    envelope.oracle_state.oracle_metadata = StructMetadata::new(8, 0x1234);
+    for (i, byte) in envelope.oracle_state.data.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    envelope.auxiliary_metadata = StructMetadata::new(16, 0x5678);
+    for (i, byte) in envelope.auxiliary_data.iter_mut().enumerate() {
+        *byte = !(i as u8);
+    }
+    account
+}
+
+#[test]
+fn test_read_oracle_returns_metadata_sequence_and_data() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let envelope_pubkey = Address::new_unique();
+    let account = populated_envelope();
+
+    let result = mollusk.process_and_validate_instruction(
+        &read_instruction(envelope_pubkey, read_oracle_instruction_data().unwrap()),
+        &[(envelope_pubkey, account.clone())],
+        &[Check::success()],
+    );
+
+    assert_eq!(result.return_data.len(), READ_ORACLE_RETURN_SIZE);
+    let (metadata, sequence, data) = split_read_oracle(&result.return_data).unwrap();
+    assert_eq!(metadata, StructMetadata::new(8, 0x1234));
+    assert_eq!(sequence, 41);
+    let expected: Vec<u8> = (0..ORACLE_BYTES).map(|i| i as u8).collect();
+    assert_eq!(data, expected.as_slice());
+
+    // Read-only: the envelope is unchanged.
+    assert_eq!(result.resulting_accounts[0].1.data, account.data);
+}
+
+#[test]
+fn test_read_aux_returns_metadata_and_data() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let envelope_pubkey = Address::new_unique();
+    let account = populated_envelope();
+
+    let result = mollusk.process_and_validate_instruction(
+        &read_instruction(envelope_pubkey, read_aux_instruction_data().unwrap()),
+        &[(envelope_pubkey, account.clone())],
+        &[Check::success()],
+    );
+
+    assert_eq!(result.return_data.len(), READ_AUX_RETURN_SIZE);
+    let (metadata, data) = split_read_aux(&result.return_data).unwrap();
+    assert_eq!(metadata, StructMetadata::new(16, 0x5678));
+    let expected: Vec<u8> = (0..AUX_DATA_SIZE).map(|i| !(i as u8)).collect();
+    assert_eq!(data, expected.as_slice());
+    assert_eq!(result.resulting_accounts[0].1.data, account.data);
+}
+
+#[test]
+fn test_export_state_returns_slot_and_envelope_hash() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.warp_to_slot(123);
+    let envelope_pubkey = Address::new_unique();
+    let account = populated_envelope();
+
+    let result = mollusk.process_and_validate_instruction(
+        &read_instruction(envelope_pubkey, export_state_instruction_data().unwrap()),
+        &[(envelope_pubkey, account.clone())],
+        &[Check::success()],
+    );
+
+    assert_eq!(result.return_data.len(), EXPORT_STATE_RETURN_SIZE);
+    let (slot, hash) = split_export_state(&result.return_data).unwrap();
+    assert_eq!(slot, 123);
+    let envelope: &Envelope = bytemuck::from_bytes(&account.data);
+    assert_eq!(hash, envelope_state_hash(envelope));
+    assert_eq!(result.resulting_accounts[0].1.data, account.data);
+}
+
+#[test]
+fn test_read_getters_reject_foreign_account() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let envelope_pubkey = Address::new_unique();
+    let mut account = populated_envelope();
+    account.owner = Address::new_unique();
+
+    for data in [
+        read_oracle_instruction_data().unwrap(),
+        read_aux_instruction_data().unwrap(),
+        export_state_instruction_data().unwrap(),
+    ] {
+        mollusk.process_and_validate_instruction(
+            &read_instruction(envelope_pubkey, data),
+            &[(envelope_pubkey, account.clone())],
+            &[Check::err(ProgramError::IncorrectProgramId)],
+        );
+    }
+}
+
+#[test]
+fn test_read_getters_reject_missing_account() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+
+    for data in [
+        read_oracle_instruction_data().unwrap(),
+        read_aux_instruction_data().unwrap(),
+        export_state_instruction_data().unwrap(),
+    ] {
+        mollusk.process_and_validate_instruction(
+            &Instruction::new_with_bytes(PROGRAM_ID, &data, vec![]),
+            &[],
+            &[Check::err(ProgramError::NotEnoughAccountKeys)],
+        );
+    }
+}
@@ -0,0 +1,294 @@
+mod common;
+
+use c_u_soon::{Envelope, ReaderList, StructMetadata, FLAG_GATED_READS};
+use c_u_soon_client::{
+    read_oracle_gated_instruction_data, read_oracle_instruction_data, set_readers_instruction_data,
+};
+use c_u_soon_instruction::{split_read_oracle, READ_ORACLE_RETURN_SIZE};
+use common::{
+    create_existing_envelope, create_funded_account, create_instructions_sysvar,
+    create_publisher_set, create_reader_list, find_publishers_pda, find_readers_pda, new_mollusk,
+    new_mollusk_silent, INSTRUCTIONS_SYSVAR_ID, PROGRAM_ID, PROGRAM_PATH,
+};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_system_interface::program as system_program;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn envelope(account: &Account) -> &Envelope {
+    bytemuck::from_bytes(&account.data)
+}
+
+fn set_readers_instruction(
+    authority: &Address,
+    envelope_pubkey: &Address,
+    list_pubkey: &Address,
+    bump: u8,
+    readers: &[Address],
+) -> Instruction {
+    let readers: Vec<[u8; 32]> = readers.iter().map(|r| r.to_bytes()).collect();
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_readers_instruction_data(bump, &readers).unwrap(),
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(*envelope_pubkey, false),
+            AccountMeta::new(*list_pubkey, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+fn read_gated_instruction(envelope_pubkey: &Address, list_pubkey: &Address) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &read_oracle_gated_instruction_data().unwrap(),
+        vec![
+            AccountMeta::new_readonly(*envelope_pubkey, false),
+            AccountMeta::new_readonly(*list_pubkey, false),
+            AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false),
+        ],
+    )
+}
+
+/// Envelope with `FLAG_GATED_READS` set and a recognisable oracle value.
+fn gated_envelope(sequence: u64) -> Account {
+    let mut account = create_existing_envelope(&Address::new_unique(), sequence);
+    let env: &mut Envelope = bytemuck::from_bytes_mut(&mut account.data);
+    env.flags |= FLAG_GATED_READS;
+    env.oracle_state.oracle_metadata = StructMetadata::new(8, 0x1234);
+    env.oracle_state.data[..8].copy_from_slice(&77u64.to_le_bytes());
+    account
+}
+
+/// Gated read from a transaction whose current top-level instruction targets `caller`.
+fn read_gated(caller: Address, readers: &[Address], check: Check) -> Vec<u8> {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let envelope_pubkey = Address::new_unique();
+    let (list_pubkey, _) = find_readers_pda(&envelope_pubkey);
+
+    let result = mollusk.process_and_validate_instruction(
+        &read_gated_instruction(&envelope_pubkey, &list_pubkey),
+        &[
+            (envelope_pubkey, gated_envelope(6)),
+            (list_pubkey, create_reader_list(&envelope_pubkey, readers)),
+            (
+                INSTRUCTIONS_SYSVAR_ID,
+                create_instructions_sysvar(&[caller]),
+            ),
+        ],
+        &[check],
+    );
+    result.return_data
+}
+
+// ============================================================================
+// SetReaders
+// ============================================================================
+
+#[test]
+fn test_set_readers_creates_list_and_gates_envelope() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (list_pubkey, bump) = find_readers_pda(&envelope_pubkey);
+    let readers = [Address::new_unique(), Address::new_unique()];
+
+    let result = mollusk.process_and_validate_instruction(
+        &set_readers_instruction(&authority, &envelope_pubkey, &list_pubkey, bump, &readers),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (list_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    assert!(envelope(&result.resulting_accounts[1].1).has_gated_reads());
+    let list_account = &result.resulting_accounts[2].1;
+    assert_eq!(list_account.owner, PROGRAM_ID);
+    assert_eq!(list_account.data.len(), ReaderList::SIZE);
+    let list: &ReaderList = bytemuck::from_bytes(&list_account.data);
+    assert_eq!(list.envelope, envelope_pubkey);
+    assert_eq!(list.bump, bump);
+    assert_eq!(list.active(), &readers);
+}
+
+#[test]
+fn test_set_readers_empty_list_clears_flag() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (list_pubkey, bump) = find_readers_pda(&envelope_pubkey);
+    let mut envelope_account = create_existing_envelope(&authority, 0);
+    bytemuck::from_bytes_mut::<Envelope>(&mut envelope_account.data).flags |= FLAG_GATED_READS;
+
+    let result = mollusk.process_and_validate_instruction(
+        &set_readers_instruction(&authority, &envelope_pubkey, &list_pubkey, bump, &[]),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope_account),
+            (
+                list_pubkey,
+                create_reader_list(&envelope_pubkey, &[Address::new_unique()]),
+            ),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+
+    assert!(!envelope(&result.resulting_accounts[1].1).has_gated_reads());
+    let list: &ReaderList = bytemuck::from_bytes(&result.resulting_accounts[2].1.data);
+    assert!(list.active().is_empty());
+}
+
+#[test]
+fn test_set_readers_rejects_wrong_authority() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let attacker = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (list_pubkey, bump) = find_readers_pda(&envelope_pubkey);
+
+    mollusk.process_and_validate_instruction(
+        &set_readers_instruction(&attacker, &envelope_pubkey, &list_pubkey, bump, &[attacker]),
+        &[
+            (attacker, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (list_pubkey, create_funded_account(0)),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+// ============================================================================
+// ReadOracle / ReadOracleGated
+// ============================================================================
+
+#[test]
+fn test_read_oracle_rejected_when_gated() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let envelope_pubkey = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &Instruction::new_with_bytes(
+            PROGRAM_ID,
+            &read_oracle_instruction_data().unwrap(),
+            vec![AccountMeta::new_readonly(envelope_pubkey, false)],
+        ),
+        &[(envelope_pubkey, gated_envelope(0))],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_read_oracle_gated_returns_oracle_to_listed_reader() {
+    let reader = Address::new_unique();
+    let data = read_gated(reader, &[Address::new_unique(), reader], Check::success());
+
+    assert_eq!(data.len(), READ_ORACLE_RETURN_SIZE);
+    let (metadata, sequence, payload) = split_read_oracle(&data).unwrap();
+    assert_eq!(metadata, StructMetadata::new(8, 0x1234));
+    assert_eq!(sequence, 6);
+    assert_eq!(payload[..8], 77u64.to_le_bytes());
+}
+
+#[test]
+fn test_read_oracle_gated_rejects_unlisted_caller() {
+    let readers = [Address::new_unique()];
+    read_gated(
+        Address::new_unique(),
+        &readers,
+        Check::err(ProgramError::IncorrectAuthority),
+    );
+    // Called directly, the top-level program is c_u_soon itself.
+    read_gated(
+        PROGRAM_ID,
+        &readers,
+        Check::err(ProgramError::IncorrectAuthority),
+    );
+}
+
+#[test]
+fn test_read_oracle_gated_rejects_other_envelopes_list() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let reader = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (list_pubkey, _) = find_readers_pda(&envelope_pubkey);
+
+    mollusk.process_and_validate_instruction(
+        &read_gated_instruction(&envelope_pubkey, &list_pubkey),
+        &[
+            (envelope_pubkey, gated_envelope(0)),
+            (
+                list_pubkey,
+                create_reader_list(&Address::new_unique(), &[reader]),
+            ),
+            (
+                INSTRUCTIONS_SYSVAR_ID,
+                create_instructions_sysvar(&[reader]),
+            ),
+        ],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_read_oracle_gated_rejects_publisher_set() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let reader = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    // A publisher set naming the reader has the list's size and owner and names the same
+    // envelope; only its address tells them apart.
+    let (set_pubkey, _) = find_publishers_pda(&envelope_pubkey);
+
+    mollusk.process_and_validate_instruction(
+        &read_gated_instruction(&envelope_pubkey, &set_pubkey),
+        &[
+            (envelope_pubkey, gated_envelope(0)),
+            (
+                set_pubkey,
+                create_publisher_set(&envelope_pubkey, &[reader], 1),
+            ),
+            (
+                INSTRUCTIONS_SYSVAR_ID,
+                create_instructions_sysvar(&[reader]),
+            ),
+        ],
+        &[Check::err(ProgramError::InvalidSeeds)],
+    );
+}
+
+#[test]
+fn test_read_oracle_gated_rejects_fake_sysvar() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let reader = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let (list_pubkey, _) = find_readers_pda(&envelope_pubkey);
+    let fake_sysvar = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &Instruction::new_with_bytes(
+            PROGRAM_ID,
+            &read_oracle_gated_instruction_data().unwrap(),
+            vec![
+                AccountMeta::new_readonly(envelope_pubkey, false),
+                AccountMeta::new_readonly(list_pubkey, false),
+                AccountMeta::new_readonly(fake_sysvar, false),
+            ],
+        ),
+        &[
+            (envelope_pubkey, gated_envelope(0)),
+            (list_pubkey, create_reader_list(&envelope_pubkey, &[reader])),
+            (fake_sysvar, create_instructions_sysvar(&[reader])),
+        ],
+        &[Check::err(ProgramError::UnsupportedSysvar)],
+    );
+}
@@ -0,0 +1,141 @@
+use c_u_soon::{StructMetadata, ORACLE_BYTES};
+use c_u_soon_client::fast_path_instruction_data;
+use c_u_soon_test_utils::{
+    attacks, fixture_address, EnvelopeFixture, Runner, Scenario, DEFAULT_PROGRAM_ID,
+};
+use pinocchio::error::ProgramError;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+const AUX_META: StructMetadata = StructMetadata::new(16, 0);
+
+#[test]
+fn test_scenario_create_and_fast_path() {
+    let result = Scenario::new()
+        .create_envelope("feed1")
+        .fast_path(1, &[7; 8])
+        .expect_oracle(1, &[7; 8])
+        .fast_path(1, &[9; 8])
+        .expect_err(ProgramError::InvalidInstructionData)
+        .fast_path(2, &[9; 8])
+        .expect_oracle(2, &[9; 8])
+        .run();
+
+    let envelope = result.envelope("feed1");
+    assert_eq!(envelope.authority, fixture_address("feed1/authority"));
+}
+
+#[test]
+fn test_scenario_delegated_aux_write() {
+    Scenario::new()
+        .existing_envelope("feed1", AUX_META)
+        .delegate_to("prog")
+        .update_aux_delegated(1, &[0xAB; 16])
+        .expect_aux(0, &[0xAB; 16])
+        // The authority's bitmask blocks every byte.
+        .update_aux(1, &[0x01; 16])
+        .expect_err(ProgramError::InvalidArgument)
+        .expect_aux(0, &[0xAB; 16])
+        .clear_delegation()
+        .expect_aux(0, &[0; 16])
+        .run();
+}
+
+#[test]
+fn test_scenario_envelopes_are_independent() {
+    let result = Scenario::new()
+        .create_envelope("feed1")
+        .create_envelope("feed2")
+        .fast_path(5, &[2; 4])
+        .on("feed1")
+        .fast_path(1, &[1; 4])
+        .close()
+        .run();
+
+    assert_eq!(result.envelope("feed2").oracle_state.sequence, 5);
+    let closed = result.account(&result.address("feed1")).unwrap();
+    assert_eq!(closed.lamports, 0);
+}
+
+#[test]
+fn test_runner_chains_state_and_rejects_attacks() {
+    let mut runner = Runner::new();
+    let authority = fixture_address("runner/authority");
+    let envelope = fixture_address("runner/envelope");
+    runner.fund(authority, 1_000_000_000).set_account(
+        envelope,
+        EnvelopeFixture::new(authority).sequence(4).account(),
+    );
+
+    let update = |sequence: u64| {
+        Instruction::new_with_bytes(
+            DEFAULT_PROGRAM_ID,
+            &fast_path_instruction_data(0, sequence, &[3; 8]).unwrap(),
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(envelope, false),
+            ],
+        )
+    };
+
+    attacks::assert_requires_signer(
+        &mut runner,
+        &update(5),
+        0,
+        ProgramError::MissingRequiredSignature,
+    );
+    attacks::assert_rejects_impostor(
+        &mut runner,
+        &update(5),
+        0,
+        fixture_address("runner/impostor"),
+        ProgramError::IncorrectAuthority,
+    );
+    attacks::assert_replay_rejected(
+        &mut runner,
+        &update(5),
+        ProgramError::InvalidInstructionData,
+    );
+
+    runner.expect_ok(&update(6));
+    let oracle = &runner.envelope(&envelope).oracle_state;
+    assert_eq!(oracle.sequence, 6);
+    assert_eq!(oracle.data[..8], [3; 8]);
+}
+
+#[test]
+fn test_fast_path_rejects_foreign_owned_envelope() {
+    let mut runner = Runner::new();
+    let authority = fixture_address("foreign/authority");
+    let envelope = fixture_address("foreign/envelope");
+    let foreign = fixture_address("foreign/spoofed");
+    let fixture = EnvelopeFixture::new(authority);
+    runner
+        .fund(authority, 1_000_000_000)
+        .set_account(envelope, fixture.account())
+        .set_account(
+            foreign,
+            attacks::owned_by(&fixture.account(), fixture_address("foreign/other_program")),
+        );
+
+    // Empty and full payloads, the two ends of the single memcpy.
+    for (sequence, payload) in [(1, &[][..]), (2, &[0xAB; ORACLE_BYTES][..])] {
+        let update = Instruction::new_with_bytes(
+            DEFAULT_PROGRAM_ID,
+            &fast_path_instruction_data(0, sequence, payload).unwrap(),
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(envelope, false),
+            ],
+        );
+        attacks::assert_rejects_impostor(
+            &mut runner,
+            &update,
+            1,
+            foreign,
+            ProgramError::IncorrectProgramId,
+        );
+        runner.expect_ok(&update);
+    }
+    assert_eq!(runner.envelope(&foreign).oracle_state.sequence, 0);
+    assert_eq!(runner.envelope(&envelope).oracle_state.sequence, 2);
+}
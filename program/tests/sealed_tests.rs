@@ -0,0 +1,271 @@
+use c_u_soon::{CompactMask, Mask, TypeHash, COMPACT_MASK_SIZE};
+use c_u_soon_client::{
+    build_clear_delegation_ix, build_seal_auxiliary_range_ix,
+    build_set_delegated_program_compact_ix, build_set_delegated_program_ix,
+    build_update_auxiliary_ix, update_auxiliary_delegated_instruction_data,
+    update_auxiliary_force_instruction_data,
+};
+use c_u_soon_instruction::{SlowPathInstruction, WriteSpec};
+use c_u_soon_test_utils::{fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+type Aux = [u8; 16];
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    delegate: Address,
+    padding: Address,
+    envelope: Address,
+}
+
+/// A delegated envelope with a 16-byte aux type, whose delegate may write aux bytes 0..8 and
+/// whose authority may write aux bytes 8..16. Bytes 8..10 hold `[0xAB, 0xCD]`.
+fn setup() -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("sealed/authority");
+    let delegate = fixture_address("sealed/delegate");
+    let padding = fixture_address("sealed/padding");
+    let envelope = fixture_address("sealed/envelope");
+    let mut program = Mask::ALL_BLOCKED;
+    let mut user = Mask::ALL_BLOCKED;
+    program.allow_range(0, 8);
+    user.allow_range(8, 8);
+    let mut aux = [0u8; 16];
+    aux[8..10].copy_from_slice(&[0xAB, 0xCD]);
+    runner
+        .fund(authority, 1_000_000_000)
+        .fund(delegate, 1_000_000_000)
+        .fund(padding, 0)
+        .set_account(
+            envelope,
+            EnvelopeFixture::new(authority)
+                .oracle(u64::METADATA, &7u64.to_le_bytes())
+                .sequence(1)
+                .aux(Aux::METADATA, &aux)
+                .delegated(delegate, program, user)
+                .account(),
+        );
+    Setup {
+        runner,
+        authority,
+        delegate,
+        padding,
+        envelope,
+    }
+}
+
+impl Setup {
+    fn seal(&self, signer: Address, offset: u16, len: u16) -> Instruction {
+        build_seal_auxiliary_range_ix(&DEFAULT_PROGRAM_ID, &signer, &self.envelope, offset, len)
+            .unwrap()
+    }
+
+    fn aux_write(&self, sequence: u64, data: Aux) -> Instruction {
+        build_update_auxiliary_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            &self.envelope,
+            &self.padding,
+            Aux::METADATA.as_u64(),
+            sequence,
+            &data,
+        )
+    }
+
+    fn delegated_write(&self, sequence: u64, data: Aux) -> Instruction {
+        Instruction::new_with_bytes(
+            DEFAULT_PROGRAM_ID,
+            &update_auxiliary_delegated_instruction_data(Aux::METADATA.as_u64(), sequence, &data),
+            vec![
+                AccountMeta::new_readonly(self.delegate, true),
+                AccountMeta::new(self.envelope, false),
+                AccountMeta::new_readonly(self.padding, false),
+            ],
+        )
+    }
+
+    fn both_sign(&self, data: &[u8]) -> Instruction {
+        Instruction::new_with_bytes(
+            DEFAULT_PROGRAM_ID,
+            data,
+            vec![
+                AccountMeta::new_readonly(self.authority, true),
+                AccountMeta::new(self.envelope, false),
+                AccountMeta::new_readonly(self.delegate, true),
+            ],
+        )
+    }
+
+    fn force_write(&self, sequence: u64, data: Aux) -> Instruction {
+        self.both_sign(&update_auxiliary_force_instruction_data(
+            Aux::METADATA.as_u64(),
+            sequence,
+            sequence,
+            &data,
+        ))
+    }
+
+    fn force_ranges(&self, sequence: u64, ranges: Vec<WriteSpec>) -> Instruction {
+        self.both_sign(
+            &wincode::serialize(&SlowPathInstruction::UpdateAuxiliaryMultiRangeForce {
+                metadata: Aux::METADATA.as_u64(),
+                authority_sequence: sequence,
+                program_sequence: sequence,
+                ranges,
+            })
+            .unwrap(),
+        )
+    }
+}
+
+#[test]
+fn test_seal_marks_both_masks() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.seal(s.authority, 8, 2));
+    let envelope = s.runner.envelope(&s.envelope);
+    for mask in [&envelope.program_bitmask, &envelope.user_bitmask] {
+        assert!(mask.is_sealed(8) && mask.is_sealed(9));
+        assert!(!mask.is_sealed(7) && !mask.is_sealed(10));
+    }
+    assert!(envelope.user_bitmask.is_writable(10));
+    assert_eq!(envelope.auxiliary_data[8..10], [0xAB, 0xCD]);
+
+    // Sealing again is a no-op.
+    s.runner.expect_ok(&s.seal(s.authority, 8, 2));
+}
+
+#[test]
+fn test_only_authority_seals_valid_ranges() {
+    let mut s = setup();
+    s.runner
+        .expect_err(&s.seal(s.delegate, 8, 2), ProgramError::IncorrectAuthority);
+
+    let data = wincode::serialize(&SlowPathInstruction::SealAuxiliaryRange {
+        offset: 250,
+        len: 7,
+    })
+    .unwrap();
+    let mut ix = s.seal(s.authority, 8, 2);
+    ix.data = data;
+    s.runner
+        .expect_err(&ix, ProgramError::InvalidInstructionData);
+    assert!(!s.runner.envelope(&s.envelope).user_bitmask.has_sealed());
+}
+
+#[test]
+fn test_masked_writes_cannot_change_sealed_bytes() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.seal(s.authority, 8, 2));
+
+    let mut data = [0u8; 16];
+    data[8..10].copy_from_slice(&[0xAB, 0xCD]);
+    data[10..].fill(0x11);
+    s.runner.expect_ok(&s.aux_write(1, data));
+    data[8] = 0;
+    s.runner
+        .expect_err(&s.aux_write(2, data), ProgramError::InvalidArgument);
+
+    data[8] = 0xAB;
+    data[..8].fill(0x22);
+    s.runner.expect_ok(&s.delegated_write(1, data));
+
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.auxiliary_data[8..10], [0xAB, 0xCD]);
+    assert_eq!(envelope.auxiliary_data[10..16], [0x11; 6]);
+}
+
+#[test]
+fn test_force_writes_cannot_change_sealed_bytes() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.seal(s.authority, 8, 2));
+
+    let mut data = [0x33u8; 16];
+    s.runner
+        .expect_err(&s.force_write(1, data), ProgramError::InvalidArgument);
+    s.runner.expect_err(
+        &s.force_ranges(
+            1,
+            vec![WriteSpec {
+                offset: 9,
+                data: vec![0],
+            }],
+        ),
+        ProgramError::InvalidArgument,
+    );
+
+    data[8..10].copy_from_slice(&[0xAB, 0xCD]);
+    s.runner.expect_ok(&s.force_write(1, data));
+    s.runner.expect_ok(&s.force_ranges(
+        2,
+        vec![WriteSpec {
+            offset: 10,
+            data: vec![0x44; 6],
+        }],
+    ));
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.auxiliary_data[..8], [0x33; 8]);
+    assert_eq!(envelope.auxiliary_data[8..10], [0xAB, 0xCD]);
+    assert_eq!(envelope.auxiliary_data[10..16], [0x44; 6]);
+}
+
+#[test]
+fn test_sealed_bytes_survive_redelegation() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.seal(s.authority, 8, 2));
+    s.runner.expect_ok(
+        &build_clear_delegation_ix(&DEFAULT_PROGRAM_ID, &s.authority, &s.envelope, &s.delegate)
+            .unwrap(),
+    );
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.auxiliary_data[8..10], [0xAB, 0xCD]);
+    assert_eq!(envelope.auxiliary_data[..8], [0; 8]);
+    assert!(envelope.program_bitmask.is_sealed(8) && envelope.user_bitmask.is_sealed(9));
+    assert!(envelope.program_bitmask.is_all_blocked());
+
+    s.runner.expect_ok(
+        &build_set_delegated_program_ix(
+            &DEFAULT_PROGRAM_ID,
+            &s.authority,
+            &s.envelope,
+            &s.delegate,
+            Mask::ALL_WRITABLE,
+            Mask::ALL_WRITABLE,
+        )
+        .unwrap(),
+    );
+    let envelope = s.runner.envelope(&s.envelope);
+    assert!(envelope.program_bitmask.is_sealed(8) && envelope.user_bitmask.is_sealed(9));
+    assert!(envelope.program_bitmask.is_writable(10));
+}
+
+#[test]
+fn test_compact_redelegation_keeps_seals() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.seal(s.authority, 8, 2));
+    s.runner.expect_ok(
+        &build_clear_delegation_ix(&DEFAULT_PROGRAM_ID, &s.authority, &s.envelope, &s.delegate)
+            .unwrap(),
+    );
+
+    let mut program = [0u8; COMPACT_MASK_SIZE];
+    program[..2].fill(0xFF);
+    s.runner.expect_ok(
+        &build_set_delegated_program_compact_ix(
+            &DEFAULT_PROGRAM_ID,
+            &s.authority,
+            &s.envelope,
+            &s.delegate,
+            CompactMask::from_bytes(program),
+            CompactMask::ZERO,
+        )
+        .unwrap(),
+    );
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.delegation_authority, s.delegate);
+    assert!(envelope.program_bitmask.is_writable(0) && envelope.program_bitmask.is_writable(10));
+    assert!(!envelope.program_bitmask.is_writable(16));
+    assert!(envelope.program_bitmask.is_sealed(8) && envelope.user_bitmask.is_sealed(9));
+    assert!(!envelope.user_bitmask.is_writable(0));
+}
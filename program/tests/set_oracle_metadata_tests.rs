@@ -0,0 +1,166 @@
+use c_u_soon::{Mask, StructMetadata, TwapAccumulator, TypeHash, FLAG_TWAP, ORACLE_BYTES};
+use c_u_soon_client::{
+    build_fast_path_ix, build_seal_auxiliary_range_ix, build_set_oracle_metadata_ix,
+};
+use c_u_soon_test_utils::{attacks, fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::Instruction;
+
+type Aux = [u8; 16];
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    envelope: Address,
+}
+
+fn setup_with(fixture: impl FnOnce(EnvelopeFixture) -> EnvelopeFixture) -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("set_oracle_metadata/authority");
+    let envelope = fixture_address("set_oracle_metadata/envelope");
+    runner.fund(authority, 1_000_000_000).set_account(
+        envelope,
+        fixture(EnvelopeFixture::new(authority))
+            .oracle(u64::METADATA, &7u64.to_le_bytes())
+            .sequence(3)
+            .aux(Aux::METADATA, &[1; 16])
+            .aux_sequences(2, 0)
+            .account(),
+    );
+    Setup {
+        runner,
+        authority,
+        envelope,
+    }
+}
+
+/// A `u64` oracle holding 7 at sequence 3, with `Aux` bytes `[1; 16]`.
+fn setup() -> Setup {
+    setup_with(|fixture| fixture)
+}
+
+impl Setup {
+    fn retype(&self, oracle: StructMetadata, aux: StructMetadata) -> Instruction {
+        build_set_oracle_metadata_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            &self.envelope,
+            oracle,
+            aux,
+        )
+        .unwrap()
+    }
+}
+
+#[test]
+fn test_set_oracle_metadata_retypes_the_oracle() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.retype(i64::METADATA, Aux::METADATA));
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.oracle_state.oracle_metadata, i64::METADATA);
+    assert_eq!(envelope.oracle_state.data, [0; ORACLE_BYTES]);
+    assert_eq!(envelope.oracle::<i64>(), Some(&0));
+    assert_eq!(envelope.oracle::<u64>(), None);
+    assert_eq!(envelope.aux::<Aux>(), Some(&[1; 16]));
+
+    // The sequence is kept, so writes signed before the change stay stale.
+    let write = |sequence| {
+        build_fast_path_ix(
+            &DEFAULT_PROGRAM_ID,
+            &s.authority,
+            &s.envelope,
+            i64::METADATA.as_u64(),
+            sequence,
+            &(-5i64).to_le_bytes(),
+        )
+        .unwrap()
+    };
+    s.runner
+        .expect_err(&write(3), ProgramError::InvalidInstructionData);
+    s.runner.expect_ok(&write(4));
+    assert_eq!(s.runner.envelope(&s.envelope).oracle::<i64>(), Some(&-5));
+}
+
+#[test]
+fn test_set_oracle_metadata_retypes_aux_around_sealed_bytes() {
+    let mut s = setup();
+    s.runner.expect_ok(
+        &build_seal_auxiliary_range_ix(&DEFAULT_PROGRAM_ID, &s.authority, &s.envelope, 0, 4)
+            .unwrap(),
+    );
+    let write_count = s.runner.envelope(&s.envelope).aux_write_count;
+
+    s.runner.expect_ok(&s.retype(u64::METADATA, u32::METADATA));
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.auxiliary_metadata, u32::METADATA);
+    assert_eq!(envelope.auxiliary_data[..4], [1; 4]);
+    assert!(envelope.auxiliary_data[4..].iter().all(|&b| b == 0));
+    assert_eq!(envelope.aux_write_count, write_count + 1);
+    assert_eq!(envelope.aux_dirty_blocks, 0xFF);
+    assert_eq!(envelope.authority_aux_sequence, 2);
+    assert_eq!(envelope.oracle::<u64>(), Some(&7));
+}
+
+#[test]
+fn test_set_oracle_metadata_with_current_values_changes_nothing() {
+    let mut s = setup();
+    let before = s.runner.account(&s.envelope).unwrap().data.clone();
+    s.runner.expect_ok(&s.retype(u64::METADATA, Aux::METADATA));
+    assert_eq!(s.runner.account(&s.envelope).unwrap().data, before);
+}
+
+#[test]
+fn test_set_oracle_metadata_requires_authority() {
+    let mut s = setup();
+    let ix = s.retype(i64::METADATA, u32::METADATA);
+    attacks::assert_requires_signer(
+        &mut s.runner,
+        &ix,
+        0,
+        ProgramError::MissingRequiredSignature,
+    );
+    let impostor = fixture_address("set_oracle_metadata/impostor");
+    s.runner.fund(impostor, 1_000_000_000);
+    s.runner.expect_err(
+        &attacks::with_account(&ix, 0, impostor),
+        ProgramError::IncorrectAuthority,
+    );
+    assert_eq!(
+        s.runner.envelope(&s.envelope).oracle_state.oracle_metadata,
+        u64::METADATA
+    );
+}
+
+#[test]
+fn test_set_oracle_metadata_blocked_while_delegated() {
+    let delegate = fixture_address("set_oracle_metadata/delegate");
+    let mut s =
+        setup_with(|fixture| fixture.delegated(delegate, Mask::ALL_WRITABLE, Mask::ALL_BLOCKED));
+    s.runner.expect_err(
+        &s.retype(i64::METADATA, u32::METADATA),
+        ProgramError::InvalidArgument,
+    );
+    assert_eq!(s.runner.envelope(&s.envelope).aux::<Aux>(), Some(&[1; 16]));
+}
+
+#[test]
+fn test_set_oracle_metadata_checks_the_oracle_capacity() {
+    let mut s = setup();
+    s.runner.expect_err(
+        &s.retype(
+            StructMetadata::new(ORACLE_BYTES as u8 + 1, 1),
+            Aux::METADATA,
+        ),
+        ProgramError::InvalidArgument,
+    );
+
+    // TWAP reads the first 8 payload bytes, so the new type must hold them.
+    let mut s = setup_with(|fixture| fixture.flags(FLAG_TWAP));
+    s.runner.expect_err(
+        &s.retype(u32::METADATA, Aux::METADATA),
+        ProgramError::InvalidArgument,
+    );
+    s.runner.expect_ok(&s.retype(i64::METADATA, Aux::METADATA));
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.twap(), Some(&TwapAccumulator::default()));
+}
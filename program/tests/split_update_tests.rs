@@ -0,0 +1,282 @@
+mod common;
+
+use c_u_soon::{Envelope, TypeHash};
+use c_u_soon_client::{build_split_update_ixs, resize_instruction_data, split_update_typed};
+use c_u_soon_instruction::SPLIT_UPDATE_UNPAIRED_ERROR;
+use common::{
+    create_existing_envelope, create_funded_account, create_instructions_sysvar_for,
+    new_mollusk_silent, LogWriteGuard, MolluskGuard, INSTRUCTIONS_SYSVAR_ID, PROGRAM_ID,
+    PROGRAM_PATH,
+};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_system_interface::program as system_program;
+
+/// A 320-byte composite, too large for the 239-byte oracle region or one fast-path write.
+type Composite = [u64; 40];
+
+const VALUE: Composite = {
+    let mut value = [0u64; 40];
+    let mut i = 0;
+    while i < 40 {
+        value[i] = i as u64 + 1;
+        i += 1;
+    }
+    value
+};
+
+struct Setup {
+    mollusk: MolluskGuard<LogWriteGuard>,
+    authority: Address,
+    envelope_pubkey: Address,
+    envelope: Account,
+}
+
+/// An envelope resized to hold a [`Composite`] extension.
+fn setup() -> Setup {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let mut envelope = create_existing_envelope(&authority, 0);
+    envelope.lamports = mollusk.sysvars.rent.minimum_balance(envelope.data.len());
+    let resize = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &resize_instruction_data(Composite::TYPE_HASH, core::mem::size_of::<Composite>()).unwrap(),
+        vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &resize,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            keyed_account_for_system_program(),
+        ],
+        &[Check::success()],
+    );
+    let envelope = result.resulting_accounts[1].1.clone();
+
+    Setup {
+        mollusk,
+        authority,
+        envelope_pubkey,
+        envelope,
+    }
+}
+
+impl Setup {
+    fn halves(&self, type_hash: u64, sequence: u64) -> [Instruction; 2] {
+        build_split_update_ixs(
+            &PROGRAM_ID,
+            &self.authority,
+            &self.envelope_pubkey,
+            type_hash,
+            sequence,
+            bytemuck::bytes_of(&VALUE),
+        )
+        .unwrap()
+    }
+
+    /// Run `transaction[current]` against `envelope` with a sysvar describing `transaction`.
+    fn run(
+        &self,
+        transaction: &[Instruction],
+        current: u16,
+        envelope: Account,
+        check: Check,
+    ) -> Account {
+        let ix = &transaction[current as usize];
+        let result = self.mollusk.process_and_validate_instruction(
+            ix,
+            &[
+                (ix.accounts[0].pubkey, create_funded_account(1_000_000_000)),
+                (self.envelope_pubkey, envelope),
+                (
+                    INSTRUCTIONS_SYSVAR_ID,
+                    create_instructions_sysvar_for(transaction, current),
+                ),
+            ],
+            &[check],
+        );
+        result.resulting_accounts[1].1.clone()
+    }
+
+    fn unpaired(&self, transaction: &[Instruction], current: u16) {
+        self.run(
+            transaction,
+            current,
+            self.envelope.clone(),
+            Check::err(ProgramError::Custom(SPLIT_UPDATE_UNPAIRED_ERROR)),
+        );
+    }
+}
+
+#[test]
+fn test_split_update_lands_after_both_halves() {
+    let s = setup();
+    let halves = s.halves(Composite::TYPE_HASH, 5);
+
+    let envelope = s.run(&halves, 0, s.envelope.clone(), Check::success());
+    let ext = Envelope::ext_aux::<Composite>(&envelope.data).unwrap();
+    assert_eq!(ext[..20], VALUE[..20]);
+    assert_eq!(ext[20..], [0; 20]);
+    assert_eq!(
+        Envelope::ext_aux_header(&envelope.data).unwrap().sequence,
+        0
+    );
+
+    let envelope = s.run(&halves, 1, envelope, Check::success());
+    assert_eq!(Envelope::ext_aux::<Composite>(&envelope.data), Some(&VALUE));
+    assert_eq!(
+        Envelope::ext_aux_header(&envelope.data).unwrap().sequence,
+        5
+    );
+
+    // The sequence has moved, so the same pair cannot be replayed.
+    s.run(
+        &halves,
+        0,
+        envelope,
+        Check::err(ProgramError::InvalidInstructionData),
+    );
+}
+
+#[test]
+fn test_split_update_typed_matches_raw_halves() {
+    let s = setup();
+    let typed = split_update_typed(5, &VALUE).unwrap();
+    let halves = s.halves(Composite::TYPE_HASH, 5);
+    assert_eq!(typed, [halves[0].data.clone(), halves[1].data.clone()]);
+}
+
+#[test]
+fn test_split_update_rejects_unpaired_half() {
+    let s = setup();
+    let [first, second] = s.halves(Composite::TYPE_HASH, 5);
+
+    // Alone.
+    s.unpaired(&[first.clone()], 0);
+    s.unpaired(&[second.clone()], 0);
+
+    // Out of order.
+    s.unpaired(&[second.clone(), first.clone()], 0);
+    s.unpaired(&[second.clone(), first.clone()], 1);
+
+    // Not adjacent.
+    let memo = Instruction::new_with_bytes(Address::new_unique(), &[], vec![]);
+    s.unpaired(&[first.clone(), memo.clone(), second.clone()], 0);
+    s.unpaired(&[first.clone(), memo, second.clone()], 2);
+
+    // Partner for another sequence.
+    let [_, other_sequence] = s.halves(Composite::TYPE_HASH, 6);
+    s.unpaired(&[first.clone(), other_sequence], 0);
+
+    // Partner for another envelope.
+    let mut other_envelope = second.clone();
+    other_envelope.accounts[1].pubkey = Address::new_unique();
+    s.unpaired(&[first.clone(), other_envelope], 0);
+
+    // Partner sent to another program.
+    let mut other_program = second;
+    other_program.program_id = Address::new_unique();
+    s.unpaired(&[first, other_program], 0);
+}
+
+#[test]
+fn test_split_update_rejects_cpi() {
+    let s = setup();
+    let [first, second] = s.halves(Composite::TYPE_HASH, 5);
+    // Under CPI, the current top-level instruction belongs to the calling program.
+    let mut outer = first.clone();
+    outer.program_id = Address::new_unique();
+    s.mollusk.process_and_validate_instruction(
+        &first,
+        &[
+            (s.authority, create_funded_account(1_000_000_000)),
+            (s.envelope_pubkey, s.envelope.clone()),
+            (
+                INSTRUCTIONS_SYSVAR_ID,
+                create_instructions_sysvar_for(&[outer, second], 0),
+            ),
+        ],
+        &[Check::err(ProgramError::Custom(
+            SPLIT_UPDATE_UNPAIRED_ERROR,
+        ))],
+    );
+}
+
+#[test]
+fn test_split_update_checks_extension_and_signer() {
+    let s = setup();
+
+    let halves = s.halves(Composite::TYPE_HASH ^ 1, 5);
+    s.run(
+        &halves,
+        0,
+        s.envelope.clone(),
+        Check::err(ProgramError::InvalidArgument),
+    );
+
+    let halves = s.halves(Composite::TYPE_HASH, 0);
+    s.run(
+        &halves,
+        0,
+        s.envelope.clone(),
+        Check::err(ProgramError::InvalidInstructionData),
+    );
+
+    let mut halves = s.halves(Composite::TYPE_HASH, 5);
+    let stranger = Address::new_unique();
+    for ix in &mut halves {
+        ix.accounts[0].pubkey = stranger;
+    }
+    s.run(
+        &halves,
+        0,
+        s.envelope.clone(),
+        Check::err(ProgramError::IncorrectAuthority),
+    );
+
+    // A registered publisher may send both halves in the authority's place.
+    let mut envelope = s.envelope.clone();
+    Envelope::from_prefix_bytes_mut(&mut envelope.data)
+        .unwrap()
+        .publisher = stranger;
+    let envelope = s.run(&halves, 0, envelope, Check::success());
+    let envelope = s.run(&halves, 1, envelope, Check::success());
+    assert_eq!(Envelope::ext_aux::<Composite>(&envelope.data), Some(&VALUE));
+
+    let mut halves = s.halves(Composite::TYPE_HASH, 5);
+    halves[0].accounts[2].pubkey = Address::new_unique();
+    s.mollusk.process_and_validate_instruction(
+        &halves[0],
+        &[
+            (s.authority, create_funded_account(1_000_000_000)),
+            (s.envelope_pubkey, s.envelope.clone()),
+            (
+                halves[0].accounts[2].pubkey,
+                create_instructions_sysvar_for(&halves, 0),
+            ),
+        ],
+        &[Check::err(ProgramError::UnsupportedSysvar)],
+    );
+}
+
+#[test]
+fn test_split_update_requires_extension() {
+    let s = setup();
+    let authority = s.authority;
+    let halves = s.halves(Composite::TYPE_HASH, 5);
+    s.run(
+        &halves,
+        0,
+        create_existing_envelope(&authority, 0),
+        Check::err(ProgramError::InvalidAccountData),
+    );
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use c_u_soon::{Envelope, Mask};
+use c_u_soon_client::{
+    close_instruction_data, fast_path_instruction_data, set_delegated_program_instruction_data,
+};
+use common::{
+    create_existing_envelope, create_funded_account, new_mollusk, new_mollusk_silent, PROGRAM_ID,
+    PROGRAM_PATH,
+};
+use mollusk_svm::result::Check;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+};
+
+const TRAILING: usize = 64;
+const TRAILING_BYTE: u8 = 0xAB;
+
+fn with_trailing_bytes(mut account: Account, extra: usize) -> Account {
+    let len = account.data.len() + extra;
+    account.data.resize(len, TRAILING_BYTE);
+    account
+}
+
+fn set_delegated_program_instruction(
+    authority: Address,
+    envelope: Address,
+    delegation_auth: Address,
+) -> Instruction {
+    let mut program_bitmask = Mask::ALL_BLOCKED;
+    program_bitmask.allow(0);
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_delegated_program_instruction_data(program_bitmask, Mask::ALL_BLOCKED).unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope, false),
+            AccountMeta::new_readonly(delegation_auth, true),
+        ],
+    )
+}
+
+#[test]
+fn test_slow_path_ignores_trailing_bytes() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+
+    let envelope = with_trailing_bytes(create_existing_envelope(&authority, 0), TRAILING);
+
+    let result = mollusk.process_and_validate_instruction(
+        &set_delegated_program_instruction(authority, envelope_pubkey, delegation_auth),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            (delegation_auth, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+
+    let data = &result.resulting_accounts[1].1.data;
+    assert_eq!(data.len(), Envelope::SIZE + TRAILING);
+    let env = Envelope::from_prefix_bytes(data).unwrap();
+    assert_eq!(env.delegation_authority, delegation_auth);
+    assert!(data[Envelope::SIZE..].iter().all(|&b| b == TRAILING_BYTE));
+}
+
+#[test]
+fn test_slow_path_rejects_truncated_envelope() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let delegation_auth = Address::new_unique();
+
+    let mut envelope = create_existing_envelope(&authority, 0);
+    envelope.data.truncate(Envelope::SIZE - 1);
+
+    mollusk.process_and_validate_instruction(
+        &set_delegated_program_instruction(authority, envelope_pubkey, delegation_auth),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            (delegation_auth, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_close_zeroes_trailing_bytes() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let recipient = Address::new_unique();
+
+    let envelope = with_trailing_bytes(create_existing_envelope(&authority, 3), TRAILING);
+    let envelope_lamports = envelope.lamports;
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &close_instruction_data().unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new(recipient, false),
+        ],
+    );
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            (recipient, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+
+    assert!(result.resulting_accounts[1].1.data.iter().all(|&b| b == 0));
+    assert_eq!(result.resulting_accounts[2].1.lamports, envelope_lamports);
+}
+
+#[test]
+fn test_fast_path_accepts_trailing_bytes() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let envelope = with_trailing_bytes(create_existing_envelope(&authority, 0), TRAILING);
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &fast_path_instruction_data(0, 1, &[42]).unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+        ],
+    );
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+        ],
+        &[Check::success()],
+    );
+
+    let data = &result.resulting_accounts[1].1.data;
+    let envelope = Envelope::from_prefix_bytes(data).unwrap();
+    assert_eq!(envelope.oracle_state.sequence, 1);
+    assert_eq!(envelope.oracle_state.data[0], 42);
+    assert!(data[Envelope::SIZE..].iter().all(|&b| b == TRAILING_BYTE));
+}
+
+#[test]
+fn test_fast_path_rejects_short_envelope() {
+    let mollusk = new_mollusk_silent(&PROGRAM_ID, PROGRAM_PATH, log::LevelFilter::Off);
+
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let mut envelope = create_existing_envelope(&authority, 0);
+    envelope.data.truncate(Envelope::SIZE - 1);
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &fast_path_instruction_data(0, 1, &[42]).unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+        ],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
@@ -0,0 +1,163 @@
+use c_u_soon::{Mask, TypeHash};
+use c_u_soon_client::{
+    build_clear_delegation_ix, build_transfer_delegation_ix,
+    update_auxiliary_delegated_instruction_data,
+};
+use c_u_soon_test_utils::{attacks, fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+type Aux = [u8; 16];
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    old_delegate: Address,
+    new_delegate: Address,
+    padding: Address,
+    envelope: Address,
+}
+
+/// An envelope holding 7 at sequence 1 and aux bytes `[1; 16]`, delegated to `old_delegate`
+/// with aux bytes 0..8 writable by it and 8..16 by the authority, at program aux sequence 3.
+fn setup() -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("transfer/authority");
+    let old_delegate = fixture_address("transfer/old_delegate");
+    let new_delegate = fixture_address("transfer/new_delegate");
+    let padding = fixture_address("transfer/padding");
+    let envelope = fixture_address("transfer/envelope");
+    let mut program = Mask::ALL_BLOCKED;
+    let mut user = Mask::ALL_BLOCKED;
+    program.allow_range(0, 8);
+    user.allow_range(8, 8);
+    runner
+        .fund(authority, 1_000_000_000)
+        .fund(old_delegate, 1_000_000_000)
+        .fund(new_delegate, 1_000_000_000)
+        .fund(padding, 0)
+        .set_account(
+            envelope,
+            EnvelopeFixture::new(authority)
+                .oracle(u64::METADATA, &7u64.to_le_bytes())
+                .sequence(1)
+                .aux(Aux::METADATA, &[1; 16])
+                .aux_sequences(0, 3)
+                .delegated(old_delegate, program, user)
+                .account(),
+        );
+    Setup {
+        runner,
+        authority,
+        old_delegate,
+        new_delegate,
+        padding,
+        envelope,
+    }
+}
+
+impl Setup {
+    /// Hand the delegation to `new_delegate`, which may then write aux bytes 8..16.
+    fn transfer(&self) -> Instruction {
+        let mut program = Mask::ALL_BLOCKED;
+        program.allow_range(8, 8);
+        build_transfer_delegation_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            &self.envelope,
+            &self.old_delegate,
+            &self.new_delegate,
+            program,
+            Mask::ALL_BLOCKED,
+        )
+        .unwrap()
+    }
+
+    fn delegated_write(&self, delegate: Address, sequence: u64, data: Aux) -> Instruction {
+        Instruction::new_with_bytes(
+            DEFAULT_PROGRAM_ID,
+            &update_auxiliary_delegated_instruction_data(Aux::METADATA.as_u64(), sequence, &data),
+            vec![
+                AccountMeta::new_readonly(delegate, true),
+                AccountMeta::new(self.envelope, false),
+                AccountMeta::new_readonly(self.padding, false),
+            ],
+        )
+    }
+}
+
+#[test]
+fn test_transfer_keeps_oracle_and_aux() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.transfer());
+
+    let envelope = s.runner.envelope(&s.envelope);
+    assert_eq!(envelope.delegation_authority, s.new_delegate);
+    assert!(envelope.program_bitmask.is_writable(8) && !envelope.program_bitmask.is_writable(0));
+    assert!(envelope.user_bitmask.is_all_blocked());
+    assert_eq!(envelope.oracle::<u64>(), Some(&7));
+    assert_eq!(envelope.oracle_state.sequence, 1);
+    assert_eq!(envelope.aux::<Aux>(), Some(&[1; 16]));
+    assert_eq!(envelope.program_aux_sequence, 3);
+
+    let mut data = [1u8; 16];
+    data[8..].fill(2);
+    s.runner.expect_err(
+        &s.delegated_write(s.new_delegate, 3, data),
+        ProgramError::InvalidInstructionData,
+    );
+    s.runner
+        .expect_ok(&s.delegated_write(s.new_delegate, 4, data));
+    s.runner.expect_err(
+        &s.delegated_write(s.old_delegate, 5, data),
+        ProgramError::IncorrectAuthority,
+    );
+    assert_eq!(
+        s.runner.envelope(&s.envelope).aux::<Aux>().unwrap()[8..],
+        [2; 8]
+    );
+}
+
+#[test]
+fn test_transfer_requires_all_three_signers() {
+    let mut s = setup();
+    let ix = s.transfer();
+    for index in [0, 2, 3] {
+        attacks::assert_requires_signer(
+            &mut s.runner,
+            &ix,
+            index,
+            ProgramError::MissingRequiredSignature,
+        );
+    }
+    let impostor = fixture_address("transfer/impostor");
+    s.runner.fund(impostor, 1_000_000_000);
+    s.runner.expect_err(
+        &attacks::with_account(&ix, 2, impostor),
+        ProgramError::IncorrectAuthority,
+    );
+    s.runner.expect_err(
+        &attacks::with_account(&ix, 0, impostor),
+        ProgramError::IncorrectAuthority,
+    );
+    assert_eq!(
+        s.runner.envelope(&s.envelope).delegation_authority,
+        s.old_delegate
+    );
+}
+
+#[test]
+fn test_transfer_requires_active_delegation() {
+    let mut s = setup();
+    s.runner.expect_ok(
+        &build_clear_delegation_ix(
+            &DEFAULT_PROGRAM_ID,
+            &s.authority,
+            &s.envelope,
+            &s.old_delegate,
+        )
+        .unwrap(),
+    );
+    s.runner
+        .expect_err(&s.transfer(), ProgramError::InvalidArgument);
+}
@@ -0,0 +1,283 @@
+mod common;
+
+use c_u_soon::{Envelope, StructMetadata, TypeHash, FLAG_TWAP, ORACLE_BYTES, TWAP_OFFSET};
+use c_u_soon_client::{
+    batch_update_instruction_data, fast_path_instruction_data, set_twap_instruction_data,
+};
+use c_u_soon_instruction::BatchEntry;
+use common::{
+    create_existing_envelope, create_funded_account, new_mollusk, PROGRAM_ID, PROGRAM_PATH,
+};
+use mollusk_svm::{result::Check, Mollusk};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Third account that keeps SetTwap off the two-account fast path.
+const TWAP_PADDING: Address = Address::new_from_array([0xEE; 32]);
+
+/// Envelope whose oracle type is `u64`, the smallest type a TWAP envelope accepts.
+fn u64_envelope(authority: &Address, flags: u8) -> Account {
+    let mut account = create_existing_envelope(authority, 0);
+    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut account.data);
+    envelope.oracle_state.oracle_metadata = u64::METADATA;
+    envelope.flags = flags;
+    account
+}
+
+fn envelope(account: &Account) -> &Envelope {
+    bytemuck::from_bytes(&account.data)
+}
+
+fn set_twap_instruction(authority: Address, envelope: Address, enabled: bool) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_twap_instruction_data(enabled).unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope, false),
+            AccountMeta::new_readonly(TWAP_PADDING, false),
+        ],
+    )
+}
+
+fn fast_path(
+    mollusk: &Mollusk,
+    authority: Address,
+    envelope_pubkey: Address,
+    envelope: Account,
+    sequence: u64,
+    payload: &[u8],
+    check: Check,
+) -> Account {
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &fast_path_instruction_data(u64::METADATA.as_u64(), sequence, payload).unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+        ],
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+        ],
+        &[check],
+    );
+    result.resulting_accounts[1].1.clone()
+}
+
+// ============================================================================
+// SetTwap
+// ============================================================================
+
+#[test]
+fn test_set_twap_enables_and_zeroes_accumulator() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let mut account = u64_envelope(&authority, 0);
+    bytemuck::from_bytes_mut::<Envelope>(&mut account.data)
+        .oracle_state
+        .data[TWAP_OFFSET..]
+        .fill(0xAA);
+
+    let result = mollusk.process_and_validate_instruction(
+        &set_twap_instruction(authority, envelope_pubkey, true),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, account),
+            (TWAP_PADDING, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+    let env = envelope(&result.resulting_accounts[1].1);
+    assert_eq!(env.flags & FLAG_TWAP, FLAG_TWAP);
+    assert_eq!(env.twap(), Some(&Default::default()));
+
+    let result = mollusk.process_and_validate_instruction(
+        &set_twap_instruction(authority, envelope_pubkey, false),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, result.resulting_accounts[1].1.clone()),
+            (TWAP_PADDING, create_funded_account(0)),
+        ],
+        &[Check::success()],
+    );
+    assert!(envelope(&result.resulting_accounts[1].1).twap().is_none());
+}
+
+#[test]
+fn test_set_twap_rejects_oracle_types_that_do_not_fit() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    for metadata in [
+        StructMetadata::ZERO,
+        u32::METADATA,
+        <[u8; TWAP_OFFSET + 1]>::METADATA,
+    ] {
+        let mut account = u64_envelope(&authority, 0);
+        bytemuck::from_bytes_mut::<Envelope>(&mut account.data)
+            .oracle_state
+            .oracle_metadata = metadata;
+        mollusk.process_and_validate_instruction(
+            &set_twap_instruction(authority, envelope_pubkey, true),
+            &[
+                (authority, create_funded_account(1_000_000_000)),
+                (envelope_pubkey, account),
+                (TWAP_PADDING, create_funded_account(0)),
+            ],
+            &[Check::err(ProgramError::InvalidArgument)],
+        );
+    }
+}
+
+#[test]
+fn test_set_twap_rejects_foreign_authority() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let owner = Address::new_unique();
+    let attacker = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &set_twap_instruction(attacker, envelope_pubkey, true),
+        &[
+            (attacker, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, u64_envelope(&owner, 0)),
+            (TWAP_PADDING, create_funded_account(0)),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+// ============================================================================
+// Folding
+// ============================================================================
+
+#[test]
+fn test_fast_path_folds_value_into_accumulator() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let mut account = u64_envelope(&authority, FLAG_TWAP);
+
+    for (slot, sequence, value) in [(10, 1, 100u64), (13, 2, 200), (14, 3, 250)] {
+        mollusk.warp_to_slot(slot);
+        account = fast_path(
+            &mollusk,
+            authority,
+            envelope_pubkey,
+            account,
+            sequence,
+            &value.to_le_bytes(),
+            Check::success(),
+        );
+    }
+
+    let env = envelope(&account);
+    assert_eq!(env.oracle::<u64>(), Some(&250));
+    let twap = env.twap().unwrap();
+    assert_eq!(twap.cumulative(), 100 * 3 + 200);
+    assert_eq!(twap.last_value.get(), 250);
+    assert_eq!(twap.last_slot.get(), 14);
+
+    // Consumer view: the average over slots 10..=20, with 250 standing since slot 14.
+    let start = c_u_soon::TwapObservation {
+        cumulative: 0,
+        slot: 10,
+    };
+    let now = twap.observe(20);
+    assert_eq!(now.twap_since(&start), Some((300 + 200 + 250 * 6) / 10));
+}
+
+#[test]
+fn test_fast_path_rejects_payload_overlapping_accumulator() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.warp_to_slot(5);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    for payload in [vec![0u8; 4], vec![0u8; TWAP_OFFSET + 1]] {
+        fast_path(
+            &mollusk,
+            authority,
+            envelope_pubkey,
+            u64_envelope(&authority, FLAG_TWAP),
+            1,
+            &payload,
+            Check::err(ProgramError::InvalidInstructionData),
+        );
+    }
+
+    // The same payloads are fine without the flag.
+    fast_path(
+        &mollusk,
+        authority,
+        envelope_pubkey,
+        u64_envelope(&authority, 0),
+        1,
+        &[0u8; ORACLE_BYTES],
+        Check::success(),
+    );
+}
+
+#[test]
+fn test_batch_update_folds_twap_envelopes() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let twap_pubkey = Address::new_unique();
+    let plain_pubkey = Address::new_unique();
+    let mut accounts = vec![
+        (authority, create_funded_account(1_000_000_000)),
+        (twap_pubkey, u64_envelope(&authority, FLAG_TWAP)),
+        (plain_pubkey, u64_envelope(&authority, 0)),
+    ];
+
+    for (slot, sequence, value) in [(7, 1, 40u64), (9, 2, 60)] {
+        mollusk.warp_to_slot(slot);
+        let value = value.to_le_bytes();
+        let entries = [
+            BatchEntry {
+                oracle_meta: u64::METADATA.as_u64(),
+                sequence,
+                payload: &value,
+            },
+            BatchEntry {
+                oracle_meta: u64::METADATA.as_u64(),
+                sequence,
+                payload: &value,
+            },
+        ];
+        let instruction = Instruction::new_with_bytes(
+            PROGRAM_ID,
+            &batch_update_instruction_data(&entries).unwrap(),
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(twap_pubkey, false),
+                AccountMeta::new(plain_pubkey, false),
+            ],
+        );
+        let result =
+            mollusk.process_and_validate_instruction(&instruction, &accounts, &[Check::success()]);
+        accounts = result.resulting_accounts;
+    }
+
+    let twap = envelope(&accounts[1].1).twap().unwrap();
+    assert_eq!(twap.cumulative(), 40 * 2);
+    assert_eq!(twap.last_slot.get(), 9);
+    let plain = envelope(&accounts[2].1);
+    assert!(plain.twap().is_none());
+    assert!(plain.oracle_state.data[TWAP_OFFSET..]
+        .iter()
+        .all(|&b| b == 0));
+}
@@ -0,0 +1,270 @@
+mod common;
+
+use c_u_soon::{
+    Envelope, TypeHash, FLAG_TWAP, FLAG_UPDATE_STAMP, ORACLE_BYTES, TWAP_OFFSET,
+    UPDATE_STAMP_OFFSET,
+};
+use c_u_soon_client::{fast_path_instruction_data, set_update_stamp_instruction_data};
+use common::{
+    create_existing_envelope, create_funded_account, new_mollusk, MolluskGuard, PROGRAM_ID,
+    PROGRAM_PATH,
+};
+use mollusk_svm::{result::Check, Mollusk};
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Third account that keeps SetUpdateStamp off the two-account fast path.
+const STAMP_PADDING: Address = Address::new_from_array([0xEE; 32]);
+
+/// Envelope whose oracle type is `u64`.
+fn u64_envelope(authority: &Address, flags: u8) -> Account {
+    let mut account = create_existing_envelope(authority, 0);
+    let envelope: &mut Envelope = bytemuck::from_bytes_mut(&mut account.data);
+    envelope.oracle_state.oracle_metadata = u64::METADATA;
+    envelope.flags = flags;
+    account
+}
+
+fn envelope(account: &Account) -> &Envelope {
+    bytemuck::from_bytes(&account.data)
+}
+
+fn set_update_stamp<G>(
+    mollusk: &MolluskGuard<G>,
+    authority: Address,
+    envelope_pubkey: Address,
+    envelope: Account,
+    enabled: bool,
+    check: Check,
+) -> Account {
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &set_update_stamp_instruction_data(enabled).unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+            AccountMeta::new_readonly(STAMP_PADDING, false),
+        ],
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+            (STAMP_PADDING, create_funded_account(0)),
+        ],
+        &[check],
+    );
+    result.resulting_accounts[1].1.clone()
+}
+
+fn fast_path(
+    mollusk: &Mollusk,
+    authority: Address,
+    envelope_pubkey: Address,
+    envelope: Account,
+    sequence: u64,
+    payload: &[u8],
+    check: Check,
+) -> Account {
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &fast_path_instruction_data(u64::METADATA.as_u64(), sequence, payload).unwrap(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope_pubkey, false),
+        ],
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, envelope),
+        ],
+        &[check],
+    );
+    result.resulting_accounts[1].1.clone()
+}
+
+// ============================================================================
+// SetUpdateStamp
+// ============================================================================
+
+#[test]
+fn test_set_update_stamp_enables_and_zeroes_stamp() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let mut account = u64_envelope(&authority, 0);
+    bytemuck::from_bytes_mut::<Envelope>(&mut account.data)
+        .oracle_state
+        .data[UPDATE_STAMP_OFFSET..]
+        .fill(0xAA);
+
+    let account = set_update_stamp(
+        &mollusk,
+        authority,
+        envelope_pubkey,
+        account,
+        true,
+        Check::success(),
+    );
+    let env = envelope(&account);
+    assert_eq!(env.flags & FLAG_UPDATE_STAMP, FLAG_UPDATE_STAMP);
+    assert_eq!(env.update_stamp(), Some(&Default::default()));
+    assert_eq!(env.last_update_slot(), None);
+
+    let account = set_update_stamp(
+        &mollusk,
+        authority,
+        envelope_pubkey,
+        account,
+        false,
+        Check::success(),
+    );
+    assert!(envelope(&account).update_stamp().is_none());
+}
+
+#[test]
+fn test_set_update_stamp_rejects_oracle_types_that_do_not_fit() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    for (flags, metadata) in [
+        (0, <[u8; UPDATE_STAMP_OFFSET + 1]>::METADATA),
+        (FLAG_TWAP, <[u8; TWAP_OFFSET + 1]>::METADATA),
+    ] {
+        let mut account = u64_envelope(&authority, flags);
+        bytemuck::from_bytes_mut::<Envelope>(&mut account.data)
+            .oracle_state
+            .oracle_metadata = metadata;
+        set_update_stamp(
+            &mollusk,
+            authority,
+            envelope_pubkey,
+            account,
+            true,
+            Check::err(ProgramError::InvalidArgument),
+        );
+    }
+}
+
+#[test]
+fn test_set_update_stamp_rejects_foreign_authority() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let owner = Address::new_unique();
+    let attacker = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    set_update_stamp(
+        &mollusk,
+        attacker,
+        envelope_pubkey,
+        u64_envelope(&owner, 0),
+        true,
+        Check::err(ProgramError::IncorrectAuthority),
+    );
+}
+
+// ============================================================================
+// Stamping
+// ============================================================================
+
+#[test]
+fn test_fast_path_records_slot_and_timestamp() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let mut account = u64_envelope(&authority, FLAG_UPDATE_STAMP);
+
+    for (slot, timestamp, sequence) in [(10, 1_700_000_000, 1), (25, 1_700_000_006, 2)] {
+        mollusk.warp_to_slot(slot);
+        mollusk.sysvars.clock.unix_timestamp = timestamp;
+        account = fast_path(
+            &mollusk,
+            authority,
+            envelope_pubkey,
+            account,
+            sequence,
+            &(sequence * 100).to_le_bytes(),
+            Check::success(),
+        );
+    }
+
+    let env = envelope(&account);
+    assert_eq!(env.last_update_slot(), Some(25));
+    assert_eq!(env.last_update_timestamp(), Some(1_700_000_006));
+    assert_eq!(env.oracle_max_staleness::<u64>(30, 5), Some(&200));
+    assert_eq!(env.oracle_max_staleness::<u64>(31, 5), None);
+}
+
+#[test]
+fn test_fast_path_stamps_alongside_twap() {
+    let mut mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    mollusk.warp_to_slot(42);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    let account = fast_path(
+        &mollusk,
+        authority,
+        envelope_pubkey,
+        u64_envelope(&authority, FLAG_TWAP | FLAG_UPDATE_STAMP),
+        1,
+        &7u64.to_le_bytes(),
+        Check::success(),
+    );
+    let env = envelope(&account);
+    assert_eq!(env.last_update_slot(), Some(42));
+    assert_eq!(env.twap().unwrap().last_slot.get(), 42);
+    assert_eq!(env.twap().unwrap().last_value.get(), 7);
+}
+
+#[test]
+fn test_fast_path_rejects_payload_overlapping_stamp() {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+
+    fast_path(
+        &mollusk,
+        authority,
+        envelope_pubkey,
+        u64_envelope(&authority, FLAG_UPDATE_STAMP),
+        1,
+        &[0u8; UPDATE_STAMP_OFFSET + 1],
+        Check::err(ProgramError::InvalidInstructionData),
+    );
+
+    // A payload that stops at the stamp is accepted, and short payloads need no TWAP value.
+    for payload in [vec![0u8; UPDATE_STAMP_OFFSET], vec![1u8; 4]] {
+        fast_path(
+            &mollusk,
+            authority,
+            envelope_pubkey,
+            u64_envelope(&authority, FLAG_UPDATE_STAMP),
+            1,
+            &payload,
+            Check::success(),
+        );
+    }
+
+    // Without the flag the whole region is payload.
+    fast_path(
+        &mollusk,
+        authority,
+        envelope_pubkey,
+        u64_envelope(&authority, 0),
+        1,
+        &[0u8; ORACLE_BYTES],
+        Check::success(),
+    );
+}
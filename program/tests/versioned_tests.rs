@@ -0,0 +1,102 @@
+mod common;
+
+use c_u_soon_client::{close_instruction_data, versioned_instruction_data};
+use c_u_soon_instruction::{
+    versioned, INSTRUCTION_VERSION, MAX_SLOW_PATH_TAG, UNSUPPORTED_VERSION_ERROR,
+};
+use common::{
+    create_existing_envelope, create_funded_account, new_mollusk, PROGRAM_ID, PROGRAM_PATH,
+};
+use mollusk_svm::result::Check;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+fn close_instruction(
+    data: &[u8],
+    authority: Address,
+    envelope: Address,
+    recipient: Address,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        data,
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(envelope, false),
+            AccountMeta::new(recipient, false),
+        ],
+    )
+}
+
+fn process_close(data: &[u8], check: Check) {
+    let mollusk = new_mollusk(&PROGRAM_ID, PROGRAM_PATH);
+    let authority = Address::new_unique();
+    let envelope_pubkey = Address::new_unique();
+    let recipient = Address::new_unique();
+
+    mollusk.process_and_validate_instruction(
+        &close_instruction(data, authority, envelope_pubkey, recipient),
+        &[
+            (authority, create_funded_account(1_000_000_000)),
+            (envelope_pubkey, create_existing_envelope(&authority, 0)),
+            (recipient, create_funded_account(0)),
+        ],
+        &[check],
+    );
+}
+
+#[test]
+fn test_versioned_close_succeeds() {
+    let data = versioned_instruction_data(&close_instruction_data().unwrap());
+    process_close(&data, Check::success());
+}
+
+#[test]
+fn test_newer_version_rejected_as_unsupported() {
+    let data = versioned(INSTRUCTION_VERSION + 1, &close_instruction_data().unwrap());
+    process_close(
+        &data,
+        Check::err(ProgramError::Custom(UNSUPPORTED_VERSION_ERROR)),
+    );
+}
+
+#[test]
+fn test_version_zero_rejected_as_unsupported() {
+    let data = versioned(0, &close_instruction_data().unwrap());
+    process_close(
+        &data,
+        Check::err(ProgramError::Custom(UNSUPPORTED_VERSION_ERROR)),
+    );
+}
+
+#[test]
+fn test_unknown_future_tag_rejected_as_unsupported() {
+    let data = (MAX_SLOW_PATH_TAG + 1).to_le_bytes();
+    process_close(
+        &data,
+        Check::err(ProgramError::Custom(UNSUPPORTED_VERSION_ERROR)),
+    );
+
+    let data = versioned(INSTRUCTION_VERSION, &data);
+    process_close(
+        &data,
+        Check::err(ProgramError::Custom(UNSUPPORTED_VERSION_ERROR)),
+    );
+}
+
+#[test]
+fn test_nested_and_truncated_wrappers_rejected() {
+    let inner = versioned_instruction_data(&close_instruction_data().unwrap());
+    process_close(
+        &versioned_instruction_data(&inner),
+        Check::err(ProgramError::InvalidInstructionData),
+    );
+    process_close(
+        &inner[..4],
+        Check::err(ProgramError::InvalidInstructionData),
+    );
+    process_close(
+        &inner[..5],
+        Check::err(ProgramError::InvalidInstructionData),
+    );
+}
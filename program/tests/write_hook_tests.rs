@@ -0,0 +1,219 @@
+use c_u_soon::{Mask, TypeHash};
+use c_u_soon_client::{
+    build_clear_delegation_ix, build_set_write_hook_ix, build_transfer_delegation_ix,
+    build_update_auxiliary_ix, update_auxiliary_delegated_instruction_data,
+};
+use c_u_soon_test_utils::{attacks, fixture_address, EnvelopeFixture, Runner, DEFAULT_PROGRAM_ID};
+use mollusk_svm::program::create_program_account_loader_v3;
+use pinocchio::{error::ProgramError, Address};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+type Aux = [u8; 16];
+
+struct Setup {
+    runner: Runner,
+    authority: Address,
+    delegate: Address,
+    padding: Address,
+    hook: Address,
+    envelope: Address,
+}
+
+/// An envelope with `Aux` bytes `[1; 16]`, delegated to `delegate` with every aux byte
+/// writable by both sides, and an executable `hook` program that is not attached yet.
+fn setup() -> Setup {
+    let mut runner = Runner::new();
+    let authority = fixture_address("write_hook/authority");
+    let delegate = fixture_address("write_hook/delegate");
+    let padding = fixture_address("write_hook/padding");
+    let hook = fixture_address("write_hook/hook");
+    let envelope = fixture_address("write_hook/envelope");
+    runner
+        .fund(authority, 1_000_000_000)
+        .fund(delegate, 1_000_000_000)
+        .fund(padding, 0)
+        .set_account(hook, create_program_account_loader_v3(&hook))
+        .set_account(
+            envelope,
+            EnvelopeFixture::new(authority)
+                .aux(Aux::METADATA, &[1; 16])
+                .delegated(delegate, Mask::ALL_WRITABLE, Mask::ALL_WRITABLE)
+                .account(),
+        );
+    Setup {
+        runner,
+        authority,
+        delegate,
+        padding,
+        hook,
+        envelope,
+    }
+}
+
+impl Setup {
+    fn set_hook(&self, hook_program: Option<&Address>) -> Instruction {
+        build_set_write_hook_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            &self.envelope,
+            &self.delegate,
+            hook_program,
+        )
+        .unwrap()
+    }
+
+    /// An authority write of `[value; 16]` at `sequence`, without a hook account.
+    fn authority_write(&self, sequence: u64, value: u8) -> Instruction {
+        build_update_auxiliary_ix(
+            &DEFAULT_PROGRAM_ID,
+            &self.authority,
+            &self.envelope,
+            &self.padding,
+            Aux::METADATA.as_u64(),
+            sequence,
+            &[value; 16],
+        )
+    }
+
+    fn delegated_write(&self, sequence: u64, value: u8) -> Instruction {
+        Instruction::new_with_bytes(
+            DEFAULT_PROGRAM_ID,
+            &update_auxiliary_delegated_instruction_data(
+                Aux::METADATA.as_u64(),
+                sequence,
+                &[value; 16],
+            ),
+            vec![
+                AccountMeta::new_readonly(self.delegate, true),
+                AccountMeta::new(self.envelope, false),
+                AccountMeta::new_readonly(self.padding, false),
+            ],
+        )
+    }
+}
+
+#[test]
+fn test_set_write_hook_stores_and_removes_the_program() {
+    let mut s = setup();
+    assert_eq!(s.runner.envelope(&s.envelope).write_hook(), None);
+
+    s.runner.expect_ok(&s.set_hook(Some(&s.hook)));
+    assert_eq!(s.runner.envelope(&s.envelope).write_hook(), Some(&s.hook));
+
+    s.runner.expect_ok(&s.set_hook(None));
+    assert_eq!(s.runner.envelope(&s.envelope).write_hook(), None);
+    s.runner.expect_ok(&s.authority_write(1, 2));
+}
+
+#[test]
+fn test_set_write_hook_requires_authority_and_delegate() {
+    let mut s = setup();
+    let ix = s.set_hook(Some(&s.hook));
+    for index in [0, 2] {
+        attacks::assert_requires_signer(
+            &mut s.runner,
+            &ix,
+            index,
+            ProgramError::MissingRequiredSignature,
+        );
+    }
+    let impostor = fixture_address("write_hook/impostor");
+    s.runner.fund(impostor, 1_000_000_000);
+    s.runner.expect_err(
+        &attacks::with_account(&ix, 0, impostor),
+        ProgramError::IncorrectAuthority,
+    );
+    s.runner.expect_err(
+        &attacks::with_account(&ix, 2, impostor),
+        ProgramError::IncorrectAuthority,
+    );
+    assert_eq!(s.runner.envelope(&s.envelope).write_hook(), None);
+}
+
+#[test]
+fn test_set_write_hook_rejects_non_executable_and_self() {
+    let mut s = setup();
+    s.runner
+        .expect_err(&s.set_hook(Some(&s.padding)), ProgramError::InvalidArgument);
+    s.runner.set_account(
+        DEFAULT_PROGRAM_ID,
+        create_program_account_loader_v3(&DEFAULT_PROGRAM_ID),
+    );
+    s.runner.expect_err(
+        &s.set_hook(Some(&DEFAULT_PROGRAM_ID)),
+        ProgramError::InvalidArgument,
+    );
+}
+
+#[test]
+fn test_set_write_hook_requires_active_delegation() {
+    let mut s = setup();
+    s.runner.expect_ok(
+        &build_clear_delegation_ix(&DEFAULT_PROGRAM_ID, &s.authority, &s.envelope, &s.delegate)
+            .unwrap(),
+    );
+    s.runner
+        .expect_err(&s.set_hook(Some(&s.hook)), ProgramError::InvalidArgument);
+}
+
+#[test]
+fn test_hooked_authority_write_requires_the_hook_account() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.set_hook(Some(&s.hook)));
+
+    let write = s.authority_write(1, 2);
+    s.runner
+        .expect_err(&write, ProgramError::NotEnoughAccountKeys);
+
+    // The config stays last; the hook goes before it.
+    let mut wrong = write.clone();
+    let config = wrong.accounts.len() - 1;
+    wrong
+        .accounts
+        .insert(config, AccountMeta::new_readonly(s.padding, false));
+    s.runner
+        .expect_err(&wrong, ProgramError::IncorrectProgramId);
+    assert_eq!(s.runner.envelope(&s.envelope).aux::<Aux>(), Some(&[1; 16]));
+}
+
+#[test]
+fn test_delegated_write_ignores_the_hook() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.set_hook(Some(&s.hook)));
+    s.runner.expect_ok(&s.delegated_write(1, 3));
+    assert_eq!(s.runner.envelope(&s.envelope).aux::<Aux>(), Some(&[3; 16]));
+}
+
+#[test]
+fn test_hook_ends_with_the_delegation() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.set_hook(Some(&s.hook)));
+    s.runner.expect_ok(
+        &build_clear_delegation_ix(&DEFAULT_PROGRAM_ID, &s.authority, &s.envelope, &s.delegate)
+            .unwrap(),
+    );
+    assert_eq!(s.runner.envelope(&s.envelope).write_hook(), None);
+    s.runner.expect_ok(&s.authority_write(1, 2));
+}
+
+#[test]
+fn test_hook_does_not_pass_to_the_new_delegate() {
+    let mut s = setup();
+    s.runner.expect_ok(&s.set_hook(Some(&s.hook)));
+    let new_delegate = fixture_address("write_hook/new_delegate");
+    s.runner.fund(new_delegate, 1_000_000_000);
+    s.runner.expect_ok(
+        &build_transfer_delegation_ix(
+            &DEFAULT_PROGRAM_ID,
+            &s.authority,
+            &s.envelope,
+            &s.delegate,
+            &new_delegate,
+            Mask::ALL_WRITABLE,
+            Mask::ALL_WRITABLE,
+        )
+        .unwrap(),
+    );
+    assert_eq!(s.runner.envelope(&s.envelope).write_hook(), None);
+    s.runner.expect_ok(&s.authority_write(1, 2));
+}
@@ -0,0 +1,316 @@
+//! Python bindings for c_u_soon, imported as `c_u_soon`.
+//!
+//! [`decode_envelope`] turns raw account bytes into a flat `dict`, one key per envelope
+//! field, so a list of decoded accounts loads straight into `pandas.DataFrame`. [`PyMask`]
+//! inspects and builds delegation bitmasks, [`type_metadata`] computes a type's
+//! `StructMetadata` from a JSON schema (see `c_u_soon_client::schema_metadata`), and the
+//! `*_instruction_data` functions wrap the `c_u_soon_client` builders of the same names, so
+//! Python produces the same bytes as Rust.
+//!
+//! Build a wheel with `maturin build --release -m py/Cargo.toml`.
+
+use std::str::FromStr;
+
+use c_u_soon::{Envelope, Mask, StructMetadata, MASK_SIZE};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use solana_address::Address;
+
+fn value_error(e: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn address(value: &str) -> PyResult<Address> {
+    Address::from_str(value).map_err(|_| value_error(format!("`{value}` is not an address")))
+}
+
+/// Base58 of `address`, or `None` for the zeroed "unset" address.
+fn optional_address(address: &Address) -> Option<String> {
+    (address != &Address::zeroed()).then(|| address.to_string())
+}
+
+fn seed_slices(custom_seeds: &[Vec<u8>]) -> Vec<&[u8]> {
+    custom_seeds.iter().map(Vec::as_slice).collect()
+}
+
+/// Decode the first `ENVELOPE_SIZE` bytes of an envelope account into a `dict`.
+///
+/// Addresses are base58 strings; the optional ones (`delegation_authority`, `publisher`,
+/// `write_hook`) are `None` when unset. Metadata words and counters are `int`, data
+/// regions and masks are `bytes`, and `paused` and `emit_events` are `bool`.
+#[pyfunction]
+fn decode_envelope<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyDict>> {
+    let bytes = data.get(..Envelope::SIZE).ok_or_else(|| {
+        value_error(format!(
+            "expected at least {} envelope bytes, got {}",
+            Envelope::SIZE,
+            data.len()
+        ))
+    })?;
+    let envelope: Envelope = bytemuck::pod_read_unaligned(bytes);
+    let dict = PyDict::new_bound(py);
+    dict.set_item("authority", envelope.authority.to_string())?;
+    dict.set_item(
+        "oracle_metadata",
+        envelope.oracle_state.oracle_metadata.as_u64(),
+    )?;
+    dict.set_item("oracle_sequence", envelope.oracle_state.sequence)?;
+    dict.set_item(
+        "oracle_data",
+        PyBytes::new_bound(py, &envelope.oracle_state.data),
+    )?;
+    dict.set_item("bump", envelope.bump)?;
+    dict.set_item("flags", envelope.flags)?;
+    dict.set_item("aux_write_count", envelope.aux_write_count)?;
+    dict.set_item("aux_dirty_blocks", envelope.aux_dirty_blocks)?;
+    dict.set_item("constrained_regions", envelope.constrained_regions)?;
+    dict.set_item("paused", envelope.is_paused())?;
+    dict.set_item("emit_events", envelope.emits_events())?;
+    dict.set_item(
+        "delegation_authority",
+        optional_address(&envelope.delegation_authority),
+    )?;
+    dict.set_item(
+        "program_bitmask",
+        PyBytes::new_bound(py, envelope.program_bitmask.as_bytes()),
+    )?;
+    dict.set_item(
+        "user_bitmask",
+        PyBytes::new_bound(py, envelope.user_bitmask.as_bytes()),
+    )?;
+    dict.set_item("authority_aux_sequence", envelope.authority_aux_sequence)?;
+    dict.set_item("program_aux_sequence", envelope.program_aux_sequence)?;
+    dict.set_item("auxiliary_metadata", envelope.auxiliary_metadata.as_u64())?;
+    dict.set_item(
+        "auxiliary_data",
+        PyBytes::new_bound(py, &envelope.auxiliary_data),
+    )?;
+    dict.set_item("publisher", optional_address(&envelope.publisher))?;
+    dict.set_item("heartbeat_slot", envelope.heartbeat_slot)?;
+    dict.set_item("write_hook", optional_address(&envelope.write_hook))?;
+    dict.set_item("mutation_marker", envelope.mutation_marker)?;
+    Ok(dict)
+}
+
+/// A delegation bitmask, exported as `Mask`. `Mask(data)` takes the 256 mask bytes of an
+/// envelope, as in `decode_envelope(..)["program_bitmask"]`.
+#[pyclass(name = "Mask", module = "c_u_soon", eq)]
+#[derive(Clone, Copy, PartialEq)]
+struct PyMask(Mask);
+
+#[pymethods]
+impl PyMask {
+    #[new]
+    fn new(data: &[u8]) -> PyResult<Self> {
+        let bytes: [u8; MASK_SIZE] = data.try_into().map_err(|_| {
+            value_error(format!(
+                "expected {MASK_SIZE} mask bytes, got {}",
+                data.len()
+            ))
+        })?;
+        Ok(Self(Mask::from(bytes)))
+    }
+
+    /// Every byte blocked.
+    #[staticmethod]
+    fn all_blocked() -> Self {
+        Self(Mask::ALL_BLOCKED)
+    }
+
+    /// Every byte writable.
+    #[staticmethod]
+    fn all_writable() -> Self {
+        Self(Mask::ALL_WRITABLE)
+    }
+
+    fn is_writable(&self, byte_idx: usize) -> bool {
+        self.0.is_writable(byte_idx)
+    }
+
+    fn is_sealed(&self, byte_idx: usize) -> bool {
+        self.0.is_sealed(byte_idx)
+    }
+
+    /// Whether the program would accept this mask in `SetDelegatedProgram`.
+    fn is_canonical(&self) -> bool {
+        self.0.is_canonical()
+    }
+
+    fn allow(&mut self, byte_idx: usize) {
+        self.0.allow(byte_idx);
+    }
+
+    fn block(&mut self, byte_idx: usize) {
+        self.0.block(byte_idx);
+    }
+
+    fn allow_range(&mut self, offset: usize, len: usize) {
+        self.0.allow_range(offset, len);
+    }
+
+    fn block_range(&mut self, offset: usize, len: usize) {
+        self.0.block_range(offset, len);
+    }
+
+    /// `(offset, len)` of every run of writable bytes, in order.
+    fn writable_ranges(&self) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for i in (0..MASK_SIZE).filter(|&i| self.0.is_writable(i)) {
+            match ranges.last_mut() {
+                Some((offset, len)) if *offset + *len == i => *len += 1,
+                _ => ranges.push((i, 1)),
+            }
+        }
+        ranges
+    }
+
+    fn __bytes__<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, self.0.as_bytes())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Mask(writable={:?})", self.writable_ranges())
+    }
+}
+
+/// Packed `StructMetadata` of the struct a JSON schema string describes.
+#[pyfunction]
+fn type_metadata(schema: &str) -> PyResult<u64> {
+    let schema: serde_json::Value = serde_json::from_str(schema).map_err(value_error)?;
+    c_u_soon_client::schema_metadata(&schema)
+        .map(|metadata| metadata.as_u64())
+        .map_err(value_error)
+}
+
+/// `(address, bump)` of the envelope PDA.
+#[pyfunction]
+fn find_envelope_address(
+    program_id: &str,
+    authority: &str,
+    custom_seeds: Vec<Vec<u8>>,
+) -> PyResult<(String, u8)> {
+    let (envelope, bump) = c_u_soon_client::find_envelope_address(
+        &address(program_id)?,
+        &address(authority)?,
+        &seed_slices(&custom_seeds),
+    );
+    Ok((envelope.to_string(), bump))
+}
+
+#[pyfunction]
+fn fast_path_instruction_data(
+    py: Python<'_>,
+    oracle_meta: u64,
+    sequence: u64,
+    payload: &[u8],
+) -> PyResult<Py<PyBytes>> {
+    let data = c_u_soon_client::fast_path_instruction_data(oracle_meta, sequence, payload)
+        .map_err(value_error)?;
+    Ok(PyBytes::new_bound(py, &data).unbind())
+}
+
+#[pyfunction]
+fn versioned_instruction_data(py: Python<'_>, instruction_data: &[u8]) -> Py<PyBytes> {
+    let data = c_u_soon_client::versioned_instruction_data(instruction_data);
+    PyBytes::new_bound(py, &data).unbind()
+}
+
+#[pyfunction]
+fn create_instruction_data(
+    py: Python<'_>,
+    custom_seeds: Vec<Vec<u8>>,
+    bump: u8,
+    oracle_metadata: u64,
+) -> PyResult<Py<PyBytes>> {
+    let data = c_u_soon_client::create_instruction_data(
+        &seed_slices(&custom_seeds),
+        bump,
+        StructMetadata::from_raw(oracle_metadata),
+    )
+    .map_err(value_error)?;
+    Ok(PyBytes::new_bound(py, &data).unbind())
+}
+
+#[pyfunction]
+fn close_instruction_data(py: Python<'_>) -> PyResult<Py<PyBytes>> {
+    let data = c_u_soon_client::close_instruction_data().map_err(value_error)?;
+    Ok(PyBytes::new_bound(py, &data).unbind())
+}
+
+#[pyfunction]
+fn set_delegated_program_instruction_data(
+    py: Python<'_>,
+    program_bitmask: PyMask,
+    user_bitmask: PyMask,
+) -> PyResult<Py<PyBytes>> {
+    let data =
+        c_u_soon_client::set_delegated_program_instruction_data(program_bitmask.0, user_bitmask.0)
+            .map_err(value_error)?;
+    Ok(PyBytes::new_bound(py, &data).unbind())
+}
+
+#[pyfunction]
+fn clear_delegation_instruction_data(py: Python<'_>) -> PyResult<Py<PyBytes>> {
+    let data = c_u_soon_client::clear_delegation_instruction_data().map_err(value_error)?;
+    Ok(PyBytes::new_bound(py, &data).unbind())
+}
+
+#[pyfunction]
+fn transfer_delegation_instruction_data(
+    py: Python<'_>,
+    program_bitmask: PyMask,
+    user_bitmask: PyMask,
+) -> PyResult<Py<PyBytes>> {
+    let data =
+        c_u_soon_client::transfer_delegation_instruction_data(program_bitmask.0, user_bitmask.0)
+            .map_err(value_error)?;
+    Ok(PyBytes::new_bound(py, &data).unbind())
+}
+
+#[pyfunction]
+fn update_auxiliary_instruction_data(
+    py: Python<'_>,
+    metadata: u64,
+    sequence: u64,
+    data: &[u8],
+) -> Py<PyBytes> {
+    let data = c_u_soon_client::update_auxiliary_instruction_data(metadata, sequence, data);
+    PyBytes::new_bound(py, &data).unbind()
+}
+
+#[pyfunction]
+fn update_auxiliary_range_instruction_data(
+    py: Python<'_>,
+    metadata: u64,
+    sequence: u64,
+    offset: u8,
+    data: &[u8],
+) -> Py<PyBytes> {
+    let data =
+        c_u_soon_client::update_auxiliary_range_instruction_data(metadata, sequence, offset, data);
+    PyBytes::new_bound(py, &data).unbind()
+}
+
+#[pymodule]
+#[pyo3(name = "c_u_soon")]
+fn c_u_soon_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("ENVELOPE_SIZE", Envelope::SIZE)?;
+    m.add_class::<PyMask>()?;
+    m.add_function(wrap_pyfunction!(decode_envelope, m)?)?;
+    m.add_function(wrap_pyfunction!(type_metadata, m)?)?;
+    m.add_function(wrap_pyfunction!(find_envelope_address, m)?)?;
+    m.add_function(wrap_pyfunction!(fast_path_instruction_data, m)?)?;
+    m.add_function(wrap_pyfunction!(versioned_instruction_data, m)?)?;
+    m.add_function(wrap_pyfunction!(create_instruction_data, m)?)?;
+    m.add_function(wrap_pyfunction!(close_instruction_data, m)?)?;
+    m.add_function(wrap_pyfunction!(set_delegated_program_instruction_data, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_delegation_instruction_data, m)?)?;
+    m.add_function(wrap_pyfunction!(transfer_delegation_instruction_data, m)?)?;
+    m.add_function(wrap_pyfunction!(update_auxiliary_instruction_data, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        update_auxiliary_range_instruction_data,
+        m
+    )?)?;
+    Ok(())
+}
@@ -0,0 +1,81 @@
+"""Checks the wheel against the golden vectors in fixtures/.
+
+Run with `maturin develop -m py/Cargo.toml && pytest py/tests`.
+"""
+
+import json
+from pathlib import Path
+
+import pytest
+
+import c_u_soon
+
+FIXTURES = Path(__file__).resolve().parents[2] / "fixtures"
+ORACLE_META = (8 << 56) | 0x0011_2233_4455_66
+
+
+def vectors():
+    doc = json.loads((FIXTURES / "instructions.json").read_text())
+    return {v["name"]: bytes.fromhex(v["data"]) for v in doc["instructions"]}
+
+
+def half_mask():
+    mask = c_u_soon.Mask.all_blocked()
+    mask.allow_range(0, 128)
+    return mask
+
+
+def other_half_mask():
+    mask = c_u_soon.Mask.all_writable()
+    mask.block_range(0, 128)
+    return mask
+
+
+def test_instruction_data_matches_the_golden_vectors():
+    v = vectors()
+    seeds = [b"feed", bytes([7] * 32)]
+    assert c_u_soon.fast_path_instruction_data(ORACLE_META, 1, bytes(range(1, 9))) == v["fast_path"]
+    assert c_u_soon.create_instruction_data(seeds, 254, ORACLE_META) == v["create"]
+    assert c_u_soon.close_instruction_data() == v["close"]
+    assert (
+        c_u_soon.set_delegated_program_instruction_data(half_mask(), other_half_mask())
+        == v["set_delegated_program"]
+    )
+    assert c_u_soon.clear_delegation_instruction_data() == v["clear_delegation"]
+    assert (
+        c_u_soon.transfer_delegation_instruction_data(other_half_mask(), half_mask())
+        == v["transfer_delegation"]
+    )
+    assert c_u_soon.versioned_instruction_data(v["close"]) == v["versioned_close"]
+
+
+def test_decode_envelope_matches_the_golden_layout():
+    doc = json.loads((FIXTURES / "envelope.json").read_text())
+    data = bytes.fromhex(doc["data"])
+    assert c_u_soon.ENVELOPE_SIZE == doc["size"]
+    decoded = c_u_soon.decode_envelope(data)
+    raw = {f["name"]: data[f["offset"] : f["offset"] + f["len"]] for f in doc["fields"]}
+    assert decoded["oracle_metadata"] == int.from_bytes(raw["oracle_state.oracle_metadata"], "little")
+    assert decoded["oracle_sequence"] == int.from_bytes(raw["oracle_state.sequence"], "little")
+    assert decoded["oracle_data"] == raw["oracle_state.data"]
+    assert decoded["auxiliary_data"] == raw["auxiliary_data"]
+    assert decoded["program_bitmask"] == raw["program_bitmask"]
+    assert decoded["heartbeat_slot"] == int.from_bytes(raw["heartbeat_slot"], "little")
+    assert bytes(c_u_soon.Mask(decoded["user_bitmask"])) == raw["user_bitmask"]
+    with pytest.raises(ValueError):
+        c_u_soon.decode_envelope(data[:-1])
+
+
+def test_mask_ranges():
+    mask = half_mask()
+    mask.block(5)
+    assert mask.writable_ranges() == [(0, 5), (6, 122)]
+    assert mask.is_canonical() and not mask.is_writable(5)
+    assert c_u_soon.Mask(bytes(mask)) == mask
+
+
+def test_type_metadata():
+    u64_struct = {"name": "Price", "fields": [{"name": "value", "type": "u64"}]}
+    assert c_u_soon.type_metadata(json.dumps(u64_struct)) >> 56 == 8
+    with pytest.raises(ValueError):
+        c_u_soon.type_metadata(json.dumps({"name": "Bad", "fields": [{"name": "b", "type": "bool"}]}))
@@ -0,0 +1,90 @@
+//! Median aggregation over several envelopes' oracle values, used by the program's
+//! `Aggregate` instruction.
+//!
+//! Each source contributes the first 8 bytes of its oracle payload as a `u64` LE value, the
+//! same value the TWAP and conflation trailers track. The aggregate is the lower median of
+//! the sources that lie within a deviation band around the median of all sources, so a
+//! minority of stale or faulty publishers cannot move it. Values are compared as unsigned
+//! integers: signed payloads such as a negative [`PriceValue`](crate::PriceValue) mantissa
+//! do not order correctly.
+
+/// Most source envelopes one aggregation reads.
+pub const MAX_AGGREGATE_SOURCES: usize = 16;
+
+/// Lower median of `values` after dropping outliers.
+///
+/// Takes the lower median `m` of all `values`, drops every value more than
+/// `max_deviation_bps` basis points of `m` away from it, and returns `(index, accepted)`:
+/// the position in `values` of the lower median of the values left, and how many are left.
+/// Equal values keep their order, so ties resolve to the earliest source. `m` itself always
+/// stays, so `accepted` is at least 1.
+///
+/// Returns `None` if `values` is empty or longer than [`MAX_AGGREGATE_SOURCES`].
+pub fn median_within(values: &[u64], max_deviation_bps: u16) -> Option<(usize, usize)> {
+    let n = values.len();
+    if n == 0 || n > MAX_AGGREGATE_SOURCES {
+        return None;
+    }
+
+    let mut order = [0u8; MAX_AGGREGATE_SOURCES];
+    for (i, slot) in order.iter_mut().enumerate().take(n) {
+        *slot = i as u8;
+    }
+    let order = &mut order[..n];
+    for i in 1..n {
+        let mut j = i;
+        while j > 0 && values[order[j - 1] as usize] > values[order[j] as usize] {
+            order.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+
+    let median = values[order[(n - 1) / 2] as usize];
+    let within = |&i: &u8| {
+        let deviation = values[i as usize].abs_diff(median) as u128;
+        deviation * 10_000 <= median as u128 * max_deviation_bps as u128
+    };
+    // The band around the median is an interval, so the kept values are a contiguous run
+    // of the sorted order.
+    let lo = order.iter().position(within)?;
+    let hi = order.iter().rposition(within)? + 1;
+    let accepted = hi - lo;
+    Some((order[lo + (accepted - 1) / 2] as usize, accepted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_without_outliers() {
+        assert_eq!(median_within(&[30, 10, 20], 10_000), Some((2, 3)));
+        assert_eq!(median_within(&[7], 0), Some((0, 1)));
+        // Even counts take the lower median.
+        assert_eq!(median_within(&[40, 10, 30, 20], 10_000), Some((3, 4)));
+    }
+
+    #[test]
+    fn test_median_drops_outliers() {
+        // 100 bps of 1000 keeps 990..=1010.
+        let values = [1000, 1005, 5, 995, 9000];
+        assert_eq!(median_within(&values, 100), Some((0, 3)));
+
+        // Dropping a one-sided outlier shifts the median of the rest.
+        let values = [100, 101, 102, 103, 500];
+        assert_eq!(median_within(&values, 200), Some((1, 4)));
+
+        // With no tolerance only copies of the median stay.
+        assert_eq!(median_within(&[5, 6, 6, 7], 0), Some((1, 2)));
+    }
+
+    #[test]
+    fn test_median_rejects_bad_counts() {
+        assert_eq!(median_within(&[], 100), None);
+        assert_eq!(median_within(&[1; MAX_AGGREGATE_SOURCES + 1], 100), None);
+        assert_eq!(
+            median_within(&[1; MAX_AGGREGATE_SOURCES], 100),
+            Some((7, MAX_AGGREGATE_SOURCES))
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Conversions between bool masks (`true` = writable), the packed [`BitVec256`], and the
+//! on-chain [`Mask`] wire format (`0x00` = writable, `0xFF` = blocked).
+//!
+//! These are the same conversions `c_u_later` uses for derived masks; they live here so
+//! clients that don't need the derive can convert masks directly. The bool-vector
+//! conversions require the `alloc` feature.
+//!
+//! [`BitVec256`], also named [`CompactMask`], is a stable 32-byte encoding of a canonical
+//! mask: bit `i % 8` of byte `i / 8` is set when aux byte `i` is writable. Clients can carry
+//! masks in their own instruction data at an eighth of the size, and
+//! `SetDelegatedProgramCompact` takes this form directly.
+
+use crate::{Mask, Permission, MASK_SIZE};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use bytemuck::{Pod, Zeroable};
+
+/// Byte size of a [`CompactMask`].
+pub const COMPACT_MASK_SIZE: usize = MASK_SIZE / 8;
+
+/// Compact 256-bit permission mask (32 bytes, 1 bit per aux byte). A set bit = writable.
+#[derive(Clone, Copy, Pod, Zeroable, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct BitVec256([u8; COMPACT_MASK_SIZE]);
+
+/// [`BitVec256`] under the name used for its wire encoding.
+pub type CompactMask = BitVec256;
+
+impl BitVec256 {
+    /// Every byte blocked.
+    pub const ZERO: Self = BitVec256([0; COMPACT_MASK_SIZE]);
+    /// Every byte writable.
+    pub const FULL: Self = BitVec256([0xFF; COMPACT_MASK_SIZE]);
+
+    /// Wrap packed bytes in the layout [`as_bytes`](Self::as_bytes) returns. Every bit
+    /// pattern is a valid mask.
+    #[inline]
+    pub const fn from_bytes(bytes: [u8; COMPACT_MASK_SIZE]) -> Self {
+        Self(bytes)
+    }
+
+    /// The packed bytes, by value.
+    #[inline]
+    pub const fn to_bytes(self) -> [u8; COMPACT_MASK_SIZE] {
+        self.0
+    }
+
+    /// Marks aux byte `bit` as writable. No-op if `bit >= 256`.
+    #[inline]
+    pub fn set_bit(&mut self, bit: usize) {
+        if bit < 256 {
+            let byte = bit / 8;
+            let bit_in_byte = bit % 8;
+            self.0[byte] |= 1 << bit_in_byte;
+        }
+    }
+
+    /// Returns `true` if aux byte `bit` is writable. Returns `false` if `bit >= 256`.
+    #[inline]
+    pub fn get_bit(&self, bit: usize) -> bool {
+        if bit < 256 {
+            let byte = bit / 8;
+            let bit_in_byte = bit % 8;
+            (self.0[byte] & (1 << bit_in_byte)) != 0
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if every byte in `offset..offset+size` is writable.
+    /// Returns `false` if `offset + size` overflows or exceeds 256, or any byte in the range
+    /// is blocked.
+    ///
+    /// Checks the range a u64 word (64 aux bytes) at a time: full words against all ones,
+    /// the partial words at either end against a mask of just the bits in range.
+    #[inline]
+    pub fn is_write_allowed(&self, offset: usize, size: usize) -> bool {
+        let end = match offset.checked_add(size) {
+            Some(e) => e,
+            None => return false,
+        };
+        if end > MASK_SIZE {
+            return false;
+        }
+        let mut bit = offset;
+        while bit < end {
+            let word = bit / 64;
+            let lo = bit % 64;
+            let hi = (end - word * 64).min(64);
+            let want = (u64::MAX >> (64 - (hi - lo))) << lo;
+            if self.word(word) & want != want {
+                return false;
+            }
+            bit = (word + 1) * 64;
+        }
+        true
+    }
+
+    /// Bits `64 * index..64 * (index + 1)`, bit `i % 64` covering aux byte `i`.
+    #[inline]
+    fn word(&self, index: usize) -> u64 {
+        u64::from_le_bytes(self.0[index * 8..index * 8 + 8].try_into().unwrap())
+    }
+
+    /// Raw packed bytes. Bit `i % 8` of byte `i / 8` covers aux byte `i`.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; COMPACT_MASK_SIZE] {
+        &self.0
+    }
+
+    /// Unpack the first `len` bits (clamped to 256) into a bool mask.
+    #[cfg(feature = "alloc")]
+    pub fn to_bools(&self, len: usize) -> Vec<bool> {
+        (0..len.min(MASK_SIZE)).map(|i| self.get_bit(i)).collect()
+    }
+}
+
+impl From<&Mask> for BitVec256 {
+    /// Pack a wire mask. Non-canonical bytes count as blocked, as in [`Mask::get`].
+    fn from(mask: &Mask) -> Self {
+        let mut result = BitVec256::ZERO;
+        for (i, permission) in mask.permissions().enumerate() {
+            if permission.is_writable() {
+                result.set_bit(i);
+            }
+        }
+        result
+    }
+}
+
+impl From<BitVec256> for Mask {
+    /// Expand to one byte per bit. Runs on-chain for `SetDelegatedProgramCompact`, so it
+    /// writes the bytes directly rather than through [`Mask::set`].
+    fn from(bits: BitVec256) -> Self {
+        let mut wire = [Permission::Blocked.to_byte(); MASK_SIZE];
+        for (i, byte) in wire.iter_mut().enumerate() {
+            if bits.0[i / 8] & (1 << (i % 8)) != 0 {
+                *byte = Permission::Writable.to_byte();
+            }
+        }
+        Mask::from(wire)
+    }
+}
+
+/// Convert a bool slice mask to [`BitVec256`].
+/// Only iterates `mask.len()` entries; bits beyond that stay 0 (not writable).
+#[inline]
+pub fn bools_to_bitvec(mask: &[bool]) -> BitVec256 {
+    let mut result = BitVec256::ZERO;
+    for (i, &writable) in mask.iter().enumerate() {
+        if writable {
+            result.set_bit(i);
+        }
+    }
+    result
+}
+
+/// Convert a bool slice mask to the on-chain [`Mask`] format.
+/// `true` becomes [`Permission::Writable`]. Bytes past `mask.len()` are blocked.
+pub fn bools_to_wire_mask(mask: &[bool]) -> Mask {
+    let mut wire = Mask::ALL_BLOCKED;
+    for (i, &writable) in mask.iter().enumerate() {
+        wire.set(i, Permission::from(writable));
+    }
+    wire
+}
+
+/// Convert the first `len` bytes (clamped to [`MASK_SIZE`]) of a wire mask to a bool mask.
+/// Inverse of [`bools_to_wire_mask`] for canonical masks.
+#[cfg(feature = "alloc")]
+pub fn wire_mask_to_bools(mask: &Mask, len: usize) -> Vec<bool> {
+    mask.permissions()
+        .take(len)
+        .map(Permission::is_writable)
+        .collect()
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_bitmask_set_get_bits() {
+        let mut mask = BitVec256::ZERO;
+        assert!(!mask.get_bit(0));
+        assert!(!mask.get_bit(255));
+
+        mask.set_bit(0);
+        assert!(mask.get_bit(0));
+        assert!(!mask.get_bit(1));
+
+        mask.set_bit(255);
+        assert!(mask.get_bit(255));
+        assert!(mask.get_bit(0));
+
+        mask.set_bit(256);
+        assert!(!mask.get_bit(256));
+    }
+
+    #[test]
+    fn test_bitmask_is_write_allowed() {
+        let mut mask = BitVec256::ZERO;
+
+        assert!(!mask.is_write_allowed(0, 1));
+        assert!(!mask.is_write_allowed(0, 256));
+
+        for i in 0..8 {
+            mask.set_bit(i);
+        }
+        assert!(mask.is_write_allowed(0, 8));
+        assert!(!mask.is_write_allowed(0, 9));
+        assert!(!mask.is_write_allowed(7, 2));
+
+        let full_mask = BitVec256::FULL;
+        assert!(full_mask.is_write_allowed(0, 256));
+        assert!(full_mask.is_write_allowed(100, 100));
+        assert!(!full_mask.is_write_allowed(255, 2));
+    }
+
+    #[test]
+    fn test_bitmask_is_write_allowed_across_words() {
+        let mut mask = BitVec256::ZERO;
+        for i in 60..200 {
+            mask.set_bit(i);
+        }
+        assert!(mask.is_write_allowed(60, 140), "spans three words");
+        assert!(mask.is_write_allowed(64, 64), "exactly one word");
+        assert!(mask.is_write_allowed(199, 1));
+        assert!(!mask.is_write_allowed(59, 2));
+        assert!(!mask.is_write_allowed(199, 2));
+        assert!(mask.is_write_allowed(256, 0));
+        assert!(!mask.is_write_allowed(257, 0));
+        assert!(!BitVec256::FULL.is_write_allowed(1, usize::MAX), "overflow");
+    }
+
+    #[test]
+    fn test_bools_to_wire_mask_pads_blocked() {
+        let wire = bools_to_wire_mask(&[true, false, true]);
+        assert_eq!(&wire.as_bytes()[..3], &[0x00, 0xFF, 0x00]);
+        assert!(wire.as_bytes()[3..].iter().all(|&b| b == 0xFF));
+    }
+
+    #[test]
+    fn test_wire_mask_bools_roundtrip() {
+        let bools = vec![true, true, false, true, false, false, true];
+        let wire = bools_to_wire_mask(&bools);
+        assert_eq!(wire_mask_to_bools(&wire, bools.len()), bools);
+    }
+
+    #[test]
+    fn test_bitvec_mask_roundtrip() {
+        let bools = vec![false, true, true, false, true];
+        let bits = bools_to_bitvec(&bools);
+        assert_eq!(bits.to_bools(bools.len()), bools);
+
+        let wire = Mask::from(bits);
+        assert_eq!(wire, bools_to_wire_mask(&bools));
+        assert_eq!(BitVec256::from(&wire), bits);
+    }
+
+    #[test]
+    fn test_compact_mask_wire_encoding() {
+        let mut wire = Mask::ALL_BLOCKED;
+        wire.allow_range(0, 3);
+        wire.allow(9);
+        wire.allow(255);
+        let compact = CompactMask::from(&wire);
+        let mut expected = [0u8; COMPACT_MASK_SIZE];
+        expected[0] = 0b0000_0111;
+        expected[1] = 0b0000_0010;
+        expected[31] = 0b1000_0000;
+        assert_eq!(compact.to_bytes(), expected);
+        assert_eq!(Mask::from(CompactMask::from_bytes(expected)), wire);
+        assert_eq!(Mask::from(CompactMask::FULL), Mask::ALL_WRITABLE);
+        assert_eq!(Mask::from(CompactMask::ZERO), Mask::ALL_BLOCKED);
+    }
+
+    #[test]
+    fn test_bitvec_from_mask_treats_non_canonical_as_blocked() {
+        let mut bytes = [0x00u8; MASK_SIZE];
+        bytes[4] = 0x01;
+        let bits = BitVec256::from(&Mask::from(bytes));
+        assert!(bits.get_bit(3));
+        assert!(!bits.get_bit(4));
+    }
+}
@@ -0,0 +1,60 @@
+//! Program-wide configuration: a singleton [`ProgramConfig`] account at `[CONFIG_SEED, bump]`.
+//!
+//! The program's upgrade authority creates it once with `InitConfig`, naming a protocol
+//! admin. The admin changes it with `SetConfig`. `Create` and `CreateWithEvents` take the
+//! config account as their fourth account: they charge its `create_fee`, paid into the
+//! config account, and fail while it is paused. Every other state-changing instruction
+//! takes it as its last account and fails while it is paused too. The account is required
+//! either way. Until `InitConfig` runs the config address holds no data, and creation is
+//! free and never paused.
+
+use bytemuck::{Pod, Zeroable};
+use solana_address::Address;
+
+/// PDA seed discriminator for the [`ProgramConfig`] account: `[CONFIG_SEED, bump]`.
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Program configuration account (48 bytes), stored at the PDA `[CONFIG_SEED, bump]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
+#[repr(C)]
+pub struct ProgramConfig {
+    pub admin: Address,    // 32 [0..32]
+    pub create_fee: u64,   // 8  [32..40]
+    pub bump: u8,          // 1  [40]
+    pub paused: u8,        // 1  [41]
+    pub _padding: [u8; 6], // 6  [42..48]
+}
+
+const _: () = assert!(core::mem::size_of::<ProgramConfig>() == 48);
+
+impl ProgramConfig {
+    /// Total byte size of the config account.
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// Returns `true` if instructions passed the config are paused.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_layout() {
+        let mut config = ProgramConfig::zeroed();
+        config.admin = Address::new_from_array([7; 32]);
+        config.create_fee = 0x0102_0304_0506_0708;
+        config.bump = 254;
+        config.paused = 1;
+        let bytes = bytemuck::bytes_of(&config);
+        assert_eq!(bytes.len(), ProgramConfig::SIZE);
+        assert_eq!(bytes[..32], [7; 32]);
+        assert_eq!(bytes[32..40], 0x0102_0304_0506_0708u64.to_le_bytes());
+        assert_eq!(bytes[40], 254);
+        assert!(config.is_paused());
+        assert_eq!(*bytemuck::from_bytes::<ProgramConfig>(bytes), config);
+    }
+}
@@ -0,0 +1,151 @@
+//! Per-slot extremes of the oracle value, kept by the program for envelopes with
+//! [`FLAG_CONFLATION`].
+//!
+//! Several oracle writes can land in one slot, and a consumer reading once per slot only sees
+//! the last. A conflating envelope gives up [`CONFLATION_WINDOW_SIZE`] bytes just before the
+//! [`TwapAccumulator`](crate::TwapAccumulator) slot to a [`ConflationWindow`] at
+//! [`CONFLATION_OFFSET`]. Every oracle write (fast path, `BatchUpdate`, `GroupCommit`,
+//! `PublisherUpdate`) treats the first 8 payload bytes as a `u64` LE value and folds it in:
+//! the first write in a slot resets the window to that value, later writes in the same slot
+//! widen `min`/`max` and bump `count`. The last value is the oracle value itself.
+//!
+//! The window describes the slot of the latest write, so it still holds that slot's
+//! extremes when read in a later slot before the next write. Check
+//! [`ConflationWindow::slot`] (or use [`ConflationWindow::in_slot`]) to tell.
+//!
+//! The TWAP and update-stamp slots after the window stay reserved whether or not they are
+//! enabled, so no trailer moves when another is toggled. Like them, the window overlaps the
+//! memo trailer.
+
+use crate::{Envelope, U64Le, FLAG_CONFLATION, TWAP_OFFSET};
+use bytemuck::{Pod, Zeroable};
+
+/// Byte size of a [`ConflationWindow`].
+pub const CONFLATION_WINDOW_SIZE: usize = core::mem::size_of::<ConflationWindow>();
+
+/// Offset of the [`ConflationWindow`] within [`OracleState::data`](crate::OracleState::data).
+/// Payloads written to a conflating envelope must be between 8 and `CONFLATION_OFFSET` (159)
+/// bytes.
+pub const CONFLATION_OFFSET: usize = TWAP_OFFSET - CONFLATION_WINDOW_SIZE;
+
+/// Lowest, highest, and number of oracle values written in one slot. Alignment 1, like the
+/// other oracle trailers.
+#[derive(Clone, Copy, Default, Pod, Zeroable, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct ConflationWindow {
+    /// Slot the window covers. Zero until the first write after conflation was enabled.
+    pub slot: U64Le,
+    pub min: U64Le,
+    pub max: U64Le,
+    /// Writes folded in during [`slot`](Self::slot).
+    pub count: U64Le,
+}
+
+const _: () = assert!(CONFLATION_WINDOW_SIZE == 32);
+
+impl ConflationWindow {
+    /// Fold a write of `value` at `slot`.
+    ///
+    /// A write in a different slot than the window's (or the first write) starts a new
+    /// window holding only `value`. A write in the same slot widens the range and counts.
+    pub fn fold(&mut self, value: u64, slot: u64) {
+        if self.count.get() == 0 || self.slot.get() != slot {
+            *self = ConflationWindow {
+                slot: U64Le::new(slot),
+                min: U64Le::new(value),
+                max: U64Le::new(value),
+                count: U64Le::new(1),
+            };
+            return;
+        }
+        self.min = U64Le::new(self.min.get().min(value));
+        self.max = U64Le::new(self.max.get().max(value));
+        self.count = U64Le::new(self.count.get().saturating_add(1));
+    }
+
+    /// The window if it covers `slot` and holds at least one write.
+    pub fn in_slot(&self, slot: u64) -> Option<&Self> {
+        (self.count.get() != 0 && self.slot.get() == slot).then_some(self)
+    }
+}
+
+impl Envelope {
+    /// Returns `true` if [`FLAG_CONFLATION`] is set: oracle writes fold into
+    /// [`conflation`](Self::conflation).
+    #[inline]
+    pub fn has_conflation(&self) -> bool {
+        self.flags & FLAG_CONFLATION != 0
+    }
+
+    /// The conflation window, or `None` if [`FLAG_CONFLATION`] is not set.
+    pub fn conflation(&self) -> Option<&ConflationWindow> {
+        if !self.has_conflation() {
+            return None;
+        }
+        Some(bytemuck::from_bytes(
+            &self.oracle_state.data[CONFLATION_OFFSET..TWAP_OFFSET],
+        ))
+    }
+
+    /// Mutable variant of [`conflation`](Self::conflation).
+    pub fn conflation_mut(&mut self) -> Option<&mut ConflationWindow> {
+        if !self.has_conflation() {
+            return None;
+        }
+        Some(bytemuck::from_bytes_mut(
+            &mut self.oracle_state.data[CONFLATION_OFFSET..TWAP_OFFSET],
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{oracle_capacity, FLAG_TWAP};
+
+    #[test]
+    fn test_conflation_layout() {
+        assert_eq!(CONFLATION_OFFSET, 159);
+        assert_eq!(core::mem::align_of::<ConflationWindow>(), 1);
+        assert_eq!(oracle_capacity(FLAG_CONFLATION), CONFLATION_OFFSET);
+        assert_eq!(
+            oracle_capacity(FLAG_CONFLATION | FLAG_TWAP),
+            CONFLATION_OFFSET
+        );
+
+        let mut env = Envelope::zeroed();
+        assert!(env.conflation().is_none());
+        env.flags |= FLAG_CONFLATION;
+        env.conflation_mut().unwrap().count = U64Le::new(0x0102);
+        assert_eq!(env.oracle_state.data[TWAP_OFFSET - 8..][..2], [0x02, 0x01]);
+        assert!(env.oracle_state.data[TWAP_OFFSET..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_conflation_resets_each_slot() {
+        let mut window = ConflationWindow::zeroed();
+        assert!(window.in_slot(0).is_none(), "never written");
+
+        window.fold(50, 7);
+        window.fold(10, 7);
+        window.fold(90, 7);
+        window.fold(40, 7);
+        assert_eq!(window.min.get(), 10);
+        assert_eq!(window.max.get(), 90);
+        assert_eq!(window.count.get(), 4);
+        assert!(window.in_slot(7).is_some());
+        assert!(window.in_slot(8).is_none());
+
+        window.fold(60, 8);
+        assert_eq!(window.slot.get(), 8);
+        assert_eq!((window.min.get(), window.max.get()), (60, 60));
+        assert_eq!(window.count.get(), 1);
+
+        // Slot 0 is a real slot once something was written in it.
+        let mut window = ConflationWindow::zeroed();
+        window.fold(3, 0);
+        window.fold(1, 0);
+        assert_eq!(window.count.get(), 2);
+        assert_eq!(window.in_slot(0).unwrap().min.get(), 1);
+    }
+}
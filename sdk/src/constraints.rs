@@ -0,0 +1,501 @@
+//! Declarative invariants on oracle and auxiliary fields, kept by the program for envelopes
+//! with [`FLAG_CONSTRAINTS`].
+//!
+//! The authority stores up to [`MAX_CONSTRAINTS`] [`Constraint`]s in a [`ConstraintTable`]
+//! account at `[CONSTRAINTS_SEED, envelope_address, bump]`. Each one compares an unsigned or
+//! signed little-endian field of 1, 2, 4, or 8 bytes against a constant, against another
+//! field of the same region and width, or (auxiliary fields only) against its own value
+//! before the write:
+//!
+//! ```text
+//! aux[16..24] as i64 >= 0
+//! aux[0..8]   as u64 <= aux[8..16]
+//! aux[24..32] as u64 >= previous aux[24..32]
+//! ```
+//!
+//! The last form, built by [`Constraint::sequenced`], makes a field a counter that no write
+//! may decrease, kept separately from the envelope's aux sequences.
+//!
+//! Every write to a constrained region must pass the table account, and fails with
+//! `Custom(CONSTRAINT_VIOLATED_ERROR)` if the region afterwards breaks any of the region's
+//! constraints. Evaluation reads at most two 8-byte fields per constraint, so its cost is
+//! bounded regardless of the data written.
+//!
+//! [`Envelope::constrained_regions`] records which regions the table covers. The fast path
+//! cannot take the table account, so it rejects envelopes with oracle constraints; their
+//! oracle writes go through `BatchUpdate` or `GroupCommit` instead. Auxiliary-only
+//! constraints leave the fast path untouched.
+
+use crate::{Envelope, U64Le, AUX_DATA_SIZE, FLAG_CONSTRAINTS, ORACLE_BYTES};
+use bytemuck::{Pod, Zeroable};
+use solana_address::Address;
+
+/// PDA seed discriminator for [`ConstraintTable`] accounts:
+/// `[CONSTRAINTS_SEED, envelope_address, bump]`.
+pub const CONSTRAINTS_SEED: &[u8] = b"constraints";
+
+/// Maximum number of entries in a [`ConstraintTable`].
+pub const MAX_CONSTRAINTS: usize = 4;
+
+/// The envelope region a [`Constraint`] reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ConstraintRegion {
+    /// [`OracleState::data`](crate::OracleState::data).
+    Oracle = 0,
+    /// [`Envelope::auxiliary_data`].
+    Aux = 1,
+}
+
+impl ConstraintRegion {
+    /// Decode a stored region byte.
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Oracle),
+            1 => Some(Self::Aux),
+            _ => None,
+        }
+    }
+
+    /// This region's bit in [`Envelope::constrained_regions`].
+    #[inline]
+    pub const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// Byte length of the region.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::Oracle => ORACLE_BYTES,
+            Self::Aux => AUX_DATA_SIZE,
+        }
+    }
+}
+
+/// Comparison applied by a [`Constraint`]: `field <op> rhs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ConstraintOp {
+    Eq = 0,
+    Ne = 1,
+    Lt = 2,
+    Le = 3,
+    Gt = 4,
+    Ge = 5,
+}
+
+impl ConstraintOp {
+    /// Decode a stored op byte.
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Eq),
+            1 => Some(Self::Ne),
+            2 => Some(Self::Lt),
+            3 => Some(Self::Le),
+            4 => Some(Self::Gt),
+            5 => Some(Self::Ge),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn apply<T: Ord>(self, lhs: T, rhs: T) -> bool {
+        match self {
+            Self::Eq => lhs == rhs,
+            Self::Ne => lhs != rhs,
+            Self::Lt => lhs < rhs,
+            Self::Le => lhs <= rhs,
+            Self::Gt => lhs > rhs,
+            Self::Ge => lhs >= rhs,
+        }
+    }
+}
+
+/// [`Constraint::rhs_kind`]: compare against [`Constraint::value`].
+pub const RHS_CONST: u8 = 0;
+/// [`Constraint::rhs_kind`]: compare against the field at [`Constraint::rhs_offset`].
+pub const RHS_FIELD: u8 = 1;
+/// [`Constraint::rhs_kind`]: compare against the same field before the write. Only valid on
+/// [`ConstraintRegion::Aux`].
+pub const RHS_PREVIOUS: u8 = 2;
+
+/// One stored invariant: `region[offset..offset + width] <op> rhs`.
+///
+/// Fields are little-endian, read as unsigned unless `signed` is non-zero. A constant `value`
+/// is compared at full 64-bit width (as `i64` when signed), so a constant outside the
+/// field's range makes the comparison constant too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
+#[repr(C)]
+pub struct Constraint {
+    pub region: u8,     // 1 [0]
+    pub op: u8,         // 1 [1]
+    pub width: u8,      // 1 [2]
+    pub signed: u8,     // 1 [3]
+    pub offset: u8,     // 1 [4]
+    pub rhs_kind: u8,   // 1 [5]
+    pub rhs_offset: u8, // 1 [6]
+    pub _padding: u8,   // 1 [7]
+    pub value: U64Le,   // 8 [8..16]
+}
+
+impl Constraint {
+    /// `region[offset..offset + width] <op> value`.
+    pub const fn against_const(
+        region: ConstraintRegion,
+        offset: u8,
+        width: u8,
+        signed: bool,
+        op: ConstraintOp,
+        value: u64,
+    ) -> Self {
+        Self {
+            region: region as u8,
+            op: op as u8,
+            width,
+            signed: signed as u8,
+            offset,
+            rhs_kind: RHS_CONST,
+            rhs_offset: 0,
+            _padding: 0,
+            value: U64Le::new(value),
+        }
+    }
+
+    /// `region[offset..offset + width] <op> region[rhs_offset..rhs_offset + width]`.
+    pub const fn against_field(
+        region: ConstraintRegion,
+        offset: u8,
+        width: u8,
+        signed: bool,
+        op: ConstraintOp,
+        rhs_offset: u8,
+    ) -> Self {
+        Self {
+            region: region as u8,
+            op: op as u8,
+            width,
+            signed: signed as u8,
+            offset,
+            rhs_kind: RHS_FIELD,
+            rhs_offset,
+            _padding: 0,
+            value: U64Le::new(0),
+        }
+    }
+
+    /// `aux[offset..offset + 8] as u64 >= previous aux[offset..offset + 8]`: the 8-byte
+    /// counter at `offset` may stay put or grow, but no write may decrease it.
+    pub const fn sequenced(offset: u8) -> Self {
+        Self {
+            region: ConstraintRegion::Aux as u8,
+            op: ConstraintOp::Ge as u8,
+            width: 8,
+            signed: 0,
+            offset,
+            rhs_kind: RHS_PREVIOUS,
+            rhs_offset: 0,
+            _padding: 0,
+            value: U64Le::new(0),
+        }
+    }
+
+    /// The region this constraint reads, or `None` for an unknown region byte.
+    #[inline]
+    pub const fn region(&self) -> Option<ConstraintRegion> {
+        ConstraintRegion::from_byte(self.region)
+    }
+
+    /// Returns `true` if every byte decodes (region, op, `rhs_kind`), `width` is 1, 2, 4, or
+    /// 8, each field it reads lies within its region, and [`RHS_PREVIOUS`] is only used on
+    /// the auxiliary region.
+    pub fn is_valid(&self) -> bool {
+        let Some(region) = self.region() else {
+            return false;
+        };
+        let width = self.width as usize;
+        let in_region = |offset: u8| offset as usize + width <= region.size();
+        ConstraintOp::from_byte(self.op).is_some()
+            && matches!(width, 1 | 2 | 4 | 8)
+            && in_region(self.offset)
+            && match self.rhs_kind {
+                RHS_CONST => true,
+                RHS_FIELD => in_region(self.rhs_offset),
+                RHS_PREVIOUS => region == ConstraintRegion::Aux,
+                _ => false,
+            }
+    }
+
+    /// Evaluate against the bytes of this constraint's region, as if a write left them
+    /// unchanged. An invalid constraint never holds.
+    pub fn holds(&self, region_bytes: &[u8]) -> bool {
+        self.holds_after(region_bytes, region_bytes)
+    }
+
+    /// Evaluate against `after`, the region once a write is applied, reading
+    /// [`RHS_PREVIOUS`] fields from `before`. An invalid constraint never holds.
+    pub fn holds_after(&self, before: &[u8], after: &[u8]) -> bool {
+        let Some(op) = ConstraintOp::from_byte(self.op) else {
+            return false;
+        };
+        if !self.is_valid() {
+            return false;
+        }
+        let Some(lhs) = self.read(after, self.offset) else {
+            return false;
+        };
+        let rhs = match self.rhs_kind {
+            RHS_FIELD => self.read(after, self.rhs_offset),
+            RHS_PREVIOUS => self.read(before, self.offset),
+            _ => Some(self.value.get()),
+        };
+        let Some(rhs) = rhs else {
+            return false;
+        };
+        if self.signed != 0 {
+            op.apply(lhs as i64, rhs as i64)
+        } else {
+            op.apply(lhs, rhs)
+        }
+    }
+
+    /// The field at `offset`, zero- or sign-extended to 64 bits.
+    #[inline]
+    fn read(&self, bytes: &[u8], offset: u8) -> Option<u64> {
+        let width = self.width as usize;
+        let field = bytes.get(offset as usize..offset as usize + width)?;
+        let mut raw = [0u8; 8];
+        raw[..width].copy_from_slice(field);
+        let value = u64::from_le_bytes(raw);
+        let shift = 64 - 8 * width as u32;
+        Some(if self.signed != 0 {
+            (((value << shift) as i64) >> shift) as u64
+        } else {
+            value
+        })
+    }
+}
+
+/// Per-envelope constraint account (104 bytes), stored at the PDA
+/// `[CONSTRAINTS_SEED, envelope_address, bump]`.
+///
+/// Only the first `count` entries are active. Enforced on writes to the regions recorded in
+/// [`Envelope::constrained_regions`].
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct ConstraintTable {
+    pub envelope: Address,                      // 32 [0..32]
+    pub count: u8,                              // 1  [32]
+    pub bump: u8,                               // 1  [33]
+    pub _padding: [u8; 6],                      // 6  [34..40]
+    pub entries: [Constraint; MAX_CONSTRAINTS], // 64 [40..104]
+}
+
+const _: () = assert!(core::mem::size_of::<Constraint>() == 16);
+const _: () = assert!(core::mem::size_of::<ConstraintTable>() == 104);
+
+impl ConstraintTable {
+    /// Total byte size of a constraint table account.
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// The active entries (`entries[..count]`, clamped to [`MAX_CONSTRAINTS`]).
+    #[inline]
+    pub fn active(&self) -> &[Constraint] {
+        &self.entries[..(self.count as usize).min(MAX_CONSTRAINTS)]
+    }
+
+    /// Bitmap of the regions the active entries read (see [`ConstraintRegion::bit`]).
+    pub fn regions(&self) -> u8 {
+        self.active()
+            .iter()
+            .filter_map(Constraint::region)
+            .fold(0, |bits, region| bits | region.bit())
+    }
+
+    /// Index of the first active constraint on `region` that `region_bytes` breaks, or
+    /// `None` if they all hold.
+    pub fn first_violation(&self, region: ConstraintRegion, region_bytes: &[u8]) -> Option<usize> {
+        self.first_violation_after(region, region_bytes, region_bytes)
+    }
+
+    /// [`first_violation`](Self::first_violation) for a write that turned `before` into
+    /// `after`, so [`RHS_PREVIOUS`] constraints compare against `before`.
+    pub fn first_violation_after(
+        &self,
+        region: ConstraintRegion,
+        before: &[u8],
+        after: &[u8],
+    ) -> Option<usize> {
+        self.active()
+            .iter()
+            .position(|c| c.region == region as u8 && !c.holds_after(before, after))
+    }
+}
+
+impl Envelope {
+    /// Returns `true` if [`FLAG_CONSTRAINTS`] is set: writes to the regions in
+    /// [`constrained_regions`](Self::constrained_regions) are checked against the
+    /// envelope's [`ConstraintTable`].
+    #[inline]
+    pub fn has_constraints(&self) -> bool {
+        self.flags & FLAG_CONSTRAINTS != 0
+    }
+
+    /// Returns `true` if writes to `region` must pass the constraint table.
+    #[inline]
+    pub fn is_constrained(&self, region: ConstraintRegion) -> bool {
+        self.has_constraints() && self.constrained_regions & region.bit() != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constraint_against_const() {
+        let mut aux = [0u8; AUX_DATA_SIZE];
+        aux[8..16].copy_from_slice(&(-5i64).to_le_bytes());
+        aux[16..18].copy_from_slice(&300u16.to_le_bytes());
+
+        let non_negative =
+            Constraint::against_const(ConstraintRegion::Aux, 8, 8, true, ConstraintOp::Ge, 0);
+        assert!(!non_negative.holds(&aux));
+        let unsigned = Constraint {
+            signed: 0,
+            ..non_negative
+        };
+        assert!(unsigned.holds(&aux), "-5 read unsigned is huge");
+
+        let small =
+            |op, value| Constraint::against_const(ConstraintRegion::Aux, 16, 2, false, op, value);
+        assert!(small(ConstraintOp::Le, 300).holds(&aux));
+        assert!(!small(ConstraintOp::Lt, 300).holds(&aux));
+        assert!(small(ConstraintOp::Eq, 300).holds(&aux));
+        assert!(small(ConstraintOp::Ne, 299).holds(&aux));
+
+        // A 1-byte signed field is sign-extended before comparing.
+        aux[20] = 0xFF;
+        let byte = Constraint::against_const(
+            ConstraintRegion::Aux,
+            20,
+            1,
+            true,
+            ConstraintOp::Eq,
+            (-1i64) as u64,
+        );
+        assert!(byte.holds(&aux));
+    }
+
+    #[test]
+    fn test_constraint_against_field() {
+        let mut oracle = [0u8; ORACLE_BYTES];
+        oracle[0..4].copy_from_slice(&10u32.to_le_bytes());
+        oracle[4..8].copy_from_slice(&20u32.to_le_bytes());
+        let ordered =
+            Constraint::against_field(ConstraintRegion::Oracle, 0, 4, false, ConstraintOp::Le, 4);
+        assert!(ordered.holds(&oracle));
+        oracle[0] = 21;
+        assert!(!ordered.holds(&oracle));
+    }
+
+    #[test]
+    fn test_sequenced_constraint() {
+        let counter = Constraint::sequenced(8);
+        let mut before = [0u8; AUX_DATA_SIZE];
+        before[8..16].copy_from_slice(&5u64.to_le_bytes());
+        let mut after = before;
+        assert!(counter.holds_after(&before, &after));
+        after[8..16].copy_from_slice(&6u64.to_le_bytes());
+        assert!(counter.holds_after(&before, &after));
+        after[8..16].copy_from_slice(&4u64.to_le_bytes());
+        assert!(!counter.holds_after(&before, &after));
+        assert!(counter.holds(&after), "no write, no decrease");
+
+        // Counters compare unsigned, so wrapping to zero is a decrease.
+        before[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        after[8..16].fill(0);
+        assert!(!counter.holds_after(&before, &after));
+
+        let mut table = ConstraintTable::zeroed();
+        table.entries[0] = counter;
+        table.count = 1;
+        assert_eq!(
+            table.first_violation_after(ConstraintRegion::Aux, &before, &after),
+            Some(0)
+        );
+        assert_eq!(table.first_violation(ConstraintRegion::Aux, &after), None);
+    }
+
+    #[test]
+    fn test_constraint_validity() {
+        let base =
+            Constraint::against_const(ConstraintRegion::Oracle, 0, 8, false, ConstraintOp::Ge, 0);
+        assert!(base.is_valid());
+        assert!(!Constraint { width: 3, ..base }.is_valid());
+        assert!(!Constraint { op: 9, ..base }.is_valid());
+        assert!(!Constraint { region: 2, ..base }.is_valid());
+        assert!(!Constraint {
+            rhs_kind: 3,
+            ..base
+        }
+        .is_valid());
+        assert!(
+            !Constraint {
+                rhs_kind: RHS_PREVIOUS,
+                ..base
+            }
+            .is_valid(),
+            "oracle fields have no previous value"
+        );
+        assert!(Constraint::sequenced(248).is_valid());
+        assert!(!Constraint::sequenced(249).is_valid());
+        assert!(
+            !Constraint {
+                offset: 232,
+                ..base
+            }
+            .is_valid(),
+            "239-byte region"
+        );
+        assert!(Constraint {
+            region: ConstraintRegion::Aux as u8,
+            offset: 248,
+            ..base
+        }
+        .is_valid());
+        let field =
+            Constraint::against_field(ConstraintRegion::Oracle, 0, 8, false, ConstraintOp::Le, 232);
+        assert!(!field.is_valid());
+        assert!(!field.holds(&[0u8; ORACLE_BYTES]));
+    }
+
+    #[test]
+    fn test_constraint_table_regions_and_violation() {
+        let mut table = ConstraintTable::zeroed();
+        assert_eq!(table.regions(), 0);
+        table.entries[0] =
+            Constraint::against_const(ConstraintRegion::Aux, 0, 1, false, ConstraintOp::Eq, 1);
+        table.entries[1] =
+            Constraint::against_const(ConstraintRegion::Aux, 1, 1, false, ConstraintOp::Eq, 1);
+        table.entries[2] =
+            Constraint::against_const(ConstraintRegion::Oracle, 0, 1, false, ConstraintOp::Eq, 1);
+        table.count = 2;
+        assert_eq!(table.regions(), ConstraintRegion::Aux.bit());
+        table.count = 3;
+        assert_eq!(table.regions(), 0b11);
+
+        let mut aux = [0u8; AUX_DATA_SIZE];
+        aux[0] = 1;
+        assert_eq!(table.first_violation(ConstraintRegion::Aux, &aux), Some(1));
+        aux[1] = 1;
+        assert_eq!(table.first_violation(ConstraintRegion::Aux, &aux), None);
+
+        let mut env = Envelope::zeroed();
+        env.constrained_regions = table.regions();
+        assert!(!env.is_constrained(ConstraintRegion::Aux));
+        env.flags |= FLAG_CONSTRAINTS;
+        assert!(env.is_constrained(ConstraintRegion::Aux));
+        env.constrained_regions = ConstraintRegion::Oracle.bit();
+        assert!(!env.is_constrained(ConstraintRegion::Aux));
+    }
+}
@@ -0,0 +1,39 @@
+//! Oracle write events logged by the fast path for indexers.
+//!
+//! An envelope created with `CreateWithEvents` has [`Envelope::emit_events`](crate::Envelope::emit_events) set. Each
+//! fast-path write to it then logs one [`OracleEvent`] through `sol_log_data` before the
+//! copy, which the runtime prints as `Program data: <base64>`. Indexers can rebuild the
+//! oracle history from those lines instead of polling the account, and check a payload they
+//! received elsewhere against `payload_hash`.
+//!
+//! The flag is fixed at creation. Envelopes without it pay nothing for the feature: the
+//! fast path tests it together with [`Envelope::paused`](crate::Envelope::paused).
+
+use crate::StructMetadata;
+use bytemuck::{Pod, Zeroable};
+use solana_address::Address;
+
+/// Byte size of an [`OracleEvent`].
+pub const ORACLE_EVENT_SIZE: usize = core::mem::size_of::<OracleEvent>();
+
+/// One fast-path write, as logged for envelopes with [`Envelope::emit_events`](crate::Envelope::emit_events).
+/// `payload_hash` is the SHA-256 of the payload, the instruction data after
+/// `[oracle_meta][sequence]`.
+#[derive(Clone, Copy, Pod, Zeroable, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct OracleEvent {
+    pub envelope: Address,               // 32 [0..32]
+    pub sequence: u64,                   // 8  [32..40]
+    pub oracle_metadata: StructMetadata, // 8  [40..48]
+    pub payload_hash: [u8; 32],          // 32 [48..80]
+}
+
+const _: () = assert!(ORACLE_EVENT_SIZE == 80);
+
+impl OracleEvent {
+    /// Read an event from the bytes of a `Program data:` log line. Returns `None` unless
+    /// `data` is exactly [`ORACLE_EVENT_SIZE`] bytes; alignment is not required.
+    pub fn from_log_data(data: &[u8]) -> Option<Self> {
+        (data.len() == ORACLE_EVENT_SIZE).then(|| bytemuck::pod_read_unaligned(data))
+    }
+}
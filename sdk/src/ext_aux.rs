@@ -0,0 +1,186 @@
+//! Extended auxiliary region of a resized envelope.
+//!
+//! `Resize` grows an envelope account past [`Envelope::SIZE`] and appends an extension:
+//!
+//! - `[SIZE..SIZE + 16]`  [`ExtAuxHeader`]
+//! - `[SIZE + 16..]`      extension data, `ext_len` bytes
+//!
+//! The header stores the full `TYPE_HASH` of the extension type instead of a
+//! [`StructMetadata`](crate::StructMetadata), whose 8-bit size field cannot describe the
+//! types this region exists for. Only the envelope authority writes the extension
+//! (`UpdateExtAux`); the masks and delegation do not apply to it.
+//!
+//! An account that is exactly `SIZE` bytes has no extension. Lengths between `SIZE + 1` and
+//! `SIZE + 15` are malformed and read as no extension.
+
+use crate::{Envelope, TypeHash};
+use bytemuck::{Pod, Zeroable};
+
+/// Byte size of the [`ExtAuxHeader`] that precedes extension data.
+pub const EXT_AUX_HEADER_SIZE: usize = core::mem::size_of::<ExtAuxHeader>();
+
+/// Account offset of the first extension data byte. 8-byte aligned.
+pub const EXT_AUX_DATA_OFFSET: usize = Envelope::SIZE + EXT_AUX_HEADER_SIZE;
+
+/// Largest extension, in data bytes. The runtime lets one instruction grow an account by at
+/// most 10 KiB, so an envelope can go from [`Envelope::SIZE`] to the maximum in one `Resize`.
+pub const MAX_EXT_AUX_SIZE: usize = 10 * 1024 - EXT_AUX_HEADER_SIZE;
+
+/// Header of the extended auxiliary region, at [`Envelope::SIZE`] in a resized account.
+#[derive(Clone, Copy, Pod, Zeroable, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct ExtAuxHeader {
+    /// `TYPE_HASH` of the type stored in the extension. Zero = untyped.
+    pub type_hash: u64,
+    /// Sequence of the latest `UpdateExtAux`. Each update must be strictly greater.
+    /// Reset to zero when `Resize` changes `type_hash`.
+    pub sequence: u64,
+}
+
+const _: () = assert!(EXT_AUX_HEADER_SIZE == 16);
+const _: () = assert!(EXT_AUX_DATA_OFFSET & 7 == 0);
+
+/// Account data length of an envelope with `ext_len` extension bytes. `0` means no
+/// extension: the plain [`Envelope::SIZE`].
+pub const fn ext_account_size(ext_len: usize) -> usize {
+    if ext_len == 0 {
+        Envelope::SIZE
+    } else {
+        EXT_AUX_DATA_OFFSET + ext_len
+    }
+}
+
+impl Envelope {
+    /// The extension header in a resized envelope's account data, or `None` if the account
+    /// has no extension.
+    pub fn ext_aux_header(data: &[u8]) -> Option<&ExtAuxHeader> {
+        bytemuck::try_from_bytes(data.get(Self::SIZE..EXT_AUX_DATA_OFFSET)?).ok()
+    }
+
+    /// Mutable variant of [`ext_aux_header`](Self::ext_aux_header).
+    pub fn ext_aux_header_mut(data: &mut [u8]) -> Option<&mut ExtAuxHeader> {
+        bytemuck::try_from_bytes_mut(data.get_mut(Self::SIZE..EXT_AUX_DATA_OFFSET)?).ok()
+    }
+
+    /// The raw extension data, or `None` if the account has no extension.
+    pub fn ext_aux_bytes(data: &[u8]) -> Option<&[u8]> {
+        Self::ext_aux_header(data)?;
+        data.get(EXT_AUX_DATA_OFFSET..)
+    }
+
+    /// Mutable variant of [`ext_aux_bytes`](Self::ext_aux_bytes).
+    pub fn ext_aux_bytes_mut(data: &mut [u8]) -> Option<&mut [u8]> {
+        Self::ext_aux_header(data)?;
+        data.get_mut(EXT_AUX_DATA_OFFSET..)
+    }
+
+    /// Borrow the start of the extension data as `T`.
+    ///
+    /// Returns `None` if:
+    /// - the account has no extension,
+    /// - the header's `type_hash != T::TYPE_HASH`, or
+    /// - the extension is shorter than `size_of::<T>()`.
+    ///
+    /// `T` may be larger than 255 bytes: only `T::TYPE_HASH` is used, never `T::METADATA`.
+    pub fn ext_aux<T: TypeHash + Pod>(data: &[u8]) -> Option<&T> {
+        if Self::ext_aux_header(data)?.type_hash != T::TYPE_HASH {
+            return None;
+        }
+        let bytes = Self::ext_aux_bytes(data)?;
+        bytemuck::try_from_bytes(bytes.get(..core::mem::size_of::<T>())?).ok()
+    }
+
+    /// Mutably borrow the start of the extension data as `T`.
+    ///
+    /// Returns `None` under the same conditions as [`ext_aux`](Self::ext_aux).
+    pub fn ext_aux_mut<T: TypeHash + Pod>(data: &mut [u8]) -> Option<&mut T> {
+        if Self::ext_aux_header(data)?.type_hash != T::TYPE_HASH {
+            return None;
+        }
+        let bytes = Self::ext_aux_bytes_mut(data)?;
+        bytemuck::try_from_bytes_mut(bytes.get_mut(..core::mem::size_of::<T>())?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Pod, Zeroable)]
+    #[repr(C)]
+    struct Book {
+        levels: [u64; 64],
+    }
+
+    // Hand-written because the derive's METADATA assertion would reject a 512-byte type if
+    // it were ever evaluated; `ext_aux` only reads `TYPE_HASH`.
+    impl TypeHash for Book {
+        const TYPE_HASH: u64 = crate::const_fnv1a(b"Book");
+        const METADATA: crate::StructMetadata = crate::StructMetadata::ZERO;
+    }
+
+    /// 8-byte aligned scratch space for one account, sliced to length by each test.
+    fn words() -> [u64; 256] {
+        [0; 256]
+    }
+
+    #[test]
+    fn test_ext_account_size() {
+        assert_eq!(ext_account_size(0), Envelope::SIZE);
+        assert_eq!(ext_account_size(1), Envelope::SIZE + 17);
+        assert_eq!(
+            ext_account_size(MAX_EXT_AUX_SIZE) - Envelope::SIZE,
+            10 * 1024
+        );
+    }
+
+    #[test]
+    fn test_plain_and_malformed_accounts_have_no_extension() {
+        let words = words();
+        let bytes: &[u8] = bytemuck::cast_slice(&words);
+        let plain = &bytes[..ext_account_size(0)];
+        assert!(Envelope::ext_aux_header(plain).is_none());
+        assert!(Envelope::ext_aux_bytes(plain).is_none());
+
+        let short = &bytes[..EXT_AUX_DATA_OFFSET - 1];
+        assert!(Envelope::ext_aux_header(short).is_none());
+        assert!(Envelope::ext_aux::<u64>(short).is_none());
+    }
+
+    #[test]
+    fn test_ext_aux_typed_roundtrip() {
+        let mut words = words();
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut words);
+        let data = &mut bytes[..ext_account_size(core::mem::size_of::<Book>() + 8)];
+        Envelope::ext_aux_header_mut(data).unwrap().type_hash = Book::TYPE_HASH;
+
+        Envelope::ext_aux_mut::<Book>(data).unwrap().levels[63] = 99;
+        assert_eq!(Envelope::ext_aux::<Book>(data).unwrap().levels[63], 99);
+        assert_eq!(
+            &data[EXT_AUX_DATA_OFFSET + 504..EXT_AUX_DATA_OFFSET + 512],
+            &99u64.to_le_bytes()
+        );
+        assert_eq!(
+            Envelope::ext_aux_bytes(data).unwrap().len(),
+            core::mem::size_of::<Book>() + 8
+        );
+        // The envelope prefix is untouched.
+        assert!(Envelope::from_prefix_bytes(data).is_some());
+    }
+
+    #[test]
+    fn test_ext_aux_rejects_wrong_type_and_short_region() {
+        let mut words = words();
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut words);
+        let data = &mut bytes[..ext_account_size(8)];
+        assert!(Envelope::ext_aux::<u64>(data).is_none());
+
+        Envelope::ext_aux_header_mut(data).unwrap().type_hash = u64::TYPE_HASH;
+        assert!(Envelope::ext_aux::<u64>(data).is_some());
+        assert!(Envelope::ext_aux::<u32>(data).is_none());
+        assert!(Envelope::ext_aux::<[u64; 2]>(data).is_none());
+
+        Envelope::ext_aux_header_mut(data).unwrap().type_hash = Book::TYPE_HASH;
+        assert!(Envelope::ext_aux::<Book>(data).is_none());
+    }
+}
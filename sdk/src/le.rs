@@ -0,0 +1,184 @@
+//! Little-endian integer wrappers for oracle and auxiliary schemas.
+//!
+//! Envelope bytes are little-endian on the wire because SBF is. A plain `u64` field is
+//! stored in host order, so off-chain code on a big-endian host that casts envelope bytes
+//! into a schema struct reads garbage. [`U32Le`], [`U64Le`], [`I32Le`], and [`I64Le`] store
+//! their bytes in little-endian order on every host and convert only in [`get`](U64Le::get)
+//! and [`new`](U64Le::new).
+//!
+//! They are byte arrays, so they have alignment 1. A struct is still `Pod` only if it has
+//! no padding, but swapping a `u64` field for a [`U64Le`] can remove padding that the
+//! alignment used to require, changing the struct's size and `METADATA`.
+
+use crate::{const_fnv1a, StructMetadata, TypeHash};
+use bytemuck::{Pod, Zeroable};
+
+macro_rules! le_int {
+    ($(#[$doc:meta])* $name:ident, $int:ty) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Default, Pod, Zeroable, PartialEq, Eq, Hash)]
+        #[repr(transparent)]
+        pub struct $name([u8; core::mem::size_of::<$int>()]);
+
+        impl $name {
+            pub const ZERO: Self = Self([0; core::mem::size_of::<$int>()]);
+
+            #[inline]
+            pub const fn new(value: $int) -> Self {
+                Self(value.to_le_bytes())
+            }
+
+            #[inline]
+            pub const fn get(self) -> $int {
+                <$int>::from_le_bytes(self.0)
+            }
+
+            #[inline]
+            pub fn set(&mut self, value: $int) {
+                self.0 = value.to_le_bytes();
+            }
+
+            /// The stored bytes, least significant first.
+            #[inline]
+            pub const fn to_le_bytes(self) -> [u8; core::mem::size_of::<$int>()] {
+                self.0
+            }
+
+            #[inline]
+            pub const fn from_le_bytes(bytes: [u8; core::mem::size_of::<$int>()]) -> Self {
+                Self(bytes)
+            }
+        }
+
+        impl From<$int> for $name {
+            #[inline]
+            fn from(value: $int) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl From<$name> for $int {
+            #[inline]
+            fn from(value: $name) -> Self {
+                value.get()
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+                self.get().cmp(&other.get())
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::Debug::fmt(&self.get(), f)
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::Display::fmt(&self.get(), f)
+            }
+        }
+
+        impl TypeHash for $name {
+            const TYPE_HASH: u64 = const_fnv1a(stringify!($name).as_bytes());
+            const METADATA: StructMetadata = StructMetadata::new(
+                core::mem::size_of::<$name>() as u8,
+                Self::TYPE_HASH,
+            );
+        }
+    };
+}
+
+le_int!(
+    /// `u32` stored little-endian on every host.
+    U32Le,
+    u32
+);
+le_int!(
+    /// `u64` stored little-endian on every host.
+    U64Le,
+    u64
+);
+le_int!(
+    /// `i32` stored little-endian on every host.
+    I32Le,
+    i32
+);
+le_int!(
+    /// `i64` stored little-endian on every host.
+    I64Le,
+    i64
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // These assert byte order, not round-trips, so they only mean something when also run
+    // on a big-endian target (`make test-big-endian`).
+    #[test]
+    fn bytes_are_little_endian_on_any_host() {
+        assert_eq!(
+            bytemuck::bytes_of(&U64Le::new(0x0102_0304_0506_0708)),
+            &[8, 7, 6, 5, 4, 3, 2, 1]
+        );
+        assert_eq!(
+            bytemuck::bytes_of(&U32Le::new(0x0A0B_0C0D)),
+            &[0xD, 0xC, 0xB, 0xA]
+        );
+        assert_eq!(
+            bytemuck::bytes_of(&I64Le::new(-2)),
+            &[0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]
+        );
+
+        let bytes = [1u8, 0, 0, 0, 0, 0, 0, 0x80];
+        let value: &U64Le = bytemuck::from_bytes(&bytes);
+        assert_eq!(value.get(), 0x8000_0000_0000_0001);
+        let value: &I64Le = bytemuck::from_bytes(&bytes);
+        assert_eq!(value.get(), i64::MIN + 1);
+    }
+
+    #[test]
+    fn conversions_and_ordering() {
+        let mut value = U64Le::from(5u64);
+        assert_eq!(u64::from(value), 5);
+        value.set(u64::MAX);
+        assert_eq!(value.get(), u64::MAX);
+        assert_eq!(U64Le::from_le_bytes(value.to_le_bytes()), value);
+        assert_eq!(U64Le::ZERO, U64Le::default());
+
+        // Ordering follows the integer, not the byte array.
+        assert!(U64Le::new(256) > U64Le::new(1));
+        assert!(I64Le::new(-1) < I64Le::new(0));
+        assert!(U32Le::new(0x100) > U32Le::new(0xFF));
+    }
+
+    #[test]
+    fn type_hash_distinct_from_native_ints() {
+        assert_eq!(U64Le::METADATA.type_size(), 8);
+        assert_eq!(U32Le::METADATA.type_size(), 4);
+        assert_eq!(core::mem::align_of::<U64Le>(), 1);
+        let hashes = [
+            U32Le::TYPE_HASH,
+            U64Le::TYPE_HASH,
+            I64Le::TYPE_HASH,
+            u32::TYPE_HASH,
+            u64::TYPE_HASH,
+            i64::TYPE_HASH,
+        ];
+        for i in 0..hashes.len() {
+            for j in (i + 1)..hashes.len() {
+                assert_ne!(hashes[i], hashes[j]);
+            }
+        }
+    }
+}
@@ -1,20 +1,130 @@
 //! Core types for the `c_u_soon` protocol.
 //!
-//! The on-chain primitive is an [`Envelope`] account (1120 bytes) with three regions:
+//! The on-chain primitive is an [`Envelope`] account (1200 bytes) with three regions:
 //! [`OracleState`] (written atomically by the fast path), delegation state with two
 //! [`Mask`]s (controlling auxiliary write access), and a 256-byte auxiliary data region
-//! (written by the slow path, validated against both masks on every update).
+//! (written by the slow path, validated against both masks on every update). A delegation
+//! can ask for its program to be called after every authority aux write, and veto it; see
+//! [`Envelope::write_hook`].
+//!
+//! Envelopes that need more than 256 bytes of auxiliary data can be resized to carry an
+//! extended auxiliary region after the fixed layout, read with [`Envelope::ext_aux`].
+//!
+//! With [`FLAG_TWAP`] set, the program keeps a time-weighted average of the oracle value in
+//! the tail of the oracle region, read with [`Envelope::twap`]. With [`FLAG_UPDATE_STAMP`]
+//! set, it records the slot and unix timestamp of every oracle write, read with
+//! [`Envelope::last_update_slot`] and checked with [`Envelope::oracle_max_staleness`].
+//! Cranked heartbeats mark an unchanged value as still current without a write; see
+//! [`Envelope::last_live_slot`].
+//! With [`FLAG_CONFLATION`] set, it keeps the lowest and highest value written in the slot
+//! of the latest write, read with [`Envelope::conflation`].
+//!
+//! With [`FLAG_CONSTRAINTS`] set, writes must keep the fields named in the envelope's
+//! [`ConstraintTable`] within their declared bounds. Aux bytes sealed with
+//! `SealAuxiliaryRange` ([`SEALED_MASK_BYTE`]) can never change again.
+//!
+//! A [`PublisherSet`] lets up to [`MAX_PUBLISHERS`] registered keys push oracle updates
+//! in place of the authority, with an M-of-N signature threshold. A [`ReaderList`] limits
+//! the program's oracle reads to approved programs. [`median_within`] is the outlier-resistant
+//! median the program uses to aggregate several publisher envelopes into one.
+//!
+//! A singleton [`ProgramConfig`] holds the protocol admin, the fee charged per created
+//! envelope, and a pause flag for every state-changing instruction.
 //!
 //! # Type identity
 //!
 //! [`TypeHash`] and [`StructMetadata`] ensure typed reads ([`Envelope::oracle`],
 //! [`Envelope::aux`]) succeed only when the stored metadata matches the requested type.
-//! A mismatch returns `None` instead of a corrupt cast.
+//! A mismatch returns `None` instead of a corrupt cast. [`TypedEnvelope`] checks both
+//! once and then reads the oracle and aux values without further `Option` handling.
+//! [`Envelope::oracle_checked`] adds a sequence-lag check and reports failures as a
+//! [`ReadError`].
+//!
+//! # Byte order
+//!
+//! Envelope data is little-endian. Schemas read off-chain on big-endian hosts should use
+//! [`U32Le`], [`U64Le`], [`I32Le`], and [`I64Le`] instead of native integers. [`PriceValue`]
+//! is a ready-made decimal price type built from them.
+//!
+//! # Features
+//!
+//! - `alloc`: bool-vector mask conversions ([`wire_mask_to_bools`],
+//!   [`BitVec256::to_bools`]).
+//! - `derive`: `#[derive(TypeHash)]`.
+//! - `serde`: `Serialize` / `Deserialize` for [`Envelope`], [`OracleState`], [`Mask`], and
+//!   [`StructMetadata`], with byte arrays as hex strings in JSON, for dumping and loading
+//!   envelope snapshots. Stays `no_std`.
+//! - `pda`: envelope and config address derivation ([`derive_envelope_address`],
+//!   [`verify_envelope_address`], [`derive_config_address`]), through syscalls on-chain and
+//!   `curve25519` off-chain.
 #![no_std]
 
-use bytemuck::{Pod, Zeroable};
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use bytemuck::{CheckedBitPattern, Pod, Zeroable};
 use solana_address::Address;
 
+mod bitvec;
+#[cfg(feature = "alloc")]
+pub use bitvec::wire_mask_to_bools;
+pub use bitvec::{bools_to_bitvec, bools_to_wire_mask, BitVec256, CompactMask, COMPACT_MASK_SIZE};
+
+mod ext_aux;
+pub use ext_aux::{
+    ext_account_size, ExtAuxHeader, EXT_AUX_DATA_OFFSET, EXT_AUX_HEADER_SIZE, MAX_EXT_AUX_SIZE,
+};
+
+mod le;
+pub use le::{I32Le, I64Le, U32Le, U64Le};
+
+mod price;
+pub use price::PriceValue;
+
+mod constraints;
+pub use constraints::{
+    Constraint, ConstraintOp, ConstraintRegion, ConstraintTable, CONSTRAINTS_SEED, MAX_CONSTRAINTS,
+    RHS_CONST, RHS_FIELD, RHS_PREVIOUS,
+};
+
+mod publishers;
+pub use publishers::{is_valid_publisher_set, PublisherSet, MAX_PUBLISHERS, PUBLISHERS_SEED};
+
+mod conflation;
+pub use conflation::{ConflationWindow, CONFLATION_OFFSET, CONFLATION_WINDOW_SIZE};
+
+mod twap;
+pub use twap::{TwapAccumulator, TwapObservation, TWAP_ACCUMULATOR_SIZE, TWAP_OFFSET};
+
+mod update_stamp;
+pub use update_stamp::{oracle_capacity, UpdateStamp, UPDATE_STAMP_OFFSET, UPDATE_STAMP_SIZE};
+
+mod readers;
+pub use readers::{is_valid_reader_list, ReaderList, MAX_READERS, READERS_SEED};
+
+mod aggregate;
+pub use aggregate::{median_within, MAX_AGGREGATE_SOURCES};
+
+mod typed;
+pub use typed::{TypedEnvelope, TypedEnvelopeMut};
+
+mod event;
+pub use event::{OracleEvent, ORACLE_EVENT_SIZE};
+
+mod config;
+pub use config::{ProgramConfig, CONFIG_SEED};
+
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+#[cfg(feature = "pda")]
+mod pda;
+#[cfg(feature = "pda")]
+pub use pda::{
+    create_config_address, create_envelope_address, derive_config_address, derive_envelope_address,
+    verify_envelope_address,
+};
+
 /// Byte size of an [`OracleState`] account region.
 pub const ORACLE_ACCOUNT_SIZE: usize = core::mem::size_of::<OracleState>();
 
@@ -24,6 +134,21 @@ pub const ORACLE_ACCOUNT_SIZE: usize = core::mem::size_of::<OracleState>();
 /// The 255-byte cap lets the fast path encode the copy length in a single byte.
 pub const ORACLE_BYTES: usize = 239;
 
+/// Payload size of the fast path's tiny lane. Instruction data of exactly
+/// `[meta:8][seq:8][data:32]` is copied with inline word stores instead of a `sol_memcpy_`
+/// call, so it costs fewer compute units than any other size. Shorter payloads opt in by
+/// zero-padding to this size.
+pub const TINY_PAYLOAD_SIZE: usize = 32;
+
+/// Byte size of an oracle memo: an opaque external reference (e.g. an exchange trade-id hash)
+/// bound to a specific fast-path update. See [`Envelope::oracle_memo`].
+pub const ORACLE_MEMO_SIZE: usize = 32;
+
+/// Offset of the memo trailer within [`OracleState::data`]. The trailer is
+/// `[memo:32][memo_sequence:8]` and fills the last 40 payload bytes, so an update carrying
+/// a memo has at most 199 bytes of typed payload.
+pub const ORACLE_MEMO_OFFSET: usize = ORACLE_BYTES - ORACLE_MEMO_SIZE - 8;
+
 /// Byte size of the auxiliary data region and each [`Mask`].
 pub const AUX_DATA_SIZE: usize = 256;
 
@@ -35,6 +160,51 @@ pub const MAX_AUX_STRUCT_SIZE: usize = 255;
 /// Number of bytes in a [`Mask`]: one control byte per auxiliary data byte.
 pub const MASK_SIZE: usize = 256;
 
+/// Stored mask byte of an auxiliary byte sealed by `SealAuxiliaryRange`.
+///
+/// Only the program writes it, into both of an envelope's masks at once. A sealed byte reads
+/// as [`Permission::Blocked`], keeps its value through every write (forced ones included)
+/// and `ClearDelegation`, and stays sealed when the masks are replaced. It is not a
+/// [`Permission`], so `SetDelegatedProgram` rejects masks that contain it.
+pub const SEALED_MASK_BYTE: u8 = 0x5E;
+
+/// Result of [`StructMetadata::diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataDiff {
+    /// Identical metadata.
+    Same,
+    /// The type sizes differ (layout drift). The hashes almost certainly differ too.
+    Size { from: u8, to: u8 },
+    /// Same size, different hash: a renamed, reordered, or retyped field of equal width.
+    Hash,
+}
+
+/// Why [`Envelope::oracle_checked`] refused a read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadError {
+    /// No oracle type is set, or no value has been written yet (sequence 0).
+    Uninitialized,
+    /// The stored oracle metadata is not `T::METADATA`. See
+    /// [`Envelope::oracle_metadata_diff`].
+    TypeMismatch,
+    /// The oracle is at sequence `have`, more than the allowed lag behind `want`.
+    Stale { have: u64, want: u64 },
+}
+
+impl core::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Uninitialized => write!(f, "oracle is uninitialized"),
+            Self::TypeMismatch => write!(f, "oracle holds a different type"),
+            Self::Stale { have, want } => {
+                write!(f, "oracle is at sequence {have}, expected {want}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ReadError {}
+
 /// Packed type identity for on-chain data. bits\[63:56\] = size (u8), bits\[55:0\] = FNV-1a hash.
 ///
 /// Constructed via [`TypeHash::METADATA`] or [`StructMetadata::new`].
@@ -66,7 +236,7 @@ impl StructMetadata {
     }
 
     /// Extract the type size from bits 63:56.
-    pub fn type_size(&self) -> u8 {
+    pub const fn type_size(&self) -> u8 {
         (self.0 >> 56) as u8
     }
 
@@ -75,6 +245,41 @@ impl StructMetadata {
         self.0 & 0x00FF_FFFF_FFFF_FFFF
     }
 
+    /// Hash bit that is always set by [`StructMetadata::new_xxh64`].
+    ///
+    /// FNV-1a metadata leaves this bit as the hash produced it, so a clear bit proves the
+    /// default scheme, while a set bit only means the xxh64 scheme is possible.
+    pub const XXH64_BIT: u64 = 1 << 55;
+
+    /// Like [`StructMetadata::new`] for the xxh64 scheme: keeps bits 54:0 of `hash` and
+    /// sets [`XXH64_BIT`](Self::XXH64_BIT).
+    pub const fn new_xxh64(type_size: u8, hash: u64) -> Self {
+        Self::new(type_size, (hash & (Self::XXH64_BIT - 1)) | Self::XXH64_BIT)
+    }
+
+    /// Whether [`XXH64_BIT`](Self::XXH64_BIT) is set.
+    pub fn has_xxh64_bit(&self) -> bool {
+        self.0 & Self::XXH64_BIT != 0
+    }
+
+    /// How `other` differs from `self`.
+    ///
+    /// A size change means the layout drifted and stored bytes cannot be reinterpreted; a
+    /// hash-only change at the same size is typically a rename or field reorder that a
+    /// migration can map byte for byte.
+    pub fn diff(&self, other: &StructMetadata) -> MetadataDiff {
+        if self.type_size() != other.type_size() {
+            MetadataDiff::Size {
+                from: self.type_size(),
+                to: other.type_size(),
+            }
+        } else if self.hash_56() != other.hash_56() {
+            MetadataDiff::Hash
+        } else {
+            MetadataDiff::Same
+        }
+    }
+
     /// Convenience alias for `T::METADATA`.
     pub fn of<T: TypeHash>() -> Self {
         T::METADATA
@@ -87,8 +292,29 @@ const _: () = assert!(
 );
 
 const _: () = assert!(
-    core::mem::size_of::<Envelope>() == 1120,
-    "Envelope must be 1120 bytes"
+    core::mem::size_of::<Envelope>() == 1200,
+    "Envelope must be 1200 bytes"
+);
+
+const _: () = assert!(
+    core::mem::offset_of!(Envelope, publisher) == LEGACY_ENVELOPE_SIZE,
+    "fields added since the legacy layout must follow auxiliary_data"
+);
+
+const _: () = assert!(
+    core::mem::offset_of!(Envelope, bump) % 8 == 0
+        && core::mem::offset_of!(Envelope, emit_events) < core::mem::offset_of!(Envelope, bump) + 8,
+    "flags, paused, and emit_events must share the aligned u64 at bump: the fast path tests them together"
+);
+
+const _: () = assert!(
+    core::mem::size_of::<RangeGuard>() == 200,
+    "RangeGuard must be 200 bytes (32 envelope + 8 header + 4 * 40 entries)"
+);
+
+const _: () = assert!(
+    core::mem::size_of::<DelegateSchema>() == 80,
+    "DelegateSchema must be 80 bytes (32 envelope + 32 delegation authority + 8 meta + 8 header)"
 );
 
 /// FNV-1a hash, const-evaluable. Used by [`TypeHash`] derive.
@@ -111,21 +337,150 @@ pub const fn combine_hash(accumulated: u64, field_hash: u64) -> u64 {
     rotated.wrapping_mul(0x517cc1b727220a95)
 }
 
+const XXH_PRIME64_1: u64 = 0x9E3779B185EBCA87;
+const XXH_PRIME64_2: u64 = 0xC2B2AE3D27D4EB4F;
+const XXH_PRIME64_3: u64 = 0x165667B19E3779F9;
+const XXH_PRIME64_4: u64 = 0x85EBCA77C2B2AE63;
+const XXH_PRIME64_5: u64 = 0x27D4EB2F165667C5;
+
+const fn xxh64_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(XXH_PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(XXH_PRIME64_1)
+}
+
+const fn xxh64_merge_round(acc: u64, val: u64) -> u64 {
+    (acc ^ xxh64_round(0, val))
+        .wrapping_mul(XXH_PRIME64_1)
+        .wrapping_add(XXH_PRIME64_4)
+}
+
+const fn xxh64_avalanche(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(XXH_PRIME64_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(XXH_PRIME64_3);
+    hash ^ (hash >> 32)
+}
+
+const fn read_u64_le(bytes: &[u8], at: usize) -> u64 {
+    let mut value = 0u64;
+    let mut i = 0;
+    while i < 8 {
+        value |= (bytes[at + i] as u64) << (8 * i);
+        i += 1;
+    }
+    value
+}
+
+const fn read_u32_le(bytes: &[u8], at: usize) -> u64 {
+    let mut value = 0u64;
+    let mut i = 0;
+    while i < 4 {
+        value |= (bytes[at + i] as u64) << (8 * i);
+        i += 1;
+    }
+    value
+}
+
+/// xxHash64 with seed 0, const-evaluable. Used by `#[type_hash(xxh64)]` derives.
+pub const fn const_xxh64(bytes: &[u8]) -> u64 {
+    let len = bytes.len();
+    let mut i = 0;
+    let mut hash = if len >= 32 {
+        let mut v1 = XXH_PRIME64_1.wrapping_add(XXH_PRIME64_2);
+        let mut v2 = XXH_PRIME64_2;
+        let mut v3 = 0u64;
+        let mut v4 = 0u64.wrapping_sub(XXH_PRIME64_1);
+        while i + 32 <= len {
+            v1 = xxh64_round(v1, read_u64_le(bytes, i));
+            v2 = xxh64_round(v2, read_u64_le(bytes, i + 8));
+            v3 = xxh64_round(v3, read_u64_le(bytes, i + 16));
+            v4 = xxh64_round(v4, read_u64_le(bytes, i + 24));
+            i += 32;
+        }
+        let mut hash = v1
+            .rotate_left(1)
+            .wrapping_add(v2.rotate_left(7))
+            .wrapping_add(v3.rotate_left(12))
+            .wrapping_add(v4.rotate_left(18));
+        hash = xxh64_merge_round(hash, v1);
+        hash = xxh64_merge_round(hash, v2);
+        hash = xxh64_merge_round(hash, v3);
+        xxh64_merge_round(hash, v4)
+    } else {
+        XXH_PRIME64_5
+    };
+    hash = hash.wrapping_add(len as u64);
+    while i + 8 <= len {
+        hash ^= xxh64_round(0, read_u64_le(bytes, i));
+        hash = hash
+            .rotate_left(27)
+            .wrapping_mul(XXH_PRIME64_1)
+            .wrapping_add(XXH_PRIME64_4);
+        i += 8;
+    }
+    if i + 4 <= len {
+        hash ^= read_u32_le(bytes, i).wrapping_mul(XXH_PRIME64_1);
+        hash = hash
+            .rotate_left(23)
+            .wrapping_mul(XXH_PRIME64_2)
+            .wrapping_add(XXH_PRIME64_3);
+        i += 4;
+    }
+    while i < len {
+        hash ^= (bytes[i] as u64).wrapping_mul(XXH_PRIME64_5);
+        hash = hash.rotate_left(11).wrapping_mul(XXH_PRIME64_1);
+        i += 1;
+    }
+    xxh64_avalanche(hash)
+}
+
+/// Combine two hashes with an xxh64 merge round and full avalanche. Used by
+/// `#[type_hash(xxh64)]` derives in place of [`combine_hash`].
+pub const fn combine_hash_xxh64(accumulated: u64, field_hash: u64) -> u64 {
+    xxh64_avalanche(
+        (accumulated ^ xxh64_round(0, field_hash))
+            .rotate_left(27)
+            .wrapping_mul(XXH_PRIME64_1)
+            .wrapping_add(XXH_PRIME64_4),
+    )
+}
+
 /// Const-evaluable type identity for envelope oracle/auxiliary data.
 ///
 /// Hash is computed over the struct name and ordered field type hashes (for derived structs),
 /// so structs with the same fields but different names produce different hashes.
-/// Primitives and `[T; N]` arrays have built-in impls.
+/// Derived enums hash their name, `repr`, and each variant's name, discriminant, and payload
+/// types. Primitives and `[T; N]` arrays have built-in impls.
 /// Derive with `#[derive(TypeHash)]` (requires `derive` feature).
 ///
+/// # Borrowing
+///
+/// `TypeHash` only names a layout. The zero-copy accessors ([`Envelope::oracle`],
+/// [`Envelope::aux`] and their `_mut` variants) also require `Pod`. Types with invalid bit
+/// patterns, such as enums used as tagged unions, implement `bytemuck::CheckedBitPattern`
+/// instead and are borrowed through [`Envelope::oracle_validated`] and
+/// [`Envelope::aux_validated`], which reject bytes that are not a valid value.
+///
+/// # Hash schemes
+///
+/// The default scheme is [`const_fnv1a`] folded with [`combine_hash`]. Adding
+/// `#[type_hash(xxh64)]` to a derive switches that struct to [`const_xxh64`] and
+/// [`combine_hash_xxh64`], which avalanche fully at every step, and packs the result with
+/// [`StructMetadata::new_xxh64`]. Field hashes are taken as-is in either scheme, so an xxh64
+/// struct may contain default-scheme fields. Changing a struct's scheme changes its
+/// metadata just like renaming it does.
+///
 /// # Hash mismatch
 ///
 /// The on-chain metadata is written once when the oracle or auxiliary slot is initialized.
 /// If you request a type `T` whose `METADATA` differs from what was stored, [`Envelope::oracle`]
 /// and [`Envelope::aux`] return `None`. There is no runtime panic; callers must handle the
 /// `None` case.
-pub trait TypeHash: Pod + Zeroable {
-    /// FNV-1a hash of the type name, combined with ordered field hashes for structs.
+pub trait TypeHash: Copy + 'static {
+    /// FNV-1a hash of the type name, combined with ordered field hashes for structs and
+    /// variant hashes for enums.
     /// Feeds into [`METADATA`](TypeHash::METADATA).
     const TYPE_HASH: u64;
     /// Packed `(size, TYPE_HASH)` stored on-chain in `oracle_metadata` / `auxiliary_metadata`.
@@ -170,6 +525,95 @@ pub const ENVELOPE_SEED: &[u8] = b"envelope";
 /// leaving 13 for caller use.
 pub const MAX_CUSTOM_SEEDS: usize = 13;
 
+/// Maximum number of seeds naming a PDA delegate in `SetDelegatedPda`, excluding its bump.
+pub const MAX_DELEGATE_SEEDS: usize = 15;
+
+/// PDA seed discriminator for [`RangeGuard`] accounts: `[RANGE_GUARD_SEED, envelope_address, bump]`.
+pub const RANGE_GUARD_SEED: &[u8] = b"range_guard";
+
+/// Maximum number of entries in a [`RangeGuard`].
+pub const MAX_RANGE_GUARDS: usize = 4;
+
+/// [`Envelope::flags`] bit: delegated auxiliary writes are subject to a [`RangeGuard`].
+pub const FLAG_RANGE_GUARD: u8 = 1 << 0;
+
+/// [`Envelope::flags`] bit: the envelope is permanent. Close is rejected and the flag cannot
+/// be cleared, so the envelope address stays valid for as long as the program exists.
+pub const FLAG_PERMANENT: u8 = 1 << 1;
+
+/// [`Envelope::flags`] bit: oracle, auxiliary, and heartbeat writes are rejected if the
+/// envelope was already written in the same transaction, whether by another top-level
+/// instruction or by an earlier CPI from the same one (see [`Envelope::mutation_marker`]).
+/// Cleared by `SetMutationGuard { allow_multi: true }`.
+pub const FLAG_MUTATION_GUARD: u8 = 1 << 2;
+
+/// [`Envelope::flags`] bit: oracle writes fold their value into the [`TwapAccumulator`] at
+/// the end of the oracle payload. Set and cleared by `SetTwap`.
+pub const FLAG_TWAP: u8 = 1 << 3;
+
+/// [`Envelope::flags`] bit: oracle writes record their slot and unix timestamp in the
+/// [`UpdateStamp`] at the end of the oracle payload. Set and cleared by `SetUpdateStamp`.
+pub const FLAG_UPDATE_STAMP: u8 = 1 << 4;
+
+/// [`Envelope::flags`] bit: writes to the regions in [`Envelope::constrained_regions`] are
+/// checked against the envelope's [`ConstraintTable`]. Set and cleared by `SetConstraints`.
+pub const FLAG_CONSTRAINTS: u8 = 1 << 5;
+
+/// [`Envelope::flags`] bit: oracle writes fold their value into the [`ConflationWindow`]
+/// before the TWAP accumulator, tracking the extremes within a slot. Set and cleared by
+/// `SetConflation`.
+pub const FLAG_CONFLATION: u8 = 1 << 6;
+
+/// [`Envelope::flags`] bit: the program returns the oracle only to the programs in the
+/// envelope's [`ReaderList`], through `ReadOracleGated`. Set and cleared by `SetReaders`.
+pub const FLAG_GATED_READS: u8 = 1 << 7;
+
+/// Granularity of [`Envelope::aux_dirty_blocks`]: one bit per 32-byte block of
+/// `auxiliary_data`.
+pub const AUX_BLOCK_SIZE: usize = 32;
+
+/// Number of blocks in `auxiliary_data` (bits used in [`Envelope::aux_dirty_blocks`]).
+pub const AUX_BLOCKS: usize = AUX_DATA_SIZE / AUX_BLOCK_SIZE;
+
+/// Bitmap of the [`AUX_BLOCK_SIZE`] blocks overlapping `[offset, offset + len)`. Bit `i`
+/// covers `auxiliary_data[32 * i..32 * (i + 1)]`. Bytes past [`AUX_DATA_SIZE`] are ignored.
+pub const fn aux_blocks_touched(offset: usize, len: usize) -> u8 {
+    let end = offset.saturating_add(len);
+    let end = if end > AUX_DATA_SIZE {
+        AUX_DATA_SIZE
+    } else {
+        end
+    };
+    if offset >= end {
+        return 0;
+    }
+    let first = offset / AUX_BLOCK_SIZE;
+    let last = (end - 1) / AUX_BLOCK_SIZE;
+    let upper = if last + 1 >= 8 {
+        0xFF
+    } else {
+        (1u8 << (last + 1)) - 1
+    };
+    upper & !((1u8 << first) - 1)
+}
+
+/// PDA seed discriminator for [`GroupAnchor`] accounts:
+/// `[GROUP_ANCHOR_SEED, authority, group_id (u64 LE), bump]`.
+pub const GROUP_ANCHOR_SEED: &[u8] = b"group_anchor";
+
+/// Maximum number of envelopes in a [`GroupAnchor`].
+pub const MAX_GROUP_MEMBERS: usize = 8;
+
+/// PDA seed discriminator for [`EnvelopeLabel`] accounts: `[LABEL_SEED, envelope_address, bump]`.
+pub const LABEL_SEED: &[u8] = b"label";
+
+/// Byte length of an envelope label (symbol or name, UTF-8, zero-padded).
+pub const LABEL_SIZE: usize = 64;
+
+/// PDA seed discriminator for [`DelegateSchema`] accounts:
+/// `[DELEGATE_SCHEMA_SEED, envelope_address, bump]`.
+pub const DELEGATE_SCHEMA_SEED: &[u8] = b"delegate_schema";
+
 /// Oracle data region (256 bytes). Layout: `[meta:8][seq:8][data:239][pad:1]`.
 ///
 /// Fast path copies the first 255 bytes (meta+seq+data) directly from instruction data.
@@ -188,13 +632,26 @@ pub struct OracleState {
     pub _pad: [u8; 1],
 }
 
-/// On-chain envelope account (1120 bytes). Contains oracle, delegation, bitmasks, and aux data.
+/// Data length of envelopes created before `publisher`, `heartbeat_slot`, `write_hook`, and
+/// `mutation_marker` were appended: the layout up to and including `auxiliary_data`. Such
+/// accounts are shorter than [`Envelope::SIZE`], so every instruction rejects them until
+/// `MigrateEnvelope` grows them.
+pub const LEGACY_ENVELOPE_SIZE: usize = 1120;
+
+/// On-chain envelope account (1200 bytes). Contains oracle, delegation, bitmasks, and aux data.
 ///
 /// Field layout (byte offsets):
 /// - `[0..32]`     authority
 /// - `[32..288]`   oracle_state (256 bytes)
 /// - `[288]`       bump
-/// - `[289..296]`  padding
+/// - `[289]`       flags (see [`FLAG_RANGE_GUARD`], [`FLAG_PERMANENT`], [`FLAG_TWAP`],
+///   [`FLAG_UPDATE_STAMP`], [`FLAG_CONSTRAINTS`], [`FLAG_CONFLATION`], [`FLAG_GATED_READS`])
+/// - `[290..292]`  aux_write_count
+/// - `[292]`       aux_dirty_blocks
+/// - `[293]`       constrained_regions (see [`ConstraintRegion::bit`])
+/// - `[294]`       paused (non-zero = oracle and aux writes rejected; see `SetPaused`)
+/// - `[295]`       emit_events (non-zero = fast-path writes log an [`OracleEvent`]; set at
+///   creation by `CreateWithEvents`)
 /// - `[296..328]`  delegation_authority (zeroed = no delegation)
 /// - `[328..584]`  program_bitmask
 /// - `[584..840]`  user_bitmask
@@ -202,13 +659,29 @@ pub struct OracleState {
 /// - `[848..856]`  program_aux_sequence
 /// - `[856..864]`  auxiliary_metadata
 /// - `[864..1120]` auxiliary_data
+/// - `[1120..1152]` publisher: a key that may write the oracle in place of `authority` but
+///   cannot administer the envelope (zeroed = none; see `SetPublisher`)
+/// - `[1152..1160]` heartbeat_slot: slot of the latest `Heartbeat` (zero = none; see
+///   [`Envelope::last_live_slot`])
+/// - `[1160..1192]` write_hook: program the delegation asked to be called after every
+///   authority aux write (zeroed = none; see `SetWriteHook`)
+/// - `[1192..1200]` mutation_marker: fingerprint of the transaction instruction that last
+///   wrote the envelope while [`FLAG_MUTATION_GUARD`] was set (zero = none)
+///
+/// A resized envelope continues with an extended auxiliary region; see
+/// [`Envelope::ext_aux`].
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
 pub struct Envelope {
     pub authority: Address,                  // 32  [0..32]
     pub oracle_state: OracleState,           // 256 [32..288]
     pub bump: u8,                            // 1   [288]
-    pub _padding: [u8; 7],                   // 7   [289..296]
+    pub flags: u8,                           // 1   [289]
+    pub aux_write_count: u16,                // 2   [290..292]
+    pub aux_dirty_blocks: u8,                // 1   [292]
+    pub constrained_regions: u8,             // 1   [293]
+    pub paused: u8,                          // 1   [294]
+    pub emit_events: u8,                     // 1   [295]
     pub delegation_authority: Address,       // 32  [296..328]
     pub program_bitmask: Mask,               // 256 [328..584]
     pub user_bitmask: Mask,                  // 256 [584..840]
@@ -216,24 +689,106 @@ pub struct Envelope {
     pub program_aux_sequence: u64,           // 8   [848..856]
     pub auxiliary_metadata: StructMetadata,  // 8   [856..864]
     pub auxiliary_data: [u8; AUX_DATA_SIZE], // 256 [864..1120]
+    pub publisher: Address,                  // 32  [1120..1152]
+    pub heartbeat_slot: u64,                 // 8   [1152..1160]
+    pub write_hook: Address,                 // 32  [1160..1192]
+    pub mutation_marker: u64,                // 8   [1192..1200]
 }
 
 impl Envelope {
     /// Total byte size of an envelope account.
     pub const SIZE: usize = core::mem::size_of::<Self>();
 
+    /// Interpret the first [`Self::SIZE`] bytes of `data` as an envelope.
+    ///
+    /// Trailing bytes are ignored, so accounts grown past `SIZE` (by realloc or external
+    /// tooling) still parse. Returns `None` if `data` is shorter than `SIZE` or not
+    /// 8-byte aligned.
+    #[inline]
+    pub fn from_prefix_bytes(data: &[u8]) -> Option<&Self> {
+        bytemuck::try_from_bytes(data.get(..Self::SIZE)?).ok()
+    }
+
+    /// Mutable variant of [`from_prefix_bytes`][Self::from_prefix_bytes]. Trailing bytes
+    /// are neither read nor written.
+    #[inline]
+    pub fn from_prefix_bytes_mut(data: &mut [u8]) -> Option<&mut Self> {
+        bytemuck::try_from_bytes_mut(data.get_mut(..Self::SIZE)?).ok()
+    }
+
     /// Returns `true` if `delegation_authority` is non-zero (a delegated program is configured).
     #[inline]
     pub fn has_delegation(&self) -> bool {
         self.delegation_authority != Address::zeroed()
     }
 
+    /// The program called after every authority aux write, if the delegation opted in with
+    /// `SetWriteHook`.
+    #[inline]
+    pub fn write_hook(&self) -> Option<&Address> {
+        (self.write_hook != Address::zeroed()).then_some(&self.write_hook)
+    }
+
+    /// Returns `true` if `publisher` is non-zero and equal to `key`: `key` may write the
+    /// oracle, though it is not the authority.
+    #[inline]
+    pub fn is_publisher(&self, key: &Address) -> bool {
+        self.publisher != Address::zeroed() && &self.publisher == key
+    }
+
+    /// Returns `true` if [`FLAG_RANGE_GUARD`] is set: delegated auxiliary writes must be
+    /// checked against the envelope's [`RangeGuard`] account.
+    #[inline]
+    pub fn has_range_guard(&self) -> bool {
+        self.flags & FLAG_RANGE_GUARD != 0
+    }
+
+    /// Record an auxiliary write that touched `blocks` (see [`aux_blocks_touched`]).
+    /// Replaces [`aux_dirty_blocks`](Self::aux_dirty_blocks) and bumps
+    /// [`aux_write_count`](Self::aux_write_count), wrapping at `u16::MAX`.
+    ///
+    /// A reader that cached the envelope at write count `n` and now sees `n + 1` only needs
+    /// to re-fetch the dirty blocks. Any larger gap means several writes happened and the
+    /// bitmap describes only the last one.
+    #[inline]
+    pub fn record_aux_write(&mut self, blocks: u8) {
+        self.aux_dirty_blocks = blocks;
+        self.aux_write_count = self.aux_write_count.wrapping_add(1);
+    }
+
+    /// Returns `true` if `paused` is non-zero: the program rejects every oracle and aux
+    /// write until the authority resumes the envelope.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused != 0
+    }
+
+    /// Returns `true` if `emit_events` is non-zero: every fast-path write logs an
+    /// [`OracleEvent`].
+    #[inline]
+    pub fn emits_events(&self) -> bool {
+        self.emit_events != 0
+    }
+
+    /// Returns `true` if [`FLAG_PERMANENT`] is set: the envelope can never be closed.
+    #[inline]
+    pub fn is_permanent(&self) -> bool {
+        self.flags & FLAG_PERMANENT != 0
+    }
+
+    /// Returns `true` if [`FLAG_MUTATION_GUARD`] is set: oracle, auxiliary, and heartbeat
+    /// writes must be the only write to the envelope in their transaction.
+    #[inline]
+    pub fn has_mutation_guard(&self) -> bool {
+        self.flags & FLAG_MUTATION_GUARD != 0
+    }
+
     /// Borrow the oracle region as `T`.
     ///
     /// Returns `None` if:
     /// - `size_of::<T>() > ORACLE_BYTES` (type too large for the oracle region), or
     /// - `oracle_metadata != T::METADATA` (stored type hash does not match `T`).
-    pub fn oracle<T: TypeHash>(&self) -> Option<&T> {
+    pub fn oracle<T: TypeHash + Pod>(&self) -> Option<&T> {
         let size = core::mem::size_of::<T>();
         if size > ORACLE_BYTES {
             return None;
@@ -244,10 +799,71 @@ impl Envelope {
         bytemuck::try_from_bytes(&self.oracle_state.data[..size]).ok()
     }
 
+    /// The memo attached to the latest fast-path update, if any.
+    ///
+    /// Returns `None` if no update has been written yet, if the stored oracle type overlaps
+    /// the memo trailer (`type_size > ORACLE_MEMO_OFFSET`), or if the trailer's
+    /// `memo_sequence` does not equal `oracle_state.sequence`, i.e. the latest update did not
+    /// carry a memo. The fast path copies the trailer verbatim, so a memo is never reported
+    /// against a later update. Always `None` for TWAP, stamped, and conflating envelopes,
+    /// whose trailers occupy the memo's bytes.
+    pub fn oracle_memo(&self) -> Option<&[u8; ORACLE_MEMO_SIZE]> {
+        if self.oracle_state.sequence == 0
+            || self.has_twap()
+            || self.has_update_stamp()
+            || self.has_conflation()
+        {
+            return None;
+        }
+        if self.oracle_state.oracle_metadata.type_size() as usize > ORACLE_MEMO_OFFSET {
+            return None;
+        }
+        let trailer = &self.oracle_state.data[ORACLE_MEMO_OFFSET..];
+        let (memo, memo_sequence) = trailer.split_at(ORACLE_MEMO_SIZE);
+        if u64::from_le_bytes(memo_sequence.try_into().unwrap()) != self.oracle_state.sequence {
+            return None;
+        }
+        memo.try_into().ok()
+    }
+
+    /// How the stored oracle metadata differs from `T::METADATA`, to tell a layout change
+    /// from a rename after [`oracle`](Envelope::oracle) returns `None`.
+    pub fn oracle_metadata_diff<T: TypeHash>(&self) -> MetadataDiff {
+        self.oracle_state.oracle_metadata.diff(&T::METADATA)
+    }
+
+    /// Borrow the oracle region as `T`, failing with a [`ReadError`] that says why instead
+    /// of `None`.
+    ///
+    /// Fails with:
+    /// - [`ReadError::Uninitialized`] if the oracle metadata is [`StructMetadata::ZERO`] or
+    ///   nothing has been written (`sequence == 0`),
+    /// - [`ReadError::TypeMismatch`] if [`oracle`](Envelope::oracle) would return `None`,
+    /// - [`ReadError::Stale`] if the oracle sequence trails `current_expected_seq` by more
+    ///   than `max_sequence_lag`. A sequence ahead of `current_expected_seq` is not stale.
+    pub fn oracle_checked<T: TypeHash + Pod>(
+        &self,
+        max_sequence_lag: u64,
+        current_expected_seq: u64,
+    ) -> Result<&T, ReadError> {
+        let have = self.oracle_state.sequence;
+        if self.oracle_state.oracle_metadata == StructMetadata::ZERO || have == 0 {
+            return Err(ReadError::Uninitialized);
+        }
+        let value = self.oracle().ok_or(ReadError::TypeMismatch)?;
+        if current_expected_seq.saturating_sub(have) > max_sequence_lag {
+            return Err(ReadError::Stale {
+                have,
+                want: current_expected_seq,
+            });
+        }
+        Ok(value)
+    }
+
     /// Mutably borrow the oracle region as `T`.
     ///
     /// Returns `None` under the same conditions as [`oracle`](Envelope::oracle).
-    pub fn oracle_mut<T: TypeHash>(&mut self) -> Option<&mut T> {
+    pub fn oracle_mut<T: TypeHash + Pod>(&mut self) -> Option<&mut T> {
         let size = core::mem::size_of::<T>();
         if size > ORACLE_BYTES {
             return None;
@@ -263,7 +879,7 @@ impl Envelope {
     /// Returns `None` if:
     /// - `size_of::<T>() > AUX_DATA_SIZE` (type too large for the auxiliary region), or
     /// - `auxiliary_metadata != T::METADATA` (stored type hash does not match `T`).
-    pub fn aux<T: TypeHash>(&self) -> Option<&T> {
+    pub fn aux<T: TypeHash + Pod>(&self) -> Option<&T> {
         let size = core::mem::size_of::<T>();
         if size > AUX_DATA_SIZE {
             return None;
@@ -274,10 +890,45 @@ impl Envelope {
         bytemuck::try_from_bytes(&self.auxiliary_data[..size]).ok()
     }
 
+    /// Borrow the oracle region as a `T` that not every bit pattern is valid for, such as a
+    /// tagged-union enum.
+    ///
+    /// Returns `None` under the same conditions as [`oracle`](Envelope::oracle), or if the
+    /// bytes are not a valid `T` (for example an unknown discriminant).
+    pub fn oracle_validated<T: TypeHash + CheckedBitPattern>(&self) -> Option<&T> {
+        let size = core::mem::size_of::<T>();
+        if size > ORACLE_BYTES {
+            return None;
+        }
+        if self.oracle_state.oracle_metadata != T::METADATA {
+            return None;
+        }
+        bytemuck::checked::try_from_bytes(&self.oracle_state.data[..size]).ok()
+    }
+
+    /// Borrow the auxiliary data region as a `T` with invalid bit patterns. See
+    /// [`oracle_validated`](Envelope::oracle_validated).
+    pub fn aux_validated<T: TypeHash + CheckedBitPattern>(&self) -> Option<&T> {
+        let size = core::mem::size_of::<T>();
+        if size > AUX_DATA_SIZE {
+            return None;
+        }
+        if self.auxiliary_metadata != T::METADATA {
+            return None;
+        }
+        bytemuck::checked::try_from_bytes(&self.auxiliary_data[..size]).ok()
+    }
+
+    /// How the stored auxiliary metadata differs from `T::METADATA`. See
+    /// [`oracle_metadata_diff`](Envelope::oracle_metadata_diff).
+    pub fn aux_metadata_diff<T: TypeHash>(&self) -> MetadataDiff {
+        self.auxiliary_metadata.diff(&T::METADATA)
+    }
+
     /// Mutably borrow the auxiliary data region as `T`.
     ///
     /// Returns `None` under the same conditions as [`aux`](Envelope::aux).
-    pub fn aux_mut<T: TypeHash>(&mut self) -> Option<&mut T> {
+    pub fn aux_mut<T: TypeHash + Pod>(&mut self) -> Option<&mut T> {
         let size = core::mem::size_of::<T>();
         if size > AUX_DATA_SIZE {
             return None;
@@ -287,12 +938,240 @@ impl Envelope {
         }
         bytemuck::try_from_bytes_mut(&mut self.auxiliary_data[..size]).ok()
     }
+
+    /// Borrow the delegate's sub-struct `T` at `schema.offset` in the auxiliary region.
+    ///
+    /// Returns `None` if:
+    /// - `schema` was registered under a different delegation
+    ///   (`schema.delegation_authority != delegation_authority`, including no delegation),
+    /// - `schema.metadata != T::METADATA`,
+    /// - the sub-struct extends past [`AUX_DATA_SIZE`], or
+    /// - `offset` is not aligned for `T`.
+    ///
+    /// The caller is responsible for pairing `schema` with this envelope
+    /// (`schema.envelope` is the envelope's address, which the envelope does not store).
+    pub fn delegate_aux<T: TypeHash + Pod>(&self, schema: &DelegateSchema) -> Option<&T> {
+        if !self.has_delegation() || schema.delegation_authority != self.delegation_authority {
+            return None;
+        }
+        if schema.metadata != T::METADATA {
+            return None;
+        }
+        let span = schema.span()?;
+        if span.len() != core::mem::size_of::<T>() {
+            return None;
+        }
+        bytemuck::try_from_bytes(&self.auxiliary_data[span]).ok()
+    }
+}
+
+/// One guarded auxiliary byte range.
+///
+/// Delegated writes that change any byte in `[offset, offset + len)` succeed only if the
+/// transaction also contains a top-level instruction whose program id is `program_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
+#[repr(C)]
+pub struct RangeGuardEntry {
+    pub program_id: Address,
+    pub offset: u16,
+    pub len: u16,
+    pub _padding: [u8; 4],
+}
+
+impl RangeGuardEntry {
+    /// Returns `true` if any byte in the guarded range differs between `before` and `after`.
+    ///
+    /// Ranges extending past [`AUX_DATA_SIZE`] are clamped.
+    #[inline]
+    pub fn is_touched(&self, before: &[u8; AUX_DATA_SIZE], after: &[u8; AUX_DATA_SIZE]) -> bool {
+        let start = (self.offset as usize).min(AUX_DATA_SIZE);
+        let end = (start + self.len as usize).min(AUX_DATA_SIZE);
+        before[start..end] != after[start..end]
+    }
+}
+
+/// Per-envelope range allow-list account (200 bytes), stored at the PDA
+/// `[RANGE_GUARD_SEED, envelope_address, bump]`.
+///
+/// Only the first `count` entries are active. Enforced on delegated auxiliary writes
+/// while the envelope has [`FLAG_RANGE_GUARD`] set.
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct RangeGuard {
+    pub envelope: Address,                            // 32  [0..32]
+    pub count: u8,                                    // 1   [32]
+    pub bump: u8,                                     // 1   [33]
+    pub _padding: [u8; 6],                            // 6   [34..40]
+    pub entries: [RangeGuardEntry; MAX_RANGE_GUARDS], // 160 [40..200]
+}
+
+impl RangeGuard {
+    /// Total byte size of a range guard account.
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// The active entries (`entries[..count]`, clamped to [`MAX_RANGE_GUARDS`]).
+    #[inline]
+    pub fn active(&self) -> &[RangeGuardEntry] {
+        &self.entries[..(self.count as usize).min(MAX_RANGE_GUARDS)]
+    }
+}
+
+/// Delegate-owned sub-schema account (80 bytes), stored at the PDA
+/// `[DELEGATE_SCHEMA_SEED, envelope_address, bump]`.
+///
+/// Describes a sub-struct (`metadata`) the delegated program writes at `offset` within its
+/// program-writable span of the auxiliary region, independent of the authority's
+/// `auxiliary_metadata`. Bound to the delegation that registered it: once
+/// `delegation_authority` no longer matches the envelope, the schema is stale and typed
+/// delegate writes fail.
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct DelegateSchema {
+    pub envelope: Address,             // 32 [0..32]
+    pub delegation_authority: Address, // 32 [32..64]
+    pub metadata: StructMetadata,      // 8  [64..72]
+    pub offset: u8,                    // 1  [72]
+    pub bump: u8,                      // 1  [73]
+    pub _padding: [u8; 6],             // 6  [74..80]
+}
+
+impl DelegateSchema {
+    /// Total byte size of a delegate schema account.
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// Auxiliary byte range covered by the sub-struct, or `None` if it is empty or extends
+    /// past [`AUX_DATA_SIZE`].
+    #[inline]
+    pub fn span(&self) -> Option<core::ops::Range<usize>> {
+        let start = self.offset as usize;
+        let end = start + self.metadata.type_size() as usize;
+        (end > start && end <= AUX_DATA_SIZE).then_some(start..end)
+    }
+}
+
+/// Group anchor account (312 bytes), stored at the PDA
+/// `[GROUP_ANCHOR_SEED, authority, group_id (u64 LE), bump]`.
+///
+/// Lists up to [`MAX_GROUP_MEMBERS`] envelopes owned by `authority`. A group commit writes
+/// every member's oracle payload with the same `sequence` and stores it here, so a consumer
+/// can check that a basket of feeds came from one publisher batch with
+/// [`in_batch`](Self::in_batch). A later fast-path update to one member raises its sequence
+/// past the anchor's and takes it out of the batch.
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct GroupAnchor {
+    pub authority: Address,                    // 32  [0..32]
+    pub group_id: u64,                         // 8   [32..40]
+    pub sequence: u64,                         // 8   [40..48]
+    pub count: u8,                             // 1   [48]
+    pub bump: u8,                              // 1   [49]
+    pub _padding: [u8; 6],                     // 6   [50..56]
+    pub members: [Address; MAX_GROUP_MEMBERS], // 256 [56..312]
+}
+
+impl GroupAnchor {
+    /// Total byte size of a group anchor account.
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// The member envelope addresses (`members[..count]`, clamped to [`MAX_GROUP_MEMBERS`]).
+    #[inline]
+    pub fn active(&self) -> &[Address] {
+        &self.members[..(self.count as usize).min(MAX_GROUP_MEMBERS)]
+    }
+
+    /// Returns `true` if `address` is a member and `envelope` still holds the oracle payload
+    /// from the latest group commit.
+    #[inline]
+    pub fn in_batch(&self, address: &Address, envelope: &Envelope) -> bool {
+        self.active().contains(address)
+            && envelope.authority == self.authority
+            && envelope.oracle_state.sequence == self.sequence
+    }
+}
+
+/// Envelope label account (104 bytes), stored at the PDA `[LABEL_SEED, envelope_address, bump]`.
+///
+/// Holds a human-readable symbol or name for the envelope, so explorers and indexers can
+/// identify a feed without an external registry. Written once by the envelope authority
+/// and never changed.
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct EnvelopeLabel {
+    pub envelope: Address,       // 32 [0..32]
+    pub label: [u8; LABEL_SIZE], // 64 [32..96]
+    pub bump: u8,                // 1  [96]
+    pub _padding: [u8; 7],       // 7  [97..104]
+}
+
+impl EnvelopeLabel {
+    /// Total byte size of a label account.
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// The label text with trailing zero padding removed, or `None` if it is not UTF-8.
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        let len = self
+            .label
+            .iter()
+            .rposition(|&b| b != 0)
+            .map_or(0, |i| i + 1);
+        core::str::from_utf8(&self.label[..len]).ok()
+    }
+}
+
+/// Write permission of a single auxiliary byte, as stored in a [`Mask`].
+///
+/// The discriminants are the wire bytes: writable is `0x00` and blocked is `0xFF`. Code
+/// that goes through [`Mask::get`] and [`Mask::set`] never has to spell out that inverted
+/// polarity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Permission {
+    Writable = 0x00,
+    Blocked = 0xFF,
+}
+
+impl Permission {
+    /// Decode a mask byte. Returns `None` for non-canonical values, which the program
+    /// rejects in `SetDelegatedProgram`.
+    #[inline]
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0x00 => Some(Self::Writable),
+            0xFF => Some(Self::Blocked),
+            _ => None,
+        }
+    }
+
+    /// The mask byte for this permission.
+    #[inline]
+    pub const fn to_byte(self) -> u8 {
+        self as u8
+    }
+
+    #[inline]
+    pub const fn is_writable(self) -> bool {
+        matches!(self, Self::Writable)
+    }
+}
+
+impl From<bool> for Permission {
+    /// `true` is [`Permission::Writable`], matching the `Vec<bool>` masks of `c_u_later`.
+    fn from(writable: bool) -> Self {
+        if writable {
+            Self::Writable
+        } else {
+            Self::Blocked
+        }
+    }
 }
 
 /// Per-byte access control mask for auxiliary data (256 bytes).
 ///
-/// Storage polarity: `0x00` = writable, `0xFF` = blocked. Only canonical values
-/// (`0x00`/`0xFF`) are accepted on-chain.
+/// Each byte holds a [`Permission`]; use [`get`](Self::get) and [`set`](Self::set) rather
+/// than the raw storage (`0x00` = writable, `0xFF` = blocked). Only canonical values are
+/// accepted on-chain. A stored envelope mask may also hold [`SEALED_MASK_BYTE`]s, which
+/// read as blocked.
 ///
 /// - [`Mask::ALL_BLOCKED`] — all blocked (default for new envelopes)
 /// - [`Mask::ALL_WRITABLE`] — all writable
@@ -301,61 +1180,197 @@ impl Envelope {
 pub struct Mask([u8; MASK_SIZE]);
 
 impl Mask {
-    /// All blocked (0xFF). Default for new envelopes.
-    pub const ALL_BLOCKED: Self = Self([0xFF; MASK_SIZE]);
-    /// All writable (0x00).
-    pub const ALL_WRITABLE: Self = Self([0x00; MASK_SIZE]);
+    /// All blocked. Default for new envelopes.
+    pub const ALL_BLOCKED: Self = Self::filled(Permission::Blocked);
+    /// All writable.
+    pub const ALL_WRITABLE: Self = Self::filled(Permission::Writable);
 
-    /// Mark byte at `byte_idx` as writable (0x00).
+    /// A mask with every byte set to `permission`.
     #[inline]
-    pub fn allow(&mut self, byte_idx: usize) {
+    pub const fn filled(permission: Permission) -> Self {
+        Self([permission.to_byte(); MASK_SIZE])
+    }
+
+    /// Permission of byte at `byte_idx`.
+    ///
+    /// Out-of-range indices and non-canonical bytes read as [`Permission::Blocked`], the
+    /// same way the program treats them.
+    #[inline]
+    pub fn get(&self, byte_idx: usize) -> Permission {
+        match self.0.get(byte_idx) {
+            Some(&b) if b == Permission::Writable.to_byte() => Permission::Writable,
+            _ => Permission::Blocked,
+        }
+    }
+
+    /// Set the permission of byte at `byte_idx`. Out-of-range indices are ignored.
+    #[inline]
+    pub fn set(&mut self, byte_idx: usize, permission: Permission) {
         if byte_idx >= MASK_SIZE {
             return;
         }
-        self.0[byte_idx] = 0x00;
+        self.0[byte_idx] = permission.to_byte();
+    }
+
+    /// Mark byte at `byte_idx` as writable.
+    #[inline]
+    pub fn allow(&mut self, byte_idx: usize) {
+        self.set(byte_idx, Permission::Writable);
     }
 
-    /// Mark byte at `byte_idx` as blocked (0xFF).
+    /// Mark byte at `byte_idx` as blocked.
     #[inline]
     pub fn block(&mut self, byte_idx: usize) {
-        if byte_idx >= MASK_SIZE {
-            return;
+        self.set(byte_idx, Permission::Blocked);
+    }
+
+    /// Set the permission of every byte in `[offset, offset + len)`. Bytes past
+    /// [`MASK_SIZE`] are ignored, as in [`set`](Self::set).
+    pub fn set_range(&mut self, offset: usize, len: usize, permission: Permission) {
+        let start = offset.min(MASK_SIZE);
+        let end = offset.saturating_add(len).min(MASK_SIZE);
+        self.0[start..end].fill(permission.to_byte());
+    }
+
+    /// Mark every byte in `[offset, offset + len)` as writable.
+    #[inline]
+    pub fn allow_range(&mut self, offset: usize, len: usize) {
+        self.set_range(offset, len, Permission::Writable);
+    }
+
+    /// Mark every byte in `[offset, offset + len)` as blocked.
+    #[inline]
+    pub fn block_range(&mut self, offset: usize, len: usize) {
+        self.set_range(offset, len, Permission::Blocked);
+    }
+
+    /// A mask with the `(offset, len)` ranges writable and every other byte blocked.
+    pub fn from_ranges(ranges: &[(usize, usize)]) -> Self {
+        let mut mask = Self::ALL_BLOCKED;
+        for &(offset, len) in ranges {
+            mask.allow_range(offset, len);
+        }
+        mask
+    }
+
+    /// Bytes writable in either mask.
+    pub fn union(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a || b)
+    }
+
+    /// Bytes writable in both masks.
+    pub fn intersect(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a && b)
+    }
+
+    /// Writable bytes become blocked and blocked bytes writable.
+    pub fn invert(&self) -> Self {
+        self.combine(self, |a, _| !a)
+    }
+
+    /// Canonical mask whose byte `i` is writable iff `f(self[i], other[i])`, reading each
+    /// side with [`get`](Self::get) so non-canonical bytes count as blocked.
+    fn combine(&self, other: &Self, f: impl Fn(bool, bool) -> bool) -> Self {
+        let mut out = Self::ALL_BLOCKED;
+        for i in 0..MASK_SIZE {
+            out.set(i, f(self.is_writable(i), other.is_writable(i)).into());
         }
-        self.0[byte_idx] = 0xFF;
+        out
     }
 
     /// Returns `true` if byte at `byte_idx` is writable.
     #[inline]
     pub fn is_writable(&self, byte_idx: usize) -> bool {
-        if byte_idx >= MASK_SIZE {
-            return false;
+        self.get(byte_idx).is_writable()
+    }
+
+    /// Returns `true` if byte at `byte_idx` is sealed ([`SEALED_MASK_BYTE`]).
+    #[inline]
+    pub fn is_sealed(&self, byte_idx: usize) -> bool {
+        self.0.get(byte_idx) == Some(&SEALED_MASK_BYTE)
+    }
+
+    /// Returns `true` if any byte is sealed.
+    #[inline]
+    pub fn has_sealed(&self) -> bool {
+        self.0.contains(&SEALED_MASK_BYTE)
+    }
+
+    /// Seal every byte in `[offset, offset + len)`. Bytes past [`MASK_SIZE`] are ignored.
+    /// Sealing cannot be undone through the program.
+    pub fn seal_range(&mut self, offset: usize, len: usize) {
+        let start = offset.min(MASK_SIZE);
+        let end = offset.saturating_add(len).min(MASK_SIZE);
+        self.0[start..end].fill(SEALED_MASK_BYTE);
+    }
+
+    /// This mask with every byte sealed in `previous` sealed as well. The program installs
+    /// new masks this way so that replacing them never unseals a byte.
+    pub fn with_sealed_from(&self, previous: &Self) -> Self {
+        let mut out = *self;
+        for (byte, &prev) in out.0.iter_mut().zip(&previous.0) {
+            if prev == SEALED_MASK_BYTE {
+                *byte = SEALED_MASK_BYTE;
+            }
         }
-        self.0[byte_idx] == 0x00
+        out
+    }
+
+    /// A mask with the bytes sealed here sealed and every other byte writable, for writes
+    /// that bypass the permission masks but must still leave sealed bytes unchanged.
+    pub fn sealed_only(&self) -> Self {
+        Self::ALL_WRITABLE.with_sealed_from(self)
+    }
+
+    /// Permissions of all [`MASK_SIZE`] bytes, in offset order.
+    pub fn permissions(&self) -> impl Iterator<Item = Permission> + '_ {
+        (0..MASK_SIZE).map(|i| self.get(i))
+    }
+
+    /// Bytes whose permission differs from `other`, in offset order, as
+    /// `(byte_idx, writable in self, writable in other)`.
+    ///
+    /// Bytes are read with [`get`](Self::get), so a non-canonical byte counts as blocked.
+    pub fn diff<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = (usize, bool, bool)> + 'a {
+        (0..MASK_SIZE).filter_map(|i| {
+            let (a, b) = (self.is_writable(i), other.is_writable(i));
+            (a != b).then_some((i, a, b))
+        })
+    }
+
+    /// Returns `true` if every byte is a canonical [`Permission`] byte. The program rejects
+    /// non-canonical masks in `SetDelegatedProgram`.
+    #[inline]
+    pub fn is_canonical(&self) -> bool {
+        self.0.iter().all(|&b| Permission::from_byte(b).is_some())
     }
 
-    /// Raw mask bytes for inspection or serialization.
+    /// Raw mask bytes for the wire format. Prefer [`get`](Self::get) elsewhere.
     #[inline]
     pub fn as_bytes(&self) -> &[u8; MASK_SIZE] {
         &self.0
     }
 
-    /// Raw mutable mask bytes. Caller must preserve the canonical polarity invariant:
-    /// every byte must be either `0x00` (writable) or `0xFF` (blocked).
+    /// Raw mutable mask bytes. Caller must preserve the canonical invariant: every byte
+    /// must be a [`Permission`] byte (see [`is_canonical`](Self::is_canonical)).
     #[inline]
     pub fn as_bytes_mut(&mut self) -> &mut [u8; MASK_SIZE] {
         &mut self.0
     }
 
-    /// Returns `true` if all bytes are blocked.
+    /// Returns `true` if all bytes are blocked or sealed.
     #[inline]
     pub fn is_all_blocked(&self) -> bool {
-        self.0 == [0xFF; MASK_SIZE]
+        self.0
+            .iter()
+            .all(|&b| b == Permission::Blocked.to_byte() || b == SEALED_MASK_BYTE)
     }
 
     /// Returns `true` if every byte in `[offset, offset + len)` is writable (`0x00`).
     ///
     /// Returns `true` for `len == 0`. Returns `false` if the range overflows or exceeds
     /// [`AUX_DATA_SIZE`].
+    /// Compares 8-aligned u64 words against 0 in the body; byte-level for head/tail.
     #[inline]
     pub fn is_write_allowed(&self, offset: usize, len: usize) -> bool {
         if len == 0 {
@@ -368,12 +1383,20 @@ impl Mask {
         if end > AUX_DATA_SIZE {
             return false;
         }
-        for byte_idx in offset..end {
-            if !self.is_writable(byte_idx) {
+
+        let writable = Permission::Writable.to_byte();
+        let head_end = ((offset + 7) & !7).min(end);
+        let body_end = (end & !7).max(head_end);
+
+        if self.0[offset..head_end].iter().any(|&b| b != writable) {
+            return false;
+        }
+        for word in self.0[head_end..body_end].chunks_exact(8) {
+            if u64::from_ne_bytes(word.try_into().unwrap()) != 0 {
                 return false;
             }
         }
-        true
+        self.0[body_end..end].iter().all(|&b| b == writable)
     }
 
     /// Validate a masked update without applying it.
@@ -382,7 +1405,8 @@ impl Mask {
     /// blocked byte. Returns `false` if the region exceeds `AUX_DATA_SIZE` or if
     /// any blocked byte differs between `src` and `dest[offset..]`.
     ///
-    /// Storage polarity: 0xFF = blocked, 0x00 = writable.
+    /// Storage polarity: 0xFF = blocked, 0x00 = writable. Any other byte, such as
+    /// [`SEALED_MASK_BYTE`], blocks like 0xFF.
     /// Uses u64-chunked fast path for aligned regions; byte-level for head/tail.
     #[inline]
     pub fn check_masked_update(
@@ -412,7 +1436,7 @@ impl Mask {
         };
         for abs in offset..head_end {
             let si = abs - offset;
-            if src[si] != dest[abs] && self.0[abs] == 0xFF {
+            if src[si] != dest[abs] && self.0[abs] != Permission::Writable.to_byte() {
                 return false;
             }
         }
@@ -426,6 +1450,7 @@ impl Mask {
                 let dest_qw = u64::from_ne_bytes(dest[abs..abs + 8].try_into().unwrap());
                 if src_qw != dest_qw {
                     let mask_qw = u64::from_ne_bytes(self.0[abs..abs + 8].try_into().unwrap());
+                    let mask_qw = spread_nonzero_bytes(mask_qw);
                     if (mask_qw & src_qw) != (mask_qw & dest_qw) {
                         return false;
                     }
@@ -442,7 +1467,7 @@ impl Mask {
         };
         for abs in tail_start..end {
             let si = abs - offset;
-            if src[si] != dest[abs] && self.0[abs] == 0xFF {
+            if src[si] != dest[abs] && self.0[abs] != Permission::Writable.to_byte() {
                 return false;
             }
         }
@@ -474,12 +1499,49 @@ impl Mask {
     }
 }
 
+/// `0xFF` in every byte of `qw` that is non-zero, `0x00` elsewhere.
+#[inline(always)]
+const fn spread_nonzero_bytes(qw: u64) -> u64 {
+    let mut bits = qw | (qw >> 4);
+    bits |= bits >> 2;
+    bits |= bits >> 1;
+    (bits & 0x0101_0101_0101_0101) * 0xFF
+}
+
 impl Default for Mask {
     fn default() -> Self {
         Self::ALL_BLOCKED
     }
 }
 
+impl core::fmt::Display for Mask {
+    /// 16 rows of 16 bytes, each row prefixed with its first offset in hex: `W` for
+    /// writable, `B` for blocked, `S` for sealed, and `?` for any other non-canonical byte.
+    ///
+    /// ```text
+    /// 00 WWWWWWWWBBBBBBBB
+    /// 10 BBBBBBBBBBBBBBBB
+    /// ...
+    /// ```
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (row, bytes) in self.0.chunks_exact(16).enumerate() {
+            if row > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{:02x} ", row * 16)?;
+            for &byte in bytes {
+                f.write_str(match Permission::from_byte(byte) {
+                    Some(Permission::Writable) => "W",
+                    Some(Permission::Blocked) => "B",
+                    None if byte == SEALED_MASK_BYTE => "S",
+                    None => "?",
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl From<[u8; MASK_SIZE]> for Mask {
     fn from(bytes: [u8; MASK_SIZE]) -> Self {
         Self(bytes)
@@ -526,6 +1588,34 @@ mod tests {
         assert_ne!(combine_hash(a, b), combine_hash(b, a));
     }
 
+    #[test]
+    fn test_const_xxh64_reference_vectors() {
+        assert_eq!(const_xxh64(b""), 0xEF46DB3751D8E999);
+        assert_eq!(const_xxh64(b"abc"), 0x44BC2CF5AD770999);
+        assert_eq!(
+            const_xxh64(b"Nobody inspects the spammish repetition"),
+            0xFBCEA83C8A378BF1
+        );
+    }
+
+    #[test]
+    fn test_combine_hash_xxh64_order_sensitive() {
+        let a = const_xxh64(b"alpha");
+        let b = const_xxh64(b"beta");
+        assert_ne!(combine_hash_xxh64(a, b), combine_hash_xxh64(b, a));
+        assert_ne!(combine_hash_xxh64(a, b), combine_hash(a, b));
+    }
+
+    #[test]
+    fn test_struct_metadata_new_xxh64_sets_bit() {
+        let meta = StructMetadata::new_xxh64(8, u64::MAX);
+        assert!(meta.has_xxh64_bit());
+        assert_eq!(meta.type_size(), 8);
+        assert_eq!(meta.hash_56(), 0x00FF_FFFF_FFFF_FFFF);
+        assert!(StructMetadata::new_xxh64(8, 0).has_xxh64_bit());
+        assert!(!StructMetadata::new(8, 0).has_xxh64_bit());
+    }
+
     #[test]
     fn test_array_hashes_distinct_by_element_type() {
         assert_ne!(<[u8; 4]>::TYPE_HASH, <[u32; 1]>::TYPE_HASH);
@@ -555,9 +1645,341 @@ mod tests {
         assert_eq!(StructMetadata::of::<[u8; 4]>(), <[u8; 4]>::METADATA);
     }
 
+    #[test]
+    fn test_struct_metadata_diff() {
+        assert_eq!(u32::METADATA.diff(&u32::METADATA), MetadataDiff::Same);
+        assert_eq!(
+            u32::METADATA.diff(&u64::METADATA),
+            MetadataDiff::Size { from: 4, to: 8 }
+        );
+        assert_eq!(u32::METADATA.diff(&<[u8; 4]>::METADATA), MetadataDiff::Hash);
+        assert_eq!(
+            StructMetadata::ZERO.diff(&u32::METADATA),
+            MetadataDiff::Size { from: 0, to: 4 }
+        );
+    }
+
     #[test]
     fn test_envelope_size() {
-        assert_eq!(core::mem::size_of::<Envelope>(), 1120);
+        assert_eq!(core::mem::size_of::<Envelope>(), 1200);
+    }
+
+    #[test]
+    fn test_envelope_from_prefix_bytes_ignores_trailing() {
+        let mut words = [0u64; Envelope::SIZE / 8 + 4];
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut words);
+        bytes[Envelope::SIZE..].fill(0xAB);
+
+        let envelope = Envelope::from_prefix_bytes_mut(bytes).unwrap();
+        envelope.bump = 7;
+        envelope.mutation_marker = 0x4200_0000_0000_0000;
+
+        assert_eq!(bytes[288], 7);
+        assert_eq!(bytes[Envelope::SIZE - 1], 0x42);
+        assert!(bytes[Envelope::SIZE..].iter().all(|&b| b == 0xAB));
+        assert_eq!(Envelope::from_prefix_bytes(bytes).unwrap().bump, 7);
+    }
+
+    #[test]
+    fn test_envelope_is_publisher() {
+        let mut envelope = Envelope::zeroed();
+        let key = Address::new_from_array([7; 32]);
+        assert!(!envelope.is_publisher(&key));
+        assert!(!envelope.is_publisher(&Address::zeroed()));
+
+        envelope.publisher = key;
+        assert!(envelope.is_publisher(&key));
+        assert!(!envelope.is_publisher(&Address::new_from_array([8; 32])));
+    }
+
+    #[test]
+    fn test_envelope_write_hook() {
+        let mut envelope = Envelope::zeroed();
+        assert_eq!(envelope.write_hook(), None);
+
+        let hook = Address::new_from_array([9; 32]);
+        envelope.write_hook = hook;
+        assert_eq!(envelope.write_hook(), Some(&hook));
+    }
+
+    #[test]
+    fn test_envelope_is_paused() {
+        let mut envelope = Envelope::zeroed();
+        assert!(!envelope.is_paused());
+        envelope.paused = 1;
+        assert!(envelope.is_paused());
+        assert_eq!(core::mem::offset_of!(Envelope, paused), 294);
+    }
+
+    #[test]
+    fn test_oracle_event_from_log_data() {
+        let event = OracleEvent {
+            envelope: Address::new_from_array([3; 32]),
+            sequence: 9,
+            oracle_metadata: u64::METADATA,
+            payload_hash: [4; 32],
+        };
+        let mut data = [0u8; ORACLE_EVENT_SIZE + 1];
+        data[1..].copy_from_slice(bytemuck::bytes_of(&event));
+        assert_eq!(OracleEvent::from_log_data(&data[1..]), Some(event));
+        assert_eq!(OracleEvent::from_log_data(&data), None);
+        assert_eq!(OracleEvent::from_log_data(&data[2..]), None);
+    }
+
+    #[test]
+    fn test_envelope_from_prefix_bytes_rejects_short() {
+        let words = [0u64; Envelope::SIZE / 8];
+        let bytes: &[u8] = bytemuck::cast_slice(&words);
+        assert!(Envelope::from_prefix_bytes(bytes).is_some());
+        assert!(Envelope::from_prefix_bytes(&bytes[..Envelope::SIZE - 1]).is_none());
+        assert!(Envelope::from_prefix_bytes(&[]).is_none());
+    }
+
+    #[test]
+    fn test_range_guard_size() {
+        assert_eq!(RangeGuard::SIZE, 200);
+        assert_eq!(core::mem::size_of::<RangeGuardEntry>(), 40);
+    }
+
+    #[test]
+    fn test_range_guard_entry_is_touched() {
+        let entry = RangeGuardEntry {
+            program_id: Address::zeroed(),
+            offset: 8,
+            len: 4,
+            _padding: [0; 4],
+        };
+        let before = [0u8; AUX_DATA_SIZE];
+        let mut after = before;
+        after[7] = 1;
+        after[12] = 1;
+        assert!(!entry.is_touched(&before, &after), "edges outside range");
+        after[11] = 1;
+        assert!(entry.is_touched(&before, &after));
+    }
+
+    #[test]
+    fn test_range_guard_entry_clamps_past_end() {
+        let entry = RangeGuardEntry {
+            program_id: Address::zeroed(),
+            offset: 250,
+            len: u16::MAX,
+            _padding: [0; 4],
+        };
+        let before = [0u8; AUX_DATA_SIZE];
+        let mut after = before;
+        after[255] = 1;
+        assert!(entry.is_touched(&before, &after));
+    }
+
+    #[test]
+    fn test_range_guard_active_clamps_count() {
+        let mut guard = RangeGuard::zeroed();
+        assert!(guard.active().is_empty());
+        guard.count = 2;
+        assert_eq!(guard.active().len(), 2);
+        guard.count = 200;
+        assert_eq!(guard.active().len(), MAX_RANGE_GUARDS);
+    }
+
+    #[test]
+    fn test_group_anchor_size() {
+        assert_eq!(GroupAnchor::SIZE, 312);
+    }
+
+    #[test]
+    fn test_group_anchor_in_batch() {
+        let authority = Address::new_from_array([1; 32]);
+        let member = Address::new_from_array([2; 32]);
+        let mut anchor = GroupAnchor::zeroed();
+        anchor.authority = authority;
+        anchor.sequence = 7;
+        anchor.members[0] = member;
+
+        let mut envelope = Envelope::zeroed();
+        envelope.authority = authority;
+        envelope.oracle_state.sequence = 7;
+        assert!(!anchor.in_batch(&member, &envelope), "count is zero");
+
+        anchor.count = 1;
+        assert!(anchor.in_batch(&member, &envelope));
+        assert!(!anchor.in_batch(&authority, &envelope), "not a member");
+
+        envelope.oracle_state.sequence = 8;
+        assert!(
+            !anchor.in_batch(&member, &envelope),
+            "updated after the commit"
+        );
+
+        envelope.oracle_state.sequence = 7;
+        envelope.authority = member;
+        assert!(!anchor.in_batch(&member, &envelope), "different authority");
+    }
+
+    #[test]
+    fn test_envelope_label_size() {
+        assert_eq!(EnvelopeLabel::SIZE, 104);
+    }
+
+    #[test]
+    fn test_envelope_label_as_str() {
+        let mut label = EnvelopeLabel::zeroed();
+        assert_eq!(label.as_str(), Some(""));
+
+        label.label[..7].copy_from_slice(b"SOL/USD");
+        assert_eq!(label.as_str(), Some("SOL/USD"));
+
+        label.label[LABEL_SIZE - 1] = 0xFF;
+        assert_eq!(label.as_str(), None);
+    }
+
+    #[test]
+    fn test_delegate_schema_size() {
+        assert_eq!(DelegateSchema::SIZE, 80);
+    }
+
+    #[test]
+    fn test_delegate_schema_span() {
+        let mut schema = DelegateSchema::zeroed();
+        assert_eq!(schema.span(), None);
+
+        schema.metadata = StructMetadata::new(8, 1);
+        schema.offset = 16;
+        assert_eq!(schema.span(), Some(16..24));
+
+        schema.offset = 249;
+        assert_eq!(schema.span(), None);
+    }
+
+    #[test]
+    fn test_envelope_delegate_aux() {
+        let delegate = Address::new_from_array([7; 32]);
+        let mut env = Envelope::zeroed();
+        env.delegation_authority = delegate;
+        env.auxiliary_data[16..24].copy_from_slice(&0xABCDu64.to_le_bytes());
+
+        let mut schema = DelegateSchema::zeroed();
+        schema.delegation_authority = delegate;
+        schema.metadata = u64::METADATA;
+        schema.offset = 16;
+        assert_eq!(env.delegate_aux::<u64>(&schema), Some(&0xABCD));
+        assert_eq!(env.delegate_aux::<u32>(&schema), None);
+
+        schema.offset = 12;
+        assert_eq!(env.delegate_aux::<u64>(&schema), None, "misaligned");
+
+        schema.offset = 16;
+        env.delegation_authority = Address::new_from_array([8; 32]);
+        assert_eq!(env.delegate_aux::<u64>(&schema), None, "stale delegation");
+    }
+
+    #[test]
+    fn test_envelope_has_range_guard() {
+        let mut env = Envelope::zeroed();
+        assert!(!env.has_range_guard());
+        env.flags |= FLAG_RANGE_GUARD;
+        assert!(env.has_range_guard());
+    }
+
+    #[test]
+    fn test_envelope_has_mutation_guard() {
+        let mut env = Envelope::zeroed();
+        env.flags = FLAG_RANGE_GUARD | FLAG_PERMANENT;
+        assert!(!env.has_mutation_guard());
+        env.flags |= FLAG_MUTATION_GUARD;
+        assert!(env.has_mutation_guard());
+        assert_eq!(FLAG_MUTATION_GUARD & (FLAG_RANGE_GUARD | FLAG_PERMANENT), 0);
+    }
+
+    #[test]
+    fn test_aux_blocks_touched() {
+        assert_eq!(AUX_BLOCKS, 8);
+        assert_eq!(aux_blocks_touched(0, 0), 0);
+        assert_eq!(aux_blocks_touched(0, 1), 0b0000_0001);
+        assert_eq!(aux_blocks_touched(0, 32), 0b0000_0001);
+        assert_eq!(aux_blocks_touched(31, 2), 0b0000_0011);
+        assert_eq!(aux_blocks_touched(64, 64), 0b0000_1100);
+        assert_eq!(aux_blocks_touched(0, AUX_DATA_SIZE), 0xFF);
+        assert_eq!(aux_blocks_touched(255, 1), 0b1000_0000);
+        assert_eq!(aux_blocks_touched(250, 100), 0b1000_0000);
+        assert_eq!(aux_blocks_touched(AUX_DATA_SIZE, 8), 0);
+    }
+
+    #[test]
+    fn test_envelope_record_aux_write() {
+        let mut env = Envelope::zeroed();
+        env.record_aux_write(0b0000_0110);
+        assert_eq!(env.aux_dirty_blocks, 0b0000_0110);
+        assert_eq!(env.aux_write_count, 1);
+        env.aux_write_count = u16::MAX;
+        env.record_aux_write(0x01);
+        assert_eq!(env.aux_dirty_blocks, 0x01);
+        assert_eq!(env.aux_write_count, 0);
+    }
+
+    #[test]
+    fn test_envelope_is_permanent() {
+        let mut env = Envelope::zeroed();
+        assert!(!env.is_permanent());
+        env.flags |= FLAG_RANGE_GUARD;
+        assert!(!env.is_permanent());
+        env.flags |= FLAG_PERMANENT;
+        assert!(env.is_permanent());
+        assert!(env.has_range_guard());
+    }
+
+    #[test]
+    fn test_oracle_memo_layout() {
+        assert_eq!(ORACLE_MEMO_OFFSET, 199);
+        assert_eq!(ORACLE_MEMO_OFFSET + ORACLE_MEMO_SIZE + 8, ORACLE_BYTES);
+    }
+
+    #[test]
+    fn test_oracle_memo_bound_to_sequence() {
+        let mut env = Envelope::zeroed();
+        env.oracle_state.oracle_metadata = u64::METADATA;
+        env.oracle_state.sequence = 5;
+        env.oracle_state.data[ORACLE_MEMO_OFFSET..ORACLE_MEMO_OFFSET + ORACLE_MEMO_SIZE]
+            .copy_from_slice(&[0xAB; ORACLE_MEMO_SIZE]);
+        env.oracle_state.data[ORACLE_MEMO_OFFSET + ORACLE_MEMO_SIZE..]
+            .copy_from_slice(&5u64.to_le_bytes());
+        assert_eq!(env.oracle_memo(), Some(&[0xAB; ORACLE_MEMO_SIZE]));
+
+        // A later update without a memo leaves the trailer stale.
+        env.oracle_state.sequence = 6;
+        assert_eq!(env.oracle_memo(), None);
+    }
+
+    #[test]
+    fn test_oracle_memo_none_before_first_update() {
+        let mut env = Envelope::zeroed();
+        env.oracle_state.oracle_metadata = u64::METADATA;
+        assert_eq!(env.oracle_memo(), None);
+    }
+
+    #[test]
+    fn test_oracle_memo_none_for_large_types() {
+        let mut env = Envelope::zeroed();
+        env.oracle_state.oracle_metadata = <[u8; ORACLE_MEMO_OFFSET + 1]>::METADATA;
+        env.oracle_state.sequence = 1;
+        env.oracle_state.data[ORACLE_MEMO_OFFSET + ORACLE_MEMO_SIZE..]
+            .copy_from_slice(&1u64.to_le_bytes());
+        assert_eq!(env.oracle_memo(), None);
+    }
+
+    #[test]
+    fn test_oracle_memo_none_for_twap() {
+        let mut env = Envelope::zeroed();
+        env.oracle_state.sequence = 1;
+        env.oracle_state.data[ORACLE_MEMO_OFFSET + ORACLE_MEMO_SIZE..]
+            .copy_from_slice(&1u64.to_le_bytes());
+        assert!(env.oracle_memo().is_some());
+        env.flags |= FLAG_TWAP;
+        assert_eq!(env.oracle_memo(), None);
+        env.flags = FLAG_UPDATE_STAMP;
+        assert_eq!(env.oracle_memo(), None);
+        env.flags = FLAG_CONFLATION;
+        assert_eq!(env.oracle_memo(), None);
     }
 
     #[test]
@@ -615,6 +2037,34 @@ mod tests {
         assert!(env.oracle::<u64>().is_none());
     }
 
+    #[test]
+    fn test_envelope_oracle_checked() {
+        let mut env = Envelope::zeroed();
+        assert_eq!(
+            env.oracle_checked::<u64>(0, 0),
+            Err(ReadError::Uninitialized)
+        );
+        env.oracle_state.oracle_metadata = u64::METADATA;
+        assert_eq!(
+            env.oracle_checked::<u64>(0, 0),
+            Err(ReadError::Uninitialized)
+        );
+
+        env.oracle_state.sequence = 5;
+        *env.oracle_mut::<u64>().unwrap() = 7;
+        assert_eq!(
+            env.oracle_checked::<u32>(0, 5),
+            Err(ReadError::TypeMismatch)
+        );
+        assert_eq!(env.oracle_checked::<u64>(0, 5), Ok(&7));
+        assert_eq!(env.oracle_checked::<u64>(0, 3), Ok(&7));
+        assert_eq!(env.oracle_checked::<u64>(2, 7), Ok(&7));
+        assert_eq!(
+            env.oracle_checked::<u64>(2, 8),
+            Err(ReadError::Stale { have: 5, want: 8 })
+        );
+    }
+
     #[test]
     fn test_envelope_aux_typed_roundtrip() {
         let mut env = Envelope::zeroed();
@@ -635,6 +2085,12 @@ mod tests {
         let mut env = Envelope::zeroed();
         env.auxiliary_metadata = u32::METADATA;
         assert!(env.aux::<u64>().is_none());
+        assert_eq!(
+            env.aux_metadata_diff::<u64>(),
+            MetadataDiff::Size { from: 4, to: 8 }
+        );
+        assert_eq!(env.aux_metadata_diff::<[u8; 4]>(), MetadataDiff::Hash);
+        assert_eq!(env.aux_metadata_diff::<u32>(), MetadataDiff::Same);
     }
 
     #[test]
@@ -661,6 +2117,140 @@ mod tests {
         assert!(!bitmask.is_writable(129)); // adjacent untouched
     }
 
+    #[test]
+    fn test_mask_range_builders() {
+        let mut mask = Mask::from_ranges(&[(0, 4), (250, 100)]);
+        assert!(mask.is_write_allowed(0, 4));
+        assert!(!mask.is_writable(4));
+        assert!(mask.is_write_allowed(250, 6), "clamped at MASK_SIZE");
+        assert!(!mask.is_writable(249));
+
+        mask.block_range(2, 1);
+        mask.allow_range(usize::MAX, 4);
+        assert!(mask.is_writable(1));
+        assert!(!mask.is_writable(2));
+        assert!(mask.is_writable(3));
+        assert!(mask.is_canonical());
+        assert_eq!(Mask::from_ranges(&[]), Mask::ALL_BLOCKED);
+        assert_eq!(Mask::from_ranges(&[(0, MASK_SIZE)]), Mask::ALL_WRITABLE);
+    }
+
+    #[test]
+    fn test_mask_set_algebra() {
+        let a = Mask::from_ranges(&[(0, 8)]);
+        let b = Mask::from_ranges(&[(4, 8)]);
+        assert_eq!(a.union(&b), Mask::from_ranges(&[(0, 12)]));
+        assert_eq!(a.intersect(&b), Mask::from_ranges(&[(4, 4)]));
+        assert_eq!(a.invert(), Mask::from_ranges(&[(8, MASK_SIZE - 8)]));
+        assert_eq!(a.invert().invert(), a);
+        assert_eq!(Mask::ALL_BLOCKED.invert(), Mask::ALL_WRITABLE);
+
+        let mut odd = Mask::ALL_WRITABLE;
+        odd.as_bytes_mut()[0] = 0x7F;
+        assert!(
+            odd.union(&Mask::ALL_BLOCKED).is_canonical(),
+            "non-canonical bytes read as blocked"
+        );
+        assert!(!odd.intersect(&Mask::ALL_WRITABLE).is_writable(0));
+        assert!(odd.invert().is_writable(0));
+    }
+
+    #[test]
+    fn test_mask_sealed_bytes() {
+        let mut mask = Mask::ALL_WRITABLE;
+        mask.seal_range(6, 12); // head byte, a whole aligned word, and tail bytes
+        assert!(mask.has_sealed() && !Mask::ALL_BLOCKED.has_sealed());
+        assert!(mask.is_sealed(6) && mask.is_sealed(17) && !mask.is_sealed(18));
+        assert_eq!(mask.get(8), Permission::Blocked);
+        assert!(!mask.is_canonical());
+
+        let dest = [0u8; AUX_DATA_SIZE];
+        for i in 0..12 {
+            let mut src = [0u8; 12];
+            src[i] = 0x80;
+            assert!(
+                !mask.check_masked_update(&dest, 6, &src),
+                "sealed byte {}",
+                6 + i
+            );
+        }
+        assert!(mask.check_masked_update(&dest, 6, &[0; 12]));
+        assert!(mask.check_masked_update(&dest, 5, &[1]));
+        assert!(mask.check_masked_update(&dest, 18, &[1]));
+
+        let installed = Mask::from_ranges(&[(0, 32)]).with_sealed_from(&mask);
+        assert!(installed.is_sealed(10) && installed.is_writable(0));
+        assert!(!installed.is_writable(32));
+        assert!(Mask::ALL_BLOCKED.with_sealed_from(&mask).is_all_blocked());
+        assert!(!installed.is_all_blocked());
+
+        let sealed = installed.sealed_only();
+        assert!(sealed.is_sealed(6) && sealed.is_writable(0) && sealed.is_writable(200));
+    }
+
+    #[test]
+    fn test_mask_diff_and_display() {
+        extern crate std;
+
+        let a = Mask::from_ranges(&[(0, 8)]);
+        let b = Mask::from_ranges(&[(4, 8), (255, 1)]);
+        let diff: std::vec::Vec<_> = a.diff(&b).collect();
+        assert_eq!(
+            diff,
+            [
+                (0, true, false),
+                (1, true, false),
+                (2, true, false),
+                (3, true, false),
+                (8, false, true),
+                (9, false, true),
+                (10, false, true),
+                (11, false, true),
+                (255, false, true),
+            ]
+        );
+        assert_eq!(a.diff(&a).count(), 0);
+
+        let mut odd = a;
+        odd.as_bytes_mut()[20] = 0x7F;
+        odd.seal_range(24, 1);
+        assert_eq!(
+            odd.diff(&a).count(),
+            0,
+            "non-canonical bytes read as blocked"
+        );
+
+        let text = std::format!("{odd}");
+        let rows: std::vec::Vec<&str> = text.lines().collect();
+        assert_eq!(rows.len(), 16);
+        assert_eq!(rows[0], "00 WWWWWWWWBBBBBBBB");
+        assert_eq!(rows[1], "10 BBBB?BBBSBBBBBBB");
+        assert_eq!(rows[15], "f0 BBBBBBBBBBBBBBBB");
+    }
+
+    #[test]
+    fn test_mask_permission_get_set() {
+        assert_eq!(Permission::Writable.to_byte(), 0x00);
+        assert_eq!(Permission::Blocked.to_byte(), 0xFF);
+        assert_eq!(Permission::from_byte(0x01), None);
+        assert_eq!(Permission::from(true), Permission::Writable);
+
+        let mut mask = Mask::filled(Permission::Blocked);
+        assert_eq!(mask, Mask::ALL_BLOCKED);
+        mask.set(10, Permission::Writable);
+        mask.set(MASK_SIZE, Permission::Writable); // ignored
+        assert_eq!(mask.get(10), Permission::Writable);
+        assert_eq!(mask.get(11), Permission::Blocked);
+        assert_eq!(mask.get(MASK_SIZE), Permission::Blocked);
+        assert_eq!(mask.as_bytes()[10], 0x00);
+        assert_eq!(mask.permissions().filter(|p| p.is_writable()).count(), 1);
+        assert!(mask.is_canonical());
+
+        mask.as_bytes_mut()[11] = 0x01;
+        assert!(!mask.is_canonical());
+        assert_eq!(mask.get(11), Permission::Blocked);
+    }
+
     #[test]
     fn test_apply_masked_update_high_offsets_writable() {
         let mut bitmask = Mask::ALL_BLOCKED;
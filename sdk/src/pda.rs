@@ -0,0 +1,169 @@
+//! Envelope and config address derivation, behind the `pda` feature.
+//!
+//! An envelope lives at the PDA of `[ENVELOPE_SEED, authority, ...custom_seeds, bump]` under
+//! the c_u_soon program. On-chain the derivation goes through the runtime's syscalls, and
+//! off-chain through `solana-address`'s `curve25519` implementation, so the program,
+//! clients, and CPI callers all check the same seeds.
+
+use solana_address::Address;
+
+use crate::{CONFIG_SEED, ENVELOPE_SEED, MAX_CUSTOM_SEEDS};
+
+/// Call `f` with `[ENVELOPE_SEED, authority, ...custom_seeds, bump]`, leaving out an empty
+/// `bump`. `None` if there are more than [`MAX_CUSTOM_SEEDS`] custom seeds.
+fn with_envelope_seeds<R>(
+    authority: &Address,
+    custom_seeds: &[&[u8]],
+    bump: &[u8],
+    f: impl FnOnce(&[&[u8]]) -> R,
+) -> Option<R> {
+    if custom_seeds.len() > MAX_CUSTOM_SEEDS {
+        return None;
+    }
+    let mut seeds: [&[u8]; MAX_CUSTOM_SEEDS + 3] = [&[]; MAX_CUSTOM_SEEDS + 3];
+    seeds[0] = ENVELOPE_SEED;
+    seeds[1] = authority.as_ref();
+    seeds[2..2 + custom_seeds.len()].copy_from_slice(custom_seeds);
+    let mut len = 2 + custom_seeds.len();
+    if !bump.is_empty() {
+        seeds[len] = bump;
+        len += 1;
+    }
+    Some(f(&seeds[..len]))
+}
+
+/// Envelope address and canonical bump for `authority` and `custom_seeds`.
+///
+/// # Panics
+///
+/// If `custom_seeds` are not valid `Create` seeds: more than [`MAX_CUSTOM_SEEDS`] of them,
+/// or one longer than 32 bytes.
+pub fn derive_envelope_address(
+    program_id: &Address,
+    authority: &Address,
+    custom_seeds: &[&[u8]],
+) -> (Address, u8) {
+    with_envelope_seeds(authority, custom_seeds, &[], |seeds| {
+        Address::find_program_address(seeds, program_id)
+    })
+    .expect("more than MAX_CUSTOM_SEEDS custom seeds")
+}
+
+/// Envelope address for `authority`, `custom_seeds`, and a known `bump`, with one hash
+/// instead of [`derive_envelope_address`]'s bump search.
+///
+/// Returns `None` if the seeds are not valid `Create` seeds or the bump does not give an
+/// off-curve address. Any bump that does is accepted, not only the canonical one.
+pub fn create_envelope_address(
+    program_id: &Address,
+    authority: &Address,
+    custom_seeds: &[&[u8]],
+    bump: u8,
+) -> Option<Address> {
+    with_envelope_seeds(authority, custom_seeds, &[bump], |seeds| {
+        Address::create_program_address(seeds, program_id).ok()
+    })
+    .flatten()
+}
+
+/// Whether `address` is the envelope of `authority` at `custom_seeds` and `bump`; see
+/// [`create_envelope_address`].
+pub fn verify_envelope_address(
+    program_id: &Address,
+    authority: &Address,
+    custom_seeds: &[&[u8]],
+    bump: u8,
+    address: &Address,
+) -> bool {
+    create_envelope_address(program_id, authority, custom_seeds, bump).as_ref() == Some(address)
+}
+
+/// [`ProgramConfig`](crate::ProgramConfig) address and canonical bump.
+pub fn derive_config_address(program_id: &Address) -> (Address, u8) {
+    Address::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+/// [`ProgramConfig`](crate::ProgramConfig) address for a known `bump`, or `None` if the bump
+/// gives an on-curve address.
+pub fn create_config_address(program_id: &Address, bump: u8) -> Option<Address> {
+    Address::create_program_address(&[CONFIG_SEED, &[bump]], program_id).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM_ID: Address = Address::new_from_array([3; 32]);
+    const AUTHORITY: Address = Address::new_from_array([4; 32]);
+
+    #[test]
+    fn test_derive_matches_find_program_address() {
+        let (address, bump) = derive_envelope_address(&PROGRAM_ID, &AUTHORITY, &[b"feed"]);
+        assert_eq!(
+            Address::find_program_address(
+                &[ENVELOPE_SEED, AUTHORITY.as_ref(), b"feed"],
+                &PROGRAM_ID
+            ),
+            (address, bump)
+        );
+        assert_eq!(
+            create_envelope_address(&PROGRAM_ID, &AUTHORITY, &[b"feed"], bump),
+            Some(address)
+        );
+        assert!(verify_envelope_address(
+            &PROGRAM_ID,
+            &AUTHORITY,
+            &[b"feed"],
+            bump,
+            &address
+        ));
+
+        // Other seeds, authority, or program give another address.
+        assert!(!verify_envelope_address(
+            &PROGRAM_ID,
+            &AUTHORITY,
+            &[b"feeds"],
+            bump,
+            &address
+        ));
+        assert!(!verify_envelope_address(
+            &PROGRAM_ID,
+            &PROGRAM_ID,
+            &[b"feed"],
+            bump,
+            &address
+        ));
+        assert!(!verify_envelope_address(
+            &AUTHORITY,
+            &AUTHORITY,
+            &[b"feed"],
+            bump,
+            &address
+        ));
+    }
+
+    #[test]
+    fn test_config_address() {
+        let (address, bump) = derive_config_address(&PROGRAM_ID);
+        assert_eq!(
+            Address::find_program_address(&[CONFIG_SEED], &PROGRAM_ID),
+            (address, bump)
+        );
+        assert_eq!(create_config_address(&PROGRAM_ID, bump), Some(address));
+        assert_ne!(derive_config_address(&AUTHORITY).0, address);
+    }
+
+    #[test]
+    fn test_invalid_seeds() {
+        let seeds = [&b"s"[..]; MAX_CUSTOM_SEEDS + 1];
+        assert!(create_envelope_address(&PROGRAM_ID, &AUTHORITY, &seeds, 255).is_none());
+        assert!(create_envelope_address(&PROGRAM_ID, &AUTHORITY, &[&[0; 33]], 255).is_none());
+
+        let (_, bump) =
+            derive_envelope_address(&PROGRAM_ID, &AUTHORITY, &seeds[..MAX_CUSTOM_SEEDS]);
+        assert!(
+            create_envelope_address(&PROGRAM_ID, &AUTHORITY, &seeds[..MAX_CUSTOM_SEEDS], bump)
+                .is_some()
+        );
+    }
+}
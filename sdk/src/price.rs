@@ -0,0 +1,231 @@
+//! Fixed-point prices: `mantissa * 10^exponent`, with a confidence interval.
+//!
+//! [`PriceValue`] is a ready-made oracle type for feeds that publish a decimal price, so
+//! consumers do not each reinvent scaling on raw bytes. It is 20 bytes with alignment 1
+//! (fields are [`I64Le`], [`I32Le`], [`U64Le`]), implements [`TypeHash`], and can be
+//! written with the typed fast-path builders like any other oracle type.
+//!
+//! The mantissa comes first, so TWAP and conflation, which read the first 8 payload bytes
+//! as a `u64`, track it directly for non-negative prices.
+//!
+//! Arithmetic is checked: every helper returns `None` instead of overflowing. Mantissas are
+//! truncated toward zero when precision is dropped; confidences are rounded up, so a
+//! derived interval never understates the source intervals.
+
+use crate::{combine_hash, const_fnv1a, I32Le, I64Le, StructMetadata, TypeHash, U64Le};
+use bytemuck::{Pod, Zeroable};
+
+/// `mantissa * 10^exponent`, plus or minus `conf * 10^exponent`.
+#[derive(Clone, Copy, Default, Pod, Zeroable, Debug, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct PriceValue {
+    pub mantissa: I64Le,
+    pub exponent: I32Le,
+    /// Half-width of the confidence interval, in units of `10^exponent`.
+    pub conf: U64Le,
+}
+
+const _: () = assert!(core::mem::size_of::<PriceValue>() == 20);
+
+impl TypeHash for PriceValue {
+    const TYPE_HASH: u64 = combine_hash(
+        combine_hash(
+            combine_hash(const_fnv1a(b"PriceValue"), I64Le::TYPE_HASH),
+            I32Le::TYPE_HASH,
+        ),
+        U64Le::TYPE_HASH,
+    );
+    const METADATA: StructMetadata =
+        StructMetadata::new(core::mem::size_of::<PriceValue>() as u8, Self::TYPE_HASH);
+}
+
+/// `10^exp` as `u128`, or `None` past `10^38`.
+#[inline]
+fn pow10(exp: u32) -> Option<u128> {
+    10u128.checked_pow(exp)
+}
+
+/// `value * 10^shift` for `shift >= 0`, `value / 10^-shift` (toward zero) otherwise.
+fn shift_mantissa(value: i128, shift: i32) -> Option<i128> {
+    if shift >= 0 {
+        value.checked_mul(pow10(shift as u32)?.try_into().ok()?)
+    } else {
+        Some(match pow10(shift.unsigned_abs()) {
+            Some(divisor) => value / i128::try_from(divisor).ok()?,
+            None => 0,
+        })
+    }
+}
+
+/// As [`shift_mantissa`] for confidences, rounding up when dividing.
+fn shift_conf(value: u128, shift: i32) -> Option<u128> {
+    if shift >= 0 {
+        value.checked_mul(pow10(shift as u32)?)
+    } else {
+        Some(match pow10(shift.unsigned_abs()) {
+            Some(divisor) => value.div_ceil(divisor),
+            None => u128::from(value != 0),
+        })
+    }
+}
+
+impl PriceValue {
+    #[inline]
+    pub const fn new(mantissa: i64, exponent: i32, conf: u64) -> Self {
+        Self {
+            mantissa: I64Le::new(mantissa),
+            exponent: I32Le::new(exponent),
+            conf: U64Le::new(conf),
+        }
+    }
+
+    /// Build from `i128` parts, or `None` if either does not fit.
+    fn from_wide(mantissa: i128, exponent: i32, conf: u128) -> Option<Self> {
+        Some(Self::new(
+            mantissa.try_into().ok()?,
+            exponent,
+            conf.try_into().ok()?,
+        ))
+    }
+
+    /// The same price expressed with `exponent`.
+    ///
+    /// A larger exponent drops digits (mantissa truncated toward zero, confidence rounded
+    /// up); a smaller one adds them and returns `None` if the mantissa or confidence no
+    /// longer fits.
+    pub fn scale_to(self, exponent: i32) -> Option<Self> {
+        let shift = self.exponent.get().checked_sub(exponent)?;
+        Self::from_wide(
+            shift_mantissa(self.mantissa.get().into(), shift)?,
+            exponent,
+            shift_conf(self.conf.get().into(), shift)?,
+        )
+    }
+
+    /// The mantissa at `exponent`, e.g. `to_mantissa(-6)` for a 6-decimal token amount.
+    /// Truncates toward zero like [`scale_to`](Self::scale_to).
+    pub fn to_mantissa(self, exponent: i32) -> Option<i64> {
+        let shift = self.exponent.get().checked_sub(exponent)?;
+        shift_mantissa(self.mantissa.get().into(), shift)?
+            .try_into()
+            .ok()
+    }
+
+    /// Product of two prices, at the sum of their exponents.
+    ///
+    /// The confidence is `|a| * conf_b + |b| * conf_a`, the first-order propagation for a
+    /// product. Returns `None` if the mantissa, exponent, or confidence overflows; call
+    /// [`scale_to`](Self::scale_to) on the inputs first to make room.
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let a = i128::from(self.mantissa.get());
+        let b = i128::from(other.mantissa.get());
+        let conf = a
+            .unsigned_abs()
+            .checked_mul(other.conf.get().into())?
+            .checked_add(b.unsigned_abs().checked_mul(self.conf.get().into())?)?;
+        Self::from_wide(
+            a.checked_mul(b)?,
+            self.exponent.get().checked_add(other.exponent.get())?,
+            conf,
+        )
+    }
+
+    /// Quotient of two prices, expressed with `exponent`.
+    ///
+    /// The confidence is `conf_a / |b| + |a| * conf_b / b^2`, the first-order propagation for
+    /// a quotient. Returns `None` if `other` is zero or anything overflows.
+    pub fn checked_div(self, other: Self, exponent: i32) -> Option<Self> {
+        let a = i128::from(self.mantissa.get());
+        let b = i128::from(other.mantissa.get());
+        if b == 0 {
+            return None;
+        }
+        let shift = self
+            .exponent
+            .get()
+            .checked_sub(other.exponent.get())?
+            .checked_sub(exponent)?;
+
+        let (numerator, denominator) = if shift >= 0 {
+            (shift_mantissa(a, shift)?, b)
+        } else {
+            (a, shift_mantissa(b, -shift)?)
+        };
+        let mantissa = numerator / denominator;
+
+        let b_abs = b.unsigned_abs();
+        let from_a = shift_conf(self.conf.get().into(), shift)?.div_ceil(b_abs);
+        let from_b = shift_conf(
+            a.unsigned_abs().checked_mul(other.conf.get().into())?,
+            shift,
+        )?
+        .div_ceil(b_abs)
+        .div_ceil(b_abs);
+        Self::from_wide(mantissa, exponent, from_a.checked_add(from_b)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_and_metadata() {
+        assert_eq!(core::mem::align_of::<PriceValue>(), 1);
+        let price = PriceValue::new(-2, -3, 7);
+        assert_eq!(
+            bytemuck::bytes_of(&price),
+            &[
+                0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, // mantissa
+                0xFD, 0xFF, 0xFF, 0xFF, // exponent
+                7, 0, 0, 0, 0, 0, 0, 0, // conf
+            ]
+        );
+        assert_eq!(PriceValue::METADATA.type_size(), 20);
+        assert_ne!(PriceValue::TYPE_HASH, <[u8; 20]>::TYPE_HASH);
+    }
+
+    #[test]
+    fn test_scale_to() {
+        // 123.456 +- 0.001
+        let price = PriceValue::new(123_456, -3, 1);
+        assert_eq!(
+            price.scale_to(-5),
+            Some(PriceValue::new(12_345_600, -5, 100))
+        );
+        assert_eq!(price.scale_to(-1), Some(PriceValue::new(1_234, -1, 1)));
+        assert_eq!(price.scale_to(40), Some(PriceValue::new(0, 40, 1)));
+        assert_eq!(PriceValue::new(-1_999, -3, 0).to_mantissa(0), Some(-1));
+        assert_eq!(price.scale_to(-30), None);
+        assert_eq!(PriceValue::new(1, i32::MIN, 0).scale_to(1), None);
+    }
+
+    #[test]
+    fn test_checked_mul() {
+        // (2.5 +- 0.1) * (4.0 +- 0.2) = 10.0 +- (2.5 * 0.2 + 4.0 * 0.1)
+        let a = PriceValue::new(25, -1, 1);
+        let b = PriceValue::new(40, -1, 2);
+        assert_eq!(a.checked_mul(b), Some(PriceValue::new(1_000, -2, 90)));
+        assert_eq!(PriceValue::new(i64::MAX, 0, 0).checked_mul(b), None);
+    }
+
+    #[test]
+    fn test_checked_div() {
+        // 10.0 / 4.0 = 2.5
+        let a = PriceValue::new(100, -1, 0);
+        let b = PriceValue::new(4, 0, 0);
+        assert_eq!(a.checked_div(b, -2), Some(PriceValue::new(250, -2, 0)));
+        // 1 / 3, truncated at 4 decimals.
+        let third = PriceValue::new(1, 0, 0).checked_div(PriceValue::new(3, 0, 0), -4);
+        assert_eq!(third, Some(PriceValue::new(3_333, -4, 0)));
+        // (10 +- 1) / (5 +- 1) = 2 +- (1/5 + 10/25) = 2 +- 0.6
+        let c = PriceValue::new(10, 0, 1).checked_div(PriceValue::new(5, 0, 1), -1);
+        assert_eq!(c, Some(PriceValue::new(20, -1, 6)));
+        // A coarser result exponent divides the numerator instead.
+        assert_eq!(
+            PriceValue::new(12_345, -2, 0).checked_div(PriceValue::new(1, 0, 0), 1),
+            Some(PriceValue::new(12, 1, 0))
+        );
+        assert_eq!(a.checked_div(PriceValue::new(0, 0, 0), 0), None);
+    }
+}
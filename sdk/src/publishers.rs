@@ -0,0 +1,148 @@
+//! Threshold publisher sets: oracle writes signed by registered publisher keys instead of
+//! the envelope authority.
+//!
+//! The authority stores up to [`MAX_PUBLISHERS`] addresses and a `threshold` in a
+//! [`PublisherSet`] account at `[PUBLISHERS_SEED, envelope_address, bump]` (`SetPublishers`).
+//! A `PublisherUpdate` instruction then carries the same `[oracle_meta][sequence][payload]`
+//! as the fast path, and succeeds if at least `threshold` distinct registered publishers
+//! sign it. With a threshold of 1, any single publisher can push updates.
+//!
+//! Publisher writes share the oracle sequence with the authority's fast-path writes, so a
+//! replayed or reordered update fails the same way whoever signed it. The fast path itself
+//! still takes the authority only: a two-account instruction has no room for the set.
+
+use bytemuck::{Pod, Zeroable};
+use solana_address::Address;
+
+/// PDA seed discriminator for [`PublisherSet`] accounts:
+/// `[PUBLISHERS_SEED, envelope_address, bump]`.
+pub const PUBLISHERS_SEED: &[u8] = b"publishers";
+
+/// Maximum number of addresses in a [`PublisherSet`].
+pub const MAX_PUBLISHERS: usize = 8;
+
+/// Publisher set account (296 bytes), stored at the PDA
+/// `[PUBLISHERS_SEED, envelope_address, bump]`.
+///
+/// A `PublisherUpdate` to `envelope` needs signatures from at least `threshold` of the
+/// `count` registered publishers. A set with `count == 0` accepts no publisher writes.
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct PublisherSet {
+    pub envelope: Address,                     // 32  [0..32]
+    pub count: u8,                             // 1   [32]
+    pub bump: u8,                              // 1   [33]
+    pub threshold: u8,                         // 1   [34]
+    pub _padding: [u8; 5],                     // 5   [35..40]
+    pub publishers: [Address; MAX_PUBLISHERS], // 256 [40..296]
+}
+
+const _: () = assert!(core::mem::size_of::<PublisherSet>() == 296);
+
+impl PublisherSet {
+    /// Total byte size of a publisher set account.
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// The registered publishers (`publishers[..count]`, clamped to [`MAX_PUBLISHERS`]).
+    #[inline]
+    pub fn active(&self) -> &[Address] {
+        &self.publishers[..(self.count as usize).min(MAX_PUBLISHERS)]
+    }
+
+    /// Returns `true` if `address` is a registered publisher.
+    #[inline]
+    pub fn contains(&self, address: &Address) -> bool {
+        self.active().contains(address)
+    }
+
+    /// Returns `true` if `signers` include at least `threshold` distinct registered
+    /// publishers. A set with no publishers or a zero threshold is never satisfied.
+    pub fn is_satisfied_by<'a>(&self, signers: impl IntoIterator<Item = &'a Address>) -> bool {
+        if self.threshold == 0 {
+            return false;
+        }
+        let mut seen = 0u8;
+        for signer in signers {
+            if let Some(index) = self.active().iter().position(|p| p == signer) {
+                seen |= 1 << index;
+            }
+        }
+        seen.count_ones() >= self.threshold as u32
+    }
+}
+
+/// Returns `true` if `publishers` is a valid `SetPublishers` list for `threshold`: at most
+/// [`MAX_PUBLISHERS`] distinct, non-zero addresses, with `1 <= threshold <= len`, or an
+/// empty list with a zero threshold (which disables publisher writes).
+pub fn is_valid_publisher_set(publishers: &[[u8; 32]], threshold: u8) -> bool {
+    if publishers.is_empty() {
+        return threshold == 0;
+    }
+    publishers.len() <= MAX_PUBLISHERS
+        && (1..=publishers.len()).contains(&(threshold as usize))
+        && publishers.iter().all(|p| p != &[0u8; 32])
+        && publishers
+            .iter()
+            .enumerate()
+            .all(|(i, p)| !publishers[..i].contains(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> Address {
+        Address::new_from_array([byte; 32])
+    }
+
+    fn set(publishers: &[u8], threshold: u8) -> PublisherSet {
+        let mut set = PublisherSet::zeroed();
+        set.count = publishers.len() as u8;
+        set.threshold = threshold;
+        for (slot, &byte) in set.publishers.iter_mut().zip(publishers) {
+            *slot = key(byte);
+        }
+        set
+    }
+
+    #[test]
+    fn test_threshold_counts_distinct_publishers() {
+        let set = set(&[1, 2, 3], 2);
+        assert!(set.contains(&key(2)));
+        assert!(!set.contains(&key(4)));
+        assert!(!set.is_satisfied_by(&[key(1)]));
+        assert!(
+            !set.is_satisfied_by(&[key(1), key(1)]),
+            "duplicates count once"
+        );
+        assert!(
+            !set.is_satisfied_by(&[key(1), key(4)]),
+            "outsiders do not count"
+        );
+        assert!(set.is_satisfied_by(&[key(4), key(3), key(1)]));
+    }
+
+    #[test]
+    fn test_empty_set_is_never_satisfied() {
+        assert!(!set(&[], 0).is_satisfied_by(&[key(1)]));
+        let mut stale = set(&[1], 1);
+        stale.threshold = 0;
+        assert!(!stale.is_satisfied_by(&[key(1)]));
+    }
+
+    #[test]
+    fn test_publisher_set_validation() {
+        let keys = [[1u8; 32], [2u8; 32], [3u8; 32]];
+        assert!(is_valid_publisher_set(&[], 0));
+        assert!(!is_valid_publisher_set(&[], 1));
+        assert!(is_valid_publisher_set(&keys, 1));
+        assert!(is_valid_publisher_set(&keys, 3));
+        assert!(!is_valid_publisher_set(&keys, 0));
+        assert!(!is_valid_publisher_set(&keys, 4));
+        assert!(!is_valid_publisher_set(&[[1; 32], [1; 32]], 1));
+        assert!(!is_valid_publisher_set(&[[0; 32]], 1));
+        assert!(!is_valid_publisher_set(&[[9; 32]; MAX_PUBLISHERS + 1], 1));
+        let max: [[u8; 32]; MAX_PUBLISHERS] = core::array::from_fn(|i| [i as u8 + 1; 32]);
+        assert!(is_valid_publisher_set(&max, MAX_PUBLISHERS as u8));
+    }
+}
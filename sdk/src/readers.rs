@@ -0,0 +1,107 @@
+//! Reader allowlists: oracle reads through the program restricted to approved programs.
+//!
+//! The authority stores up to [`MAX_READERS`] program ids in a [`ReaderList`] account at
+//! `[READERS_SEED, envelope_address, bump]` (`SetReaders`), which also sets
+//! [`FLAG_GATED_READS`] on the envelope. From then on `ReadOracle` is refused, and
+//! `ReadOracleGated` returns the oracle only when the top-level instruction of the
+//! transaction targets a listed program: the reader CPIs into c_u_soon and receives the value
+//! as return data. An empty list clears the flag.
+//!
+//! The gate covers what the program hands out, not the account itself. Envelope data is
+//! still readable by anyone who fetches the account or passes it to their own program, so
+//! the mode is for feeds whose consumers have agreed to read through the program, not for
+//! secrets.
+
+use bytemuck::{Pod, Zeroable};
+use solana_address::Address;
+
+use crate::{Envelope, FLAG_GATED_READS};
+
+/// PDA seed discriminator for [`ReaderList`] accounts: `[READERS_SEED, envelope_address, bump]`.
+pub const READERS_SEED: &[u8] = b"readers";
+
+/// Maximum number of programs in a [`ReaderList`].
+pub const MAX_READERS: usize = 8;
+
+/// Reader allowlist account (296 bytes), stored at the PDA
+/// `[READERS_SEED, envelope_address, bump]`.
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct ReaderList {
+    pub envelope: Address,               // 32  [0..32]
+    pub count: u8,                       // 1   [32]
+    pub bump: u8,                        // 1   [33]
+    pub _padding: [u8; 6],               // 6   [34..40]
+    pub readers: [Address; MAX_READERS], // 256 [40..296]
+}
+
+const _: () = assert!(core::mem::size_of::<ReaderList>() == 296);
+
+impl ReaderList {
+    /// Total byte size of a reader list account.
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// The approved programs (`readers[..count]`, clamped to [`MAX_READERS`]).
+    #[inline]
+    pub fn active(&self) -> &[Address] {
+        &self.readers[..(self.count as usize).min(MAX_READERS)]
+    }
+
+    /// Returns `true` if `program_id` is an approved reader.
+    #[inline]
+    pub fn contains(&self, program_id: &Address) -> bool {
+        self.active().contains(program_id)
+    }
+}
+
+/// Returns `true` if `readers` is a valid `SetReaders` list: at most [`MAX_READERS`]
+/// distinct, non-zero program ids. An empty list is valid and turns gating off.
+pub fn is_valid_reader_list(readers: &[[u8; 32]]) -> bool {
+    readers.len() <= MAX_READERS
+        && readers.iter().all(|r| r != &[0u8; 32])
+        && readers
+            .iter()
+            .enumerate()
+            .all(|(i, r)| !readers[..i].contains(r))
+}
+
+impl Envelope {
+    /// Returns `true` if [`FLAG_GATED_READS`] is set: the program returns the oracle only
+    /// to programs in the envelope's [`ReaderList`].
+    #[inline]
+    pub fn has_gated_reads(&self) -> bool {
+        self.flags & FLAG_GATED_READS != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_list_membership() {
+        let mut list = ReaderList::zeroed();
+        list.readers[0] = Address::new_from_array([1; 32]);
+        list.readers[1] = Address::new_from_array([2; 32]);
+        assert!(
+            !list.contains(&Address::new_from_array([1; 32])),
+            "count is 0"
+        );
+        list.count = 2;
+        assert!(list.contains(&Address::new_from_array([2; 32])));
+        assert!(!list.contains(&Address::new_from_array([3; 32])));
+        list.count = u8::MAX;
+        assert_eq!(list.active().len(), MAX_READERS);
+    }
+
+    #[test]
+    fn test_valid_reader_lists() {
+        assert!(is_valid_reader_list(&[]));
+        assert!(is_valid_reader_list(&[[1; 32], [2; 32]]));
+        assert!(!is_valid_reader_list(&[[1; 32], [1; 32]]), "duplicate");
+        assert!(!is_valid_reader_list(&[[0; 32]]), "zero address");
+        assert!(!is_valid_reader_list(&[[1; 32]; MAX_READERS + 1]));
+        let full: [[u8; 32]; MAX_READERS] = core::array::from_fn(|i| [i as u8 + 1; 32]);
+        assert!(is_valid_reader_list(&full));
+    }
+}
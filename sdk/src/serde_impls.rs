@@ -0,0 +1,298 @@
+//! `Serialize` / `Deserialize` for envelope snapshots, behind the `serde` feature.
+//!
+//! Byte arrays (addresses, masks, oracle and aux data) are lowercase hex strings in
+//! human-readable formats such as JSON, and plain bytes in binary formats. A
+//! [`StructMetadata`] is its packed `u64`. [`OracleState`]'s alignment pad is neither written
+//! nor read, and deserializing rejects unknown fields, so a snapshot loads back into exactly
+//! the account bytes it was taken from.
+
+use core::fmt;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use solana_address::Address;
+
+use crate::{Envelope, Mask, OracleState, StructMetadata, AUX_DATA_SIZE, MASK_SIZE, ORACLE_BYTES};
+
+/// `N` bytes, hex-encoded in human-readable formats.
+struct Hex<const N: usize>([u8; N]);
+
+struct HexDisplay<'a>(&'a [u8]);
+
+impl fmt::Display for HexDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl<const N: usize> Serialize for Hex<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(&HexDisplay(&self.0))
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+fn hex_digit(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+struct HexVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for HexVisitor<N> {
+    type Value = Hex<N>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{N} bytes as {} hex digits", 2 * N)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Hex<N>, E> {
+        if v.len() != 2 * N {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        let mut bytes = [0u8; N];
+        for (byte, pair) in bytes.iter_mut().zip(v.as_bytes().chunks_exact(2)) {
+            let (Some(hi), Some(lo)) = (hex_digit(pair[0]), hex_digit(pair[1])) else {
+                return Err(E::invalid_value(de::Unexpected::Str(v), &self));
+            };
+            *byte = (hi << 4) | lo;
+        }
+        Ok(Hex(bytes))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Hex<N>, E> {
+        v.try_into()
+            .map(Hex)
+            .map_err(|_| E::invalid_length(v.len(), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Hex<N>, A::Error> {
+        let mut bytes = [0u8; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(de::Error::invalid_length(N + 1, &self));
+        }
+        Ok(Hex(bytes))
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for Hex<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(HexVisitor)
+        } else {
+            deserializer.deserialize_bytes(HexVisitor)
+        }
+    }
+}
+
+impl Serialize for StructMetadata {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.as_u64())
+    }
+}
+
+impl<'de> Deserialize<'de> for StructMetadata {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u64::deserialize(deserializer).map(StructMetadata::from_raw)
+    }
+}
+
+impl Serialize for Mask {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Hex(*self.as_bytes()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Mask {
+    /// Non-canonical bytes are kept as they are, since they can occur on-chain.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Hex::<MASK_SIZE>::deserialize(deserializer).map(|Hex(bytes)| Mask(bytes))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "OracleState", deny_unknown_fields)]
+struct OracleStateRepr {
+    oracle_metadata: StructMetadata,
+    sequence: u64,
+    data: Hex<ORACLE_BYTES>,
+}
+
+impl Serialize for OracleState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        OracleStateRepr {
+            oracle_metadata: self.oracle_metadata,
+            sequence: self.sequence,
+            data: Hex(self.data),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for OracleState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = OracleStateRepr::deserialize(deserializer)?;
+        Ok(OracleState {
+            oracle_metadata: repr.oracle_metadata,
+            sequence: repr.sequence,
+            data: repr.data.0,
+            _pad: [0],
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Envelope", deny_unknown_fields)]
+struct EnvelopeRepr {
+    authority: Hex<32>,
+    oracle_state: OracleState,
+    bump: u8,
+    flags: u8,
+    aux_write_count: u16,
+    aux_dirty_blocks: u8,
+    constrained_regions: u8,
+    paused: u8,
+    emit_events: u8,
+    delegation_authority: Hex<32>,
+    program_bitmask: Mask,
+    user_bitmask: Mask,
+    authority_aux_sequence: u64,
+    program_aux_sequence: u64,
+    auxiliary_metadata: StructMetadata,
+    auxiliary_data: Hex<AUX_DATA_SIZE>,
+    publisher: Hex<32>,
+    heartbeat_slot: u64,
+    write_hook: Hex<32>,
+    mutation_marker: u64,
+}
+
+impl Serialize for Envelope {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        EnvelopeRepr {
+            authority: Hex(self.authority.to_bytes()),
+            oracle_state: self.oracle_state,
+            bump: self.bump,
+            flags: self.flags,
+            aux_write_count: self.aux_write_count,
+            aux_dirty_blocks: self.aux_dirty_blocks,
+            constrained_regions: self.constrained_regions,
+            paused: self.paused,
+            emit_events: self.emit_events,
+            delegation_authority: Hex(self.delegation_authority.to_bytes()),
+            program_bitmask: self.program_bitmask,
+            user_bitmask: self.user_bitmask,
+            authority_aux_sequence: self.authority_aux_sequence,
+            program_aux_sequence: self.program_aux_sequence,
+            auxiliary_metadata: self.auxiliary_metadata,
+            auxiliary_data: Hex(self.auxiliary_data),
+            publisher: Hex(self.publisher.to_bytes()),
+            heartbeat_slot: self.heartbeat_slot,
+            write_hook: Hex(self.write_hook.to_bytes()),
+            mutation_marker: self.mutation_marker,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Envelope {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = EnvelopeRepr::deserialize(deserializer)?;
+        Ok(Envelope {
+            authority: Address::new_from_array(repr.authority.0),
+            oracle_state: repr.oracle_state,
+            bump: repr.bump,
+            flags: repr.flags,
+            aux_write_count: repr.aux_write_count,
+            aux_dirty_blocks: repr.aux_dirty_blocks,
+            constrained_regions: repr.constrained_regions,
+            paused: repr.paused,
+            emit_events: repr.emit_events,
+            delegation_authority: Address::new_from_array(repr.delegation_authority.0),
+            program_bitmask: repr.program_bitmask,
+            user_bitmask: repr.user_bitmask,
+            authority_aux_sequence: repr.authority_aux_sequence,
+            program_aux_sequence: repr.program_aux_sequence,
+            auxiliary_metadata: repr.auxiliary_metadata,
+            auxiliary_data: repr.auxiliary_data.0,
+            publisher: Address::new_from_array(repr.publisher.0),
+            heartbeat_slot: repr.heartbeat_slot,
+            write_hook: Address::new_from_array(repr.write_hook.0),
+            mutation_marker: repr.mutation_marker,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use bytemuck::Zeroable;
+    use std::format;
+
+    fn sample() -> Envelope {
+        let mut envelope = Envelope::zeroed();
+        envelope.authority = Address::new_from_array([0xAB; 32]);
+        envelope.oracle_state.oracle_metadata = StructMetadata::from_raw(0x0812_3456_789A_BCDE);
+        envelope.oracle_state.sequence = u64::MAX;
+        envelope.oracle_state.data[..8].copy_from_slice(&42u64.to_le_bytes());
+        envelope.bump = 254;
+        envelope.program_bitmask = Mask::ALL_BLOCKED;
+        envelope.program_bitmask.allow_range(0, 8);
+        envelope.auxiliary_data[255] = 0x7F;
+        envelope
+    }
+
+    #[test]
+    fn test_envelope_json_round_trip() {
+        let envelope = sample();
+        let json = serde_json::to_string(&envelope).unwrap();
+        let back: Envelope = serde_json::from_str(&json).unwrap();
+        assert_eq!(bytemuck::bytes_of(&back), bytemuck::bytes_of(&envelope));
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["authority"], "ab".repeat(32));
+        assert_eq!(value["bump"], 254);
+        assert_eq!(value["oracle_state"]["sequence"], u64::MAX);
+        assert_eq!(
+            value["oracle_state"]["oracle_metadata"],
+            0x0812_3456_789A_BCDEu64
+        );
+        assert!(value["oracle_state"].get("_pad").is_none());
+        let mask = value["program_bitmask"].as_str().unwrap();
+        assert_eq!(mask.len(), 2 * MASK_SIZE);
+        assert!(mask.starts_with("0000000000000000ff"));
+        assert!(value["auxiliary_data"].as_str().unwrap().ends_with("7f"));
+    }
+
+    #[test]
+    fn test_hex_rejects_bad_input() {
+        let mask = |s: &str| serde_json::from_str::<Mask>(&format!("\"{s}\""));
+        assert_eq!(mask(&"FF".repeat(MASK_SIZE)).unwrap(), Mask::ALL_BLOCKED);
+        assert!(mask(&"ff".repeat(MASK_SIZE - 1)).is_err());
+        assert!(mask(&"zz".repeat(MASK_SIZE)).is_err());
+
+        let mut json = serde_json::to_value(sample()).unwrap();
+        json["paused"] = 1.into();
+        assert_eq!(
+            serde_json::from_value::<Envelope>(json.clone())
+                .unwrap()
+                .paused,
+            1
+        );
+        json["extra"] = 0.into();
+        assert!(serde_json::from_value::<Envelope>(json).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! Time-weighted average accumulator kept by the program for envelopes with [`FLAG_TWAP`].
+//!
+//! A TWAP envelope gives up [`TWAP_ACCUMULATOR_SIZE`] bytes at the end of the oracle payload,
+//! just before the [`UpdateStamp`](crate::UpdateStamp) slot, to a [`TwapAccumulator`] at
+//! [`TWAP_OFFSET`]. Every oracle write (fast path, `BatchUpdate`,
+//! `GroupCommit`) treats the first 8 payload bytes as a `u64` LE value and folds it in:
+//!
+//! ```text
+//! cumulative += last_value * (slot - last_slot)
+//! last_value  = value
+//! last_slot   = slot
+//! ```
+//!
+//! so the previous value is weighted by how many slots it stood. A consumer takes two
+//! [`TwapObservation`]s, now and at some earlier point, and divides the difference of their
+//! cumulative values by the slots between them ([`TwapObservation::twap_since`]); no history
+//! has to be indexed.
+//!
+//! `cumulative` wraps at `u128::MAX`. Differences are taken with wrapping subtraction, so a
+//! wrap between two observations does not change the result, as long as the window itself
+//! does not accumulate more than `u128::MAX`.
+//!
+//! The accumulator overlaps the memo trailer, so TWAP envelopes cannot carry memos.
+
+use crate::{Envelope, U64Le, FLAG_TWAP, UPDATE_STAMP_OFFSET};
+use bytemuck::{Pod, Zeroable};
+
+/// Byte size of a [`TwapAccumulator`].
+pub const TWAP_ACCUMULATOR_SIZE: usize = core::mem::size_of::<TwapAccumulator>();
+
+/// Offset of the [`TwapAccumulator`] within [`OracleState::data`](crate::OracleState::data).
+/// Payloads written to a TWAP envelope must be between 8 and `TWAP_OFFSET` (191) bytes.
+///
+/// The 16 bytes after the accumulator are reserved for the update stamp whether or not it
+/// is enabled, so neither trailer moves when the other is toggled.
+pub const TWAP_OFFSET: usize = UPDATE_STAMP_OFFSET - TWAP_ACCUMULATOR_SIZE;
+
+/// Running sum of `value * slots` over every oracle write. Alignment 1, since
+/// [`TWAP_OFFSET`] is not 8-byte aligned within the envelope.
+#[derive(Clone, Copy, Default, Pod, Zeroable, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct TwapAccumulator {
+    cumulative: [u8; 16],
+    /// Value of the latest write.
+    pub last_value: U64Le,
+    /// Slot of the latest write. Zero until the first write after TWAP was enabled.
+    pub last_slot: U64Le,
+}
+
+const _: () = assert!(TWAP_ACCUMULATOR_SIZE == 32);
+
+impl TwapAccumulator {
+    /// Sum of `value * slots` up to [`last_slot`](Self::last_slot), modulo 2^128.
+    #[inline]
+    pub fn cumulative(&self) -> u128 {
+        u128::from_le_bytes(self.cumulative)
+    }
+
+    /// Fold a write of `value` at `slot`.
+    ///
+    /// The first write only records `value` and `slot`. A write in the same slot as the
+    /// previous one (or, defensively, an earlier slot) replaces `last_value` without adding
+    /// to the sum.
+    pub fn fold(&mut self, value: u64, slot: u64) {
+        let now = self.observe(slot);
+        self.cumulative = now.cumulative.to_le_bytes();
+        self.last_value = U64Le::new(value);
+        self.last_slot = U64Le::new(now.slot);
+    }
+
+    /// The accumulator as of `slot`, assuming `last_value` still stands then.
+    pub fn observe(&self, slot: u64) -> TwapObservation {
+        let last_slot = self.last_slot.get();
+        let cumulative = if last_slot == 0 {
+            self.cumulative()
+        } else {
+            let slots = slot.saturating_sub(last_slot) as u128;
+            // u64 * u64 always fits in u128; only the running sum can wrap.
+            let weighted = self.last_value.get() as u128 * slots;
+            self.cumulative().wrapping_add(weighted)
+        };
+        TwapObservation {
+            cumulative,
+            slot: slot.max(last_slot),
+        }
+    }
+}
+
+/// A cumulative value at a slot, as returned by [`TwapAccumulator::observe`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TwapObservation {
+    pub cumulative: u128,
+    pub slot: u64,
+}
+
+impl TwapObservation {
+    /// Time-weighted average value over `(earlier.slot, self.slot]`.
+    ///
+    /// Returns `None` if `earlier` is not strictly before `self`, or if the observations are
+    /// inconsistent (an average above `u64::MAX`, which a real pair cannot produce).
+    pub fn twap_since(&self, earlier: &TwapObservation) -> Option<u64> {
+        let slots = self.slot.checked_sub(earlier.slot).filter(|&s| s > 0)?;
+        let sum = self.cumulative.wrapping_sub(earlier.cumulative);
+        u64::try_from(sum / slots as u128).ok()
+    }
+}
+
+impl Envelope {
+    /// Returns `true` if [`FLAG_TWAP`] is set: oracle writes fold into
+    /// [`twap`](Self::twap).
+    #[inline]
+    pub fn has_twap(&self) -> bool {
+        self.flags & FLAG_TWAP != 0
+    }
+
+    /// The TWAP accumulator, or `None` if [`FLAG_TWAP`] is not set.
+    pub fn twap(&self) -> Option<&TwapAccumulator> {
+        if !self.has_twap() {
+            return None;
+        }
+        Some(bytemuck::from_bytes(
+            &self.oracle_state.data[TWAP_OFFSET..TWAP_OFFSET + TWAP_ACCUMULATOR_SIZE],
+        ))
+    }
+
+    /// Mutable variant of [`twap`](Self::twap).
+    pub fn twap_mut(&mut self) -> Option<&mut TwapAccumulator> {
+        if !self.has_twap() {
+            return None;
+        }
+        Some(bytemuck::from_bytes_mut(
+            &mut self.oracle_state.data[TWAP_OFFSET..TWAP_OFFSET + TWAP_ACCUMULATOR_SIZE],
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ORACLE_BYTES;
+
+    #[test]
+    fn test_twap_layout() {
+        assert_eq!(TWAP_OFFSET, 191);
+        assert_eq!(core::mem::align_of::<TwapAccumulator>(), 1);
+        let mut env = Envelope::zeroed();
+        assert!(env.twap().is_none());
+        env.flags |= FLAG_TWAP;
+        env.twap_mut().unwrap().last_slot = U64Le::new(0x0102);
+        assert_eq!(
+            env.oracle_state.data[UPDATE_STAMP_OFFSET - 8..][..2],
+            [0x02, 0x01]
+        );
+        assert!(env.oracle_state.data[UPDATE_STAMP_OFFSET..ORACLE_BYTES]
+            .iter()
+            .all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_twap_fold_weights_by_slots() {
+        let mut acc = TwapAccumulator::zeroed();
+        acc.fold(100, 10);
+        assert_eq!(acc.cumulative(), 0, "first write only records");
+        let start = acc.observe(10);
+
+        acc.fold(200, 13); // 100 stood for 3 slots
+        acc.fold(250, 13); // same slot: replaces 200
+        acc.fold(400, 14); // 250 stood for 1 slot
+        assert_eq!(acc.cumulative(), 100 * 3 + 250);
+        assert_eq!(acc.last_value.get(), 400);
+
+        // 400 still stands at slot 20.
+        let now = acc.observe(20);
+        assert_eq!(now.cumulative, 100 * 3 + 250 + 400 * 6);
+        assert_eq!(now.twap_since(&start), Some((300 + 250 + 2400) / 10));
+        assert_eq!(now.twap_since(&now), None);
+        assert_eq!(start.twap_since(&now), None);
+    }
+
+    #[test]
+    fn test_twap_cumulative_wraps() {
+        let mut acc = TwapAccumulator::zeroed();
+        acc.cumulative = (u128::MAX - 5).to_le_bytes();
+        acc.fold(u64::MAX, 1);
+        let before = acc.observe(1);
+        acc.fold(7, 3);
+        assert!(acc.cumulative() < before.cumulative, "sum wrapped");
+        assert_eq!(acc.observe(3).twap_since(&before), Some(u64::MAX));
+
+        // The largest single step does not overflow before the sum.
+        let mut acc = TwapAccumulator::zeroed();
+        acc.fold(u64::MAX, 1);
+        acc.fold(0, u64::MAX);
+        assert_eq!(acc.cumulative(), u64::MAX as u128 * (u64::MAX - 1) as u128);
+    }
+
+    #[test]
+    fn test_twap_ignores_earlier_slot() {
+        let mut acc = TwapAccumulator::zeroed();
+        acc.fold(5, 10);
+        acc.fold(9, 4);
+        assert_eq!(acc.cumulative(), 0);
+        assert_eq!(acc.observe(2).slot, 10);
+    }
+}
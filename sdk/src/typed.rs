@@ -0,0 +1,197 @@
+//! Envelope views with the oracle and auxiliary types fixed at construction.
+//!
+//! [`Envelope::oracle`] and [`Envelope::aux`] check the stored metadata on every call and
+//! return `Option`. A [`TypedEnvelope`] does both checks once, in
+//! [`TypedEnvelope::try_from_bytes`] or [`TypedEnvelope::new`], and then hands out `&O` and
+//! `&A` directly. [`TypedEnvelopeMut`] does the same for `&mut Envelope`; it exposes only
+//! typed mutable access, so the metadata checked at construction cannot change under it.
+//!
+//! `A` needs only `TypeHash + Pod`, which every `c_u_later::CuLater` type implements.
+
+use core::marker::PhantomData;
+
+use crate::{Envelope, TypeHash};
+use bytemuck::Pod;
+
+/// Whether `envelope` stores an `O` oracle and an `A` aux value that can be borrowed.
+fn holds<O: TypeHash + Pod, A: TypeHash + Pod>(envelope: &Envelope) -> bool {
+    envelope.oracle::<O>().is_some() && envelope.aux::<A>().is_some()
+}
+
+/// A shared envelope borrow whose oracle region is known to hold `O` and whose auxiliary
+/// region is known to hold `A`.
+pub struct TypedEnvelope<'a, O, A> {
+    envelope: &'a Envelope,
+    _types: PhantomData<(O, A)>,
+}
+
+impl<'a, O: TypeHash + Pod, A: TypeHash + Pod> TypedEnvelope<'a, O, A> {
+    /// Check `envelope`'s oracle and auxiliary metadata against `O` and `A`.
+    ///
+    /// Returns `None` if either [`Envelope::oracle`] or [`Envelope::aux`] would. Use
+    /// [`Envelope::oracle_metadata_diff`] and [`Envelope::aux_metadata_diff`] to tell why.
+    pub fn new(envelope: &'a Envelope) -> Option<Self> {
+        holds::<O, A>(envelope).then_some(Self {
+            envelope,
+            _types: PhantomData,
+        })
+    }
+
+    /// Parse account data with [`Envelope::from_prefix_bytes`], then check it as
+    /// [`new`](Self::new) does.
+    pub fn try_from_bytes(data: &'a [u8]) -> Option<Self> {
+        Self::new(Envelope::from_prefix_bytes(data)?)
+    }
+
+    /// The oracle value.
+    #[inline]
+    pub fn oracle(&self) -> &'a O {
+        bytemuck::from_bytes(&self.envelope.oracle_state.data[..core::mem::size_of::<O>()])
+    }
+
+    /// The auxiliary value.
+    #[inline]
+    pub fn aux(&self) -> &'a A {
+        bytemuck::from_bytes(&self.envelope.auxiliary_data[..core::mem::size_of::<A>()])
+    }
+
+    /// The oracle sequence of the value returned by [`oracle`](Self::oracle).
+    #[inline]
+    pub fn sequence(&self) -> u64 {
+        self.envelope.oracle_state.sequence
+    }
+
+    /// The underlying envelope.
+    #[inline]
+    pub fn envelope(&self) -> &'a Envelope {
+        self.envelope
+    }
+}
+
+impl<O, A> Clone for TypedEnvelope<'_, O, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<O, A> Copy for TypedEnvelope<'_, O, A> {}
+
+/// Mutable counterpart of [`TypedEnvelope`].
+pub struct TypedEnvelopeMut<'a, O, A> {
+    envelope: &'a mut Envelope,
+    _types: PhantomData<(O, A)>,
+}
+
+impl<'a, O: TypeHash + Pod, A: TypeHash + Pod> TypedEnvelopeMut<'a, O, A> {
+    /// Mutable variant of [`TypedEnvelope::new`].
+    pub fn new(envelope: &'a mut Envelope) -> Option<Self> {
+        holds::<O, A>(envelope).then_some(Self {
+            envelope,
+            _types: PhantomData,
+        })
+    }
+
+    /// Mutable variant of [`TypedEnvelope::try_from_bytes`]. Trailing bytes are neither
+    /// read nor written.
+    pub fn try_from_bytes(data: &'a mut [u8]) -> Option<Self> {
+        Self::new(Envelope::from_prefix_bytes_mut(data)?)
+    }
+
+    /// The oracle value.
+    #[inline]
+    pub fn oracle(&self) -> &O {
+        self.as_typed().oracle()
+    }
+
+    /// The auxiliary value.
+    #[inline]
+    pub fn aux(&self) -> &A {
+        self.as_typed().aux()
+    }
+
+    /// Mutably borrow the oracle value. The sequence is not touched.
+    #[inline]
+    pub fn oracle_mut(&mut self) -> &mut O {
+        bytemuck::from_bytes_mut(&mut self.envelope.oracle_state.data[..core::mem::size_of::<O>()])
+    }
+
+    /// Mutably borrow the auxiliary value. Neither aux sequence is touched.
+    #[inline]
+    pub fn aux_mut(&mut self) -> &mut A {
+        bytemuck::from_bytes_mut(&mut self.envelope.auxiliary_data[..core::mem::size_of::<A>()])
+    }
+
+    /// The underlying envelope, read-only.
+    #[inline]
+    pub fn envelope(&self) -> &Envelope {
+        self.envelope
+    }
+
+    /// Reborrow as a shared [`TypedEnvelope`].
+    #[inline]
+    pub fn as_typed(&self) -> TypedEnvelope<'_, O, A> {
+        TypedEnvelope {
+            envelope: self.envelope,
+            _types: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StructMetadata;
+    use bytemuck::Zeroable;
+
+    fn envelope<O: TypeHash, A: TypeHash>() -> Envelope {
+        let mut envelope = Envelope::zeroed();
+        envelope.oracle_state.oracle_metadata = O::METADATA;
+        envelope.auxiliary_metadata = A::METADATA;
+        envelope
+    }
+
+    #[test]
+    fn test_checks_both_regions_once() {
+        let mut env = envelope::<u64, [u32; 4]>();
+        env.oracle_state.data[..8].copy_from_slice(&42u64.to_le_bytes());
+        env.oracle_state.sequence = 3;
+        env.auxiliary_data[4..8].copy_from_slice(&7u32.to_le_bytes());
+
+        let typed = TypedEnvelope::<u64, [u32; 4]>::new(&env).unwrap();
+        assert_eq!(*typed.oracle(), 42);
+        assert_eq!(typed.aux()[1], 7);
+        assert_eq!(typed.sequence(), 3);
+
+        assert!(TypedEnvelope::<u32, [u32; 4]>::new(&env).is_none());
+        assert!(TypedEnvelope::<u64, [u64; 2]>::new(&env).is_none());
+
+        env.auxiliary_metadata = StructMetadata::ZERO;
+        assert!(TypedEnvelope::<u64, [u32; 4]>::new(&env).is_none());
+    }
+
+    #[test]
+    fn test_try_from_bytes_ignores_trailing_bytes() {
+        let env = envelope::<u64, u64>();
+        let mut words = [0u64; Envelope::SIZE / 8 + 1];
+        bytemuck::cast_slice_mut::<u64, u8>(&mut words)[..Envelope::SIZE]
+            .copy_from_slice(bytemuck::bytes_of(&env));
+        let bytes: &[u8] = bytemuck::cast_slice(&words);
+
+        assert!(TypedEnvelope::<u64, u64>::try_from_bytes(bytes).is_some());
+        assert!(TypedEnvelope::<u64, u64>::try_from_bytes(&bytes[..Envelope::SIZE - 1]).is_none());
+    }
+
+    #[test]
+    fn test_mut_writes_through() {
+        let mut env = envelope::<u64, u32>();
+        {
+            let mut typed = TypedEnvelopeMut::<u64, u32>::new(&mut env).unwrap();
+            *typed.oracle_mut() = 9;
+            *typed.aux_mut() = 5;
+            assert_eq!(*typed.as_typed().oracle(), 9);
+            assert_eq!(*typed.aux(), 5);
+        }
+        assert_eq!(env.oracle::<u64>(), Some(&9));
+        assert_eq!(env.aux::<u32>(), Some(&5));
+    }
+}
@@ -0,0 +1,222 @@
+//! Slot and unix timestamp of the latest oracle write, kept by the program for envelopes with
+//! [`FLAG_UPDATE_STAMP`].
+//!
+//! A stamped envelope gives up the last [`UPDATE_STAMP_SIZE`] bytes of the oracle payload to
+//! an [`UpdateStamp`] at [`UPDATE_STAMP_OFFSET`]. Every oracle write (fast path,
+//! `BatchUpdate`, `GroupCommit`) records the `Clock` sysvar's slot and unix timestamp there,
+//! so consumers can bound the age of a value without trusting the feeder
+//! ([`Envelope::oracle_max_staleness`]).
+//!
+//! Stamping costs a `Clock` read on every write. An envelope whose value has not changed
+//! can instead be kept visibly alive with the permissionless `Heartbeat` instruction, which
+//! records the slot in [`Envelope::heartbeat_slot`] without writing the oracle or spending a
+//! sequence number. [`Envelope::last_live_slot`] combines both.
+//!
+//! The stamp sits after the TWAP accumulator, so both can be enabled together; the payload
+//! then has to fit before [`TWAP_OFFSET`](crate::TWAP_OFFSET). Like the accumulator, the
+//! stamp overlaps the memo trailer.
+
+use crate::{
+    Envelope, I64Le, TypeHash, U64Le, FLAG_CONFLATION, FLAG_TWAP, FLAG_UPDATE_STAMP, ORACLE_BYTES,
+};
+use bytemuck::{Pod, Zeroable};
+
+/// Byte size of an [`UpdateStamp`].
+pub const UPDATE_STAMP_SIZE: usize = core::mem::size_of::<UpdateStamp>();
+
+/// Offset of the [`UpdateStamp`] within [`OracleState::data`](crate::OracleState::data).
+/// Payloads written to a stamped envelope without TWAP must be at most
+/// `UPDATE_STAMP_OFFSET` (223) bytes.
+pub const UPDATE_STAMP_OFFSET: usize = ORACLE_BYTES - UPDATE_STAMP_SIZE;
+
+/// When the latest oracle write landed. Alignment 1, like the other oracle trailers.
+#[derive(Clone, Copy, Default, Pod, Zeroable, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct UpdateStamp {
+    /// `Clock::slot` of the latest write. Zero until the first write after stamping was
+    /// enabled.
+    pub slot: U64Le,
+    /// `Clock::unix_timestamp` of the latest write.
+    pub unix_timestamp: I64Le,
+}
+
+const _: () = assert!(UPDATE_STAMP_SIZE == 16);
+
+/// Bytes of [`OracleState::data`](crate::OracleState::data) left for the payload once the
+/// trailers selected by `flags` ([`FLAG_CONFLATION`], [`FLAG_TWAP`], [`FLAG_UPDATE_STAMP`])
+/// are reserved.
+pub const fn oracle_capacity(flags: u8) -> usize {
+    if flags & FLAG_CONFLATION != 0 {
+        crate::CONFLATION_OFFSET
+    } else if flags & FLAG_TWAP != 0 {
+        crate::TWAP_OFFSET
+    } else if flags & FLAG_UPDATE_STAMP != 0 {
+        UPDATE_STAMP_OFFSET
+    } else {
+        ORACLE_BYTES
+    }
+}
+
+impl Envelope {
+    /// Returns `true` if [`FLAG_UPDATE_STAMP`] is set: oracle writes record
+    /// [`update_stamp`](Self::update_stamp).
+    #[inline]
+    pub fn has_update_stamp(&self) -> bool {
+        self.flags & FLAG_UPDATE_STAMP != 0
+    }
+
+    /// Payload bytes available to oracle writes under the envelope's current flags. See
+    /// [`oracle_capacity`].
+    #[inline]
+    pub fn oracle_capacity(&self) -> usize {
+        oracle_capacity(self.flags)
+    }
+
+    /// The update stamp, or `None` if [`FLAG_UPDATE_STAMP`] is not set.
+    pub fn update_stamp(&self) -> Option<&UpdateStamp> {
+        if !self.has_update_stamp() {
+            return None;
+        }
+        Some(bytemuck::from_bytes(
+            &self.oracle_state.data[UPDATE_STAMP_OFFSET..],
+        ))
+    }
+
+    /// Mutable variant of [`update_stamp`](Self::update_stamp).
+    pub fn update_stamp_mut(&mut self) -> Option<&mut UpdateStamp> {
+        if !self.has_update_stamp() {
+            return None;
+        }
+        Some(bytemuck::from_bytes_mut(
+            &mut self.oracle_state.data[UPDATE_STAMP_OFFSET..],
+        ))
+    }
+
+    /// Slot of the latest oracle write. `None` if the envelope is not stamped or has not
+    /// been written since stamping was enabled.
+    #[inline]
+    pub fn last_update_slot(&self) -> Option<u64> {
+        let slot = self.update_stamp()?.slot.get();
+        (slot != 0).then_some(slot)
+    }
+
+    /// Unix timestamp of the latest oracle write, under the same conditions as
+    /// [`last_update_slot`](Self::last_update_slot).
+    #[inline]
+    pub fn last_update_timestamp(&self) -> Option<i64> {
+        self.last_update_slot()?;
+        self.update_stamp().map(|stamp| stamp.unix_timestamp.get())
+    }
+
+    /// Slot of the latest `Heartbeat`, which confirmed that the oracle value at its sequence
+    /// was still current. `None` if the envelope has never received one.
+    #[inline]
+    pub fn last_heartbeat_slot(&self) -> Option<u64> {
+        (self.heartbeat_slot != 0).then_some(self.heartbeat_slot)
+    }
+
+    /// The later of [`last_update_slot`](Self::last_update_slot) and
+    /// [`last_heartbeat_slot`](Self::last_heartbeat_slot): the last slot at which the oracle
+    /// value is known to have been current. A value that is old but recently heartbeated is
+    /// unchanged; one with neither a recent write nor a recent heartbeat points at a
+    /// publisher that is down. `None` if there is neither.
+    #[inline]
+    pub fn last_live_slot(&self) -> Option<u64> {
+        self.last_update_slot().max(self.last_heartbeat_slot())
+    }
+
+    /// Borrow the oracle region as `T` if it was written at most `max_staleness` slots
+    /// before `current_slot`.
+    ///
+    /// Returns `None` if [`oracle`](Self::oracle) does, or if the envelope carries no update
+    /// stamp to check against: an unstamped value's age is unknown, so it never counts as
+    /// fresh.
+    pub fn oracle_max_staleness<T: TypeHash + Pod>(
+        &self,
+        current_slot: u64,
+        max_staleness: u64,
+    ) -> Option<&T> {
+        let age = current_slot.saturating_sub(self.last_update_slot()?);
+        if age > max_staleness {
+            return None;
+        }
+        self.oracle()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TWAP_OFFSET;
+
+    #[test]
+    fn test_update_stamp_layout() {
+        assert_eq!(UPDATE_STAMP_OFFSET, 223);
+        assert_eq!(core::mem::align_of::<UpdateStamp>(), 1);
+        assert_eq!(
+            TWAP_OFFSET + crate::TWAP_ACCUMULATOR_SIZE,
+            UPDATE_STAMP_OFFSET
+        );
+
+        assert_eq!(oracle_capacity(0), ORACLE_BYTES);
+        assert_eq!(oracle_capacity(FLAG_UPDATE_STAMP), UPDATE_STAMP_OFFSET);
+        assert_eq!(oracle_capacity(FLAG_TWAP), TWAP_OFFSET);
+        assert_eq!(oracle_capacity(FLAG_TWAP | FLAG_UPDATE_STAMP), TWAP_OFFSET);
+
+        let mut env = Envelope::zeroed();
+        assert!(env.update_stamp().is_none());
+        env.flags |= FLAG_UPDATE_STAMP;
+        env.update_stamp_mut().unwrap().unix_timestamp = I64Le::new(-2);
+        assert_eq!(
+            env.oracle_state.data[ORACLE_BYTES - 8..],
+            [0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]
+        );
+    }
+
+    #[test]
+    fn test_oracle_max_staleness() {
+        let mut env = Envelope::zeroed();
+        env.oracle_state.oracle_metadata = u64::METADATA;
+        env.oracle_state.data[..8].copy_from_slice(&7u64.to_le_bytes());
+        assert_eq!(env.oracle::<u64>(), Some(&7));
+        assert_eq!(
+            env.oracle_max_staleness::<u64>(100, u64::MAX),
+            None,
+            "unstamped"
+        );
+
+        env.flags |= FLAG_UPDATE_STAMP;
+        assert_eq!(env.last_update_slot(), None, "stamped, never written");
+        *env.update_stamp_mut().unwrap() = UpdateStamp {
+            slot: U64Le::new(90),
+            unix_timestamp: I64Le::new(1_700_000_000),
+        };
+        assert_eq!(env.last_update_slot(), Some(90));
+        assert_eq!(env.last_update_timestamp(), Some(1_700_000_000));
+
+        assert_eq!(env.oracle_max_staleness::<u64>(100, 10), Some(&7));
+        assert_eq!(env.oracle_max_staleness::<u64>(101, 10), None);
+        assert_eq!(
+            env.oracle_max_staleness::<u64>(50, 0),
+            Some(&7),
+            "clock behind stamp"
+        );
+        assert_eq!(env.oracle_max_staleness::<u32>(100, 10), None);
+    }
+
+    #[test]
+    fn test_last_live_slot() {
+        let mut env = Envelope::zeroed();
+        assert_eq!(env.last_live_slot(), None);
+
+        env.heartbeat_slot = 40;
+        assert_eq!(env.last_heartbeat_slot(), Some(40));
+        assert_eq!(env.last_live_slot(), Some(40), "unstamped, heartbeat only");
+
+        env.flags |= FLAG_UPDATE_STAMP;
+        env.update_stamp_mut().unwrap().slot = U64Le::new(30);
+        assert_eq!(env.last_live_slot(), Some(40));
+        env.update_stamp_mut().unwrap().slot = U64Le::new(50);
+        assert_eq!(env.last_live_slot(), Some(50));
+    }
+}
@@ -1,7 +1,7 @@
 #![cfg(feature = "derive")]
 
-use bytemuck::{Pod, Zeroable};
-use c_u_soon::{combine_hash, const_fnv1a, StructMetadata, TypeHash};
+use bytemuck::{CheckedBitPattern, NoUninit, Pod, Zeroable};
+use c_u_soon::{combine_hash, const_fnv1a, Envelope, StructMetadata, TypeHash};
 
 #[derive(Clone, Copy, Pod, Zeroable, TypeHash)]
 #[repr(C)]
@@ -134,3 +134,230 @@ fn metadata_size_matches_sizeof() {
 fn struct_metadata_of_helper() {
     assert_eq!(StructMetadata::of::<PairA>(), PairA::METADATA);
 }
+
+#[derive(Clone, Copy, Pod, Zeroable, TypeHash)]
+#[repr(C)]
+#[type_hash(xxh64)]
+struct PairXxh {
+    x: u32,
+    y: u32,
+}
+
+#[test]
+fn xxh64_scheme_formula() {
+    use c_u_soon::{combine_hash_xxh64, const_xxh64};
+    let expected = combine_hash_xxh64(
+        combine_hash_xxh64(const_xxh64(b"PairXxh"), u32::TYPE_HASH),
+        u32::TYPE_HASH,
+    );
+    assert_eq!(PairXxh::TYPE_HASH, expected);
+    assert_eq!(
+        PairXxh::METADATA,
+        StructMetadata::new_xxh64(core::mem::size_of::<PairXxh>() as u8, expected)
+    );
+    assert!(PairXxh::METADATA.has_xxh64_bit());
+}
+
+#[test]
+fn explicit_fnv1a_matches_default_formula() {
+    #[derive(Clone, Copy, Pod, Zeroable, TypeHash)]
+    #[repr(C)]
+    #[type_hash(fnv1a)]
+    struct Explicit {
+        x: u32,
+    }
+
+    let expected = combine_hash(const_fnv1a(b"Explicit"), u32::TYPE_HASH);
+    assert_eq!(Explicit::TYPE_HASH, expected);
+    assert_eq!(Explicit::METADATA, StructMetadata::new(4, expected));
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CheckedBitPattern, NoUninit, TypeHash)]
+#[repr(u8)]
+enum Status {
+    Trading,
+    Halted,
+    Auction = 4,
+}
+
+#[test]
+fn fieldless_enum_formula() {
+    let acc = combine_hash(const_fnv1a(b"Status"), const_fnv1a(b"u8"));
+    let acc = combine_hash(combine_hash(acc, const_fnv1a(b"Trading")), 0);
+    let acc = combine_hash(combine_hash(acc, const_fnv1a(b"Halted")), 1);
+    let expected = combine_hash(combine_hash(acc, const_fnv1a(b"Auction")), 4);
+    assert_eq!(Status::TYPE_HASH, expected);
+    assert_eq!(Status::METADATA, StructMetadata::new(1, expected));
+}
+
+#[test]
+fn enum_variants_and_repr_affect_hash() {
+    mod base {
+        #[allow(dead_code)]
+        #[derive(Clone, Copy, c_u_soon::TypeHash)]
+        #[repr(u8)]
+        pub enum Side {
+            Bid,
+            Ask,
+        }
+    }
+    mod swapped {
+        #[allow(dead_code)]
+        #[derive(Clone, Copy, c_u_soon::TypeHash)]
+        #[repr(u8)]
+        pub enum Side {
+            Ask,
+            Bid,
+        }
+    }
+    mod renumbered {
+        #[allow(dead_code)]
+        #[derive(Clone, Copy, c_u_soon::TypeHash)]
+        #[repr(u8)]
+        pub enum Side {
+            Bid,
+            Ask = 2,
+        }
+    }
+    mod wider {
+        #[allow(dead_code)]
+        #[derive(Clone, Copy, c_u_soon::TypeHash)]
+        #[repr(u16)]
+        pub enum Side {
+            Bid,
+            Ask,
+        }
+    }
+
+    let base = base::Side::TYPE_HASH;
+    assert_ne!(base, swapped::Side::TYPE_HASH);
+    assert_ne!(base, renumbered::Side::TYPE_HASH);
+    assert_ne!(base, wider::Side::TYPE_HASH);
+    assert_ne!(base::Side::METADATA, wider::Side::METADATA);
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, CheckedBitPattern, TypeHash)]
+#[repr(C, u8)]
+enum Quote {
+    Price { value: u32 },
+    Missing,
+}
+
+#[test]
+fn data_enum_hashes_payload_types() {
+    mod signed {
+        #[allow(dead_code)]
+        #[derive(Clone, Copy, c_u_soon::TypeHash)]
+        #[repr(C, u8)]
+        pub enum Quote {
+            Price { value: i32 },
+            Missing,
+        }
+    }
+
+    let acc = combine_hash(const_fnv1a(b"Quote"), const_fnv1a(b"C,u8"));
+    let acc = combine_hash(combine_hash(acc, const_fnv1a(b"Price")), 0);
+    let acc = combine_hash(acc, u32::TYPE_HASH);
+    let expected = combine_hash(combine_hash(acc, const_fnv1a(b"Missing")), 1);
+    assert_eq!(Quote::TYPE_HASH, expected);
+    assert_eq!(
+        Quote::METADATA.type_size() as usize,
+        core::mem::size_of::<Quote>()
+    );
+    assert_ne!(Quote::TYPE_HASH, signed::Quote::TYPE_HASH);
+}
+
+#[test]
+fn enum_read_rejects_unknown_discriminant() {
+    let mut env = Envelope::zeroed();
+    env.oracle_state.oracle_metadata = Status::METADATA;
+    env.oracle_state.data[0] = 4;
+    assert_eq!(env.oracle_validated::<Status>(), Some(&Status::Auction));
+    env.oracle_state.data[0] = 2;
+    assert_eq!(env.oracle_validated::<Status>(), None);
+
+    env.auxiliary_metadata = Quote::METADATA;
+    env.auxiliary_data[4..8].copy_from_slice(&7u32.to_le_bytes());
+    assert_eq!(
+        env.aux_validated::<Quote>(),
+        Some(&Quote::Price { value: 7 })
+    );
+    env.auxiliary_data[0] = 1;
+    assert_eq!(env.aux_validated::<Quote>(), Some(&Quote::Missing));
+    env.auxiliary_data[0] = 9;
+    assert_eq!(env.aux_validated::<Quote>(), None);
+    assert_eq!(env.oracle_validated::<Quote>(), None, "metadata mismatch");
+}
+
+#[derive(Clone, Copy, TypeHash)]
+#[repr(C)]
+struct Window<T: Copy, const N: usize> {
+    samples: [T; N],
+    head: u32,
+}
+
+#[test]
+fn generic_params_fold_into_hash() {
+    let acc = combine_hash(const_fnv1a(b"Window"), u32::TYPE_HASH);
+    let acc = combine_hash(acc, 4);
+    let acc = combine_hash(acc, <[u32; 4]>::TYPE_HASH);
+    let expected = combine_hash(acc, u32::TYPE_HASH);
+    assert_eq!(<Window<u32, 4>>::TYPE_HASH, expected);
+    assert_eq!(
+        <Window<u32, 4>>::METADATA,
+        StructMetadata::new(core::mem::size_of::<Window<u32, 4>>() as u8, expected)
+    );
+
+    assert_ne!(<Window<u32, 4>>::TYPE_HASH, <Window<i32, 4>>::TYPE_HASH);
+    assert_ne!(<Window<u32, 4>>::TYPE_HASH, <Window<u32, 8>>::TYPE_HASH);
+    assert_eq!(
+        <Window<u64, 4>>::METADATA.type_size() as usize,
+        core::mem::size_of::<Window<u64, 4>>()
+    );
+}
+
+/// A fixed-point value whose scale lives only in the type, as a const parameter.
+#[derive(Clone, Copy, TypeHash)]
+#[repr(C)]
+struct Scaled<const DECIMALS: u8> {
+    value: u64,
+}
+
+unsafe impl<const DECIMALS: u8> Zeroable for Scaled<DECIMALS> {}
+unsafe impl<const DECIMALS: u8> Pod for Scaled<DECIMALS> {}
+
+#[test]
+fn const_param_outside_layout_still_changes_metadata() {
+    assert_eq!(
+        core::mem::size_of::<Scaled<6>>(),
+        core::mem::size_of::<Scaled<9>>()
+    );
+    assert_ne!(<Scaled<6>>::METADATA, <Scaled<9>>::METADATA);
+
+    let mut env = Envelope::zeroed();
+    env.oracle_state.oracle_metadata = <Scaled<6>>::METADATA;
+    env.oracle_state.data[..8].copy_from_slice(&1_500_000u64.to_le_bytes());
+    assert_eq!(env.oracle::<Scaled<6>>().map(|s| s.value), Some(1_500_000));
+    assert!(env.oracle::<Scaled<9>>().is_none());
+}
+
+#[derive(Clone, Copy, TypeHash)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Slot<T: Copy> {
+    Empty,
+    Full(T),
+}
+
+#[test]
+fn generic_enum_hashes_param_after_repr() {
+    let acc = combine_hash(const_fnv1a(b"Slot"), const_fnv1a(b"u8"));
+    let acc = combine_hash(acc, u32::TYPE_HASH);
+    let acc = combine_hash(combine_hash(acc, const_fnv1a(b"Empty")), 0);
+    let acc = combine_hash(combine_hash(acc, const_fnv1a(b"Full")), 1);
+    let expected = combine_hash(acc, u32::TYPE_HASH);
+    assert_eq!(<Slot<u32>>::TYPE_HASH, expected);
+    assert_ne!(<Slot<u32>>::TYPE_HASH, <Slot<i32>>::TYPE_HASH);
+}
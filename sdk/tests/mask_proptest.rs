@@ -0,0 +1,121 @@
+//! Property tests pinning the u64-chunked [`Mask`] update and range checks, and the
+//! word-level [`BitVec256::is_write_allowed`], to byte-by-byte references.
+//!
+//! Masks mix writable, blocked, sealed, and arbitrary non-canonical bytes, since only `0x00`
+//! grants a write. Sources mostly copy `dest` with a few changed bytes, so updates that only
+//! rewrite blocked bytes with their current value, which the chunked path must accept, are
+//! common. `fuzz/fuzz_targets/masked_update.rs` covers the same ground with coverage guidance.
+
+use c_u_soon::{BitVec256, Mask, AUX_DATA_SIZE, MASK_SIZE, SEALED_MASK_BYTE};
+use proptest::prelude::*;
+
+/// The masked update as specified: fails if `src` does not fit at `offset` or would change a
+/// byte whose mask byte is not `0x00`, otherwise copies `src` into place.
+fn reference_update(
+    mask: &[u8; MASK_SIZE],
+    dest: &[u8; AUX_DATA_SIZE],
+    offset: usize,
+    src: &[u8],
+) -> Option<[u8; AUX_DATA_SIZE]> {
+    if offset + src.len() > AUX_DATA_SIZE {
+        return None;
+    }
+    let mut out = *dest;
+    for (i, &byte) in src.iter().enumerate() {
+        if byte != dest[offset + i] && mask[offset + i] != 0x00 {
+            return None;
+        }
+        out[offset + i] = byte;
+    }
+    Some(out)
+}
+
+fn mask_bytes() -> impl Strategy<Value = [u8; MASK_SIZE]> {
+    let byte = prop_oneof![
+        4 => Just(0x00),
+        4 => Just(0xFF),
+        1 => Just(SEALED_MASK_BYTE),
+        1 => any::<u8>(),
+    ];
+    prop::collection::vec(byte, MASK_SIZE).prop_map(|v| v.try_into().unwrap())
+}
+
+fn aux_bytes() -> impl Strategy<Value = [u8; AUX_DATA_SIZE]> {
+    prop::collection::vec(any::<u8>(), AUX_DATA_SIZE).prop_map(|v| v.try_into().unwrap())
+}
+
+/// `(offset, src)` where `src` may run past the end of the aux data. Each source byte is
+/// either `dest`'s byte at that position or, one time in eight, a random one.
+fn update(dest: [u8; AUX_DATA_SIZE]) -> impl Strategy<Value = (usize, Vec<u8>)> {
+    (0..=AUX_DATA_SIZE, 0..=AUX_DATA_SIZE).prop_flat_map(move |(offset, len)| {
+        prop::collection::vec((prop::bool::weighted(0.125), any::<u8>()), len).prop_map(
+            move |bytes| {
+                let src = bytes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(random, byte))| match dest.get(offset + i) {
+                        Some(&current) if !random => current,
+                        _ => byte,
+                    })
+                    .collect();
+                (offset, src)
+            },
+        )
+    })
+}
+
+proptest! {
+    #[test]
+    fn apply_masked_update_matches_reference(
+        mask in mask_bytes(),
+        (dest, (offset, src)) in aux_bytes().prop_flat_map(|dest| (Just(dest), update(dest))),
+    ) {
+        let expected = reference_update(&mask, &dest, offset, &src);
+        let mask = Mask::from(mask);
+        prop_assert_eq!(mask.check_masked_update(&dest, offset, &src), expected.is_some());
+
+        let mut actual = dest;
+        let applied = mask.apply_masked_update(&mut actual, offset, &src);
+        prop_assert_eq!(applied, expected.is_some());
+        prop_assert_eq!(actual, expected.unwrap_or(dest));
+    }
+
+    #[test]
+    fn write_allowed_iff_full_rewrite_applies(
+        mask in mask_bytes(),
+        dest in aux_bytes(),
+        offset in 0..=AUX_DATA_SIZE,
+        len in 0..=AUX_DATA_SIZE,
+    ) {
+        let mask = Mask::from(mask);
+        let len = len.min(AUX_DATA_SIZE - offset);
+        let src: Vec<u8> = dest[offset..offset + len].iter().map(|b| !b).collect();
+        let mut actual = dest;
+        prop_assert_eq!(
+            mask.apply_masked_update(&mut actual, offset, &src),
+            mask.is_write_allowed(offset, len)
+        );
+    }
+
+    #[test]
+    fn is_write_allowed_matches_reference(
+        mask in mask_bytes(),
+        offset in 0..=MASK_SIZE + 8,
+        len in 0..=MASK_SIZE + 8,
+    ) {
+        let expected = len == 0
+            || (offset + len <= AUX_DATA_SIZE && mask[offset..offset + len].iter().all(|&b| b == 0));
+        prop_assert_eq!(Mask::from(mask).is_write_allowed(offset, len), expected);
+    }
+
+    #[test]
+    fn bitvec_is_write_allowed_matches_get_bit(
+        bits in prop::array::uniform32(prop_oneof![3 => Just(0xFF), 1 => any::<u8>()]),
+        offset in 0..=MASK_SIZE + 8,
+        len in 0..=MASK_SIZE + 8,
+    ) {
+        let bits = BitVec256::from_bytes(bits);
+        let expected = offset + len <= MASK_SIZE && (offset..offset + len).all(|i| bits.get_bit(i));
+        prop_assert_eq!(bits.is_write_allowed(offset, len), expected);
+    }
+}
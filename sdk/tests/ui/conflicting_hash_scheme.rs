@@ -0,0 +1,12 @@
+// A type has exactly one hash scheme; naming two must not silently pick one.
+
+use c_u_soon::TypeHash;
+
+#[derive(TypeHash)]
+#[type_hash(fnv1a, xxh64)]
+#[repr(C)]
+struct Both {
+    x: u32,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// Without a repr the discriminant size is up to the compiler, so the layout is not stable.
+
+use c_u_soon::TypeHash;
+
+#[derive(TypeHash)]
+enum Side {
+    Bid,
+    Ask,
+}
+
+fn main() {}
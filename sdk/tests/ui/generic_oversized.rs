@@ -0,0 +1,14 @@
+// Size is only known per instantiation, so a generic type checks it in METADATA: an
+// instantiation over 255 bytes must fail where its metadata is used.
+
+use c_u_soon::{StructMetadata, TypeHash};
+
+#[derive(Clone, Copy, TypeHash)]
+#[repr(C)]
+struct Big<const N: usize> {
+    bytes: [u8; N],
+}
+
+const _: StructMetadata = <Big<256> as TypeHash>::METADATA;
+
+fn main() {}
@@ -0,0 +1,11 @@
+// Envelope data is plain bytes, so a type that borrows cannot be a payload.
+
+use c_u_soon::TypeHash;
+
+#[derive(TypeHash)]
+#[repr(C)]
+struct Borrowed<'a> {
+    value: &'a u32,
+}
+
+fn main() {}
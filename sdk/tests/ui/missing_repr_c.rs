@@ -0,0 +1,10 @@
+// The hash describes fields in declaration order, which only matches memory under #[repr(C)].
+
+use c_u_soon::TypeHash;
+
+#[derive(TypeHash)]
+struct NoRepr {
+    x: u32,
+}
+
+fn main() {}
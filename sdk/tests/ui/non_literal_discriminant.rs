@@ -0,0 +1,14 @@
+// Discriminants are folded into the hash at expansion time, so they must be literals.
+
+use c_u_soon::TypeHash;
+
+const BASE: u8 = 4;
+
+#[derive(TypeHash)]
+#[repr(u8)]
+enum Level {
+    Low,
+    High = BASE,
+}
+
+fn main() {}
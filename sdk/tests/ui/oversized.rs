@@ -0,0 +1,13 @@
+// StructMetadata stores the size in one byte, so a type over 255 bytes must fail to compile
+// where it is defined, not only where its METADATA is used.
+
+use c_u_soon::TypeHash;
+
+#[derive(Clone, Copy, TypeHash)]
+#[repr(C)]
+struct Big {
+    a: [u8; 128],
+    b: [u8; 128],
+}
+
+fn main() {}
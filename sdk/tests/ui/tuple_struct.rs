@@ -0,0 +1,9 @@
+// Tuple structs are rejected along with unit structs; the derive expects named fields.
+
+use c_u_soon::TypeHash;
+
+#[derive(TypeHash)]
+#[repr(C)]
+struct Pair(u32, u32);
+
+fn main() {}
@@ -0,0 +1,12 @@
+// A union has no field order to hash.
+
+use c_u_soon::TypeHash;
+
+#[derive(TypeHash)]
+#[repr(C)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
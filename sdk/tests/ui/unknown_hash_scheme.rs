@@ -0,0 +1,12 @@
+// Only `fnv1a` and `xxh64` are supported.
+
+use c_u_soon::TypeHash;
+
+#[derive(TypeHash)]
+#[type_hash(sha256)]
+#[repr(C)]
+struct Unknown {
+    x: u32,
+}
+
+fn main() {}
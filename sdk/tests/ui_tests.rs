@@ -0,0 +1,16 @@
+#![cfg(feature = "derive")]
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/conflicting_hash_scheme.rs");
+    t.compile_fail("tests/ui/enum_without_repr.rs");
+    t.compile_fail("tests/ui/generic_oversized.rs");
+    t.compile_fail("tests/ui/lifetime_param.rs");
+    t.compile_fail("tests/ui/missing_repr_c.rs");
+    t.compile_fail("tests/ui/non_literal_discriminant.rs");
+    t.compile_fail("tests/ui/oversized.rs");
+    t.compile_fail("tests/ui/tuple_struct.rs");
+    t.compile_fail("tests/ui/union.rs");
+    t.compile_fail("tests/ui/unknown_hash_scheme.rs");
+}
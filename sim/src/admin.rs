@@ -0,0 +1,393 @@
+//! Delegation, flag, and assertion instructions.
+
+use bytemuck::Zeroable;
+use c_u_soon::{
+    oracle_capacity, ConflationWindow, Envelope, Mask, OracleState, Permission, StructMetadata,
+    TwapAccumulator, UpdateStamp, AUX_DATA_SIZE, FLAG_CONFLATION, FLAG_MUTATION_GUARD,
+    FLAG_PERMANENT, FLAG_TWAP, FLAG_UPDATE_STAMP, MASK_SIZE, MAX_DELEGATE_SEEDS, ORACLE_BYTES,
+};
+use c_u_soon_client::{aux_hash, CuSoonError};
+use solana_address::Address;
+
+use crate::{verify_authority, verify_delegation_authority, Context, Signer};
+
+/// `set_delegated_program::with_validated_delegate`, with the instruction's canonical-mask
+/// check. `verify` checks the delegate.
+fn install_delegate<F>(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    program_bitmask: &[u8; MASK_SIZE],
+    user_bitmask: &[u8; MASK_SIZE],
+    verify: F,
+) -> Result<(), CuSoonError>
+where
+    F: FnOnce(&Signer) -> Result<(), CuSoonError>,
+{
+    if !program_bitmask
+        .iter()
+        .chain(user_bitmask)
+        .all(|&b| Permission::from_byte(b).is_some())
+    {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    verify_authority(envelope, ctx)?;
+
+    if envelope.delegation_authority != Address::zeroed() {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    if !envelope.program_bitmask.is_all_blocked() || !envelope.user_bitmask.is_all_blocked() {
+        return Err(CuSoonError::InvalidAccountData);
+    }
+
+    let delegate = &ctx.delegation_authority;
+    verify(delegate)?;
+
+    if delegate.address == Address::zeroed() {
+        return Err(CuSoonError::InvalidAccountData);
+    }
+
+    envelope.delegation_authority = delegate.address;
+    envelope.program_bitmask =
+        Mask::from(*program_bitmask).with_sealed_from(&envelope.program_bitmask);
+    envelope.user_bitmask = Mask::from(*user_bitmask).with_sealed_from(&envelope.user_bitmask);
+    Ok(())
+}
+
+/// `set_delegated_program::process`.
+pub(crate) fn set_delegated_program(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    program_bitmask: &[u8; MASK_SIZE],
+    user_bitmask: &[u8; MASK_SIZE],
+) -> Result<(), CuSoonError> {
+    install_delegate(envelope, ctx, program_bitmask, user_bitmask, |delegate| {
+        if !delegate.is_signer {
+            return Err(CuSoonError::MissingRequiredSignature);
+        }
+        Ok(())
+    })
+}
+
+/// `set_delegated_program::process_pda`, with the instruction's seed and mask checks.
+pub(crate) fn set_delegated_pda(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    program_id: &Address,
+    seeds: &[Vec<u8>],
+    bump: u8,
+    program_bitmask: &[u8; MASK_SIZE],
+    user_bitmask: &[u8; MASK_SIZE],
+) -> Result<(), CuSoonError> {
+    if seeds.len() > MAX_DELEGATE_SEEDS || seeds.iter().any(|seed| seed.len() > 32) {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+    install_delegate(envelope, ctx, program_bitmask, user_bitmask, |delegate| {
+        let bump = [bump];
+        let mut seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        seeds.push(&bump);
+        match Address::create_program_address(&seeds, program_id) {
+            Ok(derived) if derived == delegate.address => Ok(()),
+            _ => Err(CuSoonError::InvalidSeeds),
+        }
+    })
+}
+
+/// `clear_delegation::process`: drop the delegate and wipe the oracle and aux state, apart
+/// from sealed aux bytes.
+pub(crate) fn clear_delegation(envelope: &mut Envelope, ctx: &Context) -> Result<(), CuSoonError> {
+    verify_authority(envelope, ctx)?;
+
+    if envelope.delegation_authority == Address::zeroed() {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    verify_delegation_authority(&ctx.delegation_authority, &envelope.delegation_authority)?;
+
+    envelope.delegation_authority = Address::zeroed();
+    envelope.write_hook = Address::zeroed();
+    for (i, byte) in envelope.auxiliary_data.iter_mut().enumerate() {
+        if !envelope.program_bitmask.is_sealed(i) {
+            *byte = 0;
+        }
+    }
+    envelope.program_bitmask = Mask::ALL_BLOCKED.with_sealed_from(&envelope.program_bitmask);
+    envelope.user_bitmask = Mask::ALL_BLOCKED.with_sealed_from(&envelope.user_bitmask);
+    envelope.oracle_state = OracleState::zeroed();
+    envelope.auxiliary_metadata = StructMetadata::ZERO;
+    envelope.record_aux_write(0xFF);
+    Ok(())
+}
+
+/// `transfer_delegation::process`, with the instruction's canonical-mask check: swap the
+/// delegate and masks, keeping the oracle and aux state.
+pub(crate) fn transfer_delegation(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    new_delegation_authority: &Signer,
+    program_bitmask: &[u8; MASK_SIZE],
+    user_bitmask: &[u8; MASK_SIZE],
+) -> Result<(), CuSoonError> {
+    if !program_bitmask
+        .iter()
+        .chain(user_bitmask)
+        .all(|&b| Permission::from_byte(b).is_some())
+    {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    verify_authority(envelope, ctx)?;
+
+    if envelope.delegation_authority == Address::zeroed() {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    verify_delegation_authority(&ctx.delegation_authority, &envelope.delegation_authority)?;
+
+    if !new_delegation_authority.is_signer {
+        return Err(CuSoonError::MissingRequiredSignature);
+    }
+
+    if new_delegation_authority.address == Address::zeroed() {
+        return Err(CuSoonError::InvalidAccountData);
+    }
+
+    envelope.delegation_authority = new_delegation_authority.address;
+    envelope.write_hook = Address::zeroed();
+    envelope.program_bitmask =
+        Mask::from(*program_bitmask).with_sealed_from(&envelope.program_bitmask);
+    envelope.user_bitmask = Mask::from(*user_bitmask).with_sealed_from(&envelope.user_bitmask);
+    Ok(())
+}
+
+/// `set_write_hook::process`: store or remove the delegation's write hook.
+pub(crate) fn set_write_hook(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    notify_on_authority_write: bool,
+    hook_program: &Address,
+) -> Result<(), CuSoonError> {
+    verify_authority(envelope, ctx)?;
+
+    if !envelope.has_delegation() {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    verify_delegation_authority(&ctx.delegation_authority, &envelope.delegation_authority)?;
+
+    envelope.write_hook = if notify_on_authority_write {
+        *hook_program
+    } else {
+        Address::zeroed()
+    };
+    Ok(())
+}
+
+/// `set_permanent::process`.
+pub(crate) fn set_permanent(envelope: &mut Envelope, ctx: &Context) -> Result<(), CuSoonError> {
+    verify_authority(envelope, ctx)?;
+
+    if envelope.is_permanent() {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    envelope.flags |= FLAG_PERMANENT;
+    Ok(())
+}
+
+/// `set_publisher::process`.
+pub(crate) fn set_publisher(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    publisher: &Address,
+) -> Result<(), CuSoonError> {
+    verify_authority(envelope, ctx)?;
+
+    envelope.publisher = *publisher;
+    Ok(())
+}
+
+/// `set_paused::process`.
+pub(crate) fn set_paused(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    paused: bool,
+) -> Result<(), CuSoonError> {
+    verify_authority(envelope, ctx)?;
+
+    envelope.paused = paused as u8;
+    Ok(())
+}
+
+/// `seal_auxiliary_range::process`, with the instruction's range check.
+pub(crate) fn seal_auxiliary_range(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    offset: u16,
+    len: u16,
+) -> Result<(), CuSoonError> {
+    if len == 0 || offset as usize + len as usize > AUX_DATA_SIZE {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    verify_authority(envelope, ctx)?;
+
+    envelope
+        .program_bitmask
+        .seal_range(offset as usize, len as usize);
+    envelope
+        .user_bitmask
+        .seal_range(offset as usize, len as usize);
+    Ok(())
+}
+
+/// `set_mutation_guard::process`.
+pub(crate) fn set_mutation_guard(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    allow_multi: bool,
+) -> Result<(), CuSoonError> {
+    verify_authority(envelope, ctx)?;
+
+    if allow_multi {
+        envelope.flags &= !FLAG_MUTATION_GUARD;
+    } else {
+        envelope.flags |= FLAG_MUTATION_GUARD;
+    }
+    Ok(())
+}
+
+/// `set_twap::process`.
+pub(crate) fn set_twap(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    enabled: bool,
+) -> Result<(), CuSoonError> {
+    verify_authority(envelope, ctx)?;
+
+    if !enabled {
+        envelope.flags &= !FLAG_TWAP;
+        return Ok(());
+    }
+    if envelope.has_twap() {
+        return Ok(());
+    }
+
+    let type_size = envelope.oracle_state.oracle_metadata.type_size() as usize;
+    if !(8..=oracle_capacity(envelope.flags | FLAG_TWAP)).contains(&type_size) {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    envelope.flags |= FLAG_TWAP;
+    if let Some(accumulator) = envelope.twap_mut() {
+        *accumulator = TwapAccumulator::default();
+    }
+    Ok(())
+}
+
+/// `set_oracle_metadata::process`.
+pub(crate) fn set_oracle_metadata(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    oracle_metadata: u64,
+    auxiliary_metadata: u64,
+) -> Result<(), CuSoonError> {
+    verify_authority(envelope, ctx)?;
+
+    if envelope.has_delegation() {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    let oracle_metadata = StructMetadata::from_raw(oracle_metadata);
+    let auxiliary_metadata = StructMetadata::from_raw(auxiliary_metadata);
+
+    if oracle_metadata != envelope.oracle_state.oracle_metadata {
+        let min_size = if envelope.flags & (FLAG_TWAP | FLAG_CONFLATION) != 0 {
+            8
+        } else {
+            0
+        };
+        let type_size = oracle_metadata.type_size() as usize;
+        if !(min_size..=oracle_capacity(envelope.flags)).contains(&type_size) {
+            return Err(CuSoonError::InvalidArgument);
+        }
+        envelope.oracle_state.oracle_metadata = oracle_metadata;
+        envelope.oracle_state.data = [0; ORACLE_BYTES];
+    }
+
+    if auxiliary_metadata != envelope.auxiliary_metadata {
+        envelope.auxiliary_metadata = auxiliary_metadata;
+        for (i, byte) in envelope.auxiliary_data.iter_mut().enumerate() {
+            if !envelope.program_bitmask.is_sealed(i) {
+                *byte = 0;
+            }
+        }
+        envelope.record_aux_write(0xFF);
+    }
+    Ok(())
+}
+
+/// `set_update_stamp::process`.
+pub(crate) fn set_update_stamp(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    enabled: bool,
+) -> Result<(), CuSoonError> {
+    verify_authority(envelope, ctx)?;
+
+    if !enabled {
+        envelope.flags &= !FLAG_UPDATE_STAMP;
+        return Ok(());
+    }
+    if envelope.has_update_stamp() {
+        return Ok(());
+    }
+
+    let type_size = envelope.oracle_state.oracle_metadata.type_size() as usize;
+    if type_size > oracle_capacity(envelope.flags | FLAG_UPDATE_STAMP) {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    envelope.flags |= FLAG_UPDATE_STAMP;
+    if let Some(stamp) = envelope.update_stamp_mut() {
+        *stamp = UpdateStamp::default();
+    }
+    Ok(())
+}
+
+/// `set_conflation::process`.
+pub(crate) fn set_conflation(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    enabled: bool,
+) -> Result<(), CuSoonError> {
+    verify_authority(envelope, ctx)?;
+
+    if !enabled {
+        envelope.flags &= !FLAG_CONFLATION;
+        return Ok(());
+    }
+    if envelope.has_conflation() {
+        return Ok(());
+    }
+
+    let type_size = envelope.oracle_state.oracle_metadata.type_size() as usize;
+    if !(8..=oracle_capacity(envelope.flags | FLAG_CONFLATION)).contains(&type_size) {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    envelope.flags |= FLAG_CONFLATION;
+    if let Some(window) = envelope.conflation_mut() {
+        *window = ConflationWindow::default();
+    }
+    Ok(())
+}
+
+/// `assert_aux_hash::process`. Reads the envelope only.
+pub(crate) fn assert_aux_hash(envelope: &Envelope, expected: &[u8; 32]) -> Result<(), CuSoonError> {
+    if &aux_hash(envelope) != expected {
+        return Err(CuSoonError::InvalidArgument);
+    }
+    Ok(())
+}
@@ -0,0 +1,394 @@
+//! Auxiliary writes: `update_auxiliary*` and `apply_ranges`.
+
+use bytemuck::Zeroable;
+use c_u_soon::{aux_blocks_touched, ConstraintRegion, Envelope, Mask, AUX_DATA_SIZE};
+use c_u_soon_client::CuSoonError;
+use c_u_soon_instruction::{ranges_overlap, WriteRange, WriteSpecV2, MAX_WRITE_RANGES};
+use solana_address::Address;
+
+use crate::{
+    check_metadata, check_paused, constraint_table, enforce_constraints_after,
+    verify_delegation_authority, Context,
+};
+
+/// `(authority_sequence, program_sequence)` of a force write.
+pub(crate) type ForceSequences = (u64, u64);
+
+/// `validate_and_apply_single`.
+pub(crate) fn apply_single(
+    aux_data: &mut [u8; AUX_DATA_SIZE],
+    mask: &Mask,
+    type_size: usize,
+    offset: u8,
+    data: &[u8],
+) -> Result<u8, CuSoonError> {
+    if data.is_empty() {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+    let off = offset as usize;
+    let end = off + data.len();
+    if end > type_size {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+    if !mask.check_masked_update(aux_data, off, data) {
+        return Err(CuSoonError::InvalidArgument);
+    }
+    aux_data[off..end].copy_from_slice(data);
+    Ok(aux_blocks_touched(off, data.len()))
+}
+
+/// `validate_and_apply`: bounds-check every range, then mask-check every range, then write.
+pub(crate) fn apply_ranges<R: WriteRange>(
+    aux_data: &mut [u8; AUX_DATA_SIZE],
+    mask: &Mask,
+    type_size: usize,
+    ranges: &[R],
+) -> Result<u8, CuSoonError> {
+    if ranges.len() > MAX_WRITE_RANGES {
+        return Err(CuSoonError::TooManyRanges);
+    }
+    for spec in ranges {
+        if spec.bytes().is_empty() || spec.start() + spec.bytes().len() > type_size {
+            return Err(CuSoonError::InvalidInstructionData);
+        }
+    }
+    for spec in ranges {
+        if !mask.check_masked_update(aux_data, spec.start(), spec.bytes()) {
+            return Err(CuSoonError::InvalidArgument);
+        }
+    }
+    let mut blocks = 0;
+    for spec in ranges {
+        let off = spec.start();
+        aux_data[off..off + spec.bytes().len()].copy_from_slice(spec.bytes());
+        blocks |= aux_blocks_touched(off, spec.bytes().len());
+    }
+    Ok(blocks)
+}
+
+/// The multi-range part of `SlowPathInstruction::validate`, checked before dispatch.
+pub(crate) fn validate_ranges<R: WriteRange>(ranges: &[R]) -> Result<(), CuSoonError> {
+    if ranges.is_empty()
+        || ranges.len() > c_u_soon::MAX_AUX_STRUCT_SIZE
+        || ranges.iter().any(|spec| spec.bytes().is_empty())
+        || (ranges.len() <= MAX_WRITE_RANGES && ranges_overlap(ranges))
+    {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+    Ok(())
+}
+
+/// [`validate_ranges`] for `WriteSpecV2`, whose ranges must also end within the aux region.
+pub(crate) fn validate_ranges_v2(ranges: &[WriteSpecV2]) -> Result<(), CuSoonError> {
+    validate_ranges(ranges)?;
+    if ranges.iter().any(|spec| spec.end() > AUX_DATA_SIZE) {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+    Ok(())
+}
+
+/// `update_auxiliary::process`.
+pub(crate) fn authority_full(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    metadata: u64,
+    sequence: u64,
+    data: &[u8],
+) -> Result<(), CuSoonError> {
+    if !ctx.authority.is_signer {
+        return Err(CuSoonError::MissingRequiredSignature);
+    }
+
+    let meta = check_metadata(envelope.auxiliary_metadata, metadata)?;
+
+    if data.len() != meta.type_size() as usize {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    if envelope.authority != ctx.authority.address {
+        return Err(CuSoonError::IncorrectAuthority);
+    }
+
+    check_paused(envelope)?;
+
+    if sequence <= envelope.authority_aux_sequence {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    if !envelope.has_delegation() {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    let before = envelope.auxiliary_data;
+
+    if !envelope
+        .user_bitmask
+        .apply_masked_update(&mut envelope.auxiliary_data, 0, data)
+    {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    let table = constraint_table(ctx, envelope, ConstraintRegion::Aux)?;
+    enforce_constraints_after(
+        ctx,
+        table,
+        ConstraintRegion::Aux,
+        &before,
+        &envelope.auxiliary_data,
+    )?;
+
+    envelope.record_aux_write(aux_blocks_touched(0, data.len()));
+    envelope.authority_aux_sequence = sequence;
+    Ok(())
+}
+
+/// `update_auxiliary_multi_range::with_validated_authority`. `apply` gets the aux data,
+/// `user_bitmask`, and the type size, and returns the blocks it wrote.
+pub(crate) fn authority_ranges<F>(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    metadata: u64,
+    sequence: u64,
+    apply: F,
+) -> Result<(), CuSoonError>
+where
+    F: FnOnce(&mut [u8; AUX_DATA_SIZE], &Mask, usize) -> Result<u8, CuSoonError>,
+{
+    if !ctx.authority.is_signer {
+        return Err(CuSoonError::MissingRequiredSignature);
+    }
+
+    let meta = check_metadata(envelope.auxiliary_metadata, metadata)?;
+
+    if envelope.authority != ctx.authority.address {
+        return Err(CuSoonError::IncorrectAuthority);
+    }
+
+    check_paused(envelope)?;
+
+    if sequence <= envelope.authority_aux_sequence {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    if !envelope.has_delegation() {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    let before = envelope.auxiliary_data;
+
+    let blocks = apply(
+        &mut envelope.auxiliary_data,
+        &envelope.user_bitmask,
+        meta.type_size() as usize,
+    )?;
+
+    let table = constraint_table(ctx, envelope, ConstraintRegion::Aux)?;
+    enforce_constraints_after(
+        ctx,
+        table,
+        ConstraintRegion::Aux,
+        &before,
+        &envelope.auxiliary_data,
+    )?;
+
+    envelope.record_aux_write(blocks);
+    envelope.authority_aux_sequence = sequence;
+    Ok(())
+}
+
+/// Delegate checks shared by the delegated writes, up to and including the sequence.
+fn verify_delegated(
+    envelope: &Envelope,
+    ctx: &Context,
+    metadata: u64,
+    sequence: u64,
+    data_len: Option<usize>,
+) -> Result<usize, CuSoonError> {
+    let meta = check_metadata(envelope.auxiliary_metadata, metadata)?;
+    let type_size = meta.type_size() as usize;
+
+    if data_len.is_some_and(|len| len != type_size) {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    if envelope.delegation_authority == Address::zeroed() {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    verify_delegation_authority(&ctx.delegation_authority, &envelope.delegation_authority)?;
+
+    check_paused(envelope)?;
+
+    if sequence <= envelope.program_aux_sequence {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+    Ok(type_size)
+}
+
+/// `update_auxiliary_delegated::process`.
+pub(crate) fn delegated_full(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    metadata: u64,
+    sequence: u64,
+    data: &[u8],
+) -> Result<(), CuSoonError> {
+    verify_delegated(envelope, ctx, metadata, sequence, Some(data.len()))?;
+
+    let before = envelope.auxiliary_data;
+
+    if !envelope
+        .program_bitmask
+        .apply_masked_update(&mut envelope.auxiliary_data, 0, data)
+    {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    let table = constraint_table(ctx, envelope, ConstraintRegion::Aux)?;
+    enforce_constraints_after(
+        ctx,
+        table,
+        ConstraintRegion::Aux,
+        &before,
+        &envelope.auxiliary_data,
+    )?;
+
+    envelope.record_aux_write(aux_blocks_touched(0, data.len()));
+    envelope.program_aux_sequence = sequence;
+    Ok(())
+}
+
+/// `update_auxiliary_delegated_multi_range::with_validated_delegation`. `apply` gets the
+/// aux data, `program_bitmask`, and the type size, and returns the blocks it wrote.
+pub(crate) fn delegated_ranges<F>(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    metadata: u64,
+    sequence: u64,
+    apply: F,
+) -> Result<(), CuSoonError>
+where
+    F: FnOnce(&mut [u8; AUX_DATA_SIZE], &Mask, usize) -> Result<u8, CuSoonError>,
+{
+    let type_size = verify_delegated(envelope, ctx, metadata, sequence, None)?;
+
+    let before = envelope.auxiliary_data;
+
+    let blocks = apply(
+        &mut envelope.auxiliary_data,
+        &envelope.program_bitmask,
+        type_size,
+    )?;
+
+    let table = constraint_table(ctx, envelope, ConstraintRegion::Aux)?;
+    enforce_constraints_after(
+        ctx,
+        table,
+        ConstraintRegion::Aux,
+        &before,
+        &envelope.auxiliary_data,
+    )?;
+
+    envelope.record_aux_write(blocks);
+    envelope.program_aux_sequence = sequence;
+    Ok(())
+}
+
+/// `update_auxiliary_force::with_validated_force`. `apply` gets the aux data, a mask of just
+/// the sealed bytes, and the type size, and returns the blocks it wrote.
+fn force<F>(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    metadata: u64,
+    (authority_sequence, program_sequence): ForceSequences,
+    apply: F,
+) -> Result<(), CuSoonError>
+where
+    F: FnOnce(&mut [u8; AUX_DATA_SIZE], &Mask, usize) -> Result<u8, CuSoonError>,
+{
+    if !ctx.authority.is_signer {
+        return Err(CuSoonError::MissingRequiredSignature);
+    }
+
+    let meta = check_metadata(envelope.auxiliary_metadata, metadata)?;
+
+    if envelope.authority != ctx.authority.address {
+        return Err(CuSoonError::IncorrectAuthority);
+    }
+
+    if envelope.delegation_authority == Address::zeroed() {
+        return Err(CuSoonError::InvalidArgument);
+    }
+
+    verify_delegation_authority(&ctx.delegation_authority, &envelope.delegation_authority)?;
+
+    check_paused(envelope)?;
+
+    if authority_sequence <= envelope.authority_aux_sequence
+        || program_sequence <= envelope.program_aux_sequence
+    {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    let table = constraint_table(ctx, envelope, ConstraintRegion::Aux)?;
+
+    let before = envelope.auxiliary_data;
+
+    let sealed = envelope.program_bitmask.sealed_only();
+    let blocks = apply(
+        &mut envelope.auxiliary_data,
+        &sealed,
+        meta.type_size() as usize,
+    )?;
+
+    enforce_constraints_after(
+        ctx,
+        table,
+        ConstraintRegion::Aux,
+        &before,
+        &envelope.auxiliary_data,
+    )?;
+
+    envelope.record_aux_write(blocks);
+    envelope.authority_aux_sequence = authority_sequence;
+    envelope.program_aux_sequence = program_sequence;
+    Ok(())
+}
+
+/// `update_auxiliary_force::process`: overwrite the aux data and zero the rest, leaving
+/// sealed bytes unchanged.
+pub(crate) fn force_full(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    metadata: u64,
+    sequences: ForceSequences,
+    data: &[u8],
+) -> Result<(), CuSoonError> {
+    force(envelope, ctx, metadata, sequences, |aux, sealed, size| {
+        if data.len() != size {
+            return Err(CuSoonError::InvalidInstructionData);
+        }
+        let tail = [0u8; AUX_DATA_SIZE];
+        if !sealed.check_masked_update(aux, data.len(), &tail[data.len()..])
+            || !sealed.apply_masked_update(aux, 0, data)
+        {
+            return Err(CuSoonError::InvalidArgument);
+        }
+        aux[data.len()..].fill(0);
+        Ok(0xFF)
+    })
+}
+
+/// `update_auxiliary_force::process_multi_range`: ranges with neither mask consulted, except
+/// for sealed bytes.
+pub(crate) fn force_ranges<R: WriteRange>(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    metadata: u64,
+    sequences: ForceSequences,
+    ranges: &[R],
+) -> Result<(), CuSoonError> {
+    force(envelope, ctx, metadata, sequences, |aux, sealed, size| {
+        apply_ranges(aux, sealed, size, ranges)
+    })
+}
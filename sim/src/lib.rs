@@ -0,0 +1,1408 @@
+//! Pure-Rust model of the c_u_soon program's envelope state machine.
+//!
+//! [`apply`] runs one [`Transition`] against an [`Envelope`] with the same checks, in the
+//! same order, and with the same errors as the on-chain handlers, so integrators can
+//! property-test publishers and delegated programs without Mollusk or an SBF build:
+//!
+//! ```
+//! use c_u_soon::{Envelope, TypeHash};
+//! use c_u_soon_sim::{apply, Context, Transition};
+//! use solana_address::Address;
+//!
+//! let authority = Address::new_from_array([1; 32]);
+//! let mut envelope: Envelope = bytemuck::Zeroable::zeroed();
+//! envelope.authority = authority;
+//! envelope.oracle_state.oracle_metadata = u64::METADATA;
+//!
+//! let ctx = Context::signed_by(authority);
+//! let update = Transition::OracleUpdate {
+//!     metadata: u64::METADATA.as_u64(),
+//!     sequence: 1,
+//!     payload: 42u64.to_le_bytes().to_vec(),
+//! };
+//! apply(&mut envelope, &ctx, &update).unwrap();
+//! assert_eq!(envelope.oracle::<u64>(), Some(&42));
+//! assert!(apply(&mut envelope, &ctx, &update).is_err(), "sequence must increase");
+//! ```
+//!
+//! The accounts a transition would carry are described by [`Context`]: who signed in each
+//! role, the envelope's address, the publisher set and constraint table when the
+//! instruction needs them, and the `Clock` used for the oracle trailers.
+//!
+//! Only the envelope itself is modeled. Instructions that create, close, or resize
+//! accounts, or write other PDAs (range guards, groups, labels, delegate schemas), are not
+//! transitions. Account ownership and sizes are assumed valid. Envelopes with a range guard
+//! or the mutation guard are written as if the guard passed, since both depend on the rest
+//! of the transaction. Likewise, authority aux writes to an envelope with a write hook are
+//! applied as if the hook program accepted them; it is not run.
+
+mod admin;
+mod aux;
+mod oracle;
+
+use c_u_soon::{
+    CompactMask, ConstraintRegion, ConstraintTable, Envelope, Mask, MetadataDiff, PublisherSet,
+    StructMetadata, COMPACT_MASK_SIZE, MASK_SIZE,
+};
+use c_u_soon_client::CuSoonError;
+use c_u_soon_instruction::{WriteSpec, WriteSpecV2};
+use solana_address::Address;
+
+/// An account passed in a signer role, and whether it signed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Signer {
+    pub address: Address,
+    pub is_signer: bool,
+}
+
+impl Signer {
+    /// `address`, signing.
+    pub fn signed(address: Address) -> Self {
+        Self {
+            address,
+            is_signer: true,
+        }
+    }
+
+    /// `address`, passed without a signature.
+    pub fn unsigned(address: Address) -> Self {
+        Self {
+            address,
+            is_signer: false,
+        }
+    }
+}
+
+/// The `Clock` sysvar fields the program reads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Clock {
+    pub slot: u64,
+    pub unix_timestamp: i64,
+}
+
+/// The accounts and sysvars around a transition.
+///
+/// `publisher_set` and `constraints` stand for the accounts of the same name; leaving one
+/// out where the instruction needs it fails with [`CuSoonError::NotEnoughAccountKeys`].
+/// Both are checked to name `envelope_address`, as on-chain.
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    pub envelope_address: Address,
+    pub authority: Signer,
+    pub delegation_authority: Signer,
+    pub publishers: Vec<Signer>,
+    pub publisher_set: Option<PublisherSet>,
+    pub constraints: Option<ConstraintTable>,
+    pub clock: Clock,
+}
+
+impl Context {
+    /// A context where `authority` signs and no other account is passed.
+    pub fn signed_by(authority: Address) -> Self {
+        Self {
+            authority: Signer::signed(authority),
+            ..Self::default()
+        }
+    }
+
+    /// A context where both `authority` and `delegation_authority` sign.
+    pub fn signed_by_both(authority: Address, delegation_authority: Address) -> Self {
+        Self {
+            delegation_authority: Signer::signed(delegation_authority),
+            ..Self::signed_by(authority)
+        }
+    }
+}
+
+/// An envelope write or administration instruction, with the fields of its wire format.
+///
+/// Aux writes signed by the authority use [`Context::authority`], delegated writes use
+/// [`Context::delegation_authority`], and the force writes use both.
+#[derive(Debug, Clone)]
+pub enum Transition {
+    /// Two-account fast-path oracle write, signed by the authority or the envelope's
+    /// publisher in [`Context::authority`].
+    OracleUpdate {
+        metadata: u64,
+        sequence: u64,
+        payload: Vec<u8>,
+    },
+    /// Oracle write signed by [`Context::publishers`] (`PUBLISHER_UPDATE_TAG`).
+    PublisherUpdate {
+        metadata: u64,
+        sequence: u64,
+        payload: Vec<u8>,
+    },
+    /// `UPDATE_AUX_TAG`.
+    UpdateAuxiliary {
+        metadata: u64,
+        sequence: u64,
+        data: Vec<u8>,
+    },
+    /// `UPDATE_AUX_DELEGATED_TAG`.
+    UpdateAuxiliaryDelegated {
+        metadata: u64,
+        sequence: u64,
+        data: Vec<u8>,
+    },
+    /// `UPDATE_AUX_FORCE_TAG`.
+    UpdateAuxiliaryForce {
+        metadata: u64,
+        authority_sequence: u64,
+        program_sequence: u64,
+        data: Vec<u8>,
+    },
+    /// `UPDATE_AUX_RANGE_TAG`.
+    UpdateAuxiliaryRange {
+        metadata: u64,
+        sequence: u64,
+        offset: u8,
+        data: Vec<u8>,
+    },
+    /// `UPDATE_AUX_DELEGATED_RANGE_TAG`.
+    UpdateAuxiliaryDelegatedRange {
+        metadata: u64,
+        sequence: u64,
+        offset: u8,
+        data: Vec<u8>,
+    },
+    UpdateAuxiliaryMultiRange {
+        metadata: u64,
+        sequence: u64,
+        ranges: Vec<WriteSpec>,
+    },
+    UpdateAuxiliaryDelegatedMultiRange {
+        metadata: u64,
+        sequence: u64,
+        ranges: Vec<WriteSpec>,
+    },
+    UpdateAuxiliaryMultiRangeForce {
+        metadata: u64,
+        authority_sequence: u64,
+        program_sequence: u64,
+        ranges: Vec<WriteSpec>,
+    },
+    /// The `*V2` multi-range forms reject any range ending past the aux region before
+    /// dispatch, then write as their `WriteSpec` counterparts.
+    UpdateAuxiliaryMultiRangeV2 {
+        metadata: u64,
+        sequence: u64,
+        ranges: Vec<WriteSpecV2>,
+    },
+    UpdateAuxiliaryDelegatedMultiRangeV2 {
+        metadata: u64,
+        sequence: u64,
+        ranges: Vec<WriteSpecV2>,
+    },
+    UpdateAuxiliaryMultiRangeForceV2 {
+        metadata: u64,
+        authority_sequence: u64,
+        program_sequence: u64,
+        ranges: Vec<WriteSpecV2>,
+    },
+    /// Raw mask bytes, as carried by the instruction; non-canonical bytes are rejected.
+    SetDelegatedProgram {
+        program_bitmask: [u8; MASK_SIZE],
+        user_bitmask: [u8; MASK_SIZE],
+    },
+    /// Packed masks, one bit per aux byte; every bit pattern is canonical.
+    SetDelegatedProgramCompact {
+        program_bitmask: [u8; COMPACT_MASK_SIZE],
+        user_bitmask: [u8; COMPACT_MASK_SIZE],
+    },
+    /// The delegate is `ctx.delegation_authority`, checked as the PDA of `program_id` at
+    /// `[...seeds, bump]`; it need not sign.
+    SetDelegatedPda {
+        program_id: Address,
+        seeds: Vec<Vec<u8>>,
+        bump: u8,
+        program_bitmask: [u8; MASK_SIZE],
+        user_bitmask: [u8; MASK_SIZE],
+    },
+    ClearDelegation,
+    /// The current delegate signs in [`Context::delegation_authority`]; the new one is
+    /// carried here, since the context has a single delegate slot.
+    TransferDelegation {
+        new_delegation_authority: Signer,
+        program_bitmask: [u8; MASK_SIZE],
+        user_bitmask: [u8; MASK_SIZE],
+    },
+    /// The hook program account is carried here and assumed executable. It is ignored when
+    /// `notify_on_authority_write` is `false`.
+    SetWriteHook {
+        notify_on_authority_write: bool,
+        hook_program: Address,
+    },
+    SetPermanent,
+    SetMutationGuard {
+        allow_multi: bool,
+    },
+    SetTwap {
+        enabled: bool,
+    },
+    SetUpdateStamp {
+        enabled: bool,
+    },
+    SetConflation {
+        enabled: bool,
+    },
+    SetOracleMetadata {
+        oracle_metadata: u64,
+        auxiliary_metadata: u64,
+    },
+    AssertAuxHash {
+        expected: [u8; 32],
+    },
+    /// An all-zero `publisher` removes it.
+    SetPublisher {
+        publisher: Address,
+    },
+    SetPaused {
+        paused: bool,
+    },
+    SealAuxiliaryRange {
+        offset: u16,
+        len: u16,
+    },
+    /// Permissionless: the cranker in [`Context::authority`] need not be the authority.
+    Heartbeat {
+        sequence: u64,
+    },
+}
+
+/// Apply `transition` to `envelope`.
+///
+/// On error `envelope` is left unchanged, as a failed transaction leaves the account.
+pub fn apply(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    transition: &Transition,
+) -> Result<(), CuSoonError> {
+    let mut next = *envelope;
+    step(&mut next, ctx, transition)?;
+    *envelope = next;
+    Ok(())
+}
+
+fn step(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    transition: &Transition,
+) -> Result<(), CuSoonError> {
+    match transition {
+        Transition::OracleUpdate {
+            metadata,
+            sequence,
+            payload,
+        } => oracle::fast_path(envelope, ctx, *metadata, *sequence, payload),
+        Transition::PublisherUpdate {
+            metadata,
+            sequence,
+            payload,
+        } => oracle::publisher_update(envelope, ctx, *metadata, *sequence, payload),
+        Transition::UpdateAuxiliary {
+            metadata,
+            sequence,
+            data,
+        } => aux::authority_full(envelope, ctx, *metadata, *sequence, data),
+        Transition::UpdateAuxiliaryDelegated {
+            metadata,
+            sequence,
+            data,
+        } => aux::delegated_full(envelope, ctx, *metadata, *sequence, data),
+        Transition::UpdateAuxiliaryForce {
+            metadata,
+            authority_sequence,
+            program_sequence,
+            data,
+        } => aux::force_full(
+            envelope,
+            ctx,
+            *metadata,
+            (*authority_sequence, *program_sequence),
+            data,
+        ),
+        Transition::UpdateAuxiliaryRange {
+            metadata,
+            sequence,
+            offset,
+            data,
+        } => aux::authority_ranges(
+            envelope,
+            ctx,
+            *metadata,
+            *sequence,
+            |aux_data, mask, type_size| aux::apply_single(aux_data, mask, type_size, *offset, data),
+        ),
+        Transition::UpdateAuxiliaryDelegatedRange {
+            metadata,
+            sequence,
+            offset,
+            data,
+        } => aux::delegated_ranges(
+            envelope,
+            ctx,
+            *metadata,
+            *sequence,
+            |aux_data, mask, type_size| aux::apply_single(aux_data, mask, type_size, *offset, data),
+        ),
+        Transition::UpdateAuxiliaryMultiRange {
+            metadata,
+            sequence,
+            ranges,
+        } => {
+            aux::validate_ranges(ranges)?;
+            aux::authority_ranges(
+                envelope,
+                ctx,
+                *metadata,
+                *sequence,
+                |aux_data, mask, type_size| aux::apply_ranges(aux_data, mask, type_size, ranges),
+            )
+        }
+        Transition::UpdateAuxiliaryDelegatedMultiRange {
+            metadata,
+            sequence,
+            ranges,
+        } => {
+            aux::validate_ranges(ranges)?;
+            aux::delegated_ranges(
+                envelope,
+                ctx,
+                *metadata,
+                *sequence,
+                |aux_data, mask, type_size| aux::apply_ranges(aux_data, mask, type_size, ranges),
+            )
+        }
+        Transition::UpdateAuxiliaryMultiRangeForce {
+            metadata,
+            authority_sequence,
+            program_sequence,
+            ranges,
+        } => {
+            aux::validate_ranges(ranges)?;
+            aux::force_ranges(
+                envelope,
+                ctx,
+                *metadata,
+                (*authority_sequence, *program_sequence),
+                ranges,
+            )
+        }
+        Transition::UpdateAuxiliaryMultiRangeV2 {
+            metadata,
+            sequence,
+            ranges,
+        } => {
+            aux::validate_ranges_v2(ranges)?;
+            aux::authority_ranges(
+                envelope,
+                ctx,
+                *metadata,
+                *sequence,
+                |aux_data, mask, type_size| aux::apply_ranges(aux_data, mask, type_size, ranges),
+            )
+        }
+        Transition::UpdateAuxiliaryDelegatedMultiRangeV2 {
+            metadata,
+            sequence,
+            ranges,
+        } => {
+            aux::validate_ranges_v2(ranges)?;
+            aux::delegated_ranges(
+                envelope,
+                ctx,
+                *metadata,
+                *sequence,
+                |aux_data, mask, type_size| aux::apply_ranges(aux_data, mask, type_size, ranges),
+            )
+        }
+        Transition::UpdateAuxiliaryMultiRangeForceV2 {
+            metadata,
+            authority_sequence,
+            program_sequence,
+            ranges,
+        } => {
+            aux::validate_ranges_v2(ranges)?;
+            aux::force_ranges(
+                envelope,
+                ctx,
+                *metadata,
+                (*authority_sequence, *program_sequence),
+                ranges,
+            )
+        }
+        Transition::SetDelegatedProgram {
+            program_bitmask,
+            user_bitmask,
+        } => admin::set_delegated_program(envelope, ctx, program_bitmask, user_bitmask),
+        Transition::SetDelegatedProgramCompact {
+            program_bitmask,
+            user_bitmask,
+        } => {
+            let expand = |bits| <[u8; MASK_SIZE]>::from(Mask::from(CompactMask::from_bytes(bits)));
+            admin::set_delegated_program(
+                envelope,
+                ctx,
+                &expand(*program_bitmask),
+                &expand(*user_bitmask),
+            )
+        }
+        Transition::SetDelegatedPda {
+            program_id,
+            seeds,
+            bump,
+            program_bitmask,
+            user_bitmask,
+        } => admin::set_delegated_pda(
+            envelope,
+            ctx,
+            program_id,
+            seeds,
+            *bump,
+            program_bitmask,
+            user_bitmask,
+        ),
+        Transition::ClearDelegation => admin::clear_delegation(envelope, ctx),
+        Transition::TransferDelegation {
+            new_delegation_authority,
+            program_bitmask,
+            user_bitmask,
+        } => admin::transfer_delegation(
+            envelope,
+            ctx,
+            new_delegation_authority,
+            program_bitmask,
+            user_bitmask,
+        ),
+        Transition::SetWriteHook {
+            notify_on_authority_write,
+            hook_program,
+        } => admin::set_write_hook(envelope, ctx, *notify_on_authority_write, hook_program),
+        Transition::SetPermanent => admin::set_permanent(envelope, ctx),
+        Transition::SetMutationGuard { allow_multi } => {
+            admin::set_mutation_guard(envelope, ctx, *allow_multi)
+        }
+        Transition::SetTwap { enabled } => admin::set_twap(envelope, ctx, *enabled),
+        Transition::SetUpdateStamp { enabled } => admin::set_update_stamp(envelope, ctx, *enabled),
+        Transition::SetConflation { enabled } => admin::set_conflation(envelope, ctx, *enabled),
+        Transition::SetOracleMetadata {
+            oracle_metadata,
+            auxiliary_metadata,
+        } => admin::set_oracle_metadata(envelope, ctx, *oracle_metadata, *auxiliary_metadata),
+        Transition::AssertAuxHash { expected } => admin::assert_aux_hash(envelope, expected),
+        Transition::SetPublisher { publisher } => admin::set_publisher(envelope, ctx, publisher),
+        Transition::SetPaused { paused } => admin::set_paused(envelope, ctx, *paused),
+        Transition::SealAuxiliaryRange { offset, len } => {
+            admin::seal_auxiliary_range(envelope, ctx, *offset, *len)
+        }
+        Transition::Heartbeat { sequence } => oracle::heartbeat(envelope, ctx, *sequence),
+    }
+}
+
+/// `paused::check`.
+fn check_paused(envelope: &Envelope) -> Result<(), CuSoonError> {
+    if envelope.is_paused() {
+        return Err(CuSoonError::EnvelopePaused);
+    }
+    Ok(())
+}
+
+/// `metadata::check`: fail unless `given` equals `stored`, telling size and hash apart.
+fn check_metadata(stored: StructMetadata, given: u64) -> Result<StructMetadata, CuSoonError> {
+    let given = StructMetadata::from_raw(given);
+    if stored == given {
+        return Ok(given);
+    }
+    Err(match stored.diff(&given) {
+        MetadataDiff::Size { .. } => CuSoonError::MetadataSizeMismatch,
+        MetadataDiff::Hash | MetadataDiff::Same => CuSoonError::MetadataHashMismatch,
+    })
+}
+
+/// `constraints::split_table`: the constraint table, if `envelope` constrains `region`.
+fn constraint_table<'a>(
+    ctx: &'a Context,
+    envelope: &Envelope,
+    region: ConstraintRegion,
+) -> Result<Option<&'a ConstraintTable>, CuSoonError> {
+    if !envelope.is_constrained(region) {
+        return Ok(None);
+    }
+    ctx.constraints
+        .as_ref()
+        .map(Some)
+        .ok_or(CuSoonError::NotEnoughAccountKeys)
+}
+
+/// `constraints::enforce` for a table returned by [`constraint_table`].
+fn enforce_constraints(
+    ctx: &Context,
+    table: Option<&ConstraintTable>,
+    region: ConstraintRegion,
+    region_bytes: &[u8],
+) -> Result<(), CuSoonError> {
+    enforce_constraints_after(ctx, table, region, region_bytes, region_bytes)
+}
+
+/// `constraints::enforce_after` for a table returned by [`constraint_table`].
+fn enforce_constraints_after(
+    ctx: &Context,
+    table: Option<&ConstraintTable>,
+    region: ConstraintRegion,
+    before: &[u8],
+    after: &[u8],
+) -> Result<(), CuSoonError> {
+    let Some(table) = table else {
+        return Ok(());
+    };
+    if table.envelope != ctx.envelope_address {
+        return Err(CuSoonError::InvalidAccountData);
+    }
+    if table.first_violation_after(region, before, after).is_some() {
+        return Err(CuSoonError::ConstraintViolated);
+    }
+    Ok(())
+}
+
+/// `verify_delegation_authority` against the envelope's stored delegate.
+fn verify_delegation_authority(signer: &Signer, expected: &Address) -> Result<(), CuSoonError> {
+    if !signer.is_signer {
+        return Err(CuSoonError::MissingRequiredSignature);
+    }
+    if &signer.address != expected {
+        return Err(CuSoonError::IncorrectAuthority);
+    }
+    Ok(())
+}
+
+/// The authority signer check shared by every authority-signed handler.
+fn verify_authority(envelope: &Envelope, ctx: &Context) -> Result<(), CuSoonError> {
+    if !ctx.authority.is_signer {
+        return Err(CuSoonError::MissingRequiredSignature);
+    }
+    if envelope.authority != ctx.authority.address {
+        return Err(CuSoonError::IncorrectAuthority);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+    use c_u_soon::{
+        Constraint, ConstraintOp, Mask, TypeHash, AUX_DATA_SIZE, FLAG_CONSTRAINTS, FLAG_TWAP,
+        ORACLE_BYTES,
+    };
+
+    const AUTHORITY: Address = Address::new_from_array([1; 32]);
+    const DELEGATE: Address = Address::new_from_array([2; 32]);
+
+    fn envelope() -> Envelope {
+        let mut envelope = Envelope::zeroed();
+        envelope.authority = AUTHORITY;
+        envelope.oracle_state.oracle_metadata = u64::METADATA;
+        envelope.auxiliary_metadata = <[u8; 16]>::METADATA;
+        envelope.program_bitmask = Mask::ALL_BLOCKED;
+        envelope.user_bitmask = Mask::ALL_BLOCKED;
+        envelope
+    }
+
+    fn delegated() -> Envelope {
+        let mut envelope = envelope();
+        let mut program = Mask::ALL_BLOCKED;
+        let mut user = Mask::ALL_BLOCKED;
+        for i in 0..8 {
+            program.allow(i);
+            user.allow(i + 8);
+        }
+        let set = Transition::SetDelegatedProgram {
+            program_bitmask: bytemuck::cast(program),
+            user_bitmask: bytemuck::cast(user),
+        };
+        apply(
+            &mut envelope,
+            &Context::signed_by_both(AUTHORITY, DELEGATE),
+            &set,
+        )
+        .unwrap();
+        envelope
+    }
+
+    fn oracle_update(sequence: u64, payload: &[u8]) -> Transition {
+        Transition::OracleUpdate {
+            metadata: u64::METADATA.as_u64(),
+            sequence,
+            payload: payload.to_vec(),
+        }
+    }
+
+    fn aux_update(sequence: u64, data: [u8; 16]) -> Transition {
+        Transition::UpdateAuxiliary {
+            metadata: <[u8; 16]>::METADATA.as_u64(),
+            sequence,
+            data: data.to_vec(),
+        }
+    }
+
+    #[test]
+    fn fast_path_checks_signer_authority_metadata_and_sequence() {
+        let mut envelope = envelope();
+        let ctx = Context::signed_by(AUTHORITY);
+        let unsigned = Context {
+            authority: Signer::unsigned(AUTHORITY),
+            ..Context::default()
+        };
+        let intruder = Context::signed_by(DELEGATE);
+        let wrong_type = Transition::OracleUpdate {
+            metadata: u32::METADATA.as_u64(),
+            sequence: 1,
+            payload: vec![0; 4],
+        };
+
+        let update = oracle_update(2, &[7; 8]);
+        assert_eq!(
+            apply(&mut envelope, &unsigned, &update),
+            Err(CuSoonError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            apply(&mut envelope, &intruder, &update),
+            Err(CuSoonError::IncorrectAuthority)
+        );
+        assert_eq!(
+            apply(&mut envelope, &ctx, &wrong_type),
+            Err(CuSoonError::MetadataSizeMismatch)
+        );
+        apply(&mut envelope, &ctx, &update).unwrap();
+        assert_eq!(
+            apply(&mut envelope, &ctx, &oracle_update(2, &[8; 8])),
+            Err(CuSoonError::InvalidInstructionData)
+        );
+
+        apply(&mut envelope, &ctx, &oracle_update(3, &[9; 4])).unwrap();
+        assert_eq!(envelope.oracle_state.sequence, 3);
+        assert_eq!(envelope.oracle_state.data[..8], [9, 9, 9, 9, 7, 7, 7, 7]);
+    }
+
+    #[test]
+    fn first_fast_path_write_adopts_metadata() {
+        let mut envelope = envelope();
+        envelope.oracle_state.oracle_metadata = StructMetadata::ZERO;
+        let ctx = Context::signed_by(AUTHORITY);
+        let wrong_type = Transition::OracleUpdate {
+            metadata: u32::METADATA.as_u64(),
+            sequence: 2,
+            payload: vec![0; 4],
+        };
+
+        assert_eq!(
+            apply(&mut envelope, &ctx, &oracle_update(0, &[7; 8])),
+            Err(CuSoonError::InvalidInstructionData)
+        );
+        assert_eq!(envelope.oracle_state.oracle_metadata, StructMetadata::ZERO);
+
+        apply(&mut envelope, &ctx, &oracle_update(1, &[7; 8])).unwrap();
+        assert_eq!(envelope.oracle_state.oracle_metadata, u64::METADATA);
+        assert_eq!(
+            apply(&mut envelope, &ctx, &wrong_type),
+            Err(CuSoonError::MetadataSizeMismatch)
+        );
+        assert_eq!(
+            apply(&mut envelope, &ctx, &oracle_update(1, &[7; 8])),
+            Err(CuSoonError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn fast_path_updates_trailers_and_refuses_oracle_constraints() {
+        let mut envelope = envelope();
+        let mut ctx = Context::signed_by(AUTHORITY);
+        apply(&mut envelope, &ctx, &Transition::SetTwap { enabled: true }).unwrap();
+        assert_eq!(envelope.flags & FLAG_TWAP, FLAG_TWAP);
+
+        ctx.clock.slot = 10;
+        apply(&mut envelope, &ctx, &oracle_update(1, &5u64.to_le_bytes())).unwrap();
+        ctx.clock.slot = 20;
+        apply(&mut envelope, &ctx, &oracle_update(2, &7u64.to_le_bytes())).unwrap();
+        assert_eq!(envelope.twap().unwrap().cumulative(), 50);
+        assert_eq!(
+            apply(&mut envelope, &ctx, &oracle_update(3, &[1; 4])),
+            Err(CuSoonError::InvalidInstructionData),
+            "a TWAP payload carries the u64 value"
+        );
+
+        envelope.flags |= FLAG_CONSTRAINTS;
+        envelope.constrained_regions = ConstraintRegion::Oracle.bit();
+        assert_eq!(
+            apply(&mut envelope, &ctx, &oracle_update(3, &[1; 8])),
+            Err(CuSoonError::NotEnoughAccountKeys)
+        );
+    }
+
+    #[test]
+    fn oracle_updates_conflate_within_a_slot() {
+        let mut envelope = envelope();
+        let mut ctx = Context::signed_by(AUTHORITY);
+        apply(
+            &mut envelope,
+            &ctx,
+            &Transition::SetConflation { enabled: true },
+        )
+        .unwrap();
+
+        ctx.clock.slot = 10;
+        for (sequence, value) in [(1, 40u64), (2, 90), (3, 10), (4, 50)] {
+            apply(
+                &mut envelope,
+                &ctx,
+                &oracle_update(sequence, &value.to_le_bytes()),
+            )
+            .unwrap();
+        }
+        let window = envelope.conflation().unwrap().in_slot(10).unwrap();
+        assert_eq!((window.min.get(), window.max.get()), (10, 90));
+        assert_eq!(window.count.get(), 4);
+        assert_eq!(envelope.oracle_state.data[..8], 50u64.to_le_bytes());
+
+        ctx.clock.slot = 11;
+        apply(&mut envelope, &ctx, &oracle_update(5, &70u64.to_le_bytes())).unwrap();
+        let window = envelope.conflation().unwrap();
+        assert_eq!(
+            (window.min.get(), window.max.get(), window.count.get()),
+            (70, 70, 1)
+        );
+    }
+
+    #[test]
+    fn aux_writes_follow_each_signers_mask() {
+        let mut envelope = delegated();
+        let ctx = Context::signed_by_both(AUTHORITY, DELEGATE);
+
+        let mut data = [0u8; 16];
+        data[8..].fill(3);
+        apply(&mut envelope, &ctx, &aux_update(1, data)).unwrap();
+        assert_eq!(envelope.authority_aux_sequence, 1);
+        assert_eq!(envelope.aux_dirty_blocks, 0b1);
+
+        data[0] = 4;
+        assert_eq!(
+            apply(&mut envelope, &ctx, &aux_update(2, data)),
+            Err(CuSoonError::InvalidArgument),
+            "byte 0 belongs to the delegate"
+        );
+
+        let delegated_write = Transition::UpdateAuxiliaryDelegatedRange {
+            metadata: <[u8; 16]>::METADATA.as_u64(),
+            sequence: 1,
+            offset: 0,
+            data: vec![4; 8],
+        };
+        apply(&mut envelope, &ctx, &delegated_write).unwrap();
+        assert_eq!(envelope.program_aux_sequence, 1);
+        assert_eq!(
+            apply(&mut envelope, &ctx, &delegated_write),
+            Err(CuSoonError::InvalidInstructionData),
+            "stale program sequence"
+        );
+
+        let outsider = Context::signed_by_both(AUTHORITY, AUTHORITY);
+        let next = Transition::UpdateAuxiliaryDelegatedRange {
+            metadata: <[u8; 16]>::METADATA.as_u64(),
+            sequence: 2,
+            offset: 0,
+            data: vec![5; 8],
+        };
+        assert_eq!(
+            apply(&mut envelope, &outsider, &next),
+            Err(CuSoonError::IncorrectAuthority)
+        );
+        assert_eq!(envelope.auxiliary_data[..16], [[4; 8], [3; 8]].concat()[..]);
+    }
+
+    #[test]
+    fn multi_range_writes_are_all_or_nothing() {
+        let mut envelope = delegated();
+        let ctx = Context::signed_by_both(AUTHORITY, DELEGATE);
+        let before = envelope;
+
+        let ranges = |second: u8| Transition::UpdateAuxiliaryMultiRange {
+            metadata: <[u8; 16]>::METADATA.as_u64(),
+            sequence: 1,
+            ranges: vec![
+                WriteSpec {
+                    offset: 8,
+                    data: vec![1; 4],
+                },
+                WriteSpec {
+                    offset: second,
+                    data: vec![2; 4],
+                },
+            ],
+        };
+        assert_eq!(
+            apply(&mut envelope, &ctx, &ranges(0)),
+            Err(CuSoonError::InvalidArgument)
+        );
+        assert_eq!(
+            apply(&mut envelope, &ctx, &ranges(14)),
+            Err(CuSoonError::InvalidInstructionData),
+            "past the aux type"
+        );
+        assert_eq!(
+            apply(&mut envelope, &ctx, &ranges(10)),
+            Err(CuSoonError::InvalidInstructionData),
+            "overlapping ranges"
+        );
+        assert_eq!(bytemuck::bytes_of(&envelope), bytemuck::bytes_of(&before));
+
+        let force = Transition::UpdateAuxiliaryMultiRangeForce {
+            metadata: <[u8; 16]>::METADATA.as_u64(),
+            authority_sequence: 1,
+            program_sequence: 1,
+            ranges: vec![WriteSpec {
+                offset: 0,
+                data: vec![9; 16],
+            }],
+        };
+        apply(&mut envelope, &ctx, &force).unwrap();
+        assert_eq!(envelope.auxiliary_data[..16], [9; 16]);
+        assert_eq!(
+            (
+                envelope.authority_aux_sequence,
+                envelope.program_aux_sequence
+            ),
+            (1, 1)
+        );
+    }
+
+    #[test]
+    fn multi_range_v2_writes_check_the_aux_region() {
+        let mut envelope = delegated();
+        let ctx = Context::signed_by_both(AUTHORITY, DELEGATE);
+        let before = envelope;
+
+        let write = |offset: u16| Transition::UpdateAuxiliaryMultiRangeV2 {
+            metadata: <[u8; 16]>::METADATA.as_u64(),
+            sequence: 1,
+            ranges: vec![WriteSpecV2 {
+                offset,
+                data: vec![7; 4],
+            }],
+        };
+        assert_eq!(
+            apply(&mut envelope, &ctx, &write(AUX_DATA_SIZE as u16 - 2)),
+            Err(CuSoonError::InvalidInstructionData),
+            "past the aux region"
+        );
+        assert_eq!(
+            apply(&mut envelope, &ctx, &write(14)),
+            Err(CuSoonError::InvalidInstructionData),
+            "past the aux type"
+        );
+        assert_eq!(bytemuck::bytes_of(&envelope), bytemuck::bytes_of(&before));
+
+        apply(&mut envelope, &ctx, &write(12)).unwrap();
+        assert_eq!(envelope.auxiliary_data[12..16], [7; 4]);
+        assert_eq!(envelope.authority_aux_sequence, 1);
+    }
+
+    #[test]
+    fn constrained_aux_writes_need_a_matching_table() {
+        let mut envelope = delegated();
+        envelope.flags |= FLAG_CONSTRAINTS;
+        envelope.constrained_regions = ConstraintRegion::Aux.bit();
+
+        let mut table = ConstraintTable::zeroed();
+        table.count = 1;
+        table.entries[0] =
+            Constraint::against_const(ConstraintRegion::Aux, 8, 1, false, ConstraintOp::Le, 5);
+
+        let mut ctx = Context::signed_by_both(AUTHORITY, DELEGATE);
+        let mut data = [0u8; 16];
+        data[8] = 6;
+        assert_eq!(
+            apply(&mut envelope, &ctx, &aux_update(1, data)),
+            Err(CuSoonError::NotEnoughAccountKeys)
+        );
+        ctx.constraints = Some(table);
+        ctx.envelope_address = Address::new_from_array([3; 32]);
+        assert_eq!(
+            apply(&mut envelope, &ctx, &aux_update(1, data)),
+            Err(CuSoonError::InvalidAccountData),
+            "table for another envelope"
+        );
+        table.envelope = ctx.envelope_address;
+        ctx.constraints = Some(table);
+        assert_eq!(
+            apply(&mut envelope, &ctx, &aux_update(1, data)),
+            Err(CuSoonError::ConstraintViolated)
+        );
+        data[8] = 5;
+        apply(&mut envelope, &ctx, &aux_update(1, data)).unwrap();
+    }
+
+    #[test]
+    fn sequenced_aux_counter_never_decreases() {
+        let mut envelope = delegated();
+        envelope.flags |= FLAG_CONSTRAINTS;
+        envelope.constrained_regions = ConstraintRegion::Aux.bit();
+
+        let mut table = ConstraintTable::zeroed();
+        table.count = 1;
+        table.entries[0] = Constraint::sequenced(8);
+        let mut ctx = Context::signed_by_both(AUTHORITY, DELEGATE);
+        table.envelope = ctx.envelope_address;
+        ctx.constraints = Some(table);
+
+        let mut data = [0u8; 16];
+        data[8..16].copy_from_slice(&7u64.to_le_bytes());
+        apply(&mut envelope, &ctx, &aux_update(1, data)).unwrap();
+        apply(&mut envelope, &ctx, &aux_update(2, data)).unwrap();
+        data[8..16].copy_from_slice(&6u64.to_le_bytes());
+        assert_eq!(
+            apply(&mut envelope, &ctx, &aux_update(3, data)),
+            Err(CuSoonError::ConstraintViolated)
+        );
+        assert_eq!(envelope.auxiliary_data[8..16], 7u64.to_le_bytes());
+    }
+
+    #[test]
+    fn publisher_update_needs_threshold_signatures() {
+        let mut envelope = envelope();
+        let publishers = [[4u8; 32], [5; 32], [6; 32]].map(Address::new_from_array);
+        let mut set = PublisherSet::zeroed();
+        set.count = 3;
+        set.threshold = 2;
+        set.publishers[..3].copy_from_slice(&publishers);
+
+        let mut ctx = Context {
+            publishers: vec![Signer::signed(publishers[0])],
+            ..Context::default()
+        };
+        let update = Transition::PublisherUpdate {
+            metadata: u64::METADATA.as_u64(),
+            sequence: 1,
+            payload: 11u64.to_le_bytes().to_vec(),
+        };
+        assert_eq!(
+            apply(&mut envelope, &ctx, &update),
+            Err(CuSoonError::NotEnoughAccountKeys)
+        );
+        ctx.publisher_set = Some(set);
+        assert_eq!(
+            apply(&mut envelope, &ctx, &update),
+            Err(CuSoonError::IncorrectAuthority)
+        );
+        ctx.publishers.push(Signer::unsigned(publishers[2]));
+        assert_eq!(
+            apply(&mut envelope, &ctx, &update),
+            Err(CuSoonError::MissingRequiredSignature)
+        );
+        ctx.publishers[1].is_signer = true;
+        apply(&mut envelope, &ctx, &update).unwrap();
+        assert_eq!(envelope.oracle::<u64>(), Some(&11));
+    }
+
+    #[test]
+    fn clear_delegation_wipes_state() {
+        let mut envelope = delegated();
+        let ctx = Context::signed_by_both(AUTHORITY, DELEGATE);
+        apply(&mut envelope, &ctx, &oracle_update(1, &[1; 8])).unwrap();
+        assert_eq!(
+            apply(
+                &mut envelope,
+                &ctx,
+                &Transition::SetDelegatedProgram {
+                    program_bitmask: [0; MASK_SIZE],
+                    user_bitmask: [0; MASK_SIZE],
+                }
+            ),
+            Err(CuSoonError::InvalidArgument),
+            "already delegated"
+        );
+
+        apply(&mut envelope, &ctx, &Transition::ClearDelegation).unwrap();
+        assert!(!envelope.has_delegation());
+        assert!(envelope.program_bitmask.is_all_blocked());
+        assert_eq!(envelope.oracle_state.sequence, 0);
+        assert_eq!(envelope.auxiliary_metadata, StructMetadata::ZERO);
+        assert_eq!(
+            apply(&mut envelope, &ctx, &Transition::ClearDelegation),
+            Err(CuSoonError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn set_delegated_program_compact_matches_full_masks() {
+        let mut compact = envelope();
+        let mut full = envelope();
+        let ctx = Context::signed_by_both(AUTHORITY, DELEGATE);
+        let mut program = [0u8; COMPACT_MASK_SIZE];
+        program[0] = 0xFF;
+        apply(
+            &mut compact,
+            &ctx,
+            &Transition::SetDelegatedProgramCompact {
+                program_bitmask: program,
+                user_bitmask: [0; COMPACT_MASK_SIZE],
+            },
+        )
+        .unwrap();
+        let mut wire = Mask::ALL_BLOCKED;
+        wire.allow_range(0, 8);
+        apply(
+            &mut full,
+            &ctx,
+            &Transition::SetDelegatedProgram {
+                program_bitmask: wire.into(),
+                user_bitmask: Mask::ALL_BLOCKED.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(compact.program_bitmask, full.program_bitmask);
+        assert_eq!(compact.user_bitmask, full.user_bitmask);
+        assert_eq!(compact.delegation_authority, DELEGATE);
+    }
+
+    #[test]
+    fn write_hook_needs_both_signers_and_ends_with_the_delegation() {
+        let hook = Address::new_from_array([8; 32]);
+        let set = |notify_on_authority_write| Transition::SetWriteHook {
+            notify_on_authority_write,
+            hook_program: hook,
+        };
+        let both = Context::signed_by_both(AUTHORITY, DELEGATE);
+
+        let mut envelope = delegated();
+        assert_eq!(
+            apply(&mut envelope, &Context::signed_by(AUTHORITY), &set(true)),
+            Err(CuSoonError::MissingRequiredSignature)
+        );
+        apply(&mut envelope, &both, &set(true)).unwrap();
+        assert_eq!(envelope.write_hook(), Some(&hook));
+        apply(&mut envelope, &both, &set(false)).unwrap();
+        assert_eq!(envelope.write_hook(), None);
+
+        apply(&mut envelope, &both, &set(true)).unwrap();
+        apply(&mut envelope, &both, &Transition::ClearDelegation).unwrap();
+        assert_eq!(envelope.write_hook(), None);
+        assert_eq!(
+            apply(&mut envelope, &both, &set(true)),
+            Err(CuSoonError::InvalidArgument)
+        );
+
+        let mut envelope = delegated();
+        apply(&mut envelope, &both, &set(true)).unwrap();
+        let transfer = Transition::TransferDelegation {
+            new_delegation_authority: Signer::signed(Address::new_from_array([7; 32])),
+            program_bitmask: [0xFF; MASK_SIZE],
+            user_bitmask: [0; MASK_SIZE],
+        };
+        apply(&mut envelope, &both, &transfer).unwrap();
+        assert_eq!(envelope.write_hook(), None);
+    }
+
+    #[test]
+    fn set_oracle_metadata_zeroes_only_the_retyped_region() {
+        let mut envelope = envelope();
+        let ctx = Context::signed_by(AUTHORITY);
+        apply(&mut envelope, &ctx, &oracle_update(1, &[1; 8])).unwrap();
+        // Aux writes need a delegation, which blocks re-typing, so seed the aux directly.
+        envelope.auxiliary_data[..16].copy_from_slice(&[2; 16]);
+        envelope.authority_aux_sequence = 1;
+        let retype = |oracle: StructMetadata, aux: StructMetadata| Transition::SetOracleMetadata {
+            oracle_metadata: oracle.as_u64(),
+            auxiliary_metadata: aux.as_u64(),
+        };
+
+        apply(
+            &mut envelope,
+            &ctx,
+            &retype(i64::METADATA, <[u8; 16]>::METADATA),
+        )
+        .unwrap();
+        assert_eq!(envelope.oracle_state.oracle_metadata, i64::METADATA);
+        assert_eq!(envelope.oracle_state.data, [0; ORACLE_BYTES]);
+        assert_eq!(envelope.oracle_state.sequence, 1);
+        assert_eq!(envelope.aux::<[u8; 16]>(), Some(&[2; 16]));
+
+        apply(&mut envelope, &ctx, &retype(i64::METADATA, u32::METADATA)).unwrap();
+        assert_eq!(envelope.auxiliary_metadata, u32::METADATA);
+        assert!(envelope.auxiliary_data.iter().all(|&b| b == 0));
+        assert_eq!(envelope.authority_aux_sequence, 1);
+
+        let mut envelope = delegated();
+        assert_eq!(
+            apply(&mut envelope, &ctx, &retype(i64::METADATA, u32::METADATA)),
+            Err(CuSoonError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn transfer_delegation_keeps_state() {
+        let mut envelope = delegated();
+        let ctx = Context::signed_by_both(AUTHORITY, DELEGATE);
+        apply(&mut envelope, &ctx, &oracle_update(1, &[1; 8])).unwrap();
+        let next = Address::new_from_array([7; 32]);
+        let transfer = |new_delegation_authority| Transition::TransferDelegation {
+            new_delegation_authority,
+            program_bitmask: [0xFF; MASK_SIZE],
+            user_bitmask: [0; MASK_SIZE],
+        };
+
+        assert_eq!(
+            apply(&mut envelope, &ctx, &transfer(Signer::unsigned(next))),
+            Err(CuSoonError::MissingRequiredSignature)
+        );
+        let stranger = Context::signed_by_both(AUTHORITY, next);
+        assert_eq!(
+            apply(&mut envelope, &stranger, &transfer(Signer::signed(next))),
+            Err(CuSoonError::IncorrectAuthority)
+        );
+
+        let before = envelope;
+        apply(&mut envelope, &ctx, &transfer(Signer::signed(next))).unwrap();
+        assert_eq!(envelope.delegation_authority, next);
+        assert_eq!(envelope.program_bitmask, Mask::ALL_BLOCKED);
+        assert_eq!(envelope.user_bitmask, Mask::ALL_WRITABLE);
+        assert_eq!(envelope.oracle_state.sequence, 1);
+        assert_eq!(envelope.oracle_state.data, before.oracle_state.data);
+        assert_eq!(envelope.auxiliary_data, before.auxiliary_data);
+        assert_eq!(
+            apply(&mut envelope, &ctx, &Transition::ClearDelegation),
+            Err(CuSoonError::IncorrectAuthority),
+            "the old delegate is out"
+        );
+    }
+
+    #[test]
+    fn set_delegated_pda_checks_the_derivation_not_a_signature() {
+        let program_id = Address::new_from_array([7; 32]);
+        let (pda, bump) = Address::find_program_address(&[b"writer"], &program_id);
+        let set = |bump| Transition::SetDelegatedPda {
+            program_id,
+            seeds: vec![b"writer".to_vec()],
+            bump,
+            program_bitmask: [0; MASK_SIZE],
+            user_bitmask: [0xFF; MASK_SIZE],
+        };
+        let ctx = Context {
+            delegation_authority: Signer::unsigned(pda),
+            ..Context::signed_by(AUTHORITY)
+        };
+
+        let mut envelope = envelope();
+        let wrong = Context {
+            delegation_authority: Signer::unsigned(DELEGATE),
+            ..Context::signed_by(AUTHORITY)
+        };
+        assert_eq!(
+            apply(&mut envelope, &wrong, &set(bump)),
+            Err(CuSoonError::InvalidSeeds)
+        );
+        assert_eq!(
+            apply(&mut envelope, &ctx, &set(bump.wrapping_add(1))),
+            Err(CuSoonError::InvalidSeeds)
+        );
+        apply(&mut envelope, &ctx, &set(bump)).unwrap();
+        assert_eq!(envelope.delegation_authority, pda);
+        assert_eq!(envelope.program_bitmask, Mask::ALL_WRITABLE);
+
+        // Writes still need the PDA's signature.
+        assert_eq!(
+            apply(&mut envelope, &ctx, &Transition::ClearDelegation),
+            Err(CuSoonError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn set_permanent_is_one_way() {
+        let mut envelope = envelope();
+        let ctx = Context::signed_by(AUTHORITY);
+        apply(&mut envelope, &ctx, &Transition::SetPermanent).unwrap();
+        assert!(envelope.is_permanent());
+        assert_eq!(
+            apply(&mut envelope, &ctx, &Transition::SetPermanent),
+            Err(CuSoonError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn publisher_writes_oracle_but_cannot_administer() {
+        let publisher = Address::new_from_array([9; 32]);
+        let mut envelope = envelope();
+        let update = |sequence| Transition::OracleUpdate {
+            metadata: u64::METADATA.as_u64(),
+            sequence,
+            payload: 5u64.to_le_bytes().to_vec(),
+        };
+        let by_publisher = Context::signed_by(publisher);
+        assert_eq!(
+            apply(&mut envelope, &by_publisher, &update(1)),
+            Err(CuSoonError::IncorrectAuthority)
+        );
+
+        let set = Transition::SetPublisher { publisher };
+        assert_eq!(
+            apply(&mut envelope, &by_publisher, &set),
+            Err(CuSoonError::IncorrectAuthority)
+        );
+        apply(&mut envelope, &Context::signed_by(AUTHORITY), &set).unwrap();
+        apply(&mut envelope, &by_publisher, &update(1)).unwrap();
+        assert_eq!(envelope.oracle::<u64>(), Some(&5));
+        assert_eq!(
+            apply(&mut envelope, &by_publisher, &Transition::SetPermanent),
+            Err(CuSoonError::IncorrectAuthority)
+        );
+
+        let clear = Transition::SetPublisher {
+            publisher: Address::default(),
+        };
+        apply(&mut envelope, &Context::signed_by(AUTHORITY), &clear).unwrap();
+        assert_eq!(
+            apply(&mut envelope, &by_publisher, &update(2)),
+            Err(CuSoonError::IncorrectAuthority)
+        );
+    }
+
+    #[test]
+    fn paused_envelope_rejects_writes_and_keeps_delegation() {
+        let mut envelope = delegated();
+        let ctx = Context::signed_by(AUTHORITY);
+        let pause = |paused| Transition::SetPaused { paused };
+        assert_eq!(
+            apply(&mut envelope, &Context::signed_by(DELEGATE), &pause(true)),
+            Err(CuSoonError::IncorrectAuthority)
+        );
+        apply(&mut envelope, &ctx, &pause(true)).unwrap();
+        assert!(envelope.is_paused());
+
+        assert_eq!(
+            apply(&mut envelope, &ctx, &oracle_update(1, &[1; 8])),
+            Err(CuSoonError::EnvelopePaused)
+        );
+        assert_eq!(
+            apply(&mut envelope, &ctx, &aux_update(1, [1; 16])),
+            Err(CuSoonError::EnvelopePaused)
+        );
+        let delegated_write = Transition::UpdateAuxiliaryDelegated {
+            metadata: <[u8; 16]>::METADATA.as_u64(),
+            sequence: 1,
+            data: [[2; 8], [0; 8]].concat(),
+        };
+        let by_delegate = Context {
+            delegation_authority: Signer::signed(DELEGATE),
+            ..Context::default()
+        };
+        assert_eq!(
+            apply(&mut envelope, &by_delegate, &delegated_write),
+            Err(CuSoonError::EnvelopePaused)
+        );
+        // Signers are checked first.
+        assert_eq!(
+            apply(
+                &mut envelope,
+                &Context::signed_by(DELEGATE),
+                &oracle_update(1, &[1; 8])
+            ),
+            Err(CuSoonError::IncorrectAuthority)
+        );
+
+        apply(&mut envelope, &ctx, &pause(false)).unwrap();
+        assert_eq!(envelope.delegation_authority, DELEGATE);
+        apply(&mut envelope, &ctx, &oracle_update(1, &[1; 8])).unwrap();
+        apply(&mut envelope, &by_delegate, &delegated_write).unwrap();
+        assert_eq!(envelope.auxiliary_data[..8], [2; 8]);
+    }
+
+    #[test]
+    fn sealed_bytes_survive_every_write() {
+        let mut envelope = delegated();
+        let ctx = Context::signed_by(AUTHORITY);
+        let both = Context::signed_by_both(AUTHORITY, DELEGATE);
+        let mut data = [0; 16];
+        data[8..10].copy_from_slice(&[0xAB, 0xCD]);
+        apply(&mut envelope, &ctx, &aux_update(1, data)).unwrap();
+
+        let seal = |offset, len| Transition::SealAuxiliaryRange { offset, len };
+        assert_eq!(
+            apply(&mut envelope, &ctx, &seal(250, 7)),
+            Err(CuSoonError::InvalidInstructionData)
+        );
+        apply(&mut envelope, &ctx, &seal(8, 2)).unwrap();
+        assert!(envelope.program_bitmask.is_sealed(8) && envelope.user_bitmask.is_sealed(9));
+        assert!(!envelope.user_bitmask.is_sealed(10));
+
+        data[10] = 1;
+        apply(&mut envelope, &ctx, &aux_update(2, data)).unwrap();
+        data[8] = 0;
+        assert_eq!(
+            apply(&mut envelope, &ctx, &aux_update(3, data)),
+            Err(CuSoonError::InvalidArgument)
+        );
+
+        let force = |sequence, data: [u8; 16]| Transition::UpdateAuxiliaryForce {
+            metadata: <[u8; 16]>::METADATA.as_u64(),
+            authority_sequence: sequence,
+            program_sequence: sequence,
+            data: data.to_vec(),
+        };
+        assert_eq!(
+            apply(&mut envelope, &both, &force(3, data)),
+            Err(CuSoonError::InvalidArgument)
+        );
+        data[8] = 0xAB;
+        apply(&mut envelope, &both, &force(3, data)).unwrap();
+
+        apply(&mut envelope, &both, &Transition::ClearDelegation).unwrap();
+        assert_eq!(envelope.auxiliary_data[8..10], [0xAB, 0xCD]);
+        assert!(envelope.auxiliary_data[10..].iter().all(|&b| b == 0));
+        assert_eq!(
+            envelope
+                .program_bitmask
+                .permissions()
+                .filter(|p| p.is_writable())
+                .count(),
+            0
+        );
+        assert!(envelope.program_bitmask.is_sealed(8) && envelope.user_bitmask.is_sealed(9));
+
+        let set = Transition::SetDelegatedProgram {
+            program_bitmask: bytemuck::cast(Mask::ALL_WRITABLE),
+            user_bitmask: bytemuck::cast(Mask::ALL_WRITABLE),
+        };
+        apply(&mut envelope, &both, &set).unwrap();
+        assert!(envelope.program_bitmask.is_sealed(8) && envelope.user_bitmask.is_sealed(9));
+        assert!(envelope.program_bitmask.is_writable(10));
+    }
+
+    #[test]
+    fn heartbeat_stamps_unchanged_value_for_any_cranker() {
+        let mut envelope = envelope();
+        let mut cranker = Context::signed_by(DELEGATE);
+        cranker.clock.slot = 30;
+        apply(
+            &mut envelope,
+            &Context::signed_by(AUTHORITY),
+            &oracle_update(1, &[1; 8]),
+        )
+        .unwrap();
+
+        let heartbeat = |sequence| Transition::Heartbeat { sequence };
+        assert_eq!(
+            apply(&mut envelope, &cranker, &heartbeat(0)),
+            Err(CuSoonError::InvalidInstructionData),
+            "value replaced since"
+        );
+        apply(&mut envelope, &cranker, &heartbeat(1)).unwrap();
+        assert_eq!(envelope.last_heartbeat_slot(), Some(30));
+        assert_eq!(envelope.oracle_state.sequence, 1);
+
+        envelope.paused = 1;
+        assert_eq!(
+            apply(&mut envelope, &cranker, &heartbeat(1)),
+            Err(CuSoonError::EnvelopePaused)
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Oracle writes: the fast path, `publisher_update`, and `trailer::record`, and the
+//! `heartbeat` that vouches for an unchanged value.
+
+use c_u_soon::{ConstraintRegion, Envelope, StructMetadata, ORACLE_BYTES};
+use c_u_soon_client::CuSoonError;
+
+use crate::{check_metadata, check_paused, constraint_table, enforce_constraints, Clock, Context};
+
+/// `trailer::record`: fold the conflation window and the TWAP, and stamp the write, when
+/// those trailers are enabled.
+fn record_trailers(
+    envelope: &mut Envelope,
+    clock: &Clock,
+    payload: &[u8],
+) -> Result<(), CuSoonError> {
+    if !envelope.has_twap() && !envelope.has_update_stamp() && !envelope.has_conflation() {
+        return Ok(());
+    }
+
+    if payload.len() > envelope.oracle_capacity() {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    if envelope.has_twap() || envelope.has_conflation() {
+        let Some(value) = payload.get(..8) else {
+            return Err(CuSoonError::InvalidInstructionData);
+        };
+        let value = u64::from_le_bytes(value.try_into().unwrap());
+        if let Some(window) = envelope.conflation_mut() {
+            window.fold(value, clock.slot);
+        }
+        if let Some(accumulator) = envelope.twap_mut() {
+            accumulator.fold(value, clock.slot);
+        }
+    }
+
+    if let Some(stamp) = envelope.update_stamp_mut() {
+        stamp.slot = clock.slot.into();
+        stamp.unix_timestamp = clock.unix_timestamp.into();
+    }
+
+    Ok(())
+}
+
+/// Store `sequence` and `payload`. Payload bytes past its length keep their values.
+fn write(envelope: &mut Envelope, sequence: u64, payload: &[u8]) {
+    envelope.oracle_state.sequence = sequence;
+    envelope.oracle_state.data[..payload.len()].copy_from_slice(payload);
+}
+
+/// The two-account fast path.
+///
+/// The first write to an envelope without oracle metadata adopts `metadata`.
+///
+/// The program reads only the low byte of the instruction length, so a payload longer than
+/// [`ORACLE_BYTES`] is truncated there; the SDK never builds one, and here it fails with
+/// [`CuSoonError::InvalidInstructionData`].
+pub(crate) fn fast_path(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    metadata: u64,
+    sequence: u64,
+    payload: &[u8],
+) -> Result<(), CuSoonError> {
+    if payload.len() > ORACLE_BYTES {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    if !ctx.authority.is_signer {
+        return Err(CuSoonError::MissingRequiredSignature);
+    }
+
+    if envelope.authority != ctx.authority.address && !envelope.is_publisher(&ctx.authority.address)
+    {
+        return Err(CuSoonError::IncorrectAuthority);
+    }
+
+    check_paused(envelope)?;
+
+    if envelope.oracle_state.oracle_metadata != StructMetadata::ZERO {
+        check_metadata(envelope.oracle_state.oracle_metadata, metadata)?;
+    }
+
+    if sequence <= envelope.oracle_state.sequence {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    if envelope.is_constrained(ConstraintRegion::Oracle) {
+        return Err(CuSoonError::NotEnoughAccountKeys);
+    }
+
+    record_trailers(envelope, &ctx.clock, payload)?;
+    envelope.oracle_state.oracle_metadata = StructMetadata::from_raw(metadata);
+    write(envelope, sequence, payload);
+    Ok(())
+}
+
+/// `publisher_update::process`.
+pub(crate) fn publisher_update(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    metadata: u64,
+    sequence: u64,
+    payload: &[u8],
+) -> Result<(), CuSoonError> {
+    let Some(set) = &ctx.publisher_set else {
+        return Err(CuSoonError::NotEnoughAccountKeys);
+    };
+
+    if payload.len() > ORACLE_BYTES {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    let table = constraint_table(ctx, envelope, ConstraintRegion::Oracle)?;
+
+    if set.envelope != ctx.envelope_address {
+        return Err(CuSoonError::InvalidAccountData);
+    }
+
+    if ctx.publishers.iter().any(|p| !p.is_signer) {
+        return Err(CuSoonError::MissingRequiredSignature);
+    }
+    if !set.is_satisfied_by(ctx.publishers.iter().map(|p| &p.address)) {
+        return Err(CuSoonError::IncorrectAuthority);
+    }
+
+    check_metadata(envelope.oracle_state.oracle_metadata, metadata)?;
+    check_paused(envelope)?;
+
+    if sequence <= envelope.oracle_state.sequence {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    record_trailers(envelope, &ctx.clock, payload)?;
+    write(envelope, sequence, payload);
+
+    enforce_constraints(
+        ctx,
+        table,
+        ConstraintRegion::Oracle,
+        &envelope.oracle_state.data,
+    )
+}
+
+/// `heartbeat::process`. Any signer in [`Context::authority`] may crank it.
+pub(crate) fn heartbeat(
+    envelope: &mut Envelope,
+    ctx: &Context,
+    sequence: u64,
+) -> Result<(), CuSoonError> {
+    if !ctx.authority.is_signer {
+        return Err(CuSoonError::MissingRequiredSignature);
+    }
+
+    check_paused(envelope)?;
+
+    if envelope.oracle_state.sequence != sequence {
+        return Err(CuSoonError::InvalidInstructionData);
+    }
+
+    envelope.heartbeat_slot = ctx.clock.slot;
+    Ok(())
+}
@@ -31,11 +31,43 @@ use pinocchio::{
 ///   Attack: sequence <= envelope.oracle_state.sequence → c_u_soon rejects InvalidInstructionData
 ///
 /// 0x05: Echo
+///
+/// 0x06: ReentrantAuxWrite [metadata: u64 LE][seq: u64 LE][reentered: u8][data: rest]
+///   Accounts: [0]=delegation_auth(signer), [1]=envelope(writable), [2]=padding, [3]=c_u_soon_program,
+///   [4]=attacker_probe_program
+///   Attack: UpdateAuxiliaryDelegated, then (if reentered == 0) CPI back into this program, which
+///   repeats the same write from inside the first one → c_u_soon rejects InvalidInstructionData
+///
+/// 0x07: CloseAsDelegate
+///   Accounts: [0]=delegate(signer), [1]=envelope(writable), [2]=recipient(writable), [3]=c_u_soon_program
+///   Attack: Close signed by the envelope's delegate → c_u_soon rejects IncorrectAuthority
+///
+/// 0x08: SetDelegatedProgramToEnvelope
+///   Accounts: [0]=authority(signer), [1]=envelope(writable), [2]=c_u_soon_program
+///   Attack: SetDelegatedProgram with the envelope duplicated as delegation_auth, which no one can
+///   sign for → c_u_soon rejects MissingRequiredSignature
+///
+/// 0x09: MultiRangeSecondSpecBlocked [metadata: u64 LE][seq: u64 LE][offset_0: u8][len_0: u8]
+///   [offset_1: u8][len_1: u8]
+///   Accounts: [0]=delegation_auth(signer), [1]=envelope(writable), [2]=padding, [3]=c_u_soon_program
+///   Attack: UpdateAuxiliaryDelegatedMultiRange whose first range is writable and whose second
+///   changes a blocked byte → c_u_soon rejects InvalidArgument and writes neither range
+///
+/// Slow-path variants (0x02, 0x03, 0x06 to 0x09) take the c_u_soon program config as their
+/// last account, after those listed, and forward it, so c_u_soon reaches the check under
+/// attack.
 
 pinocchio::program_entrypoint!(process_instruction);
 pinocchio::default_allocator!();
 pinocchio::nostd_panic_handler!();
 
+/// Split off the c_u_soon program config, the last account of the slow-path variants.
+fn split_config(accounts: &[AccountView]) -> Result<(&AccountView, &[AccountView]), ProgramError> {
+    accounts
+        .split_last()
+        .ok_or(ProgramError::NotEnoughAccountKeys)
+}
+
 pub fn process_instruction(
     _program_id: &Address,
     accounts: &[AccountView],
@@ -105,6 +137,32 @@ pub fn process_instruction(
             stale_sequence(accounts, oracle_meta, sequence, payload)
         }
         0x05 => Ok(()), // Echo
+        0x06 => {
+            // [metadata:8][seq:8][reentered:1][data:rest]
+            if instruction_data.len() < 18 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let metadata = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+            let sequence = u64::from_le_bytes(instruction_data[9..17].try_into().unwrap());
+            let reentered = instruction_data[17] != 0;
+            let data = &instruction_data[18..];
+            reentrant_aux_write(accounts, metadata, sequence, reentered, data)
+        }
+        0x07 => close_as_delegate(accounts),
+        0x08 => set_delegated_program_to_envelope(accounts),
+        0x09 => {
+            // [metadata:8][seq:8][offset_0:1][len_0:1][offset_1:1][len_1:1]
+            if instruction_data.len() < 21 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let metadata = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+            let sequence = u64::from_le_bytes(instruction_data[9..17].try_into().unwrap());
+            let ranges = [
+                (instruction_data[17], instruction_data[18]),
+                (instruction_data[19], instruction_data[20]),
+            ];
+            multi_range_second_spec_blocked(accounts, metadata, sequence, ranges)
+        }
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
@@ -179,6 +237,7 @@ fn wrong_delegation_authority(
     sequence: u64,
     data: &[u8],
 ) -> ProgramResult {
+    let (config, accounts) = split_config(accounts)?;
     if accounts.len() < 4 {
         return Err(ProgramError::NotEnoughAccountKeys);
     }
@@ -192,15 +251,19 @@ fn wrong_delegation_authority(
 
     let cpi_accounts = [
         InstructionAccount::readonly_signer(accounts[0].address()), // wrong delegation, signer
-        InstructionAccount::writable(accounts[1].address()),         // envelope, writable
-        InstructionAccount::readonly(accounts[2].address()),         // padding
+        InstructionAccount::writable(accounts[1].address()),        // envelope, writable
+        InstructionAccount::readonly(accounts[2].address()),        // padding
+        InstructionAccount::readonly(config.address()),             // program config
     ];
     let instruction = InstructionView {
         program_id: accounts[3].address(),
         accounts: &cpi_accounts,
         data: &ix_data[..total],
     };
-    invoke(&instruction, &[&accounts[0], &accounts[1], &accounts[2]])
+    invoke(
+        &instruction,
+        &[&accounts[0], &accounts[1], &accounts[2], config],
+    )
 }
 
 /// ATTACK: UpdateAuxiliary without delegation.
@@ -212,6 +275,7 @@ fn slow_path_without_pda_signer(
     sequence: u64,
     data: &[u8],
 ) -> ProgramResult {
+    let (config, accounts) = split_config(accounts)?;
     if accounts.len() < 4 {
         return Err(ProgramError::NotEnoughAccountKeys);
     }
@@ -226,15 +290,19 @@ fn slow_path_without_pda_signer(
     // Attack: UpdateAuxiliary on envelope without delegation
     let cpi_accounts = [
         InstructionAccount::readonly_signer(accounts[0].address()), // authority, signer
-        InstructionAccount::writable(accounts[1].address()),         // envelope, writable
-        InstructionAccount::readonly(accounts[2].address()),         // padding
+        InstructionAccount::writable(accounts[1].address()),        // envelope, writable
+        InstructionAccount::readonly(accounts[2].address()),        // padding
+        InstructionAccount::readonly(config.address()),             // program config
     ];
     let instruction = InstructionView {
         program_id: accounts[3].address(),
         accounts: &cpi_accounts,
         data: &ix_data[..total],
     };
-    invoke(&instruction, &[&accounts[0], &accounts[1], &accounts[2]])
+    invoke(
+        &instruction,
+        &[&accounts[0], &accounts[1], &accounts[2], config],
+    )
 }
 
 /// ATTACK: Fast path CPI with stale sequence (sequence <= envelope.oracle_state.sequence).
@@ -265,3 +333,194 @@ fn stale_sequence(
     };
     invoke(&instruction, &[&accounts[0], &accounts[1]])
 }
+
+/// UpdateAuxiliaryDelegated through CPI with `accounts[0]` as the signing delegation authority.
+/// Wire: [disc:4][metadata:8][sequence:8][data:N]
+/// Accounts: [delegation_auth(signer), envelope(writable), padding, c_u_soon_program], and
+/// `config` forwarded last
+fn delegated_write(
+    accounts: &[AccountView],
+    config: &AccountView,
+    metadata: u64,
+    sequence: u64,
+    data: &[u8],
+) -> ProgramResult {
+    let data_len = data.len().min(255);
+    let mut ix_data = [0u8; 275]; // 4 + 8 + 8 + 255 max
+    ix_data[..4].copy_from_slice(&5u32.to_le_bytes()); // UPDATE_AUX_DELEGATED_TAG
+    ix_data[4..12].copy_from_slice(&metadata.to_le_bytes());
+    ix_data[12..20].copy_from_slice(&sequence.to_le_bytes());
+    ix_data[20..20 + data_len].copy_from_slice(&data[..data_len]);
+
+    let cpi_accounts = [
+        InstructionAccount::readonly_signer(accounts[0].address()), // delegation_auth, signer
+        InstructionAccount::writable(accounts[1].address()),        // envelope, writable
+        InstructionAccount::readonly(accounts[2].address()),        // padding
+        InstructionAccount::readonly(config.address()),             // program config
+    ];
+    let instruction = InstructionView {
+        program_id: accounts[3].address(),
+        accounts: &cpi_accounts,
+        data: &ix_data[..20 + data_len],
+    };
+    invoke(
+        &instruction,
+        &[&accounts[0], &accounts[1], &accounts[2], config],
+    )
+}
+
+/// ATTACK: write aux data, then re-enter this program through CPI and replay the write while
+/// the first one is still on the stack.
+fn reentrant_aux_write(
+    accounts: &[AccountView],
+    metadata: u64,
+    sequence: u64,
+    reentered: bool,
+    data: &[u8],
+) -> ProgramResult {
+    let (config, accounts) = split_config(accounts)?;
+    if accounts.len() < 5 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    delegated_write(accounts, config, metadata, sequence, data)?;
+    if reentered {
+        return Ok(());
+    }
+
+    let data_len = data.len().min(255);
+    let mut ix_data = [0u8; 273]; // 1 + 8 + 8 + 1 + 255 max
+    ix_data[0] = 0x06;
+    ix_data[1..9].copy_from_slice(&metadata.to_le_bytes());
+    ix_data[9..17].copy_from_slice(&sequence.to_le_bytes());
+    ix_data[17] = 1; // reentered
+    ix_data[18..18 + data_len].copy_from_slice(&data[..data_len]);
+
+    let cpi_accounts = [
+        InstructionAccount::readonly_signer(accounts[0].address()), // delegation_auth, signer
+        InstructionAccount::writable(accounts[1].address()),        // envelope, writable
+        InstructionAccount::readonly(accounts[2].address()),        // padding
+        InstructionAccount::readonly(accounts[3].address()),        // c_u_soon program
+        InstructionAccount::readonly(accounts[4].address()),        // this program
+        InstructionAccount::readonly(config.address()),             // program config
+    ];
+    let instruction = InstructionView {
+        program_id: accounts[4].address(),
+        accounts: &cpi_accounts,
+        data: &ix_data[..18 + data_len],
+    };
+    invoke(
+        &instruction,
+        &[
+            &accounts[0],
+            &accounts[1],
+            &accounts[2],
+            &accounts[3],
+            &accounts[4],
+            config,
+        ],
+    )
+}
+
+/// ATTACK: Close signed by the delegate instead of the authority.
+/// Wire: [disc:4]
+/// Accounts: [authority(signer), envelope(writable), recipient(writable)]
+fn close_as_delegate(accounts: &[AccountView]) -> ProgramResult {
+    let (config, accounts) = split_config(accounts)?;
+    if accounts.len() < 4 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let ix_data = 1u32.to_le_bytes(); // Close
+
+    let cpi_accounts = [
+        InstructionAccount::readonly_signer(accounts[0].address()), // delegate posing as authority
+        InstructionAccount::writable(accounts[1].address()),        // envelope, writable
+        InstructionAccount::writable(accounts[2].address()),        // recipient, writable
+        InstructionAccount::readonly(config.address()),             // program config
+    ];
+    let instruction = InstructionView {
+        program_id: accounts[3].address(),
+        accounts: &cpi_accounts,
+        data: &ix_data,
+    };
+    invoke(
+        &instruction,
+        &[&accounts[0], &accounts[1], &accounts[2], config],
+    )
+}
+
+/// ATTACK: SetDelegatedProgram naming the envelope itself as the delegation authority.
+/// Wire: [disc:4][program_bitmask:256][user_bitmask:256]
+/// Accounts: [authority(signer), envelope(writable), delegation_auth(signer)]
+fn set_delegated_program_to_envelope(accounts: &[AccountView]) -> ProgramResult {
+    let (config, accounts) = split_config(accounts)?;
+    if accounts.len() < 3 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let mut ix_data = [0u8; 4 + 256 + 256];
+    ix_data[..4].copy_from_slice(&2u32.to_le_bytes()); // SetDelegatedProgram
+                                                       // program_bitmask all writable (0x00), user_bitmask all blocked (0xFF)
+    ix_data[260..].fill(0xFF);
+
+    // Attack: the envelope stands in for delegation_auth; it is a PDA of c_u_soon, so this
+    // program cannot mark it as a signer.
+    let cpi_accounts = [
+        InstructionAccount::readonly_signer(accounts[0].address()), // authority, signer
+        InstructionAccount::writable(accounts[1].address()),        // envelope, writable
+        InstructionAccount::readonly(accounts[1].address()),        // envelope as delegation_auth
+        InstructionAccount::readonly(config.address()),             // program config
+    ];
+    let instruction = InstructionView {
+        program_id: accounts[2].address(),
+        accounts: &cpi_accounts,
+        data: &ix_data,
+    };
+    invoke(
+        &instruction,
+        &[&accounts[0], &accounts[1], &accounts[1], config],
+    )
+}
+
+/// ATTACK: UpdateAuxiliaryDelegatedMultiRange with two ranges, of which only the second
+/// changes a blocked byte. Range `i` is `len_i` bytes of `0x11 * (i + 1)` at `offset_i`.
+/// Wire: [disc:4][metadata:8][sequence:8][count:8] then per range [offset:1][len:8][data:len]
+/// Accounts: [delegation_auth(signer), envelope(writable), padding]
+fn multi_range_second_spec_blocked(
+    accounts: &[AccountView],
+    metadata: u64,
+    sequence: u64,
+    ranges: [(u8, u8); 2],
+) -> ProgramResult {
+    let (config, accounts) = split_config(accounts)?;
+    if accounts.len() < 4 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let mut ix_data = [0u8; 28 + 2 * (9 + 255)];
+    ix_data[..4].copy_from_slice(&10u32.to_le_bytes()); // UpdateAuxiliaryDelegatedMultiRange
+    ix_data[4..12].copy_from_slice(&metadata.to_le_bytes());
+    ix_data[12..20].copy_from_slice(&sequence.to_le_bytes());
+    ix_data[20..28].copy_from_slice(&2u64.to_le_bytes());
+    let mut pos = 28;
+    for (i, (offset, len)) in ranges.into_iter().enumerate() {
+        let len = len as usize;
+        ix_data[pos] = offset;
+        ix_data[pos + 1..pos + 9].copy_from_slice(&(len as u64).to_le_bytes());
+        ix_data[pos + 9..pos + 9 + len].fill(0x11 * (i as u8 + 1));
+        pos += 9 + len;
+    }
+
+    let cpi_accounts = [
+        InstructionAccount::readonly_signer(accounts[0].address()), // delegation_auth, signer
+        InstructionAccount::writable(accounts[1].address()),        // envelope, writable
+        InstructionAccount::readonly(accounts[2].address()),        // padding
+        InstructionAccount::readonly(config.address()),             // program config
+    ];
+    let instruction = InstructionView {
+        program_id: accounts[3].address(),
+        accounts: &cpi_accounts,
+        data: &ix_data[..pos],
+    };
+    invoke(
+        &instruction,
+        &[&accounts[0], &accounts[1], &accounts[2], config],
+    )
+}
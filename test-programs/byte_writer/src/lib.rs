@@ -4,11 +4,11 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 use c_u_soon_cpi::{
-    FastPathUpdate, UpdateAuxiliary, UpdateAuxiliaryDelegated, UpdateAuxiliaryDelegatedMultiRange,
-    UpdateAuxiliaryDelegatedRange, UpdateAuxiliaryForce, UpdateAuxiliaryMultiRange,
-    UpdateAuxiliaryRange,
+    with_fresh_oracle, FastPathUpdate, RangeGuardAccounts, UpdateAuxiliary,
+    UpdateAuxiliaryDelegated, UpdateAuxiliaryDelegatedMultiRange, UpdateAuxiliaryDelegatedRange,
+    UpdateAuxiliaryForce, UpdateAuxiliaryMultiRange, UpdateAuxiliaryRange,
 };
-use c_u_soon_instruction::WriteSpec;
+use c_u_soon_instruction::{split_write_hook, WriteSpec, WRITE_HOOK_DISCRIMINATOR};
 use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 
 /// Legitimate CPI caller for c_u_soon. Used to test valid multi-program CPI paths.
@@ -39,11 +39,38 @@ use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 ///
 /// 0x08: UpdateViaDelegatedMultiRange [metadata: u64 LE][seq: u64 LE][count: u8][(offset: u8)(len: u8)(data: len bytes)]...
 ///   Accounts: [0]=delegation_auth(signer), [1]=envelope(writable), [2]=padding, [3]=c_u_soon_program
+///
+/// 0x09: ReadFreshOracle [min_sequence: u64 LE]
+///   Accounts: [0]=envelope, [1]=c_u_soon_program
+///   Reads the oracle as a u64 via `with_fresh_oracle!` and sets it as return data.
+///
+/// Delegated variants (0x02, 0x06, 0x08) forward [4]=range_guard, [5]=instructions_sysvar
+/// when present. Authority variants (0x01, 0x05, 0x07) forward [4]=write_hook when present.
+/// Every variant but 0x00 and 0x09 takes the c_u_soon program config as its last account,
+/// after these, and forwards it.
+///
+/// Write hook: instruction data starting with `WRITE_HOOK_DISCRIMINATOR`
+///   Accounts: [0]=envelope
+///   Vetoes with `Custom(HOOK_VETO)` if the write changed aux byte 0, else echoes the
+///   instruction data as return data.
 
 pinocchio::program_entrypoint!(process_instruction);
 pinocchio::default_allocator!();
 pinocchio::nostd_panic_handler!();
 
+/// Error returned by the write hook to reject a write to aux byte 0.
+pub const HOOK_VETO: u32 = 0xB0;
+
+fn process_write_hook(instruction_data: &[u8]) -> ProgramResult {
+    let (_, mut changed) =
+        split_write_hook(instruction_data).ok_or(ProgramError::InvalidInstructionData)?;
+    if changed.any(|range| range.contains(&0)) {
+        return Err(ProgramError::Custom(HOOK_VETO));
+    }
+    pinocchio::cpi::set_return_data(instruction_data);
+    Ok(())
+}
+
 fn parse_ranges(data: &[u8]) -> Result<Vec<WriteSpec>, ProgramError> {
     if data.is_empty() {
         return Err(ProgramError::InvalidInstructionData);
@@ -70,6 +97,23 @@ fn parse_ranges(data: &[u8]) -> Result<Vec<WriteSpec>, ProgramError> {
     Ok(specs)
 }
 
+/// Split off the c_u_soon program config, the last account of the slow-path variants.
+fn split_config(accounts: &[AccountView]) -> Result<(&AccountView, &[AccountView]), ProgramError> {
+    accounts
+        .split_last()
+        .ok_or(ProgramError::NotEnoughAccountKeys)
+}
+
+fn range_guard_accounts(accounts: &[AccountView]) -> Option<RangeGuardAccounts<'_>> {
+    match accounts {
+        [_, _, _, _, range_guard, instructions_sysvar, ..] => Some(RangeGuardAccounts {
+            range_guard,
+            instructions_sysvar,
+        }),
+        _ => None,
+    }
+}
+
 pub fn process_instruction(
     _program_id: &Address,
     accounts: &[AccountView],
@@ -78,6 +122,9 @@ pub fn process_instruction(
     if instruction_data.is_empty() {
         return Err(ProgramError::InvalidInstructionData);
     }
+    if instruction_data.starts_with(&WRITE_HOOK_DISCRIMINATOR) {
+        return process_write_hook(instruction_data);
+    }
     match instruction_data[0] {
         0x00 => {
             if accounts.len() < 3 || instruction_data.len() < 18 {
@@ -102,6 +149,7 @@ pub fn process_instruction(
         }
         0x01 => {
             // [metadata:8][seq:8][data:rest]
+            let (config, accounts) = split_config(accounts)?;
             if accounts.len() < 4 || instruction_data.len() < 17 {
                 return Err(ProgramError::InvalidInstructionData);
             }
@@ -112,6 +160,9 @@ pub fn process_instruction(
                 authority: &accounts[0],
                 envelope: &accounts[1],
                 pda: &accounts[2],
+                write_hook: accounts.get(4),
+                instructions_sysvar: None,
+                config,
                 program: &accounts[3],
                 metadata,
                 sequence,
@@ -121,6 +172,7 @@ pub fn process_instruction(
         }
         0x02 => {
             // [metadata:8][seq:8][data:rest]
+            let (config, accounts) = split_config(accounts)?;
             if accounts.len() < 4 || instruction_data.len() < 17 {
                 return Err(ProgramError::InvalidInstructionData);
             }
@@ -131,6 +183,8 @@ pub fn process_instruction(
                 envelope: &accounts[1],
                 delegation_auth: &accounts[0],
                 padding: &accounts[2],
+                range_guard: range_guard_accounts(accounts),
+                config,
                 program: &accounts[3],
                 metadata,
                 sequence,
@@ -140,6 +194,7 @@ pub fn process_instruction(
         }
         0x03 => {
             // [metadata:8][auth_seq:8][prog_seq:8][data:rest]
+            let (config, accounts) = split_config(accounts)?;
             if accounts.len() < 4 || instruction_data.len() < 25 {
                 return Err(ProgramError::InvalidInstructionData);
             }
@@ -151,6 +206,7 @@ pub fn process_instruction(
                 authority: &accounts[0],
                 envelope: &accounts[1],
                 delegation_auth: &accounts[2],
+                config,
                 program: &accounts[3],
                 metadata,
                 authority_sequence: auth_seq,
@@ -162,6 +218,7 @@ pub fn process_instruction(
         0x04 => Ok(()), // Echo
         0x05 => {
             // [metadata:8][seq:8][offset:1][data:rest]
+            let (config, accounts) = split_config(accounts)?;
             if accounts.len() < 4 || instruction_data.len() < 18 {
                 return Err(ProgramError::InvalidInstructionData);
             }
@@ -173,6 +230,9 @@ pub fn process_instruction(
                 authority: &accounts[0],
                 envelope: &accounts[1],
                 pda: &accounts[2],
+                write_hook: accounts.get(4),
+                instructions_sysvar: None,
+                config,
                 program: &accounts[3],
                 metadata,
                 sequence,
@@ -183,6 +243,7 @@ pub fn process_instruction(
         }
         0x06 => {
             // [metadata:8][seq:8][offset:1][data:rest]
+            let (config, accounts) = split_config(accounts)?;
             if accounts.len() < 4 || instruction_data.len() < 18 {
                 return Err(ProgramError::InvalidInstructionData);
             }
@@ -194,6 +255,8 @@ pub fn process_instruction(
                 envelope: &accounts[1],
                 delegation_auth: &accounts[0],
                 padding: &accounts[2],
+                range_guard: range_guard_accounts(accounts),
+                config,
                 program: &accounts[3],
                 metadata,
                 sequence,
@@ -204,6 +267,7 @@ pub fn process_instruction(
         }
         0x07 => {
             // [metadata:8][seq:8][ranges_data:rest]
+            let (config, accounts) = split_config(accounts)?;
             if accounts.len() < 4 || instruction_data.len() < 17 {
                 return Err(ProgramError::InvalidInstructionData);
             }
@@ -214,6 +278,9 @@ pub fn process_instruction(
                 authority: &accounts[0],
                 envelope: &accounts[1],
                 pda: &accounts[2],
+                write_hook: accounts.get(4),
+                instructions_sysvar: None,
+                config,
                 program: &accounts[3],
                 metadata,
                 sequence,
@@ -223,6 +290,7 @@ pub fn process_instruction(
         }
         0x08 => {
             // [metadata:8][seq:8][ranges_data:rest]
+            let (config, accounts) = split_config(accounts)?;
             if accounts.len() < 4 || instruction_data.len() < 17 {
                 return Err(ProgramError::InvalidInstructionData);
             }
@@ -233,6 +301,8 @@ pub fn process_instruction(
                 envelope: &accounts[1],
                 delegation_auth: &accounts[0],
                 padding: &accounts[2],
+                range_guard: range_guard_accounts(accounts),
+                config,
                 program: &accounts[3],
                 metadata,
                 sequence,
@@ -240,6 +310,18 @@ pub fn process_instruction(
             }
             .invoke()
         }
+        0x09 => {
+            // [min_sequence:8]
+            if accounts.len() < 2 || instruction_data.len() < 9 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let min_sequence = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+            let value = with_fresh_oracle!(&accounts[0], &accounts[1], u64, min_sequence, |v| {
+                Ok(*v)
+            })?;
+            pinocchio::cpi::set_return_data(&value.to_le_bytes());
+            Ok(())
+        }
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
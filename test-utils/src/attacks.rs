@@ -0,0 +1,104 @@
+//! Standard attack scenarios: tampered copies of a valid instruction or account, and
+//! assertions that the program rejects them.
+//!
+//! Each `assert_*` helper takes a [`Runner`] holding the state under which the untampered
+//! instruction would succeed. Rejections leave that state unchanged, so several can be
+//! checked against one runner before running the real instruction.
+
+use pinocchio::error::ProgramError;
+use solana_address::Address;
+use solana_sdk::{account::Account, instruction::Instruction};
+
+use crate::Runner;
+
+/// `instruction` with the account at `index` no longer marked as a signer.
+pub fn without_signer(instruction: &Instruction, index: usize) -> Instruction {
+    let mut tampered = instruction.clone();
+    tampered.accounts[index].is_signer = false;
+    tampered
+}
+
+/// `instruction` with the account at `index` replaced by `address`, keeping its flags.
+pub fn with_account(instruction: &Instruction, index: usize, address: Address) -> Instruction {
+    let mut tampered = instruction.clone();
+    tampered.accounts[index].pubkey = address;
+    tampered
+}
+
+/// `instruction` with its data cut to `len` bytes.
+pub fn truncated(instruction: &Instruction, len: usize) -> Instruction {
+    let mut tampered = instruction.clone();
+    tampered.data.truncate(len);
+    tampered
+}
+
+/// `account` with its owner replaced, e.g. an envelope spoofed by another program.
+pub fn owned_by(account: &Account, owner: Address) -> Account {
+    Account {
+        owner,
+        ..account.clone()
+    }
+}
+
+/// `instruction` succeeds once and fails with `err` when submitted again unchanged.
+pub fn assert_replay_rejected(runner: &mut Runner, instruction: &Instruction, err: ProgramError) {
+    runner.expect_ok(instruction);
+    runner.expect_err(instruction, err);
+}
+
+/// `instruction` fails with `err` when the account at `index` does not sign.
+pub fn assert_requires_signer(
+    runner: &mut Runner,
+    instruction: &Instruction,
+    index: usize,
+    err: ProgramError,
+) {
+    runner.expect_err(&without_signer(instruction, index), err);
+}
+
+/// `instruction` fails with `err` when `impostor` takes the place of the account at
+/// `index`. The impostor keeps the original's signer and writable flags, and its account is
+/// whatever the runner holds for it.
+pub fn assert_rejects_impostor(
+    runner: &mut Runner,
+    instruction: &Instruction,
+    index: usize,
+    impostor: Address,
+    err: ProgramError,
+) {
+    runner.expect_err(&with_account(instruction, index, impostor), err);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+
+    fn instruction() -> Instruction {
+        Instruction::new_with_bytes(
+            Address::new_from_array([9; 32]),
+            &[1, 2, 3, 4],
+            vec![
+                AccountMeta::new_readonly(Address::new_from_array([1; 32]), true),
+                AccountMeta::new(Address::new_from_array([2; 32]), false),
+            ],
+        )
+    }
+
+    #[test]
+    fn tampering_changes_only_the_target() {
+        let ix = instruction();
+
+        let unsigned = without_signer(&ix, 0);
+        assert!(!unsigned.accounts[0].is_signer);
+        assert_eq!(unsigned.accounts[1], ix.accounts[1]);
+
+        let impostor = Address::new_from_array([3; 32]);
+        let swapped = with_account(&ix, 1, impostor);
+        assert_eq!(swapped.accounts[1].pubkey, impostor);
+        assert!(swapped.accounts[1].is_writable);
+        assert_eq!(swapped.accounts[0], ix.accounts[0]);
+
+        assert_eq!(truncated(&ix, 2).data, [1, 2]);
+    }
+}
@@ -0,0 +1,70 @@
+//! Program config accounts, and the loader accounts `InitConfig` checks the upgrade authority
+//! against.
+
+use c_u_soon::{derive_config_address, ProgramConfig};
+use solana_address::Address;
+use solana_sdk::account::Account;
+
+use crate::{DEFAULT_PROGRAM_ID, FIXTURE_LAMPORTS};
+
+/// The upgradeable BPF loader (`BPFLoaderUpgradeab1e11111111111111111111111`).
+pub const BPF_LOADER_UPGRADEABLE_ID: Address = Address::new_from_array([
+    0x02, 0xa8, 0xf6, 0x91, 0x4e, 0x88, 0xa1, 0xb0, 0xe2, 0x10, 0x15, 0x3e, 0xf7, 0x63, 0xae, 0x2b,
+    0x00, 0xc2, 0xb9, 0x3d, 0x16, 0xc1, 0x24, 0xd2, 0xc0, 0x53, 0x7a, 0x10, 0x04, 0x80, 0x00, 0x00,
+]);
+
+/// Config PDA and bump under `program_id`.
+pub fn find_config_pda(program_id: &Address) -> (Address, u8) {
+    derive_config_address(program_id)
+}
+
+/// Initialized config account at its PDA under [`DEFAULT_PROGRAM_ID`], holding
+/// [`FIXTURE_LAMPORTS`]. Everything above the rent-exempt minimum counts as collected fees.
+pub fn config_account(admin: Address, create_fee: u64, paused: bool) -> (Address, Account) {
+    let (address, bump) = find_config_pda(&DEFAULT_PROGRAM_ID);
+    let config = ProgramConfig {
+        admin,
+        create_fee,
+        bump,
+        paused: paused as u8,
+        _padding: [0; 6],
+    };
+    let account = Account {
+        lamports: FIXTURE_LAMPORTS,
+        data: bytemuck::bytes_of(&config).to_vec(),
+        owner: DEFAULT_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    (address, account)
+}
+
+/// The program and program data accounts of `program_id` deployed through the upgradeable
+/// loader, with `upgrade_authority` or, for `None`, made immutable.
+pub fn loader_accounts(
+    program_id: &Address,
+    upgrade_authority: Option<Address>,
+) -> [(Address, Account); 2] {
+    let (program_data, _) =
+        Address::find_program_address(&[program_id.as_ref()], &BPF_LOADER_UPGRADEABLE_ID);
+
+    let mut program = 2u32.to_le_bytes().to_vec();
+    program.extend_from_slice(program_data.as_ref());
+
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(upgrade_authority.is_some() as u8);
+    data.extend_from_slice(upgrade_authority.unwrap_or_default().as_ref());
+
+    let account = |data: Vec<u8>, executable| Account {
+        lamports: FIXTURE_LAMPORTS,
+        data,
+        owner: BPF_LOADER_UPGRADEABLE_ID,
+        executable,
+        rent_epoch: 0,
+    };
+    [
+        (*program_id, account(program, true)),
+        (program_data, account(data, false)),
+    ]
+}
@@ -0,0 +1,226 @@
+//! Envelope accounts in a chosen state, without running `Create`.
+
+use bytemuck::Zeroable;
+use c_u_soon::{derive_envelope_address, Envelope, Mask, StructMetadata};
+use solana_address::Address;
+use solana_sdk::account::Account;
+
+use crate::DEFAULT_PROGRAM_ID;
+
+/// Lamports held by fixture envelopes and [`funded_account`]s unless set otherwise.
+pub const FIXTURE_LAMPORTS: u64 = 1_000_000_000;
+
+/// Envelope PDA and bump for `authority` and `custom_seeds` under `program_id`.
+pub fn find_envelope_pda(
+    program_id: &Address,
+    authority: &Address,
+    custom_seeds: &[&[u8]],
+) -> (Address, u8) {
+    derive_envelope_address(program_id, authority, custom_seeds)
+}
+
+/// System account holding `lamports` and no data.
+pub fn funded_account(lamports: u64) -> Account {
+    Account {
+        lamports,
+        data: vec![],
+        owner: Address::default(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Builder for an initialized envelope account.
+///
+/// Starts undelegated, with both bitmasks blocking every byte, zero sequences and
+/// metadata, and owned by [`DEFAULT_PROGRAM_ID`]:
+///
+/// ```ignore
+/// let account = EnvelopeFixture::new(authority)
+///     .sequence(5)
+///     .aux_metadata(Price::METADATA)
+///     .delegated(delegate, Mask::ALL_WRITABLE, Mask::ALL_BLOCKED)
+///     .account();
+/// ```
+#[derive(Clone, Copy)]
+pub struct EnvelopeFixture {
+    envelope: Envelope,
+    owner: Address,
+    lamports: u64,
+}
+
+impl EnvelopeFixture {
+    pub fn new(authority: Address) -> Self {
+        let mut envelope = Envelope::zeroed();
+        envelope.authority = authority;
+        envelope.program_bitmask = Mask::ALL_BLOCKED;
+        envelope.user_bitmask = Mask::ALL_BLOCKED;
+        Self {
+            envelope,
+            owner: DEFAULT_PROGRAM_ID,
+            lamports: FIXTURE_LAMPORTS,
+        }
+    }
+
+    /// Oracle sequence. The next fast-path update must exceed it.
+    pub fn sequence(mut self, sequence: u64) -> Self {
+        self.envelope.oracle_state.sequence = sequence;
+        self
+    }
+
+    pub fn bump(mut self, bump: u8) -> Self {
+        self.envelope.bump = bump;
+        self
+    }
+
+    /// OR `flags` into the envelope flags (`FLAG_PERMANENT`, `FLAG_TWAP`, ...).
+    pub fn flags(mut self, flags: u8) -> Self {
+        self.envelope.flags |= flags;
+        self
+    }
+
+    pub fn oracle_metadata(mut self, metadata: StructMetadata) -> Self {
+        self.envelope.oracle_state.oracle_metadata = metadata;
+        self
+    }
+
+    /// Oracle metadata and the payload at the start of the oracle data.
+    pub fn oracle(mut self, metadata: StructMetadata, payload: &[u8]) -> Self {
+        self.envelope.oracle_state.oracle_metadata = metadata;
+        self.envelope.oracle_state.data[..payload.len()].copy_from_slice(payload);
+        self
+    }
+
+    pub fn aux_metadata(mut self, metadata: StructMetadata) -> Self {
+        self.envelope.auxiliary_metadata = metadata;
+        self
+    }
+
+    /// Aux metadata and the bytes at the start of the auxiliary data.
+    pub fn aux(mut self, metadata: StructMetadata, data: &[u8]) -> Self {
+        self.envelope.auxiliary_metadata = metadata;
+        self.envelope.auxiliary_data[..data.len()].copy_from_slice(data);
+        self
+    }
+
+    /// Authority and program aux sequences.
+    pub fn aux_sequences(mut self, authority: u64, program: u64) -> Self {
+        self.envelope.authority_aux_sequence = authority;
+        self.envelope.program_aux_sequence = program;
+        self
+    }
+
+    /// Delegate to `delegation_authority` with the given masks.
+    pub fn delegated(
+        mut self,
+        delegation_authority: Address,
+        program_bitmask: Mask,
+        user_bitmask: Mask,
+    ) -> Self {
+        self.envelope.delegation_authority = delegation_authority;
+        self.envelope.program_bitmask = program_bitmask;
+        self.envelope.user_bitmask = user_bitmask;
+        self
+    }
+
+    /// Program called after every authority aux write, as after `SetWriteHook`.
+    pub fn write_hook(mut self, program: Address) -> Self {
+        self.envelope.write_hook = program;
+        self
+    }
+
+    /// Key allowed to write the oracle in place of the authority.
+    pub fn publisher(mut self, publisher: Address) -> Self {
+        self.envelope.publisher = publisher;
+        self
+    }
+
+    /// Reject oracle and aux writes, as after `SetPaused { paused: true }`.
+    pub fn paused(mut self) -> Self {
+        self.envelope.paused = 1;
+        self
+    }
+
+    /// Log an oracle event on every fast-path write, as after `CreateWithEvents`.
+    pub fn emit_events(mut self) -> Self {
+        self.envelope.emit_events = 1;
+        self
+    }
+
+    /// Account owner, for a program loaded under another id or a spoofed envelope.
+    pub fn owner(mut self, owner: Address) -> Self {
+        self.owner = owner;
+        self
+    }
+
+    pub fn lamports(mut self, lamports: u64) -> Self {
+        self.lamports = lamports;
+        self
+    }
+
+    /// Edit any field the builder has no method for.
+    pub fn with(mut self, edit: impl FnOnce(&mut Envelope)) -> Self {
+        edit(&mut self.envelope);
+        self
+    }
+
+    pub fn envelope(&self) -> Envelope {
+        self.envelope
+    }
+
+    /// The account holding the envelope, exactly [`Envelope::SIZE`] bytes.
+    pub fn account(&self) -> Account {
+        Account {
+            lamports: self.lamports,
+            data: bytemuck::bytes_of(&self.envelope).to_vec(),
+            owner: self.owner,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c_u_soon::{ENVELOPE_SEED, FLAG_PERMANENT};
+
+    #[test]
+    fn fixture_builds_the_requested_state() {
+        let authority = Address::new_from_array([1; 32]);
+        let delegate = Address::new_from_array([2; 32]);
+        let meta = StructMetadata::new(4, 9);
+        let account = EnvelopeFixture::new(authority)
+            .sequence(5)
+            .flags(FLAG_PERMANENT)
+            .aux(meta, &[1, 2, 3, 4])
+            .delegated(delegate, Mask::ALL_WRITABLE, Mask::ALL_BLOCKED)
+            .account();
+
+        assert_eq!(account.owner, DEFAULT_PROGRAM_ID);
+        assert_eq!(account.lamports, FIXTURE_LAMPORTS);
+        let envelope: &Envelope = bytemuck::from_bytes(&account.data);
+        assert_eq!(envelope.authority, authority);
+        assert_eq!(envelope.oracle_state.sequence, 5);
+        assert!(envelope.is_permanent());
+        assert!(envelope.has_delegation());
+        assert_eq!(envelope.auxiliary_metadata, meta);
+        assert_eq!(envelope.auxiliary_data[..4], [1, 2, 3, 4]);
+        assert_eq!(envelope.program_bitmask, Mask::ALL_WRITABLE);
+    }
+
+    #[test]
+    fn envelope_pda_matches_the_program_seeds() {
+        let program_id = Address::new_from_array([3; 32]);
+        let authority = Address::new_from_array([4; 32]);
+        let (address, bump) = find_envelope_pda(&program_id, &authority, &[b"feed"]);
+        assert_eq!(
+            Address::create_program_address(
+                &[ENVELOPE_SEED, authority.as_ref(), b"feed", &[bump]],
+                &program_id
+            )
+            .unwrap(),
+            address
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! Deterministic keys derived from labels.
+
+use solana_address::Address;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+/// Keypair whose secret key is derived from `label` alone.
+///
+/// Only for tests: anyone who knows the label knows the key.
+pub fn fixture_keypair(label: &str) -> Keypair {
+    let mut secret = [0u8; 32];
+    for (lane, chunk) in secret.chunks_exact_mut(8).enumerate() {
+        chunk.copy_from_slice(&fnv1a(lane as u8, label.as_bytes()).to_le_bytes());
+    }
+    Keypair::new_from_array(secret)
+}
+
+/// Public key of [`fixture_keypair`]`(label)`.
+pub fn fixture_address(label: &str) -> Address {
+    fixture_keypair(label).pubkey()
+}
+
+/// 64-bit FNV-1a over `salt || bytes`.
+fn fnv1a(salt: u8, bytes: &[u8]) -> u64 {
+    core::iter::once(salt)
+        .chain(bytes.iter().copied())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures_are_stable_and_distinct() {
+        assert_eq!(fixture_address("feed1"), fixture_address("feed1"));
+        assert_ne!(fixture_address("feed1"), fixture_address("feed2"));
+        assert_eq!(
+            fixture_keypair("feed1").pubkey(),
+            fixture_address("feed1"),
+            "keypair signs for the fixture address"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! Test support for programs and clients built on c_u_soon.
+//!
+//! [`fixture_address`] and [`fixture_keypair`] turn a label into the same key on every run,
+//! so failures reproduce and logs stay readable. [`Scenario`] strings envelope operations
+//! together by name and runs them as a Mollusk instruction chain, carrying account state
+//! from one step to the next:
+//!
+//! ```ignore
+//! Scenario::new()
+//!     .create_envelope("feed1")
+//!     .delegate_to("prog")
+//!     .fast_path(1, &[7; 8])
+//!     .expect_oracle(1, &[7; 8])
+//!     .expect_aux(0, &[0; 8])
+//!     .run();
+//! ```
+//!
+//! For hand-built instructions, [`Runner`] does the same chaining one instruction at a
+//! time, [`EnvelopeFixture`] builds envelope accounts in any state without running
+//! `Create`, and the [`attacks`] module covers missing signers, impostor accounts, and
+//! replays. [`config_account`] and [`loader_accounts`] stand in for an initialized program
+//! config and the upgradeable loader's records. [`new_mollusk`] and [`new_mollusk_silent`]
+//! keep tests that change the log level from interfering with each other, and add the
+//! program config to instructions that leave it out ([`with_config`]).
+//!
+//! The program is loaded from `target/deploy/c_u_soon_program.so`; build it with
+//! `make build-sbf` first.
+
+pub mod attacks;
+mod config;
+mod envelope;
+mod fixtures;
+mod mollusk;
+mod runner;
+mod scenario;
+
+pub use config::{config_account, find_config_pda, loader_accounts, BPF_LOADER_UPGRADEABLE_ID};
+pub use envelope::{find_envelope_pda, funded_account, EnvelopeFixture, FIXTURE_LAMPORTS};
+pub use fixtures::{fixture_address, fixture_keypair};
+pub use mollusk::{new_mollusk, new_mollusk_silent, with_config, LogWriteGuard, MolluskGuard};
+pub use runner::Runner;
+pub use scenario::{Scenario, ScenarioResult, DEFAULT_PROGRAM_ID, DEFAULT_PROGRAM_PATH};
@@ -0,0 +1,160 @@
+//! Mollusk construction that keeps log-level changes from leaking between tests.
+//!
+//! Mollusk resets the global log level when it is built, and tests that silence logs set
+//! it too. Every Mollusk made here holds [`LOG_LOCK`] for its lifetime: normal tests share
+//! a read lock, silent tests take the write lock, so a silenced test never mutes (or is
+//! un-muted by) one running alongside it.
+//!
+//! A [`MolluskGuard`] also adds the program config to the instructions it runs, as
+//! [`with_config`] describes, so tests of other behaviour need not list it.
+
+use c_u_soon::derive_config_address;
+use c_u_soon_instruction::{requires_trailing_config, split_versioned};
+use mollusk_svm::{
+    result::{Check, InstructionResult},
+    Mollusk,
+};
+use solana_address::Address;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+static LOG_LOCK: RwLock<()> = RwLock::new(());
+
+/// A Mollusk together with the log lock it was built under. Derefs to [`Mollusk`].
+///
+/// Generic over the lock kind so read-lock and write-lock tests share one type. Its
+/// `process_instruction` and `process_and_validate_instruction` pass instructions through
+/// [`with_config`] first; call them on the `mollusk` field to send an instruction as is.
+pub struct MolluskGuard<G> {
+    pub mollusk: Mollusk,
+    program_id: Address,
+    _log: G,
+}
+
+impl<G> MolluskGuard<G> {
+    /// [`Mollusk::process_instruction`] after [`with_config`].
+    pub fn process_instruction(
+        &self,
+        instruction: &Instruction,
+        accounts: &[(Address, Account)],
+    ) -> InstructionResult {
+        let (instruction, accounts) = with_config(&self.program_id, instruction, accounts);
+        self.mollusk.process_instruction(&instruction, &accounts)
+    }
+
+    /// [`Mollusk::process_and_validate_instruction`] after [`with_config`].
+    pub fn process_and_validate_instruction(
+        &self,
+        instruction: &Instruction,
+        accounts: &[(Address, Account)],
+        checks: &[Check],
+    ) -> InstructionResult {
+        let (instruction, accounts) = with_config(&self.program_id, instruction, accounts);
+        self.mollusk
+            .process_and_validate_instruction(&instruction, &accounts, checks)
+    }
+}
+
+/// `instruction` and `accounts` with the program config added where `program_id` requires
+/// it and the instruction leaves it out: as the fourth account of a three-account `Create`
+/// or `CreateWithEvents`, and as the last account of an instruction that
+/// [`requires_trailing_config`] and does not already end with it. The config is also added
+/// to `accounts`, uninitialized, unless they already hold it.
+///
+/// Instructions for other programs and fast-path updates pass through unchanged.
+pub fn with_config(
+    program_id: &Address,
+    instruction: &Instruction,
+    accounts: &[(Address, Account)],
+) -> (Instruction, Vec<(Address, Account)>) {
+    let mut instruction = instruction.clone();
+    let mut accounts = accounts.to_vec();
+    if instruction.program_id != *program_id || instruction.accounts.len() == 2 {
+        return (instruction, accounts);
+    }
+    let data = split_versioned(&instruction.data).map_or(&instruction.data[..], |(_, inner)| inner);
+    let Some(tag) = data
+        .get(..4)
+        .map(|tag| u32::from_le_bytes(tag.try_into().unwrap()))
+    else {
+        return (instruction, accounts);
+    };
+    let config = derive_config_address(program_id).0;
+    let meta = match tag {
+        // Create and CreateWithEvents.
+        0 | 42 if instruction.accounts.len() == 3 => AccountMeta::new(config, false),
+        _ if requires_trailing_config(tag)
+            && instruction.accounts.last().map(|meta| meta.pubkey) != Some(config) =>
+        {
+            AccountMeta::new_readonly(config, false)
+        }
+        _ => return (instruction, accounts),
+    };
+    instruction.accounts.push(meta);
+    if !accounts.iter().any(|(address, _)| *address == config) {
+        accounts.push((config, Account::default()));
+    }
+    (instruction, accounts)
+}
+
+impl<G> std::ops::Deref for MolluskGuard<G> {
+    type Target = Mollusk;
+    fn deref(&self) -> &Mollusk {
+        &self.mollusk
+    }
+}
+
+impl<G> std::ops::DerefMut for MolluskGuard<G> {
+    fn deref_mut(&mut self) -> &mut Mollusk {
+        &mut self.mollusk
+    }
+}
+
+/// Write guard that restores the previous log level on drop.
+pub struct LogWriteGuard {
+    _inner: RwLockWriteGuard<'static, ()>,
+    prev_level: log::LevelFilter,
+}
+
+impl Drop for LogWriteGuard {
+    fn drop(&mut self) {
+        log::set_max_level(self.prev_level);
+    }
+}
+
+/// Mollusk for a normal test: holds the shared log lock for the test's lifetime.
+pub fn new_mollusk(
+    program_id: &Address,
+    program_name: &str,
+) -> MolluskGuard<RwLockReadGuard<'static, ()>> {
+    let _log = LOG_LOCK.read().unwrap_or_else(|e| e.into_inner());
+    let mollusk = Mollusk::new(program_id, program_name);
+    MolluskGuard {
+        mollusk,
+        program_id: *program_id,
+        _log,
+    }
+}
+
+/// Mollusk for a test that sets the log level to `level`: holds the exclusive log lock and
+/// restores the previous level when the guard drops.
+pub fn new_mollusk_silent(
+    program_id: &Address,
+    program_name: &str,
+    level: log::LevelFilter,
+) -> MolluskGuard<LogWriteGuard> {
+    let _inner = LOG_LOCK.write().unwrap_or_else(|e| e.into_inner());
+    // Mollusk::new calls setup_with_default() which resets the log level, so
+    // capture prev_level and set our desired level only after construction.
+    let mollusk = Mollusk::new(program_id, program_name);
+    let prev_level = log::max_level();
+    log::set_max_level(level);
+    MolluskGuard {
+        mollusk,
+        program_id: *program_id,
+        _log: LogWriteGuard { _inner, prev_level },
+    }
+}
@@ -0,0 +1,173 @@
+//! Instruction runner that carries account state from one instruction to the next.
+
+use c_u_soon::Envelope;
+use mollusk_svm::{
+    program::keyed_account_for_system_program,
+    result::{Check, InstructionResult},
+};
+use pinocchio::error::ProgramError;
+use solana_address::Address;
+use solana_sdk::{account::Account, instruction::Instruction};
+use solana_system_interface::program as system_program;
+use std::sync::RwLockReadGuard;
+
+use crate::{new_mollusk, with_config, MolluskGuard, DEFAULT_PROGRAM_ID, DEFAULT_PROGRAM_PATH};
+
+/// A Mollusk plus the account store every instruction reads from and writes back to.
+///
+/// [`process`](Self::process) looks up each account the instruction references (addresses
+/// never seen start as empty system accounts), adds the program config if the instruction
+/// leaves it out ([`with_config`]), runs it, checks the result, and stores the resulting
+/// accounts, so the next instruction sees the state this one left:
+///
+/// ```ignore
+/// let mut runner = Runner::new();
+/// runner.set_account(envelope, EnvelopeFixture::new(authority).account());
+/// runner.expect_ok(&fast_path_ix(authority, envelope, 1));
+/// runner.expect_err(&fast_path_ix(authority, envelope, 1), ProgramError::InvalidInstructionData);
+/// assert_eq!(runner.envelope(&envelope).oracle_state.sequence, 1);
+/// ```
+pub struct Runner {
+    mollusk: MolluskGuard<RwLockReadGuard<'static, ()>>,
+    program_id: Address,
+    accounts: Vec<(Address, Account)>,
+}
+
+impl Default for Runner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runner {
+    /// Runner for the workspace build of the program at [`DEFAULT_PROGRAM_ID`].
+    pub fn new() -> Self {
+        Self::with_program(DEFAULT_PROGRAM_ID, DEFAULT_PROGRAM_PATH)
+    }
+
+    /// Runner for the program `.so` at `program_path` (without extension).
+    pub fn with_program(program_id: Address, program_path: &str) -> Self {
+        Self {
+            mollusk: new_mollusk(&program_id, program_path),
+            program_id,
+            accounts: vec![keyed_account_for_system_program()],
+        }
+    }
+
+    pub fn program_id(&self) -> Address {
+        self.program_id
+    }
+
+    /// The underlying Mollusk, for sysvars, compute budget, and other settings.
+    pub fn mollusk_mut(&mut self) -> &mut mollusk_svm::Mollusk {
+        &mut self.mollusk
+    }
+
+    /// Insert or replace the account at `address`.
+    pub fn set_account(&mut self, address: Address, account: Account) -> &mut Self {
+        set_account(&mut self.accounts, address, account);
+        self
+    }
+
+    /// Give `address` a system account with `lamports`, replacing any account there.
+    pub fn fund(&mut self, address: Address, lamports: u64) -> &mut Self {
+        self.set_account(address, Account::new(lamports, 0, &system_program::ID))
+    }
+
+    /// Current state of the account at `address`.
+    pub fn account(&self, address: &Address) -> Option<&Account> {
+        self.accounts
+            .iter()
+            .find(|(a, _)| a == address)
+            .map(|(_, account)| account)
+    }
+
+    /// Current state of the envelope at `address`. Panics if there is no initialized
+    /// envelope there.
+    pub fn envelope(&self, address: &Address) -> &Envelope {
+        envelope_in(&self.accounts, address)
+    }
+
+    /// Every stored account in its current state.
+    pub fn accounts(&self) -> &[(Address, Account)] {
+        &self.accounts
+    }
+
+    /// Run `instruction`, validate `checks`, and store the resulting accounts. A failed
+    /// instruction leaves the stored state unchanged.
+    pub fn process(&mut self, instruction: &Instruction, checks: &[Check]) -> InstructionResult {
+        let (instruction, _) = with_config(&self.program_id, instruction, &[]);
+        self.process_as_is(&instruction, checks)
+    }
+
+    /// [`process`](Self::process) without adding the program config, for instructions that
+    /// leave it out on purpose.
+    pub fn process_as_is(
+        &mut self,
+        instruction: &Instruction,
+        checks: &[Check],
+    ) -> InstructionResult {
+        let inputs = instruction_accounts(instruction, &self.accounts);
+        let result =
+            self.mollusk
+                .mollusk
+                .process_and_validate_instruction(instruction, &inputs, checks);
+        if result.program_result.is_ok() {
+            for (address, account) in &result.resulting_accounts {
+                set_account(&mut self.accounts, *address, account.clone());
+            }
+        }
+        result
+    }
+
+    /// [`process`](Self::process) expecting success.
+    pub fn expect_ok(&mut self, instruction: &Instruction) -> InstructionResult {
+        self.process(instruction, &[Check::success()])
+    }
+
+    /// [`process`](Self::process) expecting `err`.
+    pub fn expect_err(&mut self, instruction: &Instruction, err: ProgramError) -> InstructionResult {
+        self.process(instruction, &[Check::err(err)])
+    }
+}
+
+pub(crate) fn set_account(
+    accounts: &mut Vec<(Address, Account)>,
+    address: Address,
+    account: Account,
+) {
+    match accounts.iter_mut().find(|(a, _)| a == &address) {
+        Some((_, slot)) => *slot = account,
+        None => accounts.push((address, account)),
+    }
+}
+
+pub(crate) fn envelope_in<'a>(accounts: &'a [(Address, Account)], address: &Address) -> &'a Envelope {
+    let (_, account) = accounts
+        .iter()
+        .find(|(a, _)| a == address)
+        .unwrap_or_else(|| panic!("no account at {address}"));
+    Envelope::from_prefix_bytes(&account.data)
+        .unwrap_or_else(|| panic!("account {address} is not an initialized envelope"))
+}
+
+/// The accounts `instruction` references, once each, in first-use order. Addresses not in
+/// `accounts` start as empty system accounts.
+pub(crate) fn instruction_accounts(
+    instruction: &Instruction,
+    accounts: &[(Address, Account)],
+) -> Vec<(Address, Account)> {
+    let mut inputs: Vec<(Address, Account)> = Vec::new();
+    for meta in &instruction.accounts {
+        if inputs.iter().any(|(a, _)| a == &meta.pubkey) {
+            continue;
+        }
+        let account = accounts
+            .iter()
+            .find(|(a, _)| a == &meta.pubkey)
+            .map(|(_, account)| account.clone())
+            .unwrap_or_default();
+        inputs.push((meta.pubkey, account));
+    }
+    inputs
+}
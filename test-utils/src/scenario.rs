@@ -0,0 +1,584 @@
+//! Named-envelope scenario DSL over Mollusk.
+
+use c_u_soon::{derive_config_address, Envelope, Mask, StructMetadata};
+use c_u_soon_client::{
+    clear_delegation_instruction_data, close_instruction_data, create_instruction_data,
+    fast_path_instruction_data, set_delegated_program_instruction_data,
+    update_auxiliary_delegated_instruction_data, update_auxiliary_instruction_data,
+};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
+use pinocchio::error::ProgramError;
+use solana_address::Address;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+};
+use solana_system_interface::program as system_program;
+
+use crate::runner::{envelope_in, set_account};
+use crate::{find_envelope_pda, fixture_address, EnvelopeFixture, Runner};
+
+/// Program id the scenario loads the program under unless overridden.
+pub const DEFAULT_PROGRAM_ID: Address = Address::new_from_array([
+    0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+    0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f, 0x20,
+]);
+
+/// Workspace build output of the program, without the `.so` extension.
+pub const DEFAULT_PROGRAM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../target/deploy/c_u_soon_program"
+);
+
+/// Lamports given to every authority the scenario creates.
+const AUTHORITY_LAMPORTS: u64 = 10_000_000_000;
+
+#[derive(Debug, Clone)]
+struct NamedEnvelope {
+    name: String,
+    address: Address,
+    authority: Address,
+    /// Delegation authority as last set by this scenario, for `clear_delegation`.
+    delegate: Option<Address>,
+}
+
+enum StepKind {
+    Create {
+        address: Address,
+        authority: Address,
+        seed: Vec<u8>,
+        bump: u8,
+        oracle_metadata: StructMetadata,
+    },
+    SetDelegatedProgram {
+        address: Address,
+        authority: Address,
+        delegate: Address,
+        program_bitmask: Mask,
+        user_bitmask: Mask,
+    },
+    ClearDelegation {
+        address: Address,
+        authority: Address,
+        delegate: Address,
+    },
+    FastPath {
+        address: Address,
+        authority: Address,
+        sequence: u64,
+        payload: Vec<u8>,
+    },
+    UpdateAux {
+        address: Address,
+        authority: Address,
+        sequence: u64,
+        data: Vec<u8>,
+    },
+    UpdateAuxDelegated {
+        address: Address,
+        delegate: Address,
+        sequence: u64,
+        data: Vec<u8>,
+    },
+    Close {
+        address: Address,
+        authority: Address,
+    },
+    Assert {
+        address: Address,
+        check: Box<dyn Fn(&Envelope)>,
+    },
+}
+
+struct Step {
+    label: String,
+    kind: StepKind,
+    /// `None` expects success.
+    expect_err: Option<ProgramError>,
+}
+
+/// A sequence of envelope operations, addressed by name, run as one Mollusk chain.
+///
+/// Builder methods act on the current envelope: the one most recently created or selected
+/// with [`on`](Self::on). Keys are [`fixture_address`]es derived from the names, so a
+/// scenario produces the same accounts on every run. Instruction steps expect success
+/// unless followed by [`expect_err`](Self::expect_err). Nothing executes until
+/// [`run`](Self::run), which panics at the first step that does not behave as expected.
+pub struct Scenario {
+    program_id: Address,
+    program_path: String,
+    accounts: Vec<(Address, Account)>,
+    envelopes: Vec<NamedEnvelope>,
+    current: Option<usize>,
+    steps: Vec<Step>,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scenario {
+    /// Scenario against the workspace build of the program at [`DEFAULT_PROGRAM_ID`].
+    pub fn new() -> Self {
+        Self::with_program(DEFAULT_PROGRAM_ID, DEFAULT_PROGRAM_PATH)
+    }
+
+    /// Scenario against the program `.so` at `program_path` (without extension).
+    pub fn with_program(program_id: Address, program_path: impl Into<String>) -> Self {
+        Self {
+            program_id,
+            program_path: program_path.into(),
+            accounts: vec![keyed_account_for_system_program()],
+            envelopes: Vec::new(),
+            current: None,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Create envelope `name` with the `Create` instruction and make it current.
+    ///
+    /// The authority is `fixture_address("<name>/authority")` and `name` is the single
+    /// custom seed, so it must be at most 32 bytes.
+    pub fn create_envelope(self, name: &str) -> Self {
+        self.create_envelope_with(name, StructMetadata::ZERO)
+    }
+
+    /// [`create_envelope`](Self::create_envelope) with the given oracle metadata.
+    pub fn create_envelope_with(mut self, name: &str, oracle_metadata: StructMetadata) -> Self {
+        assert!(name.len() <= 32, "envelope name {name:?} exceeds 32 bytes");
+        let authority = fixture_address(&format!("{name}/authority"));
+        let (address, bump) = find_envelope_pda(&self.program_id, &authority, &[name.as_bytes()]);
+        self.fund(authority);
+        self.set_account(address, Account::default());
+        self.push_envelope(name, address, authority);
+        self.push(
+            format!("create {name}"),
+            StepKind::Create {
+                address,
+                authority,
+                seed: name.as_bytes().to_vec(),
+                bump,
+                oracle_metadata,
+            },
+        )
+    }
+
+    /// Place an initialized envelope `name` in the starting state, without running
+    /// `Create`, and make it current.
+    ///
+    /// `Create` always leaves the aux metadata zeroed; use this when later steps write
+    /// auxiliary data of a given type. The envelope lives at `fixture_address(name)`.
+    pub fn existing_envelope(mut self, name: &str, auxiliary_metadata: StructMetadata) -> Self {
+        let authority = fixture_address(&format!("{name}/authority"));
+        let address = fixture_address(name);
+        let account = EnvelopeFixture::new(authority)
+            .aux_metadata(auxiliary_metadata)
+            .owner(self.program_id)
+            .account();
+        self.fund(authority);
+        self.set_account(address, account);
+        self.push_envelope(name, address, authority);
+        self
+    }
+
+    /// Make envelope `name` current. Panics if no such envelope was created.
+    pub fn on(mut self, name: &str) -> Self {
+        self.current = Some(
+            self.envelopes
+                .iter()
+                .position(|e| e.name == name)
+                .unwrap_or_else(|| panic!("no envelope named {name:?}")),
+        );
+        self
+    }
+
+    /// Delegate the current envelope to `fixture_address(program)` with the whole aux region
+    /// program-writable and nothing writable by the authority.
+    pub fn delegate_to(self, program: &str) -> Self {
+        self.delegate_with_masks(program, Mask::ALL_WRITABLE, Mask::ALL_BLOCKED)
+    }
+
+    /// Delegate the current envelope to `fixture_address(program)` with explicit bitmasks.
+    pub fn delegate_with_masks(
+        mut self,
+        program: &str,
+        program_bitmask: Mask,
+        user_bitmask: Mask,
+    ) -> Self {
+        let delegate = fixture_address(program);
+        self.fund(delegate);
+        let envelope = self.current_mut();
+        envelope.delegate = Some(delegate);
+        let (address, authority, name) =
+            (envelope.address, envelope.authority, envelope.name.clone());
+        self.push(
+            format!("delegate {name} to {program}"),
+            StepKind::SetDelegatedProgram {
+                address,
+                authority,
+                delegate,
+                program_bitmask,
+                user_bitmask,
+            },
+        )
+    }
+
+    /// Clear the delegation this scenario last set on the current envelope.
+    pub fn clear_delegation(self) -> Self {
+        let envelope = self.current();
+        let delegate = envelope
+            .delegate
+            .unwrap_or_else(|| panic!("{} was never delegated", envelope.name));
+        let (address, authority) = (envelope.address, envelope.authority);
+        let label = format!("clear delegation of {}", envelope.name);
+        self.push(
+            label,
+            StepKind::ClearDelegation {
+                address,
+                authority,
+                delegate,
+            },
+        )
+    }
+
+    /// Fast-path oracle update signed by the authority. The oracle metadata is read from the
+    /// envelope when the step runs.
+    pub fn fast_path(self, sequence: u64, payload: &[u8]) -> Self {
+        let envelope = self.current();
+        let (address, authority) = (envelope.address, envelope.authority);
+        let label = format!("fast path {} seq {sequence}", envelope.name);
+        self.push(
+            label,
+            StepKind::FastPath {
+                address,
+                authority,
+                sequence,
+                payload: payload.to_vec(),
+            },
+        )
+    }
+
+    /// `UpdateAuxiliary` from the authority. The aux metadata is read from the envelope when
+    /// the step runs.
+    pub fn update_aux(self, sequence: u64, data: &[u8]) -> Self {
+        let envelope = self.current();
+        let (address, authority) = (envelope.address, envelope.authority);
+        let label = format!("authority aux write {} seq {sequence}", envelope.name);
+        self.push(
+            label,
+            StepKind::UpdateAux {
+                address,
+                authority,
+                sequence,
+                data: data.to_vec(),
+            },
+        )
+    }
+
+    /// `UpdateAuxiliaryDelegated` from the delegate this scenario last set.
+    pub fn update_aux_delegated(self, sequence: u64, data: &[u8]) -> Self {
+        let envelope = self.current();
+        let delegate = envelope
+            .delegate
+            .unwrap_or_else(|| panic!("{} was never delegated", envelope.name));
+        let address = envelope.address;
+        let label = format!("delegated aux write {} seq {sequence}", envelope.name);
+        self.push(
+            label,
+            StepKind::UpdateAuxDelegated {
+                address,
+                delegate,
+                sequence,
+                data: data.to_vec(),
+            },
+        )
+    }
+
+    /// Close the current envelope, sending its lamports to
+    /// `fixture_address("scenario/recipient")`.
+    pub fn close(self) -> Self {
+        let envelope = self.current();
+        let (address, authority) = (envelope.address, envelope.authority);
+        let label = format!("close {}", envelope.name);
+        self.push(label, StepKind::Close { address, authority })
+    }
+
+    /// Expect the previous instruction step to fail with `err` instead of succeeding. A
+    /// failed step leaves account state unchanged.
+    pub fn expect_err(mut self, err: ProgramError) -> Self {
+        let step = self
+            .steps
+            .iter_mut()
+            .rev()
+            .find(|s| !matches!(s.kind, StepKind::Assert { .. }))
+            .expect("expect_err needs a preceding instruction step");
+        step.expect_err = Some(err);
+        self
+    }
+
+    /// Assert the current envelope's oracle sequence and payload prefix at this point.
+    pub fn expect_oracle(self, sequence: u64, payload: &[u8]) -> Self {
+        let payload = payload.to_vec();
+        self.expect_envelope(move |envelope| {
+            assert_eq!(envelope.oracle_state.sequence, sequence, "oracle sequence");
+            assert_eq!(
+                &envelope.oracle_state.data[..payload.len()],
+                &payload[..],
+                "oracle payload"
+            );
+        })
+    }
+
+    /// Assert `auxiliary_data[offset..offset + bytes.len()]` of the current envelope.
+    pub fn expect_aux(self, offset: usize, bytes: &[u8]) -> Self {
+        let bytes = bytes.to_vec();
+        self.expect_envelope(move |envelope| {
+            assert_eq!(
+                &envelope.auxiliary_data[offset..offset + bytes.len()],
+                &bytes[..],
+                "aux bytes at {offset}"
+            );
+        })
+    }
+
+    /// Run `check` against the current envelope's state at this point.
+    pub fn expect_envelope(self, check: impl Fn(&Envelope) + 'static) -> Self {
+        let envelope = self.current();
+        let address = envelope.address;
+        let label = format!("check {}", envelope.name);
+        self.push(
+            label,
+            StepKind::Assert {
+                address,
+                check: Box::new(check),
+            },
+        )
+    }
+
+    /// Execute every step in order. Panics at the first unexpected outcome; the failing
+    /// step's label is printed to stderr before it runs.
+    pub fn run(self) -> ScenarioResult {
+        let mut runner = Runner::with_program(self.program_id, &self.program_path);
+        for (address, account) in self.accounts {
+            runner.set_account(address, account);
+        }
+
+        for (index, step) in self.steps.into_iter().enumerate() {
+            eprintln!("scenario step {index}: {}", step.label);
+            if let StepKind::Assert { address, check } = &step.kind {
+                check(runner.envelope(address));
+                continue;
+            }
+
+            let instruction = step.kind.instruction(&self.program_id, runner.accounts());
+            let check = match step.expect_err {
+                None => Check::success(),
+                Some(err) => Check::err(err),
+            };
+            runner.process(&instruction, &[check]);
+        }
+
+        ScenarioResult {
+            accounts: runner.accounts().to_vec(),
+            envelopes: self.envelopes,
+        }
+    }
+
+    fn push(mut self, label: String, kind: StepKind) -> Self {
+        self.steps.push(Step {
+            label,
+            kind,
+            expect_err: None,
+        });
+        self
+    }
+
+    fn push_envelope(&mut self, name: &str, address: Address, authority: Address) {
+        assert!(
+            self.envelopes.iter().all(|e| e.name != name),
+            "envelope {name:?} already exists"
+        );
+        self.envelopes.push(NamedEnvelope {
+            name: name.to_string(),
+            address,
+            authority,
+            delegate: None,
+        });
+        self.current = Some(self.envelopes.len() - 1);
+    }
+
+    fn current(&self) -> &NamedEnvelope {
+        &self.envelopes[self.current.expect("no envelope created yet")]
+    }
+
+    fn current_mut(&mut self) -> &mut NamedEnvelope {
+        &mut self.envelopes[self.current.expect("no envelope created yet")]
+    }
+
+    fn fund(&mut self, address: Address) {
+        if !self.accounts.iter().any(|(a, _)| a == &address) {
+            self.set_account(
+                address,
+                Account::new(AUTHORITY_LAMPORTS, 0, &system_program::ID),
+            );
+        }
+    }
+
+    fn set_account(&mut self, address: Address, account: Account) {
+        set_account(&mut self.accounts, address, account);
+    }
+}
+
+impl StepKind {
+    fn instruction(&self, program_id: &Address, accounts: &[(Address, Account)]) -> Instruction {
+        let config = AccountMeta::new_readonly(derive_config_address(program_id).0, false);
+        let (data, metas) = match self {
+            StepKind::Create {
+                address,
+                authority,
+                seed,
+                bump,
+                oracle_metadata,
+            } => (
+                create_instruction_data(&[seed.as_slice()], *bump, *oracle_metadata).unwrap(),
+                vec![
+                    AccountMeta::new(*authority, true),
+                    AccountMeta::new(*address, false),
+                    AccountMeta::new_readonly(system_program::ID, false),
+                    AccountMeta::new(derive_config_address(program_id).0, false),
+                ],
+            ),
+            StepKind::SetDelegatedProgram {
+                address,
+                authority,
+                delegate,
+                program_bitmask,
+                user_bitmask,
+            } => (
+                set_delegated_program_instruction_data(*program_bitmask, *user_bitmask).unwrap(),
+                vec![
+                    AccountMeta::new_readonly(*authority, true),
+                    AccountMeta::new(*address, false),
+                    AccountMeta::new_readonly(*delegate, true),
+                    config,
+                ],
+            ),
+            StepKind::ClearDelegation {
+                address,
+                authority,
+                delegate,
+            } => (
+                clear_delegation_instruction_data().unwrap(),
+                vec![
+                    AccountMeta::new_readonly(*authority, true),
+                    AccountMeta::new(*address, false),
+                    AccountMeta::new_readonly(*delegate, true),
+                    config,
+                ],
+            ),
+            StepKind::FastPath {
+                address,
+                authority,
+                sequence,
+                payload,
+            } => {
+                let meta = envelope_in(accounts, address)
+                    .oracle_state
+                    .oracle_metadata
+                    .as_u64();
+                (
+                    fast_path_instruction_data(meta, *sequence, payload).unwrap(),
+                    vec![
+                        AccountMeta::new_readonly(*authority, true),
+                        AccountMeta::new(*address, false),
+                    ],
+                )
+            }
+            StepKind::UpdateAux {
+                address,
+                authority,
+                sequence,
+                data,
+            } => {
+                let meta = envelope_in(accounts, address).auxiliary_metadata.as_u64();
+                (
+                    update_auxiliary_instruction_data(meta, *sequence, data),
+                    vec![
+                        AccountMeta::new_readonly(*authority, true),
+                        AccountMeta::new(*address, false),
+                        AccountMeta::new_readonly(fixture_address("scenario/aux-signer"), true),
+                        config,
+                    ],
+                )
+            }
+            StepKind::UpdateAuxDelegated {
+                address,
+                delegate,
+                sequence,
+                data,
+            } => {
+                let meta = envelope_in(accounts, address).auxiliary_metadata.as_u64();
+                (
+                    update_auxiliary_delegated_instruction_data(meta, *sequence, data),
+                    vec![
+                        AccountMeta::new_readonly(*delegate, true),
+                        AccountMeta::new(*address, false),
+                        AccountMeta::new_readonly(fixture_address("scenario/padding"), false),
+                        config,
+                    ],
+                )
+            }
+            StepKind::Close { address, authority } => (
+                close_instruction_data().unwrap(),
+                vec![
+                    AccountMeta::new_readonly(*authority, true),
+                    AccountMeta::new(*address, false),
+                    AccountMeta::new(fixture_address("scenario/recipient"), false),
+                    config,
+                ],
+            ),
+            StepKind::Assert { .. } => unreachable!("assertions do not build instructions"),
+        };
+        Instruction::new_with_bytes(*program_id, &data, metas)
+    }
+}
+
+/// Final account state of a scenario.
+pub struct ScenarioResult {
+    accounts: Vec<(Address, Account)>,
+    envelopes: Vec<NamedEnvelope>,
+}
+
+impl ScenarioResult {
+    /// Address of envelope `name`.
+    pub fn address(&self, name: &str) -> Address {
+        self.named(name).address
+    }
+
+    /// Final state of envelope `name`. Panics if it was closed.
+    pub fn envelope(&self, name: &str) -> &Envelope {
+        envelope_in(&self.accounts, &self.named(name).address)
+    }
+
+    /// Final state of any account the scenario touched.
+    pub fn account(&self, address: &Address) -> Option<&Account> {
+        self.accounts
+            .iter()
+            .find(|(a, _)| a == address)
+            .map(|(_, account)| account)
+    }
+
+    /// Every account in its final state, for chaining further raw instructions.
+    pub fn accounts(&self) -> &[(Address, Account)] {
+        &self.accounts
+    }
+
+    fn named(&self, name: &str) -> &NamedEnvelope {
+        self.envelopes
+            .iter()
+            .find(|e| e.name == name)
+            .unwrap_or_else(|| panic!("no envelope named {name:?}"))
+    }
+}
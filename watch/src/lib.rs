@@ -0,0 +1,187 @@
+//! Streaming oracle updates from envelope accounts over the RPC websocket.
+//!
+//! [`subscribe`] opens an `accountSubscribe` subscription on a nonblocking `PubsubClient`
+//! and returns an [`OracleStream`], a `futures` `Stream` of typed [`OracleUpdate`]s.
+//! [`watch`] does the same on a blocking connection and hands each update to a callback
+//! until it returns [`ControlFlow::Break`].
+//!
+//! Both are built on [`OracleTracker`], which turns successive account states into updates:
+//! only states whose oracle sequence advanced are reported, and each update carries how
+//! many sequence numbers were skipped since the previous one. The tracker has no network
+//! dependency, so other transports (Geyser, polling) can feed it directly.
+
+mod tracker;
+
+use core::ops::ControlFlow;
+use core::pin::Pin;
+use core::str::FromStr;
+use core::task::{Context, Poll};
+
+use bytemuck::Pod;
+use c_u_soon::{StructMetadata, TypeHash};
+use c_u_soon_client::Commitment;
+use futures_util::future::BoxFuture;
+use futures_util::stream::{BoxStream, Stream, StreamExt};
+use solana_account_decoder_client_types::{UiAccount, UiAccountEncoding};
+use solana_address::Address;
+use solana_commitment_config::CommitmentConfig;
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_pubsub_client::pubsub_client::PubsubClientError;
+use solana_rpc_client_api::config::RpcAccountInfoConfig;
+use solana_rpc_client_api::response::Response as RpcResponse;
+
+pub use tracker::{OracleTracker, OracleUpdate};
+
+/// Errors from watching an envelope.
+#[derive(Debug)]
+pub enum WatchError {
+    /// Opening the subscription failed.
+    Pubsub(PubsubClientError),
+    /// A notification's owner or data could not be decoded.
+    UndecodableAccount,
+    /// The account is not owned by the c_u_soon program, for example after it was closed.
+    WrongOwner { owner: Address },
+    /// The account is shorter than [`Envelope::SIZE`](c_u_soon::Envelope::SIZE).
+    InvalidAccountData,
+    /// The envelope's oracle metadata is not the watched type's.
+    TypeMismatch { metadata: StructMetadata },
+}
+
+impl core::fmt::Display for WatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Pubsub(e) => write!(f, "pubsub: {e}"),
+            Self::UndecodableAccount => write!(f, "account notification could not be decoded"),
+            Self::WrongOwner { owner } => write!(f, "account owned by {owner}, not c_u_soon"),
+            Self::InvalidAccountData => write!(f, "account too small for an envelope"),
+            Self::TypeMismatch { metadata } => {
+                write!(
+                    f,
+                    "oracle metadata 0x{:016x} is not the watched type",
+                    metadata.as_u64()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for WatchError {}
+
+fn account_config(commitment: Commitment) -> RpcAccountInfoConfig {
+    RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        data_slice: None,
+        commitment: Some(match commitment {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }),
+        min_context_slot: None,
+    }
+}
+
+fn decode<T: TypeHash + Pod>(
+    tracker: &mut OracleTracker<T>,
+    response: RpcResponse<UiAccount>,
+) -> Result<Option<OracleUpdate<T>>, WatchError> {
+    let account = response.value;
+    let owner = Address::from_str(&account.owner).map_err(|_| WatchError::UndecodableAccount)?;
+    let data = account
+        .data
+        .decode()
+        .ok_or(WatchError::UndecodableAccount)?;
+    tracker.on_account(response.context.slot, &owner, &data)
+}
+
+type UnsubscribeFn = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+/// Oracle updates of one envelope, from [`subscribe`].
+///
+/// Yields `Err` for notifications that cannot be decoded as a `T` envelope and keeps going;
+/// ends when the websocket closes. Dropping the stream leaves the subscription open until
+/// the client is dropped; call [`unsubscribe`](Self::unsubscribe) to end it first.
+pub struct OracleStream<'a, T> {
+    notifications: BoxStream<'a, RpcResponse<UiAccount>>,
+    unsubscribe: UnsubscribeFn,
+    tracker: OracleTracker<T>,
+}
+
+impl<T> OracleStream<'_, T> {
+    /// End the subscription.
+    pub async fn unsubscribe(self) {
+        (self.unsubscribe)().await
+    }
+}
+
+impl<T: TypeHash + Pod> Stream for OracleStream<'_, T> {
+    type Item = Result<OracleUpdate<T>, WatchError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let Some(response) = futures_util::ready!(this.notifications.poll_next_unpin(cx))
+            else {
+                return Poll::Ready(None);
+            };
+            if let Some(item) = decode(&mut this.tracker, response).transpose() {
+                return Poll::Ready(Some(item));
+            }
+        }
+    }
+}
+
+/// Subscribe to oracle updates of the envelope at `address`, decoded as `T`.
+pub async fn subscribe<'a, T: TypeHash + Pod>(
+    client: &'a PubsubClient,
+    program_id: Address,
+    address: Address,
+    commitment: Commitment,
+) -> Result<OracleStream<'a, T>, WatchError> {
+    let (notifications, unsubscribe) = client
+        .account_subscribe(&address, Some(account_config(commitment)))
+        .await
+        .map_err(WatchError::Pubsub)?;
+    Ok(OracleStream {
+        notifications,
+        unsubscribe,
+        tracker: OracleTracker::new(program_id, address),
+    })
+}
+
+/// Call `on_update` with every oracle update of the envelope at `address`, decoded as `T`,
+/// over a blocking websocket connection to `url`.
+///
+/// Returns when `on_update` returns [`ControlFlow::Break`] or the websocket closes, and
+/// fails only if the subscription cannot be opened. Decoding errors are passed to
+/// `on_update`, which decides whether to keep watching.
+pub fn watch<T, F>(
+    url: &str,
+    program_id: Address,
+    address: Address,
+    commitment: Commitment,
+    mut on_update: F,
+) -> Result<(), WatchError>
+where
+    T: TypeHash + Pod,
+    F: FnMut(Result<OracleUpdate<T>, WatchError>) -> ControlFlow<()>,
+{
+    let (mut subscription, notifications) =
+        solana_pubsub_client::pubsub_client::PubsubClient::account_subscribe(
+            url,
+            &address,
+            Some(account_config(commitment)),
+        )
+        .map_err(WatchError::Pubsub)?;
+    let mut tracker = OracleTracker::new(program_id, address);
+    while let Ok(response) = notifications.recv() {
+        let Some(item) = decode(&mut tracker, response).transpose() else {
+            continue;
+        };
+        if on_update(item).is_break() {
+            break;
+        }
+    }
+    // The connection is going away either way; a panicked reader thread has nothing to add.
+    let _ = subscription.shutdown();
+    Ok(())
+}
@@ -0,0 +1,199 @@
+//! Decoding of account notifications into [`OracleUpdate`]s, independent of the transport.
+
+use core::marker::PhantomData;
+
+use bytemuck::Pod;
+use c_u_soon::TypeHash;
+use c_u_soon_client::{DecodedEnvelope, EnvelopeError};
+use solana_address::Address;
+
+use crate::WatchError;
+
+/// A new oracle value written to a watched envelope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OracleUpdate<T> {
+    pub address: Address,
+    /// Slot of the notification.
+    pub slot: u64,
+    pub sequence: u64,
+    pub value: T,
+    /// Sequence numbers between the previous update and this one, `sequence - previous - 1`.
+    /// Zero for the first update. Publishers are not required to count by one, so whether
+    /// a gap means a missed write depends on the feed.
+    pub missed: u64,
+}
+
+impl<T> OracleUpdate<T> {
+    /// `true` if sequence numbers were skipped since the previous update.
+    pub fn has_gap(&self) -> bool {
+        self.missed > 0
+    }
+}
+
+/// Turns the successive account states of one envelope into [`OracleUpdate`]s.
+///
+/// Only states whose oracle sequence advanced produce an update: aux writes, admin
+/// instructions, and replays of an already seen state return `Ok(None)`, as do envelopes
+/// whose oracle was never written (sequence 0).
+#[derive(Debug, Clone, Copy)]
+pub struct OracleTracker<T> {
+    program_id: Address,
+    address: Address,
+    /// `(slot, sequence)` of the last update.
+    last: Option<(u64, u64)>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T: TypeHash + Pod> OracleTracker<T> {
+    pub fn new(program_id: Address, address: Address) -> Self {
+        Self {
+            program_id,
+            address,
+            last: None,
+            _type: PhantomData,
+        }
+    }
+
+    /// Sequence of the last update, if any.
+    pub fn last_sequence(&self) -> Option<u64> {
+        self.last.map(|(_, sequence)| sequence)
+    }
+
+    /// Decode the account state `data`, owned by `owner`, seen at `slot`.
+    ///
+    /// Fails with [`WatchError::WrongOwner`] if the account is not owned by the program
+    /// (including after it is closed), [`WatchError::InvalidAccountData`] if it is too short
+    /// for an envelope, and [`WatchError::TypeMismatch`] if its oracle metadata is not
+    /// `T::METADATA`. Errors leave the tracker unchanged.
+    pub fn on_account(
+        &mut self,
+        slot: u64,
+        owner: &Address,
+        data: &[u8],
+    ) -> Result<Option<OracleUpdate<T>>, WatchError> {
+        let decoded = DecodedEnvelope::from_account::<core::convert::Infallible>(
+            &self.program_id,
+            self.address,
+            owner,
+            slot,
+            data,
+        )
+        .map_err(|e| match e {
+            EnvelopeError::WrongOwner { owner } => WatchError::WrongOwner { owner },
+            _ => WatchError::InvalidAccountData,
+        })?;
+
+        let sequence = decoded.oracle_sequence();
+        if sequence == 0 {
+            return Ok(None);
+        }
+        if let Some((last_slot, last_sequence)) = self.last {
+            if slot < last_slot || sequence <= last_sequence {
+                return Ok(None);
+            }
+        }
+        let value = *decoded.oracle::<T>().ok_or(WatchError::TypeMismatch {
+            metadata: decoded.envelope().oracle_state.oracle_metadata,
+        })?;
+
+        let missed = self
+            .last
+            .map_or(0, |(_, last_sequence)| sequence - last_sequence - 1);
+        self.last = Some((slot, sequence));
+        Ok(Some(OracleUpdate {
+            address: self.address,
+            slot,
+            sequence,
+            value,
+            missed,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+    use c_u_soon::Envelope;
+
+    const PROGRAM: Address = Address::new_from_array([1; 32]);
+    const ENVELOPE: Address = Address::new_from_array([2; 32]);
+
+    fn data(sequence: u64, value: u64) -> Vec<u8> {
+        let mut envelope = Envelope::zeroed();
+        envelope.oracle_state.oracle_metadata = u64::METADATA;
+        envelope.oracle_state.sequence = sequence;
+        envelope.oracle_state.data[..8].copy_from_slice(&value.to_le_bytes());
+        bytemuck::bytes_of(&envelope).to_vec()
+    }
+
+    #[test]
+    fn reports_advancing_sequences_with_gaps() {
+        let mut tracker = OracleTracker::<u64>::new(PROGRAM, ENVELOPE);
+        assert_eq!(tracker.on_account(10, &PROGRAM, &data(0, 0)).unwrap(), None);
+
+        let first = tracker
+            .on_account(11, &PROGRAM, &data(5, 100))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            first,
+            OracleUpdate {
+                address: ENVELOPE,
+                slot: 11,
+                sequence: 5,
+                value: 100,
+                missed: 0,
+            }
+        );
+
+        // An aux write leaves the oracle sequence alone.
+        assert_eq!(
+            tracker.on_account(12, &PROGRAM, &data(5, 100)).unwrap(),
+            None
+        );
+
+        let next = tracker
+            .on_account(13, &PROGRAM, &data(6, 101))
+            .unwrap()
+            .unwrap();
+        assert!(!next.has_gap());
+        let skipped = tracker
+            .on_account(14, &PROGRAM, &data(9, 99))
+            .unwrap()
+            .unwrap();
+        assert_eq!((skipped.value, skipped.missed), (99, 2));
+        assert_eq!(tracker.last_sequence(), Some(9));
+    }
+
+    #[test]
+    fn ignores_replays() {
+        let mut tracker = OracleTracker::<u64>::new(PROGRAM, ENVELOPE);
+        tracker
+            .on_account(20, &PROGRAM, &data(3, 1))
+            .unwrap()
+            .unwrap();
+        assert_eq!(tracker.on_account(21, &PROGRAM, &data(2, 1)).unwrap(), None);
+        assert_eq!(tracker.on_account(19, &PROGRAM, &data(4, 1)).unwrap(), None);
+        assert_eq!(tracker.last_sequence(), Some(3));
+    }
+
+    #[test]
+    fn rejects_foreign_short_and_mistyped_accounts() {
+        let mut tracker = OracleTracker::<u32>::new(PROGRAM, ENVELOPE);
+        let other = Address::new_from_array([3; 32]);
+        assert!(matches!(
+            tracker.on_account(1, &other, &data(1, 1)),
+            Err(WatchError::WrongOwner { owner }) if owner == other
+        ));
+        assert!(matches!(
+            tracker.on_account(1, &PROGRAM, &[0; 16]),
+            Err(WatchError::InvalidAccountData)
+        ));
+        assert!(matches!(
+            tracker.on_account(1, &PROGRAM, &data(1, 1)),
+            Err(WatchError::TypeMismatch { metadata }) if metadata == u64::METADATA
+        ));
+        assert_eq!(tracker.last_sequence(), None);
+    }
+}
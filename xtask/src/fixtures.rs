@@ -0,0 +1,706 @@
+//! Golden vectors for clients in other languages, written to `fixtures/` by
+//! `cargo xtask gen-fixtures`.
+//!
+//! - `fixtures/instructions.json`: the instruction data of one instance of every
+//!   instruction, named as in the IDL, plus a versioned `Close`. Inputs are fixed and valid,
+//!   so each vector also passes `SlowPathInstruction::validate`.
+//! - `fixtures/envelope.json`: an `Envelope` with every field set to a distinct non-zero
+//!   value, with the offset and length of each field.
+//!
+//! Bytes are lowercase hex. The files are checked in; the tests below fail when they no
+//! longer match what this module generates, so a wire or layout change must regenerate them.
+
+use std::mem::{offset_of, size_of};
+use std::path::Path;
+
+use bytemuck::Zeroable;
+use c_u_soon::{
+    Constraint, ConstraintOp, ConstraintRegion, Envelope, Mask, OracleState, StructMetadata,
+    AUX_DATA_SIZE, COMPACT_MASK_SIZE, FLAG_TWAP, FLAG_UPDATE_STAMP, LABEL_SIZE, MASK_SIZE,
+    ORACLE_BYTES,
+};
+use c_u_soon_client::{
+    batch_update_instruction_data, fast_path_instruction_data, publisher_update_instruction_data,
+    split_update_instruction_data, update_auxiliary_delegated_instruction_data,
+    update_auxiliary_delegated_range_instruction_data,
+    update_auxiliary_delegated_schema_instruction_data, update_auxiliary_force_instruction_data,
+    update_auxiliary_instruction_data, update_auxiliary_range_instruction_data,
+    versioned_instruction_data,
+};
+use c_u_soon_instruction::{
+    BatchEntry, GroupMemberUpdate, RangeGuardSpec, SlowPathInstruction, WriteSpec, WriteSpecV2,
+    INSTRUCTION_VERSION,
+};
+use serde_json::{json, Value};
+use solana_address::Address;
+
+/// Oracle metadata carried by the vectors: an 8-byte type.
+const ORACLE_META: StructMetadata = StructMetadata::new(8, 0x00112233445566);
+/// Auxiliary metadata carried by the vectors: a 16-byte type.
+const AUX_META: StructMetadata = StructMetadata::new(16, 0x00778899AABBCC);
+
+/// First half writable, second half blocked.
+fn half_mask() -> [u8; MASK_SIZE] {
+    let mut mask = Mask::ALL_BLOCKED;
+    mask.allow_range(0, 128);
+    mask.into()
+}
+
+/// [`half_mask`] inverted.
+fn other_half_mask() -> [u8; MASK_SIZE] {
+    let mut mask = Mask::ALL_WRITABLE;
+    mask.block_range(0, 128);
+    mask.into()
+}
+
+fn write_specs() -> Vec<WriteSpec> {
+    vec![
+        WriteSpec {
+            offset: 0,
+            data: vec![1, 2, 3],
+        },
+        WriteSpec {
+            offset: 200,
+            data: vec![0xAA; 8],
+        },
+    ]
+}
+
+fn write_specs_v2() -> Vec<WriteSpecV2> {
+    write_specs().into_iter().map(WriteSpecV2::from).collect()
+}
+
+fn label(text: &str) -> [u8; LABEL_SIZE] {
+    let mut label = [0; LABEL_SIZE];
+    label[..text.len()].copy_from_slice(text.as_bytes());
+    label
+}
+
+fn wincode(ix: SlowPathInstruction) -> Vec<u8> {
+    wincode::serialize(&ix).expect("fixture instructions always serialize")
+}
+
+/// `(IDL name, instruction data)` for every instruction, in tag order.
+pub fn instruction_vectors() -> Vec<(&'static str, Vec<u8>)> {
+    let meta = ORACLE_META.as_u64();
+    let seeds = vec![b"feed".to_vec(), vec![7; 32]];
+    vec![
+        (
+            "fast_path",
+            fast_path_instruction_data(meta, 1, &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap(),
+        ),
+        (
+            "create",
+            wincode(SlowPathInstruction::Create {
+                custom_seeds: seeds.clone(),
+                bump: 254,
+                oracle_metadata: meta,
+            }),
+        ),
+        ("close", wincode(SlowPathInstruction::Close)),
+        (
+            "set_delegated_program",
+            wincode(SlowPathInstruction::SetDelegatedProgram {
+                program_bitmask: half_mask(),
+                user_bitmask: other_half_mask(),
+            }),
+        ),
+        (
+            "clear_delegation",
+            wincode(SlowPathInstruction::ClearDelegation),
+        ),
+        (
+            "update_auxiliary",
+            update_auxiliary_instruction_data(AUX_META.as_u64(), 2, &[0xA1; 16]),
+        ),
+        (
+            "update_auxiliary_delegated",
+            update_auxiliary_delegated_instruction_data(AUX_META.as_u64(), 3, &[0xA2; 16]),
+        ),
+        (
+            "update_auxiliary_force",
+            update_auxiliary_force_instruction_data(AUX_META.as_u64(), 4, 5, &[0xA3; 16]),
+        ),
+        (
+            "update_auxiliary_range",
+            update_auxiliary_range_instruction_data(AUX_META.as_u64(), 6, 8, &[0xA4; 4]),
+        ),
+        (
+            "update_auxiliary_delegated_range",
+            update_auxiliary_delegated_range_instruction_data(AUX_META.as_u64(), 7, 12, &[0xA5; 4]),
+        ),
+        (
+            "update_auxiliary_multi_range",
+            wincode(SlowPathInstruction::UpdateAuxiliaryMultiRange {
+                metadata: AUX_META.as_u64(),
+                sequence: 8,
+                ranges: write_specs(),
+            }),
+        ),
+        (
+            "update_auxiliary_delegated_multi_range",
+            wincode(SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRange {
+                metadata: AUX_META.as_u64(),
+                sequence: 9,
+                ranges: write_specs(),
+            }),
+        ),
+        (
+            "update_auxiliary_multi_range_force",
+            wincode(SlowPathInstruction::UpdateAuxiliaryMultiRangeForce {
+                metadata: AUX_META.as_u64(),
+                authority_sequence: 10,
+                program_sequence: 11,
+                ranges: write_specs(),
+            }),
+        ),
+        (
+            "set_range_guards",
+            wincode(SlowPathInstruction::SetRangeGuards {
+                bump: 253,
+                entries: vec![RangeGuardSpec {
+                    program_id: [0x13; 32],
+                    offset: 32,
+                    len: 16,
+                }],
+            }),
+        ),
+        (
+            "set_delegate_schema",
+            wincode(SlowPathInstruction::SetDelegateSchema {
+                bump: 252,
+                offset: 8,
+                metadata: ORACLE_META.as_u64(),
+            }),
+        ),
+        (
+            "update_auxiliary_delegated_schema",
+            update_auxiliary_delegated_schema_instruction_data(meta, 12, &[0xA6; 8]),
+        ),
+        ("set_permanent", wincode(SlowPathInstruction::SetPermanent)),
+        (
+            "set_mutation_guard",
+            wincode(SlowPathInstruction::SetMutationGuard { allow_multi: true }),
+        ),
+        (
+            "read_aux_range",
+            wincode(SlowPathInstruction::ReadAuxRange { offset: 16, len: 8 }),
+        ),
+        (
+            "set_group_members",
+            wincode(SlowPathInstruction::SetGroupMembers {
+                group_id: 0x0102_0304_0506_0708,
+                bump: 251,
+            }),
+        ),
+        (
+            "group_commit",
+            wincode(SlowPathInstruction::GroupCommit {
+                sequence: 13,
+                updates: vec![
+                    GroupMemberUpdate {
+                        oracle_meta: meta,
+                        payload: vec![0xB1; 8],
+                    },
+                    GroupMemberUpdate {
+                        oracle_meta: meta,
+                        payload: vec![0xB2; 4],
+                    },
+                ],
+            }),
+        ),
+        (
+            "set_label",
+            wincode(SlowPathInstruction::SetLabel {
+                bump: 250,
+                label: label("SOL/USD"),
+            }),
+        ),
+        (
+            "assert_aux_hash",
+            wincode(SlowPathInstruction::AssertAuxHash {
+                expected: [0x22; 32],
+            }),
+        ),
+        ("read_oracle", wincode(SlowPathInstruction::ReadOracle)),
+        ("read_aux", wincode(SlowPathInstruction::ReadAux)),
+        (
+            "batch_update",
+            batch_update_instruction_data(&[
+                BatchEntry {
+                    oracle_meta: meta,
+                    sequence: 14,
+                    payload: &[1, 2, 3],
+                },
+                BatchEntry {
+                    oracle_meta: meta,
+                    sequence: 15,
+                    payload: &[4; 8],
+                },
+            ])
+            .unwrap(),
+        ),
+        (
+            "resize",
+            wincode(SlowPathInstruction::Resize {
+                type_hash: 0x0BAD_F00D,
+                ext_len: 64,
+            }),
+        ),
+        (
+            "update_ext_aux",
+            wincode(SlowPathInstruction::UpdateExtAux {
+                sequence: 16,
+                offset: 4,
+                data: vec![0xA7; 4],
+            }),
+        ),
+        (
+            "set_twap",
+            wincode(SlowPathInstruction::SetTwap { enabled: true }),
+        ),
+        (
+            "set_update_stamp",
+            wincode(SlowPathInstruction::SetUpdateStamp { enabled: true }),
+        ),
+        (
+            "set_constraints",
+            wincode(SlowPathInstruction::SetConstraints {
+                bump: 249,
+                constraints: vec![Constraint::against_const(
+                    ConstraintRegion::Aux,
+                    8,
+                    8,
+                    false,
+                    ConstraintOp::Le,
+                    1_000,
+                )
+                .into()],
+            }),
+        ),
+        (
+            "set_publishers",
+            wincode(SlowPathInstruction::SetPublishers {
+                bump: 248,
+                threshold: 2,
+                publishers: vec![[0x31; 32], [0x32; 32]],
+            }),
+        ),
+        (
+            "publisher_update",
+            publisher_update_instruction_data(meta, 17, &[5; 8]).unwrap(),
+        ),
+        (
+            "set_delegated_pda",
+            wincode(SlowPathInstruction::SetDelegatedPda {
+                program_id: [0x33; 32],
+                seeds: vec![b"delegate".to_vec()],
+                bump: 247,
+                program_bitmask: half_mask(),
+                user_bitmask: other_half_mask(),
+            }),
+        ),
+        (
+            "close_with_split",
+            wincode(SlowPathInstruction::CloseWithSplit { treasury_bps: 250 }),
+        ),
+        (
+            "versioned_close",
+            versioned_instruction_data(&wincode(SlowPathInstruction::Close)),
+        ),
+        (
+            "set_conflation",
+            wincode(SlowPathInstruction::SetConflation { enabled: true }),
+        ),
+        (
+            "set_readers",
+            wincode(SlowPathInstruction::SetReaders {
+                bump: 246,
+                readers: vec![[0x37; 32]],
+            }),
+        ),
+        (
+            "read_oracle_gated",
+            wincode(SlowPathInstruction::ReadOracleGated),
+        ),
+        (
+            "aggregate",
+            wincode(SlowPathInstruction::Aggregate {
+                sequence: 18,
+                min_sources: 2,
+                max_deviation_bps: 100,
+            }),
+        ),
+        (
+            "set_publisher",
+            wincode(SlowPathInstruction::SetPublisher {
+                publisher: [0x40; 32],
+            }),
+        ),
+        (
+            "set_paused",
+            wincode(SlowPathInstruction::SetPaused { paused: true }),
+        ),
+        (
+            "create_with_events",
+            wincode(SlowPathInstruction::CreateWithEvents {
+                custom_seeds: seeds,
+                bump: 254,
+                oracle_metadata: meta,
+            }),
+        ),
+        (
+            "seal_auxiliary_range",
+            wincode(SlowPathInstruction::SealAuxiliaryRange { offset: 32, len: 8 }),
+        ),
+        (
+            "init_config",
+            wincode(SlowPathInstruction::InitConfig {
+                bump: 245,
+                admin: [0x44; 32],
+                create_fee: 1_000_000,
+            }),
+        ),
+        (
+            "set_config",
+            wincode(SlowPathInstruction::SetConfig {
+                admin: [0x45; 32],
+                create_fee: 2_000_000,
+                paused: false,
+            }),
+        ),
+        (
+            "heartbeat",
+            wincode(SlowPathInstruction::Heartbeat { sequence: 19 }),
+        ),
+        (
+            "transfer_delegation",
+            wincode(SlowPathInstruction::TransferDelegation {
+                program_bitmask: other_half_mask(),
+                user_bitmask: half_mask(),
+            }),
+        ),
+        (
+            "set_delegated_program_compact",
+            wincode(SlowPathInstruction::SetDelegatedProgramCompact {
+                program_bitmask: [0x0F; COMPACT_MASK_SIZE],
+                user_bitmask: [0xF0; COMPACT_MASK_SIZE],
+            }),
+        ),
+        ("export_state", wincode(SlowPathInstruction::ExportState)),
+        (
+            "set_write_hook",
+            wincode(SlowPathInstruction::SetWriteHook {
+                notify_on_authority_write: true,
+            }),
+        ),
+        (
+            "set_oracle_metadata",
+            wincode(SlowPathInstruction::SetOracleMetadata {
+                oracle_metadata: meta,
+                auxiliary_metadata: AUX_META.as_u64(),
+            }),
+        ),
+        (
+            "update_auxiliary_multi_range_v2",
+            wincode(SlowPathInstruction::UpdateAuxiliaryMultiRangeV2 {
+                metadata: AUX_META.as_u64(),
+                sequence: 20,
+                ranges: write_specs_v2(),
+            }),
+        ),
+        (
+            "update_auxiliary_delegated_multi_range_v2",
+            wincode(SlowPathInstruction::UpdateAuxiliaryDelegatedMultiRangeV2 {
+                metadata: AUX_META.as_u64(),
+                sequence: 21,
+                ranges: write_specs_v2(),
+            }),
+        ),
+        (
+            "update_auxiliary_multi_range_force_v2",
+            wincode(SlowPathInstruction::UpdateAuxiliaryMultiRangeForceV2 {
+                metadata: AUX_META.as_u64(),
+                authority_sequence: 22,
+                program_sequence: 23,
+                ranges: write_specs_v2(),
+            }),
+        ),
+        (
+            "migrate_envelope",
+            wincode(SlowPathInstruction::MigrateEnvelope),
+        ),
+        (
+            "split_update",
+            split_update_instruction_data(0x1234, 17, &[5; 8]).unwrap()[0].clone(),
+        ),
+    ]
+}
+
+/// An envelope with every field non-zero, except the oracle state's trailing padding byte.
+pub fn envelope_vector() -> Envelope {
+    let mut envelope = Envelope::zeroed();
+    envelope.authority = Address::new_from_array([0x01; 32]);
+    envelope.oracle_state.oracle_metadata = ORACLE_META;
+    envelope.oracle_state.sequence = 42;
+    envelope.oracle_state.data = core::array::from_fn(|i| i as u8 + 1);
+    envelope.bump = 254;
+    envelope.flags = FLAG_TWAP | FLAG_UPDATE_STAMP;
+    envelope.aux_write_count = 0x0102;
+    envelope.aux_dirty_blocks = 0x81;
+    envelope.constrained_regions = ConstraintRegion::Aux.bit();
+    envelope.paused = 1;
+    envelope.emit_events = 1;
+    envelope.delegation_authority = Address::new_from_array([0x02; 32]);
+    envelope.program_bitmask = bytemuck::cast(half_mask());
+    envelope.user_bitmask = bytemuck::cast(other_half_mask());
+    envelope.program_bitmask.seal_range(0, 4);
+    envelope.user_bitmask.seal_range(0, 4);
+    envelope.authority_aux_sequence = 7;
+    envelope.program_aux_sequence = 9;
+    envelope.auxiliary_metadata = AUX_META;
+    envelope.auxiliary_data = core::array::from_fn(|i| 0xFF - i as u8);
+    envelope.publisher = Address::new_from_array([0x03; 32]);
+    envelope.heartbeat_slot = 123_456_789;
+    envelope.write_hook = Address::new_from_array([0x04; 32]);
+    envelope.mutation_marker = 0x0506_0708_090A_0B0C;
+    envelope
+}
+
+/// `(name, offset, len)` of every `Envelope` field, nested `OracleState` fields included.
+fn envelope_fields() -> Vec<(&'static str, usize, usize)> {
+    let oracle = offset_of!(Envelope, oracle_state);
+    vec![
+        ("authority", offset_of!(Envelope, authority), 32),
+        (
+            "oracle_state.oracle_metadata",
+            oracle + offset_of!(OracleState, oracle_metadata),
+            8,
+        ),
+        (
+            "oracle_state.sequence",
+            oracle + offset_of!(OracleState, sequence),
+            8,
+        ),
+        (
+            "oracle_state.data",
+            oracle + offset_of!(OracleState, data),
+            ORACLE_BYTES,
+        ),
+        ("bump", offset_of!(Envelope, bump), 1),
+        ("flags", offset_of!(Envelope, flags), 1),
+        ("aux_write_count", offset_of!(Envelope, aux_write_count), 2),
+        (
+            "aux_dirty_blocks",
+            offset_of!(Envelope, aux_dirty_blocks),
+            1,
+        ),
+        (
+            "constrained_regions",
+            offset_of!(Envelope, constrained_regions),
+            1,
+        ),
+        ("paused", offset_of!(Envelope, paused), 1),
+        ("emit_events", offset_of!(Envelope, emit_events), 1),
+        (
+            "delegation_authority",
+            offset_of!(Envelope, delegation_authority),
+            32,
+        ),
+        (
+            "program_bitmask",
+            offset_of!(Envelope, program_bitmask),
+            size_of::<Mask>(),
+        ),
+        (
+            "user_bitmask",
+            offset_of!(Envelope, user_bitmask),
+            size_of::<Mask>(),
+        ),
+        (
+            "authority_aux_sequence",
+            offset_of!(Envelope, authority_aux_sequence),
+            8,
+        ),
+        (
+            "program_aux_sequence",
+            offset_of!(Envelope, program_aux_sequence),
+            8,
+        ),
+        (
+            "auxiliary_metadata",
+            offset_of!(Envelope, auxiliary_metadata),
+            8,
+        ),
+        (
+            "auxiliary_data",
+            offset_of!(Envelope, auxiliary_data),
+            AUX_DATA_SIZE,
+        ),
+        ("publisher", offset_of!(Envelope, publisher), 32),
+        ("heartbeat_slot", offset_of!(Envelope, heartbeat_slot), 8),
+        ("write_hook", offset_of!(Envelope, write_hook), 32),
+        ("mutation_marker", offset_of!(Envelope, mutation_marker), 8),
+    ]
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// `fixtures/instructions.json`. Keys are in sorted order so the output does not depend on
+/// `serde_json`'s map ordering.
+pub fn instructions_json() -> Value {
+    json!({
+        "instruction_version": INSTRUCTION_VERSION,
+        "instructions": instruction_vectors()
+            .into_iter()
+            .map(|(name, data)| json!({ "data": hex(&data), "name": name }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// `fixtures/envelope.json`.
+pub fn envelope_json() -> Value {
+    json!({
+        "data": hex(bytemuck::bytes_of(&envelope_vector())),
+        "fields": envelope_fields()
+            .into_iter()
+            .map(|(name, offset, len)| json!({ "len": len, "name": name, "offset": offset }))
+            .collect::<Vec<_>>(),
+        "size": Envelope::SIZE,
+    })
+}
+
+/// Every fixture file as `(path relative to the workspace root, contents)`.
+pub fn files() -> Vec<(&'static str, String)> {
+    let render = |value: Value| {
+        serde_json::to_string_pretty(&value).expect("fixture JSON always serializes") + "\n"
+    };
+    vec![
+        ("fixtures/instructions.json", render(instructions_json())),
+        ("fixtures/envelope.json", render(envelope_json())),
+    ]
+}
+
+/// Write [`files`] under `root`.
+pub fn generate(root: &Path) -> Result<(), String> {
+    for (path, contents) in files() {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().expect("fixture paths have a directory"))
+            .map_err(|e| format!("failed to create {}: {e}", path.display()))?;
+        std::fs::write(&path, contents)
+            .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+        eprintln!("wrote {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace_root;
+    use c_u_soon_client::decode_instruction;
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn checked_in(path: &str) -> Value {
+        let source = std::fs::read_to_string(workspace_root().join(path))
+            .unwrap_or_else(|e| panic!("{path}: {e}; run `cargo xtask gen-fixtures`"));
+        serde_json::from_str(&source).unwrap()
+    }
+
+    #[test]
+    fn checked_in_fixtures_are_current() {
+        for (path, contents) in files() {
+            let on_disk = std::fs::read_to_string(workspace_root().join(path)).unwrap();
+            assert!(
+                on_disk == contents,
+                "{path} is stale; run `cargo xtask gen-fixtures`"
+            );
+        }
+    }
+
+    #[test]
+    fn instruction_vectors_round_trip() {
+        let fixtures = checked_in("fixtures/instructions.json");
+        for entry in fixtures["instructions"].as_array().unwrap() {
+            let name = entry["name"].as_str().unwrap();
+            let data = unhex(entry["data"].as_str().unwrap());
+            let accounts = if name == "fast_path" { 2 } else { 3 };
+            decode_instruction(
+                &Address::default(),
+                &vec![Address::default(); accounts],
+                &data,
+            )
+            .unwrap_or_else(|e| panic!("{name}: {e:?}"));
+
+            let Ok(ix) = wincode::deserialize::<SlowPathInstruction>(&data) else {
+                continue;
+            };
+            assert!(ix.validate(), "{name} fails validate()");
+            assert_eq!(
+                wincode::serialize(&ix).unwrap(),
+                data,
+                "{name} does not re-encode to its vector"
+            );
+        }
+    }
+
+    #[test]
+    fn every_idl_instruction_has_a_vector() {
+        let vectors = instruction_vectors();
+        let idl = c_u_soon_idl::idl(&Address::default());
+        let instructions = idl["instructions"].as_array().unwrap();
+        for ix in instructions {
+            let name = ix["name"].as_str().unwrap();
+            let (_, data) = vectors
+                .iter()
+                .find(|(vector, _)| *vector == name)
+                .unwrap_or_else(|| panic!("no vector for {name}"));
+            let discriminator: Vec<u8> = ix["discriminator"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|byte| byte.as_u64().unwrap() as u8)
+                .collect();
+            assert!(data.starts_with(&discriminator), "{name}");
+        }
+        // The IDL has no entry for the versioned wrapper.
+        assert_eq!(vectors.len(), instructions.len() + 1);
+    }
+
+    #[test]
+    fn envelope_vector_round_trips() {
+        let fixture = checked_in("fixtures/envelope.json");
+        let data = unhex(fixture["data"].as_str().unwrap());
+        let envelope = Envelope::from_prefix_bytes(&data).unwrap();
+        assert_eq!(bytemuck::bytes_of(envelope), &data[..]);
+        assert_eq!(envelope.oracle_state.oracle_metadata, ORACLE_META);
+        assert_eq!(envelope.oracle_state.sequence, 42);
+        assert_eq!(envelope.heartbeat_slot, 123_456_789);
+        assert_eq!(
+            envelope.write_hook(),
+            Some(&Address::new_from_array([0x04; 32]))
+        );
+
+        // The fields tile the envelope, and each one holds a non-zero byte.
+        let mut end = 0;
+        for field in fixture["fields"].as_array().unwrap() {
+            let offset = field["offset"].as_u64().unwrap() as usize;
+            let len = field["len"].as_u64().unwrap() as usize;
+            assert!(offset >= end, "{field}");
+            assert!(
+                data[offset..offset + len].iter().any(|&b| b != 0),
+                "{field}"
+            );
+            end = offset + len;
+        }
+        assert_eq!(end, Envelope::SIZE);
+    }
+}
@@ -0,0 +1,283 @@
+//! Workspace automation, run as `cargo xtask <command>`.
+//!
+//! - `check-targets [--skip-sbf]`: build the `no_std` crates for the host, for
+//!   `wasm32-unknown-unknown`, and for SBF (through `cargo build-sbf`), once per feature set
+//!   in [`CHECKS`]. A `std` item leaking into any of them fails the wasm32 or SBF build.
+//! - `gen-fixtures`: regenerate the golden vectors in `fixtures/`, see [`fixtures`].
+
+mod fixtures;
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
+/// One crate and feature combination that must build on every target.
+struct TargetCheck {
+    package: &'static str,
+    /// Crate directory relative to the workspace root.
+    path: &'static str,
+    /// Features enabled on top of `--no-default-features`.
+    features: &'static [&'static str],
+}
+
+const CHECKS: &[TargetCheck] = &[
+    TargetCheck {
+        package: "c_u_soon",
+        path: "sdk",
+        features: &[],
+    },
+    TargetCheck {
+        package: "c_u_soon",
+        path: "sdk",
+        features: &["alloc"],
+    },
+    TargetCheck {
+        package: "c_u_soon",
+        path: "sdk",
+        features: &["alloc", "derive"],
+    },
+    TargetCheck {
+        package: "c_u_soon",
+        path: "sdk",
+        features: &["serde"],
+    },
+    TargetCheck {
+        package: "c_u_soon",
+        path: "sdk",
+        features: &["pda"],
+    },
+    TargetCheck {
+        package: "c_u_soon_instruction",
+        path: "instruction",
+        features: &[],
+    },
+    TargetCheck {
+        package: "c_u_soon_instruction",
+        path: "instruction",
+        features: &["borsh"],
+    },
+    TargetCheck {
+        package: "c_u_later",
+        path: "c_u_later",
+        features: &[],
+    },
+    TargetCheck {
+        package: "c_u_later",
+        path: "c_u_later",
+        features: &["alloc", "derive"],
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    Host,
+    Wasm32,
+    Sbf,
+}
+
+impl Target {
+    fn name(self) -> &'static str {
+        match self {
+            Target::Host => "host",
+            Target::Wasm32 => "wasm32-unknown-unknown",
+            Target::Sbf => "sbf",
+        }
+    }
+}
+
+/// Arguments after the `cargo` executable for `check` on `target`.
+fn cargo_args(check: &TargetCheck, target: Target, root: &Path) -> Vec<String> {
+    let mut args: Vec<String> = match target {
+        Target::Host => ["check", "--lib", "-p", check.package]
+            .map(String::from)
+            .to_vec(),
+        Target::Wasm32 => [
+            "check",
+            "--lib",
+            "-p",
+            check.package,
+            "--target",
+            "wasm32-unknown-unknown",
+        ]
+        .map(String::from)
+        .to_vec(),
+        Target::Sbf => vec![
+            "build-sbf".to_string(),
+            "--manifest-path".to_string(),
+            root.join(check.path)
+                .join("Cargo.toml")
+                .display()
+                .to_string(),
+        ],
+    };
+    args.push("--no-default-features".to_string());
+    if !check.features.is_empty() {
+        args.push("--features".to_string());
+        args.push(check.features.join(","));
+    }
+    args
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives one level below the workspace root")
+        .to_path_buf()
+}
+
+fn check_targets(skip_sbf: bool) -> Result<(), String> {
+    let root = workspace_root();
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let mut targets = vec![Target::Host, Target::Wasm32];
+    if !skip_sbf {
+        targets.push(Target::Sbf);
+    }
+
+    let mut failures = Vec::new();
+    for &target in &targets {
+        for check in CHECKS {
+            let label = format!(
+                "{} [{}] on {}",
+                check.package,
+                check.features.join(","),
+                target.name()
+            );
+            eprintln!("checking {label}");
+            let status = Command::new(&cargo)
+                .args(cargo_args(check, target, &root))
+                .current_dir(&root)
+                .status()
+                .map_err(|e| format!("failed to run {cargo}: {e}"))?;
+            if !status.success() {
+                failures.push(label);
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "target checks failed:\n  {}",
+            failures.join("\n  ")
+        ))
+    }
+}
+
+const USAGE: &str = "usage: cargo xtask check-targets [--skip-sbf] | cargo xtask gen-fixtures";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("check-targets") => match &args[1..] {
+            [] => check_targets(false),
+            [flag] if flag == "--skip-sbf" => check_targets(true),
+            _ => Err(USAGE.to_string()),
+        },
+        Some("gen-fixtures") if args.len() == 1 => fixtures::generate(&workspace_root()),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_crates_are_no_std() {
+        let root = workspace_root();
+        for check in CHECKS {
+            let lib = root.join(check.path).join("src/lib.rs");
+            let source = std::fs::read_to_string(&lib).unwrap();
+            assert!(
+                source.lines().any(|line| line.trim() == "#![no_std]"),
+                "{} must be #![no_std]",
+                lib.display()
+            );
+        }
+    }
+
+    #[test]
+    fn checked_paths_match_package_names() {
+        let root = workspace_root();
+        for check in CHECKS {
+            let manifest =
+                std::fs::read_to_string(root.join(check.path).join("Cargo.toml")).unwrap();
+            assert!(
+                manifest.contains(&format!("name = \"{}\"", check.package)),
+                "{}/Cargo.toml is not {}",
+                check.path,
+                check.package
+            );
+        }
+    }
+
+    #[test]
+    fn every_declared_feature_is_checked() {
+        let root = workspace_root();
+        for check in CHECKS {
+            let manifest =
+                std::fs::read_to_string(root.join(check.path).join("Cargo.toml")).unwrap();
+            let Some(features) = manifest.split("[features]").nth(1) else {
+                continue;
+            };
+            let declared = features
+                .lines()
+                .skip(1)
+                .take_while(|line| !line.starts_with('['))
+                .filter_map(|line| line.split_once('=').map(|(name, _)| name.trim()))
+                .filter(|name| !name.is_empty() && *name != "default");
+            for feature in declared {
+                assert!(
+                    CHECKS
+                        .iter()
+                        .any(|c| c.package == check.package && c.features.contains(&feature)),
+                    "feature `{feature}` of {} is not covered by check-targets",
+                    check.package
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn wasm_args_disable_default_features() {
+        let check = &CHECKS[1];
+        assert_eq!(
+            cargo_args(check, Target::Wasm32, Path::new("/ws")),
+            [
+                "check",
+                "--lib",
+                "-p",
+                "c_u_soon",
+                "--target",
+                "wasm32-unknown-unknown",
+                "--no-default-features",
+                "--features",
+                "alloc",
+            ]
+        );
+    }
+
+    #[test]
+    fn sbf_args_use_manifest_path() {
+        let check = CHECKS
+            .iter()
+            .find(|check| check.path == "instruction" && check.features.is_empty())
+            .unwrap();
+        assert_eq!(
+            cargo_args(check, Target::Sbf, Path::new("/ws")),
+            [
+                "build-sbf",
+                "--manifest-path",
+                "/ws/instruction/Cargo.toml",
+                "--no-default-features",
+            ]
+        );
+    }
+}